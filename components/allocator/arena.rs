@@ -0,0 +1,114 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Dedicated allocator arenas for long-lived threads.
+//!
+//! By default jemalloc spreads threads over a fixed set of arenas, so that
+//! the script thread, the WebRender threads and the many short-lived helper
+//! threads end up allocating from the same arenas. Pinning the busiest
+//! long-lived threads to arenas of their own reduces lock contention, keeps
+//! their memory from being interleaved with that of unrelated threads, and
+//! allows the page purging behaviour to be tuned per kind of thread.
+
+/// How a dedicated arena returns unused pages to the operating system.
+///
+/// Decay times are in milliseconds. `Some(0)` purges pages as soon as they
+/// become unused, `Some(-1)` never purges them, and `None` keeps jemalloc's
+/// default.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ArenaConfig {
+    /// How long unused dirty pages are kept before being purged.
+    pub dirty_decay_ms: Option<isize>,
+    /// How long lazily purged (muzzy) pages are kept before being released.
+    pub muzzy_decay_ms: Option<isize>,
+}
+
+/// An arena created through [`use_dedicated_arena`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Arena {
+    index: u32,
+}
+
+impl Arena {
+    /// The index of this arena, as found in [`crate::ArenaStats::index`].
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+pub use self::platform::{dedicated_arenas, use_dedicated_arena};
+
+#[cfg(jemalloc)]
+mod platform {
+    use std::os::raw::c_uint;
+    use std::sync::Mutex;
+
+    use super::{Arena, ArenaConfig};
+    use crate::mallctl::{read, write};
+
+    /// The arenas created so far, along with the name they were created for.
+    static ARENAS: Mutex<Vec<(&'static str, Arena)>> = Mutex::new(Vec::new());
+
+    fn create_arena(config: ArenaConfig) -> Option<Arena> {
+        let index = read::<c_uint>("arenas.create")?;
+        let arena = Arena { index };
+        if let Some(decay) = config.dirty_decay_ms {
+            if !write::<isize>(&format!("arena.{}.dirty_decay_ms", index), decay) {
+                log::warn!("Failed to set the dirty decay time of arena {}", index);
+            }
+        }
+        if let Some(decay) = config.muzzy_decay_ms {
+            if !write::<isize>(&format!("arena.{}.muzzy_decay_ms", index), decay) {
+                log::warn!("Failed to set the muzzy decay time of arena {}", index);
+            }
+        }
+        Some(arena)
+    }
+
+    /// Make all the allocations of the current thread come from the arena
+    /// dedicated to `name`, creating it with `config` if this is the first
+    /// thread to use it. Threads passing the same name share an arena, and
+    /// `config` is ignored once the arena exists.
+    ///
+    /// This is meant to be called first thing when a long-lived thread is
+    /// spawned. Returns the arena the thread is now bound to, or `None` if
+    /// the allocator does not support dedicated arenas.
+    pub fn use_dedicated_arena(name: &'static str, config: ArenaConfig) -> Option<Arena> {
+        let arena = {
+            let mut arenas = ARENAS.lock().unwrap();
+            match arenas.iter().find(|(arena_name, _)| *arena_name == name) {
+                Some(&(_, arena)) => arena,
+                None => {
+                    let arena = create_arena(config)?;
+                    arenas.push((name, arena));
+                    arena
+                },
+            }
+        };
+        if !write::<c_uint>("thread.arena", arena.index) {
+            return None;
+        }
+        Some(arena)
+    }
+
+    /// The dedicated arenas created so far, along with their names.
+    pub fn dedicated_arenas() -> Vec<(&'static str, Arena)> {
+        ARENAS.lock().unwrap().clone()
+    }
+}
+
+#[cfg(not(jemalloc))]
+mod platform {
+    use super::{Arena, ArenaConfig};
+
+    /// The system allocator does not support dedicated arenas.
+    pub fn use_dedicated_arena(_name: &'static str, _config: ArenaConfig) -> Option<Arena> {
+        None
+    }
+
+    /// The system allocator does not support dedicated arenas.
+    pub fn dedicated_arenas() -> Vec<(&'static str, Arena)> {
+        vec![]
+    }
+}
@@ -0,0 +1,27 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::env;
+
+/// Set the `jemalloc` cfg when jemalloc is the allocator of this build, so
+/// that the code depending on it does not need to repeat the conditions.
+///
+/// Sanitizers find memory errors by intercepting the calls to the system
+/// allocator, which jemalloc bypasses, so builds with a sanitizer (such as
+/// `-Zsanitizer=address`) always use the system allocator, and get the
+/// `sanitizer` cfg.
+fn main() {
+    let android = env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("android");
+    let windows = env::var_os("CARGO_CFG_WINDOWS").is_some();
+    let sanitizer = env::var_os("CARGO_CFG_SANITIZE").is_some();
+    let use_system_allocator = env::var_os("CARGO_FEATURE_USE_SYSTEM_ALLOCATOR").is_some();
+    let windows_jemalloc = env::var_os("CARGO_FEATURE_WINDOWS_JEMALLOC").is_some();
+
+    if sanitizer {
+        println!("cargo:rustc-cfg=sanitizer");
+    }
+    if !android && !sanitizer && !use_system_allocator && (!windows || windows_jemalloc) {
+        println!("cargo:rustc-cfg=jemalloc");
+    }
+}
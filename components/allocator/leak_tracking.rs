@@ -0,0 +1,297 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An opt-in live allocation tracking layer, used to find leaks.
+//!
+//! [`LeakTrackingAllocator`] wraps another allocator and, once tracking is
+//! enabled with [`start`], records the size and a truncated call stack of
+//! every allocation until it is freed. The allocations that are still alive
+//! when the process shuts down cleanly can then be listed with
+//! [`write_report`], grouped by the call stack that made them.
+//!
+//! Tracking takes a global lock and walks the stack on every allocation, so
+//! it is far too slow to be enabled outside of debugging and CI runs, but it
+//! is still much faster than running under Valgrind.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+/// The maximum number of frames that are recorded for each allocation.
+const MAX_FRAMES: usize = 24;
+
+/// The prefixes of the symbols of the frames that belong to the tracker
+/// itself, which are left out of the report.
+const TRACKER_FRAME_PREFIXES: &[&str] = &[
+    "backtrace::",
+    "servo_allocator::",
+    "<servo_allocator::",
+    "std::thread::local::",
+    "__rust",
+];
+
+/// Whether allocations are currently being tracked.
+static TRACKING: AtomicBool = AtomicBool::new(false);
+
+/// Whether any allocation was ever tracked, so that deallocations do not
+/// need to take the lock before tracking is first started.
+static TRACKED_ANY: AtomicBool = AtomicBool::new(false);
+
+/// The allocations that are still alive, keyed by their address.
+static LIVE_ALLOCATIONS: Mutex<Option<HashMap<usize, LiveAllocation>>> = Mutex::new(None);
+
+thread_local! {
+    /// Set while this thread is inside the tracker, so that the allocations
+    /// made to record an allocation are not tracked themselves.
+    static IN_TRACKER: Cell<bool> = const { Cell::new(false) };
+}
+
+struct LiveAllocation {
+    size: usize,
+    frames: [usize; MAX_FRAMES],
+    frame_count: usize,
+}
+
+/// A [`GlobalAlloc`] wrapper that tracks the allocations made through the
+/// wrapped allocator until they are freed.
+pub struct LeakTrackingAllocator<A> {
+    inner: A,
+}
+
+impl<A> LeakTrackingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        LeakTrackingAllocator { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for LeakTrackingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc(layout);
+        note_allocation(ptr, layout.size());
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.inner.alloc_zeroed(layout);
+        note_allocation(ptr, layout.size());
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        note_deallocation(ptr);
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = self.inner.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            note_deallocation(ptr);
+            note_allocation(new_ptr, new_size);
+        }
+        new_ptr
+    }
+}
+
+/// Start tracking the allocations made from now on. Allocations made before
+/// tracking started are never reported.
+pub fn start() {
+    TRACKING.store(true, Ordering::Relaxed);
+}
+
+/// Stop tracking new allocations. The allocations tracked so far keep being
+/// tracked until they are freed.
+pub fn stop() {
+    TRACKING.store(false, Ordering::Relaxed);
+}
+
+/// Whether new allocations are currently being tracked.
+pub fn is_tracking() -> bool {
+    TRACKING.load(Ordering::Relaxed)
+}
+
+/// The number of tracked allocations that are still alive, and their total
+/// size in bytes.
+pub fn live_allocations() -> (usize, usize) {
+    with_tracker_guard(|| match *LIVE_ALLOCATIONS.lock().unwrap() {
+        Some(ref allocations) => (
+            allocations.len(),
+            allocations.values().map(|allocation| allocation.size).sum(),
+        ),
+        None => (0, 0),
+    })
+}
+
+/// Write a report of the tracked allocations that are still alive to
+/// `writer`: one entry per distinct call stack, from the one holding on to
+/// the most bytes to the one holding on to the fewest, each listing the
+/// number of allocations, their total size and the frames of the stack from
+/// the innermost to the outermost one.
+pub fn write_report<W: Write>(writer: &mut W) -> io::Result<()> {
+    with_tracker_guard(|| {
+        let mut stacks: HashMap<Vec<usize>, (usize, usize)> = HashMap::new();
+        if let Some(ref allocations) = *LIVE_ALLOCATIONS.lock().unwrap() {
+            for allocation in allocations.values() {
+                let frames = allocation.frames[..allocation.frame_count].to_vec();
+                let entry = stacks.entry(frames).or_default();
+                entry.0 += 1;
+                entry.1 += allocation.size;
+            }
+        }
+
+        let mut stacks: Vec<_> = stacks.into_iter().collect();
+        stacks.sort_by_key(|&(_, (_, bytes))| Reverse(bytes));
+
+        let total_count: usize = stacks.iter().map(|(_, (count, _))| count).sum();
+        let total_bytes: usize = stacks.iter().map(|(_, (_, bytes))| bytes).sum();
+        writeln!(
+            writer,
+            "{} bytes in {} live allocations from {} call stacks",
+            total_bytes,
+            total_count,
+            stacks.len()
+        )?;
+
+        let mut symbol_cache = HashMap::new();
+        for (frames, (count, bytes)) in stacks {
+            writeln!(writer)?;
+            writeln!(writer, "{} bytes in {} allocations", bytes, count)?;
+            let symbols = frames
+                .iter()
+                .map(|ip| {
+                    symbol_cache
+                        .entry(*ip)
+                        .or_insert_with(|| resolve(*ip))
+                        .clone()
+                })
+                .skip_while(|symbol| {
+                    TRACKER_FRAME_PREFIXES
+                        .iter()
+                        .any(|prefix| symbol.starts_with(prefix))
+                });
+            for symbol in symbols {
+                writeln!(writer, "    {}", symbol)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+fn note_allocation(ptr: *mut u8, size: usize) {
+    if ptr.is_null() || !TRACKING.load(Ordering::Relaxed) {
+        return;
+    }
+
+    // `try_with` fails while the thread-local storage of this thread is being
+    // torn down, in which case the allocation is simply not tracked.
+    let _ = IN_TRACKER.try_with(|in_tracker| {
+        if in_tracker.get() {
+            return;
+        }
+        in_tracker.set(true);
+
+        let mut allocation = LiveAllocation {
+            size,
+            frames: [0; MAX_FRAMES],
+            frame_count: 0,
+        };
+        backtrace::trace(|frame| {
+            allocation.frames[allocation.frame_count] = frame.ip() as usize;
+            allocation.frame_count += 1;
+            allocation.frame_count < MAX_FRAMES
+        });
+        LIVE_ALLOCATIONS
+            .lock()
+            .unwrap()
+            .get_or_insert_with(HashMap::new)
+            .insert(ptr as usize, allocation);
+        TRACKED_ANY.store(true, Ordering::Relaxed);
+
+        in_tracker.set(false);
+    });
+}
+
+fn note_deallocation(ptr: *mut u8) {
+    if !TRACKED_ANY.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let _ = IN_TRACKER.try_with(|in_tracker| {
+        if in_tracker.get() {
+            return;
+        }
+        in_tracker.set(true);
+        if let Some(ref mut allocations) = *LIVE_ALLOCATIONS.lock().unwrap() {
+            allocations.remove(&(ptr as usize));
+        }
+        in_tracker.set(false);
+    });
+}
+
+fn resolve(ip: usize) -> String {
+    let mut name = None;
+    backtrace::resolve(ip as *mut c_void, |symbol| {
+        if name.is_none() {
+            name = symbol.name().map(|name| format!("{:#}", name));
+        }
+    });
+    name.unwrap_or_else(|| format!("{:#x}", ip))
+}
+
+fn with_tracker_guard<R>(f: impl FnOnce() -> R) -> R {
+    let was_in_tracker = IN_TRACKER.with(|in_tracker| in_tracker.replace(true));
+    let result = f();
+    IN_TRACKER.with(|in_tracker| in_tracker.set(was_in_tracker));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracked_size(ptr: *mut u8) -> Option<usize> {
+        with_tracker_guard(|| {
+            LIVE_ALLOCATIONS
+                .lock()
+                .unwrap()
+                .as_ref()
+                .and_then(|allocations| allocations.get(&(ptr as usize)))
+                .map(|allocation| allocation.size)
+        })
+    }
+
+    #[test]
+    fn allocations_are_tracked_until_they_are_freed() {
+        // Addresses that no real allocation has.
+        let before = 8 as *mut u8;
+        let leaked = 16 as *mut u8;
+        let freed = 24 as *mut u8;
+
+        note_allocation(before, 10);
+        assert_eq!(tracked_size(before), None, "tracking is not started yet");
+
+        start();
+        note_allocation(leaked, 100);
+        note_allocation(freed, 200);
+        note_deallocation(freed);
+        stop();
+        note_allocation(freed, 300);
+
+        assert_eq!(tracked_size(leaked), Some(100));
+        assert_eq!(tracked_size(freed), None);
+
+        let mut report = Vec::new();
+        write_report(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        assert!(report.contains("live allocations"));
+        assert!(report.contains("\n100 bytes in 1 allocations\n"));
+
+        note_deallocation(leaked);
+        assert_eq!(tracked_size(leaked), None);
+    }
+}
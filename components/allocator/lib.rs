@@ -4,12 +4,58 @@
 
 //! Selecting the default global allocator for Servo
 
+#[cfg(feature = "heap-partitions")]
+type PartitionedAllocator = partition::PartitionAllocator<Allocator>;
+#[cfg(feature = "heap-partitions")]
+const PARTITIONED_ALLOCATOR: PartitionedAllocator = partition::PartitionAllocator::new(Allocator);
+
+#[cfg(not(feature = "heap-partitions"))]
+type PartitionedAllocator = Allocator;
+#[cfg(not(feature = "heap-partitions"))]
+const PARTITIONED_ALLOCATOR: PartitionedAllocator = Allocator;
+
+#[cfg(feature = "leak-tracking")]
+type TrackedAllocator = leak_tracking::LeakTrackingAllocator<PartitionedAllocator>;
+#[cfg(feature = "leak-tracking")]
+const TRACKED_ALLOCATOR: TrackedAllocator =
+    leak_tracking::LeakTrackingAllocator::new(PARTITIONED_ALLOCATOR);
+
+#[cfg(not(feature = "leak-tracking"))]
+type TrackedAllocator = PartitionedAllocator;
+#[cfg(not(feature = "leak-tracking"))]
+const TRACKED_ALLOCATOR: TrackedAllocator = PARTITIONED_ALLOCATOR;
+
+#[cfg(not(feature = "heap-sampling"))]
+#[global_allocator]
+static ALLOC: TrackedAllocator = TRACKED_ALLOCATOR;
+
+#[cfg(feature = "heap-sampling")]
 #[global_allocator]
-static ALLOC: Allocator = Allocator;
+static ALLOC: sampling::SamplingAllocator<TrackedAllocator> =
+    sampling::SamplingAllocator::new(TRACKED_ALLOCATOR);
 
+pub use crate::arena::{dedicated_arenas, use_dedicated_arena, Arena, ArenaConfig};
+pub use crate::partition::{
+    enter_partition, partition_stats, Partition, PartitionGuard, PartitionStats,
+};
 pub use crate::platform::*;
+pub use crate::purge::purge;
+pub use crate::size::usable_size_for_layout;
+pub use crate::stats::{stats, AllocatorStats, ArenaStats};
 
-#[cfg(not(any(windows, target_os = "android", feature = "use-system-allocator")))]
+mod arena;
+#[cfg(feature = "leak-tracking")]
+pub mod leak_tracking;
+#[cfg(jemalloc)]
+mod mallctl;
+mod partition;
+mod purge;
+#[cfg(feature = "heap-sampling")]
+pub mod sampling;
+mod size;
+mod stats;
+
+#[cfg(jemalloc)]
 mod platform {
     use std::os::raw::c_void;
 
@@ -28,43 +74,199 @@ mod platform {
     }
 }
 
-#[cfg(all(
-    not(windows),
-    any(target_os = "android", feature = "use-system-allocator")
-))]
+#[cfg(all(not(jemalloc), not(windows)))]
 mod platform {
     pub use std::alloc::System as Allocator;
     use std::os::raw::c_void;
 
     /// Get the size of a heap block.
     pub unsafe extern "C" fn usable_size(ptr: *const c_void) -> usize {
-        #[cfg(target_os = "linux")]
+        #[cfg(sanitizer)]
+        return sanitizer::usable_size(ptr);
+
+        #[cfg(all(not(sanitizer), target_os = "linux"))]
         return libc::malloc_usable_size(ptr as *mut _);
 
-        #[cfg(not(target_os = "linux"))]
+        #[cfg(all(not(sanitizer), not(target_os = "linux")))]
         return libc::malloc_usable_size(ptr);
     }
 
+    /// The allocator interface of the sanitizer runtimes, which replace the
+    /// system allocator with their own.
+    #[cfg(sanitizer)]
+    mod sanitizer {
+        use std::os::raw::{c_int, c_void};
+
+        extern "C" {
+            fn __sanitizer_get_ownership(ptr: *const c_void) -> c_int;
+            fn __sanitizer_get_allocated_size(ptr: *const c_void) -> usize;
+        }
+
+        /// Get the size of a heap block, or 0 if it was not allocated by
+        /// the sanitizer, as `malloc_usable_size` would report an error
+        /// about such a pointer rather than return.
+        pub unsafe fn usable_size(ptr: *const c_void) -> usize {
+            if __sanitizer_get_ownership(ptr) == 0 {
+                return 0;
+            }
+            __sanitizer_get_allocated_size(ptr)
+        }
+    }
+
     pub mod libc_compat {
         pub use libc::{free, malloc, realloc};
     }
 }
 
-#[cfg(windows)]
+#[cfg(all(not(jemalloc), windows))]
 mod platform {
-    pub use std::alloc::System as Allocator;
+    use std::alloc::{GlobalAlloc, Layout};
     use std::os::raw::c_void;
+    use std::{mem, ptr};
+
+    use winapi::um::heapapi::{GetProcessHeap, HeapAlloc, HeapFree, HeapReAlloc, HeapSize};
+    use winapi::um::winnt::HEAP_ZERO_MEMORY;
+
+    /// The alignment of the blocks returned by `HeapAlloc`
+    /// (`MEMORY_ALLOCATION_ALIGNMENT`).
+    #[cfg(target_pointer_width = "64")]
+    pub(crate) const MIN_ALIGN: usize = 16;
+    #[cfg(target_pointer_width = "32")]
+    pub(crate) const MIN_ALIGN: usize = 8;
+
+    /// Marks the header of the allocations that needed a larger alignment
+    /// than `HeapAlloc` provides, which is the address of their heap block
+    /// followed by that address xored with this value.
+    #[cfg(target_pointer_width = "64")]
+    const OVER_ALIGNED_COOKIE: usize = 0x5e7f_0a11_9ed5_a17e;
+    #[cfg(target_pointer_width = "32")]
+    const OVER_ALIGNED_COOKIE: usize = 0x5e7f_a17e;
+
+    /// The size of the header of over-aligned allocations.
+    const HEADER_SIZE: usize = 2 * mem::size_of::<usize>();
+
+    /// An allocator that hands out blocks of the process heap. Blocks that
+    /// need a larger alignment than the heap provides are over-allocated, and
+    /// have a header right before the aligned address that leads back to the
+    /// underlying heap block.
+    pub struct Allocator;
+
+    /// The heap block of the over-aligned allocation at `ptr`, or `None` if
+    /// `ptr` is the start of a heap block.
+    ///
+    /// The bytes before the start of a heap block belong to the header the
+    /// heap keeps for it, so they can always be read. The heap encodes that
+    /// header with a random key, so it is as good as impossible for it to
+    /// look like the header of an over-aligned allocation.
+    unsafe fn over_aligned_block(ptr: *const u8) -> Option<*mut u8> {
+        let header = (ptr as *const usize).sub(2);
+        let block = header.read();
+        let check = header.add(1).read();
+        if check != block ^ OVER_ALIGNED_COOKIE ||
+            block >= ptr as usize ||
+            ptr as usize - block < HEADER_SIZE
+        {
+            return None;
+        }
+        Some(block as *mut u8)
+    }
+
+    impl Allocator {
+        unsafe fn alloc_with_flags(&self, layout: Layout, flags: u32) -> *mut u8 {
+            let heap = GetProcessHeap();
+            if layout.align() <= MIN_ALIGN {
+                return HeapAlloc(heap, flags, layout.size()) as *mut u8;
+            }
 
-    use winapi::um::heapapi::{GetProcessHeap, HeapSize, HeapValidate};
+            let block = HeapAlloc(heap, flags, layout.size() + layout.align()) as *mut u8;
+            if block.is_null() {
+                return block;
+            }
+            // The block is `MIN_ALIGN`-aligned and the alignment is a larger
+            // power of two, so the aligned address is at least `MIN_ALIGN`
+            // bytes, which is `HEADER_SIZE`, into the block.
+            let offset = layout.align() - (block as usize & (layout.align() - 1));
+            let aligned = block.add(offset);
+            let header = (aligned as *mut usize).sub(2);
+            header.write(block as usize);
+            header.add(1).write(block as usize ^ OVER_ALIGNED_COOKIE);
+            aligned
+        }
+    }
+
+    unsafe impl GlobalAlloc for Allocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            self.alloc_with_flags(layout, 0)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            self.alloc_with_flags(layout, HEAP_ZERO_MEMORY)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            let heap = GetProcessHeap();
+            if layout.align() <= MIN_ALIGN {
+                HeapFree(heap, 0, ptr as *mut _);
+                return;
+            }
+
+            let header = (ptr as *mut usize).sub(2);
+            let block = header.read();
+            // Clear the header, so that a heap block that reuses this memory
+            // is never taken for an over-aligned allocation.
+            header.write_bytes(0, 2);
+            HeapFree(heap, 0, block as *mut _);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            if layout.align() <= MIN_ALIGN {
+                return HeapReAlloc(GetProcessHeap(), 0, ptr as *mut _, new_size) as *mut u8;
+            }
+
+            let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+            let new_ptr = self.alloc(new_layout);
+            if !new_ptr.is_null() {
+                ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+                self.dealloc(ptr, layout);
+            }
+            new_ptr
+        }
+    }
 
     /// Get the size of a heap block.
-    pub unsafe extern "C" fn usable_size(mut ptr: *const c_void) -> usize {
+    pub unsafe extern "C" fn usable_size(ptr: *const c_void) -> usize {
         let heap = GetProcessHeap();
 
-        if HeapValidate(heap, 0, ptr) == 0 {
-            ptr = *(ptr as *const *const c_void).offset(-1);
+        if let Some(block) = over_aligned_block(ptr as *const u8) {
+            let offset = ptr as usize - block as usize;
+            return HeapSize(heap, 0, block as *const _) as usize - offset;
+        }
+
+        HeapSize(heap, 0, ptr as *const _) as usize
+    }
+
+    /// Memory allocation APIs compatible with libc, backed by the same heap
+    /// as `Allocator` so that `usable_size` works on what they return.
+    pub mod libc_compat {
+        use std::os::raw::c_void;
+
+        use winapi::um::heapapi::{GetProcessHeap, HeapAlloc, HeapFree, HeapReAlloc};
+
+        pub unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+            HeapAlloc(GetProcessHeap(), 0, size) as *mut c_void
         }
 
-        HeapSize(heap, 0, ptr) as usize
+        pub unsafe extern "C" fn realloc(ptr: *mut c_void, size: usize) -> *mut c_void {
+            if ptr.is_null() {
+                return malloc(size);
+            }
+            HeapReAlloc(GetProcessHeap(), 0, ptr as *mut _, size) as *mut c_void
+        }
+
+        pub unsafe extern "C" fn free(ptr: *mut c_void) {
+            if !ptr.is_null() {
+                HeapFree(GetProcessHeap(), 0, ptr as *mut _);
+            }
+        }
     }
 }
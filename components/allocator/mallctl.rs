@@ -0,0 +1,61 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Typed wrappers around jemalloc's `mallctl` interface.
+
+use std::ffi::CString;
+use std::mem::size_of;
+use std::os::raw::c_void;
+use std::ptr::null_mut;
+
+use jemalloc_sys::mallctl;
+
+/// Read the value named `name` through `mallctl`.
+pub(crate) fn read<T: Copy + Default>(name: &str) -> Option<T> {
+    let c_name = CString::new(name).ok()?;
+    let mut value = T::default();
+    let mut len = size_of::<T>();
+    let rv = unsafe {
+        mallctl(
+            c_name.as_ptr(),
+            &mut value as *mut T as *mut c_void,
+            &mut len,
+            null_mut(),
+            0,
+        )
+    };
+    if rv != 0 || len != size_of::<T>() {
+        return None;
+    }
+    Some(value)
+}
+
+/// Write `value` to the value named `name` through `mallctl`.
+pub(crate) fn write<T: Copy>(name: &str, mut value: T) -> bool {
+    let c_name = match CString::new(name) {
+        Ok(c_name) => c_name,
+        Err(_) => return false,
+    };
+    let rv = unsafe {
+        mallctl(
+            c_name.as_ptr(),
+            null_mut(),
+            null_mut(),
+            &mut value as *mut T as *mut c_void,
+            size_of::<T>(),
+        )
+    };
+    rv == 0
+}
+
+/// Run the command named `name` through `mallctl`, which neither reads nor
+/// writes a value.
+pub(crate) fn call(name: &str) -> bool {
+    let c_name = match CString::new(name) {
+        Ok(c_name) => c_name,
+        Err(_) => return false,
+    };
+    let rv = unsafe { mallctl(c_name.as_ptr(), null_mut(), null_mut(), null_mut(), 0) };
+    rv == 0
+}
@@ -0,0 +1,354 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Attributing heap allocations to coarse subsystems.
+//!
+//! Only the allocations made through Rust's global allocator are counted.
+//! The JS engine allocates its GC heap and most of its own data with its own
+//! allocator, so the memory of JS objects is not part of any partition.
+//!
+//! Code that belongs to a subsystem enters its [`Partition`] with
+//! [`enter_partition`], and the allocations made on that thread until the
+//! returned guard is dropped are attributed to it. [`partition_stats`] then
+//! gives the number of live bytes of every partition, which memory reports
+//! show next to the allocator statistics.
+//!
+//! Allocations keep the partition they were made in when they are
+//! reallocated, and allocations made outside of any partition are not
+//! counted. Only the calling thread is affected, so work handed to thread
+//! pools is not attributed to the partition of the thread handing it out.
+//!
+//! Counting is only done when the `heap-partitions` feature is enabled, as
+//! it takes a global lock on every allocation and deallocation once a
+//! partition has been entered. Without it, entering a partition does nothing
+//! and no statistics are reported.
+
+/// A subsystem that allocations can be attributed to.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Partition {
+    /// The script threads, which is mostly the DOM.
+    Dom,
+    /// The values serialized and deserialized by the structured clone
+    /// algorithm, for messages posted between globals and stored values.
+    StructuredClone,
+    /// The layout threads while they handle requests, which includes style
+    /// and display list building, but not the work done by their thread pool.
+    Layout,
+    /// The font cache thread.
+    Gfx,
+}
+
+impl Partition {
+    /// All the partitions, in the order they are reported in.
+    pub const ALL: [Partition; 4] = [
+        Partition::Dom,
+        Partition::StructuredClone,
+        Partition::Layout,
+        Partition::Gfx,
+    ];
+
+    /// The name of this partition in memory reports.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Partition::Dom => "dom",
+            Partition::StructuredClone => "structured-clone",
+            Partition::Layout => "layout",
+            Partition::Gfx => "gfx",
+        }
+    }
+}
+
+/// The live allocations attributed to a partition.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PartitionStats {
+    pub partition: Partition,
+    /// Number of bytes requested by the live allocations.
+    pub allocated: usize,
+    /// Number of live allocations.
+    pub allocations: usize,
+}
+
+/// Makes the allocations of the current thread be attributed to a partition
+/// until it is dropped, at which point the partition that was active before
+/// is restored.
+#[must_use]
+pub struct PartitionGuard {
+    #[cfg(feature = "heap-partitions")]
+    previous: Option<Partition>,
+    /// The current partition is per thread, so the guard must not be sent
+    /// to another one.
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+
+#[cfg(feature = "heap-partitions")]
+pub use self::platform::PartitionAllocator;
+pub use self::platform::{enter_partition, partition_stats};
+
+#[cfg(feature = "heap-partitions")]
+mod platform {
+    use std::alloc::{GlobalAlloc, Layout};
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::marker::PhantomData;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    use super::{Partition, PartitionGuard, PartitionStats};
+
+    /// The partition of every live allocation made in one, keyed by address.
+    static LIVE_ALLOCATIONS: Mutex<Option<HashMap<usize, Partition>>> = Mutex::new(None);
+
+    /// The number of entries of `LIVE_ALLOCATIONS`, so that deallocations do
+    /// not need to take the lock when there are none.
+    static LIVE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+    /// The number of live bytes of every partition, indexed like
+    /// `Partition::ALL`.
+    static ALLOCATED: [AtomicUsize; 4] = [
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+    ];
+
+    /// The number of live allocations of every partition, indexed like
+    /// `Partition::ALL`.
+    static ALLOCATIONS: [AtomicUsize; 4] = [
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+        AtomicUsize::new(0),
+    ];
+
+    thread_local! {
+        /// The partition the allocations of this thread are attributed to.
+        static CURRENT: Cell<Option<Partition>> = const { Cell::new(None) };
+
+        /// Set while this thread is updating the partitions, so that the
+        /// allocations made to do so are not counted themselves.
+        static IN_PARTITIONS: Cell<bool> = const { Cell::new(false) };
+    }
+
+    /// A [`GlobalAlloc`] wrapper that counts the allocations made through the
+    /// wrapped allocator towards the partition of the allocating thread.
+    pub struct PartitionAllocator<A> {
+        inner: A,
+    }
+
+    impl<A> PartitionAllocator<A> {
+        pub const fn new(inner: A) -> Self {
+            PartitionAllocator { inner }
+        }
+    }
+
+    unsafe impl<A: GlobalAlloc> GlobalAlloc for PartitionAllocator<A> {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.inner.alloc(layout);
+            note_allocation(ptr, layout.size());
+            ptr
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            let ptr = self.inner.alloc_zeroed(layout);
+            note_allocation(ptr, layout.size());
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            note_deallocation(ptr, layout.size());
+            self.inner.dealloc(ptr, layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = self.inner.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                note_reallocation(ptr, layout.size(), new_ptr, new_size);
+            }
+            new_ptr
+        }
+    }
+
+    fn index(partition: Partition) -> usize {
+        partition as usize
+    }
+
+    /// Attribute the allocations made on this thread to `partition` until the
+    /// returned guard is dropped.
+    pub fn enter_partition(partition: Partition) -> PartitionGuard {
+        PartitionGuard {
+            previous: CURRENT.with(|current| current.replace(Some(partition))),
+            _not_send: PhantomData,
+        }
+    }
+
+    impl Drop for PartitionGuard {
+        fn drop(&mut self) {
+            // This fails while the thread-local storage of this thread is
+            // being torn down, when there is nothing left to restore.
+            let _ = CURRENT.try_with(|current| current.set(self.previous));
+        }
+    }
+
+    /// The live allocations of every partition.
+    pub fn partition_stats() -> Vec<PartitionStats> {
+        Partition::ALL
+            .iter()
+            .map(|&partition| PartitionStats {
+                partition,
+                allocated: ALLOCATED[index(partition)].load(Ordering::Relaxed),
+                allocations: ALLOCATIONS[index(partition)].load(Ordering::Relaxed),
+            })
+            .collect()
+    }
+
+    /// Run `f` with the table of live allocations, unless this thread is
+    /// already updating it.
+    fn with_live_allocations(f: impl FnOnce(&mut HashMap<usize, Partition>)) {
+        let _ = IN_PARTITIONS.try_with(|in_partitions| {
+            if in_partitions.get() {
+                return;
+            }
+            in_partitions.set(true);
+            let mut live_allocations = LIVE_ALLOCATIONS.lock().unwrap();
+            let live_allocations = live_allocations.get_or_insert_with(HashMap::new);
+            f(live_allocations);
+            LIVE_COUNT.store(live_allocations.len(), Ordering::Relaxed);
+            in_partitions.set(false);
+        });
+    }
+
+    fn add(partition: Partition, size: usize) {
+        ALLOCATED[index(partition)].fetch_add(size, Ordering::Relaxed);
+        ALLOCATIONS[index(partition)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn remove(partition: Partition, size: usize) {
+        ALLOCATED[index(partition)].fetch_sub(size, Ordering::Relaxed);
+        ALLOCATIONS[index(partition)].fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn note_allocation(ptr: *mut u8, size: usize) {
+        if ptr.is_null() {
+            return;
+        }
+        let partition = match CURRENT.try_with(Cell::get) {
+            Ok(Some(partition)) => partition,
+            _ => return,
+        };
+        with_live_allocations(|live_allocations| {
+            live_allocations.insert(ptr as usize, partition);
+            add(partition, size);
+        });
+    }
+
+    fn note_deallocation(ptr: *mut u8, size: usize) {
+        if LIVE_COUNT.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        with_live_allocations(|live_allocations| {
+            if let Some(partition) = live_allocations.remove(&(ptr as usize)) {
+                remove(partition, size);
+            }
+        });
+    }
+
+    fn note_reallocation(ptr: *mut u8, size: usize, new_ptr: *mut u8, new_size: usize) {
+        if LIVE_COUNT.load(Ordering::Relaxed) == 0 {
+            return;
+        }
+        with_live_allocations(|live_allocations| {
+            if let Some(partition) = live_allocations.remove(&(ptr as usize)) {
+                remove(partition, size);
+                live_allocations.insert(new_ptr as usize, partition);
+                add(partition, new_size);
+            }
+        });
+    }
+}
+
+#[cfg(all(test, feature = "heap-partitions"))]
+mod tests {
+    use std::alloc::{alloc, dealloc, realloc, Layout};
+
+    use super::*;
+
+    fn stats_of(partition: Partition) -> (usize, usize) {
+        let stats = partition_stats()
+            .into_iter()
+            .find(|stats| stats.partition == partition)
+            .unwrap();
+        (stats.allocated, stats.allocations)
+    }
+
+    #[test]
+    fn allocations_are_counted_towards_the_partition_they_are_made_in() {
+        // No other test enters this partition, so its counts only change
+        // with the allocations of this test.
+        let partition = Partition::Gfx;
+        let (allocated, allocations) = stats_of(partition);
+
+        let layout = Layout::from_size_align(1000, 8).unwrap();
+        let block = {
+            let _guard = enter_partition(partition);
+            unsafe { alloc(layout) }
+        };
+        assert_eq!(stats_of(partition), (allocated + 1000, allocations + 1));
+
+        // Reallocating outside of the partition keeps the block in it.
+        let block = unsafe { realloc(block, layout, 3000) };
+        assert_eq!(stats_of(partition), (allocated + 3000, allocations + 1));
+
+        unsafe { dealloc(block, Layout::from_size_align(3000, 8).unwrap()) };
+        assert_eq!(stats_of(partition), (allocated, allocations));
+    }
+
+    #[test]
+    fn leaving_a_partition_restores_the_previous_one() {
+        let layout = Layout::from_size_align(100, 8).unwrap();
+        let (allocated, _) = stats_of(Partition::Layout);
+
+        let block = {
+            let _outer = enter_partition(Partition::Layout);
+            {
+                let _inner = enter_partition(Partition::StructuredClone);
+            }
+            unsafe { alloc(layout) }
+        };
+        assert_eq!(stats_of(Partition::Layout).0, allocated + 100);
+        unsafe { dealloc(block, layout) };
+    }
+}
+
+#[cfg(all(test, not(feature = "heap-partitions")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partitions_are_not_counted() {
+        let _guard = enter_partition(Partition::Dom);
+        let _block = vec![0u8; 1000];
+        assert!(partition_stats().is_empty());
+    }
+}
+
+#[cfg(not(feature = "heap-partitions"))]
+mod platform {
+    use std::marker::PhantomData;
+
+    use super::{Partition, PartitionGuard, PartitionStats};
+
+    /// Allocations are only attributed to partitions with the
+    /// `heap-partitions` feature.
+    pub fn enter_partition(_partition: Partition) -> PartitionGuard {
+        PartitionGuard {
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Allocations are only attributed to partitions with the
+    /// `heap-partitions` feature.
+    pub fn partition_stats() -> Vec<PartitionStats> {
+        vec![]
+    }
+}
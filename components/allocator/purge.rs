@@ -0,0 +1,69 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Returning unused memory to the operating system on demand.
+//!
+//! Allocators keep the pages freed by the application around for a while,
+//! so that they can be reused cheaply. This is usually the right trade-off,
+//! but not when the system is low on memory or when the application is in
+//! the background, which is when [`purge`] is meant to be called.
+
+pub use self::platform::purge;
+
+#[cfg(jemalloc)]
+mod platform {
+    use crate::mallctl::call;
+
+    /// The pseudo arena index that stands for all the arenas, which is
+    /// `MALLCTL_ARENAS_ALL` in jemalloc's headers.
+    const ALL_ARENAS: u32 = 4096;
+
+    /// Return the dirty and muzzy pages of every arena to the operating
+    /// system right away, rather than once their decay time has elapsed.
+    ///
+    /// Only the allocation cache of the calling thread is flushed, so the
+    /// few pages held by the caches of other threads are not returned.
+    pub fn purge() {
+        if !call("thread.tcache.flush") {
+            log::warn!("Failed to flush the allocation cache of the current thread");
+        }
+        if !call(&format!("arena.{}.purge", ALL_ARENAS)) {
+            log::warn!("Failed to purge the allocator arenas");
+        }
+    }
+}
+
+#[cfg(all(not(jemalloc), target_os = "linux", target_env = "gnu"))]
+mod platform {
+    /// Return the free memory at the top of the heap, and the free pages
+    /// inside of it, to the operating system.
+    pub fn purge() {
+        unsafe {
+            libc::malloc_trim(0);
+        }
+    }
+}
+
+#[cfg(all(not(jemalloc), windows))]
+mod platform {
+    use winapi::um::heapapi::{GetProcessHeap, HeapCompact};
+
+    /// Coalesce the free blocks of the process heap, which lets Windows
+    /// decommit the pages they span.
+    pub fn purge() {
+        unsafe {
+            HeapCompact(GetProcessHeap(), 0);
+        }
+    }
+}
+
+#[cfg(all(
+    not(jemalloc),
+    not(all(target_os = "linux", target_env = "gnu")),
+    not(windows)
+))]
+mod platform {
+    /// The system allocator does not support purging unused memory.
+    pub fn purge() {}
+}
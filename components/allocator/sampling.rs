@@ -0,0 +1,230 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! An opt-in allocation sampling layer.
+//!
+//! [`SamplingAllocator`] wraps another allocator and, while sampling is
+//! enabled with [`start`], records the call stack of the allocation that
+//! crosses every `interval`th byte allocated on a thread. The recorded stacks
+//! can be dumped with [`write_collapsed_stacks`] in the collapsed-stack format
+//! understood by `flamegraph.pl`, `inferno` and most other flame graph tools.
+//!
+//! Sampling is process-wide and costs a single atomic load per allocation
+//! while it is disabled.
+
+use std::alloc::{GlobalAlloc, Layout};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::os::raw::c_void;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// The maximum number of frames that are recorded for each sample.
+const MAX_FRAMES: usize = 64;
+
+/// The number of bytes between two samples, or zero when sampling is
+/// disabled.
+static INTERVAL: AtomicUsize = AtomicUsize::new(0);
+
+/// The number of bytes sampled for each stack, keyed by the instruction
+/// pointers of its frames, innermost first.
+static SAMPLES: Mutex<Option<HashMap<Vec<usize>, usize>>> = Mutex::new(None);
+
+thread_local! {
+    /// The number of bytes allocated on this thread since the last sample.
+    static ALLOCATED_SINCE_SAMPLE: Cell<usize> = const { Cell::new(0) };
+
+    /// Set while this thread is inside the sampler, so that the allocations
+    /// made while recording a sample are not sampled themselves.
+    static IN_SAMPLER: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A [`GlobalAlloc`] wrapper that samples the allocations made through the
+/// wrapped allocator.
+pub struct SamplingAllocator<A> {
+    inner: A,
+}
+
+impl<A> SamplingAllocator<A> {
+    pub const fn new(inner: A) -> Self {
+        SamplingAllocator { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for SamplingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        note_allocation(layout.size());
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        note_allocation(layout.size());
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        note_allocation(new_size.saturating_sub(layout.size()));
+        self.inner.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Start sampling one allocation for every `interval` bytes allocated.
+/// Samples recorded by a previous run are kept.
+pub fn start(interval: usize) {
+    INTERVAL.store(interval, Ordering::Relaxed);
+}
+
+/// Stop sampling. The samples recorded so far are kept until [`clear`] is
+/// called.
+pub fn stop() {
+    INTERVAL.store(0, Ordering::Relaxed);
+}
+
+/// Whether sampling is currently enabled.
+pub fn is_sampling() -> bool {
+    INTERVAL.load(Ordering::Relaxed) != 0
+}
+
+/// Throw away all the samples recorded so far.
+pub fn clear() {
+    with_sampler_guard(|| {
+        SAMPLES.lock().unwrap().take();
+    })
+}
+
+/// Write the recorded samples to `writer` in the collapsed-stack format: one
+/// line per distinct stack, with the frames separated by semicolons from the
+/// outermost to the innermost one, followed by the number of bytes sampled.
+pub fn write_collapsed_stacks<W: Write>(writer: &mut W) -> io::Result<()> {
+    with_sampler_guard(|| {
+        let samples: Vec<_> = match *SAMPLES.lock().unwrap() {
+            Some(ref samples) => samples
+                .iter()
+                .map(|(frames, bytes)| (frames.clone(), *bytes))
+                .collect(),
+            None => return Ok(()),
+        };
+
+        let mut symbol_cache = HashMap::new();
+        for (frames, bytes) in samples {
+            let stack: Vec<_> = frames
+                .iter()
+                .rev()
+                .map(|ip| {
+                    symbol_cache
+                        .entry(*ip)
+                        .or_insert_with(|| resolve(*ip))
+                        .clone()
+                })
+                .collect();
+            writeln!(writer, "{} {}", stack.join(";"), bytes)?;
+        }
+        Ok(())
+    })
+}
+
+fn note_allocation(size: usize) {
+    let interval = INTERVAL.load(Ordering::Relaxed);
+    if interval == 0 || size == 0 {
+        return;
+    }
+
+    // `try_with` fails while the thread-local storage of this thread is being
+    // torn down, in which case the allocation is simply not accounted for.
+    let _ = IN_SAMPLER.try_with(|in_sampler| {
+        if in_sampler.get() {
+            return;
+        }
+        let sampled =
+            ALLOCATED_SINCE_SAMPLE.with(|allocated| bytes_to_sample(allocated, size, interval));
+        if sampled != 0 {
+            in_sampler.set(true);
+            record_sample(sampled);
+            in_sampler.set(false);
+        }
+    });
+}
+
+/// Count an allocation of `size` bytes towards the bytes `allocated` since
+/// the last sample, and return the number of bytes that the allocation is to
+/// be sampled for: a multiple of `interval`, or zero if it does not cross
+/// the next sampling point.
+fn bytes_to_sample(allocated: &Cell<usize>, size: usize, interval: usize) -> usize {
+    let total = allocated.get() + size;
+    allocated.set(total % interval);
+    total / interval * interval
+}
+
+fn record_sample(bytes: usize) {
+    let mut frames = [0usize; MAX_FRAMES];
+    let mut frame_count = 0;
+    backtrace::trace(|frame| {
+        frames[frame_count] = frame.ip() as usize;
+        frame_count += 1;
+        frame_count < MAX_FRAMES
+    });
+
+    let mut samples = SAMPLES.lock().unwrap();
+    *samples
+        .get_or_insert_with(HashMap::new)
+        .entry(frames[..frame_count].to_vec())
+        .or_default() += bytes;
+}
+
+fn resolve(ip: usize) -> String {
+    let mut name = None;
+    backtrace::resolve(ip as *mut c_void, |symbol| {
+        if name.is_none() {
+            name = symbol.name().map(|name| format!("{:#}", name));
+        }
+    });
+    name.unwrap_or_else(|| format!("{:#x}", ip))
+}
+
+fn with_sampler_guard<R>(f: impl FnOnce() -> R) -> R {
+    let was_in_sampler = IN_SAMPLER.with(|in_sampler| in_sampler.replace(true));
+    let result = f();
+    IN_SAMPLER.with(|in_sampler| in_sampler.set(was_in_sampler));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn one_sample_is_taken_every_interval_bytes() {
+        let allocated = Cell::new(0);
+        let samples: Vec<_> = [30, 30, 30, 30, 250, 5, 100]
+            .iter()
+            .map(|&size| bytes_to_sample(&allocated, size, 100))
+            .collect();
+        // Allocations larger than the interval stand for every interval they
+        // cross.
+        assert_eq!(samples, [0, 0, 0, 100, 200, 0, 100]);
+        assert_eq!(allocated.get(), 75);
+    }
+
+    #[test]
+    fn recorded_samples_add_up() {
+        with_sampler_guard(|| {
+            for _ in 0..2 {
+                record_sample(300);
+            }
+        });
+        let mut report = Vec::new();
+        write_collapsed_stacks(&mut report).unwrap();
+        let report = String::from_utf8(report).unwrap();
+        // Both samples come from the same stack, which is on a line of its
+        // own, so its count is the sum of both.
+        assert!(report
+            .lines()
+            .any(|line| line.ends_with(" 600") && line.contains("recorded_samples_add_up")));
+    }
+}
@@ -0,0 +1,130 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Cheaper ways to find out the size of heap blocks than calling
+//! [`crate::usable_size`] on every pointer, for memory reports walking
+//! millions of objects.
+
+pub use self::platform::usable_size_for_layout;
+
+#[cfg(jemalloc)]
+mod platform {
+    use std::alloc::Layout;
+    use std::os::raw::c_int;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use jemalloc_sys::{nallocx, MALLOCX_ALIGN};
+
+    /// The alignment of `max_align_t`, which jemalloc guarantees without
+    /// being asked for it.
+    #[cfg(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    ))]
+    const MAX_ALIGN: usize = 16;
+    #[cfg(not(any(
+        target_arch = "x86_64",
+        target_arch = "aarch64",
+        target_arch = "powerpc64",
+        target_arch = "s390x"
+    )))]
+    const MAX_ALIGN: usize = 8;
+
+    /// Every size class of jemalloc is a multiple of this.
+    const QUANTUM: usize = 8;
+
+    /// The largest size whose size class is cached.
+    const MAX_CACHED_SIZE: usize = 4096;
+
+    /// The size classes of the sizes up to `MAX_CACHED_SIZE`, indexed by the
+    /// size divided by `QUANTUM` and rounded up, or zero when not looked up
+    /// yet.
+    static SIZE_CLASSES: [AtomicUsize; MAX_CACHED_SIZE / QUANTUM + 1] =
+        [ZERO; MAX_CACHED_SIZE / QUANTUM + 1];
+    #[allow(clippy::declare_interior_mutable_const)]
+    const ZERO: AtomicUsize = AtomicUsize::new(0);
+
+    /// The flags jemalloc is passed for `layout`, as in `jemallocator`.
+    fn flags(layout: Layout) -> c_int {
+        if layout.align() <= MAX_ALIGN && layout.align() <= layout.size() {
+            0
+        } else {
+            MALLOCX_ALIGN(layout.align())
+        }
+    }
+
+    /// Get the size of the heap blocks allocated for `layout`, which is that
+    /// of the size class it falls in.
+    pub fn usable_size_for_layout(layout: Layout) -> Option<usize> {
+        let flags = flags(layout);
+        if flags != 0 || layout.size() > MAX_CACHED_SIZE {
+            return Some(unsafe { nallocx(layout.size().max(1), flags) });
+        }
+
+        let class = &SIZE_CLASSES[layout.size().div_ceil(QUANTUM)];
+        match class.load(Ordering::Relaxed) {
+            0 => {
+                let size = unsafe { nallocx(layout.size().max(1), 0) };
+                class.store(size, Ordering::Relaxed);
+                Some(size)
+            },
+            size => Some(size),
+        }
+    }
+}
+
+#[cfg(all(not(jemalloc), not(windows)))]
+mod platform {
+    use std::alloc::Layout;
+
+    /// The size classes of the system allocator are unknown.
+    pub fn usable_size_for_layout(_layout: Layout) -> Option<usize> {
+        None
+    }
+}
+
+#[cfg(all(not(jemalloc), windows))]
+mod platform {
+    use std::alloc::Layout;
+
+    use crate::platform::MIN_ALIGN;
+
+    /// The process heap hands out blocks of exactly the requested size, but
+    /// over-aligned allocations are offset into a larger one.
+    pub fn usable_size_for_layout(layout: Layout) -> Option<usize> {
+        if layout.align() <= MIN_ALIGN {
+            Some(layout.size())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::alloc::{alloc, dealloc, Layout};
+    use std::os::raw::c_void;
+
+    use super::*;
+
+    #[test]
+    fn usable_size_for_layout_is_that_of_the_block() {
+        for size in [1, 8, 24, 100, 4096, 5000] {
+            let layout = Layout::from_size_align(size, 8).unwrap();
+            let predicted = match usable_size_for_layout(layout) {
+                Some(predicted) => predicted,
+                None => continue,
+            };
+            // Ask twice, as the second answer comes from the cache.
+            assert_eq!(usable_size_for_layout(layout), Some(predicted));
+            unsafe {
+                let block = alloc(layout);
+                assert_eq!(crate::usable_size(block as *const c_void), predicted);
+                dealloc(block, layout);
+            }
+        }
+    }
+}
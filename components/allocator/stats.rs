@@ -0,0 +1,187 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Typed access to allocator-level statistics.
+//!
+//! Heap-walk based memory reports only see the bytes that `MallocSizeOf`
+//! implementations know about. The numbers gathered here come straight from
+//! the allocator and include the slack between what was requested and what is
+//! actually held on to, which is what is needed to spot fragmentation.
+
+/// Process-wide statistics reported by the allocator.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllocatorStats {
+    /// Total number of bytes allocated by the application.
+    pub allocated: usize,
+    /// Total number of bytes in active pages allocated by the application.
+    /// This is a multiple of the page size, and greater than or equal to
+    /// `allocated`.
+    pub active: usize,
+    /// Maximum number of bytes in physically resident data pages mapped by
+    /// the allocator, including allocator metadata and dirty pages.
+    pub resident: usize,
+    /// Total number of bytes in active extents mapped by the allocator. This
+    /// is at least as large as `active`.
+    pub mapped: usize,
+    /// Total number of bytes in virtual memory mappings that were retained
+    /// rather than being returned to the operating system.
+    pub retained: usize,
+    /// Per-arena statistics, for every arena that has been initialized.
+    pub arenas: Vec<ArenaStats>,
+}
+
+impl AllocatorStats {
+    /// The number of bytes held in active pages that are not handed out to
+    /// the application. A large value relative to `allocated` indicates
+    /// fragmentation.
+    pub fn fragmentation(&self) -> usize {
+        self.active.saturating_sub(self.allocated)
+    }
+}
+
+/// Statistics for a single allocator arena.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArenaStats {
+    /// The index of this arena.
+    pub index: usize,
+    /// Number of threads currently assigned to this arena.
+    pub threads: usize,
+    /// Number of bytes in active pages.
+    pub active: usize,
+    /// Number of bytes in pages that are unused but not yet purged.
+    pub dirty: usize,
+    /// Number of bytes in pages that were lazily purged and may be reused.
+    pub muzzy: usize,
+    /// Number of bytes in mapped extents.
+    pub mapped: usize,
+    /// Number of bytes in physically resident pages.
+    pub resident: usize,
+    /// Number of bytes allocated by small (size-classed) allocations.
+    pub small_allocated: usize,
+    /// Number of bytes allocated by large allocations.
+    pub large_allocated: usize,
+}
+
+impl ArenaStats {
+    /// Total number of bytes allocated from this arena.
+    pub fn allocated(&self) -> usize {
+        self.small_allocated + self.large_allocated
+    }
+}
+
+pub use self::platform::stats;
+
+#[cfg(jemalloc)]
+mod platform {
+    use std::os::raw::c_uint;
+
+    use super::{AllocatorStats, ArenaStats};
+    use crate::mallctl::{read, write};
+
+    /// Ask jemalloc to refresh its cached statistics. Without this jemalloc
+    /// gives cached statistics(!) which can be highly inaccurate.
+    fn advance_epoch() -> bool {
+        write::<u64>("epoch", 1)
+    }
+
+    fn arena_stats(index: usize, page_size: usize) -> Option<ArenaStats> {
+        let stat = |name: &str| read::<usize>(&format!("stats.arenas.{}.{}", index, name));
+        Some(ArenaStats {
+            index,
+            threads: read::<c_uint>(&format!("stats.arenas.{}.nthreads", index))? as usize,
+            active: stat("pactive")? * page_size,
+            dirty: stat("pdirty")? * page_size,
+            muzzy: stat("pmuzzy").unwrap_or(0) * page_size,
+            mapped: stat("mapped")?,
+            resident: stat("resident").unwrap_or(0),
+            small_allocated: stat("small.allocated")?,
+            large_allocated: stat("large.allocated")?,
+        })
+    }
+
+    /// Gather a fresh snapshot of the allocator statistics. Returns `None` if
+    /// the allocator was built without statistics support.
+    pub fn stats() -> Option<AllocatorStats> {
+        if !advance_epoch() {
+            return None;
+        }
+
+        let page_size = read::<usize>("arenas.page")?;
+        let arena_count = read::<c_uint>("arenas.narenas")? as usize;
+        let arenas = (0..arena_count)
+            .filter(|&index| read::<bool>(&format!("arena.{}.initialized", index)) == Some(true))
+            .filter_map(|index| arena_stats(index, page_size))
+            .collect();
+
+        Some(AllocatorStats {
+            allocated: read("stats.allocated")?,
+            active: read("stats.active")?,
+            resident: read("stats.resident")?,
+            mapped: read("stats.mapped")?,
+            retained: read("stats.retained").unwrap_or(0),
+            arenas,
+        })
+    }
+}
+
+#[cfg(not(jemalloc))]
+mod platform {
+    use super::AllocatorStats;
+
+    /// The system allocator does not expose statistics in a portable way.
+    pub fn stats() -> Option<AllocatorStats> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fragmentation_is_the_slack_of_active_pages() {
+        let stats = AllocatorStats {
+            allocated: 1000,
+            active: 4096,
+            ..AllocatorStats::default()
+        };
+        assert_eq!(stats.fragmentation(), 3096);
+
+        // The counters are gathered one at a time, so they may be slightly
+        // inconsistent with each other.
+        let stats = AllocatorStats {
+            allocated: 5000,
+            active: 4096,
+            ..AllocatorStats::default()
+        };
+        assert_eq!(stats.fragmentation(), 0);
+    }
+
+    #[test]
+    fn arena_allocated_is_small_and_large_allocations() {
+        let arena = ArenaStats {
+            small_allocated: 100,
+            large_allocated: 20000,
+            ..ArenaStats::default()
+        };
+        assert_eq!(arena.allocated(), 20100);
+    }
+
+    #[cfg(jemalloc)]
+    #[test]
+    fn stats_see_new_allocations() {
+        let block = vec![1u8; 64 << 20];
+        let stats = stats().expect("jemalloc is built with statistics");
+        assert!(stats.allocated >= block.len());
+        assert!(stats.active >= stats.allocated);
+        assert!(stats.mapped >= stats.active);
+        assert!(!stats.arenas.is_empty());
+    }
+
+    #[cfg(not(jemalloc))]
+    #[test]
+    fn stats_are_not_available() {
+        assert_eq!(stats(), None);
+    }
+}
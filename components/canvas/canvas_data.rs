@@ -128,6 +128,7 @@ pub trait GenericPathBuilder {
     fn line_to(&mut self, point: Point2D<f32>);
     fn move_to(&mut self, point: Point2D<f32>);
     fn quadratic_curve_to(&mut self, control_point: &Point2D<f32>, end_point: &Point2D<f32>);
+    fn append_path(&mut self, path: &Path, transform: &Transform2D<f32>);
     fn finish(&mut self) -> Path;
 }
 
@@ -167,6 +168,66 @@ impl<'a> PathBuilderRef<'a> {
         self.move_to(&first);
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    fn round_rect(&mut self, rect: &Rect<f32>, radii: &CornerRadii) {
+        let [mut upper_left, mut upper_right, mut lower_right, mut lower_left] = *radii;
+        let (x, y) = (rect.origin.x, rect.origin.y);
+        let (width, height) = (rect.size.width, rect.size.height);
+        // The radii are given for the corners as they appear, but the path goes from the
+        // origin of the rectangle along its width first, whichever way they go.
+        if width < 0. {
+            mem::swap(&mut upper_left, &mut upper_right);
+            mem::swap(&mut lower_left, &mut lower_right);
+        }
+        if height < 0. {
+            mem::swap(&mut upper_left, &mut lower_left);
+            mem::swap(&mut upper_right, &mut lower_right);
+        }
+        let (sign_x, sign_y) = (width.signum(), height.signum());
+
+        self.move_to(&point2(x + sign_x * upper_left.width, y));
+        self.line_to(&point2(x + width - sign_x * upper_right.width, y));
+        self.corner_to(
+            &point2(x + width, y),
+            &point2(x + width, y + sign_y * upper_right.height),
+        );
+        self.line_to(&point2(x + width, y + height - sign_y * lower_right.height));
+        self.corner_to(
+            &point2(x + width, y + height),
+            &point2(x + width - sign_x * lower_right.width, y + height),
+        );
+        self.line_to(&point2(x + sign_x * lower_left.width, y + height));
+        self.corner_to(
+            &point2(x, y + height),
+            &point2(x, y + height - sign_y * lower_left.height),
+        );
+        self.line_to(&point2(x, y + sign_y * upper_left.height));
+        self.corner_to(&point2(x, y), &point2(x + sign_x * upper_left.width, y));
+        self.close();
+        self.move_to(&point2(x, y));
+    }
+
+    /// Curve from the current point to `endpoint` around `corner`, along a quarter of the
+    /// ellipse that touches the sides of the corner at both points.
+    fn corner_to(&mut self, corner: &Point2D<f32>, endpoint: &Point2D<f32>) {
+        // How far along the sides the control points of a cubic Bézier curve approximating
+        // a quarter of an ellipse are.
+        const KAPPA: f32 = 0.552_284_8;
+        let start = match self.current_point() {
+            Some(start) => start,
+            None => return,
+        };
+        if start == *corner || *endpoint == *corner {
+            self.line_to(endpoint);
+            return;
+        }
+        self.bezier_curve_to(
+            &start.lerp(*corner, KAPPA),
+            &endpoint.lerp(*corner, KAPPA),
+            endpoint,
+        );
+    }
+
     fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, endpoint: &Point2D<f32>) {
         self.builder.quadratic_curve_to(
             &self.transform.transform_point(*cp),
@@ -217,6 +278,83 @@ impl<'a> PathBuilderRef<'a> {
         );
     }
 
+    fn arc_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, radius: f32) {
+        let cp0 = match self.current_point() {
+            Some(p) => p,
+            None => {
+                self.move_to(cp1);
+                cp1.clone()
+            },
+        };
+        let cp1 = *cp1;
+        let cp2 = *cp2;
+
+        if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
+            self.line_to(&cp1);
+            return;
+        }
+
+        // if all three control points lie on a single straight line,
+        // connect the first two by a straight line
+        let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
+        if direction == 0.0 {
+            self.line_to(&cp1);
+            return;
+        }
+
+        // otherwise, draw the Arc
+        let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
+        let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
+        let d = {
+            let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
+            let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
+            let sinx = (1.0 - cosx.powi(2)).sqrt();
+            radius / ((1.0 - cosx) / sinx)
+        };
+
+        // first tangent point
+        let anx = (cp1.x - cp0.x) / a2.sqrt();
+        let any = (cp1.y - cp0.y) / a2.sqrt();
+        let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
+
+        // second tangent point
+        let bnx = (cp1.x - cp2.x) / b2.sqrt();
+        let bny = (cp1.y - cp2.y) / b2.sqrt();
+        let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
+
+        // arc center and angles
+        let anticlockwise = direction < 0.0;
+        let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
+        let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
+        let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
+        let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
+
+        self.line_to(&tp1);
+        if [cx, cy, angle_start, angle_end]
+            .iter()
+            .all(|x| x.is_finite())
+        {
+            self.arc(
+                &Point2D::new(cx, cy),
+                radius,
+                angle_start,
+                angle_end,
+                anticlockwise,
+            );
+        }
+    }
+
+    /// Append the subpaths of `path`, transformed by `transform`, then start a new
+    /// subpath at the last point of the last of them.
+    /// <https://html.spec.whatwg.org/multipage/#dom-path2d-addpath>
+    fn add_path(&mut self, path: &Path, transform: &Transform2D<f32>) {
+        self.builder
+            .append_path(path, &transform.then(&self.transform));
+        if let Some(point) = self.builder.get_current_point() {
+            self.builder.move_to(point);
+        }
+    }
+
     fn current_point(&mut self) -> Option<Point2D<f32>> {
         let inverse = match self.transform.inverse() {
             Some(i) => i,
@@ -620,7 +758,8 @@ impl<'a> CanvasData<'a> {
                 },
                 crate::raqote_backend::Pattern::Color(..) |
                 crate::raqote_backend::Pattern::LinearGradient(..) |
-                crate::raqote_backend::Pattern::RadialGradient(..) => *rect,
+                crate::raqote_backend::Pattern::RadialGradient(..) |
+                crate::raqote_backend::Pattern::ConicGradient(..) => *rect,
             },
         };
 
@@ -805,6 +944,95 @@ impl<'a> CanvasData<'a> {
         chan.send(result).unwrap();
     }
 
+    /// Flatten the segments of a `Path2D` into a path, in the coordinate space of the
+    /// `Path2D`, which is the user space of the canvas it is drawn on.
+    fn path_from_segments(&self, segments: &[PathSegment]) -> Path {
+        let mut builder = self.drawtarget.create_path_builder();
+        let mut builder_ref = PathBuilderRef {
+            builder: &mut builder,
+            transform: Transform2D::identity(),
+        };
+        for segment in segments {
+            match *segment {
+                PathSegment::Arc(ref center, radius, start, end, ccw) => {
+                    builder_ref.arc(center, radius, start, end, ccw)
+                },
+                PathSegment::ArcTo(ref cp1, ref cp2, radius) => {
+                    builder_ref.arc_to(cp1, cp2, radius)
+                },
+                PathSegment::BezierCurveTo(ref cp1, ref cp2, ref endpoint) => {
+                    if builder_ref.current_point().is_none() {
+                        builder_ref.move_to(cp1);
+                    }
+                    builder_ref.bezier_curve_to(cp1, cp2, endpoint)
+                },
+                PathSegment::ClosePath => builder_ref.close(),
+                PathSegment::Ellipse(ref center, radius_x, radius_y, rotation, start, end, ccw) => {
+                    builder_ref.ellipse(center, radius_x, radius_y, rotation, start, end, ccw)
+                },
+                PathSegment::LineTo(ref point) => builder_ref.line_to(point),
+                PathSegment::MoveTo(ref point) => builder_ref.move_to(point),
+                PathSegment::QuadraticCurveTo(ref cp, ref endpoint) => {
+                    if builder_ref.current_point().is_none() {
+                        builder_ref.move_to(cp);
+                    }
+                    builder_ref.quadratic_curve_to(cp, endpoint)
+                },
+                PathSegment::Rect(ref rect) => builder_ref.rect(rect),
+                PathSegment::RoundRect(ref rect, ref radii) => builder_ref.round_rect(rect, radii),
+                PathSegment::AddPath(ref segments, ref transform) => {
+                    builder_ref.add_path(&self.path_from_segments(segments), transform)
+                },
+            }
+        }
+        builder.finish()
+    }
+
+    pub fn fill_path(&mut self, segments: &[PathSegment]) {
+        if self.state.fill_style.is_zero_size_gradient() {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        let path = self.path_from_segments(segments);
+        self.drawtarget.fill(
+            &path,
+            self.state.fill_style.clone(),
+            &self.state.draw_options,
+        );
+    }
+
+    pub fn stroke_path(&mut self, segments: &[PathSegment]) {
+        if self.state.stroke_style.is_zero_size_gradient() {
+            return; // Paint nothing if gradient size is zero.
+        }
+
+        let path = self.path_from_segments(segments);
+        self.drawtarget.stroke(
+            &path,
+            self.state.stroke_style.clone(),
+            &self.state.stroke_opts,
+            &self.state.draw_options,
+        );
+    }
+
+    pub fn clip_path(&mut self, segments: &[PathSegment]) {
+        let path = self.path_from_segments(segments);
+        self.drawtarget.push_clip(&path);
+    }
+
+    pub fn is_point_in_path_2d(
+        &mut self,
+        segments: &[PathSegment],
+        x: f64,
+        y: f64,
+        _fill_rule: FillRule,
+        chan: IpcSender<bool>,
+    ) {
+        let path = self.path_from_segments(segments);
+        let result = path.contains_point(x, y, &self.drawtarget.get_transform());
+        chan.send(result).unwrap();
+    }
+
     pub fn move_to(&mut self, point: &Point2D<f32>) {
         self.path_builder().move_to(point);
     }
@@ -882,6 +1110,10 @@ impl<'a> CanvasData<'a> {
         self.path_builder().rect(rect);
     }
 
+    pub fn round_rect(&mut self, rect: &Rect<f32>, radii: &CornerRadii) {
+        self.path_builder().round_rect(rect, radii);
+    }
+
     pub fn quadratic_curve_to(&mut self, cp: &Point2D<f32>, endpoint: &Point2D<f32>) {
         if self.path_state.is_none() {
             self.move_to(cp);
@@ -914,69 +1146,7 @@ impl<'a> CanvasData<'a> {
     }
 
     pub fn arc_to(&mut self, cp1: &Point2D<f32>, cp2: &Point2D<f32>, radius: f32) {
-        let cp0 = match self.path_builder().current_point() {
-            Some(p) => p,
-            None => {
-                self.path_builder().move_to(cp1);
-                cp1.clone()
-            },
-        };
-        let cp1 = *cp1;
-        let cp2 = *cp2;
-
-        if (cp0.x == cp1.x && cp0.y == cp1.y) || cp1 == cp2 || radius == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // if all three control points lie on a single straight line,
-        // connect the first two by a straight line
-        let direction = (cp2.x - cp1.x) * (cp0.y - cp1.y) + (cp2.y - cp1.y) * (cp1.x - cp0.x);
-        if direction == 0.0 {
-            self.line_to(&cp1);
-            return;
-        }
-
-        // otherwise, draw the Arc
-        let a2 = (cp0.x - cp1.x).powi(2) + (cp0.y - cp1.y).powi(2);
-        let b2 = (cp1.x - cp2.x).powi(2) + (cp1.y - cp2.y).powi(2);
-        let d = {
-            let c2 = (cp0.x - cp2.x).powi(2) + (cp0.y - cp2.y).powi(2);
-            let cosx = (a2 + b2 - c2) / (2.0 * (a2 * b2).sqrt());
-            let sinx = (1.0 - cosx.powi(2)).sqrt();
-            radius / ((1.0 - cosx) / sinx)
-        };
-
-        // first tangent point
-        let anx = (cp1.x - cp0.x) / a2.sqrt();
-        let any = (cp1.y - cp0.y) / a2.sqrt();
-        let tp1 = Point2D::new(cp1.x - anx * d, cp1.y - any * d);
-
-        // second tangent point
-        let bnx = (cp1.x - cp2.x) / b2.sqrt();
-        let bny = (cp1.y - cp2.y) / b2.sqrt();
-        let tp2 = Point2D::new(cp1.x - bnx * d, cp1.y - bny * d);
-
-        // arc center and angles
-        let anticlockwise = direction < 0.0;
-        let cx = tp1.x + any * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let cy = tp1.y - anx * radius * if anticlockwise { 1.0 } else { -1.0 };
-        let angle_start = (tp1.y - cy).atan2(tp1.x - cx);
-        let angle_end = (tp2.y - cy).atan2(tp2.x - cx);
-
-        self.line_to(&tp1);
-        if [cx, cy, angle_start, angle_end]
-            .iter()
-            .all(|x| x.is_finite())
-        {
-            self.arc(
-                &Point2D::new(cx, cy),
-                radius,
-                angle_start,
-                angle_end,
-                anticlockwise,
-            );
-        }
+        self.path_builder().arc_to(cp1, cp2, radius);
     }
 
     pub fn ellipse(
@@ -1061,6 +1231,7 @@ impl<'a> CanvasData<'a> {
             .create_drawtarget(Size2D::new(size.width, size.height));
         self.state = self.backend.recreate_paint_state(&self.state);
         self.saved_states.clear();
+        self.path_state = None;
         // Webrender doesn't let images change size, so we clear the webrender image key.
         // TODO: there is an annying race condition here: the display list builder
         // might still be using the old image key. Really, we should be scheduling the image
@@ -174,6 +174,18 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::IsPointInPath(x, y, fill_rule, chan) => self
                 .canvas(canvas_id)
                 .is_point_in_path(x, y, fill_rule, chan),
+            Canvas2dMsg::FillPath(ref segments, style) => {
+                self.canvas(canvas_id).set_fill_style(style);
+                self.canvas(canvas_id).fill_path(segments);
+            },
+            Canvas2dMsg::StrokePath(ref segments, style) => {
+                self.canvas(canvas_id).set_stroke_style(style);
+                self.canvas(canvas_id).stroke_path(segments);
+            },
+            Canvas2dMsg::ClipPath(ref segments) => self.canvas(canvas_id).clip_path(segments),
+            Canvas2dMsg::IsPointInPath2D(ref segments, x, y, fill_rule, chan) => self
+                .canvas(canvas_id)
+                .is_point_in_path_2d(segments, x, y, fill_rule, chan),
             Canvas2dMsg::DrawImage(
                 ref image_data,
                 image_size,
@@ -220,6 +232,9 @@ impl<'a> CanvasPaintThread<'a> {
             Canvas2dMsg::MoveTo(ref point) => self.canvas(canvas_id).move_to(point),
             Canvas2dMsg::LineTo(ref point) => self.canvas(canvas_id).line_to(point),
             Canvas2dMsg::Rect(ref rect) => self.canvas(canvas_id).rect(rect),
+            Canvas2dMsg::RoundRect(ref rect, ref radii) => {
+                self.canvas(canvas_id).round_rect(rect, radii)
+            },
             Canvas2dMsg::QuadraticCurveTo(ref cp, ref pt) => {
                 self.canvas(canvas_id).quadratic_curve_to(cp, pt)
             },
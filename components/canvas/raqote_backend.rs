@@ -101,6 +101,7 @@ pub enum Pattern<'a> {
     Color(u8, u8, u8, u8),
     LinearGradient(LinearGradientPattern),
     RadialGradient(RadialGradientPattern),
+    ConicGradient(ConicGradientPattern),
     Surface(SurfacePattern<'a>),
 }
 
@@ -108,9 +109,10 @@ impl<'a> Pattern<'a> {
     fn set_transform(&mut self, transform: Transform2D<f32>) {
         match self {
             Pattern::Surface(pattern) => pattern.set_transform(transform),
-            Pattern::LinearGradient(..) | Pattern::RadialGradient(..) | Pattern::Color(..) => {
-                warn!("transform not supported")
-            },
+            Pattern::LinearGradient(..) |
+            Pattern::RadialGradient(..) |
+            Pattern::ConicGradient(..) |
+            Pattern::Color(..) => warn!("transform not supported"),
         }
     }
 }
@@ -159,6 +161,24 @@ impl RadialGradientPattern {
     }
 }
 
+#[derive(Clone)]
+pub struct ConicGradientPattern {
+    gradient: raqote::Gradient,
+    center: Point2D<f32>,
+    /// In degrees, as raqote expects them.
+    start_angle: f32,
+}
+
+impl ConicGradientPattern {
+    fn new(center: Point2D<f32>, start_angle: f32, stops: Vec<raqote::GradientStop>) -> Self {
+        ConicGradientPattern {
+            gradient: raqote::Gradient { stops: stops },
+            center: center,
+            start_angle: start_angle,
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct SurfacePattern<'a> {
     image: raqote::Image<'a>,
@@ -238,6 +258,15 @@ impl canvas_data::Pattern<'_> {
                     pattern.radius2,
                     raqote::Spread::Pad,
                 ),
+                // The gradient makes exactly one turn around its center, so repeating it
+                // only matters where the angles wrap around.
+                Pattern::ConicGradient(pattern) => raqote::Source::new_sweep_gradient(
+                    pattern.gradient.clone(),
+                    pattern.center,
+                    pattern.start_angle,
+                    pattern.start_angle + 360.,
+                    raqote::Spread::Repeat,
+                ),
                 Pattern::Surface(pattern) => raqote::Source::Image(
                     pattern.image,
                     pattern.extend,
@@ -258,6 +287,7 @@ impl canvas_data::Pattern<'_> {
                 Pattern::LinearGradient(pattern) => {
                     (pattern.start == pattern.end) || pattern.gradient.stops.is_empty()
                 },
+                Pattern::ConicGradient(pattern) => pattern.gradient.stops.is_empty(),
                 Pattern::Color(..) | Pattern::Surface(..) => false,
             },
         }
@@ -812,6 +842,22 @@ impl GenericPathBuilder for PathBuilder {
             end_point.y,
         );
     }
+    fn append_path(&mut self, path: &Path, transform: &Transform2D<f32>) {
+        let builder = self.0.as_mut().unwrap();
+        for op in path.as_raqote().clone().transform(transform).ops {
+            match op {
+                PathOp::MoveTo(point) => builder.move_to(point.x, point.y),
+                PathOp::LineTo(point) => builder.line_to(point.x, point.y),
+                PathOp::QuadTo(control, point) => {
+                    builder.quad_to(control.x, control.y, point.x, point.y)
+                },
+                PathOp::CubicTo(control1, control2, point) => builder.cubic_to(
+                    control1.x, control1.y, control2.x, control2.y, point.x, point.y,
+                ),
+                PathOp::Close => builder.close(),
+            }
+        }
+    }
     fn finish(&mut self) -> Path {
         Path::Raqote(self.0.take().unwrap().finish())
     }
@@ -927,6 +973,15 @@ impl<'a> ToRaqotePattern<'_> for FillOrStrokeStyle {
                     stops,
                 )))
             },
+            ConicGradient(style) => {
+                let center = Point2D::new(style.x as f32, style.y as f32);
+                let stops = create_gradient_stops(style.stops);
+                Some(Pattern::ConicGradient(ConicGradientPattern::new(
+                    center,
+                    style.start_angle.to_degrees() as f32,
+                    stops,
+                )))
+            },
             Surface(ref style) => {
                 let repeat = Repetition::from_xy(style.repeat_x, style.repeat_y);
                 let data = &style.surface_data[..];
@@ -61,6 +61,8 @@ struct GLContextData {
     gl: Rc<Gl>,
     state: GLState,
     attributes: GLContextAttributes,
+    /// Whether the driver reset the context, which then needs to be recreated.
+    lost: bool,
 }
 
 #[derive(Debug)]
@@ -237,6 +239,9 @@ pub(crate) struct WebGLThreadInit {
 // A size at which it should be safe to create GL contexts
 const SAFE_VIEWPORT_DIMS: [u32; 2] = [1024, 1024];
 
+// The error reported once a context was lost, from KHR_robustness.
+const GL_CONTEXT_LOST: u32 = 0x0507;
+
 impl WebGLThread {
     /// Create a new instance of WebGLThread.
     pub(crate) fn new(
@@ -364,6 +369,9 @@ impl WebGLThread {
             WebGLMsg::RemoveContext(ctx_id) => {
                 self.remove_webgl_context(ctx_id);
             },
+            WebGLMsg::RestoreContext(ctx_id, size, sender) => {
+                let _ = sender.send(self.restore_webgl_context(ctx_id, size));
+            },
             WebGLMsg::WebGLCommand(ctx_id, command, backtrace) => {
                 self.handle_webgl_command(ctx_id, command, backtrace);
             },
@@ -480,6 +488,44 @@ impl WebGLThread {
             webgl_version, requested_size, attributes
         );
 
+        let id = WebGLContextId(
+            self.external_images
+                .lock()
+                .expect("Lock poisoned?")
+                .next_id(WebrenderImageHandlerType::WebGL)
+                .0,
+        );
+
+        let (limits, size) = self.init_gl_context(id, webgl_version, requested_size, attributes)?;
+        let has_alpha = self.contexts[&id]
+            .state
+            .requested_flags
+            .contains(ContextAttributeFlags::ALPHA);
+        let image_buffer_kind = current_wr_image_buffer_kind(&self.device);
+
+        let image_key = Self::create_wr_external_image(
+            &mut self.webrender_api,
+            self.webrender_doc,
+            size.to_i32(),
+            has_alpha,
+            id,
+            image_buffer_kind,
+        );
+
+        self.cached_context_info
+            .insert(id, WebGLContextInfo { image_key });
+
+        Ok((id, limits))
+    }
+
+    /// Creates the GL context and the swap chain of a WebGLContext
+    fn init_gl_context(
+        &mut self,
+        id: WebGLContextId,
+        webgl_version: WebGLVersion,
+        requested_size: Size2D<u32>,
+        attributes: GLContextAttributes,
+    ) -> Result<(webgl::GLLimits, Size2D<u32>), String> {
         // Creating a new GLContext may make the current bound context_id dirty.
         // Clear it to ensure that  make_current() is called in subsequent commands.
         self.bound_context_id = None;
@@ -530,14 +576,6 @@ impl WebGLThread {
             .make_context_current(&ctx)
             .map_err(|err| format!("Failed to make new context current: {:?}", err))?;
 
-        let id = WebGLContextId(
-            self.external_images
-                .lock()
-                .expect("Lock poisoned?")
-                .next_id(WebrenderImageHandlerType::WebGL)
-                .0,
-        );
-
         self.webrender_swap_chains
             .create_attached_swap_chain(id, &mut self.device, &mut ctx, surface_access)
             .map_err(|err| format!("Failed to create swap chain: {:?}", err))?;
@@ -576,7 +614,6 @@ impl WebGLThread {
         let descriptor_attributes = self.device.context_descriptor_attributes(&descriptor);
         let gl_version = descriptor_attributes.version;
         let has_alpha = requested_flags.contains(ContextAttributeFlags::ALPHA);
-        let image_buffer_kind = current_wr_image_buffer_kind(&self.device);
 
         self.device.make_context_current(&ctx).unwrap();
         let framebuffer = self
@@ -626,22 +663,11 @@ impl WebGLThread {
                 gl,
                 state,
                 attributes,
+                lost: false,
             },
         );
 
-        let image_key = Self::create_wr_external_image(
-            &mut self.webrender_api,
-            self.webrender_doc,
-            size.to_i32(),
-            has_alpha,
-            id,
-            image_buffer_kind,
-        );
-
-        self.cached_context_info
-            .insert(id, WebGLContextInfo { image_key });
-
-        Ok((id, limits))
+        Ok((limits, size))
     }
 
     /// Resizes a WebGLContext
@@ -704,6 +730,35 @@ impl WebGLThread {
             self.webrender_api.send_transaction(self.webrender_doc, txn)
         }
 
+        self.destroy_gl_context(context_id);
+    }
+
+    /// Recreates a WebGLContext which was lost, keeping its id and WebRender image key.
+    fn restore_webgl_context(
+        &mut self,
+        context_id: WebGLContextId,
+        requested_size: Size2D<u32>,
+    ) -> Result<(), String> {
+        let (webgl_version, attributes) = match self.contexts.get(&context_id) {
+            Some(data) => (data.state.webgl_version, data.attributes),
+            None => return Err("Missing WebGL context!".into()),
+        };
+
+        self.destroy_gl_context(context_id);
+        let (_, size) =
+            self.init_gl_context(context_id, webgl_version, requested_size, attributes)?;
+
+        let has_alpha = self.contexts[&context_id]
+            .state
+            .requested_flags
+            .contains(ContextAttributeFlags::ALPHA);
+        self.update_wr_image_for_context(context_id, size.to_i32(), has_alpha);
+
+        Ok(())
+    }
+
+    /// Destroys the GL context and the swap chain of a WebGLContext.
+    fn destroy_gl_context(&mut self, context_id: WebGLContextId) {
         // We need to make the context current so its resources can be disposed of.
         Self::make_current_if_needed(
             &self.device,
@@ -730,13 +785,24 @@ impl WebGLThread {
             None => return,
         };
 
-        // Destroy the swap chains
-        self.webrender_swap_chains
-            .destroy(context_id, &mut self.device, &mut data.ctx)
-            .unwrap();
+        // Destroy the swap chains, which may fail if the context was lost.
+        if let Err(err) =
+            self.webrender_swap_chains
+                .destroy(context_id, &mut self.device, &mut data.ctx)
+        {
+            warn!(
+                "Failed to destroy swap chain of {:?}: {:?}",
+                context_id, err
+            );
+        }
 
         // Destroy the context
-        self.device.destroy_context(&mut data.ctx).unwrap();
+        if let Err(err) = self.device.destroy_context(&mut data.ctx) {
+            warn!(
+                "Failed to destroy GL context of {:?}: {:?}",
+                context_id, err
+            );
+        }
 
         // Removing a GLContext may make the current bound context_id dirty.
         self.bound_context_id = None;
@@ -745,10 +811,11 @@ impl WebGLThread {
     fn handle_swap_buffers(
         &mut self,
         context_ids: Vec<WebGLContextId>,
-        completed_sender: WebGLSender<u64>,
+        completed_sender: WebGLSender<(u64, Vec<WebGLContextId>)>,
         _sent_time: u64,
     ) {
         debug!("handle_swap_buffers()");
+        let mut lost_contexts = vec![];
         for context_id in context_ids {
             let data = Self::make_current_if_needed_mut(
                 &self.device,
@@ -758,8 +825,16 @@ impl WebGLThread {
             )
             .expect("Where's the GL data?");
 
-            // Ensure there are no pending GL errors from other parts of the pipeline.
-            debug_assert_eq!(data.gl.get_error(), gl::NO_ERROR);
+            // Ensure there are no pending GL errors from other parts of the pipeline, unless
+            // the driver reset the context.
+            let error = data.gl.get_error();
+            if data.lost || error == GL_CONTEXT_LOST {
+                warn!("WebGL context {:?} was lost", context_id);
+                data.lost = true;
+                lost_contexts.push(context_id);
+                continue;
+            }
+            debug_assert_eq!(error, gl::NO_ERROR);
 
             // Check to see if any of the current framebuffer bindings are the surface we're about
             // to swap out. If so, we'll have to reset them after destroying the surface.
@@ -774,17 +849,24 @@ impl WebGLThread {
                 .expect("Where's the swap chain?");
 
             debug!("Swapping {:?}", context_id);
-            swap_chain
-                .swap_buffers(
-                    &mut self.device,
-                    &mut data.ctx,
-                    if data.attributes.preserve_drawing_buffer {
-                        PreserveBuffer::Yes(&*data.gl)
-                    } else {
-                        PreserveBuffer::No
-                    },
-                )
-                .unwrap();
+            let result = swap_chain.swap_buffers(
+                &mut self.device,
+                &mut data.ctx,
+                if data.attributes.preserve_drawing_buffer {
+                    PreserveBuffer::Yes(&*data.gl)
+                } else {
+                    PreserveBuffer::No
+                },
+            );
+            if let Err(err) = result {
+                warn!(
+                    "Failed to swap {:?}, treating it as lost: {:?}",
+                    context_id, err
+                );
+                data.lost = true;
+                lost_contexts.push(context_id);
+                continue;
+            }
             debug_assert_eq!(data.gl.get_error(), gl::NO_ERROR);
 
             if !data.attributes.preserve_drawing_buffer {
@@ -837,7 +919,7 @@ impl WebGLThread {
                 to_ms(end_swap - start_swap)
             );
         }
-        completed_sender.send(end_swap).unwrap();
+        completed_sender.send((end_swap, lost_contexts)).unwrap();
     }
 
     /// Which access mode to use
@@ -2013,7 +2095,7 @@ impl WebGLImpl {
                 gl.delete_samplers(&[sampler_id.get()]);
             },
             WebGLCommand::BindSampler(unit, sampler_id) => {
-                gl.bind_sampler(unit, sampler_id.get());
+                gl.bind_sampler(unit, sampler_id.map_or(0, WebGLSamplerId::get));
             },
             WebGLCommand::SetSamplerParameterInt(sampler_id, pname, value) => {
                 gl.sampler_parameter_i(sampler_id.get(), pname, value);
@@ -2207,7 +2289,6 @@ impl WebGLImpl {
                     base_name: from_name_in_compiled_shader(&name).into(),
                     size: if is_array { Some(size) } else { None },
                     type_,
-                    bind_index: None,
                 }
             })
             .collect::<Vec<_>>()
@@ -2227,7 +2308,13 @@ impl WebGLImpl {
                 let size =
                     gl.get_active_uniform_block_iv(program.get(), i, gl::UNIFORM_BLOCK_DATA_SIZE)
                         [0];
-                ActiveUniformBlockInfo { name, size }
+                let binding =
+                    gl.get_active_uniform_block_iv(program.get(), i, gl::UNIFORM_BLOCK_BINDING)[0];
+                ActiveUniformBlockInfo {
+                    name,
+                    size,
+                    binding: binding as u32,
+                }
             })
             .collect::<Vec<_>>()
             .into();
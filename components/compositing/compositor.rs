@@ -8,6 +8,7 @@ use std::env;
 use std::fs::{create_dir_all, File};
 use std::io::Write;
 use std::num::NonZeroU32;
+use std::ptr;
 use std::rc::Rc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -18,13 +19,14 @@ use compositing_traits::{
     SendableFrameTree,
 };
 use crossbeam_channel::Sender;
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, CustomCursor};
 use euclid::{Point2D, Rect, Scale, Transform3D, Vector2D};
 use fnv::{FnvHashMap, FnvHashSet};
 use gfx::rendering_context::RenderingContext;
 use gfx_traits::{Epoch, FontData, WebRenderEpochToU16};
 use image::{DynamicImage, ImageFormat};
 use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
 use libc::c_void;
 use log::{debug, error, info, trace, warn};
 use msg::constellation_msg::{
@@ -33,13 +35,20 @@ use msg::constellation_msg::{
 use net_traits::image::base::Image;
 use net_traits::image_cache::CorsStatus;
 use pixels::PixelFormat;
+use profile_traits::mem::{
+    self as profile_mem, ProfilerMsg, Report, ReportKind, Reporter, ReporterRequest, ReportsChan,
+};
+use profile_traits::path;
 use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
 use script_traits::compositor::{HitTestInfo, ScrollTree};
-use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent, TouchEvent, WheelEvent};
+use script_traits::CompositorEvent::{
+    DragEvent, MouseButtonEvent, MouseMoveEvent, TouchEvent, WheelEvent,
+};
 use script_traits::{
-    AnimationState, AnimationTickType, CompositorHitTestResult, ConstellationControlMsg,
-    LayoutControlMsg, MouseButton, MouseEventType, ScrollState, TouchEventType, TouchId,
-    UntrustedNodeAddress, WheelDelta, WindowSizeData, WindowSizeType,
+    AnimationState, AnimationTickType, BoxModelHighlight, CompositorHitTestResult,
+    ConstellationControlMsg, DragEventType, LayoutControlMsg, MouseButton, MouseEventType,
+    ScrollState, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta, WindowSizeData,
+    WindowSizeType,
 };
 use servo_geometry::{DeviceIndependentPixel, FramebufferUintLength};
 use style_traits::{CSSPixel, DevicePixel, PinchZoomFactor};
@@ -50,10 +59,10 @@ use webrender_api::units::{
     LayoutVector2D, WorldPoint,
 };
 use webrender_api::{
-    self, BuiltDisplayList, ClipId, DirtyRect, DocumentId, Epoch as WebRenderEpoch,
-    ExternalScrollId, HitTestFlags, PipelineId as WebRenderPipelineId, PropertyBinding,
-    ReferenceFrameKind, ScrollClamping, ScrollLocation, SpaceAndClipInfo, SpatialId,
-    TransformStyle, ZoomFactor,
+    self, BuiltDisplayList, ClipId, ColorF, CommonItemProperties, DirtyRect, DocumentId,
+    Epoch as WebRenderEpoch, ExternalScrollId, HitTestFlags, MemoryReport,
+    PipelineId as WebRenderPipelineId, PrimitiveFlags, PropertyBinding, ReferenceFrameKind,
+    ScrollClamping, ScrollLocation, SpaceAndClipInfo, SpatialId, TransformStyle, ZoomFactor,
 };
 
 use crate::gl::RenderTargetInfo;
@@ -92,6 +101,51 @@ impl ConvertPipelineIdFromWebRender for WebRenderPipelineId {
     }
 }
 
+// The colors of the box model highlight of the devtools.
+const HIGHLIGHT_CONTENT_COLOR: ColorF = ColorF::new(0.44, 0.66, 0.86, 0.66);
+const HIGHLIGHT_PADDING_COLOR: ColorF = ColorF::new(0.58, 0.77, 0.49, 0.55);
+const HIGHLIGHT_BORDER_COLOR: ColorF = ColorF::new(1.0, 0.9, 0.6, 0.66);
+const HIGHLIGHT_MARGIN_COLOR: ColorF = ColorF::new(0.96, 0.7, 0.42, 0.66);
+const HIGHLIGHT_GUIDE_COLOR: ColorF = ColorF::new(0.8, 0.2, 0.4, 0.8);
+
+/// Paint the area of `outer` around `inner` with four rectangles.
+fn push_ring(
+    builder: &mut webrender_api::DisplayListBuilder,
+    common: &CommonItemProperties,
+    outer: LayoutRect,
+    inner: LayoutRect,
+    color: ColorF,
+) {
+    // Negative margins, or paddings larger than their box, put the inner box out of the
+    // outer one.
+    let inner = outer
+        .intersection(&inner)
+        .unwrap_or_else(|| LayoutRect::new(outer.origin, LayoutSize::zero()));
+    let strips = [
+        LayoutRect::new(
+            outer.origin,
+            LayoutSize::new(outer.size.width, inner.min_y() - outer.min_y()),
+        ),
+        LayoutRect::new(
+            LayoutPoint::new(outer.min_x(), inner.max_y()),
+            LayoutSize::new(outer.size.width, outer.max_y() - inner.max_y()),
+        ),
+        LayoutRect::new(
+            LayoutPoint::new(outer.min_x(), inner.min_y()),
+            LayoutSize::new(inner.min_x() - outer.min_x(), inner.size.height),
+        ),
+        LayoutRect::new(
+            LayoutPoint::new(inner.max_x(), inner.min_y()),
+            LayoutSize::new(outer.max_x() - inner.max_x(), inner.size.height),
+        ),
+    ];
+    for strip in strips.iter() {
+        if !strip.is_empty() {
+            builder.push_rect(common, *strip, color);
+        }
+    }
+}
+
 /// Holds the state when running reftests that determines when it is
 /// safe to save the output image.
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -138,6 +192,10 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Tracks details about each active pipeline that the compositor knows about.
     pipeline_details: HashMap<PipelineId, PipelineDetails>,
 
+    /// The box model of an element highlighted by the devtools, and the pipeline of its
+    /// document.
+    box_model_highlight: Option<(PipelineId, BoxModelHighlight)>,
+
     /// The scene scale, to allow for zooming and high-resolution painting.
     scale: Scale<f32, LayerPixel, DevicePixel>,
 
@@ -151,6 +209,10 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// "Desktop-style" zoom that resizes the viewport to fit the window.
     page_zoom: Scale<f32, CSSPixel, DeviceIndependentPixel>,
 
+    /// The scale the content is rendered at while an element is captured at a scale of its
+    /// own, during which the window is not composited.
+    capture_scale: Option<Scale<f32, CSSPixel, DevicePixel>>,
+
     /// The type of composition to perform
     composite_target: CompositeTarget,
 
@@ -176,6 +238,12 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// The channel on which messages can be sent to the time profiler.
     time_profiler_chan: profile_time::ProfilerChan,
 
+    /// The channel on which messages can be sent to the memory profiler.
+    mem_profiler_chan: profile_mem::ProfilerChan,
+
+    /// How much GPU memory WebRender may use, and what keeping to it took.
+    gpu_memory_budget: GpuMemoryBudget,
+
     /// Touch input state machine
     touch_handler: TouchHandler,
 
@@ -220,6 +288,9 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
     /// Current mouse cursor.
     cursor: Cursor,
 
+    /// Current image cursor, which is shown instead of `cursor` when there is one.
+    custom_cursor: Option<CustomCursor>,
+
     /// Current cursor position.
     cursor_pos: DevicePoint,
 
@@ -251,6 +322,66 @@ pub struct IOCompositor<Window: WindowMethods + ?Sized> {
 
     /// Waiting for external code to call present.
     waiting_on_present: bool,
+
+    /// Whether the rendering surface was recreated since the last frame presented, so that
+    /// failing to present again doesn't make it recreated in a loop.
+    surface_reinitialized: bool,
+}
+
+/// Huge pages would make WebRender keep more textures around, such as the tiles of the
+/// parts of the page that were scrolled away, than the GPU has memory for. When it uses
+/// more than the budget after a frame, it is asked to free what the current frame, that
+/// is the viewport and the margin WebRender prepares around it, does not need.
+struct GpuMemoryBudget {
+    /// The budget in bytes, or zero for no limit.
+    limit: usize,
+    /// The memory used when WebRender was last asked to free some, until the next frame.
+    usage_before_eviction: Option<usize>,
+    /// The memory used after WebRender last freed some. The current frame may need more
+    /// than the budget, in which case asking again before it uses more would only make
+    /// WebRender rasterize the same content again every frame.
+    usage_after_eviction: usize,
+    /// How many times WebRender was asked to free memory.
+    evictions: usize,
+    /// How much memory that freed in total, in bytes.
+    evicted: usize,
+}
+
+impl GpuMemoryBudget {
+    fn new(limit_mb: i64) -> Self {
+        GpuMemoryBudget {
+            limit: limit_mb.max(0) as usize * 1024 * 1024,
+            usage_before_eviction: None,
+            usage_after_eviction: 0,
+            evictions: 0,
+            evicted: 0,
+        }
+    }
+
+    /// Record the memory used after a frame, and return whether WebRender should free some.
+    fn frame_rendered(&mut self, usage: usize) -> bool {
+        if let Some(usage_before_eviction) = self.usage_before_eviction.take() {
+            self.evicted += usage_before_eviction.saturating_sub(usage);
+            self.usage_after_eviction = usage;
+        }
+        if self.limit == 0 || usage <= self.limit || usage <= self.usage_after_eviction {
+            return false;
+        }
+        self.evictions += 1;
+        self.usage_before_eviction = Some(usage);
+        true
+    }
+}
+
+/// The GPU memory used by WebRender, in bytes.
+fn gpu_memory_usage(report: &MemoryReport) -> usize {
+    report.gpu_cache_textures +
+        report.vertex_data_textures +
+        report.render_target_textures +
+        report.picture_tile_textures +
+        report.atlas_textures +
+        report.standalone_textures +
+        report.depth_target_textures
 }
 
 #[derive(Clone, Copy)]
@@ -306,6 +437,9 @@ struct PipelineDetails {
     /// information to the full information necessary for Servo.
     hit_test_items: Vec<HitTestInfo>,
 
+    /// The image cursors that `hit_test_items` refer to.
+    custom_cursors: Vec<CustomCursor>,
+
     /// The compositor-side [ScrollTree]. This is used to allow finding and scrolling
     /// nodes in the compositor before forwarding new offsets to WebRender.
     scroll_tree: ScrollTree,
@@ -320,6 +454,7 @@ impl PipelineDetails {
             animation_callbacks_running: false,
             visible: true,
             hit_test_items: Vec::new(),
+            custom_cursors: Vec::new(),
             scroll_tree: ScrollTree::default(),
         }
     }
@@ -383,6 +518,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 id: None,
             },
             pipeline_details: HashMap::new(),
+            box_model_highlight: None,
             scale: Scale::new(1.0),
             composition_request: CompositionRequest::NoCompositingNecessary,
             touch_handler: TouchHandler::new(),
@@ -391,6 +527,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             composite_target,
             shutdown_state: ShutdownState::NotShuttingDown,
             page_zoom: Scale::new(1.0),
+            capture_scale: None,
             viewport_zoom: PinchZoomFactor::new(1.0),
             min_viewport_zoom: Some(PinchZoomFactor::new(1.0)),
             max_viewport_zoom: None,
@@ -399,6 +536,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             frame_tree_id: FrameTreeId(0),
             constellation_chan: state.constellation_chan,
             time_profiler_chan: state.time_profiler_chan,
+            mem_profiler_chan: state.mem_profiler_chan,
+            gpu_memory_budget: GpuMemoryBudget::new(servo_config::pref!(gfx.gpu_memory.budget_mb)),
             ready_to_save_state: ReadyState::Unknown,
             webrender: state.webrender,
             webrender_document: state.webrender_document,
@@ -408,6 +547,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             webxr_main_thread: state.webxr_main_thread,
             pending_paint_metrics: HashMap::new(),
             cursor: Cursor::None,
+            custom_cursor: None,
             cursor_pos: DevicePoint::new(0.0, 0.0),
             next_offscreen_framebuffer: OnceCell::new(),
             prev_offscreen_framebuffer: None,
@@ -417,6 +557,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             convert_mouse_to_touch,
             waiting_on_pending_frame: false,
             waiting_on_present: false,
+            surface_reinitialized: false,
         }
     }
 
@@ -429,6 +570,22 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         convert_mouse_to_touch: bool,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) -> Self {
+        // Measuring the memory used by WebRender takes the renderer, which is only
+        // available on the compositor thread.
+        let (reporter_sender, reporter_receiver) = ipc::channel().unwrap();
+        let compositor_proxy = state.sender.clone();
+        ROUTER.add_route(
+            reporter_receiver.to_opaque(),
+            Box::new(move |message| {
+                let request: ReporterRequest = message.to().unwrap();
+                compositor_proxy.send(CompositorMsg::CollectMemoryReport(request.reports_channel));
+            }),
+        );
+        state.mem_profiler_chan.send(ProfilerMsg::RegisterReporter(
+            "compositor".to_owned(),
+            Reporter(reporter_sender),
+        ));
+
         let mut compositor = IOCompositor::new(
             window,
             state,
@@ -449,6 +606,8 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     pub fn deinit(self) {
+        self.mem_profiler_chan
+            .send(ProfilerMsg::UnregisterReporter("compositor".to_owned()));
         if let Err(err) = self.rendering_context.make_gl_context_current() {
             warn!("Failed to make GL context current: {:?}", err);
         }
@@ -457,12 +616,26 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
 
     fn update_cursor(&mut self, result: CompositorHitTestResult) {
         let cursor = match result.cursor {
-            Some(cursor) if cursor != self.cursor => cursor,
-            _ => return,
+            Some(cursor) => cursor,
+            None => return,
         };
+        let custom_cursor = result.custom_cursor.and_then(|index| {
+            self.pipeline_details
+                .get(&result.pipeline_id)?
+                .custom_cursors
+                .get(index)
+                .cloned()
+        });
+        if cursor == self.cursor && custom_cursor == self.custom_cursor {
+            return;
+        }
 
         self.cursor = cursor;
-        let msg = ConstellationMsg::SetCursor(cursor);
+        self.custom_cursor = custom_cursor.clone();
+        let msg = match custom_cursor {
+            Some(custom_cursor) => ConstellationMsg::SetCustomCursor(custom_cursor, cursor),
+            None => ConstellationMsg::SetCursor(cursor),
+        };
         if let Err(e) = self.constellation_chan.send(msg) {
             warn!("Sending event to constellation failed ({:?}).", e);
         }
@@ -526,6 +699,40 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         {
             warn!("Binding native surface to context failed ({:?})", e);
         }
+        self.resubmit_scene();
+    }
+
+    /// The rendering surface was lost, as when the driver resets the device: recreate it
+    /// and paint the whole scene again, so that the webviews don't stay blank.
+    fn reinitialize_surface(&mut self) {
+        // The embedder unbinds the surface while it has none to render to, and replaces it
+        // once it does.
+        if self.surface_reinitialized ||
+            !matches!(self.rendering_context.context_surface_info(), Ok(Some(_)))
+        {
+            return;
+        }
+        self.surface_reinitialized = true;
+
+        warn!("Reinitializing the rendering surface in compositor");
+        let size = self.embedder_coordinates.framebuffer.to_untyped();
+        if let Err(e) = self.rendering_context.resize(size) {
+            warn!("Recreating the rendering surface failed ({:?})", e);
+        }
+        self.next_offscreen_framebuffer = OnceCell::new();
+        self.invalidate_prev_offscreen_framebuffer = true;
+        self.resubmit_scene();
+    }
+
+    /// Make WebRender render the whole scene again, even though it did not change.
+    fn resubmit_scene(&mut self) {
+        let mut txn = Transaction::new();
+        self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+        txn.invalidate_rendered_frame();
+        txn.generate_frame(0);
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+        self.composite_if_necessary(CompositingReason::SurfaceReinitialized);
     }
 
     fn handle_browser_message(&mut self, msg: CompositorMsg) -> bool {
@@ -572,6 +779,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (CompositorMsg::SetCaptureScale(scale), ShutdownState::NotShuttingDown) => {
+                self.set_capture_scale(scale.map(Scale::new));
+            },
+
             (CompositorMsg::IsReadyToSaveImageReply(is_ready), ShutdownState::NotShuttingDown) => {
                 assert_eq!(
                     self.ready_to_save_state,
@@ -680,6 +891,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 }
             },
 
+            (CompositorMsg::CollectMemoryReport(reports_chan), ShutdownState::NotShuttingDown) => {
+                self.collect_memory_report(reports_chan);
+            },
+
             (CompositorMsg::Forwarded(msg), ShutdownState::NotShuttingDown) => {
                 self.handle_webrender_message(msg);
             },
@@ -693,6 +908,31 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         true
     }
 
+    /// Report the GPU memory used by WebRender, and what keeping to the budget took.
+    fn collect_memory_report(&self, reports_chan: ReportsChan) {
+        let report = self.webrender.report_memory(ptr::null_mut());
+        let gpu_report = |name: &str, size: usize| Report {
+            path: path!["webrender", "gpu", name],
+            kind: ReportKind::NonExplicitSize,
+            size,
+        };
+        let budget = &self.gpu_memory_budget;
+        let reports = vec![
+            gpu_report("gpu-cache", report.gpu_cache_textures),
+            gpu_report("vertex-data", report.vertex_data_textures),
+            gpu_report("render-targets", report.render_target_textures),
+            gpu_report("picture-tiles", report.picture_tile_textures),
+            gpu_report("texture-atlases", report.atlas_textures),
+            gpu_report("standalone-textures", report.standalone_textures),
+            gpu_report("depth-targets", report.depth_target_textures),
+            gpu_report(
+                &format!("evicted ({} evictions)", budget.evictions),
+                budget.evicted,
+            ),
+        ];
+        reports_chan.send(reports);
+    }
+
     /// Accept messages from content processes that need to be relayed to the WebRender
     /// instance in the parent process.
     fn handle_webrender_message(&mut self, msg: ForwardedToCompositorMsg) {
@@ -719,7 +959,23 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             ) => {
                 self.waiting_for_results_of_scroll = true;
 
+                // Keep track of the offset, so that a highlight follows the scrolled content.
+                let pipeline_id = PipelineId::from_webrender(scroll_id.1);
+                if let Some(details) = self.pipeline_details.get_mut(&pipeline_id) {
+                    let node = details
+                        .scroll_tree
+                        .nodes
+                        .iter_mut()
+                        .find(|node| node.external_id() == Some(scroll_id));
+                    if let Some(node) = node {
+                        node.set_offset(LayoutVector2D::new(-point.x, -point.y));
+                    }
+                }
+
                 let mut txn = Transaction::new();
+                if self.highlights_pipeline(pipeline_id) {
+                    self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                }
                 txn.scroll_node_with_id(point, scroll_id, ScrollClamping::NoClamping);
                 txn.generate_frame(0);
                 self.webrender_api
@@ -744,6 +1000,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 let details = self.pipeline_details(PipelineId::from_webrender(pipeline_id));
                 details.most_recent_display_list_epoch = Some(display_list_info.epoch);
                 details.hit_test_items = display_list_info.hit_test_info;
+                details.custom_cursors = display_list_info.custom_cursors;
                 details.install_new_scroll_tree(display_list_info.scroll_tree);
 
                 let mut txn = Transaction::new();
@@ -793,6 +1050,17 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                 let _ = sender.send(self.webrender_api.generate_image_key());
             },
 
+            ForwardedToCompositorMsg::Layout(
+                script_traits::ScriptToCompositorMsg::HighlightBoxModel(pipeline_id, highlight),
+            ) => {
+                self.box_model_highlight = highlight.map(|highlight| (pipeline_id, highlight));
+                let mut txn = Transaction::new();
+                self.set_root_content_pipeline_handling_pinch_zoom(&mut txn);
+                txn.generate_frame(0);
+                self.webrender_api
+                    .send_transaction(self.webrender_document, txn);
+            },
+
             ForwardedToCompositorMsg::Layout(
                 script_traits::ScriptToCompositorMsg::UpdateImages(updates),
             ) => {
@@ -935,10 +1203,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
     }
 
-    /// Set the root pipeline for our WebRender scene. If there is no pinch zoom applied,
-    /// the root pipeline is the root content pipeline. If there is pinch zoom, the root
-    /// content pipeline is wrapped in a display list that applies a pinch zoom
-    /// transformation to it.
+    /// Set the root pipeline for our WebRender scene. If there is no pinch zoom applied
+    /// and no box model highlighted, the root pipeline is the root content pipeline.
+    /// Otherwise, the root content pipeline is wrapped in a display list that applies a
+    /// pinch zoom transformation to it, and paints the highlight above it.
     fn set_root_content_pipeline_handling_pinch_zoom(&self, transaction: &mut Transaction) {
         let root_content_pipeline = match self.root_content_pipeline.id {
             Some(id) => id.to_webrender(),
@@ -946,7 +1214,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         };
 
         let zoom_factor = self.pinch_zoom_level();
-        if zoom_factor == 1.0 {
+        if zoom_factor == 1.0 && self.box_model_highlight.is_none() {
             transaction.set_root_pipeline(root_content_pipeline);
             return;
         }
@@ -984,6 +1252,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             root_content_pipeline,
             true,
         );
+        self.push_box_model_highlight(
+            &mut builder,
+            &SpaceAndClipInfo {
+                spatial_id: zoom_reference_frame,
+                clip_id: ClipId::root(root_pipeline),
+            },
+            viewport_rect,
+        );
         let built_display_list = builder.finalize();
 
         // NB: We are always passing 0 as the epoch here, but this doesn't seem to
@@ -998,6 +1274,86 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         );
     }
 
+    /// Whether the box model highlight is painted above the given pipeline, which is only
+    /// done for the root content pipeline.
+    fn highlights_pipeline(&self, pipeline_id: PipelineId) -> bool {
+        match self.box_model_highlight {
+            Some((highlighted_pipeline_id, _)) => {
+                highlighted_pipeline_id == pipeline_id &&
+                    self.root_content_pipeline.id == Some(pipeline_id)
+            },
+            None => false,
+        }
+    }
+
+    /// Paint the box model highlighted by the devtools where its document is scrolled to,
+    /// with guides along its border box across the viewport. It is painted in the zoomed
+    /// space of the content, so it stays over the element at any pinch zoom level, and the
+    /// guides are kept one pixel wide.
+    fn push_box_model_highlight(
+        &self,
+        builder: &mut webrender_api::DisplayListBuilder,
+        space_and_clip: &SpaceAndClipInfo,
+        viewport_rect: LayoutRect,
+    ) {
+        let (pipeline_id, highlight) = match self.box_model_highlight {
+            Some((pipeline_id, ref highlight)) if self.highlights_pipeline(pipeline_id) => {
+                (pipeline_id, highlight)
+            },
+            _ => return,
+        };
+        let scroll_offset = self
+            .pipeline_details
+            .get(&pipeline_id)
+            .and_then(|details| {
+                details
+                    .scroll_tree
+                    .nodes
+                    .iter()
+                    .find(|node| node.external_id() == Some(pipeline_id.root_scroll_id()))
+            })
+            .and_then(|node| node.offset())
+            .unwrap_or_else(LayoutVector2D::zero);
+
+        let common = CommonItemProperties {
+            clip_rect: viewport_rect,
+            spatial_id: space_and_clip.spatial_id,
+            clip_id: space_and_clip.clip_id,
+            flags: PrimitiveFlags::default(),
+        };
+        let content_box = highlight.content_box.translate(scroll_offset);
+        let padding_box = highlight.padding_box.translate(scroll_offset);
+        let border_box = highlight.border_box.translate(scroll_offset);
+        let margin_box = highlight.margin_box.translate(scroll_offset);
+        let rings = [
+            (margin_box, border_box, HIGHLIGHT_MARGIN_COLOR),
+            (border_box, padding_box, HIGHLIGHT_BORDER_COLOR),
+            (padding_box, content_box, HIGHLIGHT_PADDING_COLOR),
+        ];
+        for &(outer, inner, color) in rings.iter() {
+            push_ring(builder, &common, outer, inner, color);
+        }
+        if !content_box.is_empty() {
+            builder.push_rect(&common, content_box, HIGHLIGHT_CONTENT_COLOR);
+        }
+
+        let guide_width = 1. / self.pinch_zoom_level();
+        for &y in [border_box.min_y(), border_box.max_y() - guide_width].iter() {
+            let guide = LayoutRect::new(
+                LayoutPoint::new(viewport_rect.min_x(), y),
+                LayoutSize::new(viewport_rect.size.width, guide_width),
+            );
+            builder.push_rect(&common, guide, HIGHLIGHT_GUIDE_COLOR);
+        }
+        for &x in [border_box.min_x(), border_box.max_x() - guide_width].iter() {
+            let guide = LayoutRect::new(
+                LayoutPoint::new(x, viewport_rect.min_y()),
+                LayoutSize::new(guide_width, viewport_rect.size.height),
+            );
+            builder.push_rect(&common, guide, HIGHLIGHT_GUIDE_COLOR);
+        }
+    }
+
     fn set_frame_tree(&mut self, frame_tree: &SendableFrameTree) {
         debug!(
             "Setting the frame tree for pipeline {:?}",
@@ -1217,6 +1573,7 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                     point_relative_to_item: item.point_relative_to_item.to_untyped(),
                     node: UntrustedNodeAddress(info.node as *const c_void),
                     cursor: info.cursor,
+                    custom_cursor: info.custom_cursor,
                     scroll_tree_node: info.scroll_tree_node,
                 })
             })
@@ -1352,6 +1709,20 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         self.send_wheel_event(delta, p);
     }
 
+    /// Send a step of a drag from another application to the pipeline under the pointer.
+    ///
+    /// TODO: Drags that move between the documents of iframes do not leave the document they
+    /// were over.
+    pub fn on_drag_event(&mut self, event_type: DragEventType, point: DevicePoint) {
+        if let Some(result) = self.hit_test_at_device_point(point) {
+            let event = DragEvent(event_type, result.point_in_viewport, Some(result.node));
+            let msg = ConstellationMsg::ForwardEvent(result.pipeline_id, event);
+            if let Err(e) = self.constellation_chan.send(msg) {
+                warn!("Sending event to constellation failed ({:?}).", e);
+            }
+        }
+    }
+
     pub fn on_scroll_event(
         &mut self,
         scroll_location: ScrollLocation,
@@ -1440,8 +1811,12 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             return;
         }
 
+        let highlight_scrolled = match scroll_result {
+            Some((pipeline_id, ..)) => self.highlights_pipeline(pipeline_id),
+            None => false,
+        };
         let mut transaction = Transaction::new();
-        if zoom_changed {
+        if zoom_changed || highlight_scrolled {
             self.set_root_content_pipeline_handling_pinch_zoom(&mut transaction);
         }
 
@@ -1579,6 +1954,29 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
             .send_transaction(self.webrender_document, txn);
     }
 
+    /// Render the content at `scale` rather than at the device pixel ratio of the window,
+    /// without telling layout, or go back to the device pixel ratio. The images composited
+    /// meanwhile wait for a frame rendered at the new scale.
+    fn set_capture_scale(&mut self, scale: Option<Scale<f32, CSSPixel, DevicePixel>>) {
+        if self.capture_scale == scale {
+            return;
+        }
+        self.capture_scale = scale;
+
+        // WebRender multiplies the page zoom by the device pixel ratio of the document.
+        let page_zoom = match scale {
+            Some(scale) => scale.get() / self.embedder_coordinates.hidpi_factor.get(),
+            None => self.page_zoom.get(),
+        };
+        let mut txn = webrender::Transaction::new();
+        txn.set_page_zoom(ZoomFactor::new(page_zoom));
+        txn.generate_frame(0);
+        self.webrender_api
+            .send_transaction(self.webrender_document, txn);
+        self.waiting_on_pending_frame = true;
+        self.ready_to_save_state = ReadyState::Unknown;
+    }
+
     /// Simulate a pinch zoom
     pub fn on_pinch_zoom_window_event(&mut self, magnification: f32) {
         // TODO: Scroll to keep the center in view?
@@ -1684,6 +2082,10 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     pub fn composite(&mut self) {
+        // The frames rendered at the scale of a capture are not meant to be shown.
+        if self.capture_scale.is_some() {
+            return;
+        }
         match self.composite_specific_target(self.composite_target.clone(), None) {
             Ok(_) => {
                 if matches!(self.composite_target, CompositeTarget::PngFile(_)) ||
@@ -1788,12 +2190,19 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
                     DeviceIntSize::from_untyped(self.embedder_coordinates.framebuffer.to_untyped());
 
                 // Paint the scene.
-                // TODO(gw): Take notice of any errors the renderer returns!
                 self.clear_background();
-                self.webrender.render(size, 0 /* buffer_age */).ok();
+                if let Err(errors) = self.webrender.render(size, 0 /* buffer_age */) {
+                    warn!("Failed to render the scene ({:?})", errors);
+                }
             },
         );
 
+        let usage = gpu_memory_usage(&self.webrender.report_memory(ptr::null_mut()));
+        if self.gpu_memory_budget.frame_rendered(usage) {
+            debug!("WebRender uses {} bytes of GPU memory, over budget", usage);
+            self.webrender_api.notify_memory_pressure();
+        }
+
         // If there are pending paint metrics, we check if any of the painted epochs is one of the
         // ones that the paint metrics recorder is expecting. In that case, we get the current
         // time, inform layout about it and remove the pending metric from the list.
@@ -1834,12 +2243,21 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
         }
 
         let (x, y, width, height) = if let Some(rect) = rect {
-            let rect = self.device_pixels_per_page_px().transform_rect(&rect);
-
-            let x = rect.origin.x as i32;
+            let scale = self
+                .capture_scale
+                .unwrap_or_else(|| self.device_pixels_per_page_px());
+            // Only the part of the rect inside the framebuffer can be read back.
+            let rect = scale
+                .transform_rect(&rect)
+                .round()
+                .to_i32()
+                .intersection(&Rect::from_size(size.to_i32()))
+                .unwrap_or_else(Rect::zero);
+
+            let x = rect.origin.x;
             // We need to convert to the bottom-left origin coordinate
             // system used by OpenGL
-            let y = (size.height as f32 - rect.origin.y - rect.size.height) as i32;
+            let y = size.height as i32 - rect.max_y();
             let w = rect.size.width as u32;
             let h = rect.size.height as u32;
 
@@ -1944,10 +2362,14 @@ impl<Window: WindowMethods + ?Sized> IOCompositor<Window> {
     }
 
     pub fn present(&mut self) {
-        if let Err(err) = self.rendering_context.present() {
-            warn!("Failed to present surface: {:?}", err);
-        }
         self.waiting_on_present = false;
+        match self.rendering_context.present() {
+            Ok(()) => self.surface_reinitialized = false,
+            Err(err) => {
+                warn!("Failed to present surface: {:?}", err);
+                self.reinitialize_surface();
+            },
+        }
     }
 
     fn composite_if_necessary(&mut self, reason: CompositingReason) {
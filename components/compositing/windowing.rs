@@ -5,19 +5,26 @@
 //! Abstract windowing methods. The concrete implementations of these can be found in `platform/`.
 
 use std::fmt::{Debug, Error, Formatter};
+use std::path::PathBuf;
 use std::time::Duration;
 
-use embedder_traits::{EmbedderProxy, EventLoopWaker};
+use embedder_traits::{
+    ElementExportRequest, EmbedderProxy, EventLoopWaker, NetworkChange, PermissionName,
+    PermissionState, PrerenderRequest, ScriptPolicy, SpeechSynthesizer, UserAgentOverride,
+};
 use euclid::Scale;
 use gfx::rendering_context::RenderingContext;
 use keyboard_types::KeyboardEvent;
 use libc::c_void;
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId, TraversalDirection};
+use script_traits::content_script::{ContentScript, ContentScriptId};
+use script_traits::extension::{Extension, ExtensionId};
 use script_traits::{
-    GamepadEvent, MediaSessionActionType, MouseButton, TouchEventType, TouchId, WheelDelta,
+    DragEventType, GamepadEvent, MediaSessionActionType, MouseButton, TouchEventType, TouchId,
+    UserStylesheet, UserStylesheetId, WheelDelta,
 };
 use servo_geometry::DeviceIndependentPixel;
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 use style_traits::DevicePixel;
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize, DevicePoint};
 use webrender_api::ScrollLocation;
@@ -64,6 +71,9 @@ pub enum EmbedderEvent {
     Touch(TouchEventType, TouchId, DevicePoint),
     /// Sent when user moves the mouse wheel.
     Wheel(WheelDelta, DevicePoint),
+    /// Sent when a drag from another application enters, moves over, leaves or is dropped
+    /// on the webview, at the point where the pointer is.
+    Drag(DragEventType, DevicePoint),
     /// Sent when the user scrolls. The first point is the delta and the second point is the
     /// origin.
     Scroll(ScrollLocation, DeviceIntPoint, TouchEventType),
@@ -117,6 +127,67 @@ pub enum EmbedderEvent {
     ReplaceNativeSurface(*mut c_void, DeviceIntSize),
     /// Sent when new Gamepad information is available.
     Gamepad(GamepadEvent),
+    /// Apply a user-origin stylesheet to the given webview, or to all webviews if `None`.
+    /// The stylesheet applies to documents that are already loaded as well as future ones.
+    AddUserStylesheet(Option<TopLevelBrowsingContextId>, UserStylesheet),
+    /// Stop applying a previously added user stylesheet.
+    RemoveUserStylesheet(UserStylesheetId),
+    /// Inject a content script into the documents it matches in the given webview, or in all
+    /// webviews if `None`. Only documents created after the registration are affected.
+    RegisterContentScript(Option<TopLevelBrowsingContextId>, ContentScript),
+    /// Stop injecting a previously registered content script into new documents.
+    UnregisterContentScript(ContentScriptId),
+    /// Load an extension: start its background script in a hidden background page and
+    /// inject its content scripts into the documents created from now on.
+    LoadExtension(Extension),
+    /// Unload a previously loaded extension.
+    UnloadExtension(ExtensionId),
+    /// The system is running low on memory, or the application was sent to the background:
+    /// release as much memory as possible.
+    MemoryPressure,
+    /// Start or stop sending `EmbedderMsg::WebResourceRequested` for every network request,
+    /// letting the embedder answer, redirect or block it before it is sent.
+    InterceptRequests(bool),
+    /// Start recording the network activity of the session, including the response bodies
+    /// if the flag is set.
+    StartHarRecording(bool),
+    /// Stop recording the network activity, and save what was recorded to the given file,
+    /// in the HAR format.
+    StopHarRecording(PathBuf),
+    /// Send the state of the HTTP connection pool with `EmbedderMsg::ReportConnectionPool`.
+    ReportConnectionPool,
+    /// Send the state of the content processes with `EmbedderMsg::ReportContentProcesses`.
+    ReportContentProcesses,
+    /// Send a snapshot of the frame tree with `EmbedderMsg::ReportFrameTree`.
+    ReportFrameTree,
+    /// The device lost or regained connectivity, or switched networks: close the open
+    /// connections instead of reusing them, and let the pages know.
+    NetworkChanged(NetworkChange),
+    /// Start or stop working offline: answer requests from the HTTP cache only, and let
+    /// the pages know they are offline.
+    SetWorkOffline(bool),
+    /// The system entered or left a forced colors mode, like a high contrast theme: paint
+    /// the pages with its limited palette instead of their own colors.
+    SetForcedColors(bool),
+    /// Tell the pages of a webview what the override says about the user agent, in the
+    /// `User-Agent` and `Sec-CH-UA` headers and `navigator.userAgentData`, instead of the
+    /// defaults, or go back to the defaults.
+    SetUserAgentOverride(TopLevelBrowsingContextId, Option<UserAgentOverride>),
+    /// Run the script of the documents and workers of an origin as the policy says, from
+    /// the next realm made for the origin on.
+    SetScriptPolicy(ImmutableOrigin, ScriptPolicy),
+    /// Grant or deny a permission to the origin of the url, from now on and in later
+    /// sessions, or forget what was decided with `PermissionState::Prompt`. The
+    /// `PermissionStatus` objects of the origin see the change.
+    SetPermission(ServoUrl, PermissionName, PermissionState),
+    /// Render an element of the webview into a PNG image or a PDF document, sent back with
+    /// `EmbedderMsg::ElementExported`. The webview must be the one shown, as the element is
+    /// rendered as it is painted.
+    ExportElement(TopLevelBrowsingContextId, ElementExportRequest),
+    /// Load a page in the webview and, once it is quiescent, serialize its DOM or take a
+    /// screenshot of it, sent back with `EmbedderMsg::Prerendered`. A screenshot can only be
+    /// taken of the webview shown.
+    Prerender(TopLevelBrowsingContextId, PrerenderRequest),
 }
 
 impl Debug for EmbedderEvent {
@@ -132,6 +203,7 @@ impl Debug for EmbedderEvent {
             EmbedderEvent::MouseWindowMoveEventClass(..) => write!(f, "MouseMove"),
             EmbedderEvent::Touch(..) => write!(f, "Touch"),
             EmbedderEvent::Wheel(..) => write!(f, "Wheel"),
+            EmbedderEvent::Drag(..) => write!(f, "Drag"),
             EmbedderEvent::Scroll(..) => write!(f, "Scroll"),
             EmbedderEvent::Zoom(..) => write!(f, "Zoom"),
             EmbedderEvent::PinchZoom(..) => write!(f, "PinchZoom"),
@@ -154,6 +226,27 @@ impl Debug for EmbedderEvent {
             EmbedderEvent::InvalidateNativeSurface => write!(f, "InvalidateNativeSurface"),
             EmbedderEvent::ReplaceNativeSurface(..) => write!(f, "ReplaceNativeSurface"),
             EmbedderEvent::Gamepad(..) => write!(f, "Gamepad"),
+            EmbedderEvent::AddUserStylesheet(..) => write!(f, "AddUserStylesheet"),
+            EmbedderEvent::RemoveUserStylesheet(..) => write!(f, "RemoveUserStylesheet"),
+            EmbedderEvent::RegisterContentScript(..) => write!(f, "RegisterContentScript"),
+            EmbedderEvent::UnregisterContentScript(..) => write!(f, "UnregisterContentScript"),
+            EmbedderEvent::LoadExtension(..) => write!(f, "LoadExtension"),
+            EmbedderEvent::UnloadExtension(..) => write!(f, "UnloadExtension"),
+            EmbedderEvent::MemoryPressure => write!(f, "MemoryPressure"),
+            EmbedderEvent::InterceptRequests(..) => write!(f, "InterceptRequests"),
+            EmbedderEvent::StartHarRecording(..) => write!(f, "StartHarRecording"),
+            EmbedderEvent::StopHarRecording(..) => write!(f, "StopHarRecording"),
+            EmbedderEvent::ReportConnectionPool => write!(f, "ReportConnectionPool"),
+            EmbedderEvent::ReportContentProcesses => write!(f, "ReportContentProcesses"),
+            EmbedderEvent::ReportFrameTree => write!(f, "ReportFrameTree"),
+            EmbedderEvent::NetworkChanged(..) => write!(f, "NetworkChanged"),
+            EmbedderEvent::SetWorkOffline(..) => write!(f, "SetWorkOffline"),
+            EmbedderEvent::SetForcedColors(..) => write!(f, "SetForcedColors"),
+            EmbedderEvent::SetUserAgentOverride(..) => write!(f, "SetUserAgentOverride"),
+            EmbedderEvent::SetScriptPolicy(..) => write!(f, "SetScriptPolicy"),
+            EmbedderEvent::SetPermission(..) => write!(f, "SetPermission"),
+            EmbedderEvent::ExportElement(..) => write!(f, "ExportElement"),
+            EmbedderEvent::Prerender(..) => write!(f, "Prerender"),
         }
     }
 }
@@ -190,6 +283,12 @@ pub trait EmbedderMethods {
     fn get_user_agent_string(&self) -> Option<String> {
         None
     }
+
+    /// Returns the text-to-speech engine that web content speaks with, if the platform
+    /// has one.
+    fn speech_synthesizer(&mut self) -> Option<Box<dyn SpeechSynthesizer>> {
+        None
+    }
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -106,9 +106,25 @@ pub struct Opts {
     /// True to exit after the page load (`-x`).
     pub exit_after_load: bool,
 
+    /// Where to write the markup of the page once it is quiescent, before exiting, when
+    /// prerendering it in headless mode (`--prerender`).
+    pub prerender_output: Option<String>,
+
+    /// Where to write a screenshot of the page once it is quiescent, before exiting, when
+    /// prerendering it in headless mode (`--prerender-screenshot`).
+    pub prerender_screenshot: Option<String>,
+
+    /// How many milliseconds no fetch of the page must be in progress for it to be
+    /// quiescent, when prerendering it.
+    pub prerender_network_idle: u64,
+
     /// Load shaders from disk.
     pub shaders_dir: Option<PathBuf>,
 
+    /// Ignore the system fonts and only use those in this directory, so that pages render
+    /// the same on every machine.
+    pub bundled_fonts_dir: Option<PathBuf>,
+
     /// Directory for a default config directory
     pub config_dir: Option<PathBuf>,
 
@@ -421,9 +437,13 @@ pub fn default_opts() -> Opts {
         sandbox: false,
         debug: Default::default(),
         exit_after_load: false,
+        prerender_output: None,
+        prerender_screenshot: None,
+        prerender_network_idle: 500,
         config_dir: None,
         is_printing_version: false,
         shaders_dir: None,
+        bundled_fonts_dir: None,
         certificate_path: None,
         ignore_certificate_errors: false,
         unminify_js: false,
@@ -460,6 +480,27 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         "10",
     );
     opts.optflag("x", "exit", "Exit after load flag");
+    opts.optopt(
+        "",
+        "prerender",
+        "Load the page headless and, once it is quiescent, write its markup to this file \
+         and exit",
+        "page.html",
+    );
+    opts.optopt(
+        "",
+        "prerender-screenshot",
+        "Load the page headless and, once it is quiescent, write a screenshot of it to \
+         this file and exit",
+        "page.png",
+    );
+    opts.optopt(
+        "",
+        "prerender-network-idle",
+        "How many milliseconds no fetch of a prerendered page must be in progress for it \
+         to be quiescent",
+        "500",
+    );
     opts.optopt(
         "y",
         "layout-threads",
@@ -489,6 +530,13 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         "Shaders will be loaded from the specified directory instead of using the builtin ones.",
         "",
     );
+    opts.optopt(
+        "",
+        "bundled-fonts",
+        "Ignore the system fonts and only use the fonts in the specified directory, \
+         for rendering that is the same on every machine.",
+        "/home/servo/fonts",
+    );
     opts.optflag("z", "headless", "Headless mode");
     opts.optflag(
         "f",
@@ -683,6 +731,21 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
                 })
             });
 
+    let prerender_output = opt_match.opt_str("prerender");
+    let prerender_screenshot = opt_match.opt_str("prerender-screenshot");
+    let prerender_network_idle = opt_match
+        .opt_str("prerender-network-idle")
+        .map(|milliseconds| {
+            milliseconds.parse().unwrap_or_else(|err| {
+                args_fail(&format!(
+                    "Error parsing option: --prerender-network-idle ({})",
+                    err
+                ))
+            })
+        })
+        .unwrap_or(500);
+    let prerendering = prerender_output.is_some() || prerender_screenshot.is_some();
+
     if debug_options.trace_layout {
         layout_threads = Some(1);
     }
@@ -764,7 +827,7 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         userscripts: opt_match.opt_default("userscripts", ""),
         user_stylesheets,
         output_file: opt_match.opt_str("o"),
-        headless: opt_match.opt_present("z"),
+        headless: opt_match.opt_present("z") || prerendering,
         hard_fail: opt_match.opt_present("f") && !opt_match.opt_present("F"),
         devtools_port,
         devtools_server_enabled,
@@ -776,9 +839,13 @@ pub fn from_cmdline_args(mut opts: Options, args: &[String]) -> ArgumentParsingR
         random_pipeline_closure_probability,
         random_pipeline_closure_seed,
         exit_after_load: opt_match.opt_present("x"),
+        prerender_output,
+        prerender_screenshot,
+        prerender_network_idle,
         config_dir: opt_match.opt_str("config-dir").map(Into::into),
         is_printing_version,
         shaders_dir: opt_match.opt_str("shaders").map(Into::into),
+        bundled_fonts_dir: opt_match.opt_str("bundled-fonts").map(Into::into),
         certificate_path: opt_match.opt_str("certificate-path"),
         ignore_certificate_errors: opt_match.opt_present("ignore-certificate-errors"),
         unminify_js: opt_match.opt_present("unminify-js"),
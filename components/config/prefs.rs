@@ -165,6 +165,48 @@ mod gen {
         std::cmp::max(num_cpus::get() * 3 / 4, 1) as i64
     }
 
+    // The defaults of hyper, which the HTTP connection pool is made with.
+    fn default_http_pool_idle_timeout() -> i64 {
+        90
+    }
+
+    fn default_http_pool_max_idle_per_host() -> i64 {
+        i64::MAX
+    }
+
+    // The network state is partitioned unless a user turns it off, for instance to tell
+    // whether a site breaks because of it.
+    fn default_network_partitioning_enabled() -> bool {
+        true
+    }
+
+    // The precision Firefox gives its clocks when it resists fingerprinting.
+    fn default_resist_fingerprinting_timer_precision() -> i64 {
+        100
+    }
+
+    // One spare content process is enough to hide how long starting a process takes from
+    // the next cross-site navigation.
+    fn default_process_spare_count() -> i64 {
+        1
+    }
+
+    // Enough for the tiles and caches of a 4K screen full of content, with room to spare.
+    fn default_gpu_memory_budget_mb() -> i64 {
+        512
+    }
+
+    // What a browser profile can reasonably take on disk before the storage of the origins
+    // that were not used for the longest time is cleared.
+    fn default_storage_quota_total_mb() -> i64 {
+        2048
+    }
+
+    // A quarter of the total, so that one origin cannot push all the others out.
+    fn default_storage_quota_origin_mb() -> i64 {
+        512
+    }
+
     fn black() -> i64 {
         0x000000
     }
@@ -207,6 +249,9 @@ mod gen {
                     /// Enable WebGPU APIs.
                     enabled: bool,
                 },
+                async_clipboard: {
+                    enabled: bool,
+                },
                 bluetooth: {
                     enabled: bool,
                     testing: {
@@ -223,6 +268,13 @@ mod gen {
                     #[serde(rename = "dom.compositionevent.enabled")]
                     enabled: bool,
                 },
+                crypto: {
+                    subtle: {
+                        /// Enable the SubtleCrypto interface of the Web Cryptography API.
+                        #[serde(default)]
+                        enabled: bool,
+                    }
+                },
                 custom_elements: {
                     #[serde(rename = "dom.customelements.enabled")]
                     enabled: bool,
@@ -231,9 +283,26 @@ mod gen {
                     dblclick_timeout: i64,
                     dblclick_dist: i64,
                 },
+                file_system: {
+                    /// Enable the origin private file system.
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 forcetouch: {
                     enabled: bool,
                 },
+                forms: {
+                    password: {
+                        /// Whether the text selected in password fields can be copied or cut.
+                        #[serde(default)]
+                        allow_copy: bool,
+                        /// How long the character last typed in a password field is shown
+                        /// before it is masked like the others, in milliseconds, or 0 to mask
+                        /// it right away.
+                        #[serde(default)]
+                        reveal_duration_ms: i64,
+                    },
+                },
                 fullscreen: {
                     test: bool,
                 },
@@ -243,6 +312,10 @@ mod gen {
                 imagebitmap: {
                     enabled: bool,
                 },
+                indexeddb: {
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 microdata: {
                     testing: {
                         enabled: bool,
@@ -257,6 +330,9 @@ mod gen {
                 mutation_observer: {
                     enabled: bool,
                 },
+                netinfo: {
+                    enabled: bool,
+                },
                 offscreen_canvas: {
                     enabled: bool,
                 },
@@ -266,6 +342,12 @@ mod gen {
                         allowed_in_nonsecure_contexts: bool,
                     }
                 },
+                reporting: {
+                    enabled: bool,
+                },
+                resize_observer: {
+                    enabled: bool,
+                },
                 script: {
                     asynch: bool,
                 },
@@ -284,6 +366,10 @@ mod gen {
                 shadowdom: {
                     enabled: bool,
                 },
+                speech_synthesis: {
+                    #[serde(default)]
+                    enabled: bool,
+                },
                 svg: {
                     enabled: bool,
                 },
@@ -337,6 +423,9 @@ mod gen {
                     /// Enable WebGL2 APIs.
                     enabled: bool,
                 },
+                web_animations: {
+                    enabled: bool,
+                },
                 webrtc: {
                     transceiver: {
                         enabled: bool,
@@ -389,6 +478,12 @@ mod gen {
                 },
             },
             gfx: {
+                gpu_memory: {
+                    /// The GPU memory WebRender may use before it is asked to free what
+                    /// the current frame does not need, in megabytes, or 0 for no limit.
+                    #[serde(default = "default_gpu_memory_budget_mb")]
+                    budget_mb: i64,
+                },
                 subpixel_text_antialiasing: {
                     #[serde(rename = "gfx.subpixel-text-antialiasing.enabled")]
                     enabled: bool,
@@ -540,6 +635,16 @@ mod gen {
                 }
             },
             network: {
+                client_hints: {
+                    /// Whether to send the `Sec-CH-UA` headers to secure origins.
+                    enabled: bool,
+                    high_entropy_values: {
+                        /// Whether sites can learn the user-agent client hints that identify
+                        /// the user the most, with `Accept-CH` or with
+                        /// `navigator.userAgentData.getHighEntropyValues()`.
+                        enabled: bool,
+                    },
+                },
                 enforce_tls: {
                     enabled: bool,
                     localhost: bool,
@@ -549,10 +654,58 @@ mod gen {
                     #[serde(rename = "network.http-cache.disabled")]
                     disabled: bool,
                 },
+                http_pool: {
+                    /// How long a connection is kept open while no request uses it, in seconds,
+                    /// or 0 to keep it open until the server closes it.
+                    #[serde(default = "default_http_pool_idle_timeout")]
+                    idle_timeout: i64,
+                    /// The most connections kept open to an origin while no request uses them.
+                    #[serde(default = "default_http_pool_max_idle_per_host")]
+                    max_idle_per_host: i64,
+                },
                 mime: {
                     sniff: bool,
+                },
+                partitioning: {
+                    /// Whether to keep the HTTP cache, the connections, the resolved host
+                    /// names, the HSTS entries and the TLS sessions apart for each top-level
+                    /// site.
+                    #[serde(default = "default_network_partitioning_enabled")]
+                    enabled: bool,
                 }
             },
+            privacy: {
+                resist_fingerprinting: {
+                    /// Whether to hide what sites could tell users apart with: the user agent,
+                    /// the screen size, the installed fonts and the precision of the clocks.
+                    #[serde(default)]
+                    enabled: bool,
+                    canvas_noise: {
+                        /// Whether to slightly alter the pixels sites read back from canvases
+                        /// while resisting fingerprinting.
+                        #[serde(default)]
+                        enabled: bool,
+                    },
+                    /// The precision of `performance.now()`, `Date.now()` and the event
+                    /// timestamps while resisting fingerprinting, in milliseconds.
+                    #[serde(default = "default_resist_fingerprinting_timer_precision")]
+                    timer_precision: i64,
+                }
+            },
+            process: {
+                /// How many content processes are started ahead of time in multiprocess
+                /// mode, so that a navigation to another site does not wait for one to start.
+                #[serde(default = "default_process_spare_count")]
+                spare_count: i64,
+                /// The most event loops running the documents of a site, or 0 for no limit.
+                /// Past it, new browsing context groups share those already running the site.
+                #[serde(default)]
+                max_per_site: i64,
+                /// How long an event loop which runs no document anymore is kept running, in
+                /// seconds, in case another document of its site is loaded.
+                #[serde(default)]
+                idle_timeout: i64,
+            },
             session_history: {
                 #[serde(rename = "session-history.max-length")]
                 max_length: i64,
@@ -581,6 +734,19 @@ mod gen {
                 /// URL string of the search engine page (for example <https://google.com> or and <https://duckduckgo.com>.
                 searchpage: String,
             },
+            storage: {
+                quota: {
+                    /// The most the storage of an origin takes, localStorage, IndexedDB and the
+                    /// origin private file system together, in megabytes.
+                    #[serde(default = "default_storage_quota_origin_mb")]
+                    origin_mb: i64,
+                    /// The most the storage of all origins takes, in megabytes. Past it, the
+                    /// origins that were not used for the longest time and did not ask for
+                    /// their storage to persist are cleared.
+                    #[serde(default = "default_storage_quota_total_mb")]
+                    total_mb: i64,
+                },
+            },
             webgl: {
                 testing: {
                     context_creation_error: bool,
@@ -89,6 +89,7 @@ use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::marker::PhantomData;
 use std::mem::replace;
+use std::path::PathBuf;
 use std::rc::{Rc, Weak};
 use std::sync::{Arc, Mutex};
 use std::{process, thread};
@@ -105,10 +106,14 @@ use compositing_traits::{
 use crossbeam_channel::{after, never, select, unbounded, Receiver, Sender};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, DevtoolsPageInfo, NavigationState,
-    ScriptToDevtoolsControlMsg,
+    NetworkThrottling, ScriptToDevtoolsControlMsg,
 };
 use embedder_traits::{
-    Cursor, EmbedderMsg, EmbedderProxy, MediaSessionEvent, MediaSessionPlaybackState,
+    BrowsingContextSnapshot, ContentProcessReport, CrashReason, CrashReport, Cursor, CustomCursor,
+    ElementExportError, ElementExportRequest, EmbedderMsg, EmbedderProxy, FrameTreeSnapshot,
+    MediaSessionEvent, MediaSessionPlaybackState, NetworkChange, PermissionName, PermissionState,
+    PipelineSnapshot, PipelineSnapshotState, PrerenderError, PrerenderRequest, ScriptPolicies,
+    ScriptPolicy, SiteEventLoops, UserAgentOverride,
 };
 use euclid::default::Size2D as UntypedSize2D;
 use euclid::Size2D;
@@ -127,23 +132,32 @@ use msg::constellation_msg::{
     MessagePortRouterId, PipelineId, PipelineNamespace, PipelineNamespaceId,
     PipelineNamespaceRequest, TopLevelBrowsingContextId, TraversalDirection,
 };
+use net_traits::file_system_thread::FileSystemThreadMsg;
+use net_traits::indexeddb_thread::IndexedDBThreadMsg;
 use net_traits::pub_domains::reg_host;
 use net_traits::request::{Referrer, RequestBuilder};
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads};
+use net_traits::{self, FetchResponseMsg, IpcSend, ResourceThreads, TopLevelDocument};
 use profile_traits::{mem, time};
 use script_layout_interface::{LayoutFactory, ScriptThreadFactory};
+use script_traits::content_script::{ContentScript, ContentScriptId};
+use script_traits::extension::{
+    Extension, ExtensionApiError, ExtensionApiResult, ExtensionId, ExtensionMessage,
+    ExtensionMessageTarget, ExtensionRequest, ExtensionTab, ExtensionTabQuery, STORAGE_PERMISSION,
+    TABS_PERMISSION,
+};
 use script_traits::CompositorEvent::{MouseButtonEvent, MouseMoveEvent};
 use script_traits::{
     webdriver_msg, AnimationState, AnimationTickType, AuxiliaryBrowsingContextLoadInfo,
     BroadcastMsg, CompositorEvent, ConstellationControlMsg, DiscardBrowsingContext,
-    DocumentActivity, DocumentState, GamepadEvent, HistoryEntryReplacement, IFrameLoadInfo,
-    IFrameLoadInfoWithData, IFrameSandboxState, IFrameSizeMsg, Job, LayoutMsg as FromLayoutMsg,
-    LoadData, LoadOrigin, LogEntry, MediaSessionActionType, MessagePortMsg, MouseEventType,
-    PortMessageTask, SWManagerMsg, SWManagerSenders, ScriptMsg as FromScriptMsg,
-    ScriptToConstellationChan, ServiceWorkerManagerFactory, ServiceWorkerMsg,
-    StructuredSerializedData, TimerSchedulerMsg, UpdatePipelineIdReason, WebDriverCommandMsg,
-    WindowSizeData, WindowSizeType,
+    DocumentActivity, DocumentState, GamepadEvent, HistoryEntryReplacement,
+    HistoryEntryScrollState, IFrameLoadInfo, IFrameLoadInfoWithData, IFrameSandboxState,
+    IFrameSizeMsg, Job, JsWorld, LayoutMsg as FromLayoutMsg, LoadData, LoadOrigin, LogEntry,
+    MediaSessionActionType, MessagePortMsg, MouseEventType, PortMessageTask, SWManagerMsg,
+    SWManagerSenders, ScriptMsg as FromScriptMsg, ScriptToConstellationChan,
+    ServiceWorkerManagerFactory, ServiceWorkerMsg, StructuredSerializedData, TimerSchedulerMsg,
+    UpdatePipelineIdReason, UserStylesheet, UserStylesheetId, WebDriverCommandMsg, WindowSizeData,
+    WindowSizeType,
 };
 use serde::{Deserialize, Serialize};
 use servo_config::{opts, pref};
@@ -160,17 +174,23 @@ use crate::browsingcontext::{
     NewBrowsingContextInfo,
 };
 use crate::event_loop::EventLoop;
+use crate::extension::{ExtensionTabIds, LoadedExtension};
 use crate::network_listener::NetworkListener;
 use crate::pipeline::{InitialPipelineState, Pipeline};
+use crate::process_manager::ProcessManager;
 use crate::serviceworker::ServiceWorkerUnprivilegedContent;
 use crate::session_history::{
     JointSessionHistory, NeedsToReload, SessionHistoryChange, SessionHistoryDiff,
 };
 use crate::timer_scheduler::TimerScheduler;
 use crate::webview::WebViewManager;
+use crate::{element_export, frame_tree, prerender};
 
 type PendingApprovalNavigations = HashMap<PipelineId, (LoadData, HistoryEntryReplacement)>;
 
+/// The history state, url and scroll state of a session history entry that is traversed to.
+type SessionHistoryEntryChange = (Option<HistoryStateId>, ServoUrl, HistoryEntryScrollState);
+
 #[derive(Debug)]
 /// The state used by MessagePortInfo to represent the various states the port can be in.
 enum TransferState {
@@ -316,6 +336,17 @@ pub struct Constellation<STF, SWF> {
     /// A channel for the constellation to receive messages from network listener.
     network_listener_receiver: Receiver<(PipelineId, FetchResponseMsg)>,
 
+    /// A channel for the threads watching the content processes to tell which crashed,
+    /// by the pipeline they were started for.
+    content_process_crash_sender: Sender<PipelineId>,
+
+    /// A channel for the constellation to receive which content processes crashed.
+    content_process_crash_receiver: Receiver<PipelineId>,
+
+    /// The event loops running in the content processes being watched, by the pipeline
+    /// the processes were started for.
+    content_process_event_loops: HashMap<PipelineId, Weak<EventLoop>>,
+
     /// A channel for the constellation to receive messages from the compositor thread.
     compositor_receiver: Receiver<FromCompositorMsg>,
 
@@ -495,6 +526,51 @@ pub struct Constellation<STF, SWF> {
     /// Pipeline ID of the active media session.
     active_media_session: Option<PipelineId>,
 
+    /// The user stylesheets registered by the embedder, along with the top-level
+    /// browsing context they apply to, or `None` if they apply to all of them.
+    user_stylesheets: Vec<(Option<TopLevelBrowsingContextId>, UserStylesheet)>,
+
+    /// The content scripts registered by the embedder, along with the top-level
+    /// browsing context they apply to, or `None` if they apply to all of them.
+    content_scripts: Vec<(Option<TopLevelBrowsingContextId>, ContentScript)>,
+
+    /// The extensions loaded by the embedder.
+    extensions: HashMap<ExtensionId, LoadedExtension>,
+
+    /// The directory the `browser.storage.local` of extensions is kept in, if it
+    /// persists.
+    extension_storage_directory: Option<PathBuf>,
+
+    /// The last change of the connectivity of the device reported by the embedder, if any.
+    last_network_change: Option<NetworkChange>,
+
+    /// Whether the user chose to work offline.
+    work_offline: bool,
+
+    /// Whether the system is in forced colors mode, as reported by the embedder.
+    forced_colors: bool,
+
+    /// The network conditions emulated for the top-level browsing contexts, set through
+    /// devtools or WebDriver.
+    network_throttling: HashMap<TopLevelBrowsingContextId, NetworkThrottling>,
+
+    /// What the top-level browsing contexts tell about the user agent instead of the
+    /// defaults, set by the embedder or WebDriver.
+    user_agent_overrides: HashMap<TopLevelBrowsingContextId, UserAgentOverride>,
+
+    /// What the embedder lets the script of origins it does not trust do.
+    script_policies: ScriptPolicies,
+
+    /// The ids by which extensions know the top-level browsing contexts.
+    extension_tab_ids: ExtensionTabIds,
+
+    /// The pipelines loading pages to prerender for the embedder, with the channels telling
+    /// the prerender threads that the pages are loaded.
+    pending_prerenders: HashMap<PipelineId, Sender<()>>,
+
+    /// The spare content processes and idle event loops.
+    process_manager: ProcessManager,
+
     /// User agent string to report in network requests.
     user_agent: Cow<'static, str>,
 }
@@ -551,6 +627,9 @@ pub struct InitialConstellationState {
     /// User agent string to report in network requests.
     pub user_agent: Cow<'static, str>,
 
+    /// The directory to keep persistent data in, if any.
+    pub config_dir: Option<PathBuf>,
+
     pub wgpu_image_map: Arc<Mutex<HashMap<u64, webgpu::PresentationData>>>,
 }
 
@@ -693,6 +772,7 @@ where
                     );
 
                 let (network_listener_sender, network_listener_receiver) = unbounded();
+                let (content_process_crash_sender, content_process_crash_receiver) = unbounded();
 
                 let swmanager_receiver =
                     route_ipc_receiver_to_new_crossbeam_receiver_preserving_errors(
@@ -752,6 +832,9 @@ where
                     layout_receiver: layout_receiver,
                     network_listener_sender: network_listener_sender,
                     network_listener_receiver: network_listener_receiver,
+                    content_process_crash_sender,
+                    content_process_crash_receiver,
+                    content_process_event_loops: HashMap::new(),
                     embedder_proxy: state.embedder_proxy,
                     compositor_proxy: state.compositor_proxy,
                     webviews: WebViewManager::default(),
@@ -813,6 +896,21 @@ where
                     glplayer_threads: state.glplayer_threads,
                     player_context: state.player_context,
                     active_media_session: None,
+                    user_stylesheets: vec![],
+                    content_scripts: vec![],
+                    extensions: HashMap::new(),
+                    extension_storage_directory: state
+                        .config_dir
+                        .map(|config_dir| config_dir.join("extensions")),
+                    last_network_change: None,
+                    work_offline: false,
+                    forced_colors: false,
+                    network_throttling: HashMap::new(),
+                    user_agent_overrides: HashMap::new(),
+                    script_policies: HashMap::new(),
+                    extension_tab_ids: ExtensionTabIds::default(),
+                    pending_prerenders: HashMap::new(),
+                    process_manager: ProcessManager::new(),
                     user_agent: state.user_agent,
                 };
 
@@ -942,6 +1040,51 @@ where
         }
     }
 
+    /// An event loop to run a document of `host` in, for a browsing context group which has
+    /// none for the site yet, rather than starting a new one. Documents of private browsing
+    /// only share event loops with each other, as do the other documents, since an event
+    /// loop keeps the resource threads it was started with.
+    fn event_loop_for_new_group(&mut self, host: &Host, is_private: bool) -> Option<Rc<EventLoop>> {
+        if let Some(event_loop) = self.process_manager.take_idle_event_loop(host, is_private) {
+            return Some(event_loop);
+        }
+        let max = self.process_manager.max_event_loops_per_site()?;
+        let event_loops = self.event_loops_by_site().remove(host)?;
+        if event_loops.len() < max {
+            return None;
+        }
+        let event_loop = event_loops
+            .into_iter()
+            .find(|event_loop| event_loop.is_private() == is_private)?;
+        self.process_manager.metrics.shared_over_site_cap += 1;
+        Some(event_loop)
+    }
+
+    /// The event loops running the documents of each site, in every browsing context
+    /// group, including the idle ones.
+    fn event_loops_by_site(&self) -> HashMap<Host, Vec<Rc<EventLoop>>> {
+        let mut sites: HashMap<Host, Vec<Rc<EventLoop>>> = HashMap::new();
+        let group_event_loops = self
+            .browsing_context_group_set
+            .values()
+            .flat_map(|bc_group| bc_group.event_loops.iter())
+            .filter_map(|(host, event_loop)| Some((host, event_loop.upgrade()?)));
+        let idle_event_loops = self
+            .process_manager
+            .idle_event_loops()
+            .map(|(host, event_loop)| (host, event_loop.clone()));
+        for (host, event_loop) in group_event_loops.chain(idle_event_loops) {
+            let event_loops = sites.entry(host.clone()).or_default();
+            if !event_loops
+                .iter()
+                .any(|other| Rc::ptr_eq(other, &event_loop))
+            {
+                event_loops.push(event_loop);
+            }
+        }
+        sites
+    }
+
     /// Helper function for creating a pipeline
     fn new_pipeline(
         &mut self,
@@ -1014,6 +1157,20 @@ where
             },
         };
 
+        // There is no event loop for the site in this browsing context group: reuse an idle
+        // one, or share one of another group if the site has as many as it is allowed.
+        let event_loop = match (event_loop, &host) {
+            (None, Some(host)) => self.event_loop_for_new_group(host, is_private),
+            (event_loop, _) => event_loop,
+        };
+        let content_process = match event_loop {
+            Some(ref event_loop) => {
+                self.process_manager.event_loop_used(event_loop);
+                None
+            },
+            None => self.process_manager.event_loop_started(),
+        };
+
         let resource_threads = if is_private {
             self.private_resource_threads.clone()
         } else {
@@ -1045,6 +1202,7 @@ where
             swmanager_thread: self.swmanager_ipc_sender.clone(),
             font_cache_thread: self.font_cache_thread.clone(),
             resource_threads,
+            is_private,
             time_profiler_chan: self.time_profiler_chan.clone(),
             mem_profiler_chan: self.mem_profiler_chan.clone(),
             window_size: WindowSizeData {
@@ -1052,6 +1210,7 @@ where
                 device_pixel_ratio: self.window_size.device_pixel_ratio,
             },
             event_loop,
+            content_process,
             load_data,
             prev_visibility: is_visible,
             webrender_api_sender: self.webrender_api_ipc_sender.clone(),
@@ -1076,6 +1235,10 @@ where
             self.background_monitor_control_senders.push(chan);
         }
 
+        if let Some(lifeline) = pipeline.lifeline {
+            self.watch_content_process(pipeline_id, &pipeline.pipeline.event_loop, lifeline);
+        }
+
         if let Some(host) = host {
             debug!(
                 "{}: Adding new host entry {}",
@@ -1091,6 +1254,28 @@ where
 
         assert!(!self.pipelines.contains_key(&pipeline_id));
         self.pipelines.insert(pipeline_id, pipeline.pipeline);
+        self.send_user_content_to_pipeline(pipeline_id);
+
+        // A new script thread assumes the device is online until told otherwise.
+        if let Some(change) = self.last_network_change {
+            let msg = ConstellationControlMsg::NetworkChanged(change);
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+        if self.work_offline {
+            let msg = ConstellationControlMsg::SetWorkOffline(true);
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+        if self.forced_colors {
+            let msg = ConstellationControlMsg::SetForcedColors(true);
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+        if !self.script_policies.is_empty() {
+            let msg = ConstellationControlMsg::SetScriptPolicies(self.script_policies.clone());
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+        self.send_network_throttling_to_pipeline(pipeline_id, top_level_browsing_context_id);
+        self.send_user_agent_override_to_pipeline(pipeline_id, top_level_browsing_context_id);
+        self.send_network_partition_to_pipeline(pipeline_id, parent_pipeline_id);
     }
 
     /// Get an iterator for the fully active browsing contexts in a subtree.
@@ -1207,6 +1392,7 @@ where
             NetworkListener((PipelineId, FetchResponseMsg)),
             FromSWManager(SWManagerMsg),
             Timer(TimerSchedulerMsg),
+            ContentProcessCrashed(PipelineId),
         }
 
         // A timeout corresponding to the earliest scheduled timer event, if any.
@@ -1216,6 +1402,13 @@ where
             .map(|timeout| after(timeout))
             .unwrap_or(never());
 
+        // A timeout corresponding to the next idle event loop to shut down, if any.
+        let reap_timeout = self
+            .process_manager
+            .next_reap()
+            .map(|timeout| after(timeout))
+            .unwrap_or(never());
+
         // Get one incoming request.
         // This is one of the few places where the compositor is
         // allowed to panic. If one of the receiver.recv() calls
@@ -1254,11 +1447,20 @@ where
             recv(self.scheduler_receiver) -> msg => {
                 msg.expect("Unexpected schedule channel panic in constellation").map(Request::Timer)
             }
+            recv(self.content_process_crash_receiver) -> msg => {
+                Ok(Request::ContentProcessCrashed(
+                    msg.expect("Unexpected content process monitor channel panic in constellation")
+                ))
+            }
             recv(scheduler_timeout) -> _ => {
                 // Note: by returning, we go back to the top,
                 // where check_timers will be called.
                 return;
             },
+            recv(reap_timeout) -> _ => {
+                self.process_manager.reap_idle_event_loops();
+                return;
+            },
         };
 
         let request = match request {
@@ -1289,6 +1491,9 @@ where
             Request::Timer(message) => {
                 self.timer_scheduler.handle_timer_request(message);
             },
+            Request::ContentProcessCrashed(pipeline_id) => {
+                self.handle_content_process_crashed(pipeline_id);
+            },
         }
     }
 
@@ -1518,6 +1723,9 @@ where
                 self.forward_event(destination_pipeline_id, event);
             },
             FromCompositorMsg::SetCursor(cursor) => self.handle_set_cursor_msg(cursor),
+            FromCompositorMsg::SetCustomCursor(custom_cursor, cursor) => {
+                self.handle_set_custom_cursor_msg(custom_cursor, cursor)
+            },
             FromCompositorMsg::EnableProfiler(rate, max_duration) => {
                 for background_monitor_control_sender in &self.background_monitor_control_senders {
                     if let Err(e) = background_monitor_control_sender.send(
@@ -1554,6 +1762,88 @@ where
             FromCompositorMsg::Gamepad(gamepad_event) => {
                 self.handle_gamepad_msg(gamepad_event);
             },
+            FromCompositorMsg::AddUserStylesheet(top_level_browsing_context_id, stylesheet) => {
+                self.handle_add_user_stylesheet_msg(top_level_browsing_context_id, stylesheet);
+            },
+            FromCompositorMsg::RemoveUserStylesheet(stylesheet_id) => {
+                self.handle_remove_user_stylesheet_msg(stylesheet_id);
+            },
+            FromCompositorMsg::RegisterContentScript(top_level_browsing_context_id, script) => {
+                self.content_scripts
+                    .push((top_level_browsing_context_id, script));
+            },
+            FromCompositorMsg::UnregisterContentScript(content_script_id) => {
+                self.content_scripts
+                    .retain(|(_, script)| script.id != content_script_id);
+            },
+            FromCompositorMsg::LoadExtension(extension) => {
+                self.handle_load_extension_msg(extension);
+            },
+            FromCompositorMsg::UnloadExtension(extension_id) => {
+                self.handle_unload_extension_msg(extension_id);
+            },
+            FromCompositorMsg::MemoryPressure => {
+                self.handle_memory_pressure();
+            },
+            FromCompositorMsg::InterceptRequests(enabled) => {
+                self.public_resource_threads.intercept_requests(enabled);
+                self.private_resource_threads.intercept_requests(enabled);
+            },
+            // The public and private resource threads share a single recorder.
+            FromCompositorMsg::StartHarRecording(include_bodies) => {
+                self.public_resource_threads
+                    .start_har_recording(include_bodies);
+            },
+            FromCompositorMsg::StopHarRecording(path) => {
+                self.public_resource_threads.stop_har_recording(path);
+            },
+            // The threads share a connection monitor, but each has its own warm connections.
+            FromCompositorMsg::ReportConnectionPool => {
+                self.public_resource_threads.report_connection_pool();
+            },
+            FromCompositorMsg::ReportContentProcesses => {
+                self.handle_report_content_processes();
+            },
+            FromCompositorMsg::ReportFrameTree => {
+                let embedder_proxy = self.embedder_proxy.clone();
+                self.request_frame_tree(move |snapshot| {
+                    embedder_proxy.send((None, EmbedderMsg::ReportFrameTree(snapshot)));
+                });
+            },
+            FromCompositorMsg::NetworkChanged(change) => {
+                self.handle_network_changed(change);
+            },
+            FromCompositorMsg::SetWorkOffline(work_offline) => {
+                self.handle_set_work_offline(work_offline);
+            },
+            FromCompositorMsg::SetForcedColors(forced_colors) => {
+                self.handle_set_forced_colors(forced_colors);
+            },
+            FromCompositorMsg::SetUserAgentOverride(
+                top_level_browsing_context_id,
+                user_agent_override,
+            ) => {
+                self.handle_set_user_agent_override(
+                    top_level_browsing_context_id,
+                    user_agent_override,
+                );
+            },
+            FromCompositorMsg::SetScriptPolicy(origin, script_policy) => {
+                self.handle_set_script_policy(origin, script_policy);
+            },
+            FromCompositorMsg::SetPermission(url, name, state) => {
+                let msg = StorageThreadMsg::SetPermission(url.clone(), name, state);
+                if let Err(e) = self.public_resource_threads.send(msg) {
+                    warn!("Failed to set permission in the storage thread ({:?}).", e);
+                }
+                self.handle_broadcast_permission_change(None, url, name, state);
+            },
+            FromCompositorMsg::ExportElement(top_level_browsing_context_id, request) => {
+                self.handle_export_element(top_level_browsing_context_id, request);
+            },
+            FromCompositorMsg::Prerender(top_level_browsing_context_id, request) => {
+                self.handle_prerender(top_level_browsing_context_id, request);
+            },
         }
     }
 
@@ -1688,6 +1978,16 @@ where
             FromScriptMsg::ReplaceHistoryState(history_state_id, url) => {
                 self.handle_replace_history_state_msg(source_pipeline_id, history_state_id, url);
             },
+            FromScriptMsg::ScrollPositionChanged(position) => {
+                if let Some(pipeline) = self.pipelines.get_mut(&source_pipeline_id) {
+                    pipeline.scroll_state.position = position;
+                }
+            },
+            FromScriptMsg::SetScrollRestorationMode(restoration_mode) => {
+                if let Some(pipeline) = self.pipelines.get_mut(&source_pipeline_id) {
+                    pipeline.scroll_state.restoration_mode = restoration_mode;
+                }
+            },
             // Handle a joint session history length request.
             FromScriptMsg::JointSessionHistoryLength(response_sender) => {
                 self.handle_joint_session_history_length(source_top_ctx_id, response_sender);
@@ -1816,6 +2116,19 @@ where
                     warn!("Unable to forward DOMMessage for postMessage call");
                 }
             },
+            FromScriptMsg::NavigationPreload(scope_url, action, sender) => {
+                match self.sw_managers.get(&scope_url.origin()) {
+                    Some(mgr) => {
+                        let _ = mgr.send(ServiceWorkerMsg::NavigationPreload(
+                            scope_url, action, sender,
+                        ));
+                    },
+                    // Without a manager there is no registration, let alone an active worker.
+                    None => {
+                        let _ = sender.send(None);
+                    },
+                }
+            },
             FromScriptMsg::BroadcastStorageEvent(storage, url, key, old_value, new_value) => {
                 self.handle_broadcast_storage_event(
                     source_pipeline_id,
@@ -1826,6 +2139,9 @@ where
                     new_value,
                 );
             },
+            FromScriptMsg::BroadcastPermissionChange(url, name, state) => {
+                self.handle_broadcast_permission_change(Some(source_pipeline_id), url, name, state);
+            },
             FromScriptMsg::MediaSessionEvent(pipeline_id, event) => {
                 // Unlikely at this point, but we may receive events coming from
                 // different media sessions, so we set the active media session based
@@ -1867,6 +2183,12 @@ where
                     pipeline.title = title;
                 }
             },
+            FromScriptMsg::ExtensionRequest(extension_id, request) => {
+                self.handle_extension_request(source_pipeline_id, extension_id, request);
+            },
+            FromScriptMsg::SetNetworkThrottling(throttling) => {
+                self.handle_set_network_throttling(source_top_ctx_id, throttling);
+            },
         }
     }
 
@@ -2567,129 +2889,694 @@ where
         }
     }
 
-    fn handle_exit(&mut self) {
-        debug!("Handling exit.");
-
-        // TODO: add a timer, which forces shutdown if threads aren't responsive.
-        if self.shutting_down {
-            return;
-        }
-        self.shutting_down = true;
-
-        self.mem_profiler_chan.send(mem::ProfilerMsg::Exit);
-
-        // Tell all BHMs to exit,
-        // and to ensure their monitored components exit
-        // even when currently hanging(on JS or sync XHR).
-        // This must be done before starting the process of closing all pipelines.
-        for chan in &self.background_monitor_control_senders {
-            let (exit_ipc_sender, exit_ipc_receiver) =
-                ipc::channel().expect("Failed to create IPC channel!");
-            if let Err(e) = chan.send(BackgroundHangMonitorControlMsg::Exit(exit_ipc_sender)) {
-                warn!("error communicating with bhm: {}", e);
+    /// Let the pipelines of the origin of `url`, other than the one which made the change if
+    /// any, update their `PermissionStatus` objects.
+    fn handle_broadcast_permission_change(
+        &self,
+        source_pipeline_id: Option<PipelineId>,
+        url: ServoUrl,
+        name: PermissionName,
+        state: PermissionState,
+    ) {
+        let origin = url.origin();
+        for pipeline in self.pipelines.values() {
+            if Some(pipeline.id) == source_pipeline_id || pipeline.url.origin() != origin {
                 continue;
             }
-            if exit_ipc_receiver.recv().is_err() {
-                warn!("Failed to receive exit confirmation from BHM.");
+            let msg = ConstellationControlMsg::DispatchPermissionChange(pipeline.id, name, state);
+            if let Err(err) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "{}: Failed to broadcast permission change to pipeline ({:?}).",
+                    pipeline.id, err
+                );
             }
         }
+    }
 
-        // Close the top-level browsing contexts
-        let browsing_context_ids: Vec<BrowsingContextId> = self
-            .browsing_contexts
-            .values()
-            .filter(|browsing_context| browsing_context.is_top_level())
-            .map(|browsing_context| browsing_context.id)
+    fn handle_memory_pressure(&mut self) {
+        // The webviews in the background stop running script, and give up the documents
+        // in their session history, which are reloaded when traversed back to.
+        let hidden_webviews: Vec<_> = self
+            .webviews
+            .ids()
+            .filter(|top_level_browsing_context_id| {
+                self.browsing_contexts
+                    .get(&BrowsingContextId::from(*top_level_browsing_context_id))
+                    .map_or(false, |browsing_context| !browsing_context.is_visible)
+            })
             .collect();
-        for browsing_context_id in browsing_context_ids {
-            debug!(
-                "{}: Removing top-level browsing context",
-                browsing_context_id
-            );
-            self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
-        }
-
-        // Close any pending changes and pipelines
-        while let Some(pending) = self.pending_changes.pop() {
-            debug!(
-                "{}: Removing pending browsing context",
-                pending.browsing_context_id
-            );
-            self.close_browsing_context(pending.browsing_context_id, ExitPipelineMode::Normal);
-            debug!("{}: Removing pending pipeline", pending.new_pipeline_id);
-            self.close_pipeline(
-                pending.new_pipeline_id,
-                DiscardBrowsingContext::Yes,
-                ExitPipelineMode::Normal,
-            );
+        for top_level_browsing_context_id in hidden_webviews {
+            self.set_webview_frozen(top_level_browsing_context_id, true);
+            self.discard_history(top_level_browsing_context_id);
         }
 
-        // In case there are browsing contexts which weren't attached, we close them.
-        let browsing_context_ids: Vec<BrowsingContextId> =
-            self.browsing_contexts.keys().cloned().collect();
-        for browsing_context_id in browsing_context_ids {
-            debug!(
-                "{}: Removing detached browsing context",
-                browsing_context_id
-            );
-            self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
+        // Several pipelines can share a script thread, which only needs to be told once.
+        let mut event_loops: Vec<&Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            if event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                continue;
+            }
+            event_loops.push(&pipeline.event_loop);
+            if let Err(err) = pipeline
+                .event_loop
+                .send(ConstellationControlMsg::MemoryPressure)
+            {
+                warn!(
+                    "{}: Failed to send MemoryPressure to pipeline ({:?}).",
+                    pipeline.id, err
+                );
+            }
         }
+    }
 
-        // In case there are pipelines which weren't attached to the pipeline tree, we close them.
-        let pipeline_ids: Vec<PipelineId> = self.pipelines.keys().cloned().collect();
+    /// Freeze the fully active documents of a webview, or resume all its frozen documents.
+    /// <https://wicg.github.io/page-lifecycle/#sec-lifecycle-states>
+    fn set_webview_frozen(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        frozen: bool,
+    ) {
+        let pipeline_ids: Vec<PipelineId> = if frozen {
+            self.fully_active_browsing_contexts_iter(top_level_browsing_context_id)
+                .map(|browsing_context| browsing_context.pipeline_id)
+                .collect()
+        } else {
+            // Documents frozen while fully active may since have become inactive.
+            self.pipelines
+                .values()
+                .filter(|pipeline| {
+                    pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+                })
+                .map(|pipeline| pipeline.id)
+                .collect()
+        };
         for pipeline_id in pipeline_ids {
-            debug!("{}: Removing detached pipeline", pipeline_id);
-            self.close_pipeline(
-                pipeline_id,
-                DiscardBrowsingContext::Yes,
-                ExitPipelineMode::Normal,
-            );
+            let pipeline = match self.pipelines.get_mut(&pipeline_id) {
+                Some(pipeline) if pipeline.frozen != frozen => pipeline,
+                _ => continue,
+            };
+            pipeline.frozen = frozen;
+            let msg = ConstellationControlMsg::SetDocumentFrozen(pipeline_id, frozen);
+            if let Err(e) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "{}: Failed to freeze or resume document ({:?}).",
+                    pipeline_id, e
+                );
+            }
         }
     }
 
-    fn handle_shutdown(&mut self) {
-        debug!("Handling shutdown.");
+    /// Discard the documents of the session history of a webview, but for the active ones.
+    fn discard_history(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        let pipelines_to_discard = {
+            let session_history = self.get_joint_session_history(top_level_browsing_context_id);
+            let mut pipelines_to_discard: Vec<_> = session_history
+                .past
+                .iter()
+                .filter_map(|diff| diff.alive_old_pipeline())
+                .collect();
+            pipelines_to_discard.extend(
+                session_history
+                    .future
+                    .iter()
+                    .filter_map(|diff| diff.alive_new_pipeline()),
+            );
+            pipelines_to_discard
+        };
+        self.discard_pipelines(top_level_browsing_context_id, pipelines_to_discard);
+    }
 
-        // At this point, there are no active pipelines,
-        // so we can safely block on other threads, without worrying about deadlock.
-        // Channels to receive signals when threads are done exiting.
-        let (core_ipc_sender, core_ipc_receiver) =
-            ipc::channel().expect("Failed to create IPC channel!");
-        let (storage_ipc_sender, storage_ipc_receiver) =
-            ipc::channel().expect("Failed to create IPC channel!");
+    fn handle_network_changed(&mut self, change: NetworkChange) {
+        self.public_resource_threads.network_changed(change);
+        self.private_resource_threads.network_changed(change);
+        self.send_to_all_event_loops(|| ConstellationControlMsg::NetworkChanged(change));
+        self.last_network_change = Some(change);
+    }
 
-        debug!("Exiting core resource threads.");
-        if let Err(e) = self
-            .public_resource_threads
-            .send(net_traits::CoreResourceMsg::Exit(core_ipc_sender))
-        {
-            warn!("Exit resource thread failed ({})", e);
+    fn handle_set_work_offline(&mut self, work_offline: bool) {
+        if self.work_offline == work_offline {
+            return;
         }
+        self.public_resource_threads.set_work_offline(work_offline);
+        self.private_resource_threads.set_work_offline(work_offline);
+        self.send_to_all_event_loops(|| ConstellationControlMsg::SetWorkOffline(work_offline));
+        self.work_offline = work_offline;
+    }
 
-        if let Some(ref chan) = self.devtools_sender {
-            debug!("Exiting devtools.");
-            let msg = DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::ServerExitMsg);
-            if let Err(e) = chan.send(msg) {
-                warn!("Exit devtools failed ({:?})", e);
-            }
+    fn handle_set_forced_colors(&mut self, forced_colors: bool) {
+        if self.forced_colors == forced_colors {
+            return;
         }
+        self.send_to_all_event_loops(|| ConstellationControlMsg::SetForcedColors(forced_colors));
+        self.forced_colors = forced_colors;
+    }
 
-        debug!("Exiting storage resource threads.");
-        if let Err(e) = self
-            .public_resource_threads
-            .send(StorageThreadMsg::Exit(storage_ipc_sender))
-        {
-            warn!("Exit storage thread failed ({})", e);
+    /// Emulate `throttling` for the requests of every pipeline of a top-level browsing context,
+    /// now and as new pipelines are made for it, or stop emulating any network conditions.
+    fn handle_set_network_throttling(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        throttling: Option<NetworkThrottling>,
+    ) {
+        let previous = match throttling {
+            Some(throttling) => self
+                .network_throttling
+                .insert(top_level_browsing_context_id, throttling),
+            None => self
+                .network_throttling
+                .remove(&top_level_browsing_context_id),
+        };
+        if previous == throttling {
+            return;
         }
+        let pipelines: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        self.public_resource_threads
+            .throttle_network(pipelines.clone(), throttling);
+        self.private_resource_threads
+            .throttle_network(pipelines, throttling);
+    }
 
-        debug!("Exiting bluetooth thread.");
-        if let Err(e) = self.bluetooth_ipc_sender.send(BluetoothRequest::Exit) {
-            warn!("Exit bluetooth thread failed ({})", e);
+    /// Emulate the network conditions of its top-level browsing context for a new pipeline.
+    fn send_network_throttling_to_pipeline(
+        &self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        if let Some(throttling) = self.network_throttling.get(&top_level_browsing_context_id) {
+            self.public_resource_threads
+                .throttle_network(vec![pipeline_id], Some(*throttling));
+            self.private_resource_threads
+                .throttle_network(vec![pipeline_id], Some(*throttling));
         }
+    }
 
-        debug!("Exiting service worker manager thread.");
-        for (_, mgr) in self.sw_managers.drain() {
-            if let Err(e) = mgr.send(ServiceWorkerMsg::Exit) {
+    /// Tell the embedder how many event loops run, and what the process management
+    /// policies did so far.
+    fn handle_report_content_processes(&mut self) {
+        let mut sites: Vec<SiteEventLoops> = self
+            .event_loops_by_site()
+            .into_iter()
+            .map(|(host, event_loops)| SiteEventLoops {
+                site: host.to_string(),
+                event_loops: event_loops.len(),
+            })
+            .collect();
+        sites.sort_by(|a, b| a.site.cmp(&b.site));
+        let metrics = &self.process_manager.metrics;
+        let report = ContentProcessReport {
+            spare: self.process_manager.spare_count(),
+            idle: self.process_manager.idle_event_loops().count(),
+            sites,
+            started: metrics.started,
+            started_in_spare_process: metrics.started_in_spare_process,
+            idle_reused: metrics.idle_reused,
+            idle_reaped: metrics.idle_reaped,
+            shared_over_site_cap: metrics.shared_over_site_cap,
+        };
+        self.embedder_proxy
+            .send((None, EmbedderMsg::ReportContentProcesses(report)));
+    }
+
+    /// Take a snapshot of the frame tree, and ask the event loops what their documents use,
+    /// to call `done` with the complete snapshot once they answered.
+    fn request_frame_tree<F>(&self, done: F)
+    where
+        F: FnOnce(FrameTreeSnapshot) + Send + 'static,
+    {
+        let mut event_loops = vec![];
+        let mut top_level_browsing_contexts: Vec<BrowsingContextSnapshot> = self
+            .browsing_contexts
+            .values()
+            .filter(|browsing_context| browsing_context.parent_pipeline_id.is_none())
+            .map(|browsing_context| {
+                self.browsing_context_snapshot(browsing_context, &mut event_loops)
+            })
+            .collect();
+        top_level_browsing_contexts.sort_by_key(|browsing_context| browsing_context.id);
+        let snapshot = FrameTreeSnapshot {
+            top_level_browsing_contexts,
+            multiprocess: opts::multiprocess(),
+        };
+
+        let (sender, receiver) = ipc::channel().expect("ipc channel failure");
+        let mut asked = 0;
+        for event_loop in &event_loops {
+            let msg = ConstellationControlMsg::ReportPipelineUsage(sender.clone());
+            if event_loop.send(msg).is_ok() {
+                asked += 1;
+            }
+        }
+        frame_tree::collect_usage(snapshot, receiver, asked, done);
+    }
+
+    /// A snapshot of a browsing context and of those nested in its documents, noting the
+    /// event loops running them in `event_loops`.
+    fn browsing_context_snapshot(
+        &self,
+        browsing_context: &BrowsingContext,
+        event_loops: &mut Vec<Rc<EventLoop>>,
+    ) -> BrowsingContextSnapshot {
+        let loading: Vec<PipelineId> = self
+            .pending_changes
+            .iter()
+            .filter(|change| change.browsing_context_id == browsing_context.id)
+            .map(|change| change.new_pipeline_id)
+            .collect();
+        let mut pipeline_ids: Vec<PipelineId> = browsing_context
+            .pipelines
+            .iter()
+            .chain(loading.iter())
+            .copied()
+            .collect();
+        pipeline_ids.sort();
+        pipeline_ids.dedup();
+
+        let mut pipelines = vec![];
+        for pipeline in pipeline_ids.iter().filter_map(|id| self.pipelines.get(id)) {
+            let state = if pipeline.id == browsing_context.pipeline_id {
+                PipelineSnapshotState::Active
+            } else if loading.contains(&pipeline.id) {
+                PipelineSnapshotState::Loading
+            } else {
+                PipelineSnapshotState::Inactive
+            };
+            let event_loop = match event_loops
+                .iter()
+                .position(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                Some(index) => index,
+                None => {
+                    event_loops.push(pipeline.event_loop.clone());
+                    event_loops.len() - 1
+                },
+            };
+            let children = pipeline
+                .children
+                .iter()
+                .filter_map(|id| self.browsing_contexts.get(id))
+                .map(|child| self.browsing_context_snapshot(child, event_loops))
+                .collect();
+            pipelines.push(PipelineSnapshot {
+                id: pipeline.id,
+                url: pipeline.url.clone(),
+                title: pipeline.title.clone(),
+                state,
+                completely_loaded: pipeline.completely_loaded,
+                crashed: pipeline.load_data.crash.is_some(),
+                event_loop,
+                usage: None,
+                children,
+            });
+        }
+
+        BrowsingContextSnapshot {
+            id: browsing_context.id,
+            is_private: browsing_context.is_private,
+            is_visible: browsing_context.is_visible,
+            pipelines,
+        }
+    }
+
+    /// Run the script of `origin` as `script_policy` says, in the realms the event loops
+    /// make for it from now on.
+    fn handle_set_script_policy(&mut self, origin: ImmutableOrigin, script_policy: ScriptPolicy) {
+        if script_policy == ScriptPolicy::Allow {
+            self.script_policies.remove(&origin);
+        } else {
+            self.script_policies.insert(origin, script_policy);
+        }
+        let script_policies = &self.script_policies;
+        self.send_to_all_event_loops(|| {
+            ConstellationControlMsg::SetScriptPolicies(script_policies.clone())
+        });
+    }
+
+    /// Render an element of the active document of a top-level browsing context on another
+    /// thread, and send the result to the embedder.
+    fn handle_export_element(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        request: ElementExportRequest,
+    ) {
+        let id = request.id;
+        let embedder_proxy = self.embedder_proxy.clone();
+        let done = move |result| {
+            embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::ElementExported(id, result),
+            ));
+        };
+        // The compositor only renders the webviews that are shown.
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let pipeline = self
+            .browsing_contexts
+            .get(&browsing_context_id)
+            .filter(|browsing_context| browsing_context.is_visible)
+            .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id));
+        match pipeline {
+            Some(pipeline) => element_export::export_element(
+                pipeline.id,
+                pipeline.event_loop.sender(),
+                self.compositor_proxy.clone(),
+                self.window_size,
+                request,
+                done,
+            ),
+            None => done(Err(ElementExportError::NoSuchWebView)),
+        }
+    }
+
+    /// Load a page in a top-level browsing context, and prerender it on another thread once
+    /// it is loaded, sending the result to the embedder.
+    fn handle_prerender(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        request: PrerenderRequest,
+    ) {
+        let id = request.id;
+        let embedder_proxy = self.embedder_proxy.clone();
+        let done = move |result| {
+            embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::Prerendered(id, result),
+            ));
+        };
+        let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
+        let (source_id, is_private) = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => (browsing_context.pipeline_id, browsing_context.is_private),
+            None => return done(Err(PrerenderError::NoSuchWebView)),
+        };
+        let load_data = LoadData::new(
+            LoadOrigin::Constellation,
+            request.url.clone(),
+            None,
+            Referrer::NoReferrer,
+            None,
+            None,
+        );
+        let pipeline_id = match self.load_url(
+            top_level_browsing_context_id,
+            source_id,
+            load_data,
+            HistoryEntryReplacement::Disabled,
+        ) {
+            Some(pipeline_id) => pipeline_id,
+            None => return done(Err(PrerenderError::NoSuchWebView)),
+        };
+        let pipeline = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return done(Err(PrerenderError::NoSuchWebView)),
+        };
+        let resource_threads = if is_private {
+            self.private_resource_threads.clone()
+        } else {
+            self.public_resource_threads.clone()
+        };
+        let (loaded_sender, loaded) = unbounded();
+        self.pending_prerenders.insert(pipeline.id, loaded_sender);
+        prerender::prerender(
+            pipeline.id,
+            pipeline.event_loop.sender(),
+            resource_threads,
+            self.compositor_proxy.clone(),
+            loaded,
+            request,
+            done,
+        );
+    }
+
+    /// Make every pipeline of a top-level browsing context tell what `user_agent_override`
+    /// says about the user agent, now and as new pipelines are made for it, or go back to the
+    /// defaults.
+    fn handle_set_user_agent_override(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        user_agent_override: Option<UserAgentOverride>,
+    ) {
+        let previous = match user_agent_override {
+            Some(ref user_agent_override) => self
+                .user_agent_overrides
+                .insert(top_level_browsing_context_id, user_agent_override.clone()),
+            None => self
+                .user_agent_overrides
+                .remove(&top_level_browsing_context_id),
+        };
+        if previous == user_agent_override {
+            return;
+        }
+        let pipelines: Vec<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| pipeline.id)
+            .collect();
+        for pipeline_id in &pipelines {
+            let msg = ConstellationControlMsg::SetUserAgentOverride(
+                *pipeline_id,
+                user_agent_override.clone(),
+            );
+            self.send_to_pipeline(*pipeline_id, msg);
+        }
+        self.public_resource_threads
+            .override_user_agent(pipelines.clone(), user_agent_override.clone());
+        self.private_resource_threads
+            .override_user_agent(pipelines, user_agent_override);
+    }
+
+    /// Make a new pipeline tell what the override of its top-level browsing context says
+    /// about the user agent.
+    fn send_user_agent_override_to_pipeline(
+        &mut self,
+        pipeline_id: PipelineId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) {
+        let user_agent_override = match self
+            .user_agent_overrides
+            .get(&top_level_browsing_context_id)
+        {
+            Some(user_agent_override) => user_agent_override.clone(),
+            None => return,
+        };
+        self.public_resource_threads
+            .override_user_agent(vec![pipeline_id], Some(user_agent_override.clone()));
+        self.private_resource_threads
+            .override_user_agent(vec![pipeline_id], Some(user_agent_override.clone()));
+        let msg =
+            ConstellationControlMsg::SetUserAgentOverride(pipeline_id, Some(user_agent_override));
+        self.send_to_pipeline(pipeline_id, msg);
+    }
+
+    /// Partition the network state used by the requests of a new pipeline by the site of its
+    /// top-level document, which is the document of the top-most ancestor of
+    /// `parent_pipeline_id`, or the one of the new pipeline if it has no parent.
+    fn send_network_partition_to_pipeline(
+        &self,
+        pipeline_id: PipelineId,
+        parent_pipeline_id: Option<PipelineId>,
+    ) {
+        let mut top_level_document = TopLevelDocument::Itself;
+        let mut ancestor = parent_pipeline_id.and_then(|id| self.pipelines.get(&id));
+        while let Some(pipeline) = ancestor {
+            top_level_document = TopLevelDocument::At(pipeline.url.clone());
+            ancestor = self
+                .browsing_contexts
+                .get(&pipeline.browsing_context_id)
+                .and_then(|browsing_context| browsing_context.parent_pipeline_id)
+                .and_then(|id| self.pipelines.get(&id));
+        }
+        self.public_resource_threads
+            .set_network_partition(pipeline_id, Some(top_level_document.clone()));
+        self.private_resource_threads
+            .set_network_partition(pipeline_id, Some(top_level_document));
+    }
+
+    /// Send the message made by `make_msg` to every script thread. Several pipelines can share
+    /// a script thread, which only needs to be sent it once.
+    fn send_to_all_event_loops(&self, make_msg: impl Fn() -> ConstellationControlMsg) {
+        let mut event_loops: Vec<&Rc<EventLoop>> = vec![];
+        for pipeline in self.pipelines.values() {
+            if event_loops
+                .iter()
+                .any(|event_loop| Rc::ptr_eq(event_loop, &pipeline.event_loop))
+            {
+                continue;
+            }
+            event_loops.push(&pipeline.event_loop);
+            let msg = make_msg();
+            let msg_name = format!("{:?}", msg);
+            if let Err(err) = pipeline.event_loop.send(msg) {
+                warn!(
+                    "{}: Failed to send {} to pipeline ({:?}).",
+                    pipeline.id, msg_name, err
+                );
+            }
+        }
+        for (host, event_loop) in self.process_manager.idle_event_loops() {
+            if let Err(err) = event_loop.send(make_msg()) {
+                warn!(
+                    "Failed to send to the idle event loop of {} ({:?}).",
+                    host, err
+                );
+            }
+        }
+    }
+
+    fn handle_exit(&mut self) {
+        debug!("Handling exit.");
+
+        // TODO: add a timer, which forces shutdown if threads aren't responsive.
+        if self.shutting_down {
+            return;
+        }
+        self.shutting_down = true;
+        self.process_manager.shutdown();
+
+        self.mem_profiler_chan.send(mem::ProfilerMsg::Exit);
+
+        // Tell all BHMs to exit,
+        // and to ensure their monitored components exit
+        // even when currently hanging(on JS or sync XHR).
+        // This must be done before starting the process of closing all pipelines.
+        for chan in &self.background_monitor_control_senders {
+            let (exit_ipc_sender, exit_ipc_receiver) =
+                ipc::channel().expect("Failed to create IPC channel!");
+            if let Err(e) = chan.send(BackgroundHangMonitorControlMsg::Exit(exit_ipc_sender)) {
+                warn!("error communicating with bhm: {}", e);
+                continue;
+            }
+            if exit_ipc_receiver.recv().is_err() {
+                warn!("Failed to receive exit confirmation from BHM.");
+            }
+        }
+
+        // Close the top-level browsing contexts
+        let browsing_context_ids: Vec<BrowsingContextId> = self
+            .browsing_contexts
+            .values()
+            .filter(|browsing_context| browsing_context.is_top_level())
+            .map(|browsing_context| browsing_context.id)
+            .collect();
+        for browsing_context_id in browsing_context_ids {
+            debug!(
+                "{}: Removing top-level browsing context",
+                browsing_context_id
+            );
+            self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
+        }
+
+        // Close any pending changes and pipelines
+        while let Some(pending) = self.pending_changes.pop() {
+            debug!(
+                "{}: Removing pending browsing context",
+                pending.browsing_context_id
+            );
+            self.close_browsing_context(pending.browsing_context_id, ExitPipelineMode::Normal);
+            debug!("{}: Removing pending pipeline", pending.new_pipeline_id);
+            self.close_pipeline(
+                pending.new_pipeline_id,
+                DiscardBrowsingContext::Yes,
+                ExitPipelineMode::Normal,
+            );
+        }
+
+        // In case there are browsing contexts which weren't attached, we close them.
+        let browsing_context_ids: Vec<BrowsingContextId> =
+            self.browsing_contexts.keys().cloned().collect();
+        for browsing_context_id in browsing_context_ids {
+            debug!(
+                "{}: Removing detached browsing context",
+                browsing_context_id
+            );
+            self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
+        }
+
+        // In case there are pipelines which weren't attached to the pipeline tree, we close them.
+        let pipeline_ids: Vec<PipelineId> = self.pipelines.keys().cloned().collect();
+        for pipeline_id in pipeline_ids {
+            debug!("{}: Removing detached pipeline", pipeline_id);
+            self.close_pipeline(
+                pipeline_id,
+                DiscardBrowsingContext::Yes,
+                ExitPipelineMode::Normal,
+            );
+        }
+    }
+
+    fn handle_shutdown(&mut self) {
+        debug!("Handling shutdown.");
+
+        // At this point, there are no active pipelines,
+        // so we can safely block on other threads, without worrying about deadlock.
+        // Channels to receive signals when threads are done exiting.
+        let (core_ipc_sender, core_ipc_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
+        let (storage_ipc_sender, storage_ipc_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
+        let (indexeddb_ipc_sender, indexeddb_ipc_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
+        let (file_system_ipc_sender, file_system_ipc_receiver) =
+            ipc::channel().expect("Failed to create IPC channel!");
+
+        debug!("Exiting core resource threads.");
+        if let Err(e) = self
+            .public_resource_threads
+            .send(net_traits::CoreResourceMsg::Exit(core_ipc_sender))
+        {
+            warn!("Exit resource thread failed ({})", e);
+        }
+
+        if let Some(ref chan) = self.devtools_sender {
+            debug!("Exiting devtools.");
+            let msg = DevtoolsControlMsg::FromChrome(ChromeToDevtoolsControlMsg::ServerExitMsg);
+            if let Err(e) = chan.send(msg) {
+                warn!("Exit devtools failed ({:?})", e);
+            }
+        }
+
+        debug!("Exiting storage resource threads.");
+        if let Err(e) = self
+            .public_resource_threads
+            .send(StorageThreadMsg::Exit(storage_ipc_sender))
+        {
+            warn!("Exit storage thread failed ({})", e);
+        }
+
+        debug!("Exiting IndexedDB thread.");
+        if let Err(e) = self
+            .public_resource_threads
+            .send(IndexedDBThreadMsg::Exit(indexeddb_ipc_sender))
+        {
+            warn!("Exit IndexedDB thread failed ({})", e);
+        }
+
+        debug!("Exiting file system thread.");
+        if let Err(e) = self
+            .public_resource_threads
+            .send(FileSystemThreadMsg::Exit(file_system_ipc_sender))
+        {
+            warn!("Exit file system thread failed ({})", e);
+        }
+
+        debug!("Exiting bluetooth thread.");
+        if let Err(e) = self.bluetooth_ipc_sender.send(BluetoothRequest::Exit) {
+            warn!("Exit bluetooth thread failed ({})", e);
+        }
+
+        debug!("Exiting service worker manager thread.");
+        for (_, mgr) in self.sw_managers.drain() {
+            if let Err(e) = mgr.send(ServiceWorkerMsg::Exit) {
                 warn!("Exit service worker manager failed ({})", e);
             }
         }
@@ -2747,6 +3634,12 @@ where
         if let Err(e) = storage_ipc_receiver.recv() {
             warn!("Exit storage thread failed ({:?})", e);
         }
+        if let Err(e) = indexeddb_ipc_receiver.recv() {
+            warn!("Exit IndexedDB thread failed ({:?})", e);
+        }
+        if let Err(e) = file_system_ipc_receiver.recv() {
+            warn!("Exit file system thread failed ({:?})", e);
+        }
 
         debug!("Asking compositor to complete shutdown.");
         self.compositor_proxy.send(CompositorMsg::ShutdownComplete);
@@ -2757,7 +3650,23 @@ where
 
     fn handle_pipeline_exited(&mut self, pipeline_id: PipelineId) {
         debug!("{}: Exited", pipeline_id);
-        self.pipelines.remove(&pipeline_id);
+        let pipeline = self.pipelines.remove(&pipeline_id);
+        self.pending_prerenders.remove(&pipeline_id);
+
+        // Keep the event loop running for a while if this was its last document, in case
+        // another document of the site is loaded.
+        if let Some(pipeline) = pipeline {
+            if !self.shutting_down && Rc::strong_count(&pipeline.event_loop) == 1 {
+                if let Some(host) = reg_host(&pipeline.url) {
+                    self.process_manager
+                        .event_loop_idle(host, pipeline.event_loop);
+                }
+            }
+        }
+        self.public_resource_threads
+            .set_network_partition(pipeline_id, None);
+        self.private_resource_threads
+            .set_network_partition(pipeline_id, None);
     }
 
     fn handle_send_error(&mut self, pipeline_id: PipelineId, err: IpcError) {
@@ -2801,12 +3710,25 @@ where
             EmbedderMsg::Panic(reason.clone(), backtrace.clone()),
         ));
 
+        self.replace_with_crash_page(browsing_context_id, CrashReason::Panic(reason, backtrace));
+    }
+
+    /// Replace the document of a browsing context, which crashed, by the crash page. The
+    /// document is loaded again once the crash page is reloaded, or traversed back to.
+    fn replace_with_crash_page(
+        &mut self,
+        browsing_context_id: BrowsingContextId,
+        reason: CrashReason,
+    ) {
         let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
             Some(context) => context,
             None => return warn!("failed browsing context is missing"),
         };
+        let top_level_browsing_context_id = browsing_context.top_level_id;
+        let parent_pipeline_id = browsing_context.parent_pipeline_id;
         let window_size = browsing_context.size;
         let pipeline_id = browsing_context.pipeline_id;
+        let is_private = browsing_context.is_private;
         let is_visible = browsing_context.is_visible;
 
         let pipeline = match self.pipelines.get(&pipeline_id) {
@@ -2823,32 +3745,53 @@ where
 
         let old_pipeline_id = pipeline_id;
         let old_load_data = match self.pipelines.get(&pipeline_id) {
-            Some(pipeline) => pipeline.load_data.clone(),
+            Some(pipeline) => pipeline.load_data_for_active_entry(),
             None => return warn!("failed pipeline is missing"),
         };
         if old_load_data.crash.is_some() {
             return error!("crash page crashed");
         }
 
+        let details = match reason {
+            CrashReason::Panic(ref reason, Some(ref backtrace)) => {
+                format!("{}\n{}", reason, backtrace)
+            },
+            CrashReason::Panic(ref reason, None) => reason.clone(),
+            CrashReason::ContentProcessExited => "The content process exited unexpectedly".into(),
+        };
+        self.embedder_proxy.send((
+            Some(top_level_browsing_context_id),
+            EmbedderMsg::WebViewCrashed(CrashReport {
+                url: old_load_data.url.clone(),
+                top_level: parent_pipeline_id.is_none(),
+                reason,
+            }),
+        ));
+
+        let resource_threads = if is_private {
+            &self.private_resource_threads
+        } else {
+            &self.public_resource_threads
+        };
+        let _ = resource_threads.send(net_traits::CoreResourceMsg::QueueCrashReport(
+            old_pipeline_id,
+            old_load_data.url.clone(),
+        ));
+
         warn!("creating replacement pipeline for crash page");
 
         let new_pipeline_id = PipelineId::new();
         let new_load_data = LoadData {
-            crash: Some(
-                backtrace
-                    .map(|b| format!("{}\n{}", reason, b))
-                    .unwrap_or(reason),
-            ),
+            crash: Some(details),
             ..old_load_data.clone()
         };
 
         let sandbox = IFrameSandboxState::IFrameSandboxed;
-        let is_private = false;
         self.new_pipeline(
             new_pipeline_id,
             browsing_context_id,
             top_level_browsing_context_id,
-            None,
+            parent_pipeline_id,
             opener,
             window_size,
             new_load_data,
@@ -2868,6 +3811,112 @@ where
         });
     }
 
+    /// Watch the content process started for a pipeline, to recover the documents it runs
+    /// if it crashes.
+    fn watch_content_process(
+        &mut self,
+        pipeline_id: PipelineId,
+        event_loop: &Rc<EventLoop>,
+        lifeline: IpcReceiver<()>,
+    ) {
+        self.content_process_event_loops
+            .retain(|_, event_loop| event_loop.strong_count() > 0);
+        self.content_process_event_loops
+            .insert(pipeline_id, Rc::downgrade(event_loop));
+        let crash_sender = self.content_process_crash_sender.clone();
+        let result = thread::Builder::new()
+            .name("ContentProcessWatcher".to_owned())
+            .spawn(move || {
+                // The process disconnects without sending anything if it crashes.
+                if lifeline.recv().is_err() {
+                    let _ = crash_sender.send(pipeline_id);
+                }
+            });
+        if let Err(e) = result {
+            warn!(
+                "{}: Failed to watch the content process ({})",
+                pipeline_id, e
+            );
+        }
+    }
+
+    /// The content process started for `pipeline_id` crashed: replace the active documents
+    /// it ran by the crash page, and let the session history entries of the others load
+    /// them again.
+    fn handle_content_process_crashed(&mut self, pipeline_id: PipelineId) {
+        let event_loop = match self
+            .content_process_event_loops
+            .remove(&pipeline_id)
+            .and_then(|event_loop| event_loop.upgrade())
+        {
+            Some(event_loop) => event_loop,
+            // The process ran no document anymore.
+            None => return,
+        };
+        if self.shutting_down {
+            return;
+        }
+        if self.hard_fail {
+            println!("Content process crashed in hard-fail mode.  Crashing!");
+            process::exit(1);
+        }
+        error!("{}: Content process crashed", pipeline_id);
+
+        self.process_manager.event_loop_crashed(&event_loop);
+        let crashed: HashSet<PipelineId> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| Rc::ptr_eq(&pipeline.event_loop, &event_loop))
+            .map(|pipeline| pipeline.id)
+            .collect();
+
+        for &crashed_id in &crashed {
+            let pipeline = match self.pipelines.get(&crashed_id) {
+                Some(pipeline) => pipeline,
+                None => continue,
+            };
+            let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+            let browsing_context = match self.browsing_contexts.get(&pipeline.browsing_context_id) {
+                Some(browsing_context) => browsing_context,
+                // It was closed along with a crashed parent.
+                None => continue,
+            };
+            // Closing or replacing the crashed parent closes it.
+            if browsing_context
+                .parent_pipeline_id
+                .map_or(false, |parent_id| crashed.contains(&parent_id))
+            {
+                continue;
+            }
+            if browsing_context.pipeline_id == crashed_id {
+                let browsing_context_id = browsing_context.id;
+                self.replace_with_crash_page(
+                    browsing_context_id,
+                    CrashReason::ContentProcessExited,
+                );
+                continue;
+            }
+            let mut load_data = pipeline.load_data.clone();
+            load_data.url = pipeline.url.clone();
+            if let Some(webview) = self.webviews.get_mut(top_level_browsing_context_id) {
+                webview.session_history.replace_reloader(
+                    NeedsToReload::No(crashed_id),
+                    NeedsToReload::Yes(crashed_id, load_data),
+                );
+            }
+            self.close_pipeline(
+                crashed_id,
+                DiscardBrowsingContext::No,
+                ExitPipelineMode::Force,
+            );
+        }
+
+        // The crashed pipelines will not tell they exited.
+        for crashed_id in crashed {
+            self.handle_pipeline_exited(crashed_id);
+        }
+    }
+
     fn handle_log_entry(
         &mut self,
         top_level_browsing_context_id: Option<TopLevelBrowsingContextId>,
@@ -2952,13 +4001,22 @@ where
         url: ServoUrl,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
-        let window_size = self.window_size.initial_viewport;
-        let pipeline_id = PipelineId::new();
         let msg = (
             Some(top_level_browsing_context_id),
             EmbedderMsg::WebViewOpened(top_level_browsing_context_id),
         );
         self.embedder_proxy.send(msg);
+        self.new_top_level_browsing_context(url, top_level_browsing_context_id, true);
+    }
+
+    fn new_top_level_browsing_context(
+        &mut self,
+        url: ServoUrl,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        is_visible: bool,
+    ) {
+        let window_size = self.window_size.initial_viewport;
+        let pipeline_id = PipelineId::new();
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         let load_data = LoadData::new(
             LoadOrigin::Constellation,
@@ -2970,7 +4028,6 @@ where
         );
         let sandbox = IFrameSandboxState::IFrameUnsandboxed;
         let is_private = false;
-        let is_visible = true;
 
         // Register this new top-level browsing context id as a webview and set
         // its focused browsing context to be itself.
@@ -3023,6 +4080,8 @@ where
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
         debug!("{top_level_browsing_context_id}: Closing");
+        self.handle_set_network_throttling(top_level_browsing_context_id, None);
+        self.handle_set_user_agent_override(top_level_browsing_context_id, None);
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
         let browsing_context =
             self.close_browsing_context(browsing_context_id, ExitPipelineMode::Normal);
@@ -3032,11 +4091,18 @@ where
                 .send((None, EmbedderMsg::WebViewBlurred));
         }
         self.webviews.remove(top_level_browsing_context_id);
+        self.extension_tab_ids.remove(top_level_browsing_context_id);
+        // Stylesheets added to this webview only can no longer apply to anything.
+        self.user_stylesheets
+            .retain(|(id, _)| *id != Some(top_level_browsing_context_id));
         // TODO Send the compositor a RemoveWebView event.
-        self.embedder_proxy.send((
-            Some(top_level_browsing_context_id),
-            EmbedderMsg::WebViewClosed(top_level_browsing_context_id),
-        ));
+        // The background pages of extensions are never shown to the embedder.
+        if !self.is_extension_background_page(top_level_browsing_context_id) {
+            self.embedder_proxy.send((
+                Some(top_level_browsing_context_id),
+                EmbedderMsg::WebViewClosed(top_level_browsing_context_id),
+            ));
+        }
 
         let Some(browsing_context) = browsing_context else {
             return;
@@ -3125,6 +4191,21 @@ where
         request_builder: RequestBuilder,
         cancel_chan: IpcReceiver<()>,
     ) {
+        // The page showing the frame tree is made here rather than fetched, and only for
+        // top-level documents, so that no other page can embed it.
+        if request_builder.url.as_str() == frame_tree::PROCESSES_PAGE_URL {
+            let top_level = self.pipelines.get(&id).map_or(false, |pipeline| {
+                pipeline.browsing_context_id == pipeline.top_level_browsing_context_id
+            });
+            if top_level {
+                let url = request_builder.url;
+                let sender = self.network_listener_sender.clone();
+                return self.request_frame_tree(move |snapshot| {
+                    frame_tree::send_processes_page(id, url, &snapshot, &sender)
+                });
+            }
+        }
+
         let listener = NetworkListener::new(
             request_builder,
             id,
@@ -3289,6 +4370,10 @@ where
 
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
         self.pipelines.insert(new_pipeline_id, pipeline);
+        self.send_user_content_to_pipeline(new_pipeline_id);
+        self.send_network_throttling_to_pipeline(new_pipeline_id, top_level_browsing_context_id);
+        self.send_user_agent_override_to_pipeline(new_pipeline_id, top_level_browsing_context_id);
+        self.send_network_partition_to_pipeline(new_pipeline_id, Some(parent_pipeline_id));
         self.add_pending_change(SessionHistoryChange {
             top_level_browsing_context_id,
             browsing_context_id,
@@ -3347,6 +4432,8 @@ where
 
         assert!(!self.pipelines.contains_key(&new_pipeline_id));
         self.pipelines.insert(new_pipeline_id, pipeline);
+        self.send_user_content_to_pipeline(new_pipeline_id);
+        self.send_network_partition_to_pipeline(new_pipeline_id, None);
         self.webviews.add(
             new_top_level_browsing_context_id,
             WebView {
@@ -3400,6 +4487,11 @@ where
             .send((None, EmbedderMsg::SetCursor(cursor)))
     }
 
+    fn handle_set_custom_cursor_msg(&mut self, custom_cursor: CustomCursor, cursor: Cursor) {
+        self.embedder_proxy
+            .send((None, EmbedderMsg::SetCustomCursor(custom_cursor, cursor)))
+    }
+
     fn handle_change_running_animations_state(
         &mut self,
         pipeline_id: PipelineId,
@@ -3625,6 +4717,33 @@ where
             pipeline.completely_loaded = true;
         }
 
+        if let Some(loaded) = self.pending_prerenders.remove(&pipeline_id) {
+            let _ = loaded.send(());
+        }
+
+        // The background script of an extension runs once its background page is loaded.
+        let background_script = self
+            .extensions
+            .values()
+            .find(|loaded| loaded.background_page == Some(top_level_browsing_context_id))
+            .map(|loaded| {
+                (
+                    loaded.extension.id,
+                    loaded.extension.background_script.clone(),
+                )
+            });
+        if let Some((extension_id, script)) = background_script {
+            if let Some(script) = script {
+                let msg = ConstellationControlMsg::RunExtensionBackgroundScript(
+                    pipeline_id,
+                    extension_id,
+                    script,
+                );
+                self.send_to_pipeline(pipeline_id, msg);
+            }
+            return;
+        }
+
         // Notify the embedder that the TopLevelBrowsingContext current document
         // has finished loading.
         // We need to make sure the pipeline that has finished loading is the current
@@ -3657,22 +4776,29 @@ where
         new_url: ServoUrl,
         replacement_enabled: HistoryEntryReplacement,
     ) {
-        let (top_level_browsing_context_id, old_url) = match self.pipelines.get_mut(&pipeline_id) {
-            Some(pipeline) => {
-                let old_url = replace(&mut pipeline.url, new_url.clone());
-                (pipeline.top_level_browsing_context_id, old_url)
-            },
-            None => {
-                return warn!("{}: Navigated to fragment after closure", pipeline_id);
-            },
-        };
+        let (top_level_browsing_context_id, old_url, scroll_state) =
+            match self.pipelines.get_mut(&pipeline_id) {
+                Some(pipeline) => {
+                    let old_url = replace(&mut pipeline.url, new_url.clone());
+                    (
+                        pipeline.top_level_browsing_context_id,
+                        old_url,
+                        pipeline.scroll_state,
+                    )
+                },
+                None => {
+                    return warn!("{}: Navigated to fragment after closure", pipeline_id);
+                },
+            };
 
         match replacement_enabled {
             HistoryEntryReplacement::Disabled => {
                 let diff = SessionHistoryDiff::HashDiff {
                     pipeline_reloader: NeedsToReload::No(pipeline_id),
                     new_url,
+                    new_scroll_state: scroll_state,
                     old_url,
+                    old_scroll_state: scroll_state,
                 };
                 self.get_joint_session_history(top_level_browsing_context_id)
                     .push_diff(diff);
@@ -3688,8 +4814,19 @@ where
         direction: TraversalDirection,
     ) {
         let mut browsing_context_changes = HashMap::<BrowsingContextId, NeedsToReload>::new();
-        let mut pipeline_changes = HashMap::<PipelineId, (Option<HistoryStateId>, ServoUrl)>::new();
-        let mut url_to_load = HashMap::<PipelineId, ServoUrl>::new();
+        let mut pipeline_changes = HashMap::<PipelineId, SessionHistoryEntryChange>::new();
+        let mut entries_to_load = HashMap::<PipelineId, SessionHistoryEntryChange>::new();
+
+        // The scroll state of the active entries, which is saved in the entries as they are left.
+        let mut scroll_states: HashMap<PipelineId, HistoryEntryScrollState> = self
+            .pipelines
+            .values()
+            .filter(|pipeline| {
+                pipeline.top_level_browsing_context_id == top_level_browsing_context_id
+            })
+            .map(|pipeline| (pipeline.id, pipeline.scroll_state))
+            .collect();
+
         {
             let session_history = self.get_joint_session_history(top_level_browsing_context_id);
             match direction {
@@ -3700,7 +4837,7 @@ where
                         return warn!("Cannot traverse that far into the future.");
                     }
 
-                    for diff in session_history
+                    for mut diff in session_history
                         .future
                         .drain(future_length - forward..)
                         .rev()
@@ -3716,33 +4853,60 @@ where
                             },
                             SessionHistoryDiff::PipelineDiff {
                                 ref pipeline_reloader,
+                                ref mut old_scroll_state,
                                 new_history_state_id,
                                 ref new_url,
+                                new_scroll_state,
                                 ..
-                            } => match *pipeline_reloader {
-                                NeedsToReload::No(pipeline_id) => {
-                                    pipeline_changes.insert(
-                                        pipeline_id,
-                                        (Some(new_history_state_id), new_url.clone()),
-                                    );
-                                },
-                                NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, new_url.clone());
-                                },
+                            } => {
+                                let change = (
+                                    Some(new_history_state_id),
+                                    new_url.clone(),
+                                    new_scroll_state,
+                                );
+                                match *pipeline_reloader {
+                                    NeedsToReload::No(pipeline_id) => {
+                                        if let Some(scroll_state) =
+                                            scroll_states.insert(pipeline_id, new_scroll_state)
+                                        {
+                                            *old_scroll_state = scroll_state;
+                                        }
+                                        pipeline_changes.insert(pipeline_id, change);
+                                    },
+                                    NeedsToReload::Yes(pipeline_id, ..) => {
+                                        entries_to_load.insert(pipeline_id, change);
+                                    },
+                                }
                             },
                             SessionHistoryDiff::HashDiff {
                                 ref pipeline_reloader,
+                                ref mut old_scroll_state,
                                 ref new_url,
+                                new_scroll_state,
                                 ..
                             } => match *pipeline_reloader {
                                 NeedsToReload::No(pipeline_id) => {
+                                    if let Some(scroll_state) =
+                                        scroll_states.insert(pipeline_id, new_scroll_state)
+                                    {
+                                        *old_scroll_state = scroll_state;
+                                    }
                                     let state = pipeline_changes
                                         .get(&pipeline_id)
                                         .and_then(|change| change.0);
-                                    pipeline_changes.insert(pipeline_id, (state, new_url.clone()));
+                                    pipeline_changes.insert(
+                                        pipeline_id,
+                                        (state, new_url.clone(), new_scroll_state),
+                                    );
                                 },
                                 NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, new_url.clone());
+                                    let state = entries_to_load
+                                        .get(&pipeline_id)
+                                        .and_then(|change| change.0);
+                                    entries_to_load.insert(
+                                        pipeline_id,
+                                        (state, new_url.clone(), new_scroll_state),
+                                    );
                                 },
                             },
                         }
@@ -3756,7 +4920,7 @@ where
                         return warn!("Cannot traverse that far into the past.");
                     }
 
-                    for diff in session_history.past.drain(past_length - back..).rev() {
+                    for mut diff in session_history.past.drain(past_length - back..).rev() {
                         match diff {
                             SessionHistoryDiff::BrowsingContextDiff {
                                 browsing_context_id,
@@ -3770,31 +4934,55 @@ where
                                 ref pipeline_reloader,
                                 old_history_state_id,
                                 ref old_url,
+                                old_scroll_state,
+                                ref mut new_scroll_state,
                                 ..
-                            } => match *pipeline_reloader {
-                                NeedsToReload::No(pipeline_id) => {
-                                    pipeline_changes.insert(
-                                        pipeline_id,
-                                        (old_history_state_id, old_url.clone()),
-                                    );
-                                },
-                                NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, old_url.clone());
-                                },
+                            } => {
+                                let change =
+                                    (old_history_state_id, old_url.clone(), old_scroll_state);
+                                match *pipeline_reloader {
+                                    NeedsToReload::No(pipeline_id) => {
+                                        if let Some(scroll_state) =
+                                            scroll_states.insert(pipeline_id, old_scroll_state)
+                                        {
+                                            *new_scroll_state = scroll_state;
+                                        }
+                                        pipeline_changes.insert(pipeline_id, change);
+                                    },
+                                    NeedsToReload::Yes(pipeline_id, ..) => {
+                                        entries_to_load.insert(pipeline_id, change);
+                                    },
+                                }
                             },
                             SessionHistoryDiff::HashDiff {
                                 ref pipeline_reloader,
                                 ref old_url,
+                                old_scroll_state,
+                                ref mut new_scroll_state,
                                 ..
                             } => match *pipeline_reloader {
                                 NeedsToReload::No(pipeline_id) => {
+                                    if let Some(scroll_state) =
+                                        scroll_states.insert(pipeline_id, old_scroll_state)
+                                    {
+                                        *new_scroll_state = scroll_state;
+                                    }
                                     let state = pipeline_changes
                                         .get(&pipeline_id)
                                         .and_then(|change| change.0);
-                                    pipeline_changes.insert(pipeline_id, (state, old_url.clone()));
+                                    pipeline_changes.insert(
+                                        pipeline_id,
+                                        (state, old_url.clone(), old_scroll_state),
+                                    );
                                 },
                                 NeedsToReload::Yes(pipeline_id, ..) => {
-                                    url_to_load.insert(pipeline_id, old_url.clone());
+                                    let state = entries_to_load
+                                        .get(&pipeline_id)
+                                        .and_then(|change| change.0);
+                                    entries_to_load.insert(
+                                        pipeline_id,
+                                        (state, old_url.clone(), old_scroll_state),
+                                    );
                                 },
                             },
                         }
@@ -3806,15 +4994,19 @@ where
 
         for (browsing_context_id, mut pipeline_reloader) in browsing_context_changes.drain() {
             if let NeedsToReload::Yes(pipeline_id, ref mut load_data) = pipeline_reloader {
-                if let Some(url) = url_to_load.get(&pipeline_id) {
-                    load_data.url = url.clone();
+                if let Some((history_state_id, url, scroll_state)) =
+                    entries_to_load.remove(&pipeline_id)
+                {
+                    load_data.url = url;
+                    load_data.history_state_id = history_state_id;
+                    load_data.scroll_state = Some(scroll_state);
                 }
             }
             self.update_browsing_context(browsing_context_id, pipeline_reloader);
         }
 
-        for (pipeline_id, (history_state_id, url)) in pipeline_changes.drain() {
-            self.update_pipeline(pipeline_id, history_state_id, url);
+        for (pipeline_id, (history_state_id, url, scroll_state)) in pipeline_changes.drain() {
+            self.update_pipeline(pipeline_id, history_state_id, url, scroll_state);
         }
 
         self.notify_history_changed(top_level_browsing_context_id);
@@ -3952,6 +5144,7 @@ where
         pipeline_id: PipelineId,
         history_state_id: Option<HistoryStateId>,
         url: ServoUrl,
+        scroll_state: HistoryEntryScrollState,
     ) {
         let result = match self.pipelines.get_mut(&pipeline_id) {
             None => {
@@ -3962,9 +5155,11 @@ where
                     pipeline_id,
                     history_state_id,
                     url.clone(),
+                    scroll_state,
                 );
                 pipeline.history_state_id = history_state_id;
                 pipeline.url = url;
+                pipeline.scroll_state = scroll_state;
                 pipeline.event_loop.send(msg)
             },
         };
@@ -3992,7 +5187,7 @@ where
         history_state_id: HistoryStateId,
         url: ServoUrl,
     ) {
-        let (top_level_browsing_context_id, old_state_id, old_url) =
+        let (top_level_browsing_context_id, old_state_id, old_url, scroll_state) =
             match self.pipelines.get_mut(&pipeline_id) {
                 Some(pipeline) => {
                     let old_history_state_id = pipeline.history_state_id;
@@ -4003,6 +5198,7 @@ where
                         pipeline.top_level_browsing_context_id,
                         old_history_state_id,
                         old_url,
+                        pipeline.scroll_state,
                     )
                 },
                 None => {
@@ -4013,12 +5209,16 @@ where
                 },
             };
 
+        // The new entry starts out with the scroll state of the entry it was pushed from.
+        // https://html.spec.whatwg.org/multipage/#shared-history-push/replace-state-steps
         let diff = SessionHistoryDiff::PipelineDiff {
             pipeline_reloader: NeedsToReload::No(pipeline_id),
             new_history_state_id: history_state_id,
             new_url: url,
+            new_scroll_state: scroll_state,
             old_history_state_id: old_state_id,
             old_url,
+            old_scroll_state: scroll_state,
         };
         self.get_joint_session_history(top_level_browsing_context_id)
             .push_diff(diff);
@@ -4125,16 +5325,70 @@ where
                 return warn!("{}: Got reload event after closure", browsing_context_id);
             },
         };
-        let msg = ConstellationControlMsg::Reload(pipeline_id);
-        let result = match self.pipelines.get(&pipeline_id) {
+        let pipeline = match self.pipelines.get(&pipeline_id) {
             None => return warn!("{}: Got reload event after closure", pipeline_id),
-            Some(pipeline) => pipeline.event_loop.send(msg),
+            Some(pipeline) => pipeline,
         };
+        // Reloading the crash page loads the document which crashed again.
+        if pipeline.load_data.crash.is_some() {
+            let load_data = LoadData {
+                crash: None,
+                ..pipeline.load_data.clone()
+            };
+            return self.reload_crashed_document(browsing_context_id, pipeline_id, load_data);
+        }
+        let msg = ConstellationControlMsg::Reload(pipeline_id);
+        let result = pipeline.event_loop.send(msg);
         if let Err(e) = result {
             self.handle_send_error(pipeline_id, e);
         }
     }
 
+    /// Load the document which crashed in place of the crash page, keeping the session
+    /// history of the browsing context.
+    fn reload_crashed_document(
+        &mut self,
+        browsing_context_id: BrowsingContextId,
+        crash_pipeline_id: PipelineId,
+        load_data: LoadData,
+    ) {
+        let browsing_context = match self.browsing_contexts.get(&browsing_context_id) {
+            Some(browsing_context) => browsing_context,
+            None => return warn!("{}: Reloading after closure", browsing_context_id),
+        };
+        let top_level_browsing_context_id = browsing_context.top_level_id;
+        let parent_pipeline_id = browsing_context.parent_pipeline_id;
+        let window_size = browsing_context.size;
+        let is_private = browsing_context.is_private;
+        let is_visible = browsing_context.is_visible;
+        let opener = self
+            .pipelines
+            .get(&crash_pipeline_id)
+            .and_then(|pipeline| pipeline.opener);
+
+        let new_pipeline_id = PipelineId::new();
+        self.new_pipeline(
+            new_pipeline_id,
+            browsing_context_id,
+            top_level_browsing_context_id,
+            parent_pipeline_id,
+            opener,
+            window_size,
+            load_data,
+            IFrameSandboxState::IFrameUnsandboxed,
+            is_private,
+            is_visible,
+        );
+        self.add_pending_change(SessionHistoryChange {
+            top_level_browsing_context_id,
+            browsing_context_id,
+            new_pipeline_id,
+            replace: Some(NeedsToReload::No(crash_pipeline_id)),
+            new_browsing_context_info: None,
+            window_size,
+        });
+    }
+
     fn handle_post_message_msg(
         &mut self,
         browsing_context_id: BrowsingContextId,
@@ -4468,6 +5722,21 @@ where
                 self.compositor_proxy
                     .send(CompositorMsg::CreatePng(rect, response_sender));
             },
+            WebDriverCommandMsg::SetNetworkThrottling(
+                top_level_browsing_context_id,
+                throttling,
+            ) => {
+                self.handle_set_network_throttling(top_level_browsing_context_id, throttling);
+            },
+            WebDriverCommandMsg::SetUserAgentOverride(
+                top_level_browsing_context_id,
+                user_agent_override,
+            ) => {
+                self.handle_set_user_agent_override(
+                    top_level_browsing_context_id,
+                    user_agent_override,
+                );
+            },
         }
     }
 
@@ -4477,12 +5746,18 @@ where
         visible: bool,
     ) {
         let browsing_context_id = BrowsingContextId::from(top_level_browsing_context_id);
-        let pipeline_id = match self.browsing_contexts.get(&browsing_context_id) {
-            Some(browsing_context) => browsing_context.pipeline_id,
+        let pipeline_id = match self.browsing_contexts.get_mut(&browsing_context_id) {
+            Some(browsing_context) => {
+                browsing_context.is_visible = visible;
+                browsing_context.pipeline_id
+            },
             None => {
                 return warn!("{browsing_context_id}: Tried to notify visibility after closure");
             },
         };
+        if visible {
+            self.set_webview_frozen(top_level_browsing_context_id, false);
+        }
         match self.pipelines.get(&pipeline_id) {
             None => return warn!("{pipeline_id}: Tried to notify visibility after closure"),
             Some(pipeline) => pipeline.notify_visibility(visible),
@@ -4834,20 +6109,30 @@ where
             pipelines_to_evict
         };
 
+        self.discard_pipelines(top_level_browsing_context_id, pipelines_to_evict);
+    }
+
+    /// Close inactive pipelines of the session history of a webview, replacing them with
+    /// entries that reload their documents, which then know they were discarded.
+    fn discard_pipelines(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+        pipeline_ids: Vec<PipelineId>,
+    ) {
         let mut dead_pipelines = vec![];
-        for evicted_id in pipelines_to_evict {
-            let load_data = match self.pipelines.get(&evicted_id) {
+        for discarded_id in pipeline_ids {
+            let load_data = match self.pipelines.get(&discarded_id) {
                 Some(pipeline) => {
-                    let mut load_data = pipeline.load_data.clone();
-                    load_data.url = pipeline.url.clone();
+                    let mut load_data = pipeline.load_data_for_active_entry();
+                    load_data.was_discarded = true;
                     load_data
                 },
                 None => continue,
             };
 
-            dead_pipelines.push((evicted_id, NeedsToReload::Yes(evicted_id, load_data)));
+            dead_pipelines.push((discarded_id, NeedsToReload::Yes(discarded_id, load_data)));
             self.close_pipeline(
-                evicted_id,
+                discarded_id,
                 DiscardBrowsingContext::No,
                 ExitPipelineMode::Normal,
             );
@@ -5400,6 +6685,10 @@ where
         &mut self,
         top_level_browsing_context_id: TopLevelBrowsingContextId,
     ) {
+        // The background pages of extensions are never displayed.
+        if self.is_extension_background_page(top_level_browsing_context_id) {
+            return;
+        }
         // Only send the frame tree if the given webview is focused.
         if let Some(focused_webview_id) = self.webviews.focused_webview().map(|(id, _)| id) {
             if top_level_browsing_context_id != focused_webview_id {
@@ -5477,4 +6766,370 @@ where
             },
         }
     }
+
+    fn handle_add_user_stylesheet_msg(
+        &mut self,
+        top_level_browsing_context_id: Option<TopLevelBrowsingContextId>,
+        stylesheet: UserStylesheet,
+    ) {
+        for pipeline_id in self.user_stylesheet_pipelines(top_level_browsing_context_id) {
+            let msg = ConstellationControlMsg::AddUserStylesheet(pipeline_id, stylesheet.clone());
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+        self.user_stylesheets
+            .push((top_level_browsing_context_id, stylesheet));
+    }
+
+    fn handle_remove_user_stylesheet_msg(&mut self, stylesheet_id: UserStylesheetId) {
+        let index = match self
+            .user_stylesheets
+            .iter()
+            .position(|(_, stylesheet)| stylesheet.id == stylesheet_id)
+        {
+            Some(index) => index,
+            None => return warn!("Attempted to remove unknown user stylesheet"),
+        };
+        let (top_level_browsing_context_id, _) = self.user_stylesheets.remove(index);
+        for pipeline_id in self.user_stylesheet_pipelines(top_level_browsing_context_id) {
+            let msg = ConstellationControlMsg::RemoveUserStylesheet(pipeline_id, stylesheet_id);
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+    }
+
+    /// The pipelines that a user stylesheet registered for the given top-level browsing
+    /// context applies to.
+    fn user_stylesheet_pipelines(
+        &self,
+        top_level_browsing_context_id: Option<TopLevelBrowsingContextId>,
+    ) -> Vec<PipelineId> {
+        self.pipelines
+            .values()
+            .filter(|pipeline| {
+                top_level_browsing_context_id
+                    .map_or(true, |id| id == pipeline.top_level_browsing_context_id)
+            })
+            .map(|pipeline| pipeline.id)
+            .collect()
+    }
+
+    /// Send the user stylesheets and content scripts that apply to a newly created pipeline.
+    fn send_user_content_to_pipeline(&mut self, pipeline_id: PipelineId) {
+        let top_level_browsing_context_id = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.top_level_browsing_context_id,
+            None => return,
+        };
+        let stylesheets: Vec<UserStylesheet> = self
+            .user_stylesheets
+            .iter()
+            .filter(|(id, _)| id.map_or(true, |id| id == top_level_browsing_context_id))
+            .map(|(_, stylesheet)| stylesheet.clone())
+            .collect();
+        for stylesheet in stylesheets {
+            let msg = ConstellationControlMsg::AddUserStylesheet(pipeline_id, stylesheet);
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+
+        let content_scripts: Vec<ContentScript> = self
+            .content_scripts
+            .iter()
+            .filter(|(id, _)| id.map_or(true, |id| id == top_level_browsing_context_id))
+            .map(|(_, script)| script.clone())
+            .collect();
+        for content_script in content_scripts {
+            let msg = ConstellationControlMsg::AddContentScript(pipeline_id, content_script);
+            self.send_to_pipeline(pipeline_id, msg);
+        }
+    }
+
+    fn send_to_pipeline(&mut self, pipeline_id: PipelineId, msg: ConstellationControlMsg) {
+        let result = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline.event_loop.send(msg),
+            None => return debug!("{}: Sending message after closure", pipeline_id),
+        };
+        if let Err(e) = result {
+            self.handle_send_error(pipeline_id, e);
+        }
+    }
+
+    fn handle_load_extension_msg(&mut self, extension: Extension) {
+        let extension_id = extension.id;
+        debug!("Loading extension {} ({})", extension.name, extension_id);
+        self.content_scripts.extend(
+            extension
+                .content_scripts
+                .iter()
+                .map(|content_script| (None, content_script.clone())),
+        );
+
+        // The background script runs in a document of its own, which is never
+        // displayed. The extension must be registered before that document is
+        // created, so that it is recognised as a background page.
+        let background_page = extension
+            .background_script
+            .as_ref()
+            .map(|_| TopLevelBrowsingContextId::new());
+        self.extensions.insert(
+            extension_id,
+            LoadedExtension::new(
+                extension,
+                background_page,
+                self.extension_storage_directory.as_deref(),
+            ),
+        );
+        if let Some(top_level_browsing_context_id) = background_page {
+            let url = ServoUrl::parse("about:blank").expect("about:blank is a valid URL");
+            self.new_top_level_browsing_context(url, top_level_browsing_context_id, false);
+        }
+    }
+
+    fn handle_unload_extension_msg(&mut self, extension_id: ExtensionId) {
+        let background_page = match self.extensions.get(&extension_id) {
+            Some(loaded) => loaded.background_page,
+            None => return warn!("Unloading unknown extension {}", extension_id),
+        };
+        if let Some(top_level_browsing_context_id) = background_page {
+            self.handle_close_top_level_browsing_context(top_level_browsing_context_id);
+        }
+        self.extensions.remove(&extension_id);
+        self.content_scripts
+            .retain(|(_, script)| script.world != JsWorld::Extension(extension_id));
+    }
+
+    fn is_extension_background_page(
+        &self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> bool {
+        self.extensions
+            .values()
+            .any(|loaded| loaded.background_page == Some(top_level_browsing_context_id))
+    }
+
+    fn handle_extension_request(
+        &mut self,
+        source_pipeline_id: PipelineId,
+        extension_id: ExtensionId,
+        request: ExtensionRequest,
+    ) {
+        // Scripts say which extension they act for, since a document may have the
+        // content scripts of several extensions injected, but that is only trusted if
+        // the document is one the extension runs scripts in.
+        let rejection = match self.extensions.get(&extension_id) {
+            None => Some(ExtensionApiError::NoSuchExtension),
+            Some(loaded) if !self.runs_extension_scripts(source_pipeline_id, loaded) => {
+                warn!(
+                    "{}: Extension request on behalf of {}, whose scripts do not run there",
+                    source_pipeline_id, extension_id
+                );
+                Some(ExtensionApiError::NotExtensionContext)
+            },
+            Some(_) => None,
+        };
+        if let Some(error) = rejection {
+            if let Err(e) = request.reject(error) {
+                warn!(
+                    "{}: Failed to reject extension request ({:?})",
+                    source_pipeline_id, e
+                );
+            }
+            return;
+        }
+
+        let result = match request {
+            ExtensionRequest::SendMessage(target, data, response_sender) => {
+                // Scripts never receive their own messages.
+                let target = self
+                    .extension_message_target(extension_id, target)
+                    .and_then(|target_pipeline_id| {
+                        if target_pipeline_id == source_pipeline_id {
+                            return Err(ExtensionApiError::NoReceivingEnd);
+                        }
+                        Ok(target_pipeline_id)
+                    });
+                match target {
+                    Ok(target_pipeline_id) => {
+                        let message =
+                            self.extension_message(source_pipeline_id, extension_id, data);
+                        let msg = ConstellationControlMsg::DispatchExtensionMessage(
+                            target_pipeline_id,
+                            extension_id,
+                            message,
+                            response_sender,
+                        );
+                        self.send_to_pipeline(target_pipeline_id, msg);
+                        Ok(())
+                    },
+                    Err(error) => response_sender.send(Err(error)),
+                }
+            },
+            ExtensionRequest::QueryTabs(query, response_sender) => {
+                response_sender.send(self.query_extension_tabs(extension_id, query))
+            },
+            ExtensionRequest::StorageGet(keys, response_sender) => response_sender.send(
+                self.extension_storage(extension_id)
+                    .map(|loaded| loaded.storage_get(keys)),
+            ),
+            ExtensionRequest::StorageSet(items, response_sender) => response_sender.send(
+                self.extension_storage(extension_id)
+                    .map(|loaded| loaded.storage_set(items)),
+            ),
+            ExtensionRequest::StorageRemove(keys, response_sender) => response_sender.send(
+                self.extension_storage(extension_id)
+                    .map(|loaded| loaded.storage_remove(keys)),
+            ),
+            ExtensionRequest::StorageClear(response_sender) => response_sender.send(
+                self.extension_storage(extension_id)
+                    .map(|loaded| loaded.storage_clear()),
+            ),
+        };
+        if let Err(e) = result {
+            warn!(
+                "{}: Failed to reply to extension request ({:?})",
+                source_pipeline_id, e
+            );
+        }
+    }
+
+    /// Whether the scripts of `loaded` may run in `pipeline_id`: the document of the
+    /// background page of the extension, or a document its content scripts are injected
+    /// into.
+    fn runs_extension_scripts(&self, pipeline_id: PipelineId, loaded: &LoadedExtension) -> bool {
+        let pipeline = match self.pipelines.get(&pipeline_id) {
+            Some(pipeline) => pipeline,
+            None => return false,
+        };
+        let is_top_level = pipeline.browsing_context_id ==
+            BrowsingContextId::from(pipeline.top_level_browsing_context_id);
+        if loaded.background_page == Some(pipeline.top_level_browsing_context_id) {
+            return is_top_level;
+        }
+        loaded
+            .extension
+            .injects_content_scripts_into(&pipeline.url, is_top_level)
+    }
+
+    /// The extension whose storage is being accessed, if it may access it.
+    fn extension_storage(
+        &mut self,
+        extension_id: ExtensionId,
+    ) -> ExtensionApiResult<&mut LoadedExtension> {
+        let loaded = self
+            .extensions
+            .get_mut(&extension_id)
+            .ok_or(ExtensionApiError::NoSuchExtension)?;
+        if !loaded.extension.has_permission(STORAGE_PERMISSION) {
+            return Err(ExtensionApiError::MissingPermission(
+                STORAGE_PERMISSION.to_owned(),
+            ));
+        }
+        Ok(loaded)
+    }
+
+    /// The pipeline whose scripts receive a message sent by an extension to `target`.
+    fn extension_message_target(
+        &self,
+        extension_id: ExtensionId,
+        target: ExtensionMessageTarget,
+    ) -> ExtensionApiResult<PipelineId> {
+        let loaded = self
+            .extensions
+            .get(&extension_id)
+            .ok_or(ExtensionApiError::NoSuchExtension)?;
+        let top_level_browsing_context_id = match target {
+            ExtensionMessageTarget::Background => loaded
+                .background_page
+                .ok_or(ExtensionApiError::NoReceivingEnd)?,
+            ExtensionMessageTarget::Tab(tab_id) => self
+                .extension_tab_ids
+                .top_level_browsing_context_for(tab_id)
+                .filter(|id| self.webviews.get(*id).is_some())
+                .ok_or(ExtensionApiError::NoSuchTab(tab_id))?,
+        };
+        self.browsing_contexts
+            .get(&BrowsingContextId::from(top_level_browsing_context_id))
+            .map(|browsing_context| browsing_context.pipeline_id)
+            .ok_or(ExtensionApiError::NoReceivingEnd)
+    }
+
+    /// Wrap a message sent by a script of an extension running in `source_pipeline_id`.
+    fn extension_message(
+        &mut self,
+        source_pipeline_id: PipelineId,
+        extension_id: ExtensionId,
+        data: StructuredSerializedData,
+    ) -> ExtensionMessage {
+        // Script messages from closed pipelines are dropped before getting here.
+        let pipeline = &self.pipelines[&source_pipeline_id];
+        let top_level_browsing_context_id = pipeline.top_level_browsing_context_id;
+        let sender_url = pipeline.url.clone();
+        let sender_tab = if self.is_extension_background_page(top_level_browsing_context_id) {
+            None
+        } else {
+            Some(self.extension_tab(extension_id, top_level_browsing_context_id))
+        };
+        ExtensionMessage {
+            data,
+            sender_tab,
+            sender_url,
+        }
+    }
+
+    /// Describe a top-level browsing context to an extension.
+    fn extension_tab(
+        &mut self,
+        extension_id: ExtensionId,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> ExtensionTab {
+        let can_see_contents = self.extensions.get(&extension_id).map_or(false, |loaded| {
+            loaded.extension.has_permission(TABS_PERMISSION)
+        });
+        let pipeline = self
+            .browsing_contexts
+            .get(&BrowsingContextId::from(top_level_browsing_context_id))
+            .and_then(|browsing_context| self.pipelines.get(&browsing_context.pipeline_id))
+            .filter(|_| can_see_contents);
+        ExtensionTab {
+            id: self.extension_tab_ids.id_for(top_level_browsing_context_id),
+            url: pipeline.map(|pipeline| pipeline.url.clone()),
+            title: pipeline.map(|pipeline| pipeline.title.clone()),
+            active: self.webviews.focused_webview().map(|(id, _)| id) ==
+                Some(top_level_browsing_context_id),
+        }
+    }
+
+    fn query_extension_tabs(
+        &mut self,
+        extension_id: ExtensionId,
+        query: ExtensionTabQuery,
+    ) -> ExtensionApiResult<Vec<ExtensionTab>> {
+        let loaded = self
+            .extensions
+            .get(&extension_id)
+            .ok_or(ExtensionApiError::NoSuchExtension)?;
+        // Without the `tabs` permission, the URLs of tabs are not revealed, not even
+        // by matching them.
+        if !query.url.is_empty() && !loaded.extension.has_permission(TABS_PERMISSION) {
+            return Err(ExtensionApiError::MissingPermission(
+                TABS_PERMISSION.to_owned(),
+            ));
+        }
+
+        let top_level_browsing_context_ids: Vec<_> = self
+            .webviews
+            .ids()
+            .filter(|id| !self.is_extension_background_page(*id))
+            .collect();
+        let mut tabs: Vec<ExtensionTab> = top_level_browsing_context_ids
+            .into_iter()
+            .map(|id| self.extension_tab(extension_id, id))
+            .filter(|tab| query.active.map_or(true, |active| tab.active == active))
+            .filter(|tab| {
+                query.url.is_empty() ||
+                    tab.url.as_ref().map_or(false, |url| {
+                        query.url.iter().any(|pattern| pattern.matches(url))
+                    })
+            })
+            .collect();
+        tabs.sort_by_key(|tab| tab.id);
+        Ok(tabs)
+    }
 }
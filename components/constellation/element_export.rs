@@ -0,0 +1,277 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Rendering of an element of a webview into a PNG image or a PDF document, for embedders
+//! rendering charts or receipts with Servo.
+
+use std::io::Cursor;
+use std::thread;
+use std::time::Duration;
+
+use compositing_traits::{CompositorMsg, CompositorProxy};
+use embedder_traits::{ElementExportError, ElementExportFormat, ElementExportRequest};
+use euclid::Rect;
+use image::{DynamicImage, ImageFormat, RgbImage};
+use ipc_channel::ipc::{self, IpcSender};
+use log::warn;
+use msg::constellation_msg::PipelineId;
+use net_traits::image::base::Image;
+use script_traits::webdriver_msg::WebDriverScriptCommand;
+use script_traits::{ConstellationControlMsg, WindowSizeData};
+use style_traits::CSSPixel;
+
+/// How long to wait before rendering the element again, while the page is not stable.
+const RENDER_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How many times to try rendering the element before giving up.
+const RENDER_ATTEMPTS: u32 = 300;
+
+/// The number of PDF points in a CSS pixel.
+const POINTS_PER_CSS_PIXEL: f32 = 0.75;
+
+/// Render the first element matching the selector of `request` in the document of the
+/// pipeline, shown in a window of size `window_size`, on another thread, once the page is
+/// stable, and call `done` with the result.
+pub fn export_element<F>(
+    pipeline_id: PipelineId,
+    script_chan: IpcSender<ConstellationControlMsg>,
+    compositor_proxy: CompositorProxy,
+    window_size: WindowSizeData,
+    request: ElementExportRequest,
+    done: F,
+) where
+    F: FnOnce(Result<Vec<u8>, ElementExportError>) + Send + 'static,
+{
+    let spawned = thread::Builder::new()
+        .name("ElementExport".to_owned())
+        .spawn(move || {
+            done(render_element(
+                pipeline_id,
+                &script_chan,
+                &compositor_proxy,
+                window_size,
+                &request,
+            ))
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to spawn the element export thread ({:?}).", e);
+    }
+}
+
+fn render_element(
+    pipeline_id: PipelineId,
+    script_chan: &IpcSender<ConstellationControlMsg>,
+    compositor_proxy: &CompositorProxy,
+    window_size: WindowSizeData,
+    request: &ElementExportRequest,
+) -> Result<Vec<u8>, ElementExportError> {
+    let send = |command| {
+        script_chan
+            .send(ConstellationControlMsg::WebDriverScriptCommand(
+                pipeline_id,
+                command,
+            ))
+            .map_err(|_| ElementExportError::NoSuchWebView)
+    };
+
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    send(WebDriverScriptCommand::FindElementCSS(
+        request.selector.clone(),
+        sender,
+    ))?;
+    let element = match receiver.recv() {
+        Ok(Ok(Some(element))) => element,
+        Ok(Ok(None)) => return Err(ElementExportError::NoSuchElement),
+        Ok(Err(_)) => return Err(ElementExportError::InvalidSelector),
+        Err(_) => return Err(ElementExportError::NoSuchWebView),
+    };
+
+    // Only what is in the viewport is rendered.
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    send(WebDriverScriptCommand::ScrollIntoView(
+        element.clone(),
+        sender,
+    ))?;
+    match receiver.recv() {
+        Ok(Ok(())) => {},
+        Ok(Err(_)) => return Err(ElementExportError::NoSuchElement),
+        Err(_) => return Err(ElementExportError::NoSuchWebView),
+    }
+
+    // Let the callbacks of the next animation frame run, so that the element is rendered
+    // as they leave it.
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    send(WebDriverScriptCommand::WaitForAnimationFrame(sender))?;
+    receiver
+        .recv()
+        .map_err(|_| ElementExportError::NoSuchWebView)?;
+
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    send(WebDriverScriptCommand::GetBoundingClientRect(
+        element, sender,
+    ))?;
+    let rect = match receiver.recv() {
+        Ok(Ok(rect)) => Rect::from_untyped(&rect),
+        Ok(Err(_)) => return Err(ElementExportError::NoSuchElement),
+        Err(_) => return Err(ElementExportError::NoSuchWebView),
+    };
+    if rect.is_empty() {
+        return Err(ElementExportError::EmptyElement);
+    }
+
+    // The framebuffer keeps its size in device pixels, so the larger the scale, the less of
+    // the page fits in it. Half a pixel is allowed for the rounding of the scroll position.
+    let device_pixel_ratio = window_size.device_pixel_ratio.get();
+    let scale = request.scale.unwrap_or(device_pixel_ratio);
+    let visible = Rect::from_size(window_size.initial_viewport * (device_pixel_ratio / scale));
+    if !visible.inflate(0.5, 0.5).contains_rect(&rect) {
+        return Err(ElementExportError::DoesNotFit);
+    }
+
+    let rescale = scale != device_pixel_ratio;
+    if rescale {
+        compositor_proxy.send(CompositorMsg::SetCaptureScale(Some(scale)));
+    }
+    let rendered = render_stable_image(compositor_proxy, Some(rect));
+    if rescale {
+        compositor_proxy.send(CompositorMsg::SetCaptureScale(None));
+    }
+    let image = rgb_image(rendered.ok_or(ElementExportError::Timeout)?)
+        .ok_or(ElementExportError::EncodingFailed)?;
+
+    let (width, height) = image.dimensions();
+    let png = encode_png(image).ok_or(ElementExportError::EncodingFailed)?;
+
+    Ok(match request.format {
+        ElementExportFormat::Png => png,
+        ElementExportFormat::Pdf => pdf_with_png(
+            &png,
+            width,
+            height,
+            rect.size.width * POINTS_PER_CSS_PIXEL,
+            rect.size.height * POINTS_PER_CSS_PIXEL,
+        ),
+    })
+}
+
+/// Render the page, or the given part of it, once it is stable: its fonts and images are
+/// loaded, and it has no running animations. `None` if it does not become stable in time.
+pub(crate) fn render_stable_image(
+    compositor_proxy: &CompositorProxy,
+    rect: Option<Rect<f32, CSSPixel>>,
+) -> Option<Image> {
+    for _ in 0..RENDER_ATTEMPTS {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        compositor_proxy.send(CompositorMsg::CreatePng(rect, sender));
+        if let Ok(Some(image)) = receiver.recv() {
+            return Some(image);
+        }
+        thread::sleep(RENDER_RETRY_INTERVAL);
+    }
+    None
+}
+
+/// The image rendered by the compositor, which always sends RGB pixels.
+pub(crate) fn rgb_image(rendered: Image) -> Option<RgbImage> {
+    RgbImage::from_raw(rendered.width, rendered.height, rendered.bytes.to_vec())
+}
+
+pub(crate) fn encode_png(image: RgbImage) -> Option<Vec<u8>> {
+    let mut png = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(image)
+        .write_to(&mut png, ImageFormat::Png)
+        .ok()?;
+    Some(png.into_inner())
+}
+
+/// The data of the `IDAT` chunks of a PNG image, which together are a zlib stream of the
+/// filtered scanlines of the image.
+fn png_image_data(png: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    // Skip the PNG signature.
+    let mut chunks = png.get(8..).unwrap_or_default();
+    while chunks.len() >= 12 {
+        let length = u32::from_be_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+        if chunks.len() < length + 12 {
+            break;
+        }
+        if &chunks[4..8] == b"IDAT" {
+            data.extend_from_slice(&chunks[8..8 + length]);
+        }
+        chunks = &chunks[length + 12..];
+    }
+    data
+}
+
+/// A PDF document with a single page of the given size in points, filled with the image
+/// of the given size in pixels. The compressed data of the PNG image is used as it is, PDF
+/// readers undoing the PNG filters of its scanlines.
+fn pdf_with_png(png: &[u8], width: u32, height: u32, page_width: f32, page_height: f32) -> Vec<u8> {
+    let image_data = png_image_data(png);
+    let content = format!(
+        "q {:.2} 0 0 {:.2} 0 0 cm /Image Do Q",
+        page_width, page_height
+    );
+    let objects = [
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {:.2} {:.2}] \
+             /Resources << /XObject << /Image 4 0 R >> >> /Contents 5 0 R >>",
+            page_width, page_height
+        )
+        .into_bytes(),
+        pdf_stream(
+            format!(
+                "<< /Type /XObject /Subtype /Image /Width {} /Height {} \
+                 /ColorSpace /DeviceRGB /BitsPerComponent 8 /Filter /FlateDecode \
+                 /DecodeParms << /Predictor 15 /Colors 3 /BitsPerComponent 8 /Columns {} >> \
+                 /Length {} >>",
+                width,
+                height,
+                width,
+                image_data.len()
+            ),
+            &image_data,
+        ),
+        pdf_stream(
+            format!("<< /Length {} >>", content.len()),
+            content.as_bytes(),
+        ),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", index + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    // Each entry of the cross-reference table is exactly 20 bytes long.
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n", objects.len() + 1).as_bytes());
+    pdf.extend_from_slice(b"0000000000 65535 f \n");
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    pdf
+}
+
+fn pdf_stream(dictionary: String, data: &[u8]) -> Vec<u8> {
+    let mut object = dictionary.into_bytes();
+    object.extend_from_slice(b"\nstream\n");
+    object.extend_from_slice(data);
+    object.extend_from_slice(b"\nendstream");
+    object
+}
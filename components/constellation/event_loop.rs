@@ -16,6 +16,9 @@ use script_traits::ConstellationControlMsg;
 /// <https://html.spec.whatwg.org/multipage/#event-loop>
 pub struct EventLoop {
     script_chan: IpcSender<ConstellationControlMsg>,
+    /// Whether the script thread uses the resource threads of private browsing, which it
+    /// hands to every document it runs.
+    is_private: bool,
     dont_send_or_sync: PhantomData<Rc<()>>,
 }
 
@@ -29,13 +32,19 @@ impl Drop for EventLoop {
 
 impl EventLoop {
     /// Create a new event loop from the channel to its script thread.
-    pub fn new(script_chan: IpcSender<ConstellationControlMsg>) -> Rc<EventLoop> {
+    pub fn new(script_chan: IpcSender<ConstellationControlMsg>, is_private: bool) -> Rc<EventLoop> {
         Rc::new(EventLoop {
             script_chan: script_chan,
+            is_private,
             dont_send_or_sync: PhantomData,
         })
     }
 
+    /// Whether the event loop can only run documents of private browsing.
+    pub fn is_private(&self) -> bool {
+        self.is_private
+    }
+
     /// Send a message to the event loop.
     pub fn send(&self, msg: ConstellationControlMsg) -> Result<(), Error> {
         self.script_chan.send(msg)
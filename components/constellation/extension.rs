@@ -0,0 +1,249 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The constellation's view of the extensions loaded by the embedder.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use msg::constellation_msg::TopLevelBrowsingContextId;
+use script_traits::extension::{Extension, ExtensionTabId};
+use script_traits::StructuredSerializedData;
+
+/// An extension loaded by the embedder, along with the state of the extension APIs
+/// that is kept by the constellation.
+pub struct LoadedExtension {
+    /// The extension itself.
+    pub extension: Extension,
+
+    /// The hidden top-level browsing context whose document hosts the background
+    /// script of the extension, if it has one.
+    pub background_page: Option<TopLevelBrowsingContextId>,
+
+    /// The contents of `browser.storage.local`.
+    storage: HashMap<String, StructuredSerializedData>,
+
+    /// The file `storage` is kept in, if it outlives the extension being unloaded.
+    storage_path: Option<PathBuf>,
+}
+
+impl LoadedExtension {
+    /// Register a newly loaded extension, reading its `browser.storage.local` from
+    /// `storage_directory` if given.
+    pub fn new(
+        extension: Extension,
+        background_page: Option<TopLevelBrowsingContextId>,
+        storage_directory: Option<&Path>,
+    ) -> LoadedExtension {
+        let storage_path =
+            storage_directory.map(|directory| directory.join(storage_file_name(&extension)));
+        let storage = match storage_path.as_deref().map(read_storage) {
+            Some(Ok(storage)) => storage,
+            Some(Err(error)) if error.kind() != io::ErrorKind::NotFound => {
+                warn!(
+                    "Failed to read the storage of extension {:?} ({:?})",
+                    extension.name, error
+                );
+                HashMap::new()
+            },
+            _ => HashMap::new(),
+        };
+        LoadedExtension {
+            extension,
+            background_page,
+            storage,
+            storage_path,
+        }
+    }
+
+    /// The stored values for `keys`, or all of them if `keys` is `None`.
+    pub fn storage_get(
+        &self,
+        keys: Option<Vec<String>>,
+    ) -> Vec<(String, StructuredSerializedData)> {
+        match keys {
+            Some(keys) => keys
+                .into_iter()
+                .filter_map(|key| {
+                    let value = self.storage.get(&key)?.clone_for_broadcast();
+                    Some((key, value))
+                })
+                .collect(),
+            None => self
+                .storage
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone_for_broadcast()))
+                .collect(),
+        }
+    }
+
+    pub fn storage_set(&mut self, items: Vec<(String, StructuredSerializedData)>) {
+        self.storage.extend(items);
+        self.write_storage();
+    }
+
+    pub fn storage_remove(&mut self, keys: Vec<String>) {
+        for key in keys {
+            self.storage.remove(&key);
+        }
+        self.write_storage();
+    }
+
+    pub fn storage_clear(&mut self) {
+        self.storage.clear();
+        self.write_storage();
+    }
+
+    /// Replace the file the storage is kept in with its current contents, so that a
+    /// crash while doing so leaves either the old contents or the new ones.
+    fn write_storage(&self) {
+        let path = match self.storage_path {
+            Some(ref path) => path,
+            None => return,
+        };
+        let write = || -> io::Result<()> {
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory)?;
+            }
+            let temporary_path = path.with_extension("storage.tmp");
+            let mut writer = BufWriter::new(File::create(&temporary_path)?);
+            bincode::serialize_into(&mut writer, &self.storage)
+                .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+            writer.into_inner()?.sync_all()?;
+            fs::rename(&temporary_path, path)
+        };
+        if let Err(error) = write() {
+            warn!(
+                "Failed to write the storage of extension {:?} ({:?})",
+                self.extension.name, error
+            );
+        }
+    }
+}
+
+/// The name of the file the storage of `extension` is kept in. Extensions are given a
+/// new id each time they are loaded, so the file is named after the extension instead.
+fn storage_file_name(extension: &Extension) -> String {
+    let mut name = String::new();
+    for byte in extension.name.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'-' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("_{:02x}", byte));
+        }
+    }
+    name.push_str(".storage");
+    name
+}
+
+fn read_storage(path: &Path) -> io::Result<HashMap<String, StructuredSerializedData>> {
+    let reader = BufReader::new(File::open(path)?);
+    bincode::deserialize_from(reader).map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+}
+
+/// The ids by which extensions know the top-level browsing contexts, since those
+/// have to be plain numbers in the extension APIs. Ids are handed out the first
+/// time a tab is described to an extension, and are never reused.
+#[derive(Default)]
+pub struct ExtensionTabIds {
+    ids: HashMap<TopLevelBrowsingContextId, ExtensionTabId>,
+    next_id: ExtensionTabId,
+}
+
+impl ExtensionTabIds {
+    pub fn id_for(
+        &mut self,
+        top_level_browsing_context_id: TopLevelBrowsingContextId,
+    ) -> ExtensionTabId {
+        let next_id = &mut self.next_id;
+        *self
+            .ids
+            .entry(top_level_browsing_context_id)
+            .or_insert_with(|| {
+                *next_id += 1;
+                *next_id
+            })
+    }
+
+    pub fn top_level_browsing_context_for(
+        &self,
+        tab_id: ExtensionTabId,
+    ) -> Option<TopLevelBrowsingContextId> {
+        self.ids
+            .iter()
+            .find(|(_, id)| **id == tab_id)
+            .map(|(top_level_browsing_context_id, _)| *top_level_browsing_context_id)
+    }
+
+    pub fn remove(&mut self, top_level_browsing_context_id: TopLevelBrowsingContextId) {
+        self.ids.remove(&top_level_browsing_context_id);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use script_traits::extension::Extension;
+    use script_traits::StructuredSerializedData;
+
+    use crate::extension::LoadedExtension;
+
+    fn extension() -> Extension {
+        let manifest = r#"{
+            "manifest_version": 3,
+            "name": "Example / Extension",
+            "version": "1.0",
+            "permissions": ["storage"]
+        }"#;
+        Extension::from_manifest(manifest, |name: &str| panic!("Unexpected file {}", name))
+            .expect("Incorrect test case")
+    }
+
+    fn data(serialized: Vec<u8>) -> StructuredSerializedData {
+        StructuredSerializedData {
+            serialized,
+            blobs: None,
+            ports: None,
+            offscreen_canvases: None,
+            image_bitmaps: None,
+        }
+    }
+
+    #[test]
+    fn test_storage_survives_reload() {
+        let directory = tempfile::tempdir().expect("Failed to create a directory");
+
+        let mut loaded = LoadedExtension::new(extension(), None, Some(directory.path()));
+        loaded.storage_set(vec![
+            ("kept".to_owned(), data(vec![1, 2])),
+            ("removed".to_owned(), data(vec![3])),
+        ]);
+        loaded.storage_remove(vec!["removed".to_owned()]);
+        drop(loaded);
+
+        // The extension is given a new id when it is loaded again.
+        let mut loaded = LoadedExtension::new(extension(), None, Some(directory.path()));
+        let stored = loaded.storage_get(None);
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].0, "kept");
+        assert_eq!(stored[0].1.serialized, vec![1, 2]);
+
+        loaded.storage_clear();
+        drop(loaded);
+        let loaded = LoadedExtension::new(extension(), None, Some(directory.path()));
+        assert!(loaded.storage_get(None).is_empty());
+    }
+
+    #[test]
+    fn test_storage_without_directory() {
+        let mut loaded = LoadedExtension::new(extension(), None, None);
+        loaded.storage_set(vec![("key".to_owned(), data(vec![1]))]);
+        assert_eq!(loaded.storage_get(Some(vec!["key".to_owned()])).len(), 1);
+        assert!(loaded
+            .storage_get(Some(vec!["other".to_owned()]))
+            .is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Snapshots of the frame tree, with what each document in it uses of the event loop
+//! running it, for the embedder and for the `servo:processes` page.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use embedder_traits::{
+    BrowsingContextSnapshot, FrameTreeSnapshot, PipelineSnapshot, PipelineSnapshotState,
+    PipelineUsage,
+};
+use ipc_channel::ipc::IpcReceiver;
+use log::warn;
+use msg::constellation_msg::PipelineId;
+use net_traits::{
+    FetchMetadata, FetchResponseMsg, Metadata, ResourceFetchTiming, ResourceTimingType,
+};
+use servo_url::ServoUrl;
+
+/// How long to wait for the event loops to report what their documents use.
+const USAGE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The URL of the page showing a snapshot of the frame tree.
+pub const PROCESSES_PAGE_URL: &str = "servo:processes";
+
+/// Wait on another thread for the given number of event loops to report what their
+/// documents use, or for the timeout, and complete the snapshot with it.
+pub fn collect_usage<F>(
+    mut snapshot: FrameTreeSnapshot,
+    receiver: IpcReceiver<Vec<(PipelineId, PipelineUsage)>>,
+    event_loops: usize,
+    done: F,
+) where
+    F: FnOnce(FrameTreeSnapshot) + Send + 'static,
+{
+    let spawned = thread::Builder::new()
+        .name("FrameTreeSnapshot".to_owned())
+        .spawn(move || {
+            let deadline = Instant::now() + USAGE_TIMEOUT;
+            let mut usage = HashMap::new();
+            for _ in 0..event_loops {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                match receiver.try_recv_timeout(timeout) {
+                    Ok(reports) => usage.extend(reports),
+                    Err(_) => break,
+                }
+            }
+            for browsing_context in &mut snapshot.top_level_browsing_contexts {
+                set_usage(browsing_context, &mut usage);
+            }
+            done(snapshot);
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to spawn the frame tree snapshot thread ({:?}).", e);
+    }
+}
+
+fn set_usage(
+    browsing_context: &mut BrowsingContextSnapshot,
+    usage: &mut HashMap<PipelineId, PipelineUsage>,
+) {
+    for pipeline in &mut browsing_context.pipelines {
+        pipeline.usage = usage.remove(&pipeline.id);
+        for child in &mut pipeline.children {
+            set_usage(child, usage);
+        }
+    }
+}
+
+/// Send the `servo:processes` page to the pipeline which navigated to it, as the response
+/// of its navigation request.
+pub fn send_processes_page(
+    pipeline_id: PipelineId,
+    url: ServoUrl,
+    snapshot: &FrameTreeSnapshot,
+    sender: &Sender<(PipelineId, FetchResponseMsg)>,
+) {
+    let mut metadata = Metadata::default(url);
+    metadata.set_content_type(Some(&mime::TEXT_HTML_UTF_8));
+    let messages = [
+        FetchResponseMsg::ProcessResponse(Ok(FetchMetadata::Unfiltered(metadata))),
+        FetchResponseMsg::ProcessResponseChunk(processes_page(snapshot).into_bytes()),
+        FetchResponseMsg::ProcessResponseEOF(Ok(ResourceFetchTiming::new(
+            ResourceTimingType::None,
+        ))),
+    ];
+    for message in messages {
+        if sender.send((pipeline_id, message)).is_err() {
+            return warn!("{}: Failed to send the processes page.", pipeline_id);
+        }
+    }
+}
+
+/// Render a snapshot of the frame tree as a page, with a table row per document.
+fn processes_page(snapshot: &FrameTreeSnapshot) -> String {
+    let mut page = String::from(
+        "<!DOCTYPE html>\n<html><head><title>Processes</title><style>\
+         table { border-collapse: collapse; } \
+         th, td { border: 1px solid #ccc; padding: 2px 6px; text-align: left; }\
+         </style></head><body>\n",
+    );
+    let mode = if snapshot.multiprocess {
+        "content processes"
+    } else {
+        "this process"
+    };
+    let _ = writeln!(page, "<p>The event loops run in {}.</p>", mode);
+    page.push_str(
+        "<table><tr><th>Document</th><th>State</th><th>Event loop</th><th>Process</th>\
+         <th>DOM memory</th><th>Task time</th><th>Task queue</th></tr>\n",
+    );
+    for browsing_context in &snapshot.top_level_browsing_contexts {
+        render_browsing_context(&mut page, browsing_context, 0);
+    }
+    page.push_str("</table></body></html>\n");
+    page
+}
+
+fn render_browsing_context(
+    page: &mut String,
+    browsing_context: &BrowsingContextSnapshot,
+    depth: usize,
+) {
+    for pipeline in &browsing_context.pipelines {
+        render_pipeline(page, browsing_context, pipeline, depth);
+        for child in &pipeline.children {
+            render_browsing_context(page, child, depth + 1);
+        }
+    }
+}
+
+fn render_pipeline(
+    page: &mut String,
+    browsing_context: &BrowsingContextSnapshot,
+    pipeline: &PipelineSnapshot,
+    depth: usize,
+) {
+    let mut state = match pipeline.state {
+        PipelineSnapshotState::Active => "active",
+        PipelineSnapshotState::Inactive => "inactive",
+        PipelineSnapshotState::Loading => "loading",
+    }
+    .to_owned();
+    if pipeline.crashed {
+        state.push_str(", crashed");
+    } else if !pipeline.completely_loaded {
+        state.push_str(", not loaded");
+    }
+    if browsing_context.is_private {
+        state.push_str(", private");
+    }
+    if !browsing_context.is_visible {
+        state.push_str(", hidden");
+    }
+    let (process, dom_memory, task_time, task_queue) = match pipeline.usage {
+        Some(ref usage) => {
+            let health = &usage.event_loop_health;
+            (
+                usage.process_id.to_string(),
+                format!("{} KiB", usage.dom_memory / 1024),
+                format!("{} ms", usage.task_time.as_millis()),
+                format!(
+                    "{} waiting, {} ms latency{}",
+                    health.queued_tasks,
+                    health.dequeue_latency.as_millis(),
+                    if health.saturated { ", saturated" } else { "" },
+                ),
+            )
+        },
+        None => (
+            "?".to_owned(),
+            "?".to_owned(),
+            "?".to_owned(),
+            "?".to_owned(),
+        ),
+    };
+    let _ = writeln!(
+        page,
+        "<tr><td style=\"padding-left: {}em\" title=\"{}\">{}<br><small>{}</small></td>\
+         <td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+        depth * 2 + 1,
+        pipeline.id,
+        escape(&pipeline.title),
+        escape(pipeline.url.as_str()),
+        state,
+        pipeline.event_loop,
+        process,
+        dom_memory,
+        task_time,
+        task_queue,
+    );
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -6,10 +6,15 @@
 
 mod browsingcontext;
 mod constellation;
+mod element_export;
 mod event_loop;
+mod extension;
+mod frame_tree;
 mod logging;
 mod network_listener;
 mod pipeline;
+mod prerender;
+mod process_manager;
 mod sandboxing;
 mod serviceworker;
 mod session_history;
@@ -33,7 +33,7 @@ use profile_traits::{mem as profile_mem, time};
 use script_layout_interface::{LayoutFactory, ScriptThreadFactory};
 use script_traits::{
     AnimationState, ConstellationControlMsg, DiscardBrowsingContext, DocumentActivity,
-    InitialScriptState, LayoutMsg, LoadData, NewLayoutInfo, SWManagerMsg,
+    HistoryEntryScrollState, InitialScriptState, LayoutMsg, LoadData, NewLayoutInfo, SWManagerMsg,
     ScriptToConstellationChan, TimerSchedulerMsg, WindowSizeData,
 };
 use serde::{Deserialize, Serialize};
@@ -44,7 +44,7 @@ use servo_url::ServoUrl;
 use webrender_api::DocumentId;
 
 use crate::event_loop::EventLoop;
-use crate::sandboxing::{spawn_multiprocess, UnprivilegedContent};
+use crate::sandboxing::{spawn_multiprocess, ContentProcess, UnprivilegedContent};
 
 /// A `Pipeline` is the constellation's view of a `Window`. Each pipeline has an event loop
 /// (executed by a script thread). A script thread may be responsible for many pipelines.
@@ -87,12 +87,19 @@ pub struct Pipeline {
     /// The history states owned by this pipeline.
     pub history_states: HashSet<HistoryStateId>,
 
+    /// The scroll state of the active session history entry of this pipeline, as last
+    /// reported by script.
+    pub scroll_state: HistoryEntryScrollState,
+
     /// Has this pipeline received a notification that it is completely loaded?
     pub completely_loaded: bool,
 
     /// The title of this pipeline's document.
     pub title: String,
 
+    /// Has this pipeline's document been frozen, its webview being in the background?
+    pub frozen: bool,
+
     /// The last compositor [`Epoch`] that was laid out in this pipeline if "exit after load" is
     /// enabled.
     pub layout_epoch: Epoch,
@@ -158,6 +165,9 @@ pub struct InitialPipelineState {
     /// Channels to the resource-related threads.
     pub resource_threads: ResourceThreads,
 
+    /// Whether `resource_threads` are the ones of private browsing.
+    pub is_private: bool,
+
     /// A channel to the time profiler thread.
     pub time_profiler_chan: time::ProfilerChan,
 
@@ -173,6 +183,10 @@ pub struct InitialPipelineState {
     /// The event loop to run in, if applicable.
     pub event_loop: Option<Rc<EventLoop>>,
 
+    /// A content process started ahead of time, to run the new event loop in if there is
+    /// no event loop to run in and in multiprocess mode.
+    pub content_process: Option<ContentProcess>,
+
     /// Information about the page to load.
     pub load_data: LoadData,
 
@@ -210,6 +224,9 @@ pub struct InitialPipelineState {
 pub struct NewPipeline {
     pub pipeline: Pipeline,
     pub bhm_control_chan: Option<IpcSender<BackgroundHangMonitorControlMsg>>,
+    /// A receiver of the content process started for the pipeline, if one was: it is sent
+    /// a message once the process shuts down as asked, and disconnects if it crashes.
+    pub lifeline: Option<IpcReceiver<()>>,
 }
 
 impl Pipeline {
@@ -219,7 +236,7 @@ impl Pipeline {
     ) -> Result<NewPipeline, Error> {
         // Note: we allow channel creation to panic, since recovering from this
         // probably requires a general low-memory strategy.
-        let (script_chan, bhm_control_chan) = match state.event_loop {
+        let (script_chan, bhm_control_chan, lifeline) = match state.event_loop {
             Some(script_chan) => {
                 let new_layout_info = NewLayoutInfo {
                     parent_info: state.parent_pipeline_id,
@@ -236,7 +253,7 @@ impl Pipeline {
                 {
                     warn!("Sending to script during pipeline creation failed ({})", e);
                 }
-                (script_chan, None)
+                (script_chan, None, None)
             },
             None => {
                 let (script_chan, script_port) = ipc::channel().expect("Pipeline script chan");
@@ -289,6 +306,7 @@ impl Pipeline {
                     mem_profiler_chan: state.mem_profiler_chan,
                     window_size: state.window_size,
                     layout_to_constellation_chan: state.layout_to_constellation_chan,
+                    lifeline: None,
                     script_chan: script_chan.clone(),
                     load_data: state.load_data.clone(),
                     script_port: script_port,
@@ -307,12 +325,18 @@ impl Pipeline {
                 // Spawn the child process.
                 //
                 // Yes, that's all there is to it!
-                let bhm_control_chan = if opts::multiprocess() {
+                let (bhm_control_chan, lifeline) = if opts::multiprocess() {
                     let (bhm_control_chan, bhm_control_port) =
                         ipc::channel().expect("Sampler chan");
                     unprivileged_pipeline_content.bhm_control_port = Some(bhm_control_port);
-                    let _ = unprivileged_pipeline_content.spawn_multiprocess()?;
-                    Some(bhm_control_chan)
+                    let (lifeline_sender, lifeline) = ipc::channel().expect("Lifeline chan");
+                    unprivileged_pipeline_content.lifeline = Some(lifeline_sender);
+                    match state.content_process {
+                        Some(process) => process
+                            .start(UnprivilegedContent::Pipeline(unprivileged_pipeline_content))?,
+                        None => unprivileged_pipeline_content.spawn_multiprocess()?,
+                    }
+                    (Some(bhm_control_chan), Some(lifeline))
                 } else {
                     // Should not be None in single-process mode.
                     let register = state
@@ -323,10 +347,14 @@ impl Pipeline {
                         state.layout_factory,
                         register,
                     );
-                    None
+                    (None, None)
                 };
 
-                (EventLoop::new(script_chan), bhm_control_chan)
+                (
+                    EventLoop::new(script_chan, state.is_private),
+                    bhm_control_chan,
+                    lifeline,
+                )
             },
         };
 
@@ -343,6 +371,7 @@ impl Pipeline {
         Ok(NewPipeline {
             pipeline,
             bhm_control_chan,
+            lifeline,
         })
     }
 
@@ -367,11 +396,13 @@ impl Pipeline {
             url: load_data.url.clone(),
             children: vec![],
             animation_state: AnimationState::NoAnimationsPresent,
-            load_data: load_data,
-            history_state_id: None,
+            history_state_id: load_data.history_state_id,
             history_states: HashSet::new(),
+            scroll_state: load_data.scroll_state.unwrap_or_default(),
+            load_data: load_data,
             completely_loaded: false,
             title: String::new(),
+            frozen: false,
             layout_epoch: Epoch(0),
         };
 
@@ -380,6 +411,16 @@ impl Pipeline {
         pipeline
     }
 
+    /// The load data to load the document of this pipeline again, in its active session
+    /// history entry.
+    pub fn load_data_for_active_entry(&self) -> LoadData {
+        let mut load_data = self.load_data.clone();
+        load_data.url = self.url.clone();
+        load_data.history_state_id = self.history_state_id;
+        load_data.scroll_state = Some(self.scroll_state);
+        load_data
+    }
+
     /// A normal exit of the pipeline, which waits for the compositor,
     /// and delegates layout shutdown to the script thread.
     pub fn exit(&self, discard_bc: DiscardBrowsingContext) {
@@ -481,6 +522,8 @@ pub struct UnprivilegedPipelineContent {
     background_hang_monitor_to_constellation_chan: IpcSender<HangMonitorAlert>,
     bhm_control_port: Option<IpcReceiver<BackgroundHangMonitorControlMsg>>,
     layout_to_constellation_chan: IpcSender<LayoutMsg>,
+    /// Sent a message when the content process shuts down as asked, in multiprocess mode.
+    lifeline: Option<IpcSender<()>>,
     scheduler_chan: IpcSender<TimerSchedulerMsg>,
     devtools_ipc_sender: Option<IpcSender<ScriptToDevtoolsControlMsg>>,
     bluetooth_thread: IpcSender<BluetoothRequest>,
@@ -555,7 +598,11 @@ impl UnprivilegedPipelineContent {
 
         if wait_for_completion {
             match content_process_shutdown_port.recv() {
-                Ok(()) => {},
+                Ok(()) => {
+                    if let Some(lifeline) = self.lifeline {
+                        let _ = lifeline.send(());
+                    }
+                },
                 Err(_) => error!("Script-thread shut-down unexpectedly"),
             }
         }
@@ -0,0 +1,141 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Prerendering of pages for embedders using Servo as a server: once the page is quiescent,
+//! its DOM is serialized and a screenshot of it is taken.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use compositing_traits::CompositorProxy;
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use embedder_traits::{PrerenderError, PrerenderRequest, PrerenderedPage};
+use ipc_channel::ipc::{self, IpcSender};
+use log::warn;
+use msg::constellation_msg::PipelineId;
+use net_traits::ResourceThreads;
+use script_traits::webdriver_msg::WebDriverScriptCommand;
+use script_traits::ConstellationControlMsg;
+
+use crate::element_export::{encode_png, render_stable_image, rgb_image};
+
+/// How often to check whether the page is quiescent.
+const QUIESCENCE_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Prerender the document of the pipeline on another thread once `loaded` says it is
+/// loaded, and call `done` with the result. `loaded` is disconnected if the pipeline goes
+/// away first.
+pub fn prerender<F>(
+    pipeline_id: PipelineId,
+    script_chan: IpcSender<ConstellationControlMsg>,
+    resource_threads: ResourceThreads,
+    compositor_proxy: CompositorProxy,
+    loaded: Receiver<()>,
+    request: PrerenderRequest,
+    done: F,
+) where
+    F: FnOnce(Result<PrerenderedPage, PrerenderError>) + Send + 'static,
+{
+    let spawned = thread::Builder::new()
+        .name("Prerender".to_owned())
+        .spawn(move || {
+            let deadline = Instant::now() + request.timeout;
+            done(render_page(
+                pipeline_id,
+                &script_chan,
+                &resource_threads,
+                &compositor_proxy,
+                &loaded,
+                &request,
+                deadline,
+            ))
+        });
+    if let Err(e) = spawned {
+        warn!("Failed to spawn the prerender thread ({:?}).", e);
+    }
+}
+
+fn render_page(
+    pipeline_id: PipelineId,
+    script_chan: &IpcSender<ConstellationControlMsg>,
+    resource_threads: &ResourceThreads,
+    compositor_proxy: &CompositorProxy,
+    loaded: &Receiver<()>,
+    request: &PrerenderRequest,
+    deadline: Instant,
+) -> Result<PrerenderedPage, PrerenderError> {
+    match loaded.recv_deadline(deadline) {
+        Ok(()) => {},
+        Err(RecvTimeoutError::Timeout) => return Err(PrerenderError::Timeout),
+        Err(RecvTimeoutError::Disconnected) => return Err(PrerenderError::NoSuchWebView),
+    }
+
+    let send = |command| {
+        script_chan
+            .send(ConstellationControlMsg::WebDriverScriptCommand(
+                pipeline_id,
+                command,
+            ))
+            .map_err(|_| PrerenderError::NoSuchWebView)
+    };
+
+    // The page is quiescent once none of its fetches has been in progress for a while,
+    // and the callbacks of the next animation frame did not start any.
+    let count_fetches = || {
+        resource_threads
+            .count_fetches_in_progress(vec![pipeline_id])
+            .ok_or(PrerenderError::NoSuchWebView)
+    };
+    let mut idle_since = None;
+    loop {
+        if Instant::now() >= deadline {
+            return Err(PrerenderError::Timeout);
+        }
+        if count_fetches()? > 0 {
+            idle_since = None;
+        } else if idle_since.get_or_insert_with(Instant::now).elapsed() >= request.network_idle {
+            let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+            send(WebDriverScriptCommand::WaitForAnimationFrame(sender))?;
+            receiver.recv().map_err(|_| PrerenderError::NoSuchWebView)?;
+            if count_fetches()? == 0 {
+                break;
+            }
+            idle_since = None;
+        }
+        thread::sleep(QUIESCENCE_POLL_INTERVAL);
+    }
+
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    send(WebDriverScriptCommand::GetUrl(sender))?;
+    let url = receiver.recv().map_err(|_| PrerenderError::NoSuchWebView)?;
+
+    let dom = if request.serialize_dom {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        send(WebDriverScriptCommand::GetPageSource(sender))?;
+        match receiver.recv() {
+            Ok(Ok(source)) => Some(source),
+            Ok(Err(_)) => return Err(PrerenderError::SerializationFailed),
+            Err(_) => return Err(PrerenderError::NoSuchWebView),
+        }
+    } else {
+        None
+    };
+
+    let screenshot = if request.screenshot {
+        let rendered =
+            render_stable_image(compositor_proxy, None).ok_or(PrerenderError::Timeout)?;
+        let png = rgb_image(rendered)
+            .and_then(encode_png)
+            .ok_or(PrerenderError::SerializationFailed)?;
+        Some(png)
+    } else {
+        None
+    };
+
+    Ok(PrerenderedPage {
+        url,
+        dom,
+        screenshot,
+    })
+}
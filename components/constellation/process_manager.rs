@@ -0,0 +1,217 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! This module contains the `ProcessManager` type, which holds the policies the
+//! constellation manages event loops and content processes with: a pool of content
+//! processes started ahead of time, and event loops kept running for a while once they
+//! run no document, so that another document of their site can reuse them.
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use servo_config::{opts, pref};
+use servo_url::Host;
+
+use crate::event_loop::EventLoop;
+use crate::sandboxing::{spawn_content_process, ContentProcess};
+
+/// An event loop which runs no document anymore.
+struct IdleEventLoop {
+    host: Host,
+    /// Whether the event loop ran documents of private browsing, and so can only run
+    /// those.
+    is_private: bool,
+    event_loop: Rc<EventLoop>,
+    since: Instant,
+}
+
+/// What the process management policies did so far, reported to the embedder.
+#[derive(Default)]
+pub struct ProcessMetrics {
+    pub started: u64,
+    pub started_in_spare_process: u64,
+    pub idle_reused: u64,
+    pub idle_reaped: u64,
+    pub shared_over_site_cap: u64,
+}
+
+pub struct ProcessManager {
+    /// The content processes started ahead of time, in multiprocess mode.
+    spare_processes: VecDeque<ContentProcess>,
+    /// The event loops kept running while they run no document.
+    idle_event_loops: Vec<IdleEventLoop>,
+    pub metrics: ProcessMetrics,
+}
+
+impl ProcessManager {
+    pub fn new() -> ProcessManager {
+        let mut manager = ProcessManager {
+            spare_processes: VecDeque::new(),
+            idle_event_loops: vec![],
+            metrics: Default::default(),
+        };
+        manager.start_spare_processes();
+        manager
+    }
+
+    fn start_spare_processes(&mut self) {
+        if !opts::multiprocess() {
+            return;
+        }
+        let count = pref!(process.spare_count).max(0) as usize;
+        while self.spare_processes.len() < count {
+            let process = spawn_content_process(opts::get().sandbox);
+            self.spare_processes.push_back(process);
+        }
+    }
+
+    pub fn spare_count(&self) -> usize {
+        self.spare_processes.len()
+    }
+
+    /// A new event loop is about to be started: get a spare content process to start it in,
+    /// if there is one, and start another to replace it.
+    pub fn event_loop_started(&mut self) -> Option<ContentProcess> {
+        self.metrics.started += 1;
+        let process = self.spare_processes.pop_front()?;
+        self.metrics.started_in_spare_process += 1;
+        self.start_spare_processes();
+        Some(process)
+    }
+
+    /// The most event loops allowed to run the documents of a site, if there is a limit.
+    pub fn max_event_loops_per_site(&self) -> Option<usize> {
+        match pref!(process.max_per_site) {
+            max if max > 0 => Some(max as usize),
+            _ => None,
+        }
+    }
+
+    /// The last document of an event loop went away: keep it running for a while if the
+    /// policy says so, or let it shut down.
+    pub fn event_loop_idle(&mut self, host: Host, event_loop: Rc<EventLoop>) {
+        if pref!(process.idle_timeout) <= 0 {
+            return;
+        }
+        self.idle_event_loops.push(IdleEventLoop {
+            host,
+            is_private: event_loop.is_private(),
+            event_loop,
+            since: Instant::now(),
+        });
+    }
+
+    /// Take an idle event loop of a site, to run another document of the site that is in
+    /// private browsing or not, as `is_private` says.
+    pub fn take_idle_event_loop(&mut self, host: &Host, is_private: bool) -> Option<Rc<EventLoop>> {
+        let index = self
+            .idle_event_loops
+            .iter()
+            .position(|idle| &idle.host == host && idle.is_private == is_private)?;
+        self.metrics.idle_reused += 1;
+        Some(self.idle_event_loops.remove(index).event_loop)
+    }
+
+    /// An event loop runs a new document: it is not idle anymore, if it was.
+    pub fn event_loop_used(&mut self, event_loop: &Rc<EventLoop>) {
+        let length = self.idle_event_loops.len();
+        self.idle_event_loops
+            .retain(|idle| !Rc::ptr_eq(&idle.event_loop, event_loop));
+        if self.idle_event_loops.len() != length {
+            self.metrics.idle_reused += 1;
+        }
+    }
+
+    /// The content process of an event loop crashed: it cannot be reused.
+    pub fn event_loop_crashed(&mut self, event_loop: &Rc<EventLoop>) {
+        self.idle_event_loops
+            .retain(|idle| !Rc::ptr_eq(&idle.event_loop, event_loop));
+    }
+
+    pub fn idle_event_loops(&self) -> impl Iterator<Item = (&Host, &Rc<EventLoop>)> {
+        self.idle_event_loops
+            .iter()
+            .map(|idle| (&idle.host, &idle.event_loop))
+    }
+
+    fn idle_timeout(&self) -> Duration {
+        Duration::from_secs(pref!(process.idle_timeout).max(0) as u64)
+    }
+
+    /// How long until the next idle event loop is to be shut down, if there is one.
+    pub fn next_reap(&self) -> Option<Duration> {
+        let timeout = self.idle_timeout();
+        let now = Instant::now();
+        self.idle_event_loops
+            .iter()
+            .map(|idle| (idle.since + timeout).saturating_duration_since(now))
+            .min()
+    }
+
+    /// Shut down the event loops which ran no document for too long.
+    pub fn reap_idle_event_loops(&mut self) {
+        let timeout = self.idle_timeout();
+        let now = Instant::now();
+        let length = self.idle_event_loops.len();
+        self.idle_event_loops
+            .retain(|idle| now.duration_since(idle.since) < timeout);
+        self.metrics.idle_reaped += (length - self.idle_event_loops.len()) as u64;
+    }
+
+    /// Let the spare content processes and the idle event loops shut down.
+    pub fn shutdown(&mut self) {
+        self.spare_processes.clear();
+        self.idle_event_loops.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Instant;
+
+    use ipc_channel::ipc;
+    use servo_url::Host;
+
+    use crate::event_loop::EventLoop;
+    use crate::process_manager::{IdleEventLoop, ProcessManager};
+
+    fn idle_event_loop(host: &Host, is_private: bool) -> IdleEventLoop {
+        let (script_chan, _) = ipc::channel().expect("Incorrect test case");
+        let event_loop = EventLoop::new(script_chan, is_private);
+        IdleEventLoop {
+            host: host.clone(),
+            is_private: event_loop.is_private(),
+            event_loop,
+            since: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_idle_event_loops_only_run_documents_of_the_same_browsing_mode() {
+        let host = Host::parse("servo.org").expect("Incorrect test case");
+        let mut manager = ProcessManager {
+            spare_processes: Default::default(),
+            idle_event_loops: vec![idle_event_loop(&host, true)],
+            metrics: Default::default(),
+        };
+
+        assert!(manager.take_idle_event_loop(&host, false).is_none());
+        let other_host = Host::parse("example.com").expect("Incorrect test case");
+        assert!(manager.take_idle_event_loop(&other_host, true).is_none());
+
+        let event_loop = manager
+            .take_idle_event_loop(&host, true)
+            .expect("Incorrect test case");
+        assert!(event_loop.is_private());
+        assert!(manager.take_idle_event_loop(&host, true).is_none());
+        assert_eq!(manager.metrics.idle_reused, 1);
+
+        manager.idle_event_loops.push(idle_event_loop(&host, false));
+        let event_loop = manager
+            .take_idle_event_loop(&host, false)
+            .expect("Incorrect test case");
+        assert!(!event_loop.is_private());
+    }
+}
@@ -19,6 +19,7 @@ use std::process;
     )
 ))]
 use gaol::profile::{Operation, PathPattern, Profile};
+use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
 use ipc_channel::Error;
 use serde::{Deserialize, Serialize};
 use servo_config::opts::Opts;
@@ -142,8 +143,7 @@ pub fn content_process_sandbox_profile() {
     target_arch = "arm",
     all(target_arch = "aarch64", not(target_os = "windows"))
 ))]
-pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
-    use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
+pub fn spawn_content_process(_sandbox: bool) -> ContentProcess {
     // Note that this function can panic, due to process creation,
     // avoiding this panic would require a mechanism for dealing
     // with low-resource scenarios.
@@ -157,10 +157,7 @@ pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
         .spawn()
         .expect("Failed to start unsandboxed child process!");
 
-    let (_receiver, sender) = server.accept().expect("Server failed to accept.");
-    sender.send(content)?;
-
-    Ok(())
+    ContentProcess { server }
 }
 
 #[cfg(all(
@@ -170,9 +167,8 @@ pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
     not(target_arch = "arm"),
     not(target_arch = "aarch64")
 ))]
-pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
+pub fn spawn_content_process(sandbox: bool) -> ContentProcess {
     use gaol::sandbox::{self, Sandbox, SandboxMethods};
-    use ipc_channel::ipc::{IpcOneShotServer, IpcSender};
 
     impl CommandMethods for sandbox::Command {
         fn arg<T>(&mut self, arg: T)
@@ -198,7 +194,7 @@ pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
         .expect("Failed to create IPC one-shot server.");
 
     // If there is a sandbox, use the `gaol` API to create the child process.
-    if content.opts().sandbox {
+    if sandbox {
         let mut command = sandbox::Command::me().expect("Failed to get current sandbox.");
         setup_common(&mut command, token);
 
@@ -215,18 +211,34 @@ pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
             .expect("Failed to start unsandboxed child process!");
     }
 
-    let (_receiver, sender) = server.accept().expect("Server failed to accept.");
-    sender.send(content)?;
-
-    Ok(())
+    ContentProcess { server }
 }
 
 #[cfg(any(target_os = "windows", target_os = "ios"))]
-pub fn spawn_multiprocess(_content: UnprivilegedContent) -> Result<(), Error> {
+pub fn spawn_content_process(_sandbox: bool) -> ContentProcess {
     log::error!("Multiprocess is not supported on Windows or iOS.");
     process::exit(1);
 }
 
+/// A content process, started before the content it runs is known. The process waits for
+/// its content once it has started, so that starting it ahead of time hides how long it
+/// takes to.
+pub struct ContentProcess {
+    server: IpcOneShotServer<IpcSender<UnprivilegedContent>>,
+}
+
+impl ContentProcess {
+    /// Send the process the content to run, waiting for it to have started if needed.
+    pub fn start(self, content: UnprivilegedContent) -> Result<(), Error> {
+        let (_receiver, sender) = self.server.accept().expect("Server failed to accept.");
+        sender.send(content)
+    }
+}
+
+pub fn spawn_multiprocess(content: UnprivilegedContent) -> Result<(), Error> {
+    spawn_content_process(content.opts().sandbox).start(content)
+}
+
 #[cfg(not(windows))]
 fn setup_common<C: CommandMethods>(command: &mut C, token: String) {
     C::arg(command, "--content-process");
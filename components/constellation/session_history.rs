@@ -10,7 +10,7 @@ use log::debug;
 use msg::constellation_msg::{
     BrowsingContextId, HistoryStateId, PipelineId, TopLevelBrowsingContextId,
 };
-use script_traits::LoadData;
+use script_traits::{HistoryEntryScrollState, LoadData};
 use servo_url::ServoUrl;
 use style_traits::CSSPixel;
 
@@ -196,15 +196,21 @@ pub enum SessionHistoryDiff {
         old_history_state_id: Option<HistoryStateId>,
         /// The old url
         old_url: ServoUrl,
+        /// The scroll state of the old entry, saved when it was left.
+        old_scroll_state: HistoryEntryScrollState,
         /// The new history state id.
         new_history_state_id: HistoryStateId,
         /// The new url
         new_url: ServoUrl,
+        /// The scroll state of the new entry, saved when it was left.
+        new_scroll_state: HistoryEntryScrollState,
     },
     HashDiff {
         pipeline_reloader: NeedsToReload,
         old_url: ServoUrl,
+        old_scroll_state: HistoryEntryScrollState,
         new_url: ServoUrl,
+        new_scroll_state: HistoryEntryScrollState,
     },
 }
 
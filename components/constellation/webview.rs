@@ -64,6 +64,11 @@ impl<WebView> WebViewManager<WebView> {
         self.webviews.get_mut(&top_level_browsing_context_id)
     }
 
+    /// The ids of all the webviews, in no particular order.
+    pub fn ids(&self) -> impl Iterator<Item = TopLevelBrowsingContextId> + '_ {
+        self.webviews.keys().copied()
+    }
+
     pub fn focused_webview(&self) -> Option<(TopLevelBrowsingContextId, &WebView)> {
         if !self.is_focused {
             return None;
@@ -255,10 +255,10 @@ impl BrowsingContextActor {
         script_sender: IpcSender<DevtoolScriptControlMsg>,
         actors: &mut ActorRegistry,
     ) -> BrowsingContextActor {
-        let emulation = EmulationActor::new(actors.new_name("emulation"));
-
         let name = actors.new_name("target");
 
+        let emulation = EmulationActor::new(actors.new_name("emulation"), name.clone());
+
         let inspector = InspectorActor {
             name: actors.new_name("inspector"),
             walker: RefCell::new(None),
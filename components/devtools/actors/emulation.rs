@@ -2,15 +2,43 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cell::Cell;
 use std::net::TcpStream;
 
+use devtools_traits::{DevtoolScriptControlMsg, NetworkThrottling};
+use serde::Serialize;
 use serde_json::{Map, Value};
 
 use crate::actor::{Actor, ActorMessageStatus, ActorRegistry};
+use crate::actors::browsing_context::BrowsingContextActor;
+use crate::protocol::JsonPacketStream;
 use crate::StreamId;
 
 pub struct EmulationActor {
     pub name: String,
+    /// The browsing context actor of the tab whose network conditions are emulated.
+    pub browsing_context: String,
+    throttling: Cell<Option<NetworkThrottling>>,
+}
+
+#[derive(Serialize)]
+struct EmptyReply {
+    from: String,
+}
+
+#[derive(Serialize)]
+#[allow(non_snake_case)]
+struct NetworkThrottlingValue {
+    latency: u64,
+    downloadThroughput: u64,
+    uploadThroughput: u64,
+    offline: bool,
+}
+
+#[derive(Serialize)]
+struct GetNetworkThrottlingReply {
+    from: String,
+    value: Option<NetworkThrottlingValue>,
 }
 
 impl Actor for EmulationActor {
@@ -20,20 +48,82 @@ impl Actor for EmulationActor {
 
     fn handle_message(
         &self,
-        _registry: &ActorRegistry,
+        registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
-        _stream: &mut TcpStream,
+        msg: &Map<String, Value>,
+        stream: &mut TcpStream,
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
+            "setNetworkThrottling" => {
+                let options = match msg.get("options").and_then(|o| o.as_object()) {
+                    Some(options) => options,
+                    None => return Ok(ActorMessageStatus::Ignored),
+                };
+                let option = |name: &str| options.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+                let throttling = NetworkThrottling {
+                    latency: option("latency"),
+                    download_throughput: option("downloadThroughput"),
+                    upload_throughput: option("uploadThroughput"),
+                    offline: options
+                        .get("offline")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false),
+                };
+                self.set_network_throttling(registry, Some(throttling));
+                let _ = stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
+            "getNetworkThrottling" => {
+                let msg = GetNetworkThrottlingReply {
+                    from: self.name(),
+                    value: self
+                        .throttling
+                        .get()
+                        .map(|throttling| NetworkThrottlingValue {
+                            latency: throttling.latency,
+                            downloadThroughput: throttling.download_throughput,
+                            uploadThroughput: throttling.upload_throughput,
+                            offline: throttling.offline,
+                        }),
+                };
+                let _ = stream.write_json_packet(&msg);
+                ActorMessageStatus::Processed
+            },
+
+            "clearNetworkThrottling" => {
+                self.set_network_throttling(registry, None);
+                let _ = stream.write_json_packet(&EmptyReply { from: self.name() });
+                ActorMessageStatus::Processed
+            },
+
             _ => ActorMessageStatus::Ignored,
         })
     }
 }
 
 impl EmulationActor {
-    pub fn new(name: String) -> EmulationActor {
-        EmulationActor { name: name }
+    pub fn new(name: String, browsing_context: String) -> EmulationActor {
+        EmulationActor {
+            name: name,
+            browsing_context: browsing_context,
+            throttling: Cell::new(None),
+        }
+    }
+
+    fn set_network_throttling(
+        &self,
+        registry: &ActorRegistry,
+        throttling: Option<NetworkThrottling>,
+    ) {
+        self.throttling.set(throttling);
+        let browsing_context = registry.find::<BrowsingContextActor>(&self.browsing_context);
+        let _ = browsing_context
+            .script_chan
+            .send(DevtoolScriptControlMsg::SetNetworkThrottling(
+                browsing_context.active_pipeline.get(),
+                throttling,
+            ));
     }
 }
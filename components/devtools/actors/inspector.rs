@@ -8,7 +8,7 @@ use std::cell::RefCell;
 use std::net::TcpStream;
 
 use devtools_traits::DevtoolScriptControlMsg::{
-    GetChildren, GetDocumentElement, GetLayout, GetRootNode, ModifyAttribute,
+    GetChildren, GetDocumentElement, GetLayout, GetRootNode, HighlightBoxModel, ModifyAttribute,
 };
 use devtools_traits::{ComputedNodeLayout, DevtoolScriptControlMsg, NodeInfo};
 use ipc_channel::ipc::{self, IpcSender};
@@ -43,6 +43,8 @@ struct HighlighterMsg {
 
 struct HighlighterActor {
     name: String,
+    script_chan: IpcSender<DevtoolScriptControlMsg>,
+    pipeline: PipelineId,
 }
 
 pub struct NodeActor {
@@ -68,20 +70,30 @@ impl Actor for HighlighterActor {
 
     fn handle_message(
         &self,
-        _registry: &ActorRegistry,
+        registry: &ActorRegistry,
         msg_type: &str,
-        _msg: &Map<String, Value>,
+        msg: &Map<String, Value>,
         stream: &mut TcpStream,
         _id: StreamId,
     ) -> Result<ActorMessageStatus, ()> {
         Ok(match msg_type {
             "showBoxModel" => {
+                let node = msg
+                    .get("node")
+                    .and_then(|node| node.as_str())
+                    .map(|node| registry.actor_to_script(node.to_owned()));
+                let _ = self
+                    .script_chan
+                    .send(HighlightBoxModel(self.pipeline, node));
                 let msg = ShowBoxModelReply { from: self.name() };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
             },
 
             "hideBoxModel" => {
+                let _ = self
+                    .script_chan
+                    .send(HighlightBoxModel(self.pipeline, None));
                 let msg = HideBoxModelReply { from: self.name() };
                 let _ = stream.write_json_packet(&msg);
                 ActorMessageStatus::Processed
@@ -483,6 +495,9 @@ impl Actor for PageStyleActor {
         Ok(match msg_type {
             "getApplied" => {
                 //TODO: query script for relevant applied styles to node (msg.node)
+                //TODO: once rules are reported, those of the user stylesheets added by the
+                //      embedder must be marked as being at the user origin, not as rules
+                //      of the page. No rule is reported yet, so neither is their origin.
                 let msg = GetAppliedReply {
                     entries: vec![],
                     rules: vec![],
@@ -667,6 +682,8 @@ impl Actor for InspectorActor {
                 if self.highlighter.borrow().is_none() {
                     let highlighter_actor = HighlighterActor {
                         name: registry.new_name("highlighter"),
+                        script_chan: self.script_chan.clone(),
+                        pipeline,
                     };
                     let mut highlighter = self.highlighter.borrow_mut();
                     *highlighter = Some(highlighter_actor.name());
@@ -9,7 +9,9 @@ use std::net::TcpStream;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use chrono::{Local, LocalResult, TimeZone};
-use devtools_traits::{HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse};
+use devtools_traits::{
+    HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse, ResourceHint,
+};
 use headers::{ContentType, Cookie, HeaderMapExt};
 use http::{header, HeaderMap, Method, StatusCode};
 use serde::Serialize;
@@ -41,6 +43,7 @@ pub struct NetworkEventActor {
     request: HttpRequest,
     response: HttpResponse,
     is_xhr: bool,
+    resource_hint: Option<ResourceHint>,
 }
 
 #[derive(Serialize)]
@@ -52,6 +55,15 @@ pub struct EventActor {
     pub timeStamp: i64,
     pub isXHR: bool,
     pub private: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<Cause>,
+}
+
+/// What made a request, shown in the cause column of the network panel.
+#[derive(Serialize)]
+pub struct Cause {
+    #[serde(rename = "type")]
+    pub type_: String,
 }
 
 #[derive(Serialize)]
@@ -342,6 +354,7 @@ impl NetworkEventActor {
                 body: None,
             },
             is_xhr: false,
+            resource_hint: None,
         }
     }
 
@@ -356,6 +369,7 @@ impl NetworkEventActor {
         self.request.connect_time = request.connect_time;
         self.request.send_time = request.send_time;
         self.is_xhr = request.is_xhr;
+        self.resource_hint = request.resource_hint;
     }
 
     pub fn add_response(&mut self, response: DevtoolsHttpResponse) {
@@ -390,6 +404,9 @@ impl NetworkEventActor {
             timeStamp: self.request.timeStamp,
             isXHR: self.is_xhr,
             private: false,
+            cause: self.resource_hint.map(|hint| Cause {
+                type_: hint.name().to_owned(),
+            }),
         }
     }
 
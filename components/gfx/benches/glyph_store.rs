@@ -0,0 +1,83 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Measures the time that building and measuring the glyph stores of large documents takes,
+//! with `cargo bench -p gfx --bench glyph_store`. Each document is split in runs of about
+//! the length of a paragraph, as text runs are. The memory they take is checked by the
+//! tests in `tests/glyph_store.rs`.
+
+use app_units::Au;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use gfx::text::glyph::{ByteIndex, GlyphData, GlyphStore};
+use range::Range;
+
+const DOCUMENT_LENGTH: usize = 4 * 1024 * 1024;
+
+const DOCUMENTS: &[(&str, &str)] = &[
+    (
+        "latin",
+        "The quick brown fox jumps over the lazy dog, and then some more. ",
+    ),
+    (
+        "cjk",
+        "吾輩は猫である。名前はまだ無い。どこで生れたかとんと見当がつかぬ。",
+    ),
+    (
+        "mixed",
+        "Servo は Rust で書かれたブラウザエンジンです。It is fast and safe. ",
+    ),
+];
+
+fn document(paragraph: &str) -> Vec<String> {
+    let paragraph = paragraph.repeat(8);
+    (0..DOCUMENT_LENGTH / paragraph.len())
+        .map(|_| paragraph.clone())
+        .collect()
+}
+
+fn shape(text: &str) -> GlyphStore {
+    let mut glyphs = GlyphStore::new(text, false, false);
+    for (i, character) in text.char_indices() {
+        let data = GlyphData::new(character as u32 & 0xFFFF, Au::from_px(10), None, true, true);
+        glyphs.add_glyph_for_byte_index(ByteIndex(i as isize), character, &data);
+    }
+    glyphs.finalize_changes();
+    glyphs
+}
+
+/// Measure lines of some 40 bytes, as layout does when breaking the runs.
+fn measure_lines(glyph_stores: &[GlyphStore]) -> Au {
+    let mut advance = Au(0);
+    for glyphs in glyph_stores {
+        let length = glyphs.len().to_usize();
+        for begin in (0..length).step_by(40) {
+            let range = Range::new(
+                ByteIndex(begin as isize),
+                ByteIndex((length - begin).min(40) as isize),
+            );
+            advance += glyphs.advance_for_byte_range(&range, Au(0));
+        }
+    }
+    advance
+}
+
+fn bench_glyph_stores(c: &mut Criterion) {
+    for &(name, paragraph) in DOCUMENTS {
+        let runs = document(paragraph);
+        let text_length: usize = runs.iter().map(|run| run.len()).sum();
+        let glyph_stores: Vec<_> = runs.iter().map(|run| shape(run)).collect();
+
+        let mut group = c.benchmark_group(name);
+        group.throughput(Throughput::Bytes(text_length as u64));
+        group.sample_size(10);
+        group.bench_function("shape", |b| {
+            b.iter(|| runs.iter().map(|run| shape(run)).collect::<Vec<_>>())
+        });
+        group.bench_function("measure lines", |b| b.iter(|| measure_lines(&glyph_stores)));
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_glyph_stores);
+criterion_main!(benches);
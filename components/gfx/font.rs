@@ -112,13 +112,13 @@ pub struct FontMetrics {
     pub underline_offset: Au,
     pub strikeout_size: Au,
     pub strikeout_offset: Au,
-    pub leading: Au,
     pub x_height: Au,
     pub em_size: Au,
     pub ascent: Au,
     pub descent: Au,
     pub max_advance: Au,
     pub average_advance: Au,
+    /// The space the font wants between the descent of a line and the ascent of the next.
     pub line_gap: Au,
 }
 
@@ -131,7 +131,6 @@ impl FontMetrics {
             underline_offset: Au(0),
             strikeout_size: Au(0),
             strikeout_offset: Au(0),
-            leading: Au(0),
             x_height: Au(0),
             em_size: Au(0),
             ascent: Au(0),
@@ -141,6 +140,13 @@ impl FontMetrics {
             line_gap: Au(0),
         }
     }
+
+    /// The distance between the baselines of two lines set in this font, which is what
+    /// `line-height: normal` means.
+    /// <https://drafts.csswg.org/css-inline/#line-height-property>
+    pub fn normal_line_height(&self) -> Au {
+        self.ascent + self.descent + self.line_gap
+    }
 }
 
 /// `FontDescriptor` describes the parameters of a `Font`. It represents rendering a given font
@@ -260,7 +266,7 @@ impl Font {
             .or_insert_with(|| {
                 let start_time = Instant::now();
                 let mut glyphs = GlyphStore::new(
-                    text.len(),
+                    text,
                     options
                         .flags
                         .contains(ShapingFlags::IS_WHITESPACE_SHAPING_FLAG),
@@ -403,7 +409,21 @@ impl FontGroup {
             .font_family
             .families
             .iter()
-            .map(|family| FontGroupFamily::new(descriptor.clone(), &family))
+            .map(|family| FontGroupFamily::new(descriptor.clone(), FontFamilyName::from(family)))
+            .collect();
+
+        FontGroup {
+            descriptor,
+            families,
+            last_matching_fallback: None,
+        }
+    }
+
+    /// A group of the given families, for text that is not styled by CSS.
+    pub fn with_families(descriptor: FontDescriptor, families: &[FontFamilyName]) -> FontGroup {
+        let families = families
+            .iter()
+            .map(|family| FontGroupFamily::new(descriptor.clone(), family.clone()))
             .collect();
 
         FontGroup {
@@ -477,9 +497,9 @@ impl FontGroup {
     }
 
     /// Attempts to find a suitable fallback font which matches the `predicate`. The default
-    /// family (i.e. "serif") will be tried first, followed by platform-specific family names.
-    /// If a `codepoint` is provided, then its Unicode block may be used to refine the list of
-    /// family names which will be tried.
+    /// family (i.e. "serif") will be tried first, followed by platform-specific family names, or
+    /// the families of the bundled fonts. If a `codepoint` is provided, then its Unicode block
+    /// may be used to refine the list of platform-specific family names which will be tried.
     fn find_fallback<S, P>(
         &mut self,
         font_context: &mut FontContext<S>,
@@ -490,10 +510,13 @@ impl FontGroup {
         S: FontSource,
         P: FnMut(&FontRef) -> bool,
     {
+        let fallback_families = font_context.fallback_font_families(codepoint);
         iter::once(FontFamilyDescriptor::default())
-            .chain(fallback_font_families(codepoint).into_iter().map(|family| {
-                FontFamilyDescriptor::new(FontFamilyName::from(family), FontSearchScope::Local)
-            }))
+            .chain(
+                fallback_families
+                    .into_iter()
+                    .map(|family| FontFamilyDescriptor::new(family, FontSearchScope::Local)),
+            )
             .filter_map(|family| font_context.font(&self.descriptor, &family))
             .find(predicate)
     }
@@ -511,9 +534,8 @@ struct FontGroupFamily {
 }
 
 impl FontGroupFamily {
-    fn new(font_descriptor: FontDescriptor, family: &SingleFontFamily) -> FontGroupFamily {
-        let family_descriptor =
-            FontFamilyDescriptor::new(FontFamilyName::from(family), FontSearchScope::Any);
+    fn new(font_descriptor: FontDescriptor, family: FontFamilyName) -> FontGroupFamily {
+        let family_descriptor = FontFamilyDescriptor::new(family, FontSearchScope::Any);
 
         FontGroupFamily {
             font_descriptor,
@@ -0,0 +1,126 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A directory of fonts that Servo uses instead of the system fonts when asked to, so that
+//! pages render the same on every machine.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use log::{debug, warn};
+use servo_atoms::Atom;
+
+use crate::font::{FontFamilyName, FontHandleMethods};
+use crate::font_cache_thread::LowercaseString;
+use crate::platform::font::FontHandle;
+use crate::platform::font_context::FontContextHandle;
+use crate::platform::font_template::FontTemplateData;
+
+/// The fonts of a directory, by family. Families are kept in the order of their names, which
+/// is the order they are fallen back to in, whatever order the file system lists them in.
+#[derive(Debug, Default)]
+pub struct FontBundle {
+    families: BTreeMap<LowercaseString, Vec<PathBuf>>,
+}
+
+impl FontBundle {
+    /// Find the fonts of the files in `directory`. Files that are not fonts are skipped.
+    pub fn load(directory: &Path, font_context: &FontContextHandle) -> FontBundle {
+        let mut paths: Vec<PathBuf> = match fs::read_dir(directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.is_file())
+                .collect(),
+            Err(error) => {
+                warn!(
+                    "Could not read bundled fonts from {:?} ({}).",
+                    directory, error
+                );
+                vec![]
+            },
+        };
+        paths.sort();
+
+        let mut bundle = FontBundle::default();
+        for path in paths {
+            match family_name(&path, font_context) {
+                Some(family_name) => bundle
+                    .families
+                    .entry(LowercaseString::new(&family_name))
+                    .or_default()
+                    .push(path),
+                None => debug!("Skipping {:?}, which is not a font.", path),
+            }
+        }
+        bundle
+    }
+
+    pub fn for_each_family<F>(&self, mut callback: F)
+    where
+        F: FnMut(&LowercaseString),
+    {
+        for family_name in self.families.keys() {
+            callback(family_name);
+        }
+    }
+
+    /// Call `callback` with the identifier and the data of each font of the family. The data
+    /// is always in memory, as the identifiers of fonts are not paths on every platform.
+    pub fn for_each_variation<F>(&self, family_name: &str, mut callback: F)
+    where
+        F: FnMut(Atom, Vec<u8>),
+    {
+        let paths = match self.families.get(&LowercaseString::new(family_name)) {
+            Some(paths) => paths,
+            None => return,
+        };
+        for path in paths {
+            match fs::read(path) {
+                Ok(bytes) => callback(Atom::from(&*path.to_string_lossy()), bytes),
+                Err(error) => warn!("Could not read bundled font {:?} ({}).", path, error),
+            }
+        }
+    }
+
+    /// The family of the bundle that a generic family maps to: the first one whose name
+    /// suggests it, or else the first one. `None` if the bundle is empty.
+    pub fn generic_family(&self, generic_name: &str) -> Option<LowercaseString> {
+        let suggests = |family_name: &LowercaseString| match generic_name {
+            "monospace" => family_name.contains("mono"),
+            "sans-serif" => family_name.contains("sans") && !family_name.contains("mono"),
+            "serif" => {
+                family_name.contains("serif") &&
+                    !family_name.contains("sans") &&
+                    !family_name.contains("mono")
+            },
+            _ => false,
+        };
+        self.families
+            .keys()
+            .find(|family_name| suggests(family_name))
+            .or_else(|| self.families.keys().next())
+            .cloned()
+    }
+
+    /// The families to look for a glyph in when none of the families of the text has one,
+    /// whatever the character: all of them, in order.
+    pub fn fallback_families(&self) -> Vec<FontFamilyName> {
+        self.families
+            .keys()
+            .map(|family_name| FontFamilyName::from(&**family_name))
+            .collect()
+    }
+}
+
+fn family_name(path: &Path, font_context: &FontContextHandle) -> Option<String> {
+    let bytes = fs::read(path).ok()?;
+    let identifier = Atom::from(&*path.to_string_lossy());
+    let template = FontTemplateData::new(identifier, Some(bytes)).ok()?;
+    let handle: FontHandle =
+        FontHandleMethods::new_from_template(font_context, Arc::new(template), None).ok()?;
+    handle.family_name()
+}
@@ -3,8 +3,9 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::borrow::ToOwned;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 use std::{f32, fmt, mem, thread};
 
@@ -15,18 +16,22 @@ use log::{debug, trace};
 use net_traits::request::{Destination, Referrer, RequestBuilder};
 use net_traits::{fetch_async, CoreResourceThread, FetchResponseMsg};
 use serde::{Deserialize, Serialize};
+use servo_allocator::Partition;
 use servo_atoms::Atom;
+use servo_config::pref;
 use servo_url::ServoUrl;
 use style::font_face::{EffectiveSources, Source};
 use style::values::computed::font::FamilyName;
 use webrender_api::{FontInstanceKey, FontKey};
 
 use crate::font::{FontFamilyDescriptor, FontFamilyName, FontSearchScope};
+use crate::font_bundle::FontBundle;
 use crate::font_context::FontSource;
 use crate::font_template::{FontTemplate, FontTemplateDescriptor};
 use crate::platform::font_context::FontContextHandle;
 use crate::platform::font_list::{
-    for_each_available_family, for_each_variation, system_default_family, SANS_SERIF_FONT_FAMILY,
+    fallback_font_families, for_each_available_family, for_each_variation, system_default_family,
+    SANS_SERIF_FONT_FAMILY,
 };
 use crate::platform::font_template::FontTemplateData;
 
@@ -158,26 +163,56 @@ struct FontCache {
     webrender_api: Box<dyn WebrenderApi>,
     webrender_fonts: HashMap<Atom, FontKey>,
     font_instances: HashMap<(FontKey, Au), FontInstanceKey>,
+    /// The fonts to use instead of the system ones, if any.
+    font_bundle: Option<FontBundle>,
 }
 
-fn populate_generic_fonts() -> HashMap<FontFamilyName, LowercaseString> {
+/// Families that most systems have, which sites can use on top of the ones generic families
+/// map to while resisting fingerprinting.
+const BUNDLED_FONT_FAMILIES: &[&str] = &[
+    "Arial",
+    "Courier New",
+    "DejaVu Sans",
+    "DejaVu Sans Mono",
+    "DejaVu Serif",
+    "Georgia",
+    "Helvetica",
+    "Liberation Mono",
+    "Liberation Sans",
+    "Liberation Serif",
+    "Noto Sans",
+    "Noto Serif",
+    "Times New Roman",
+    "Verdana",
+];
+
+fn populate_generic_fonts(
+    font_bundle: Option<&FontBundle>,
+) -> HashMap<FontFamilyName, LowercaseString> {
     let mut generic_fonts = HashMap::with_capacity(5);
 
-    append_map(&mut generic_fonts, "serif", "Times New Roman");
-    append_map(&mut generic_fonts, "sans-serif", SANS_SERIF_FONT_FAMILY);
-    append_map(&mut generic_fonts, "cursive", "Apple Chancery");
-    append_map(&mut generic_fonts, "fantasy", "Papyrus");
-    append_map(&mut generic_fonts, "monospace", "Menlo");
+    append_map(&mut generic_fonts, font_bundle, "serif", "Times New Roman");
+    append_map(
+        &mut generic_fonts,
+        font_bundle,
+        "sans-serif",
+        SANS_SERIF_FONT_FAMILY,
+    );
+    append_map(&mut generic_fonts, font_bundle, "cursive", "Apple Chancery");
+    append_map(&mut generic_fonts, font_bundle, "fantasy", "Papyrus");
+    append_map(&mut generic_fonts, font_bundle, "monospace", "Menlo");
 
     fn append_map(
         generic_fonts: &mut HashMap<FontFamilyName, LowercaseString>,
+        font_bundle: Option<&FontBundle>,
         generic_name: &str,
         mapped_name: &str,
     ) {
-        let family_name = match system_default_family(generic_name) {
-            Some(system_default) => LowercaseString::new(&system_default),
-            None => LowercaseString::new(mapped_name),
+        let default_family = match font_bundle {
+            Some(font_bundle) => font_bundle.generic_family(generic_name),
+            None => system_default_family(generic_name).map(|family| LowercaseString::new(&family)),
         };
+        let family_name = default_family.unwrap_or_else(|| LowercaseString::new(mapped_name));
 
         let generic_name = FontFamilyName::Generic(Atom::from(generic_name));
 
@@ -189,6 +224,7 @@ fn populate_generic_fonts() -> HashMap<FontFamilyName, LowercaseString> {
 
 impl FontCache {
     fn run(&mut self) {
+        let _partition = servo_allocator::enter_partition(Partition::Gfx);
         loop {
             let msg = self.port.recv().unwrap();
 
@@ -345,12 +381,27 @@ impl FontCache {
             },
             Source::Local(ref font) => {
                 let font_face_name = LowercaseString::new(&font.name);
+                let allowed = self.bundled_families().map_or(true, |families| {
+                    families
+                        .iter()
+                        .any(|family| font_face_name.starts_with(&**family))
+                });
                 let templates = &mut self.web_families.get_mut(&family_name).unwrap();
                 let mut found = false;
-                for_each_variation(&font_face_name, |path| {
-                    found = true;
-                    templates.add_template(Atom::from(&*path), None);
-                });
+                if allowed {
+                    match self.font_bundle {
+                        Some(ref font_bundle) => {
+                            font_bundle.for_each_variation(&font_face_name, |identifier, bytes| {
+                                found = true;
+                                templates.add_template(identifier, Some(bytes));
+                            })
+                        },
+                        None => for_each_variation(&font_face_name, |path| {
+                            found = true;
+                            templates.add_template(Atom::from(&*path), None);
+                        }),
+                    }
+                }
                 if found {
                     sender.send(()).unwrap();
                 } else {
@@ -361,15 +412,43 @@ impl FontCache {
         }
     }
 
+    /// The local families sites can use while resisting fingerprinting, so that the fonts
+    /// someone installed do not tell them apart, or `None` if they can use them all.
+    fn bundled_families(&self) -> Option<HashSet<LowercaseString>> {
+        if !pref!(privacy.resist_fingerprinting.enabled) {
+            return None;
+        }
+        let generic_families = self.generic_fonts.values().cloned();
+        let bundled_families = BUNDLED_FONT_FAMILIES
+            .iter()
+            .map(|family_name| LowercaseString::new(family_name));
+        Some(generic_families.chain(bundled_families).collect())
+    }
+
     fn refresh_local_families(&mut self) {
         self.local_families.clear();
-        for_each_available_family(|family_name| {
-            let family_name = LowercaseString::new(&family_name);
-            if !self.local_families.contains_key(&family_name) {
+        let bundled_families = self.bundled_families();
+        let local_families = &mut self.local_families;
+        let mut add_family = |family_name: LowercaseString| {
+            if bundled_families
+                .as_ref()
+                .map_or(false, |families| !families.contains(&family_name))
+            {
+                return;
+            }
+            if !local_families.contains_key(&family_name) {
                 let templates = FontTemplates::new();
-                self.local_families.insert(family_name, templates);
+                local_families.insert(family_name, templates);
             }
-        });
+        };
+        match self.font_bundle {
+            Some(ref font_bundle) => {
+                font_bundle.for_each_family(|family_name| add_family(family_name.clone()))
+            },
+            None => for_each_available_family(|family_name| {
+                add_family(LowercaseString::new(&family_name))
+            }),
+        }
     }
 
     fn transform_family(&self, family_name: &FontFamilyName) -> LowercaseString {
@@ -393,9 +472,16 @@ impl FontCache {
             let s = self.local_families.get_mut(&family_name).unwrap();
 
             if s.templates.is_empty() {
-                for_each_variation(&family_name, |path| {
-                    s.add_template(Atom::from(&*path), None);
-                });
+                match self.font_bundle {
+                    Some(ref font_bundle) => {
+                        font_bundle.for_each_variation(&family_name, |identifier, bytes| {
+                            s.add_template(identifier, Some(bytes));
+                        })
+                    },
+                    None => for_each_variation(&family_name, |path| {
+                        s.add_template(Atom::from(&*path), None);
+                    }),
+                }
             }
 
             // TODO(Issue #192: handle generic font families, like 'serif' and 'sans-serif'.
@@ -472,21 +558,32 @@ impl FontCache {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct FontCacheThread {
     chan: IpcSender<Command>,
+    /// The fallback families of the fonts used instead of the system ones, if any.
+    bundled_fallback_families: Option<Vec<FontFamilyName>>,
 }
 
 impl FontCacheThread {
+    /// Create the font cache thread. If `bundled_fonts_dir` is given, the system fonts are
+    /// ignored and only the fonts in that directory are used.
     pub fn new(
         core_resource_thread: CoreResourceThread,
         webrender_api: Box<dyn WebrenderApi + Send>,
+        bundled_fonts_dir: Option<PathBuf>,
     ) -> FontCacheThread {
         let (chan, port) = ipc::channel().unwrap();
 
+        // The fonts are bundled before the thread starts, as the font contexts of all the
+        // other threads need their fallback families.
+        let font_bundle = bundled_fonts_dir
+            .map(|directory| FontBundle::load(&directory, &FontContextHandle::new()));
+        let bundled_fallback_families = font_bundle.as_ref().map(FontBundle::fallback_families);
+
         let channel_to_self = chan.clone();
         thread::Builder::new()
             .name("FontCache".to_owned())
             .spawn(move || {
                 // TODO: Allow users to specify these.
-                let generic_fonts = populate_generic_fonts();
+                let generic_fonts = populate_generic_fonts(font_bundle.as_ref());
 
                 let mut cache = FontCache {
                     port: port,
@@ -499,6 +596,7 @@ impl FontCacheThread {
                     webrender_api,
                     webrender_fonts: HashMap::new(),
                     font_instances: HashMap::new(),
+                    font_bundle,
                 };
 
                 cache.refresh_local_families();
@@ -506,7 +604,10 @@ impl FontCacheThread {
             })
             .expect("Thread spawning failed");
 
-        FontCacheThread { chan: chan }
+        FontCacheThread {
+            chan: chan,
+            bundled_fallback_families,
+        }
     }
 
     pub fn add_web_font(
@@ -595,9 +696,19 @@ impl FontSource for FontCacheThread {
             },
         }
     }
+
+    fn fallback_font_families(&self, codepoint: Option<char>) -> Vec<FontFamilyName> {
+        match self.bundled_fallback_families {
+            Some(ref families) => families.clone(),
+            None => fallback_font_families(codepoint)
+                .into_iter()
+                .map(FontFamilyName::from)
+                .collect(),
+        }
+    }
 }
 
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct LowercaseString {
     inner: String,
 }
@@ -19,7 +19,8 @@ use style::properties::style_structs::Font as FontStyleStruct;
 use webrender_api::{FontInstanceKey, FontKey};
 
 use crate::font::{
-    Font, FontDescriptor, FontFamilyDescriptor, FontGroup, FontHandleMethods, FontRef,
+    fallback_font_families, Font, FontDescriptor, FontFamilyDescriptor, FontFamilyName, FontGroup,
+    FontHandleMethods, FontRef,
 };
 use crate::font_cache_thread::FontTemplateInfo;
 use crate::font_template::FontTemplateDescriptor;
@@ -40,6 +41,15 @@ pub trait FontSource {
         template_descriptor: FontTemplateDescriptor,
         family_descriptor: FontFamilyDescriptor,
     ) -> Option<FontTemplateInfo>;
+
+    /// The families to look for a glyph in when none of the families of the text has one,
+    /// after the default family.
+    fn fallback_font_families(&self, codepoint: Option<char>) -> Vec<FontFamilyName> {
+        fallback_font_families(codepoint)
+            .into_iter()
+            .map(FontFamilyName::from)
+            .collect()
+    }
 }
 
 /// The FontContext represents the per-thread/thread state necessary for
@@ -108,6 +118,12 @@ impl<S: FontSource> FontContext<S> {
         font_group
     }
 
+    /// The families to look for a glyph in when none of the families of the text has one,
+    /// after the default family.
+    pub fn fallback_font_families(&self, codepoint: Option<char>) -> Vec<FontFamilyName> {
+        self.font_source.fallback_font_families(codepoint)
+    }
+
     /// Returns a font matching the parameters. Fonts are cached, so repeated calls will return a
     /// reference to the same underlying `Font`.
     pub fn font(
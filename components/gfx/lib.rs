@@ -5,6 +5,7 @@
 #![deny(unsafe_code)]
 
 pub mod font;
+pub mod font_bundle;
 pub mod font_cache_thread;
 pub mod font_context;
 pub mod font_template;
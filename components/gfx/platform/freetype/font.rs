@@ -67,8 +67,17 @@ struct OS2Table {
     y_strikeout_size: i16,
     y_strikeout_position: i16,
     sx_height: i16,
+    fs_selection: u16,
+    s_typo_ascender: i16,
+    s_typo_descender: i16,
+    s_typo_line_gap: i16,
 }
 
+/// The bit of `fsSelection` telling that the typographic metrics of the OS/2 table are the
+/// ones to lay out lines with, rather than those of the `hhea` table.
+/// <https://learn.microsoft.com/en-us/typography/opentype/spec/os2#fsselection>
+const FS_SELECTION_USE_TYPO_METRICS: u16 = 1 << 7;
+
 #[derive(Debug)]
 #[allow(unused)]
 pub struct FontHandle {
@@ -284,18 +293,14 @@ impl FontHandleMethods for FontHandle {
         let underline_size = self.font_units_to_au(face.underline_thickness as f64);
         let underline_offset = self.font_units_to_au(face.underline_position as f64);
         let em_size = self.font_units_to_au(face.units_per_EM as f64);
-        let ascent = self.font_units_to_au(face.ascender as f64);
-        let descent = self.font_units_to_au(face.descender as f64);
         let max_advance = self.font_units_to_au(face.max_advance_width as f64);
 
-        // 'leading' is supposed to be the vertical distance between two baselines,
-        // reflected by the height attribute in freetype.  On OS X (w/ CTFont),
-        // leading represents the distance between the bottom of a line descent to
-        // the top of the next line's ascent or: (line_height - ascent - descent),
-        // see http://stackoverflow.com/a/5635981 for CTFont implementation.
-        // Convert using a formula similar to what CTFont returns for consistency.
-        let height = self.font_units_to_au(face.height as f64);
-        let leading = height - (ascent + descent);
+        // FreeType takes the ascent and descent from the `hhea` table, and tells the distance
+        // between two baselines, which adds the line gap to them, as the height. Its descender
+        // is negative, unlike the descent of the other platforms.
+        let mut ascent = self.font_units_to_au(face.ascender as f64);
+        let mut descent = -self.font_units_to_au(face.descender as f64);
+        let mut line_gap = self.font_units_to_au(face.height as f64) - (ascent + descent);
 
         let mut strikeout_size = Au(0);
         let mut strikeout_offset = Au(0);
@@ -305,6 +310,19 @@ impl FontHandleMethods for FontHandle {
             strikeout_size = self.font_units_to_au(os2.y_strikeout_size as f64);
             strikeout_offset = self.font_units_to_au(os2.y_strikeout_position as f64);
             x_height = self.font_units_to_au(os2.sx_height as f64);
+
+            if os2.fs_selection & FS_SELECTION_USE_TYPO_METRICS != 0 {
+                ascent = self.font_units_to_au(os2.s_typo_ascender as f64);
+                descent = -self.font_units_to_au(os2.s_typo_descender as f64);
+                line_gap = self.font_units_to_au(os2.s_typo_line_gap as f64);
+            }
+        }
+
+        // Fonts whose OS/2 table is older than its version 2 do not tell their x-height, which
+        // CSS then takes to be half an em.
+        // <https://drafts.csswg.org/css-values/#ex>
+        if x_height == Au(0) {
+            x_height = em_size.scale_by(0.5);
         }
 
         let average_advance = self
@@ -317,14 +335,13 @@ impl FontHandleMethods for FontHandle {
             underline_offset: underline_offset,
             strikeout_size: strikeout_size,
             strikeout_offset: strikeout_offset,
-            leading: leading,
             x_height: x_height,
             em_size: em_size,
             ascent: ascent,
-            descent: -descent, // linux font's seem to use the opposite sign from mac
+            descent: descent,
             max_advance: max_advance,
             average_advance: average_advance,
-            line_gap: height,
+            line_gap: line_gap.max(Au(0)),
         };
 
         debug!("Font metrics (@{}px): {:?}", em_size.to_f32_px(), metrics);
@@ -429,6 +446,10 @@ impl<'a> FontHandle {
                 y_strikeout_size: (*os2).yStrikeoutSize,
                 y_strikeout_position: (*os2).yStrikeoutPosition,
                 sx_height: (*os2).sxHeight,
+                fs_selection: (*os2).fsSelection,
+                s_typo_ascender: (*os2).sTypoAscender,
+                s_typo_descender: (*os2).sTypoDescender,
+                s_typo_line_gap: (*os2).sTypoLineGap,
             })
         }
     }
@@ -284,7 +284,6 @@ impl FontHandleMethods for FontHandle {
         let leading = self.ctfont.leading() as f64;
 
         let scale = px_to_pt(self.ctfont.pt_size() as f64) / (ascent + descent);
-        let line_gap = (ascent + descent + leading + 0.5).floor();
 
         let max_advance_width = au_from_pt(bounding_rect.size.width as f64);
         let average_advance = self
@@ -303,14 +302,14 @@ impl FontHandleMethods for FontHandle {
             underline_offset: au_from_pt(self.ctfont.underline_position() as f64),
             strikeout_size: Au(0),   // FIXME(Issue #942)
             strikeout_offset: Au(0), // FIXME(Issue #942)
-            leading: au_from_pt(leading),
             x_height: au_from_pt((self.ctfont.x_height() as f64) * scale),
             em_size: em_size,
             ascent: au_from_pt(ascent * scale),
             descent: au_from_pt(descent * scale),
             max_advance: max_advance_width,
             average_advance: average_advance,
-            line_gap: Au::from_f64_px(line_gap),
+            // Core Text calls the line gap of the font its leading.
+            line_gap: au_from_pt(leading * scale),
         };
         debug!(
             "Font metrics (@{} pt): {:?}",
@@ -0,0 +1,81 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::path::PathBuf;
+
+use gfx::font_bundle::FontBundle;
+use gfx::font_context::FontContextHandle;
+
+fn dejavu_directory(subdirectory: Option<&str>) -> PathBuf {
+    let mut path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "tests",
+        "support",
+        "dejavu-fonts-ttf-2.37",
+    ]
+    .iter()
+    .collect();
+    if let Some(subdirectory) = subdirectory {
+        path.push(subdirectory);
+    }
+    path
+}
+
+#[test]
+fn test_font_bundle_groups_fonts_by_family() {
+    let bundle = FontBundle::load(&dejavu_directory(Some("ttf")), &FontContextHandle::new());
+
+    let mut identifiers = vec![];
+    bundle.for_each_variation("DejaVu Sans Mono", |identifier, bytes| {
+        assert!(!bytes.is_empty());
+        identifiers.push(identifier);
+    });
+    assert_eq!(identifiers.len(), 4);
+    assert!(identifiers
+        .iter()
+        .all(|identifier| identifier.contains("DejaVuSansMono")));
+
+    let mut families = vec![];
+    bundle.for_each_family(|family_name| families.push(family_name.to_string()));
+    assert!(families.windows(2).all(|pair| pair[0] < pair[1]));
+    let fallback_families: Vec<_> = bundle
+        .fallback_families()
+        .iter()
+        .map(|family| family.name().to_owned())
+        .collect();
+    assert_eq!(fallback_families, families);
+}
+
+#[test]
+fn test_font_bundle_generic_families() {
+    let bundle = FontBundle::load(&dejavu_directory(Some("ttf")), &FontContextHandle::new());
+
+    assert_eq!(
+        bundle.generic_family("monospace").as_deref(),
+        Some("dejavu sans mono")
+    );
+    assert_eq!(
+        bundle.generic_family("sans-serif").as_deref(),
+        Some("dejavu sans")
+    );
+    assert_eq!(
+        bundle.generic_family("serif").as_deref(),
+        Some("dejavu serif")
+    );
+
+    // Generic families that no name suggests map to the first family.
+    let first_family = bundle.fallback_families()[0].name().to_owned();
+    assert_eq!(
+        bundle.generic_family("cursive").as_deref(),
+        Some(&*first_family)
+    );
+}
+
+#[test]
+fn test_font_bundle_skips_files_that_are_not_fonts() {
+    let bundle = FontBundle::load(&dejavu_directory(None), &FontContextHandle::new());
+
+    assert!(bundle.fallback_families().is_empty());
+    assert_eq!(bundle.generic_family("serif"), None);
+}
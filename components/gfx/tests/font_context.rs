@@ -12,10 +12,12 @@ use std::rc::Rc;
 use app_units::Au;
 use gfx::font::{
     fallback_font_families, FontDescriptor, FontFamilyDescriptor, FontFamilyName, FontSearchScope,
+    ShapingFlags, ShapingOptions,
 };
 use gfx::font_cache_thread::{FontTemplateInfo, FontTemplates};
 use gfx::font_context::{FontContext, FontContextHandle, FontSource};
 use gfx::font_template::FontTemplateDescriptor;
+use gfx::text::measure::{measure_text, TextMeasurement};
 use servo_arc::Arc;
 use servo_atoms::Atom;
 use style::properties::longhands::font_variant_caps::computed_value::T as FontVariantCaps;
@@ -24,6 +26,7 @@ use style::values::computed::font::{
     FamilyName, FontFamily, FontFamilyList, FontFamilyNameSyntax, FontSize, FontStretch, FontStyle,
     FontWeight, SingleFontFamily,
 };
+use unicode_script::Script;
 use webrender_api::{FontInstanceKey, FontKey, IdNamespace};
 
 struct TestFontSource {
@@ -256,3 +259,64 @@ fn test_font_template_is_cached() {
         "we should only have fetched the template data from the cache thread once"
     );
 }
+
+fn measure(context: &mut FontContext<TestFontSource>, text: &str) -> TextMeasurement {
+    let font_descriptor = FontDescriptor {
+        template_descriptor: FontTemplateDescriptor {
+            weight: FontWeight::normal(),
+            stretch: FontStretch::hundred(),
+            style: FontStyle::normal(),
+        },
+        variant: FontVariantCaps::Normal,
+        pt_size: Au::from_px(16),
+    };
+    let options = ShapingOptions {
+        letter_spacing: None,
+        word_spacing: Au(0),
+        script: Script::Common,
+        flags: ShapingFlags::empty(),
+    };
+    measure_text(
+        context,
+        &font_descriptor,
+        &[FontFamilyName::from("CSSTest ASCII")],
+        text,
+        &options,
+    )
+}
+
+#[test]
+fn test_measure_text_splits_segments_by_font() {
+    let mut context = FontContext::new(TestFontSource::new());
+
+    let measurement = measure(&mut context, "ab á");
+    let segments: Vec<_> = measurement
+        .segments
+        .iter()
+        .map(|segment| (segment.font.borrow().identifier(), segment.range.clone()))
+        .collect();
+    assert_eq!(
+        segments,
+        vec![
+            (Atom::from("csstest-ascii"), 0..3),
+            (Atom::from("fallback"), 3..5)
+        ],
+        "a character without a glyph in the family should start a segment in a fallback font"
+    );
+    assert_eq!(measurement.segments[0].script, Script::Latin);
+    assert!(measurement.advance > Au(0));
+    assert_eq!(
+        measurement.advance,
+        measurement.segments[0].advance + measurement.segments[1].advance
+    );
+}
+
+#[test]
+fn test_measure_text_of_empty_text() {
+    let mut context = FontContext::new(TestFontSource::new());
+
+    let measurement = measure(&mut context, "");
+    assert_eq!(measurement.segments.len(), 1);
+    assert_eq!(measurement.advance, Au(0));
+    assert!(measurement.ascent > Au(0));
+}
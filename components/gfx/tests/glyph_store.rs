@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use app_units::Au;
+use euclid::default::Point2D;
+use gfx::text::glyph::{ByteIndex, GlyphData, GlyphStore};
+use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use range::Range;
+
+/// A glyph store for `text` with a glyph of 10px for each character, taking its id from
+/// the character.
+fn shape(text: &str, is_rtl: bool) -> GlyphStore {
+    let mut glyphs = GlyphStore::new(text, false, is_rtl);
+    for (i, character) in text.char_indices() {
+        let data = GlyphData::new(character as u32 & 0xFFFF, Au::from_px(10), None, true, true);
+        glyphs.add_glyph_for_byte_index(ByteIndex(i as isize), character, &data);
+    }
+    glyphs.finalize_changes();
+    glyphs
+}
+
+fn byte_range(begin: usize, end: usize) -> Range<ByteIndex> {
+    Range::new(ByteIndex(begin as isize), ByteIndex((end - begin) as isize))
+}
+
+fn ids(glyphs: &GlyphStore, range: Range<ByteIndex>) -> Vec<u32> {
+    glyphs
+        .iter_glyphs_for_byte_range(&range)
+        .map(|glyph| glyph.id())
+        .collect()
+}
+
+fn size_of(glyphs: &GlyphStore) -> usize {
+    let mut ops = MallocSizeOfOps::new(servo_allocator::usable_size, None, None);
+    glyphs.size_of(&mut ops)
+}
+
+#[test]
+fn test_glyphs_of_multibyte_characters() {
+    let text = "a日本 語";
+    let glyphs = shape(text, false);
+
+    assert_eq!(glyphs.len(), ByteIndex(text.len() as isize));
+    assert_eq!(
+        ids(&glyphs, byte_range(0, text.len())),
+        text.chars().map(|c| c as u32).collect::<Vec<_>>()
+    );
+    assert_eq!(glyphs.total_advance(), Au::from_px(50));
+    assert_eq!(glyphs.total_word_separators(), 1);
+
+    // The bytes continuing a character have no glyphs.
+    assert_eq!(ids(&glyphs, byte_range(2, 4)), vec![]);
+    assert_eq!(ids(&glyphs, byte_range(2, 5)), vec!['本' as u32]);
+    assert_eq!(
+        ids(&glyphs, byte_range(4, 8)),
+        vec!['本' as u32, ' ' as u32]
+    );
+    assert_eq!(
+        glyphs.advance_for_byte_range(&byte_range(1, 8), Au::from_px(2)),
+        Au::from_px(32)
+    );
+}
+
+#[test]
+fn test_word_separators_of_multibyte_text() {
+    let text = "日 本\u{00A0}語";
+    let glyphs = shape(text, false);
+
+    assert!(!glyphs.char_is_word_separator(ByteIndex(0)));
+    assert!(glyphs.char_is_word_separator(ByteIndex(3)));
+    assert!(!glyphs.char_is_word_separator(ByteIndex(5)));
+    assert!(glyphs.char_is_word_separator(ByteIndex(7)));
+    assert!(!glyphs.char_is_word_separator(ByteIndex(8)));
+    assert_eq!(glyphs.word_separator_count_in_range(&byte_range(0, 12)), 2);
+    assert_eq!(glyphs.word_separator_count_in_range(&byte_range(4, 8)), 1);
+    assert_eq!(glyphs.total_word_separators(), 2);
+}
+
+#[test]
+fn test_glyphs_of_rtl_text() {
+    let text = "שלום";
+    let glyphs = shape(text, true);
+    assert_eq!(
+        ids(&glyphs, byte_range(0, text.len())),
+        text.chars().rev().map(|c| c as u32).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_detailed_glyphs_of_multibyte_characters() {
+    let text = "日本語";
+    let mut glyphs = GlyphStore::new(text, false, false);
+    let offset = Point2D::new(Au::from_px(1), Au::from_px(2));
+    glyphs.add_glyph_for_byte_index(
+        ByteIndex(0),
+        '日',
+        &GlyphData::new(1, Au::from_px(10), None, true, true),
+    );
+    glyphs.add_glyphs_for_byte_index(
+        ByteIndex(3),
+        &[
+            GlyphData::new(2, Au::from_px(10), None, true, true),
+            GlyphData::new(3, Au::from_px(5), Some(offset), true, false),
+        ],
+    );
+    glyphs.add_glyph_for_byte_index(
+        ByteIndex(6),
+        '語',
+        &GlyphData::new(4, Au::from_px(10), Some(offset), true, true),
+    );
+    glyphs.finalize_changes();
+
+    let all = byte_range(0, text.len());
+    assert_eq!(ids(&glyphs, all), vec![1, 2, 3, 4]);
+    let offsets: Vec<_> = glyphs
+        .iter_glyphs_for_byte_range(&all)
+        .map(|glyph| glyph.offset())
+        .collect();
+    assert_eq!(
+        offsets,
+        vec![None, Some(Point2D::zero()), Some(offset), Some(offset)]
+    );
+    assert_eq!(glyphs.total_advance(), Au::from_px(35));
+    assert_eq!(ids(&glyphs, byte_range(3, 6)), vec![2, 3]);
+}
+
+#[test]
+fn test_glyph_store_memory_of_cjk_text() {
+    // The entries of a character used to take four bytes for each of its bytes.
+    let text = "漢字かな交じり文".repeat(4096);
+    let glyphs = shape(&text, false);
+    assert!(size_of(&glyphs) < text.len() * 2);
+}
+
+#[test]
+fn test_glyph_store_memory_of_ascii_text() {
+    // ASCII text has no map of the bytes starting characters, and no detailed glyphs.
+    let text = "The quick brown fox jumps over the lazy dog. ".repeat(1024);
+    let glyphs = shape(&text, false);
+    let entries_size = text.len() * 4;
+    assert!(size_of(&glyphs) >= entries_size);
+    assert!(size_of(&glyphs) < entries_size + entries_size / 8);
+}
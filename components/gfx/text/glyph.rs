@@ -4,12 +4,13 @@
 
 use std::cmp::{Ordering, PartialOrd};
 use std::vec::Vec;
-use std::{fmt, mem, u16};
+use std::{fmt, ops, u16};
 
 use app_units::Au;
 use euclid::default::Point2D;
 pub use gfx_traits::ByteIndex;
 use log::debug;
+use malloc_size_of::{MallocShallowSizeOf, MallocSizeOf, MallocSizeOfOps};
 use range::{self, EachIndex, Range, RangeIndex};
 use serde::{Deserialize, Serialize};
 
@@ -166,21 +167,21 @@ impl DetailedGlyph {
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 struct DetailedGlyphRecord {
-    // source string offset/GlyphEntry offset in the TextRun
-    entry_offset: ByteIndex,
+    // index of the GlyphEntry in the GlyphStore
+    entry_index: u32,
     // offset into the detailed glyphs buffer
-    detail_offset: usize,
+    detail_offset: u32,
 }
 
 impl PartialOrd for DetailedGlyphRecord {
     fn partial_cmp(&self, other: &DetailedGlyphRecord) -> Option<Ordering> {
-        self.entry_offset.partial_cmp(&other.entry_offset)
+        Some(self.cmp(other))
     }
 }
 
 impl Ord for DetailedGlyphRecord {
     fn cmp(&self, other: &DetailedGlyphRecord) -> Ordering {
-        self.entry_offset.cmp(&other.entry_offset)
+        self.entry_index.cmp(&other.entry_index)
     }
 }
 
@@ -190,11 +191,7 @@ impl Ord for DetailedGlyphRecord {
 // then querying without setting.
 #[derive(Clone, Deserialize, Serialize)]
 struct DetailedGlyphStore {
-    // TODO(pcwalton): Allocation of this buffer is expensive. Consider a small-vector
-    // optimization.
     detail_buffer: Vec<DetailedGlyph>,
-    // TODO(pcwalton): Allocation of this buffer is expensive. Consider a small-vector
-    // optimization.
     detail_lookup: Vec<DetailedGlyphRecord>,
     lookup_is_sorted: bool,
 }
@@ -202,21 +199,22 @@ struct DetailedGlyphStore {
 impl<'a> DetailedGlyphStore {
     fn new() -> DetailedGlyphStore {
         DetailedGlyphStore {
-            detail_buffer: vec![], // TODO: default size?
+            detail_buffer: vec![],
             detail_lookup: vec![],
             lookup_is_sorted: false,
         }
     }
 
-    fn add_detailed_glyphs_for_entry(&mut self, entry_offset: ByteIndex, glyphs: &[DetailedGlyph]) {
+    fn add_detailed_glyphs_for_entry(&mut self, entry_index: usize, glyphs: &[DetailedGlyph]) {
+        assert!(self.detail_buffer.len() + glyphs.len() <= u32::MAX as usize);
         let entry = DetailedGlyphRecord {
-            entry_offset: entry_offset,
-            detail_offset: self.detail_buffer.len(),
+            entry_index: entry_index as u32,
+            detail_offset: self.detail_buffer.len() as u32,
         };
 
         debug!(
-            "Adding entry[off={:?}] for detailed glyphs: {:?}",
-            entry_offset, glyphs
+            "Adding entry[idx={}] for detailed glyphs: {:?}",
+            entry_index, glyphs
         );
 
         debug_assert!(!self.detail_lookup.contains(&entry));
@@ -225,14 +223,10 @@ impl<'a> DetailedGlyphStore {
         self.lookup_is_sorted = false;
     }
 
-    fn detailed_glyphs_for_entry(
-        &'a self,
-        entry_offset: ByteIndex,
-        count: u16,
-    ) -> &'a [DetailedGlyph] {
+    fn detailed_glyphs_for_entry(&'a self, entry_index: usize, count: u16) -> &'a [DetailedGlyph] {
         debug!(
-            "Requesting detailed glyphs[n={}] for entry[off={:?}]",
-            count, entry_offset
+            "Requesting detailed glyphs[n={}] for entry[idx={}]",
+            count, entry_index
         );
 
         // FIXME: Is this right? --pcwalton
@@ -242,18 +236,8 @@ impl<'a> DetailedGlyphStore {
         }
 
         assert!((count as usize) <= self.detail_buffer.len());
-        assert!(self.lookup_is_sorted);
-
-        let key = DetailedGlyphRecord {
-            entry_offset: entry_offset,
-            detail_offset: 0, // unused
-        };
 
-        let i = self
-            .detail_lookup
-            .binary_search(&key)
-            .expect("Invalid index not found in detailed glyph lookup table!");
-        let main_detail_offset = self.detail_lookup[i].detail_offset;
+        let main_detail_offset = self.detail_offset_for_entry(entry_index);
         assert!(main_detail_offset + (count as usize) <= self.detail_buffer.len());
         // return a slice into the buffer
         &self.detail_buffer[main_detail_offset..main_detail_offset + count as usize]
@@ -261,14 +245,21 @@ impl<'a> DetailedGlyphStore {
 
     fn detailed_glyph_with_index(
         &'a self,
-        entry_offset: ByteIndex,
+        entry_index: usize,
         detail_offset: u16,
     ) -> &'a DetailedGlyph {
         assert!((detail_offset as usize) <= self.detail_buffer.len());
+
+        let main_detail_offset = self.detail_offset_for_entry(entry_index);
+        assert!(main_detail_offset + (detail_offset as usize) < self.detail_buffer.len());
+        &self.detail_buffer[main_detail_offset + (detail_offset as usize)]
+    }
+
+    fn detail_offset_for_entry(&self, entry_index: usize) -> usize {
         assert!(self.lookup_is_sorted);
 
         let key = DetailedGlyphRecord {
-            entry_offset: entry_offset,
+            entry_index: entry_index as u32,
             detail_offset: 0, // unused
         };
 
@@ -276,9 +267,7 @@ impl<'a> DetailedGlyphStore {
             .detail_lookup
             .binary_search(&key)
             .expect("Invalid index not found in detailed glyph lookup table!");
-        let main_detail_offset = self.detail_lookup[i].detail_offset;
-        assert!(main_detail_offset + (detail_offset as usize) < self.detail_buffer.len());
-        &self.detail_buffer[main_detail_offset + (detail_offset as usize)]
+        self.detail_lookup[i].detail_offset as usize
     }
 
     fn ensure_sorted(&mut self) {
@@ -286,28 +275,67 @@ impl<'a> DetailedGlyphStore {
             return;
         }
 
-        // Sorting a unique vector is surprisingly hard. The following
-        // code is a good argument for using DVecs, but they require
-        // immutable locations thus don't play well with freezing.
-
-        // Thar be dragons here. You have been warned. (Tips accepted.)
-        let mut unsorted_records: Vec<DetailedGlyphRecord> = vec![];
-        mem::swap(&mut self.detail_lookup, &mut unsorted_records);
-        let mut mut_records: Vec<DetailedGlyphRecord> = unsorted_records;
-        mut_records.sort_by(|a, b| {
-            if a < b {
-                Ordering::Less
-            } else {
-                Ordering::Greater
-            }
-        });
-        let mut sorted_records = mut_records;
-        mem::swap(&mut self.detail_lookup, &mut sorted_records);
-
+        // The records are unique, as each entry gets its detailed glyphs only once.
+        self.detail_lookup.sort_unstable();
         self.lookup_is_sorted = true;
     }
 }
 
+impl MallocSizeOf for DetailedGlyphStore {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        self.detail_buffer.shallow_size_of(ops) + self.detail_lookup.shallow_size_of(ops)
+    }
+}
+
+/// The bytes of a text that start a character. Only these have a `GlyphEntry`, so this maps
+/// the byte indices of the text to the entries, taking 12 bytes for every 64 bytes of text.
+#[derive(Clone, Deserialize, Serialize)]
+struct CharStarts {
+    /// The length of the text, in bytes.
+    length: usize,
+    /// One bit for each byte of the text, set if the byte starts a character. There is one
+    /// more bit than there are bytes, so that the end of the text can be looked up too.
+    bits: Vec<u64>,
+    /// The number of characters that start before each block of 64 bytes.
+    ranks: Vec<u32>,
+}
+
+impl CharStarts {
+    fn new(text: &str) -> CharStarts {
+        assert!(text.len() <= u32::MAX as usize);
+        let mut bits = vec![0u64; text.len() / 64 + 1];
+        for (i, _) in text.char_indices() {
+            bits[i / 64] |= 1 << (i % 64);
+        }
+
+        let mut ranks = Vec::with_capacity(bits.len());
+        let mut rank = 0;
+        for block in bits.iter() {
+            ranks.push(rank);
+            rank += block.count_ones();
+        }
+
+        CharStarts {
+            length: text.len(),
+            bits,
+            ranks,
+        }
+    }
+
+    /// The number of characters that start before the byte `i`, which is the index of the
+    /// entry of the character starting at `i`, if there is one.
+    #[inline]
+    fn rank(&self, i: usize) -> usize {
+        let bits_before = self.bits[i / 64] & ((1 << (i % 64)) - 1);
+        self.ranks[i / 64] as usize + bits_before.count_ones() as usize
+    }
+
+    #[inline]
+    fn starts_char(&self, i: usize) -> bool {
+        (self.bits[i / 64] & (1 << (i % 64))) != 0
+    }
+}
+
 // This struct is used by GlyphStore clients to provide new glyph data.
 // It should be allocated on the stack and passed by reference to GlyphStore.
 #[derive(Clone, Copy)]
@@ -341,22 +369,20 @@ impl GlyphData {
 // This enum is a proxy that's provided to GlyphStore clients when iterating
 // through glyphs (either for a particular TextRun offset, or all glyphs).
 // Rather than eagerly assembling and copying glyph data, it only retrieves
-// values as they are needed from the GlyphStore, using provided offsets.
+// values as they are needed from the GlyphStore, using the index of the
+// character's entry.
 #[derive(Clone, Copy)]
 pub enum GlyphInfo<'a> {
-    Simple(&'a GlyphStore, ByteIndex),
-    Detail(&'a GlyphStore, ByteIndex, u16),
+    Simple(&'a GlyphStore, usize),
+    Detail(&'a GlyphStore, usize, u16),
 }
 
 impl<'a> GlyphInfo<'a> {
     pub fn id(self) -> GlyphId {
         match self {
-            GlyphInfo::Simple(store, entry_i) => store.entry_buffer[entry_i.to_usize()].id(),
+            GlyphInfo::Simple(store, entry_i) => store.entry_buffer[entry_i].id(),
             GlyphInfo::Detail(store, entry_i, detail_j) => {
-                store
-                    .detail_store
-                    .detailed_glyph_with_index(entry_i, detail_j)
-                    .id
+                store.detailed_glyph_with_index(entry_i, detail_j).id
             },
         }
     }
@@ -365,12 +391,9 @@ impl<'a> GlyphInfo<'a> {
     // FIXME: Resolution conflicts with IteratorUtil trait so adding trailing _
     pub fn advance(self) -> Au {
         match self {
-            GlyphInfo::Simple(store, entry_i) => store.entry_buffer[entry_i.to_usize()].advance(),
+            GlyphInfo::Simple(store, entry_i) => store.entry_buffer[entry_i].advance(),
             GlyphInfo::Detail(store, entry_i, detail_j) => {
-                store
-                    .detail_store
-                    .detailed_glyph_with_index(entry_i, detail_j)
-                    .advance
+                store.detailed_glyph_with_index(entry_i, detail_j).advance
             },
         }
     }
@@ -379,12 +402,9 @@ impl<'a> GlyphInfo<'a> {
     pub fn offset(self) -> Option<Point2D<Au>> {
         match self {
             GlyphInfo::Simple(_, _) => None,
-            GlyphInfo::Detail(store, entry_i, detail_j) => Some(
-                store
-                    .detail_store
-                    .detailed_glyph_with_index(entry_i, detail_j)
-                    .offset,
-            ),
+            GlyphInfo::Detail(store, entry_i, detail_j) => {
+                Some(store.detailed_glyph_with_index(entry_i, detail_j).offset)
+            },
         }
     }
 
@@ -394,22 +414,25 @@ impl<'a> GlyphInfo<'a> {
             GlyphInfo::Detail(store, entry_i, _) => (store, entry_i),
         };
 
-        store.char_is_word_separator(entry_i)
+        store.entry_buffer[entry_i].char_is_word_separator()
     }
 }
 
 /// Stores the glyph data belonging to a text run.
 ///
-/// Simple glyphs are stored inline in the `entry_buffer`, detailed glyphs are
-/// stored as pointers into the `detail_store`.
+/// There is one entry for each character of the text, rather than for each byte, so that
+/// text in scripts taking several bytes per character, such as CJK, does not pay for the
+/// bytes continuing a character. Simple glyphs are stored inline in the `entry_buffer`,
+/// detailed glyphs are stored as pointers into the `detail_store`, which is only allocated
+/// for the text runs that have some.
 ///
 /// ~~~ascii
 /// +- GlyphStore --------------------------------+
 /// |               +---+---+---+---+---+---+---+ |
-/// | entry_buffer: |   | s |   | s |   | s | s | |  d = detailed
-/// |               +-|-+---+-|-+---+-|-+---+---+ |  s = simple
-/// |                 |       |       |           |
-/// |                 |   +---+-------+           |
+/// | entry_buffer: | d | s | d | s | s | s | s | |  d = detailed
+/// |               +-|-+---+-|-+---+---+---+---+ |  s = simple
+/// |                 |       |                   |
+/// |                 |   +---+                   |
 /// |                 |   |                       |
 /// |               +-V-+-V-+                     |
 /// | detail_store: | d | d |                     |
@@ -418,16 +441,16 @@ impl<'a> GlyphInfo<'a> {
 /// ~~~
 #[derive(Clone, Deserialize, Serialize)]
 pub struct GlyphStore {
-    // TODO(pcwalton): Allocation of this buffer is expensive. Consider a small-vector
-    // optimization.
-    /// A buffer of glyphs within the text run, in the order in which they
-    /// appear in the input text.
-    /// Any changes will also need to be reflected in
-    /// transmute_entry_buffer_to_u32_buffer().
+    /// A buffer of glyphs within the text run, one for each character, in the order in
+    /// which they appear in the input text.
     entry_buffer: Vec<GlyphEntry>,
+    /// The bytes of the text that start a character, to find their entry in the
+    /// `entry_buffer`, or `None` if the text is ASCII and every byte has an entry.
+    char_starts: Option<CharStarts>,
     /// A store of the detailed glyph data. Detailed glyphs contained in the
-    /// `entry_buffer` point to locations in this data structure.
-    detail_store: DetailedGlyphStore,
+    /// `entry_buffer` point to locations in this data structure. Also used to check if
+    /// fast path should be used in glyph iteration.
+    detail_store: Option<Box<DetailedGlyphStore>>,
 
     /// A cache of the advance of the entire glyph store.
     total_advance: Au,
@@ -436,25 +459,33 @@ pub struct GlyphStore {
     /// See <https://drafts.csswg.org/css-text/#word-separator>.
     total_word_separators: usize,
 
-    /// Used to check if fast path should be used in glyph iteration.
-    has_detailed_glyphs: bool,
     is_whitespace: bool,
     is_rtl: bool,
 }
 
 impl<'a> GlyphStore {
-    /// Initializes the glyph store, but doesn't actually shape anything.
+    /// Initializes the glyph store for `text`, but doesn't actually shape anything.
     ///
     /// Use the `add_*` methods to store glyph data.
-    pub fn new(length: usize, is_whitespace: bool, is_rtl: bool) -> GlyphStore {
-        assert!(length > 0);
+    pub fn new(text: &str, is_whitespace: bool, is_rtl: bool) -> GlyphStore {
+        assert!(!text.is_empty());
+
+        let char_starts = if text.is_ascii() {
+            None
+        } else {
+            Some(CharStarts::new(text))
+        };
+        let char_count = match char_starts {
+            Some(ref char_starts) => char_starts.rank(text.len()),
+            None => text.len(),
+        };
 
         GlyphStore {
-            entry_buffer: vec![GlyphEntry::initial(); length],
-            detail_store: DetailedGlyphStore::new(),
+            entry_buffer: vec![GlyphEntry::initial(); char_count],
+            char_starts,
+            detail_store: None,
             total_advance: Au(0),
             total_word_separators: 0,
-            has_detailed_glyphs: false,
             is_whitespace: is_whitespace,
             is_rtl: is_rtl,
         }
@@ -465,9 +496,14 @@ impl<'a> GlyphStore {
         self.total_advance
     }
 
+    /// The length of the text, in bytes.
     #[inline]
     pub fn len(&self) -> ByteIndex {
-        ByteIndex(self.entry_buffer.len() as isize)
+        let length = match self.char_starts {
+            Some(ref char_starts) => char_starts.length,
+            None => self.entry_buffer.len(),
+        };
+        ByteIndex(length as isize)
     }
 
     #[inline]
@@ -481,7 +517,9 @@ impl<'a> GlyphStore {
     }
 
     pub fn finalize_changes(&mut self) {
-        self.detail_store.ensure_sorted();
+        if let Some(ref mut detail_store) = self.detail_store {
+            detail_store.ensure_sorted();
+        }
         self.cache_total_advance_and_word_seperators()
     }
 
@@ -499,6 +537,37 @@ impl<'a> GlyphStore {
         self.total_word_separators = total_word_separators;
     }
 
+    /// The index in the `entry_buffer` of the character starting at the byte `i`, or of the
+    /// first one after it if `i` continues a character.
+    #[inline]
+    fn entry_index(&self, i: ByteIndex) -> usize {
+        match self.char_starts {
+            Some(ref char_starts) => char_starts.rank(i.to_usize()),
+            None => i.to_usize(),
+        }
+    }
+
+    #[inline]
+    fn byte_starts_char(&self, i: ByteIndex) -> bool {
+        match self.char_starts {
+            Some(ref char_starts) => char_starts.starts_char(i.to_usize()),
+            None => true,
+        }
+    }
+
+    fn detailed_glyph_with_index(&self, entry_index: usize, detail_offset: u16) -> &DetailedGlyph {
+        self.detail_store
+            .as_ref()
+            .expect("Detailed glyph in a store without any!")
+            .detailed_glyph_with_index(entry_index, detail_offset)
+    }
+
+    fn add_detailed_glyphs_for_entry(&mut self, entry_index: usize, glyphs: &[DetailedGlyph]) {
+        self.detail_store
+            .get_or_insert_with(|| Box::new(DetailedGlyphStore::new()))
+            .add_detailed_glyphs_for_entry(entry_index, glyphs);
+    }
+
     /// Adds a single glyph.
     pub fn add_glyph_for_byte_index(&mut self, i: ByteIndex, character: char, data: &GlyphData) {
         let glyph_is_compressible = is_simple_glyph_id(data.id) &&
@@ -508,13 +577,14 @@ impl<'a> GlyphStore {
 
         debug_assert!(data.ligature_start); // can't compress ligature continuation glyphs.
         debug_assert!(i < self.len());
+        debug_assert!(self.byte_starts_char(i));
 
+        let entry_index = self.entry_index(i);
         let mut entry = if glyph_is_compressible {
             GlyphEntry::simple(data.id, data.advance)
         } else {
             let glyph = &[DetailedGlyph::new(data.id, data.advance, data.offset)];
-            self.has_detailed_glyphs = true;
-            self.detail_store.add_detailed_glyphs_for_entry(i, glyph);
+            self.add_detailed_glyphs_for_entry(entry_index, glyph);
             GlyphEntry::complex(data.cluster_start, data.ligature_start, 1)
         };
 
@@ -534,12 +604,13 @@ impl<'a> GlyphStore {
             entry.set_char_is_word_separator();
         }
 
-        self.entry_buffer[i.to_usize()] = entry;
+        self.entry_buffer[entry_index] = entry;
     }
 
     pub fn add_glyphs_for_byte_index(&mut self, i: ByteIndex, data_for_glyphs: &[GlyphData]) {
         assert!(i < self.len());
         assert!(data_for_glyphs.len() > 0);
+        debug_assert!(self.byte_starts_char(i));
 
         let glyph_count = data_for_glyphs.len();
 
@@ -554,9 +625,8 @@ impl<'a> GlyphStore {
             })
             .collect();
 
-        self.has_detailed_glyphs = true;
-        self.detail_store
-            .add_detailed_glyphs_for_entry(i, &glyphs_vec);
+        let entry_index = self.entry_index(i);
+        self.add_detailed_glyphs_for_entry(entry_index, &glyphs_vec);
 
         let entry = GlyphEntry::complex(
             first_glyph_data.cluster_start,
@@ -569,7 +639,7 @@ impl<'a> GlyphStore {
             i, glyph_count, entry
         );
 
-        self.entry_buffer[i.to_usize()] = entry;
+        self.entry_buffer[entry_index] = entry;
     }
 
     #[inline]
@@ -583,12 +653,8 @@ impl<'a> GlyphStore {
 
         GlyphIterator {
             store: self,
-            byte_index: if self.is_rtl {
-                range.end()
-            } else {
-                range.begin() - ByteIndex(1)
-            },
-            byte_range: *range,
+            entry_index: 0,
+            entry_range: self.entry_index(range.begin())..self.entry_index(range.end()),
             glyph_range: None,
         }
     }
@@ -623,7 +689,7 @@ impl<'a> GlyphStore {
     pub fn advance_for_byte_range(&self, range: &Range<ByteIndex>, extra_word_spacing: Au) -> Au {
         if range.begin() == ByteIndex(0) && range.end() == self.len() {
             self.total_advance + extra_word_spacing * (self.total_word_separators as i32)
-        } else if !self.has_detailed_glyphs {
+        } else if self.detail_store.is_none() {
             self.advance_for_byte_range_simple_glyphs(range, extra_word_spacing)
         } else {
             self.advance_for_byte_range_simple_glyphs(range, extra_word_spacing)
@@ -646,26 +712,42 @@ impl<'a> GlyphStore {
             })
     }
 
+    /// Whether the character starting at the byte `i` is a word separator, which is never
+    /// the case of the bytes continuing a character.
     pub fn char_is_word_separator(&self, i: ByteIndex) -> bool {
         assert!(i < self.len());
-        self.entry_buffer[i.to_usize()].char_is_word_separator()
+        self.byte_starts_char(i) && self.entry_buffer[self.entry_index(i)].char_is_word_separator()
     }
 
     pub fn word_separator_count_in_range(&self, range: &Range<ByteIndex>) -> u32 {
-        let mut spaces = 0;
-        for index in range.each_index() {
-            if self.char_is_word_separator(index) {
-                spaces += 1
-            }
-        }
-        spaces
+        assert!(range.end() <= self.len());
+        let entries = self.entry_index(range.begin())..self.entry_index(range.end());
+        self.entry_buffer[entries]
+            .iter()
+            .filter(|entry| entry.char_is_word_separator())
+            .count() as u32
+    }
+}
+
+impl MallocSizeOf for GlyphStore {
+    fn size_of(&self, ops: &mut MallocSizeOfOps) -> usize {
+        let char_starts_size = match self.char_starts {
+            Some(ref char_starts) => {
+                char_starts.bits.shallow_size_of(ops) + char_starts.ranks.shallow_size_of(ops)
+            },
+            None => 0,
+        };
+        self.entry_buffer.shallow_size_of(ops) + char_starts_size + self.detail_store.size_of(ops)
     }
 }
 
 impl fmt::Debug for GlyphStore {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         write!(formatter, "GlyphStore:\n")?;
-        let mut detailed_buffer = self.detail_store.detail_buffer.iter();
+        let mut detailed_buffer = self
+            .detail_store
+            .iter()
+            .flat_map(|detail_store| detail_store.detail_buffer.iter());
         for entry in self.entry_buffer.iter() {
             if entry.is_simple() {
                 write!(
@@ -697,8 +779,10 @@ impl fmt::Debug for GlyphStore {
 /// An iterator over the glyphs in a byte range in a `GlyphStore`.
 pub struct GlyphIterator<'a> {
     store: &'a GlyphStore,
-    byte_index: ByteIndex,
-    byte_range: Range<ByteIndex>,
+    /// The index of the entry whose detailed glyphs are being iterated, if any.
+    entry_index: usize,
+    /// The entries of the characters in the byte range that are left.
+    entry_range: ops::Range<usize>,
     glyph_range: Option<EachIndex<ByteIndex>>,
 }
 
@@ -710,7 +794,7 @@ impl<'a> GlyphIterator<'a> {
             Some(j) => {
                 Some(GlyphInfo::Detail(
                     self.store,
-                    self.byte_index,
+                    self.entry_index,
                     j.get() as u16, /* ??? */
                 ))
             },
@@ -724,11 +808,18 @@ impl<'a> GlyphIterator<'a> {
 
     // Slow path when there is a complex glyph.
     #[inline(never)]
-    fn next_complex_glyph(&mut self, entry: &GlyphEntry, i: ByteIndex) -> Option<GlyphInfo<'a>> {
+    fn next_complex_glyph(&mut self, entry: &GlyphEntry, i: usize) -> Option<GlyphInfo<'a>> {
+        let glyph_count = entry.glyph_count();
+        if glyph_count == 0 {
+            return self.next();
+        }
         let glyphs = self
             .store
             .detail_store
-            .detailed_glyphs_for_entry(i, entry.glyph_count());
+            .as_ref()
+            .expect("Complex glyph in a store without detailed glyphs!")
+            .detailed_glyphs_for_entry(i, glyph_count);
+        self.entry_index = i;
         self.glyph_range = Some(range::each_index(
             ByteIndex(0),
             ByteIndex(glyphs.len() as isize),
@@ -753,19 +844,13 @@ impl<'a> Iterator for GlyphIterator<'a> {
             return self.next_glyph_range();
         }
 
-        // No glyph range. Look at next byte.
-        self.byte_index = self.byte_index +
-            if self.store.is_rtl {
-                ByteIndex(-1)
-            } else {
-                ByteIndex(1)
-            };
-        let i = self.byte_index;
-        if !self.byte_range.contains(i) {
-            return None;
-        }
-        debug_assert!(i < self.store.len());
-        let entry = self.store.entry_buffer[i.to_usize()];
+        // No glyph range. Look at next character.
+        let i = if self.store.is_rtl {
+            self.entry_range.next_back()?
+        } else {
+            self.entry_range.next()?
+        };
+        let entry = self.store.entry_buffer[i];
         if entry.is_simple() {
             Some(GlyphInfo::Simple(self.store, i))
         } else {
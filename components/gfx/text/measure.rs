@@ -0,0 +1,140 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Shaping of text outside of any document, for embedders drawing their own user interface
+//! with the fonts that pages are rendered with.
+
+use std::ops::Range;
+use std::rc::Rc;
+
+use app_units::Au;
+use unicode_script::Script;
+use xi_unicode::LineBreakLeafIter;
+
+use crate::font::{FontDescriptor, FontFamilyName, FontGroup, FontRef, ShapingOptions};
+use crate::font_context::{FontContext, FontSource};
+use crate::text::text_run::{GlyphRun, TextRun};
+use crate::text::util::char_does_not_change_font;
+
+/// A part of a text shaped with a single font and script.
+#[derive(Debug)]
+pub struct ShapedSegment {
+    pub font: FontRef,
+    pub script: Script,
+    /// The byte range of the segment in the text.
+    pub range: Range<usize>,
+    /// The glyphs of the segment, split at the opportunities to break the line. Their ranges
+    /// are relative to the start of the segment.
+    pub glyph_runs: Vec<GlyphRun>,
+    pub advance: Au,
+}
+
+/// The glyphs of a text shaped on a single line, and its size.
+#[derive(Debug)]
+pub struct TextMeasurement {
+    pub segments: Vec<ShapedSegment>,
+    pub advance: Au,
+    /// The largest ascent of the fonts of the text.
+    pub ascent: Au,
+    /// The largest descent of the fonts of the text.
+    pub descent: Au,
+}
+
+/// Shape `text` on a single line the way the text of pages is shaped: each character is
+/// given the first of `families`, or else the first fallback font, that has a glyph for it,
+/// and the text is split into segments wherever the font or the script changes. The script
+/// of `options` is replaced with that of each segment.
+pub fn measure_text<S: FontSource>(
+    font_context: &mut FontContext<S>,
+    descriptor: &FontDescriptor,
+    families: &[FontFamilyName],
+    text: &str,
+    options: &ShapingOptions,
+) -> TextMeasurement {
+    let mut font_group = FontGroup::with_families(descriptor.clone(), families);
+    let segments = segment_text(font_context, &mut font_group, text);
+
+    let mut measurement = TextMeasurement {
+        segments: Vec::with_capacity(segments.len()),
+        advance: Au(0),
+        ascent: Au(0),
+        descent: Au(0),
+    };
+    // The line breaker goes on from one segment to the next, as it does in layout.
+    let mut linebreaker: Option<LineBreakLeafIter> = None;
+    for (font, script, range) in segments {
+        let options = ShapingOptions { script, ..*options };
+        let glyph_runs = {
+            let mut font = font.borrow_mut();
+            measurement.ascent = measurement.ascent.max(font.metrics.ascent);
+            measurement.descent = measurement.descent.max(font.metrics.descent);
+            TextRun::break_and_shape(&mut font, &text[range.clone()], &options, &mut linebreaker).0
+        };
+        let advance = glyph_runs.iter().fold(Au(0), |advance, run| {
+            advance + run.glyph_store.total_advance()
+        });
+        measurement.advance += advance;
+        measurement.segments.push(ShapedSegment {
+            font,
+            script,
+            range,
+            glyph_runs,
+            advance,
+        });
+    }
+    measurement
+}
+
+/// Split `text` where the font or the script changes, like layout splits text runs.
+/// Characters that do not change the font stay in the segment before them, or the first
+/// segment if they start the text.
+fn segment_text<S: FontSource>(
+    font_context: &mut FontContext<S>,
+    font_group: &mut FontGroup,
+    text: &str,
+) -> Vec<(FontRef, Script, Range<usize>)> {
+    fn is_specific(script: Script) -> bool {
+        script != Script::Common && script != Script::Inherited
+    }
+
+    let mut segments: Vec<(FontRef, Script, Range<usize>)> = Vec::new();
+    for (index, character) in text.char_indices() {
+        if char_does_not_change_font(character) {
+            continue;
+        }
+        let font = match font_group.find_by_codepoint(font_context, character) {
+            Some(font) => font,
+            None => continue,
+        };
+
+        let script = Script::from(character);
+        if let Some((current_font, current_script, _)) = segments.last_mut() {
+            if Rc::ptr_eq(current_font, &font) {
+                if !is_specific(*current_script) && is_specific(script) {
+                    *current_script = script;
+                }
+                if script == *current_script || !is_specific(script) {
+                    continue;
+                }
+            }
+        }
+
+        let start = match segments.last_mut() {
+            Some((_, _, range)) => {
+                range.end = index;
+                index
+            },
+            None => 0,
+        };
+        segments.push((font, script, start..text.len()));
+    }
+
+    // The text only has characters that do not change the font, if any.
+    if segments.is_empty() {
+        if let Some(font) = font_group.first(font_context) {
+            segments.push((font, Script::Common, 0..text.len()));
+        }
+    }
+    segments
+}
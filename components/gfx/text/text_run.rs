@@ -293,13 +293,19 @@ impl<'a> TextRun {
         // TODO(Issue #98): using inter-char and inter-word spacing settings when measuring text
         self.natural_word_slices_in_range(range)
             .fold(Au(0), |advance, slice| {
-                advance +
-                    slice
-                        .glyphs
-                        .advance_for_byte_range(&slice.range, self.extra_word_spacing)
+                advance + self.advance_for_slice(&slice)
             })
     }
 
+    /// The advance of the glyphs of a slice. The glyph store of each word is shared by all the
+    /// occurrences of the word shaped with the same font and options, through the shape cache of
+    /// the font, and it caches its advance, so measuring a whole word does not walk its glyphs.
+    fn advance_for_slice(&self, slice: &TextRunSlice) -> Au {
+        slice
+            .glyphs
+            .advance_for_byte_range(&slice.range, self.extra_word_spacing)
+    }
+
     pub fn metrics_for_range(&self, range: &Range<ByteIndex>) -> RunMetrics {
         RunMetrics::new(
             self.advance_for_range(range),
@@ -325,14 +331,14 @@ impl<'a> TextRun {
         self.natural_word_slices_in_range(range)
             .fold(Au(0), |max_piece_width, slice| {
                 debug!("iterated on {:?}[{:?}]", slice.offset, slice.range);
-                max(max_piece_width, self.advance_for_range(&slice.range))
+                max(max_piece_width, self.advance_for_slice(&slice))
             })
     }
 
     pub fn minimum_splittable_inline_size(&self, range: &Range<ByteIndex>) -> Au {
         match self.natural_word_slices_in_range(range).next() {
             None => Au(0),
-            Some(slice) => self.advance_for_range(&slice.range),
+            Some(slice) => self.advance_for_slice(&slice),
         }
     }
 
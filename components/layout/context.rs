@@ -16,7 +16,7 @@ use gfx::font_context::FontContext;
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
 use msg::constellation_msg::PipelineId;
 use net_traits::image_cache::{
-    ImageCache, ImageCacheResult, ImageOrMetadataAvailable, UsePlaceholder,
+    ImageCache, ImageCacheResult, ImageDecoding, ImageOrMetadataAvailable, UsePlaceholder,
 };
 use parking_lot::RwLock;
 use script_layout_interface::{PendingImage, PendingImageState};
@@ -112,6 +112,7 @@ impl<'a> LayoutContext<'a> {
             self.origin.clone(),
             None,
             use_placeholder,
+            ImageDecoding::Sync,
         );
 
         match cache_result {
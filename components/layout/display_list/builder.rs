@@ -3084,6 +3084,73 @@ pub struct IndexableTextItem {
     pub baseline_origin: Point2D<Au>,
 }
 
+impl IndexableTextItem {
+    /// The number of characters of the text of this item.
+    fn char_count(&self) -> usize {
+        self.text().chars().count()
+    }
+
+    fn text(&self) -> &str {
+        &self.text_run.text[self.range.begin().to_usize()..self.range.end().to_usize()]
+    }
+
+    /// Returns the caret rectangle before the character at `index` in the text of this
+    /// item, which is on its right in right-to-left text.
+    fn caret_rect(&self, index: usize) -> Rect<Au> {
+        let prefix_length = self
+            .text()
+            .char_indices()
+            .nth(index)
+            .map_or(self.range.length(), |(offset, _)| {
+                ByteIndex(offset as isize)
+            });
+        let advance = self
+            .text_run
+            .advance_for_range(&Range::new(self.range.begin(), prefix_length));
+        let x = if self.text_run.bidi_level.is_rtl() {
+            self.baseline_origin.x + self.text_run.advance_for_range(&self.range) - advance
+        } else {
+            self.baseline_origin.x + advance
+        };
+        let metrics = &self.text_run.font_metrics;
+        Rect::new(
+            Point2D::new(x, self.baseline_origin.y - metrics.ascent),
+            Size2D::new(Au(0), metrics.ascent + metrics.descent),
+        )
+    }
+
+    /// Returns the index of the character of this item at `x`, horizontally.
+    fn index_at(&self, x: Au) -> usize {
+        let mut advance = x - self.baseline_origin.x;
+        if self.text_run.bidi_level.is_rtl() {
+            advance = self.text_run.advance_for_range(&self.range) - advance;
+        }
+        self.text_run
+            .range_index_of_advance(&self.range, advance)
+            .min(self.char_count())
+    }
+
+    /// The distance from a point to the line and the horizontal extent of this item.
+    fn distance_to(&self, point: Point2D<Au>) -> (Au, Au) {
+        let distance = |value: Au, start: Au, end: Au| {
+            if value < start {
+                start - value
+            } else if value > end {
+                value - end
+            } else {
+                Au(0)
+            }
+        };
+        let line = self.caret_rect(0);
+        let start = self.baseline_origin.x;
+        let end = start + self.text_run.advance_for_range(&self.range);
+        (
+            distance(point.y, line.min_y(), line.max_y()),
+            distance(point.x, start, end),
+        )
+    }
+}
+
 #[derive(Default)]
 pub struct IndexableText {
     inner: FnvHashMap<OpaqueNode, Vec<IndexableTextItem>>,
@@ -3111,6 +3178,35 @@ impl IndexableText {
                 .range_index_of_advance(&item[0].range, offset.x),
         )
     }
+
+    /// Returns the caret rectangle at a character offset within the text of a node. An offset
+    /// at the boundary of two items is placed at the end of the first one, and an offset past
+    /// the end of the text at the end of the last one.
+    pub fn caret_rect(&self, node: OpaqueNode, offset: usize) -> Option<Rect<Au>> {
+        let items = self.inner.get(&node)?;
+        let mut remaining = offset;
+        for (index, item) in items.iter().enumerate() {
+            let char_count = item.char_count();
+            if remaining <= char_count || index + 1 == items.len() {
+                return Some(item.caret_rect(remaining.min(char_count)));
+            }
+            remaining -= char_count;
+        }
+        None
+    }
+
+    /// Returns the character offset within the text of a node of the caret position closest
+    /// to a point, in stacking-relative coordinates. The point is looked for on the line it is
+    /// vertically closest to, which the text of the node is split in items over.
+    pub fn caret_offset(&self, node: OpaqueNode, point: Point2D<Au>) -> Option<usize> {
+        let items = self.inner.get(&node)?;
+        let (index, item) = items
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, item)| item.distance_to(point))?;
+        let preceding: usize = items[..index].iter().map(|item| item.char_count()).sum();
+        Some(preceding + item.index_at(point.x))
+    }
 }
 
 trait ToF32Px {
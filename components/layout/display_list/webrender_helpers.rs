@@ -181,6 +181,7 @@ impl DisplayItem {
             let hit_test_index = state.compositor_info.add_hit_test_info(
                 base.metadata.node.0 as u64,
                 Some(cursor),
+                None,
                 current_scroll_node_id,
             );
 
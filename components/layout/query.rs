@@ -14,8 +14,9 @@ use euclid::Size2D as TypedSize2D;
 use ipc_channel::ipc::IpcSender;
 use msg::constellation_msg::PipelineId;
 use script_layout_interface::rpc::{
-    ContentBoxResponse, ContentBoxesResponse, LayoutRPC, NodeGeometryResponse,
-    NodeScrollIdResponse, OffsetParentResponse, ResolvedStyleResponse, TextIndexResponse,
+    CaretPositionResponse, CaretRectResponse, ContentBoxResponse, ContentBoxesResponse, LayoutRPC,
+    NodeGeometryResponse, NodeScrollIdResponse, OffsetParentResponse, ResolvedStyleResponse,
+    TextIndexResponse,
 };
 use script_layout_interface::wrapper_traits::{
     LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
@@ -93,6 +94,12 @@ pub struct LayoutThreadData {
     /// Index in a text fragment. We need this do determine the insertion point.
     pub text_index_response: TextIndexResponse,
 
+    /// A queued response for the caret rectangle at an offset in the text of a node.
+    pub caret_rect_response: CaretRectResponse,
+
+    /// A queued response for the caret position closest to a point.
+    pub caret_position_response: CaretPositionResponse,
+
     /// A queued response for the list of nodes at a given point.
     pub nodes_from_point_response: Vec<UntrustedNodeAddress>,
 
@@ -199,6 +206,18 @@ impl LayoutRPC for LayoutRPCImpl {
         rw_data.text_index_response.clone()
     }
 
+    fn caret_rect(&self) -> CaretRectResponse {
+        let LayoutRPCImpl(rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.caret_rect_response.clone()
+    }
+
+    fn caret_position(&self) -> CaretPositionResponse {
+        let LayoutRPCImpl(rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.caret_position_response.clone()
+    }
+
     fn element_inner_text(&self) -> String {
         let LayoutRPCImpl(rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -551,7 +551,7 @@ pub fn font_metrics_for_style(
 pub fn line_height_from_style(style: &ComputedValues, metrics: &FontMetrics) -> Au {
     let font_size = style.get_font().font_size.computed_size();
     match style.get_inherited_text().line_height {
-        LineHeight::Normal => metrics.line_gap,
+        LineHeight::Normal => metrics.normal_line_height(),
         LineHeight::Number(l) => Au::from(font_size * l.0),
         LineHeight::Length(l) => Au::from(l),
     }
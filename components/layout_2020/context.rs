@@ -10,7 +10,7 @@ use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context::FontContext;
 use msg::constellation_msg::PipelineId;
 use net_traits::image_cache::{
-    ImageCache, ImageCacheResult, ImageOrMetadataAvailable, UsePlaceholder,
+    ImageCache, ImageCacheResult, ImageDecoding, ImageOrMetadataAvailable, UsePlaceholder,
 };
 use parking_lot::RwLock;
 use script_layout_interface::{PendingImage, PendingImageState};
@@ -39,6 +39,9 @@ pub struct LayoutContext<'a> {
 
     pub webrender_image_cache:
         Arc<RwLock<FnvHashMap<(ServoUrl, UsePlaceholder), WebRenderImageInfo>>>,
+
+    /// Whether to paint with the palette of the forced colors mode of the system.
+    pub forced_colors: bool,
 }
 
 impl<'a> Drop for LayoutContext<'a> {
@@ -67,6 +70,7 @@ impl<'a> LayoutContext<'a> {
             self.origin.clone(),
             None,
             use_placeholder,
+            ImageDecoding::Sync,
         );
 
         match cache_result {
@@ -135,6 +139,9 @@ impl<'a> LayoutContext<'a> {
 
 pub(crate) type LayoutFontContext = FontContext<FontCacheThread>;
 
+// Each thread has its own font context, and so its own cache of the text its fonts shaped,
+// which lets the threads of the pool shape the text of different inline formatting contexts
+// without contending for a shared cache.
 thread_local!(static FONT_CONTEXT: RefCell<Option<LayoutFontContext>> = RefCell::new(None));
 
 pub(crate) fn with_thread_local_font_context<F, R>(layout_context: &LayoutContext, f: F) -> R
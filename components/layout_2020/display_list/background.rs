@@ -4,6 +4,7 @@
 
 use app_units::Au;
 use euclid::{Size2D, Vector2D};
+use style::computed_values::background_attachment::single_value::T as Attachment;
 use style::computed_values::background_clip::single_value::T as Clip;
 use style::computed_values::background_origin::single_value::T as Origin;
 use style::properties::ComputedValues;
@@ -87,6 +88,55 @@ impl<'a> BackgroundPainter<'a> {
             }
         })
     }
+
+    /// Make a layer with `background-attachment: fixed` be positioned and tiled relative to
+    /// the containing block for fixed descendants of the box, which is the viewport unless an
+    /// ancestor establishes a containing block for all descendants, in the spatial node of
+    /// that containing block so that it doesn't move when the compositor scrolls the page,
+    /// while still being clipped to the painting area of the box, which does move. Returns
+    /// the rectangle of that containing block for fixed layers.
+    /// <https://drafts.csswg.org/css-backgrounds/#background-attachment>
+    fn fix_to_containing_block(
+        &self,
+        builder: &mut super::DisplayListBuilder,
+        painting_area: &units::LayoutRect,
+        common: &mut wr::CommonItemProperties,
+        layer_index: usize,
+    ) -> Option<units::LayoutRect> {
+        // The backgrounds of table parts that are painted in their cells keep scrolling.
+        if self.positioning_area_override.is_some() {
+            return None;
+        }
+        match get_cyclic(
+            &self.style.get_background().background_attachment.0,
+            layer_index,
+        ) {
+            Attachment::Scroll => return None,
+            Attachment::Fixed => {},
+        }
+
+        // Fixed layers of transformed boxes are transformed with them.
+        // <https://drafts.csswg.org/css-transforms/#transform-rendering>
+        let fixed_area = builder.current_fixed_background_area?;
+        let parent_clip_chain_id = match common.clip_id {
+            wr::ClipId::ClipChain(clip_chain_id) => clip_chain_id,
+            _ => builder.current_clip_chain_id,
+        };
+        let wr = builder.wr();
+        let parent_space_and_clip = wr::SpaceAndClipInfo {
+            spatial_id: common.spatial_id,
+            clip_id: wr::ClipId::root(wr.pipeline_id),
+        };
+        let clip_id = wr.define_clip_rect(&parent_space_and_clip, *painting_area);
+        let clip_chain_id = wr.define_clip_chain(Some(parent_clip_chain_id), [clip_id]);
+        *common = wr::CommonItemProperties {
+            clip_rect: fixed_area.rect,
+            spatial_id: fixed_area.spatial_id,
+            clip_id: wr::ClipId::ClipChain(clip_chain_id),
+            flags: common.flags,
+        };
+        Some(fixed_area.rect)
+    }
 }
 
 pub(super) fn layout_layer(
@@ -96,8 +146,17 @@ pub(super) fn layout_layer(
     layer_index: usize,
     intrinsic: IntrinsicSizes,
 ) -> Option<BackgroundLayer> {
-    let (painting_area, common) = painter.painting_area(fragment_builder, builder, layer_index);
-    let positioning_area = painter.positioning_area(fragment_builder, layer_index);
+    let (painting_area, mut common) = painter.painting_area(fragment_builder, builder, layer_index);
+    let mut painting_area = *painting_area;
+    let mut positioning_area = *painter.positioning_area(fragment_builder, layer_index);
+    // Fixed layers cover the part of the box that is in their containing block, wherever the
+    // page is scrolled to, so they are tiled over the whole containing block.
+    if let Some(fixed_area) =
+        painter.fix_to_containing_block(builder, &painting_area, &mut common, layer_index)
+    {
+        painting_area = fixed_area;
+        positioning_area = fixed_area;
+    }
 
     // https://drafts.csswg.org/css-backgrounds/#background-size
     enum ContainOrCover {
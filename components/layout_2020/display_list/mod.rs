@@ -5,22 +5,27 @@
 use std::cell::OnceCell;
 use std::sync::Arc;
 
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, CustomCursor};
 use euclid::{Point2D, SideOffsets2D, Size2D};
 use fnv::FnvHashMap;
 use gfx::text::glyph::GlyphStore;
 use gfx_traits::WebRenderEpochToU16;
 use msg::constellation_msg::BrowsingContextId;
-use net_traits::image_cache::UsePlaceholder;
+use net_traits::image::base::Image as RasterImage;
+use net_traits::image_cache::{ImageOrMetadataAvailable, UsePlaceholder};
+use pixels::PixelFormat;
 use script_traits::compositor::{CompositorDisplayListInfo, ScrollTreeNodeId};
+use servo_url::ServoUrl;
 use style::color::{AbsoluteColor, ColorSpace};
 use style::computed_values::text_decoration_style::T as ComputedTextDecorationStyle;
 use style::dom::OpaqueNode;
 use style::properties::longhands::visibility::computed_value::T as Visibility;
 use style::properties::ComputedValues;
-use style::values::computed::{BorderStyle, Color, Length, LengthPercentage, OutlineStyle};
+use style::values::computed::ui::Cursor as ComputedCursor;
+use style::values::computed::{BorderStyle, Length, LengthPercentage, OutlineStyle};
 use style::values::specified::text::TextDecorationLine;
 use style::values::specified::ui::CursorKind;
+use style::values::specified::Display;
 use style_traits::CSSPixel;
 use webrender_api::{self as wr, units, ClipChainId, ClipId, CommonItemProperties};
 
@@ -28,7 +33,7 @@ use crate::context::LayoutContext;
 use crate::display_list::conversions::ToWebRender;
 use crate::display_list::stacking_context::StackingContextSection;
 use crate::fragment_tree::{BoxFragment, Fragment, FragmentTree, Tag, TextFragment};
-use crate::geom::{LogicalRect, PhysicalPoint, PhysicalRect};
+use crate::geom::{LogicalRect, PhysicalPoint, PhysicalRect, PhysicalSides};
 use crate::replaced::IntrinsicSizes;
 use crate::style_ext::ComputedValuesExt;
 
@@ -47,6 +52,14 @@ pub struct WebRenderImageInfo {
     pub key: Option<wr::ImageKey>,
 }
 
+/// The area that `background-attachment: fixed` layers are positioned and tiled in, and its
+/// spatial node.
+#[derive(Clone, Copy)]
+pub(crate) struct FixedBackgroundArea {
+    pub rect: units::LayoutRect,
+    pub spatial_id: wr::SpatialId,
+}
+
 // webrender's `ItemTag` is private.
 type ItemTag = (u64, u16);
 type HitInfo = Option<ItemTag>;
@@ -100,6 +113,10 @@ pub(crate) struct DisplayListBuilder<'a> {
     /// list building functions.
     current_clip_chain_id: ClipChainId,
 
+    /// The [FixedBackgroundArea] of the fragments being painted, or `None` if their fixed
+    /// background layers scroll like other layers.
+    current_fixed_background_area: Option<FixedBackgroundArea>,
+
     /// The [OpaqueNode] handle to the node used to paint the page background
     /// if the background was a canvas.
     element_for_canvas_background: OpaqueNode,
@@ -121,6 +138,10 @@ pub(crate) struct DisplayListBuilder<'a> {
     /// text, image, non-white canvas or SVG). Used by metrics.
     /// See <https://w3c.github.io/paint-timing/#first-contentful-paint>.
     is_contentful: bool,
+
+    /// The image cursors already added to the display list, by image URL and hotspot, so
+    /// that each image is only converted once.
+    custom_cursors: FnvHashMap<(ServoUrl, u32, u32), usize>,
 }
 
 impl DisplayList {
@@ -133,11 +154,16 @@ impl DisplayList {
         let mut builder = DisplayListBuilder {
             current_scroll_node_id: self.compositor_info.root_reference_frame_id,
             current_clip_chain_id: ClipChainId(0, self.compositor_info.pipeline_id),
+            current_fixed_background_area: Some(FixedBackgroundArea {
+                rect: units::LayoutRect::from_size(self.compositor_info.viewport_size),
+                spatial_id: self.compositor_info.root_reference_frame_id.spatial_id,
+            }),
             element_for_canvas_background: fragment_tree.canvas_background.from_element,
             is_contentful: false,
             context,
             display_list: self,
             iframe_sizes: FnvHashMap::default(),
+            custom_cursors: FnvHashMap::default(),
         };
         fragment_tree.build_display_list(&mut builder, root_stacking_context);
         (builder.iframe_sizes, builder.is_contentful)
@@ -178,9 +204,12 @@ impl<'a> DisplayListBuilder<'a> {
             return None;
         }
 
+        let node = tag?.node;
+        let custom_cursor = self.custom_cursor(&inherited_ui.cursor, node);
         let hit_test_index = self.display_list.compositor_info.add_hit_test_info(
-            tag?.node.0 as u64,
+            node.0 as u64,
             Some(cursor(inherited_ui.cursor.keyword, auto_cursor)),
+            custom_cursor,
             self.current_scroll_node_id,
         );
         Some((
@@ -188,6 +217,118 @@ impl<'a> DisplayListBuilder<'a> {
             self.display_list.compositor_info.epoch.as_u16(),
         ))
     }
+
+    /// Find the first image of the `cursor` property that can be shown and return the index of
+    /// its image cursor in the display list. Images that have not loaded yet are requested,
+    /// and skipped until they have.
+    fn custom_cursor(&mut self, cursor: &ComputedCursor, node: OpaqueNode) -> Option<usize> {
+        use style::values::computed::image::Image;
+        for cursor_image in cursor.images.iter() {
+            let url: ServoUrl = match cursor_image.image {
+                Image::Url(ref image_url) => match image_url.url() {
+                    Some(url) => url.clone().into(),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            // Without a hotspot, the pointer is at the top left corner of the image.
+            let (hotspot_x, hotspot_y) = if cursor_image.has_hotspot {
+                (
+                    cursor_image.hotspot_x.max(0.) as u32,
+                    cursor_image.hotspot_y.max(0.) as u32,
+                )
+            } else {
+                (0, 0)
+            };
+            let key = (url.clone(), hotspot_x, hotspot_y);
+            if let Some(index) = self.custom_cursors.get(&key) {
+                return Some(*index);
+            }
+            let status = self
+                .context
+                .get_or_request_image_or_meta(node, url, UsePlaceholder::No);
+            let image = match status {
+                Some(ImageOrMetadataAvailable::ImageAvailable { image, .. }) => image,
+                _ => continue,
+            };
+            let custom_cursor = match custom_cursor_from_image(&image, hotspot_x, hotspot_y) {
+                Some(custom_cursor) => custom_cursor,
+                None => continue,
+            };
+            let index = self
+                .display_list
+                .compositor_info
+                .add_custom_cursor(custom_cursor);
+            self.custom_cursors.insert(key, index);
+            return Some(index);
+        }
+        None
+    }
+
+    /// The color to paint text, text decorations, borders and outlines with. In forced colors
+    /// mode this is `CanvasText`, keeping the alpha channel of the author color.
+    /// <https://drafts.csswg.org/css-color-adjust/#forced-colors-properties>
+    fn foreground_color(&self, color: AbsoluteColor) -> wr::ColorF {
+        let color = rgba(color);
+        if !self.context.forced_colors {
+            return color;
+        }
+        wr::ColorF {
+            a: color.a,
+            ..FORCED_CANVAS_TEXT
+        }
+    }
+
+    /// The color to paint backgrounds with. In forced colors mode this is `Canvas`, keeping
+    /// the alpha channel of the author color.
+    /// <https://drafts.csswg.org/css-color-adjust/#forced-colors-properties>
+    fn background_color(&self, color: AbsoluteColor) -> wr::ColorF {
+        let color = rgba(color);
+        if !self.context.forced_colors {
+            return color;
+        }
+        wr::ColorF {
+            a: color.a,
+            ..FORCED_CANVAS
+        }
+    }
+}
+
+/// The `Canvas` and `CanvasText` system colors of the palette of forced colors mode.
+/// <https://drafts.csswg.org/css-color-4/#css-system-colors>
+const FORCED_CANVAS: wr::ColorF = wr::ColorF::WHITE;
+const FORCED_CANVAS_TEXT: wr::ColorF = wr::ColorF::BLACK;
+
+/// The largest width and height in pixels of an image used as a cursor. Larger images are
+/// ignored, as they could hide much of the page.
+const MAX_CUSTOM_CURSOR_SIZE: u32 = 128;
+
+fn custom_cursor_from_image(
+    image: &RasterImage,
+    hotspot_x: u32,
+    hotspot_y: u32,
+) -> Option<CustomCursor> {
+    if image.width == 0 ||
+        image.height == 0 ||
+        image.width > MAX_CUSTOM_CURSOR_SIZE ||
+        image.height > MAX_CUSTOM_CURSOR_SIZE
+    {
+        return None;
+    }
+    let mut pixels = image.bytes.to_vec();
+    match image.format {
+        PixelFormat::RGBA8 => {},
+        PixelFormat::BGRA8 => pixels::rgba8_byte_swap_colors_inplace(&mut pixels),
+        _ => return None,
+    }
+    Some(CustomCursor {
+        width: image.width,
+        height: image.height,
+        // Hotspots outside of the image are clamped to its edges.
+        hotspot_x: hotspot_x.min(image.width - 1),
+        hotspot_y: hotspot_y.min(image.height - 1),
+        pixels,
+    })
 }
 
 impl Fragment {
@@ -368,12 +509,13 @@ impl Fragment {
 
         // Text.
         let common = builder.common_properties(rect.to_webrender(), &fragment.parent_style);
+        let text_color = builder.foreground_color(color);
         builder.wr().push_text(
             &common,
             rect.to_webrender(),
             &glyphs,
             fragment.font_key,
-            rgba(color),
+            text_color,
             None,
         );
 
@@ -407,12 +549,13 @@ impl Fragment {
         if text_decoration_style == ComputedTextDecorationStyle::MozNone {
             return;
         }
+        let text_decoration_color = builder.foreground_color(text_decoration_color);
         builder.display_list.wr.push_line(
             &builder.common_properties(rect, &fragment.parent_style),
             &rect,
             wavy_line_thickness,
             wr::LineOrientation::Horizontal,
-            &rgba(text_decoration_color),
+            &text_decoration_color,
             text_decoration_style.to_webrender(),
         );
         // XXX(ferjm) support text-decoration-style: double
@@ -551,6 +694,20 @@ impl<'a> BuilderForBoxFragment<'a> {
             self.build_outline(builder);
         } else {
             self.build_hit_test(builder);
+
+            // Table rows and row groups only get a box fragment when they are sticky, so that
+            // their cells move along with them. Their backgrounds are painted by their cells
+            // and their borders are either painted by their cells or not painted at all.
+            if matches!(
+                self.fragment.style.clone_display(),
+                Display::TableRow |
+                    Display::TableRowGroup |
+                    Display::TableHeaderGroup |
+                    Display::TableFooterGroup
+            ) {
+                return;
+            }
+
             self.build_background(builder);
             self.build_border(builder);
         }
@@ -587,9 +744,8 @@ impl<'a> BuilderForBoxFragment<'a> {
             //  value associated with the bottom-most background image layer.”
             let layer_index = b.background_image.0.len() - 1;
             let (bounds, common) = painter.painting_area(self, builder, layer_index);
-            builder
-                .wr()
-                .push_rect(&common, *bounds, rgba(background_color))
+            let background_color = builder.background_color(background_color);
+            builder.wr().push_rect(&common, *bounds, background_color)
         }
 
         self.build_background_image(builder, painter);
@@ -640,6 +796,9 @@ impl<'a> BuilderForBoxFragment<'a> {
         for (index, image) in b.background_image.0.iter().enumerate().rev() {
             match image {
                 Image::None => {},
+                // Only url() images are kept in forced colors mode.
+                // <https://drafts.csswg.org/css-color-adjust/#forced-colors-properties>
+                Image::Gradient(_) if builder.context.forced_colors => {},
                 Image::Gradient(ref gradient) => {
                     let intrinsic = IntrinsicSizes::empty();
                     if let Some(layer) =
@@ -722,9 +881,14 @@ impl<'a> BuilderForBoxFragment<'a> {
         }
     }
 
-    fn build_border_side(&mut self, style: BorderStyle, color: Color) -> wr::BorderSide {
+    fn build_border_side(
+        &mut self,
+        builder: &DisplayListBuilder,
+        style: BorderStyle,
+        color: AbsoluteColor,
+    ) -> wr::BorderSide {
         wr::BorderSide {
-            color: rgba(self.fragment.style.resolve_color(color)),
+            color: builder.foreground_color(color),
             style: match style {
                 BorderStyle::None => wr::BorderStyle::None,
                 BorderStyle::Solid => wr::BorderStyle::Solid,
@@ -756,16 +920,35 @@ impl<'a> BuilderForBoxFragment<'a> {
             return;
         }
         let common = builder.common_properties(self.border_rect, &self.fragment.style);
-        let details = wr::BorderDetails::Normal(wr::NormalBorder {
-            top: self.build_border_side(border.border_top_style, border.border_top_color.clone()),
-            right: self
-                .build_border_side(border.border_right_style, border.border_right_color.clone()),
-            bottom: self.build_border_side(
-                border.border_bottom_style,
-                border.border_bottom_color.clone(),
+        let style = &self.fragment.style;
+        let sides = match self.fragment.collapsed_borders {
+            Some(ref collapsed_borders) => collapsed_borders
+                .map(|side| (side.style, side.color.clone()))
+                .to_physical(style.writing_mode),
+            None => PhysicalSides::new(
+                (
+                    border.border_top_style,
+                    style.resolve_color(border.border_top_color.clone()),
+                ),
+                (
+                    border.border_right_style,
+                    style.resolve_color(border.border_right_color.clone()),
+                ),
+                (
+                    border.border_bottom_style,
+                    style.resolve_color(border.border_bottom_color.clone()),
+                ),
+                (
+                    border.border_left_style,
+                    style.resolve_color(border.border_left_color.clone()),
+                ),
             ),
-            left: self
-                .build_border_side(border.border_left_style, border.border_left_color.clone()),
+        };
+        let details = wr::BorderDetails::Normal(wr::NormalBorder {
+            top: self.build_border_side(builder, sides.top.0, sides.top.1),
+            right: self.build_border_side(builder, sides.right.0, sides.right.1),
+            bottom: self.build_border_side(builder, sides.bottom.0, sides.bottom.1),
+            left: self.build_border_side(builder, sides.left.0, sides.left.1),
             radius: self.border_radius,
             do_aa: true,
         });
@@ -795,7 +978,11 @@ impl<'a> BuilderForBoxFragment<'a> {
             OutlineStyle::Auto => BorderStyle::Solid,
             OutlineStyle::BorderStyle(s) => s,
         };
-        let side = self.build_border_side(style, outline.outline_color.clone());
+        let color = self
+            .fragment
+            .style
+            .resolve_color(outline.outline_color.clone());
+        let side = self.build_border_side(builder, style, color);
         let details = wr::BorderDetails::Normal(wr::NormalBorder {
             top: side,
             right: side,
@@ -20,7 +20,7 @@ use style::properties::ComputedValues;
 use style::values::computed::{ClipRectOrAuto, Length};
 use style::values::generics::box_::Perspective;
 use style::values::generics::transform;
-use style::values::specified::box_::DisplayOutside;
+use style::values::specified::box_::{Contain, DisplayOutside};
 use webrender_api as wr;
 use webrender_api::units::{LayoutPoint, LayoutRect, LayoutTransform, LayoutVector2D};
 use webrender_api::ScrollSensitivity;
@@ -30,7 +30,7 @@ use wr::StickyOffsetBounds;
 use super::DisplayList;
 use crate::cell::ArcRefCell;
 use crate::display_list::conversions::{FilterToWebRender, ToWebRender};
-use crate::display_list::DisplayListBuilder;
+use crate::display_list::{DisplayListBuilder, FixedBackgroundArea};
 use crate::fragment_tree::{
     BoxFragment, ContainingBlockManager, Fragment, FragmentTree, PositioningFragment,
 };
@@ -77,6 +77,16 @@ impl ContainingBlock {
             ..*self
         }
     }
+
+    /// The area the fixed background layers of boxes are positioned in when this is their
+    /// containing block for fixed descendants.
+    /// <https://drafts.csswg.org/css-transforms-1/#containing-block-for-all-descendants>
+    fn fixed_background_area(&self) -> FixedBackgroundArea {
+        FixedBackgroundArea {
+            rect: self.rect.to_webrender(),
+            spatial_id: self.scroll_node_id.spatial_id,
+        }
+    }
 }
 
 pub(crate) type ContainingBlockInfo<'a> = ContainingBlockManager<'a, ContainingBlock>;
@@ -245,6 +255,9 @@ pub(crate) enum StackingContextContent {
         clip_chain_id: wr::ClipChainId,
         section: StackingContextSection,
         containing_block: PhysicalRect<Length>,
+        /// Where the fixed background layers of the fragment are painted, or `None` if they
+        /// scroll like other layers.
+        fixed_background_area: Option<FixedBackgroundArea>,
         fragment: ArcRefCell<Fragment>,
     },
 
@@ -273,10 +286,12 @@ impl StackingContextContent {
                 clip_chain_id,
                 section,
                 containing_block,
+                fixed_background_area,
                 fragment,
             } => {
                 builder.current_scroll_node_id = *scroll_node_id;
                 builder.current_clip_chain_id = *clip_chain_id;
+                builder.current_fixed_background_area = *fixed_background_area;
                 fragment
                     .borrow()
                     .build_display_list(builder, containing_block, *section);
@@ -542,7 +557,7 @@ impl StackingContext {
         let background_color = style.resolve_color(style.get_background().background_color.clone());
         if background_color.alpha > 0.0 {
             let common = builder.common_properties(painting_area, style);
-            let color = super::rgba(background_color);
+            let color = builder.background_color(background_color);
             builder
                 .display_list
                 .wr
@@ -590,6 +605,7 @@ impl StackingContext {
             fragment,
             scroll_node_id,
             containing_block,
+            fixed_background_area,
             ..
         } = first_stacking_context_fragment
         else {
@@ -613,6 +629,7 @@ impl StackingContext {
         // background image to be transformed. To do so, take its `SpatialId`
         // (but not its `ClipId`)
         builder.current_scroll_node_id = *scroll_node_id;
+        builder.current_fixed_background_area = *fixed_background_area;
 
         // Now we need express the painting area rectangle in the local coordinate system,
         // which differs from the top-level coordinate system based on…
@@ -875,6 +892,7 @@ impl Fragment {
                         scroll_node_id: containing_block.scroll_node_id,
                         clip_chain_id: containing_block.clip_chain_id,
                         containing_block: containing_block.rect,
+                        fixed_background_area: None,
                         fragment: fragment_ref.clone(),
                     });
             },
@@ -1098,6 +1116,18 @@ impl BoxFragment {
             new_clip_chain_id = clip_chain_id;
         }
 
+        // The containing block for fixed descendants is only replaced after the reference
+        // frame of a transformed box is pushed, but fixed layers of transformed boxes scroll
+        // anyway.
+        let fixed_background_area = if self.style.has_transform_or_perspective() {
+            None
+        } else {
+            Some(
+                containing_block_info
+                    .for_absolute_and_fixed_descendants
+                    .fixed_background_area(),
+            )
+        };
         stacking_context
             .contents
             .push(StackingContextContent::Fragment {
@@ -1105,6 +1135,7 @@ impl BoxFragment {
                 clip_chain_id: new_clip_chain_id,
                 section: self.get_stacking_context_section(),
                 containing_block: containing_block.rect,
+                fixed_background_area,
                 fragment: fragment.clone(),
             });
         use style::Zero;
@@ -1116,12 +1147,22 @@ impl BoxFragment {
                     clip_chain_id: new_clip_chain_id,
                     section: StackingContextSection::Outline,
                     containing_block: containing_block.rect,
+                    fixed_background_area: None,
                     fragment: fragment.clone(),
                 });
         }
 
-        // We want to build the scroll frame after the background and border, because
-        // they shouldn't scroll with the rest of the box content.
+        // The overflow clip and the scroll frame are built after the background and border,
+        // because those are neither clipped nor scrolled with the rest of the box content.
+        if let Some(clip_chain_id) = self.build_overflow_clip_frame_if_necessary(
+            display_list,
+            &new_scroll_node_id,
+            &new_clip_chain_id,
+            &containing_block.rect,
+        ) {
+            new_clip_chain_id = clip_chain_id;
+        }
+
         if let Some((scroll_node_id, clip_chain_id, scroll_frame_size)) = self
             .build_scroll_frame_if_necessary(
                 display_list,
@@ -1234,6 +1275,64 @@ impl BoxFragment {
         )
     }
 
+    /// Clip the contents of a box with `overflow: clip` to its padding box, in the clipped
+    /// axes only, and those of a box with paint containment in both axes. This does not
+    /// create a scroll frame.
+    /// <https://drafts.csswg.org/css-overflow-3/#valdef-overflow-clip>
+    /// <https://drafts.csswg.org/css-contain/#containment-paint>
+    fn build_overflow_clip_frame_if_necessary(
+        &self,
+        display_list: &mut DisplayList,
+        parent_scroll_node_id: &ScrollTreeNodeId,
+        parent_clip_chain_id: &wr::ClipChainId,
+        containing_block_rect: &PhysicalRect<Length>,
+    ) -> Option<wr::ClipChainId> {
+        // `clip` is the only value that clips without being scrollable.
+        let clips = |overflow: ComputedOverflow| {
+            overflow != ComputedOverflow::Visible && !overflow.is_scrollable()
+        };
+        let paint_contained = self.style.effective_containment().contains(Contain::PAINT);
+        let clip_x = paint_contained || clips(self.style.get_box().overflow_x);
+        let clip_y = paint_contained || clips(self.style.get_box().overflow_y);
+        if !clip_x && !clip_y {
+            return None;
+        }
+
+        // TODO: Expand the clip by `overflow-clip-margin`. The style system only parses that
+        // property for Gecko, so until it is enabled for Servo the margin is always zero and
+        // the clip is the padding box.
+        let padding_rect = self
+            .padding_rect()
+            .to_physical(self.style.writing_mode, containing_block_rect)
+            .translate(containing_block_rect.origin.to_vector())
+            .to_webrender();
+
+        // Content may overflow freely in an axis that is not clipped.
+        let mut clip_rect = padding_rect;
+        if !clip_x {
+            clip_rect.origin.x = f32::MIN / 2.0;
+            clip_rect.size.width = f32::MAX;
+        }
+        if !clip_y {
+            clip_rect.origin.y = f32::MIN / 2.0;
+            clip_rect.size.height = f32::MAX;
+        }
+
+        let parent_space_and_clip = &wr::SpaceAndClipInfo {
+            spatial_id: parent_scroll_node_id.spatial_id,
+            clip_id: wr::ClipId::root(display_list.wr.pipeline_id),
+        };
+
+        let clip_id = display_list
+            .wr
+            .define_clip_rect(parent_space_and_clip, clip_rect);
+        Some(
+            display_list
+                .wr
+                .define_clip_chain(Some(*parent_clip_chain_id), [clip_id]),
+        )
+    }
+
     fn build_scroll_frame_if_necessary(
         &self,
         display_list: &mut DisplayList,
@@ -10,12 +10,15 @@ use servo_arc::Arc as ServoArc;
 use style::properties::ComputedValues;
 use style::selector_parser::PseudoElement;
 use style::values::generics::counters::{Content, ContentItem};
+use style::values::specified::box_::{Contain, ContentVisibility};
 
 use crate::context::LayoutContext;
 use crate::dom::{BoxSlot, LayoutBox, NodeExt};
 use crate::fragment_tree::{BaseFragmentInfo, FragmentFlags, Tag};
 use crate::replaced::ReplacedContent;
-use crate::style_ext::{Display, DisplayGeneratingBox, DisplayInside, DisplayOutside};
+use crate::style_ext::{
+    ComputedValuesExt, Display, DisplayGeneratingBox, DisplayInside, DisplayOutside,
+};
 
 #[derive(Clone, Copy, Debug)]
 pub(crate) enum WhichPseudoElement {
@@ -75,15 +78,26 @@ where
         });
 
         let threadsafe_node = info.node.to_threadsafe();
-        let flags = match threadsafe_node.as_element() {
+        let mut flags = match threadsafe_node.as_element() {
             Some(element) if element.is_body_element_of_html_element_root() => {
                 FragmentFlags::IS_BODY_ELEMENT_OF_HTML_ELEMENT_ROOT
             },
             Some(element) if element.get_local_name() == &local_name!("br") => {
                 FragmentFlags::IS_BR_ELEMENT
             },
+            Some(element)
+                if matches!(
+                    *element.get_local_name(),
+                    local_name!("input") | local_name!("button") | local_name!("select")
+                ) =>
+            {
+                FragmentFlags::IS_FORM_CONTROL
+            },
             _ => FragmentFlags::empty(),
         };
+        if info.pseudo_element_type.is_none() && skips_contents(info.node, &info.style) {
+            flags.insert(FragmentFlags::SKIPS_CONTENTS);
+        }
 
         Self {
             tag: Tag::new_pseudo(threadsafe_node.opaque(), pseudo),
@@ -142,7 +156,7 @@ fn traverse_children_of<'dom, Node>(
 {
     traverse_pseudo_element(WhichPseudoElement::Before, parent_element, context, handler);
 
-    for child in iter_child_nodes(parent_element) {
+    for child in parent_element.flat_tree_children() {
         if child.is_text_node() {
             let info = NodeAndStyleInfo::new(child, child.style(context));
             handler.handle_text(&info, child.to_threadsafe().node_text_content());
@@ -303,7 +317,11 @@ impl NonReplacedContents {
         Node: NodeExt<'dom>,
     {
         match self {
-            NonReplacedContents::OfElement => traverse_children_of(info.node, context, handler),
+            NonReplacedContents::OfElement => {
+                if !skips_contents(info.node, &info.style) {
+                    traverse_children_of(info.node, context, handler)
+                }
+            },
             NonReplacedContents::OfPseudoElement(items) => {
                 traverse_pseudo_element_contents(info, context, handler, items)
             },
@@ -311,6 +329,27 @@ impl NonReplacedContents {
     }
 }
 
+/// Whether the element skips its contents, which are then neither laid out nor painted,
+/// because of its `content-visibility`.
+/// <https://drafts.csswg.org/css-contain-2/#skips-its-contents>
+fn skips_contents<'dom, Node>(element: Node, style: &ComputedValues) -> bool
+where
+    Node: NodeExt<'dom>,
+{
+    // `content-visibility` only has an effect on the boxes layout containment applies to.
+    if !style.effective_containment().contains(Contain::LAYOUT) {
+        return false;
+    }
+    match style.get_box().content_visibility {
+        ContentVisibility::Visible => false,
+        ContentVisibility::Hidden => true,
+        ContentVisibility::Auto => !element
+            .to_threadsafe()
+            .as_element()
+            .map_or(false, |element| element.is_relevant_to_the_user()),
+    }
+}
+
 fn pseudo_element_style<'dom, Node>(
     which: WhichPseudoElement,
     element: Node,
@@ -383,16 +422,3 @@ where
         Content::Normal | Content::None => unreachable!(),
     }
 }
-
-pub(crate) fn iter_child_nodes<'dom, Node>(parent: Node) -> impl Iterator<Item = Node>
-where
-    Node: NodeExt<'dom>,
-{
-    let mut next = parent.first_child();
-    std::iter::from_fn(move || {
-        next.map(|child| {
-            next = child.next_sibling();
-            child
-        })
-    })
-}
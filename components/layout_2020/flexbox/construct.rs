@@ -217,6 +217,9 @@ where
             FlexLevelBox::OutOfFlowAbsolutelyPositionedBox(_) => 0,
         });
 
-        FlexContainer { children }
+        FlexContainer {
+            children,
+            style: self.info.style.clone(),
+        }
     }
 }
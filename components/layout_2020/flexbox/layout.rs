@@ -6,6 +6,7 @@ use std::cell::Cell;
 
 use app_units::Au;
 use atomic_refcell::AtomicRefMut;
+use style::logical_geometry::WritingMode;
 use style::properties::longhands::align_content::computed_value::T as AlignContent;
 use style::properties::longhands::align_items::computed_value::T as AlignItems;
 use style::properties::longhands::align_self::computed_value::T as AlignSelf;
@@ -13,7 +14,8 @@ use style::properties::longhands::box_sizing::computed_value::T as BoxSizing;
 use style::properties::longhands::flex_direction::computed_value::T as FlexDirection;
 use style::properties::longhands::flex_wrap::computed_value::T as FlexWrap;
 use style::properties::longhands::justify_content::computed_value::T as JustifyContent;
-use style::values::computed::length::Size;
+use style::properties::ComputedValues;
+use style::values::computed::length::{NonNegativeLengthPercentageOrNormal, Size};
 use style::values::computed::Length;
 use style::values::generics::flex::GenericFlexBasis as FlexBasis;
 use style::values::CSSFloat;
@@ -53,6 +55,8 @@ struct FlexContext<'a> {
     align_content: AlignContent,
     align_items: AlignItems,
     justify_content: JustifyContent,
+    /// The gap between adjacent flex items of a line.
+    main_axis_gap: Length,
 }
 
 /// A flex item with some intermediate results
@@ -96,12 +100,16 @@ struct FlexItemLayoutResult {
     hypothetical_cross_size: Length,
     fragments: Vec<Fragment>,
     positioning_context: PositioningContext,
+    /// The baselines of the contents of the item, relative to its content box.
+    baselines: Baselines,
 }
 
 /// Return type of `FlexLine::layout`
 struct FlexLineLayoutResult {
     cross_size: Length,
     item_fragments: Vec<(BoxFragment, PositioningContext)>, // One per flex item, in the given order
+    /// The baselines of the line, relative to its block-start edge.
+    baselines: Baselines,
 }
 
 impl FlexContext<'_> {
@@ -144,13 +152,62 @@ impl FlexContext<'_> {
     }
 }
 
+/// The gap between adjacent flex items of a line, with percentages resolved against `basis`.
+/// `normal` behaves as zero in flex containers.
+/// <https://drafts.csswg.org/css-align/#column-row-gap>
+///
+/// Column flex containers are laid out as row flex containers for now, so this is always
+/// `column-gap`.
+/// TODO: Use `row-gap` between flex lines once style supports it for Servo.
+fn main_axis_gap(style: &ComputedValues, basis: Length) -> Length {
+    match style.get_position().column_gap {
+        NonNegativeLengthPercentageOrNormal::Normal => Length::zero(),
+        NonNegativeLengthPercentageOrNormal::LengthPercentage(ref length_percentage) => {
+            length_percentage.0.percentage_relative_to(basis)
+        },
+    }
+}
+
 impl FlexContainer {
-    pub fn inline_content_sizes(&self) -> ContentSizes {
-        // FIXME: implement this. The spec for it is the same as for "normal" layout:
-        // https://drafts.csswg.org/css-flexbox/#layout-algorithm
-        // … except that the parts that say “the flex container is being sized
-        // under a min or max-content constraint” apply.
-        ContentSizes::zero() // Return an incorrect result rather than panic
+    /// <https://drafts.csswg.org/css-flexbox/#intrinsic-main-sizes>
+    ///
+    /// FIXME: This sums the outer content sizes of the items rather than running the whole
+    /// algorithm, and doesn't take their flex factors into account.
+    pub fn inline_content_sizes(
+        &self,
+        layout_context: &LayoutContext,
+        writing_mode: WritingMode,
+    ) -> ContentSizes {
+        // Column flex containers are laid out as row flex containers for now (see `layout`),
+        // so the inline axis is always the main axis.
+        let mut sum = ContentSizes::zero();
+        let mut largest_min_content = Au::zero();
+        let mut item_count = 0;
+        for child in &self.children {
+            if let FlexLevelBox::FlexItem(item) = &mut *child.borrow_mut() {
+                let sizes = item.outer_inline_content_sizes(layout_context, writing_mode);
+                largest_min_content = largest_min_content.max(sizes.min_content);
+                sum += sizes;
+                item_count += 1;
+            }
+        }
+
+        // Percentage gaps are resolved against zero for intrinsic size contributions.
+        // https://drafts.csswg.org/css-sizing-3/#min-percentage-contribution
+        let gap: Au = main_axis_gap(&self.style, Length::zero()).into();
+        let gaps = gap * (item_count.max(1) - 1);
+        let min_content = match self.style.get_position().flex_wrap {
+            // “The min-content main size of a single-line flex container is the sum of the
+            //  min-content contributions of its flex items”
+            FlexWrap::Nowrap => sum.min_content + gaps,
+            // “The min-content main size of a multi-line container is the largest min-content
+            //  contribution of its flex items”
+            FlexWrap::Wrap | FlexWrap::WrapReverse => largest_min_content,
+        };
+        ContentSizes {
+            min_content,
+            max_content: sum.max_content + gaps,
+        }
     }
 
     /// <https://drafts.csswg.org/css-flexbox/#layout-algorithm>
@@ -237,6 +294,10 @@ impl FlexContainer {
             align_content,
             align_items,
             justify_content,
+            main_axis_gap: main_axis_gap(
+                containing_block.style,
+                containing_block.inline_size.into(),
+            ),
             main_start_cross_start_sides_are: MainStartCrossStart::from(
                 flex_direction,
                 flex_wrap_reverse,
@@ -347,11 +408,11 @@ impl FlexContainer {
             },
         };
 
-        let mut flex_item_fragments = flex_lines
-            .into_iter()
+        let flow_relative_line_positions = flex_lines
+            .iter()
             .zip(line_cross_start_positions)
-            .flat_map(move |(mut line, line_cross_start_position)| {
-                let flow_relative_line_position = match (flex_axis, flex_wrap_reverse) {
+            .map(
+                |(line, line_cross_start_position)| match (flex_axis, flex_wrap_reverse) {
                     (FlexAxis::Row, false) => LogicalVec2 {
                         block: line_cross_start_position,
                         inline: Length::zero(),
@@ -368,7 +429,32 @@ impl FlexContainer {
                         block: Length::zero(),
                         inline: container_cross_size - line_cross_start_position - line.cross_size,
                     },
-                };
+                },
+            )
+            .collect::<Vec<_>>();
+
+        // “If any of the flex items on the flex container’s first line participate in baseline
+        //  alignment, the flex container’s first main-axis baseline set is generated from the
+        //  shared alignment baseline of those flex items. Otherwise, if the flex container has
+        //  at least one flex item, the flex container’s first main-axis baseline set is
+        //  generated from the alignment baseline of the startmost flex item.”
+        // And likewise for the last baseline set and the last line.
+        // https://drafts.csswg.org/css-flexbox/#flex-baselines
+        let mut baselines = Baselines::default();
+        if flex_axis == FlexAxis::Row {
+            let line_baselines = flex_lines.iter().zip(&flow_relative_line_positions);
+            baselines.first = line_baselines
+                .clone()
+                .find_map(|(line, position)| Some(line.baselines.first? + position.block.into()));
+            baselines.last = line_baselines
+                .rev()
+                .find_map(|(line, position)| Some(line.baselines.last? + position.block.into()));
+        }
+
+        let mut flex_item_fragments = flex_lines
+            .into_iter()
+            .zip(flow_relative_line_positions)
+            .flat_map(move |(mut line, flow_relative_line_position)| {
                 for (fragment, _) in &mut line.item_fragments {
                     fragment.content_rect.start_corner += &flow_relative_line_position
                 }
@@ -411,7 +497,7 @@ impl FlexContainer {
             fragments,
             content_block_size: content_block_size.into(),
             content_inline_size_for_table: None,
-            baselines: Baselines::default(),
+            baselines,
         }
     }
 }
@@ -478,6 +564,11 @@ impl<'a> FlexItem<'a> {
                             .contents
                             .inline_size_over_block_size_intrinsic_ratio(box_.style())
                         {
+                            // The min-content size of a box with an aspect ratio and a definite
+                            // block size is that block size converted through the ratio.
+                            // https://drafts.csswg.org/css-sizing-4/#aspect-ratio-size-transfers
+                            let inline_content_size =
+                                transferred_size_suggestion.map_or(inline_content_size, Au::from);
                             inline_content_size.clamp_between_extremums(
                                 (min_size.block.auto_is(Length::zero) * ratio).into(),
                                 max_size.block.map(|l| (l * ratio).into()),
@@ -619,7 +710,40 @@ fn flex_base_size(
             length
         },
         FlexBasis::Content => {
-            // FIXME: implement cases B, C, D.
+            // Case B: “If the flex item has a preferred aspect ratio, a used flex basis of
+            //  content, and a definite cross size, then the flex base size is calculated from
+            //  its used cross size and the flex item’s aspect ratio.”
+            if let (
+                IndependentFormattingContext::Replaced(replaced),
+                LengthOrAuto::LengthPercentage(cross_size),
+            ) = (&*flex_item, content_box_size.cross)
+            {
+                if let Some(ratio) = replaced
+                    .contents
+                    .inline_size_over_block_size_intrinsic_ratio(&replaced.style)
+                {
+                    return if cross_axis_is_item_block_axis {
+                        cross_size * ratio
+                    } else {
+                        cross_size / ratio
+                    };
+                }
+            }
+
+            // Case C: “If the used flex basis is content or depends on its available space,
+            //  and the flex container is being sized under a min-content or max-content
+            //  constraint (e.g. when performing automatic table layout), size the item under
+            //  that constraint.”
+            // This is only reached when laying out the container. Its intrinsic sizes are
+            // computed by `FlexContainer::inline_content_sizes`, from the content sizes of the
+            // items under the same constraint.
+
+            // Case D: “Otherwise, if the used flex basis is content or depends on its
+            //  available space, the available main size is infinite, and the flex item’s
+            //  inline axis is parallel to the main axis, lay the item out using the rules for
+            //  a box in an orthogonal flow.”
+            // The main axis is the inline axis of the container, whose size is always
+            // definite here, so the available main size is never infinite.
 
             // Case E: everything else
             // “treating a value of content as max-content.”
@@ -665,7 +789,10 @@ fn collect_flex_lines<'items, LineResult>(
         while let Some(item) = items.get(index) {
             let item_size = item.hypothetical_main_size + item.pbm_auto_is_zero.main;
             let line_size_would_be = line_size_so_far + item_size;
-            let item_fits = line_size_would_be <= container_main_size;
+            // Gaps are only taken into account to break lines, and are not part of the sum
+            // of the outer hypothetical main sizes.
+            let gaps_would_be = flex_context.main_axis_gap * index as CSSFloat;
+            let item_fits = line_size_would_be + gaps_would_be <= container_main_size;
             if item_fits || line_so_far_is_empty {
                 line_size_so_far = line_size_would_be;
                 line_so_far_is_empty = false;
@@ -700,8 +827,9 @@ impl FlexLine<'_> {
         flex_context: &mut FlexContext,
         container_main_size: Length,
     ) -> FlexLineLayoutResult {
+        let gaps = flex_context.main_axis_gap * (self.items.len().max(1) - 1) as CSSFloat;
         let (item_used_main_sizes, remaining_free_space) =
-            self.resolve_flexible_lengths(container_main_size);
+            self.resolve_flexible_lengths(container_main_size - gaps);
 
         // https://drafts.csswg.org/css-flexbox/#algo-cross-item
         let item_layout_results = self
@@ -817,17 +945,33 @@ impl FlexLine<'_> {
             &item_used_main_sizes,
             &item_margins,
             main_start_position,
-            item_main_interval,
+            item_main_interval + flex_context.main_axis_gap,
         );
 
+        // https://drafts.csswg.org/css-flexbox/#baseline-participation
+        let item_baselines = self
+            .items
+            .iter()
+            .zip(&item_results)
+            .map(|(item, item_result)| {
+                item.baseline_from_outer_cross_start(flex_context, item_result)
+            })
+            .collect::<Vec<_>>();
+        let shared_baseline = item_baselines
+            .iter()
+            .flatten()
+            .fold(Length::zero(), |shared, &baseline| shared.max(baseline));
+
         // https://drafts.csswg.org/css-flexbox/#algo-cross-align
         let item_content_cross_start_posititons = self
             .items
             .iter()
             .zip(&item_margins)
             .zip(&item_used_cross_sizes)
-            .map(|((item, margin), size)| {
-                item.align_along_cross_axis(margin, size, line_cross_size)
+            .zip(&item_baselines)
+            .map(|(((item, margin), size), baseline)| {
+                let baseline_offset = baseline.map(|baseline| shared_baseline - baseline);
+                item.align_along_cross_axis(margin, size, line_cross_size, baseline_offset)
             });
 
         let item_fragments = self
@@ -851,26 +995,69 @@ impl FlexLine<'_> {
                 let content_rect = flex_context.rect_to_flow_relative(line_size, content_rect);
                 let margin = flex_context.sides_to_flow_relative(*margin);
                 let collapsed_margin = CollapsedBlockMargins::from_margin(&margin);
-                (
-                    // TODO: We should likely propagate baselines from `display: flex`.
-                    BoxFragment::new(
-                        item.box_.base_fragment_info(),
-                        item.box_.style().clone(),
-                        item_result.fragments,
-                        content_rect,
-                        flex_context.sides_to_flow_relative(item.padding.map(|t| (*t).into())),
-                        flex_context.sides_to_flow_relative(item.border.map(|t| (*t).into())),
-                        margin,
-                        None, /* clearance */
-                        collapsed_margin,
-                    ),
-                    item_result.positioning_context,
+                let mut fragment = BoxFragment::new(
+                    item.box_.base_fragment_info(),
+                    item.box_.style().clone(),
+                    item_result.fragments,
+                    content_rect,
+                    flex_context.sides_to_flow_relative(item.padding.map(|t| (*t).into())),
+                    flex_context.sides_to_flow_relative(item.border.map(|t| (*t).into())),
+                    margin,
+                    None, /* clearance */
+                    collapsed_margin,
                 )
+                .with_baselines(item_result.baselines);
+                let mut positioning_context = item_result.positioning_context;
+                if item
+                    .box_
+                    .style()
+                    .establishes_containing_block_for_absolute_descendants()
+                {
+                    positioning_context
+                        .layout_collected_children(flex_context.layout_context, &mut fragment);
+                }
+                (fragment, positioning_context)
             })
-            .collect();
+            .collect::<Vec<_>>();
+
+        // The baselines of the line come from the items that participate in baseline
+        // alignment, or else from its first and last items.
+        // https://drafts.csswg.org/css-flexbox/#flex-baselines
+        let fragment_baseline = |(fragment, _): &(BoxFragment, PositioningContext)| {
+            let baseline = fragment
+                .baselines
+                .first
+                .map(Length::from)
+                .unwrap_or_else(|| {
+                    // Synthesized from the block-end border edge.
+                    fragment.content_rect.size.block +
+                        fragment.padding.block_end +
+                        fragment.border.block_end
+                });
+            Au::from(fragment.content_rect.start_corner.block + baseline)
+        };
+        let participants = || {
+            item_fragments
+                .iter()
+                .zip(&item_baselines)
+                .filter(|(_, baseline)| baseline.is_some())
+                .map(|(fragment, _)| fragment)
+        };
+        let baselines = Baselines {
+            first: participants()
+                .next()
+                .or(item_fragments.first())
+                .map(fragment_baseline),
+            last: participants()
+                .last()
+                .or(item_fragments.last())
+                .map(fragment_baseline),
+        };
+
         FlexLineLayoutResult {
             cross_size: line_cross_size,
             item_fragments,
+            baselines,
         }
     }
 
@@ -1047,11 +1234,16 @@ impl<'a> FlexItem<'a> {
         flex_context: &mut FlexContext,
         used_cross_size_override: Option<Length>,
     ) -> FlexItemLayoutResult {
-        let mut positioning_context = PositioningContext::new_for_subtree(
-            flex_context
-                .positioning_context
-                .collects_for_nearest_positioned_ancestor(),
-        );
+        // Items that establish a containing block lay out the boxes hoisted to them once their
+        // fragment is built, in `FlexLine::layout`.
+        let mut positioning_context = PositioningContext::new_for_style(self.box_.style())
+            .unwrap_or_else(|| {
+                PositioningContext::new_for_subtree(
+                    flex_context
+                        .positioning_context
+                        .collects_for_nearest_positioned_ancestor(),
+                )
+            });
         match flex_context.flex_axis {
             FlexAxis::Row => {
                 // The main axis is the container’s inline axis
@@ -1088,6 +1280,7 @@ impl<'a> FlexItem<'a> {
                             hypothetical_cross_size: cross_size.into(),
                             fragments,
                             positioning_context,
+                            baselines: Baselines::default(),
                         }
                     },
                     IndependentFormattingContext::NonReplaced(non_replaced) => {
@@ -1104,6 +1297,7 @@ impl<'a> FlexItem<'a> {
                         let IndependentLayout {
                             fragments,
                             content_block_size,
+                            baselines,
                             ..
                         } = non_replaced.layout(
                             flex_context.layout_context,
@@ -1125,6 +1319,7 @@ impl<'a> FlexItem<'a> {
                             hypothetical_cross_size,
                             fragments,
                             positioning_context,
+                            baselines,
                         }
                     },
                 }
@@ -1150,17 +1345,24 @@ impl<'items> FlexLine<'items> {
                 return size;
             }
         }
-        let outer_hypothetical_cross_sizes =
-            item_layout_results
-                .iter()
-                .zip(&*self.items)
-                .map(|(item_result, item)| {
-                    item_result.hypothetical_cross_size + item.pbm_auto_is_zero.cross
-                });
-        // FIXME: add support for `align-self: baseline`
-        // and computing the baseline of flex items.
+        let mut largest = Length::zero();
+        // The largest distances between the baseline of the items that participate in baseline
+        // alignment and their outer cross-start and cross-end edges.
         // https://drafts.csswg.org/css-flexbox/#baseline-participation
-        let largest = outer_hypothetical_cross_sizes.fold(Length::zero(), Length::max);
+        let mut largest_above_baseline = Length::zero();
+        let mut largest_below_baseline = Length::zero();
+        for (item_result, item) in item_layout_results.iter().zip(&*self.items) {
+            let outer_hypothetical_cross_size =
+                item_result.hypothetical_cross_size + item.pbm_auto_is_zero.cross;
+            largest = largest.max(outer_hypothetical_cross_size);
+            if let Some(baseline) = item.baseline_from_outer_cross_start(flex_context, item_result)
+            {
+                largest_above_baseline = largest_above_baseline.max(baseline);
+                largest_below_baseline =
+                    largest_below_baseline.max(outer_hypothetical_cross_size - baseline);
+            }
+        }
+        let largest = largest.max(largest_above_baseline + largest_below_baseline);
         if flex_context.container_is_single_line {
             largest.clamp_between_extremums(
                 flex_context.container_min_cross_size,
@@ -1296,12 +1498,56 @@ impl FlexItem<'_> {
         (start.into(), end.into())
     }
 
-    /// Return the coordinate of the cross-start side of the content area
+    /// The distance between the outer cross-start edge of the item and its first baseline,
+    /// synthesized from its border box if it doesn't have one, or `None` if the item doesn't
+    /// participate in baseline alignment.
+    /// <https://drafts.csswg.org/css-flexbox/#baseline-participation>
+    fn baseline_from_outer_cross_start(
+        &self,
+        flex_context: &FlexContext,
+        layout_result: &FlexItemLayoutResult,
+    ) -> Option<Length> {
+        // Baselines are only aligned when they are parallel to the main axis, and when the
+        // item has no `auto` cross margins.
+        // TODO: Align baselines when the cross-start edge is the block-end edge, with
+        // `flex-wrap: wrap-reverse`.
+        if self.align_self != AlignItems::Baseline ||
+            flex_context.flex_axis != FlexAxis::Row ||
+            matches!(
+                flex_context.containing_block.style.get_position().flex_wrap,
+                FlexWrap::WrapReverse
+            ) ||
+            self.margin.cross_start.is_auto() ||
+            self.margin.cross_end.is_auto()
+        {
+            return None;
+        }
+        let baseline = layout_result
+            .baselines
+            .first
+            .map(Length::from)
+            .unwrap_or_else(|| {
+                layout_result.hypothetical_cross_size +
+                    self.padding.cross_end.into() +
+                    self.border.cross_end.into()
+            });
+        Some(
+            self.margin.cross_start.auto_is(Au::zero).into() +
+                self.border.cross_start.into() +
+                self.padding.cross_start.into() +
+                baseline,
+        )
+    }
+
+    /// Return the coordinate of the cross-start side of the content area. `baseline_offset` is
+    /// the offset that aligns the baseline of the item with that of the line, if it
+    /// participates in baseline alignment.
     fn align_along_cross_axis(
         &self,
         margin: &FlexRelativeSides<Length>,
         content_size: &Length,
         line_cross_size: Length,
+        baseline_offset: Option<Length>,
     ) -> Length {
         let outer_cross_start =
             if self.margin.cross_start.is_auto() || self.margin.cross_end.is_auto() {
@@ -1317,8 +1563,7 @@ impl FlexItem<'_> {
                         let margin_box_cross = *content_size + self.pbm_auto_is_zero.cross;
                         (line_cross_size - margin_box_cross) / 2.
                     },
-                    // FIXME: handle baseline alignment
-                    AlignItems::Baseline => Length::zero(),
+                    AlignItems::Baseline => baseline_offset.unwrap_or_else(Length::zero),
                 }
             };
         outer_cross_start +
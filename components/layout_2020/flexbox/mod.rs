@@ -3,6 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use serde::Serialize;
+use servo_arc::Arc;
+use style::properties::ComputedValues;
 
 use crate::cell::ArcRefCell;
 use crate::formatting_contexts::IndependentFormattingContext;
@@ -15,6 +17,10 @@ mod layout;
 #[derive(Debug, Serialize)]
 pub(crate) struct FlexContainer {
     children: Vec<ArcRefCell<FlexLevelBox>>,
+
+    /// The style of this flex container.
+    #[serde(skip_serializing)]
+    style: Arc<ComputedValues>,
 }
 
 #[derive(Debug, Serialize)]
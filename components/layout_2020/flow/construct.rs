@@ -106,12 +106,13 @@ enum BlockLevelCreator {
 /// A block container that may still have to be constructed.
 ///
 /// Represents either the inline formatting context of an anonymous block
-/// box or the yet-to-be-computed block container generated from the children
-/// of a given element.
+/// box, whose text is yet to be broken and shaped, or the yet-to-be-computed
+/// block container generated from the children of a given element.
 ///
-/// Deferring allows using rayon’s `into_par_iter`.
+/// Deferring allows using rayon’s `into_par_iter`, so that the text of the
+/// inline formatting contexts of sibling blocks is shaped in parallel.
 enum IntermediateBlockContainer {
-    InlineFormattingContext(BlockContainer),
+    InlineFormattingContext(InlineFormattingContext),
     Deferred {
         contents: NonReplacedContents,
         propagated_text_decoration_line: TextDecorationLine,
@@ -696,9 +697,7 @@ where
             // FIXME(nox): We should be storing this somewhere.
             box_slot: BoxSlot::dummy(),
             kind: BlockLevelCreator::SameFormattingContextBlock(
-                IntermediateBlockContainer::InlineFormattingContext(
-                    BlockContainer::construct_inline_formatting_context(self.context, ifc),
-                ),
+                IntermediateBlockContainer::InlineFormattingContext(ifc),
             ),
         });
     }
@@ -798,7 +797,9 @@ impl IntermediateBlockContainer {
                 propagated_text_decoration_line,
                 is_list_item,
             ),
-            IntermediateBlockContainer::InlineFormattingContext(block_container) => block_container,
+            IntermediateBlockContainer::InlineFormattingContext(ifc) => {
+                BlockContainer::construct_inline_formatting_context(context, ifc)
+            },
         }
     }
 }
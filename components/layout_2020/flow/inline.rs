@@ -33,7 +33,9 @@ use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
 use crate::flow::float::{FloatBox, SequentialLayoutState};
 use crate::flow::FlowLayout;
-use crate::formatting_contexts::{Baselines, IndependentFormattingContext};
+use crate::formatting_contexts::{
+    Baselines, IndependentFormattingContext, NonReplacedFormattingContextContents,
+};
 use crate::fragment_tree::{
     BaseFragmentInfo, BoxFragment, CollapsedBlockMargins, CollapsedMargin, Fragment, FragmentFlags,
     PositioningFragment,
@@ -1664,6 +1666,7 @@ impl InlineContainerState {
             baseline_offset = parent_container.get_cumulative_baseline_offset_for_child(
                 style.effective_vertical_align_for_inline_layout(),
                 &strut_block_sizes,
+                line_height,
             );
             strut_block_sizes.adjust_for_baseline_offset(baseline_offset);
         }
@@ -1711,8 +1714,7 @@ impl InlineContainerState {
         let mut ascent = font_metrics.ascent;
         let mut descent = font_metrics.descent;
         if style.get_inherited_text().line_height == LineHeight::Normal {
-            let half_leading_from_line_gap =
-                (font_metrics.line_gap - descent - ascent).scale_by(0.5);
+            let half_leading_from_line_gap = font_metrics.line_gap.scale_by(0.5);
             ascent += half_leading_from_line_gap;
             descent += half_leading_from_line_gap;
         }
@@ -1759,12 +1761,24 @@ impl InlineContainerState {
         )
     }
 
+    /// The offset of the baseline of a child from the baseline of the line, given its
+    /// `vertical-align`, its block size contribution and the used value of its `line-height`,
+    /// which percentages of `vertical-align` refer to.
     fn get_cumulative_baseline_offset_for_child(
         &self,
         child_vertical_align: GenericVerticalAlign<LengthPercentage>,
         child_block_size: &LineBlockSizes,
+        child_line_height: Length,
     ) -> Au {
         let block_size = self.get_block_size_contribution(&self.font_metrics);
+
+        // The box that is aligned is the layout bounds of the child, which for inline boxes
+        // include the half-leading that `line-height` adds to their content area.
+        // <https://drafts.csswg.org/css2/#line-height>
+        let child_size = child_block_size
+            .baseline_relative_size_for_line_height
+            .as_ref()
+            .unwrap_or(&child_block_size.size_for_baseline_positioning);
         self.baseline_offset +
             match child_vertical_align {
                 // `top` and `bottom are not actually relative to the baseline, but this value is unused
@@ -1787,22 +1801,19 @@ impl InlineContainerState {
                         .px(),
                 ),
                 GenericVerticalAlign::Keyword(VerticalAlignKeyword::TextTop) => {
-                    child_block_size.size_for_baseline_positioning.ascent - self.font_metrics.ascent
+                    child_size.ascent - self.font_metrics.ascent
                 },
                 GenericVerticalAlign::Keyword(VerticalAlignKeyword::Middle) => {
                     // "Align the vertical midpoint of the box with the baseline of the parent
                     // box plus half the x-height of the parent."
-                    (child_block_size.size_for_baseline_positioning.ascent -
-                        child_block_size.size_for_baseline_positioning.descent -
-                        self.font_metrics.x_height)
+                    (child_size.ascent - child_size.descent - self.font_metrics.x_height)
                         .scale_by(0.5)
                 },
                 GenericVerticalAlign::Keyword(VerticalAlignKeyword::TextBottom) => {
-                    self.font_metrics.descent -
-                        child_block_size.size_for_baseline_positioning.descent
+                    self.font_metrics.descent - child_size.descent
                 },
                 GenericVerticalAlign::Length(length_percentage) => {
-                    Au::from_f32_px(-length_percentage.resolve(child_block_size.line_height).px())
+                    Au::from_f32_px(-length_percentage.resolve(child_line_height).px())
                 },
             }
     }
@@ -1863,6 +1874,17 @@ impl IndependentFormattingContext {
         let pbm_sums = &(&pbm.padding + &pbm.border) + &margin.clone().into();
         let mut child_positioning_context = None;
 
+        // With the initial `baseline-source`, inline-blocks are aligned by their last baseline,
+        // while inline flex containers and inline tables are aligned by their first one.
+        // <https://drafts.csswg.org/css-inline-3/#baseline-source>
+        let aligns_by_first_baseline = match self {
+            IndependentFormattingContext::NonReplaced(non_replaced) => !matches!(
+                non_replaced.contents,
+                NonReplacedFormattingContextContents::Flow(_)
+            ),
+            IndependentFormattingContext::Replaced(_) => false,
+        };
+
         // We need to know the inline size of the atomic before deciding whether to do the line break.
         let fragment = match self {
             IndependentFormattingContext::Replaced(replaced) => {
@@ -1930,15 +1952,23 @@ impl IndependentFormattingContext {
                     "Mixed writing modes are not supported yet"
                 );
 
-                // This always collects for the nearest positioned ancestor even if the parent positioning
-                // context doesn't. The thing is we haven't kept track up to this point and there isn't
-                // any harm in keeping the hoisted boxes separate.
-                child_positioning_context = Some(PositioningContext::new_for_subtree(
-                    true, /* collects_for_nearest_positioned_ancestor */
-                ));
+                // If the atomic inline establishes a containing block, it lays out the boxes
+                // hoisted to it itself. Otherwise this always collects for the nearest positioned
+                // ancestor even if the parent positioning context doesn't. The thing is we haven't
+                // kept track up to this point and there isn't any harm in keeping the hoisted boxes
+                // separate.
+                let establishes_containing_block = non_replaced
+                    .style
+                    .establishes_containing_block_for_absolute_descendants();
+                let mut positioning_context =
+                    PositioningContext::new_for_style(&non_replaced.style).unwrap_or_else(|| {
+                        PositioningContext::new_for_subtree(
+                            true, /* collects_for_nearest_positioned_ancestor */
+                        )
+                    });
                 let independent_layout = non_replaced.layout(
                     layout_context,
-                    child_positioning_context.as_mut().unwrap(),
+                    &mut positioning_context,
                     &containing_block_for_children,
                     &ifc.containing_block,
                 );
@@ -1969,7 +1999,7 @@ impl IndependentFormattingContext {
                     },
                 };
 
-                BoxFragment::new(
+                let mut fragment = BoxFragment::new(
                     non_replaced.base_fragment_info,
                     non_replaced.style.clone(),
                     independent_layout.fragments,
@@ -1980,7 +2010,12 @@ impl IndependentFormattingContext {
                     None,
                     CollapsedBlockMargins::zero(),
                 )
-                .with_baselines(independent_layout.baselines)
+                .with_baselines(independent_layout.baselines);
+                if establishes_containing_block {
+                    positioning_context.layout_collected_children(layout_context, &mut fragment);
+                }
+                child_positioning_context = Some(positioning_context);
+                fragment
             },
         };
 
@@ -1993,14 +2028,25 @@ impl IndependentFormattingContext {
         }
 
         let size = &pbm_sums.sum().into() + &fragment.content_rect.size;
-        let baseline_offset = fragment
-            .baselines
-            .last
+
+        // Without a baseline, as for replaced elements, one is synthesized from the block-end
+        // margin edge.
+        // <https://drafts.csswg.org/css-inline-3/#baseline-synthesis-box>
+        let baseline = if aligns_by_first_baseline {
+            fragment.baselines.first
+        } else {
+            fragment.baselines.last
+        };
+        let baseline_offset = baseline
             .map(|baseline| pbm_sums.block_start + baseline)
             .unwrap_or(size.block.into());
 
-        let (block_sizes, baseline_offset_in_parent) =
-            self.get_block_sizes_and_baseline_offset(ifc, size.block, baseline_offset);
+        let (block_sizes, baseline_offset_in_parent) = self.get_block_sizes_and_baseline_offset(
+            layout_context,
+            ifc,
+            size.block,
+            baseline_offset,
+        );
         ifc.update_unbreakable_segment_for_new_content(&block_sizes, size.inline, false);
         ifc.push_line_item_to_unbreakable_segment(LineItem::Atomic(AtomicLineItem {
             fragment,
@@ -2016,6 +2062,7 @@ impl IndependentFormattingContext {
 
     fn get_block_sizes_and_baseline_offset(
         &self,
+        layout_context: &LayoutContext,
         ifc: &InlineFormattingContextState,
         block_size: Length,
         baseline_offset_in_content_area: Au,
@@ -2038,11 +2085,27 @@ impl IndependentFormattingContext {
             }
         };
 
+        // Only percentages of `vertical-align` need the line height, which is the one of the
+        // first available font of the atomic when it is `normal`.
+        let vertical_align = self.style().clone_vertical_align();
+        let child_line_height = match vertical_align {
+            GenericVerticalAlign::Length(_) => {
+                let font_metrics = crate::context::with_thread_local_font_context(
+                    layout_context,
+                    |font_context| get_font_for_first_font_for_style(self.style(), font_context),
+                )
+                .map(|font| font.borrow().metrics.clone())
+                .unwrap_or_else(FontMetrics::empty);
+                line_height(self.style(), &font_metrics)
+            },
+            _ => block_size,
+        };
         let baseline_offset = ifc
             .current_inline_container_state()
             .get_cumulative_baseline_offset_for_child(
-                self.style().clone_vertical_align(),
+                vertical_align,
                 &contribution,
+                child_line_height,
             );
         contribution.adjust_for_baseline_offset(baseline_offset);
 
@@ -2084,7 +2147,7 @@ fn place_pending_floats(ifc: &mut InlineFormattingContextState, line_items: &mut
 fn line_height(parent_style: &ComputedValues, font_metrics: &FontMetrics) -> Length {
     let font_size = parent_style.get_font().font_size.computed_size();
     match parent_style.get_inherited_text().line_height {
-        LineHeight::Normal => Length::from(font_metrics.line_gap),
+        LineHeight::Normal => Length::from(font_metrics.normal_line_height()),
         LineHeight::Number(number) => font_size * number.0,
         LineHeight::Length(length) => length.0,
     }
@@ -240,7 +240,7 @@ impl TextRunLineItem {
         let rect = LogicalRect {
             start_corner,
             size: LogicalVec2 {
-                block: self.font_metrics.line_gap.into(),
+                block: (self.font_metrics.ascent + self.font_metrics.descent).into(),
                 inline: inline_advance,
             },
         };
@@ -365,7 +365,7 @@ impl InlineBoxLineItem {
             },
             size: LogicalVec2 {
                 inline: nested_state.inline_position - state.inline_position,
-                block: self.font_metrics.line_gap.into(),
+                block: (self.font_metrics.ascent + self.font_metrics.descent).into(),
             },
         };
 
@@ -414,33 +414,29 @@ impl InlineBoxLineItem {
 
     /// Given our font metrics, calculate the space above the baseline we need for our content.
     /// Note that this space does not include space for any content in child inline boxes, as
-    /// they are not included in our content rect.
+    /// they are not included in our content rect, nor the half-leading, as the content area
+    /// only spans the ascent and descent of the font.
+    /// <https://drafts.csswg.org/css-inline/#inline-box-dimensions>
     fn calculate_space_above_baseline(&self) -> Au {
-        let (ascent, descent, line_gap) = (
-            self.font_metrics.ascent,
-            self.font_metrics.descent,
-            self.font_metrics.line_gap,
-        );
-        let leading = line_gap - (ascent + descent);
-        leading.scale_by(0.5) + ascent
+        self.font_metrics.ascent
     }
 
     /// Given the state for a line item layout and the space above the baseline for this inline
     /// box, find the block start position relative to the line block start position.
     fn calculate_block_start(&self, state: &LineItemLayoutState, space_above_baseline: Au) -> Au {
         let vertical_align = self.style.effective_vertical_align_for_inline_layout();
-        let line_gap = self.font_metrics.line_gap;
+        let content_height = self.font_metrics.ascent + self.font_metrics.descent;
 
         // The baseline offset that we have in `Self::baseline_offset` is relative to the line
         // baseline, so we need to make it relative to the line block start.
         match vertical_align {
             GenericVerticalAlign::Keyword(VerticalAlignKeyword::Top) => {
                 let line_height: Au = line_height(&self.style, &self.font_metrics).into();
-                (line_height - line_gap).scale_by(0.5)
+                (line_height - content_height).scale_by(0.5)
             },
             GenericVerticalAlign::Keyword(VerticalAlignKeyword::Bottom) => {
                 let line_height: Au = line_height(&self.style, &self.font_metrics).into();
-                let half_leading = (line_height - line_gap).scale_by(0.5);
+                let half_leading = (line_height - content_height).scale_by(0.5);
                 Au::from(state.line_metrics.block_size) - line_height + half_leading
             },
             _ => {
@@ -597,7 +593,7 @@ fn is_baseline_relative(vertical_align: GenericVerticalAlign<LengthPercentage>)
 fn line_height(parent_style: &ComputedValues, font_metrics: &FontMetrics) -> Length {
     let font_size = parent_style.get_font().font_size.computed_size();
     match parent_style.get_inherited_text().line_height {
-        LineHeight::Normal => Length::from(font_metrics.line_gap),
+        LineHeight::Normal => Length::from(font_metrics.normal_line_height()),
         LineHeight::Number(number) => font_size * number.0,
         LineHeight::Length(length) => length.0,
     }
@@ -13,6 +13,7 @@ use style::computed_values::float::T as Float;
 use style::logical_geometry::WritingMode;
 use style::properties::ComputedValues;
 use style::values::computed::{Length, LengthOrAuto};
+use style::values::specified::box_::Contain;
 use style::values::specified::{Display, TextAlignKeyword};
 use style::Zero;
 
@@ -274,6 +275,9 @@ fn calculate_inline_content_size_for_block_level_boxes(
                 style, contents, ..
             } => {
                 let size = sizing::outer_inline(style, writing_mode, || {
+                    if style.effective_containment().contains(Contain::SIZE) {
+                        return ContentSizes::zero();
+                    }
                     contents.inline_content_sizes(layout_context, style.writing_mode)
                 })
                 .max(ContentSizes::zero());
@@ -759,8 +763,15 @@ fn layout_in_flow_non_replaced_block_level_same_formatting_context(
         (computed_block_size.is_definitely_zero() || computed_block_size.is_auto()) &&
         (computed_min_block_size.is_definitely_zero() || computed_min_block_size.is_auto());
 
-    let block_size = containing_block_for_children.block_size.auto_is(|| {
+    // A box with size containment is sized as if it had no contents.
+    // <https://drafts.csswg.org/css-contain/#containment-size>
+    let content_block_size_for_sizing = if style.effective_containment().contains(Contain::SIZE) {
+        Length::zero()
+    } else {
         content_block_size
+    };
+    let block_size = containing_block_for_children.block_size.auto_is(|| {
+        content_block_size_for_sizing
             .clamp_between_extremums(min_box_size.block, max_box_size.block)
             .into()
     });
@@ -825,7 +836,7 @@ impl NonReplacedFormattingContext {
     ///
     /// - <https://drafts.csswg.org/css2/visudet.html#blockwidth>
     /// - <https://drafts.csswg.org/css2/visudet.html#normal-block>
-    fn layout_in_flow_block_level(
+    pub(crate) fn layout_in_flow_block_level(
         &self,
         layout_context: &LayoutContext,
         positioning_context: &mut PositioningContext,
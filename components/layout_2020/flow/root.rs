@@ -15,7 +15,7 @@ use style_traits::CSSPixel;
 use crate::cell::ArcRefCell;
 use crate::context::LayoutContext;
 use crate::dom::{LayoutBox, NodeExt};
-use crate::dom_traversal::{iter_child_nodes, Contents, NodeAndStyleInfo};
+use crate::dom_traversal::{Contents, NodeAndStyleInfo};
 use crate::flexbox::FlexLevelBox;
 use crate::flow::float::FloatBox;
 use crate::flow::inline::InlineLevelBox;
@@ -372,7 +372,7 @@ impl CanvasBackground {
             !matches!(style.clone_display().into(), Display::None)
         {
             // “that element’s first HTML `BODY` or XHTML `body` child element”
-            if let Some(body) = iter_child_nodes(root_element).find(|child| {
+            if let Some(body) = root_element.flat_tree_children().find(|child| {
                 child.is_element() &&
                     child.type_id() ==
                         LayoutNodeType::Element(LayoutElementType::HTMLBodyElement)
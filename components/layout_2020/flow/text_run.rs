@@ -10,6 +10,7 @@ use gfx::font::{FontRef, ShapingFlags, ShapingOptions};
 use gfx::font_cache_thread::FontCacheThread;
 use gfx::font_context::FontContext;
 use gfx::text::text_run::GlyphRun;
+use gfx::text::util::char_does_not_change_font;
 use gfx_traits::ByteIndex;
 use log::warn;
 use range::Range;
@@ -30,9 +31,7 @@ use crate::fragment_tree::BaseFragmentInfo;
 
 // These constants are the xi-unicode line breaking classes that are defined in
 // `table.rs`. Unfortunately, they are only identified by number.
-const XI_LINE_BREAKING_CLASS_CM: u8 = 9;
 const XI_LINE_BREAKING_CLASS_GL: u8 = 12;
-const XI_LINE_BREAKING_CLASS_ZW: u8 = 28;
 const XI_LINE_BREAKING_CLASS_WJ: u8 = 30;
 const XI_LINE_BREAKING_CLASS_ZWJ: u8 = 40;
 
@@ -468,24 +467,6 @@ fn char_prevents_soft_wrap_opportunity_when_before_or_after_atomic(character: ch
         class == XI_LINE_BREAKING_CLASS_ZWJ
 }
 
-/// Whether or not this character should be able to change the font during segmentation.  Certain
-/// character are not rendered at all, so it doesn't matter what font we use to render them. They
-/// should just be added to the current segment.
-fn char_does_not_change_font(character: char) -> bool {
-    if character.is_whitespace() || character.is_control() {
-        return true;
-    }
-    if character == '\u{00A0}' {
-        return true;
-    }
-    let class = linebreak_property(character);
-    class == XI_LINE_BREAKING_CLASS_CM ||
-        class == XI_LINE_BREAKING_CLASS_GL ||
-        class == XI_LINE_BREAKING_CLASS_ZW ||
-        class == XI_LINE_BREAKING_CLASS_WJ ||
-        class == XI_LINE_BREAKING_CLASS_ZWJ
-}
-
 pub(super) fn add_or_get_font(font: &FontRef, ifc_fonts: &mut Vec<FontKeyAndMetrics>) -> usize {
     let font = font.borrow();
     for (index, ifc_font_info) in ifc_fonts.iter().enumerate() {
@@ -9,18 +9,20 @@ use serde::Serialize;
 use servo_arc::Arc;
 use style::logical_geometry::WritingMode;
 use style::properties::ComputedValues;
+use style::values::specified::box_::Contain;
 use style::values::specified::text::TextDecorationLine;
+use style::Zero;
 
 use crate::context::LayoutContext;
 use crate::dom::NodeExt;
 use crate::dom_traversal::{Contents, NodeAndStyleInfo};
 use crate::flexbox::FlexContainer;
 use crate::flow::BlockFormattingContext;
-use crate::fragment_tree::{BaseFragmentInfo, Fragment};
+use crate::fragment_tree::{BaseFragmentInfo, Fragment, FragmentFlags};
 use crate::positioned::PositioningContext;
 use crate::replaced::ReplacedContent;
 use crate::sizing::{self, ContentSizes};
-use crate::style_ext::DisplayInside;
+use crate::style_ext::{ComputedValuesExt, DisplayInside};
 use crate::table::Table;
 use crate::ContainingBlock;
 
@@ -155,9 +157,7 @@ impl IndependentFormattingContext {
 
     pub fn inline_content_sizes(&mut self, layout_context: &LayoutContext) -> ContentSizes {
         match self {
-            Self::NonReplaced(inner) => inner
-                .contents
-                .inline_content_sizes(layout_context, inner.style.writing_mode),
+            Self::NonReplaced(inner) => inner.inline_content_sizes(layout_context),
             Self::Replaced(inner) => inner.contents.inline_content_sizes(&inner.style),
         }
     }
@@ -169,13 +169,9 @@ impl IndependentFormattingContext {
     ) -> ContentSizes {
         match self {
             Self::NonReplaced(non_replaced) => {
-                let style = &non_replaced.style;
-                let content_sizes = &mut non_replaced.content_sizes;
-                let contents = &mut non_replaced.contents;
-                sizing::outer_inline(style, containing_block_writing_mode, || {
-                    *content_sizes.get_or_insert_with(|| {
-                        contents.inline_content_sizes(layout_context, style.writing_mode)
-                    })
+                let style = Arc::clone(&non_replaced.style);
+                sizing::outer_inline(&style, containing_block_writing_mode, || {
+                    non_replaced.inline_content_sizes(layout_context)
                 })
             },
             Self::Replaced(replaced) => {
@@ -195,7 +191,7 @@ impl NonReplacedFormattingContext {
         containing_block_for_children: &ContainingBlock,
         containing_block: &ContainingBlock,
     ) -> IndependentLayout {
-        match &self.contents {
+        let mut layout = match &self.contents {
             NonReplacedFormattingContextContents::Flow(bfc) => bfc.layout(
                 layout_context,
                 positioning_context,
@@ -212,16 +208,47 @@ impl NonReplacedFormattingContext {
                 containing_block_for_children,
                 containing_block,
             ),
+        };
+
+        // A box with size containment is sized as if it had no contents, which still
+        // overflow it, unless `contain-intrinsic-size` gives it a size.
+        // <https://drafts.csswg.org/css-contain/#containment-size>
+        if self.is_size_contained() {
+            layout.content_block_size = self
+                .style
+                .contain_intrinsic_size()
+                .block
+                .unwrap_or_else(Au::zero);
         }
+        layout
     }
 
     pub fn inline_content_sizes(&mut self, layout_context: &LayoutContext) -> ContentSizes {
+        if self.is_size_contained() {
+            return self.style.contain_intrinsic_size().inline.map_or_else(
+                ContentSizes::zero,
+                |size| ContentSizes {
+                    min_content: size,
+                    max_content: size,
+                },
+            );
+        }
         let writing_mode = self.style.writing_mode;
         let contents = &mut self.contents;
         *self
             .content_sizes
             .get_or_insert_with(|| contents.inline_content_sizes(layout_context, writing_mode))
     }
+
+    /// Whether the box has size containment, which it also has while it skips its
+    /// contents.
+    /// <https://drafts.csswg.org/css-contain-2/#valdef-content-visibility-auto>
+    fn is_size_contained(&self) -> bool {
+        self.style.effective_containment().contains(Contain::SIZE) ||
+            self.base_fragment_info
+                .flags
+                .contains(FragmentFlags::SKIPS_CONTENTS)
+    }
 }
 
 impl NonReplacedFormattingContextContents {
@@ -234,7 +261,7 @@ impl NonReplacedFormattingContextContents {
             Self::Flow(inner) => inner
                 .contents
                 .inline_content_sizes(layout_context, writing_mode),
-            Self::Flex(inner) => inner.inline_content_sizes(),
+            Self::Flex(inner) => inner.inline_content_sizes(layout_context, writing_mode),
             Self::Table(table) => table.inline_content_sizes(layout_context, writing_mode),
         }
     }
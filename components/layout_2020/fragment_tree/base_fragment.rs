@@ -81,6 +81,12 @@ bitflags! {
         const IS_BODY_ELEMENT_OF_HTML_ELEMENT_ROOT = 0b00000001;
         /// Whether or not the node that created this Fragment is a `<br>` element.
         const IS_BR_ELEMENT = 0b00000010;
+        /// Whether or not the node that created this Fragment is an `<input>`, `<button>` or
+        /// `<select>` element, whose baseline is the one of its text even when it clips it.
+        const IS_FORM_CONTROL = 0b00000100;
+        /// Whether or not layout skips the contents of the element that created this
+        /// Fragment, because of its `content-visibility`.
+        const SKIPS_CONTENTS = 0b00001000;
     }
 }
 
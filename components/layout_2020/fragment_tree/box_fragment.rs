@@ -6,13 +6,17 @@ use app_units::Au;
 use gfx_traits::print_tree::PrintTree;
 use serde::Serialize;
 use servo_arc::Arc as ServoArc;
+use style::color::AbsoluteColor;
 use style::computed_values::overflow_x::T as ComputedOverflow;
 use style::computed_values::position::T as ComputedPosition;
 use style::properties::ComputedValues;
-use style::values::computed::{CSSPixelLength, Length, LengthPercentage, LengthPercentageOrAuto};
+use style::values::computed::{
+    BorderStyle, CSSPixelLength, Length, LengthPercentage, LengthPercentageOrAuto,
+};
+use style::values::specified::box_::Contain;
 use style::Zero;
 
-use super::{BaseFragment, BaseFragmentInfo, CollapsedBlockMargins, Fragment};
+use super::{BaseFragment, BaseFragmentInfo, CollapsedBlockMargins, Fragment, FragmentFlags};
 use crate::cell::ArcRefCell;
 use crate::formatting_contexts::Baselines;
 use crate::geom::{
@@ -26,6 +30,15 @@ pub(crate) struct ExtraBackground {
     pub rect: LogicalRect<Au>,
 }
 
+/// The style and color of one side of the border of a table cell in the collapsing border
+/// model, which come from whichever of the borders meeting there won the border conflict
+/// resolution. See <https://drafts.csswg.org/css-tables/#border-conflict-resolution-algorithm>.
+#[derive(Clone, Debug)]
+pub(crate) struct CollapsedBorder {
+    pub style: BorderStyle,
+    pub color: AbsoluteColor,
+}
+
 #[derive(Serialize)]
 pub(crate) struct BoxFragment {
     pub base: BaseFragment,
@@ -71,6 +84,11 @@ pub(crate) struct BoxFragment {
 
     #[serde(skip_serializing)]
     pub extra_backgrounds: Vec<ExtraBackground>,
+
+    /// The borders to paint instead of the ones of `style`, for table cells in the collapsing
+    /// border model. The widths of these borders are in `border`.
+    #[serde(skip_serializing)]
+    pub collapsed_borders: Option<LogicalSides<CollapsedBorder>>,
 }
 
 impl BoxFragment {
@@ -135,9 +153,10 @@ impl BoxFragment {
         // > margin edge.
         //
         // This applies even if there is no baseline set, so we unconditionally set the value here
-        // and ignore anything that is set via [`Self::with_baselines`].
+        // and ignore anything that is set via [`Self::with_baselines`]. Form controls that clip
+        // their text are not concerned, as they are aligned by their text like in other browsers.
         let mut baselines = Baselines::default();
-        if style.establishes_scroll_container() {
+        if has_scroll_container_baseline(&style, base_fragment_info.flags) {
             baselines.last = Some(
                 (content_rect.size.block + padding.block_end + border.block_end + margin.block_end)
                     .into(),
@@ -159,6 +178,7 @@ impl BoxFragment {
             overconstrained,
             resolved_sticky_insets: None,
             extra_backgrounds: Vec::new(),
+            collapsed_borders: None,
         }
     }
 
@@ -168,7 +188,7 @@ impl BoxFragment {
         // > value) a block-level or inline-level block container that is a scroll container
         // > always has a last baseline set, whose baselines all correspond to its block-end
         // > margin edge.
-        if !self.style.establishes_scroll_container() {
+        if !has_scroll_container_baseline(&self.style, self.base.flags) {
             self.baselines.last = baselines.last;
         }
         self.baselines.first = baselines.first;
@@ -179,6 +199,10 @@ impl BoxFragment {
         self.extra_backgrounds.push(extra_background);
     }
 
+    pub fn set_collapsed_borders(&mut self, collapsed_borders: LogicalSides<CollapsedBorder>) {
+        self.collapsed_borders = Some(collapsed_borders);
+    }
+
     pub fn scrollable_overflow(
         &self,
         containing_block: &PhysicalRect<Length>,
@@ -244,7 +268,10 @@ impl BoxFragment {
             .border_rect()
             .to_physical(self.style.writing_mode, containing_block);
 
-        if self.style.establishes_scroll_container() {
+        // Scroll containers and boxes with paint containment clip their contents.
+        if self.style.establishes_scroll_container() ||
+            self.style.effective_containment().contains(Contain::PAINT)
+        {
             return overflow;
         }
 
@@ -356,3 +383,10 @@ impl BoxFragment {
         convert_to_length_or_auto(PhysicalSides::new(top, right, bottom, left))
     }
 }
+
+/// Whether the last baseline of a box is its block-end margin edge, as it is for scroll
+/// containers other than form controls.
+/// <https://drafts.csswg.org/css-align-3/#baseline-export>
+fn has_scroll_container_baseline(style: &ComputedValues, flags: FragmentFlags) -> bool {
+    style.establishes_scroll_container() && !flags.contains(FragmentFlags::IS_FORM_CONTROL)
+}
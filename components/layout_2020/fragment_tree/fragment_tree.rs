@@ -10,6 +10,7 @@ use serde::Serialize;
 use style::animation::AnimationSetKey;
 use style::dom::OpaqueNode;
 use style::values::computed::Length;
+use style::values::specified::box_::ContentVisibility;
 use webrender_api::units;
 
 use super::{ContainingBlockManager, Fragment, Tag};
@@ -93,6 +94,27 @@ impl FragmentTree {
         });
     }
 
+    /// The nodes of the elements with `content-visibility: auto` that generated boxes, which
+    /// script needs to know about to keep track of whether they are relevant to the user.
+    pub fn nodes_with_content_visibility_auto(&self) -> Vec<OpaqueNode> {
+        let mut nodes = Vec::new();
+        self.find(|fragment, _, _| {
+            let fragment = match fragment {
+                Fragment::Box(fragment) | Fragment::Float(fragment) => fragment,
+                _ => return None::<()>,
+            };
+            let tag = fragment.base.tag?;
+            if !tag.is_pseudo() &&
+                fragment.style.get_box().content_visibility == ContentVisibility::Auto &&
+                nodes.last() != Some(&tag.node)
+            {
+                nodes.push(tag.node);
+            }
+            None
+        });
+        nodes
+    }
+
     pub fn get_content_box_for_node(&self, requested_node: OpaqueNode) -> Option<Rect<Au>> {
         let mut bounding_box = PhysicalRect::zero();
         let mut found_any_nodes = false;
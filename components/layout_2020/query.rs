@@ -12,8 +12,9 @@ use euclid::{SideOffsets2D, Size2D, Vector2D};
 use log::warn;
 use msg::constellation_msg::PipelineId;
 use script_layout_interface::rpc::{
-    ContentBoxResponse, ContentBoxesResponse, LayoutRPC, NodeGeometryResponse,
-    NodeScrollIdResponse, OffsetParentResponse, ResolvedStyleResponse, TextIndexResponse,
+    CaretPositionResponse, CaretRectResponse, ContentBoxResponse, ContentBoxesResponse, LayoutRPC,
+    NodeGeometryResponse, NodeScrollIdResponse, OffsetParentResponse, ResolvedStyleResponse,
+    TextIndexResponse,
 };
 use script_layout_interface::wrapper_traits::{
     LayoutNode, ThreadSafeLayoutElement, ThreadSafeLayoutNode,
@@ -79,6 +80,12 @@ pub struct LayoutThreadData {
     /// Index in a text fragment. We need this do determine the insertion point.
     pub text_index_response: TextIndexResponse,
 
+    /// A queued response for the caret rectangle at an offset in the text of a node.
+    pub caret_rect_response: CaretRectResponse,
+
+    /// A queued response for the caret position closest to a point.
+    pub caret_position_response: CaretPositionResponse,
+
     /// A queued response for the list of nodes at a given point.
     pub nodes_from_point_response: Vec<UntrustedNodeAddress>,
 
@@ -162,6 +169,18 @@ impl LayoutRPC for LayoutRPCImpl {
         rw_data.text_index_response.clone()
     }
 
+    fn caret_rect(&self) -> CaretRectResponse {
+        let LayoutRPCImpl(rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.caret_rect_response.clone()
+    }
+
+    fn caret_position(&self) -> CaretPositionResponse {
+        let LayoutRPCImpl(rw_data) = self;
+        let rw_data = rw_data.lock().unwrap();
+        rw_data.caret_position_response.clone()
+    }
+
     fn element_inner_text(&self) -> String {
         let LayoutRPCImpl(rw_data) = self;
         let rw_data = rw_data.lock().unwrap();
@@ -649,6 +668,14 @@ pub fn process_text_index_request(_node: OpaqueNode, _point: Point2D<Au>) -> Tex
     TextIndexResponse(None)
 }
 
+pub fn process_caret_rect_request(_node: OpaqueNode, _offset: usize) -> CaretRectResponse {
+    CaretRectResponse(None)
+}
+
+pub fn process_caret_position_request(_point: Point2D<f32>) -> CaretPositionResponse {
+    CaretPositionResponse(None)
+}
+
 pub fn process_resolved_font_style_query<'dom, E>(
     context: &LayoutContext,
     node: E,
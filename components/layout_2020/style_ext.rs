@@ -13,11 +13,13 @@ use style::properties::longhands::box_sizing::computed_value::T as BoxSizing;
 use style::properties::longhands::column_span::computed_value::T as ColumnSpan;
 use style::properties::ComputedValues;
 use style::values::computed::image::Image as ComputedImageLayer;
-use style::values::computed::{Length, LengthPercentage, NonNegativeLengthPercentage, Size};
+use style::values::computed::{
+    ContainIntrinsicSize, Length, LengthPercentage, NonNegativeLengthPercentage, Size,
+};
 use style::values::generics::box_::{GenericVerticalAlign, Perspective, VerticalAlignKeyword};
-use style::values::generics::length::MaxSize;
+use style::values::generics::length::{GenericContainIntrinsicSize, MaxSize};
+use style::values::specified::box_ as stylo;
 use style::values::specified::box_::DisplayOutside as StyloDisplayOutside;
-use style::values::specified::{box_ as stylo, Overflow};
 use style::Zero;
 use webrender_api as wr;
 
@@ -181,6 +183,8 @@ pub(crate) trait ComputedValuesExt {
     ) -> LogicalSides<LengthPercentageOrAuto<'_>>;
     fn has_transform_or_perspective(&self) -> bool;
     fn effective_z_index(&self) -> i32;
+    fn effective_containment(&self) -> stylo::Contain;
+    fn contain_intrinsic_size(&self) -> LogicalVec2<Option<Au>>;
     fn establishes_block_formatting_context(&self) -> bool;
     fn establishes_stacking_context(&self) -> bool;
     fn establishes_scroll_container(&self) -> bool;
@@ -438,6 +442,63 @@ impl ComputedValuesExt for ComputedValues {
         }
     }
 
+    /// The containment of this box, without the types of containment that do not apply to it.
+    /// Layout, paint and size containment have no effect on internal table boxes and on
+    /// non-atomic inline boxes, and size containment has no effect on tables either.
+    /// <https://drafts.csswg.org/css-contain/#contain-property>
+    fn effective_containment(&self) -> stylo::Contain {
+        let mut containment = self.get_box().contain;
+        // <https://drafts.csswg.org/css-contain-2/#content-visibility>
+        match self.get_box().content_visibility {
+            stylo::ContentVisibility::Visible => {},
+            stylo::ContentVisibility::Auto => containment
+                .insert(stylo::Contain::LAYOUT | stylo::Contain::PAINT | stylo::Contain::STYLE),
+            stylo::ContentVisibility::Hidden => containment.insert(
+                stylo::Contain::LAYOUT |
+                    stylo::Contain::PAINT |
+                    stylo::Contain::STYLE |
+                    stylo::Contain::SIZE,
+            ),
+        }
+        let display = self.get_box().display;
+        let is_internal_table_box = matches!(
+            Display::from(display),
+            Display::GeneratingBox(DisplayGeneratingBox::LayoutInternal(_))
+        );
+        if display.is_inline_flow() || is_internal_table_box {
+            containment
+                .remove(stylo::Contain::LAYOUT | stylo::Contain::PAINT | stylo::Contain::SIZE);
+        } else if display.inside() == stylo::DisplayInside::Table {
+            containment.remove(stylo::Contain::SIZE);
+        }
+        containment
+    }
+
+    /// The size that a box with size containment has in each axis, instead of zero, in
+    /// the writing mode of the box.
+    /// <https://drafts.csswg.org/css-sizing-4/#intrinsic-size-override>
+    fn contain_intrinsic_size(&self) -> LogicalVec2<Option<Au>> {
+        fn to_au(size: &ContainIntrinsicSize) -> Option<Au> {
+            match size {
+                // The last remembered size of boxes is not tracked, so `auto <length>`
+                // always behaves like `<length>`.
+                GenericContainIntrinsicSize::Length(length) |
+                GenericContainIntrinsicSize::AutoLength(length) => {
+                    Some(Au::from_f32_px(length.px()))
+                },
+                GenericContainIntrinsicSize::None | GenericContainIntrinsicSize::AutoNone => None,
+            }
+        }
+        let position = self.get_position();
+        LogicalVec2::from_physical_size(
+            &PhysicalSize::new(
+                to_au(&position.contain_intrinsic_width),
+                to_au(&position.contain_intrinsic_height),
+            ),
+            self.writing_mode,
+        )
+    }
+
     /// Return true if this style is a normal block and establishes
     /// a new block formatting context.
     fn establishes_block_formatting_context(&self) -> bool {
@@ -453,14 +514,22 @@ impl ComputedValuesExt for ComputedValues {
             return true;
         }
 
-        // TODO: We need to handle CSS Contain here.
+        // Layout and paint containment make the box an independent formatting context.
+        if self
+            .effective_containment()
+            .intersects(stylo::Contain::LAYOUT | stylo::Contain::PAINT)
+        {
+            return true;
+        }
+
         false
     }
 
     /// Whether or not the `overflow` value of this style establishes a scroll container.
+    /// Unlike `hidden`, `overflow: clip` clips the contents of the box without making
+    /// it a scroll container.
     fn establishes_scroll_container(&self) -> bool {
-        self.get_box().overflow_x != Overflow::Visible ||
-            self.get_box().overflow_y != Overflow::Visible
+        self.get_box().overflow_x.is_scrollable() || self.get_box().overflow_y.is_scrollable()
     }
 
     /// Returns true if this fragment establishes a new stacking context and false otherwise.
@@ -488,9 +557,19 @@ impl ComputedValuesExt for ComputedValues {
             return true;
         }
 
+        // Layout and paint containment create stacking contexts.
+        if self
+            .effective_containment()
+            .intersects(stylo::Contain::LAYOUT | stylo::Contain::PAINT)
+        {
+            return true;
+        }
+
         // Fixed position and sticky position always create stacking contexts.
-        // TODO(mrobinson): We need to handle sticky positioning here when we support it.
-        if self.get_box().position == ComputedPosition::Fixed {
+        if matches!(
+            self.get_box().position,
+            ComputedPosition::Fixed | ComputedPosition::Sticky
+        ) {
             return true;
         }
 
@@ -534,7 +613,26 @@ impl ComputedValuesExt for ComputedValues {
             return true;
         }
 
-        // TODO: We need to handle CSS Contain here.
+        // "A computed value of preserve-3d for transform-style on a transformable element
+        // establishes both a stacking context and a containing block for all descendants."
+        // https://drafts.csswg.org/css-transforms-2/#transform-style-property
+        if self.get_box().transform_style == ComputedTransformStyle::Preserve3d &&
+            !self.get_box().display.is_inline_flow()
+        {
+            return true;
+        }
+
+        // Layout and paint containment make the box a containing block for all of its
+        // descendants.
+        if self
+            .effective_containment()
+            .intersects(stylo::Contain::LAYOUT | stylo::Contain::PAINT)
+        {
+            return true;
+        }
+
+        // TODO: We need to handle `backdrop-filter` and `will-change` here, once they are
+        // supported.
         false
     }
 
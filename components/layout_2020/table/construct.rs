@@ -15,8 +15,8 @@ use style::str::char_is_whitespace;
 use style::values::specified::TextDecorationLine;
 
 use super::{
-    Table, TableSlot, TableSlotCell, TableSlotCoordinates, TableSlotOffset, TableTrack,
-    TableTrackGroup, TableTrackGroupType,
+    Table, TableCaption, TableSlot, TableSlotCell, TableSlotCoordinates, TableSlotOffset,
+    TableTrack, TableTrackGroup, TableTrackGroupType,
 };
 use crate::context::LayoutContext;
 use crate::dom::{BoxSlot, NodeExt};
@@ -816,7 +816,28 @@ where
                     ::std::mem::forget(box_slot);
                 },
                 DisplayLayoutInternal::TableCaption => {
-                    // TODO: Handle table captions.
+                    let contents = match contents.try_into() {
+                        Ok(non_replaced_contents) => BlockFormattingContext::construct(
+                            self.context,
+                            info,
+                            non_replaced_contents,
+                            self.current_text_decoration_line,
+                            false, /* is_list_item */
+                        ),
+                        Err(_replaced) => {
+                            unreachable!("Replaced should not have a LayoutInternal display type.");
+                        },
+                    };
+
+                    self.builder.table.captions.push(TableCaption {
+                        context: NonReplacedFormattingContext {
+                            base_fragment_info: info.into(),
+                            style: info.style.clone(),
+                            content_sizes: None,
+                            contents: NonReplacedFormattingContextContents::Flow(contents),
+                        },
+                    });
+
                     // We are doing this until we have actually set a Box for this `BoxSlot`.
                     ::std::mem::forget(box_slot);
                 },
@@ -7,11 +7,14 @@ use std::ops::Range;
 use app_units::{Au, MAX_AU};
 use log::warn;
 use servo_arc::Arc;
+use style::color::AbsoluteColor;
 use style::computed_values::border_collapse::T as BorderCollapse;
+use style::computed_values::caption_side::T as CaptionSide;
+use style::computed_values::position::T as Position;
 use style::logical_geometry::WritingMode;
 use style::properties::ComputedValues;
 use style::values::computed::{
-    CSSPixelLength, Length, LengthPercentage as ComputedLengthPercentage, Percentage,
+    BorderStyle, CSSPixelLength, Length, LengthPercentage as ComputedLengthPercentage, Percentage,
 };
 use style::values::generics::box_::{GenericVerticalAlign as VerticalAlign, VerticalAlignKeyword};
 use style::values::generics::length::GenericLengthPercentageOrAuto::{Auto, LengthPercentage};
@@ -21,9 +24,12 @@ use super::{Table, TableSlot, TableSlotCell, TableTrackGroup};
 use crate::context::LayoutContext;
 use crate::formatting_contexts::{Baselines, IndependentLayout};
 use crate::fragment_tree::{
-    BoxFragment, CollapsedBlockMargins, ExtraBackground, Fragment, PositioningFragment,
+    BaseFragmentInfo, BoxFragment, CollapsedBlockMargins, CollapsedBorder, ExtraBackground,
+    Fragment, PositioningFragment,
+};
+use crate::geom::{
+    AuOrAuto, LengthPercentageOrAuto, LogicalRect, LogicalSides, LogicalVec2, PhysicalSides,
 };
-use crate::geom::{AuOrAuto, LengthPercentageOrAuto, LogicalRect, LogicalSides, LogicalVec2};
 use crate::positioned::{PositioningContext, PositioningContextLength};
 use crate::sizing::ContentSizes;
 use crate::style_ext::{Clamp, ComputedValuesExt, PaddingBorderMargin};
@@ -69,6 +75,121 @@ struct ColumnLayout {
     has_originating_cells: bool,
 }
 
+/// The kinds of boxes whose borders meet in the collapsing border model, from the one whose
+/// borders have the least precedence to the one whose borders have the most.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum BorderOrigin {
+    Table,
+    ColumnGroup,
+    Column,
+    RowGroup,
+    Row,
+    Cell,
+}
+
+/// A border of a table box in the collapsing border model, taking part in the conflict
+/// resolution with the other borders at the same place.
+#[derive(Clone, Debug)]
+struct CollapsingBorder {
+    style: BorderStyle,
+    color: AbsoluteColor,
+    width: Au,
+    origin: BorderOrigin,
+}
+
+impl CollapsingBorder {
+    fn from_style(
+        style: &ComputedValues,
+        writing_mode: WritingMode,
+        origin: BorderOrigin,
+    ) -> LogicalSides<Self> {
+        let border = style.get_border();
+        let border_styles = LogicalSides::from_physical(
+            &PhysicalSides::new(
+                border.border_top_style,
+                border.border_right_style,
+                border.border_bottom_style,
+                border.border_left_style,
+            ),
+            writing_mode,
+        );
+        let colors = LogicalSides::from_physical(
+            &PhysicalSides::new(
+                style.resolve_color(border.border_top_color.clone()),
+                style.resolve_color(border.border_right_color.clone()),
+                style.resolve_color(border.border_bottom_color.clone()),
+                style.resolve_color(border.border_left_color.clone()),
+            ),
+            writing_mode,
+        );
+        let widths = style.border_width(writing_mode);
+        let side = |style: BorderStyle, color: &AbsoluteColor, width: Length| CollapsingBorder {
+            style,
+            color: color.clone(),
+            width: width.into(),
+            origin,
+        };
+        LogicalSides {
+            inline_start: side(
+                border_styles.inline_start,
+                &colors.inline_start,
+                widths.inline_start,
+            ),
+            inline_end: side(
+                border_styles.inline_end,
+                &colors.inline_end,
+                widths.inline_end,
+            ),
+            block_start: side(
+                border_styles.block_start,
+                &colors.block_start,
+                widths.block_start,
+            ),
+            block_end: side(border_styles.block_end, &colors.block_end, widths.block_end),
+        }
+    }
+
+    /// The precedence of the style of this border over the style of the borders of the
+    /// same width.
+    fn style_precedence(&self) -> u8 {
+        match self.style {
+            BorderStyle::Double => 8,
+            BorderStyle::Solid => 7,
+            BorderStyle::Dashed => 6,
+            BorderStyle::Dotted => 5,
+            BorderStyle::Ridge => 4,
+            BorderStyle::Outset => 3,
+            BorderStyle::Groove => 2,
+            BorderStyle::Inset => 1,
+            BorderStyle::None | BorderStyle::Hidden => 0,
+        }
+    }
+
+    /// Resolve the conflict between the borders at the same place, as described in
+    /// <https://drafts.csswg.org/css-tables/#border-conflict-resolution-algorithm>:
+    /// a hidden border suppresses all the others, then wider borders win over narrower ones,
+    /// then the style decides and finally the kind of box the border belongs to. When there
+    /// is still a tie, the first border wins.
+    fn resolve(first: Self, others: impl IntoIterator<Item = Self>) -> Self {
+        let mut winner = first;
+        for border in others {
+            if winner.style == BorderStyle::Hidden {
+                break;
+            }
+            let wins = border.style == BorderStyle::Hidden ||
+                (border.width, border.style_precedence(), border.origin) >
+                    (winner.width, winner.style_precedence(), winner.origin);
+            if wins {
+                winner = border;
+            }
+        }
+        if winner.style == BorderStyle::Hidden {
+            winner.width = Au::zero();
+        }
+        winner
+    }
+}
+
 /// A helper struct that performs the layout of the box tree version
 /// of a table into the fragment tree version. This implements
 /// <https://drafts.csswg.org/css-tables/#table-layout-algorithm>
@@ -86,6 +207,11 @@ struct TableLayout<'a> {
     row_baselines: Vec<Au>,
     cells_laid_out: Vec<Vec<Option<CellLayout>>>,
     basis_for_cell_padding_percentage: Au,
+    /// The used width of the table, which is the inline size of its captions.
+    table_width: Au,
+    /// The resolved borders of the cells, indexed like the slots of the table, when the
+    /// table is in the collapsing border model.
+    collapsed_borders: Option<Vec<Vec<Option<LogicalSides<CollapsingBorder>>>>>,
 }
 
 #[derive(Clone, Debug)]
@@ -123,6 +249,8 @@ impl<'a> TableLayout<'a> {
             row_baselines: Vec::new(),
             cells_laid_out: Vec::new(),
             basis_for_cell_padding_percentage: Au::zero(),
+            table_width: Au::zero(),
+            collapsed_borders: None,
         }
     }
 
@@ -137,6 +265,7 @@ impl<'a> TableLayout<'a> {
     ) {
         let writing_mode = containing_block_for_children.style.writing_mode;
         self.compute_track_constrainedness_and_has_originating_cells(writing_mode);
+        self.compute_collapsed_borders(writing_mode);
         self.compute_cell_measures(layout_context, containing_block_for_children);
         self.compute_column_measures(writing_mode);
         self.compute_table_width(containing_block_for_children, containing_block_for_table);
@@ -155,6 +284,229 @@ impl<'a> TableLayout<'a> {
         );
     }
 
+    /// In the collapsing border model, resolve the borders of every cell. Where the borders
+    /// of several boxes meet, only the one that wins the conflict between them is drawn,
+    /// centered on the grid line. A cell whose side runs along several grid segments, because
+    /// it spans several tracks, uses the widest of the borders that won on these segments.
+    fn compute_collapsed_borders(&mut self, writing_mode: WritingMode) {
+        if self.table.style.clone_border_collapse() != BorderCollapse::Collapse {
+            return;
+        }
+
+        let table = self.table;
+        let height = table.size.height;
+        let width = table.size.width;
+        let table_borders =
+            CollapsingBorder::from_style(&table.style, writing_mode, BorderOrigin::Table);
+        let cell_borders = |coords: TableSlotCoordinates| {
+            table.resolve_first_cell(coords).map(|cell| {
+                CollapsingBorder::from_style(&cell.style, writing_mode, BorderOrigin::Cell)
+            })
+        };
+        let row_borders = |row_index: usize| {
+            table.rows.get(row_index).map(|row| {
+                CollapsingBorder::from_style(&row.style, writing_mode, BorderOrigin::Row)
+            })
+        };
+        let row_group = |row_index: usize| {
+            table
+                .rows
+                .get(row_index)
+                .and_then(|row| row.group_index)
+                .map(|index| &table.row_groups[index])
+        };
+        let row_group_borders = |row_group: &TableTrackGroup| {
+            CollapsingBorder::from_style(&row_group.style, writing_mode, BorderOrigin::RowGroup)
+        };
+        let column_borders = |column_index: usize| {
+            table.columns.get(column_index).map(|column| {
+                CollapsingBorder::from_style(&column.style, writing_mode, BorderOrigin::Column)
+            })
+        };
+        let column_group = |column_index: usize| {
+            table
+                .columns
+                .get(column_index)
+                .and_then(|column| column.group_index)
+                .and_then(|index| table.column_groups.get(index))
+        };
+        let column_group_borders = |column_group: &TableTrackGroup| {
+            CollapsingBorder::from_style(
+                &column_group.style,
+                writing_mode,
+                BorderOrigin::ColumnGroup,
+            )
+        };
+
+        // The border on the grid line before the row at `row_index`, in the given column.
+        let border_before_row = |row_index: usize, column_index: usize| {
+            let mut borders = Vec::new();
+            if row_index > 0 {
+                let previous_row = row_index - 1;
+                borders.extend(
+                    cell_borders(TableSlotCoordinates::new(column_index, previous_row))
+                        .map(|borders| borders.block_end),
+                );
+                borders.extend(row_borders(previous_row).map(|borders| borders.block_end));
+                borders.extend(
+                    row_group(previous_row)
+                        .filter(|row_group| row_group.track_range.end == row_index)
+                        .map(|row_group| row_group_borders(row_group).block_end),
+                );
+            } else {
+                borders.push(table_borders.block_start.clone());
+                borders.extend(column_borders(column_index).map(|borders| borders.block_start));
+                borders.extend(
+                    column_group(column_index)
+                        .map(|column_group| column_group_borders(column_group).block_start),
+                );
+            }
+            if row_index < height {
+                borders.extend(
+                    cell_borders(TableSlotCoordinates::new(column_index, row_index))
+                        .map(|borders| borders.block_start),
+                );
+                borders.extend(row_borders(row_index).map(|borders| borders.block_start));
+                borders.extend(
+                    row_group(row_index)
+                        .filter(|row_group| row_group.track_range.start == row_index)
+                        .map(|row_group| row_group_borders(row_group).block_start),
+                );
+            } else {
+                borders.push(table_borders.block_end.clone());
+                borders.extend(column_borders(column_index).map(|borders| borders.block_end));
+                borders.extend(
+                    column_group(column_index)
+                        .map(|column_group| column_group_borders(column_group).block_end),
+                );
+            }
+            let mut borders = borders.into_iter();
+            let first = borders
+                .next()
+                .unwrap_or_else(|| table_borders.block_start.clone());
+            CollapsingBorder::resolve(first, borders)
+        };
+
+        // The border on the grid line before the column at `column_index`, in the given row.
+        let border_before_column = |column_index: usize, row_index: usize| {
+            let mut borders = Vec::new();
+            if column_index > 0 {
+                let previous_column = column_index - 1;
+                borders.extend(
+                    cell_borders(TableSlotCoordinates::new(previous_column, row_index))
+                        .map(|borders| borders.inline_end),
+                );
+                borders.extend(column_borders(previous_column).map(|borders| borders.inline_end));
+                borders.extend(
+                    column_group(previous_column)
+                        .filter(|column_group| column_group.track_range.end == column_index)
+                        .map(|column_group| column_group_borders(column_group).inline_end),
+                );
+            } else {
+                borders.push(table_borders.inline_start.clone());
+                borders.extend(row_borders(row_index).map(|borders| borders.inline_start));
+                borders.extend(
+                    row_group(row_index).map(|row_group| row_group_borders(row_group).inline_start),
+                );
+            }
+            if column_index < width {
+                borders.extend(
+                    cell_borders(TableSlotCoordinates::new(column_index, row_index))
+                        .map(|borders| borders.inline_start),
+                );
+                borders.extend(column_borders(column_index).map(|borders| borders.inline_start));
+                borders.extend(
+                    column_group(column_index)
+                        .filter(|column_group| column_group.track_range.start == column_index)
+                        .map(|column_group| column_group_borders(column_group).inline_start),
+                );
+            } else {
+                borders.push(table_borders.inline_end.clone());
+                borders.extend(row_borders(row_index).map(|borders| borders.inline_end));
+                borders.extend(
+                    row_group(row_index).map(|row_group| row_group_borders(row_group).inline_end),
+                );
+            }
+            let mut borders = borders.into_iter();
+            let first = borders
+                .next()
+                .unwrap_or_else(|| table_borders.inline_start.clone());
+            CollapsingBorder::resolve(first, borders)
+        };
+
+        let widest = |borders: Vec<CollapsingBorder>| {
+            borders
+                .into_iter()
+                .reduce(|widest, border| {
+                    if border.width > widest.width {
+                        border
+                    } else {
+                        widest
+                    }
+                })
+                .expect("Cells should span at least one row and one column.")
+        };
+
+        let mut collapsed_borders = Vec::with_capacity(height);
+        for row_index in 0..height {
+            let mut collapsed_borders_in_row = Vec::with_capacity(width);
+            for column_index in 0..width {
+                let cell = match table.slots[row_index][column_index] {
+                    TableSlot::Cell(ref cell) => cell,
+                    _ => {
+                        collapsed_borders_in_row.push(None);
+                        continue;
+                    },
+                };
+                let columns = column_index..column_index + cell.colspan;
+                let rows = row_index..row_index + cell.rowspan;
+                collapsed_borders_in_row.push(Some(LogicalSides {
+                    inline_start: widest(
+                        rows.clone()
+                            .map(|row| border_before_column(column_index, row))
+                            .collect(),
+                    ),
+                    inline_end: widest(
+                        rows.map(|row| border_before_column(columns.end, row))
+                            .collect(),
+                    ),
+                    block_start: widest(
+                        columns
+                            .clone()
+                            .map(|column| border_before_row(row_index, column))
+                            .collect(),
+                    ),
+                    block_end: widest(
+                        columns
+                            .map(|column| border_before_row(row_index + cell.rowspan, column))
+                            .collect(),
+                    ),
+                }));
+            }
+            collapsed_borders.push(collapsed_borders_in_row);
+        }
+        self.collapsed_borders = Some(collapsed_borders);
+    }
+
+    /// The used border widths of the cell at the given coordinates. In the collapsing border
+    /// model, a cell gets half of each of its resolved borders, the other half belonging to
+    /// its neighbor or to the table.
+    fn get_cell_border(
+        &self,
+        cell: &TableSlotCell,
+        coordinates: TableSlotCoordinates,
+        writing_mode: WritingMode,
+    ) -> LogicalSides<Length> {
+        let collapsed_borders = self
+            .collapsed_borders
+            .as_ref()
+            .and_then(|borders| borders[coordinates.y][coordinates.x].as_ref());
+        match collapsed_borders {
+            Some(borders) => borders.map(|border| border.width.scale_by(0.5).into()),
+            None => cell.style.border_width(writing_mode),
+        }
+    }
+
     /// This is an implementation of *Computing Cell Measures* from
     /// <https://drafts.csswg.org/css-tables/#computing-cell-measures>.
     pub(crate) fn compute_cell_measures(
@@ -209,7 +561,11 @@ impl<'a> TableLayout<'a> {
                     .style
                     .padding(writing_mode)
                     .percentages_relative_to(Length::zero());
-                let border = cell.style.border_width(writing_mode);
+                let border = self.get_cell_border(
+                    cell,
+                    TableSlotCoordinates::new(column_index, row_index),
+                    writing_mode,
+                );
 
                 let inline_padding_border_sum =
                     Au::from(padding.inline_sum() + border.inline_sum());
@@ -370,7 +726,6 @@ impl<'a> TableLayout<'a> {
         // >     1, of its corresponding table-column (if any), and of its corresponding table-column-group (if
         // >     any)
         //
-        // TODO: Take into account `table-column` and `table-column-group` lengths.
         // TODO: Take into account changes to this computation for fixed table layout.
         let mut next_span_n = usize::MAX;
         for column_index in 0..self.table.size.width {
@@ -441,8 +796,8 @@ impl<'a> TableLayout<'a> {
 
         for column_index in 0..self.table.size.width {
             let old_column_measure = &old_column_measures[column_index];
-            let mut new_column_content_sizes = ContentSizes::zero();
-            let mut new_column_intrinsic_percentage_width = Percentage(0.);
+            let mut new_column_content_sizes = old_column_measure.content_sizes;
+            let mut new_column_intrinsic_percentage_width = old_column_measure.percentage;
 
             for row_index in 0..self.table.size.height {
                 let coords = TableSlotCoordinates::new(column_index, row_index);
@@ -491,7 +846,7 @@ impl<'a> TableLayout<'a> {
                 // >     2. Define the baseline border spacing as the sum of the horizontal
                 // >        border-spacing for any columns spanned by the cell, other than the one in
                 // >        which the cell originates.
-                let baseline_border_spacing = border_spacing.inline * (n as i32 - 1);
+                let baseline_border_spacing = border_spacing.inline * (cell.colspan as i32 - 1);
 
                 // >     3. The contribution of the cell is the sum of:
                 // >         a. the min-content width of the column based on cells of span up to N-1
@@ -511,7 +866,7 @@ impl<'a> TableLayout<'a> {
                 // >               max-content width and the baseline min-content width
                 let old_content_size_difference =
                     old_column_content_size.max_content - old_column_content_size.min_content;
-                let baseline_difference = baseline_min_content_width - baseline_max_content_width;
+                let baseline_difference = baseline_max_content_width - baseline_min_content_width;
 
                 let mut b =
                     old_content_size_difference.to_f32_px() / baseline_difference.to_f32_px();
@@ -527,17 +882,19 @@ impl<'a> TableLayout<'a> {
                 // >         c. the product of:
                 // >             - the ratio of the max-content width based on cells of span up to
                 // >               N-1 of the column to the baseline max-content width
+                let mut max_content_ratio = old_column_content_size.max_content.to_f32_px() /
+                    baseline_content_sizes.max_content.to_f32_px();
+                if !max_content_ratio.is_finite() {
+                    max_content_ratio = 0.0;
+                }
                 // >             - the outer min-content width of the cell minus the baseline
                 // >               max-content width and baseline border spacing, or 0 if this is
                 // >               negative
                 let c = (cell_inline_content_sizes.min_content -
                     baseline_content_sizes.max_content -
                     baseline_border_spacing)
-                    .min(Au::zero())
-                    .scale_by(
-                        old_column_content_size.max_content.to_f32_px() /
-                            baseline_content_sizes.max_content.to_f32_px(),
-                    );
+                    .max(Au::zero())
+                    .scale_by(max_content_ratio);
 
                 let new_column_min_content_width = a + b + c;
 
@@ -565,8 +922,9 @@ impl<'a> TableLayout<'a> {
                 // >          b. the product of:
                 // >              1. the ratio of the max-content width based on cells of span up to
                 // >                 N-1 of the column to the baseline max-content width
-                let b_1 = old_column_content_size.max_content.to_f32_px() /
-                    baseline_content_sizes.max_content.to_f32_px();
+                //
+                // This is the same ratio as above, for the min-content width.
+                let b_1 = max_content_ratio;
 
                 // >              2. the outer max-content width of the cell minus the baseline
                 // >                 max-content width and the baseline border spacing, or 0 if this
@@ -574,9 +932,9 @@ impl<'a> TableLayout<'a> {
                 let b_2 = (cell_inline_content_sizes.max_content -
                     baseline_content_sizes.max_content -
                     baseline_border_spacing)
-                    .min(Au::zero());
+                    .max(Au::zero());
                 let b = b_2.scale_by(b_1);
-                let new_column_max_content_width = a + b + c;
+                let new_column_max_content_width = a + b;
 
                 // The computed values for the column are always the largest of any processed cell
                 // in that column.
@@ -704,6 +1062,7 @@ impl<'a> TableLayout<'a> {
         // > border spacing (if any). This is the width that we will be able to allocate to the
         // > columns.
         self.assignable_width = used_width_of_table - inline_border_spacing;
+        self.table_width = used_width_of_table;
 
         // This is the amount that we will use to resolve percentages in the padding of cells.
         // It matches what Gecko and Blink do, though they disagree when there is a big caption.
@@ -1020,9 +1379,11 @@ impl<'a> TableLayout<'a> {
                     total_width += self.distributed_column_widths[width_index];
                 }
 
-                let border = cell
-                    .style
-                    .border_width(containing_block_for_table.style.writing_mode);
+                let border = self.get_cell_border(
+                    cell,
+                    TableSlotCoordinates::new(column_index, row_index),
+                    containing_block_for_table.style.writing_mode,
+                );
                 let padding = cell
                     .style
                     .padding(containing_block_for_table.style.writing_mode)
@@ -1404,25 +1765,50 @@ impl<'a> TableLayout<'a> {
 
     /// Lay out the table of this [`TableLayout`] into fragments. This should only be be called
     /// after calling [`TableLayout.compute_measures`].
-    fn layout(mut self, positioning_context: &mut PositioningContext) -> IndependentLayout {
+    fn layout(
+        mut self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+    ) -> IndependentLayout {
         assert_eq!(self.table.size.height, self.row_sizes.len());
         assert_eq!(self.table.size.width, self.distributed_column_widths.len());
 
         let mut baselines = Baselines::default();
         let mut fragments = Vec::new();
 
+        // The captions on the top side come before the table grid, which starts after them.
+        //
+        // TODO: The border and padding of the table should only surround the table grid,
+        // which requires a table wrapper box holding both the captions and the grid.
+        let top_captions_size = self.layout_captions(
+            layout_context,
+            positioning_context,
+            CaptionSide::Top,
+            Au::zero(),
+            &mut fragments,
+        );
+
         if self.table.size.width == 0 || self.table.size.height == 0 {
+            let table_block_end = top_captions_size + self.final_table_height;
+            let bottom_captions_size = self.layout_captions(
+                layout_context,
+                positioning_context,
+                CaptionSide::Bottom,
+                table_block_end,
+                &mut fragments,
+            );
             return IndependentLayout {
                 fragments,
-                content_block_size: self.final_table_height,
+                content_block_size: table_block_end + bottom_captions_size,
                 content_inline_size_for_table: Some(self.assignable_width),
                 baselines,
             };
         }
 
-        let dimensions = TableAndTrackDimensions::new(&self);
+        let dimensions = TableAndTrackDimensions::new(&self, top_captions_size);
         self.make_fragments_for_columns_rows_and_groups(&dimensions, &mut fragments);
 
+        let mut cell_fragments = Vec::new();
         for row_index in 0..self.table.size.height {
             // From <https://drafts.csswg.org/css-align-3/#baseline-export>
             // > If any cells in the row participate in first baseline/last baseline alignment along
@@ -1474,6 +1860,19 @@ impl<'a> TableLayout<'a> {
                 let mut fragment =
                     cell.create_fragment(layout, cell_rect, row_baseline, positioning_context);
 
+                let collapsed_borders = self
+                    .collapsed_borders
+                    .as_ref()
+                    .and_then(|borders| borders[row_index][column_index].as_ref());
+                if let Some(collapsed_borders) = collapsed_borders {
+                    fragment.set_collapsed_borders(collapsed_borders.map(|border| {
+                        CollapsedBorder {
+                            style: border.style,
+                            color: border.color.clone(),
+                        }
+                    }));
+                }
+
                 let column = self.table.columns.get(column_index);
                 let column_group = column
                     .and_then(|column| column.group_index)
@@ -1512,23 +1911,99 @@ impl<'a> TableLayout<'a> {
                     })
                 }
 
-                fragments.push(Fragment::Box(fragment));
+                cell_fragments.push((row_index..row_index + cell.rowspan, fragment));
             }
         }
 
+        self.make_fragments_for_sticky_rows_and_groups(
+            &dimensions,
+            &mut cell_fragments,
+            &mut fragments,
+        );
+        fragments.extend(
+            cell_fragments
+                .into_iter()
+                .map(|(_, fragment)| Fragment::Box(fragment)),
+        );
+
         if self.table.anonymous {
             baselines.first = None;
             baselines.last = None;
         }
 
+        let table_block_end = dimensions.table_rect.max_block_position();
+        let bottom_captions_size = self.layout_captions(
+            layout_context,
+            positioning_context,
+            CaptionSide::Bottom,
+            table_block_end,
+            &mut fragments,
+        );
+
         IndependentLayout {
             fragments,
-            content_block_size: dimensions.table_rect.max_block_position(),
+            content_block_size: table_block_end + bottom_captions_size,
             content_inline_size_for_table: Some(dimensions.table_rect.max_inline_position()),
             baselines,
         }
     }
 
+    /// Lay out the captions on the given side of the table grid one after the other, starting
+    /// at `block_offset`, and return the block size that they take up. Their margins do not
+    /// collapse with each other nor with the table grid.
+    fn layout_captions(
+        &self,
+        layout_context: &LayoutContext,
+        positioning_context: &mut PositioningContext,
+        side: CaptionSide,
+        block_offset: Au,
+        fragments: &mut Vec<Fragment>,
+    ) -> Au {
+        let containing_block = ContainingBlock {
+            inline_size: self.table_width,
+            block_size: AuOrAuto::Auto,
+            style: &self.table.style,
+        };
+
+        let mut current_block_offset = block_offset;
+        for caption in self.table.captions.iter() {
+            let style = &caption.context.style;
+            if style.get_inherited_table().caption_side != side {
+                continue;
+            }
+
+            let positioning_context_length = positioning_context.len();
+            let mut fragment = positioning_context.layout_maybe_position_relative_fragment(
+                layout_context,
+                &containing_block,
+                style,
+                |positioning_context| {
+                    caption.context.layout_in_flow_block_level(
+                        layout_context,
+                        positioning_context,
+                        &containing_block,
+                        None, /* sequential_layout_state */
+                    )
+                },
+            );
+
+            fragment.content_rect.start_corner.block +=
+                Length::from(current_block_offset) + fragment.margin.block_start;
+            current_block_offset += (fragment.margin.block_sum() +
+                fragment.border.block_sum() +
+                fragment.padding.block_sum() +
+                fragment.content_rect.size.block)
+                .into();
+
+            let fragment = Fragment::Box(fragment);
+            positioning_context
+                .adjust_static_position_of_hoisted_fragments(&fragment, positioning_context_length);
+            fragments.push(fragment);
+        }
+
+        current_block_offset - block_offset
+    }
+
     fn make_fragments_for_columns_rows_and_groups(
         &mut self,
         dimensions: &TableAndTrackDimensions,
@@ -1553,7 +2028,7 @@ impl<'a> TableLayout<'a> {
         }
 
         for row_group in self.table.row_groups.iter() {
-            if !row_group.is_empty() {
+            if !row_group.is_empty() && !is_sticky(&row_group.style) {
                 fragments.push(Fragment::Positioning(PositioningFragment::new_empty(
                     row_group.base_fragment_info,
                     dimensions.get_row_group_rect(row_group).into(),
@@ -1563,10 +2038,91 @@ impl<'a> TableLayout<'a> {
         }
 
         for (row_index, row) in self.table.rows.iter().enumerate() {
-            fragments.push(Fragment::Positioning(PositioningFragment::new_empty(
+            if !self.is_in_sticky_row_or_group(row_index) {
+                fragments.push(Fragment::Positioning(PositioningFragment::new_empty(
+                    row.base_fragment_info,
+                    dimensions.get_row_rect(row_index).into(),
+                    row.style.clone(),
+                )));
+            }
+        }
+    }
+
+    /// Whether the row at the given index is sticky or belongs to a sticky row group, in which
+    /// case its fragment is a child of the box fragment of the sticky row or row group.
+    fn is_in_sticky_row_or_group(&self, row_index: usize) -> bool {
+        let row = match self.table.rows.get(row_index) {
+            Some(row) => row,
+            None => return false,
+        };
+        is_sticky(&row.style) ||
+            row.group_index.map_or(false, |index| {
+                is_sticky(&self.table.row_groups[index].style)
+            })
+    }
+
+    /// Sticky row groups and rows get a box fragment, holding the fragments of their rows and
+    /// cells, so that these move along with it when it sticks. The fragments of the cells that
+    /// are taken are removed from `cell_fragments`.
+    ///
+    /// TODO: Cells spanning several rows only move along with a sticky row group, not with
+    /// a sticky row.
+    fn make_fragments_for_sticky_rows_and_groups(
+        &self,
+        dimensions: &TableAndTrackDimensions,
+        cell_fragments: &mut Vec<(Range<usize>, BoxFragment)>,
+        fragments: &mut Vec<Fragment>,
+    ) {
+        let mut take_cell_fragments = |rows: Range<usize>| {
+            let (taken, kept): (Vec<_>, Vec<_>) = std::mem::take(cell_fragments)
+                .into_iter()
+                .partition(|(cell_rows, _)| {
+                    cell_rows.start >= rows.start && cell_rows.end <= rows.end
+                });
+            *cell_fragments = kept;
+            taken
+                .into_iter()
+                .map(|(_, fragment)| Fragment::Box(fragment))
+                .collect::<Vec<_>>()
+        };
+
+        for row_group in self.table.row_groups.iter() {
+            if row_group.is_empty() || !is_sticky(&row_group.style) {
+                continue;
+            }
+            let mut children: Vec<Fragment> = row_group
+                .track_range
+                .clone()
+                .filter_map(|row_index| {
+                    let row = self.table.rows.get(row_index)?;
+                    Some(Fragment::Positioning(PositioningFragment::new_empty(
+                        row.base_fragment_info,
+                        dimensions.get_row_rect(row_index).into(),
+                        row.style.clone(),
+                    )))
+                })
+                .collect();
+            children.extend(take_cell_fragments(row_group.track_range.clone()));
+            fragments.push(Fragment::Box(create_sticky_track_fragment(
+                row_group.base_fragment_info,
+                &row_group.style,
+                dimensions.get_row_group_rect(row_group),
+                children,
+            )));
+        }
+
+        for (row_index, row) in self.table.rows.iter().enumerate() {
+            let in_sticky_row_group = row.group_index.map_or(false, |index| {
+                is_sticky(&self.table.row_groups[index].style)
+            });
+            if in_sticky_row_group || !is_sticky(&row.style) {
+                continue;
+            }
+            fragments.push(Fragment::Box(create_sticky_track_fragment(
                 row.base_fragment_info,
-                dimensions.get_row_rect(row_index).into(),
-                row.style.clone(),
+                &row.style,
+                dimensions.get_row_rect(row_index),
+                take_cell_fragments(row_index..row_index + 1),
             )));
         }
     }
@@ -1585,7 +2141,9 @@ struct TableAndTrackDimensions {
 }
 
 impl TableAndTrackDimensions {
-    fn new(table_layout: &TableLayout) -> Self {
+    /// Compute the dimensions of the table grid, which starts at `block_offset` in the
+    /// block direction, after the captions above it.
+    fn new(table_layout: &TableLayout, block_offset: Au) -> Self {
         let border_spacing = table_layout.table.border_spacing();
 
         let mut column_dimensions = Vec::new();
@@ -1597,7 +2155,7 @@ impl TableAndTrackDimensions {
         }
 
         let mut row_dimensions = Vec::new();
-        let mut row_offset = border_spacing.block;
+        let mut row_offset = block_offset + border_spacing.block;
         for row_index in 0..table_layout.table.size.height {
             let row_size = table_layout.row_sizes[row_index];
             row_dimensions.push((row_offset, row_offset + row_size));
@@ -1618,10 +2176,13 @@ impl TableAndTrackDimensions {
         };
 
         let table_rect = LogicalRect {
-            start_corner: LogicalVec2::zero(),
+            start_corner: LogicalVec2 {
+                inline: Au::zero(),
+                block: block_offset,
+            },
             size: LogicalVec2 {
                 inline: column_offset,
-                block: row_offset,
+                block: row_offset - block_offset,
             },
         };
 
@@ -1770,24 +2331,20 @@ impl Table {
             None => return CellOrTrackMeasure::zero(),
         };
 
-        let (size, min_size, max_size) = get_sizes_from_style(&column.style, writing_mode);
-        let percentage_contribution =
-            get_size_percentage_contribution_from_style(&column.style, writing_mode);
-
-        CellOrTrackMeasure {
-            content_sizes: ContentSizes {
-                // > The outer min-content width of a table-column or table-column-group is
-                // > max(min-width, width).
-                // But that's clearly wrong, since it would be equal to or greater than
-                // the outer max-content width. So we match other browsers instead.
-                min_content: min_size.inline,
-                // > The outer max-content width of a table-column or table-column-group is
-                // > max(min-width, min(max-width, width)).
-                // This matches Gecko, but Blink and WebKit ignore max_size.
-                max_content: min_size.inline.max(max_size.inline.min(size.inline)),
-            },
-            percentage: percentage_contribution.inline,
+        let mut measure = get_column_or_column_group_measure(&column.style, writing_mode);
+        let column_group = column
+            .group_index
+            .and_then(|index| self.column_groups.get(index));
+        if let Some(column_group) = column_group {
+            let column_group_measure =
+                get_column_or_column_group_measure(&column_group.style, writing_mode);
+            measure
+                .content_sizes
+                .max_assign(column_group_measure.content_sizes);
+            measure.percentage =
+                Percentage(measure.percentage.0.max(column_group_measure.percentage.0));
         }
+        measure
     }
 
     fn get_row_measure_for_row_at_index(
@@ -1836,7 +2393,7 @@ impl Table {
             containing_block_for_children,
             containing_block_for_table,
         );
-        table_layout.layout(positioning_context)
+        table_layout.layout(layout_context, positioning_context)
     }
 }
 
@@ -1943,6 +2500,80 @@ impl TableSlotCell {
     }
 }
 
+/// The measure of a table-column or table-column-group, which is at least the
+/// measure of every column it applies to.
+fn is_sticky(style: &ComputedValues) -> bool {
+    style.clone_position() == Position::Sticky
+}
+
+/// Create the box fragment of a sticky row or row group, with the given rect in the table
+/// grid. Its `children` are positioned in the table grid too, so they are moved to be
+/// relative to the new fragment, along with the extra backgrounds of the cells.
+fn create_sticky_track_fragment(
+    base_fragment_info: BaseFragmentInfo,
+    style: &Arc<ComputedValues>,
+    rect: LogicalRect<Au>,
+    mut children: Vec<Fragment>,
+) -> BoxFragment {
+    let offset = &rect.start_corner;
+    let length_offset: LogicalVec2<Length> = LogicalVec2 {
+        inline: offset.inline.into(),
+        block: offset.block.into(),
+    };
+    for child in children.iter_mut() {
+        match child {
+            Fragment::Box(box_fragment) => {
+                box_fragment.content_rect.start_corner =
+                    &box_fragment.content_rect.start_corner - &length_offset;
+                for extra_background in box_fragment.extra_backgrounds.iter_mut() {
+                    extra_background.rect.start_corner =
+                        &extra_background.rect.start_corner - offset;
+                }
+            },
+            Fragment::Positioning(positioning_fragment) => {
+                positioning_fragment.rect.start_corner =
+                    &positioning_fragment.rect.start_corner - &length_offset;
+            },
+            _ => {},
+        }
+    }
+
+    BoxFragment::new(
+        base_fragment_info,
+        style.clone(),
+        children,
+        rect.into(),
+        LogicalSides::zero(), /* padding */
+        LogicalSides::zero(), /* border */
+        LogicalSides::zero(), /* margin */
+        None,                 /* clearance */
+        CollapsedBlockMargins::zero(),
+    )
+}
+
+fn get_column_or_column_group_measure(
+    style: &Arc<ComputedValues>,
+    writing_mode: WritingMode,
+) -> CellOrTrackMeasure {
+    let (size, min_size, max_size) = get_sizes_from_style(style, writing_mode);
+    let percentage_contribution = get_size_percentage_contribution_from_style(style, writing_mode);
+
+    CellOrTrackMeasure {
+        content_sizes: ContentSizes {
+            // > The outer min-content width of a table-column or table-column-group is
+            // > max(min-width, width).
+            // But that's clearly wrong, since it would be equal to or greater than
+            // the outer max-content width. So we match other browsers instead.
+            min_content: min_size.inline,
+            // > The outer max-content width of a table-column or table-column-group is
+            // > max(min-width, min(max-width, width)).
+            // This matches Gecko, but Blink and WebKit ignore max_size.
+            max_content: min_size.inline.max(max_size.inline.min(size.inline)),
+        },
+        percentage: percentage_contribution.inline,
+    }
+}
+
 fn get_size_percentage_contribution_from_style(
     style: &Arc<ComputedValues>,
     writing_mode: WritingMode,
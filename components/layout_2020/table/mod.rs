@@ -24,6 +24,7 @@ use style_traits::dom::OpaqueNode;
 
 use super::flow::BlockFormattingContext;
 use crate::flow::BlockContainer;
+use crate::formatting_contexts::NonReplacedFormattingContext;
 use crate::fragment_tree::BaseFragmentInfo;
 
 pub type TableSize = Size2D<usize, UnknownUnit>;
@@ -53,6 +54,10 @@ pub struct Table {
     /// The size of this table.
     pub size: TableSize,
 
+    /// The captions of this table, in tree order, defined by `<caption>` or
+    /// `display: table-caption` elements.
+    captions: Vec<TableCaption>,
+
     /// Whether or not this Table is anonymous.
     anonymous: bool,
 }
@@ -67,6 +72,7 @@ impl Table {
             rows: Vec::new(),
             slots: Vec::new(),
             size: TableSize::zero(),
+            captions: Vec::new(),
             anonymous: false,
         }
     }
@@ -235,3 +241,11 @@ impl TableTrackGroup {
         self.track_range.is_empty()
     }
 }
+
+/// A table caption, which is laid out above or below the table grid depending on its
+/// `caption-side`. See <https://drafts.csswg.org/css-tables/#table-caption>.
+#[derive(Debug, Serialize)]
+pub struct TableCaption {
+    /// The contents of this caption, which establish a block formatting context.
+    context: NonReplacedFormattingContext,
+}
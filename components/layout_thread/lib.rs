@@ -18,7 +18,7 @@ use std::sync::{Arc, Mutex, MutexGuard};
 
 use app_units::Au;
 use embedder_traits::resources::{self, Resource};
-use euclid::default::Size2D as UntypedSize2D;
+use euclid::default::{Point2D as UntypedPoint2D, Size2D as UntypedSize2D};
 use euclid::{Point2D, Rect, Scale, Size2D};
 use fnv::FnvHashMap;
 use fxhash::{FxHashMap, FxHashSet};
@@ -65,14 +65,18 @@ use script::layout_dom::{ServoLayoutDocument, ServoLayoutElement, ServoLayoutNod
 use script_layout_interface::message::{
     Msg, NodesFromPointQueryType, QueryMsg, Reflow, ReflowComplete, ReflowGoal, ScriptReflow,
 };
-use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, TextIndexResponse};
+use script_layout_interface::rpc::{
+    CaretPositionResponse, CaretRectResponse, LayoutRPC, OffsetParentResponse, TextIndexResponse,
+};
+use script_layout_interface::user_stylesheets::UserStylesheets;
 use script_layout_interface::wrapper_traits::LayoutNode;
 use script_layout_interface::{Layout, LayoutConfig, LayoutFactory};
 use script_traits::{
     ConstellationControlMsg, DrawAPaintImageResult, IFrameSizeMsg, LayoutControlMsg,
     LayoutMsg as ConstellationMsg, PaintWorkletError, Painter, ScrollState, UntrustedNodeAddress,
-    WebrenderIpcSender, WindowSizeData, WindowSizeType,
+    UserStylesheet, WebrenderIpcSender, WindowSizeData, WindowSizeType,
 };
+use servo_allocator::Partition;
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
 use servo_config::opts::{self, DebugOptions};
@@ -81,7 +85,7 @@ use style::animation::{AnimationSetKey, DocumentAnimationSet, ElementAnimationSe
 use style::context::{
     QuirksMode, RegisteredSpeculativePainter, RegisteredSpeculativePainters, SharedStyleContext,
 };
-use style::dom::{ShowSubtree, ShowSubtreeDataAndPrimaryValues, TElement, TNode};
+use style::dom::{OpaqueNode, ShowSubtree, ShowSubtreeDataAndPrimaryValues, TElement, TNode};
 use style::driver;
 use style::error_reporting::RustLogReporter;
 use style::global_style_data::{GLOBAL_STYLE_DATA, STYLE_THREAD_POOL};
@@ -181,6 +185,9 @@ pub struct LayoutThread {
     /// The sizes of all iframes encountered during the last layout operation.
     last_iframe_sizes: RefCell<FnvHashMap<BrowsingContextId, Size2D<f32, CSSPixel>>>,
 
+    /// The user stylesheets injected by the embedder, in the order they were added.
+    user_stylesheets: UserStylesheets,
+
     /// Debug options, copied from configuration to this `LayoutThread` in order
     /// to avoid having to constantly access the thread-safe global options.
     debug: DebugOptions,
@@ -205,6 +212,7 @@ impl LayoutFactory for LayoutFactoryImpl {
             config.webrender_api_sender,
             config.paint_time_metrics,
             config.window_size,
+            config.user_stylesheets,
         ))
     }
 }
@@ -376,6 +384,7 @@ impl LayoutThread {
         webrender_api: WebrenderIpcSender,
         paint_time_metrics: PaintTimeMetrics,
         window_size: WindowSizeData,
+        user_stylesheets: Vec<UserStylesheet>,
     ) -> LayoutThread {
         // Let webrender know about this pipeline by sending an empty display list.
         webrender_api.send_initial_transaction(id.to_webrender());
@@ -433,6 +442,8 @@ impl LayoutThread {
                 offset_parent_response: OffsetParentResponse::empty(),
                 scroll_offsets: HashMap::new(),
                 text_index_response: TextIndexResponse(None),
+                caret_rect_response: CaretRectResponse(None),
+                caret_position_response: CaretPositionResponse(None),
                 nodes_from_point_response: vec![],
                 element_inner_text_response: String::new(),
                 inner_window_dimensions_response: None,
@@ -441,6 +452,7 @@ impl LayoutThread {
             paint_time_metrics,
             layout_query_waiting_time: Histogram::new(),
             last_iframe_sizes: Default::default(),
+            user_stylesheets: UserStylesheets::new(&user_stylesheets),
             debug: opts::get().debug.clone(),
             nonincremental_layout: opts::get().nonincremental_layout,
         }
@@ -485,6 +497,7 @@ impl LayoutThread {
 
     /// Receives and dispatches messages from the script and constellation threads
     fn handle_request<'a, 'b>(&mut self, request: Request) {
+        let _partition = servo_allocator::enter_partition(Partition::Layout);
         let rw_data = self.rw_data.clone();
         let mut possibly_locked_rw_data = Some(rw_data.lock().unwrap());
         let mut rw_data = RwData {
@@ -541,7 +554,26 @@ impl LayoutThread {
                 self.stylist
                     .remove_stylesheet(DocumentStyleSheet(stylesheet.clone()), &guard);
             },
+            Msg::AddUserStylesheet(stylesheet) => {
+                let stylesheet = self.user_stylesheets.add(&stylesheet);
+                // Before the first reflow, user stylesheets are added along with the UA ones.
+                if !self.first_reflow.get() {
+                    let guard = GLOBAL_STYLE_DATA.shared_lock.read();
+                    self.stylist.append_stylesheet(stylesheet.clone(), &guard);
+                    self.handle_add_stylesheet(&stylesheet.0, &guard);
+                }
+            },
+            Msg::RemoveUserStylesheet(id) => {
+                if let Some(stylesheet) = self.user_stylesheets.remove(id) {
+                    if !self.first_reflow.get() {
+                        let guard = GLOBAL_STYLE_DATA.shared_lock.read();
+                        self.stylist.remove_stylesheet(stylesheet, &guard);
+                    }
+                }
+            },
             Msg::SetQuirksMode(mode) => self.handle_set_quirks_mode(mode),
+            // Only Layout 2020 paints in forced colors mode.
+            Msg::SetForcedColors(_) => {},
             Msg::GetRPC(response_chan) => {
                 response_chan
                     .send(Box::new(LayoutRPCImpl(self.rw_data.clone())) as Box<dyn LayoutRPC + Send>)
@@ -947,6 +979,12 @@ impl LayoutThread {
                         &QueryMsg::TextIndexQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
+                        &QueryMsg::CaretRectQuery(..) => {
+                            rw_data.caret_rect_response = CaretRectResponse(None);
+                        },
+                        &QueryMsg::CaretPositionFromPointQuery(_) => {
+                            rw_data.caret_position_response = CaretPositionResponse(None);
+                        },
                         &QueryMsg::ElementInnerTextQuery(_) => {
                             rw_data.element_inner_text_response = String::new();
                         },
@@ -1022,6 +1060,12 @@ impl LayoutThread {
                 self.handle_add_stylesheet(&stylesheet.0, &ua_or_user_guard);
             }
 
+            for stylesheet in self.user_stylesheets.iter() {
+                self.stylist
+                    .append_stylesheet(stylesheet.clone(), &ua_or_user_guard);
+                self.handle_add_stylesheet(&stylesheet.0, &ua_or_user_guard);
+            }
+
             if self.stylist.quirks_mode() != QuirksMode::NoQuirks {
                 self.stylist.append_stylesheet(
                     ua_stylesheets.quirks_mode_stylesheet.clone(),
@@ -1238,6 +1282,14 @@ impl LayoutThread {
                     rw_data.text_index_response =
                         TextIndexResponse(rw_data.indexable_text.text_index(node, point_in_node));
                 },
+                &QueryMsg::CaretRectQuery(node, offset) => {
+                    rw_data.caret_rect_response =
+                        CaretRectResponse(rw_data.indexable_text.caret_rect(node, offset));
+                },
+                &QueryMsg::CaretPositionFromPointQuery(client_point) => {
+                    rw_data.caret_position_response =
+                        self.process_caret_position_request(client_point, rw_data);
+                },
                 &QueryMsg::ClientRectQuery(node) => {
                     rw_data.client_rect_response = process_client_rect_query(node, root_flow);
                 },
@@ -1311,6 +1363,37 @@ impl LayoutThread {
         }
     }
 
+    /// Finds the caret position closest to a point of the viewport: the topmost node there,
+    /// and the offset of the point in its text.
+    fn process_caret_position_request(
+        &self,
+        client_point: UntypedPoint2D<f32>,
+        rw_data: &LayoutThreadData,
+    ) -> CaretPositionResponse {
+        let results = self.webrender_api.hit_test(
+            Some(self.id.to_webrender()),
+            units::WorldPoint::from_untyped(client_point),
+            HitTestFlags::POINT_RELATIVE_TO_PIPELINE_VIEWPORT,
+        );
+        let node = match results.first() {
+            Some(result) => result.node,
+            None => return CaretPositionResponse(None),
+        };
+
+        // Text is positioned relative to its stacking context, which is only the document for
+        // text that is not in a nested scroll frame or transformed.
+        let point = match rw_data.scroll_offsets.get(&self.id.root_scroll_id()) {
+            Some(scroll_offset) => client_point - scroll_offset.to_untyped(),
+            None => client_point,
+        };
+        let point = Point2D::new(Au::from_f32_px(point.x), Au::from_f32_px(point.y));
+        let offset = rw_data
+            .indexable_text
+            .caret_offset(OpaqueNode(node.0 as usize), point)
+            .unwrap_or(0);
+        CaretPositionResponse(Some((node, offset)))
+    }
+
     fn update_scroll_node_state(&self, state: &ScrollState, rw_data: &mut LayoutThreadData) {
         rw_data
             .scroll_offsets
@@ -1638,6 +1721,11 @@ fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
             "servo.css",
             &resources::read_bytes(Resource::ServoCSS),
         )?,
+        parse_ua_stylesheet(
+            &shared_lock,
+            "form-controls.css",
+            &resources::read_bytes(Resource::FormControlsCSS),
+        )?,
         parse_ua_stylesheet(
             &shared_lock,
             "presentational-hints.css",
@@ -22,7 +22,7 @@ use euclid::{Point2D, Rect, Scale, Size2D};
 use fnv::FnvHashMap;
 use fxhash::FxHashMap;
 use gfx::font_cache_thread::FontCacheThread;
-use gfx::font_context;
+use gfx::{font, font_context};
 use gfx_traits::{node_id_from_scroll_id, Epoch};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
@@ -30,10 +30,11 @@ use layout::context::LayoutContext;
 use layout::display_list::{DisplayList, WebRenderImageInfo};
 use layout::dom::DOMLayoutData;
 use layout::query::{
-    process_content_box_request, process_content_boxes_request, process_element_inner_text_query,
-    process_node_geometry_request, process_node_scroll_area_request,
-    process_node_scroll_id_request, process_offset_parent_query, process_resolved_font_style_query,
-    process_resolved_style_request, process_text_index_request, LayoutRPCImpl, LayoutThreadData,
+    process_caret_position_request, process_caret_rect_request, process_content_box_request,
+    process_content_boxes_request, process_element_inner_text_query, process_node_geometry_request,
+    process_node_scroll_area_request, process_node_scroll_id_request, process_offset_parent_query,
+    process_resolved_font_style_query, process_resolved_style_request, process_text_index_request,
+    LayoutRPCImpl, LayoutThreadData,
 };
 use layout::traversal::RecalcStyle;
 use layout::{layout_debug, BoxTree, FragmentTree};
@@ -53,13 +54,17 @@ use script::layout_dom::{ServoLayoutDocument, ServoLayoutElement, ServoLayoutNod
 use script_layout_interface::message::{
     Msg, NodesFromPointQueryType, QueryMsg, ReflowComplete, ReflowGoal, ScriptReflow,
 };
-use script_layout_interface::rpc::{LayoutRPC, OffsetParentResponse, TextIndexResponse};
+use script_layout_interface::rpc::{
+    CaretPositionResponse, CaretRectResponse, LayoutRPC, OffsetParentResponse, TextIndexResponse,
+};
+use script_layout_interface::user_stylesheets::UserStylesheets;
 use script_layout_interface::{Layout, LayoutConfig, LayoutFactory};
 use script_traits::{
     ConstellationControlMsg, DrawAPaintImageResult, IFrameSizeMsg, LayoutControlMsg,
     LayoutMsg as ConstellationMsg, PaintWorkletError, Painter, ScrollState, UntrustedNodeAddress,
-    WebrenderIpcSender, WindowSizeData, WindowSizeType,
+    UserStylesheet, WebrenderIpcSender, WindowSizeData, WindowSizeType,
 };
+use servo_allocator::Partition;
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
 use servo_config::opts::{self, DebugOptions};
@@ -162,6 +167,12 @@ pub struct LayoutThread {
     /// The sizes of all iframes encountered during the last layout operation.
     last_iframe_sizes: RefCell<FnvHashMap<BrowsingContextId, Size2D<f32, CSSPixel>>>,
 
+    /// The user stylesheets injected by the embedder, in the order they were added.
+    user_stylesheets: UserStylesheets,
+
+    /// Whether to paint with the palette of the forced colors mode of the system.
+    forced_colors: bool,
+
     /// Debug options, copied from configuration to this `LayoutThread` in order
     /// to avoid having to constantly access the thread-safe global options.
     debug: DebugOptions,
@@ -183,6 +194,8 @@ impl LayoutFactory for LayoutFactoryImpl {
             config.webrender_api_sender,
             config.paint_time_metrics,
             config.window_size,
+            config.user_stylesheets,
+            config.forced_colors,
         ))
     }
 }
@@ -354,6 +367,8 @@ impl LayoutThread {
         webrender_api_sender: WebrenderIpcSender,
         paint_time_metrics: PaintTimeMetrics,
         window_size: WindowSizeData,
+        user_stylesheets: Vec<UserStylesheet>,
+        forced_colors: bool,
     ) -> LayoutThread {
         // Let webrender know about this pipeline by sending an empty display list.
         webrender_api_sender.send_initial_transaction(id.to_webrender());
@@ -411,6 +426,8 @@ impl LayoutThread {
                 offset_parent_response: OffsetParentResponse::empty(),
                 scroll_offsets: HashMap::new(),
                 text_index_response: TextIndexResponse(None),
+                caret_rect_response: CaretRectResponse(None),
+                caret_position_response: CaretPositionResponse(None),
                 nodes_from_point_response: vec![],
                 element_inner_text_response: String::new(),
                 inner_window_dimensions_response: None,
@@ -418,6 +435,8 @@ impl LayoutThread {
             webrender_image_cache: Default::default(),
             paint_time_metrics: paint_time_metrics,
             last_iframe_sizes: Default::default(),
+            user_stylesheets: UserStylesheets::new(&user_stylesheets),
+            forced_colors,
             debug: opts::get().debug.clone(),
         }
     }
@@ -457,11 +476,13 @@ impl LayoutThread {
             webrender_image_cache: self.webrender_image_cache.clone(),
             pending_images: Mutex::new(vec![]),
             use_rayon,
+            forced_colors: self.forced_colors,
         }
     }
 
     /// Receives and dispatches messages from the script and constellation threads
     fn handle_request<'a, 'b>(&mut self, request: Request) {
+        let _partition = servo_allocator::enter_partition(Partition::Layout);
         let rw_data = self.rw_data.clone();
         let mut possibly_locked_rw_data = Some(rw_data.lock().unwrap());
         let mut rw_data = RwData {
@@ -518,7 +539,25 @@ impl LayoutThread {
                 self.stylist
                     .remove_stylesheet(DocumentStyleSheet(stylesheet.clone()), &guard);
             },
+            Msg::AddUserStylesheet(stylesheet) => {
+                let stylesheet = self.user_stylesheets.add(&stylesheet);
+                // Before the first reflow, user stylesheets are added along with the UA ones.
+                if !self.first_reflow.get() {
+                    let guard = GLOBAL_STYLE_DATA.shared_lock.read();
+                    self.stylist.append_stylesheet(stylesheet.clone(), &guard);
+                    self.handle_add_stylesheet(&stylesheet.0, &guard);
+                }
+            },
+            Msg::RemoveUserStylesheet(id) => {
+                if let Some(stylesheet) = self.user_stylesheets.remove(id) {
+                    if !self.first_reflow.get() {
+                        let guard = GLOBAL_STYLE_DATA.shared_lock.read();
+                        self.stylist.remove_stylesheet(stylesheet, &guard);
+                    }
+                }
+            },
             Msg::SetQuirksMode(mode) => self.handle_set_quirks_mode(mode),
+            Msg::SetForcedColors(forced_colors) => self.forced_colors = forced_colors,
             Msg::GetRPC(response_chan) => {
                 response_chan
                     .send(Box::new(LayoutRPCImpl(self.rw_data.clone())) as Box<dyn LayoutRPC + Send>)
@@ -643,6 +682,12 @@ impl LayoutThread {
                         &QueryMsg::TextIndexQuery(..) => {
                             rw_data.text_index_response = TextIndexResponse(None);
                         },
+                        &QueryMsg::CaretRectQuery(..) => {
+                            rw_data.caret_rect_response = CaretRectResponse(None);
+                        },
+                        &QueryMsg::CaretPositionFromPointQuery(_) => {
+                            rw_data.caret_position_response = CaretPositionResponse(None);
+                        },
                         &QueryMsg::ElementInnerTextQuery(_) => {
                             rw_data.element_inner_text_response = String::new();
                         },
@@ -702,6 +747,12 @@ impl LayoutThread {
                 self.handle_add_stylesheet(&stylesheet.0, &ua_or_user_guard);
             }
 
+            for stylesheet in self.user_stylesheets.iter() {
+                self.stylist
+                    .append_stylesheet(stylesheet.clone(), &ua_or_user_guard);
+                self.handle_add_stylesheet(&stylesheet.0, &ua_or_user_guard);
+            }
+
             if self.stylist.quirks_mode() != QuirksMode::NoQuirks {
                 self.stylist.append_stylesheet(
                     ua_stylesheets.quirks_mode_stylesheet.clone(),
@@ -767,6 +818,7 @@ impl LayoutThread {
         self.stylist.flush(&guards, Some(root_element), Some(&map));
 
         let rayon_pool = STYLE_THREAD_POOL.lock().unwrap();
+        let num_threads = rayon_pool.num_threads.unwrap_or(1);
         let rayon_pool = rayon_pool.pool();
         let rayon_pool = rayon_pool.as_ref();
 
@@ -812,6 +864,17 @@ impl LayoutThread {
                 build_box_tree()
             };
 
+            // The text is shaped as the box tree is built, by all the threads of the pool.
+            let text_shaping_time =
+                font::get_and_reset_text_shaping_performance_counter() / num_threads;
+            profile_time::send_profile_data(
+                profile_time::ProfilerCategory::LayoutTextShaping,
+                self.profiler_metadata(),
+                &self.time_profiler_chan,
+                0,
+                text_shaping_time as u64,
+            );
+
             let viewport_size = Size2D::new(
                 self.viewport_size.width.to_f32_px(),
                 self.viewport_size.height.to_f32_px(),
@@ -827,6 +890,17 @@ impl LayoutThread {
             } else {
                 run_layout()
             });
+            data.result
+                .borrow_mut()
+                .as_mut()
+                .unwrap()
+                .content_visibility_auto_nodes = Some(
+                fragment_tree
+                    .nodes_with_content_visibility_auto()
+                    .into_iter()
+                    .map(Into::into)
+                    .collect(),
+            );
             *self.fragment_tree.borrow_mut() = Some(fragment_tree);
         }
 
@@ -901,6 +975,12 @@ impl LayoutThread {
                     );
                     rw_data.text_index_response = process_text_index_request(node, point_in_node);
                 },
+                &QueryMsg::CaretRectQuery(node, offset) => {
+                    rw_data.caret_rect_response = process_caret_rect_request(node, offset);
+                },
+                &QueryMsg::CaretPositionFromPointQuery(client_point) => {
+                    rw_data.caret_position_response = process_caret_position_request(client_point);
+                },
                 &QueryMsg::ClientRectQuery(node) => {
                     rw_data.client_rect_response =
                         process_node_geometry_request(node, self.fragment_tree.borrow().clone());
@@ -1233,6 +1313,11 @@ fn get_ua_stylesheets() -> Result<UserAgentStylesheets, &'static str> {
             "servo.css",
             &resources::read_bytes(Resource::ServoCSS),
         )?,
+        parse_ua_stylesheet(
+            &shared_lock,
+            "form-controls.css",
+            &resources::read_bytes(Resource::FormControlsCSS),
+        )?,
         parse_ua_stylesheet(
             &shared_lock,
             "presentational-hints.css",
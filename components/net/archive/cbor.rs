@@ -0,0 +1,91 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! A reader for the subset of [CBOR](https://www.rfc-editor.org/rfc/rfc8949) used by
+//! Web Bundles: unsigned integers, byte and text strings, arrays and maps, all of
+//! definite length.
+
+use std::convert::{TryFrom, TryInto};
+
+const UNSIGNED_INTEGER: u8 = 0;
+const BYTE_STRING: u8 = 2;
+const TEXT_STRING: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+
+pub struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(data: &'a [u8]) -> Reader<'a> {
+        Reader { data, position: 0 }
+    }
+
+    /// Read the next `length` bytes as they are.
+    pub fn raw(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|&end| end <= self.data.len())
+            .ok_or("Unexpected end of CBOR data")?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Read the header of the next item, returning its major type and argument.
+    fn header(&mut self) -> Result<(u8, u64), String> {
+        let initial = self.raw(1)?[0];
+        let major_type = initial >> 5;
+        let argument = match initial & 0x1f {
+            value @ 0..=23 => value as u64,
+            24 => self.raw(1)?[0] as u64,
+            25 => u16::from_be_bytes(self.raw(2)?.try_into().unwrap()) as u64,
+            26 => u32::from_be_bytes(self.raw(4)?.try_into().unwrap()) as u64,
+            27 => u64::from_be_bytes(self.raw(8)?.try_into().unwrap()),
+            _ => return Err("Unsupported CBOR item".into()),
+        };
+        Ok((major_type, argument))
+    }
+
+    fn expect(&mut self, expected_type: u8) -> Result<u64, String> {
+        match self.header()? {
+            (major_type, argument) if major_type == expected_type => Ok(argument),
+            (major_type, _) => Err(format!(
+                "Expected CBOR major type {}, found {}",
+                expected_type, major_type
+            )),
+        }
+    }
+
+    fn length(&mut self, expected_type: u8) -> Result<usize, String> {
+        usize::try_from(self.expect(expected_type)?).map_err(|_| "CBOR length too large".into())
+    }
+
+    pub fn unsigned(&mut self) -> Result<u64, String> {
+        self.expect(UNSIGNED_INTEGER)
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8], String> {
+        let length = self.length(BYTE_STRING)?;
+        self.raw(length)
+    }
+
+    pub fn text(&mut self) -> Result<&'a str, String> {
+        let length = self.length(TEXT_STRING)?;
+        std::str::from_utf8(self.raw(length)?).map_err(|_| "Invalid UTF-8 in CBOR text".into())
+    }
+
+    /// Read the header of an array, returning its number of items.
+    pub fn array(&mut self) -> Result<usize, String> {
+        self.length(ARRAY)
+    }
+
+    /// Read the header of a map, returning its number of entries.
+    pub fn map(&mut self) -> Result<usize, String> {
+        self.length(MAP)
+    }
+}
@@ -0,0 +1,154 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The archives opened by the user, which answer the requests for the URLs they contain
+//! in place of the network.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use http::{HeaderMap, Method, StatusCode};
+use log::debug;
+use net_traits::request::Request;
+use net_traits::response::{HttpsState, Response, ResponseBody};
+use servo_url::ServoUrl;
+
+use crate::archive::{warc, web_bundle};
+use crate::fetch::methods::FetchContext;
+
+/// A response stored in an archive.
+#[derive(Clone, Debug)]
+pub struct ArchivedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Vec<u8>,
+}
+
+/// The responses of an archive, keyed by their URL without its fragment.
+#[derive(Debug, Default)]
+pub struct Archive {
+    /// The URL to start browsing the archive at.
+    pub primary_url: Option<ServoUrl>,
+    pub responses: HashMap<ServoUrl, ArchivedResponse>,
+}
+
+impl Archive {
+    /// Parse the archive at `path`, whose format is guessed from its extension.
+    pub fn open(path: &Path) -> Result<Archive, String> {
+        let data = fs::read(path).map_err(|error| error.to_string())?;
+        match ArchiveFormat::of(path) {
+            Some(ArchiveFormat::WebBundle) => web_bundle::parse(&data),
+            Some(ArchiveFormat::Warc) => warc::parse(&data),
+            Some(ArchiveFormat::CompressedWarc) => warc::parse_compressed(&data),
+            None => Err("Not an archive".into()),
+        }
+    }
+
+    pub fn get(&self, url: &ServoUrl) -> Option<&ArchivedResponse> {
+        self.responses.get(&without_fragment(url))
+    }
+
+    pub fn insert(&mut self, url: &ServoUrl, response: ArchivedResponse) {
+        self.responses.insert(without_fragment(url), response);
+    }
+}
+
+enum ArchiveFormat {
+    WebBundle,
+    Warc,
+    CompressedWarc,
+}
+
+impl ArchiveFormat {
+    fn of(path: &Path) -> Option<ArchiveFormat> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".wbn") {
+            Some(ArchiveFormat::WebBundle)
+        } else if name.ends_with(".warc") {
+            Some(ArchiveFormat::Warc)
+        } else if name.ends_with(".warc.gz") {
+            Some(ArchiveFormat::CompressedWarc)
+        } else {
+            None
+        }
+    }
+}
+
+/// Whether the file at `path` is opened as an archive when navigated to.
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveFormat::of(path).is_some()
+}
+
+fn without_fragment(url: &ServoUrl) -> ServoUrl {
+    let mut url = url.clone();
+    url.set_fragment(None);
+    url
+}
+
+/// The archives opened so far. An archive stays open for as long as the resource manager
+/// lives, and opening it again reloads it from disk.
+#[derive(Default)]
+pub struct ArchiveStore {
+    /// The archives, from the least to the most recently opened one.
+    archives: RwLock<Vec<(PathBuf, Archive)>>,
+}
+
+impl ArchiveStore {
+    pub fn new() -> ArchiveStore {
+        ArchiveStore::default()
+    }
+
+    /// Open the archive at `path`, so that the requests for the URLs it contains are
+    /// answered from it. Returns the URL to start browsing the archive at.
+    pub fn open(&self, path: &Path) -> Result<ServoUrl, String> {
+        let archive = Archive::open(path)?;
+        let primary_url = archive
+            .primary_url
+            .clone()
+            .ok_or("The archive contains no response")?;
+        debug!(
+            "Opened {} responses from {}",
+            archive.responses.len(),
+            path.display()
+        );
+        let mut archives = self.archives.write().unwrap();
+        archives.retain(|(archive_path, _)| archive_path != path);
+        archives.push((path.to_owned(), archive));
+        Ok(primary_url)
+    }
+
+    /// The response to `request` from the most recently opened archive containing its
+    /// URL, if any.
+    pub fn response(&self, request: &Request, context: &FetchContext) -> Option<Response> {
+        if request.method != Method::GET && request.method != Method::HEAD {
+            return None;
+        }
+        let archives = self.archives.read().unwrap();
+        if archives.is_empty() {
+            return None;
+        }
+        let url = request.current_url();
+        let archived = archives
+            .iter()
+            .rev()
+            .find_map(|(_, archive)| archive.get(&url))?;
+
+        let timing = context.timing.lock().unwrap().clone();
+        let mut response = Response::new(url.clone(), timing);
+        let reason = archived.status.canonical_reason().unwrap_or("");
+        response.status = Some((archived.status, reason.into()));
+        response.raw_status = Some((archived.status.as_u16(), reason.into()));
+        response.headers = archived.headers.clone();
+        response.referrer = request.referrer.to_url().cloned();
+        response.referrer_policy = request.referrer_policy.clone();
+        response.https_state = match url.scheme() {
+            "https" => HttpsState::Modern,
+            _ => HttpsState::None,
+        };
+        *response.body.lock().unwrap() = ResponseBody::Done(archived.body.clone());
+        Some(response)
+    }
+}
@@ -0,0 +1,225 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Reading the HTTP responses of [WARC](https://iipc.github.io/warc-specifications/)
+//! files, as written by web crawlers.
+
+use std::io::Read;
+use std::str;
+
+use brotli::Decompressor;
+use flate2::read::{DeflateDecoder, GzDecoder, MultiGzDecoder, ZlibDecoder};
+use http::header::{self, HeaderName, HeaderValue};
+use http::{HeaderMap, StatusCode};
+use log::warn;
+use servo_url::ServoUrl;
+
+use crate::archive::store::{Archive, ArchivedResponse};
+
+/// Parse a WARC file whose records are compressed with gzip, as `.warc.gz` files are.
+pub fn parse_compressed(data: &[u8]) -> Result<Archive, String> {
+    let mut decompressed = vec![];
+    MultiGzDecoder::new(data)
+        .read_to_end(&mut decompressed)
+        .map_err(|error| error.to_string())?;
+    parse(&decompressed)
+}
+
+pub fn parse(mut data: &[u8]) -> Result<Archive, String> {
+    let mut archive = Archive::default();
+    let mut first_url = None;
+    let mut first_document_url = None;
+    loop {
+        while let Some(rest) = data
+            .strip_prefix(b"\r\n")
+            .or_else(|| data.strip_prefix(b"\n"))
+        {
+            data = rest;
+        }
+        if data.is_empty() {
+            break;
+        }
+
+        let (head, rest) = split_head(data).ok_or("Truncated WARC record")?;
+        let mut lines = head.split(|&byte| byte == b'\n').map(trim_line);
+        if !lines
+            .next()
+            .map_or(false, |line| line.starts_with(b"WARC/"))
+        {
+            return Err("Not a WARC record".into());
+        }
+        let fields = lines.filter_map(split_field).collect::<Vec<_>>();
+        let field = |name: &str| {
+            fields
+                .iter()
+                .find(|(field_name, _)| field_name.eq_ignore_ascii_case(name))
+                .map(|(_, value)| *value)
+        };
+
+        let length = field("Content-Length")
+            .and_then(|length| length.parse::<usize>().ok())
+            .ok_or("WARC record without a length")?;
+        let block = rest.get(..length).ok_or("Truncated WARC record")?;
+        data = &rest[length..];
+
+        if field("WARC-Type") != Some("response") ||
+            !field("Content-Type").map_or(false, |content_type| {
+                content_type.starts_with("application/http")
+            })
+        {
+            continue;
+        }
+        // Older versions of the format enclose the URI in angle brackets.
+        let url = match field("WARC-Target-URI")
+            .map(|uri| uri.trim_start_matches('<').trim_end_matches('>'))
+            .and_then(|uri| ServoUrl::parse(uri).ok())
+        {
+            Some(url) => url,
+            None => continue,
+        };
+        let response = match parse_http_response(block) {
+            Ok(response) => response,
+            Err(error) => {
+                warn!("Skipping the WARC response for {} ({})", url, error);
+                continue;
+            },
+        };
+
+        if first_url.is_none() {
+            first_url = Some(url.clone());
+        }
+        if first_document_url.is_none() && is_document(&response) {
+            first_document_url = Some(url.clone());
+        }
+        archive.insert(&url, response);
+    }
+    archive.primary_url = first_document_url.or(first_url);
+    Ok(archive)
+}
+
+/// Whether `response` is a successful HTML one, which the archive is likely meant to be
+/// browsed from if it is the first one.
+fn is_document(response: &ArchivedResponse) -> bool {
+    response.status.is_success() &&
+        response
+            .headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| value.starts_with("text/html"))
+}
+
+/// Split `data` at the empty line ending the head of a record or message.
+fn split_head(data: &[u8]) -> Option<(&[u8], &[u8])> {
+    let end = data.windows(4).position(|window| window == b"\r\n\r\n")?;
+    Some((&data[..end], &data[end + 4..]))
+}
+
+fn trim_line(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+fn split_field(line: &[u8]) -> Option<(&str, &str)> {
+    let (name, value) = str::from_utf8(line).ok()?.split_once(':')?;
+    Some((name.trim(), value.trim()))
+}
+
+/// Parse the HTTP/1.x response stored in a response record, undoing its transfer and
+/// content encodings, as the archived response is used as it was received.
+fn parse_http_response(data: &[u8]) -> Result<ArchivedResponse, String> {
+    let (head, body) = split_head(data).ok_or("Truncated HTTP response")?;
+    let mut lines = head.split(|&byte| byte == b'\n').map(trim_line);
+    let status = lines
+        .next()
+        .and_then(|line| line.split(|&byte| byte == b' ').nth(1))
+        .and_then(|status| StatusCode::from_bytes(status).ok())
+        .ok_or("Invalid HTTP status line")?;
+
+    let mut headers = HeaderMap::new();
+    for line in lines {
+        let colon = match line.iter().position(|&byte| byte == b':') {
+            Some(colon) => colon,
+            None => continue,
+        };
+        let name = HeaderName::from_bytes(&line[..colon]);
+        let value = HeaderValue::from_bytes(trim_whitespace(&line[colon + 1..]));
+        if let (Ok(name), Ok(value)) = (name, value) {
+            headers.append(name, value);
+        }
+    }
+
+    let mut body = body.to_vec();
+    if header_is(&headers, header::TRANSFER_ENCODING, "chunked") {
+        body = dechunk(&body).ok_or("Invalid chunked body")?;
+        headers.remove(header::TRANSFER_ENCODING);
+        headers.remove(header::CONTENT_LENGTH);
+    }
+    if let Some(encoding) = headers
+        .get(header::CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.trim().to_ascii_lowercase())
+    {
+        body = decode(&body, &encoding)?;
+        headers.remove(header::CONTENT_ENCODING);
+        headers.remove(header::CONTENT_LENGTH);
+    }
+    Ok(ArchivedResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+fn trim_whitespace(bytes: &[u8]) -> &[u8] {
+    let start = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    let end = bytes
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(start, |end| end + 1);
+    &bytes[start..end]
+}
+
+fn header_is(headers: &HeaderMap, name: HeaderName, expected: &str) -> bool {
+    headers
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .map_or(false, |value| value.trim().eq_ignore_ascii_case(expected))
+}
+
+/// Decode a body with the `chunked` transfer encoding.
+fn dechunk(mut data: &[u8]) -> Option<Vec<u8>> {
+    let mut body = vec![];
+    loop {
+        let line_end = data.windows(2).position(|window| window == b"\r\n")?;
+        let size = str::from_utf8(&data[..line_end]).ok()?;
+        // Chunk extensions follow the size after a semicolon.
+        let size = size.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        data = &data[line_end + 2..];
+        if size == 0 {
+            return Some(body);
+        }
+        body.extend_from_slice(data.get(..size)?);
+        data = data.get(size..)?.strip_prefix(b"\r\n")?;
+    }
+}
+
+fn decode(body: &[u8], encoding: &str) -> Result<Vec<u8>, String> {
+    let mut decoded = vec![];
+    let result = match encoding {
+        "identity" => return Ok(body.to_vec()),
+        "gzip" | "x-gzip" => GzDecoder::new(body).read_to_end(&mut decoded),
+        // Servers disagree on whether `deflate` bodies have a zlib header.
+        "deflate" if body.first().map_or(false, |byte| byte & 0x0f == 8) => {
+            ZlibDecoder::new(body).read_to_end(&mut decoded)
+        },
+        "deflate" => DeflateDecoder::new(body).read_to_end(&mut decoded),
+        "br" => Decompressor::new(body, 4096).read_to_end(&mut decoded),
+        _ => return Err(format!("Unsupported content encoding {}", encoding)),
+    };
+    result.map_err(|error| error.to_string())?;
+    Ok(decoded)
+}
@@ -0,0 +1,112 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Reading [Web Bundles](https://www.ietf.org/archive/id/draft-ietf-wpack-bundled-responses-01.html),
+//! in their `b2` version.
+
+use http::header::{HeaderName, HeaderValue};
+use http::{HeaderMap, StatusCode};
+use servo_url::ServoUrl;
+
+use crate::archive::cbor::Reader;
+use crate::archive::store::{Archive, ArchivedResponse};
+
+/// "🌐📦" in UTF-8.
+const MAGIC: &[u8] = b"\xF0\x9F\x8C\x90\xF0\x9F\x93\xA6";
+const VERSION: &[u8] = b"b2\0\0";
+
+pub fn parse(data: &[u8]) -> Result<Archive, String> {
+    let mut reader = Reader::new(data);
+    if reader.array()? != 5 {
+        return Err("Not a Web Bundle".into());
+    }
+    if reader.bytes()? != MAGIC {
+        return Err("Not a Web Bundle".into());
+    }
+    if reader.bytes()? != VERSION {
+        return Err("Unsupported Web Bundle version".into());
+    }
+
+    let mut section_lengths = Reader::new(reader.bytes()?);
+    let section_count = section_lengths.array()? / 2;
+    if reader.array()? != section_count {
+        return Err("Mismatched Web Bundle section count".into());
+    }
+    let mut index = None;
+    let mut responses = None;
+    let mut primary_url = None;
+    for _ in 0..section_count {
+        let name = section_lengths.text()?;
+        let length = section_lengths.unsigned()? as usize;
+        let section = reader.raw(length)?;
+        match name {
+            "index" => index = Some(parse_index(section)?),
+            "responses" => responses = Some(section),
+            "primary" => primary_url = Some(parse_url(Reader::new(section).text()?)?),
+            _ => {},
+        }
+    }
+
+    let index = index.ok_or("Web Bundle without an index")?;
+    let responses = responses.ok_or("Web Bundle without responses")?;
+    let mut archive = Archive::default();
+    for (url, offset, length) in &index {
+        let response = responses
+            .get(*offset..offset.saturating_add(*length))
+            .ok_or("Web Bundle response out of bounds")?;
+        archive.insert(url, parse_response(response)?);
+    }
+    archive.primary_url = primary_url.or_else(|| index.first().map(|(url, ..)| url.clone()));
+    Ok(archive)
+}
+
+fn parse_url(url: &str) -> Result<ServoUrl, String> {
+    ServoUrl::parse(url).map_err(|error| format!("Invalid URL {:?} ({})", url, error))
+}
+
+/// The URL, offset and length of every response in the responses section, in the order
+/// they appear in the index.
+fn parse_index(section: &[u8]) -> Result<Vec<(ServoUrl, usize, usize)>, String> {
+    let mut reader = Reader::new(section);
+    let count = reader.map()?;
+    let mut index = Vec::with_capacity(count);
+    for _ in 0..count {
+        let url = parse_url(reader.text()?)?;
+        if reader.array()? != 2 {
+            return Err("Unsupported Web Bundle index entry".into());
+        }
+        let offset = reader.unsigned()? as usize;
+        let length = reader.unsigned()? as usize;
+        index.push((url, offset, length));
+    }
+    Ok(index)
+}
+
+fn parse_response(data: &[u8]) -> Result<ArchivedResponse, String> {
+    let mut reader = Reader::new(data);
+    if reader.array()? != 2 {
+        return Err("Invalid Web Bundle response".into());
+    }
+    let mut headers_reader = Reader::new(reader.bytes()?);
+    let mut status = None;
+    let mut headers = HeaderMap::new();
+    for _ in 0..headers_reader.map()? {
+        let name = headers_reader.bytes()?;
+        let value = headers_reader.bytes()?;
+        if name == b":status" {
+            status = std::str::from_utf8(value)
+                .ok()
+                .and_then(|status| status.parse::<StatusCode>().ok());
+            continue;
+        }
+        let name = HeaderName::from_bytes(name).map_err(|error| error.to_string())?;
+        let value = HeaderValue::from_bytes(value).map_err(|error| error.to_string())?;
+        headers.append(name, value);
+    }
+    Ok(ArchivedResponse {
+        status: status.ok_or("Web Bundle response without a status")?,
+        headers,
+        body: reader.bytes()?.to_vec(),
+    })
+}
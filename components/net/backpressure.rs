@@ -0,0 +1,58 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The pipelines whose event loop has more tasks queued than it keeps up with, and which
+//! get the chunks of their responses more slowly until it catches up.
+
+use std::collections::HashSet;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use msg::constellation_msg::PipelineId;
+
+/// How often a held back chunk checks whether its event loop caught up.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The longest a chunk is held back, so that an event loop which never says it caught up
+/// does not stall its fetches.
+const MAX_HOLD_BACK: Duration = Duration::from_secs(1);
+
+#[derive(Clone, Default)]
+pub struct Backpressure {
+    saturated: Arc<RwLock<HashSet<PipelineId>>>,
+}
+
+impl Backpressure {
+    pub fn new() -> Backpressure {
+        Backpressure::default()
+    }
+
+    /// Start or stop holding back the chunks of the responses to the requests of
+    /// `pipelines`.
+    pub fn set(&self, pipelines: Vec<PipelineId>, saturated: bool) {
+        let mut saturated_pipelines = self.saturated.write().unwrap();
+        for pipeline in pipelines {
+            if saturated {
+                saturated_pipelines.insert(pipeline);
+            } else {
+                saturated_pipelines.remove(&pipeline);
+            }
+        }
+    }
+
+    /// Whether the event loop of `pipeline` is saturated.
+    pub fn is_saturated(&self, pipeline: Option<PipelineId>) -> bool {
+        pipeline.map_or(false, |pipeline| {
+            self.saturated.read().unwrap().contains(&pipeline)
+        })
+    }
+
+    /// Wait for the event loop of `pipeline` to catch up before delivering it a chunk.
+    pub async fn wait(&self, pipeline: Option<PipelineId>) {
+        let start = Instant::now();
+        while self.is_saturated(pipeline) && start.elapsed() < MAX_HOLD_BACK {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
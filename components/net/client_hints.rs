@@ -0,0 +1,140 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The user-agent client hints sent with the requests: the ones every secure origin gets,
+//! the ones an origin asked for with `Accept-CH`, and what the embedder or WebDriver chose to
+//! tell instead of the defaults for some pipelines.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use embedder_traits::UserAgentOverride;
+use http::{HeaderMap, HeaderValue};
+use msg::constellation_msg::PipelineId;
+use net_traits::client_hints::{ClientHint, UserAgentMetadata};
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+/// The client hints each origin asked for with the `Accept-CH` header of its documents.
+/// <https://html.spec.whatwg.org/multipage/#concept-client-hints-set>
+#[derive(Default)]
+pub struct AcceptChCache {
+    origins: RwLock<HashMap<ImmutableOrigin, Vec<ClientHint>>>,
+}
+
+impl AcceptChCache {
+    pub fn new() -> AcceptChCache {
+        AcceptChCache::default()
+    }
+
+    /// Remember the hints the `Accept-CH` header of a document of `url` asks for, replacing
+    /// the ones its origin asked for before. Only secure origins can ask for hints.
+    /// <https://wicg.github.io/client-hints-infrastructure/#accept-ch-cache-definition>
+    pub fn update_from_response(&self, url: &ServoUrl, headers: &HeaderMap) {
+        if !url.is_potentially_trustworthy() {
+            return;
+        }
+        let accept_ch: Vec<_> = headers.get_all("accept-ch").iter().collect();
+        if accept_ch.is_empty() {
+            return;
+        }
+        let hints: Vec<ClientHint> = accept_ch
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .filter_map(ClientHint::from_header_name)
+            .collect();
+        let mut origins = self.origins.write().unwrap();
+        if hints.is_empty() {
+            origins.remove(&url.origin());
+        } else {
+            origins.insert(url.origin(), hints);
+        }
+    }
+
+    /// The hints `origin` asked for.
+    pub fn hints(&self, origin: &ImmutableOrigin) -> Vec<ClientHint> {
+        self.origins
+            .read()
+            .unwrap()
+            .get(origin)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// What the requests of some pipelines tell about the user agent instead of the defaults.
+#[derive(Clone, Default)]
+pub struct UserAgentOverrides {
+    pipelines: Arc<RwLock<HashMap<PipelineId, UserAgentOverride>>>,
+}
+
+impl UserAgentOverrides {
+    pub fn new() -> UserAgentOverrides {
+        UserAgentOverrides::default()
+    }
+
+    /// Use `user_agent_override` for the requests of `pipelines`, or stop overriding the
+    /// defaults for them.
+    pub fn set(&self, pipelines: Vec<PipelineId>, user_agent_override: Option<UserAgentOverride>) {
+        let mut overrides = self.pipelines.write().unwrap();
+        for pipeline in pipelines {
+            match user_agent_override {
+                Some(ref user_agent_override) => {
+                    overrides.insert(pipeline, user_agent_override.clone())
+                },
+                None => overrides.remove(&pipeline),
+            };
+        }
+    }
+
+    /// The override for the requests of `pipeline`, if any.
+    pub fn get(&self, pipeline: Option<PipelineId>) -> Option<UserAgentOverride> {
+        let pipeline = pipeline?;
+        self.pipelines.read().unwrap().get(&pipeline).cloned()
+    }
+}
+
+/// Add the client hint headers to a request to `url`: the low-entropy ones, and if
+/// `high_entropy_values` are allowed, the ones `hints_origin` asked for if it is the origin of
+/// `url`. Hints are only sent to secure origins, and never replace the headers set by the
+/// request's initiator.
+/// <https://wicg.github.io/client-hints-infrastructure/#abstract-opdef-append-client-hints-to-request>
+pub fn append_client_hints(
+    headers: &mut HeaderMap,
+    url: &ServoUrl,
+    hints_origin: Option<&ImmutableOrigin>,
+    accept_ch_cache: &AcceptChCache,
+    metadata: &UserAgentMetadata,
+    high_entropy_values: bool,
+) {
+    if !url.is_potentially_trustworthy() {
+        return;
+    }
+
+    let mut hints: Vec<ClientHint> = ClientHint::ALL
+        .iter()
+        .copied()
+        .filter(ClientHint::is_low_entropy)
+        .collect();
+    // Third parties would need to be delegated the hints with a permissions policy, which we
+    // do not support.
+    let origin = url.origin();
+    if high_entropy_values && hints_origin.map_or(false, |hints_origin| *hints_origin == origin) {
+        hints.extend(
+            accept_ch_cache
+                .hints(&origin)
+                .into_iter()
+                .filter(|hint| !hint.is_low_entropy()),
+        );
+    }
+
+    for hint in hints {
+        if headers.contains_key(hint.header_name()) {
+            continue;
+        }
+        if let Ok(value) = HeaderValue::from_str(&metadata.header_value(hint)) {
+            headers.insert(hint.header_name(), value);
+        }
+    }
+}
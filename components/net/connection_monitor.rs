@@ -0,0 +1,310 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Keeping track of the connections in the pool of the HTTP client, to report on them and
+//! to close them when the network changes, and of the network changes themselves.
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use embedder_traits::{ConnectionPoolReport, NetworkChange, OpenConnection, OriginConnections};
+use futures::task::{AtomicWaker, Context, Poll};
+use futures::Future;
+use http::uri::Uri as Destination;
+use hyper::client::connect::{Connected, Connection};
+use hyper::service::Service;
+use servo_config::pref;
+use servo_url::ServoUrl;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The requests sent to an origin, and the connections opened to it.
+#[derive(Default)]
+struct OriginTotals {
+    opened: u64,
+    requests: u64,
+    http2_requests: u64,
+}
+
+/// What is shared between a connection and the monitor.
+struct ConnectionActivity {
+    origin: String,
+    http2: bool,
+    opened: Instant,
+    last_active: Mutex<Instant>,
+    /// Whether the connection must be closed rather than used again.
+    evicted: AtomicBool,
+    /// Woken to let the connection notice it was evicted, even while idle in the pool.
+    waker: AtomicWaker,
+}
+
+#[derive(Default)]
+struct ConnectionMonitorInternal {
+    next_id: u64,
+    connections: HashMap<u64, Arc<ConnectionActivity>>,
+    origins: HashMap<String, OriginTotals>,
+    /// The number of network changes so far.
+    network_changes: u64,
+    offline: bool,
+}
+
+/// The connections of the HTTP clients, which the [MonitoredConnector] of each client
+/// registers here as they are opened.
+#[derive(Clone)]
+pub struct ConnectionMonitor {
+    internal: Arc<Mutex<ConnectionMonitorInternal>>,
+    idle_timeout: Option<Duration>,
+    max_idle_per_host: usize,
+}
+
+impl ConnectionMonitor {
+    /// Create a monitor for clients whose pool is tuned with the `network.http_pool` prefs.
+    pub fn new() -> ConnectionMonitor {
+        let idle_timeout = match pref!(network.http_pool.idle_timeout) {
+            seconds if seconds > 0 => Some(Duration::from_secs(seconds as u64)),
+            _ => None,
+        };
+        let max_idle_per_host = pref!(network.http_pool.max_idle_per_host).max(0);
+        ConnectionMonitor {
+            internal: Default::default(),
+            idle_timeout,
+            max_idle_per_host: usize::try_from(max_idle_per_host).unwrap_or(usize::MAX),
+        }
+    }
+
+    /// How long a connection is kept in the pool while no request uses it.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout
+    }
+
+    /// The most connections to an origin kept in the pool while no request uses them.
+    pub fn max_idle_per_host(&self) -> usize {
+        self.max_idle_per_host
+    }
+
+    /// Count a request sent to the origin of `url`, and answered over HTTP/2 if the flag is set.
+    pub fn record_request(&self, url: &ServoUrl, http2: bool) {
+        let mut internal = self.internal.lock().unwrap();
+        let totals = internal
+            .origins
+            .entry(url.origin().ascii_serialization())
+            .or_default();
+        totals.requests += 1;
+        if http2 {
+            totals.http2_requests += 1;
+        }
+    }
+
+    /// Record that the connectivity of the device changed, and close every open connection,
+    /// which no longer leads anywhere.
+    pub fn network_changed(&self, change: NetworkChange) {
+        {
+            let mut internal = self.internal.lock().unwrap();
+            internal.network_changes += 1;
+            internal.offline = change == NetworkChange::Down;
+        }
+        self.evict_all();
+    }
+
+    /// The number of network changes so far, to tell whether one happened during a request.
+    pub fn network_changes(&self) -> u64 {
+        self.internal.lock().unwrap().network_changes
+    }
+
+    /// Whether a request failing after `network_changes` network changes likely failed
+    /// because of a later change, and the device is still online to retry it.
+    pub fn failed_by_network_change(&self, network_changes: u64) -> bool {
+        let internal = self.internal.lock().unwrap();
+        internal.network_changes != network_changes && !internal.offline
+    }
+
+    /// Close every open connection, for instance because the network changed and they no
+    /// longer lead anywhere. Those in use fail their requests.
+    pub fn evict_all(&self) {
+        let mut internal = self.internal.lock().unwrap();
+        for (_, activity) in internal.connections.drain() {
+            activity.evicted.store(true, Ordering::SeqCst);
+            activity.waker.wake();
+        }
+    }
+
+    pub fn report(&self) -> ConnectionPoolReport {
+        let internal = self.internal.lock().unwrap();
+        let mut origins: Vec<_> = internal
+            .origins
+            .iter()
+            .map(|(origin, totals)| OriginConnections {
+                origin: origin.clone(),
+                open: internal
+                    .connections
+                    .values()
+                    .filter(|activity| activity.origin == *origin)
+                    .map(|activity| OpenConnection {
+                        http2: activity.http2,
+                        age: activity.opened.elapsed(),
+                        idle: activity.last_active.lock().unwrap().elapsed(),
+                    })
+                    .collect(),
+                opened: totals.opened,
+                requests: totals.requests,
+                http2_requests: totals.http2_requests,
+            })
+            .collect();
+        origins.sort_by(|a, b| a.origin.cmp(&b.origin));
+        ConnectionPoolReport {
+            idle_timeout: self.idle_timeout,
+            max_idle_per_host: self.max_idle_per_host,
+            origins,
+        }
+    }
+
+    fn register<S: Connection>(&self, origin: String, stream: S) -> MonitoredStream<S> {
+        let now = Instant::now();
+        let activity = Arc::new(ConnectionActivity {
+            origin: origin.clone(),
+            http2: stream.connected().is_negotiated_h2(),
+            opened: now,
+            last_active: Mutex::new(now),
+            evicted: AtomicBool::new(false),
+            waker: AtomicWaker::new(),
+        });
+
+        let mut internal = self.internal.lock().unwrap();
+        let id = internal.next_id;
+        internal.next_id += 1;
+        internal.connections.insert(id, activity.clone());
+        internal.origins.entry(origin).or_default().opened += 1;
+        MonitoredStream {
+            inner: stream,
+            id,
+            activity,
+            monitor: self.clone(),
+        }
+    }
+
+    fn unregister(&self, id: u64) {
+        self.internal.lock().unwrap().connections.remove(&id);
+    }
+}
+
+/// A connection registered with a [ConnectionMonitor], until it is dropped.
+pub struct MonitoredStream<S> {
+    inner: S,
+    id: u64,
+    activity: Arc<ConnectionActivity>,
+    monitor: ConnectionMonitor,
+}
+
+impl<S> MonitoredStream<S> {
+    /// Fail with an error once the connection was evicted, so that it is closed.
+    fn check_evicted(&self, cx: &mut Context<'_>) -> Result<(), io::Error> {
+        self.activity.waker.register(cx.waker());
+        if self.activity.evicted.load(Ordering::SeqCst) {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionAborted,
+                "The connection was evicted from the pool",
+            ));
+        }
+        Ok(())
+    }
+
+    fn mark_active(&self) {
+        *self.activity.last_active.lock().unwrap() = Instant::now();
+    }
+}
+
+impl<S> Drop for MonitoredStream<S> {
+    fn drop(&mut self) {
+        self.monitor.unregister(self.id);
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for MonitoredStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        this.check_evicted(cx)?;
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if result.is_ready() {
+            this.mark_active();
+        }
+        result
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for MonitoredStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        this.check_evicted(cx)?;
+        let result = Pin::new(&mut this.inner).poll_write(cx, buf);
+        if result.is_ready() {
+            this.mark_active();
+        }
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+impl<S: Connection> Connection for MonitoredStream<S> {
+    fn connected(&self) -> Connected {
+        self.inner.connected()
+    }
+}
+
+/// A connector registering the connections it opens with a [ConnectionMonitor].
+#[derive(Clone)]
+pub struct MonitoredConnector<C> {
+    inner: C,
+    monitor: ConnectionMonitor,
+}
+
+impl<C> MonitoredConnector<C> {
+    pub fn new(inner: C, monitor: ConnectionMonitor) -> MonitoredConnector<C> {
+        MonitoredConnector { inner, monitor }
+    }
+}
+
+impl<C> Service<Destination> for MonitoredConnector<C>
+where
+    C: Service<Destination>,
+    C::Response: Connection + Send + 'static,
+    C::Future: Send + 'static,
+{
+    type Response = MonitoredStream<C::Response>;
+    type Error = C::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, C::Error>> + Send>>;
+
+    fn call(&mut self, dest: Destination) -> Self::Future {
+        let origin = format!(
+            "{}://{}",
+            dest.scheme_str().unwrap_or_default(),
+            dest.authority().map_or("", |authority| authority.as_str())
+        );
+        let monitor = self.monitor.clone();
+        let connecting = self.inner.call(dest);
+        Box::pin(async move { Ok(monitor.register(origin, connecting.await?)) })
+    }
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+}
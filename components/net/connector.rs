@@ -4,45 +4,247 @@
 
 use std::collections::hash_map::HashMap;
 use std::convert::TryFrom;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use std::{io, mem};
 
+use devtools_traits::ResourceHint;
 use futures::task::{Context, Poll};
-use futures::Future;
+use futures::{future, Future};
 use http::uri::{Authority, Uri as Destination};
+use hyper::client::connect::dns::{GaiResolver, Name};
 use hyper::client::HttpConnector as HyperHttpConnector;
 use hyper::rt::Executor;
 use hyper::service::Service;
 use hyper::{Body, Client};
 use hyper_rustls::HttpsConnector as HyperRustlsHttpsConnector;
-use log::warn;
+use log::{debug, warn};
 use rustls::client::WebPkiVerifier;
 use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use servo_url::ServoUrl;
+use tokio::net::TcpStream;
 
+use crate::connection_monitor::{ConnectionMonitor, MonitoredConnector};
 use crate::hosts::replace_host;
 use crate::http_loader::HANDLE;
 
 pub const BUF_SIZE: usize = 32768;
 
+/// How long the addresses of a host name resolved for a `dns-prefetch` hint are used.
+const PREFETCHED_DNS_LIFETIME: Duration = Duration::from_secs(60);
+
+/// How long a connection opened for a `preconnect` hint is kept waiting for a request,
+/// short enough for servers not to have closed it yet.
+const PRECONNECTION_LIFETIME: Duration = Duration::from_secs(10);
+
+/// The most connections kept waiting for a request at once.
+const MAX_PRECONNECTIONS: usize = 6;
+
+#[derive(Default)]
+struct WarmPoolInternal {
+    /// The addresses of the host names resolved for `dns-prefetch` hints.
+    addresses: HashMap<String, (Instant, Vec<SocketAddr>)>,
+    /// The connections opened for `preconnect` hints and not used yet, by origin.
+    connections: HashMap<String, (Instant, TcpStream)>,
+    /// When the origins were last given a `preconnect` hint.
+    preconnected: HashMap<String, Instant>,
+}
+
+impl WarmPoolInternal {
+    fn remove_expired(&mut self) {
+        let now = Instant::now();
+        self.addresses
+            .retain(|_, (resolved, _)| now - *resolved < PREFETCHED_DNS_LIFETIME);
+        self.connections
+            .retain(|_, (connected, _)| now - *connected < PRECONNECTION_LIFETIME);
+        self.preconnected
+            .retain(|_, preconnected| now - *preconnected < PRECONNECTION_LIFETIME);
+    }
+}
+
+/// The host names resolved and the connections opened ahead of the requests needing them,
+/// for `dns-prefetch` and `preconnect` hints, which the connector uses before going to
+/// the network.
+///
+/// Only the TCP connection is opened ahead, as the TLS handshake is made by the
+/// [Connector] wrapping the [ServoHttpConnector]. Servo never presents client
+/// certificates, so the same connection serves requests with and without credentials.
+#[derive(Clone, Default)]
+pub struct WarmPool(Arc<Mutex<WarmPoolInternal>>);
+
+impl WarmPool {
+    pub fn new() -> WarmPool {
+        WarmPool::default()
+    }
+
+    /// Resolve the host of `url`, for a `dns-prefetch` hint.
+    pub async fn prefetch_dns(&self, url: &ServoUrl) {
+        let host = match url.domain() {
+            Some(domain) => replace_host(domain).into_owned(),
+            None => return,
+        };
+        {
+            let mut internal = self.0.lock().unwrap();
+            internal.remove_expired();
+            if internal.addresses.contains_key(&host) {
+                return;
+            }
+        }
+        self.resolve(host).await;
+    }
+
+    /// Resolve `host` again, replacing the addresses known for it.
+    pub async fn resolve(&self, host: String) {
+        let name = Name::from_str(&host).expect("Host names are always valid");
+        match GaiResolver::new().call(name).await {
+            Ok(addresses) => {
+                debug!("Prefetched the addresses of {}", host);
+                self.0
+                    .lock()
+                    .unwrap()
+                    .addresses
+                    .insert(host, (Instant::now(), addresses.collect()));
+            },
+            Err(error) => debug!("Failed to prefetch the addresses of {} ({})", host, error),
+        }
+    }
+
+    /// Open a connection to the origin of `url`, for a `preconnect` hint.
+    pub async fn preconnect(&self, url: &ServoUrl) {
+        if !matches!(url.scheme(), "http" | "https") {
+            return;
+        }
+        let origin = url.origin().ascii_serialization();
+        {
+            let mut internal = self.0.lock().unwrap();
+            internal.remove_expired();
+            internal.preconnected.insert(origin.clone(), Instant::now());
+            if internal.connections.contains_key(&origin) ||
+                internal.connections.len() >= MAX_PRECONNECTIONS
+            {
+                return;
+            }
+        }
+
+        let dest = match Destination::try_from(origin.as_str()) {
+            Ok(dest) => dest,
+            Err(_) => return,
+        };
+        match ServoHttpConnector::new(self.clone()).call(dest).await {
+            Ok(stream) => {
+                debug!("Preconnected to {}", origin);
+                self.0
+                    .lock()
+                    .unwrap()
+                    .connections
+                    .insert(origin, (Instant::now(), stream));
+            },
+            Err(error) => debug!("Failed to preconnect to {} ({})", origin, error),
+        }
+    }
+
+    /// Forget the addresses and close the connections obtained so far, for instance because
+    /// the network changed and they no longer lead anywhere. Returns the host names whose
+    /// addresses were forgotten.
+    pub fn clear(&self) -> Vec<String> {
+        let internal = mem::take(&mut *self.0.lock().unwrap());
+        internal.addresses.into_keys().collect()
+    }
+
+    /// The hint that warmed up the connections to the origin of `url`, if any did lately.
+    pub fn hint_for(&self, url: &ServoUrl) -> Option<ResourceHint> {
+        let mut internal = self.0.lock().unwrap();
+        internal.remove_expired();
+        if internal
+            .preconnected
+            .contains_key(&url.origin().ascii_serialization())
+        {
+            return Some(ResourceHint::Preconnect);
+        }
+        let host = replace_host(url.domain()?);
+        if internal.addresses.contains_key(&*host) {
+            return Some(ResourceHint::DnsPrefetch);
+        }
+        None
+    }
+
+    fn take_connection(&self, dest: &Destination) -> Option<TcpStream> {
+        let origin = format!("{}://{}", dest.scheme_str()?, dest.authority()?);
+        let mut internal = self.0.lock().unwrap();
+        internal.remove_expired();
+        internal
+            .connections
+            .remove(&origin)
+            .map(|(_, stream)| stream)
+    }
+
+    fn addresses(&self, host: &str) -> Option<Vec<SocketAddr>> {
+        let internal = self.0.lock().unwrap();
+        let (resolved, addresses) = internal.addresses.get(host)?;
+        if resolved.elapsed() >= PREFETCHED_DNS_LIFETIME {
+            return None;
+        }
+        Some(addresses.clone())
+    }
+}
+
+/// A resolver answering with the addresses prefetched in the [WarmPool] when it can.
+#[derive(Clone)]
+pub struct WarmResolver {
+    inner: GaiResolver,
+    warm_pool: WarmPool,
+}
+
+impl Service<Name> for WarmResolver {
+    type Response = std::vec::IntoIter<SocketAddr>;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, io::Error>> + Send>>;
+
+    fn call(&mut self, name: Name) -> Self::Future {
+        if let Some(addresses) = self.warm_pool.addresses(name.as_str()) {
+            return Box::pin(future::ready(Ok(addresses.into_iter())));
+        }
+        let lookup = self.inner.call(name);
+        Box::pin(async move { Ok(lookup.await?.collect::<Vec<_>>().into_iter()) })
+    }
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Ok(()).into()
+    }
+}
+
 #[derive(Clone)]
 pub struct ServoHttpConnector {
-    inner: HyperHttpConnector,
+    inner: HyperHttpConnector<WarmResolver>,
+    warm_pool: WarmPool,
 }
 
 impl ServoHttpConnector {
-    fn new() -> ServoHttpConnector {
-        let mut inner = HyperHttpConnector::new();
+    fn new(warm_pool: WarmPool) -> ServoHttpConnector {
+        let resolver = WarmResolver {
+            inner: GaiResolver::new(),
+            warm_pool: warm_pool.clone(),
+        };
+        let mut inner = HyperHttpConnector::new_with_resolver(resolver);
         inner.enforce_http(false);
         inner.set_happy_eyeballs_timeout(None);
-        ServoHttpConnector { inner }
+        ServoHttpConnector { inner, warm_pool }
     }
 }
 
 impl Service<Destination> for ServoHttpConnector {
-    type Response = <HyperHttpConnector as Service<Destination>>::Response;
-    type Error = <HyperHttpConnector as Service<Destination>>::Error;
-    type Future = <HyperHttpConnector as Service<Destination>>::Future;
+    type Response = TcpStream;
+    type Error = <HyperHttpConnector<WarmResolver> as Service<Destination>>::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<TcpStream, Self::Error>> + Send>>;
 
     fn call(&mut self, dest: Destination) -> Self::Future {
+        if let Some(stream) = self.warm_pool.take_connection(&dest) {
+            return Box::pin(future::ready(Ok(stream)));
+        }
+
         // Perform host replacement when making the actual TCP connection.
         let mut new_dest = dest.clone();
         let mut parts = dest.into_parts();
@@ -65,7 +267,7 @@ impl Service<Destination> for ServoHttpConnector {
             }
         }
 
-        self.inner.call(new_dest)
+        Box::pin(self.inner.call(new_dest))
     }
 
     fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
@@ -73,7 +275,7 @@ impl Service<Destination> for ServoHttpConnector {
     }
 }
 
-pub type Connector = HyperRustlsHttpsConnector<ServoHttpConnector>;
+pub type Connector = MonitoredConnector<HyperRustlsHttpsConnector<ServoHttpConnector>>;
 pub type TlsConfig = ClientConfig;
 
 #[derive(Clone, Debug, Default)]
@@ -247,16 +449,22 @@ impl rustls::client::ServerCertVerifier for CertificateVerificationOverrideVerif
     }
 }
 
-pub fn create_http_client(tls_config: TlsConfig) -> Client<Connector, Body> {
+pub fn create_http_client(
+    tls_config: TlsConfig,
+    warm_pool: WarmPool,
+    monitor: ConnectionMonitor,
+) -> Client<Connector, Body> {
     let connector = hyper_rustls::HttpsConnectorBuilder::new()
         .with_tls_config(tls_config)
         .https_or_http()
         .enable_http1()
         .enable_http2()
-        .wrap_connector(ServoHttpConnector::new());
+        .wrap_connector(ServoHttpConnector::new(warm_pool));
 
     Client::builder()
         .http1_title_case_headers(true)
+        .pool_idle_timeout(monitor.idle_timeout())
+        .pool_max_idle_per_host(monitor.max_idle_per_host())
         .executor(TokioExecutor {})
-        .build(connector)
+        .build(MonitoredConnector::new(connector, monitor))
 }
@@ -16,7 +16,7 @@ use content_security_policy as csp;
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
 use headers::{AccessControlExposeHeaders, ContentType, HeaderMapExt, Range};
-use http::header::{self, HeaderMap, HeaderName};
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use http::{Method, StatusCode};
 use ipc_channel::ipc::{self, IpcReceiver};
 use lazy_static::lazy_static;
@@ -42,14 +42,17 @@ use tokio::sync::mpsc::{
     unbounded_channel, UnboundedReceiver as TokioReceiver, UnboundedSender as TokioSender,
 };
 
+use crate::archive::store::{is_archive, ArchiveStore};
 use crate::data_loader::decode;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::headers::determine_nosniff;
 use crate::filemanager_thread::{FileManager, FILE_CHUNK_SIZE};
+use crate::har::HarRecorder;
 use crate::http_loader::{
     determine_requests_referrer, http_fetch, set_default_accept, set_default_accept_language,
     HttpState,
 };
+use crate::request_interceptor::RequestInterceptor;
 use crate::subresource_integrity::is_response_integrity_valid;
 
 lazy_static! {
@@ -74,6 +77,9 @@ pub struct FetchContext {
     pub file_token: FileTokenCheck,
     pub cancellation_listener: Arc<Mutex<CancellationListener>>,
     pub timing: ServoArc<Mutex<ResourceFetchTiming>>,
+    pub request_interceptor: Arc<RequestInterceptor>,
+    pub har_recorder: Arc<HarRecorder>,
+    pub archives: Arc<ArchiveStore>,
 }
 
 pub struct CancellationListener {
@@ -273,12 +279,10 @@ pub async fn main_fetch(
     // TODO: handle FTP URLs.
 
     // Step 10.
+    let partition = context.state.network_partition_key(request);
     context
         .state
-        .hsts_list
-        .read()
-        .unwrap()
-        .apply_hsts_rules(request.current_url_mut());
+        .apply_hsts_rules(partition.as_ref(), request.current_url_mut());
 
     // Step 11.
     // Not applicable: see fetch_async.
@@ -517,8 +521,9 @@ pub async fn main_fetch(
     // Step 24.
     target.process_response_eof(&response);
 
+    let partition = context.state.network_partition_key(request);
     if let Ok(http_cache) = context.state.http_cache.write() {
-        http_cache.update_awaiting_consumers(&request, &response);
+        http_cache.update_awaiting_consumers(&request, partition.as_ref(), &response);
     }
 
     // Steps 25-27.
@@ -641,6 +646,24 @@ fn create_blank_reply(url: ServoUrl, timing_type: ResourceTimingType) -> Respons
     response
 }
 
+/// A redirect from the file of an archive to the URL to start browsing it at, which is
+/// followed like an HTTP one.
+fn create_archive_redirect(
+    url: ServoUrl,
+    primary_url: ServoUrl,
+    timing_type: ResourceTimingType,
+) -> Response {
+    let mut response = Response::new(url, ResourceFetchTiming::new(timing_type));
+    if let Ok(location) = HeaderValue::from_str(primary_url.as_str()) {
+        response.headers.insert(header::LOCATION, location);
+    }
+    response.location_url = Some(Ok(primary_url));
+    *response.body.lock().unwrap() = ResponseBody::Done(vec![]);
+    response.status = Some((StatusCode::FOUND, "Found".to_string()));
+    response.raw_status = Some((StatusCode::FOUND.as_u16(), b"Found".to_vec()));
+    response
+}
+
 /// Handle a request from the user interface to ignore validation errors for a certificate.
 fn handle_allowcert_request(request: &mut Request, context: &FetchContext) -> io::Result<()> {
     let error = |string| Err(io::Error::new(io::ErrorKind::Other, string));
@@ -732,6 +755,19 @@ async fn scheme_fetch(
                 ));
             }
             if let Ok(file_path) = url.to_file_path() {
+                // Navigating to an archive opens it, and continues at its primary URL.
+                if request.destination == Destination::Document && is_archive(&file_path) {
+                    return match context.archives.open(&file_path) {
+                        Ok(primary_url) => {
+                            create_archive_redirect(url, primary_url, request.timing_type())
+                        },
+                        Err(error) => Response::network_error(NetworkError::Internal(format!(
+                            "Opening archive failed: {}",
+                            error
+                        ))),
+                    };
+                }
+
                 if let Ok(file) = File::open(file_path.clone()) {
                     if let Ok(metadata) = file.metadata() {
                         if metadata.is_dir() {
@@ -0,0 +1,326 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Recording the network activity of a whole session in the
+//! [HAR 1.2](http://www.softwareishard.com/blog/har-12-spec/) format.
+
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use http::header::{self, HeaderMap};
+use http::Version;
+use net_traits::request::Request;
+use net_traits::response::Response;
+use serde::Serialize;
+
+/// Keeps an entry for every response received from the network while a recording is in
+/// progress. Shared by all the fetches of a resource manager.
+#[derive(Default)]
+pub struct HarRecorder {
+    recording: Mutex<Option<Recording>>,
+}
+
+struct Recording {
+    include_bodies: bool,
+    entries: Vec<Entry>,
+}
+
+impl HarRecorder {
+    pub fn new() -> HarRecorder {
+        HarRecorder::default()
+    }
+
+    /// Start a new recording, discarding the current one if any.
+    pub fn start(&self, include_bodies: bool) {
+        *self.recording.lock().unwrap() = Some(Recording {
+            include_bodies,
+            entries: vec![],
+        });
+    }
+
+    /// Stop the current recording, and return it as the contents of a HAR file.
+    pub fn stop(&self) -> Option<String> {
+        let recording = self.recording.lock().unwrap().take()?;
+        let har = Har {
+            log: Log {
+                version: "1.2",
+                creator: Creator {
+                    name: "Servo",
+                    version: env!("CARGO_PKG_VERSION"),
+                },
+                entries: recording.entries,
+            },
+        };
+        serde_json::to_string_pretty(&har).ok()
+    }
+
+    /// Start the entry of `request`, sent at `started`, if a recording is in progress.
+    /// `response` is the response whose headers were just received.
+    pub fn begin_entry(
+        &self,
+        request: &Request,
+        started: SystemTime,
+        version: Version,
+        response: &Response,
+    ) -> Option<PendingEntry> {
+        let include_bodies = self.recording.lock().unwrap().as_ref()?.include_bodies;
+        let url = request.current_url();
+        let http_version = format!("{:?}", version);
+        let (status, status_text) = response.raw_status.clone().unwrap_or_default();
+        let entry = Entry {
+            started_date_time: format_date_time(started),
+            time: 0.,
+            request: HarRequest {
+                method: request.method.to_string(),
+                url: url.to_string(),
+                http_version: http_version.clone(),
+                cookies: request_cookies(&request.headers),
+                headers: name_values(&request.headers),
+                query_string: url
+                    .as_url()
+                    .query_pairs()
+                    .map(|(name, value)| NameValue {
+                        name: name.into_owned(),
+                        value: value.into_owned(),
+                    })
+                    .collect(),
+                headers_size: -1,
+                body_size: request
+                    .body
+                    .as_ref()
+                    .map_or(0, |body| body.len().map_or(-1, |len| len as i64)),
+            },
+            response: HarResponse {
+                status,
+                status_text: String::from_utf8_lossy(&status_text).into_owned(),
+                http_version,
+                cookies: response_cookies(&response.headers),
+                headers: name_values(&response.headers),
+                content: Content {
+                    size: 0,
+                    mime_type: header_value(&response.headers, header::CONTENT_TYPE),
+                    text: None,
+                    encoding: None,
+                },
+                redirect_url: header_value(&response.headers, header::LOCATION),
+                headers_size: -1,
+                body_size: -1,
+            },
+            cache: Cache {},
+            timings: Timings {
+                blocked: -1.,
+                dns: -1.,
+                connect: -1.,
+                send: 0.,
+                wait: 0.,
+                receive: 0.,
+                ssl: -1.,
+            },
+        };
+        Some(PendingEntry {
+            entry,
+            include_bodies,
+            started,
+            headers_received: SystemTime::now(),
+        })
+    }
+
+    /// Complete `entry` with `body`, the whole body of the response, and add it to the
+    /// recording.
+    pub fn finish_entry(&self, entry: PendingEntry, body: &[u8]) {
+        let entry = entry.finish(body);
+        if let Some(ref mut recording) = *self.recording.lock().unwrap() {
+            recording.entries.push(entry);
+        }
+    }
+}
+
+/// An entry whose response body is still being received.
+pub struct PendingEntry {
+    entry: Entry,
+    include_bodies: bool,
+    started: SystemTime,
+    headers_received: SystemTime,
+}
+
+impl PendingEntry {
+    fn finish(mut self, body: &[u8]) -> Entry {
+        let wait = milliseconds_between(self.started, self.headers_received);
+        let receive = milliseconds_between(self.headers_received, SystemTime::now());
+        self.entry.time = wait + receive;
+        self.entry.timings.wait = wait;
+        self.entry.timings.receive = receive;
+
+        let content = &mut self.entry.response.content;
+        content.size = body.len() as i64;
+        if self.include_bodies {
+            match String::from_utf8(body.to_vec()) {
+                Ok(text) => content.text = Some(text),
+                Err(_) => {
+                    content.text = Some(base64::engine::general_purpose::STANDARD.encode(body));
+                    content.encoding = Some("base64");
+                },
+            }
+        }
+        self.entry.response.body_size = body.len() as i64;
+        self.entry
+    }
+}
+
+fn milliseconds_between(start: SystemTime, end: SystemTime) -> f64 {
+    end.duration_since(start).unwrap_or_default().as_secs_f64() * 1000.
+}
+
+/// Format `time` as an ISO 8601 date, with milliseconds.
+fn format_date_time(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let tm = time::at_utc(time::Timespec::new(
+        since_epoch.as_secs() as i64,
+        since_epoch.subsec_nanos() as i32,
+    ));
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        tm.tm_year + 1900,
+        tm.tm_mon + 1,
+        tm.tm_mday,
+        tm.tm_hour,
+        tm.tm_min,
+        tm.tm_sec,
+        since_epoch.subsec_millis()
+    )
+}
+
+fn header_value(headers: &HeaderMap, name: header::HeaderName) -> String {
+    headers
+        .get(name)
+        .map(|value| String::from_utf8_lossy(value.as_bytes()).into_owned())
+        .unwrap_or_default()
+}
+
+fn name_values(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .iter()
+        .map(|(name, value)| NameValue {
+            name: name.as_str().to_owned(),
+            value: String::from_utf8_lossy(value.as_bytes()).into_owned(),
+        })
+        .collect()
+}
+
+fn cookie(cookie: &str) -> Option<NameValue> {
+    let (name, value) = cookie.split_once('=')?;
+    Some(NameValue {
+        name: name.trim().to_owned(),
+        value: value.trim().to_owned(),
+    })
+}
+
+fn request_cookies(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(cookie)
+        .collect()
+}
+
+fn response_cookies(headers: &HeaderMap) -> Vec<NameValue> {
+    headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| cookie(value.split(';').next().unwrap_or("")))
+        .collect()
+}
+
+#[derive(Serialize)]
+struct Har {
+    log: Log,
+}
+
+#[derive(Serialize)]
+struct Log {
+    version: &'static str,
+    creator: Creator,
+    entries: Vec<Entry>,
+}
+
+#[derive(Serialize)]
+struct Creator {
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    started_date_time: String,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: Cache,
+    timings: Timings,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<NameValue>,
+    headers: Vec<NameValue>,
+    query_string: Vec<NameValue>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<NameValue>,
+    headers: Vec<NameValue>,
+    content: Content,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Content {
+    size: i64,
+    mime_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoding: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct NameValue {
+    name: String,
+    value: String,
+}
+
+/// Nothing is known about the use of the cache, as only network responses are recorded.
+#[derive(Serialize)]
+struct Cache {}
+
+#[derive(Serialize)]
+struct Timings {
+    blocked: f64,
+    dns: f64,
+    connect: f64,
+    send: f64,
+    wait: f64,
+    receive: f64,
+    ssl: f64,
+}
@@ -34,23 +34,27 @@ use time::{Duration, Timespec, Tm};
 use tokio::sync::mpsc::{unbounded_channel as unbounded, UnboundedSender as TokioSender};
 
 use crate::fetch::methods::{Data, DoneChannel};
+use crate::partitioning::NetworkPartitionKey;
 
 /// The key used to differentiate requests in the cache.
 #[derive(Clone, Eq, Hash, MallocSizeOf, PartialEq)]
 pub struct CacheKey {
+    partition: Option<NetworkPartitionKey>,
     url: ServoUrl,
 }
 
 impl CacheKey {
-    /// Create a cache-key from a request.
-    pub(crate) fn new(request: &Request) -> CacheKey {
+    /// Create a cache-key from a request, and the partition of the cache it uses.
+    pub(crate) fn new(request: &Request, partition: Option<&NetworkPartitionKey>) -> CacheKey {
         CacheKey {
+            partition: partition.cloned(),
             url: request.current_url(),
         }
     }
 
-    fn from_servo_url(servo_url: &ServoUrl) -> CacheKey {
+    fn from_servo_url(servo_url: &ServoUrl, partition: Option<&NetworkPartitionKey>) -> CacheKey {
         CacheKey {
+            partition: partition.cloned(),
             url: servo_url.clone(),
         }
     }
@@ -599,6 +603,7 @@ impl HttpCache {
     pub fn construct_response(
         &self,
         request: &Request,
+        partition: Option<&NetworkPartitionKey>,
         done_chan: &mut DoneChannel,
     ) -> Option<CachedResponse> {
         // TODO: generate warning headers as appropriate <https://tools.ietf.org/html/rfc7234#section-5.5>
@@ -608,7 +613,7 @@ impl HttpCache {
             debug!("non-GET method, not caching");
             return None;
         }
-        let entry_key = CacheKey::new(&request);
+        let entry_key = CacheKey::new(&request, partition);
         let resources = self
             .entries
             .get(&entry_key)?
@@ -711,8 +716,13 @@ impl HttpCache {
     /// Wake-up consumers of cached resources
     /// whose response body was still receiving data when the resource was constructed,
     /// and whose response has now either been completed or cancelled.
-    pub fn update_awaiting_consumers(&self, request: &Request, response: &Response) {
-        let entry_key = CacheKey::new(&request);
+    pub fn update_awaiting_consumers(
+        &self,
+        request: &Request,
+        partition: Option<&NetworkPartitionKey>,
+        response: &Response,
+    ) {
+        let entry_key = CacheKey::new(&request, partition);
 
         let cached_resources = match self.entries.get(&entry_key) {
             None => return,
@@ -758,11 +768,12 @@ impl HttpCache {
     pub fn refresh(
         &mut self,
         request: &Request,
+        partition: Option<&NetworkPartitionKey>,
         response: Response,
         done_chan: &mut DoneChannel,
     ) -> Option<Response> {
         assert_eq!(response.status.map(|s| s.0), Some(StatusCode::NOT_MODIFIED));
-        let entry_key = CacheKey::new(&request);
+        let entry_key = CacheKey::new(&request, partition);
         if let Some(cached_resources) = self.entries.get_mut(&entry_key) {
             for cached_resource in cached_resources.iter_mut() {
                 // done_chan will have been set to Some(..) by http_network_fetch.
@@ -809,8 +820,8 @@ impl HttpCache {
         None
     }
 
-    fn invalidate_for_url(&mut self, url: &ServoUrl) {
-        let entry_key = CacheKey::from_servo_url(url);
+    fn invalidate_for_url(&mut self, url: &ServoUrl, partition: Option<&NetworkPartitionKey>) {
+        let entry_key = CacheKey::from_servo_url(url, partition);
         if let Some(cached_resources) = self.entries.get_mut(&entry_key) {
             for cached_resource in cached_resources.iter_mut() {
                 cached_resource.data.expires = Duration::seconds(0i64);
@@ -820,7 +831,12 @@ impl HttpCache {
 
     /// Invalidation.
     /// <https://tools.ietf.org/html/rfc7234#section-4.4>
-    pub fn invalidate(&mut self, request: &Request, response: &Response) {
+    pub fn invalidate(
+        &mut self,
+        request: &Request,
+        partition: Option<&NetworkPartitionKey>,
+        response: &Response,
+    ) {
         // TODO(eijebong): Once headers support typed_get, update this to use them
         if let Some(Ok(location)) = response
             .headers
@@ -828,7 +844,7 @@ impl HttpCache {
             .map(HeaderValue::to_str)
         {
             if let Ok(url) = request.current_url().join(location) {
-                self.invalidate_for_url(&url);
+                self.invalidate_for_url(&url, partition);
             }
         }
         if let Some(Ok(ref content_location)) = response
@@ -837,15 +853,20 @@ impl HttpCache {
             .map(HeaderValue::to_str)
         {
             if let Ok(url) = request.current_url().join(&content_location) {
-                self.invalidate_for_url(&url);
+                self.invalidate_for_url(&url, partition);
             }
         }
-        self.invalidate_for_url(&request.url());
+        self.invalidate_for_url(&request.url(), partition);
     }
 
     /// Storing Responses in Caches.
     /// <https://tools.ietf.org/html/rfc7234#section-3>
-    pub fn store(&mut self, request: &Request, response: &Response) {
+    pub fn store(
+        &mut self,
+        request: &Request,
+        partition: Option<&NetworkPartitionKey>,
+        response: &Response,
+    ) {
         if pref!(network.http_cache.disabled) {
             return;
         }
@@ -862,7 +883,7 @@ impl HttpCache {
             // responses to be stored is present in the response.
             return;
         };
-        let entry_key = CacheKey::new(&request);
+        let entry_key = CacheKey::new(&request, partition);
         let metadata = match response.metadata() {
             Ok(FetchMetadata::Filtered {
                 filtered: _,
@@ -7,6 +7,7 @@ use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 use std::mem;
 use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc as StdArc, Condvar, Mutex, RwLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -14,9 +15,9 @@ use async_recursion::async_recursion;
 use crossbeam_channel::Sender;
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
-    HttpResponse as DevtoolsHttpResponse, NetworkEvent,
+    HttpResponse as DevtoolsHttpResponse, NetworkEvent, ResourceHint,
 };
-use futures::{future, StreamExt, TryFutureExt, TryStreamExt};
+use futures::{future, FutureExt, StreamExt, TryFutureExt, TryStreamExt};
 use headers::authorization::Basic;
 use headers::{
     AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowMethods,
@@ -27,7 +28,7 @@ use headers::{
 use http::header::{
     self, HeaderValue, ACCEPT, CONTENT_ENCODING, CONTENT_LANGUAGE, CONTENT_LOCATION, CONTENT_TYPE,
 };
-use http::{HeaderMap, Method, Request as HyperRequest, StatusCode};
+use http::{HeaderMap, Method, Request as HyperRequest, StatusCode, Version};
 use hyper::header::{HeaderName, TRANSFER_ENCODING};
 use hyper::{Body, Client, Response as HyperResponse};
 use hyper_serde::Serde;
@@ -36,21 +37,24 @@ use ipc_channel::router::ROUTER;
 use lazy_static::lazy_static;
 use log::{debug, error, info, log_enabled, warn};
 use msg::constellation_msg::{HistoryStateId, PipelineId};
+use net_traits::client_hints::UserAgentMetadata;
 use net_traits::pub_domains::reg_suffix;
 use net_traits::quality::{quality_to_value, Quality, QualityItem};
 use net_traits::request::Origin::Origin as SpecificOrigin;
 use net_traits::request::{
     get_cors_unsafe_header_names, is_cors_non_wildcard_request_header_name,
     is_cors_safelisted_method, is_cors_safelisted_request_header, BodyChunkRequest,
-    BodyChunkResponse, CacheMode, CredentialsMode, Destination, Origin, RedirectMode, Referrer,
-    Request, RequestBuilder, RequestMode, ResponseTainting, ServiceWorkersMode,
+    BodyChunkResponse, CacheMode, CredentialsMode, Destination, Initiator, Origin, RedirectMode,
+    Referrer, Request, RequestBuilder, RequestMode, ResponseTainting, ServiceWorkersMode,
 };
 use net_traits::response::{HttpsState, Response, ResponseBody, ResponseType};
+use net_traits::server_timing::parse_server_timing;
 use net_traits::{
     CookieSource, FetchMetadata, NetworkError, RedirectEndValue, RedirectStartValue,
     ReferrerPolicy, ResourceAttribute, ResourceFetchTiming, ResourceTimeValue,
 };
 use servo_arc::Arc;
+use servo_config::pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use tokio::runtime::Runtime;
 use tokio::sync::mpsc::{
@@ -59,9 +63,12 @@ use tokio::sync::mpsc::{
 };
 use tokio_stream::wrappers::ReceiverStream;
 
+use crate::backpressure::Backpressure;
+use crate::client_hints::{append_client_hints, AcceptChCache, UserAgentOverrides};
+use crate::connection_monitor::ConnectionMonitor;
 use crate::connector::{
     create_http_client, create_tls_config, CACertificates, CertificateErrorOverrideManager,
-    Connector,
+    Connector, WarmPool,
 };
 use crate::cookie;
 use crate::cookie_storage::CookieStorage;
@@ -70,7 +77,12 @@ use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::methods::{main_fetch, Data, DoneChannel, FetchContext, Target};
 use crate::hsts::HstsList;
 use crate::http_cache::{CacheKey, HttpCache};
+use crate::keepalive::KeepAliveQuota;
+use crate::partitioning::{NetworkPartitionKey, NetworkPartitions};
+use crate::reporting::ReportingQueue;
 use crate::resource_thread::AuthCache;
+use crate::service_workers::ServiceWorkers;
+use crate::throttling::NetworkThrottler;
 
 lazy_static! {
     pub static ref HANDLE: Mutex<Option<Runtime>> = Mutex::new(Some(Runtime::new().unwrap()));
@@ -99,11 +111,43 @@ pub struct HttpState {
     pub history_states: RwLock<HashMap<HistoryStateId, Vec<u8>>>,
     pub client: Client<Connector, Body>,
     pub override_manager: CertificateErrorOverrideManager,
+    /// The host names resolved and connections opened for resource hints, which the
+    /// client's connector uses.
+    pub warm_pool: WarmPool,
+    /// The connections the client opened.
+    pub connection_monitor: ConnectionMonitor,
+    /// Whether the user chose to work offline, answering requests from the cache only.
+    pub work_offline: AtomicBool,
+    /// The network conditions emulated for the requests of some pipelines.
+    pub throttler: NetworkThrottler,
+    /// The pipelines whose event loop is saturated, and whose response chunks are held back.
+    pub backpressure: Backpressure,
+    /// The service worker managers which navigations are handed to.
+    pub service_workers: ServiceWorkers,
+    /// The client hints each origin asked for.
+    pub accept_ch_cache: AcceptChCache,
+    /// What the requests of some pipelines tell about the user agent instead of the defaults.
+    pub user_agent_overrides: UserAgentOverrides,
+    /// The network state kept apart for each top-level site. The state above is the one of
+    /// the requests that have no top-level site, or of every request when it is not
+    /// partitioned.
+    pub partitions: NetworkPartitions,
+    /// The reports waiting to be delivered to their endpoints.
+    pub reporting_queue: ReportingQueue,
+    /// The bytes of the bodies of the keep-alive requests in flight.
+    pub keepalive_quota: KeepAliveQuota,
 }
 
 impl HttpState {
     pub fn new() -> HttpState {
         let override_manager = CertificateErrorOverrideManager::new();
+        let warm_pool = WarmPool::new();
+        let connection_monitor = ConnectionMonitor::new();
+        let tls_config = create_tls_config(
+            CACertificates::Default,
+            false, /* ignore_certificate_errors */
+            override_manager.clone(),
+        );
         HttpState {
             hsts_list: RwLock::new(HstsList::new()),
             cookie_jar: RwLock::new(CookieStorage::new(150)),
@@ -111,12 +155,90 @@ impl HttpState {
             history_states: RwLock::new(HashMap::new()),
             http_cache: RwLock::new(HttpCache::new()),
             http_cache_state: Mutex::new(HashMap::new()),
-            client: create_http_client(create_tls_config(
-                CACertificates::Default,
-                false, /* ignore_certificate_errors */
-                override_manager.clone(),
-            )),
+            client: create_http_client(
+                tls_config.clone(),
+                warm_pool.clone(),
+                connection_monitor.clone(),
+            ),
             override_manager,
+            warm_pool,
+            connection_monitor: connection_monitor.clone(),
+            work_offline: AtomicBool::new(false),
+            throttler: NetworkThrottler::new(),
+            backpressure: Backpressure::new(),
+            service_workers: ServiceWorkers::new(),
+            accept_ch_cache: AcceptChCache::new(),
+            user_agent_overrides: UserAgentOverrides::new(),
+            partitions: NetworkPartitions::new(tls_config, connection_monitor),
+            reporting_queue: ReportingQueue::new(),
+            keepalive_quota: KeepAliveQuota::new(),
+        }
+    }
+
+    /// The partition of the network state `request` uses, if the state is partitioned and
+    /// the request has a top-level site.
+    pub fn network_partition_key(&self, request: &Request) -> Option<NetworkPartitionKey> {
+        if !pref!(network.partitioning.enabled) {
+            return None;
+        }
+        self.partitions.key_for_request(request)
+    }
+
+    /// The partition of the network state the requests of `pipeline` that are not
+    /// navigations use, if the state is partitioned and the pipeline has a top-level site.
+    pub fn pipeline_network_partition_key(
+        &self,
+        pipeline: Option<PipelineId>,
+    ) -> Option<NetworkPartitionKey> {
+        if !pref!(network.partitioning.enabled) {
+            return None;
+        }
+        self.partitions.key_for_pipeline(pipeline)
+    }
+
+    /// The client making the requests that use the network state of `partition`.
+    pub fn client_for(&self, partition: Option<&NetworkPartitionKey>) -> Client<Connector, Body> {
+        match partition {
+            Some(partition) => self.partitions.state(partition).client.clone(),
+            None => self.client.clone(),
+        }
+    }
+
+    /// The host names resolved and the connections opened ahead for the requests that use
+    /// the network state of `partition`.
+    pub fn warm_pool_for(&self, partition: Option<&NetworkPartitionKey>) -> WarmPool {
+        match partition {
+            Some(partition) => self.partitions.state(partition).warm_pool.clone(),
+            None => self.warm_pool.clone(),
+        }
+    }
+
+    /// Upgrade `url` if its host asked to only be reached securely, either in the preloaded
+    /// list or to the requests using the network state of `partition`.
+    pub fn apply_hsts_rules(&self, partition: Option<&NetworkPartitionKey>, url: &mut ServoUrl) {
+        self.hsts_list.read().unwrap().apply_hsts_rules(url);
+        if let Some(partition) = partition {
+            self.partitions.apply_hsts_rules(partition, url);
+        }
+    }
+
+    /// Remember if the host of `url` asks to only be reached securely by the requests using
+    /// the network state of `partition`.
+    pub fn update_hsts_list_from_response(
+        &self,
+        partition: Option<&NetworkPartitionKey>,
+        url: &ServoUrl,
+        headers: &HeaderMap,
+    ) {
+        match partition {
+            Some(partition) => self
+                .partitions
+                .update_hsts_list_from_response(partition, url, headers),
+            None => self
+                .hsts_list
+                .write()
+                .unwrap()
+                .update_hsts_list_from_response(url, headers),
         }
     }
 }
@@ -361,6 +483,7 @@ fn prepare_devtools_request(
     connect_time: u64,
     send_time: u64,
     is_xhr: bool,
+    resource_hint: Option<ResourceHint>,
 ) -> ChromeToDevtoolsControlMsg {
     let request = DevtoolsHttpRequest {
         url: url,
@@ -373,6 +496,7 @@ fn prepare_devtools_request(
         connect_time: connect_time,
         send_time: send_time,
         is_xhr: is_xhr,
+        resource_hint,
     };
     let net_event = NetworkEvent::HttpRequest(request);
 
@@ -490,6 +614,7 @@ async fn obtain_response(
     pipeline_id: &Option<PipelineId>,
     request_id: Option<&str>,
     is_xhr: bool,
+    resource_hint: Option<ResourceHint>,
     context: &FetchContext,
     fetch_terminated: UnboundedSender<bool>,
 ) -> Result<(HyperResponse<Decoder>, Option<ChromeToDevtoolsControlMsg>), NetworkError> {
@@ -678,6 +803,7 @@ async fn obtain_response(
                             connect_end - connect_start,
                             send_end - send_start,
                             is_xhr,
+                            resource_hint,
                         ))
                     // TODO: ^This is not right, connect_start is taken before contructing the
                     // request and connect_end at the end of it. send_start is takend before the
@@ -725,8 +851,12 @@ pub async fn http_fetch(
 
     // Step 3
     if request.service_workers_mode == ServiceWorkersMode::All {
-        // TODO: Substep 1
-        // Set response to the result of invoking handle fetch for request.
+        // Substep 1
+        response = context
+            .state
+            .service_workers
+            .handle_fetch(request, context)
+            .await;
 
         // Substep 2
         if let Some(ref res) = response {
@@ -1152,9 +1282,8 @@ async fn http_network_or_cache_fetch(
         http_request
             .headers
             .typed_insert(ContentLength(content_length_value));
-        if http_request.keep_alive {
-            // Step 5.8 TODO: needs request's client object
-        }
+        // Step 5.8 The quota of the keep-alive requests is reserved by the resource thread,
+        // which reads their whole body before they are fetched.
     }
 
     // Step 5.9
@@ -1184,11 +1313,42 @@ async fn http_network_or_cache_fetch(
     }
 
     // Step 5.11
+    let user_agent_override = context
+        .state
+        .user_agent_overrides
+        .get(http_request.pipeline_id);
     if !http_request.headers.contains_key(header::USER_AGENT) {
-        let user_agent = context.user_agent.clone().into_owned();
-        http_request
-            .headers
-            .typed_insert::<UserAgent>(user_agent.parse().unwrap());
+        let user_agent = user_agent_override
+            .as_ref()
+            .and_then(|user_agent_override| user_agent_override.user_agent.as_deref())
+            .unwrap_or(&*context.user_agent);
+        match user_agent.parse::<UserAgent>() {
+            Ok(user_agent) => http_request.headers.typed_insert(user_agent),
+            Err(_) => warn!("Invalid user agent string {:?}", user_agent),
+        }
+    }
+
+    // https://wicg.github.io/client-hints-infrastructure/#fetch
+    // Documents are sent the hints their own origin asked for, and subresources the ones of
+    // the origin that requested them.
+    let current_url = http_request.current_url();
+    let hints_origin = if http_request.is_navigation_request() {
+        Some(current_url.origin())
+    } else {
+        match http_request.origin {
+            Origin::Origin(ref origin) => Some(origin.clone()),
+            Origin::Client => None,
+        }
+    };
+    if pref!(network.client_hints.enabled) {
+        append_client_hints(
+            &mut http_request.headers,
+            &current_url,
+            hints_origin.as_ref(),
+            &context.state.accept_ch_cache,
+            &UserAgentMetadata::new(&context.user_agent, user_agent_override.as_ref()),
+            pref!(network.client_hints.high_entropy_values.enabled),
+        );
     }
 
     match http_request.cache_mode {
@@ -1276,6 +1436,9 @@ async fn http_network_or_cache_fetch(
     // Step 5.18
     // TODO If there’s a proxy-authentication entry, use it as appropriate.
 
+    let work_offline = context.state.work_offline.load(Ordering::Relaxed);
+    let partition = context.state.network_partition_key(&http_request);
+
     // If the cache is not ready to construct a response, wait.
     //
     // The cache is not ready if a previous fetch checked the cache, found nothing,
@@ -1285,7 +1448,7 @@ async fn http_network_or_cache_fetch(
     // That one happens when a fetch gets a cache hit, and the resource is pending completion from the network.
     {
         let (lock, cvar) = {
-            let entry_key = CacheKey::new(&http_request);
+            let entry_key = CacheKey::new(&http_request, partition.as_ref());
             let mut state_map = context.state.http_cache_state.lock().unwrap();
             &*state_map
                 .entry(entry_key)
@@ -1314,12 +1477,14 @@ async fn http_network_or_cache_fetch(
         // Step 5.19
         if let Ok(http_cache) = context.state.http_cache.read() {
             if let Some(response_from_cache) =
-                http_cache.construct_response(&http_request, done_chan)
+                http_cache.construct_response(&http_request, partition.as_ref(), done_chan)
             {
                 let response_headers = response_from_cache.response.headers.clone();
                 // Substep 1, 2, 3, 4
                 let (cached_response, needs_revalidation) =
                     match (http_request.cache_mode, &http_request.mode) {
+                        // When working offline, whatever is in the cache is used as is.
+                        _ if work_offline => (Some(response_from_cache.response), false),
                         (CacheMode::ForceCache, _) => (Some(response_from_cache.response), false),
                         (CacheMode::OnlyIfCached, &RequestMode::SameOrigin) => {
                             (Some(response_from_cache.response), false)
@@ -1376,9 +1541,13 @@ async fn http_network_or_cache_fetch(
     // Decrement the number of pending stores,
     // and set the state to ready to construct,
     // if no stores are pending.
-    fn update_http_cache_state(context: &FetchContext, http_request: &Request) {
+    fn update_http_cache_state(
+        context: &FetchContext,
+        http_request: &Request,
+        partition: Option<&NetworkPartitionKey>,
+    ) {
         let (lock, cvar) = {
-            let entry_key = CacheKey::new(&http_request);
+            let entry_key = CacheKey::new(&http_request, partition);
             let mut state_map = context.state.http_cache_state.lock().unwrap();
             &*state_map
                 .get_mut(&entry_key)
@@ -1434,10 +1603,10 @@ async fn http_network_or_cache_fetch(
     // Step 7
     if response.is_none() {
         // Substep 1
-        if http_request.cache_mode == CacheMode::OnlyIfCached {
+        if http_request.cache_mode == CacheMode::OnlyIfCached || work_offline {
             // The cache will not be updated,
             // set its state to ready to construct.
-            update_http_cache_state(context, &http_request);
+            update_http_cache_state(context, &http_request, partition.as_ref());
             return Response::network_error(NetworkError::Internal(
                 "Couldn't find response in cache".into(),
             ));
@@ -1452,7 +1621,7 @@ async fn http_network_or_cache_fetch(
         if let Some((200..=399, _)) = forward_response.raw_status {
             if !http_request.method.is_safe() {
                 if let Ok(mut http_cache) = context.state.http_cache.write() {
-                    http_cache.invalidate(&http_request, &forward_response);
+                    http_cache.invalidate(&http_request, partition.as_ref(), &forward_response);
                 }
             }
         }
@@ -1467,7 +1636,12 @@ async fn http_network_or_cache_fetch(
                 // Ensure done_chan is None,
                 // since the network response will be replaced by the revalidated stored one.
                 *done_chan = None;
-                response = http_cache.refresh(&http_request, forward_response.clone(), done_chan);
+                response = http_cache.refresh(
+                    &http_request,
+                    partition.as_ref(),
+                    forward_response.clone(),
+                    done_chan,
+                );
             }
             wait_for_cached_response(done_chan, &mut response).await;
         }
@@ -1477,7 +1651,7 @@ async fn http_network_or_cache_fetch(
             if http_request.cache_mode != CacheMode::NoStore {
                 // Subsubstep 2, doing it first to avoid a clone of forward_response.
                 if let Ok(mut http_cache) = context.state.http_cache.write() {
-                    http_cache.store(&http_request, &forward_response);
+                    http_cache.store(&http_request, partition.as_ref(), &forward_response);
                 }
             }
             // Subsubstep 1
@@ -1488,7 +1662,7 @@ async fn http_network_or_cache_fetch(
     let mut response = response.unwrap();
 
     // The cache has been updated, set its state to ready to construct.
-    update_http_cache_state(context, &http_request);
+    update_http_cache_state(context, &http_request, partition.as_ref());
 
     // Step 8
     // TODO: if necessary set response's range-requested flag
@@ -1677,8 +1851,38 @@ async fn http_network_fetch(
     // Step 4
     // TODO: check whether the connection is HTTP/2
 
+    // Let the embedder answer the request before any connection is made. Its response then
+    // goes through the same steps as a network one, so the CORS checks still apply to it.
+    if let Some(response) = context
+        .request_interceptor
+        .intercept_request(request, done_chan, context)
+        .await
+    {
+        return response;
+    }
+
+    // Sites opened from an archive are loaded from it rather than from the network.
+    if let Some(response) = context.archives.response(request, context) {
+        return response;
+    }
+
+    // Emulate the network conditions chosen for the tab that made the request.
+    let throttling = context.state.throttler.get(request.pipeline_id);
+    if let Some(throttling) = throttling {
+        if throttling.offline {
+            return Response::network_error(NetworkError::Internal(
+                "The network is emulated to be offline".into(),
+            ));
+        }
+        let body_length = request.body.as_ref().and_then(|body| body.len());
+        let delay = throttling.latency() + throttling.upload_time(body_length.unwrap_or(0));
+        tokio::time::sleep(delay).await;
+    }
+
     // Step 5
     let url = request.current_url();
+    let partition = context.state.network_partition_key(request);
+    let client = context.state.client_for(partition.as_ref());
 
     let request_id = context
         .devtools_chan
@@ -1695,7 +1899,18 @@ async fn http_network_fetch(
     // XHR uses the default destination; other kinds of fetches (which haven't been implemented yet)
     // do not. Once we support other kinds of fetches we'll need to be more fine grained here
     // since things like image fetches are classified differently by devtools
-    let is_xhr = request.destination == Destination::None;
+    let is_xhr =
+        request.destination == Destination::None && request.initiator != Initiator::Prefetch;
+
+    // Let devtools show the requests made, or sped up, by resource hints.
+    let resource_hint = match request.initiator {
+        Initiator::Prefetch => Some(ResourceHint::Prefetch),
+        _ if context.devtools_chan.is_none() => None,
+        _ => context
+            .state
+            .warm_pool_for(partition.as_ref())
+            .hint_for(&url),
+    };
 
     // The receiver will receive true if there has been an error streaming the request body.
     let (fetch_terminated_sender, mut fetch_terminated_receiver) = unbounded_channel();
@@ -1710,8 +1925,14 @@ async fn http_network_fetch(
         let _ = fetch_terminated_sender.send(false);
     }
 
+    // A request without a body that is safe to repeat is retried once if the network changed
+    // while it was made, as the connection it used most likely broke with the change.
+    let retriable = body.is_none() && request.method.is_idempotent();
+    let network_changes = context.state.connection_monitor.network_changes();
+
+    let started = SystemTime::now();
     let response_future = obtain_response(
-        &context.state.client,
+        &client,
         &url,
         &request.method,
         &mut request.headers,
@@ -1724,6 +1945,7 @@ async fn http_network_fetch(
         &request.pipeline_id,
         request_id.as_ref().map(Deref::deref),
         is_xhr,
+        resource_hint,
         context,
         fetch_terminated_sender,
     );
@@ -1732,8 +1954,42 @@ async fn http_network_fetch(
     // This will only get the headers, the body is read later
     let (res, msg) = match response_future.await {
         Ok(wrapped_response) => wrapped_response,
-        Err(error) => return Response::network_error(error),
+        Err(error) => {
+            if !retriable ||
+                !context
+                    .state
+                    .connection_monitor
+                    .failed_by_network_change(network_changes)
+            {
+                return Response::network_error(error);
+            }
+            debug!("Retrying {} after a network change ({:?})", url, error);
+            // The confirmation that there was no body to stream has already been sent.
+            let (fetch_terminated_sender, _) = unbounded_channel();
+            let response_future = obtain_response(
+                &client,
+                &url,
+                &request.method,
+                &mut request.headers,
+                None,
+                false,
+                &request.pipeline_id,
+                request_id.as_ref().map(Deref::deref),
+                is_xhr,
+                resource_hint,
+                context,
+                fetch_terminated_sender,
+            );
+            match response_future.await {
+                Ok(wrapped_response) => wrapped_response,
+                Err(error) => return Response::network_error(error),
+            }
+        },
     };
+    context
+        .state
+        .connection_monitor
+        .record_request(&url, res.version() == Version::HTTP_2);
 
     if log_enabled!(log::Level::Info) {
         debug!("{:?} response for {}", res.version(), url);
@@ -1778,11 +2034,16 @@ async fn http_network_fetch(
         _ => false,
     });
 
-    if !(is_same_origin || req_origin_in_timing_allow || wildcard_present) {
-        context.timing.lock().unwrap().mark_timing_check_failed();
-    }
-
-    let timing = context.timing.lock().unwrap().clone();
+    let timing = {
+        let mut timing = context.timing.lock().unwrap();
+        if !(is_same_origin || req_origin_in_timing_allow || wildcard_present) {
+            timing.mark_timing_check_failed();
+        } else if timing.timing_check_passed {
+            // https://w3c.github.io/server-timing/#process-the-server-timing-header
+            timing.server_timing = parse_server_timing(res.headers());
+        }
+        timing.clone()
+    };
     let mut response = Response::new(url.clone(), timing);
 
     response.status = Some((
@@ -1798,6 +2059,10 @@ async fn http_network_fetch(
     response.referrer = request.referrer.to_url().cloned();
     response.referrer_policy = request.referrer_policy.clone();
 
+    let har_entry = context
+        .har_recorder
+        .begin_entry(request, started, res.version(), &response);
+
     let res_body = response.body.clone();
 
     // We're about to spawn a future to be waited on here
@@ -1842,7 +2107,9 @@ async fn http_network_fetch(
     }
 
     let done_sender2 = done_sender.clone();
-    let done_sender3 = done_sender.clone();
+    let res_body3 = res_body.clone();
+    let backpressure = context.state.backpressure.clone();
+    let har_recorder = context.har_recorder.clone();
     let timing_ptr2 = context.timing.clone();
     let timing_ptr3 = context.timing.clone();
     let url1 = request.url();
@@ -1854,6 +2121,18 @@ async fn http_network_fetch(
                 warn!("Error streaming response body: {:?}", e);
                 ()
             })
+            .and_then(move |chunk| {
+                let delay = throttling.map_or(Duration::ZERO, |throttling| {
+                    throttling.download_time(chunk.len())
+                });
+                let backpressure = backpressure.clone();
+                async move {
+                    tokio::time::sleep(delay).await;
+                    // Let a saturated event loop catch up before sending it more to handle.
+                    backpressure.wait(pipeline_id).await;
+                    Ok(chunk)
+                }
+            })
             .try_fold(res_body, move |res_body, chunk| {
                 if cancellation_listener.lock().unwrap().cancelled() {
                     *res_body.lock().unwrap() = ResponseBody::Done(vec![]);
@@ -1879,7 +2158,6 @@ async fn http_network_fetch(
                     .lock()
                     .unwrap()
                     .set_attribute(ResourceAttribute::ResponseEnd);
-                let _ = done_sender2.send(Data::Done);
                 future::ready(Ok(()))
             })
             .map_err(move |_| {
@@ -1894,7 +2172,14 @@ async fn http_network_fetch(
                     .lock()
                     .unwrap()
                     .set_attribute(ResourceAttribute::ResponseEnd);
-                let _ = done_sender3.send(Data::Done);
+            })
+            .map(move |_| {
+                if let Some(har_entry) = har_entry {
+                    if let ResponseBody::Done(ref body) = *res_body3.lock().unwrap() {
+                        har_recorder.finish_entry(har_entry, body);
+                    }
+                }
+                let _ = done_sender2.send(Data::Done);
             }),
     );
 
@@ -1935,10 +2220,17 @@ async fn http_network_fetch(
     }
     context
         .state
-        .hsts_list
-        .write()
-        .unwrap()
-        .update_hsts_list_from_response(&url, &response.headers);
+        .update_hsts_list_from_response(partition.as_ref(), &url, &response.headers);
+    if request.is_navigation_request() {
+        context
+            .state
+            .partitions
+            .navigated(request.pipeline_id, &url);
+        context
+            .state
+            .accept_ch_cache
+            .update_from_response(&url, &response.headers);
+    }
 
     // TODO these steps
     // Step 16
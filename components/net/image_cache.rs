@@ -9,18 +9,23 @@ use std::{mem, thread};
 
 use embedder_traits::resources::{self, Resource};
 use imsz::imsz_from_reader;
-use ipc_channel::ipc::IpcSender;
+use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
+use lazy_static::lazy_static;
 use log::{debug, warn};
-use net_traits::image::base::{load_from_memory, Image, ImageMetadata};
+use mime::Mime;
+use net_traits::image::base::{load_from_memory, load_from_memory_at_size, Image, ImageMetadata};
 use net_traits::image_cache::{
-    CorsStatus, ImageCache, ImageCacheResult, ImageOrMetadataAvailable, ImageResponder,
-    ImageResponse, PendingImageId, PendingImageResponse, UsePlaceholder,
+    CorsStatus, FaviconCallback, ImageCache, ImageCacheResult, ImageDecoding,
+    ImageOrMetadataAvailable, ImageResponder, ImageResponse, PendingImageId, PendingImageResponse,
+    UsePlaceholder,
 };
-use net_traits::request::CorsSettings;
+use net_traits::request::{CorsSettings, RequestBuilder};
 use net_traits::{
-    FetchMetadata, FetchResponseMsg, FilteredMetadata, NetworkError, WebrenderIpcSender,
+    fetch_async, CoreResourceThread, FetchMetadata, FetchResponseMsg, FilteredMetadata,
+    NetworkError, WebrenderIpcSender,
 };
 use pixels::PixelFormat;
+use resvg::{tiny_skia, usvg};
 use servo_url::{ImmutableOrigin, ServoUrl};
 use webrender_api::units::DeviceIntSize;
 use webrender_api::{ImageData, ImageDescriptor, ImageDescriptorFlags, ImageFormat};
@@ -35,6 +40,16 @@ use webrender_api::{ImageData, ImageDescriptor, ImageDescriptorFlags, ImageForma
 ///     * For faster lookups, it might be useful to store the LoadKey in the
 ///       DOM once we have performed a first load.
 
+lazy_static! {
+    /// The threads that decode images asynchronously. They are shared by the image caches
+    /// of all the event loops in the process, so that decoding never takes every core.
+    static ref DECODE_POOL: rayon::ThreadPool = rayon::ThreadPoolBuilder::new()
+        .thread_name(|index| format!("ImageDecoder#{}", index))
+        .num_threads(thread::available_parallelism().map_or(1, |threads| threads.get()).min(4))
+        .build()
+        .unwrap();
+}
+
 // ======================================================================
 // Helper functions.
 // ======================================================================
@@ -47,6 +62,51 @@ fn decode_bytes_sync(key: LoadKey, bytes: &[u8], cors: CorsStatus) -> DecoderMsg
     }
 }
 
+fn decode_favicon(bytes: &[u8], is_svg: bool, size: u32) -> Option<Image> {
+    // Favicons are only shown by the embedder, never to web content.
+    if is_svg {
+        rasterize_svg(bytes, size, CorsStatus::Unsafe)
+    } else {
+        load_from_memory_at_size(bytes, CorsStatus::Unsafe, size)
+    }
+}
+
+/// Rasterize an SVG document so that it fits in a square of `size` device pixels.
+fn rasterize_svg(bytes: &[u8], size: u32, cors_status: CorsStatus) -> Option<Image> {
+    let tree = match usvg::Tree::from_data(bytes, &usvg::Options::default()) {
+        Ok(tree) => tree,
+        Err(error) => {
+            debug!("SVG parsing error: {:?}", error);
+            return None;
+        },
+    };
+    let svg_size = tree.size();
+    let scale = size as f32 / svg_size.width().max(svg_size.height());
+    let width = ((svg_size.width() * scale).round() as u32).max(1);
+    let height = ((svg_size.height() * scale).round() as u32).max(1);
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+    resvg::render(
+        &tree,
+        tiny_skia::Transform::from_scale(scale, scale),
+        &mut pixmap.as_mut(),
+    );
+
+    // The pixmap is premultiplied RGBA, while decoded images have straight alpha.
+    let mut bgra = Vec::with_capacity(pixmap.data().len());
+    for pixel in pixmap.pixels() {
+        let color = pixel.demultiply();
+        bgra.extend_from_slice(&[color.blue(), color.green(), color.red(), color.alpha()]);
+    }
+    Some(Image {
+        width,
+        height,
+        format: PixelFormat::BGRA8,
+        bytes: IpcSharedMemory::from_bytes(&bgra),
+        id: None,
+        cors_status,
+    })
+}
+
 fn get_placeholder_image(webrender_api: &WebrenderIpcSender, data: &[u8]) -> Arc<Image> {
     let mut image = load_from_memory(&data, CorsStatus::Unsafe).unwrap();
     set_webrender_image_key(webrender_api, &mut image);
@@ -196,6 +256,17 @@ impl CompletedLoad {
     }
 }
 
+/// A favicon that is being fetched.
+struct FaviconLoad {
+    bytes: Vec<u8>,
+    /// Whether the response is an SVG document, from its `Content-Type`.
+    is_svg: bool,
+    /// Whether the fetch failed.
+    failed: bool,
+    /// The callback to call once the favicon is decoded, taken when the response ends.
+    callback: Option<FaviconCallback>,
+}
+
 /// Message that the decoder worker threads send to the image cache.
 struct DecoderMsg {
     key: LoadKey,
@@ -333,6 +404,10 @@ struct ImageCacheStore {
     // The URL used for the placeholder image
     placeholder_url: ServoUrl,
 
+    // Favicons that have been fetched, by URL and size. Favicons that failed to load
+    // or to decode are kept as `None` so that they aren't fetched again.
+    favicons: HashMap<(ServoUrl, u32), Option<Arc<Image>>>,
+
     // Webrender API instance.
     webrender_api: WebrenderIpcSender,
 }
@@ -429,6 +504,7 @@ impl ImageCache for ImageCacheImpl {
                 completed_loads: HashMap::new(),
                 placeholder_image: get_placeholder_image(&webrender_api, &rippy_data),
                 placeholder_url: ServoUrl::parse("chrome://resources/rippy.png").unwrap(),
+                favicons: HashMap::new(),
                 webrender_api: webrender_api,
             })),
         }
@@ -455,6 +531,7 @@ impl ImageCache for ImageCacheImpl {
         origin: ImmutableOrigin,
         cors_setting: Option<CorsSettings>,
         use_placeholder: UsePlaceholder,
+        decoding: ImageDecoding,
     ) -> ImageCacheResult {
         let mut store = self.store.lock().unwrap();
         if let Some(result) = store.get_completed_image_if_available(
@@ -486,17 +563,17 @@ impl ImageCache for ImageCacheImpl {
                 .get_cached(url.clone(), origin.clone(), cors_setting);
             match result {
                 CacheResult::Hit(key, pl) => match (&pl.result, &pl.metadata) {
-                    (&Some(Ok(_)), _) => {
+                    (&Some(Ok(_)), _) if decoding == ImageDecoding::Sync => {
                         debug!("Sync decoding {} ({:?})", url, key);
                         decode_bytes_sync(key, &pl.bytes.as_slice(), pl.cors_status)
                     },
-                    (&None, &Some(ref meta)) => {
+                    (&Some(Ok(_)), &Some(ref meta)) | (&None, &Some(ref meta)) => {
                         debug!("Metadata available for {} ({:?})", url, key);
                         return ImageCacheResult::Available(
                             ImageOrMetadataAvailable::MetadataAvailable(meta.clone()),
                         );
                     },
-                    (&Some(Err(_)), _) | (&None, &None) => {
+                    (&Some(_), _) | (&None, &None) => {
                         debug!("{} ({:?}) is still pending", url, key);
                         return ImageCacheResult::Pending(key);
                     },
@@ -515,7 +592,6 @@ impl ImageCache for ImageCacheImpl {
         // In the case where a decode is ongoing (or waiting in a queue) but we
         // have the full response available, we decode the bytes synchronously
         // and ignore the async decode when it finishes later.
-        // TODO: make this behaviour configurable according to the caller's needs.
         store.handle_decoder(decoded);
         match store.get_completed_image_if_available(url, origin, cors_setting, use_placeholder) {
             Some(Ok((image, image_url))) => {
@@ -537,6 +613,7 @@ impl ImageCache for ImageCacheImpl {
         cors_setting: Option<CorsSettings>,
         sender: IpcSender<PendingImageResponse>,
         use_placeholder: UsePlaceholder,
+        decoding: ImageDecoding,
     ) -> ImageCacheResult {
         debug!("Track image for {} ({:?})", url, origin);
         let cache_result = self.get_cached_image_status(
@@ -544,6 +621,7 @@ impl ImageCache for ImageCacheImpl {
             origin.clone(),
             cors_setting,
             use_placeholder,
+            decoding,
         );
 
         match cache_result {
@@ -634,7 +712,7 @@ impl ImageCache for ImageCacheImpl {
                         };
 
                         let local_store = self.store.clone();
-                        thread::spawn(move || {
+                        DECODE_POOL.spawn(move || {
                             let msg = decode_bytes_sync(key, &*bytes, cors_status);
                             debug!("Image decoded");
                             local_store.lock().unwrap().handle_decoder(msg);
@@ -650,6 +728,67 @@ impl ImageCache for ImageCacheImpl {
             },
         }
     }
+
+    fn fetch_favicon(
+        &self,
+        request: RequestBuilder,
+        size: u32,
+        core_resource_thread: &CoreResourceThread,
+        callback: FaviconCallback,
+    ) {
+        let key = (request.url.clone(), size);
+        let cached_favicon = self.store.lock().unwrap().favicons.get(&key).cloned();
+        if let Some(favicon) = cached_favicon {
+            return callback(favicon);
+        }
+
+        debug!("Fetching favicon {}", key.0);
+        let load = Mutex::new(FaviconLoad {
+            bytes: vec![],
+            is_svg: false,
+            failed: false,
+            callback: Some(callback),
+        });
+        let store = self.store.clone();
+        fetch_async(request, core_resource_thread, move |message| {
+            let mut load = load.lock().unwrap();
+            match message {
+                FetchResponseMsg::ProcessResponse(Ok(metadata)) => {
+                    let metadata = match metadata {
+                        FetchMetadata::Unfiltered(metadata) => metadata,
+                        FetchMetadata::Filtered { unsafe_, .. } => unsafe_,
+                    };
+                    load.is_svg = metadata.content_type.map_or(false, |content_type| {
+                        Mime::from(content_type.into_inner()).essence_str() == "image/svg+xml"
+                    });
+                },
+                FetchResponseMsg::ProcessResponse(Err(_)) => load.failed = true,
+                FetchResponseMsg::ProcessResponseChunk(data) => load.bytes.extend_from_slice(&data),
+                FetchResponseMsg::ProcessResponseEOF(result) => {
+                    let callback = match load.callback.take() {
+                        Some(callback) => callback,
+                        None => return,
+                    };
+                    let succeeded = result.is_ok() && !load.failed;
+                    let bytes = mem::take(&mut load.bytes);
+                    let is_svg = load.is_svg;
+                    let store = store.clone();
+                    let key = key.clone();
+                    DECODE_POOL.spawn(move || {
+                        let favicon = if succeeded {
+                            decode_favicon(&bytes, is_svg, size).map(Arc::new)
+                        } else {
+                            None
+                        };
+                        debug!("Favicon {} decoded: {}", key.0, favicon.is_some());
+                        store.lock().unwrap().favicons.insert(key, favicon.clone());
+                        callback(favicon);
+                    });
+                },
+                FetchResponseMsg::ProcessRequestBody | FetchResponseMsg::ProcessRequestEOF => {},
+            }
+        });
+    }
 }
 
 impl ImageCacheImpl {
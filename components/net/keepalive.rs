@@ -0,0 +1,113 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The keep-alive requests, such as the beacons a document sends as it is unloaded, which
+//! outlive the document that made them. Their body is read into this process before they
+//! are sent, so that it no longer depends on the document, and the bodies a document has in
+//! flight are limited to a quota.
+//! <https://fetch.spec.whatwg.org/#request-keepalive-flag>
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use msg::constellation_msg::PipelineId;
+use net_traits::request::{
+    BodyChunkRequest, BodyChunkResponse, Request, RequestBody, KEEPALIVE_QUOTA,
+};
+use tokio::sync::mpsc::unbounded_channel;
+
+/// The bytes of the bodies of the keep-alive requests in flight, by the pipeline that made
+/// them.
+#[derive(Default)]
+pub struct KeepAliveQuota {
+    in_flight: Mutex<HashMap<Option<PipelineId>, usize>>,
+}
+
+impl KeepAliveQuota {
+    pub fn new() -> KeepAliveQuota {
+        KeepAliveQuota::default()
+    }
+
+    /// Reserve `bytes` of the quota of `pipeline` for the body of a request, returning
+    /// whether there was room for them.
+    pub fn reserve(&self, pipeline: Option<PipelineId>, bytes: usize) -> bool {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        let reserved = in_flight.entry(pipeline).or_default();
+        if *reserved + bytes > KEEPALIVE_QUOTA {
+            return false;
+        }
+        *reserved += bytes;
+        true
+    }
+
+    /// Give back the `bytes` reserved for the body of a request that is done.
+    pub fn release(&self, pipeline: Option<PipelineId>, bytes: usize) {
+        if let Entry::Occupied(mut reserved) = self.in_flight.lock().unwrap().entry(pipeline) {
+            *reserved.get_mut() = reserved.get().saturating_sub(bytes);
+            if *reserved.get() == 0 {
+                reserved.remove();
+            }
+        }
+    }
+
+    /// The bytes of the bodies of the keep-alive requests of `pipeline` in flight.
+    pub fn in_flight(&self, pipeline: Option<PipelineId>) -> usize {
+        self.in_flight
+            .lock()
+            .unwrap()
+            .get(&pipeline)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// Read the whole body of `request` from the process that made it, and serve it from this
+/// process instead. Returns the length of the body, or `None` if it could not be read, as
+/// is the case of a stream, which keep-alive requests cannot have as body.
+pub async fn adopt_body(request: &mut Request) -> Option<usize> {
+    let body = match request.body.as_ref() {
+        Some(body) => body,
+        None => return Some(0),
+    };
+    if body.source_is_null() {
+        return None;
+    }
+
+    let (sender, mut receiver) = unbounded_channel();
+    let (body_chan, body_port) = ipc::channel().ok()?;
+    ROUTER.add_route(
+        body_port.to_opaque(),
+        Box::new(move |message| {
+            let _ = sender.send(message.to::<BodyChunkResponse>().ok());
+        }),
+    );
+
+    let chunk_requester = body.take_stream();
+    let request_chunk = |message| {
+        let _ = chunk_requester.lock().unwrap().send(message);
+    };
+    request_chunk(BodyChunkRequest::Connect(body_chan));
+    request_chunk(BodyChunkRequest::Chunk);
+
+    let mut bytes = vec![];
+    loop {
+        // The route is gone, and the channel closed, if the process that made the request
+        // is gone before it sent the whole body.
+        match receiver.recv().await {
+            Some(Some(BodyChunkResponse::Chunk(mut chunk))) => {
+                bytes.append(&mut chunk);
+                request_chunk(BodyChunkRequest::Chunk);
+            },
+            Some(Some(BodyChunkResponse::Done)) => break,
+            _ => return None,
+        }
+    }
+
+    let length = bytes.len();
+    request.body = Some(RequestBody::from_bytes(bytes));
+    Some(length)
+}
@@ -4,21 +4,39 @@
 
 #![deny(unsafe_code)]
 
+/// Loading archived sites, from Web Bundles and WARC files, without the network.
+pub mod archive {
+    mod cbor;
+    pub mod store;
+    pub mod warc;
+    pub mod web_bundle;
+}
+pub mod backpressure;
+pub mod client_hints;
+pub mod connection_monitor;
 pub mod connector;
 pub mod cookie;
 pub mod cookie_storage;
 mod data_loader;
 mod decoder;
 pub mod filemanager_thread;
+pub mod har;
 mod hosts;
 pub mod hsts;
 pub mod http_cache;
 pub mod http_loader;
 pub mod image_cache;
+pub mod keepalive;
 pub mod mime_classifier;
+pub mod partitioning;
+pub mod reporting;
+pub mod request_interceptor;
 pub mod resource_thread;
+pub mod service_workers;
 mod storage_thread;
 pub mod subresource_integrity;
+pub mod throttling;
+pub mod websocket_deflate;
 mod websocket_loader;
 
 /// An implementation of the [Fetch specification](https://fetch.spec.whatwg.org/)
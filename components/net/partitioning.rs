@@ -0,0 +1,245 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The network state kept apart for each top-level site, so that a site embedded in several
+//! others cannot recognize the user across them through what the network state remembers of
+//! their past visits: its cached responses, open connections, resolved host names, HSTS
+//! entries and TLS sessions.
+//! <https://fetch.spec.whatwg.org/#network-partition-keys>
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+use http::HeaderMap;
+use hyper::{Body, Client};
+use malloc_size_of_derive::MallocSizeOf;
+use msg::constellation_msg::PipelineId;
+use net_traits::pub_domains::reg_host;
+use net_traits::request::{Destination, Request};
+use net_traits::TopLevelDocument;
+use rustls::client::Resumption;
+use serde::{Deserialize, Serialize};
+use servo_url::ServoUrl;
+
+use crate::connection_monitor::ConnectionMonitor;
+use crate::connector::{create_http_client, Connector, TlsConfig, WarmPool};
+use crate::hsts::HstsList;
+use crate::resource_thread::{read_json_from_file, write_json_to_file};
+
+/// The file in the config directory the HSTS entries of the partitions are kept in.
+const HSTS_LISTS_FILE_NAME: &str = "partitioned_hsts_list.json";
+
+/// The top-level site the network state used by a request is partitioned by.
+/// <https://fetch.spec.whatwg.org/#network-partition-key>
+///
+/// Unlike the spec's sites, the key leaves the scheme out, so that a document upgraded to
+/// HTTPS keeps using the state of the site it was requested from.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct NetworkPartitionKey(String);
+
+impl NetworkPartitionKey {
+    /// The key of the documents at `url` when they are top-level: the registrable domain of
+    /// their host, the host itself if it is an address, or their scheme if they have none.
+    pub fn for_url(url: &ServoUrl) -> NetworkPartitionKey {
+        match reg_host(url) {
+            Some(host) => NetworkPartitionKey(host.to_string()),
+            None => NetworkPartitionKey(url.scheme().to_owned()),
+        }
+    }
+}
+
+/// What keys the network state used by the requests of a pipeline.
+#[derive(Clone, Debug)]
+enum PipelinePartition {
+    /// The pipeline is top-level, so its navigation requests use the site of the URL they
+    /// are made to, and its other requests the site of the URL it last navigated to.
+    TopLevel(Option<NetworkPartitionKey>),
+    /// The pipeline is nested in a top-level document of this site.
+    Nested(NetworkPartitionKey),
+}
+
+impl PipelinePartition {
+    fn key(&self) -> Option<&NetworkPartitionKey> {
+        match self {
+            PipelinePartition::TopLevel(key) => key.as_ref(),
+            PipelinePartition::Nested(key) => Some(key),
+        }
+    }
+}
+
+/// The network state kept apart for the requests of a top-level site, for as long as a
+/// pipeline uses it.
+pub struct PartitionState {
+    /// The client opening and reusing the connections, with TLS sessions of its own.
+    pub client: Client<Connector, Body>,
+    /// The host names resolved and the connections opened for resource hints.
+    pub warm_pool: WarmPool,
+}
+
+/// The top-level sites of the pipelines, and the network state kept for each of them.
+pub struct NetworkPartitions {
+    pipelines: RwLock<HashMap<PipelineId, PipelinePartition>>,
+    states: Mutex<HashMap<NetworkPartitionKey, Arc<PartitionState>>>,
+    /// The hosts that asked the requests of each site to only reach them securely, on top of
+    /// the preloaded ones. Unlike the rest of the state, these are kept once no pipeline
+    /// uses the site anymore, since forgetting them would let the next visit be downgraded.
+    /// They are not merged into the global list either, as a host could then tell whether
+    /// it was visited under another site from whether its requests were upgraded.
+    hsts_lists: RwLock<HashMap<NetworkPartitionKey, HstsList>>,
+    tls_config: TlsConfig,
+    connection_monitor: ConnectionMonitor,
+}
+
+impl NetworkPartitions {
+    /// Partitions whose clients use `tls_config`, with sessions of their own, and tell
+    /// `connection_monitor` about the connections they open.
+    pub fn new(tls_config: TlsConfig, connection_monitor: ConnectionMonitor) -> NetworkPartitions {
+        NetworkPartitions {
+            pipelines: Default::default(),
+            states: Default::default(),
+            hsts_lists: Default::default(),
+            tls_config,
+            connection_monitor,
+        }
+    }
+
+    /// Partition the network state used by the requests of `pipeline` by the site of
+    /// `top_level_document`, or forget about the pipeline.
+    pub fn set(&self, pipeline: PipelineId, top_level_document: Option<TopLevelDocument>) {
+        let mut pipelines = self.pipelines.write().unwrap();
+        match top_level_document {
+            Some(TopLevelDocument::Itself) => {
+                pipelines.insert(pipeline, PipelinePartition::TopLevel(None));
+            },
+            Some(TopLevelDocument::At(url)) => {
+                pipelines.insert(
+                    pipeline,
+                    PipelinePartition::Nested(NetworkPartitionKey::for_url(&url)),
+                );
+            },
+            None => {
+                pipelines.remove(&pipeline);
+                self.evict_unused_states(&pipelines);
+            },
+        }
+    }
+
+    /// Remember that the top-level `pipeline` navigated to `url`, after any redirects, so
+    /// that the requests of its document use the site of `url`.
+    pub fn navigated(&self, pipeline: Option<PipelineId>, url: &ServoUrl) {
+        let pipeline = match pipeline {
+            Some(pipeline) => pipeline,
+            None => return,
+        };
+        let mut pipelines = self.pipelines.write().unwrap();
+        if let Some(PipelinePartition::TopLevel(key)) = pipelines.get_mut(&pipeline) {
+            *key = Some(NetworkPartitionKey::for_url(url));
+            self.evict_unused_states(&pipelines);
+        }
+    }
+
+    /// Drop the network state of the sites no pipeline uses anymore, closing their
+    /// connections. The state of a site navigated to again starts afresh.
+    fn evict_unused_states(&self, pipelines: &HashMap<PipelineId, PipelinePartition>) {
+        self.states.lock().unwrap().retain(|key, _| {
+            pipelines
+                .values()
+                .any(|partition| partition.key() == Some(key))
+        });
+    }
+
+    /// The partition of the network state `request` uses, if its pipeline has one.
+    /// <https://fetch.spec.whatwg.org/#request-determine-the-network-partition-key>
+    pub fn key_for_request(&self, request: &Request) -> Option<NetworkPartitionKey> {
+        let pipeline = request.pipeline_id?;
+        match self.pipelines.read().unwrap().get(&pipeline)? {
+            PipelinePartition::TopLevel(_) if request.destination == Destination::Document => {
+                Some(NetworkPartitionKey::for_url(&request.current_url()))
+            },
+            PipelinePartition::TopLevel(key) => key.clone(),
+            PipelinePartition::Nested(key) => Some(key.clone()),
+        }
+    }
+
+    /// The partition of the network state the requests of `pipeline` that are not
+    /// navigations use, if it has one.
+    pub fn key_for_pipeline(&self, pipeline: Option<PipelineId>) -> Option<NetworkPartitionKey> {
+        self.pipelines
+            .read()
+            .unwrap()
+            .get(&pipeline?)?
+            .key()
+            .cloned()
+    }
+
+    /// The network state of the partition `key`, starting a fresh one on first use.
+    pub fn state(&self, key: &NetworkPartitionKey) -> Arc<PartitionState> {
+        self.states
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_insert_with(|| {
+                let mut tls_config = self.tls_config.clone();
+                tls_config.resumption = Resumption::default();
+                let warm_pool = WarmPool::new();
+                Arc::new(PartitionState {
+                    client: create_http_client(
+                        tls_config,
+                        warm_pool.clone(),
+                        self.connection_monitor.clone(),
+                    ),
+                    warm_pool,
+                })
+            })
+            .clone()
+    }
+
+    /// The network state of every partition used so far.
+    pub fn states(&self) -> Vec<Arc<PartitionState>> {
+        self.states.lock().unwrap().values().cloned().collect()
+    }
+
+    /// Upgrade `url` if its host asked the requests of the partition `key` to only reach it
+    /// securely.
+    pub fn apply_hsts_rules(&self, key: &NetworkPartitionKey, url: &mut ServoUrl) {
+        if let Some(hsts_list) = self.hsts_lists.read().unwrap().get(key) {
+            hsts_list.apply_hsts_rules(url);
+        }
+    }
+
+    /// Remember if the host of `url` asks the requests of the partition `key` to only reach
+    /// it securely.
+    pub fn update_hsts_list_from_response(
+        &self,
+        key: &NetworkPartitionKey,
+        url: &ServoUrl,
+        headers: &HeaderMap,
+    ) {
+        let mut hsts_lists = self.hsts_lists.write().unwrap();
+        let hsts_list = hsts_lists.entry(key.clone()).or_insert_with(HstsList::new);
+        hsts_list.update_hsts_list_from_response(url, headers);
+        if hsts_list.entries_map.is_empty() {
+            hsts_lists.remove(key);
+        }
+    }
+
+    /// Read the HSTS entries of the partitions saved in `config_dir`.
+    pub fn read_hsts_lists(&self, config_dir: &Path) {
+        read_json_from_file(
+            &mut *self.hsts_lists.write().unwrap(),
+            config_dir,
+            HSTS_LISTS_FILE_NAME,
+        );
+    }
+
+    /// Save the HSTS entries of the partitions in `config_dir`.
+    pub fn write_hsts_lists(&self, config_dir: &Path) {
+        write_json_to_file(
+            &*self.hsts_lists.read().unwrap(),
+            config_dir,
+            HSTS_LISTS_FILE_NAME,
+        );
+    }
+}
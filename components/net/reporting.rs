@@ -0,0 +1,272 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The delivery of the reports that documents and workers queue to their endpoints. The
+//! reports queued for an endpoint shortly after one another are sent in one request, and the
+//! ones whose delivery failed are sent again later, waiting longer after each failure, until
+//! they were tried too many times.
+//! <https://w3c.github.io/reporting/#delivery>
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use http::header::{HeaderValue, CONTENT_TYPE};
+use http::{HeaderMap, Method, StatusCode};
+use msg::constellation_msg::PipelineId;
+use net_traits::reporting::Report;
+use net_traits::request::{
+    CredentialsMode, Destination, Referrer, Request, RequestBody, RequestBuilder, RequestMode,
+};
+use net_traits::response::Response;
+use net_traits::FetchTaskTarget;
+use serde_json::{json, Value};
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+use crate::fetch::methods::{fetch, FetchContext};
+
+/// How long a report waits for the ones queued after it, to be sent along with them.
+const BATCHING_DELAY: Duration = Duration::from_secs(1);
+
+/// How long the delivery of reports waits after their first failure, doubled after each
+/// other one.
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// How many times the delivery of a report is tried before it is dropped.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// The most reports queued for an endpoint, past which the oldest ones are dropped.
+const MAX_QUEUED_REPORTS: usize = 100;
+
+struct QueuedReport {
+    report: Report,
+    /// How many times its delivery was tried.
+    attempts: u32,
+    /// When it is to be delivered.
+    due: Instant,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// The reports queued for each endpoint, by the origin that sends them.
+    endpoints: HashMap<(ServoUrl, ImmutableOrigin), Vec<QueuedReport>>,
+    /// Whether a task is delivering the reports as they become due.
+    delivering: bool,
+}
+
+/// The reports waiting to be delivered to their endpoints.
+#[derive(Default)]
+pub struct ReportingQueue {
+    state: Mutex<QueueState>,
+    /// The endpoints that the crash reports of some pipelines are sent to.
+    crash_report_endpoints: Mutex<HashMap<PipelineId, ServoUrl>>,
+}
+
+/// The reports that an origin sends to an endpoint in one request.
+pub struct ReportBatch {
+    pub endpoint: ServoUrl,
+    pub origin: ImmutableOrigin,
+    reports: Vec<QueuedReport>,
+}
+
+impl ReportBatch {
+    pub fn reports(&self) -> impl Iterator<Item = &Report> {
+        self.reports.iter().map(|queued| &queued.report)
+    }
+
+    /// The body of the request delivering the reports at `now`.
+    /// <https://w3c.github.io/reporting/#serialize-a-list-of-reports-to-json>
+    pub fn serialize(&self, now: SystemTime) -> Vec<u8> {
+        let now = now
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let reports: Vec<Value> = self
+            .reports()
+            .map(|report| {
+                json!({
+                    "age": now.saturating_sub(report.timestamp),
+                    "type": report.type_,
+                    "url": report.url.as_str(),
+                    "user_agent": report.user_agent,
+                    "body": serde_json::from_str::<Value>(&report.body).unwrap_or(Value::Null),
+                })
+            })
+            .collect();
+        serde_json::to_vec(&reports).unwrap_or_default()
+    }
+}
+
+impl ReportingQueue {
+    pub fn new() -> ReportingQueue {
+        ReportingQueue::default()
+    }
+
+    /// Queue `report` to be delivered to `endpoint` with the other reports queued for it
+    /// shortly before or after `now`. Returns whether the task delivering the reports has to
+    /// be started, as it stops once there are none left.
+    pub fn queue(&self, report: Report, endpoint: ServoUrl, now: Instant) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let reports = state
+            .endpoints
+            .entry((endpoint, report.origin()))
+            .or_default();
+        if reports.len() >= MAX_QUEUED_REPORTS {
+            reports.remove(0);
+        }
+        // Join the batch of the reports queued before, unless they are only waiting to be
+        // tried again.
+        let due = reports
+            .iter()
+            .find(|queued| queued.attempts == 0)
+            .map_or(now + BATCHING_DELAY, |queued| queued.due);
+        reports.push(QueuedReport {
+            report,
+            attempts: 0,
+            due,
+        });
+        !std::mem::replace(&mut state.delivering, true)
+    }
+
+    /// Take the reports due at `now`, in one batch for each endpoint and origin.
+    pub fn take_due(&self, now: Instant) -> Vec<ReportBatch> {
+        let mut state = self.state.lock().unwrap();
+        let mut batches = vec![];
+        for ((endpoint, origin), reports) in state.endpoints.iter_mut() {
+            let (due, later): (Vec<_>, Vec<_>) =
+                reports.drain(..).partition(|queued| queued.due <= now);
+            *reports = later;
+            if !due.is_empty() {
+                batches.push(ReportBatch {
+                    endpoint: endpoint.clone(),
+                    origin: origin.clone(),
+                    reports: due,
+                });
+            }
+        }
+        state.endpoints.retain(|_, reports| !reports.is_empty());
+        batches
+    }
+
+    /// Queue the reports of a batch whose delivery failed at `now` again, to be tried after a
+    /// delay that doubles with each attempt, dropping the ones tried too many times.
+    pub fn retry(&self, batch: ReportBatch, now: Instant) {
+        let mut state = self.state.lock().unwrap();
+        let reports = state
+            .endpoints
+            .entry((batch.endpoint, batch.origin))
+            .or_default();
+        let retried = batch.reports.into_iter().filter_map(|mut queued| {
+            queued.attempts += 1;
+            if queued.attempts >= MAX_ATTEMPTS {
+                return None;
+            }
+            queued.due = now + FIRST_RETRY_DELAY * 2u32.pow(queued.attempts - 1);
+            Some(queued)
+        });
+        // The reports queued meanwhile come after the ones tried before them.
+        let queued_meanwhile = std::mem::take(reports);
+        reports.extend(retried);
+        reports.extend(queued_meanwhile);
+        let excess = reports.len().saturating_sub(MAX_QUEUED_REPORTS);
+        reports.drain(..excess);
+        state.endpoints.retain(|_, reports| !reports.is_empty());
+    }
+
+    /// When the next reports are due, or `None` if there are none left, in which case the
+    /// task delivering them stops until more are queued.
+    pub fn next_delivery(&self) -> Option<Instant> {
+        let mut state = self.state.lock().unwrap();
+        let next = state
+            .endpoints
+            .values()
+            .flatten()
+            .map(|queued| queued.due)
+            .min();
+        if next.is_none() {
+            state.delivering = false;
+        }
+        next
+    }
+
+    /// Send the crash reports of `pipeline` to `endpoint`, or stop sending them.
+    pub fn set_crash_report_endpoint(&self, pipeline: PipelineId, endpoint: Option<ServoUrl>) {
+        let mut endpoints = self.crash_report_endpoints.lock().unwrap();
+        match endpoint {
+            Some(endpoint) => endpoints.insert(pipeline, endpoint),
+            None => endpoints.remove(&pipeline),
+        };
+    }
+
+    /// The endpoint the crash report of `pipeline` is sent to, which is forgotten as the
+    /// pipeline crashes only once.
+    pub fn take_crash_report_endpoint(&self, pipeline: PipelineId) -> Option<ServoUrl> {
+        self.crash_report_endpoints
+            .lock()
+            .unwrap()
+            .remove(&pipeline)
+    }
+}
+
+/// Deliver the queued reports as they become due, until there are none left.
+pub async fn deliver_reports(context: FetchContext) {
+    let queue = &context.state.reporting_queue;
+    while let Some(due) = queue.next_delivery() {
+        tokio::time::sleep_until(due.into()).await;
+        for batch in queue.take_due(Instant::now()) {
+            if !deliver(&batch, &context).await {
+                queue.retry(batch, Instant::now());
+            }
+        }
+    }
+}
+
+/// Send the reports of `batch` to their endpoint, returning whether they were delivered.
+/// <https://w3c.github.io/reporting/#try-delivery>
+async fn deliver(batch: &ReportBatch, context: &FetchContext) -> bool {
+    let body = batch.serialize(SystemTime::now());
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static("application/reports+json"),
+    );
+    let mut request = RequestBuilder::new(batch.endpoint.clone(), Referrer::NoReferrer)
+        .method(Method::POST)
+        .headers(headers)
+        .unsafe_request(true)
+        .body(Some(RequestBody::from_bytes(body)))
+        .destination(Destination::Report)
+        .mode(RequestMode::CorsMode)
+        .credentials_mode(CredentialsMode::CredentialsSameOrigin)
+        .origin(batch.origin.clone())
+        .build();
+    let mut outcome = DeliveryOutcome { delivered: false };
+    fetch(&mut request, &mut outcome, context).await;
+    outcome.delivered
+}
+
+/// Whether the endpoint took the reports. An endpoint that is gone takes them too, as there
+/// is no point in sending them again.
+struct DeliveryOutcome {
+    delivered: bool,
+}
+
+impl FetchTaskTarget for DeliveryOutcome {
+    fn process_request_body(&mut self, _: &Request) {}
+
+    fn process_request_eof(&mut self, _: &Request) {}
+
+    fn process_response(&mut self, _: &Response) {}
+
+    fn process_response_chunk(&mut self, _: Vec<u8>) {}
+
+    fn process_response_eof(&mut self, response: &Response) {
+        if response.is_network_error() {
+            return;
+        }
+        self.delivered = response.status.as_ref().map_or(false, |(status, _)| {
+            status.is_success() || *status == StatusCode::GONE
+        });
+    }
+}
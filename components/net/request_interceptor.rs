@@ -0,0 +1,176 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Letting the embedder answer, redirect or block network requests before they are sent.
+
+use std::mem;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use embedder_traits::{
+    EmbedderMsg, EmbedderProxy, HttpBodyData, WebResourceRequest, WebResourceResponseMsg,
+};
+use http::header::{self, HeaderValue};
+use http::{HeaderMap, StatusCode};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use log::warn;
+use net_traits::request::{Destination, Request};
+use net_traits::response::{HttpsState, Response, ResponseBody};
+use net_traits::{NetworkError, ResourceAttribute};
+use servo_url::ServoUrl;
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::fetch::methods::{Data, DoneChannel, FetchContext};
+use crate::http_loader::HANDLE;
+
+pub struct RequestInterceptor {
+    embedder_proxy: EmbedderProxy,
+    /// Whether the embedder asked to be told about requests.
+    enabled: AtomicBool,
+}
+
+impl RequestInterceptor {
+    pub fn new(embedder_proxy: EmbedderProxy) -> RequestInterceptor {
+        RequestInterceptor {
+            embedder_proxy,
+            enabled: AtomicBool::new(false),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Ask the embedder what to do with `request`, which is about to be sent to the network.
+    /// Returns the response to use in place of the network one, if the embedder supplied
+    /// one, redirected the request or blocked it.
+    pub async fn intercept_request(
+        &self,
+        request: &Request,
+        done_chan: &mut DoneChannel,
+        context: &FetchContext,
+    ) -> Option<Response> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let url = request.current_url();
+        let web_resource_request = WebResourceRequest {
+            method: request.method.clone(),
+            headers: request.headers.clone(),
+            url: url.clone(),
+            is_for_main_frame: request.destination == Destination::Document,
+            is_redirect: request.redirect_count > 0,
+        };
+        let (sender, receiver) = ipc::channel().unwrap();
+        self.embedder_proxy.send((
+            None,
+            EmbedderMsg::WebResourceRequested(web_resource_request, sender),
+        ));
+
+        // The embedder answers whenever it likes, so wait for it without blocking the thread.
+        let (message_sender, mut message_receiver) = unbounded_channel();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let _ = message_sender.send(message.to::<WebResourceResponseMsg>());
+            }),
+        );
+
+        let web_resource_response = match message_receiver.recv().await {
+            Some(Ok(WebResourceResponseMsg::Start(response))) => response,
+            Some(Ok(WebResourceResponseMsg::Redirect(location))) => {
+                return Some(redirect_response(url, location, context));
+            },
+            Some(Ok(WebResourceResponseMsg::Block)) => {
+                return Some(Response::network_error(NetworkError::Internal(
+                    "Request blocked by the embedder".into(),
+                )));
+            },
+            Some(Ok(WebResourceResponseMsg::Body(_))) => {
+                warn!("Received a response body before the response from the embedder");
+                return Some(Response::network_error(NetworkError::Internal(
+                    "Invalid response from the embedder".into(),
+                )));
+            },
+            // The embedder let the request through, or went away without answering.
+            Some(Ok(WebResourceResponseMsg::None)) | Some(Err(_)) | None => return None,
+        };
+
+        let timing = context.timing.lock().unwrap().clone();
+        let mut response = Response::new(url.clone(), timing);
+        response.status = Some((
+            web_resource_response.status_code,
+            web_resource_response.status_message.clone(),
+        ));
+        response.raw_status = Some((
+            web_resource_response.status_code.as_u16(),
+            web_resource_response.status_message.into_bytes(),
+        ));
+        response.headers = web_resource_response.headers;
+        response.referrer = request.referrer.to_url().cloned();
+        response.referrer_policy = request.referrer_policy.clone();
+        response.https_state = match url.scheme() {
+            "https" => HttpsState::Modern,
+            _ => HttpsState::None,
+        };
+        *response.body.lock().unwrap() = ResponseBody::Receiving(vec![]);
+
+        let (done_sender, done_receiver) = unbounded_channel();
+        *done_chan = Some((done_sender.clone(), done_receiver));
+        let res_body = response.body.clone();
+        let cancellation_listener = context.cancellation_listener.clone();
+        let timing = context.timing.clone();
+        HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+            let data = loop {
+                if cancellation_listener.lock().unwrap().cancelled() {
+                    break Data::Cancelled;
+                }
+                match message_receiver.recv().await {
+                    Some(Ok(WebResourceResponseMsg::Body(HttpBodyData::Chunk(bytes)))) => {
+                        if let ResponseBody::Receiving(ref mut body) = *res_body.lock().unwrap() {
+                            body.extend_from_slice(&bytes);
+                        }
+                        let _ = done_sender.send(Data::Payload(bytes));
+                    },
+                    Some(Ok(WebResourceResponseMsg::Body(HttpBodyData::Done))) => break Data::Done,
+                    // The embedder cancelled the response, or went away before finishing it.
+                    _ => break Data::Cancelled,
+                }
+            };
+            let mut body = res_body.lock().unwrap();
+            let completed_body = match *body {
+                ResponseBody::Receiving(ref mut body) => mem::take(body),
+                _ => vec![],
+            };
+            *body = ResponseBody::Done(completed_body);
+            timing
+                .lock()
+                .unwrap()
+                .set_attribute(ResourceAttribute::ResponseEnd);
+            let _ = done_sender.send(data);
+        });
+
+        Some(response)
+    }
+}
+
+/// A response redirecting the request to `location`, so that the new URL is fetched and
+/// checked as if the server had asked for it.
+fn redirect_response(url: ServoUrl, location: ServoUrl, context: &FetchContext) -> Response {
+    let timing = context.timing.lock().unwrap().clone();
+    let mut response = Response::new(url, timing);
+    let status = StatusCode::TEMPORARY_REDIRECT;
+    let reason = status.canonical_reason().unwrap_or("");
+    response.status = Some((status, reason.into()));
+    response.raw_status = Some((status.as_u16(), reason.into()));
+    let mut headers = HeaderMap::new();
+    if let Ok(location) = HeaderValue::from_str(location.as_str()) {
+        headers.insert(header::LOCATION, location);
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    response.headers = headers;
+    *response.body.lock().unwrap() = ResponseBody::Done(vec![]);
+    response
+}
@@ -6,30 +6,36 @@
 
 use std::borrow::{Cow, ToOwned};
 use std::collections::HashMap;
-use std::fs::File;
+use std::fs::{self, File};
+use std::future::Future;
 use std::io::prelude::*;
 use std::io::{self, BufReader};
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crossbeam_channel::Sender;
 use devtools_traits::DevtoolsControlMsg;
-use embedder_traits::EmbedderProxy;
+use embedder_traits::{EmbedderMsg, EmbedderProxy, NetworkChange};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcReceiver, IpcReceiverSet, IpcSender};
 use log::{debug, warn};
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
+use msg::constellation_msg::PipelineId;
 use net_traits::blob_url_store::parse_blob_url;
+use net_traits::file_system_thread::FileSystemThreadMsg;
 use net_traits::filemanager_thread::FileTokenCheck;
-use net_traits::request::{Destination, RequestBuilder};
+use net_traits::indexeddb_thread::IndexedDBThreadMsg;
+use net_traits::reporting::Report as ReportingReport;
+use net_traits::request::{Destination, Initiator, Request, RequestBuilder, RequestPriority};
 use net_traits::response::{Response, ResponseInit};
 use net_traits::storage_thread::StorageThreadMsg;
 use net_traits::{
-    CookieSource, CoreResourceMsg, CoreResourceThread, CustomResponseMediator, DiscardFetch,
-    FetchChannels, FetchTaskTarget, ResourceFetchTiming, ResourceThreads, ResourceTimingType,
+    CookieSource, CoreResourceMsg, CoreResourceThread, DiscardFetch, FetchChannels,
+    FetchTaskTarget, NetworkError, ResourceFetchTiming, ResourceThreads, ResourceTimingType,
     WebSocketDomAction, WebSocketNetworkEvent,
 };
 use profile_traits::mem::{ProfilerChan as MemProfilerChan, Report, ReportKind, ReportsChan};
@@ -38,19 +44,36 @@ use profile_traits::time::ProfilerChan;
 use rustls::RootCertStore;
 use serde::{Deserialize, Serialize};
 use servo_arc::Arc as ServoArc;
-use servo_url::{ImmutableOrigin, ServoUrl};
+use servo_config::pref;
+use servo_url::ServoUrl;
+use storage::file_system::FileSystemThreadFactory;
+use storage::indexeddb::IndexedDBThreadFactory;
+use storage::quota::QuotaManager;
+use tokio::sync::Notify;
 
+use crate::archive::store::ArchiveStore;
+use crate::backpressure::Backpressure;
+use crate::client_hints::{AcceptChCache, UserAgentOverrides};
+use crate::connection_monitor::ConnectionMonitor;
 use crate::connector::{
     create_http_client, create_tls_config, CACertificates, CertificateErrorOverrideManager,
+    WarmPool,
 };
 use crate::cookie_storage::CookieStorage;
 use crate::fetch::cors_cache::CorsCache;
 use crate::fetch::methods::{fetch, CancellationListener, FetchContext};
 use crate::filemanager_thread::FileManager;
+use crate::har::HarRecorder;
 use crate::hsts::HstsList;
 use crate::http_cache::HttpCache;
 use crate::http_loader::{http_redirect_fetch, HttpState, HANDLE};
+use crate::keepalive::{adopt_body, KeepAliveQuota};
+use crate::partitioning::{NetworkPartitionKey, NetworkPartitions};
+use crate::reporting::{deliver_reports, ReportingQueue};
+use crate::request_interceptor::RequestInterceptor;
+use crate::service_workers::ServiceWorkers;
 use crate::storage_thread::StorageThreadFactory;
+use crate::throttling::NetworkThrottler;
 use crate::{cookie, websocket_loader};
 
 /// Load a file with CA certificate and produce a RootCertStore with the results.
@@ -95,10 +118,26 @@ pub fn new_resource_threads(
         ca_certificates,
         ignore_certificate_errors,
     );
-    let storage: IpcSender<StorageThreadMsg> = StorageThreadFactory::new(config_dir);
+    const MEGABYTE: u64 = 1024 * 1024;
+    let quota = Arc::new(QuotaManager::new(
+        config_dir.clone(),
+        pref!(storage.quota.origin_mb).max(0) as u64 * MEGABYTE,
+        pref!(storage.quota.total_mb).max(0) as u64 * MEGABYTE,
+    ));
+    let storage: IpcSender<StorageThreadMsg> =
+        StorageThreadFactory::new(config_dir.clone(), quota.clone());
+    let idb: IpcSender<IndexedDBThreadMsg> =
+        IndexedDBThreadFactory::new(config_dir.clone(), quota.clone());
+    let file_system: IpcSender<FileSystemThreadMsg> =
+        FileSystemThreadFactory::new(config_dir, quota);
     (
-        ResourceThreads::new(public_core, storage.clone()),
-        ResourceThreads::new(private_core, storage),
+        ResourceThreads::new(
+            public_core,
+            storage.clone(),
+            idb.clone(),
+            file_system.clone(),
+        ),
+        ResourceThreads::new(private_core, storage, idb, file_system),
     )
 }
 
@@ -158,6 +197,7 @@ fn create_http_states(
     config_dir: Option<&Path>,
     ca_certificates: CACertificates,
     ignore_certificate_errors: bool,
+    connection_monitor: &ConnectionMonitor,
 ) -> (Arc<HttpState>, Arc<HttpState>) {
     let mut hsts_list = HstsList::from_servo_preload();
     let mut auth_cache = AuthCache::new();
@@ -170,6 +210,12 @@ fn create_http_states(
     }
 
     let override_manager = CertificateErrorOverrideManager::new();
+    let warm_pool = WarmPool::new();
+    let tls_config = create_tls_config(
+        ca_certificates.clone(),
+        ignore_certificate_errors,
+        override_manager.clone(),
+    );
     let http_state = HttpState {
         hsts_list: RwLock::new(hsts_list),
         cookie_jar: RwLock::new(cookie_jar),
@@ -177,15 +223,35 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(http_cache),
         http_cache_state: Mutex::new(HashMap::new()),
-        client: create_http_client(create_tls_config(
-            ca_certificates.clone(),
-            ignore_certificate_errors,
-            override_manager.clone(),
-        )),
+        client: create_http_client(
+            tls_config.clone(),
+            warm_pool.clone(),
+            connection_monitor.clone(),
+        ),
         override_manager,
+        warm_pool,
+        connection_monitor: connection_monitor.clone(),
+        work_offline: AtomicBool::new(false),
+        throttler: NetworkThrottler::new(),
+        backpressure: Backpressure::new(),
+        service_workers: ServiceWorkers::new(),
+        accept_ch_cache: AcceptChCache::new(),
+        user_agent_overrides: UserAgentOverrides::new(),
+        partitions: NetworkPartitions::new(tls_config, connection_monitor.clone()),
+        reporting_queue: ReportingQueue::new(),
+        keepalive_quota: KeepAliveQuota::new(),
     };
+    if let Some(config_dir) = config_dir {
+        http_state.partitions.read_hsts_lists(config_dir);
+    }
 
     let override_manager = CertificateErrorOverrideManager::new();
+    let warm_pool = WarmPool::new();
+    let tls_config = create_tls_config(
+        ca_certificates,
+        ignore_certificate_errors,
+        override_manager.clone(),
+    );
     let private_http_state = HttpState {
         hsts_list: RwLock::new(HstsList::from_servo_preload()),
         cookie_jar: RwLock::new(CookieStorage::new(150)),
@@ -193,12 +259,23 @@ fn create_http_states(
         history_states: RwLock::new(HashMap::new()),
         http_cache: RwLock::new(HttpCache::new()),
         http_cache_state: Mutex::new(HashMap::new()),
-        client: create_http_client(create_tls_config(
-            ca_certificates,
-            ignore_certificate_errors,
-            override_manager.clone(),
-        )),
+        client: create_http_client(
+            tls_config.clone(),
+            warm_pool.clone(),
+            connection_monitor.clone(),
+        ),
         override_manager,
+        warm_pool,
+        connection_monitor: connection_monitor.clone(),
+        work_offline: AtomicBool::new(false),
+        throttler: NetworkThrottler::new(),
+        backpressure: Backpressure::new(),
+        service_workers: ServiceWorkers::new(),
+        accept_ch_cache: AcceptChCache::new(),
+        user_agent_overrides: UserAgentOverrides::new(),
+        partitions: NetworkPartitions::new(tls_config, connection_monitor.clone()),
+        reporting_queue: ReportingQueue::new(),
+        keepalive_quota: KeepAliveQuota::new(),
     };
 
     (Arc::new(http_state), Arc::new(private_http_state))
@@ -216,6 +293,7 @@ impl ResourceChannelManager {
             self.config_dir.as_ref().map(Deref::deref),
             self.ca_certificates.clone(),
             self.ignore_certificate_errors,
+            &self.resource_manager.connection_monitor,
         );
 
         let mut rx_set = IpcReceiverSet::new().unwrap();
@@ -332,10 +410,40 @@ impl ResourceChannelManager {
                     .send(cookie_jar.cookies_for_url(&url, source))
                     .unwrap();
             },
-            CoreResourceMsg::NetworkMediator(mediator_chan, origin) => {
+            CoreResourceMsg::QueueReport(report, endpoint) => self
+                .resource_manager
+                .queue_report(report, endpoint, http_state),
+            CoreResourceMsg::SetCrashReportEndpoint(pipeline, endpoint) => {
+                http_state
+                    .reporting_queue
+                    .set_crash_report_endpoint(pipeline, endpoint);
+            },
+            CoreResourceMsg::QueueCrashReport(pipeline, url) => {
+                let endpoint = match http_state
+                    .reporting_queue
+                    .take_crash_report_endpoint(pipeline)
+                {
+                    Some(endpoint) => endpoint,
+                    None => return true,
+                };
+                // https://wicg.github.io/crash-reporting/#crash-report
+                let report = ReportingReport {
+                    type_: "crash".to_owned(),
+                    url,
+                    user_agent: self.resource_manager.user_agent.to_string(),
+                    body: "{}".to_owned(),
+                    timestamp: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64,
+                };
                 self.resource_manager
-                    .sw_managers
-                    .insert(origin, mediator_chan);
+                    .queue_report(report, endpoint, http_state);
+            },
+            CoreResourceMsg::NetworkMediator(mediator_chan, origin) => {
+                http_state
+                    .service_workers
+                    .set_manager(origin, mediator_chan);
             },
             CoreResourceMsg::GetCookiesDataForUrl(url, consumer, source) => {
                 let mut cookie_jar = http_state.cookie_jar.write().unwrap();
@@ -368,6 +476,94 @@ impl ResourceChannelManager {
             CoreResourceMsg::ClearCache => {
                 http_state.http_cache.write().unwrap().clear();
             },
+            CoreResourceMsg::InterceptRequests(enabled) => {
+                self.resource_manager
+                    .request_interceptor
+                    .set_enabled(enabled);
+            },
+            CoreResourceMsg::DnsPrefetch(url, pipeline) => {
+                let partition = http_state.pipeline_network_partition_key(Some(pipeline));
+                let warm_pool = http_state.warm_pool_for(partition.as_ref());
+                HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+                    warm_pool.prefetch_dns(&url).await;
+                });
+            },
+            CoreResourceMsg::Preconnect(url, pipeline) => {
+                let partition = http_state.pipeline_network_partition_key(Some(pipeline));
+                let warm_pool = http_state.warm_pool_for(partition.as_ref());
+                HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+                    warm_pool.preconnect(&url).await;
+                });
+            },
+            CoreResourceMsg::NetworkChanged(change) => {
+                self.resource_manager
+                    .connection_monitor
+                    .network_changed(change);
+                let warm_pools = std::iter::once(http_state.warm_pool.clone()).chain(
+                    http_state
+                        .partitions
+                        .states()
+                        .into_iter()
+                        .map(|state| state.warm_pool.clone()),
+                );
+                for warm_pool in warm_pools {
+                    let hosts = warm_pool.clear();
+                    if change == NetworkChange::Down {
+                        continue;
+                    }
+                    // The hosts prefetched may resolve to other addresses on the new network.
+                    HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+                        for host in hosts {
+                            warm_pool.resolve(host).await;
+                        }
+                    });
+                }
+            },
+            CoreResourceMsg::SetWorkOffline(work_offline) => {
+                http_state
+                    .work_offline
+                    .store(work_offline, Ordering::Relaxed);
+            },
+            CoreResourceMsg::ThrottleNetwork(pipelines, throttling) => {
+                http_state.throttler.set(pipelines, throttling);
+            },
+            CoreResourceMsg::SetBackpressure(pipelines, saturated) => {
+                http_state.backpressure.set(pipelines, saturated);
+            },
+            CoreResourceMsg::OverrideUserAgent(pipelines, user_agent_override) => {
+                http_state
+                    .user_agent_overrides
+                    .set(pipelines, user_agent_override);
+            },
+            CoreResourceMsg::SetNetworkPartition(pipeline, top_level_document) => {
+                http_state.partitions.set(pipeline, top_level_document);
+            },
+            CoreResourceMsg::CountFetchesInProgress(pipelines, sender) => {
+                let count = self
+                    .resource_manager
+                    .network_activity
+                    .fetches_in_progress(&pipelines);
+                let _ = sender.send(count);
+            },
+            CoreResourceMsg::ReportConnectionPool => {
+                let report = self.resource_manager.connection_monitor.report();
+                self.resource_manager
+                    .embedder_proxy
+                    .send((None, EmbedderMsg::ReportConnectionPool(report)));
+            },
+            CoreResourceMsg::StartHarRecording(include_bodies) => {
+                self.resource_manager.har_recorder.start(include_bodies);
+            },
+            CoreResourceMsg::StopHarRecording(path) => {
+                match self.resource_manager.har_recorder.stop() {
+                    Some(har) => {
+                        if let Err(error) = fs::write(&path, har) {
+                            warn!("Failed to write HAR file {:?} ({}).", path, error);
+                        }
+                    },
+                    None => warn!("Stopping a HAR recording that was not started."),
+                }
+            },
             CoreResourceMsg::ToFileManager(msg) => self.resource_manager.filemanager.handle(msg),
             CoreResourceMsg::Exit(sender) => {
                 if let Some(ref config_dir) = self.config_dir {
@@ -385,6 +581,7 @@ impl ResourceChannelManager {
                         Ok(hsts) => write_json_to_file(&*hsts, config_dir, "hsts_list.json"),
                         Err(_) => warn!("Error writing hsts list to disk"),
                     }
+                    http_state.partitions.write_hsts_lists(config_dir);
                 }
                 self.resource_manager.exit();
                 let _ = sender.send(());
@@ -469,11 +666,206 @@ pub struct AuthCache {
 pub struct CoreResourceManager {
     user_agent: Cow<'static, str>,
     devtools_sender: Option<Sender<DevtoolsControlMsg>>,
-    sw_managers: HashMap<ImmutableOrigin, IpcSender<CustomResponseMediator>>,
     filemanager: FileManager,
     thread_pool: Arc<CoreResourceThreadPool>,
     ca_certificates: CACertificates,
     ignore_certificate_errors: bool,
+    request_interceptor: Arc<RequestInterceptor>,
+    har_recorder: Arc<HarRecorder>,
+    archives: Arc<ArchiveStore>,
+    network_activity: Arc<NetworkActivity>,
+    /// The connections of both the public and private HTTP clients.
+    connection_monitor: ConnectionMonitor,
+    embedder_proxy: EmbedderProxy,
+}
+
+/// How long a low priority fetch waits at most for the high priority fetches of its
+/// top-level site to get their response, so that a slow server cannot stall it forever.
+const MAX_HIGH_PRIORITY_HOLD: Duration = Duration::from_secs(2);
+
+/// How often a fetch waiting for its turn checks whether it was cancelled.
+const CANCELLATION_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The fetches in progress, except those for `prefetch` hints, which wait for there to be
+/// none left so as to only use the network capacity nothing else needs. Low priority
+/// fetches similarly wait for the high priority ones of the same top-level site to get
+/// their response.
+#[derive(Default)]
+struct NetworkActivity {
+    fetches: AtomicUsize,
+    idle: Notify,
+    /// The number of high priority fetches waiting for their response, for each top-level
+    /// site that has some. Fetches without a top-level site are counted together.
+    high_priority_fetches: Mutex<HashMap<Option<NetworkPartitionKey>, usize>>,
+    high_priority_done: Notify,
+    /// The number of fetches in progress for each pipeline that has some.
+    pipeline_fetches: Mutex<HashMap<PipelineId, usize>>,
+}
+
+impl NetworkActivity {
+    fn start_fetch(self: &Arc<Self>, pipeline_id: Option<PipelineId>) -> ActiveFetch {
+        self.fetches.fetch_add(1, Ordering::SeqCst);
+        if let Some(pipeline_id) = pipeline_id {
+            *self
+                .pipeline_fetches
+                .lock()
+                .unwrap()
+                .entry(pipeline_id)
+                .or_default() += 1;
+        }
+        ActiveFetch {
+            activity: self.clone(),
+            pipeline_id,
+        }
+    }
+
+    /// Hold back the low priority fetches of the top-level site `site` until the returned
+    /// value is dropped.
+    fn start_high_priority_fetch(
+        self: &Arc<Self>,
+        site: Option<NetworkPartitionKey>,
+    ) -> HighPriorityFetch {
+        *self
+            .high_priority_fetches
+            .lock()
+            .unwrap()
+            .entry(site.clone())
+            .or_default() += 1;
+        HighPriorityFetch {
+            activity: self.clone(),
+            site,
+        }
+    }
+
+    /// The number of fetches in progress for the given pipelines.
+    fn fetches_in_progress(&self, pipelines: &[PipelineId]) -> usize {
+        let pipeline_fetches = self.pipeline_fetches.lock().unwrap();
+        pipelines
+            .iter()
+            .filter_map(|pipeline_id| pipeline_fetches.get(pipeline_id))
+            .sum()
+    }
+
+    /// Wait until no fetch is in progress.
+    async fn wait_until_idle(&self) {
+        loop {
+            // Created before checking, so that it is woken by a fetch ending in between.
+            let idle = self.idle.notified();
+            if self.fetches.load(Ordering::SeqCst) == 0 {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    /// Wait until no high priority fetch of the top-level site `site` is waiting for its
+    /// response.
+    async fn wait_for_high_priority_fetches(&self, site: &Option<NetworkPartitionKey>) {
+        loop {
+            let done = self.high_priority_done.notified();
+            if !self
+                .high_priority_fetches
+                .lock()
+                .unwrap()
+                .contains_key(site)
+            {
+                return;
+            }
+            done.await;
+        }
+    }
+}
+
+/// Wait for `future`, unless `cancellation_listener` is cancelled first, in which case
+/// this returns false.
+async fn wait_unless_cancelled(
+    future: impl Future<Output = ()>,
+    cancellation_listener: &Mutex<CancellationListener>,
+) -> bool {
+    tokio::pin!(future);
+    loop {
+        tokio::select! {
+            _ = &mut future => return true,
+            _ = tokio::time::sleep(CANCELLATION_POLL_INTERVAL) => {
+                if cancellation_listener.lock().unwrap().cancelled() {
+                    return false;
+                }
+            },
+        }
+    }
+}
+
+/// A fetch in progress, which ends when this is dropped.
+struct ActiveFetch {
+    activity: Arc<NetworkActivity>,
+    pipeline_id: Option<PipelineId>,
+}
+
+impl Drop for ActiveFetch {
+    fn drop(&mut self) {
+        let activity = &self.activity;
+        if let Some(pipeline_id) = self.pipeline_id {
+            let mut pipeline_fetches = activity.pipeline_fetches.lock().unwrap();
+            if let Some(count) = pipeline_fetches.get_mut(&pipeline_id) {
+                *count -= 1;
+                if *count == 0 {
+                    pipeline_fetches.remove(&pipeline_id);
+                }
+            }
+        }
+        if activity.fetches.fetch_sub(1, Ordering::SeqCst) == 1 {
+            activity.idle.notify_waiters();
+        }
+    }
+}
+
+/// A high priority fetch waiting for its response, which it got when this is dropped.
+struct HighPriorityFetch {
+    activity: Arc<NetworkActivity>,
+    site: Option<NetworkPartitionKey>,
+}
+
+impl Drop for HighPriorityFetch {
+    fn drop(&mut self) {
+        let mut high_priority_fetches = self.activity.high_priority_fetches.lock().unwrap();
+        if let Some(count) = high_priority_fetches.get_mut(&self.site) {
+            *count -= 1;
+            if *count == 0 {
+                high_priority_fetches.remove(&self.site);
+                self.activity.high_priority_done.notify_waiters();
+            }
+        }
+    }
+}
+
+/// The target of a high priority fetch, which stops holding back the low priority ones
+/// once the response headers are in, rather than once the whole body is.
+struct HighPriorityTarget<Target> {
+    target: Target,
+    high_priority_fetch: Option<HighPriorityFetch>,
+}
+
+impl<Target: FetchTaskTarget> FetchTaskTarget for HighPriorityTarget<Target> {
+    fn process_request_body(&mut self, request: &Request) {
+        self.target.process_request_body(request);
+    }
+
+    fn process_request_eof(&mut self, request: &Request) {
+        self.target.process_request_eof(request);
+    }
+
+    fn process_response(&mut self, response: &Response) {
+        self.high_priority_fetch = None;
+        self.target.process_response(response);
+    }
+
+    fn process_response_chunk(&mut self, chunk: Vec<u8>) {
+        self.target.process_response_chunk(chunk);
+    }
+
+    fn process_response_eof(&mut self, response: &Response) {
+        self.target.process_response_eof(response);
+    }
 }
 
 /// The state of the thread-pool used by CoreResource.
@@ -615,11 +1007,16 @@ impl CoreResourceManager {
         CoreResourceManager {
             user_agent: user_agent,
             devtools_sender,
-            sw_managers: Default::default(),
-            filemanager: FileManager::new(embedder_proxy, Arc::downgrade(&pool_handle)),
+            filemanager: FileManager::new(embedder_proxy.clone(), Arc::downgrade(&pool_handle)),
             thread_pool: pool_handle,
             ca_certificates,
             ignore_certificate_errors,
+            request_interceptor: Arc::new(RequestInterceptor::new(embedder_proxy.clone())),
+            har_recorder: Arc::new(HarRecorder::new()),
+            archives: Arc::new(ArchiveStore::new()),
+            network_activity: Default::default(),
+            connection_monitor: ConnectionMonitor::new(),
+            embedder_proxy,
         }
     }
 
@@ -650,7 +1047,7 @@ impl CoreResourceManager {
         &self,
         request_builder: RequestBuilder,
         res_init_: Option<ResponseInit>,
-        mut sender: Target,
+        sender: Target,
         http_state: &Arc<HttpState>,
         cancel_chan: Option<IpcReceiver<()>>,
     ) {
@@ -658,6 +1055,10 @@ impl CoreResourceManager {
         let ua = self.user_agent.clone();
         let dc = self.devtools_sender.clone();
         let filemanager = self.filemanager.clone();
+        let request_interceptor = self.request_interceptor.clone();
+        let har_recorder = self.har_recorder.clone();
+        let archives = self.archives.clone();
+        let network_activity = self.network_activity.clone();
 
         let timing_type = match request_builder.destination {
             Destination::Document => ResourceTimingType::Navigation,
@@ -666,6 +1067,32 @@ impl CoreResourceManager {
 
         let mut request = request_builder.build();
         let url = request.current_url();
+        // Navigations are what the user is waiting for, so they go first unless
+        // the page said otherwise.
+        let priority = match request.priority {
+            RequestPriority::Auto if request.destination == Destination::Document => {
+                RequestPriority::High
+            },
+            priority => priority,
+        };
+        let active_fetch = match request.initiator {
+            Initiator::Prefetch => None,
+            _ => Some(self.network_activity.start_fetch(request.pipeline_id)),
+        };
+        // The fetches of one top-level site do not hold back those of another, so that a
+        // navigation in one tab does not stall the page loaded in another.
+        let site = http_state.partitions.key_for_request(&request);
+        let high_priority_fetch = match active_fetch {
+            Some(_) if priority == RequestPriority::High => Some(
+                self.network_activity
+                    .start_high_priority_fetch(site.clone()),
+            ),
+            _ => None,
+        };
+        let mut sender = HighPriorityTarget {
+            target: sender,
+            high_priority_fetch,
+        };
 
         // In the case of a valid blob URL, acquiring a token granting access to a file,
         // regardless if the URL is revoked after token acquisition.
@@ -689,6 +1116,38 @@ impl CoreResourceManager {
         };
 
         HANDLE.lock().unwrap().as_ref().unwrap().spawn(async move {
+            // A keep-alive request outlives the document that made it, so its body is read
+            // now, while the document is there to send it, and counted against the quota of
+            // the document until the request is done.
+            // <https://fetch.spec.whatwg.org/#http-network-or-cache-fetch>
+            let pipeline_id = request.pipeline_id;
+            let keepalive_bytes = if request.keep_alive {
+                let bytes = adopt_body(&mut request).await;
+                Some(bytes.filter(|bytes| http_state.keepalive_quota.reserve(pipeline_id, *bytes)))
+            } else {
+                None
+            };
+
+            // Prefetches are for loads that may happen later, so they must not slow down
+            // the ones happening now.
+            let cancellation_listener =
+                Arc::new(Mutex::new(CancellationListener::new(cancel_chan)));
+            let cancelled = if active_fetch.is_none() {
+                !wait_unless_cancelled(network_activity.wait_until_idle(), &cancellation_listener)
+                    .await
+            } else if priority == RequestPriority::Low {
+                let high_priority_fetches = async {
+                    let _ = tokio::time::timeout(
+                        MAX_HIGH_PRIORITY_HOLD,
+                        network_activity.wait_for_high_priority_fetches(&site),
+                    )
+                    .await;
+                };
+                !wait_unless_cancelled(high_priority_fetches, &cancellation_listener).await
+            } else {
+                false
+            };
+
             // XXXManishearth: Check origin against pipeline id (also ensure that the mode is allowed)
             // todo load context / mimesniff in fetch
             // todo referrer policy?
@@ -699,11 +1158,27 @@ impl CoreResourceManager {
                 devtools_chan: dc.map(|dc| Arc::new(Mutex::new(dc))),
                 filemanager: Arc::new(Mutex::new(filemanager)),
                 file_token,
-                cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(cancel_chan))),
+                cancellation_listener,
                 timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(request.timing_type()))),
+                request_interceptor,
+                har_recorder,
+                archives,
             };
 
             match res_init_ {
+                _ if cancelled => {
+                    let response =
+                        Response::network_error(NetworkError::Internal("Fetch aborted".into()));
+                    sender.process_response(&response);
+                    sender.process_response_eof(&response);
+                },
+                _ if keepalive_bytes == Some(None) => {
+                    let response = Response::network_error(NetworkError::Internal(
+                        "Keep-alive request body is unreadable or over the quota".into(),
+                    ));
+                    sender.process_response(&response);
+                    sender.process_response_eof(&response);
+                },
                 Some(res_init) => {
                     let response = Response::from_init(res_init, timing_type);
                     http_redirect_fetch(
@@ -722,6 +1197,10 @@ impl CoreResourceManager {
                 },
             };
 
+            if let Some(Some(bytes)) = keepalive_bytes {
+                context.state.keepalive_quota.release(pipeline_id, bytes);
+            }
+
             // Remove token after fetch.
             if let Some(id) = blob_url_file_id.as_ref() {
                 context
@@ -733,6 +1212,45 @@ impl CoreResourceManager {
         });
     }
 
+    /// Queue a report to be delivered to `endpoint`, starting the task delivering the reports
+    /// if it is not running.
+    fn queue_report(
+        &self,
+        report: ReportingReport,
+        endpoint: ServoUrl,
+        http_state: &Arc<HttpState>,
+    ) {
+        if !http_state
+            .reporting_queue
+            .queue(report, endpoint, Instant::now())
+        {
+            return;
+        }
+        let context = FetchContext {
+            state: http_state.clone(),
+            user_agent: self.user_agent.clone(),
+            devtools_chan: self
+                .devtools_sender
+                .clone()
+                .map(|sender| Arc::new(Mutex::new(sender))),
+            filemanager: Arc::new(Mutex::new(self.filemanager.clone())),
+            file_token: FileTokenCheck::NotRequired,
+            cancellation_listener: Arc::new(Mutex::new(CancellationListener::new(None))),
+            timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
+                ResourceTimingType::None,
+            ))),
+            request_interceptor: self.request_interceptor.clone(),
+            har_recorder: self.har_recorder.clone(),
+            archives: self.archives.clone(),
+        };
+        HANDLE
+            .lock()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .spawn(deliver_reports(context));
+    }
+
     fn websocket_connect(
         &self,
         request: RequestBuilder,
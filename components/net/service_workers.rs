@@ -0,0 +1,93 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Handing the navigations of an origin to its service workers, which may answer them
+//! instead of the network.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use ipc_channel::ipc::{self, IpcSender};
+use ipc_channel::router::ROUTER;
+use log::warn;
+use net_traits::request::{Request, RequestMode};
+use net_traits::response::{HttpsState, Response, ResponseBody};
+use net_traits::{CustomResponse, CustomResponseMediator};
+use servo_url::ImmutableOrigin;
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::fetch::methods::FetchContext;
+
+/// The service worker manager of each origin which registered one.
+#[derive(Clone, Default)]
+pub struct ServiceWorkers {
+    managers: Arc<RwLock<HashMap<ImmutableOrigin, IpcSender<CustomResponseMediator>>>>,
+}
+
+impl ServiceWorkers {
+    pub fn new() -> ServiceWorkers {
+        ServiceWorkers::default()
+    }
+
+    pub fn set_manager(&self, origin: ImmutableOrigin, sender: IpcSender<CustomResponseMediator>) {
+        self.managers.write().unwrap().insert(origin, sender);
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#handle-fetch>
+    ///
+    /// Only navigations are handed to the service workers for now. Returns the response
+    /// of the worker controlling the URL of `request`, if there is one and it answered.
+    pub async fn handle_fetch(
+        &self,
+        request: &Request,
+        context: &FetchContext,
+    ) -> Option<Response> {
+        if request.mode != RequestMode::Navigate {
+            return None;
+        }
+        let url = request.current_url();
+        let manager = self.managers.read().unwrap().get(&url.origin()).cloned()?;
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        let mediator = CustomResponseMediator {
+            response_chan: sender,
+            load_url: url.clone(),
+            is_navigation: true,
+        };
+        if manager.send(mediator).is_err() {
+            warn!("Failed to hand a navigation to the service worker manager.");
+            return None;
+        }
+
+        // The worker may have to start and run its fetch handler, so wait for it without
+        // blocking the thread.
+        let (message_sender, mut message_receiver) = unbounded_channel();
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let _ = message_sender.send(message.to::<Option<CustomResponse>>());
+            }),
+        );
+        let custom_response = match message_receiver.recv().await {
+            Some(Ok(Some(custom_response))) => custom_response,
+            // The worker let the navigation go to the network, or went away.
+            _ => return None,
+        };
+
+        let timing = context.timing.lock().unwrap().clone();
+        let mut response = Response::new(url.clone(), timing);
+        let (status, reason) = custom_response.raw_status;
+        response.status = Some((status, reason.clone()));
+        response.raw_status = Some((status.as_u16(), reason.into_bytes()));
+        response.headers = custom_response.headers;
+        response.referrer = request.referrer.to_url().cloned();
+        response.referrer_policy = request.referrer_policy.clone();
+        response.https_state = match url.scheme() {
+            "https" => HttpsState::Modern,
+            _ => HttpsState::None,
+        };
+        *response.body.lock().unwrap() = ResponseBody::Done(custom_response.body);
+        Some(response)
+    }
+}
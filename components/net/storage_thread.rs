@@ -5,28 +5,35 @@
 use std::borrow::ToOwned;
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::sync::Arc;
 use std::thread;
 
+use embedder_traits::{PermissionName, PermissionState};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
 use net_traits::storage_thread::{StorageThreadMsg, StorageType};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
+use storage::quota::{QuotaManager, StorageEndpoint};
 
 use crate::resource_thread;
 
 const QUOTA_SIZE_LIMIT: usize = 5 * 1024 * 1024;
 
 pub trait StorageThreadFactory {
-    fn new(config_dir: Option<PathBuf>) -> Self;
+    fn new(config_dir: Option<PathBuf>, quota: Arc<QuotaManager>) -> Self;
 }
 
 impl StorageThreadFactory for IpcSender<StorageThreadMsg> {
     /// Create a storage thread
-    fn new(config_dir: Option<PathBuf>) -> IpcSender<StorageThreadMsg> {
+    fn new(config_dir: Option<PathBuf>, quota: Arc<QuotaManager>) -> IpcSender<StorageThreadMsg> {
         let (chan, port) = ipc::channel().unwrap();
+        let evictor_chan = chan.clone();
+        quota.register_evictor(StorageEndpoint::LocalStorage, move |origin| {
+            let _ = evictor_chan.send(StorageThreadMsg::ClearOrigin(origin));
+        });
         thread::Builder::new()
             .name("StorageManager".to_owned())
             .spawn(move || {
-                StorageManager::new(port, config_dir).start();
+                StorageManager::new(port, config_dir, quota).start();
             })
             .expect("Thread spawning failed");
         chan
@@ -37,20 +44,38 @@ struct StorageManager {
     port: IpcReceiver<StorageThreadMsg>,
     session_data: HashMap<String, (usize, BTreeMap<String, String>)>,
     local_data: HashMap<String, (usize, BTreeMap<String, String>)>,
+    /// The permissions granted or denied to each origin.
+    permissions: HashMap<String, HashMap<PermissionName, PermissionState>>,
     config_dir: Option<PathBuf>,
+    /// Keeps the usage of the local storage data of each origin, with that of its other
+    /// storage.
+    quota: Arc<QuotaManager>,
 }
 
 impl StorageManager {
-    fn new(port: IpcReceiver<StorageThreadMsg>, config_dir: Option<PathBuf>) -> StorageManager {
-        let mut local_data = HashMap::new();
+    fn new(
+        port: IpcReceiver<StorageThreadMsg>,
+        config_dir: Option<PathBuf>,
+        quota: Arc<QuotaManager>,
+    ) -> StorageManager {
+        let mut local_data: HashMap<String, (usize, BTreeMap<String, String>)> = HashMap::new();
+        let mut permissions = HashMap::new();
         if let Some(ref config_dir) = config_dir {
             resource_thread::read_json_from_file(&mut local_data, config_dir, "local_data.json");
+            resource_thread::read_json_from_file(&mut permissions, config_dir, "permissions.json");
+        }
+        for (origin, &(total, _)) in &local_data {
+            if let Ok(url) = ServoUrl::parse(origin) {
+                quota.set_usage(&url.origin(), StorageEndpoint::LocalStorage, total as u64);
+            }
         }
         StorageManager {
             port: port,
             session_data: HashMap::new(),
             local_data: local_data,
+            permissions: permissions,
             config_dir: config_dir,
+            quota: quota,
         }
     }
 }
@@ -69,18 +94,41 @@ impl StorageManager {
                     self.keys(sender, url, storage_type)
                 },
                 StorageThreadMsg::SetItem(sender, url, storage_type, name, value) => {
-                    self.set_item(sender, url, storage_type, name, value);
+                    self.set_item(sender, url.clone(), storage_type, name, value);
+                    self.report_usage(url, storage_type);
                     self.save_state()
                 },
                 StorageThreadMsg::GetItem(sender, url, storage_type, name) => {
                     self.request_item(sender, url, storage_type, name)
                 },
                 StorageThreadMsg::RemoveItem(sender, url, storage_type, name) => {
-                    self.remove_item(sender, url, storage_type, name);
+                    self.remove_item(sender, url.clone(), storage_type, name);
+                    self.report_usage(url, storage_type);
                     self.save_state()
                 },
                 StorageThreadMsg::Clear(sender, url, storage_type) => {
-                    self.clear(sender, url, storage_type);
+                    self.clear(sender, url.clone(), storage_type);
+                    self.report_usage(url, storage_type);
+                    self.save_state()
+                },
+                StorageThreadMsg::GetPermission(sender, url, name) => {
+                    self.permission(sender, url, name)
+                },
+                StorageThreadMsg::SetPermission(url, name, state) => {
+                    self.set_permission(url, name, state);
+                    self.save_permissions()
+                },
+                StorageThreadMsg::Estimate(sender, url) => {
+                    let _ = sender.send(self.quota.estimate(&url.origin()));
+                },
+                StorageThreadMsg::Persist(sender, url) => {
+                    let _ = sender.send(self.quota.persist(&url.origin()));
+                },
+                StorageThreadMsg::Persisted(sender, url) => {
+                    let _ = sender.send(self.quota.persisted(&url.origin()));
+                },
+                StorageThreadMsg::ClearOrigin(origin) => {
+                    self.clear_origin(origin);
                     self.save_state()
                 },
                 StorageThreadMsg::Exit(sender) => {
@@ -98,6 +146,33 @@ impl StorageManager {
         }
     }
 
+    fn save_permissions(&self) {
+        if let Some(ref config_dir) = self.config_dir {
+            resource_thread::write_json_to_file(&self.permissions, config_dir, "permissions.json");
+        }
+    }
+
+    /// Tell the quota manager about the size of the local storage data of the origin of `url`.
+    fn report_usage(&self, url: ServoUrl, storage_type: StorageType) {
+        if let StorageType::Session = storage_type {
+            return;
+        }
+        let origin = url.origin();
+        let total = self
+            .local_data
+            .get(&origin.ascii_serialization())
+            .map_or(0, |&(total, _)| total);
+        self.quota
+            .set_usage(&origin, StorageEndpoint::LocalStorage, total as u64);
+    }
+
+    /// Remove the local storage data of `origin`, which the quota manager evicted.
+    fn clear_origin(&mut self, origin: ImmutableOrigin) {
+        self.local_data.remove(&origin.ascii_serialization());
+        self.quota
+            .set_usage(&origin, StorageEndpoint::LocalStorage, 0);
+    }
+
     fn select_data(
         &self,
         storage_type: StorageType,
@@ -164,6 +239,8 @@ impl StorageManager {
         name: String,
         value: String,
     ) {
+        let quota_origin = url.origin();
+        let quota = self.quota.clone();
         let origin = self.origin_as_string(url);
 
         let (this_storage_size, other_storage_size) = {
@@ -196,6 +273,13 @@ impl StorageManager {
                     return Err(());
                 }
 
+                if let StorageType::Local = storage_type {
+                    let usage = new_total_size as u64;
+                    if !quota.allows_usage(&quota_origin, StorageEndpoint::LocalStorage, usage) {
+                        return Err(());
+                    }
+                }
+
                 let message =
                     entry
                         .insert(name.clone(), value.clone())
@@ -271,6 +355,41 @@ impl StorageManager {
             .unwrap();
     }
 
+    fn permission(&self, sender: IpcSender<PermissionState>, url: ServoUrl, name: PermissionName) {
+        let origin = self.origin_as_string(url);
+        let state = self
+            .permissions
+            .get(&origin)
+            .and_then(|entry| entry.get(&name))
+            .copied()
+            .unwrap_or(PermissionState::Prompt);
+        sender.send(state).unwrap();
+    }
+
+    /// Setting a permission back to `Prompt` forgets what was decided for the origin.
+    fn set_permission(&mut self, url: ServoUrl, name: PermissionName, state: PermissionState) {
+        if !url.origin().is_tuple() {
+            return;
+        }
+        let origin = self.origin_as_string(url);
+        match state {
+            PermissionState::Prompt => {
+                if let Some(entry) = self.permissions.get_mut(&origin) {
+                    entry.remove(&name);
+                    if entry.is_empty() {
+                        self.permissions.remove(&origin);
+                    }
+                }
+            },
+            state => {
+                self.permissions
+                    .entry(origin)
+                    .or_default()
+                    .insert(name, state);
+            },
+        }
+    }
+
     fn origin_as_string(&self, url: ServoUrl) -> String {
         url.origin().ascii_serialization()
     }
@@ -0,0 +1,90 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use embedder_traits::UserAgentOverride;
+use http::header::{HeaderMap, HeaderValue};
+use msg::constellation_msg::TEST_PIPELINE_ID;
+use net::client_hints::{append_client_hints, AcceptChCache, UserAgentOverrides};
+use net_traits::client_hints::UserAgentMetadata;
+use servo_url::ServoUrl;
+
+use crate::DEFAULT_USER_AGENT;
+
+fn hints_sent_to(url: &ServoUrl, cache: &AcceptChCache, high_entropy_values: bool) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    let metadata = UserAgentMetadata::new(DEFAULT_USER_AGENT, None);
+    append_client_hints(
+        &mut headers,
+        url,
+        Some(&url.origin()),
+        cache,
+        &metadata,
+        high_entropy_values,
+    );
+    headers
+}
+
+#[test]
+fn test_low_entropy_hints_are_only_sent_to_secure_origins() {
+    let cache = AcceptChCache::new();
+
+    let headers = hints_sent_to(&ServoUrl::parse("https://servo.org").unwrap(), &cache, true);
+    assert!(headers.contains_key("sec-ch-ua"));
+    assert!(headers.contains_key("sec-ch-ua-mobile"));
+    assert!(headers.contains_key("sec-ch-ua-platform"));
+    assert!(!headers.contains_key("sec-ch-ua-arch"));
+
+    let headers = hints_sent_to(&ServoUrl::parse("http://servo.org").unwrap(), &cache, true);
+    assert!(headers.is_empty());
+}
+
+#[test]
+fn test_accept_ch_opts_an_origin_into_high_entropy_hints() {
+    let url = ServoUrl::parse("https://servo.org/index.html").unwrap();
+    let cache = AcceptChCache::new();
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        "accept-ch",
+        HeaderValue::from_static("Sec-CH-UA-Arch, sec-ch-ua-bitness, Unknown-Hint"),
+    );
+    cache.update_from_response(&url, &response_headers);
+
+    let headers = hints_sent_to(&url, &cache, true);
+    assert!(headers.contains_key("sec-ch-ua-arch"));
+    assert!(headers.contains_key("sec-ch-ua-bitness"));
+    assert!(!headers.contains_key("sec-ch-ua-model"));
+
+    // Other origins do not get the hints, nor does any origin when they are disallowed.
+    let other_url = ServoUrl::parse("https://example.com").unwrap();
+    assert!(!hints_sent_to(&other_url, &cache, true).contains_key("sec-ch-ua-arch"));
+    assert!(!hints_sent_to(&url, &cache, false).contains_key("sec-ch-ua-arch"));
+
+    // An `Accept-CH` header without any supported hint forgets the hints asked for before.
+    response_headers.insert("accept-ch", HeaderValue::from_static("Unknown-Hint"));
+    cache.update_from_response(&url, &response_headers);
+    assert!(!hints_sent_to(&url, &cache, true).contains_key("sec-ch-ua-arch"));
+}
+
+#[test]
+fn test_user_agent_override_is_per_pipeline() {
+    let overrides = UserAgentOverrides::new();
+    let user_agent_override = UserAgentOverride {
+        user_agent: Some("Mozilla/5.0 Test/1.0".to_owned()),
+        platform: Some("Fuchsia".to_owned()),
+        ..UserAgentOverride::default()
+    };
+    overrides.set(vec![TEST_PIPELINE_ID], Some(user_agent_override.clone()));
+
+    assert_eq!(
+        overrides.get(Some(TEST_PIPELINE_ID)),
+        Some(user_agent_override.clone())
+    );
+    assert_eq!(overrides.get(None), None);
+
+    let metadata = UserAgentMetadata::new(DEFAULT_USER_AGENT, Some(&user_agent_override));
+    assert_eq!(metadata.platform, "Fuchsia");
+
+    overrides.set(vec![TEST_PIPELINE_ID], None);
+    assert_eq!(overrides.get(Some(TEST_PIPELINE_ID)), None);
+}
@@ -4,15 +4,19 @@
 
 #![cfg(not(target_os = "windows"))]
 
-use std::fs;
 use std::iter::FromIterator;
 use std::path::Path;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, Weak};
 use std::time::{Duration, SystemTime};
+use std::{fs, thread};
 
 use crossbeam_channel::{unbounded, Sender};
 use devtools_traits::{HttpRequest as DevtoolsHttpRequest, HttpResponse as DevtoolsHttpResponse};
+use embedder_traits::{
+    EmbedderMsg, EmbedderProxy, HttpBodyData, WebResourceRequest, WebResourceResponse,
+    WebResourceResponseMsg,
+};
 use headers::{
     AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowMethods,
     AccessControlAllowOrigin, AccessControlMaxAge, CacheControl, ContentLength, ContentType,
@@ -21,12 +25,16 @@ use headers::{
 use http::header::{self, HeaderMap, HeaderName, HeaderValue};
 use http::{Method, StatusCode};
 use hyper::{Body, Request as HyperRequest, Response as HyperResponse};
+use ipc_channel::ipc::IpcSender;
 use mime::{self, Mime};
 use msg::constellation_msg::TEST_PIPELINE_ID;
+use net::archive::store::ArchiveStore;
 use net::fetch::cors_cache::CorsCache;
 use net::fetch::methods::{self, CancellationListener, FetchContext};
 use net::filemanager_thread::FileManager;
+use net::har::HarRecorder;
 use net::hsts::HstsEntry;
+use net::request_interceptor::RequestInterceptor;
 use net::resource_thread::CoreResourceThreadPool;
 use net::test::HttpState;
 use net_traits::filemanager_thread::FileTokenCheck;
@@ -769,6 +777,9 @@ fn test_fetch_with_hsts() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        request_interceptor: Arc::new(RequestInterceptor::new(create_embedder_proxy())),
+        har_recorder: Arc::new(HarRecorder::new()),
+        archives: Arc::new(ArchiveStore::new()),
     };
 
     // The server certificate is self-signed, so we need to add an override
@@ -828,6 +839,9 @@ fn test_load_adds_host_to_hsts_list_when_url_is_https() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        request_interceptor: Arc::new(RequestInterceptor::new(create_embedder_proxy())),
+        har_recorder: Arc::new(HarRecorder::new()),
+        archives: Arc::new(ArchiveStore::new()),
     };
 
     // The server certificate is self-signed, so we need to add an override
@@ -885,6 +899,9 @@ fn test_fetch_self_signed() {
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        request_interceptor: Arc::new(RequestInterceptor::new(create_embedder_proxy())),
+        har_recorder: Arc::new(HarRecorder::new()),
+        archives: Arc::new(ArchiveStore::new()),
     };
 
     let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
@@ -1375,6 +1392,7 @@ fn test_fetch_with_devtools() {
         connect_time: devhttprequest.connect_time,
         send_time: devhttprequest.send_time,
         is_xhr: true,
+        resource_hint: None,
     };
 
     let content = "Yay!";
@@ -1396,3 +1414,254 @@ fn test_fetch_with_devtools() {
     assert_eq!(devhttprequest, httprequest);
     assert_eq!(devhttpresponse, httpresponse);
 }
+
+/// A fetch context whose requests are all intercepted, and given to `handler`.
+fn new_intercepting_fetch_context<F>(handler: F) -> FetchContext
+where
+    F: Fn(WebResourceRequest, IpcSender<WebResourceResponseMsg>) + Send + 'static,
+{
+    let (sender, receiver) = unbounded();
+    let embedder_proxy = EmbedderProxy {
+        sender,
+        event_loop_waker: create_embedder_proxy().event_loop_waker,
+    };
+    let context = new_fetch_context(None, Some(embedder_proxy), None);
+    context.request_interceptor.set_enabled(true);
+    thread::spawn(move || {
+        while let Ok((_, msg)) = receiver.recv() {
+            if let EmbedderMsg::WebResourceRequested(request, response_sender) = msg {
+                handler(request, response_sender);
+            }
+        }
+    });
+    context
+}
+
+#[test]
+fn test_fetch_with_intercepted_response() {
+    static MESSAGE: &'static [u8] = b"from the network";
+    static INTERCEPTED_MESSAGE: &'static [u8] = b"from the embedder";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_intercepting_fetch_context(|request, response_sender| {
+        assert_eq!(request.method, Method::GET);
+        assert!(!request.is_redirect);
+        let mut headers = HeaderMap::new();
+        headers.typed_insert(ContentType::text());
+        let response = WebResourceResponse::new(StatusCode::OK, headers);
+        let _ = response_sender.send(WebResourceResponseMsg::Start(response));
+        for chunk in INTERCEPTED_MESSAGE.chunks(5) {
+            let _ = response_sender.send(WebResourceResponseMsg::Body(HttpBodyData::Chunk(
+                chunk.to_vec(),
+            )));
+        }
+        let _ = response_sender.send(WebResourceResponseMsg::Body(HttpBodyData::Done));
+    });
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url,
+        Some(origin),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(
+        fetch_response.headers.typed_get::<ContentType>(),
+        Some(ContentType::text())
+    );
+    match *fetch_response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, INTERCEPTED_MESSAGE),
+        _ => panic!(),
+    };
+}
+
+#[test]
+fn test_fetch_with_blocked_request() {
+    static MESSAGE: &'static [u8] = b"";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_intercepting_fetch_context(|_, response_sender| {
+        let _ = response_sender.send(WebResourceResponseMsg::Block);
+    });
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url,
+        Some(origin),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+
+    assert!(fetch_response.is_network_error());
+}
+
+#[test]
+fn test_fetch_with_redirected_request() {
+    static MESSAGE: &'static [u8] = b"redirected";
+    let handler = move |request: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        if request.uri().path() == "/redirected" {
+            *response.body_mut() = MESSAGE.to_vec().into();
+        } else {
+            *response.status_mut() = StatusCode::NOT_FOUND;
+        }
+    };
+    let (server, url) = make_server(handler);
+
+    let redirected_url = url.join("/redirected").unwrap();
+    let mut context = new_intercepting_fetch_context(move |request, response_sender| {
+        let response = if request.is_redirect {
+            assert_eq!(request.url, redirected_url);
+            WebResourceResponseMsg::None
+        } else {
+            WebResourceResponseMsg::Redirect(redirected_url.clone())
+        };
+        let _ = response_sender.send(response);
+    });
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url,
+        Some(origin),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(
+        fetch_response.actual_response().status.as_ref().unwrap().0,
+        StatusCode::OK
+    );
+    match *fetch_response.body.lock().unwrap() {
+        ResponseBody::Done(ref body) => assert_eq!(&**body, MESSAGE),
+        _ => panic!(),
+    };
+}
+
+#[test]
+fn test_fetch_recorded_in_har() {
+    static MESSAGE: &'static [u8] = b"Hello World!";
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        response.headers_mut().typed_insert(ContentType::text());
+        *response.body_mut() = MESSAGE.to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut context = new_fetch_context(None, None, None);
+    context.har_recorder.start(true);
+
+    let origin = Origin::Origin(url.origin());
+    let mut request = Request::new(
+        url.join("/page?query=1").unwrap(),
+        Some(origin),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    let _ = server.close();
+    assert!(!fetch_response.is_network_error());
+
+    let har: serde_json::Value =
+        serde_json::from_str(&context.har_recorder.stop().unwrap()).unwrap();
+    assert_eq!(har["log"]["version"], "1.2");
+    let entries = har["log"]["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    let entry = &entries[0];
+    assert_eq!(entry["request"]["method"], "GET");
+    assert_eq!(entry["request"]["url"], request.current_url().as_str());
+    assert_eq!(entry["request"]["queryString"][0]["name"], "query");
+    assert_eq!(entry["response"]["status"], 200);
+    assert_eq!(entry["response"]["content"]["mimeType"], "text/plain");
+    assert_eq!(entry["response"]["content"]["size"], MESSAGE.len());
+    assert_eq!(entry["response"]["content"]["text"], "Hello World!");
+
+    assert!(context.har_recorder.stop().is_none());
+}
+
+fn fetch_archive(name: &str) -> (FetchContext, Response) {
+    let path = Path::new("tests").join(name).canonicalize().unwrap();
+    let url = ServoUrl::from_file_path(path).unwrap();
+    let mut request = Request::new(
+        url.clone(),
+        Some(Origin::Origin(url.origin())),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    request.destination = Destination::Document;
+
+    let mut context = new_fetch_context(None, None, None);
+    let fetch_response = fetch_with_context(&mut request, &mut context);
+    (context, fetch_response)
+}
+
+fn fetch_archived(url: &str, context: &mut FetchContext) -> Response {
+    let url = ServoUrl::parse(url).unwrap();
+    let mut request = Request::new(
+        url.clone(),
+        Some(Origin::Origin(url.origin())),
+        Referrer::NoReferrer,
+        None,
+        HttpsState::None,
+    );
+    fetch_with_context(&mut request, context)
+}
+
+#[test]
+fn test_fetch_web_bundle() {
+    let (mut context, fetch_response) = fetch_archive("archive.wbn");
+    assert_eq!(
+        fetch_response.actual_response().location_url,
+        Some(Ok(ServoUrl::parse("https://example.com/").unwrap()))
+    );
+
+    // The archived responses are used without connecting to their server.
+    let fetch_response = fetch_archived("https://example.com/style.css", &mut context);
+    assert!(!fetch_response.is_network_error());
+    assert_eq!(
+        fetch_response.headers.typed_get::<ContentType>(),
+        Some(ContentType::from(mime::TEXT_CSS))
+    );
+    assert_eq!(
+        *fetch_response.body.lock().unwrap(),
+        ResponseBody::Done(b"p { color: green }\n".to_vec())
+    );
+}
+
+#[test]
+fn test_fetch_warc() {
+    let (mut context, fetch_response) = fetch_archive("archive.warc");
+    assert_eq!(
+        fetch_response.actual_response().location_url,
+        Some(Ok(ServoUrl::parse("https://example.org/").unwrap()))
+    );
+
+    // The body was stored chunked and compressed, as it was received.
+    let fetch_response = fetch_archived("https://example.org/style.css", &mut context);
+    assert!(!fetch_response.is_network_error());
+    assert!(fetch_response
+        .headers
+        .get(header::CONTENT_ENCODING)
+        .is_none());
+    assert_eq!(
+        *fetch_response.body.lock().unwrap(),
+        ResponseBody::Done(b"p { color: green }\n".to_vec())
+    );
+}
@@ -37,12 +37,12 @@ fn test_refreshing_resource_sets_done_chan_the_appropriate_value() {
     response_bodies.iter().for_each(|body| {
         *response.body.lock().unwrap() = body.clone();
         // First, store the 'normal' response.
-        cache.store(&request, &response);
+        cache.store(&request, None, &response);
         // Second, mutate the response into a 304 response, and refresh the stored one.
         response.status = Some((StatusCode::NOT_MODIFIED, String::from("304")));
         let (send, recv) = unbounded();
         let mut done_chan = Some((send, recv));
-        let refreshed_response = cache.refresh(&request, response.clone(), &mut done_chan);
+        let refreshed_response = cache.refresh(&request, None, response.clone(), &mut done_chan);
         // Ensure a resource was found, and refreshed.
         assert!(refreshed_response.is_some());
         match body {
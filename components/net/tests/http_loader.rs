@@ -15,8 +15,9 @@ use cookie_rs::Cookie as CookiePair;
 use crossbeam_channel::{unbounded, Receiver};
 use devtools_traits::{
     ChromeToDevtoolsControlMsg, DevtoolsControlMsg, HttpRequest as DevtoolsHttpRequest,
-    HttpResponse as DevtoolsHttpResponse, NetworkEvent,
+    HttpResponse as DevtoolsHttpResponse, NetworkEvent, NetworkThrottling, ResourceHint,
 };
+use embedder_traits::{ConnectionType, NetworkChange};
 use flate2::write::{DeflateEncoder, GzEncoder};
 use flate2::Compression;
 use headers::authorization::Basic;
@@ -32,18 +33,20 @@ use ipc_channel::router::ROUTER;
 use msg::constellation_msg::TEST_PIPELINE_ID;
 use net::cookie::Cookie;
 use net::cookie_storage::CookieStorage;
+use net::fetch::methods;
 use net::http_loader::determine_requests_referrer;
 use net::resource_thread::AuthCacheEntry;
 use net::test::replace_host_table;
 use net_traits::request::{
-    BodyChunkRequest, BodyChunkResponse, BodySource, CredentialsMode, Destination, Referrer,
-    RequestBody, RequestBuilder,
+    BodyChunkRequest, BodyChunkResponse, BodySource, CredentialsMode, Destination, Initiator,
+    Referrer, RequestBody, RequestBuilder,
 };
 use net_traits::response::ResponseBody;
 use net_traits::{CookieSource, NetworkError, ReferrerPolicy};
 use servo_url::{ImmutableOrigin, ServoUrl};
+use tokio_test::block_on;
 
-use crate::{fetch, fetch_with_context, make_server, new_fetch_context};
+use crate::{fetch, fetch_with_context, make_server, new_fetch_context, FetchResponseCollector};
 
 fn mock_origin() -> ImmutableOrigin {
     ServoUrl::parse("http://servo.org").unwrap().origin()
@@ -298,6 +301,7 @@ fn test_request_and_response_data_with_network_messages() {
         connect_time: devhttprequest.connect_time,
         send_time: devhttprequest.send_time,
         is_xhr: false,
+        resource_hint: None,
     };
 
     let content = "Yay!";
@@ -359,6 +363,216 @@ fn test_request_and_response_message_from_devtool_without_pipeline_id() {
     assert!(devtools_port.try_recv().is_err());
 }
 
+#[test]
+fn test_prefetch_request_to_devtools() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url, Referrer::NoReferrer)
+        .initiator(Initiator::Prefetch)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let (devtools_chan, devtools_port) = unbounded();
+    let response = fetch(&mut request, Some(devtools_chan));
+    let _ = server.close();
+    assert!(response
+        .internal_response
+        .unwrap()
+        .status
+        .unwrap()
+        .0
+        .is_success());
+
+    let devhttprequest = expect_devtools_http_request(&devtools_port);
+    assert!(!devhttprequest.is_xhr);
+    assert_eq!(devhttprequest.resource_hint, Some(ResourceHint::Prefetch));
+}
+
+#[test]
+fn test_request_to_preconnected_origin_to_devtools() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+
+    let (devtools_chan, devtools_port) = unbounded();
+    let context = new_fetch_context(Some(devtools_chan), None, None);
+    let (sender, receiver) = unbounded();
+    let mut target = FetchResponseCollector { sender };
+    // The connection opened ahead is tied to the runtime it was opened in.
+    block_on(async {
+        context.state.warm_pool.preconnect(&url).await;
+        methods::fetch(&mut request, &mut target, &context).await;
+    });
+    let response = receiver.recv().unwrap();
+    let _ = server.close();
+    assert!(response
+        .internal_response
+        .unwrap()
+        .status
+        .unwrap()
+        .0
+        .is_success());
+
+    let devhttprequest = expect_devtools_http_request(&devtools_port);
+    assert_eq!(devhttprequest.resource_hint, Some(ResourceHint::Preconnect));
+}
+
+#[test]
+fn test_connection_pool_report_and_eviction() {
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+    let origin = url.origin().ascii_serialization();
+
+    let mut requests: Vec<_> = (0..3)
+        .map(|_| {
+            RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+                .origin(mock_origin())
+                .pipeline_id(Some(TEST_PIPELINE_ID))
+                .build()
+        })
+        .collect();
+    let context = new_fetch_context(None, None, None);
+    let (sender, receiver) = unbounded();
+    let mut target = FetchResponseCollector { sender };
+    let monitor = &context.state.connection_monitor;
+    let opened_and_sent = || {
+        let report = monitor.report();
+        let connections = report
+            .origins
+            .into_iter()
+            .find(|connections| connections.origin == origin)
+            .unwrap();
+        (
+            connections.opened,
+            connections.requests,
+            connections.open.len(),
+        )
+    };
+    // The pooled connections are tied to the runtime they were opened in.
+    block_on(async {
+        methods::fetch(&mut requests[0], &mut target, &context).await;
+        methods::fetch(&mut requests[1], &mut target, &context).await;
+        assert_eq!(opened_and_sent(), (1, 2, 1));
+
+        monitor.evict_all();
+        assert_eq!(opened_and_sent(), (1, 2, 0));
+        methods::fetch(&mut requests[2], &mut target, &context).await;
+        assert_eq!(opened_and_sent(), (2, 3, 1));
+    });
+    let _ = server.close();
+
+    for _ in 0..3 {
+        let response = receiver.recv().unwrap();
+        assert!(response
+            .internal_response
+            .unwrap()
+            .status
+            .unwrap()
+            .0
+            .is_success());
+    }
+}
+
+#[test]
+fn test_network_change_evicts_connections_and_allows_retries() {
+    let context = new_fetch_context(None, None, None);
+    let monitor = &context.state.connection_monitor;
+    let network_changes = monitor.network_changes();
+    assert!(!monitor.failed_by_network_change(network_changes));
+
+    monitor.network_changed(NetworkChange::Down);
+    assert_eq!(monitor.network_changes(), network_changes + 1);
+    assert!(!monitor.failed_by_network_change(network_changes));
+
+    monitor.network_changed(NetworkChange::Up(ConnectionType::Wifi));
+    assert!(monitor.failed_by_network_change(network_changes));
+    assert!(!monitor.failed_by_network_change(monitor.network_changes()));
+    assert!(monitor
+        .report()
+        .origins
+        .iter()
+        .all(|origin| origin.open.is_empty()));
+}
+
+#[test]
+fn test_work_offline_answers_from_the_cache_only() {
+    let requested = Arc::new(AtomicBool::new(false));
+    let requested_clone = requested.clone();
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        requested_clone.store(true, Ordering::SeqCst);
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let mut context = new_fetch_context(None, None, None);
+    context.state.work_offline.store(true, Ordering::SeqCst);
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert_eq!(
+        response.get_network_error(),
+        Some(&NetworkError::Internal(
+            "Couldn't find response in cache".to_owned()
+        ))
+    );
+    assert!(!requested.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_offline_network_throttling_fails_the_requests_of_the_pipeline() {
+    let requested = Arc::new(AtomicBool::new(false));
+    let requested_clone = requested.clone();
+    let handler = move |_: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
+        requested_clone.store(true, Ordering::SeqCst);
+        *response.body_mut() = b"Yay!".to_vec().into();
+    };
+    let (server, url) = make_server(handler);
+
+    let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let mut context = new_fetch_context(None, None, None);
+    context
+        .state
+        .throttler
+        .set(vec![TEST_PIPELINE_ID], NetworkThrottling::preset("offline"));
+    let response = fetch_with_context(&mut request, &mut context);
+
+    assert!(response.is_network_error());
+    assert!(!requested.load(Ordering::SeqCst));
+
+    // Once the throttling is cleared, the pipeline can reach the network again.
+    context.state.throttler.set(vec![TEST_PIPELINE_ID], None);
+    let mut request = RequestBuilder::new(url.clone(), Referrer::NoReferrer)
+        .origin(mock_origin())
+        .pipeline_id(Some(TEST_PIPELINE_ID))
+        .build();
+    let response = fetch_with_context(&mut request, &mut context);
+
+    let _ = server.close();
+
+    assert!(!response.is_network_error());
+    assert!(requested.load(Ordering::SeqCst));
+}
+
 #[test]
 fn test_redirected_request_to_devtools() {
     let post_handler = move |request: HyperRequest<Body>, response: &mut HyperResponse<Body>| {
@@ -0,0 +1,35 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use msg::constellation_msg::TEST_PIPELINE_ID;
+use net::keepalive::{adopt_body, KeepAliveQuota};
+use net_traits::request::{Referrer, RequestBody, RequestBuilder, KEEPALIVE_QUOTA};
+use servo_url::ServoUrl;
+use tokio_test::block_on;
+
+#[test]
+fn test_keepalive_quota_is_shared_by_the_requests_of_a_pipeline() {
+    let quota = KeepAliveQuota::new();
+    let pipeline = Some(TEST_PIPELINE_ID);
+    assert!(quota.reserve(pipeline, KEEPALIVE_QUOTA - 10));
+    assert!(!quota.reserve(pipeline, 11));
+    assert!(quota.reserve(None, 11));
+    assert!(quota.reserve(pipeline, 10));
+    assert_eq!(quota.in_flight(pipeline), KEEPALIVE_QUOTA);
+
+    quota.release(pipeline, KEEPALIVE_QUOTA - 10);
+    assert_eq!(quota.in_flight(pipeline), 10);
+    assert!(quota.reserve(pipeline, 11));
+}
+
+#[test]
+fn test_adopted_body_is_read_whole() {
+    let url = ServoUrl::parse("https://servo.org/beacon").unwrap();
+    let mut request = RequestBuilder::new(url, Referrer::NoReferrer)
+        .keep_alive(true)
+        .body(Some(RequestBody::from_bytes(b"beacon".to_vec())))
+        .build();
+    assert_eq!(block_on(adopt_body(&mut request)), Some(6));
+    assert_eq!(request.body.as_ref().and_then(|body| body.len()), Some(6));
+}
@@ -5,6 +5,7 @@
 #![cfg(test)]
 #![allow(dead_code)]
 
+mod client_hints;
 mod cookie;
 mod cookie_http_state;
 mod data_loader;
@@ -14,9 +15,14 @@ mod filemanager_thread;
 mod hsts;
 mod http_cache;
 mod http_loader;
+mod keepalive;
 mod mime_classifier;
+mod partitioning;
+mod reporting;
 mod resource_thread;
+mod server_timing;
 mod subresource_integrity;
+mod websocket_deflate;
 
 use core::convert::Infallible;
 use std::fs::File;
@@ -35,9 +41,12 @@ use hyper::server::Server as HyperServer;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request as HyperRequest, Response as HyperResponse};
 use lazy_static::lazy_static;
+use net::archive::store::ArchiveStore;
 use net::fetch::cors_cache::CorsCache;
 use net::fetch::methods::{self, CancellationListener, FetchContext};
 use net::filemanager_thread::FileManager;
+use net::har::HarRecorder;
+use net::request_interceptor::RequestInterceptor;
 use net::resource_thread::CoreResourceThreadPool;
 use net::test::HttpState;
 use net_traits::filemanager_thread::FileTokenCheck;
@@ -107,7 +116,7 @@ fn new_fetch_context(
         user_agent: DEFAULT_USER_AGENT.into(),
         devtools_chan: dc.map(|dc| Arc::new(Mutex::new(dc))),
         filemanager: Arc::new(Mutex::new(FileManager::new(
-            sender,
+            sender.clone(),
             pool_handle.unwrap_or_else(|| Weak::new()),
         ))),
         file_token: FileTokenCheck::NotRequired,
@@ -115,6 +124,9 @@ fn new_fetch_context(
         timing: ServoArc::new(Mutex::new(ResourceFetchTiming::new(
             ResourceTimingType::Navigation,
         ))),
+        request_interceptor: Arc::new(RequestInterceptor::new(sender)),
+        har_recorder: Arc::new(HarRecorder::new()),
+        archives: Arc::new(ArchiveStore::new()),
     }
 }
 impl FetchTaskTarget for FetchResponseCollector {
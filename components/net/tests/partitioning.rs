@@ -0,0 +1,144 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::sync::Arc;
+
+use http::header::{HeaderValue, CACHE_CONTROL, STRICT_TRANSPORT_SECURITY};
+use http::HeaderMap;
+use msg::constellation_msg::TEST_PIPELINE_ID;
+use net::http_cache::HttpCache;
+use net::partitioning::NetworkPartitionKey;
+use net::test::HttpState;
+use net_traits::request::{Destination, Origin, Referrer, Request};
+use net_traits::response::{HttpsState, Response};
+use net_traits::{ResourceFetchTiming, ResourceTimingType, TopLevelDocument};
+use servo_url::ServoUrl;
+
+fn request_from_test_pipeline(url: &str, destination: Destination) -> Request {
+    let url = ServoUrl::parse(url).unwrap();
+    let mut request = Request::new(
+        url.clone(),
+        Some(Origin::Origin(url.origin())),
+        Referrer::NoReferrer,
+        Some(TEST_PIPELINE_ID),
+        HttpsState::None,
+    );
+    request.destination = destination;
+    request
+}
+
+fn key_for(url: &str) -> NetworkPartitionKey {
+    NetworkPartitionKey::for_url(&ServoUrl::parse(url).unwrap())
+}
+
+#[test]
+fn test_nested_pipelines_use_the_site_of_their_top_level_document() {
+    let state = HttpState::new();
+    let top_level_url = ServoUrl::parse("https://www.servo.org/index.html").unwrap();
+    state
+        .partitions
+        .set(TEST_PIPELINE_ID, Some(TopLevelDocument::At(top_level_url)));
+
+    let request =
+        request_from_test_pipeline("https://tracker.example/pixel.png", Destination::Image);
+    assert_eq!(
+        state.partitions.key_for_request(&request),
+        Some(key_for("https://servo.org"))
+    );
+    let request = request_from_test_pipeline("https://tracker.example/", Destination::Document);
+    assert_eq!(
+        state.partitions.key_for_request(&request),
+        Some(key_for("https://servo.org"))
+    );
+
+    state.partitions.set(TEST_PIPELINE_ID, None);
+    assert_eq!(state.partitions.key_for_request(&request), None);
+}
+
+#[test]
+fn test_top_level_pipelines_use_the_site_they_navigated_to() {
+    let state = HttpState::new();
+    state
+        .partitions
+        .set(TEST_PIPELINE_ID, Some(TopLevelDocument::Itself));
+
+    let navigation = request_from_test_pipeline("http://servo.org/", Destination::Document);
+    assert_eq!(
+        state.partitions.key_for_request(&navigation),
+        Some(key_for("https://servo.org"))
+    );
+    let subresource = request_from_test_pipeline("https://cdn.example/app.js", Destination::Script);
+    assert_eq!(state.partitions.key_for_request(&subresource), None);
+
+    // The navigation was redirected to another site.
+    state.partitions.navigated(
+        Some(TEST_PIPELINE_ID),
+        &ServoUrl::parse("https://docs.servo.example/").unwrap(),
+    );
+    assert_eq!(
+        state.partitions.key_for_request(&subresource),
+        Some(key_for("https://servo.example"))
+    );
+}
+
+#[test]
+fn test_state_of_a_site_is_dropped_once_no_pipeline_uses_it() {
+    let state = HttpState::new();
+    let top_level_url = ServoUrl::parse("https://www.servo.org/index.html").unwrap();
+    state
+        .partitions
+        .set(TEST_PIPELINE_ID, Some(TopLevelDocument::At(top_level_url)));
+    let site = key_for("https://servo.org");
+    let partition_state = state.partitions.state(&site);
+    assert!(Arc::ptr_eq(
+        &partition_state,
+        &state.partitions.state(&site)
+    ));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        STRICT_TRANSPORT_SECURITY,
+        HeaderValue::from_static("max-age=31536000"),
+    );
+    let secure_url = ServoUrl::parse("https://tracker.example/").unwrap();
+    state.update_hsts_list_from_response(Some(&site), &secure_url, &headers);
+
+    state.partitions.set(TEST_PIPELINE_ID, None);
+    assert!(state.partitions.states().is_empty());
+
+    // The hosts that asked to only be reached securely are still remembered, but only
+    // under the site they asked it under.
+    let mut url = ServoUrl::parse("http://tracker.example/").unwrap();
+    state.apply_hsts_rules(Some(&site), &mut url);
+    assert_eq!(url.scheme(), "https");
+    let mut url = ServoUrl::parse("http://tracker.example/").unwrap();
+    state.apply_hsts_rules(Some(&key_for("https://example.com")), &mut url);
+    assert_eq!(url.scheme(), "http");
+}
+
+#[test]
+fn test_responses_are_only_cached_for_the_partition_that_stored_them() {
+    let request =
+        request_from_test_pipeline("https://tracker.example/pixel.png", Destination::Image);
+    let timing = ResourceFetchTiming::new(ResourceTimingType::Resource);
+    let mut response = Response::new(request.current_url(), timing);
+    response
+        .headers
+        .insert(CACHE_CONTROL, HeaderValue::from_static("max-age=3600"));
+
+    let first_site = key_for("https://servo.org");
+    let second_site = key_for("https://example.com");
+    let mut cache = HttpCache::new();
+    cache.store(&request, Some(&first_site), &response);
+
+    assert!(cache
+        .construct_response(&request, Some(&first_site), &mut None)
+        .is_some());
+    assert!(cache
+        .construct_response(&request, Some(&second_site), &mut None)
+        .is_none());
+    assert!(cache
+        .construct_response(&request, None, &mut None)
+        .is_none());
+}
@@ -0,0 +1,170 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use http::header::HeaderValue;
+use http::HeaderMap;
+use msg::constellation_msg::TEST_PIPELINE_ID;
+use net::reporting::ReportingQueue;
+use net_traits::reporting::{parse_reporting_endpoints, Report};
+use serde_json::{json, Value};
+use servo_url::ServoUrl;
+
+fn report(url: &str, timestamp: u64) -> Report {
+    Report {
+        type_: "deprecation".to_owned(),
+        url: ServoUrl::parse(url).unwrap(),
+        user_agent: "Servo".to_owned(),
+        body: r#"{"id":"sync-xhr"}"#.to_owned(),
+        timestamp,
+    }
+}
+
+fn endpoint() -> ServoUrl {
+    ServoUrl::parse("https://reports.servo.org/").unwrap()
+}
+
+#[test]
+fn test_reports_queued_together_are_delivered_in_one_batch() {
+    let queue = ReportingQueue::new();
+    let now = Instant::now();
+    assert!(queue.queue(report("https://servo.org/a", 0), endpoint(), now));
+    assert!(!queue.queue(
+        report("https://servo.org/b", 0),
+        endpoint(),
+        now + Duration::from_millis(500)
+    ));
+    assert!(!queue.queue(report("https://example.com/", 0), endpoint(), now));
+
+    let due = queue.next_delivery().unwrap();
+    assert_eq!(due, now + Duration::from_secs(1));
+    assert!(queue.take_due(now).is_empty());
+
+    let mut batches = queue.take_due(due);
+    batches.sort_by_key(|batch| batch.reports().count());
+    assert_eq!(batches.len(), 2);
+    assert_eq!(
+        batches[1].origin,
+        ServoUrl::parse("https://servo.org").unwrap().origin()
+    );
+    let urls: Vec<_> = batches[1]
+        .reports()
+        .map(|report| report.url.as_str())
+        .collect();
+    assert_eq!(urls, ["https://servo.org/a", "https://servo.org/b"]);
+    assert_eq!(queue.next_delivery(), None);
+}
+
+#[test]
+fn test_failed_deliveries_are_tried_again_later_until_too_many_attempts() {
+    let queue = ReportingQueue::new();
+    let now = Instant::now();
+    queue.queue(report("https://servo.org/", 0), endpoint(), now);
+
+    let batch = queue.take_due(now + Duration::from_secs(1)).pop().unwrap();
+    queue.retry(batch, now);
+    assert_eq!(queue.next_delivery(), Some(now + Duration::from_secs(5)));
+
+    let batch = queue.take_due(now + Duration::from_secs(5)).pop().unwrap();
+    queue.retry(batch, now);
+    assert_eq!(queue.next_delivery(), Some(now + Duration::from_secs(10)));
+
+    let batch = queue.take_due(now + Duration::from_secs(10)).pop().unwrap();
+    queue.retry(batch, now);
+    assert_eq!(queue.next_delivery(), None);
+}
+
+#[test]
+fn test_reports_queued_while_others_wait_to_be_tried_again_are_not_delayed() {
+    let queue = ReportingQueue::new();
+    let now = Instant::now();
+    queue.queue(report("https://servo.org/a", 0), endpoint(), now);
+    let batch = queue.take_due(now + Duration::from_secs(1)).pop().unwrap();
+    queue.retry(batch, now);
+
+    queue.queue(report("https://servo.org/b", 0), endpoint(), now);
+    let batch = queue.take_due(now + Duration::from_secs(1)).pop().unwrap();
+    let urls: Vec<_> = batch.reports().map(|report| report.url.as_str()).collect();
+    assert_eq!(urls, ["https://servo.org/b"]);
+}
+
+#[test]
+fn test_batch_is_serialized_with_the_age_of_its_reports() {
+    let queue = ReportingQueue::new();
+    let now = Instant::now();
+    queue.queue(report("https://servo.org/", 1000), endpoint(), now);
+    let batch = queue.take_due(now + Duration::from_secs(1)).pop().unwrap();
+
+    let body = batch.serialize(UNIX_EPOCH + Duration::from_millis(1500));
+    let body: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        body,
+        json!([{
+            "age": 500,
+            "type": "deprecation",
+            "url": "https://servo.org/",
+            "user_agent": "Servo",
+            "body": { "id": "sync-xhr" },
+        }])
+    );
+}
+
+#[test]
+fn test_crash_report_endpoint_is_taken_once() {
+    let queue = ReportingQueue::new();
+    queue.set_crash_report_endpoint(TEST_PIPELINE_ID, Some(endpoint()));
+    assert_eq!(
+        queue.take_crash_report_endpoint(TEST_PIPELINE_ID),
+        Some(endpoint())
+    );
+    assert_eq!(queue.take_crash_report_endpoint(TEST_PIPELINE_ID), None);
+}
+
+#[test]
+fn test_parse_reporting_endpoints() {
+    let url = ServoUrl::parse("https://servo.org/index.html").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.append(
+        "reporting-endpoints",
+        HeaderValue::from_static(r#"default="/reports", other="https://example.com/r";p=1"#),
+    );
+    headers.append(
+        "reporting-endpoints",
+        HeaderValue::from_static(r#"insecure="http://example.com/", token=value"#),
+    );
+
+    let endpoints = parse_reporting_endpoints(&headers, &url);
+    assert_eq!(endpoints.len(), 2);
+    assert_eq!(
+        endpoints["default"],
+        ServoUrl::parse("https://servo.org/reports").unwrap()
+    );
+    assert_eq!(
+        endpoints["other"],
+        ServoUrl::parse("https://example.com/r").unwrap()
+    );
+}
+
+#[test]
+fn test_insecure_documents_do_not_configure_reporting_endpoints() {
+    let url = ServoUrl::parse("http://servo.org/").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "reporting-endpoints",
+        HeaderValue::from_static(r#"default="https://servo.org/reports""#),
+    );
+    assert!(parse_reporting_endpoints(&headers, &url).is_empty());
+}
+
+#[test]
+fn test_invalid_reporting_endpoints_header_is_ignored() {
+    let url = ServoUrl::parse("https://servo.org/").unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "reporting-endpoints",
+        HeaderValue::from_static(r#"Default="/reports""#),
+    );
+    assert!(parse_reporting_endpoints(&headers, &url).is_empty());
+}
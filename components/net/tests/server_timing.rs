@@ -0,0 +1,57 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use http::header::HeaderValue;
+use http::HeaderMap;
+use net_traits::server_timing::{parse_server_timing, ServerTimingMetric};
+
+fn metric(name: &str, duration: f64, description: &str) -> ServerTimingMetric {
+    ServerTimingMetric {
+        name: name.to_owned(),
+        duration,
+        description: description.to_owned(),
+    }
+}
+
+#[test]
+fn test_parse_server_timing() {
+    let mut headers = HeaderMap::new();
+    headers.append(
+        "server-timing",
+        HeaderValue::from_static(r#"db;dur=53.2, cache;desc="Cache, read";dur=2"#),
+    );
+    headers.append("server-timing", HeaderValue::from_static("miss"));
+
+    assert_eq!(
+        parse_server_timing(&headers),
+        [
+            metric("db", 53.2, ""),
+            metric("cache", 2., "Cache, read"),
+            metric("miss", 0., ""),
+        ]
+    );
+}
+
+#[test]
+fn test_server_timing_parameters_are_only_taken_once() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "server-timing",
+        HeaderValue::from_static(r#"total ; DUR = 12 ; dur=30; desc=first; desc="second"; x=1"#),
+    );
+    assert_eq!(
+        parse_server_timing(&headers),
+        [metric("total", 12., "first")]
+    );
+}
+
+#[test]
+fn test_server_timing_metrics_without_a_valid_name_are_ignored() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "server-timing",
+        HeaderValue::from_static(r#""quoted";dur=1, ;dur=2, app;dur=slow"#),
+    );
+    assert_eq!(parse_server_timing(&headers), [metric("app", 0., "")]);
+}
@@ -0,0 +1,87 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use net::websocket_deflate::{DeflateParams, Deflater, MAX_INFLATED_SIZE};
+
+#[test]
+fn test_response_without_the_extension_does_not_negotiate_it() {
+    assert_eq!(DeflateParams::from_response_header(""), Ok(None));
+}
+
+#[test]
+fn test_response_parameters_are_parsed() {
+    let params = DeflateParams::from_response_header(
+        "permessage-deflate; server_no_context_takeover; client_max_window_bits=\"10\"",
+    );
+    assert_eq!(
+        params,
+        Ok(Some(DeflateParams {
+            server_no_context_takeover: true,
+            client_no_context_takeover: false,
+            server_max_window_bits: None,
+            client_max_window_bits: Some(10),
+        }))
+    );
+}
+
+#[test]
+fn test_invalid_responses_fail_the_connection() {
+    for header in [
+        "x-webkit-deflate-frame",
+        "permessage-deflate, permessage-deflate",
+        "permessage-deflate; server_max_window_bits=16",
+        "permessage-deflate; client_max_window_bits",
+        "permessage-deflate; server_no_context_takeover; server_no_context_takeover",
+        "permessage-deflate; unknown_parameter",
+    ] {
+        assert!(
+            DeflateParams::from_response_header(header).is_err(),
+            "{} was accepted",
+            header
+        );
+    }
+}
+
+#[test]
+fn test_messages_survive_a_round_trip() {
+    let mut client = Deflater::new(DeflateParams::default());
+    let mut server = Deflater::new(DeflateParams::default());
+    // The second message refers back to the first one, through the shared context.
+    for message in [&b"Hello, Hello, Hello"[..], b"Hello again", b""] {
+        let compressed = client.deflate(message).unwrap();
+        assert_eq!(server.inflate(compressed).unwrap(), message);
+    }
+}
+
+#[test]
+fn test_messages_survive_a_round_trip_without_context_takeover() {
+    let params = DeflateParams {
+        server_no_context_takeover: true,
+        client_no_context_takeover: true,
+        ..DeflateParams::default()
+    };
+    let mut client = Deflater::new(params.clone());
+    let mut fresh_server = || Deflater::new(params.clone());
+    for message in [&b"Hello, Hello, Hello"[..], b"Hello again"] {
+        let compressed = client.deflate(message).unwrap();
+        assert_eq!(fresh_server().inflate(compressed).unwrap(), message);
+    }
+}
+
+#[test]
+fn test_messages_are_sent_uncompressed_for_small_windows() {
+    let deflater = Deflater::new(DeflateParams {
+        client_max_window_bits: Some(9),
+        ..DeflateParams::default()
+    });
+    assert!(!deflater.compresses());
+}
+
+#[test]
+fn test_inflating_too_large_a_message_fails() {
+    let mut client = Deflater::new(DeflateParams::default());
+    let mut server = Deflater::new(DeflateParams::default());
+    let compressed = client.deflate(&vec![0; MAX_INFLATED_SIZE + 1]).unwrap();
+    assert!(server.inflate(compressed).is_err());
+}
@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The network conditions emulated for the requests of some pipelines, which devtools and
+//! WebDriver set for a whole tab to test how pages perform on slower networks.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use devtools_traits::NetworkThrottling;
+use msg::constellation_msg::PipelineId;
+
+#[derive(Clone, Default)]
+pub struct NetworkThrottler {
+    pipelines: Arc<RwLock<HashMap<PipelineId, NetworkThrottling>>>,
+}
+
+impl NetworkThrottler {
+    pub fn new() -> NetworkThrottler {
+        NetworkThrottler::default()
+    }
+
+    /// Emulate `throttling` for the requests of `pipelines`, or stop emulating any network
+    /// conditions for them.
+    pub fn set(&self, pipelines: Vec<PipelineId>, throttling: Option<NetworkThrottling>) {
+        let mut throttled = self.pipelines.write().unwrap();
+        for pipeline in pipelines {
+            match throttling {
+                Some(throttling) => throttled.insert(pipeline, throttling),
+                None => throttled.remove(&pipeline),
+            };
+        }
+    }
+
+    /// The network conditions emulated for the requests of `pipeline`, if any.
+    pub fn get(&self, pipeline: Option<PipelineId>) -> Option<NetworkThrottling> {
+        let pipeline = pipeline?;
+        self.pipelines.read().unwrap().get(&pipeline).copied()
+    }
+}
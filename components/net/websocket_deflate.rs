@@ -0,0 +1,493 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The permessage-deflate WebSocket extension (RFC 7692). The WebSocket protocol
+//! implementation doesn't support extensions, so the extension is applied to the frames
+//! going through the connection below it: the compressed messages it receives are
+//! inflated before it parses them, and the messages it sends are deflated after.
+
+use std::io::{self, Cursor};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tungstenite::protocol::frame::coding::{Data, OpCode};
+use tungstenite::protocol::frame::FrameHeader;
+
+pub const EXTENSION_NAME: &str = "permessage-deflate";
+
+/// The offer sent in the `Sec-WebSocket-Extensions` header of the opening handshake.
+pub const EXTENSION_OFFER: &str = "permessage-deflate; client_max_window_bits";
+
+/// The largest message inflated, which is the largest frame the WebSocket protocol
+/// implementation accepts.
+pub const MAX_INFLATED_SIZE: usize = 16 << 20;
+
+/// The end of the DEFLATE blocks of each message, which is left out of its frames.
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// The window the compressor uses, which can't be made smaller.
+const COMPRESSOR_WINDOW_BITS: u8 = 15;
+
+/// The parameters of the extension the server accepted.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeflateParams {
+    pub server_no_context_takeover: bool,
+    pub client_no_context_takeover: bool,
+    pub server_max_window_bits: Option<u8>,
+    pub client_max_window_bits: Option<u8>,
+}
+
+impl DeflateParams {
+    /// Parse the `Sec-WebSocket-Extensions` header of the handshake response. Returns
+    /// `None` if the server did not accept the extension, and an error if it accepted an
+    /// extension that wasn't offered or invalid parameters, which fails the connection.
+    pub fn from_response_header(value: &str) -> Result<Option<DeflateParams>, String> {
+        let mut accepted = None;
+        for extension in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let mut parts = extension.split(';').map(str::trim);
+            let name = parts.next().unwrap_or("");
+            if !name.eq_ignore_ascii_case(EXTENSION_NAME) {
+                return Err(format!("Unexpected extension {}", name));
+            }
+            if accepted.is_some() {
+                return Err(format!("{} accepted twice", EXTENSION_NAME));
+            }
+            let mut params = DeflateParams::default();
+            for param in parts {
+                let (key, value) = match param.split_once('=') {
+                    Some((key, value)) => (key.trim(), Some(value.trim().trim_matches('"'))),
+                    None => (param, None),
+                };
+                match (key.to_ascii_lowercase().as_str(), value) {
+                    ("server_no_context_takeover", None) if !params.server_no_context_takeover => {
+                        params.server_no_context_takeover = true
+                    },
+                    ("client_no_context_takeover", None) if !params.client_no_context_takeover => {
+                        params.client_no_context_takeover = true
+                    },
+                    ("server_max_window_bits", Some(bits))
+                        if params.server_max_window_bits.is_none() =>
+                    {
+                        params.server_max_window_bits = Some(parse_window_bits(bits)?)
+                    },
+                    ("client_max_window_bits", Some(bits))
+                        if params.client_max_window_bits.is_none() =>
+                    {
+                        params.client_max_window_bits = Some(parse_window_bits(bits)?)
+                    },
+                    _ => return Err(format!("Invalid {} parameter {}", EXTENSION_NAME, param)),
+                }
+            }
+            accepted = Some(params);
+        }
+        Ok(accepted)
+    }
+}
+
+fn parse_window_bits(value: &str) -> Result<u8, String> {
+    match value.parse() {
+        Ok(bits) if (8..=15).contains(&bits) => Ok(bits),
+        _ => Err(format!("Invalid window size {}", value)),
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+fn apply_mask(payload: &mut [u8], mask: [u8; 4]) {
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i & 3];
+    }
+}
+
+/// The compression contexts of a connection which negotiated the extension.
+pub struct Deflater {
+    params: DeflateParams,
+    compress: Compress,
+    decompress: Decompress,
+}
+
+impl Deflater {
+    pub fn new(params: DeflateParams) -> Deflater {
+        Deflater {
+            params,
+            compress: Compress::new(Compression::default(), false),
+            decompress: Decompress::new(false),
+        }
+    }
+
+    /// Whether the messages sent are compressed. They are sent as they are when the
+    /// server asked for a smaller window than the compressor can use, which the
+    /// extension allows.
+    pub fn compresses(&self) -> bool {
+        self.params
+            .client_max_window_bits
+            .map_or(true, |bits| bits >= COMPRESSOR_WINDOW_BITS)
+    }
+
+    /// Inflate the payload of a received message, failing if it grows larger than
+    /// `MAX_INFLATED_SIZE`.
+    pub fn inflate(&mut self, mut payload: Vec<u8>) -> io::Result<Vec<u8>> {
+        payload.extend_from_slice(&DEFLATE_TRAILER);
+        let mut input = &payload[..];
+        let mut output = Vec::new();
+        let mut chunk = [0; 16384];
+        loop {
+            let (total_in, total_out) = (self.decompress.total_in(), self.decompress.total_out());
+            let status = self
+                .decompress
+                .decompress(input, &mut chunk, FlushDecompress::Sync)
+                .map_err(|e| invalid_data(e.to_string()))?;
+            let consumed = (self.decompress.total_in() - total_in) as usize;
+            let produced = (self.decompress.total_out() - total_out) as usize;
+            input = &input[consumed..];
+            output.extend_from_slice(&chunk[..produced]);
+            if output.len() > MAX_INFLATED_SIZE {
+                return Err(invalid_data("Inflated WebSocket message is too large"));
+            }
+            if status == Status::StreamEnd {
+                // The server ended the DEFLATE stream, so the next message starts another.
+                self.decompress.reset(false);
+                break;
+            }
+            if (input.is_empty() && produced < chunk.len()) || (consumed == 0 && produced == 0) {
+                break;
+            }
+        }
+        if self.params.server_no_context_takeover {
+            self.decompress.reset(false);
+        }
+        Ok(output)
+    }
+
+    /// Deflate the payload of a message to send.
+    pub fn deflate(&mut self, payload: &[u8]) -> io::Result<Vec<u8>> {
+        let mut input = payload;
+        let mut output = Vec::with_capacity(payload.len() / 2 + 64);
+        loop {
+            if output.len() == output.capacity() {
+                output.reserve(output.capacity());
+            }
+            let total_in = self.compress.total_in();
+            self.compress
+                .compress_vec(input, &mut output, FlushCompress::Sync)
+                .map_err(|e| invalid_data(e.to_string()))?;
+            input = &input[(self.compress.total_in() - total_in) as usize..];
+            if input.is_empty() && output.len() < output.capacity() {
+                break;
+            }
+        }
+        if output.ends_with(&DEFLATE_TRAILER) {
+            output.truncate(output.len() - DEFLATE_TRAILER.len());
+        }
+        if self.params.client_no_context_takeover {
+            self.compress.reset();
+        }
+        Ok(output)
+    }
+}
+
+/// A connection which applies the extension to the frames going through it, once the
+/// handshake response shows that the server accepted it.
+pub struct DeflateStream<S> {
+    inner: S,
+    /// The extension, once the handshake response was received.
+    deflater: Option<Option<Deflater>>,
+    /// The handshake response received so far.
+    handshake: Vec<u8>,
+    /// What was received after the handshake and isn't a whole frame yet.
+    read_raw: Vec<u8>,
+    /// What is ready to be read by the WebSocket protocol implementation.
+    read_ready: Vec<u8>,
+    read_position: usize,
+    /// The compressed message being received, with its opcode.
+    incoming: Option<(OpCode, Vec<u8>)>,
+    eof: bool,
+    /// What the WebSocket protocol implementation wrote and isn't a whole frame yet.
+    write_raw: Vec<u8>,
+    /// What is ready to be sent.
+    write_ready: Vec<u8>,
+    write_position: usize,
+    /// Whether the message being sent is fragmented, and so is sent as it is.
+    outgoing_fragmented: bool,
+}
+
+impl<S> DeflateStream<S> {
+    pub fn new(inner: S) -> DeflateStream<S> {
+        DeflateStream {
+            inner,
+            deflater: None,
+            handshake: vec![],
+            read_raw: vec![],
+            read_ready: vec![],
+            read_position: 0,
+            incoming: None,
+            eof: false,
+            write_raw: vec![],
+            write_ready: vec![],
+            write_position: 0,
+            outgoing_fragmented: false,
+        }
+    }
+
+    fn reads_through(&self) -> bool {
+        matches!(self.deflater, Some(None)) &&
+            self.read_raw.is_empty() &&
+            self.read_position == self.read_ready.len()
+    }
+
+    fn writes_through(&self) -> bool {
+        match self.deflater {
+            Some(Some(ref deflater)) => !deflater.compresses(),
+            _ => true,
+        }
+    }
+
+    fn receive(&mut self, bytes: &[u8]) -> io::Result<()> {
+        if self.deflater.is_some() {
+            self.read_raw.extend_from_slice(bytes);
+            return self.process_incoming();
+        }
+        self.handshake.extend_from_slice(bytes);
+        let end = match self.handshake.windows(4).position(|w| w == b"\r\n\r\n") {
+            Some(position) => position + 4,
+            None => return Ok(()),
+        };
+        self.read_raw.extend_from_slice(&self.handshake[end..]);
+        self.handshake.truncate(end);
+        self.deflater = Some(negotiated_deflater(&self.handshake)?);
+        self.read_ready.append(&mut self.handshake);
+        self.process_incoming()
+    }
+
+    fn process_incoming(&mut self) -> io::Result<()> {
+        let deflater = match self.deflater {
+            Some(Some(ref mut deflater)) => deflater,
+            _ => {
+                self.read_ready.append(&mut self.read_raw);
+                return Ok(());
+            },
+        };
+        while let Some((header, header_length, length)) = parse_frame(&self.read_raw)? {
+            let frame: Vec<u8> = self.read_raw.drain(..header_length + length).collect();
+            let payload = &frame[header_length..];
+            let compressed = match header.opcode {
+                OpCode::Data(Data::Continue) => self.incoming.is_some(),
+                OpCode::Data(_) => header.rsv1,
+                OpCode::Control(_) => false,
+            };
+            if !compressed {
+                self.read_ready.extend_from_slice(&frame);
+                continue;
+            }
+            if header.mask.is_some() {
+                return Err(invalid_data("Masked frame received from the server"));
+            }
+            let (opcode, mut message) = match self.incoming.take() {
+                Some(incoming) => incoming,
+                None => (header.opcode, vec![]),
+            };
+            if message.len() + payload.len() > MAX_INFLATED_SIZE {
+                return Err(invalid_data("Compressed WebSocket message is too large"));
+            }
+            message.extend_from_slice(payload);
+            if !header.is_final {
+                self.incoming = Some((opcode, message));
+                continue;
+            }
+            let message = deflater.inflate(message)?;
+            let header = FrameHeader {
+                is_final: true,
+                rsv1: false,
+                rsv2: false,
+                rsv3: false,
+                opcode,
+                mask: None,
+            };
+            header
+                .format(message.len() as u64, &mut self.read_ready)
+                .map_err(|e| invalid_data(e.to_string()))?;
+            self.read_ready.extend_from_slice(&message);
+        }
+        Ok(())
+    }
+
+    fn process_outgoing(&mut self) -> io::Result<()> {
+        let deflater = match self.deflater {
+            Some(Some(ref mut deflater)) => deflater,
+            _ => {
+                self.write_ready.append(&mut self.write_raw);
+                return Ok(());
+            },
+        };
+        while let Some((header, header_length, length)) = parse_frame(&self.write_raw)? {
+            let frame: Vec<u8> = self.write_raw.drain(..header_length + length).collect();
+            let compress = match header.opcode {
+                OpCode::Data(Data::Continue) => {
+                    self.outgoing_fragmented = !header.is_final;
+                    false
+                },
+                OpCode::Data(_) if !header.is_final || self.outgoing_fragmented => {
+                    self.outgoing_fragmented = !header.is_final;
+                    false
+                },
+                OpCode::Data(_) => true,
+                OpCode::Control(_) => false,
+            };
+            if !compress {
+                self.write_ready.extend_from_slice(&frame);
+                continue;
+            }
+            let mut payload = frame[header_length..].to_vec();
+            if let Some(mask) = header.mask {
+                apply_mask(&mut payload, mask);
+            }
+            let mut payload = deflater.deflate(&payload)?;
+            if let Some(mask) = header.mask {
+                apply_mask(&mut payload, mask);
+            }
+            let header = FrameHeader {
+                rsv1: true,
+                ..header
+            };
+            header
+                .format(payload.len() as u64, &mut self.write_ready)
+                .map_err(|e| invalid_data(e.to_string()))?;
+            self.write_ready.extend_from_slice(&payload);
+        }
+        Ok(())
+    }
+}
+
+impl<S: AsyncWrite + Unpin> DeflateStream<S> {
+    fn poll_write_ready(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while self.write_position < self.write_ready.len() {
+            let written =
+                ready!(Pin::new(&mut self.inner)
+                    .poll_write(cx, &self.write_ready[self.write_position..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(io::ErrorKind::WriteZero.into()));
+            }
+            self.write_position += written;
+        }
+        self.write_ready.clear();
+        self.write_position = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Parse the header of the frame at the start of `bytes`, returning it with its length
+/// and the length of the payload once the whole frame is there.
+fn parse_frame(bytes: &[u8]) -> io::Result<Option<(FrameHeader, usize, usize)>> {
+    let mut cursor = Cursor::new(bytes);
+    let (header, length) = match FrameHeader::parse(&mut cursor) {
+        Ok(Some(parsed)) => parsed,
+        Ok(None) => return Ok(None),
+        Err(e) => return Err(invalid_data(e.to_string())),
+    };
+    if length > MAX_INFLATED_SIZE as u64 {
+        return Err(invalid_data("WebSocket frame is too large"));
+    }
+    let header_length = cursor.position() as usize;
+    if bytes.len() < header_length + length as usize {
+        return Ok(None);
+    }
+    Ok(Some((header, header_length, length as usize)))
+}
+
+/// The extension the server accepted in the given handshake response.
+fn negotiated_deflater(response: &[u8]) -> io::Result<Option<Deflater>> {
+    let response = String::from_utf8_lossy(response);
+    let mut lines = response.split("\r\n");
+    let switching_protocols = lines
+        .next()
+        .map_or(false, |status| status.split(' ').nth(1) == Some("101"));
+    if !switching_protocols {
+        return Ok(None);
+    }
+    let extensions: Vec<&str> = lines
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-extensions"))
+        .map(|(_, value)| value.trim())
+        .collect();
+    let params =
+        DeflateParams::from_response_header(&extensions.join(", ")).map_err(invalid_data)?;
+    Ok(params.map(Deflater::new))
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for DeflateStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.reads_through() {
+                return Pin::new(&mut this.inner).poll_read(cx, buf);
+            }
+            if this.read_position < this.read_ready.len() {
+                let ready = &this.read_ready[this.read_position..];
+                let length = ready.len().min(buf.remaining());
+                buf.put_slice(&ready[..length]);
+                this.read_position += length;
+                if this.read_position == this.read_ready.len() {
+                    this.read_ready.clear();
+                    this.read_position = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            if this.eof {
+                return Poll::Ready(Ok(()));
+            }
+            let mut chunk = [0; 8192];
+            let mut chunk_buf = ReadBuf::new(&mut chunk);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut chunk_buf))?;
+            if chunk_buf.filled().is_empty() {
+                // Let the WebSocket protocol implementation see what was cut short.
+                this.eof = true;
+                this.read_ready.append(&mut this.handshake);
+                this.read_ready.append(&mut this.read_raw);
+                continue;
+            }
+            this.receive(chunk_buf.filled())?;
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for DeflateStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_ready(cx))?;
+        if this.writes_through() {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+        this.write_raw.extend_from_slice(buf);
+        this.process_outgoing()?;
+        // Start sending the frames, the rest goes out on the next write or flush.
+        if let Poll::Ready(Err(e)) = this.poll_write_ready(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_ready(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_ready(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
@@ -14,7 +14,7 @@
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
-use async_tungstenite::tokio::{client_async_tls_with_connector_and_config, ConnectStream};
+use async_tungstenite::tokio::{client_async_with_config, TokioAdapter};
 use async_tungstenite::WebSocketStream;
 use base64::Engine;
 use futures::future::TryFutureExt;
@@ -25,9 +25,12 @@ use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use ipc_channel::router::ROUTER;
 use lazy_static::lazy_static;
 use log::{debug, trace, warn};
+use msg::constellation_msg::PipelineId;
 use net_traits::request::{RequestBuilder, RequestMode};
 use net_traits::{CookieSource, MessageData, WebSocketDomAction, WebSocketNetworkEvent};
+use rustls::ServerName;
 use servo_url::ServoUrl;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
 use tokio::runtime::Runtime;
 use tokio::select;
@@ -44,6 +47,9 @@ use crate::cookie::Cookie;
 use crate::fetch::methods::should_be_blocked_due_to_bad_port;
 use crate::hosts::replace_host;
 use crate::http_loader::HttpState;
+use crate::partitioning::NetworkPartitionKey;
+use crate::throttling::NetworkThrottler;
+use crate::websocket_deflate::{DeflateStream, EXTENSION_OFFER};
 
 // Websockets get their own tokio runtime that's independent of the one used for
 // HTTP connections, otherwise a large number of websockets could occupy all workers
@@ -81,6 +87,10 @@ fn create_request(
 
     let key = HeaderValue::from_str(&tungstenite::handshake::client::generate_key()).unwrap();
     headers.insert("Sec-WebSocket-Key", key);
+    headers.insert(
+        "Sec-WebSocket-Extensions",
+        HeaderValue::from_static(EXTENSION_OFFER),
+    );
 
     if !protocols.is_empty() {
         let protocols = protocols.join(",");
@@ -113,12 +123,14 @@ fn create_request(
 /// This ensures that any `Cookie` or HSTS headers are recognized.
 /// Returns an error if the protocol selected by the handshake doesn't
 /// match the list of provided protocols in the original request.
+/// Otherwise returns the protocol and the extensions in use.
 fn process_ws_response(
     http_state: &HttpState,
+    partition: Option<&NetworkPartitionKey>,
     response: &Response,
     resource_url: &ServoUrl,
     protocols: &[String],
-) -> Result<Option<String>, Error> {
+) -> Result<(Option<String>, String), Error> {
     trace!("processing websocket http response for {}", resource_url);
     let mut protocol_in_use = None;
     if let Some(protocol_name) = response.headers().get("Sec-WebSocket-Protocol") {
@@ -131,6 +143,15 @@ fn process_ws_response(
         protocol_in_use = Some(protocol_name.to_string());
     }
 
+    // The connection already checked that these are the ones offered.
+    let extensions = response
+        .headers()
+        .get_all("Sec-WebSocket-Extensions")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect::<Vec<_>>()
+        .join(", ");
+
     let mut jar = http_state.cookie_jar.write().unwrap();
     // TODO(eijebong): Replace thise once typed headers settled on a cookie impl
     for cookie in response.headers().get_all(header::SET_COOKIE) {
@@ -143,15 +164,18 @@ fn process_ws_response(
         }
     }
 
-    http_state
-        .hsts_list
-        .write()
-        .unwrap()
-        .update_hsts_list_from_response(resource_url, &response.headers());
+    http_state.update_hsts_list_from_response(partition, resource_url, &response.headers());
 
-    Ok(protocol_in_use)
+    Ok((protocol_in_use, extensions))
 }
 
+/// The connection to the server, encrypted or not.
+trait Socket: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Socket for T {}
+
+type WebSocketConnection = WebSocketStream<TokioAdapter<DeflateStream<Box<dyn Socket>>>>;
+
 #[derive(Debug)]
 enum DomMsg {
     Send(Message),
@@ -205,7 +229,9 @@ fn setup_dom_listener(
 async fn run_ws_loop(
     mut dom_receiver: UnboundedReceiver<DomMsg>,
     resource_event_sender: IpcSender<WebSocketNetworkEvent>,
-    mut stream: WebSocketStream<ConnectStream>,
+    mut stream: WebSocketConnection,
+    throttler: NetworkThrottler,
+    pipeline_id: Option<PipelineId>,
 ) {
     loop {
         select! {
@@ -217,6 +243,9 @@ async fn run_ws_loop(
                 };
                 match dom_msg {
                     DomMsg::Send(m) => {
+                        if let Some(throttling) = throttler.get(pipeline_id) {
+                            tokio::time::sleep(throttling.upload_time(m.len())).await;
+                        }
                         if let Err(e) = stream.send(m).await {
                             warn!("error sending websocket message: {:?}", e);
                         }
@@ -248,6 +277,9 @@ async fn run_ws_loop(
                         break;
                     }
                 };
+                if let Some(throttling) = throttler.get(pipeline_id) {
+                    tokio::time::sleep(throttling.download_time(msg.len())).await;
+                }
                 match msg {
                     Message::Text(s) => {
                         let message = MessageData::Text(s);
@@ -304,6 +336,7 @@ async fn start_websocket(
     client: Request,
     tls_config: TlsConfig,
     dom_action_receiver: IpcReceiver<WebSocketDomAction>,
+    pipeline_id: Option<PipelineId>,
 ) -> Result<(), Error> {
     trace!("starting WS connection to {}", url);
 
@@ -313,8 +346,9 @@ async fn start_websocket(
     let host_str = client
         .uri()
         .host()
-        .ok_or_else(|| Error::Url(UrlError::NoHostName))?;
-    let host = replace_host(host_str);
+        .ok_or_else(|| Error::Url(UrlError::NoHostName))?
+        .to_owned();
+    let host = replace_host(&host_str);
     let mut net_url = Url::parse(&client.uri().to_string())
         .map_err(|e| Error::Url(UrlError::UnableToConnect(e.to_string())))?;
     net_url
@@ -328,25 +362,56 @@ async fn start_websocket(
         .port_or_known_default()
         .ok_or_else(|| Error::Url(UrlError::UnableToConnect("Unknown port".into())))?;
 
+    let throttler = http_state.throttler.clone();
+    if let Some(throttling) = throttler.get(pipeline_id) {
+        tokio::time::sleep(throttling.latency()).await;
+    }
+
     let try_socket = TcpStream::connect((&*domain.to_string(), port)).await;
     let socket = try_socket.map_err(Error::Io)?;
-    let connector = TlsConnector::from(Arc::new(tls_config));
+    let socket: Box<dyn Socket> = if net_url.scheme() == "wss" {
+        let connector = TlsConnector::from(Arc::new(tls_config));
+        let server_name = ServerName::try_from(&*host_str)
+            .map_err(|e| Error::Url(UrlError::UnableToConnect(e.to_string())))?;
+        Box::new(
+            connector
+                .connect(server_name, socket)
+                .await
+                .map_err(Error::Io)?,
+        )
+    } else {
+        Box::new(socket)
+    };
 
+    // The TLS connection is made here rather than by the WebSocket protocol implementation
+    // so that the permessage-deflate extension can be applied between the two.
     let (stream, response) =
-        client_async_tls_with_connector_and_config(client, socket, Some(connector), None).await?;
+        client_async_with_config(client, DeflateStream::new(socket), None).await?;
 
-    let protocol_in_use = process_ws_response(&http_state, &response, &url, &protocols)?;
+    let partition = http_state.pipeline_network_partition_key(pipeline_id);
+    let (protocol_in_use, extensions) =
+        process_ws_response(&http_state, partition.as_ref(), &response, &url, &protocols)?;
 
     if !initiated_close.load(Ordering::SeqCst) {
         if resource_event_sender
-            .send(WebSocketNetworkEvent::ConnectionEstablished { protocol_in_use })
+            .send(WebSocketNetworkEvent::ConnectionEstablished {
+                protocol_in_use,
+                extensions,
+            })
             .is_err()
         {
             return Ok(());
         }
 
         trace!("about to start ws loop for {}", url);
-        run_ws_loop(dom_receiver, resource_event_sender, stream).await;
+        run_ws_loop(
+            dom_receiver,
+            resource_event_sender,
+            stream,
+            throttler,
+            pipeline_id,
+        )
+        .await;
     } else {
         trace!("client closed connection for {}, not running loop", url);
     }
@@ -373,11 +438,8 @@ fn connect(
     };
 
     // https://fetch.spec.whatwg.org/#websocket-opening-handshake
-    http_state
-        .hsts_list
-        .read()
-        .unwrap()
-        .apply_hsts_rules(&mut req_builder.url);
+    let partition = http_state.pipeline_network_partition_key(req_builder.pipeline_id);
+    http_state.apply_hsts_rules(partition.as_ref(), &mut req_builder.url);
 
     let req_url = req_builder.url.clone();
 
@@ -385,6 +447,11 @@ fn connect(
         return Err("Port blocked".to_string());
     }
 
+    let throttling = http_state.throttler.get(req_builder.pipeline_id);
+    if throttling.map_or(false, |throttling| throttling.offline) {
+        return Err("The network is emulated to be offline".to_string());
+    }
+
     let client = match create_request(
         &req_url,
         &req_builder.origin.ascii_serialization(),
@@ -413,6 +480,7 @@ fn connect(
                 client,
                 tls_config,
                 dom_action_receiver,
+                req_builder.pipeline_id,
             )
             .map_err(move |e| {
                 warn!("Failed to establish a WebSocket connection: {:?}", e);
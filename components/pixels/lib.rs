@@ -78,6 +78,32 @@ pub fn rgba8_premultiply_inplace(pixels: &mut [u8]) -> bool {
     is_opaque
 }
 
+/// Flips the least significant bit of a color channel of about one in sixteen of the pixels
+/// that are not fully transparent. Which pixels and channels change only depends on `key` and
+/// on the pixels themselves, so reading the same pixels back twice gives the same result and
+/// averaging many readbacks does not remove the noise.
+pub fn rgba8_add_noise_inplace(pixels: &mut [u8], key: u64) {
+    assert!(pixels.len() % 4 == 0);
+    for rgba in pixels.chunks_mut(4) {
+        if rgba[3] == 0 {
+            continue;
+        }
+        let color = u32::from_le_bytes([rgba[0], rgba[1], rgba[2], rgba[3]]);
+        let hash = mix_u64(key ^ color as u64);
+        if hash & 0xF == 0 {
+            rgba[(hash >> 4) as usize % 3] ^= 1;
+        }
+    }
+}
+
+// The finalizer of SplitMix64.
+fn mix_u64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E3779B97F4A7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D049BB133111EB);
+    x ^ (x >> 31)
+}
+
 pub fn multiply_u8_color(a: u8, b: u8) -> u8 {
     return (a as u32 * b as u32 / 255) as u8;
 }
@@ -387,19 +387,11 @@ impl ReportsForest {
 //---------------------------------------------------------------------------
 
 mod system_reporter {
-    #[cfg(not(any(target_os = "windows", target_os = "android")))]
-    use std::ffi::CString;
-    #[cfg(not(any(target_os = "windows", target_os = "android")))]
-    use std::mem::size_of;
-    #[cfg(not(any(target_os = "windows", target_os = "android")))]
-    use std::ptr::null_mut;
-
     #[cfg(target_os = "linux")]
     use libc::c_int;
-    #[cfg(not(any(target_os = "windows", target_os = "android")))]
-    use libc::{c_void, size_t};
     use profile_traits::mem::{Report, ReportKind, ReporterRequest};
     use profile_traits::path;
+    use servo_allocator::AllocatorStats;
     #[cfg(target_os = "macos")]
     use task_info::task_basic_info::{resident_size, virtual_size};
 
@@ -434,22 +426,66 @@ mod system_reporter {
 
             // The descriptions of the following jemalloc measurements are taken
             // directly from the jemalloc documentation.
+            let allocator_stats = servo_allocator::stats();
+            let stat = |f: fn(&AllocatorStats) -> usize| allocator_stats.as_ref().map(f);
 
             // "Total number of bytes allocated by the application."
-            report(
-                path![JEMALLOC_HEAP_ALLOCATED_STR],
-                jemalloc_stat("stats.allocated"),
-            );
+            report(path![JEMALLOC_HEAP_ALLOCATED_STR], stat(|s| s.allocated));
 
             // "Total number of bytes in active pages allocated by the application.
             // This is a multiple of the page size, and greater than or equal to
             // |stats.allocated|."
-            report(path!["jemalloc-heap-active"], jemalloc_stat("stats.active"));
+            report(path!["jemalloc-heap-active"], stat(|s| s.active));
 
             // "Total number of bytes in chunks mapped on behalf of the application.
             // This is a multiple of the chunk size, and is at least as large as
             // |stats.active|. This does not include inactive chunks."
-            report(path!["jemalloc-heap-mapped"], jemalloc_stat("stats.mapped"));
+            report(path!["jemalloc-heap-mapped"], stat(|s| s.mapped));
+
+            // "Maximum number of bytes in physically resident data pages mapped by
+            // the allocator, comprising all pages dedicated to allocator metadata,
+            // pages backing active allocations, and unused dirty pages."
+            report(path!["jemalloc-heap-resident"], stat(|s| s.resident));
+
+            // Bytes in active pages that are not handed out to the application.
+            report(
+                path!["jemalloc-heap-fragmentation"],
+                stat(AllocatorStats::fragmentation),
+            );
+
+            // Dedicated arenas are reported under the name of the threads using them.
+            let dedicated_arenas = servo_allocator::dedicated_arenas();
+            for arena in allocator_stats.iter().flat_map(|s| s.arenas.iter()) {
+                let name = match dedicated_arenas
+                    .iter()
+                    .find(|(_, dedicated)| dedicated.index() == arena.index)
+                {
+                    Some((thread_name, _)) => format!("arena-{} ({})", arena.index, thread_name),
+                    None => format!("arena-{}", arena.index),
+                };
+                report(
+                    path!["jemalloc-arenas", &name, "allocated"],
+                    Some(arena.allocated()),
+                );
+                report(
+                    path!["jemalloc-arenas", &name, "active"],
+                    Some(arena.active),
+                );
+                report(path!["jemalloc-arenas", &name, "dirty"], Some(arena.dirty));
+                report(
+                    path!["jemalloc-arenas", &name, "resident"],
+                    Some(arena.resident),
+                );
+            }
+
+            // Live bytes of the allocations made in each heap partition, if
+            // they are being counted.
+            for partition in servo_allocator::partition_stats() {
+                report(
+                    path!["heap-partitions", partition.partition.name()],
+                    Some(partition.allocated),
+                );
+            }
         }
 
         request.reports_channel.send(reports);
@@ -499,61 +535,6 @@ mod system_reporter {
         None
     }
 
-    #[cfg(not(any(target_os = "windows", target_os = "android")))]
-    use jemalloc_sys::mallctl;
-
-    #[cfg(not(any(target_os = "windows", target_os = "android")))]
-    fn jemalloc_stat(value_name: &str) -> Option<usize> {
-        // Before we request the measurement of interest, we first send an "epoch"
-        // request. Without that jemalloc gives cached statistics(!) which can be
-        // highly inaccurate.
-        let epoch_name = "epoch";
-        let epoch_c_name = CString::new(epoch_name).unwrap();
-        let mut epoch: u64 = 0;
-        let epoch_ptr = &mut epoch as *mut _ as *mut c_void;
-        let mut epoch_len = size_of::<u64>() as size_t;
-
-        let value_c_name = CString::new(value_name).unwrap();
-        let mut value: size_t = 0;
-        let value_ptr = &mut value as *mut _ as *mut c_void;
-        let mut value_len = size_of::<size_t>() as size_t;
-
-        // Using the same values for the `old` and `new` parameters is enough
-        // to get the statistics updated.
-        let rv = unsafe {
-            mallctl(
-                epoch_c_name.as_ptr(),
-                epoch_ptr,
-                &mut epoch_len,
-                epoch_ptr,
-                epoch_len,
-            )
-        };
-        if rv != 0 {
-            return None;
-        }
-
-        let rv = unsafe {
-            mallctl(
-                value_c_name.as_ptr(),
-                value_ptr,
-                &mut value_len,
-                null_mut(),
-                0,
-            )
-        };
-        if rv != 0 {
-            return None;
-        }
-
-        Some(value as usize)
-    }
-
-    #[cfg(any(target_os = "windows", target_os = "android"))]
-    fn jemalloc_stat(_value_name: &str) -> Option<usize> {
-        None
-    }
-
     #[cfg(target_os = "linux")]
     fn page_size() -> usize {
         unsafe { ::libc::sysconf(::libc::_SC_PAGESIZE) as usize }
@@ -14,7 +14,7 @@ use std::{f64, thread, u32, u64};
 
 use ipc_channel::ipc::{self, IpcReceiver};
 use profile_traits::time::{
-    ProfilerCategory, ProfilerChan, ProfilerData, ProfilerMsg, TimerMetadata,
+    ProfilerCategory, ProfilerChan, ProfilerData, ProfilerMsg, TaskQueueCounters, TimerMetadata,
     TimerMetadataFrameType, TimerMetadataReflowType,
 };
 use servo_config::opts::OutputOptions;
@@ -158,6 +158,37 @@ impl Formattable for ProfilerCategory {
 
 type ProfilerBuckets = BTreeMap<(ProfilerCategory, Option<TimerMetadata>), Vec<f64>>;
 
+/// What the task queues of the event loops saw of the tasks of a task source.
+#[derive(Default)]
+struct TaskSourceStats {
+    /// The most tasks seen waiting at once in a task queue.
+    max_queued: usize,
+    /// The number of tasks taken from the task queues to run.
+    dequeued: u64,
+    /// The total time these tasks waited in the task queues.
+    total_latency: Duration,
+    /// The longest time one of these tasks waited in its task queue.
+    max_latency: Duration,
+}
+
+impl TaskSourceStats {
+    fn add(&mut self, counters: TaskQueueCounters) {
+        self.max_queued = self.max_queued.max(counters.queued);
+        self.dequeued += counters.dequeued;
+        self.total_latency += counters.total_latency;
+        self.max_latency = self.max_latency.max(counters.max_latency);
+    }
+
+    /// The mean and the longest time tasks waited in the task queues, in milliseconds.
+    fn latencies(&self) -> (f64, f64) {
+        let mean = match self.dequeued {
+            0 => 0.,
+            dequeued => self.total_latency.as_secs_f64() * 1000. / dequeued as f64,
+        };
+        (mean, self.max_latency.as_secs_f64() * 1000.)
+    }
+}
+
 // back end of the profiler that handles data aggregation and performance metrics
 pub struct Profiler {
     pub port: IpcReceiver<ProfilerMsg>,
@@ -166,6 +197,7 @@ pub struct Profiler {
     pub last_msg: Option<ProfilerMsg>,
     trace: Option<TraceDump>,
     blocked_layout_queries: HashMap<String, u32>,
+    task_sources: BTreeMap<String, TaskSourceStats>,
 }
 
 impl Profiler {
@@ -247,6 +279,7 @@ impl Profiler {
             last_msg: None,
             trace: trace,
             blocked_layout_queries: HashMap::new(),
+            task_sources: BTreeMap::new(),
         }
     }
 
@@ -272,7 +305,9 @@ impl Profiler {
                 self.find_or_insert(k, ms);
             },
             ProfilerMsg::Print => {
-                if let Some(ProfilerMsg::Time(..)) = self.last_msg {
+                if let Some(ProfilerMsg::Time(..)) | Some(ProfilerMsg::TaskQueueCounters(..)) =
+                    self.last_msg
+                {
                     // only print if more data has arrived since the last printout
                     self.print_buckets();
                 }
@@ -289,6 +324,12 @@ impl Profiler {
             ProfilerMsg::BlockedLayoutQuery(url) => {
                 *self.blocked_layout_queries.entry(url).or_insert(0) += 1;
             },
+            ProfilerMsg::TaskQueueCounters(task_source, counters) => {
+                self.task_sources
+                    .entry(task_source)
+                    .or_default()
+                    .add(counters);
+            },
             ProfilerMsg::Exit(chan) => {
                 self.print_buckets();
                 let _ = chan.send(());
@@ -355,6 +396,22 @@ impl Profiler {
                 for (url, count) in &self.blocked_layout_queries {
                     write!(file, "{}\t{}\n", url, count).unwrap();
                 }
+
+                write!(
+                    file,
+                    "_task source_\t_max queued_\t_dequeued_\t\
+                     _mean latency (ms)_\t_max latency (ms)_\n"
+                )
+                .unwrap();
+                for (task_source, stats) in &self.task_sources {
+                    let (mean, max) = stats.latencies();
+                    write!(
+                        file,
+                        "{}\t{}\t{}\t{:15.4}\t{:15.4}\n",
+                        task_source, stats.max_queued, stats.dequeued, mean, max
+                    )
+                    .unwrap();
+                }
             },
             Some(OutputOptions::Stdout(_)) => {
                 let stdout = io::stdout();
@@ -400,6 +457,27 @@ impl Profiler {
                     writeln!(&mut lock, "{}\t{}", url, count).unwrap();
                 }
                 writeln!(&mut lock, "").unwrap();
+
+                writeln!(
+                    &mut lock,
+                    "{:25} {:15} {:15} {:20} {:20}",
+                    "_task source_",
+                    "   _max queued_",
+                    "     _dequeued_",
+                    " _mean latency (ms)_",
+                    "  _max latency (ms)_"
+                )
+                .unwrap();
+                for (task_source, stats) in &self.task_sources {
+                    let (mean, max) = stats.latencies();
+                    writeln!(
+                        &mut lock,
+                        "{:25} {:15} {:15} {:20.4} {:20.4}",
+                        task_source, stats.max_queued, stats.dequeued, mean, max
+                    )
+                    .unwrap();
+                }
+                writeln!(&mut lock, "").unwrap();
             },
             None => { /* Do nothing if no output option has been set */ },
         };
@@ -16,24 +16,31 @@ use jstraceable_derive::JSTraceable;
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf)]
 pub(crate) struct AnimationTimeline {
     current_value: f64,
+    /// The value of the timeline when it was created.
+    start_value: f64,
 }
 
 impl AnimationTimeline {
     /// Creates a new "normal" timeline, i.e., a "Current" mode timer.
     #[inline]
     pub fn new() -> Self {
+        let current_value = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
         Self {
-            current_value: SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs_f64(),
+            current_value,
+            start_value: current_value,
         }
     }
 
     /// Creates a new "test mode" timeline, with initial time 0.
     #[inline]
     pub fn new_for_testing() -> Self {
-        Self { current_value: 0. }
+        Self {
+            current_value: 0.,
+            start_value: 0.,
+        }
     }
 
     /// Returns the current value of the timeline in seconds.
@@ -41,6 +48,12 @@ impl AnimationTimeline {
         self.current_value
     }
 
+    /// Returns the time elapsed since the timeline was created, in seconds. This is the
+    /// time of the document timeline of the Web Animations API.
+    pub fn elapsed(&self) -> f64 {
+        self.current_value - self.start_value
+    }
+
     /// Updates the value of the `AnimationTimeline` to the current clock time.
     pub fn update(&mut self) {
         self.current_value = SystemTime::now()
@@ -12,15 +12,23 @@ use libc::c_void;
 use msg::constellation_msg::PipelineId;
 use script_traits::{AnimationState as AnimationsPresentState, ScriptMsg, UntrustedNodeAddress};
 use serde::{Deserialize, Serialize};
+use servo_arc::Arc;
 use style::animation::{
     Animation, AnimationSetKey, AnimationState, DocumentAnimationSet, ElementAnimationSet,
     KeyframesIterationState, Transition,
 };
 use style::dom::OpaqueNode;
+use style::properties::animated_properties::AnimationValueMap;
+use style::properties::PropertyDeclarationBlock;
 use style::selector_parser::PseudoElement;
+use style::shared_lock::Locked;
 
+use crate::dom::animation::Animation as WebAnimation;
 use crate::dom::animationevent::AnimationEvent;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::{
+    AnimationMethods, AnimationPlayState,
+};
 use crate::dom::bindings::codegen::Bindings::AnimationEventBinding::AnimationEventInit;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
 use crate::dom::bindings::codegen::Bindings::TransitionEventBinding::TransitionEventInit;
@@ -29,6 +37,8 @@ use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::trace::NoTrace;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
 use crate::dom::event::Event;
 use crate::dom::node::{from_untrusted_node_address, window_from_node, Node, NodeDamage};
 use crate::dom::transitionevent::TransitionEvent;
@@ -45,6 +55,10 @@ pub(crate) struct Animations {
     /// Whether or not we have animations that are running.
     has_running_animations: Cell<bool>,
 
+    /// Whether the animations are paused, so that they are neither ticked nor reported as
+    /// running, as when WebDriver takes a screenshot of the page.
+    paused: Cell<bool>,
+
     /// A list of nodes with in-progress CSS transitions or pending events.
     rooted_nodes: DomRefCell<FxHashMap<NoTrace<OpaqueNode>, Dom<Node>>>,
 
@@ -55,6 +69,28 @@ pub(crate) struct Animations {
     /// This is used to prevent marking animations dirty when the timeline
     /// has not changed.
     timeline_value_at_last_dirty: Cell<f64>,
+
+    /// The animations created with the Web Animations API, in composite order.
+    web_animations: DomRefCell<Vec<Dom<WebAnimation>>>,
+
+    /// The effects of the web animations on the elements they target, keyed like the
+    /// animation states in `sets`.
+    web_animation_effects: DomRefCell<FxHashMap<NoTrace<AnimationSetKey>, WebAnimationEffects>>,
+}
+
+/// The effects of the web animations targeting an element, as last applied to it.
+#[derive(JSTraceable, MallocSizeOf)]
+#[crown::unrooted_must_root_lint::must_root]
+struct WebAnimationEffects {
+    target: Dom<Element>,
+    /// The animated value of each property.
+    #[ignore_malloc_size_of = "Defined in style"]
+    #[no_trace]
+    values: AnimationValueMap,
+    /// The declarations for `values`, which layout applies to the element.
+    #[ignore_malloc_size_of = "Arc"]
+    #[no_trace]
+    declarations: Arc<Locked<PropertyDeclarationBlock>>,
 }
 
 impl Animations {
@@ -62,9 +98,12 @@ impl Animations {
         Animations {
             sets: Default::default(),
             has_running_animations: Cell::new(false),
+            paused: Cell::new(false),
             rooted_nodes: Default::default(),
             pending_events: Default::default(),
             timeline_value_at_last_dirty: Cell::new(0.0),
+            web_animations: Default::default(),
+            web_animation_effects: Default::default(),
         }
     }
 
@@ -72,6 +111,8 @@ impl Animations {
         self.sets.sets.write().clear();
         self.rooted_nodes.borrow_mut().clear();
         self.pending_events.borrow_mut().clear();
+        self.web_animations.borrow_mut().clear();
+        self.web_animation_effects.borrow_mut().clear();
     }
 
     // Mark all animations dirty, if they haven't been marked dirty since the
@@ -97,6 +138,10 @@ impl Animations {
     }
 
     pub(crate) fn update_for_new_timeline_value(&self, window: &Window, now: f64) {
+        for animation in self.web_animations() {
+            animation.tick();
+        }
+
         let pipeline_id = window.pipeline_id();
         let mut sets = self.sets.sets.write();
 
@@ -159,8 +204,32 @@ impl Animations {
         // up space as soon as we are no longer tracking any animations for
         // a node.
         sets.retain(|_, state| !state.is_empty());
-        let have_running_animations = sets.values().any(|state| state.needs_animation_ticks());
+        let have_running_animations = !self.paused.get() &&
+            (sets.values().any(|state| state.needs_animation_ticks()) ||
+                self.has_running_web_animations());
+
+        self.update_running_animations_presence(window, have_running_animations);
+    }
+
+    pub(crate) fn paused(&self) -> bool {
+        self.paused.get()
+    }
 
+    /// Pause or resume the animations. While paused, the timeline of the document does not
+    /// follow the clock, and the compositor is told that there are no running animations, so
+    /// that it does not wait for them to end before it considers the page stable.
+    pub(crate) fn set_paused(&self, window: &Window, paused: bool) {
+        if self.paused.replace(paused) == paused {
+            return;
+        }
+        let have_running_animations = !paused &&
+            (self
+                .sets
+                .sets
+                .read()
+                .values()
+                .any(|state| state.needs_animation_ticks()) ||
+                self.has_running_web_animations());
         self.update_running_animations_presence(window, have_running_animations);
     }
 
@@ -187,6 +256,114 @@ impl Animations {
         window.send_to_constellation(ScriptMsg::ChangeRunningAnimationsState(state));
     }
 
+    /// Add `animation` to the web animations of the document, if it is not already,
+    /// and make sure that the document gets animation ticks while it runs.
+    pub(crate) fn add_web_animation(&self, window: &Window, animation: &WebAnimation) {
+        {
+            let mut web_animations = self.web_animations.borrow_mut();
+            if !web_animations.iter().any(|other| &**other == animation) {
+                web_animations.push(Dom::from_ref(animation));
+            }
+        }
+        if self.has_running_web_animations() {
+            self.update_running_animations_presence(window, true);
+        }
+    }
+
+    pub(crate) fn web_animations(&self) -> Vec<DomRoot<WebAnimation>> {
+        self.web_animations
+            .borrow()
+            .iter()
+            .map(|animation| DomRoot::from_ref(&**animation))
+            .collect()
+    }
+
+    fn has_running_web_animations(&self) -> bool {
+        self.web_animations.borrow().iter().any(|animation| {
+            animation.Pending() || animation.play_state() == AnimationPlayState::Running
+        })
+    }
+
+    /// Apply the effects of the web animations to their targets, returning whether the
+    /// values of any of the targets changed.
+    pub(crate) fn apply_web_animation_effects(&self, document: &Document) -> bool {
+        // Animations that were canceled are no longer ticked, until they are played again.
+        self.web_animations
+            .borrow_mut()
+            .retain(|animation| animation.play_state() != AnimationPlayState::Idle);
+
+        // The values for each target, where later animations in composite order override
+        // the values of the earlier ones. Sampling an effect for the first time may query
+        // the style of its target, so this is done before `web_animation_effects` is
+        // borrowed.
+        let mut targets: FxHashMap<AnimationSetKey, (DomRoot<Element>, AnimationValueMap)> =
+            FxHashMap::default();
+        for animation in self.web_animations() {
+            let effect = match animation.keyframe_effect() {
+                Some(effect) => effect,
+                None => continue,
+            };
+            let (target, values) = match (effect.target(), effect.animated_values()) {
+                (Some(target), Some(values)) => (target, values),
+                _ => continue,
+            };
+            let key = AnimationSetKey::new_for_non_pseudo(target.upcast::<Node>().to_opaque());
+            let (_, target_values) = targets
+                .entry(key)
+                .or_insert_with(|| (target, AnimationValueMap::default()));
+            for value in values {
+                target_values.insert(value.id(), value);
+            }
+        }
+
+        let mut changed = false;
+        let mut effects = self.web_animation_effects.borrow_mut();
+        effects.retain(|key, effects| {
+            if targets.contains_key(&key.0) {
+                return true;
+            }
+            effects
+                .target
+                .upcast::<Node>()
+                .dirty(NodeDamage::NodeStyleDamaged);
+            changed = true;
+            false
+        });
+        for (key, (target, values)) in targets {
+            let unchanged = effects
+                .get(&NoTrace(key.clone()))
+                .map_or(false, |effects| effects.values == values);
+            if unchanged {
+                continue;
+            }
+            let block = PropertyDeclarationBlock::from_animation_value_map(&values);
+            effects.insert(
+                NoTrace(key),
+                WebAnimationEffects {
+                    target: Dom::from_ref(&*target),
+                    values,
+                    declarations: Arc::new(document.style_shared_lock().wrap(block)),
+                },
+            );
+            target.upcast::<Node>().dirty(NodeDamage::NodeStyleDamaged);
+            changed = true;
+        }
+        changed
+    }
+
+    /// The declarations of the effects of the web animations targeting the element or
+    /// pseudo-element `key`, which apply on top of those of its CSS animations.
+    #[allow(unsafe_code)]
+    pub(crate) unsafe fn web_animation_declarations_for_layout(
+        &self,
+        key: &AnimationSetKey,
+    ) -> Option<Arc<Locked<PropertyDeclarationBlock>>> {
+        self.web_animation_effects
+            .borrow_for_layout()
+            .get(&NoTrace(key.clone()))
+            .map(|effects| effects.declarations.clone())
+    }
+
     pub(crate) fn running_animation_count(&self) -> usize {
         self.sets
             .sets
@@ -473,8 +473,8 @@ impl Extractable for BodyInit {
                 })
             },
             BodyInit::ReadableStream(stream) => {
-                // TODO:
-                // 1. If the keepalive flag is set, then throw a TypeError.
+                // Step 1. The callers extracting the body of a keep-alive request reject
+                // streams before it is extracted.
 
                 if stream.is_locked() || stream.is_disturbed() {
                     return Err(Error::Type(
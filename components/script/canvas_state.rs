@@ -8,9 +8,9 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use canvas_traits::canvas::{
-    Canvas2dMsg, CanvasId, CanvasMsg, CompositionOrBlending, Direction, FillOrStrokeStyle,
-    FillRule, LineCapStyle, LineJoinStyle, LinearGradientStyle, RadialGradientStyle,
-    RepetitionStyle, TextAlign, TextBaseline,
+    Canvas2dMsg, CanvasId, CanvasMsg, CompositionOrBlending, ConicGradientStyle, CornerRadii,
+    Direction, FillOrStrokeStyle, FillRule, LineCapStyle, LineJoinStyle, LinearGradientStyle,
+    RadialGradientStyle, RepetitionStyle, TextAlign, TextBaseline,
 };
 use cssparser::{Parser, ParserInput, RGBA};
 use euclid::default::{Point2D, Rect, Size2D, Transform2D};
@@ -39,7 +39,10 @@ use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::{
     CanvasDirection, CanvasFillRule, CanvasImageSource, CanvasLineCap, CanvasLineJoin,
     CanvasTextAlign, CanvasTextBaseline, ImageDataMethods,
 };
-use crate::dom::bindings::codegen::UnionTypes::StringOrCanvasGradientOrCanvasPattern;
+use crate::dom::bindings::codegen::UnionTypes::{
+    StringOrCanvasGradientOrCanvasPattern, UnrestrictedDoubleOrDOMPointInit,
+    UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
@@ -55,6 +58,7 @@ use crate::dom::imagedata::ImageData;
 use crate::dom::node::{window_from_node, Node, NodeDamage};
 use crate::dom::offscreencanvas::{OffscreenCanvas, OffscreenCanvasContext};
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
+use crate::dom::path2d::Path2D;
 use crate::dom::textmetrics::TextMetrics;
 use crate::unpremultiplytable::UNPREMULTIPLY_TABLE;
 
@@ -867,6 +871,20 @@ impl CanvasState {
         ))
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient>
+    pub fn create_conic_gradient(
+        &self,
+        global: &GlobalScope,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+    ) -> DomRoot<CanvasGradient> {
+        CanvasGradient::new(
+            global,
+            CanvasGradientStyle::Conic(ConicGradientStyle::new(*start_angle, *x, *y, Vec::new())),
+        )
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern
     pub fn create_pattern(
         &self,
@@ -1248,12 +1266,9 @@ impl CanvasState {
             },
         };
 
-        ImageData::new(
-            global,
-            size.width,
-            size.height,
-            Some(self.get_rect(canvas_size, read_rect)),
-        )
+        let mut pixels = self.get_rect(canvas_size, read_rect);
+        global.add_canvas_readback_noise(&mut pixels);
+        ImageData::new(global, size.width, size.height, Some(pixels))
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-putimagedata
@@ -1459,6 +1474,54 @@ impl CanvasState {
         receiver.recv().unwrap()
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-fill>
+    pub fn fill_path(&self, path: &Path2D, _fill_rule: CanvasFillRule) {
+        // TODO: Process fill rule
+        let style = self.state.borrow().fill_style.to_fill_or_stroke_style();
+        self.send_canvas_2d_msg(Canvas2dMsg::FillPath(path.segments(), style));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-stroke>
+    pub fn stroke_path(&self, path: &Path2D) {
+        let style = self.state.borrow().stroke_style.to_fill_or_stroke_style();
+        self.send_canvas_2d_msg(Canvas2dMsg::StrokePath(path.segments(), style));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-clip>
+    pub fn clip_path(&self, path: &Path2D, _fill_rule: CanvasFillRule) {
+        // TODO: Process fill rule
+        self.send_canvas_2d_msg(Canvas2dMsg::ClipPath(path.segments()));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath>
+    pub fn is_point_in_path_2d(
+        &self,
+        global: &GlobalScope,
+        path: &Path2D,
+        x: f64,
+        y: f64,
+        fill_rule: CanvasFillRule,
+    ) -> bool {
+        if !(x.is_finite() && y.is_finite()) {
+            return false;
+        }
+
+        let fill_rule = match fill_rule {
+            CanvasFillRule::Nonzero => FillRule::Nonzero,
+            CanvasFillRule::Evenodd => FillRule::Evenodd,
+        };
+        let (sender, receiver) =
+            profiled_ipc::channel::<bool>(global.time_profiler_chan().clone()).unwrap();
+        self.send_canvas_2d_msg(Canvas2dMsg::IsPointInPath2D(
+            path.segments(),
+            x,
+            y,
+            fill_rule,
+            sender,
+        ));
+        receiver.recv().unwrap()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-scale
     pub fn scale(&self, x: f64, y: f64) {
         if !(x.is_finite() && y.is_finite()) {
@@ -1578,6 +1641,31 @@ impl CanvasState {
         }
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    pub fn round_rect(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+    ) -> ErrorResult {
+        if !([x, y, width, height].iter().all(|val| val.is_finite())) {
+            return Ok(());
+        }
+        let radii = match normalize_corner_radii(width, height, radii)? {
+            Some(radii) => radii,
+            None => return Ok(()),
+        };
+
+        let rect = Rect::new(
+            Point2D::new(x as f32, y as f32),
+            Size2D::new(width as f32, height as f32),
+        );
+        self.send_canvas_2d_msg(Canvas2dMsg::RoundRect(rect, radii));
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
     pub fn quadratic_curve_to(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
         if !(cpx.is_finite() && cpy.is_finite() && x.is_finite() && y.is_finite()) {
@@ -1679,6 +1767,85 @@ impl CanvasState {
     }
 }
 
+/// The radii of the corners of a rounded rectangle of the given size, scaled down so that
+/// they do not overlap, or `None` if one of them is infinite or NaN, in which case the
+/// rectangle is not added to the path.
+/// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+pub fn normalize_corner_radii(
+    width: f64,
+    height: f64,
+    radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+) -> Fallible<Option<CornerRadii>> {
+    use UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence as Radii;
+
+    // Steps 2-3.
+    let radii = match radii {
+        Radii::UnrestrictedDouble(radius) => {
+            vec![UnrestrictedDoubleOrDOMPointInit::UnrestrictedDouble(radius)]
+        },
+        Radii::DOMPointInit(radius) => vec![UnrestrictedDoubleOrDOMPointInit::DOMPointInit(radius)],
+        Radii::UnrestrictedDoubleOrDOMPointInitSequence(radii) => radii,
+    };
+    if radii.is_empty() || radii.len() > 4 {
+        return Err(Error::Range(format!(
+            "Expected between 1 and 4 radii, but found {}.",
+            radii.len()
+        )));
+    }
+
+    // Steps 4-5.
+    let mut normalized_radii = Vec::with_capacity(radii.len());
+    for radius in radii {
+        let (x, y) = match radius {
+            UnrestrictedDoubleOrDOMPointInit::UnrestrictedDouble(radius) => (radius, radius),
+            UnrestrictedDoubleOrDOMPointInit::DOMPointInit(radius) => (radius.x, radius.y),
+        };
+        if !(x.is_finite() && y.is_finite()) {
+            return Ok(None);
+        }
+        if x < 0. || y < 0. {
+            return Err(Error::Range("Radii must not be negative.".to_owned()));
+        }
+        normalized_radii.push(Size2D::new(x, y));
+    }
+
+    // Steps 6-7.
+    let (upper_left, upper_right, lower_right, lower_left) = match normalized_radii[..] {
+        [all] => (all, all, all, all),
+        [upper_left_and_lower_right, upper_right_and_lower_left] => (
+            upper_left_and_lower_right,
+            upper_right_and_lower_left,
+            upper_left_and_lower_right,
+            upper_right_and_lower_left,
+        ),
+        [upper_left, upper_right_and_lower_left, lower_right] => (
+            upper_left,
+            upper_right_and_lower_left,
+            lower_right,
+            upper_right_and_lower_left,
+        ),
+        [upper_left, upper_right, lower_right, lower_left] => {
+            (upper_left, upper_right, lower_right, lower_left)
+        },
+        _ => unreachable!(),
+    };
+
+    // Step 8. Corner curves must not overlap, so all the radii are scaled down if
+    // two of them are longer than a side.
+    let scale = [
+        width.abs() / (upper_left.width + upper_right.width),
+        height.abs() / (upper_right.height + lower_right.height),
+        width.abs() / (lower_right.width + lower_left.width),
+        height.abs() / (upper_left.height + lower_left.height),
+    ]
+    .iter()
+    .fold(1f64, |scale, ratio| scale.min(*ratio));
+
+    Ok(Some(
+        [upper_left, upper_right, lower_right, lower_left].map(|radius| (radius * scale).to_f32()),
+    ))
+}
+
 pub fn parse_color(canvas: Option<&HTMLCanvasElement>, string: &str) -> Result<RGBA, ()> {
     let mut input = ParserInput::new(string);
     let mut parser = Parser::new(&mut input);
@@ -0,0 +1,52 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The clock the timestamps scripts can read are measured with, and how precise they are.
+//!
+//! Every timestamp exposed to scripts, in windows and in workers alike, is coarsened here, so
+//! that how precisely scripts can measure durations only depends on whether their global is
+//! cross-origin isolated and on whether fingerprinting is resisted.
+//!
+//! <https://w3c.github.io/hr-time/#clocks>
+
+use script_traits::MsDuration;
+use servo_config::pref;
+
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::num::Finite;
+
+/// The resolution of the timestamps of globals that are not cross-origin isolated, in
+/// microseconds.
+const RESOLUTION_US: f64 = 100.;
+
+/// The resolution of the timestamps of cross-origin isolated globals, in microseconds.
+const CROSS_ORIGIN_ISOLATED_RESOLUTION_US: f64 = 5.;
+
+/// The time elapsed since an arbitrary point shared by every event loop, in nanoseconds.
+///
+/// <https://w3c.github.io/hr-time/#dfn-monotonic-clock>
+pub fn monotonic_time_ns() -> u64 {
+    time::precise_time_ns()
+}
+
+/// The time elapsed since an arbitrary point shared by every event loop, in milliseconds.
+pub fn monotonic_time_ms() -> MsDuration {
+    MsDuration::new(monotonic_time_ns() / (1000 * 1000))
+}
+
+/// Coarsen a timestamp, in milliseconds, to the resolution of the timestamps of a global.
+///
+/// The resolution is a whole number of microseconds, as web-platform-tests/wpt#21526 expects.
+///
+/// <https://w3c.github.io/hr-time/#dfn-coarsen-time>
+pub fn coarsen_time(timestamp: f64, cross_origin_isolated: bool) -> DOMHighResTimeStamp {
+    let resolution = if pref!(privacy.resist_fingerprinting.enabled) {
+        pref!(privacy.resist_fingerprinting.timer_precision).max(1) as f64 * 1000.
+    } else if cross_origin_isolated {
+        CROSS_ORIGIN_ISOLATED_RESOLUTION_US
+    } else {
+        RESOLUTION_US
+    };
+    Finite::wrap((timestamp * 1000. / resolution).floor() * resolution / 1000.)
+}
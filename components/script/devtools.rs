@@ -6,14 +6,16 @@ use std::rc::Rc;
 use std::str;
 
 use devtools_traits::{
-    AutoMargins, ComputedNodeLayout, EvaluateJSReply, Modification, NodeInfo, TimelineMarker,
-    TimelineMarkerType,
+    AutoMargins, ComputedNodeLayout, EvaluateJSReply, Modification, NetworkThrottling, NodeInfo,
+    TimelineMarker, TimelineMarkerType,
 };
 use ipc_channel::ipc::IpcSender;
 use js::jsval::UndefinedValue;
 use js::rust::ToString;
 use msg::constellation_msg::PipelineId;
+use script_traits::{BoxModelHighlight, ScriptMsg};
 use uuid::Uuid;
+use webrender_api::units::{LayoutPoint, LayoutRect, LayoutSideOffsets, LayoutSize};
 
 use crate::dom::bindings::codegen::Bindings::CSSStyleDeclarationBinding::CSSStyleDeclarationMethods;
 use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
@@ -181,6 +183,69 @@ pub fn handle_get_layout(
         .unwrap();
 }
 
+pub fn handle_highlight_box_model(
+    documents: &Documents,
+    pipeline: PipelineId,
+    node_id: Option<String>,
+) {
+    let window = match documents.find_window(pipeline) {
+        Some(window) => window,
+        None => return warn!("Message sent to closed pipeline {}.", pipeline),
+    };
+    let highlight = node_id
+        .and_then(|node_id| find_node_by_unique_id(documents, pipeline, &*node_id))
+        .and_then(|node| box_model_highlight(&node));
+    window
+        .get_webrender_api_sender()
+        .highlight_box_model(pipeline, highlight);
+}
+
+/// The boxes of an element, from its border box and the used widths of its borders, paddings
+/// and margins.
+fn box_model_highlight(node: &Node) -> Option<BoxModelHighlight> {
+    let element = node.downcast::<Element>()?;
+    let border_box = node.bounding_content_box()?;
+    let border_box = LayoutRect::new(
+        LayoutPoint::new(
+            border_box.origin.x.to_f32_px(),
+            border_box.origin.y.to_f32_px(),
+        ),
+        LayoutSize::new(
+            border_box.size.width.to_f32_px(),
+            border_box.size.height.to_f32_px(),
+        ),
+    );
+
+    let computed_style = window_from_node(node).GetComputedStyle(element, None);
+    let px = |value: DOMString| value.trim_end_matches("px").parse().unwrap_or(0.);
+    let border = LayoutSideOffsets::new(
+        px(computed_style.BorderTopWidth()),
+        px(computed_style.BorderRightWidth()),
+        px(computed_style.BorderBottomWidth()),
+        px(computed_style.BorderLeftWidth()),
+    );
+    let padding = LayoutSideOffsets::new(
+        px(computed_style.PaddingTop()),
+        px(computed_style.PaddingRight()),
+        px(computed_style.PaddingBottom()),
+        px(computed_style.PaddingLeft()),
+    );
+    let margin = LayoutSideOffsets::new(
+        px(computed_style.MarginTop()),
+        px(computed_style.MarginRight()),
+        px(computed_style.MarginBottom()),
+        px(computed_style.MarginLeft()),
+    );
+
+    let padding_box = border_box.inner_rect(border);
+    Some(BoxModelHighlight {
+        content_box: padding_box.inner_rect(padding),
+        padding_box,
+        border_box,
+        margin_box: border_box.outer_rect(margin),
+    })
+}
+
 fn determine_auto_margins(node: &Node) -> AutoMargins {
     let style = node.style().unwrap();
     let margin = style.get_margin();
@@ -262,3 +327,13 @@ pub fn handle_reload(documents: &Documents, id: PipelineId) {
         win.Location().reload_without_origin_check();
     }
 }
+
+pub fn handle_set_network_throttling(
+    documents: &Documents,
+    id: PipelineId,
+    throttling: Option<NetworkThrottling>,
+) {
+    if let Some(win) = documents.find_window(id) {
+        win.send_to_constellation(ScriptMsg::SetNetworkThrottling(throttling));
+    }
+}
@@ -0,0 +1,301 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::mem;
+
+use dom_struct::dom_struct;
+use euclid::Length;
+use js::jsapi::{ExceptionStackBehavior, Heap};
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::wrappers::JS_SetPendingException;
+use js::rust::{HandleValue, MutableHandleValue};
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AbortSignalBinding::AbortSignalMethods;
+use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::EventHandlerNonNull;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::fetch::FetchAbortAlgorithm;
+use crate::realms::enter_realm;
+use crate::script_runtime::JSContext;
+use crate::timers::OneshotTimerCallback;
+
+/// What is run when a signal is aborted, as those are added by the objects following it.
+/// <https://dom.spec.whatwg.org/#abortsignal-abort-algorithms>
+#[derive(JSTraceable, MallocSizeOf)]
+pub enum AbortAlgorithm {
+    /// Abort an ongoing `fetch()` call.
+    Fetch(FetchAbortAlgorithm),
+}
+
+impl AbortAlgorithm {
+    fn run(self, reason: HandleValue) {
+        match self {
+            AbortAlgorithm::Fetch(algorithm) => algorithm.abort(reason),
+        }
+    }
+}
+
+/// <https://dom.spec.whatwg.org/#interface-AbortSignal>
+#[dom_struct]
+pub struct AbortSignal {
+    eventtarget: EventTarget,
+    /// <https://dom.spec.whatwg.org/#abortsignal-abort-reason>
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    abort_reason: Heap<JSVal>,
+    /// <https://dom.spec.whatwg.org/#abortsignal-abort-algorithms>
+    abort_algorithms: DomRefCell<Vec<AbortAlgorithm>>,
+    /// <https://dom.spec.whatwg.org/#abortsignal-dependent>
+    dependent: Cell<bool>,
+    /// <https://dom.spec.whatwg.org/#abortsignal-source-signals>
+    source_signals: DomRefCell<Vec<Dom<AbortSignal>>>,
+    /// <https://dom.spec.whatwg.org/#abortsignal-dependent-signals>
+    dependent_signals: DomRefCell<Vec<Dom<AbortSignal>>>,
+}
+
+impl AbortSignal {
+    fn new_inherited() -> AbortSignal {
+        AbortSignal {
+            eventtarget: EventTarget::new_inherited(),
+            abort_reason: Heap::default(),
+            abort_algorithms: Default::default(),
+            dependent: Cell::new(false),
+            source_signals: Default::default(),
+            dependent_signals: Default::default(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<AbortSignal> {
+        reflect_dom_object(Box::new(AbortSignal::new_inherited()), global)
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-aborted>
+    pub fn aborted(&self) -> bool {
+        !self.abort_reason.get().is_undefined()
+    }
+
+    pub fn abort_reason(&self, rval: MutableHandleValue) {
+        rval.set(self.abort_reason.get());
+    }
+
+    /// Set the abort reason to `reason`, or to a new DOMException of the given error if
+    /// `reason` is undefined.
+    #[allow(unsafe_code)]
+    fn set_abort_reason(&self, reason: HandleValue, default: Error) {
+        if !reason.is_undefined() {
+            return self.abort_reason.set(reason.get());
+        }
+        let global = self.global();
+        let _ac = enter_realm(&*global);
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        unsafe { default.to_jsval(*cx, &global, error.handle_mut()) };
+        self.abort_reason.set(error.get());
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-add>
+    pub fn add_algorithm(&self, algorithm: AbortAlgorithm) {
+        // Step 1. If signal is aborted, then return.
+        if self.aborted() {
+            return;
+        }
+        // Step 2. Append algorithm to signal's abort algorithms.
+        self.abort_algorithms.borrow_mut().push(algorithm);
+    }
+
+    /// <https://dom.spec.whatwg.org/#abortsignal-signal-abort>
+    pub fn signal_abort(&self, reason: HandleValue) {
+        self.signal_abort_with_default(reason, Error::Abort);
+    }
+
+    fn signal_abort_with_default(&self, reason: HandleValue, default: Error) {
+        // Step 1. If signal is aborted, then return.
+        if self.aborted() {
+            return;
+        }
+
+        // Step 2. Set signal's abort reason to reason if it is given;
+        // otherwise to a new "AbortError" DOMException.
+        self.set_abort_reason(reason, default);
+
+        // Step 3-4. Let dependentSignalsToAbort be the dependent signals of signal that are
+        // not aborted yet, with their abort reason set to signal's.
+        let dependent_signals_to_abort: Vec<DomRoot<AbortSignal>> = self
+            .dependent_signals
+            .borrow()
+            .iter()
+            .filter(|signal| !signal.aborted())
+            .map(|signal| DomRoot::from_ref(&**signal))
+            .collect();
+        for signal in &dependent_signals_to_abort {
+            signal.abort_reason.set(self.abort_reason.get());
+        }
+
+        // Step 5. Run the abort steps for signal.
+        self.run_abort_steps();
+
+        // Step 6. For each dependentSignal of dependentSignalsToAbort,
+        // run the abort steps for dependentSignal.
+        for signal in dependent_signals_to_abort {
+            signal.run_abort_steps();
+        }
+    }
+
+    /// <https://dom.spec.whatwg.org/#run-the-abort-steps>
+    fn run_abort_steps(&self) {
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let mut reason = UndefinedValue());
+        self.abort_reason(reason.handle_mut());
+
+        // Step 1. For each algorithm of signal's abort algorithms: run algorithm.
+        // Step 2. Empty signal's abort algorithms.
+        let algorithms = mem::take(&mut *self.abort_algorithms.borrow_mut());
+        for algorithm in algorithms {
+            algorithm.run(reason.handle());
+        }
+
+        // Step 3. Fire an event named abort at signal.
+        self.upcast::<EventTarget>().fire_event(atom!("abort"));
+    }
+
+    /// <https://dom.spec.whatwg.org/#create-a-dependent-abort-signal>
+    pub fn new_dependent(
+        global: &GlobalScope,
+        signals: &[DomRoot<AbortSignal>],
+    ) -> DomRoot<AbortSignal> {
+        // Step 1. Let resultSignal be a new object implementing signalInterface.
+        let result_signal = AbortSignal::new(global);
+
+        // Step 2. For each signal of signals: if signal is aborted, then set resultSignal's
+        // abort reason to signal's abort reason and return resultSignal.
+        if let Some(signal) = signals.iter().find(|signal| signal.aborted()) {
+            result_signal.abort_reason.set(signal.abort_reason.get());
+            return result_signal;
+        }
+
+        // Step 3. Set resultSignal's dependent to true.
+        result_signal.dependent.set(true);
+
+        // Step 4. For each signal of signals, follow it, or its own sources if it is
+        // dependent itself.
+        for signal in signals {
+            if !signal.dependent.get() {
+                result_signal.add_source_signal(signal);
+            } else {
+                for source_signal in signal.source_signals.borrow().iter() {
+                    debug_assert!(!source_signal.aborted() && !source_signal.dependent.get());
+                    result_signal.add_source_signal(source_signal);
+                }
+            }
+        }
+
+        // Step 5. Return resultSignal.
+        result_signal
+    }
+
+    fn add_source_signal(&self, source_signal: &AbortSignal) {
+        let mut source_signals = self.source_signals.borrow_mut();
+        if source_signals
+            .iter()
+            .any(|signal| &**signal == source_signal)
+        {
+            return;
+        }
+        source_signals.push(Dom::from_ref(source_signal));
+        source_signal
+            .dependent_signals
+            .borrow_mut()
+            .push(Dom::from_ref(self));
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-abort
+    #[allow(non_snake_case)]
+    pub fn Abort(
+        _cx: JSContext,
+        global: &GlobalScope,
+        reason: HandleValue,
+    ) -> DomRoot<AbortSignal> {
+        // Step 1. Let signal be a new AbortSignal object.
+        let signal = AbortSignal::new(global);
+        // Step 2. Set signal's abort reason to reason if it is given;
+        // otherwise to a new "AbortError" DOMException.
+        signal.set_abort_reason(reason, Error::Abort);
+        // Step 3. Return signal.
+        signal
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-timeout
+    #[allow(non_snake_case)]
+    pub fn Timeout(global: &GlobalScope, milliseconds: u64) -> DomRoot<AbortSignal> {
+        // Step 1. Let signal be a new AbortSignal object.
+        let signal = AbortSignal::new(global);
+
+        // Step 3-4. Run steps after a timeout on the timer task source, which signal abort
+        // given signal and a new "TimeoutError" DOMException.
+        let callback = OneshotTimerCallback::AbortSignalTimeout(AbortSignalTimeoutCallback {
+            signal: Trusted::new(&*signal),
+        });
+        global.schedule_callback(callback, Length::new(milliseconds));
+
+        // Step 5. Return signal.
+        signal
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-any
+    #[allow(non_snake_case)]
+    pub fn Any(global: &GlobalScope, signals: Vec<DomRoot<AbortSignal>>) -> DomRoot<AbortSignal> {
+        AbortSignal::new_dependent(global, &signals)
+    }
+}
+
+impl AbortSignalMethods for AbortSignal {
+    // https://dom.spec.whatwg.org/#dom-abortsignal-aborted
+    fn Aborted(&self) -> bool {
+        self.aborted()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-reason
+    fn Reason(&self, _cx: JSContext) -> JSVal {
+        self.abort_reason.get()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-throwifaborted
+    #[allow(unsafe_code)]
+    fn ThrowIfAborted(&self, cx: JSContext) -> ErrorResult {
+        if !self.aborted() {
+            return Ok(());
+        }
+        rooted!(in(*cx) let mut reason = UndefinedValue());
+        self.abort_reason(reason.handle_mut());
+        unsafe {
+            JS_SetPendingException(*cx, reason.handle(), ExceptionStackBehavior::Capture);
+        }
+        Err(Error::JSFailed)
+    }
+
+    // https://dom.spec.whatwg.org/#dom-abortsignal-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+}
+
+/// Aborts a signal made by `AbortSignal.timeout()` once its time is up.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct AbortSignalTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    signal: Trusted<AbortSignal>,
+}
+
+impl AbortSignalTimeoutCallback {
+    pub fn invoke(self) {
+        let signal = self.signal.root();
+        let _ac = enter_realm(&*signal);
+        rooted!(in(*GlobalScope::get_cx()) let reason = UndefinedValue());
+        signal.signal_abort_with_default(reason.handle(), Error::Timeout);
+    }
+}
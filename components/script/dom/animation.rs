@@ -0,0 +1,897 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+use servo_atoms::Atom;
+
+use crate::dom::animationeffect::AnimationEffect;
+use crate::dom::animationplaybackevent::AnimationPlaybackEvent;
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::{
+    AnimationMethods, AnimationPlayState,
+};
+use crate::dom::bindings::codegen::Bindings::AnimationPlaybackEventBinding::AnimationPlaybackEventInit;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventInit;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::keyframeeffect::KeyframeEffect;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+
+/// <https://drafts.csswg.org/web-animations-1/#pending-play-task>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+enum PendingTask {
+    Play,
+    Pause,
+}
+
+/// <https://drafts.csswg.org/web-animations-1/#the-animation-interface>
+#[dom_struct]
+pub struct Animation {
+    eventtarget: EventTarget,
+    id: DomRefCell<DOMString>,
+    effect: MutNullableDom<AnimationEffect>,
+    timeline: MutNullableDom<AnimationTimeline>,
+    /// <https://drafts.csswg.org/web-animations-1/#animation-start-time>
+    start_time: Cell<Option<f64>>,
+    /// <https://drafts.csswg.org/web-animations-1/#animation-hold-time>
+    hold_time: Cell<Option<f64>>,
+    /// <https://drafts.csswg.org/web-animations-1/#playback-rate>
+    playback_rate: Cell<f64>,
+    /// <https://drafts.csswg.org/web-animations-1/#pending-playback-rate>
+    pending_playback_rate: Cell<Option<f64>>,
+    pending_task: Cell<Option<PendingTask>>,
+    /// <https://drafts.csswg.org/web-animations-1/#previous-current-time>
+    previous_current_time: Cell<Option<f64>>,
+    /// <https://drafts.csswg.org/web-animations-1/#current-ready-promise>
+    #[ignore_malloc_size_of = "Rc"]
+    ready_promise: DomRefCell<Rc<Promise>>,
+    /// <https://drafts.csswg.org/web-animations-1/#current-finished-promise>
+    #[ignore_malloc_size_of = "Rc"]
+    finished_promise: DomRefCell<Rc<Promise>>,
+    /// Whether the current finished promise was resolved.
+    finished_promise_resolved: Cell<bool>,
+    /// Incremented whenever finish notification steps are queued, so that only the last
+    /// ones queued run.
+    finish_notification_counter: Cell<u64>,
+}
+
+impl Animation {
+    #[allow(crown::unrooted_must_root)]
+    fn new_inherited(window: &Window, timeline: Option<&AnimationTimeline>) -> Animation {
+        Animation {
+            eventtarget: EventTarget::new_inherited(),
+            id: DomRefCell::new(DOMString::new()),
+            effect: Default::default(),
+            timeline: MutNullableDom::new(timeline),
+            start_time: Cell::new(None),
+            hold_time: Cell::new(None),
+            playback_rate: Cell::new(1.),
+            pending_playback_rate: Cell::new(None),
+            pending_task: Cell::new(None),
+            previous_current_time: Cell::new(None),
+            ready_promise: DomRefCell::new(Promise::new(window.upcast())),
+            finished_promise: DomRefCell::new(Promise::new(window.upcast())),
+            finished_promise_resolved: Cell::new(false),
+            finish_notification_counter: Cell::new(0),
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<&AnimationTimeline>,
+    ) -> DomRoot<Animation> {
+        let animation = reflect_dom_object_with_proto(
+            Box::new(Animation::new_inherited(window, timeline)),
+            window,
+            proto,
+        );
+        // The ready promise of a new animation is already resolved with the animation.
+        animation.ready_promise.borrow().resolve_native(&*animation);
+        animation.SetEffect(effect);
+        animation
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-animation>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        effect: Option<&AnimationEffect>,
+        timeline: Option<Option<&AnimationTimeline>>,
+    ) -> DomRoot<Animation> {
+        match timeline {
+            Some(timeline) => Animation::new(window, proto, effect, timeline),
+            None => {
+                let timeline = window.Document().timeline();
+                Animation::new(window, proto, effect, Some(timeline.upcast()))
+            },
+        }
+    }
+
+    pub fn playback_rate(&self) -> f64 {
+        self.playback_rate.get()
+    }
+
+    pub fn effect(&self) -> Option<DomRoot<AnimationEffect>> {
+        self.effect.get()
+    }
+
+    /// The keyframe effect of this animation, if it has one.
+    pub fn keyframe_effect(&self) -> Option<DomRoot<KeyframeEffect>> {
+        self.effect
+            .get()
+            .and_then(|effect| DomRoot::downcast::<KeyframeEffect>(effect))
+    }
+
+    fn document(&self) -> Option<DomRoot<Document>> {
+        self.timeline.get().map(|timeline| timeline.document())
+    }
+
+    fn timeline_time(&self) -> Option<f64> {
+        self.timeline.get()?.current_time()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#associated-effect-end>
+    fn effect_end(&self) -> f64 {
+        self.effect
+            .get()
+            .map_or(0., |effect| effect.timing().end_time())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#animation-current-time>
+    pub fn current_time(&self) -> Option<f64> {
+        if let Some(hold_time) = self.hold_time.get() {
+            return Some(hold_time);
+        }
+        self.current_time_ignoring_hold_time()
+    }
+
+    fn current_time_ignoring_hold_time(&self) -> Option<f64> {
+        let timeline_time = self.timeline_time()?;
+        let start_time = self.start_time.get()?;
+        Some((timeline_time - start_time) * self.playback_rate.get())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#play-state>
+    pub fn play_state(&self) -> AnimationPlayState {
+        let current_time = self.current_time();
+        let pending_task = self.pending_task.get();
+        if current_time.is_none() && self.start_time.get().is_none() && pending_task.is_none() {
+            return AnimationPlayState::Idle;
+        }
+        if pending_task == Some(PendingTask::Pause) ||
+            (self.start_time.get().is_none() && pending_task != Some(PendingTask::Play))
+        {
+            return AnimationPlayState::Paused;
+        }
+        if let Some(current_time) = current_time {
+            let rate = self.playback_rate.get();
+            if (rate > 0. && current_time >= self.effect_end()) || (rate < 0. && current_time <= 0.)
+            {
+                return AnimationPlayState::Finished;
+            }
+        }
+        AnimationPlayState::Running
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.play_state() == AnimationPlayState::Finished
+    }
+
+    /// Whether this animation is relevant, that is whether its effect is current or in
+    /// effect, <https://drafts.csswg.org/web-animations-1/#relevant-animations-section>.
+    pub fn is_relevant(&self) -> bool {
+        self.effect.get().map_or(false, |effect| {
+            effect.is_current() || effect.progress().is_some()
+        })
+    }
+
+    /// Add this animation to the animations of its document, which tick it and apply
+    /// its effect.
+    fn ensure_registered(&self) {
+        if let Some(document) = self.document() {
+            document
+                .animations()
+                .add_web_animation(document.window(), self);
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#apply-any-pending-playback-rate>
+    fn apply_pending_playback_rate(&self) {
+        if let Some(rate) = self.pending_playback_rate.take() {
+            self.playback_rate.set(rate);
+        }
+    }
+
+    /// Resolve the current ready promise with this animation.
+    fn resolve_ready_promise(&self) {
+        self.ready_promise.borrow().resolve_native(self);
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#silently-set-the-current-time>
+    fn silently_set_current_time(&self, seek_time: Option<f64>) -> ErrorResult {
+        // Step 1.
+        let seek_time = match seek_time {
+            Some(seek_time) => seek_time,
+            None if self.current_time().is_some() => {
+                return Err(Error::Type(
+                    "The current time of a playing animation can not be unset".to_owned(),
+                ));
+            },
+            None => return Ok(()),
+        };
+
+        // Step 2.
+        let timeline_time = self.timeline_time();
+        match timeline_time {
+            Some(timeline_time)
+                if self.hold_time.get().is_none() &&
+                    self.start_time.get().is_some() &&
+                    self.playback_rate.get() != 0. =>
+            {
+                self.start_time
+                    .set(Some(timeline_time - seek_time / self.playback_rate.get()));
+            },
+            _ => self.hold_time.set(Some(seek_time)),
+        }
+
+        // Step 3.
+        if timeline_time.is_none() {
+            self.start_time.set(None);
+        }
+
+        // Step 4.
+        self.previous_current_time.set(None);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#setting-the-current-time-of-an-animation>
+    fn set_current_time(&self, seek_time: Option<f64>) -> ErrorResult {
+        // Step 1.
+        self.silently_set_current_time(seek_time)?;
+
+        // Step 2.
+        if self.pending_task.get() == Some(PendingTask::Pause) {
+            self.hold_time.set(seek_time);
+            self.apply_pending_playback_rate();
+            self.start_time.set(None);
+            self.pending_task.set(None);
+            self.resolve_ready_promise();
+        }
+
+        // Step 3.
+        self.update_finished_state(true, false);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#setting-the-start-time-of-an-animation>
+    fn set_start_time(&self, new_start_time: Option<f64>) {
+        // Step 1 - 2.
+        if self.timeline_time().is_none() && new_start_time.is_some() {
+            self.hold_time.set(None);
+        }
+
+        // Step 3 - 5.
+        let previous_current_time = self.current_time();
+        self.apply_pending_playback_rate();
+        self.start_time.set(new_start_time);
+
+        // Step 6.
+        if new_start_time.is_some() {
+            if self.playback_rate.get() != 0. {
+                self.hold_time.set(None);
+            }
+        } else {
+            self.hold_time.set(previous_current_time);
+        }
+
+        // Step 7.
+        if self.pending_task.take().is_some() {
+            self.resolve_ready_promise();
+        }
+
+        // Step 8.
+        self.update_finished_state(true, false);
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#playing-an-animation-section>
+    fn play_with_auto_rewind(&self, auto_rewind: bool) -> ErrorResult {
+        // Step 1 - 3.
+        let aborted_pause = self.pending_task.get() == Some(PendingTask::Pause);
+        let mut has_pending_ready_promise = false;
+        let mut seek_time = None;
+
+        // Step 4.
+        let effective_playback_rate = self
+            .pending_playback_rate
+            .get()
+            .unwrap_or(self.playback_rate.get());
+        let current_time = self.current_time();
+        let effect_end = self.effect_end();
+
+        // Step 5.
+        if auto_rewind {
+            if effective_playback_rate >= 0. &&
+                current_time.map_or(true, |time| time < 0. || time >= effect_end)
+            {
+                seek_time = Some(0.);
+            } else if effective_playback_rate < 0. &&
+                current_time.map_or(true, |time| time <= 0. || time > effect_end)
+            {
+                if effect_end.is_infinite() {
+                    return Err(Error::InvalidState);
+                }
+                seek_time = Some(effect_end);
+            }
+        }
+
+        // Step 6.
+        if seek_time.is_none() && self.start_time.get().is_none() && self.hold_time.get().is_none()
+        {
+            seek_time = Some(0.);
+        }
+
+        // Step 7. Document timelines are monotonically increasing, so they are not
+        // finite timelines.
+        if seek_time.is_some() {
+            self.hold_time.set(seek_time);
+        }
+
+        // Step 8.
+        if self.hold_time.get().is_some() {
+            self.start_time.set(None);
+        }
+
+        // Step 9.
+        if self.pending_task.take().is_some() {
+            has_pending_ready_promise = true;
+        }
+
+        // Step 10.
+        if self.hold_time.get().is_none() &&
+            seek_time.is_none() &&
+            self.pending_playback_rate.get().is_none() &&
+            !aborted_pause
+        {
+            return Ok(());
+        }
+
+        // Step 11.
+        if !has_pending_ready_promise {
+            *self.ready_promise.borrow_mut() = Promise::new(&self.global());
+        }
+
+        // Step 12.
+        self.pending_task.set(Some(PendingTask::Play));
+        self.ensure_registered();
+
+        // Step 13.
+        self.update_finished_state(false, false);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#pausing-an-animation-section>
+    fn pause(&self) -> ErrorResult {
+        // Step 1 - 2.
+        if self.pending_task.get() == Some(PendingTask::Pause) ||
+            self.play_state() == AnimationPlayState::Paused
+        {
+            return Ok(());
+        }
+
+        // Step 3 - 4.
+        let mut seek_time = None;
+        if self.current_time().is_none() {
+            if self.playback_rate.get() >= 0. {
+                seek_time = Some(0.);
+            } else {
+                let effect_end = self.effect_end();
+                if effect_end.is_infinite() {
+                    return Err(Error::InvalidState);
+                }
+                seek_time = Some(effect_end);
+            }
+        }
+
+        // Step 5.
+        if seek_time.is_some() {
+            self.hold_time.set(seek_time);
+        }
+
+        // Step 6 - 7.
+        let has_pending_ready_promise = self.pending_task.take() == Some(PendingTask::Play);
+
+        // Step 8.
+        if !has_pending_ready_promise {
+            *self.ready_promise.borrow_mut() = Promise::new(&self.global());
+        }
+
+        // Step 9.
+        self.pending_task.set(Some(PendingTask::Pause));
+        self.ensure_registered();
+
+        // Step 10.
+        self.update_finished_state(false, false);
+        Ok(())
+    }
+
+    /// Run the pending play or pause task of this animation, if any, now that the
+    /// animation is ready at `ready_time`.
+    fn run_pending_task(&self, ready_time: f64) {
+        match self.pending_task.take() {
+            // https://drafts.csswg.org/web-animations-1/#playing-an-animation-section
+            Some(PendingTask::Play) => {
+                if let Some(hold_time) = self.hold_time.get() {
+                    self.apply_pending_playback_rate();
+                    let rate = self.playback_rate.get();
+                    if rate != 0. {
+                        self.start_time.set(Some(ready_time - hold_time / rate));
+                        self.hold_time.set(None);
+                    } else {
+                        self.start_time.set(Some(ready_time));
+                    }
+                } else if let (Some(start_time), Some(_)) =
+                    (self.start_time.get(), self.pending_playback_rate.get())
+                {
+                    let current_time_to_match =
+                        (ready_time - start_time) * self.playback_rate.get();
+                    self.apply_pending_playback_rate();
+                    let rate = self.playback_rate.get();
+                    if rate == 0. {
+                        self.hold_time.set(Some(current_time_to_match));
+                        self.start_time.set(Some(ready_time));
+                    } else {
+                        self.start_time
+                            .set(Some(ready_time - current_time_to_match / rate));
+                    }
+                }
+            },
+            // https://drafts.csswg.org/web-animations-1/#pausing-an-animation-section
+            Some(PendingTask::Pause) => {
+                if let (Some(start_time), None) = (self.start_time.get(), self.hold_time.get()) {
+                    self.hold_time
+                        .set(Some((ready_time - start_time) * self.playback_rate.get()));
+                }
+                self.apply_pending_playback_rate();
+                self.start_time.set(None);
+            },
+            None => return,
+        }
+        self.resolve_ready_promise();
+        self.update_finished_state(false, false);
+    }
+
+    /// Advance this animation to the current time of its timeline, running its pending
+    /// tasks and updating its finished state.
+    pub fn tick(&self) {
+        let timeline_time = match self.timeline_time() {
+            Some(timeline_time) => timeline_time,
+            None => return,
+        };
+        self.run_pending_task(timeline_time);
+        self.update_finished_state(false, false);
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#update-an-animations-finished-state>
+    fn update_finished_state(&self, did_seek: bool, synchronously_notify: bool) {
+        // Step 1.
+        let unconstrained_current_time = if did_seek {
+            self.current_time()
+        } else {
+            self.current_time_ignoring_hold_time()
+        };
+
+        // Step 2.
+        if let (Some(unconstrained_current_time), Some(_), None) = (
+            unconstrained_current_time,
+            self.start_time.get(),
+            self.pending_task.get(),
+        ) {
+            let rate = self.playback_rate.get();
+            let effect_end = self.effect_end();
+            let previous_current_time = self.previous_current_time.get();
+            if rate > 0. && unconstrained_current_time >= effect_end {
+                self.hold_time.set(Some(if did_seek {
+                    unconstrained_current_time
+                } else {
+                    previous_current_time.map_or(effect_end, |time| time.max(effect_end))
+                }));
+            } else if rate < 0. && unconstrained_current_time <= 0. {
+                self.hold_time.set(Some(if did_seek {
+                    unconstrained_current_time
+                } else {
+                    previous_current_time.map_or(0., |time| time.min(0.))
+                }));
+            } else if rate != 0. {
+                if let (true, Some(hold_time), Some(timeline_time)) =
+                    (did_seek, self.hold_time.get(), self.timeline_time())
+                {
+                    self.start_time.set(Some(timeline_time - hold_time / rate));
+                }
+                self.hold_time.set(None);
+            }
+        }
+
+        // Step 3.
+        self.previous_current_time.set(self.current_time());
+
+        // Step 4 - 5.
+        let finished = self.is_finished();
+        if finished && !self.finished_promise_resolved.get() {
+            if synchronously_notify {
+                self.finish_notification_counter
+                    .set(self.finish_notification_counter.get() + 1);
+                self.run_finish_notification_steps();
+            } else {
+                self.queue_finish_notification_steps();
+            }
+        }
+
+        // Step 6.
+        if !finished && self.finished_promise_resolved.get() {
+            *self.finished_promise.borrow_mut() = Promise::new(&self.global());
+            self.finished_promise_resolved.set(false);
+        }
+    }
+
+    fn queue_finish_notification_steps(&self) {
+        let counter = self.finish_notification_counter.get() + 1;
+        self.finish_notification_counter.set(counter);
+        let window = self.global();
+        let window = window.as_window();
+        let this = Trusted::new(self);
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(animation_finish_notification_steps: move || {
+                let this = this.root();
+                if counter == this.finish_notification_counter.get() {
+                    this.run_finish_notification_steps();
+                }
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#finish-notification-steps>
+    fn run_finish_notification_steps(&self) {
+        // Step 1.
+        if !self.is_finished() || self.finished_promise_resolved.get() {
+            return;
+        }
+
+        // Step 2.
+        self.finished_promise.borrow().resolve_native(self);
+        self.finished_promise_resolved.set(true);
+
+        // Step 3.
+        let current_time = self.current_time();
+        self.fire_playback_event(Atom::from("finish"), current_time);
+    }
+
+    fn fire_playback_event(&self, type_: Atom, current_time: Option<f64>) {
+        let init = AnimationPlaybackEventInit {
+            parent: EventInit {
+                bubbles: false,
+                cancelable: false,
+            },
+            currentTime: current_time.map(Finite::wrap),
+            timelineTime: self.timeline_time().map(Finite::wrap),
+        };
+        let window = self.global();
+        let event = AnimationPlaybackEvent::new(window.as_window(), type_, &init);
+        event.upcast::<Event>().fire(self.upcast());
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#reset-an-animations-pending-tasks>
+    fn reset_pending_tasks(&self) {
+        // Step 1 - 3.
+        if self.pending_task.take().is_none() {
+            return;
+        }
+
+        // Step 4.
+        self.apply_pending_playback_rate();
+
+        // Step 5 - 7.
+        let ready_promise = self.ready_promise.borrow().clone();
+        ready_promise.reject_error(Error::Abort);
+        ready_promise.set_promise_is_handled();
+        let ready_promise = Promise::new(&self.global());
+        ready_promise.resolve_native(self);
+        *self.ready_promise.borrow_mut() = ready_promise;
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#cancel-an-animation>
+    fn cancel(&self) {
+        // Step 1.
+        if self.play_state() != AnimationPlayState::Idle {
+            self.reset_pending_tasks();
+
+            if !self.finished_promise_resolved.get() {
+                let finished_promise = self.finished_promise.borrow().clone();
+                finished_promise.reject_error(Error::Abort);
+                finished_promise.set_promise_is_handled();
+            }
+            *self.finished_promise.borrow_mut() = Promise::new(&self.global());
+            self.finished_promise_resolved.set(false);
+
+            let this = Trusted::new(self);
+            let window = self.global();
+            let window = window.as_window();
+            let _ = window.task_manager().dom_manipulation_task_source().queue(
+                task!(animation_cancel_event: move || {
+                    this.root().fire_playback_event(Atom::from("cancel"), None);
+                }),
+                window.upcast(),
+            );
+        }
+
+        // Step 2 - 3.
+        self.hold_time.set(None);
+        self.start_time.set(None);
+        self.previous_current_time.set(None);
+    }
+
+    /// Let this animation know that the timing of its effect changed.
+    pub fn effect_timing_changed(&self) {
+        self.update_finished_state(false, false);
+    }
+}
+
+impl AnimationMethods for Animation {
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-id>
+    fn Id(&self) -> DOMString {
+        self.id.borrow().clone()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-id>
+    fn SetId(&self, id: DOMString) {
+        *self.id.borrow_mut() = id;
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-effect>
+    fn GetEffect(&self) -> Option<DomRoot<AnimationEffect>> {
+        self.effect.get()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#setting-the-associated-effect>
+    fn SetEffect(&self, new_effect: Option<&AnimationEffect>) {
+        // Step 2.
+        let old_effect = self.effect.get();
+        if old_effect.as_deref() == new_effect {
+            return;
+        }
+
+        // Step 4.
+        if let Some(new_effect) = new_effect {
+            if let Some(previous_animation) = new_effect.animation() {
+                previous_animation.effect.set(None);
+                previous_animation.update_finished_state(false, false);
+            }
+        }
+
+        // Step 5 - 6.
+        if let Some(old_effect) = old_effect {
+            old_effect.set_animation(None);
+        }
+        self.effect.set(new_effect);
+        if let Some(new_effect) = new_effect {
+            new_effect.set_animation(Some(self));
+        }
+
+        // Step 7.
+        self.update_finished_state(false, false);
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-timeline>
+    fn GetTimeline(&self) -> Option<DomRoot<AnimationTimeline>> {
+        self.timeline.get()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-starttime>
+    fn GetStartTime(&self) -> Option<Finite<f64>> {
+        self.start_time.get().map(Finite::wrap)
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-starttime>
+    fn SetStartTime(&self, start_time: Option<Finite<f64>>) -> ErrorResult {
+        self.set_start_time(start_time.map(|time| *time));
+        if start_time.is_some() {
+            self.ensure_registered();
+        }
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-currenttime>
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time().map(Finite::wrap)
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-currenttime>
+    fn SetCurrentTime(&self, current_time: Option<Finite<f64>>) -> ErrorResult {
+        self.set_current_time(current_time.map(|time| *time))?;
+        self.ensure_registered();
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-playbackrate>
+    fn PlaybackRate(&self) -> Finite<f64> {
+        Finite::wrap(self.playback_rate.get())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#set-the-playback-rate>
+    fn SetPlaybackRate(&self, playback_rate: Finite<f64>) {
+        // Step 1 - 3.
+        self.pending_playback_rate.set(None);
+        let previous_time = self.current_time();
+        self.playback_rate.set(*playback_rate);
+
+        // Step 4.
+        if previous_time.is_some() {
+            let _ = self.set_current_time(previous_time);
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-playstate>
+    fn PlayState(&self) -> AnimationPlayState {
+        self.play_state()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-pending>
+    fn Pending(&self) -> bool {
+        self.pending_task.get().is_some()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-ready>
+    fn Ready(&self) -> Rc<Promise> {
+        self.ready_promise.borrow().clone()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-finished>
+    fn Finished(&self) -> Rc<Promise> {
+        self.finished_promise.borrow().clone()
+    }
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-onfinish
+    event_handler!(finish, GetOnfinish, SetOnfinish);
+
+    // https://drafts.csswg.org/web-animations-1/#dom-animation-oncancel
+    event_handler!(cancel, GetOncancel, SetOncancel);
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-cancel>
+    fn Cancel(&self) {
+        self.cancel();
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#finishing-an-animation-section>
+    fn Finish(&self) -> ErrorResult {
+        // Step 1.
+        let effective_playback_rate = self
+            .pending_playback_rate
+            .get()
+            .unwrap_or(self.playback_rate.get());
+        let effect_end = self.effect_end();
+        if effective_playback_rate == 0. ||
+            (effective_playback_rate > 0. && effect_end.is_infinite())
+        {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2 - 4.
+        self.apply_pending_playback_rate();
+        let rate = self.playback_rate.get();
+        let limit = if rate > 0. { effect_end } else { 0. };
+        self.silently_set_current_time(Some(limit))?;
+
+        // Step 5.
+        if self.start_time.get().is_none() {
+            if let Some(timeline_time) = self.timeline_time() {
+                self.start_time.set(Some(timeline_time - limit / rate));
+            }
+        }
+
+        // Step 6 - 7.
+        if self.start_time.get().is_some() {
+            if self.pending_task.get() == Some(PendingTask::Pause) {
+                self.hold_time.set(None);
+            }
+            if self.pending_task.take().is_some() {
+                self.resolve_ready_promise();
+            }
+        }
+
+        // Step 8.
+        self.ensure_registered();
+        self.update_finished_state(true, true);
+        Ok(())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-play>
+    fn Play(&self) -> ErrorResult {
+        self.play_with_auto_rewind(true)
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animation-pause>
+    fn Pause(&self) -> ErrorResult {
+        self.pause()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#seamlessly-update-the-playback-rate>
+    fn UpdatePlaybackRate(&self, playback_rate: Finite<f64>) -> ErrorResult {
+        // Step 1 - 2.
+        let previous_play_state = self.play_state();
+        self.pending_playback_rate.set(Some(*playback_rate));
+
+        // Step 3.
+        if self.pending_task.get().is_some() {
+            return Ok(());
+        }
+
+        // Step 4.
+        match previous_play_state {
+            AnimationPlayState::Idle | AnimationPlayState::Paused => {
+                self.apply_pending_playback_rate();
+                Ok(())
+            },
+            AnimationPlayState::Finished => {
+                let timeline_time = self.timeline_time();
+                let unconstrained_current_time = self.current_time_ignoring_hold_time();
+                self.apply_pending_playback_rate();
+                if let (Some(timeline_time), Some(current_time)) =
+                    (timeline_time, unconstrained_current_time)
+                {
+                    let rate = self.playback_rate.get();
+                    self.start_time.set(Some(if rate == 0. {
+                        timeline_time
+                    } else {
+                        timeline_time - current_time / rate
+                    }));
+                }
+                self.update_finished_state(false, false);
+                Ok(())
+            },
+            AnimationPlayState::Running => self.play_with_auto_rewind(false),
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#reversing-an-animation-section>
+    fn Reverse(&self) -> ErrorResult {
+        // Step 1.
+        if self.timeline_time().is_none() {
+            return Err(Error::InvalidState);
+        }
+
+        // Step 2 - 3.
+        let original_pending_playback_rate = self.pending_playback_rate.get();
+        let effective_playback_rate =
+            original_pending_playback_rate.unwrap_or(self.playback_rate.get());
+        self.pending_playback_rate
+            .set(Some(-effective_playback_rate));
+
+        // Step 4.
+        let result = self.play_with_auto_rewind(true);
+        if result.is_err() {
+            self.pending_playback_rate
+                .set(original_pending_playback_rate);
+        }
+        result
+    }
+}
@@ -0,0 +1,643 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use cssparser::{match_ignore_ascii_case, ParseError, Parser, ParserInput, Token};
+use dom_struct::dom_struct;
+
+use crate::dom::animation::Animation;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::AnimationEffectBinding::{
+    AnimationEffectMethods, ComputedEffectTiming, EffectTiming, FillMode, OptionalEffectTiming,
+    PlaybackDirection,
+};
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrString;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+
+/// <https://drafts.csswg.org/web-animations-1/#animation-effect-phase>
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Phase {
+    Before,
+    Active,
+    After,
+    Idle,
+}
+
+/// <https://drafts.csswg.org/css-easing-1/#step-position>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum StepPosition {
+    JumpStart,
+    JumpEnd,
+    JumpNone,
+    JumpBoth,
+}
+
+/// The keywords for cubic Bézier easing functions, with their control points.
+const BEZIER_KEYWORDS: [(&str, [f64; 4]); 4] = [
+    ("ease", [0.25, 0.1, 0.25, 1.]),
+    ("ease-in", [0.42, 0., 1., 1.]),
+    ("ease-out", [0., 0., 0.58, 1.]),
+    ("ease-in-out", [0.42, 0., 0.58, 1.]),
+];
+
+/// An easing function, <https://drafts.csswg.org/css-easing-1/#easing-functions>.
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum Easing {
+    Linear,
+    CubicBezier(f64, f64, f64, f64),
+    Steps(u32, StepPosition),
+}
+
+impl Easing {
+    /// Parse `input` as an easing function, returning it with its serialization.
+    pub fn parse(input: &str) -> Option<(Easing, String)> {
+        let mut input = ParserInput::new(input);
+        let mut parser = Parser::new(&mut input);
+        let easing = parser
+            .parse_entirely(|parser| {
+                Easing::parse_function(parser).map_err(|()| parser.new_custom_error(()))
+            })
+            .ok()?;
+        Some((easing, easing.serialize()))
+    }
+
+    fn parse_function(parser: &mut Parser) -> Result<Easing, ()> {
+        let function = match parser.next().map_err(|_| ())?.clone() {
+            Token::Ident(ident) => {
+                if let Some((_, [x1, y1, x2, y2])) = BEZIER_KEYWORDS
+                    .iter()
+                    .find(|(keyword, _)| ident.eq_ignore_ascii_case(keyword))
+                {
+                    return Ok(Easing::CubicBezier(*x1, *y1, *x2, *y2));
+                }
+                return Ok(match_ignore_ascii_case! { &ident,
+                    "linear" => Easing::Linear,
+                    "step-start" => Easing::Steps(1, StepPosition::JumpStart),
+                    "step-end" => Easing::Steps(1, StepPosition::JumpEnd),
+                    _ => return Err(()),
+                });
+            },
+            Token::Function(function) => function,
+            _ => return Err(()),
+        };
+        parser
+            .parse_nested_block(|parser| {
+                let easing = if function.eq_ignore_ascii_case("cubic-bezier") {
+                    let mut numbers = [0.; 4];
+                    for (index, number) in numbers.iter_mut().enumerate() {
+                        if index != 0 {
+                            parser.expect_comma()?;
+                        }
+                        *number = parser.expect_number()? as f64;
+                    }
+                    let [x1, y1, x2, y2] = numbers;
+                    if !(0. ..=1.).contains(&x1) || !(0. ..=1.).contains(&x2) {
+                        return Err(parser.new_custom_error(()));
+                    }
+                    Easing::CubicBezier(x1, y1, x2, y2)
+                } else if function.eq_ignore_ascii_case("steps") {
+                    let steps = parser.expect_integer()?;
+                    let position = if parser.try_parse(|parser| parser.expect_comma()).is_ok() {
+                        let position = parser.expect_ident()?.clone();
+                        match_ignore_ascii_case! { &position,
+                            "jump-start" | "start" => StepPosition::JumpStart,
+                            "jump-end" | "end" => StepPosition::JumpEnd,
+                            "jump-none" => StepPosition::JumpNone,
+                            "jump-both" => StepPosition::JumpBoth,
+                            _ => return Err(parser.new_custom_error(())),
+                        }
+                    } else {
+                        StepPosition::JumpEnd
+                    };
+                    let minimum = if position == StepPosition::JumpNone {
+                        2
+                    } else {
+                        1
+                    };
+                    if steps < minimum {
+                        return Err(parser.new_custom_error(()));
+                    }
+                    Easing::Steps(steps as u32, position)
+                } else {
+                    return Err(parser.new_custom_error(()));
+                };
+                Ok(easing)
+            })
+            .map_err(|_: ParseError<()>| ())
+    }
+
+    fn serialize(&self) -> String {
+        match *self {
+            Easing::Linear => "linear".to_owned(),
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let keyword = BEZIER_KEYWORDS
+                    .iter()
+                    .find(|(_, points)| *points == [x1, y1, x2, y2]);
+                match keyword {
+                    Some((keyword, _)) => (*keyword).to_owned(),
+                    None => format!("cubic-bezier({}, {}, {}, {})", x1, y1, x2, y2),
+                }
+            },
+            Easing::Steps(steps, StepPosition::JumpEnd) => format!("steps({})", steps),
+            Easing::Steps(steps, position) => {
+                let position = match position {
+                    StepPosition::JumpStart => "start",
+                    StepPosition::JumpEnd => "end",
+                    StepPosition::JumpNone => "jump-none",
+                    StepPosition::JumpBoth => "jump-both",
+                };
+                format!("steps({}, {})", steps, position)
+            },
+        }
+    }
+
+    /// Apply this easing function to `input`. `before` is the before flag of
+    /// <https://drafts.csswg.org/css-easing-1/#step-easing-algo>.
+    pub fn apply(&self, input: f64, before: bool) -> f64 {
+        match *self {
+            Easing::Linear => input,
+            Easing::CubicBezier(x1, y1, x2, y2) => cubic_bezier(x1, y1, x2, y2, input),
+            Easing::Steps(steps, position) => {
+                let steps = steps as f64;
+                let mut current_step = (input * steps).floor();
+                if matches!(position, StepPosition::JumpStart | StepPosition::JumpBoth) {
+                    current_step += 1.;
+                }
+                if before && (input * steps).fract() == 0. {
+                    current_step -= 1.;
+                }
+                if input >= 0. && current_step < 0. {
+                    current_step = 0.;
+                }
+                let jumps = match position {
+                    StepPosition::JumpStart | StepPosition::JumpEnd => steps,
+                    StepPosition::JumpNone => steps - 1.,
+                    StepPosition::JumpBoth => steps + 1.,
+                };
+                if input <= 1. && current_step > jumps {
+                    current_step = jumps;
+                }
+                current_step / jumps
+            },
+        }
+    }
+}
+
+/// The output of the cubic Bézier curve through (0, 0), (x1, y1), (x2, y2) and (1, 1)
+/// for the input progress `x`, extrapolated linearly outside [0, 1].
+fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, x: f64) -> f64 {
+    let curve = |p1: f64, p2: f64, t: f64| {
+        3. * p1 * t * (1. - t) * (1. - t) + 3. * p2 * t * t * (1. - t) + t * t * t
+    };
+    if x < 0. {
+        let slope = if x1 > 0. {
+            y1 / x1
+        } else if y1 == 0. && x2 > 0. {
+            y2 / x2
+        } else {
+            0.
+        };
+        return slope * x;
+    }
+    if x > 1. {
+        let slope = if x2 < 1. {
+            (y2 - 1.) / (x2 - 1.)
+        } else if y2 == 1. && x1 < 1. {
+            (y1 - 1.) / (x1 - 1.)
+        } else {
+            0.
+        };
+        return 1. + slope * (x - 1.);
+    }
+
+    // The x coordinate of the curve grows monotonically, so bisect for the parameter
+    // whose x coordinate is `x`.
+    let (mut low, mut high) = (0., 1.);
+    let mut t = x;
+    for _ in 0..64 {
+        let current = curve(x1, x2, t);
+        if (current - x).abs() < 1e-9 {
+            break;
+        }
+        if current < x {
+            low = t;
+        } else {
+            high = t;
+        }
+        t = (low + high) / 2.;
+    }
+    curve(y1, y2, t)
+}
+
+/// The timing properties of an animation effect,
+/// <https://drafts.csswg.org/web-animations-1/#the-effecttiming-dictionaries>.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct Timing {
+    pub delay: f64,
+    pub end_delay: f64,
+    pub fill: FillMode,
+    pub iteration_start: f64,
+    pub iterations: f64,
+    /// The iteration duration in milliseconds, or `None` for "auto".
+    pub duration: Option<f64>,
+    pub direction: PlaybackDirection,
+    pub easing: Easing,
+    easing_serialization: DOMString,
+}
+
+impl Default for Timing {
+    fn default() -> Timing {
+        Timing {
+            delay: 0.,
+            end_delay: 0.,
+            fill: FillMode::Auto,
+            iteration_start: 0.,
+            iterations: 1.,
+            duration: None,
+            direction: PlaybackDirection::Normal,
+            easing: Easing::Linear,
+            easing_serialization: DOMString::from("linear"),
+        }
+    }
+}
+
+impl Timing {
+    /// Timing with only an iteration duration, as given to the constructor of an effect
+    /// or to `animate()` as a number.
+    pub fn from_duration(duration: f64) -> Fallible<Timing> {
+        let mut timing = Timing::default();
+        timing.duration = Some(check_duration(duration)?);
+        Ok(timing)
+    }
+
+    pub fn from_effect_timing(timing: &EffectTiming) -> Fallible<Timing> {
+        let mut result = Timing::default();
+        result.update(&OptionalEffectTiming {
+            delay: Some(timing.delay),
+            endDelay: Some(timing.endDelay),
+            fill: Some(timing.fill),
+            iterationStart: Some(timing.iterationStart),
+            iterations: Some(timing.iterations),
+            duration: Some(match timing.duration {
+                UnrestrictedDoubleOrString::UnrestrictedDouble(duration) => {
+                    UnrestrictedDoubleOrString::UnrestrictedDouble(duration)
+                },
+                UnrestrictedDoubleOrString::String(ref duration) => {
+                    UnrestrictedDoubleOrString::String(duration.clone())
+                },
+            }),
+            direction: Some(timing.direction),
+            easing: Some(timing.easing.clone()),
+        })?;
+        Ok(result)
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#update-the-timing-properties-of-an-animation-effect>
+    fn update(&mut self, timing: &OptionalEffectTiming) -> ErrorResult {
+        // Step 1.
+        if let Some(iteration_start) = timing.iterationStart {
+            if *iteration_start < 0. {
+                return Err(Error::Type(
+                    "iterationStart must not be negative".to_owned(),
+                ));
+            }
+        }
+        if let Some(iterations) = timing.iterations {
+            if iterations.is_nan() || iterations < 0. {
+                return Err(Error::Type(
+                    "iterations must be a non-negative number".to_owned(),
+                ));
+            }
+        }
+        let duration = match timing.duration {
+            Some(UnrestrictedDoubleOrString::UnrestrictedDouble(duration)) => {
+                Some(Some(check_duration(duration)?))
+            },
+            Some(UnrestrictedDoubleOrString::String(ref duration)) if &**duration == "auto" => {
+                Some(None)
+            },
+            Some(UnrestrictedDoubleOrString::String(_)) => {
+                return Err(Error::Type(
+                    "duration must be a number or \"auto\"".to_owned(),
+                ));
+            },
+            None => None,
+        };
+
+        // Step 2.
+        let easing = match timing.easing {
+            Some(ref easing) => match Easing::parse(easing) {
+                Some(easing) => Some(easing),
+                None => return Err(Error::Type(format!("Invalid easing '{}'", easing))),
+            },
+            None => None,
+        };
+
+        // Step 3.
+        if let Some(delay) = timing.delay {
+            self.delay = *delay;
+        }
+        if let Some(end_delay) = timing.endDelay {
+            self.end_delay = *end_delay;
+        }
+        if let Some(fill) = timing.fill {
+            self.fill = fill;
+        }
+        if let Some(iteration_start) = timing.iterationStart {
+            self.iteration_start = *iteration_start;
+        }
+        if let Some(iterations) = timing.iterations {
+            self.iterations = iterations;
+        }
+        if let Some(duration) = duration {
+            self.duration = duration;
+        }
+        if let Some(direction) = timing.direction {
+            self.direction = direction;
+        }
+        if let Some((easing, serialization)) = easing {
+            self.easing = easing;
+            self.easing_serialization = DOMString::from(serialization);
+        }
+        Ok(())
+    }
+
+    fn to_effect_timing(&self) -> EffectTiming {
+        EffectTiming {
+            delay: Finite::wrap(self.delay),
+            endDelay: Finite::wrap(self.end_delay),
+            fill: self.fill,
+            iterationStart: Finite::wrap(self.iteration_start),
+            iterations: self.iterations,
+            duration: match self.duration {
+                Some(duration) => UnrestrictedDoubleOrString::UnrestrictedDouble(duration),
+                None => UnrestrictedDoubleOrString::String(DOMString::from("auto")),
+            },
+            direction: self.direction,
+            easing: self.easing_serialization.clone(),
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#iteration-duration>
+    fn iteration_duration(&self) -> f64 {
+        self.duration.unwrap_or(0.)
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#active-duration>
+    pub fn active_duration(&self) -> f64 {
+        let iteration_duration = self.iteration_duration();
+        if iteration_duration == 0. || self.iterations == 0. {
+            return 0.;
+        }
+        iteration_duration * self.iterations
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#end-time>
+    pub fn end_time(&self) -> f64 {
+        (self.delay + self.active_duration() + self.end_delay).max(0.)
+    }
+}
+
+fn check_duration(duration: f64) -> Fallible<f64> {
+    if duration.is_nan() || duration < 0. {
+        return Err(Error::Type(
+            "duration must be a non-negative number".to_owned(),
+        ));
+    }
+    Ok(duration)
+}
+
+/// <https://drafts.csswg.org/web-animations-1/#the-animationeffect-interface>
+#[dom_struct]
+pub struct AnimationEffect {
+    reflector_: Reflector,
+    timing: DomRefCell<Timing>,
+    /// The animation this effect is associated with, if any.
+    animation: MutNullableDom<Animation>,
+}
+
+impl AnimationEffect {
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_inherited(timing: Timing) -> AnimationEffect {
+        AnimationEffect {
+            reflector_: Reflector::new(),
+            timing: DomRefCell::new(timing),
+            animation: Default::default(),
+        }
+    }
+
+    pub fn timing(&self) -> Timing {
+        self.timing.borrow().clone()
+    }
+
+    pub fn animation(&self) -> Option<DomRoot<Animation>> {
+        self.animation.get()
+    }
+
+    pub fn set_animation(&self, animation: Option<&Animation>) {
+        self.animation.set(animation);
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#local-time>
+    pub fn local_time(&self) -> Option<f64> {
+        self.animation.get()?.current_time()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#animation-effect-phases-and-states>
+    pub fn phase(&self) -> Phase {
+        let local_time = match self.local_time() {
+            Some(local_time) => local_time,
+            None => return Phase::Idle,
+        };
+        let timing = self.timing.borrow();
+        let end_time = timing.end_time();
+        let before_active_boundary = timing.delay.min(end_time).max(0.);
+        let active_after_boundary = (timing.delay + timing.active_duration())
+            .min(end_time)
+            .max(0.);
+        let backwards = self
+            .animation
+            .get()
+            .map_or(false, |animation| animation.playback_rate() < 0.);
+
+        if local_time < before_active_boundary ||
+            (backwards && local_time == before_active_boundary)
+        {
+            Phase::Before
+        } else if local_time > active_after_boundary ||
+            (!backwards && local_time == active_after_boundary)
+        {
+            Phase::After
+        } else {
+            Phase::Active
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#in-play>
+    pub fn is_in_play(&self) -> bool {
+        self.phase() == Phase::Active &&
+            self.animation
+                .get()
+                .map_or(false, |animation| !animation.is_finished())
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#current>
+    pub fn is_current(&self) -> bool {
+        if self.is_in_play() {
+            return true;
+        }
+        let animation = match self.animation.get() {
+            Some(animation) => animation,
+            None => return false,
+        };
+        let rate = animation.playback_rate();
+        match self.phase() {
+            Phase::Before => rate > 0.,
+            Phase::After => rate < 0.,
+            _ => false,
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#active-time>
+    fn active_time(&self, phase: Phase) -> Option<f64> {
+        let local_time = self.local_time()?;
+        let timing = self.timing.borrow();
+        match phase {
+            Phase::Before => match timing.fill {
+                FillMode::Backwards | FillMode::Both => Some((local_time - timing.delay).max(0.)),
+                _ => None,
+            },
+            Phase::Active => Some(local_time - timing.delay),
+            Phase::After => match timing.fill {
+                FillMode::Forwards | FillMode::Both => Some(
+                    (local_time - timing.delay)
+                        .min(timing.active_duration())
+                        .max(0.),
+                ),
+                _ => None,
+            },
+            Phase::Idle => None,
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#overall-progress>
+    fn overall_progress(&self, phase: Phase, active_time: f64) -> f64 {
+        let timing = self.timing.borrow();
+        let progress = if timing.iteration_duration() == 0. {
+            if phase == Phase::Before {
+                0.
+            } else {
+                timing.iterations
+            }
+        } else {
+            active_time / timing.iteration_duration()
+        };
+        progress + timing.iteration_start
+    }
+
+    /// The simple iteration progress and the current iteration of this effect,
+    /// <https://drafts.csswg.org/web-animations-1/#simple-iteration-progress>.
+    fn iteration_progress(&self) -> Option<(f64, f64)> {
+        let phase = self.phase();
+        let active_time = self.active_time(phase)?;
+        let overall_progress = self.overall_progress(phase, active_time);
+        let timing = self.timing.borrow();
+
+        let mut simple_progress = if overall_progress.is_infinite() {
+            timing.iteration_start % 1.
+        } else {
+            overall_progress % 1.
+        };
+        if simple_progress == 0. &&
+            matches!(phase, Phase::Active | Phase::After) &&
+            active_time == timing.active_duration() &&
+            timing.iterations != 0.
+        {
+            simple_progress = 1.;
+        }
+
+        // https://drafts.csswg.org/web-animations-1/#current-iteration
+        let current_iteration = if phase == Phase::After && timing.iterations.is_infinite() {
+            f64::INFINITY
+        } else if simple_progress == 1. {
+            overall_progress.floor() - 1.
+        } else {
+            overall_progress.floor()
+        };
+        Some((simple_progress, current_iteration))
+    }
+
+    /// The transformed progress of this effect, and its current iteration,
+    /// <https://drafts.csswg.org/web-animations-1/#calculating-the-transformed-progress>.
+    pub fn progress(&self) -> Option<(f64, f64)> {
+        let (simple_progress, current_iteration) = self.iteration_progress()?;
+        let timing = self.timing.borrow();
+
+        // https://drafts.csswg.org/web-animations-1/#calculating-the-directed-progress
+        let even_iteration = current_iteration.is_infinite() || current_iteration % 2. == 0.;
+        let forwards = match timing.direction {
+            PlaybackDirection::Normal => true,
+            PlaybackDirection::Reverse => false,
+            PlaybackDirection::Alternate => even_iteration,
+            PlaybackDirection::Alternate_reverse => !even_iteration,
+        };
+        let directed_progress = if forwards {
+            simple_progress
+        } else {
+            1. - simple_progress
+        };
+
+        let before = self.phase() == Phase::Before;
+        Some((
+            timing.easing.apply(directed_progress, before),
+            current_iteration,
+        ))
+    }
+
+    /// Let the animation of this effect know that the timing of the effect changed.
+    fn timing_changed(&self) {
+        if let Some(animation) = self.animation.get() {
+            animation.effect_timing_changed();
+        }
+    }
+}
+
+impl AnimationEffectMethods for AnimationEffect {
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animationeffect-gettiming>
+    fn GetTiming(&self) -> EffectTiming {
+        self.timing.borrow().to_effect_timing()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animationeffect-getcomputedtiming>
+    fn GetComputedTiming(&self) -> ComputedEffectTiming {
+        let timing = self.timing();
+        let mut parent = timing.to_effect_timing();
+        // The "auto" value of these resolves to their value for keyframe effects.
+        if parent.fill == FillMode::Auto {
+            parent.fill = FillMode::None;
+        }
+        parent.duration =
+            UnrestrictedDoubleOrString::UnrestrictedDouble(timing.iteration_duration());
+
+        let progress = self.progress();
+        ComputedEffectTiming {
+            parent,
+            endTime: timing.end_time(),
+            activeDuration: timing.active_duration(),
+            localTime: self.local_time().map(Finite::wrap),
+            progress: progress.map(|(progress, _)| Finite::wrap(progress)),
+            currentIteration: progress.map(|(_, current_iteration)| current_iteration),
+        }
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animationeffect-updatetiming>
+    fn UpdateTiming(&self, timing: &OptionalEffectTiming) -> ErrorResult {
+        self.timing.borrow_mut().update(timing)?;
+        self.timing_changed();
+        Ok(())
+    }
+}
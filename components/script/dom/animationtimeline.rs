@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::AnimationTimelineBinding::AnimationTimelineMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::document::Document;
+
+/// <https://drafts.csswg.org/web-animations-1/#the-animationtimeline-interface>
+#[dom_struct]
+pub struct AnimationTimeline {
+    reflector_: Reflector,
+    document: Dom<Document>,
+    /// The time of the document's animation timeline that is the zero time of this
+    /// timeline, in milliseconds.
+    origin_time: f64,
+}
+
+impl AnimationTimeline {
+    #[allow(crown::unrooted_must_root)]
+    pub fn new_inherited(document: &Document, origin_time: f64) -> AnimationTimeline {
+        AnimationTimeline {
+            reflector_: Reflector::new(),
+            document: Dom::from_ref(document),
+            origin_time,
+        }
+    }
+
+    pub fn document(&self) -> DomRoot<Document> {
+        DomRoot::from_ref(&*self.document)
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#timeline-current-time>
+    ///
+    /// The timeline is driven by the same clock as CSS animations and transitions, so
+    /// that all of them move on together at each animation tick.
+    pub fn current_time(&self) -> Option<f64> {
+        // A timeline is inactive while its document is not fully active.
+        if !self.document.is_fully_active() {
+            return None;
+        }
+        Some(self.document.animation_timeline_time() - self.origin_time)
+    }
+}
+
+impl AnimationTimelineMethods for AnimationTimeline {
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animationtimeline-currenttime>
+    fn GetCurrentTime(&self) -> Option<Finite<f64>> {
+        self.current_time().map(Finite::wrap)
+    }
+}
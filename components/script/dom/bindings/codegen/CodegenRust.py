@@ -2361,11 +2361,13 @@ DOMClass {
 
 class CGDOMJSClass(CGThing):
     """
-    Generate a DOMJSClass for a given descriptor
+    Generate a DOMJSClass for a given descriptor, or the one of the globals of
+    the isolated worlds of a Window if isolatedWorld is true.
     """
-    def __init__(self, descriptor):
+    def __init__(self, descriptor, isolatedWorld=False):
         CGThing.__init__(self)
         self.descriptor = descriptor
+        self.isolatedWorld = isolatedWorld
 
     def define(self):
         parentName = self.descriptor.getParentName()
@@ -2373,9 +2375,12 @@ class CGDOMJSClass(CGThing):
             parentName = "crate::dom::bindings::reflector::Reflector"
 
         args = {
+            "classOps": "CLASS_OPS",
+            "className": "Class",
             "domClass": DOMClass(self.descriptor),
             "enumerateHook": "None",
             "finalizeHook": FINALIZE_HOOK_NAME,
+            "visibility": "",
             "flags": "JSCLASS_FOREGROUND_FINALIZE",
             "name": str_to_const_array(self.descriptor.interface.identifier.name),
             "resolveHook": "None",
@@ -2391,8 +2396,15 @@ class CGDOMJSClass(CGThing):
             args["traceHook"] = "js::jsapi::JS_GlobalObjectTraceHook"
         elif self.descriptor.weakReferenceable:
             args["slots"] = "2"
+        if self.isolatedWorld:
+            # The native object of the global of an isolated world is the one
+            # of the page's Window, which it must not release.
+            args["classOps"] = "ISOLATED_WORLD_CLASS_OPS"
+            args["className"] = "IsolatedWorldClass"
+            args["finalizeHook"] = "finalize_isolated_world_global"
+            args["visibility"] = "pub "
         return """\
-static CLASS_OPS: js::jsapi::JSClassOps = js::jsapi::JSClassOps {
+static %(classOps)s: js::jsapi::JSClassOps = js::jsapi::JSClassOps {
     addProperty: None,
     delProperty: None,
     enumerate: None,
@@ -2405,13 +2417,13 @@ static CLASS_OPS: js::jsapi::JSClassOps = js::jsapi::JSClassOps {
     trace: Some(%(traceHook)s),
 };
 
-static Class: DOMJSClass = DOMJSClass {
+%(visibility)sstatic %(className)s: DOMJSClass = DOMJSClass {
     base: js::jsapi::JSClass {
         name: %(name)s as *const u8 as *const libc::c_char,
         flags: JSCLASS_IS_DOMJSCLASS | %(flags)s |
                (((%(slots)s) & JSCLASS_RESERVED_SLOTS_MASK) << JSCLASS_RESERVED_SLOTS_SHIFT)
                /* JSCLASS_HAS_RESERVED_SLOTS(%(slots)s) */,
-        cOps: &CLASS_OPS,
+        cOps: &%(classOps)s,
         spec: ptr::null(),
         ext: ptr::null(),
         oOps: ptr::null(),
@@ -3031,18 +3043,10 @@ class CGWrapGlobalMethod(CGAbstractMethod):
     def definition_body(self):
         values = {
             "concreteType": self.descriptor.concreteType,
-            "unforgeable": CopyLegacyUnforgeablePropertiesToInstance(self.descriptor)
+            "unforgeable": CopyLegacyUnforgeablePropertiesToInstance(self.descriptor),
+            "members": DefineGlobalMembers(self.properties),
         }
 
-        pairs = [
-            ("define_guarded_properties", self.properties.attrs),
-            ("define_guarded_methods", self.properties.methods),
-            ("define_guarded_constants", self.properties.consts)
-        ]
-        members = ["%s(cx, obj.handle(), %s, obj.handle());" % (function, array.variableName())
-                   for (function, array) in pairs if array.length() > 0]
-        values["members"] = "\n".join(members)
-
         return CGGeneric("""\
 let raw = Root::new(MaybeUnreflectedDom::from_box(object));
 let origin = (*raw.as_ptr()).upcast::<GlobalScope>().origin();
@@ -3075,6 +3079,73 @@ DomRoot::from_ref(&*root)\
 """ % values)
 
 
+def DefineGlobalMembers(properties):
+    """
+    Define the members of a global interface on the global object.
+    """
+    pairs = [
+        ("define_guarded_properties", properties.attrs),
+        ("define_guarded_methods", properties.methods),
+        ("define_guarded_constants", properties.consts)
+    ]
+    members = ["%s(cx, obj.handle(), %s, obj.handle());" % (function, array.variableName())
+               for (function, array) in pairs if array.length() > 0]
+    return "\n".join(members)
+
+
+class CGWrapIsolatedWorldMethod(CGAbstractMethod):
+    """
+    Class that generates the Window_Binding::WrapIsolatedWorld function, which
+    creates the global object of an isolated world of a page: another reflector
+    of the page's Window, in a compartment of its own, with the prototypes of
+    the world.
+    """
+    def __init__(self, descriptor, properties):
+        assert descriptor.name == "Window"
+        args = [Argument('SafeJSContext', 'cx'),
+                Argument("&%s" % descriptor.concreteType, 'object'),
+                Argument('HandleObject', 'page_global')]
+        CGAbstractMethod.__init__(self, descriptor, 'WrapIsolatedWorld', '*mut JSObject', args,
+                                  pub=True, unsafe=True)
+        self.properties = properties
+
+    def definition_body(self):
+        values = {
+            "concreteType": self.descriptor.concreteType,
+            "unforgeable": CopyLegacyUnforgeablePropertiesToInstance(self.descriptor),
+            "members": DefineGlobalMembers(self.properties),
+        }
+
+        return CGGeneric("""\
+let origin = object.upcast::<GlobalScope>().origin();
+
+rooted!(in(*cx) let mut obj = ptr::null_mut::<JSObject>());
+create_isolated_world_global_object(
+    cx,
+    &IsolatedWorldClass.base,
+    object as *const %(concreteType)s as *const libc::c_void,
+    trace_isolated_world_global,
+    page_global,
+    obj.handle_mut(),
+    origin);
+assert!(!obj.is_null());
+
+let _ac = JSAutoRealm::new(*cx, obj.get());
+rooted!(in(*cx) let mut canonical_proto = ptr::null_mut::<JSObject>());
+GetProtoObject(cx, obj.handle(), canonical_proto.handle_mut());
+assert!(JS_SetPrototype(*cx, obj.handle(), canonical_proto.handle()));
+let mut immutable = false;
+assert!(JS_SetImmutablePrototype(*cx, obj.handle(), &mut immutable));
+assert!(immutable);
+
+%(members)s
+
+%(unforgeable)s
+
+obj.get()\
+""" % values)
+
+
 class CGIDLInterface(CGThing):
     """
     Class for codegen of an implementation of the IDLInterface trait.
@@ -6532,6 +6603,7 @@ def generate_imports(config, cgthings, descriptors, callbacks=None, dictionaries
         'crate::dom::bindings::interface::NonCallbackInterfaceObjectClass',
         'crate::dom::bindings::interface::ProtoOrIfaceIndex',
         'crate::dom::bindings::interface::create_global_object',
+        'crate::dom::bindings::interface::create_isolated_world_global_object',
         'crate::dom::bindings::interface::create_callback_interface_object',
         'crate::dom::bindings::interface::create_interface_prototype_object',
         'crate::dom::bindings::interface::create_named_constructors',
@@ -6570,6 +6642,7 @@ def generate_imports(config, cgthings, descriptors, callbacks=None, dictionaries
         'crate::dom::bindings::utils::callargs_is_constructing',
         'crate::dom::bindings::utils::enumerate_global',
         'crate::dom::bindings::utils::finalize_global',
+        'crate::dom::bindings::utils::finalize_isolated_world_global',
         'crate::dom::bindings::utils::generic_getter',
         'crate::dom::bindings::utils::generic_lenient_getter',
         'crate::dom::bindings::utils::generic_lenient_setter',
@@ -6585,6 +6658,7 @@ def generate_imports(config, cgthings, descriptors, callbacks=None, dictionaries
         'crate::dom::bindings::utils::resolve_global',
         'crate::dom::bindings::utils::set_dictionary_property',
         'crate::dom::bindings::utils::trace_global',
+        'crate::dom::bindings::utils::trace_isolated_world_global',
         'crate::dom::bindings::trace::JSTraceable',
         'crate::dom::bindings::trace::RootedTraceableBox',
         'crate::dom::bindings::callback::CallSetup',
@@ -6594,6 +6668,7 @@ def generate_imports(config, cgthings, descriptors, callbacks=None, dictionaries
         'crate::dom::bindings::callback::CallbackObject',
         'crate::dom::bindings::callback::ExceptionHandling',
         'crate::dom::bindings::callback::wrap_call_this_object',
+        'crate::dom::bindings::callback::ThisReflector',
         'crate::dom::bindings::conversions::ConversionBehavior',
         'crate::dom::bindings::conversions::ConversionResult',
         'crate::dom::bindings::conversions::DOM_OBJECT_SLOT',
@@ -6782,6 +6857,9 @@ class CGDescriptor(CGThing):
 
             if descriptor.isGlobal():
                 cgThings.append(CGWrapGlobalMethod(descriptor, properties))
+                if descriptor.name == "Window":
+                    cgThings.append(CGDOMJSClass(descriptor, isolatedWorld=True))
+                    cgThings.append(CGWrapIsolatedWorldMethod(descriptor, properties))
             else:
                 cgThings.append(CGWrapMethod(descriptor))
                 if descriptor.interface.isIteratorInterface():
@@ -7198,6 +7276,28 @@ class CGRegisterProxyHandlersMethod(CGAbstractMethod):
         ], "\n")
 
 
+class CGGetProtoObjectForIdMethod(CGAbstractMethod):
+    def __init__(self, descriptors):
+        docs = ("Get the interface prototype object of the interface `id` in `global`, creating it\n"
+                "if needed. `rval` is left null for the interfaces that have none.")
+        args = [Argument('PrototypeList::ID', 'id'),
+                Argument('SafeJSContext', 'cx'),
+                Argument('HandleObject', 'global'),
+                Argument('MutableHandleObject', 'rval')]
+        CGAbstractMethod.__init__(self, None, 'GetProtoObjectForId', 'void', args,
+                                  pub=True, docs=docs)
+        self.descriptors = descriptors
+
+    def definition_body(self):
+        arms = [
+            CGGeneric("PrototypeList::ID::%s => %s::%s::GetProtoObject(cx, global, rval),"
+                      % (desc.name, getModuleFromObject(desc.interface), toBindingNamespace(desc.name)))
+            for desc in self.descriptors
+        ]
+        arms.append(CGGeneric("_ => {},"))
+        return CGWrapper(CGIndenter(CGList(arms, "\n")), pre="match id {\n", post="\n}")
+
+
 class CGRegisterProxyHandlers(CGThing):
     def __init__(self, config):
         descriptors = config.getDescriptors(proxy=True)
@@ -7540,7 +7640,7 @@ class CGCallback(CGClass):
               })
         return [ClassMethod(method.name + '_', method.returnType, args,
                             bodyInHeader=True,
-                            templateArgs=["T: DomObject"],
+                            templateArgs=["T: ThisReflector"],
                             body=bodyWithThis,
                             visibility='pub'),
                 ClassMethod(method.name + '__', method.returnType, argsWithoutThis,
@@ -8157,10 +8257,16 @@ class GlobalGenRoots():
         # TODO - Generate the methods we want
         code = CGList([
             CGRegisterProxyHandlers(config),
+            CGGetProtoObjectForIdMethod(config.getDescriptors(isCallback=False, isNamespace=False,
+                                                              isInline=False)),
         ], "\n")
 
         return CGImports(code, descriptors=[], callbacks=[], dictionaries=[], enums=[], typedefs=[], imports=[
             'crate::dom::bindings::codegen::Bindings',
+            'crate::dom::bindings::codegen::PrototypeList',
+            'crate::script_runtime::JSContext as SafeJSContext',
+            'js::rust::HandleObject',
+            'js::rust::MutableHandleObject',
         ], config=config, ignored_warnings=[])
 
     @staticmethod
@@ -68,6 +68,7 @@ use crate::dom::htmlformcontrolscollection::HTMLFormControlsCollection;
 use crate::dom::htmloptionscollection::HTMLOptionsCollection;
 use crate::dom::nodelist::NodeList;
 use crate::dom::windowproxy::WindowProxy;
+use crate::isolated_world::maybe_wrap_value_for_isolated_world;
 
 /// A trait to check whether a given `JSObject` implements an IDL interface.
 pub trait IDLInterface {
@@ -351,6 +352,7 @@ impl ToJSValConvertible for Reflector {
         assert!(!obj.is_null());
         rval.set(ObjectValue(obj));
         maybe_wrap_value(cx, rval);
+        maybe_wrap_value_for_isolated_world(cx, rval);
     }
 }
 
@@ -12,13 +12,14 @@ use js::glue::UncheckedUnwrapObject;
 use js::jsapi::JS::CompartmentIterResult;
 use js::jsapi::{
     jsid, CallArgs, CheckedUnwrapStatic, Compartment, CompartmentSpecifier, CurrentGlobalOrNull,
-    GetFunctionRealm, GetNonCCWObjectGlobal, GetRealmGlobalOrNull, GetWellKnownSymbol,
-    HandleObject as RawHandleObject, IsSharableCompartment, IsSystemCompartment, JSAutoRealm,
-    JSClass, JSClassOps, JSContext, JSFunctionSpec, JSObject, JSPropertySpec, JSString, JSTracer,
-    JS_AtomizeAndPinString, JS_GetFunctionObject, JS_GetProperty, JS_IterateCompartments,
-    JS_NewFunction, JS_NewGlobalObject, JS_NewObject, JS_NewPlainObject, JS_NewStringCopyN,
-    JS_SetReservedSlot, JS_WrapObject, ObjectOps, OnNewGlobalHookOption, SymbolCode,
-    TrueHandleValue, Value, JSFUN_CONSTRUCTOR, JSPROP_PERMANENT, JSPROP_READONLY, JSPROP_RESOLVING,
+    GetFirstGlobalInCompartment, GetFunctionRealm, GetNonCCWObjectGlobal, GetRealmGlobalOrNull,
+    GetWellKnownSymbol, HandleObject as RawHandleObject, IsSharableCompartment,
+    IsSystemCompartment, JSAutoRealm, JSClass, JSClassOps, JSContext, JSFunctionSpec, JSObject,
+    JSPropertySpec, JSString, JSTracer, JS_AtomizeAndPinString, JS_GetFunctionObject,
+    JS_GetProperty, JS_IterateCompartments, JS_NewFunction, JS_NewGlobalObject, JS_NewObject,
+    JS_NewPlainObject, JS_NewStringCopyN, JS_SetReservedSlot, JS_WrapObject, ObjectOps,
+    OnNewGlobalHookOption, SymbolCode, TrueHandleValue, Value, JSFUN_CONSTRUCTOR, JSPROP_PERMANENT,
+    JSPROP_READONLY, JSPROP_RESOLVING,
 };
 use js::jsval::{JSVal, NullValue, PrivateValue};
 use js::rust::wrappers::{
@@ -42,6 +43,7 @@ use crate::dom::bindings::utils::{
     callargs_is_constructing, get_proto_or_iface_array, DOMJSClass, ProtoOrIfaceArray,
     DOM_PROTOTYPE_SLOT, JSCLASS_DOM_GLOBAL,
 };
+use crate::isolated_world::is_isolated_world_global;
 use crate::script_runtime::JSContext as SafeJSContext;
 
 /// The class of a non-callback interface object.
@@ -138,16 +140,56 @@ pub unsafe fn create_global_object(
     class: &'static JSClass,
     private: *const libc::c_void,
     trace: TraceHook,
-    mut rval: MutableHandleObject,
+    rval: MutableHandleObject,
     origin: &MutableOrigin,
 ) {
-    assert!(rval.is_null());
+    let mut options = global_realm_options(trace);
+    select_compartment(cx, &mut options);
+    new_global_object(cx, class, private, options, rval, origin);
+}
+
+/// Create the global object of an isolated world of the page whose global object is
+/// `page_global`, with the given class. The world gets a compartment of its own, so that
+/// the objects of the page only reach it through wrappers, in the zone of the page, so
+/// that the two globals can hold on to each other.
+pub unsafe fn create_isolated_world_global_object(
+    cx: SafeJSContext,
+    class: &'static JSClass,
+    private: *const libc::c_void,
+    trace: TraceHook,
+    page_global: HandleObject,
+    rval: MutableHandleObject,
+    origin: &MutableOrigin,
+) {
+    let mut options = global_realm_options(trace);
+    options
+        .creationOptions_
+        .setNewCompartmentInExistingZone(page_global.get());
+    new_global_object(cx, class, private, options, rval, origin);
+}
 
+/// The options of the realm of a new global object.
+fn global_realm_options(trace: TraceHook) -> RealmOptions {
     let mut options = RealmOptions::default();
     options.creationOptions_.traceGlobal_ = Some(trace);
     options.creationOptions_.sharedMemoryAndAtomics_ = false;
     options.creationOptions_.streams_ = true;
-    select_compartment(cx, &mut options);
+    options.creationOptions_.readableByteStreams_ = true;
+    options.creationOptions_.byobStreamReaders_ = true;
+    options.creationOptions_.writableStreams_ = true;
+    options.creationOptions_.readableStreamPipeTo_ = true;
+    options
+}
+
+unsafe fn new_global_object(
+    cx: SafeJSContext,
+    class: &'static JSClass,
+    private: *const libc::c_void,
+    options: RealmOptions,
+    mut rval: MutableHandleObject,
+    origin: &MutableOrigin,
+) {
+    assert!(rval.is_null());
 
     let principal = ServoJSPrincipals::new(origin);
 
@@ -187,6 +229,11 @@ fn select_compartment(cx: SafeJSContext, options: &mut RealmOptions) {
             return CompartmentIterResult::KeepGoing;
         }
 
+        // The compartments of isolated worlds are kept to themselves.
+        if is_isolated_world_global(GetFirstGlobalInCompartment(compartment)) {
+            return CompartmentIterResult::KeepGoing;
+        }
+
         // Choose any sharable, non-system compartment in this context to allow
         // same-agent documents to share JS and DOM objects.
         *data = compartment;
@@ -24,8 +24,9 @@ use js::rust::wrappers::{JS_ReadStructuredClone, JS_WriteStructuredClone};
 use js::rust::{CustomAutoRooterGuard, HandleValue, MutableHandleValue};
 use msg::constellation_msg::{BlobId, MessagePortId};
 use script_traits::serializable::BlobImpl;
-use script_traits::transferable::MessagePortImpl;
+use script_traits::transferable::{ImageBitmapImpl, MessagePortImpl, OffscreenCanvasImpl};
 use script_traits::StructuredSerializedData;
+use servo_allocator::Partition;
 
 use crate::dom::bindings::conversions::{root_from_object, ToJSValConvertible};
 use crate::dom::bindings::error::{Error, Fallible};
@@ -35,7 +36,9 @@ use crate::dom::bindings::serializable::{Serializable, StorageKey};
 use crate::dom::bindings::transferable::Transferable;
 use crate::dom::blob::Blob;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::messageport::MessagePort;
+use crate::dom::offscreencanvas::OffscreenCanvas;
 use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
 use crate::script_runtime::JSContext as SafeJSContext;
 
@@ -49,6 +52,8 @@ enum StructuredCloneTags {
     Min = 0xFFFF8000,
     DomBlob = 0xFFFF8001,
     MessagePort = 0xFFFF8002,
+    OffscreenCanvas = 0xFFFF8003,
+    ImageBitmap = 0xFFFF8004,
     Max = 0xFFFFFFFF,
 }
 
@@ -165,10 +170,10 @@ unsafe extern "C" fn read_transfer_callback(
     closure: *mut raw::c_void,
     return_object: RawMutableHandleObject,
 ) -> bool {
+    let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+    let in_realm_proof = AlreadyInRealm::assert_for_cx(SafeJSContext::from_ptr(cx));
+    let owner = GlobalScope::from_context(cx, InRealm::Already(&in_realm_proof));
     if tag == StructuredCloneTags::MessagePort as u32 {
-        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
-        let in_realm_proof = AlreadyInRealm::assert_for_cx(SafeJSContext::from_ptr(cx));
-        let owner = GlobalScope::from_context(cx, InRealm::Already(&in_realm_proof));
         if let Ok(_) = <MessagePort as Transferable>::transfer_receive(
             &owner,
             &mut sc_holder,
@@ -178,6 +183,26 @@ unsafe extern "C" fn read_transfer_callback(
             return true;
         }
     }
+    if tag == StructuredCloneTags::OffscreenCanvas as u32 {
+        if let Ok(_) = <OffscreenCanvas as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
+    if tag == StructuredCloneTags::ImageBitmap as u32 {
+        if let Ok(_) = <ImageBitmap as Transferable>::transfer_receive(
+            &owner,
+            &mut sc_holder,
+            extra_data,
+            return_object,
+        ) {
+            return true;
+        }
+    }
     false
 }
 
@@ -200,6 +225,24 @@ unsafe extern "C" fn write_transfer_callback(
             return true;
         }
     }
+    if let Ok(canvas) = root_from_object::<OffscreenCanvas>(*obj, cx) {
+        *tag = StructuredCloneTags::OffscreenCanvas as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = canvas.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
+    if let Ok(bitmap) = root_from_object::<ImageBitmap>(*obj, cx) {
+        *tag = StructuredCloneTags::ImageBitmap as u32;
+        *ownership = TransferableOwnership::SCTAG_TMO_CUSTOM;
+        let mut sc_holder = &mut *(closure as *mut StructuredDataHolder);
+        if let Ok(data) = bitmap.transfer(&mut sc_holder) {
+            *extra_data = data;
+            return true;
+        }
+    }
     false
 }
 
@@ -221,6 +264,12 @@ unsafe extern "C" fn can_transfer_callback(
     if let Ok(_port) = root_from_object::<MessagePort>(*obj, cx) {
         return true;
     }
+    if let Ok(_canvas) = root_from_object::<OffscreenCanvas>(*obj, cx) {
+        return true;
+    }
+    if let Ok(_bitmap) = root_from_object::<ImageBitmap>(*obj, cx) {
+        return true;
+    }
     false
 }
 
@@ -268,6 +317,12 @@ pub enum StructuredDataHolder {
         /// used as part of the "deserialize" steps of blobs,
         /// to produce the DOM blobs stored in `blobs` above.
         blob_impls: Option<HashMap<BlobId, BlobImpl>>,
+        /// A map of offscreen canvas implementations,
+        /// used as part of the "transfer-receiving" steps of offscreen canvases.
+        offscreen_canvas_impls: Option<HashMap<u64, OffscreenCanvasImpl>>,
+        /// A map of image bitmap implementations,
+        /// used as part of the "transfer-receiving" steps of image bitmaps.
+        image_bitmap_impls: Option<HashMap<u64, ImageBitmapImpl>>,
     },
     /// A data holder for transferred and serialized objects.
     Write {
@@ -275,6 +330,10 @@ pub enum StructuredDataHolder {
         ports: Option<HashMap<MessagePortId, MessagePortImpl>>,
         /// Serialized blobs.
         blobs: Option<HashMap<BlobId, BlobImpl>>,
+        /// Transferred offscreen canvases.
+        offscreen_canvases: Option<HashMap<u64, OffscreenCanvasImpl>>,
+        /// Transferred image bitmaps.
+        image_bitmaps: Option<HashMap<u64, ImageBitmapImpl>>,
     },
 }
 
@@ -284,6 +343,7 @@ pub fn write(
     message: HandleValue,
     transfer: Option<CustomAutoRooterGuard<Vec<*mut JSObject>>>,
 ) -> Fallible<StructuredSerializedData> {
+    let _partition = servo_allocator::enter_partition(Partition::StructuredClone);
     unsafe {
         rooted!(in(*cx) let mut val = UndefinedValue());
         if let Some(transfer) = transfer {
@@ -292,6 +352,8 @@ pub fn write(
         let mut sc_holder = StructuredDataHolder::Write {
             ports: None,
             blobs: None,
+            offscreen_canvases: None,
+            image_bitmaps: None,
         };
         let sc_holder_ptr = &mut sc_holder as *mut _;
 
@@ -326,15 +388,22 @@ pub fn write(
 
         DeleteJSAutoStructuredCloneBuffer(scbuf);
 
-        let (mut blob_impls, mut port_impls) = match sc_holder {
-            StructuredDataHolder::Write { blobs, ports } => (blobs, ports),
+        let (blob_impls, port_impls, offscreen_canvas_impls, image_bitmap_impls) = match sc_holder {
+            StructuredDataHolder::Write {
+                blobs,
+                ports,
+                offscreen_canvases,
+                image_bitmaps,
+            } => (blobs, ports, offscreen_canvases, image_bitmaps),
             _ => panic!("Unexpected variant of StructuredDataHolder"),
         };
 
         let data = StructuredSerializedData {
             serialized: data,
-            ports: port_impls.take(),
-            blobs: blob_impls.take(),
+            ports: port_impls,
+            blobs: blob_impls,
+            offscreen_canvases: offscreen_canvas_impls,
+            image_bitmaps: image_bitmap_impls,
         };
 
         Ok(data)
@@ -345,16 +414,28 @@ pub fn write(
 /// Returns a vec of rooted transfer-received ports, or an error.
 pub fn read(
     global: &GlobalScope,
+    data: StructuredSerializedData,
+    rval: MutableHandleValue,
+) -> Result<Vec<DomRoot<MessagePort>>, ()> {
+    let _ac = enter_realm(&*global);
+    read_in_current_realm(data, rval)
+}
+
+/// Like `read`, but creates the deserialized objects in the current realm rather
+/// than in the one of a given global, for when that realm is an isolated world.
+pub fn read_in_current_realm(
     mut data: StructuredSerializedData,
     rval: MutableHandleValue,
 ) -> Result<Vec<DomRoot<MessagePort>>, ()> {
+    let _partition = servo_allocator::enter_partition(Partition::StructuredClone);
     let cx = GlobalScope::get_cx();
-    let _ac = enter_realm(&*global);
     let mut sc_holder = StructuredDataHolder::Read {
         blobs: None,
         message_ports: None,
         port_impls: data.ports.take(),
         blob_impls: data.blobs.take(),
+        offscreen_canvas_impls: data.offscreen_canvases.take(),
+        image_bitmap_impls: data.image_bitmaps.take(),
     };
     let sc_holder_ptr = &mut sc_holder as *mut _;
     unsafe {
@@ -14,12 +14,13 @@ use js::glue::{
     UnwrapObjectDynamic, UnwrapObjectStatic, RUST_FUNCTION_VALUE_TO_JITINFO,
 };
 use js::jsapi::{
-    AtomToLinearString, CallArgs, DOMCallbacks, GetLinearStringCharAt, GetLinearStringLength,
-    GetNonCCWObjectGlobal, HandleId as RawHandleId, HandleObject as RawHandleObject, Heap, JSAtom,
-    JSContext, JSJitInfo, JSObject, JSTracer, JS_DeprecatedStringHasLatin1Chars,
-    JS_EnumerateStandardClasses, JS_FreezeObject, JS_GetLatin1StringCharsAndLength,
-    JS_IsExceptionPending, JS_IsGlobalObject, JS_ResolveStandardClass,
-    MutableHandleIdVector as RawMutableHandleIdVector, ObjectOpResult, StringIsArrayIndex,
+    AtomToLinearString, CallArgs, DOMCallbacks, GCContext, GetLinearStringCharAt,
+    GetLinearStringLength, GetNonCCWObjectGlobal, HandleId as RawHandleId,
+    HandleObject as RawHandleObject, Heap, JSAtom, JSContext, JSJitInfo, JSObject, JSTracer,
+    JS_DeprecatedStringHasLatin1Chars, JS_EnumerateStandardClasses, JS_FreezeObject,
+    JS_GetLatin1StringCharsAndLength, JS_IsExceptionPending, JS_IsGlobalObject,
+    JS_ResolveStandardClass, MutableHandleIdVector as RawMutableHandleIdVector, ObjectOpResult,
+    StringIsArrayIndex,
 };
 use js::jsval::{JSVal, UndefinedValue};
 use js::rust::wrappers::{
@@ -406,6 +407,18 @@ pub unsafe fn trace_global(tracer: *mut JSTracer, obj: *mut JSObject) {
     }
 }
 
+/// Trace hook of the global object of an isolated world. Its native object is the one of
+/// the page's global object, which traces it, so only the prototypes are traced.
+pub unsafe extern "C" fn trace_isolated_world_global(tracer: *mut JSTracer, obj: *mut JSObject) {
+    trace_global(tracer, obj);
+}
+
+/// Finalize hook of the global object of an isolated world. Its native object belongs to
+/// the page's global object, so only the prototypes are released.
+pub unsafe extern "C" fn finalize_isolated_world_global(_: *mut GCContext, obj: *mut JSObject) {
+    finalize_global(obj);
+}
+
 /// Enumerate lazy properties of a global object.
 pub unsafe extern "C" fn enumerate_global(
     cx: *mut JSContext,
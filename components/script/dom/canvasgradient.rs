@@ -3,7 +3,8 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use canvas_traits::canvas::{
-    CanvasGradientStop, FillOrStrokeStyle, LinearGradientStyle, RadialGradientStyle,
+    CanvasGradientStop, ConicGradientStyle, FillOrStrokeStyle, LinearGradientStyle,
+    RadialGradientStyle,
 };
 use dom_struct::dom_struct;
 
@@ -30,6 +31,7 @@ pub struct CanvasGradient {
 pub enum CanvasGradientStyle {
     Linear(#[no_trace] LinearGradientStyle),
     Radial(#[no_trace] RadialGradientStyle),
+    Conic(#[no_trace] ConicGradientStyle),
 }
 
 impl CanvasGradient {
@@ -94,6 +96,14 @@ impl<'a> ToFillOrStrokeStyle for &'a CanvasGradient {
                     gradient_stops,
                 ))
             },
+            CanvasGradientStyle::Conic(ref gradient) => {
+                FillOrStrokeStyle::ConicGradient(ConicGradientStyle::new(
+                    gradient.start_angle,
+                    gradient.x,
+                    gradient.y,
+                    gradient_stops,
+                ))
+            },
         }
     }
 }
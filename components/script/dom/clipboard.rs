@@ -0,0 +1,295 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::{ClipboardData, ClipboardImage, EmbedderMsg};
+use image::codecs::png::PngEncoder;
+use image::{ColorType, ImageEncoder, ImageFormat};
+use ipc_channel::ipc;
+use js::rust::HandleValue;
+use script_traits::serializable::BlobImpl;
+
+use crate::dom::bindings::codegen::Bindings::ClipboardBinding::ClipboardMethods;
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::PresentationStyle;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::Blob;
+use crate::dom::clipboarditem::{
+    ClipboardItem, Representation, RepresentationData, SUPPORTED_TYPES,
+};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::request_permission_to_use;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+
+/// <https://w3c.github.io/clipboard-apis/#clipboard-interface>
+#[dom_struct]
+pub struct Clipboard {
+    eventtarget: EventTarget,
+}
+
+impl Clipboard {
+    fn new_inherited() -> Clipboard {
+        Clipboard {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<Clipboard> {
+        reflect_dom_object(Box::new(Clipboard::new_inherited()), global)
+    }
+
+    /// Ask the user for `permission_name` if needed, rejecting `promise` if it is not granted.
+    fn check_permission(&self, permission_name: PermissionName, promise: &Promise) -> bool {
+        if request_permission_to_use(permission_name, &self.global()) != PermissionState::Granted {
+            promise.reject_error(Error::NotAllowed);
+            return false;
+        }
+        true
+    }
+
+    fn read_system_clipboard(&self) -> Vec<ClipboardData> {
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        self.global()
+            .send_to_embedder(EmbedderMsg::GetClipboardData(sender));
+        receiver.recv().unwrap_or_else(|e| {
+            warn!("Failed to receive clipboard data from embedder ({:?}).", e);
+            vec![]
+        })
+    }
+}
+
+impl ClipboardMethods for Clipboard {
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-read>
+    fn Read(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        if !self.check_permission(PermissionName::Clipboard_read, &promise) {
+            return promise;
+        }
+
+        let representations: Vec<Representation> = self
+            .read_system_clipboard()
+            .into_iter()
+            .filter_map(|data| {
+                let (mime_type, bytes) = match data {
+                    ClipboardData::Text(text) => ("text/plain", text.into_bytes()),
+                    ClipboardData::Image(image) => ("image/png", encode_png(image)?),
+                };
+                let blob = Blob::new(
+                    &global,
+                    BlobImpl::new_from_bytes(bytes, mime_type.to_owned()),
+                );
+                let data = Promise::new(&global);
+                data.resolve_native(&blob);
+                Some(Representation {
+                    mime_type: DOMString::from(mime_type),
+                    data,
+                })
+            })
+            .collect();
+
+        let items = if representations.is_empty() {
+            vec![]
+        } else {
+            vec![ClipboardItem::new(
+                global.as_window(),
+                None,
+                representations,
+                PresentationStyle::Unspecified,
+            )]
+        };
+        promise.resolve_native(&items);
+        promise
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-readtext>
+    fn ReadText(&self) -> Rc<Promise> {
+        let promise = Promise::new(&self.global());
+        if !self.check_permission(PermissionName::Clipboard_read, &promise) {
+            return promise;
+        }
+
+        let text = self
+            .read_system_clipboard()
+            .into_iter()
+            .find_map(|data| match data {
+                ClipboardData::Text(text) => Some(text),
+                ClipboardData::Image(_) => None,
+            });
+        match text {
+            Some(text) => promise.resolve_native(&DOMString::from(text)),
+            None => promise.reject_error(Error::NotFound),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-write>
+    fn Write(&self, data: Vec<DomRoot<ClipboardItem>>) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        if !self.check_permission(PermissionName::Clipboard_write, &promise) {
+            return promise;
+        }
+
+        let representations: Vec<&Representation> = data
+            .iter()
+            .flat_map(|item| item.representations())
+            .collect();
+        if representations
+            .iter()
+            .any(|representation| !SUPPORTED_TYPES.contains(&&*representation.mime_type))
+        {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+        if representations.is_empty() {
+            global.send_to_embedder(EmbedderMsg::SetClipboardData(vec![]));
+            promise.resolve_native(&());
+            return promise;
+        }
+
+        let write = Rc::new(PendingWrite {
+            promise: promise.clone(),
+            data: RefCell::new(vec![None; representations.len()]),
+            remaining: Cell::new(representations.len()),
+        });
+        let realm = enter_realm(&*global);
+        let comp = InRealm::Entered(&realm);
+        for (index, representation) in representations.iter().enumerate() {
+            let handler = PromiseNativeHandler::new(
+                &global,
+                Some(Box::new(WriteHandler {
+                    write: write.clone(),
+                    index,
+                    mime_type: representation.mime_type.to_string(),
+                })),
+                Some(Box::new(WriteRejectionHandler {
+                    write: write.clone(),
+                })),
+            );
+            representation.data.append_native_handler(&handler, comp);
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboard-writetext>
+    fn WriteText(&self, data: DOMString) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        if !self.check_permission(PermissionName::Clipboard_write, &promise) {
+            return promise;
+        }
+
+        global.send_to_embedder(EmbedderMsg::SetClipboardContents(data.to_string()));
+        promise.resolve_native(&());
+        promise
+    }
+}
+
+/// A write to the clipboard waiting for the data of the items written.
+#[derive(JSTraceable, MallocSizeOf)]
+struct PendingWrite {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    /// The data of each representation, as it becomes known.
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    #[no_trace]
+    data: RefCell<Vec<Option<ClipboardData>>>,
+    /// The number of representations whose data is still unknown, or zero once the write
+    /// is done or failed.
+    remaining: Cell<usize>,
+}
+
+impl PendingWrite {
+    fn fail(&self, error: Error) {
+        if self.remaining.replace(0) != 0 {
+            self.promise.reject_error(error);
+        }
+    }
+
+    fn set_data(&self, index: usize, data: ClipboardData) {
+        if self.remaining.get() == 0 {
+            return;
+        }
+        self.data.borrow_mut()[index] = Some(data);
+        self.remaining.set(self.remaining.get() - 1);
+        if self.remaining.get() != 0 {
+            return;
+        }
+
+        let data = self.data.take().into_iter().flatten().collect();
+        self.promise
+            .global()
+            .send_to_embedder(EmbedderMsg::SetClipboardData(data));
+        self.promise.resolve_native(&());
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct WriteHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    write: Rc<PendingWrite>,
+    index: usize,
+    mime_type: String,
+}
+
+impl Callback for WriteHandler {
+    fn callback(&self, cx: JSContext, v: HandleValue, _realm: InRealm) {
+        let bytes = match RepresentationData::from_value(cx, v).and_then(|data| data.bytes()) {
+            Some(bytes) => bytes,
+            None => return self.write.fail(Error::Data),
+        };
+        let data = match &*self.mime_type {
+            "text/plain" => ClipboardData::Text(String::from_utf8_lossy(&bytes).into_owned()),
+            _ => match decode_png(&bytes) {
+                Some(image) => ClipboardData::Image(image),
+                None => return self.write.fail(Error::Data),
+            },
+        };
+        self.write.set_data(self.index, data);
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct WriteRejectionHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    write: Rc<PendingWrite>,
+}
+
+impl Callback for WriteRejectionHandler {
+    fn callback(&self, _cx: JSContext, _v: HandleValue, _realm: InRealm) {
+        self.write.fail(Error::NotAllowed);
+    }
+}
+
+fn encode_png(image: ClipboardImage) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    PngEncoder::new(&mut bytes)
+        .write_image(&image.rgba, image.width, image.height, ColorType::Rgba8)
+        .ok()?;
+    Some(bytes)
+}
+
+fn decode_png(bytes: &[u8]) -> Option<ClipboardImage> {
+    let image = image::load_from_memory_with_format(bytes, ImageFormat::Png)
+        .ok()?
+        .to_rgba8();
+    Some(ClipboardImage {
+        width: image.width(),
+        height: image.height(),
+        rgba: image.into_raw(),
+    })
+}
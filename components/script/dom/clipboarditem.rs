@@ -0,0 +1,240 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use js::rust::{HandleObject, HandleValue};
+use script_traits::serializable::BlobImpl;
+
+use crate::dom::bindings::codegen::Bindings::ClipboardItemBinding::{
+    ClipboardItemMethods, ClipboardItemOptions, PresentationStyle,
+};
+use crate::dom::bindings::conversions::{jsstring_to_str, root_from_handlevalue};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::record::Record;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::blob::Blob;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::window::Window;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+
+/// The types of the data that can be read from and written to the clipboard, a subset
+/// of <https://w3c.github.io/clipboard-apis/#mandatory-data-types-x>.
+pub const SUPPORTED_TYPES: [&str; 2] = ["text/plain", "image/png"];
+
+/// The data of a clipboard item in one type.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct Representation {
+    pub mime_type: DOMString,
+    /// A promise for a string or a blob of the data.
+    #[ignore_malloc_size_of = "Rc"]
+    pub data: Rc<Promise>,
+}
+
+/// The data a representation was resolved with.
+pub enum RepresentationData {
+    Text(DOMString),
+    Blob(DomRoot<Blob>),
+}
+
+impl RepresentationData {
+    #[allow(unsafe_code)]
+    pub fn from_value(cx: JSContext, value: HandleValue) -> Option<RepresentationData> {
+        if value.is_string() {
+            let text = unsafe { jsstring_to_str(*cx, value.to_string()) };
+            return Some(RepresentationData::Text(text));
+        }
+        root_from_handlevalue::<Blob>(value, *cx)
+            .ok()
+            .map(RepresentationData::Blob)
+    }
+
+    pub fn bytes(&self) -> Option<Vec<u8>> {
+        match self {
+            RepresentationData::Text(text) => Some(text.to_string().into_bytes()),
+            RepresentationData::Blob(blob) => blob.get_bytes().ok(),
+        }
+    }
+}
+
+/// <https://w3c.github.io/clipboard-apis/#clipboard-item-interface>
+#[dom_struct]
+pub struct ClipboardItem {
+    reflector_: Reflector,
+    representations: Vec<Representation>,
+    presentation_style: PresentationStyle,
+}
+
+impl ClipboardItem {
+    fn new_inherited(
+        representations: Vec<Representation>,
+        presentation_style: PresentationStyle,
+    ) -> ClipboardItem {
+        ClipboardItem {
+            reflector_: Reflector::new(),
+            representations,
+            presentation_style,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        representations: Vec<Representation>,
+        presentation_style: PresentationStyle,
+    ) -> DomRoot<ClipboardItem> {
+        reflect_dom_object_with_proto(
+            Box::new(ClipboardItem::new_inherited(
+                representations,
+                presentation_style,
+            )),
+            window,
+            proto,
+        )
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-clipboarditem>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        items: Record<DOMString, JSVal>,
+        options: &ClipboardItemOptions,
+    ) -> Fallible<DomRoot<ClipboardItem>> {
+        // Step 1.
+        if items.is_empty() {
+            return Err(Error::Type("A ClipboardItem needs some data".to_owned()));
+        }
+
+        // Step 3 - 4.
+        let cx = GlobalScope::get_cx();
+        let mut representations = Vec::with_capacity(items.len());
+        for (mime_type, value) in items.iter() {
+            rooted!(in(*cx) let value = *value);
+            let data = Promise::new_resolved(window.upcast(), cx, value.handle())?;
+            representations.push(Representation {
+                mime_type: mime_type.clone(),
+                data,
+            });
+        }
+
+        Ok(ClipboardItem::new(
+            window,
+            proto,
+            representations,
+            options.presentationStyle,
+        ))
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-supports>
+    #[allow(non_snake_case)]
+    pub fn Supports(_window: &Window, type_: DOMString) -> bool {
+        SUPPORTED_TYPES.contains(&&*type_)
+    }
+
+    pub fn representations(&self) -> &[Representation] {
+        &self.representations
+    }
+}
+
+impl ClipboardItemMethods for ClipboardItem {
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-presentationstyle>
+    fn PresentationStyle(&self) -> PresentationStyle {
+        self.presentation_style
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-types>
+    fn Types(&self, cx: JSContext) -> JSVal {
+        let types: Vec<DOMString> = self
+            .representations
+            .iter()
+            .map(|representation| representation.mime_type.clone())
+            .collect();
+        to_frozen_array(types.as_slice(), cx)
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>
+    fn GetType(&self, type_: DOMString) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Step 6.
+        let representation = match self
+            .representations
+            .iter()
+            .find(|representation| representation.mime_type == type_)
+        {
+            Some(representation) => representation,
+            None => {
+                // Step 7.
+                promise.reject_error(Error::NotFound);
+                return promise;
+            },
+        };
+
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(GetTypeHandler {
+                promise: promise.clone(),
+                mime_type: type_.to_string(),
+            })),
+            Some(Box::new(GetTypeRejectionHandler {
+                promise: promise.clone(),
+            })),
+        );
+        let realm = enter_realm(&*global);
+        let comp = InRealm::Entered(&realm);
+        representation.data.append_native_handler(&handler, comp);
+        promise
+    }
+}
+
+/// Resolves the promise returned by `getType()` with a blob of the data of the
+/// representation, once it is known.
+#[derive(JSTraceable, MallocSizeOf)]
+struct GetTypeHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    mime_type: String,
+}
+
+impl Callback for GetTypeHandler {
+    /// Step 6.3 of <https://w3c.github.io/clipboard-apis/#dom-clipboarditem-gettype>
+    fn callback(&self, cx: JSContext, v: HandleValue, _realm: InRealm) {
+        match RepresentationData::from_value(cx, v) {
+            Some(RepresentationData::Text(text)) => {
+                let blob = Blob::new(
+                    &self.promise.global(),
+                    BlobImpl::new_from_bytes(text.to_string().into_bytes(), self.mime_type.clone()),
+                );
+                self.promise.resolve_native(&blob);
+            },
+            Some(RepresentationData::Blob(blob)) => self.promise.resolve_native(&blob),
+            None => self.promise.reject_error(Error::Type(
+                "The data of a ClipboardItem must be a string or a Blob".to_owned(),
+            )),
+        }
+    }
+}
+
+#[derive(JSTraceable, MallocSizeOf)]
+struct GetTypeRejectionHandler {
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+}
+
+impl Callback for GetTypeRejectionHandler {
+    fn callback(&self, _cx: JSContext, _v: HandleValue, _realm: InRealm) {
+        self.promise.reject_error(Error::NotFound);
+    }
+}
@@ -11,9 +11,10 @@ use servo_rand::{RngCore, ServoRng};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::CryptoBinding::CryptoMethods;
 use crate::dom::bindings::error::{Error, Fallible};
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::subtlecrypto::SubtleCrypto;
 use crate::script_runtime::JSContext;
 
 // https://developer.mozilla.org/en-US/docs/Web/API/Crypto
@@ -23,6 +24,7 @@ pub struct Crypto {
     #[ignore_malloc_size_of = "Defined in rand"]
     #[no_trace]
     rng: DomRefCell<ServoRng>,
+    subtle: MutNullableDom<SubtleCrypto>,
 }
 
 impl Crypto {
@@ -30,6 +32,7 @@ impl Crypto {
         Crypto {
             reflector_: Reflector::new(),
             rng: DomRefCell::new(ServoRng::new()),
+            subtle: MutNullableDom::default(),
         }
     }
 
@@ -61,6 +64,11 @@ impl CryptoMethods for Crypto {
                 .map_err(|_| Error::JSFailed)
         }
     }
+
+    // https://w3c.github.io/webcrypto/#dom-crypto-subtle
+    fn Subtle(&self) -> DomRoot<SubtleCrypto> {
+        self.subtle.or_init(|| SubtleCrypto::new(&self.global()))
+    }
 }
 
 fn is_integer_buffer(array_type: Type) -> bool {
@@ -0,0 +1,228 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::ptr::NonNull;
+
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{Heap, JSObject};
+use js::jsval::UndefinedValue;
+
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{
+    CryptoKeyMethods, KeyType, KeyUsage,
+};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    AesKeyAlgorithm, EcKeyAlgorithm, KeyAlgorithm,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::script_runtime::JSContext;
+
+/// The elliptic curves that ECDSA and ECDH keys can be on.
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq)]
+pub enum NamedCurve {
+    P256,
+    P384,
+}
+
+impl NamedCurve {
+    pub fn from_name(name: &str) -> Option<NamedCurve> {
+        match name {
+            "P-256" => Some(NamedCurve::P256),
+            "P-384" => Some(NamedCurve::P384),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamedCurve::P256 => "P-256",
+            NamedCurve::P384 => "P-384",
+        }
+    }
+}
+
+/// The algorithm that a key is for, with the parameters it was created with.
+#[derive(Clone, Copy, Debug, MallocSizeOf, PartialEq)]
+pub enum CryptoKeyAlgorithm {
+    Ecdsa(NamedCurve),
+    Ecdh(NamedCurve),
+    /// The length of the key in bits.
+    AesKw(u16),
+    Ed25519,
+    X25519,
+}
+
+impl CryptoKeyAlgorithm {
+    /// The name under which the algorithm is registered.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CryptoKeyAlgorithm::Ecdsa(_) => "ECDSA",
+            CryptoKeyAlgorithm::Ecdh(_) => "ECDH",
+            CryptoKeyAlgorithm::AesKw(_) => "AES-KW",
+            CryptoKeyAlgorithm::Ed25519 => "Ed25519",
+            CryptoKeyAlgorithm::X25519 => "X25519",
+        }
+    }
+
+    #[allow(unsafe_code)]
+    fn to_object(&self, cx: JSContext) -> *mut JSObject {
+        let key_algorithm = KeyAlgorithm {
+            name: DOMString::from(self.name()),
+        };
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        unsafe {
+            match *self {
+                CryptoKeyAlgorithm::Ecdsa(curve) | CryptoKeyAlgorithm::Ecdh(curve) => {
+                    EcKeyAlgorithm {
+                        parent: key_algorithm,
+                        namedCurve: DOMString::from(curve.name()),
+                    }
+                    .to_jsval(*cx, value.handle_mut())
+                },
+                CryptoKeyAlgorithm::AesKw(length) => AesKeyAlgorithm {
+                    parent: key_algorithm,
+                    length,
+                }
+                .to_jsval(*cx, value.handle_mut()),
+                CryptoKeyAlgorithm::Ed25519 | CryptoKeyAlgorithm::X25519 => {
+                    key_algorithm.to_jsval(*cx, value.handle_mut())
+                },
+            }
+        }
+        value.to_object()
+    }
+}
+
+/// The key material of a [CryptoKey].
+pub enum Handle {
+    Aes(Vec<u8>),
+    P256Private(p256::SecretKey),
+    P256Public(p256::PublicKey),
+    P384Private(p384::SecretKey),
+    P384Public(p384::PublicKey),
+    Ed25519Private(ed25519_dalek::SigningKey),
+    Ed25519Public(ed25519_dalek::VerifyingKey),
+    X25519Private(x25519_dalek::StaticSecret),
+    X25519Public(x25519_dalek::PublicKey),
+}
+
+/// <https://w3c.github.io/webcrypto/#cryptokey-interface>
+#[dom_struct]
+pub struct CryptoKey {
+    reflector_: Reflector,
+    /// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-type>
+    key_type: KeyType,
+    /// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-extractable>
+    extractable: bool,
+    /// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-algorithm>
+    #[no_trace]
+    algorithm: CryptoKeyAlgorithm,
+    /// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-usages>
+    usages: Vec<KeyUsage>,
+    /// <https://w3c.github.io/webcrypto/#dfn-CryptoKey-slot-handle>
+    #[ignore_malloc_size_of = "Defined in the crypto crates"]
+    #[no_trace]
+    handle: Handle,
+    /// The object returned by the `algorithm` attribute, created when first asked for.
+    #[ignore_malloc_size_of = "Defined in mozjs"]
+    algorithm_object: Heap<*mut JSObject>,
+    /// The object returned by the `usages` attribute, created when first asked for.
+    #[ignore_malloc_size_of = "Defined in mozjs"]
+    usages_object: Heap<*mut JSObject>,
+}
+
+impl CryptoKey {
+    fn new_inherited(
+        key_type: KeyType,
+        extractable: bool,
+        algorithm: CryptoKeyAlgorithm,
+        usages: Vec<KeyUsage>,
+        handle: Handle,
+    ) -> CryptoKey {
+        CryptoKey {
+            reflector_: Reflector::new(),
+            key_type,
+            extractable,
+            algorithm,
+            usages,
+            handle,
+            algorithm_object: Heap::default(),
+            usages_object: Heap::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        key_type: KeyType,
+        extractable: bool,
+        algorithm: CryptoKeyAlgorithm,
+        usages: Vec<KeyUsage>,
+        handle: Handle,
+    ) -> DomRoot<CryptoKey> {
+        reflect_dom_object(
+            Box::new(CryptoKey::new_inherited(
+                key_type,
+                extractable,
+                algorithm,
+                usages,
+                handle,
+            )),
+            global,
+        )
+    }
+
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    pub fn extractable(&self) -> bool {
+        self.extractable
+    }
+
+    pub fn algorithm(&self) -> CryptoKeyAlgorithm {
+        self.algorithm
+    }
+
+    pub fn usages(&self) -> &[KeyUsage] {
+        &self.usages
+    }
+
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+}
+
+impl CryptoKeyMethods for CryptoKey {
+    /// <https://w3c.github.io/webcrypto/#dom-cryptokey-type>
+    fn Type(&self) -> KeyType {
+        self.key_type
+    }
+
+    /// <https://w3c.github.io/webcrypto/#dom-cryptokey-extractable>
+    fn Extractable(&self) -> bool {
+        self.extractable
+    }
+
+    /// <https://w3c.github.io/webcrypto/#dom-cryptokey-algorithm>
+    fn Algorithm(&self, cx: JSContext) -> NonNull<JSObject> {
+        if self.algorithm_object.get().is_null() {
+            self.algorithm_object.set(self.algorithm.to_object(cx));
+        }
+        NonNull::new(self.algorithm_object.get()).unwrap()
+    }
+
+    /// <https://w3c.github.io/webcrypto/#dom-cryptokey-usages>
+    #[allow(unsafe_code)]
+    fn Usages(&self, cx: JSContext) -> NonNull<JSObject> {
+        if self.usages_object.get().is_null() {
+            rooted!(in(*cx) let mut value = UndefinedValue());
+            unsafe { self.usages.to_jsval(*cx, value.handle_mut()) };
+            self.usages_object.set(value.to_object());
+        }
+        NonNull::new(self.usages_object.get()).unwrap()
+    }
+}
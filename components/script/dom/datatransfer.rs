@@ -0,0 +1,225 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DataTransferBinding::DataTransferMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::datatransferitemlist::DataTransferItemList;
+use crate::dom::element::Element;
+use crate::dom::file::File;
+use crate::dom::filelist::FileList;
+use crate::dom::window::Window;
+use crate::drag_and_drop::{DragDataStore, DragDataStoreMode, DropEffect};
+use crate::script_runtime::JSContext;
+
+/// The values of `effectAllowed`.
+/// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed>
+const ALLOWED_EFFECTS: [&str; 9] = [
+    "none",
+    "copy",
+    "copyLink",
+    "copyMove",
+    "link",
+    "linkMove",
+    "move",
+    "all",
+    "uninitialized",
+];
+
+/// <https://html.spec.whatwg.org/multipage/#the-datatransfer-interface>
+#[dom_struct]
+pub struct DataTransfer {
+    reflector_: Reflector,
+    drop_effect: Cell<DropEffect>,
+    effect_allowed: DomRefCell<DOMString>,
+    items: MutNullableDom<DataTransferItemList>,
+    /// The files of the data, which are kept until items are added or removed.
+    files: MutNullableDom<FileList>,
+    /// The data that the object gives access to, which is taken back once the event it was
+    /// created for has been dispatched, leaving the object in the disabled mode.
+    data_store: DomRefCell<Option<DragDataStore>>,
+}
+
+impl DataTransfer {
+    fn new_inherited(data_store: DragDataStore) -> DataTransfer {
+        DataTransfer {
+            reflector_: Reflector::new(),
+            drop_effect: Cell::new(DropEffect::None),
+            effect_allowed: DomRefCell::new(DOMString::from(data_store.allowed_effects())),
+            items: Default::default(),
+            files: Default::default(),
+            data_store: DomRefCell::new(Some(data_store)),
+        }
+    }
+
+    pub(crate) fn new(window: &Window, data_store: DragDataStore) -> DomRoot<DataTransfer> {
+        Self::new_with_proto(window, None, data_store)
+    }
+
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        data_store: DragDataStore,
+    ) -> DomRoot<DataTransfer> {
+        reflect_dom_object_with_proto(
+            Box::new(DataTransfer::new_inherited(data_store)),
+            window,
+            proto,
+        )
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer>
+    #[allow(non_snake_case)]
+    pub fn Constructor(window: &Window, proto: Option<HandleObject>) -> DomRoot<DataTransfer> {
+        let mut data_store = DragDataStore::new(vec![], DragDataStoreMode::ReadWrite);
+        data_store.set_allowed_effects("none".to_owned());
+        Self::new_with_proto(window, proto, data_store)
+    }
+
+    /// Take the data back once the event this object was created for has been dispatched.
+    pub(crate) fn take_data_store(&self) -> Option<DragDataStore> {
+        self.data_store.borrow_mut().take()
+    }
+
+    pub(crate) fn drop_effect(&self) -> DropEffect {
+        self.drop_effect.get()
+    }
+
+    pub(crate) fn set_drop_effect(&self, drop_effect: DropEffect) {
+        self.drop_effect.set(drop_effect);
+    }
+
+    /// The mode of the data, which is `None` in the disabled mode.
+    pub(crate) fn mode(&self) -> Option<DragDataStoreMode> {
+        self.data_store.borrow().as_ref().map(DragDataStore::mode)
+    }
+
+    pub(crate) fn with_data_store<R>(&self, f: impl FnOnce(&DragDataStore) -> R) -> Option<R> {
+        self.data_store.borrow().as_ref().map(f)
+    }
+
+    /// Change the data, which is only possible in the read/write mode.
+    pub(crate) fn with_writable_data_store<R>(
+        &self,
+        f: impl FnOnce(&mut DragDataStore) -> R,
+    ) -> Option<R> {
+        let mut data_store = self.data_store.borrow_mut();
+        let result = data_store
+            .as_mut()
+            .filter(|data_store| data_store.mode() == DragDataStoreMode::ReadWrite)
+            .map(f);
+        if result.is_some() {
+            self.files.set(None);
+        }
+        result
+    }
+}
+
+impl DataTransferMethods for DataTransfer {
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-dropeffect>
+    fn DropEffect(&self) -> DOMString {
+        DOMString::from(self.drop_effect.get().as_str())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-dropeffect>
+    fn SetDropEffect(&self, value: DOMString) {
+        if let Some(drop_effect) = DropEffect::parse(&value) {
+            self.drop_effect.set(drop_effect);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed>
+    fn EffectAllowed(&self) -> DOMString {
+        self.effect_allowed.borrow().clone()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-effectallowed>
+    fn SetEffectAllowed(&self, value: DOMString) {
+        if !ALLOWED_EFFECTS.contains(&&*value) {
+            return;
+        }
+        let value = String::from(value);
+        if self
+            .with_writable_data_store(|data_store| data_store.set_allowed_effects(value.clone()))
+            .is_some()
+        {
+            *self.effect_allowed.borrow_mut() = DOMString::from(value);
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-items>
+    fn Items(&self) -> DomRoot<DataTransferItemList> {
+        self.items
+            .or_init(|| DataTransferItemList::new(self.global().as_window(), self))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-setdragimage>
+    fn SetDragImage(&self, _image: &Element, _x: i32, _y: i32) {
+        // TODO: Drags are not drawn under the pointer, so there is nothing to draw the image
+        // in.
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-types>
+    fn Types(&self, cx: JSContext) -> JSVal {
+        let types: Vec<DOMString> = self
+            .with_data_store(DragDataStore::types)
+            .unwrap_or_default()
+            .into_iter()
+            .map(DOMString::from)
+            .collect();
+        to_frozen_array(types.as_slice(), cx)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-getdata>
+    fn GetData(&self, format: DOMString) -> DOMString {
+        self.with_data_store(|data_store| {
+            if data_store.mode() == DragDataStoreMode::Protected {
+                return None;
+            }
+            data_store.get_data(&format)
+        })
+        .flatten()
+        .map(DOMString::from)
+        .unwrap_or_default()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-setdata>
+    fn SetData(&self, format: DOMString, data: DOMString) {
+        self.with_writable_data_store(|data_store| data_store.set_data(&format, data.into()));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-cleardata>
+    fn ClearData(&self, format: Option<DOMString>) {
+        self.with_writable_data_store(|data_store| data_store.clear_data(format.as_deref()));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-files>
+    fn Files(&self) -> DomRoot<FileList> {
+        let window = self.global();
+        let window = window.as_window();
+        self.files.or_init(|| {
+            let files = self
+                .with_data_store(|data_store| {
+                    if data_store.mode() == DragDataStoreMode::Protected {
+                        return vec![];
+                    }
+                    data_store.items().cloned().collect()
+                })
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|item| File::new_from_drag_data_item(window, item))
+                .collect();
+            FileList::new(window, files)
+        })
+    }
+}
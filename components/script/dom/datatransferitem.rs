@@ -0,0 +1,138 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::DragDataItem;
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DataTransferItemBinding::{
+    DataTransferItemMethods, FunctionStringCallback,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransfer::DataTransfer;
+use crate::dom::file::File;
+use crate::dom::window::Window;
+use crate::drag_and_drop::DragDataStoreMode;
+
+/// <https://html.spec.whatwg.org/multipage/#the-datatransferitem-interface>
+#[dom_struct]
+pub struct DataTransferItem {
+    reflector_: Reflector,
+    data_transfer: Dom<DataTransfer>,
+    /// The id of the item of the data that this object represents.
+    id: u32,
+    /// The callbacks given to `getAsString`, with the string to call them with, in the order
+    /// their tasks run.
+    #[ignore_malloc_size_of = "Rc"]
+    pending_callbacks: DomRefCell<VecDeque<(Rc<FunctionStringCallback>, DOMString)>>,
+}
+
+impl DataTransferItem {
+    fn new_inherited(data_transfer: &DataTransfer, id: u32) -> DataTransferItem {
+        DataTransferItem {
+            reflector_: Reflector::new(),
+            data_transfer: Dom::from_ref(data_transfer),
+            id,
+            pending_callbacks: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        data_transfer: &DataTransfer,
+        id: u32,
+    ) -> DomRoot<DataTransferItem> {
+        reflect_dom_object(
+            Box::new(DataTransferItem::new_inherited(data_transfer, id)),
+            window,
+        )
+    }
+
+    /// The item of the data that this object represents, if the data is readable and the item
+    /// has not been removed.
+    fn readable_item(&self) -> Option<DragDataItem> {
+        self.data_transfer
+            .with_data_store(|data_store| {
+                if data_store.mode() == DragDataStoreMode::Protected {
+                    return None;
+                }
+                data_store.item(self.id).cloned()
+            })
+            .flatten()
+    }
+}
+
+impl DataTransferItemMethods for DataTransferItem {
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitem-kind>
+    fn Kind(&self) -> DOMString {
+        let kind = self
+            .data_transfer
+            .with_data_store(|data_store| match data_store.item(self.id) {
+                Some(DragDataItem::Text { .. }) => "string",
+                Some(DragDataItem::File { .. }) => "file",
+                None => "",
+            })
+            .unwrap_or("");
+        DOMString::from(kind)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitem-type>
+    fn Type(&self) -> DOMString {
+        self.data_transfer
+            .with_data_store(|data_store| match data_store.item(self.id) {
+                Some(DragDataItem::Text { type_, .. }) | Some(DragDataItem::File { type_, .. }) => {
+                    DOMString::from(type_.clone())
+                },
+                None => DOMString::new(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitem-getasstring>
+    fn GetAsString(&self, callback: Option<Rc<FunctionStringCallback>>) {
+        // Step 1. If the callback is null, return.
+        let callback = match callback {
+            Some(callback) => callback,
+            None => return,
+        };
+
+        // Step 2-4. If the data is not readable or the item is not a string, return.
+        let data = match self.readable_item() {
+            Some(DragDataItem::Text { data, .. }) => DOMString::from(data),
+            _ => return,
+        };
+
+        // Step 5. Otherwise, queue a task to invoke the callback with the string.
+        self.pending_callbacks
+            .borrow_mut()
+            .push_back((callback, data));
+        let this = Trusted::new(self);
+        let global = self.global();
+        let window = global.as_window();
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(data_transfer_item_get_as_string: move || {
+                let this = this.root();
+                let pending = this.pending_callbacks.borrow_mut().pop_front();
+                if let Some((callback, data)) = pending {
+                    let _ = callback.Call__(data, ExceptionHandling::Report);
+                }
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitem-getasfile>
+    fn GetAsFile(&self) -> Option<DomRoot<File>> {
+        let item = self.readable_item()?;
+        File::new_from_drag_data_item(self.global().as_window(), item)
+    }
+}
@@ -0,0 +1,143 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::collections::HashMap;
+
+use dom_struct::dom_struct;
+use embedder_traits::DragDataItem;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DataTransferItemListBinding::DataTransferItemListMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::Blob;
+use crate::dom::datatransfer::DataTransfer;
+use crate::dom::datatransferitem::DataTransferItem;
+use crate::dom::file::File;
+use crate::dom::window::Window;
+use crate::drag_and_drop::DragDataStoreMode;
+
+/// <https://html.spec.whatwg.org/multipage/#the-datatransferitemlist-interface>
+#[dom_struct]
+pub struct DataTransferItemList {
+    reflector_: Reflector,
+    data_transfer: Dom<DataTransfer>,
+    /// The objects handed out for the items of the data, by item id, so that getting an item
+    /// twice gives the same object.
+    items: DomRefCell<HashMap<u32, Dom<DataTransferItem>>>,
+}
+
+impl DataTransferItemList {
+    fn new_inherited(data_transfer: &DataTransfer) -> DataTransferItemList {
+        DataTransferItemList {
+            reflector_: Reflector::new(),
+            data_transfer: Dom::from_ref(data_transfer),
+            items: Default::default(),
+        }
+    }
+
+    pub(crate) fn new(
+        window: &Window,
+        data_transfer: &DataTransfer,
+    ) -> DomRoot<DataTransferItemList> {
+        reflect_dom_object(
+            Box::new(DataTransferItemList::new_inherited(data_transfer)),
+            window,
+        )
+    }
+
+    fn item_for_id(&self, id: u32) -> DomRoot<DataTransferItem> {
+        let mut items = self.items.borrow_mut();
+        let item = items.entry(id).or_insert_with(|| {
+            Dom::from_ref(&*DataTransferItem::new(
+                self.global().as_window(),
+                &self.data_transfer,
+                id,
+            ))
+        });
+        DomRoot::from_ref(&**item)
+    }
+
+    /// Add an item to the data, returning its object, or `None` if the data is not in the
+    /// read/write mode.
+    fn add_item(&self, item: DragDataItem) -> Fallible<Option<DomRoot<DataTransferItem>>> {
+        match self
+            .data_transfer
+            .with_writable_data_store(|data_store| data_store.add(item))
+        {
+            None => Ok(None),
+            Some(None) => Err(Error::NotSupported),
+            Some(Some(id)) => Ok(Some(self.item_for_id(id))),
+        }
+    }
+}
+
+impl DataTransferItemListMethods for DataTransferItemList {
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-length>
+    fn Length(&self) -> u32 {
+        self.data_transfer
+            .with_data_store(|data_store| data_store.len() as u32)
+            .unwrap_or(0)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-item>
+    fn IndexedGetter(&self, index: u32) -> Option<DomRoot<DataTransferItem>> {
+        let id = self
+            .data_transfer
+            .with_data_store(|data_store| data_store.id_at(index as usize))??;
+        Some(self.item_for_id(id))
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-add>
+    fn Add(
+        &self,
+        data: DOMString,
+        type_: DOMString,
+    ) -> Fallible<Option<DomRoot<DataTransferItem>>> {
+        self.add_item(DragDataItem::Text {
+            type_: type_.to_ascii_lowercase(),
+            data: data.into(),
+        })
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-add>
+    fn Add_(&self, data: &File) -> Fallible<Option<DomRoot<DataTransferItem>>> {
+        let blob = data.upcast::<Blob>();
+        let bytes = blob.get_bytes().map_err(|_| Error::NotReadable)?;
+        self.add_item(DragDataItem::File {
+            name: data.name().to_string(),
+            type_: blob.type_string().to_ascii_lowercase(),
+            bytes,
+        })
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-remove>
+    fn Remove(&self, index: u32) -> ErrorResult {
+        if self.data_transfer.mode() != Some(DragDataStoreMode::ReadWrite) {
+            return Err(Error::InvalidState);
+        }
+        let removed = self
+            .data_transfer
+            .with_writable_data_store(|data_store| data_store.remove(index as usize))
+            .flatten();
+        if let Some(id) = removed {
+            self.items.borrow_mut().remove(&id);
+        }
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-clear>
+    fn Clear(&self) {
+        if self
+            .data_transfer
+            .with_writable_data_store(|data_store| data_store.clear())
+            .is_some()
+        {
+            self.items.borrow_mut().clear();
+        }
+    }
+}
@@ -0,0 +1,100 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::ptr::NonNull;
+
+use dom_struct::dom_struct;
+use js::jsapi::{ClippedTime, JSObject, NewDateObject};
+
+use crate::dom::bindings::codegen::Bindings::DeprecationReportBodyBinding::DeprecationReportBodyMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::report::SourceLocation;
+use crate::dom::reportbody::ReportBody;
+use crate::script_runtime::JSContext;
+
+/// <https://wicg.github.io/deprecation-reporting/#deprecationreportbody>
+#[dom_struct]
+pub struct DeprecationReportBody {
+    report_body: ReportBody,
+    id: DOMString,
+    /// When the feature is expected to be removed, in milliseconds since the epoch.
+    anticipated_removal: Option<f64>,
+    message: DOMString,
+    location: Option<SourceLocation>,
+}
+
+impl DeprecationReportBody {
+    fn new_inherited(
+        id: DOMString,
+        anticipated_removal: Option<f64>,
+        message: DOMString,
+        location: Option<SourceLocation>,
+    ) -> DeprecationReportBody {
+        DeprecationReportBody {
+            report_body: ReportBody::new_inherited(),
+            id,
+            anticipated_removal,
+            message,
+            location,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        id: DOMString,
+        anticipated_removal: Option<f64>,
+        message: DOMString,
+        location: Option<SourceLocation>,
+    ) -> DomRoot<DeprecationReportBody> {
+        reflect_dom_object(
+            Box::new(DeprecationReportBody::new_inherited(
+                id,
+                anticipated_removal,
+                message,
+                location,
+            )),
+            global,
+        )
+    }
+}
+
+impl DeprecationReportBodyMethods for DeprecationReportBody {
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-id>
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-anticipatedremoval>
+    #[allow(unsafe_code)]
+    fn GetAnticipatedRemoval(&self, cx: JSContext) -> Option<NonNull<JSObject>> {
+        self.anticipated_removal.map(|time| unsafe {
+            NonNull::new_unchecked(NewDateObject(*cx, ClippedTime { t: time }))
+        })
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-message>
+    fn Message(&self) -> DOMString {
+        self.message.clone()
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-sourcefile>
+    fn GetSourceFile(&self) -> Option<DOMString> {
+        self.location
+            .as_ref()
+            .map(|location| DOMString::from(location.file.clone()))
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-linenumber>
+    fn GetLineNumber(&self) -> Option<u32> {
+        self.location.as_ref().map(|location| location.line)
+    }
+
+    /// <https://wicg.github.io/deprecation-reporting/#dom-deprecationreportbody-columnnumber>
+    fn GetColumnNumber(&self) -> Option<u32> {
+        self.location.as_ref().map(|location| location.column)
+    }
+}
@@ -14,20 +14,22 @@ use std::rc::Rc;
 use std::slice::from_ref;
 use std::time::{Duration, Instant};
 
+use app_units::Au;
 use canvas_traits::webgl::{self, WebGLContextId, WebGLMsg};
 use content_security_policy::{self as csp, CspList};
 use cookie::Cookie;
 use cssparser::match_ignore_ascii_case;
 use devtools_traits::ScriptToDevtoolsControlMsg;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, ScriptPolicy};
 use encoding_rs::{Encoding, UTF_8};
 use euclid::default::{Point2D, Rect, Size2D};
 use html5ever::{local_name, namespace_url, ns, LocalName, Namespace, QualName};
 use hyper_serde::Serde;
 use ipc_channel::ipc::{self, IpcSender};
 use js::jsapi::JSObject;
-use js::rust::HandleObject;
+use js::jsval::UndefinedValue;
+use js::rust::{HandleObject, HandleValue};
 use keyboard_types::{Code, Key, KeyState};
 use lazy_static::lazy_static;
 use metrics::{
@@ -35,7 +37,7 @@ use metrics::{
     ProgressiveWebMetric,
 };
 use mime::{self, Mime};
-use msg::constellation_msg::BrowsingContextId;
+use msg::constellation_msg::{BrowsingContextId, InputMethodType};
 use net_traits::pub_domains::is_pub_domain;
 use net_traits::request::RequestBuilder;
 use net_traits::response::HttpsState;
@@ -48,21 +50,24 @@ use profile_traits::ipc as profile_ipc;
 use profile_traits::time::{TimerMetadata, TimerMetadataFrameType, TimerMetadataReflowType};
 use script_layout_interface::message::{Msg, PendingRestyle, ReflowGoal};
 use script_layout_interface::TrustedNodeAddress;
+use script_traits::content_script::{ContentScript, ContentScriptRunAt};
 use script_traits::{
-    AnimationState, DocumentActivity, MouseButton, MouseEventType, MsDuration, ScriptMsg,
-    TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
+    AnimationState, DocumentActivity, DragEventType, JsWorld, MouseButton, MouseEventType,
+    MsDuration, ScriptMsg, TouchEventType, TouchId, UntrustedNodeAddress, WheelDelta,
 };
 use servo_arc::Arc;
 use servo_atoms::Atom;
 use servo_config::pref;
 use servo_media::{ClientContextId, ServoMedia};
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
+use style::animation::AnimationSetKey;
 use style::attr::AttrValue;
 use style::context::QuirksMode;
 use style::invalidation::element::restyle_hints::RestyleHint;
 use style::media_queries::{Device, MediaType};
+use style::properties::PropertyDeclarationBlock;
 use style::selector_parser::Snapshot;
-use style::shared_lock::SharedRwLock as StyleSharedRwLock;
+use style::shared_lock::{Locked, SharedRwLock as StyleSharedRwLock};
 use style::str::{split_html_space_chars, str_join};
 use style::stylesheet_set::DocumentStylesheetSet;
 use style::stylesheets::{Origin, OriginSet, Stylesheet};
@@ -74,6 +79,7 @@ use super::bindings::trace::{HashMapTracedValues, NoTrace};
 use crate::animation_timeline::AnimationTimeline;
 use crate::animations::Animations;
 use crate::document_loader::{DocumentLoader, LoadType};
+use crate::dom::animation::Animation as WebAnimation;
 use crate::dom::attr::Attr;
 use crate::dom::beforeunloadevent::BeforeUnloadEvent;
 use crate::dom::bindings::callback::ExceptionHandling;
@@ -96,7 +102,7 @@ use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     FrameRequestCallback, ScrollBehavior, WindowMethods,
 };
 use crate::dom::bindings::codegen::UnionTypes::{NodeOrString, StringOrElementCreationOptions};
-use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::error::{Error, ErrorInfo, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
@@ -108,6 +114,7 @@ use crate::dom::bindings::xmlname::{
     namespace_from_domstring, validate_and_extract, xml_name_type,
 };
 use crate::dom::cdatasection::CDATASection;
+use crate::dom::characterdata::CharacterData;
 use crate::dom::comment::Comment;
 use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::cssstylesheet::CSSStyleSheet;
@@ -115,6 +122,7 @@ use crate::dom::customelementregistry::CustomElementDefinition;
 use crate::dom::customevent::CustomEvent;
 use crate::dom::documentfragment::DocumentFragment;
 use crate::dom::documentorshadowroot::{DocumentOrShadowRoot, StyleSheetInDocument};
+use crate::dom::documenttimeline::DocumentTimeline;
 use crate::dom::documenttype::DocumentType;
 use crate::dom::domimplementation::DOMImplementation;
 use crate::dom::element::{
@@ -155,9 +163,13 @@ use crate::dom::node::{
 use crate::dom::nodeiterator::NodeIterator;
 use crate::dom::nodelist::NodeList;
 use crate::dom::pagetransitionevent::PageTransitionEvent;
+use crate::dom::pointerevent::{
+    PointerEvent, PointerProperties, MOUSE_POINTER_ID, TOUCH_POINTER_ID_OFFSET,
+};
 use crate::dom::processinginstruction::ProcessingInstruction;
 use crate::dom::promise::Promise;
 use crate::dom::range::Range;
+use crate::dom::resizeobserver::{ResizeObservationDepth, ResizeObserver};
 use crate::dom::selection::Selection;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::shadowroot::ShadowRoot;
@@ -174,8 +186,11 @@ use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::dom::wheelevent::WheelEvent;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::WindowProxy;
+use crate::drag_and_drop::DragAndDrop;
 use crate::fetch::FetchCanceller;
-use crate::realms::{AlreadyInRealm, InRealm};
+use crate::popups::Popups;
+use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
+use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::{CommonScriptMsg, JSContext, ScriptThreadEventCategory};
 use crate::script_thread::{MainThreadScriptMsg, ScriptThread};
 use crate::stylesheet_set::StylesheetSetRef;
@@ -192,6 +207,11 @@ const SPURIOUS_ANIMATION_FRAME_THRESHOLD: u8 = 5;
 /// The amount of time between fake `requestAnimationFrame()`s.
 const FAKE_REQUEST_ANIMATION_FRAME_DELAY: u64 = 16;
 
+/// How far outside the viewport, in CSS pixels, lazily loaded images and iframes start loading.
+/// This is the scroll margin of the
+/// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>.
+const LAZY_LOAD_SCROLL_MARGIN_PX: i32 = 1250;
+
 pub enum TouchEventResult {
     Processed(bool),
     Forwarded,
@@ -216,6 +236,18 @@ impl FireMouseEventType {
             &FireMouseEventType::Leave => "mouseleave",
         }
     }
+
+    /// The name of the pointer event fired before this mouse event.
+    /// <https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events>
+    pub fn pointer_event_name(&self) -> &str {
+        match self {
+            &FireMouseEventType::Move => "pointermove",
+            &FireMouseEventType::Over => "pointerover",
+            &FireMouseEventType::Out => "pointerout",
+            &FireMouseEventType::Enter => "pointerenter",
+            &FireMouseEventType::Leave => "pointerleave",
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
@@ -340,6 +372,19 @@ pub struct Document {
     needs_paint: Cell<bool>,
     /// <http://w3c.github.io/touch-events/#dfn-active-touch-point>
     active_touch_points: DomRefCell<Vec<Dom<Touch>>>,
+    /// The identifier of the touch point that is the primary pointer of its type.
+    /// <https://w3c.github.io/pointerevents/#the-primary-pointer>
+    primary_touch_point: Cell<Option<i32>>,
+    /// The mouse buttons pressed as of the last mouse event.
+    pressed_mouse_buttons: Cell<u16>,
+    /// Whether a canceled `pointerdown` prevents the compatibility mouse events of the
+    /// mouse until its buttons are released.
+    /// <https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events>
+    prevent_compatibility_mouse_events: Cell<bool>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pointer-capture-target-override>
+    pointer_capture_targets: DomRefCell<HashMap<i32, Dom<Element>>>,
+    /// <https://w3c.github.io/pointerevents/#dfn-pending-pointer-capture-target-override>
+    pending_pointer_capture_targets: DomRefCell<HashMap<i32, Dom<Element>>>,
     /// Navigation Timing properties:
     /// <https://w3c.github.io/navigation-timing/#sec-PerformanceNavigationTiming>
     dom_loading: Cell<u64>,
@@ -402,6 +447,11 @@ pub struct Document {
     throw_on_dynamic_markup_insertion_counter: Cell<u64>,
     /// <https://html.spec.whatwg.org/multipage/#page-showing>
     page_showing: Cell<bool>,
+    /// Whether this document is frozen, its webview being in the background.
+    /// <https://wicg.github.io/page-lifecycle/#sec-lifecycle-states>
+    frozen: Cell<bool>,
+    /// <https://wicg.github.io/page-lifecycle/#dom-document-wasdiscarded>
+    was_discarded: Cell<bool>,
     /// Whether the document is salvageable.
     salvageable: Cell<bool>,
     /// Whether the document was aborted with an active parser
@@ -445,10 +495,26 @@ pub struct Document {
     animation_timeline: DomRefCell<AnimationTimeline>,
     /// Animations for this Document
     animations: DomRefCell<Animations>,
+    /// The tooltip and validation message shown over this Document.
+    popups: Popups,
+    /// The drag and drop operation over this Document, if any.
+    drag_and_drop: DragAndDrop,
+    /// <https://drafts.csswg.org/web-animations-1/#default-document-timeline>
+    timeline: MutNullableDom<DocumentTimeline>,
     /// The nearest inclusive ancestors to all the nodes that require a restyle.
     dirty_root: MutNullableDom<Element>,
     /// <https://html.spec.whatwg.org/multipage/#will-declaratively-refresh>
     declarative_refresh: DomRefCell<Option<DeclarativeRefresh>>,
+    /// The content scripts the embedder asked to inject into this document.
+    #[no_trace]
+    content_scripts: DomRefCell<Vec<ContentScript>>,
+    /// <https://drafts.csswg.org/resize-observer/#dom-document-resizeobservers-slot>
+    resize_observers: DomRefCell<Vec<Dom<ResizeObserver>>>,
+    /// The elements observed by the
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>.
+    lazy_load_elements: DomRefCell<Vec<Dom<Element>>>,
+    /// The elements with `content-visibility: auto` that generated boxes in the last layout.
+    content_visibility_auto_elements: DomRefCell<Vec<Dom<Element>>>,
 }
 
 #[derive(JSTraceable, MallocSizeOf)]
@@ -656,6 +722,61 @@ impl Document {
         self.activity.get() != DocumentActivity::Inactive
     }
 
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.get()
+    }
+
+    pub fn set_was_discarded(&self, was_discarded: bool) {
+        self.was_discarded.set(was_discarded);
+    }
+
+    fn media_client_context_id(&self) -> ClientContextId {
+        let pipeline_id = self.window().pipeline_id();
+        ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get())
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#freeze-steps>
+    pub fn run_freeze_steps(&self) {
+        if self.frozen.get() || !self.is_fully_active() {
+            return;
+        }
+
+        // Step 1.
+        self.upcast::<EventTarget>()
+            .fire_bubbling_event(Atom::from("freeze"));
+
+        // Step 2.
+        self.frozen.set(true);
+
+        // Step 3. The tasks of the document are held back by the script thread while it is
+        // frozen, and its timers and media do not run either.
+        self.window().upcast::<GlobalScope>().suspend();
+        ServoMedia::get()
+            .unwrap()
+            .suspend(&self.media_client_context_id());
+    }
+
+    /// <https://wicg.github.io/page-lifecycle/#resume-steps>
+    pub fn run_resume_steps(&self) {
+        if !self.frozen.get() {
+            return;
+        }
+
+        // Step 1. The timers and media of a document which is not fully active are resumed
+        // if it becomes fully active again.
+        self.frozen.set(false);
+        if self.is_fully_active() {
+            self.window().upcast::<GlobalScope>().resume();
+            ServoMedia::get()
+                .unwrap()
+                .resume(&self.media_client_context_id());
+        }
+
+        // Step 2.
+        self.upcast::<EventTarget>()
+            .fire_bubbling_event(Atom::from("resume"));
+    }
+
     pub fn set_activity(&self, activity: DocumentActivity) {
         // This function should only be called on documents with a browsing context
         assert!(self.has_browsing_context);
@@ -666,9 +787,7 @@ impl Document {
         // Set the document's activity level, reflow if necessary, and suspend or resume timers.
         self.activity.set(activity);
         let media = ServoMedia::get().unwrap();
-        let pipeline_id = self.window().pipeline_id();
-        let client_context_id =
-            ClientContextId::build(pipeline_id.namespace_id.0, pipeline_id.index.0.get());
+        let client_context_id = self.media_client_context_id();
 
         if activity != DocumentActivity::FullyActive {
             self.window().suspend();
@@ -681,7 +800,12 @@ impl Document {
         self.window()
             .reflow(ReflowGoal::Full, ReflowReason::CachedPageNeededReflow);
         self.window().resume();
-        media.resume(&client_context_id);
+        if self.frozen.get() {
+            // The timers and media of a frozen document stay suspended until it is resumed.
+            self.window().upcast::<GlobalScope>().suspend();
+        } else {
+            media.resume(&client_context_id);
+        }
 
         if self.ready_state.get() != DocumentReadyState::Complete {
             return;
@@ -1060,7 +1184,8 @@ impl Document {
 
     /// Return whether scripting is enabled or not
     pub fn is_scripting_enabled(&self) -> bool {
-        self.scripting_enabled
+        self.scripting_enabled &&
+            self.window.upcast::<GlobalScope>().script_policy() != ScriptPolicy::Block
     }
 
     /// Return the element that currently has focus.
@@ -1125,9 +1250,15 @@ impl Document {
             // FIXME: pass appropriate relatedTarget
             self.fire_focus_event(FocusEventType::Blur, node, None);
 
+            // The suggestions of text fields are only shown while they have focus.
+            self.popups.hide_suggestions(self);
+
             // Notify the embedder to hide the input method.
-            if elem.input_method_type().is_some() {
+            if let Some(kind) = elem.input_method_type() {
                 self.send_to_embedder(EmbedderMsg::HideIME);
+                if matches!(kind, InputMethodType::Password) {
+                    self.send_to_embedder(EmbedderMsg::SecureTextInput(false));
+                }
             }
         }
 
@@ -1146,12 +1277,16 @@ impl Document {
 
             // Notify the embedder to display an input method.
             if let Some(kind) = elem.input_method_type() {
+                let secure = matches!(kind, InputMethodType::Password);
                 let rect = elem.upcast::<Node>().bounding_content_box_or_zero();
                 let rect = Rect::new(
                     Point2D::new(rect.origin.x.to_px(), rect.origin.y.to_px()),
                     Size2D::new(rect.size.width.to_px(), rect.size.height.to_px()),
                 );
-                let (text, multiline) = if let Some(input) = elem.downcast::<HTMLInputElement>() {
+                let (text, multiline) = if secure {
+                    // The input method must not learn the password.
+                    (None, false)
+                } else if let Some(input) = elem.downcast::<HTMLInputElement>() {
                     (
                         Some((
                             (&input.Value()).to_string(),
@@ -1176,6 +1311,14 @@ impl Document {
                     multiline,
                     DeviceIntRect::from_untyped(&rect),
                 ));
+                if secure {
+                    self.send_to_embedder(EmbedderMsg::SecureTextInput(true));
+                }
+            }
+
+            // Offer the embedder to fill in the form of the field.
+            if let Some(input) = elem.downcast::<HTMLInputElement>() {
+                input.request_autofill();
             }
         }
     }
@@ -1243,6 +1386,10 @@ impl Document {
         };
         debug!("{}: at {:?}", mouse_event_type_string, client_point);
 
+        if let MouseEventType::MouseDown = mouse_event_type {
+            self.popups.dismiss(self);
+        }
+
         let el = node_address.and_then(|address| {
             let node = node::from_untrusted_node_address(address);
             node.inclusive_ancestors(ShadowIncluding::No)
@@ -1256,6 +1403,33 @@ impl Document {
 
         let node = el.upcast::<Node>();
         debug!("{} on {:?}", mouse_event_type_string, node.debug_str());
+
+        // https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model
+        // Releasing the button that dragged an element drops it instead of firing mouse
+        // events.
+        match mouse_event_type {
+            MouseEventType::MouseDown => {
+                self.drag_and_drop
+                    .handle_mouse_down(self, &el, client_point, button);
+            },
+            MouseEventType::MouseUp => {
+                if self.drag_and_drop.handle_mouse_up(
+                    self,
+                    &el,
+                    client_point,
+                    pressed_mouse_buttons,
+                ) {
+                    self.window
+                        .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+                    return;
+                }
+            },
+            MouseEventType::Click => {
+                if self.drag_and_drop.take_suppressed_click() {
+                    return;
+                }
+            },
+        }
         // Prevent click event if form control element is disabled.
         if let MouseEventType::Click = mouse_event_type {
             if el.click_event_filter_by_disabled_state() {
@@ -1266,6 +1440,64 @@ impl Document {
             self.request_focus(Some(&*el), FocusType::Element);
         }
 
+        // https://w3c.github.io/pointerevents/#the-pointerdown-event
+        // Pressing a button while another one is pressed, or releasing a button while
+        // another one remains pressed, is a `pointermove` rather than a `pointerdown` or a
+        // `pointerup`.
+        let button_flag = button as u16;
+        let pointer_event_name = match mouse_event_type {
+            MouseEventType::Click => None,
+            MouseEventType::MouseDown if pressed_mouse_buttons & !button_flag == 0 => {
+                Some("pointerdown")
+            },
+            MouseEventType::MouseUp if pressed_mouse_buttons == 0 => Some("pointerup"),
+            MouseEventType::MouseDown | MouseEventType::MouseUp => Some("pointermove"),
+        };
+        self.pressed_mouse_buttons.set(pressed_mouse_buttons);
+        let button = match &button {
+            MouseButton::Left => 0i16,
+            MouseButton::Middle => 1i16,
+            MouseButton::Right => 2i16,
+        };
+
+        if let Some(pointer_event_name) = pointer_event_name {
+            let properties = PointerProperties::mouse(pressed_mouse_buttons);
+            self.process_pending_pointer_capture(MOUSE_POINTER_ID, client_point, &properties);
+            let target = match self.pointer_capture_target(MOUSE_POINTER_ID) {
+                Some(capture_target) => DomRoot::upcast::<EventTarget>(capture_target),
+                None => DomRoot::from_ref(node.upcast::<EventTarget>()),
+            };
+            let status = self.fire_pointer_event(
+                pointer_event_name,
+                &target,
+                EventBubbles::Bubbles,
+                EventCancelable::Cancelable,
+                client_point,
+                button,
+                pressed_mouse_buttons,
+                point_in_node,
+                properties.clone(),
+            );
+            if pointer_event_name == "pointerdown" {
+                self.prevent_compatibility_mouse_events
+                    .set(status == EventStatus::Canceled);
+            }
+            let prevent_mouse_event = self.prevent_compatibility_mouse_events.get();
+            if pointer_event_name == "pointerup" {
+                self.prevent_compatibility_mouse_events.set(false);
+                self.release_pointer_capture_implicitly(
+                    MOUSE_POINTER_ID,
+                    client_point,
+                    &properties,
+                );
+            }
+            if prevent_mouse_event {
+                self.window
+                    .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+                return;
+            }
+        }
+
         // https://w3c.github.io/uievents/#event-type-click
         let client_x = client_point.x as i32;
         let client_y = client_point.y as i32;
@@ -1285,11 +1517,7 @@ impl Document {
             false,
             false,
             false,
-            match &button {
-                MouseButton::Left => 0i16,
-                MouseButton::Middle => 1i16,
-                MouseButton::Right => 2i16,
-            },
+            button,
             pressed_mouse_buttons,
             None,
             point_in_node,
@@ -1402,6 +1630,29 @@ impl Document {
         cancelable: EventCancelable,
         pressed_mouse_buttons: u16,
     ) {
+        // https://w3c.github.io/pointerevents/#compatibility-mapping-with-mouse-events
+        // The mouse moves are targeted at the element that captured the mouse, if any.
+        let pointer_target = match event_name {
+            FireMouseEventType::Move => self
+                .pointer_capture_target(MOUSE_POINTER_ID)
+                .map(DomRoot::upcast::<EventTarget>),
+            _ => None,
+        };
+        self.fire_pointer_event(
+            event_name.pointer_event_name(),
+            pointer_target.as_deref().unwrap_or(target),
+            can_bubble,
+            cancelable,
+            client_point,
+            -1i16,
+            pressed_mouse_buttons,
+            None,
+            PointerProperties::mouse(pressed_mouse_buttons),
+        );
+        if event_name == FireMouseEventType::Move && self.prevent_compatibility_mouse_events.get() {
+            return;
+        }
+
         let client_x = client_point.x.to_i32().unwrap_or(0);
         let client_y = client_point.y.to_i32().unwrap_or(0);
 
@@ -1449,6 +1700,23 @@ impl Document {
             None => return,
         };
 
+        self.pressed_mouse_buttons.set(pressed_mouse_buttons);
+        self.process_pending_pointer_capture(
+            MOUSE_POINTER_ID,
+            client_point,
+            &PointerProperties::mouse(pressed_mouse_buttons),
+        );
+        if self.drag_and_drop.handle_mouse_move(
+            self,
+            new_target,
+            client_point,
+            pressed_mouse_buttons,
+        ) {
+            return;
+        }
+        self.popups
+            .handle_mouse_move(self, new_target, client_point);
+
         let target_has_changed = prev_mouse_over_target
             .get()
             .as_ref()
@@ -1610,6 +1878,7 @@ impl Document {
     ) {
         let wheel_event_type_string = "wheel".to_owned();
         debug!("{}: at {:?}", wheel_event_type_string, client_point);
+        self.popups.dismiss(self);
 
         let el = node_address.and_then(|address| {
             let node = node::from_untrusted_node_address(address);
@@ -1647,6 +1916,27 @@ impl Document {
         event.fire(target);
     }
 
+    /// Handle the embedder dragging data over this document.
+    #[allow(unsafe_code)]
+    pub unsafe fn handle_drag_event(
+        &self,
+        event_type: DragEventType,
+        client_point: Point2D<f32>,
+        node_address: Option<UntrustedNodeAddress>,
+    ) {
+        debug!("drag event {:?}: at {:?}", event_type, client_point);
+        let el = node_address.and_then(|address| {
+            let node = node::from_untrusted_node_address(address);
+            node.inclusive_ancestors(ShadowIncluding::No)
+                .filter_map(DomRoot::downcast::<Element>)
+                .next()
+        });
+        self.drag_and_drop
+            .handle_external_drag(self, event_type, el.as_deref(), client_point);
+        self.window
+            .reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
+    }
+
     #[allow(unsafe_code)]
     pub unsafe fn handle_touch_event(
         &self,
@@ -1692,9 +1982,11 @@ impl Document {
         match event_type {
             TouchEventType::Down => {
                 // Add a new touch point
-                self.active_touch_points
-                    .borrow_mut()
-                    .push(Dom::from_ref(&*touch));
+                let mut active_touch_points = self.active_touch_points.borrow_mut();
+                if active_touch_points.is_empty() {
+                    self.primary_touch_point.set(Some(identifier));
+                }
+                active_touch_points.push(Dom::from_ref(&*touch));
             },
             TouchEventType::Move => {
                 // Replace an existing touch point
@@ -1722,6 +2014,8 @@ impl Document {
             },
         }
 
+        self.fire_touch_pointer_events(event_type, identifier, point, &target);
+
         rooted_vec!(let mut target_touches);
         let touches = {
             let touches = self.active_touch_points.borrow();
@@ -1748,6 +2042,12 @@ impl Document {
         let event = event.upcast::<Event>();
         let result = event.fire(&target);
 
+        if let TouchEventType::Up | TouchEventType::Cancel = event_type {
+            if self.primary_touch_point.get() == Some(identifier) {
+                self.primary_touch_point.set(None);
+            }
+        }
+
         window.reflow(ReflowGoal::Full, ReflowReason::MouseEvent);
 
         match result {
@@ -1756,8 +2056,279 @@ impl Document {
         }
     }
 
+    /// Fire the pointer events that map the touch point `identifier`, before its touch
+    /// event is fired at `target`.
+    /// <https://w3c.github.io/pointerevents/#mapping-for-devices-that-do-not-support-hover>
+    fn fire_touch_pointer_events(
+        &self,
+        event_type: TouchEventType,
+        identifier: i32,
+        point: Point2D<f32>,
+        target: &EventTarget,
+    ) {
+        let is_primary = self.primary_touch_point.get() == Some(identifier);
+        let pressed = matches!(event_type, TouchEventType::Down | TouchEventType::Move);
+        let properties = PointerProperties::touch(identifier, is_primary, pressed);
+        let pointer_id = properties.pointer_id;
+        let (button, buttons) = match event_type {
+            TouchEventType::Down | TouchEventType::Up => (0i16, pressed as u16),
+            TouchEventType::Move | TouchEventType::Cancel => (-1i16, pressed as u16),
+        };
+
+        // Touch points do not hover, so they enter their target as they go down.
+        if let TouchEventType::Down = event_type {
+            self.fire_pointer_boundary_events(point, target, true, &properties);
+        }
+
+        self.process_pending_pointer_capture(pointer_id, point, &properties);
+        let capture_target = self.pointer_capture_target(pointer_id);
+        let pointer_target = capture_target
+            .as_ref()
+            .map_or(target, |capture_target| capture_target.upcast());
+        let (event_name, cancelable) = match event_type {
+            TouchEventType::Down => ("pointerdown", EventCancelable::Cancelable),
+            TouchEventType::Move => ("pointermove", EventCancelable::Cancelable),
+            TouchEventType::Up => ("pointerup", EventCancelable::Cancelable),
+            TouchEventType::Cancel => ("pointercancel", EventCancelable::NotCancelable),
+        };
+        self.fire_pointer_event(
+            event_name,
+            pointer_target,
+            EventBubbles::Bubbles,
+            cancelable,
+            point,
+            button,
+            buttons,
+            None,
+            properties.clone(),
+        );
+
+        if let TouchEventType::Up | TouchEventType::Cancel = event_type {
+            self.release_pointer_capture_implicitly(pointer_id, point, &properties);
+            self.fire_pointer_boundary_events(point, pointer_target, false, &properties);
+        }
+    }
+
+    /// Fire `pointerover` and `pointerenter` at `target` and its ancestors when `entering`,
+    /// or `pointerout` and `pointerleave` otherwise.
+    fn fire_pointer_boundary_events(
+        &self,
+        client_point: Point2D<f32>,
+        target: &EventTarget,
+        entering: bool,
+        properties: &PointerProperties,
+    ) {
+        let (boundary_event_name, event_name) = if entering {
+            ("pointerover", "pointerenter")
+        } else {
+            ("pointerout", "pointerleave")
+        };
+        self.fire_pointer_event(
+            boundary_event_name,
+            target,
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+            client_point,
+            -1i16,
+            0,
+            None,
+            properties.clone(),
+        );
+
+        let node = match target.downcast::<Node>() {
+            Some(node) => node,
+            None => return,
+        };
+        let mut targets: Vec<_> = node.inclusive_ancestors(ShadowIncluding::No).collect();
+        if entering {
+            targets.reverse();
+        }
+        for target in targets {
+            self.fire_pointer_event(
+                event_name,
+                target.upcast(),
+                EventBubbles::DoesNotBubble,
+                EventCancelable::NotCancelable,
+                client_point,
+                -1i16,
+                0,
+                None,
+                properties.clone(),
+            );
+        }
+    }
+
+    /// Fire a trusted pointer event of the pointer with `properties` at `target`.
+    fn fire_pointer_event(
+        &self,
+        event_name: &str,
+        target: &EventTarget,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        client_point: Point2D<f32>,
+        button: i16,
+        pressed_buttons: u16,
+        point_in_target: Option<Point2D<f32>>,
+        properties: PointerProperties,
+    ) -> EventStatus {
+        let client_x = client_point.x.to_i32().unwrap_or(0);
+        let client_y = client_point.y.to_i32().unwrap_or(0);
+
+        let pointer_event = PointerEvent::new(
+            &self.window,
+            DOMString::from(event_name),
+            can_bubble,
+            cancelable,
+            Some(&self.window),
+            0i32,
+            client_x,
+            client_y,
+            client_x,
+            client_y,
+            false,
+            false,
+            false,
+            false,
+            button,
+            pressed_buttons,
+            None,
+            point_in_target,
+            properties,
+        );
+        if event_name == "pointermove" {
+            pointer_event.set_coalesces_itself();
+        }
+        let event = pointer_event.upcast::<Event>();
+        event.set_trusted(true);
+        event.fire(target)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dfn-active-pointer>
+    pub fn is_active_pointer(&self, pointer_id: i32) -> bool {
+        pointer_id == MOUSE_POINTER_ID ||
+            self.active_touch_points.borrow().iter().any(|touch| {
+                touch.Identifier().saturating_add(TOUCH_POINTER_ID_OFFSET) == pointer_id
+            })
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dfn-active-buttons-state>
+    pub fn pointer_has_active_buttons(&self, pointer_id: i32) -> bool {
+        if pointer_id == MOUSE_POINTER_ID {
+            return self.pressed_mouse_buttons.get() != 0;
+        }
+        // Touch points only exist while they are in contact with the screen.
+        self.is_active_pointer(pointer_id)
+    }
+
+    /// <https://w3c.github.io/pointerevents/#dfn-pending-pointer-capture-target-override>
+    pub fn pending_pointer_capture_target(&self, pointer_id: i32) -> Option<DomRoot<Element>> {
+        self.pending_pointer_capture_targets
+            .borrow()
+            .get(&pointer_id)
+            .map(|element| DomRoot::from_ref(&**element))
+    }
+
+    pub fn set_pending_pointer_capture_target(&self, pointer_id: i32, target: Option<&Element>) {
+        let mut pending_pointer_capture_targets = self.pending_pointer_capture_targets.borrow_mut();
+        match target {
+            Some(target) => {
+                pending_pointer_capture_targets.insert(pointer_id, Dom::from_ref(target));
+            },
+            None => {
+                pending_pointer_capture_targets.remove(&pointer_id);
+            },
+        }
+    }
+
+    /// The element the pointer events of `pointer_id` are targeted at instead of the element
+    /// under the pointer.
+    fn pointer_capture_target(&self, pointer_id: i32) -> Option<DomRoot<Element>> {
+        self.pointer_capture_targets
+            .borrow()
+            .get(&pointer_id)
+            .map(|element| DomRoot::from_ref(&**element))
+    }
+
+    /// <https://w3c.github.io/pointerevents/#process-pending-pointer-capture>
+    fn process_pending_pointer_capture(
+        &self,
+        pointer_id: i32,
+        client_point: Point2D<f32>,
+        properties: &PointerProperties,
+    ) {
+        let capture_target = self.pointer_capture_target(pointer_id);
+        let pending_capture_target = self.pending_pointer_capture_target(pointer_id);
+        if capture_target == pending_capture_target {
+            return;
+        }
+
+        match pending_capture_target {
+            Some(ref pending_capture_target) => {
+                self.pointer_capture_targets
+                    .borrow_mut()
+                    .insert(pointer_id, Dom::from_ref(&**pending_capture_target));
+            },
+            None => {
+                self.pointer_capture_targets
+                    .borrow_mut()
+                    .remove(&pointer_id);
+            },
+        }
+
+        // The capture is lost at the document if the capturing element was removed from it.
+        // https://w3c.github.io/pointerevents/#implicit-release-of-pointer-capture
+        if let Some(capture_target) = capture_target {
+            let target = if capture_target.upcast::<Node>().is_connected() {
+                DomRoot::upcast::<EventTarget>(capture_target)
+            } else {
+                DomRoot::from_ref(self.upcast::<EventTarget>())
+            };
+            self.fire_pointer_event(
+                "lostpointercapture",
+                &target,
+                EventBubbles::Bubbles,
+                EventCancelable::NotCancelable,
+                client_point,
+                -1i16,
+                0,
+                None,
+                properties.clone(),
+            );
+        }
+        if let Some(pending_capture_target) = pending_capture_target {
+            self.fire_pointer_event(
+                "gotpointercapture",
+                pending_capture_target.upcast(),
+                EventBubbles::Bubbles,
+                EventCancelable::NotCancelable,
+                client_point,
+                -1i16,
+                0,
+                None,
+                properties.clone(),
+            );
+        }
+    }
+
+    /// <https://w3c.github.io/pointerevents/#implicit-release-of-pointer-capture>
+    fn release_pointer_capture_implicitly(
+        &self,
+        pointer_id: i32,
+        client_point: Point2D<f32>,
+        properties: &PointerProperties,
+    ) {
+        self.pending_pointer_capture_targets
+            .borrow_mut()
+            .remove(&pointer_id);
+        self.process_pending_pointer_capture(pointer_id, client_point, properties);
+    }
+
     /// The entry point for all key processing for web content
     pub fn dispatch_key_event(&self, keyboard_event: ::keyboard_types::KeyboardEvent) {
+        // Releasing the key that submitted a form must not hide its validation message.
+        if keyboard_event.state == KeyState::Down {
+            self.popups.dismiss(self);
+        }
         let focused = self.get_focused_element();
         let body = self.GetBody();
 
@@ -2206,6 +2777,7 @@ impl Document {
         // TODO: Step 1, increase the event loop's termination nesting level by 1.
         // Step 2
         self.incr_ignore_opens_during_unload_counter();
+        self.popups.clear(self);
         // Step 3-6
         if self.page_showing.get() {
             self.page_showing.set(false);
@@ -2335,10 +2907,16 @@ impl Document {
                     // http://w3c.github.io/navigation-timing/#widl-PerformanceNavigationTiming-loadEventEnd
                     update_with_current_time_ms(&document.load_event_end);
 
+                    document.run_content_scripts(ContentScriptRunAt::DocumentIdle);
+
                     window.reflow(ReflowGoal::Full, ReflowReason::DocumentLoaded);
 
-                    if let Some(fragment) = document.url().fragment() {
-                        document.check_and_scroll_fragment(fragment);
+                    // A document that reloads a session history entry gets the scroll position
+                    // of the entry back, instead of scrolling to the fragment.
+                    if !window.History().restore_pending_scroll_position() {
+                        if let Some(fragment) = document.url().fragment() {
+                            document.check_and_scroll_fragment(fragment);
+                        }
                     }
                 }),
                 self.window.upcast(),
@@ -2584,6 +3162,7 @@ impl Document {
             .queue(
                 task!(fire_dom_content_loaded_event: move || {
                 let document = document.root();
+                document.run_content_scripts(ContentScriptRunAt::DocumentEnd);
                 document.upcast::<EventTarget>().fire_bubbling_event(atom!("DOMContentLoaded"));
                 update_with_current_time_ms(&document.dom_content_loaded_event_end);
                 }),
@@ -2691,6 +3270,51 @@ impl Document {
         self.dom_interactive.get()
     }
 
+    /// Set the content scripts that apply to this document.
+    pub fn set_content_scripts(&self, content_scripts: Vec<ContentScript>) {
+        *self.content_scripts.borrow_mut() = content_scripts;
+    }
+
+    /// Run the JavaScript of the content scripts of this document that are due at `run_at`,
+    /// in registration order.
+    pub fn run_content_scripts(&self, run_at: ContentScriptRunAt) {
+        let sources: Vec<(JsWorld, String)> = self
+            .content_scripts
+            .borrow()
+            .iter()
+            .filter(|content_script| content_script.run_at == run_at)
+            .flat_map(|content_script| {
+                content_script
+                    .js
+                    .iter()
+                    .map(move |source| (content_script.world.clone(), source.clone()))
+            })
+            .collect();
+        if sources.is_empty() {
+            return;
+        }
+
+        let global = self.global();
+        let _ac = enter_realm(&*global);
+        for (world, source) in sources {
+            rooted!(in(*GlobalScope::get_cx()) let mut rval = UndefinedValue());
+            match world {
+                JsWorld::Main => {
+                    global.evaluate_js_on_global_with_result(
+                        &source,
+                        rval.handle_mut(),
+                        ScriptFetchOptions::default_classic_script(&global),
+                        global.api_base_url(),
+                    );
+                },
+                JsWorld::Isolated(_) | JsWorld::Extension(_) => {
+                    self.window
+                        .evaluate_js_in_isolated_world(&world, &source, rval.handle_mut());
+                },
+            }
+        }
+    }
+
     pub fn set_navigation_start(&self, navigation_start: u64) {
         self.interactive_time
             .borrow_mut()
@@ -2874,17 +3498,21 @@ impl Document {
     }
 
     pub fn flush_dirty_webgl_canvases(&self) {
-        let dirty_context_ids: Vec<_> = self
+        let dirty_contexts: Vec<_> = self
             .dirty_webgl_contexts
             .borrow_mut()
             .drain()
             .filter(|(_, context)| context.onscreen())
-            .map(|(id, _)| id)
+            .map(|(_, context)| DomRoot::from_ref(&*context))
             .collect();
 
-        if dirty_context_ids.is_empty() {
+        if dirty_contexts.is_empty() {
             return;
         }
+        let dirty_context_ids = dirty_contexts
+            .iter()
+            .map(|context| context.context_id())
+            .collect();
 
         #[allow(unused)]
         let mut time = 0;
@@ -2898,7 +3526,13 @@ impl Document {
             .expect("Where's the WebGL channel?")
             .send(WebGLMsg::SwapBuffers(dirty_context_ids, sender, time))
             .unwrap();
-        receiver.recv().unwrap();
+        let (_, lost_context_ids) = receiver.recv().unwrap();
+
+        for context in dirty_contexts {
+            if lost_context_ids.contains(&context.context_id()) {
+                context.lose_context();
+            }
+        }
     }
 
     pub fn add_dirty_webgpu_canvas(&self, context: &GPUCanvasContext) {
@@ -2916,6 +3550,207 @@ impl Document {
             .for_each(|(_, context)| context.send_swap_chain_present());
     }
 
+    /// Add an observer to
+    /// <https://drafts.csswg.org/resize-observer/#dom-document-resizeobservers-slot>,
+    /// unless it is already there.
+    pub fn add_resize_observer(&self, resize_observer: &ResizeObserver) {
+        let mut resize_observers = self.resize_observers.borrow_mut();
+        if !resize_observers
+            .iter()
+            .any(|observer| &**observer == resize_observer)
+        {
+            resize_observers.push(Dom::from_ref(resize_observer));
+        }
+    }
+
+    /// Steps 14 to 16 of <https://html.spec.whatwg.org/multipage/#update-the-rendering>,
+    /// delivering the observations of the resize observers, shallowest targets first.
+    pub fn update_resize_observations(&self) {
+        if self.resize_observers.borrow().is_empty() {
+            return;
+        }
+
+        // Step 14
+        let mut depth = ResizeObservationDepth::default();
+        let mut has_active = self.gather_active_resize_observations_at_depth(depth);
+
+        // Step 15. Gathering the observations queries layout, which updates it first.
+        while has_active {
+            depth = self.broadcast_active_resize_observations();
+            has_active = self.gather_active_resize_observations_at_depth(depth);
+        }
+
+        // Step 16
+        if self.has_skipped_resize_observations() {
+            self.deliver_resize_loop_error_notification();
+        }
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#gather-active-observations-h>
+    fn gather_active_resize_observations_at_depth(&self, depth: ResizeObservationDepth) -> bool {
+        let mut has_active = false;
+        for observer in self.resize_observers() {
+            has_active |= observer.gather_active_resize_observations_at_depth(depth);
+        }
+        has_active
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#broadcast-active-resize-observations>
+    fn broadcast_active_resize_observations(&self) -> ResizeObservationDepth {
+        // The callbacks may observe other elements, so the observers are rooted first.
+        self.resize_observers()
+            .iter()
+            .map(|observer| observer.broadcast_active_resize_observations())
+            .min()
+            .unwrap_or_else(ResizeObservationDepth::max)
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#has-skipped-observations-h>
+    fn has_skipped_resize_observations(&self) -> bool {
+        self.resize_observers()
+            .iter()
+            .any(|observer| observer.has_skipped_resize_observations())
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#deliver-resize-loop-error-notification>
+    fn deliver_resize_loop_error_notification(&self) {
+        let error_info = ErrorInfo {
+            message: "ResizeObserver loop completed with undelivered notifications.".into(),
+            filename: self.window.get_url().into_string(),
+            lineno: 0,
+            column: 0,
+        };
+        self.window
+            .upcast::<GlobalScope>()
+            .report_an_error(error_info, HandleValue::null());
+    }
+
+    fn resize_observers(&self) -> Vec<DomRoot<ResizeObserver>> {
+        self.resize_observers
+            .borrow()
+            .iter()
+            .map(|observer| DomRoot::from_ref(&**observer))
+            .collect()
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#start-intersection-observing-a-lazy-loading-element>
+    pub fn start_intersection_observing_lazy_loading_element(&self, element: &Element) {
+        let mut elements = self.lazy_load_elements.borrow_mut();
+        if !elements.iter().any(|observed| &**observed == element) {
+            elements.push(Dom::from_ref(element));
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#stop-intersection-observing-a-lazy-loading-element>
+    pub fn stop_intersection_observing_lazy_loading_element(&self, element: &Element) {
+        self.lazy_load_elements
+            .borrow_mut()
+            .retain(|observed| &**observed != element);
+    }
+
+    /// Run the callback of the <https://html.spec.whatwg.org/multipage/#lazy-load-intersection-observer>
+    /// for the elements that are now within the scroll margin of the viewport. Elements that are
+    /// not being rendered have no box and stay deferred.
+    pub fn update_lazy_load_intersections(&self) {
+        if self.lazy_load_elements.borrow().is_empty() {
+            return;
+        }
+
+        let margin = Au::from_px(LAZY_LOAD_SCROLL_MARGIN_PX);
+        let viewport = self.window.current_viewport();
+        let root = Rect::new(
+            Point2D::new(viewport.origin.x - margin, viewport.origin.y - margin),
+            Size2D::new(
+                viewport.size.width + margin * 2,
+                viewport.size.height + margin * 2,
+            ),
+        );
+
+        // Querying layout and running the resumption steps may change the observed elements,
+        // so they are rooted first.
+        let elements: Vec<DomRoot<Element>> = self
+            .lazy_load_elements
+            .borrow()
+            .iter()
+            .map(|element| DomRoot::from_ref(&**element))
+            .collect();
+        for element in elements {
+            let content_box = match element.upcast::<Node>().bounding_content_box() {
+                Some(content_box) => content_box,
+                None => continue,
+            };
+            if !intersects_with_threshold_zero(&content_box, &root) {
+                continue;
+            }
+
+            // Step 1 of the callback: stop intersection-observing the element.
+            self.stop_intersection_observing_lazy_loading_element(&element);
+
+            // Step 2 of the callback: run the element's lazy load resumption steps.
+            if let Some(image) = element.downcast::<HTMLImageElement>() {
+                image.resume_lazy_load();
+            } else if let Some(iframe) = element.downcast::<HTMLIFrameElement>() {
+                iframe.resume_lazy_load();
+            }
+        }
+    }
+
+    /// Called after a layout that built the fragment tree again, with the elements with
+    /// `content-visibility: auto` that generated boxes.
+    pub fn set_content_visibility_auto_elements(&self, elements: Vec<DomRoot<Element>>) {
+        *self.content_visibility_auto_elements.borrow_mut() = elements
+            .iter()
+            .map(|element| Dom::from_ref(&**element))
+            .collect();
+    }
+
+    /// Determine whether the elements with `content-visibility: auto` are relevant to the
+    /// user, and mark those whose relevancy changed as dirty, for layout to skip their
+    /// contents or to lay them out again. Being selected or in the top layer do not make
+    /// elements relevant yet.
+    /// <https://drafts.csswg.org/css-contain-2/#relevant-to-the-user>
+    pub fn update_content_visibility_relevancy(&self) {
+        if self.content_visibility_auto_elements.borrow().is_empty() {
+            return;
+        }
+
+        // How close to the viewport an element must be is up to the user agent: within half
+        // of the size of the viewport around it, so that contents are laid out before they
+        // are scrolled into view.
+        let viewport = self.window.current_viewport();
+        let (margin_x, margin_y) = (viewport.size.width / 2, viewport.size.height / 2);
+        let root = Rect::new(
+            Point2D::new(viewport.origin.x - margin_x, viewport.origin.y - margin_y),
+            Size2D::new(
+                viewport.size.width + margin_x * 2,
+                viewport.size.height + margin_y * 2,
+            ),
+        );
+
+        let focused = self.get_focused_element();
+        // Querying layout may change the elements, so they are rooted first.
+        let elements: Vec<DomRoot<Element>> = self
+            .content_visibility_auto_elements
+            .borrow()
+            .iter()
+            .map(|element| DomRoot::from_ref(&**element))
+            .collect();
+        for element in elements {
+            let node = element.upcast::<Node>();
+            let contains_focus = focused.as_ref().map_or(false, |focused| {
+                node.is_inclusive_ancestor_of(focused.upcast())
+            });
+            let is_relevant = contains_focus ||
+                node.bounding_content_box().map_or(false, |content_box| {
+                    intersects_with_threshold_zero(&content_box, &root)
+                });
+            if node.get_flag(NodeFlags::IS_RELEVANT_TO_THE_USER) != is_relevant {
+                node.set_flag(NodeFlags::IS_RELEVANT_TO_THE_USER, is_relevant);
+                node.dirty(NodeDamage::OtherNodeDamage);
+            }
+        }
+    }
+
     pub fn id_map(&self) -> Ref<HashMapTracedValues<Atom, Vec<Dom<Element>>>> {
         self.id_map.borrow()
     }
@@ -2925,6 +3760,15 @@ impl Document {
     }
 }
 
+/// Whether `rect` intersects `root` for an intersection observer with a threshold of zero:
+/// boxes touching the edges of the root, and empty boxes inside it, are intersecting.
+fn intersects_with_threshold_zero(rect: &Rect<Au>, root: &Rect<Au>) -> bool {
+    rect.min_x() <= root.max_x() &&
+        rect.max_x() >= root.min_x() &&
+        rect.min_y() <= root.max_y() &&
+        rect.max_y() >= root.min_y()
+}
+
 fn is_character_value_key(key: &Key) -> bool {
     match key {
         Key::Character(_) | Key::Enter => true,
@@ -2945,6 +3789,10 @@ pub trait LayoutDocumentHelpers<'dom> {
     unsafe fn will_paint(self);
     fn quirks_mode(self) -> QuirksMode;
     fn style_shared_lock(self) -> &'dom StyleSharedRwLock;
+    fn web_animation_declarations(
+        self,
+        key: &AnimationSetKey,
+    ) -> Option<Arc<Locked<PropertyDeclarationBlock>>>;
     fn shadow_roots(self) -> Vec<LayoutDom<'dom, ShadowRoot>>;
     fn shadow_roots_styles_changed(self) -> bool;
     unsafe fn flush_shadow_roots_stylesheets(self);
@@ -2977,6 +3825,19 @@ impl<'dom> LayoutDocumentHelpers<'dom> for LayoutDom<'dom, Document> {
         unsafe { self.unsafe_get().style_shared_lock() }
     }
 
+    #[inline]
+    fn web_animation_declarations(
+        self,
+        key: &AnimationSetKey,
+    ) -> Option<Arc<Locked<PropertyDeclarationBlock>>> {
+        unsafe {
+            self.unsafe_get()
+                .animations
+                .borrow_for_layout()
+                .web_animation_declarations_for_layout(key)
+        }
+    }
+
     #[inline]
     fn shadow_roots(self) -> Vec<LayoutDom<'dom, ShadowRoot>> {
         // FIXME(nox): We should just return a
@@ -3177,6 +4038,11 @@ impl Document {
             pending_restyles: DomRefCell::new(HashMap::new()),
             needs_paint: Cell::new(false),
             active_touch_points: DomRefCell::new(Vec::new()),
+            primary_touch_point: Cell::new(None),
+            pressed_mouse_buttons: Cell::new(0),
+            prevent_compatibility_mouse_events: Cell::new(false),
+            pointer_capture_targets: DomRefCell::new(HashMap::new()),
+            pending_pointer_capture_targets: DomRefCell::new(HashMap::new()),
             dom_loading: Cell::new(Default::default()),
             dom_interactive: Cell::new(Default::default()),
             dom_content_loaded_event_start: Cell::new(Default::default()),
@@ -3204,6 +4070,8 @@ impl Document {
             canceller: canceller,
             throw_on_dynamic_markup_insertion_counter: Cell::new(0),
             page_showing: Cell::new(false),
+            frozen: Cell::new(false),
+            was_discarded: Cell::new(false),
             salvageable: Cell::new(true),
             active_parser_was_aborted: Cell::new(false),
             fired_unload: Cell::new(false),
@@ -3225,8 +4093,15 @@ impl Document {
                 DomRefCell::new(AnimationTimeline::new())
             },
             animations: DomRefCell::new(Animations::new()),
+            popups: Default::default(),
+            drag_and_drop: Default::default(),
+            timeline: Default::default(),
             dirty_root: Default::default(),
             declarative_refresh: Default::default(),
+            content_scripts: Default::default(),
+            resize_observers: Default::default(),
+            lazy_load_elements: Default::default(),
+            content_visibility_auto_elements: Default::default(),
         }
     }
 
@@ -3930,8 +4805,8 @@ impl Document {
     }
 
     pub(crate) fn update_animation_timeline(&self) {
-        // Only update the time if it isn't being managed by a test.
-        if !pref!(layout.animations.test.enabled) {
+        // Only update the time if it isn't being managed by a test, nor paused.
+        if !pref!(layout.animations.test.enabled) && !self.animations.borrow().paused() {
             self.animation_timeline.borrow_mut().update();
         }
 
@@ -3943,14 +4818,21 @@ impl Document {
             .update_for_new_timeline_value(&self.window, current_timeline_value);
     }
 
+    /// Pause or resume the CSS and web animations of the document, freezing its timeline
+    /// while they are paused.
+    pub(crate) fn set_animations_paused(&self, paused: bool) {
+        self.animations.borrow().set_paused(&self.window, paused);
+    }
+
     pub(crate) fn maybe_mark_animating_nodes_as_dirty(&self) {
         let current_timeline_value = self.current_animation_timeline_value();
         let marked_dirty = self
             .animations
             .borrow()
             .mark_animating_nodes_as_dirty(current_timeline_value);
+        let effects_changed = self.animations.borrow().apply_web_animation_effects(self);
 
-        if marked_dirty {
+        if marked_dirty || effects_changed {
             self.window().add_pending_reflow();
         }
     }
@@ -3959,10 +4841,49 @@ impl Document {
         self.animation_timeline.borrow().current_value()
     }
 
+    /// The time of the animation timeline of this document in milliseconds since it was
+    /// created, which drives its document timelines.
+    pub(crate) fn animation_timeline_time(&self) -> f64 {
+        self.animation_timeline.borrow().elapsed() * 1000.
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-document-timeline>
+    pub(crate) fn timeline(&self) -> DomRoot<DocumentTimeline> {
+        self.timeline.or_init(|| DocumentTimeline::new(self))
+    }
+
+    /// The relevant web animations of this document whose effect targets an element
+    /// in it for which `filter` is true, in composite order.
+    pub(crate) fn relevant_animations<F>(&self, filter: F) -> Vec<DomRoot<WebAnimation>>
+    where
+        F: Fn(&Element) -> bool,
+    {
+        self.animations
+            .borrow()
+            .web_animations()
+            .into_iter()
+            .filter(|animation| animation.is_relevant())
+            .filter(|animation| {
+                animation
+                    .keyframe_effect()
+                    .and_then(|effect| effect.target())
+                    .map_or(false, |target| {
+                        target.upcast::<Node>().is_connected() &&
+                            *document_from_node(&*target) == *self &&
+                            filter(&target)
+                    })
+            })
+            .collect()
+    }
+
     pub(crate) fn animations(&self) -> Ref<Animations> {
         self.animations.borrow()
     }
 
+    pub(crate) fn popups(&self) -> &Popups {
+        &self.popups
+    }
+
     pub(crate) fn update_animations_post_reflow(&self) {
         self.animations
             .borrow()
@@ -5045,6 +5966,36 @@ impl DocumentMethods for Document {
         )
     }
 
+    // https://drafts.csswg.org/cssom-view/#dom-document-caretpositionfrompoint
+    fn CaretRangeFromPoint(&self, x: Finite<f64>, y: Finite<f64>) -> Option<DomRoot<Range>> {
+        let x = *x as f32;
+        let y = *y as f32;
+        let viewport = self.window.window_size().initial_viewport;
+        if !self.has_browsing_context ||
+            x < 0.0 ||
+            y < 0.0 ||
+            x > viewport.width ||
+            y > viewport.height
+        {
+            return None;
+        }
+
+        // Layout counts the characters of the text, and ranges its UTF-16 code units.
+        let (node, offset) = self
+            .window
+            .caret_position_from_point_query(Point2D::new(x, y))?;
+        let offset = match node.downcast::<CharacterData>() {
+            Some(data) => data
+                .data()
+                .chars()
+                .take(offset)
+                .map(char::len_utf16)
+                .sum::<usize>() as u32,
+            None => 0,
+        };
+        Some(Range::new(self, &node, offset, &node, offset))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-document-open
     fn Open(
         &self,
@@ -5256,6 +6207,17 @@ impl DocumentMethods for Document {
     // https://fullscreen.spec.whatwg.org/#handler-document-onfullscreenerror
     event_handler!(fullscreenerror, GetOnfullscreenerror, SetOnfullscreenerror);
 
+    // https://wicg.github.io/page-lifecycle/#dom-document-onfreeze
+    event_handler!(freeze, GetOnfreeze, SetOnfreeze);
+
+    // https://wicg.github.io/page-lifecycle/#dom-document-onresume
+    event_handler!(resume, GetOnresume, SetOnresume);
+
+    // https://wicg.github.io/page-lifecycle/#dom-document-wasdiscarded
+    fn WasDiscarded(&self) -> bool {
+        self.was_discarded.get()
+    }
+
     // https://fullscreen.spec.whatwg.org/#handler-document-onfullscreenchange
     event_handler!(
         fullscreenchange,
@@ -5302,6 +6264,16 @@ impl DocumentMethods for Document {
             None
         }
     }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-document-timeline>
+    fn Timeline(&self) -> DomRoot<DocumentTimeline> {
+        self.timeline()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-documentorshadowroot-getanimations>
+    fn GetAnimations(&self) -> Vec<DomRoot<WebAnimation>> {
+        self.relevant_animations(|_| true)
+    }
 }
 
 fn update_with_current_time_ms(marker: &Cell<u64>) {
@@ -5370,6 +6342,12 @@ pub enum AnimationFrameCallback {
         #[ignore_malloc_size_of = "Rc is hard"]
         callback: Rc<FrameRequestCallback>,
     },
+    /// Tells WebDriver that an animation frame ran.
+    WebDriverFrameTick {
+        #[ignore_malloc_size_of = "defined in ipc-channel"]
+        #[no_trace]
+        sender: IpcSender<()>,
+    },
 }
 
 impl AnimationFrameCallback {
@@ -5389,6 +6367,9 @@ impl AnimationFrameCallback {
                 // https://github.com/servo/servo/issues/6928
                 let _ = callback.Call__(Finite::wrap(now), ExceptionHandling::Report);
             },
+            AnimationFrameCallback::WebDriverFrameTick { ref sender } => {
+                let _ = sender.send(());
+            },
         }
     }
 }
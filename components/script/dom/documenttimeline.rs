@@ -0,0 +1,55 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+
+use crate::dom::animationtimeline::AnimationTimeline;
+use crate::dom::bindings::codegen::Bindings::DocumentTimelineBinding::DocumentTimelineOptions;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::document::Document;
+use crate::dom::window::Window;
+
+/// <https://drafts.csswg.org/web-animations-1/#the-documenttimeline-interface>
+#[dom_struct]
+pub struct DocumentTimeline {
+    timeline: AnimationTimeline,
+}
+
+impl DocumentTimeline {
+    fn new_inherited(document: &Document, origin_time: f64) -> DocumentTimeline {
+        DocumentTimeline {
+            timeline: AnimationTimeline::new_inherited(document, origin_time),
+        }
+    }
+
+    /// The default document timeline of `document`, whose origin time is zero.
+    pub fn new(document: &Document) -> DomRoot<DocumentTimeline> {
+        Self::new_with_proto(document, None, 0.)
+    }
+
+    fn new_with_proto(
+        document: &Document,
+        proto: Option<HandleObject>,
+        origin_time: f64,
+    ) -> DomRoot<DocumentTimeline> {
+        reflect_dom_object_with_proto(
+            Box::new(DocumentTimeline::new_inherited(document, origin_time)),
+            document.window(),
+            proto,
+        )
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-documenttimeline-documenttimeline>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        options: &DocumentTimelineOptions,
+    ) -> DomRoot<DocumentTimeline> {
+        DocumentTimeline::new_with_proto(&window.Document(), proto, *options.originTime)
+    }
+}
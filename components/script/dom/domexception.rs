@@ -43,6 +43,14 @@ pub enum DOMErrorName {
     DataCloneError = DOMExceptionConstants::DATA_CLONE_ERR,
     NotReadableError,
     OperationError,
+    NotAllowedError,
+    DataError,
+    TransactionInactiveError,
+    ReadOnlyError,
+    VersionError,
+    ConstraintError,
+    UnknownError,
+    EncodingError,
 }
 
 impl DOMErrorName {
@@ -72,6 +80,14 @@ impl DOMErrorName {
             "DataCloneError" => Some(DOMErrorName::DataCloneError),
             "NotReadableError" => Some(DOMErrorName::NotReadableError),
             "OperationError" => Some(DOMErrorName::OperationError),
+            "NotAllowedError" => Some(DOMErrorName::NotAllowedError),
+            "DataError" => Some(DOMErrorName::DataError),
+            "TransactionInactiveError" => Some(DOMErrorName::TransactionInactiveError),
+            "ReadOnlyError" => Some(DOMErrorName::ReadOnlyError),
+            "VersionError" => Some(DOMErrorName::VersionError),
+            "ConstraintError" => Some(DOMErrorName::ConstraintError),
+            "UnknownError" => Some(DOMErrorName::UnknownError),
+            "EncodingError" => Some(DOMErrorName::EncodingError),
             _ => None,
         }
     }
@@ -119,6 +135,26 @@ impl DOMException {
             DOMErrorName::OperationError => {
                 "The operation failed for an operation-specific reason."
             },
+            DOMErrorName::NotAllowedError => {
+                "The request is not allowed by the user agent or the platform in the current context."
+            },
+            DOMErrorName::DataError => "The provided data is inadequate.",
+            DOMErrorName::TransactionInactiveError => {
+                "A request was placed against a transaction which is not active."
+            },
+            DOMErrorName::ReadOnlyError => {
+                "The mutating operation was attempted in a read-only transaction."
+            },
+            DOMErrorName::VersionError => {
+                "An attempt was made to open a database using a lower version than the existing version."
+            },
+            DOMErrorName::ConstraintError => {
+                "A mutation operation in the transaction failed because a constraint was not satisfied."
+            },
+            DOMErrorName::UnknownError => {
+                "The operation failed for reasons unrelated to the database itself."
+            },
+            DOMErrorName::EncodingError => "The encoding or decoding operation failed.",
         };
 
         (
@@ -7,7 +7,7 @@ use std::{f64, ptr};
 
 use cssparser::{Parser, ParserInput};
 use dom_struct::dom_struct;
-use euclid::default::Transform3D;
+use euclid::default::{Transform2D, Transform3D};
 use euclid::Angle;
 use js::jsapi::JSObject;
 use js::rust::{CustomAutoRooterGuard, HandleObject};
@@ -17,7 +17,9 @@ use url::Url;
 
 use crate::dom::bindings::buffer_source::create_buffer_source;
 use crate::dom::bindings::cell::{DomRefCell, Ref};
-use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{DOMMatrixInit, DOMMatrixMethods};
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::{
+    DOMMatrix2DInit, DOMMatrixInit, DOMMatrixMethods,
+};
 use crate::dom::bindings::codegen::Bindings::DOMMatrixReadOnlyBinding::DOMMatrixReadOnlyMethods;
 use crate::dom::bindings::codegen::Bindings::DOMPointBinding::DOMPointInit;
 use crate::dom::bindings::codegen::UnionTypes::StringOrUnrestrictedDoubleSequence;
@@ -816,6 +818,34 @@ pub fn dommatrixinit_to_matrix(dict: &DOMMatrixInit) -> Fallible<(bool, Transfor
     }
 }
 
+// https://drafts.fxtf.org/geometry-1/#create-a-dommatrix-from-the-2d-dictionary
+pub fn dommatrix2dinit_to_matrix(dict: &DOMMatrix2DInit) -> Fallible<Transform2D<f64>> {
+    // https://drafts.fxtf.org/geometry-1/#validate-and-fixup-2d
+    // Step 1.
+    let same_value_zero = |alias: Option<f64>, member: Option<f64>| match (alias, member) {
+        (Some(alias), Some(member)) => alias == member || (alias.is_nan() && member.is_nan()),
+        _ => true,
+    };
+    if !(same_value_zero(dict.a, dict.m11) &&
+        same_value_zero(dict.b, dict.m12) &&
+        same_value_zero(dict.c, dict.m21) &&
+        same_value_zero(dict.d, dict.m22) &&
+        same_value_zero(dict.e, dict.m41) &&
+        same_value_zero(dict.f, dict.m42))
+    {
+        return Err(error::Error::Type("Invalid matrix initializer.".to_owned()));
+    }
+    // Steps 2-7.
+    Ok(Transform2D::new(
+        dict.m11.unwrap_or(dict.a.unwrap_or(1.0)),
+        dict.m12.unwrap_or(dict.b.unwrap_or(0.0)),
+        dict.m21.unwrap_or(dict.c.unwrap_or(0.0)),
+        dict.m22.unwrap_or(dict.d.unwrap_or(1.0)),
+        dict.m41.unwrap_or(dict.e.unwrap_or(0.0)),
+        dict.m42.unwrap_or(dict.f.unwrap_or(0.0)),
+    ))
+}
+
 #[inline]
 fn normalize_point(x: f64, y: f64, z: f64) -> (f64, f64, f64) {
     let len = (x * x + y * y + z * z).sqrt();
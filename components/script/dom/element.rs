@@ -22,11 +22,11 @@ use html5ever::{
     local_name, namespace_prefix, namespace_url, ns, serialize, LocalName, Namespace, Prefix,
     QualName,
 };
-use js::jsapi::Heap;
+use js::jsapi::{Heap, JSObject};
 use js::jsval::JSVal;
 use js::rust::HandleObject;
 use msg::constellation_msg::InputMethodType;
-use net_traits::request::CorsSettings;
+use net_traits::request::{CorsSettings, RequestPriority};
 use net_traits::ReferrerPolicy;
 use script_layout_interface::message::ReflowGoal;
 use selectors::attr::{AttrSelectorOperation, CaseSensitivity, NamespaceConstraint};
@@ -66,8 +66,12 @@ use xml5ever::serialize::{SerializeOpts as XmlSerializeOpts, TraversalScope as X
 
 use super::htmltablecolelement::{HTMLTableColElement, HTMLTableColElementLayoutHelpers};
 use crate::dom::activation::Activatable;
+use crate::dom::animation::Animation;
+use crate::dom::animationeffect::Timing;
 use crate::dom::attr::{Attr, AttrHelpersForLayout};
 use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref, RefMut};
+use crate::dom::bindings::codegen::Bindings::AnimatableBinding::GetAnimationsOptions;
+use crate::dom::bindings::codegen::Bindings::AnimationBinding::AnimationMethods;
 use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
@@ -78,7 +82,9 @@ use crate::dom::bindings::codegen::Bindings::ShadowRootBinding::ShadowRoot_Bindi
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
     ScrollBehavior, ScrollToOptions, WindowMethods,
 };
-use crate::dom::bindings::codegen::UnionTypes::NodeOrString;
+use crate::dom::bindings::codegen::UnionTypes::{
+    NodeOrString, UnrestrictedDoubleOrKeyframeAnimationOptions,
+};
 use crate::dom::bindings::conversions::DerivedFrom;
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
@@ -131,6 +137,7 @@ use crate::dom::htmltablesectionelement::{
 };
 use crate::dom::htmltemplateelement::HTMLTemplateElement;
 use crate::dom::htmltextareaelement::{HTMLTextAreaElement, LayoutHTMLTextAreaElementHelpers};
+use crate::dom::keyframeeffect::KeyframeEffect;
 use crate::dom::mutationobserver::{Mutation, MutationObserver};
 use crate::dom::namednodemap::NamedNodeMap;
 use crate::dom::node::{
@@ -146,6 +153,7 @@ use crate::dom::text::Text;
 use crate::dom::validation::Validatable;
 use crate::dom::virtualmethods::{vtable_for, VirtualMethods};
 use crate::dom::window::ReflowReason;
+use crate::script_runtime::JSContext;
 use crate::script_thread::ScriptThread;
 use crate::stylesheet_loader::StylesheetOwner;
 use crate::task::TaskOnce;
@@ -1371,13 +1379,38 @@ impl Element {
             return None;
         }
 
-        if let Some(input) = self.downcast::<HTMLInputElement>() {
+        let kind = if let Some(input) = self.downcast::<HTMLInputElement>() {
             input.input_type().as_ime_type()
         } else if self.is::<HTMLTextAreaElement>() {
             Some(InputMethodType::Text)
         } else {
             // Other focusable elements that are not input fields.
             None
+        };
+
+        // The inputmode attribute only hints at the keyboard of fields where text is typed.
+        // https://html.spec.whatwg.org/multipage/#input-modalities:-the-inputmode-attribute
+        match kind {
+            Some(InputMethodType::Email) |
+            Some(InputMethodType::Number) |
+            Some(InputMethodType::Search) |
+            Some(InputMethodType::Tel) |
+            Some(InputMethodType::Text) |
+            Some(InputMethodType::Url) => {
+                let mode = self.get_string_attribute(&local_name!("inputmode"));
+                match_ignore_ascii_case! { &*mode,
+                    "none" => None,
+                    "text" => Some(InputMethodType::Text),
+                    "tel" => Some(InputMethodType::Tel),
+                    "url" => Some(InputMethodType::Url),
+                    "email" => Some(InputMethodType::Email),
+                    "numeric" => Some(InputMethodType::Numeric),
+                    "decimal" => Some(InputMethodType::Decimal),
+                    "search" => Some(InputMethodType::Search),
+                    _ => kind,
+                }
+            },
+            _ => kind,
         }
     }
 
@@ -1942,6 +1975,14 @@ impl Element {
             return true;
         }
 
+        // Editing hosts are focusable.
+        if self
+            .downcast::<HTMLElement>()
+            .map_or(false, |element| element.is_editing_host())
+        {
+            return true;
+        }
+
         match node.type_id() {
             // <button>, <select>, <iframe>, and <textarea> are implicitly focusable.
             NodeTypeId::Element(ElementTypeId::HTMLElement(
@@ -1963,7 +2004,6 @@ impl Element {
                 HTMLElementTypeId::HTMLAnchorElement,
             )) => element.has_attribute(&local_name!("href")),
 
-            //TODO focusable if editing host
             //TODO focusable if "sorting interface th elements"
             _ => {
                 // Draggable elements are focusable.
@@ -2888,12 +2928,97 @@ impl ElementMethods for Element {
         doc.enter_fullscreen(self)
     }
 
+    // https://w3c.github.io/pointerevents/#dom-element-setpointercapture
+    fn SetPointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        let doc = document_from_node(self);
+        // Step 1.
+        if !doc.is_active_pointer(pointer_id) {
+            return Err(Error::NotFound);
+        }
+        // Step 2.
+        if !self.upcast::<Node>().is_connected() {
+            return Err(Error::InvalidState);
+        }
+        // Step 4 - 5.
+        if doc.pointer_has_active_buttons(pointer_id) {
+            doc.set_pending_pointer_capture_target(pointer_id, Some(self));
+        }
+        Ok(())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-releasepointercapture
+    fn ReleasePointerCapture(&self, pointer_id: i32) -> ErrorResult {
+        let doc = document_from_node(self);
+        // Step 1.
+        if !doc.is_active_pointer(pointer_id) {
+            return Err(Error::NotFound);
+        }
+        // Step 2.
+        if !self.HasPointerCapture(pointer_id) {
+            return Ok(());
+        }
+        // Step 3.
+        doc.set_pending_pointer_capture_target(pointer_id, None);
+        Ok(())
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-element-haspointercapture
+    fn HasPointerCapture(&self, pointer_id: i32) -> bool {
+        document_from_node(self)
+            .pending_pointer_capture_target(pointer_id)
+            .map_or(false, |target| &*target == self)
+    }
+
     // XXX Hidden under dom.shadowdom.enabled pref. Only exposed to be able
     //     to test partial Shadow DOM support for UA widgets.
     // https://dom.spec.whatwg.org/#dom-element-attachshadow
     fn AttachShadow(&self) -> Fallible<DomRoot<ShadowRoot>> {
         self.attach_shadow(IsUserAgentWidget::No)
     }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animatable-animate>
+    fn Animate(
+        &self,
+        cx: JSContext,
+        keyframes: *mut JSObject,
+        options: UnrestrictedDoubleOrKeyframeAnimationOptions,
+    ) -> Fallible<DomRoot<Animation>> {
+        let (timing, id) = match options {
+            UnrestrictedDoubleOrKeyframeAnimationOptions::UnrestrictedDouble(duration) => {
+                (Timing::from_duration(duration)?, DOMString::new())
+            },
+            UnrestrictedDoubleOrKeyframeAnimationOptions::KeyframeAnimationOptions(options) => (
+                Timing::from_effect_timing(&options.parent.parent)?,
+                options.id.clone(),
+            ),
+        };
+
+        // Step 1 - 2.
+        let window = window_from_node(self);
+        let effect = KeyframeEffect::new(cx, &window, None, Some(self), keyframes, timing)?;
+
+        // Step 3 - 5.
+        let timeline = document_from_node(self).timeline();
+        let animation = Animation::new(
+            &window,
+            None,
+            Some(effect.upcast()),
+            Some(timeline.upcast()),
+        );
+        animation.SetId(id);
+
+        // Step 6.
+        animation.Play()?;
+        Ok(animation)
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-animatable-getanimations>
+    fn GetAnimations(&self, options: &GetAnimationsOptions) -> Vec<DomRoot<Animation>> {
+        document_from_node(self).relevant_animations(|target| {
+            target == self ||
+                (options.subtree && self.upcast::<Node>().is_ancestor_of(target.upcast()))
+        })
+    }
 }
 
 impl VirtualMethods for Element {
@@ -2918,9 +3043,10 @@ impl VirtualMethods for Element {
         let node = self.upcast::<Node>();
         let doc = node.owner_doc();
         match attr.local_name() {
-            &local_name!("tabindex") | &local_name!("draggable") | &local_name!("hidden") => {
-                self.update_sequentially_focusable_status()
-            },
+            &local_name!("tabindex") |
+            &local_name!("draggable") |
+            &local_name!("hidden") |
+            &local_name!("contenteditable") => self.update_sequentially_focusable_status(),
             &local_name!("style") => {
                 // Modifying the `style` attribute might change style.
                 *self.style_attribute.borrow_mut() = match mutation {
@@ -3944,6 +4070,72 @@ pub(crate) fn referrer_policy_for_element(element: &Element) -> Option<ReferrerP
         .or_else(|| document_from_node(element).get_referrer_policy())
 }
 
+/// <https://html.spec.whatwg.org/multipage/#fetch-priority-attribute>
+pub(crate) fn fetch_priority_for_token(token: &str) -> RequestPriority {
+    if token.eq_ignore_ascii_case("high") {
+        RequestPriority::High
+    } else if token.eq_ignore_ascii_case("low") {
+        RequestPriority::Low
+    } else {
+        RequestPriority::Auto
+    }
+}
+
+pub(crate) fn fetch_priority_for_element(element: &Element) -> RequestPriority {
+    element
+        .get_attribute_by_name(DOMString::from_string(String::from("fetchpriority")))
+        .map_or(RequestPriority::Auto, |attribute| {
+            fetch_priority_for_token(&attribute.Value())
+        })
+}
+
+pub fn reflect_fetch_priority_attribute(element: &Element) -> DOMString {
+    DOMString::from(match fetch_priority_for_element(element) {
+        RequestPriority::High => "high",
+        RequestPriority::Low => "low",
+        RequestPriority::Auto => "auto",
+    })
+}
+
+pub fn set_fetch_priority_attribute(element: &Element, value: DOMString) {
+    element.set_string_attribute(&LocalName::from("fetchpriority"), value);
+}
+
+/// Whether the element's <https://html.spec.whatwg.org/multipage/#lazy-loading-attribute>
+/// is in the Lazy state.
+fn has_lazy_loading_attribute(element: &Element) -> bool {
+    element
+        .get_attribute_by_name(DOMString::from_string(String::from("loading")))
+        .map_or(false, |attribute| {
+            attribute.Value().eq_ignore_ascii_case("lazy")
+        })
+}
+
+/// <https://html.spec.whatwg.org/multipage/#will-lazy-load-element-steps>
+pub(crate) fn will_lazy_load_element(element: &Element) -> bool {
+    // Step 1. If scripting is disabled for element, then return false.
+    // Note: lazy loading is a tracking vector when scripting is disabled.
+    if !document_from_node(element).is_scripting_enabled() {
+        return false;
+    }
+
+    // Step 2. If element's lazy loading attribute is in the Lazy state, then return true.
+    // Step 3. Return false.
+    has_lazy_loading_attribute(element)
+}
+
+pub fn reflect_loading_attribute(element: &Element) -> DOMString {
+    DOMString::from(if has_lazy_loading_attribute(element) {
+        "lazy"
+    } else {
+        "eager"
+    })
+}
+
+pub fn set_loading_attribute(element: &Element, value: DOMString) {
+    element.set_string_attribute(&LocalName::from("loading"), value);
+}
+
 pub(crate) fn cors_setting_for_element(element: &Element) -> Option<CorsSettings> {
     reflect_cross_origin_attribute(element).map_or(None, |attr| match &*attr {
         "anonymous" => Some(CorsSettings::Anonymous),
@@ -8,15 +8,14 @@ use std::default::Default;
 use devtools_traits::{TimelineMarker, TimelineMarkerType};
 use dom_struct::dom_struct;
 use js::rust::HandleObject;
-use metrics::ToMs;
 use servo_atoms::Atom;
 
+use crate::clock;
 use crate::dom::bindings::callback::ExceptionHandling;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::EventBinding;
 use crate::dom::bindings::codegen::Bindings::EventBinding::{EventConstants, EventMethods};
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
-use crate::dom::bindings::codegen::Bindings::PerformanceBinding::Performance_Binding::PerformanceMethods;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::inheritance::Castable;
@@ -29,9 +28,10 @@ use crate::dom::element::Element;
 use crate::dom::eventtarget::{CompiledEventListener, EventTarget, ListenerPhase};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlinputelement::InputActivationState;
+use crate::dom::idbrequest::IDBRequest;
+use crate::dom::idbtransaction::IDBTransaction;
 use crate::dom::mouseevent::MouseEvent;
 use crate::dom::node::{Node, ShadowIncluding};
-use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::virtualmethods::vtable_for;
 use crate::dom::window::Window;
 use crate::task::TaskOnce;
@@ -71,7 +71,7 @@ impl Event {
             trusted: Cell::new(false),
             dispatching: Cell::new(false),
             initialized: Cell::new(false),
-            precise_time_ns: time::precise_time_ns(),
+            precise_time_ns: clock::monotonic_time_ns(),
         }
     }
 
@@ -169,6 +169,18 @@ impl Event {
                     event_path.push(DomRoot::from_ref(document.window().upcast()));
                 }
             }
+        } else if let Some(request) = target.downcast::<IDBRequest>() {
+            // Requests propagate to their transaction, and transactions to their connection.
+            // <https://w3c.github.io/IndexedDB/#ref-for-get-the-parent%E2%91%A0>
+            event_path.push(DomRoot::from_ref(target));
+            if let Some(transaction) = request.transaction() {
+                let db = transaction.db();
+                event_path.push(DomRoot::upcast(transaction));
+                event_path.push(DomRoot::upcast(db));
+            }
+        } else if let Some(transaction) = target.downcast::<IDBTransaction>() {
+            event_path.push(DomRoot::from_ref(target));
+            event_path.push(DomRoot::upcast(transaction.db()));
         } else {
             // a non-node EventTarget, likely a global.
             // No parent to propagate up to, but we still
@@ -187,6 +199,7 @@ impl Event {
     ) -> EventStatus {
         // Step 1.
         self.dispatching.set(true);
+        let processing_start = clock::monotonic_time_ns();
 
         // Step 2.
         let target_override_document; // upcasted EventTarget's lifetime depends on this
@@ -360,6 +373,19 @@ impl Event {
             }
         }
 
+        // https://w3c.github.io/event-timing/#sec-modifications-DOM
+        if self.trusted.get() {
+            let global = target.global();
+            if global.is::<Window>() {
+                global.performance().queue_event_timing(
+                    self,
+                    target,
+                    processing_start,
+                    clock::monotonic_time_ns(),
+                );
+            }
+        }
+
         return self.status();
     }
 
@@ -371,6 +397,11 @@ impl Event {
         }
     }
 
+    /// When the event was created, in nanoseconds of the monotonic clock.
+    pub fn precise_time_ns(&self) -> u64 {
+        self.precise_time_ns
+    }
+
     #[inline]
     pub fn dispatching(&self) -> bool {
         self.dispatching.get()
@@ -501,9 +532,11 @@ impl EventMethods for Event {
 
     /// <https://dom.spec.whatwg.org/#dom-event-timestamp>
     fn TimeStamp(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(
-            (self.precise_time_ns - (*self.global().performance().TimeOrigin()).round() as u64)
-                .to_ms(),
+        let global = self.global();
+        global.coarsen_time(
+            global
+                .performance()
+                .relative_high_resolution_time(self.precise_time_ns),
         )
     }
 
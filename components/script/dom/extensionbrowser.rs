@@ -0,0 +1,131 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use ipc_channel::router::ROUTER;
+use profile_traits::ipc;
+use script_traits::extension::{ExtensionApiResult, ExtensionId, ExtensionRequest};
+use script_traits::ScriptMsg;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::dom::bindings::codegen::Bindings::ExtensionBrowserBinding::ExtensionBrowserMethods;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::extensionruntime::ExtensionRuntime;
+use crate::dom::extensionstorage::ExtensionStorage;
+use crate::dom::extensiontabs::ExtensionTabs;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+
+/// The `browser` object of the isolated world of an extension in a window.
+#[dom_struct]
+pub struct ExtensionBrowser {
+    reflector_: Reflector,
+    runtime: Dom<ExtensionRuntime>,
+    storage: Dom<ExtensionStorage>,
+    tabs: Dom<ExtensionTabs>,
+}
+
+impl ExtensionBrowser {
+    fn new_inherited(
+        runtime: &ExtensionRuntime,
+        storage: &ExtensionStorage,
+        tabs: &ExtensionTabs,
+    ) -> ExtensionBrowser {
+        ExtensionBrowser {
+            reflector_: Reflector::new(),
+            runtime: Dom::from_ref(runtime),
+            storage: Dom::from_ref(storage),
+            tabs: Dom::from_ref(tabs),
+        }
+    }
+
+    pub fn new(window: &Window, extension_id: ExtensionId) -> DomRoot<ExtensionBrowser> {
+        let runtime = ExtensionRuntime::new(window, extension_id);
+        let storage = ExtensionStorage::new(window, extension_id);
+        let tabs = ExtensionTabs::new(window, extension_id);
+        reflect_dom_object(
+            Box::new(ExtensionBrowser::new_inherited(&runtime, &storage, &tabs)),
+            window,
+        )
+    }
+
+    pub fn runtime(&self) -> &ExtensionRuntime {
+        &self.runtime
+    }
+}
+
+impl ExtensionBrowserMethods for ExtensionBrowser {
+    fn Runtime(&self) -> DomRoot<ExtensionRuntime> {
+        DomRoot::from_ref(&self.runtime)
+    }
+
+    fn Storage(&self) -> DomRoot<ExtensionStorage> {
+        DomRoot::from_ref(&self.storage)
+    }
+
+    fn Tabs(&self) -> DomRoot<ExtensionTabs> {
+        DomRoot::from_ref(&self.tabs)
+    }
+}
+
+/// Send `request` to the constellation on behalf of the extension `extension_id`, and
+/// settle `promise` once it replies: by calling `on_success` with the result of the
+/// call on the script thread, or by rejecting it with a `TypeError` if the call failed.
+pub fn call_extension_api<T, F>(
+    object: &impl DomObject,
+    extension_id: ExtensionId,
+    promise: &Rc<Promise>,
+    request: impl FnOnce(IpcSender<ExtensionApiResult<T>>) -> ExtensionRequest,
+    on_success: F,
+) where
+    T: DeserializeOwned + Serialize + Send + 'static,
+    F: FnOnce(&Promise, T) + Send + 'static,
+{
+    let global = object.global();
+    let (task_source, canceller) = global
+        .as_window()
+        .task_manager()
+        .dom_manipulation_task_source_with_canceller();
+    let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+    let mut pending = Some((TrustedPromise::new(promise.clone()), on_success));
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| {
+            // The router doesn't know that a single reply is ever sent.
+            let (trusted, on_success) = match pending.take() {
+                Some(pending) => pending,
+                None => return error!("Extension API call replied to twice"),
+            };
+            let result: ExtensionApiResult<T> = match message.to() {
+                Ok(result) => result,
+                Err(_) => return error!("Extension API call replied with an incorrect payload"),
+            };
+            let _ = task_source.queue_with_canceller(
+                task!(settle_extension_api_call: move || {
+                    let promise = trusted.root();
+                    match result {
+                        Ok(value) => on_success(&promise, value),
+                        Err(error) => promise.reject_error(Error::Type(error.to_string())),
+                    }
+                }),
+                &canceller,
+            );
+        }),
+    );
+    let msg = ScriptMsg::ExtensionRequest(extension_id, request(sender));
+    if let Err(e) = global.script_to_constellation_chan().send(msg) {
+        warn!(
+            "Failed to send extension request to the constellation ({:?})",
+            e
+        );
+    }
+}
@@ -0,0 +1,66 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ExtensionEventBinding::ExtensionEventMethods;
+use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::window::Window;
+
+/// An event of an extension API, such as `browser.runtime.onMessage`. Unlike DOM
+/// events, its listeners are plain functions, called with arguments that depend on
+/// the event.
+#[dom_struct]
+pub struct ExtensionEvent {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Rc"]
+    listeners: DomRefCell<Vec<Rc<Function>>>,
+}
+
+impl ExtensionEvent {
+    fn new_inherited() -> ExtensionEvent {
+        ExtensionEvent {
+            reflector_: Reflector::new(),
+            listeners: DomRefCell::new(vec![]),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<ExtensionEvent> {
+        reflect_dom_object(Box::new(ExtensionEvent::new_inherited()), window)
+    }
+
+    /// The listeners of this event, in the order they were added.
+    pub fn listeners(&self) -> Vec<Rc<Function>> {
+        self.listeners.borrow().clone()
+    }
+}
+
+impl ExtensionEventMethods for ExtensionEvent {
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/events/Event/addListener
+    fn AddListener(&self, listener: Rc<Function>) {
+        if !self.HasListener(listener.clone()) {
+            self.listeners.borrow_mut().push(listener);
+        }
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/events/Event/removeListener
+    fn RemoveListener(&self, listener: Rc<Function>) {
+        self.listeners
+            .borrow_mut()
+            .retain(|existing| *existing != listener);
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/events/Event/hasListener
+    fn HasListener(&self, listener: Rc<Function>) -> bool {
+        self.listeners
+            .borrow()
+            .iter()
+            .any(|existing| *existing == listener)
+    }
+}
@@ -0,0 +1,220 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use js::conversions::ToJSValConvertible;
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
+use script_traits::extension::{
+    ExtensionApiError, ExtensionApiResult, ExtensionId, ExtensionMessage, ExtensionMessageTarget,
+    ExtensionRequest,
+};
+use script_traits::StructuredSerializedData;
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::codegen::Bindings::ExtensionRuntimeBinding::{
+    ExtensionMessageSender, ExtensionRuntimeMethods,
+};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::bindings::structuredclone;
+use crate::dom::extensionbrowser::call_extension_api;
+use crate::dom::extensionevent::ExtensionEvent;
+use crate::dom::extensiontabs::extension_tab_dictionary;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::window::Window;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+
+#[dom_struct]
+pub struct ExtensionRuntime {
+    reflector_: Reflector,
+    #[no_trace]
+    extension_id: ExtensionId,
+    on_message: Dom<ExtensionEvent>,
+}
+
+impl ExtensionRuntime {
+    fn new_inherited(extension_id: ExtensionId, on_message: &ExtensionEvent) -> ExtensionRuntime {
+        ExtensionRuntime {
+            reflector_: Reflector::new(),
+            extension_id,
+            on_message: Dom::from_ref(on_message),
+        }
+    }
+
+    pub fn new(window: &Window, extension_id: ExtensionId) -> DomRoot<ExtensionRuntime> {
+        let on_message = ExtensionEvent::new(window);
+        reflect_dom_object(
+            Box::new(ExtensionRuntime::new_inherited(extension_id, &on_message)),
+            window,
+        )
+    }
+
+    /// Call the `onMessage` listeners with `message`, and send the first response
+    /// they give through `response_sender`. Must be called in the realm of the
+    /// isolated world of the extension.
+    pub fn dispatch_message(
+        &self,
+        message: ExtensionMessage,
+        response_sender: IpcSender<ExtensionApiResult<StructuredSerializedData>>,
+        comp: InRealm,
+    ) {
+        let listeners = self.on_message.listeners();
+        if listeners.is_empty() {
+            let _ = response_sender.send(Err(ExtensionApiError::NoReceivingEnd));
+            return;
+        }
+
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let mut data = UndefinedValue());
+        if structuredclone::read_in_current_realm(message.data, data.handle_mut()).is_err() {
+            warn!("Failed to deserialize extension message");
+            let _ = response_sender.send(Err(ExtensionApiError::NoReceivingEnd));
+            return;
+        }
+        let sender = ExtensionMessageSender {
+            id: Some(DOMString::from(self.extension_id.to_string())),
+            tab: message.sender_tab.map(extension_tab_dictionary),
+            url: Some(USVString(message.sender_url.into_string())),
+        };
+        rooted!(in(*cx) let mut sender_value = UndefinedValue());
+        #[allow(unsafe_code)]
+        unsafe {
+            sender.to_jsval(*cx, sender_value.handle_mut());
+        }
+
+        // As in Firefox, the first listener to return something other than `undefined`
+        // answers the message, possibly with a promise that is waited for.
+        for listener in listeners {
+            let arguments = vec![data.handle(), sender_value.handle()];
+            let response = match listener.Call__(arguments, ExceptionHandling::Report) {
+                Ok(response) => response,
+                Err(_) => continue,
+            };
+            if response.is_undefined() {
+                continue;
+            }
+            rooted!(in(*cx) let response = response);
+            let global = self.global();
+            let promise = match Promise::new_resolved(&global, cx, response.handle()) {
+                Ok(promise) => promise,
+                Err(_) => continue,
+            };
+            let handler = PromiseNativeHandler::new(
+                &global,
+                Some(ResponseHandler::new(response_sender.clone(), false)),
+                Some(ResponseHandler::new(response_sender, true)),
+            );
+            promise.append_native_handler(&handler, comp);
+            return;
+        }
+
+        rooted!(in(*cx) let undefined = UndefinedValue());
+        let _ = response_sender.send(
+            structuredclone::write(cx, undefined.handle(), None)
+                .map_err(|_| ExtensionApiError::UnserializableResponse),
+        );
+    }
+}
+
+impl ExtensionRuntimeMethods for ExtensionRuntime {
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/runtime/id
+    fn Id(&self) -> DOMString {
+        DOMString::from(self.extension_id.to_string())
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/runtime/onMessage
+    fn OnMessage(&self) -> DomRoot<ExtensionEvent> {
+        DomRoot::from_ref(&self.on_message)
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/runtime/sendMessage
+    fn SendMessage(&self, cx: JSContext, message: HandleValue, comp: InRealm) -> Rc<Promise> {
+        send_extension_message(
+            self,
+            self.extension_id,
+            cx,
+            ExtensionMessageTarget::Background,
+            message,
+            comp,
+        )
+    }
+}
+
+/// Send `message` to the scripts of the extension at `target`, returning a promise
+/// for their response.
+pub fn send_extension_message(
+    object: &impl DomObject,
+    extension_id: ExtensionId,
+    cx: JSContext,
+    target: ExtensionMessageTarget,
+    message: HandleValue,
+    comp: InRealm,
+) -> Rc<Promise> {
+    let promise = Promise::new_in_current_realm(comp);
+    let data = match structuredclone::write(cx, message, None) {
+        Ok(data) => data,
+        Err(error) => {
+            promise.reject_error(error);
+            return promise;
+        },
+    };
+    call_extension_api(
+        object,
+        extension_id,
+        &promise,
+        |sender| ExtensionRequest::SendMessage(target, data, sender),
+        |promise, response| {
+            let cx = GlobalScope::get_cx();
+            let _ac = enter_realm(promise);
+            rooted!(in(*cx) let mut value = UndefinedValue());
+            match structuredclone::read_in_current_realm(response, value.handle_mut()) {
+                Ok(_) => promise.resolve(cx, value.handle()),
+                Err(_) => promise.resolve_native(&()),
+            }
+        },
+    );
+    promise
+}
+
+/// Replies to an extension message once the promise returned by a listener is
+/// settled.
+#[derive(JSTraceable, MallocSizeOf)]
+struct ResponseHandler {
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    response_sender: IpcSender<ExtensionApiResult<StructuredSerializedData>>,
+    rejected: bool,
+}
+
+impl ResponseHandler {
+    fn new(
+        response_sender: IpcSender<ExtensionApiResult<StructuredSerializedData>>,
+        rejected: bool,
+    ) -> Box<dyn Callback> {
+        Box::new(ResponseHandler {
+            response_sender,
+            rejected,
+        })
+    }
+}
+
+impl Callback for ResponseHandler {
+    fn callback(&self, cx: JSContext, v: HandleValue, _realm: InRealm) {
+        let response = if self.rejected {
+            Err(ExtensionApiError::ResponseRejected)
+        } else {
+            structuredclone::write(cx, v, None)
+                .map_err(|_| ExtensionApiError::UnserializableResponse)
+        };
+        let _ = self.response_sender.send(response);
+    }
+}
@@ -0,0 +1,39 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use script_traits::extension::ExtensionId;
+
+use crate::dom::bindings::codegen::Bindings::ExtensionStorageBinding::ExtensionStorageMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::extensionstoragearea::ExtensionStorageArea;
+use crate::dom::window::Window;
+
+#[dom_struct]
+pub struct ExtensionStorage {
+    reflector_: Reflector,
+    local: Dom<ExtensionStorageArea>,
+}
+
+impl ExtensionStorage {
+    fn new_inherited(local: &ExtensionStorageArea) -> ExtensionStorage {
+        ExtensionStorage {
+            reflector_: Reflector::new(),
+            local: Dom::from_ref(local),
+        }
+    }
+
+    pub fn new(window: &Window, extension_id: ExtensionId) -> DomRoot<ExtensionStorage> {
+        let local = ExtensionStorageArea::new(window, extension_id);
+        reflect_dom_object(Box::new(ExtensionStorage::new_inherited(&local)), window)
+    }
+}
+
+impl ExtensionStorageMethods for ExtensionStorage {
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/storage/local
+    fn Local(&self) -> DomRoot<ExtensionStorageArea> {
+        DomRoot::from_ref(&self.local)
+    }
+}
@@ -0,0 +1,171 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use js::jsapi::{JSObject, JS_NewPlainObject, JSITER_OWNONLY, JSPROP_ENUMERATE};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::wrappers::{GetPropertyKeys, JS_DefineUCProperty2, JS_GetPropertyById};
+use js::rust::IdVector;
+use script_traits::extension::{ExtensionApiResult, ExtensionId, ExtensionRequest};
+use script_traits::StructuredSerializedData;
+
+use crate::dom::bindings::codegen::Bindings::ExtensionStorageAreaBinding::ExtensionStorageAreaMethods;
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use crate::dom::bindings::conversions::jsid_to_string;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::structuredclone;
+use crate::dom::extensionbrowser::call_extension_api;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+
+/// `browser.storage.local`. The values are structured-cloned, and kept by the
+/// constellation, on disk if Servo has a config directory.
+#[dom_struct]
+pub struct ExtensionStorageArea {
+    reflector_: Reflector,
+    #[no_trace]
+    extension_id: ExtensionId,
+}
+
+impl ExtensionStorageArea {
+    fn new_inherited(extension_id: ExtensionId) -> ExtensionStorageArea {
+        ExtensionStorageArea {
+            reflector_: Reflector::new(),
+            extension_id,
+        }
+    }
+
+    pub fn new(window: &Window, extension_id: ExtensionId) -> DomRoot<ExtensionStorageArea> {
+        reflect_dom_object(
+            Box::new(ExtensionStorageArea::new_inherited(extension_id)),
+            window,
+        )
+    }
+
+    fn call(
+        &self,
+        comp: InRealm,
+        request: impl FnOnce(IpcSender<ExtensionApiResult<()>>) -> ExtensionRequest,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        call_extension_api(self, self.extension_id, &promise, request, |promise, ()| {
+            promise.resolve_native(&())
+        });
+        promise
+    }
+}
+
+impl ExtensionStorageAreaMethods for ExtensionStorageArea {
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/storage/StorageArea/get
+    fn Get(&self, keys: Option<StringOrStringSequence>, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        call_extension_api(
+            self,
+            self.extension_id,
+            &promise,
+            |sender| ExtensionRequest::StorageGet(keys.map(keys_to_vec), sender),
+            resolve_with_items,
+        );
+        promise
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/storage/StorageArea/set
+    fn Set(&self, cx: JSContext, items: *mut JSObject, comp: InRealm) -> Rc<Promise> {
+        let items = match serialize_items(cx, items) {
+            Ok(items) => items,
+            Err(error) => {
+                let promise = Promise::new_in_current_realm(comp);
+                promise.reject_error(error);
+                return promise;
+            },
+        };
+        self.call(comp, |sender| ExtensionRequest::StorageSet(items, sender))
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/storage/StorageArea/remove
+    fn Remove(&self, keys: StringOrStringSequence, comp: InRealm) -> Rc<Promise> {
+        let keys = keys_to_vec(keys);
+        self.call(comp, |sender| ExtensionRequest::StorageRemove(keys, sender))
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/storage/StorageArea/clear
+    fn Clear(&self, comp: InRealm) -> Rc<Promise> {
+        self.call(comp, ExtensionRequest::StorageClear)
+    }
+}
+
+fn keys_to_vec(keys: StringOrStringSequence) -> Vec<String> {
+    match keys {
+        StringOrStringSequence::String(key) => vec![key.into()],
+        StringOrStringSequence::StringSequence(keys) => keys.into_iter().map(Into::into).collect(),
+    }
+}
+
+/// Structured-serialize the values of the own enumerable properties of `items`.
+#[allow(unsafe_code)]
+fn serialize_items(
+    cx: JSContext,
+    items: *mut JSObject,
+) -> Fallible<Vec<(String, StructuredSerializedData)>> {
+    rooted!(in(*cx) let items = items);
+    let mut ids = unsafe { IdVector::new(*cx) };
+    if !unsafe { GetPropertyKeys(*cx, items.handle(), JSITER_OWNONLY, ids.handle_mut()) } {
+        return Err(Error::JSFailed);
+    }
+
+    let mut serialized = Vec::with_capacity(ids.len());
+    for id in &*ids {
+        rooted!(in(*cx) let id = *id);
+        let key = match unsafe { jsid_to_string(*cx, id.handle()) } {
+            Some(key) => key,
+            None => continue,
+        };
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        if !unsafe { JS_GetPropertyById(*cx, items.handle(), id.handle(), value.handle_mut()) } {
+            return Err(Error::JSFailed);
+        }
+        serialized.push((
+            key.into(),
+            structuredclone::write(cx, value.handle(), None)?,
+        ));
+    }
+    Ok(serialized)
+}
+
+/// Resolve `promise` with an object holding the deserialized stored `items`.
+#[allow(unsafe_code)]
+fn resolve_with_items(promise: &Promise, items: Vec<(String, StructuredSerializedData)>) {
+    let cx = GlobalScope::get_cx();
+    let _ac = enter_realm(promise);
+    rooted!(in(*cx) let object = unsafe { JS_NewPlainObject(*cx) });
+    assert!(!object.is_null());
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    for (key, data) in items {
+        if structuredclone::read_in_current_realm(data, value.handle_mut()).is_err() {
+            warn!("Failed to deserialize the stored value of {:?}", key);
+            continue;
+        }
+        let key: Vec<u16> = key.encode_utf16().collect();
+        unsafe {
+            assert!(JS_DefineUCProperty2(
+                *cx,
+                object.handle(),
+                key.as_ptr(),
+                key.len(),
+                value.handle(),
+                JSPROP_ENUMERATE as u32,
+            ));
+        }
+    }
+    rooted!(in(*cx) let result = ObjectValue(object.get()));
+    promise.resolve(cx, result.handle());
+}
@@ -0,0 +1,113 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::rust::HandleValue;
+use script_traits::content_script::MatchPattern;
+use script_traits::extension::{
+    self, ExtensionId, ExtensionMessageTarget, ExtensionRequest, ExtensionTabQuery,
+};
+
+use crate::dom::bindings::codegen::Bindings::ExtensionTabsBinding::{
+    ExtensionTab, ExtensionTabQueryInfo, ExtensionTabsMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::extensionbrowser::call_extension_api;
+use crate::dom::extensionruntime::send_extension_message;
+use crate::dom::promise::Promise;
+use crate::dom::window::Window;
+use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
+
+#[dom_struct]
+pub struct ExtensionTabs {
+    reflector_: Reflector,
+    #[no_trace]
+    extension_id: ExtensionId,
+}
+
+impl ExtensionTabs {
+    fn new_inherited(extension_id: ExtensionId) -> ExtensionTabs {
+        ExtensionTabs {
+            reflector_: Reflector::new(),
+            extension_id,
+        }
+    }
+
+    pub fn new(window: &Window, extension_id: ExtensionId) -> DomRoot<ExtensionTabs> {
+        reflect_dom_object(Box::new(ExtensionTabs::new_inherited(extension_id)), window)
+    }
+}
+
+impl ExtensionTabsMethods for ExtensionTabs {
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/tabs/query
+    fn Query(&self, query_info: &ExtensionTabQueryInfo, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let patterns = match query_info.url {
+            Some(StringOrStringSequence::String(ref pattern)) => vec![pattern.clone()],
+            Some(StringOrStringSequence::StringSequence(ref patterns)) => patterns.clone(),
+            None => vec![],
+        };
+        let url = match patterns
+            .iter()
+            .map(|pattern| MatchPattern::parse(pattern))
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(url) => url,
+            None => {
+                promise.reject_error(Error::Type("Invalid URL match pattern".to_owned()));
+                return promise;
+            },
+        };
+        let query = ExtensionTabQuery {
+            active: query_info.active,
+            url,
+        };
+        call_extension_api(
+            self,
+            self.extension_id,
+            &promise,
+            |sender| ExtensionRequest::QueryTabs(query, sender),
+            |promise, tabs: Vec<extension::ExtensionTab>| {
+                let tabs: Vec<_> = tabs.into_iter().map(extension_tab_dictionary).collect();
+                promise.resolve_native(&tabs);
+            },
+        );
+        promise
+    }
+
+    // https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/tabs/sendMessage
+    fn SendMessage(
+        &self,
+        cx: JSContext,
+        tab_id: i32,
+        message: HandleValue,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        send_extension_message(
+            self,
+            self.extension_id,
+            cx,
+            ExtensionMessageTarget::Tab(tab_id),
+            message,
+            comp,
+        )
+    }
+}
+
+/// Describe a tab to the scripts of an extension.
+pub fn extension_tab_dictionary(tab: extension::ExtensionTab) -> ExtensionTab {
+    ExtensionTab {
+        id: tab.id,
+        url: tab.url.map(|url| USVString(url.into_string())),
+        title: tab.title.map(DOMString::from),
+        active: tab.active,
+    }
+}
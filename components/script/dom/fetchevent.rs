@@ -0,0 +1,368 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use http::StatusCode;
+use ipc_channel::ipc::IpcSender;
+use js::jsval::UndefinedValue;
+use js::rust::{HandleObject, HandleValue};
+use net_traits::CustomResponse;
+use servo_atoms::Atom;
+
+use crate::body::BodyMixin;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::FetchEventBinding;
+use crate::dom::bindings::codegen::Bindings::FetchEventBinding::FetchEventMethods;
+use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseMethods;
+use crate::dom::bindings::conversions::root_from_handlevalue;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::blob::Blob;
+use crate::dom::event::Event;
+use crate::dom::extendableevent::ExtendableEvent;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::request::Request;
+use crate::dom::response::Response;
+use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+
+/// The channel through which the resource thread waits for the response of a service
+/// worker to a fetch. `None` lets the fetch go to the network.
+type ResponseSender = IpcSender<Option<CustomResponse>>;
+
+// https://w3c.github.io/ServiceWorker/#fetchevent-interface
+#[dom_struct]
+pub struct FetchEvent {
+    event: ExtendableEvent,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-request>
+    request: Dom<Request>,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-preloadresponse>
+    #[ignore_malloc_size_of = "Rc"]
+    preload_response: Rc<Promise>,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-clientid>
+    client_id: DOMString,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-resultingclientid>
+    resulting_client_id: DOMString,
+    /// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-replacesclientid>
+    replaces_client_id: DOMString,
+    /// <https://w3c.github.io/ServiceWorker/#fetchevent-respond-with-entered-flag>
+    respond_with_entered: Cell<bool>,
+    /// Where to send the response of the worker, for events fired by the user agent
+    /// which have not been answered yet.
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    response_sender: DomRefCell<Option<ResponseSender>>,
+}
+
+impl FetchEvent {
+    #[allow(crown::unrooted_must_root)]
+    fn new_inherited(
+        request: &Request,
+        preload_response: Rc<Promise>,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+        replaces_client_id: DOMString,
+        response_sender: Option<ResponseSender>,
+    ) -> FetchEvent {
+        FetchEvent {
+            event: ExtendableEvent::new_inherited(),
+            request: Dom::from_ref(request),
+            preload_response,
+            client_id,
+            resulting_client_id,
+            replaces_client_id,
+            respond_with_entered: Cell::new(false),
+            response_sender: DomRefCell::new(response_sender),
+        }
+    }
+
+    /// Create the event fired for a fetch handed to the worker, whose response is sent
+    /// to `response_sender`.
+    pub fn new(
+        worker: &ServiceWorkerGlobalScope,
+        type_: Atom,
+        request: &Request,
+        preload_response: Rc<Promise>,
+        resulting_client_id: DOMString,
+        response_sender: ResponseSender,
+    ) -> DomRoot<FetchEvent> {
+        Self::new_with_proto(
+            worker,
+            None,
+            type_,
+            false,
+            true,
+            request,
+            preload_response,
+            DOMString::new(),
+            resulting_client_id,
+            DOMString::new(),
+            Some(response_sender),
+        )
+    }
+
+    #[allow(crown::unrooted_must_root)]
+    fn new_with_proto(
+        worker: &ServiceWorkerGlobalScope,
+        proto: Option<HandleObject>,
+        type_: Atom,
+        bubbles: bool,
+        cancelable: bool,
+        request: &Request,
+        preload_response: Rc<Promise>,
+        client_id: DOMString,
+        resulting_client_id: DOMString,
+        replaces_client_id: DOMString,
+        response_sender: Option<ResponseSender>,
+    ) -> DomRoot<FetchEvent> {
+        let ev = reflect_dom_object_with_proto(
+            Box::new(FetchEvent::new_inherited(
+                request,
+                preload_response,
+                client_id,
+                resulting_client_id,
+                replaces_client_id,
+                response_sender,
+            )),
+            worker,
+            proto,
+        );
+        {
+            let event = ev.upcast::<Event>();
+            event.init_event(type_, bubbles, cancelable);
+        }
+        ev
+    }
+
+    #[allow(crown::unrooted_must_root, non_snake_case)]
+    pub fn Constructor(
+        worker: &ServiceWorkerGlobalScope,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: &FetchEventBinding::FetchEventInit,
+    ) -> Fallible<DomRoot<FetchEvent>> {
+        let preload_response = match init.preloadResponse {
+            Some(ref promise) => promise.clone(),
+            None => {
+                let global = worker.upcast::<GlobalScope>();
+                rooted!(in(*GlobalScope::get_cx()) let undefined = UndefinedValue());
+                Promise::new_resolved(global, GlobalScope::get_cx(), undefined.handle())?
+            },
+        };
+        Ok(FetchEvent::new_with_proto(
+            worker,
+            proto,
+            Atom::from(type_),
+            init.parent.parent.bubbles,
+            init.parent.parent.cancelable,
+            &init.request,
+            preload_response,
+            init.clientId.clone(),
+            init.resultingClientId.clone(),
+            init.replacesClientId.clone(),
+            None,
+        ))
+    }
+
+    /// <https://w3c.github.io/ServiceWorker/#handle-fetch> step 24.3.4 onwards, once the
+    /// event was dispatched: if no listener called `respondWith()`, the fetch is answered
+    /// with the navigation preload response if one was requested, so that the navigation
+    /// does not fetch the same resource again, and goes to the network otherwise.
+    pub fn respond_after_dispatch(&self, preload_requested: bool, comp: InRealm) {
+        if self.respond_with_entered.get() {
+            return;
+        }
+        let response_sender = match self.response_sender.borrow_mut().take() {
+            Some(response_sender) => response_sender,
+            None => return,
+        };
+        if preload_requested {
+            respond_with_promise(
+                &self.global(),
+                &self.preload_response,
+                response_sender,
+                comp,
+            );
+        } else {
+            let _ = response_sender.send(None);
+        }
+    }
+}
+
+/// Send the response `promise` is fulfilled with to `response_sender`, once its body has
+/// been read.
+fn respond_with_promise(
+    global: &GlobalScope,
+    promise: &Promise,
+    response_sender: ResponseSender,
+    comp: InRealm,
+) {
+    let handler = PromiseNativeHandler::new(
+        global,
+        Some(Box::new(RespondWithHandler {
+            response_sender: response_sender.clone(),
+        })),
+        Some(Box::new(NetworkErrorHandler { response_sender })),
+    );
+    promise.append_native_handler(&handler, comp);
+}
+
+/// <https://w3c.github.io/ServiceWorker/#dom-fetchevent-respondwith> step 8: reads the
+/// body of the response a `respondWith()` promise was fulfilled with.
+#[derive(JSTraceable, MallocSizeOf)]
+struct RespondWithHandler {
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    response_sender: ResponseSender,
+}
+
+impl Callback for RespondWithHandler {
+    fn callback(&self, cx: JSContext, v: HandleValue, realm: InRealm) {
+        let response = match root_from_handlevalue::<Response>(v, *cx) {
+            Ok(response) => response,
+            Err(()) => return NetworkErrorHandler::send(&self.response_sender),
+        };
+        // Responses without a valid status, such as network errors and opaque
+        // redirects, cannot be handed to the resource thread.
+        let status = match StatusCode::from_u16(response.Status()) {
+            Ok(status) => status,
+            Err(_) => return NetworkErrorHandler::send(&self.response_sender),
+        };
+        if response.is_disturbed() || response.is_locked() {
+            return NetworkErrorHandler::send(&self.response_sender);
+        }
+        let status_text = String::from_utf8_lossy(&response.StatusText()).into_owned();
+        let headers = response.Headers().get_headers_list();
+
+        let _ac = enter_realm(&*response);
+        let handler = PromiseNativeHandler::new(
+            &response.global(),
+            Some(Box::new(ResponseBodyHandler {
+                response_sender: self.response_sender.clone(),
+                headers,
+                status,
+                status_text,
+            })),
+            Some(Box::new(NetworkErrorHandler {
+                response_sender: self.response_sender.clone(),
+            })),
+        );
+        response.Blob().append_native_handler(&handler, realm);
+    }
+}
+
+/// Sends a response to the resource thread once its body has been read as a blob.
+#[derive(JSTraceable, MallocSizeOf)]
+struct ResponseBodyHandler {
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    response_sender: ResponseSender,
+    #[ignore_malloc_size_of = "Defined in hyper"]
+    #[no_trace]
+    headers: http::HeaderMap,
+    #[ignore_malloc_size_of = "Defined in hyper"]
+    #[no_trace]
+    status: StatusCode,
+    status_text: String,
+}
+
+impl Callback for ResponseBodyHandler {
+    fn callback(&self, cx: JSContext, v: HandleValue, _realm: InRealm) {
+        let body = match root_from_handlevalue::<Blob>(v, *cx).map(|blob| blob.get_bytes()) {
+            Ok(Ok(body)) => body,
+            _ => return NetworkErrorHandler::send(&self.response_sender),
+        };
+        let response = CustomResponse::new(
+            self.headers.clone(),
+            (self.status, self.status_text.clone()),
+            body,
+        );
+        let _ = self.response_sender.send(Some(response));
+    }
+}
+
+/// Answers a fetch whose `respondWith()` promise was rejected, or fulfilled with
+/// something that is not a usable response.
+#[derive(JSTraceable, MallocSizeOf)]
+struct NetworkErrorHandler {
+    #[ignore_malloc_size_of = "Channels are hard"]
+    #[no_trace]
+    response_sender: ResponseSender,
+}
+
+impl NetworkErrorHandler {
+    fn send(response_sender: &ResponseSender) {
+        // TODO: This should be a network error, but the resource thread can only be told
+        // to answer the fetch itself or to use a response, so it goes to the network.
+        let _ = response_sender.send(None);
+    }
+}
+
+impl Callback for NetworkErrorHandler {
+    fn callback(&self, _cx: JSContext, _v: HandleValue, _realm: InRealm) {
+        NetworkErrorHandler::send(&self.response_sender);
+    }
+}
+
+impl FetchEventMethods for FetchEvent {
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-request
+    fn Request(&self) -> DomRoot<Request> {
+        DomRoot::from_ref(&*self.request)
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-preloadresponse
+    fn PreloadResponse(&self) -> Rc<Promise> {
+        self.preload_response.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-clientid
+    fn ClientId(&self) -> DOMString {
+        self.client_id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-resultingclientid
+    fn ResultingClientId(&self) -> DOMString {
+        self.resulting_client_id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-replacesclientid
+    fn ReplacesClientId(&self) -> DOMString {
+        self.replaces_client_id.clone()
+    }
+
+    // https://w3c.github.io/ServiceWorker/#dom-fetchevent-respondwith
+    fn RespondWith(&self, r: &Promise, comp: InRealm) -> ErrorResult {
+        // Steps 1 and 2.
+        let event = self.upcast::<Event>();
+        if !event.dispatching() || self.respond_with_entered.get() {
+            return Err(Error::InvalidState);
+        }
+        // Steps 4 and 5. Step 3, extending the lifetime of the event, is not needed
+        // since the worker is not stopped while it has pending fetches.
+        event.StopPropagation();
+        event.StopImmediatePropagation();
+        // Step 6.
+        self.respond_with_entered.set(true);
+        // Step 8. Events created by scripts have nobody to answer.
+        if let Some(response_sender) = self.response_sender.borrow_mut().take() {
+            respond_with_promise(&self.global(), r, response_sender, comp);
+        }
+        Ok(())
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.event.IsTrusted()
+    }
+}
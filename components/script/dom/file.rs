@@ -3,6 +3,7 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
+use embedder_traits::DragDataItem;
 use js::rust::HandleObject;
 use net_traits::filemanager_thread::SelectedFile;
 use script_traits::serializable::BlobImpl;
@@ -91,6 +92,19 @@ impl File {
         )
     }
 
+    // Construct from an item of the data of a drag, if it is a file
+    pub fn new_from_drag_data_item(window: &Window, item: DragDataItem) -> Option<DomRoot<File>> {
+        match item {
+            DragDataItem::File { name, type_, bytes } => Some(File::new(
+                window.upcast(),
+                BlobImpl::new_from_bytes(bytes, normalize_type_string(&type_)),
+                DOMString::from(name),
+                None,
+            )),
+            DragDataItem::Text { .. } => None,
+        }
+    }
+
     // https://w3c.github.io/FileAPI/#file-constructor
     #[allow(non_snake_case)]
     pub fn Constructor(
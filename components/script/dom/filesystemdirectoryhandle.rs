@@ -0,0 +1,147 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use net_traits::file_system_thread::{FileSystemEntryKind, FileSystemThreadMsg};
+
+use crate::dom::bindings::codegen::Bindings::FileSystemDirectoryHandleBinding::{
+    FileSystemDirectoryHandleMethods, FileSystemGetDirectoryOptions, FileSystemGetFileOptions,
+    FileSystemRemoveOptions,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::{DOMString, USVString};
+use crate::dom::filesystemfilehandle::FileSystemFileHandle;
+use crate::dom::filesystemhandle::{check_file_name, file_system_request, FileSystemHandle};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+
+/// <https://fs.spec.whatwg.org/#filesystemdirectoryhandle>
+#[dom_struct]
+pub struct FileSystemDirectoryHandle {
+    handle: FileSystemHandle,
+}
+
+impl FileSystemDirectoryHandle {
+    fn new_inherited(path: Vec<String>) -> FileSystemDirectoryHandle {
+        FileSystemDirectoryHandle {
+            handle: FileSystemHandle::new_inherited(FileSystemEntryKind::Directory, path),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, path: Vec<String>) -> DomRoot<FileSystemDirectoryHandle> {
+        reflect_dom_object(
+            Box::new(FileSystemDirectoryHandle::new_inherited(path)),
+            global,
+        )
+    }
+
+    /// Get the entry `name` of `kind` in this directory, creating it if asked to, and return
+    /// its path.
+    fn get_entry(
+        &self,
+        name: USVString,
+        kind: FileSystemEntryKind,
+        create: bool,
+    ) -> Fallible<Vec<String>> {
+        check_file_name(&name)?;
+        let global = self.global();
+        let path = self.handle.path().to_vec();
+        let origin = global.origin().immutable().clone();
+        file_system_request(&global, |sender| FileSystemThreadMsg::GetEntry {
+            origin,
+            path: path.clone(),
+            name: name.0.clone(),
+            kind,
+            create,
+            sender,
+        })?;
+        let mut entry_path = path;
+        entry_path.push(name.0);
+        Ok(entry_path)
+    }
+}
+
+impl FileSystemDirectoryHandleMethods for FileSystemDirectoryHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getfilehandle>
+    fn GetFileHandle(
+        &self,
+        name: USVString,
+        options: &FileSystemGetFileOptions,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        match self.get_entry(name, FileSystemEntryKind::File, options.create) {
+            Ok(path) => promise.resolve_native(&FileSystemFileHandle::new(&self.global(), path)),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getdirectoryhandle>
+    fn GetDirectoryHandle(
+        &self,
+        name: USVString,
+        options: &FileSystemGetDirectoryOptions,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        match self.get_entry(name, FileSystemEntryKind::Directory, options.create) {
+            Ok(path) => {
+                promise.resolve_native(&FileSystemDirectoryHandle::new(&self.global(), path))
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-removeentry>
+    fn RemoveEntry(
+        &self,
+        name: USVString,
+        options: &FileSystemRemoveOptions,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let global = self.global();
+        let origin = global.origin().immutable().clone();
+        let result = check_file_name(&name).and_then(|_| {
+            file_system_request(&global, |sender| FileSystemThreadMsg::RemoveEntry {
+                origin,
+                path: self.handle.path().to_vec(),
+                name: name.0,
+                recursive: options.recursive,
+                sender,
+            })
+        });
+        match result {
+            Ok(()) => promise.resolve_native(&()),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-resolve>
+    fn Resolve(&self, possible_descendant: &FileSystemHandle, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let path = self.handle.path();
+        let descendant_path = possible_descendant.path();
+        let relative_path: Option<Vec<DOMString>> = if descendant_path.starts_with(path) {
+            Some(
+                descendant_path[path.len()..]
+                    .iter()
+                    .map(|name| DOMString::from(name.as_str()))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+        promise.resolve_native(&relative_path);
+        promise
+    }
+}
@@ -0,0 +1,79 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use net_traits::file_system_thread::{FileSystemEntryKind, FileSystemThreadMsg};
+use script_traits::serializable::BlobImpl;
+
+use crate::dom::bindings::codegen::Bindings::FileSystemFileHandleBinding::FileSystemFileHandleMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::file::File;
+use crate::dom::filesystemhandle::{file_system_request, FileSystemHandle};
+use crate::dom::filesystemsyncaccesshandle::FileSystemSyncAccessHandle;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+
+/// <https://fs.spec.whatwg.org/#filesystemfilehandle>
+#[dom_struct]
+pub struct FileSystemFileHandle {
+    handle: FileSystemHandle,
+}
+
+impl FileSystemFileHandle {
+    fn new_inherited(path: Vec<String>) -> FileSystemFileHandle {
+        FileSystemFileHandle {
+            handle: FileSystemHandle::new_inherited(FileSystemEntryKind::File, path),
+        }
+    }
+
+    pub fn new(global: &GlobalScope, path: Vec<String>) -> DomRoot<FileSystemFileHandle> {
+        reflect_dom_object(Box::new(FileSystemFileHandle::new_inherited(path)), global)
+    }
+}
+
+impl FileSystemFileHandleMethods for FileSystemFileHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-getfile>
+    fn GetFile(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let global = self.global();
+        let origin = global.origin().immutable().clone();
+        let path = self.handle.path().to_vec();
+        let name = path.last().cloned().unwrap_or_default();
+        match file_system_request(&global, |sender| {
+            FileSystemThreadMsg::GetFile(sender, origin, path)
+        }) {
+            Ok((contents, modified)) => {
+                let file = File::new(
+                    &global,
+                    BlobImpl::new_from_bytes(contents, String::new()),
+                    DOMString::from(name),
+                    Some(modified),
+                );
+                promise.resolve_native(&file);
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-createsyncaccesshandle>
+    fn CreateSyncAccessHandle(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let global = self.global();
+        let origin = global.origin().immutable().clone();
+        let path = self.handle.path().to_vec();
+        match file_system_request(&global, |sender| {
+            FileSystemThreadMsg::CreateSyncAccessHandle(sender, origin, path)
+        }) {
+            Ok(id) => promise.resolve_native(&FileSystemSyncAccessHandle::new(&global, id)),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
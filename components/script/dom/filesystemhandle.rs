@@ -0,0 +1,116 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::file_system_thread::{
+    is_valid_file_name, FileSystemEntryKind, FileSystemError, FileSystemResult, FileSystemThreadMsg,
+};
+use net_traits::IpcSend;
+use profile_traits::ipc;
+use serde::{Deserialize, Serialize};
+
+use crate::dom::bindings::codegen::Bindings::FileSystemHandleBinding::{
+    FileSystemHandleKind, FileSystemHandleMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::Reflector;
+use crate::dom::bindings::str::USVString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+
+/// Send the message `message` builds around the reply channel to the file system thread, and
+/// wait for the reply.
+pub fn file_system_request<T>(
+    global: &GlobalScope,
+    message: impl FnOnce(IpcSender<FileSystemResult<T>>) -> FileSystemThreadMsg,
+) -> Fallible<T>
+where
+    T: for<'de> Deserialize<'de> + Serialize,
+{
+    let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+    global
+        .resource_threads()
+        .send(message(sender))
+        .map_err(|_| Error::Operation)?;
+    match receiver.recv() {
+        Ok(result) => result.map_err(file_system_error),
+        Err(_) => Err(Error::Operation),
+    }
+}
+
+/// <https://fs.spec.whatwg.org/#valid-file-name>
+///
+/// Names are checked before they are sent to the file system thread, which would refuse them.
+pub fn check_file_name(name: &str) -> Fallible<()> {
+    if is_valid_file_name(name) {
+        Ok(())
+    } else {
+        Err(file_system_error(FileSystemError::InvalidName))
+    }
+}
+
+fn file_system_error(error: FileSystemError) -> Error {
+    match error {
+        FileSystemError::NotFound => Error::NotFound,
+        FileSystemError::TypeMismatch => Error::TypeMismatch,
+        FileSystemError::InvalidModification => Error::InvalidModification,
+        FileSystemError::NoModificationAllowed => Error::NoModificationAllowed,
+        FileSystemError::InvalidState => Error::InvalidState,
+        FileSystemError::InvalidName => Error::Type("Name is not a valid file name".to_owned()),
+        FileSystemError::Io => Error::Operation,
+        FileSystemError::QuotaExceeded => Error::QuotaExceeded,
+    }
+}
+
+/// <https://fs.spec.whatwg.org/#filesystemhandle>
+#[dom_struct]
+pub struct FileSystemHandle {
+    reflector_: Reflector,
+    #[no_trace]
+    kind: FileSystemEntryKind,
+    /// The names of the directories leading to the entry from the root directory of the
+    /// origin private file system, followed by the name of the entry, and empty for the root
+    /// itself.
+    path: Vec<String>,
+}
+
+impl FileSystemHandle {
+    pub fn new_inherited(kind: FileSystemEntryKind, path: Vec<String>) -> FileSystemHandle {
+        FileSystemHandle {
+            reflector_: Reflector::new(),
+            kind,
+            path,
+        }
+    }
+
+    pub fn path(&self) -> &[String] {
+        &self.path
+    }
+}
+
+impl FileSystemHandleMethods for FileSystemHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemhandle-kind>
+    fn Kind(&self) -> FileSystemHandleKind {
+        match self.kind {
+            FileSystemEntryKind::File => FileSystemHandleKind::File,
+            FileSystemEntryKind::Directory => FileSystemHandleKind::Directory,
+        }
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemhandle-name>
+    fn Name(&self) -> USVString {
+        USVString(self.path.last().cloned().unwrap_or_default())
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemhandle-issameentry>
+    fn IsSameEntry(&self, other: &FileSystemHandle, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        promise.resolve_native(&(self.kind == other.kind && self.path == other.path));
+        promise
+    }
+}
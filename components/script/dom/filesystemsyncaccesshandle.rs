@@ -0,0 +1,177 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::file_system_thread::{FileSystemThreadMsg, SyncAccessHandleId};
+use net_traits::IpcSend;
+
+use crate::dom::bindings::codegen::Bindings::FileSystemSyncAccessHandleBinding::{
+    FileSystemReadWriteOptions, FileSystemSyncAccessHandleMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::ArrayBufferViewOrArrayBuffer;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::filesystemhandle::file_system_request;
+use crate::dom::globalscope::GlobalScope;
+
+/// <https://fs.spec.whatwg.org/#filesystemsyncaccesshandle>
+#[dom_struct]
+pub struct FileSystemSyncAccessHandle {
+    reflector_: Reflector,
+    #[no_trace]
+    id: SyncAccessHandleId,
+    /// Kept to close the access handle when it is collected without being closed.
+    #[ignore_malloc_size_of = "defined in ipc-channel"]
+    #[no_trace]
+    file_system_thread: IpcSender<FileSystemThreadMsg>,
+    /// <https://fs.spec.whatwg.org/#filesystemsyncaccesshandle-file-position-cursor>
+    file_position_cursor: Cell<u64>,
+    /// <https://fs.spec.whatwg.org/#filesystemsyncaccesshandle-state>
+    closed: Cell<bool>,
+}
+
+impl FileSystemSyncAccessHandle {
+    fn new_inherited(
+        id: SyncAccessHandleId,
+        file_system_thread: IpcSender<FileSystemThreadMsg>,
+    ) -> FileSystemSyncAccessHandle {
+        FileSystemSyncAccessHandle {
+            reflector_: Reflector::new(),
+            id,
+            file_system_thread,
+            file_position_cursor: Cell::new(0),
+            closed: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        id: SyncAccessHandleId,
+    ) -> DomRoot<FileSystemSyncAccessHandle> {
+        let file_system_thread = global.resource_threads().sender();
+        reflect_dom_object(
+            Box::new(FileSystemSyncAccessHandle::new_inherited(
+                id,
+                file_system_thread,
+            )),
+            global,
+        )
+    }
+
+    fn check_open(&self) -> Fallible<()> {
+        if self.closed.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FileSystemSyncAccessHandle {
+    fn drop(&mut self) {
+        if !self.closed.get() {
+            let _ = self
+                .file_system_thread
+                .send(FileSystemThreadMsg::Close(self.id));
+        }
+    }
+}
+
+impl FileSystemSyncAccessHandleMethods for FileSystemSyncAccessHandle {
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-read>
+    #[allow(unsafe_code)]
+    fn Read(
+        &self,
+        buffer: ArrayBufferViewOrArrayBuffer,
+        options: &FileSystemReadWriteOptions,
+    ) -> Fallible<u64> {
+        self.check_open()?;
+        let read_start = options.at.unwrap_or(self.file_position_cursor.get());
+        let length = match buffer {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(ref view) => view.len(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(ref array_buffer) => array_buffer.len(),
+        };
+        let id = self.id;
+        let data = file_system_request(&self.global(), |sender| {
+            FileSystemThreadMsg::Read(sender, id, read_start, length as u64)
+        })?;
+        let destination = match buffer {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(mut view) => unsafe {
+                view.as_mut_slice()
+            },
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(mut array_buffer) => unsafe {
+                array_buffer.as_mut_slice()
+            },
+        };
+        // No more than the length of the buffer is read.
+        destination[..data.len()].copy_from_slice(&data);
+        self.file_position_cursor
+            .set(read_start + data.len() as u64);
+        Ok(data.len() as u64)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-write>
+    fn Write(
+        &self,
+        buffer: ArrayBufferViewOrArrayBuffer,
+        options: &FileSystemReadWriteOptions,
+    ) -> Fallible<u64> {
+        self.check_open()?;
+        let write_position = options.at.unwrap_or(self.file_position_cursor.get());
+        let data = match buffer {
+            ArrayBufferViewOrArrayBuffer::ArrayBufferView(view) => view.to_vec(),
+            ArrayBufferViewOrArrayBuffer::ArrayBuffer(array_buffer) => array_buffer.to_vec(),
+        };
+        let id = self.id;
+        let written = file_system_request(&self.global(), |sender| {
+            FileSystemThreadMsg::Write(sender, id, write_position, data)
+        })?;
+        self.file_position_cursor.set(write_position + written);
+        Ok(written)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-truncate>
+    fn Truncate(&self, new_size: u64) -> Fallible<()> {
+        self.check_open()?;
+        let id = self.id;
+        file_system_request(&self.global(), |sender| {
+            FileSystemThreadMsg::Truncate(sender, id, new_size)
+        })?;
+        if self.file_position_cursor.get() > new_size {
+            self.file_position_cursor.set(new_size);
+        }
+        Ok(())
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-getsize>
+    fn GetSize(&self) -> Fallible<u64> {
+        self.check_open()?;
+        let id = self.id;
+        file_system_request(&self.global(), |sender| {
+            FileSystemThreadMsg::GetSize(sender, id)
+        })
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-flush>
+    fn Flush(&self) -> Fallible<()> {
+        self.check_open()?;
+        let id = self.id;
+        file_system_request(&self.global(), |sender| {
+            FileSystemThreadMsg::Flush(sender, id)
+        })
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemsyncaccesshandle-close>
+    fn Close(&self) {
+        if self.closed.replace(true) {
+            return;
+        }
+        let _ = self
+            .file_system_thread
+            .send(FileSystemThreadMsg::Close(self.id));
+    }
+}
@@ -4,21 +4,22 @@
 
 use std::borrow::Cow;
 use std::cell::Cell;
-use std::collections::hash_map::Entry;
+use std::collections::hash_map::{DefaultHasher, Entry};
 use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
 use std::ops::Index;
 use std::rc::Rc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread::JoinHandle;
-use std::time::Instant;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use std::{mem, ptr};
 
 use content_security_policy::CspList;
 use crossbeam_channel::Sender;
 use devtools_traits::{PageError, ScriptToDevtoolsControlMsg};
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, ScriptPolicy, UserAgentOverride};
 use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::{IsWrapper, UnwrapObjectDynamic};
@@ -30,19 +31,23 @@ use js::jsval::{JSVal, PrivateValue, UndefinedValue};
 use js::panic::maybe_resume_unwind;
 use js::rust::wrappers::{JS_ExecuteScript, JS_GetScriptPrivate};
 use js::rust::{
-    get_object_class, transform_str_to_source_text, CompileOptionsWrapper, HandleValue,
+    describe_scripted_caller, get_object_class, transform_str_to_source_text,
+    CompileOptionsWrapper, CustomAutoRooter, CustomAutoRooterGuard, HandleValue,
     MutableHandleValue, ParentRuntime, Runtime,
 };
 use js::{JSCLASS_IS_DOMJSCLASS, JSCLASS_IS_GLOBAL};
+use lazy_static::lazy_static;
 use msg::constellation_msg::{
     BlobId, BroadcastChannelRouterId, MessagePortId, MessagePortRouterId, PipelineId,
     ServiceWorkerId, ServiceWorkerRegistrationId,
 };
 use net_traits::blob_url_store::{get_blob_origin, BlobBuf};
+use net_traits::client_hints::UserAgentMetadata;
 use net_traits::filemanager_thread::{
     FileManagerResult, FileManagerThreadMsg, ReadFileProgress, RelativePos,
 };
 use net_traits::image_cache::ImageCache;
+use net_traits::reporting::{Report as NetReport, DEFAULT_ENDPOINT};
 use net_traits::request::Referrer;
 use net_traits::response::HttpsState;
 use net_traits::{CoreResourceMsg, CoreResourceThread, IpcSend, ResourceThreads};
@@ -54,12 +59,14 @@ use script_traits::{
     BroadcastMsg, GamepadEvent, GamepadUpdateType, MessagePortMsg, MsDuration, PortMessageTask,
     ScriptMsg, ScriptToConstellationChan, TimerEvent, TimerEventId, TimerSchedulerMsg, TimerSource,
 };
+use servo_config::pref;
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use uuid::Uuid;
 use webgpu::identity::WebGPUOpResult;
 use webgpu::{ErrorScopeId, WebGPUDevice};
 
 use super::bindings::trace::HashMapTracedValues;
+use crate::clock;
 use crate::dom::bindings::cell::{DomRefCell, RefMut};
 use crate::dom::bindings::codegen::Bindings::BroadcastChannelBinding::BroadcastChannelMethods;
 use crate::dom::bindings::codegen::Bindings::EventSourceBinding::EventSource_Binding::EventSourceMethods;
@@ -67,14 +74,19 @@ use crate::dom::bindings::codegen::Bindings::GamepadListBinding::GamepadList_Bin
 use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
     ImageBitmapOptions, ImageBitmapSource,
 };
+use crate::dom::bindings::codegen::Bindings::MessagePortBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::Navigator_Binding::NavigatorMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::Performance_Binding::PerformanceMethods;
-use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::PermissionState;
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
+use crate::dom::bindings::codegen::Bindings::WebGPUBinding::GPUDeviceLostReason;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerGlobalScopeBinding::WorkerGlobalScopeMethods;
 use crate::dom::bindings::conversions::{root_from_object, root_from_object_static};
-use crate::dom::bindings::error::{report_pending_exception, Error, ErrorInfo};
+use crate::dom::bindings::error::{report_pending_exception, Error, ErrorInfo, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::DomObject;
@@ -82,6 +94,7 @@ use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::settings_stack::{entry_global, incumbent_global, AutoEntryScript};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::bindings::weakref::{DOMTracker, WeakRef};
 use crate::dom::blob::Blob;
@@ -98,6 +111,7 @@ use crate::dom::file::File;
 use crate::dom::gamepad::Gamepad;
 use crate::dom::gpudevice::GPUDevice;
 use crate::dom::htmlscriptelement::{ScriptId, SourceCode};
+use crate::dom::idbfactory::IDBFactory;
 use crate::dom::identityhub::Identities;
 use crate::dom::imagebitmap::ImageBitmap;
 use crate::dom::messageevent::MessageEvent;
@@ -105,21 +119,27 @@ use crate::dom::messageport::MessagePort;
 use crate::dom::paintworkletglobalscope::PaintWorkletGlobalScope;
 use crate::dom::performance::Performance;
 use crate::dom::performanceobserver::VALID_ENTRY_TYPES;
+use crate::dom::permissionstatus::PermissionStatus;
 use crate::dom::promise::Promise;
 use crate::dom::readablestream::{ExternalUnderlyingSource, ReadableStream};
+use crate::dom::report::{Report, ReportData, SourceLocation};
+use crate::dom::reportingobserver::ReportingObserver;
 use crate::dom::serviceworker::ServiceWorker;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::dom::workletglobalscope::WorkletGlobalScope;
+use crate::isolated_world::page_global_for_isolated_world;
 use crate::microtask::{Microtask, MicrotaskQueue, UserMicrotask};
 use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
 use crate::script_module::{DynamicModuleList, ModuleScript, ModuleTree, ScriptFetchOptions};
 use crate::script_runtime::{
-    CommonScriptMsg, ContextForRequestInterrupt, JSContext as SafeJSContext, ScriptChan, ScriptPort,
+    self, CommonScriptMsg, ContextForRequestInterrupt, JSContext as SafeJSContext, ScriptChan,
+    ScriptPort,
 };
 use crate::script_thread::{MainThreadScriptChan, ScriptThread};
 use crate::task::TaskCanceller;
+use crate::task_source::database_access::DatabaseAccessTaskSource;
 use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::gamepad::GamepadTaskSource;
@@ -134,6 +154,10 @@ use crate::timers::{
     IsInterval, OneshotTimerCallback, OneshotTimerHandle, OneshotTimers, TimerCallback,
 };
 
+/// The most reports of a type kept in the report buffer of a global, past which the oldest
+/// ones are dropped.
+const MAX_BUFFERED_REPORTS_OF_TYPE: usize = 100;
+
 #[derive(JSTraceable)]
 pub struct AutoCloseWorker {
     /// <https://html.spec.whatwg.org/multipage/#dom-workerglobalscope-closing>
@@ -182,6 +206,7 @@ impl Drop for AutoCloseWorker {
 pub struct GlobalScope {
     eventtarget: EventTarget,
     crypto: MutNullableDom<Crypto>,
+    indexed_db: MutNullableDom<IDBFactory>,
 
     /// The message-port router id for this global, if it is managing ports.
     message_port_state: DomRefCell<MessagePortState>,
@@ -266,9 +291,6 @@ pub struct GlobalScope {
     #[no_trace]
     creation_url: Option<ServoUrl>,
 
-    /// A map for storing the previous permission state read results.
-    permission_state_invocation_results: DomRefCell<HashMap<String, PermissionState>>,
-
     /// The microtask queue associated with this global.
     ///
     /// It is refcounted because windows in the same script thread share the
@@ -285,6 +307,10 @@ pub struct GlobalScope {
     /// Vector storing references of all eventsources.
     event_source_tracker: DOMTracker<EventSource>,
 
+    /// The `PermissionStatus` objects handed to script, updated when the state of their
+    /// permission changes.
+    permission_status_tracker: DOMTracker<PermissionStatus>,
+
     /// Storage for watching rejected promises waiting for some client to
     /// consume their rejection.
     /// Promises in this list have been rejected in the last turn of the
@@ -310,6 +336,16 @@ pub struct GlobalScope {
     /// An optional string allowing the user agent to be set for testing.
     user_agent: Cow<'static, str>,
 
+    /// What the embedder or WebDriver chose to tell about the user agent instead of the
+    /// defaults, if anything.
+    #[ignore_malloc_size_of = "defined in embedder_traits"]
+    #[no_trace]
+    user_agent_override: DomRefCell<Option<UserAgentOverride>>,
+
+    /// What the embedder lets the script of this global do.
+    #[no_trace]
+    script_policy: Cell<ScriptPolicy>,
+
     /// Identity Manager for WebGPU resources
     #[ignore_malloc_size_of = "defined in wgpu"]
     #[no_trace]
@@ -334,6 +370,20 @@ pub struct GlobalScope {
 
     /// Is considered in a secure context
     inherited_secure_context: Option<bool>,
+
+    /// The reports generated in this global, which the reporting observers asking for them
+    /// get when they start observing.
+    /// <https://w3c.github.io/reporting/#report-buffer>
+    report_buffer: DomRefCell<Vec<Dom<Report>>>,
+
+    /// <https://w3c.github.io/reporting/#registered-reporting-observer-list>
+    registered_reporting_observers: DomRefCell<Vec<Dom<ReportingObserver>>>,
+
+    /// The endpoints, by name, that the `Reporting-Endpoints` header of the document of this
+    /// global configured.
+    /// <https://w3c.github.io/reporting/#document-configuration>
+    #[no_trace]
+    reporting_endpoints: DomRefCell<HashMap<String, ServoUrl>>,
 }
 
 /// A wrapper for glue-code between the ipc router and the event-loop.
@@ -759,6 +809,7 @@ impl GlobalScope {
             blob_state: DomRefCell::new(BlobState::UnManaged),
             eventtarget: EventTarget::new_inherited(),
             crypto: Default::default(),
+            indexed_db: Default::default(),
             registration_map: DomRefCell::new(HashMapTracedValues::new()),
             worker_map: DomRefCell::new(HashMapTracedValues::new()),
             pipeline_id,
@@ -777,14 +828,16 @@ impl GlobalScope {
             init_timers: Default::default(),
             origin,
             creation_url,
-            permission_state_invocation_results: Default::default(),
             microtask_queue,
             list_auto_close_worker: Default::default(),
             event_source_tracker: DOMTracker::new(),
+            permission_status_tracker: DOMTracker::new(),
             uncaught_rejections: Default::default(),
             consumed_rejections: Default::default(),
             is_headless,
             user_agent,
+            user_agent_override: Default::default(),
+            script_policy: Cell::new(ScriptPolicy::Allow),
             gpu_id_hub,
             gpu_devices: DomRefCell::new(HashMapTracedValues::new()),
             frozen_supported_performance_entry_types: DomRefCell::new(Default::default()),
@@ -792,6 +845,9 @@ impl GlobalScope {
             console_group_stack: DomRefCell::new(Vec::new()),
             dynamic_modules: DomRefCell::new(DynamicModuleList::new()),
             inherited_secure_context,
+            report_buffer: Default::default(),
+            registered_reporting_observers: Default::default(),
+            reporting_endpoints: Default::default(),
         }
     }
 
@@ -2098,12 +2154,6 @@ impl GlobalScope {
         }
     }
 
-    pub fn permission_state_invocation_results(
-        &self,
-    ) -> &DomRefCell<HashMap<String, PermissionState>> {
-        &self.permission_state_invocation_results
-    }
-
     pub fn track_worker(
         &self,
         closing: Arc<AtomicBool>,
@@ -2125,6 +2175,20 @@ impl GlobalScope {
         self.event_source_tracker.track(event_source);
     }
 
+    pub fn track_permission_status(&self, status: &PermissionStatus) {
+        self.permission_status_tracker.track(status);
+    }
+
+    /// The state of a permission changed for the origin of this global.
+    pub fn permission_changed(&self, name: PermissionName, state: PermissionState) {
+        self.permission_status_tracker
+            .for_each(|status: DomRoot<PermissionStatus>| {
+                if status.get_query() == name {
+                    status.update_state(state);
+                }
+            });
+    }
+
     pub fn close_event_sources(&self) -> bool {
         let mut canceled_any_fetch = false;
         self.event_source_tracker
@@ -2252,6 +2316,10 @@ impl GlobalScope {
         self.crypto.or_init(|| Crypto::new(self))
     }
 
+    pub fn indexed_db(&self) -> DomRoot<IDBFactory> {
+        self.indexed_db.or_init(|| IDBFactory::new(self))
+    }
+
     pub fn live_devtools_updates(&self) -> bool {
         self.devtools_wants_updates.get()
     }
@@ -2522,6 +2590,19 @@ impl GlobalScope {
         unreachable!();
     }
 
+    /// `TaskSource` to send messages to the database access task source of
+    /// this global scope.
+    /// <https://w3c.github.io/IndexedDB/#database-access-task-source>
+    pub fn database_access_task_source(&self) -> DatabaseAccessTaskSource {
+        if let Some(window) = self.downcast::<Window>() {
+            return window.task_manager().database_access_task_source();
+        }
+        if let Some(worker) = self.downcast::<WorkerGlobalScope>() {
+            return worker.database_access_task_source();
+        }
+        unreachable!();
+    }
+
     /// `TaskSource` to send messages to the gamepad task source of
     /// this global scope.
     /// <https://w3c.github.io/gamepad/#dfn-gamepad-task-source>
@@ -2761,6 +2842,34 @@ impl GlobalScope {
         }))
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-structuredclone>
+    pub fn structured_clone(
+        &self,
+        cx: SafeJSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        let mut rooted = CustomAutoRooter::new(
+            options
+                .transfer
+                .iter()
+                .map(|js: &RootedTraceableBox<Heap<*mut JSObject>>| js.get())
+                .collect(),
+        );
+        let transfer = CustomAutoRooterGuard::new(*cx, &mut rooted);
+
+        // Step 1. Let serialized be ? StructuredSerializeWithTransfer(value, options["transfer"]).
+        let data = structuredclone::write(cx, value, Some(transfer))?;
+
+        // Step 2. Let deserializeRecord be ? StructuredDeserializeWithTransfer(serialized,
+        // this's relevant realm).
+        rooted!(in(*cx) let mut clone = UndefinedValue());
+        structuredclone::read(self, data, clone.handle_mut()).map_err(|_| Error::DataClone)?;
+
+        // Step 3. Return deserializeRecord.[[Deserialized]].
+        Ok(clone.get())
+    }
+
     pub fn create_image_bitmap(
         &self,
         image: ImageBitmapSource,
@@ -3045,8 +3154,195 @@ impl GlobalScope {
         self.is_headless
     }
 
+    /// Slightly alter the pixels read back from a canvas while resisting fingerprinting.
+    /// They are altered the same way for every document of an origin until Servo exits.
+    pub fn add_canvas_readback_noise(&self, pixels: &mut [u8]) {
+        if !pref!(privacy.resist_fingerprinting.enabled) ||
+            !pref!(privacy.resist_fingerprinting.canvas_noise.enabled)
+        {
+            return;
+        }
+        lazy_static! {
+            static ref SESSION_KEY: u64 = servo_rand::random();
+        }
+        let mut hasher = DefaultHasher::new();
+        SESSION_KEY.hash(&mut hasher);
+        self.origin()
+            .immutable()
+            .ascii_serialization()
+            .hash(&mut hasher);
+        pixels::rgba8_add_noise_inplace(pixels, hasher.finish());
+    }
+
     pub fn get_user_agent(&self) -> Cow<'static, str> {
-        self.user_agent.clone()
+        match *self.user_agent_override.borrow() {
+            Some(UserAgentOverride {
+                user_agent: Some(ref user_agent),
+                ..
+            }) => Cow::Owned(user_agent.clone()),
+            _ => self.user_agent.clone(),
+        }
+    }
+
+    pub fn user_agent_override(&self) -> Option<UserAgentOverride> {
+        self.user_agent_override.borrow().clone()
+    }
+
+    pub fn set_user_agent_override(&self, user_agent_override: Option<UserAgentOverride>) {
+        *self.user_agent_override.borrow_mut() = user_agent_override;
+    }
+
+    pub fn script_policy(&self) -> ScriptPolicy {
+        self.script_policy.get()
+    }
+
+    /// Run the script of the realm of this global as the embedder's policy for its origin
+    /// says, right after the realm is created. Disabling the JITs applies to the whole
+    /// runtime, which the realm shares with the other realms of its event loop.
+    pub fn apply_script_policy(&self, script_policy: ScriptPolicy) {
+        self.script_policy.set(script_policy);
+        match script_policy {
+            ScriptPolicy::Allow => {},
+            ScriptPolicy::DisableJit => {
+                script_runtime::disable_jit(GlobalScope::get_cx());
+                self.queue_report(ReportData::Intervention {
+                    id: "jit-disabled",
+                    message: "Script runs without the JITs or WebAssembly, as the user chose \
+                              for this site."
+                        .to_owned(),
+                });
+            },
+            ScriptPolicy::Block => self.queue_report(ReportData::Intervention {
+                id: "script-blocked",
+                message: "Script does not run, as the user chose for this site.".to_owned(),
+            }),
+        }
+    }
+
+    /// Send the reports generated in this global to the endpoints the `Reporting-Endpoints`
+    /// header of its document configured, and its crash report to the default one.
+    /// <https://w3c.github.io/reporting/#document-configuration>
+    pub fn set_reporting_endpoints(&self, endpoints: HashMap<String, ServoUrl>) {
+        if let Some(endpoint) = endpoints.get(DEFAULT_ENDPOINT) {
+            let _ = self
+                .core_resource_thread()
+                .send(CoreResourceMsg::SetCrashReportEndpoint(
+                    self.pipeline_id,
+                    Some(endpoint.clone()),
+                ));
+        }
+        *self.reporting_endpoints.borrow_mut() = endpoints;
+    }
+
+    /// Stop sending the crash report of this global, as its document is going away.
+    pub fn clear_crash_report_endpoint(&self) {
+        if self
+            .reporting_endpoints
+            .borrow()
+            .contains_key(DEFAULT_ENDPOINT)
+        {
+            let _ = self
+                .core_resource_thread()
+                .send(CoreResourceMsg::SetCrashReportEndpoint(
+                    self.pipeline_id,
+                    None,
+                ));
+        }
+    }
+
+    /// Notify `observer` of the reports generated in this global from now on.
+    pub fn register_reporting_observer(&self, observer: &ReportingObserver) {
+        let mut observers = self.registered_reporting_observers.borrow_mut();
+        if !observers.iter().any(|registered| *registered == observer) {
+            observers.push(Dom::from_ref(observer));
+        }
+    }
+
+    pub fn unregister_reporting_observer(&self, observer: &ReportingObserver) {
+        self.registered_reporting_observers
+            .borrow_mut()
+            .retain(|registered| *registered != observer);
+    }
+
+    /// The reports generated in this global that are still in its report buffer.
+    pub fn buffered_reports(&self) -> Vec<DomRoot<Report>> {
+        self.report_buffer
+            .borrow()
+            .iter()
+            .map(|report| DomRoot::from_ref(&**report))
+            .collect()
+    }
+
+    /// Generate a report of what `data` tells, for the reporting observers of this global and
+    /// for the default endpoint of its document, if it configured one.
+    /// <https://w3c.github.io/reporting/#generate-and-queue-a-report>
+    #[allow(unsafe_code)]
+    pub fn queue_report(&self, data: ReportData) {
+        if !pref!(dom.reporting.enabled) {
+            return;
+        }
+        let location = unsafe { describe_scripted_caller(*GlobalScope::get_cx()) }
+            .ok()
+            .map(|caller| SourceLocation {
+                file: caller.filename,
+                line: caller.line,
+                column: caller.col,
+            });
+        let mut url = self.get_url();
+        url.set_fragment(None);
+
+        // Notify the observers, and keep the report for the ones created later.
+        // https://w3c.github.io/reporting/#notify-observers
+        let type_ = data.type_();
+        let body = data.body(self, location.clone());
+        let report = Report::new(
+            self,
+            DOMString::from(type_),
+            DOMString::from(url.as_str()),
+            Some(&body),
+        );
+        let observers = self.registered_reporting_observers.borrow().clone();
+        for observer in observers {
+            observer.add_report(&report);
+        }
+        let mut report_buffer = self.report_buffer.borrow_mut();
+        report_buffer.push(Dom::from_ref(&*report));
+        let reports_of_type = report_buffer
+            .iter()
+            .filter(|report| &**report.type_() == type_)
+            .count();
+        if reports_of_type > MAX_BUFFERED_REPORTS_OF_TYPE {
+            let oldest = report_buffer
+                .iter()
+                .position(|report| &**report.type_() == type_)
+                .unwrap();
+            report_buffer.remove(oldest);
+        }
+
+        // Send the report to the endpoint.
+        let endpoint = match self.reporting_endpoints.borrow().get(DEFAULT_ENDPOINT) {
+            Some(endpoint) => endpoint.clone(),
+            None => return,
+        };
+        let report = NetReport {
+            type_: type_.to_owned(),
+            url,
+            user_agent: self.get_user_agent().into_owned(),
+            body: data.serialize(location.as_ref()),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+        };
+        let _ = self
+            .core_resource_thread()
+            .send(CoreResourceMsg::QueueReport(report, endpoint));
+    }
+
+    /// What `navigator.userAgentData` tells about the user agent.
+    /// <https://wicg.github.io/ua-client-hints/#user-agent-metadata>
+    pub fn user_agent_metadata(&self) -> UserAgentMetadata {
+        UserAgentMetadata::new(&self.user_agent, self.user_agent_override.borrow().as_ref())
     }
 
     pub fn get_https_state(&self) -> HttpsState {
@@ -3070,6 +3366,18 @@ impl GlobalScope {
         false
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#concept-settings-object-cross-origin-isolated-capability>
+    pub fn cross_origin_isolated(&self) -> bool {
+        // TODO: No global is cross-origin isolated until the `Cross-Origin-Opener-Policy` and
+        // `Cross-Origin-Embedder-Policy` headers are supported.
+        false
+    }
+
+    /// Coarsen a timestamp, in milliseconds, before exposing it to the scripts of this global.
+    pub fn coarsen_time(&self, timestamp: f64) -> DOMHighResTimeStamp {
+        clock::coarsen_time(timestamp, self.cross_origin_isolated())
+    }
+
     /// <https://www.w3.org/TR/CSP/#get-csp-of-object>
     pub fn get_csp_list(&self) -> Option<CspList> {
         if let Some(window) = self.downcast::<Window>() {
@@ -3093,6 +3401,17 @@ impl GlobalScope {
         let _ = self.gpu_devices.borrow_mut().remove(&device);
     }
 
+    pub fn gpu_device_lost(&self, device: WebGPUDevice, msg: String) {
+        let device = self
+            .gpu_devices
+            .borrow()
+            .get(&device)
+            .map(|device| DomRoot::from_ref(&**device));
+        if let Some(device) = device {
+            device.lose(GPUDeviceLostReason::Unknown, msg.into());
+        }
+    }
+
     pub fn handle_wgpu_msg(
         &self,
         device: WebGPUDevice,
@@ -3253,6 +3572,7 @@ unsafe fn global_scope_from_global(
         ((*clasp).flags & (JSCLASS_IS_DOMJSCLASS | JSCLASS_IS_GLOBAL)),
         0
     );
+    let global = page_global_for_isolated_world(global).unwrap_or(global);
     root_from_object(global, cx).unwrap()
 }
 
@@ -3265,5 +3585,6 @@ unsafe fn global_scope_from_global_static(global: *mut JSObject) -> DomRoot<Glob
         ((*clasp).flags & (JSCLASS_IS_DOMJSCLASS | JSCLASS_IS_GLOBAL)),
         0
     );
+    let global = page_global_for_isolated_world(global).unwrap_or(global);
     root_from_object_static(global).unwrap()
 }
@@ -18,7 +18,7 @@ use webgpu::{WebGPU, WebGPUBuffer, WebGPURequest, WebGPUResponse, WebGPUResponse
 use super::bindings::buffer_source::{create_new_external_array_buffer, HeapBufferSource};
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
-    GPUBufferMethods, GPUMapModeConstants, GPUSize64,
+    GPUBufferMapState, GPUBufferMethods, GPUBufferUsageConstants, GPUMapModeConstants, GPUSize64,
 };
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
@@ -70,6 +70,7 @@ pub struct GPUBuffer {
     buffer: WebGPUBuffer,
     device: Dom<GPUDevice>,
     size: GPUSize64,
+    usage: u32,
     #[ignore_malloc_size_of = "promises are hard"]
     map_promise: DomRefCell<Option<Rc<Promise>>>,
     map_info: DomRefCell<Option<GPUBufferMapInfo>>,
@@ -82,6 +83,7 @@ impl GPUBuffer {
         device: &GPUDevice,
         state: GPUBufferState,
         size: GPUSize64,
+        usage: u32,
         map_info: DomRefCell<Option<GPUBufferMapInfo>>,
         label: USVString,
     ) -> Self {
@@ -94,6 +96,7 @@ impl GPUBuffer {
             buffer,
             map_promise: DomRefCell::new(None),
             size,
+            usage,
             map_info,
         }
     }
@@ -105,12 +108,13 @@ impl GPUBuffer {
         device: &GPUDevice,
         state: GPUBufferState,
         size: GPUSize64,
+        usage: u32,
         map_info: DomRefCell<Option<GPUBufferMapInfo>>,
         label: USVString,
     ) -> DomRoot<Self> {
         reflect_dom_object(
             Box::new(GPUBuffer::new_inherited(
-                channel, buffer, device, state, size, map_info, label,
+                channel, buffer, device, state, size, usage, map_info, label,
             )),
             global,
         )
@@ -125,6 +129,37 @@ impl GPUBuffer {
     pub fn state(&self) -> GPUBufferState {
         self.state.get()
     }
+
+    /// Why mapping `offset..offset + range_size` of this buffer with `mode` is invalid, if
+    /// it is.
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpubuffer-mapasync>
+    fn validate_map_async(&self, mode: u32, offset: u64, range_size: u64) -> Result<(), String> {
+        if self.state.get() != GPUBufferState::Unmapped {
+            return Err(String::from("Buffer is not unmapped"));
+        }
+        if offset % RANGE_OFFSET_ALIGN_MASK != 0 || range_size % RANGE_SIZE_ALIGN_MASK != 0 {
+            return Err(String::from("Mapped range is not aligned"));
+        }
+        if offset
+            .checked_add(range_size)
+            .map_or(true, |end| end > self.size)
+        {
+            return Err(String::from(
+                "Mapped range is out of the bounds of the buffer",
+            ));
+        }
+        let usage = match mode {
+            GPUMapModeConstants::READ => GPUBufferUsageConstants::MAP_READ,
+            GPUMapModeConstants::WRITE => GPUBufferUsageConstants::MAP_WRITE,
+            _ => return Err(String::from("Invalid MapModeFlags")),
+        };
+        if self.usage & usage == 0 {
+            return Err(String::from(
+                "Buffer usage does not allow mapping it with this mode",
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Drop for GPUBuffer {
@@ -175,7 +210,7 @@ impl GPUBufferMethods for GPUBuffer {
             // Step 2
             GPUBufferState::MappingPending => {
                 let promise = self.map_promise.borrow_mut().take().unwrap();
-                promise.reject_error(Error::Operation);
+                promise.reject_error(Error::Abort);
             },
         };
         // Step 4
@@ -217,34 +252,22 @@ impl GPUBufferMethods for GPUBuffer {
         comp: InRealm,
     ) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
-        let range_size = if let Some(s) = size {
-            s
-        } else if offset >= self.size {
+        if self.map_promise.borrow().is_some() {
             promise.reject_error(Error::Operation);
             return promise;
-        } else {
-            self.size - offset
-        };
+        }
+        let range_size = size.unwrap_or_else(|| self.size.saturating_sub(offset));
         let scope_id = self.device.use_current_scope();
-        if self.state.get() != GPUBufferState::Unmapped {
-            self.device.handle_server_msg(
-                scope_id,
-                WebGPUOpResult::ValidationError(String::from("Buffer is not Unmapped")),
-            );
-            promise.reject_error(Error::Abort);
+        if let Err(message) = self.validate_map_async(mode, offset, range_size) {
+            self.device
+                .handle_server_msg(scope_id, WebGPUOpResult::ValidationError(message));
+            promise.reject_error(Error::Operation);
             return promise;
         }
-        let host_map = match mode {
-            GPUMapModeConstants::READ => HostMap::Read,
-            GPUMapModeConstants::WRITE => HostMap::Write,
-            _ => {
-                self.device.handle_server_msg(
-                    scope_id,
-                    WebGPUOpResult::ValidationError(String::from("Invalid MapModeFlags")),
-                );
-                promise.reject_error(Error::Abort);
-                return promise;
-            },
+        let host_map = if mode == GPUMapModeConstants::READ {
+            HostMap::Read
+        } else {
+            HostMap::Write
         };
 
         let map_range = offset..offset + range_size;
@@ -333,6 +356,25 @@ impl GPUBufferMethods for GPUBuffer {
         result
     }
 
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpubuffer-size>
+    fn Size(&self) -> GPUSize64 {
+        self.size
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpubuffer-usage>
+    fn Usage(&self) -> u32 {
+        self.usage
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpubuffer-mapstate>
+    fn MapState(&self) -> GPUBufferMapState {
+        match self.state.get() {
+            GPUBufferState::Mapped | GPUBufferState::MappedAtCreation => GPUBufferMapState::Mapped,
+            GPUBufferState::MappingPending => GPUBufferMapState::Pending,
+            GPUBufferState::Unmapped | GPUBufferState::Destroyed => GPUBufferMapState::Unmapped,
+        }
+    }
+
     /// <https://gpuweb.github.io/gpuweb/#dom-gpuobjectbase-label>
     fn Label(&self) -> USVString {
         self.label.borrow().clone()
@@ -363,7 +405,9 @@ impl AsyncWGPUListener for GPUBuffer {
                 },
                 Err(e) => {
                     warn!("Could not map buffer({:?})", e);
-                    promise.reject_error(Error::Abort);
+                    self.state.set(GPUBufferState::Unmapped);
+                    *self.map_info.borrow_mut() = None;
+                    promise.reject_error(Error::Operation);
                 },
                 Ok(_) => unreachable!("GPUBuffer received wrong WebGPUResponse"),
             },
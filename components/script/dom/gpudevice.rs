@@ -35,12 +35,12 @@ use crate::dom::bindings::codegen::Bindings::WebGPUBinding::{
     GPUAddressMode, GPUBindGroupDescriptor, GPUBindGroupLayoutDescriptor, GPUBindingResource,
     GPUBlendFactor, GPUBlendOperation, GPUBufferDescriptor, GPUCommandEncoderDescriptor,
     GPUCompareFunction, GPUComputePipelineDescriptor, GPUCullMode, GPUDeviceMethods, GPUError,
-    GPUErrorFilter, GPUExtent3D, GPUExtent3DDict, GPUFilterMode, GPUFrontFace, GPUIndexFormat,
-    GPUObjectDescriptorBase, GPUPipelineLayoutDescriptor, GPUPrimitiveTopology,
-    GPURenderBundleEncoderDescriptor, GPURenderPipelineDescriptor, GPUSamplerDescriptor,
-    GPUShaderModuleDescriptor, GPUStencilOperation, GPUSupportedLimitsMethods,
-    GPUTextureDescriptor, GPUTextureDimension, GPUTextureFormat, GPUTextureViewDimension,
-    GPUUncapturedErrorEventInit, GPUVertexFormat,
+    GPUErrorFilter, GPUExtent3D, GPUExtent3DDict, GPUFeatureName, GPUFilterMode, GPUFrontFace,
+    GPUIndexFormat, GPUObjectDescriptorBase, GPUPipelineLayoutDescriptor, GPUPrimitiveTopology,
+    GPUQuerySetDescriptor, GPUQueryType, GPURenderBundleEncoderDescriptor,
+    GPURenderPipelineDescriptor, GPUSamplerDescriptor, GPUShaderModuleDescriptor,
+    GPUStencilOperation, GPUSupportedLimitsMethods, GPUTextureDescriptor, GPUTextureDimension,
+    GPUTextureFormat, GPUTextureViewDimension, GPUUncapturedErrorEventInit, GPUVertexFormat,
 };
 use crate::dom::bindings::error::Error;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
@@ -57,6 +57,7 @@ use crate::dom::gpucommandencoder::GPUCommandEncoder;
 use crate::dom::gpucomputepipeline::GPUComputePipeline;
 use crate::dom::gpuoutofmemoryerror::GPUOutOfMemoryError;
 use crate::dom::gpupipelinelayout::GPUPipelineLayout;
+use crate::dom::gpuqueryset::GPUQuerySet;
 use crate::dom::gpuqueue::GPUQueue;
 use crate::dom::gpurenderbundleencoder::GPURenderBundleEncoder;
 use crate::dom::gpurenderpipeline::GPURenderPipeline;
@@ -69,6 +70,10 @@ use crate::dom::gpuvalidationerror::GPUValidationError;
 use crate::dom::promise::Promise;
 use crate::realms::InRealm;
 
+/// The most queries a query set can hold.
+/// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createqueryset>
+const MAX_QUERY_COUNT: u32 = 4096;
+
 #[derive(JSTraceable, MallocSizeOf)]
 struct ErrorScopeInfo {
     op_count: u64,
@@ -313,18 +318,17 @@ impl GPUDevice {
     }
 
     /// <https://gpuweb.github.io/gpuweb/#lose-the-device>
-    pub fn lose(&self, reason: GPUDeviceLostReason) {
-        if let Some(ref lost_promise) = *self.lost_promise.borrow() {
-            let global = &self.global();
-            let msg = match reason {
-                GPUDeviceLostReason::Unknown => "Unknown reason for your device loss.",
-                GPUDeviceLostReason::Destroyed => {
-                    "Device self-destruction sequence activated successfully!"
-                },
-            };
-            let lost = GPUDeviceLostInfo::new(global, msg.into(), reason);
-            lost_promise.resolve_native(&*lost);
-        }
+    pub fn lose(&self, reason: GPUDeviceLostReason, msg: DOMString) {
+        // Step 1. Invalidate device.
+        self.valid.set(false);
+
+        // Step 4. Resolve device.lost with a new GPUDeviceLostInfo.
+        let global = &self.global();
+        let lost = GPUDeviceLostInfo::new(global, msg, reason);
+        self.lost_promise
+            .borrow_mut()
+            .get_or_insert_with(|| Promise::new(global))
+            .resolve_native(&*lost);
     }
 }
 
@@ -356,9 +360,11 @@ impl GPUDeviceMethods for GPUDevice {
 
     /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-lost>
     fn GetLost(&self, comp: InRealm) -> Fallible<Rc<Promise>> {
-        let promise = Promise::new_in_current_realm(comp);
-        *self.lost_promise.borrow_mut() = Some(promise.clone());
-        Ok(promise)
+        Ok(self
+            .lost_promise
+            .borrow_mut()
+            .get_or_insert_with(|| Promise::new_in_current_realm(comp))
+            .clone())
     }
 
     /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createbuffer>
@@ -421,6 +427,7 @@ impl GPUDeviceMethods for GPUDevice {
             &self,
             state,
             descriptor.size,
+            descriptor.usage,
             map_info,
             descriptor.parent.label.clone().unwrap_or_default(),
         ))
@@ -1079,6 +1086,63 @@ impl GPUDeviceMethods for GPUDevice {
         )
     }
 
+    /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-createqueryset>
+    fn CreateQuerySet(&self, descriptor: &GPUQuerySetDescriptor) -> Fallible<DomRoot<GPUQuerySet>> {
+        let ty = match descriptor.type_ {
+            GPUQueryType::Occlusion => wgt::QueryType::Occlusion,
+            GPUQueryType::Timestamp => {
+                if !self.features.contains(GPUFeatureName::Timestamp_query) {
+                    return Err(Error::Type(String::from(
+                        "timestamp-query is not enabled on the device",
+                    )));
+                }
+                wgt::QueryType::Timestamp
+            },
+        };
+
+        let scope_id = self.use_current_scope();
+        if descriptor.count > MAX_QUERY_COUNT {
+            self.handle_server_msg(
+                scope_id,
+                WebGPUOpResult::ValidationError(format!(
+                    "A query set cannot hold more than {} queries",
+                    MAX_QUERY_COUNT
+                )),
+            );
+        }
+
+        let query_set_id = self
+            .global()
+            .wgpu_id_hub()
+            .lock()
+            .create_query_set_id(self.device.0.backend());
+        let desc = wgpu_res::QuerySetDescriptor {
+            label: convert_label(&descriptor.parent),
+            ty,
+            count: descriptor.count,
+        };
+        self.channel
+            .0
+            .send((
+                scope_id,
+                WebGPURequest::CreateQuerySet {
+                    device_id: self.device.0,
+                    query_set_id,
+                    descriptor: desc,
+                },
+            ))
+            .expect("Failed to create WebGPU query set");
+
+        Ok(GPUQuerySet::new(
+            &self.global(),
+            self.channel.clone(),
+            webgpu::WebGPUQuerySet(query_set_id),
+            descriptor.type_,
+            descriptor.count,
+            descriptor.parent.label.clone().unwrap_or_default(),
+        ))
+    }
+
     /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-pusherrorscope>
     fn PushErrorScope(&self, filter: GPUErrorFilter) {
         let mut context = self.scope_context.borrow_mut();
@@ -1135,9 +1199,10 @@ impl GPUDeviceMethods for GPUDevice {
     /// <https://gpuweb.github.io/gpuweb/#dom-gpudevice-destroy>
     fn Destroy(&self) {
         if self.valid.get() {
-            self.valid.set(false);
-
-            self.lose(GPUDeviceLostReason::Destroyed);
+            self.lose(
+                GPUDeviceLostReason::Destroyed,
+                DOMString::from("Device self-destruction sequence activated successfully!"),
+            );
 
             if let Err(e) = self
                 .channel
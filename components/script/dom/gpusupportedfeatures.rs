@@ -125,6 +125,12 @@ impl FromStr for GPUFeatureName {
     }
 }
 
+impl GPUSupportedFeatures {
+    pub fn contains(&self, feature: GPUFeatureName) -> bool {
+        self.internal.borrow().contains(&feature)
+    }
+}
+
 // this error is wrong because if we inline Self::Key and Self::Value all errors are gone
 #[allow(crown::unrooted_must_root)]
 impl Setlike for GPUSupportedFeatures {
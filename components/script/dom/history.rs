@@ -5,6 +5,7 @@
 use std::cell::Cell;
 
 use dom_struct::dom_struct;
+use euclid::Vector2D;
 use js::jsapi::Heap;
 use js::jsval::{JSVal, NullValue, UndefinedValue};
 use js::rust::HandleValue;
@@ -12,12 +13,15 @@ use msg::constellation_msg::{HistoryStateId, TraversalDirection};
 use net_traits::{CoreResourceMsg, IpcSend};
 use profile_traits::ipc;
 use profile_traits::ipc::channel;
-use script_traits::{ScriptMsg, StructuredSerializedData};
+use script_traits::{
+    HistoryEntryScrollState, ScriptMsg, ScrollRestorationMode, StructuredSerializedData,
+};
 use servo_url::ServoUrl;
+use style_traits::CSSPixel;
 
-use crate::dom::bindings::codegen::Bindings::HistoryBinding::HistoryMethods;
+use crate::dom::bindings::codegen::Bindings::HistoryBinding::{HistoryMethods, ScrollRestoration};
 use crate::dom::bindings::codegen::Bindings::LocationBinding::Location_Binding::LocationMethods;
-use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
@@ -46,6 +50,13 @@ pub struct History {
     state: Heap<JSVal>,
     #[no_trace]
     state_id: Cell<Option<HistoryStateId>>,
+    /// The scroll restoration mode of the current session history entry.
+    #[no_trace]
+    scroll_restoration: Cell<ScrollRestorationMode>,
+    /// The scroll position to restore once the document has loaded, when it reloads a session
+    /// history entry.
+    #[no_trace]
+    pending_scroll_position: Cell<Option<Vector2D<f32, CSSPixel>>>,
 }
 
 impl History {
@@ -57,6 +68,8 @@ impl History {
             window: Dom::from_ref(&window),
             state: state,
             state_id: Cell::new(None),
+            scroll_restoration: Cell::new(ScrollRestorationMode::Auto),
+            pending_scroll_position: Cell::new(None),
         }
     }
 
@@ -82,7 +95,12 @@ impl History {
     // https://html.spec.whatwg.org/multipage/#history-traversal
     // Steps 5-16
     #[allow(unsafe_code)]
-    pub fn activate_state(&self, state_id: Option<HistoryStateId>, url: ServoUrl) {
+    pub fn activate_state(
+        &self,
+        state_id: Option<HistoryStateId>,
+        url: ServoUrl,
+        scroll_state: HistoryEntryScrollState,
+    ) {
         // Steps 5
         let document = self.window.Document();
         let old_url = document.url().clone();
@@ -92,12 +110,79 @@ impl History {
         let hash_changed = old_url.fragment() != url.fragment();
 
         // Step 8
-        if let Some(fragment) = url.fragment() {
-            document.check_and_scroll_fragment(fragment);
+        // https://html.spec.whatwg.org/multipage/#restore-persisted-state
+        self.scroll_restoration.set(scroll_state.restoration_mode);
+        if scroll_state.restoration_mode == ScrollRestorationMode::Auto {
+            self.restore_scroll_position(scroll_state.position);
         }
 
         // Step 11
         let state_changed = state_id != self.state_id.get();
+        self.set_state_id(state_id);
+
+        // TODO: Queue events on DOM Manipulation task source if non-blocking flag is set.
+        // Step 16.1
+        if state_changed {
+            PopStateEvent::dispatch_jsval(
+                self.window.upcast::<EventTarget>(),
+                &*self.window,
+                unsafe { HandleValue::from_raw(self.state.handle()) },
+            );
+        }
+
+        // Step 16.3
+        if hash_changed {
+            let event = HashChangeEvent::new(
+                &self.window,
+                atom!("hashchange"),
+                false,
+                false,
+                old_url.into_string(),
+                url.into_string(),
+            );
+            event
+                .upcast::<Event>()
+                .fire(self.window.upcast::<EventTarget>());
+        }
+    }
+
+    /// Give a document that reloads a session history entry, for instance after it was
+    /// discarded or its content process crashed, the state of that entry. The scroll position
+    /// is restored once the document has loaded.
+    pub fn restore_entry(
+        &self,
+        state_id: Option<HistoryStateId>,
+        scroll_state: Option<HistoryEntryScrollState>,
+    ) {
+        self.set_state_id(state_id);
+        if let Some(scroll_state) = scroll_state {
+            self.scroll_restoration.set(scroll_state.restoration_mode);
+            if scroll_state.restoration_mode == ScrollRestorationMode::Auto {
+                self.pending_scroll_position
+                    .set(Some(scroll_state.position));
+            }
+        }
+    }
+
+    /// Restore the scroll position of a reloaded session history entry, once its document has
+    /// loaded. Returns false if there is no scroll position to restore.
+    pub fn restore_pending_scroll_position(&self) -> bool {
+        match self.pending_scroll_position.take() {
+            Some(position) => {
+                self.restore_scroll_position(position);
+                true
+            },
+            None => false,
+        }
+    }
+
+    fn restore_scroll_position(&self, position: Vector2D<f32, CSSPixel>) {
+        self.window
+            .scroll(position.x as f64, position.y as f64, ScrollBehavior::Auto);
+    }
+
+    /// Make the state with the given id the current state, reading its serialized data back.
+    fn set_state_id(&self, state_id: Option<HistoryStateId>) {
         self.state_id.set(state_id);
         let serialized_data = match state_id {
             Some(state_id) => {
@@ -118,6 +203,8 @@ impl History {
                     serialized: data,
                     ports: None,
                     blobs: None,
+                    offscreen_canvases: None,
+                    image_bitmaps: None,
                 };
                 let global_scope = self.window.upcast::<GlobalScope>();
                 rooted!(in(*GlobalScope::get_cx()) let mut state = UndefinedValue());
@@ -130,31 +217,6 @@ impl History {
                 self.state.set(NullValue());
             },
         }
-
-        // TODO: Queue events on DOM Manipulation task source if non-blocking flag is set.
-        // Step 16.1
-        if state_changed {
-            PopStateEvent::dispatch_jsval(
-                self.window.upcast::<EventTarget>(),
-                &*self.window,
-                unsafe { HandleValue::from_raw(self.state.handle()) },
-            );
-        }
-
-        // Step 16.3
-        if hash_changed {
-            let event = HashChangeEvent::new(
-                &self.window,
-                atom!("hashchange"),
-                false,
-                false,
-                old_url.into_string(),
-                url.into_string(),
-            );
-            event
-                .upcast::<Event>()
-                .fire(self.window.upcast::<EventTarget>());
-        }
     }
 
     pub fn remove_states(&self, states: Vec<HistoryStateId>) {
@@ -293,6 +355,35 @@ impl HistoryMethods for History {
         Ok(self.state.get())
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn GetScrollRestoration(&self) -> Fallible<ScrollRestoration> {
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        Ok(match self.scroll_restoration.get() {
+            ScrollRestorationMode::Auto => ScrollRestoration::Auto,
+            ScrollRestorationMode::Manual => ScrollRestoration::Manual,
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-history-scroll-restoration
+    fn SetScrollRestoration(&self, value: ScrollRestoration) -> ErrorResult {
+        if !self.window.Document().is_fully_active() {
+            return Err(Error::Security);
+        }
+        let mode = match value {
+            ScrollRestoration::Auto => ScrollRestorationMode::Auto,
+            ScrollRestoration::Manual => ScrollRestorationMode::Manual,
+        };
+        self.scroll_restoration.set(mode);
+        let _ = self
+            .window
+            .upcast::<GlobalScope>()
+            .script_to_constellation_chan()
+            .send(ScriptMsg::SetScrollRestorationMode(mode));
+        Ok(())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-history-length
     fn GetLength(&self) -> Fallible<u32> {
         if !self.window.Document().is_fully_active() {
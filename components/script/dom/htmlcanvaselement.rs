@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use canvas_traits::canvas::{CanvasId, CanvasMsg, FromScriptMsg};
+use std::cell::Cell;
+
+use canvas_traits::canvas::{CanvasId, CanvasMsg, FromScriptMsg, PlaceholderCanvasFrame};
 use canvas_traits::webgl::{GLContextAttributes, WebGLVersion};
 use dom_struct::dom_struct;
 use euclid::default::{Rect, Size2D};
@@ -10,6 +12,7 @@ use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use image::codecs::png::PngEncoder;
 use image::{ColorType, ImageEncoder};
 use ipc_channel::ipc::{self as ipcchan, IpcSharedMemory};
+use ipc_channel::router::ROUTER;
 use js::error::throw_type_error;
 use js::rust::{HandleObject, HandleValue};
 use profile_traits::ipc;
@@ -30,6 +33,7 @@ use crate::dom::bindings::conversions::ConversionResult;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{Dom, DomRoot, LayoutDom};
 use crate::dom::bindings::str::{DOMString, USVString};
@@ -43,11 +47,13 @@ use crate::dom::gpucanvascontext::GPUCanvasContext;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::mediastream::MediaStream;
 use crate::dom::mediastreamtrack::MediaStreamTrack;
-use crate::dom::node::{window_from_node, Node};
+use crate::dom::node::{window_from_node, Node, NodeDamage};
+use crate::dom::offscreencanvas::OffscreenCanvas;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::webgl2renderingcontext::WebGL2RenderingContext;
 use crate::dom::webglrenderingcontext::WebGLRenderingContext;
 use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
 
 const DEFAULT_WIDTH: u32 = 300;
 const DEFAULT_HEIGHT: u32 = 150;
@@ -65,6 +71,13 @@ pub enum CanvasContext {
 pub struct HTMLCanvasElement {
     htmlelement: HTMLElement,
     context: DomRefCell<Option<CanvasContext>>,
+    /// Whether control of this canvas was transferred to an `OffscreenCanvas`, i.e. whether
+    /// its context mode is "placeholder".
+    is_placeholder: Cell<bool>,
+    /// The last frame presented by the `OffscreenCanvas` this canvas is the placeholder of.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    #[no_trace]
+    placeholder_frame: DomRefCell<Option<PlaceholderCanvasFrame>>,
 }
 
 impl HTMLCanvasElement {
@@ -76,6 +89,8 @@ impl HTMLCanvasElement {
         HTMLCanvasElement {
             htmlelement: HTMLElement::new_inherited(local_name, prefix, document),
             context: DomRefCell::new(None),
+            is_placeholder: Cell::new(false),
+            placeholder_frame: DomRefCell::new(None),
         }
     }
 
@@ -119,6 +134,12 @@ impl HTMLCanvasElement {
             _ => true,
         }
     }
+
+    /// Show a frame of the `OffscreenCanvas` this canvas is the placeholder of.
+    fn present_frame(&self, frame: PlaceholderCanvasFrame) {
+        *self.placeholder_frame.borrow_mut() = Some(frame);
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
 }
 
 pub trait LayoutCanvasRenderingContextHelpers {
@@ -136,6 +157,16 @@ pub trait LayoutHTMLCanvasElementHelpers {
 impl LayoutHTMLCanvasElementHelpers for LayoutDom<'_, HTMLCanvasElement> {
     #[allow(unsafe_code)]
     fn data(self) -> HTMLCanvasData {
+        let frame = unsafe { self.unsafe_get().placeholder_frame.borrow_for_layout() };
+        if let Some(frame) = frame.as_ref() {
+            return HTMLCanvasData {
+                source: HTMLCanvasDataSource::Image(Some(frame.ipc_renderer.clone())),
+                width: frame.size.width as u32,
+                height: frame.size.height as u32,
+                canvas_id: frame.canvas_id,
+            };
+        }
+
         let source = unsafe {
             match self.unsafe_get().context.borrow_for_layout().as_ref() {
                 Some(&CanvasContext::Context2d(ref context)) => {
@@ -186,7 +217,10 @@ impl LayoutHTMLCanvasElementHelpers for LayoutDom<'_, HTMLCanvasElement> {
     fn get_canvas_id_for_layout(self) -> CanvasId {
         unsafe {
             let canvas = &*self.unsafe_get();
-            if let &Some(CanvasContext::Context2d(ref context)) = canvas.context.borrow_for_layout()
+            if let Some(frame) = canvas.placeholder_frame.borrow_for_layout().as_ref() {
+                frame.canvas_id
+            } else if let &Some(CanvasContext::Context2d(ref context)) =
+                canvas.context.borrow_for_layout()
             {
                 context.to_layout().get_canvas_id()
             } else {
@@ -317,6 +351,15 @@ impl HTMLCanvasElement {
             return None;
         }
 
+        if let Some(frame) = self.placeholder_frame.borrow().as_ref() {
+            let (sender, receiver) =
+                ipc::channel(self.global().time_profiler_chan().clone()).unwrap();
+            let msg = CanvasMsg::FromScript(FromScriptMsg::SendPixels(sender), frame.canvas_id);
+            frame.ipc_renderer.send(msg).unwrap();
+
+            return Some((Some(receiver.recv().unwrap()), frame.size.to_u32()));
+        }
+
         let data = match self.context.borrow().as_ref() {
             Some(&CanvasContext::Context2d(ref context)) => {
                 let (sender, receiver) =
@@ -367,8 +410,11 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         cx: JSContext,
         id: DOMString,
         options: HandleValue,
-    ) -> Option<RenderingContext> {
-        match &*id {
+    ) -> Fallible<Option<RenderingContext>> {
+        if self.is_placeholder.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(RenderingContext::CanvasRenderingContext2D),
@@ -382,7 +428,7 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
                 .get_or_init_webgpu_context()
                 .map(RenderingContext::GPUCanvasContext),
             _ => None,
-        }
+        })
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-canvas-todataurl
@@ -403,7 +449,7 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         }
 
         // Step 3.
-        let file = match *self.context.borrow() {
+        let mut file = match *self.context.borrow() {
             Some(CanvasContext::Context2d(ref context)) => {
                 context.get_rect(Rect::from_size(self.get_size()))
             },
@@ -426,6 +472,7 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
                 vec![0; (self.Width() * self.Height() * 4) as usize]
             },
         };
+        self.global().add_canvas_readback_noise(&mut file);
 
         // FIXME: Only handle image/png for now.
         let mut url = "data:image/png;base64,".to_owned();
@@ -442,6 +489,55 @@ impl HTMLCanvasElementMethods for HTMLCanvasElement {
         Ok(USVString(url))
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-canvas-transfercontroltooffscreen
+    fn TransferControlToOffscreen(&self) -> Fallible<DomRoot<OffscreenCanvas>> {
+        // Step 1. If this canvas element's context mode is not set to none,
+        // throw an "InvalidStateError".
+        if self.context.borrow().is_some() || self.is_placeholder.get() {
+            return Err(Error::InvalidState);
+        }
+
+        // Frames are presented from whichever global the offscreen canvas ends up in.
+        let global = self.global();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let (sender, receiver) = ipcchan::channel().unwrap();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let frame: PlaceholderCanvasFrame = match message.to() {
+                    Ok(frame) => frame,
+                    Err(error) => return warn!("Bad offscreen canvas frame ({})", error),
+                };
+                let this = this.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(present_offscreen_canvas_frame: move || {
+                        this.root().present_frame(frame);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+
+        // Step 2-3. Let offscreenCanvas be a new OffscreenCanvas with its width and height
+        // equal to this canvas element's, and with this canvas element as its placeholder.
+        let offscreen_canvas = OffscreenCanvas::new(
+            &global,
+            None,
+            self.Width() as u64,
+            self.Height() as u64,
+            Some(self),
+            Some(sender),
+        );
+
+        // Step 4. Set this canvas element's context mode to placeholder.
+        self.is_placeholder.set(true);
+
+        // Step 5. Return offscreenCanvas.
+        Ok(offscreen_canvas)
+    }
+
     /// <https://w3c.github.io/mediacapture-fromelement/#dom-htmlcanvaselement-capturestream>
     fn CaptureStream(&self, _frame_request_rate: Option<Finite<f64>>) -> DomRoot<MediaStream> {
         let global = self.global();
@@ -15,6 +15,7 @@ use style_traits::dom::ElementState;
 
 use crate::dom::activation::Activatable;
 use crate::dom::attr::Attr;
+use crate::dom::bindings::codegen::Bindings::EventBinding::Event_Binding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::EventHandlerBinding::{
     EventHandlerNonNull, OnErrorEventHandlerNonNull,
 };
@@ -26,6 +27,7 @@ use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::{Castable, ElementTypeId, HTMLElementTypeId, NodeTypeId};
 use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::compositionevent::CompositionEvent;
 use crate::dom::cssstyledeclaration::{CSSModificationAccess, CSSStyleDeclaration, CSSStyleOwner};
 use crate::dom::document::{Document, FocusType};
 use crate::dom::documentfragment::DocumentFragment;
@@ -41,9 +43,11 @@ use crate::dom::htmlhtmlelement::HTMLHtmlElement;
 use crate::dom::htmlinputelement::{HTMLInputElement, InputType};
 use crate::dom::htmllabelelement::HTMLLabelElement;
 use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::node::{document_from_node, window_from_node, Node, ShadowIncluding};
 use crate::dom::text::Text;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::editing::{self, editing_host_of};
 
 #[dom_struct]
 pub struct HTMLElement {
@@ -52,6 +56,14 @@ pub struct HTMLElement {
     dataset: MutNullableDom<DOMStringMap>,
 }
 
+/// The states of the <https://html.spec.whatwg.org/multipage/#attr-contenteditable> attribute.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum ContentEditableState {
+    True,
+    False,
+    Inherit,
+}
+
 impl HTMLElement {
     pub fn new_inherited(
         tag_name: LocalName,
@@ -526,23 +538,44 @@ impl HTMLElementMethods for HTMLElement {
 
     // https://html.spec.whatwg.org/multipage/#dom-contenteditable
     fn ContentEditable(&self) -> DOMString {
-        // TODO: https://github.com/servo/servo/issues/12776
-        self.upcast::<Element>()
-            .get_attribute(&ns!(), &local_name!("contenteditable"))
-            .map(|attr| DOMString::from(&**attr.value()))
-            .unwrap_or_else(|| DOMString::from("inherit"))
+        DOMString::from(match self.content_editable_state() {
+            ContentEditableState::True => "true",
+            ContentEditableState::False => "false",
+            ContentEditableState::Inherit => "inherit",
+        })
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-contenteditable
-    fn SetContentEditable(&self, _: DOMString) {
-        // TODO: https://github.com/servo/servo/issues/12776
-        warn!("The contentEditable attribute is not implemented yet");
+    fn SetContentEditable(&self, value: DOMString) -> ErrorResult {
+        let element = self.upcast::<Element>();
+        if value.eq_ignore_ascii_case("inherit") {
+            element.remove_attribute(&ns!(), &local_name!("contenteditable"));
+        } else if value.eq_ignore_ascii_case("true") {
+            element.set_string_attribute(&local_name!("contenteditable"), "true".into());
+        } else if value.eq_ignore_ascii_case("false") {
+            element.set_string_attribute(&local_name!("contenteditable"), "false".into());
+        } else {
+            return Err(Error::Syntax);
+        }
+        Ok(())
     }
 
-    // https://html.spec.whatwg.org/multipage/#dom-contenteditable
+    // https://html.spec.whatwg.org/multipage/#dom-iscontenteditable
     fn IsContentEditable(&self) -> bool {
-        // TODO: https://github.com/servo/servo/issues/12776
-        false
+        editing_host_of(self.upcast()).is_some()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn Draggable(&self) -> bool {
+        self.is_draggable()
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-draggable
+    fn SetDraggable(&self, draggable: bool) {
+        self.upcast::<Element>().set_string_attribute(
+            &local_name!("draggable"),
+            DOMString::from(if draggable { "true" } else { "false" }),
+        );
     }
 }
 
@@ -618,6 +651,51 @@ fn to_camel_case(name: &str) -> Option<DOMString> {
 }
 
 impl HTMLElement {
+    /// The state of the <https://html.spec.whatwg.org/multipage/#attr-contenteditable>
+    /// attribute.
+    pub(crate) fn content_editable_state(&self) -> ContentEditableState {
+        let element = self.upcast::<Element>();
+        match element.get_attribute(&ns!(), &local_name!("contenteditable")) {
+            None => ContentEditableState::Inherit,
+            Some(attr) => {
+                let value = attr.value();
+                if value.is_empty() || value.eq_ignore_ascii_case("true") {
+                    ContentEditableState::True
+                } else if value.eq_ignore_ascii_case("false") {
+                    ContentEditableState::False
+                } else {
+                    ContentEditableState::Inherit
+                }
+            },
+        }
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#editing-host>
+    pub fn is_editing_host(&self) -> bool {
+        self.content_editable_state() == ContentEditableState::True
+    }
+
+    /// Whether the user can drag the element, which is the case of images and links unless
+    /// their `draggable` attribute says otherwise.
+    /// <https://html.spec.whatwg.org/multipage/#the-draggable-attribute>
+    pub fn is_draggable(&self) -> bool {
+        let element = self.upcast::<Element>();
+        if let Some(attr) = element.get_attribute(&ns!(), &local_name!("draggable")) {
+            let value = attr.value();
+            if value.eq_ignore_ascii_case("true") {
+                return true;
+            }
+            if value.eq_ignore_ascii_case("false") {
+                return false;
+            }
+        }
+        match *element.local_name() {
+            local_name!("img") => true,
+            local_name!("a") => element.has_attribute(&local_name!("href")),
+            _ => false,
+        }
+    }
+
     pub fn set_custom_attr(&self, name: DOMString, value: DOMString) -> ErrorResult {
         if name
             .chars()
@@ -860,6 +938,38 @@ impl VirtualMethods for HTMLElement {
                 .parse_plain_attribute(name, value),
         }
     }
+
+    fn handle_event(&self, event: &Event) {
+        if let Some(super_type) = self.super_type() {
+            super_type.handle_event(event);
+        }
+
+        // Text controls edit their own value.
+        if event.DefaultPrevented() ||
+            self.is::<HTMLInputElement>() ||
+            self.is::<HTMLTextAreaElement>()
+        {
+            return;
+        }
+        let host = match editing_host_of(self.upcast()) {
+            Some(host) => host,
+            None => return,
+        };
+
+        if event.type_() == atom!("keydown") {
+            if let Some(keyboard_event) = event.downcast::<KeyboardEvent>() {
+                if editing::handle_keydown(&host, keyboard_event) {
+                    event.mark_as_handled();
+                }
+            }
+        } else if event.type_() == atom!("compositionend") {
+            if let Some(composition_event) = event.downcast::<CompositionEvent>() {
+                if editing::handle_compositionend(&host, composition_event) {
+                    event.mark_as_handled();
+                }
+            }
+        }
+    }
 }
 
 impl Activatable for HTMLElement {
@@ -169,6 +169,16 @@ impl HTMLFormElement {
         false
     }
 
+    /// The input elements of the form.
+    pub fn input_elements(&self) -> Vec<DomRoot<HTMLInputElement>> {
+        self.controls
+            .borrow()
+            .iter()
+            .filter_map(|control| control.downcast::<HTMLInputElement>())
+            .map(DomRoot::from_ref)
+            .collect()
+    }
+
     pub fn nth_for_radio_list(
         &self,
         index: u32,
@@ -1027,17 +1037,20 @@ impl HTMLFormElement {
         };
 
         // Step 3
-        let mut first = true;
-
-        for elem in unhandled_invalid_controls {
+        // The problem of the first control that can be focused is reported.
+        let first = unhandled_invalid_controls
+            .iter()
+            .find_map(|elem| elem.downcast::<HTMLElement>());
+        if let Some(html_elem) = first {
+            html_elem.Focus();
+            let elem = html_elem.upcast::<Element>();
             if let Some(validatable) = elem.as_maybe_validatable() {
-                println!("Validation error: {}", validatable.validation_message());
-            }
-            if first {
-                if let Some(html_elem) = elem.downcast::<HTMLElement>() {
-                    html_elem.Focus();
-                    first = false;
-                }
+                let document = document_from_node(self);
+                document.popups().show_validation_message(
+                    &document,
+                    elem,
+                    validatable.validation_message(),
+                );
             }
         }
 
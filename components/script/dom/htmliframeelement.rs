@@ -32,7 +32,10 @@ use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::document::Document;
 use crate::dom::domtokenlist::DOMTokenList;
-use crate::dom::element::{AttributeMutation, Element, LayoutElementHelpers};
+use crate::dom::element::{
+    reflect_loading_attribute, set_loading_attribute, will_lazy_load_element, AttributeMutation,
+    Element, LayoutElementHelpers,
+};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
@@ -88,6 +91,11 @@ pub struct HTMLIFrameElement {
     sandbox_allowance: Cell<Option<SandboxAllowance>>,
     load_blocker: DomRefCell<Option<LoadBlocker>>,
     visibility: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>, which navigate
+    /// the child browsing context with this load data.
+    #[ignore_malloc_size_of = "LoadData"]
+    #[no_trace]
+    lazy_load_resumption: DomRefCell<Option<(LoadData, HistoryEntryReplacement)>>,
 }
 
 impl HTMLIFrameElement {
@@ -120,6 +128,24 @@ impl HTMLIFrameElement {
         self.start_new_pipeline(load_data, PipelineType::Navigation, replace);
     }
 
+    /// Run the <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps> of this
+    /// iframe, if its navigation was deferred.
+    pub fn resume_lazy_load(&self) {
+        let resumption = self.lazy_load_resumption.borrow_mut().take();
+        if let Some((load_data, replace)) = resumption {
+            self.navigate_or_reload_child_browsing_context(load_data, replace);
+        }
+    }
+
+    /// Forget a deferred navigation, because the iframe attributes are processed again or the
+    /// iframe is removed.
+    fn cancel_lazy_load(&self) {
+        if self.lazy_load_resumption.borrow_mut().take().is_some() {
+            document_from_node(self)
+                .stop_intersection_observing_lazy_loading_element(self.upcast());
+        }
+    }
+
     fn start_new_pipeline(
         &self,
         mut load_data: LoadData,
@@ -244,6 +270,8 @@ impl HTMLIFrameElement {
 
     /// <https://html.spec.whatwg.org/multipage/#process-the-iframe-attributes>
     fn process_the_iframe_attributes(&self, mode: ProcessingMode) {
+        self.cancel_lazy_load();
+
         // > 1. If `element`'s `srcdoc` attribute is specified, then:
         if self
             .upcast::<Element>()
@@ -349,6 +377,15 @@ impl HTMLIFrameElement {
         } else {
             HistoryEntryReplacement::Disabled
         };
+
+        // Step 2.4 of the shared attribute processing steps: if the element will lazy load,
+        // navigate once it gets close to the viewport instead.
+        if will_lazy_load_element(self.upcast()) {
+            *self.lazy_load_resumption.borrow_mut() = Some((load_data, replace));
+            document.start_intersection_observing_lazy_loading_element(self.upcast());
+            return;
+        }
+
         self.navigate_or_reload_child_browsing_context(load_data, replace);
     }
 
@@ -439,6 +476,7 @@ impl HTMLIFrameElement {
             sandbox_allowance: Cell::new(None),
             load_blocker: DomRefCell::new(None),
             visibility: Cell::new(true),
+            lazy_load_resumption: DomRefCell::new(None),
         }
     }
 
@@ -633,6 +671,16 @@ impl HTMLIFrameElementMethods for HTMLIFrameElement {
     // This is specified as reflecting the name content attribute of the
     // element, not the name of the child browsing context.
     make_getter!(Name, "name");
+
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-loading
+    fn Loading(&self) -> DOMString {
+        reflect_loading_attribute(self.upcast())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-iframe-loading
+    fn SetLoading(&self, value: DOMString) {
+        set_loading_attribute(self.upcast(), value);
+    }
 }
 
 impl VirtualMethods for HTMLIFrameElement {
@@ -691,6 +739,18 @@ impl VirtualMethods for HTMLIFrameElement {
                     self.process_the_iframe_attributes(ProcessingMode::NotFirstTime);
                 }
             },
+            name if &**name == "loading" => {
+                // https://html.spec.whatwg.org/multipage/#the-iframe-element:attr-iframe-loading
+                // "When the loading attribute's state is changed to the Eager state, the user
+                // agent must run these steps."
+                if !will_lazy_load_element(self.upcast()) &&
+                    self.lazy_load_resumption.borrow().is_some()
+                {
+                    document_from_node(self)
+                        .stop_intersection_observing_lazy_loading_element(self.upcast());
+                    self.resume_lazy_load();
+                }
+            },
             _ => {},
         }
     }
@@ -736,6 +796,7 @@ impl VirtualMethods for HTMLIFrameElement {
 
         let mut blocker = self.load_blocker.borrow_mut();
         LoadBlocker::terminate(&mut blocker);
+        self.cancel_lazy_load();
 
         // https://html.spec.whatwg.org/multipage/#a-browsing-context-is-discarded
         let window = window_from_node(self);
@@ -5,6 +5,7 @@
 use std::cell::Cell;
 use std::collections::HashSet;
 use std::default::Default;
+use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::{char, i32, mem};
 
@@ -22,10 +23,12 @@ use mime::{self, Mime};
 use msg::constellation_msg::PipelineId;
 use net_traits::image::base::{Image, ImageMetadata};
 use net_traits::image_cache::{
-    CorsStatus, ImageCache, ImageCacheResult, ImageOrMetadataAvailable, ImageResponse,
-    PendingImageId, PendingImageResponse, UsePlaceholder,
+    CorsStatus, ImageCache, ImageCacheResult, ImageDecoding, ImageOrMetadataAvailable,
+    ImageResponse, PendingImageId, PendingImageResponse, UsePlaceholder,
+};
+use net_traits::request::{
+    CorsSettings, Destination, Initiator, Referrer, RequestBuilder, RequestPriority,
 };
-use net_traits::request::{CorsSettings, Destination, Initiator, Referrer, RequestBuilder};
 use net_traits::{
     FetchMetadata, FetchResponseListener, FetchResponseMsg, NetworkError, ReferrerPolicy,
     ResourceFetchTiming, ResourceTimingType,
@@ -51,6 +54,7 @@ use crate::document_loader::{LoadBlocker, LoadType};
 use crate::dom::activation::Activatable;
 use crate::dom::attr::Attr;
 use crate::dom::bindings::cell::{DomRefCell, RefMut};
+use crate::dom::bindings::codegen::Bindings::AttrBinding::AttrMethods;
 use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRect_Binding::DOMRectMethods;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::Element_Binding::ElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
@@ -65,9 +69,11 @@ use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::document::{determine_policy_for_token, Document};
 use crate::dom::element::{
-    cors_setting_for_element, referrer_policy_for_element, reflect_cross_origin_attribute,
-    set_cross_origin_attribute, AttributeMutation, CustomElementCreationMode, Element,
-    ElementCreator, LayoutElementHelpers,
+    cors_setting_for_element, fetch_priority_for_element, referrer_policy_for_element,
+    reflect_cross_origin_attribute, reflect_fetch_priority_attribute, reflect_loading_attribute,
+    set_cross_origin_attribute, set_fetch_priority_attribute, set_loading_attribute,
+    will_lazy_load_element, AttributeMutation, CustomElementCreationMode, Element, ElementCreator,
+    LayoutElementHelpers,
 };
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
@@ -84,6 +90,7 @@ use crate::dom::node::{
     UnbindContext,
 };
 use crate::dom::performanceresourcetiming::InitiatorType;
+use crate::dom::promise::Promise;
 use crate::dom::values::UNSIGNED_LONG_MAX;
 use crate::dom::virtualmethods::VirtualMethods;
 use crate::dom::window::Window;
@@ -91,7 +98,7 @@ use crate::fetch::create_a_potential_cors_request;
 use crate::image_listener::{generate_cache_listener_for_element, ImageCacheListener};
 use crate::microtask::{Microtask, MicrotaskRunnable};
 use crate::network_listener::{self, NetworkListener, PreInvoke, ResourceTimingListener};
-use crate::realms::enter_realm;
+use crate::realms::{enter_realm, InRealm};
 use crate::script_thread::ScriptThread;
 use crate::task_source::TaskSource;
 
@@ -169,6 +176,14 @@ pub struct HTMLImageElement {
     #[ignore_malloc_size_of = "SourceSet"]
     source_set: DomRefCell<SourceSet>,
     last_selected_source: DomRefCell<Option<USVString>>,
+    /// <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps>, which fetch
+    /// this URL.
+    #[no_trace]
+    lazy_load_resumption: DomRefCell<Option<ServoUrl>>,
+    /// The promises returned by `decode()` that wait for the current request to be
+    /// completely available.
+    #[ignore_malloc_size_of = "promises are hard"]
+    image_decode_promises: DomRefCell<Vec<Rc<Promise>>>,
 }
 
 impl HTMLImageElement {
@@ -192,6 +207,30 @@ impl HTMLImageElement {
             State::PartiallyAvailable | State::Unavailable => Ok(false),
         }
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-img-decoding>, whose missing value
+    /// default and invalid value default are the auto state.
+    fn decoding_hint(&self) -> &'static str {
+        self.upcast::<Element>()
+            .get_attribute_by_name(DOMString::from_string(String::from("decoding")))
+            .and_then(|attribute| {
+                let value = attribute.Value();
+                ["sync", "async"]
+                    .iter()
+                    .copied()
+                    .find(|hint| value.eq_ignore_ascii_case(hint))
+            })
+            .unwrap_or("auto")
+    }
+
+    /// How the image cache should decode images of this element that are fetched but
+    /// not decoded yet. Servo decodes them right away in the auto state.
+    fn image_decoding(&self) -> ImageDecoding {
+        match self.decoding_hint() {
+            "async" => ImageDecoding::Async,
+            _ => ImageDecoding::Sync,
+        }
+    }
 }
 
 /// The context required for asynchronously loading an external image.
@@ -311,13 +350,15 @@ pub(crate) fn image_fetch_request(
     pipeline_id: PipelineId,
     cors_setting: Option<CorsSettings>,
     referrer_policy: Option<ReferrerPolicy>,
+    fetch_priority: RequestPriority,
     from_picture_or_srcset: FromPictureOrSrcSet,
 ) -> RequestBuilder {
     let mut request =
         create_a_potential_cors_request(img_url, Destination::Image, cors_setting, None, referrer)
             .origin(origin)
             .pipeline_id(Some(pipeline_id))
-            .referrer_policy(referrer_policy);
+            .referrer_policy(referrer_policy)
+            .priority(fetch_priority);
     if from_picture_or_srcset == FromPictureOrSrcSet::Yes {
         request = request.initiator(Initiator::ImageSet);
     }
@@ -337,6 +378,7 @@ impl HTMLImageElement {
             cors_setting_for_element(self.upcast()),
             sender,
             UsePlaceholder::Yes,
+            self.image_decoding(),
         );
 
         match cache_result {
@@ -398,6 +440,7 @@ impl HTMLImageElement {
             document.global().pipeline_id(),
             cors_setting_for_element(self.upcast()),
             referrer_policy_for_element(self.upcast()),
+            fetch_priority_for_element(self.upcast()),
             if Self::uses_srcset_or_picture(self.upcast()) {
                 FromPictureOrSrcSet::Yes
             } else {
@@ -473,6 +516,8 @@ impl HTMLImageElement {
 
         // Fire image.onload and loadend
         if trigger_image_load {
+            // Images are decoded by the image cache before they are sent here.
+            self.resolve_image_decode_promises();
             // TODO: https://html.spec.whatwg.org/multipage/#fire-a-progress-event-or-event
             self.upcast::<EventTarget>().fire_event(atom!("load"));
             self.upcast::<EventTarget>().fire_event(atom!("loadend"));
@@ -480,6 +525,7 @@ impl HTMLImageElement {
 
         // Fire image.onerror
         if trigger_image_error {
+            self.reject_image_decode_promises();
             self.upcast::<EventTarget>().fire_event(atom!("error"));
             self.upcast::<EventTarget>().fire_event(atom!("loadend"));
         }
@@ -515,16 +561,60 @@ impl HTMLImageElement {
         };
     }
 
+    /// Step 2 of <https://html.spec.whatwg.org/multipage/#dom-img-decode>
+    fn react_to_decode_image_sync_steps(&self, promise: Rc<Promise>) {
+        // Step 2.1. If this's node document is not fully active or this's current
+        // request's state is broken, then reject promise with an "EncodingError".
+        if !document_from_node(self).is_fully_active() {
+            return promise.reject_error(Error::Encoding);
+        }
+        let state = self.current_request.borrow().state;
+        match state {
+            State::Broken => promise.reject_error(Error::Encoding),
+            // The image cache sends images once they are decoded, so there is nothing
+            // left to wait for. See <https://github.com/whatwg/html/issues/4217>.
+            State::CompletelyAvailable => promise.resolve_native(&()),
+            // Step 2.2. Otherwise, wait for the current request to change.
+            State::PartiallyAvailable | State::Unavailable => {
+                self.image_decode_promises.borrow_mut().push(promise)
+            },
+        }
+    }
+
+    fn resolve_image_decode_promises(&self) {
+        let promises = mem::take(&mut *self.image_decode_promises.borrow_mut());
+        for promise in promises {
+            promise.resolve_native(&());
+        }
+    }
+
+    fn reject_image_decode_promises(&self) {
+        let promises = mem::take(&mut *self.image_decode_promises.borrow_mut());
+        for promise in promises {
+            promise.reject_error(Error::Encoding);
+        }
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#abort-the-image-request>
     fn abort_request(&self, state: State, request: ImageRequestPhase) {
-        let mut request = match request {
-            ImageRequestPhase::Current => self.current_request.borrow_mut(),
-            ImageRequestPhase::Pending => self.pending_request.borrow_mut(),
-        };
-        LoadBlocker::terminate(&mut request.blocker);
-        request.state = state;
-        request.image = None;
-        request.metadata = None;
+        // A current request that becomes broken can never be decoded.
+        let breaks_current_request = matches!(
+            (state, request),
+            (State::Broken, ImageRequestPhase::Current)
+        );
+        {
+            let mut request = match request {
+                ImageRequestPhase::Current => self.current_request.borrow_mut(),
+                ImageRequestPhase::Pending => self.pending_request.borrow_mut(),
+            };
+            LoadBlocker::terminate(&mut request.blocker);
+            request.state = state;
+            request.image = None;
+            request.metadata = None;
+        }
+        if breaks_current_request {
+            self.reject_image_decode_promises();
+        }
     }
 
     /// <https://html.spec.whatwg.org/multipage/#update-the-source-set>
@@ -843,9 +933,42 @@ impl HTMLImageElement {
                 }
             },
         }
+
+        // If the element will lazy load, the rest of the algorithm, starting with fetching the
+        // image, runs once it gets close to the viewport instead. Until then it does not delay the load event, and layout sizes
+        // it from its attributes as it has no image.
+        if will_lazy_load_element(self.upcast()) {
+            let mut request = match self.image_request.get() {
+                ImageRequestPhase::Current => self.current_request.borrow_mut(),
+                ImageRequestPhase::Pending => self.pending_request.borrow_mut(),
+            };
+            LoadBlocker::terminate(&mut request.blocker);
+            *self.lazy_load_resumption.borrow_mut() = Some(url.clone());
+            document_from_node(self)
+                .start_intersection_observing_lazy_loading_element(self.upcast());
+            return;
+        }
+
         self.fetch_image(&url);
     }
 
+    /// Run the <https://html.spec.whatwg.org/multipage/#lazy-load-resumption-steps> of this
+    /// image, if its fetch was deferred.
+    pub fn resume_lazy_load(&self) {
+        let url = self.lazy_load_resumption.borrow_mut().take();
+        if let Some(url) = url {
+            self.fetch_image(&url);
+        }
+    }
+
+    /// Forget a deferred fetch, because the image data is updated again.
+    fn cancel_lazy_load(&self) {
+        if self.lazy_load_resumption.borrow_mut().take().is_some() {
+            document_from_node(self)
+                .stop_intersection_observing_lazy_loading_element(self.upcast());
+        }
+    }
+
     /// Step 8-12 of html.spec.whatwg.org/multipage/#update-the-image-data
     fn update_the_image_data_sync_steps(&self) {
         let document = document_from_node(self);
@@ -915,6 +1038,8 @@ impl HTMLImageElement {
 
     /// <https://html.spec.whatwg.org/multipage/#update-the-image-data>
     pub fn update_the_image_data(&self) {
+        self.cancel_lazy_load();
+
         let document = document_from_node(self);
         let window = document.window();
         let elem = self.upcast::<Element>();
@@ -928,6 +1053,8 @@ impl HTMLImageElement {
             let mut current_request = self.current_request.borrow_mut();
             current_request.state = State::Unavailable;
         }
+        // The current request changes, so decode() can no longer resolve for it.
+        self.reject_image_decode_promises();
 
         if !document.is_active() {
             // Step 1 (if the document is inactive)
@@ -1127,6 +1254,7 @@ impl HTMLImageElement {
             cors_setting_for_element(self.upcast()),
             sender,
             UsePlaceholder::No,
+            self.image_decoding(),
         );
 
         match cache_result {
@@ -1195,6 +1323,7 @@ impl HTMLImageElement {
                     mem::swap(&mut this.current_request.borrow_mut(), &mut pending_request);
                     this.abort_request(State::Unavailable, ImageRequestPhase::Pending);
                 }
+                this.reject_image_decode_promises();
 
                 // Step 15.6
                 this.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
@@ -1247,6 +1376,8 @@ impl HTMLImageElement {
             generation: Default::default(),
             source_set: DomRefCell::new(SourceSet::new()),
             last_selected_source: DomRefCell::new(None),
+            lazy_load_resumption: DomRefCell::new(None),
+            image_decode_promises: DomRefCell::new(vec![]),
         }
     }
 
@@ -1345,6 +1476,11 @@ pub enum ImageElementMicrotask {
         elem: DomRoot<HTMLImageElement>,
         generation: u32,
     },
+    DecodeTask {
+        elem: DomRoot<HTMLImageElement>,
+        #[ignore_malloc_size_of = "promises are hard"]
+        promise: Rc<Promise>,
+    },
 }
 
 impl MicrotaskRunnable for ImageElementMicrotask {
@@ -1366,13 +1502,20 @@ impl MicrotaskRunnable for ImageElementMicrotask {
             } => {
                 elem.react_to_environment_changes_sync_steps(*generation);
             },
+            &ImageElementMicrotask::DecodeTask {
+                ref elem,
+                ref promise,
+            } => {
+                elem.react_to_decode_image_sync_steps(promise.clone());
+            },
         }
     }
 
     fn enter_realm(&self) -> JSAutoRealm {
         match self {
             &ImageElementMicrotask::StableStateUpdateImageDataTask { ref elem, .. } |
-            &ImageElementMicrotask::EnvironmentChangesTask { ref elem, .. } => enter_realm(&**elem),
+            &ImageElementMicrotask::EnvironmentChangesTask { ref elem, .. } |
+            &ImageElementMicrotask::DecodeTask { ref elem, .. } => enter_realm(&**elem),
         }
     }
 }
@@ -1610,6 +1753,54 @@ impl HTMLImageElementMethods for HTMLImageElement {
         }
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-img-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value)
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-decoding
+    fn Decoding(&self) -> DOMString {
+        DOMString::from(self.decoding_hint())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-decoding
+    fn SetDecoding(&self, value: DOMString) {
+        self.upcast::<Element>()
+            .set_string_attribute(&LocalName::from("decoding"), value);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-img-decode>
+    fn Decode(&self, comp: InRealm) -> Rc<Promise> {
+        // Step 1. Let promise be a new promise.
+        let promise = Promise::new_in_current_realm(comp);
+
+        // Step 2. Queue a microtask to perform the following steps.
+        let task = ImageElementMicrotask::DecodeTask {
+            elem: DomRoot::from_ref(self),
+            promise: promise.clone(),
+        };
+        self.global()
+            .enqueue_microtask(Microtask::ImageElement(task));
+
+        // Step 3. Return promise.
+        promise
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    fn Loading(&self) -> DOMString {
+        reflect_loading_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-img-loading
+    fn SetLoading(&self, value: DOMString) {
+        set_loading_attribute(self.upcast::<Element>(), value)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-img-name
     make_getter!(Name, "name");
 
@@ -1666,6 +1857,18 @@ impl VirtualMethods for HTMLImageElement {
             &local_name!("crossorigin") |
             &local_name!("sizes") |
             &local_name!("referrerpolicy") => self.update_the_image_data(),
+            name if &**name == "loading" => {
+                // https://html.spec.whatwg.org/multipage/#the-img-element:attr-img-loading
+                // "When the loading attribute's state is changed to the Eager state, the user
+                // agent must run these steps."
+                if !will_lazy_load_element(self.upcast()) &&
+                    self.lazy_load_resumption.borrow().is_some()
+                {
+                    document_from_node(self)
+                        .stop_intersection_observing_lazy_loading_element(self.upcast());
+                    self.resume_lazy_load();
+                }
+            },
             _ => {},
         }
     }
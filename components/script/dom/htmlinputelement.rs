@@ -11,9 +11,14 @@ use std::{f64, ptr};
 use chrono::naive::{NaiveDate, NaiveDateTime};
 use chrono::{Datelike, Weekday};
 use dom_struct::dom_struct;
-use embedder_traits::FilterPattern;
+use embedder_traits::{
+    AutofillField, AutofillForm, AutofillFormKind, EmbedderMsg, FilterPattern, InputPicker,
+    InputPickerKind, PromptDefinition, PromptOrigin,
+};
 use encoding_rs::Encoding;
+use euclid::Length;
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
+use ipc_channel::router::ROUTER;
 use js::jsapi::{
     ClippedTime, DateGetMsecSinceEpoch, Handle, JSObject, JS_ClearPendingException, NewDateObject,
     NewUCRegExpObject, ObjectIsDate, RegExpFlag_Unicode, RegExpFlags,
@@ -21,6 +26,7 @@ use js::jsapi::{
 use js::jsval::UndefinedValue;
 use js::rust::jsapi_wrapped::{ExecuteRegExpNoStatics, ObjectIsRegExp};
 use js::rust::{HandleObject, MutableHandleObject};
+use keyboard_types::{Key, Modifiers};
 use msg::constellation_msg::InputMethodType;
 use net_traits::blob_url_store::get_blob_origin;
 use net_traits::filemanager_thread::FileManagerThreadMsg;
@@ -29,6 +35,8 @@ use profile_traits::ipc;
 use script_layout_interface::rpc::TextIndexResponse;
 use script_traits::ScriptToConstellationChan;
 use servo_atoms::Atom;
+use servo_config::pref;
+use servo_url::ServoUrl;
 use style::attr::AttrValue;
 use style::str::{split_commas, str_join};
 use style_traits::dom::ElementState;
@@ -41,11 +49,16 @@ use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
 use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
 use crate::dom::bindings::codegen::Bindings::FileListBinding::FileListMethods;
-use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
+use crate::dom::bindings::codegen::Bindings::HTMLDataListElementBinding::HTMLDataListElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::{
+    HTMLFormElementMethods, SelectionMode,
+};
 use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
 use crate::dom::bindings::error::{Error, ErrorResult};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::{DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::{DOMString, USVString};
@@ -64,6 +77,7 @@ use crate::dom::htmlformelement::{
     FormControl, FormDatum, FormDatumValue, FormSubmitter, HTMLFormElement, ResetFrom,
     SubmittedFrom,
 };
+use crate::dom::htmloptionelement::HTMLOptionElement;
 use crate::dom::keyboardevent::KeyboardEvent;
 use crate::dom::mouseevent::MouseEvent;
 use crate::dom::node::{
@@ -75,17 +89,26 @@ use crate::dom::textcontrol::{TextControlElement, TextControlSelection};
 use crate::dom::validation::{is_barred_by_datalist_ancestor, Validatable};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::popups;
 use crate::realms::enter_realm;
 use crate::script_runtime::JSContext as SafeJSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
 use crate::textinput::KeyReaction::{
     DispatchInput, Nothing, RedrawSelection, TriggerDefaultAction,
 };
 use crate::textinput::Lines::Single;
-use crate::textinput::{Direction, SelectionDirection, TextInput, UTF16CodeUnits, UTF8Bytes};
+use crate::textinput::{
+    Direction, SelectionDirection, TextInput, UTF16CodeUnits, UTF8Bytes, CMD_OR_CONTROL,
+};
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
 
 const DEFAULT_SUBMIT_VALUE: &'static str = "Submit";
 const DEFAULT_RESET_VALUE: &'static str = "Reset";
 const PASSWORD_REPLACEMENT_CHAR: char = '●';
+/// The spin buttons of number fields, drawn after their value as text.
+const SPIN_BUTTONS: &'static str = "\u{a0}\u{25b4}\u{25be}";
+/// The button clearing the value of search fields, drawn after it as text.
+const CLEAR_BUTTON: &'static str = "\u{a0}\u{2715}";
 
 #[derive(Clone, Copy, JSTraceable, PartialEq)]
 #[allow(dead_code)]
@@ -264,6 +287,10 @@ pub struct HTMLInputElement {
     // happen until after all of step/min/max/value content attributes have
     // been added
     sanitization_flag: Cell<bool>,
+    /// The index in characters of the last character typed into a password field, which is
+    /// shown unmasked until `password_reveal_timer` fires.
+    revealed_password_character: Cell<Option<usize>>,
+    password_reveal_timer: Cell<Option<OneshotTimerHandle>>,
 
     filelist: MutNullableDom<FileList>,
     form_owner: MutNullableDom<HTMLFormElement>,
@@ -320,6 +347,8 @@ impl HTMLInputElement {
             )),
             value_dirty: Cell::new(false),
             sanitization_flag: Cell::new(true),
+            revealed_password_character: Cell::new(None),
+            password_reveal_timer: Cell::new(None),
             filelist: MutNullableDom::new(None),
             form_owner: Default::default(),
             labels_node_list: MutNullableDom::new(None),
@@ -770,6 +799,42 @@ impl HTMLInputElement {
             .map(|el| DomRoot::from_ref(&*el))
     }
 
+    /// The values of the suggestions source element to offer the user for the current value,
+    /// which are those that contain it but are not it.
+    /// <https://html.spec.whatwg.org/multipage/#the-list-attribute>
+    pub(crate) fn suggestions(&self) -> Vec<DOMString> {
+        // The dropdown is only offered for fields where any text can be typed.
+        let offers_suggestions = matches!(
+            self.input_type(),
+            InputType::Text |
+                InputType::Search |
+                InputType::Url |
+                InputType::Tel |
+                InputType::Email
+        );
+        if !offers_suggestions || !self.is_mutable() {
+            return vec![];
+        }
+        let datalist = match self.suggestions_source_element() {
+            Some(datalist) => datalist,
+            None => return vec![],
+        };
+        let value = self.Value();
+        let lowercase_value = value.to_lowercase();
+        datalist
+            .Options()
+            .elements_iter()
+            .filter_map(DomRoot::downcast::<HTMLOptionElement>)
+            .filter(|option| !option.upcast::<Element>().disabled_state())
+            .map(|option| option.Value())
+            .filter(|suggestion| {
+                !suggestion.is_empty() &&
+                    *suggestion != value &&
+                    suggestion.to_lowercase().contains(&lowercase_value)
+            })
+            .collect()
+    }
+
     // https://html.spec.whatwg.org/multipage/#suffering-from-being-missing
     fn suffers_from_being_missing(&self, value: &DOMString) -> bool {
         match self.input_type() {
@@ -1004,6 +1069,28 @@ impl<'dom> LayoutDom<'dom, HTMLInputElement> {
         unsafe { self.unsafe_get().input_type.get() }
     }
 
+    fn revealed_password_character(self) -> Option<usize> {
+        unsafe { self.unsafe_get().revealed_password_character.get() }
+    }
+
+    /// The text standing for the controls of the field that are drawn after its value.
+    fn controls_for_layout(self) -> &'static str {
+        let element = self.upcast::<Element>();
+        let mutable = !element
+            .get_state_for_layout()
+            .contains(ElementState::DISABLED) &&
+            element
+                .get_attr_for_layout(&ns!(), &local_name!("readonly"))
+                .is_none();
+        match self.input_type() {
+            InputType::Number if mutable => SPIN_BUTTONS,
+            InputType::Search if mutable && !self.get_raw_textinput_value().is_empty() => {
+                CLEAR_BUTTON
+            },
+            _ => "",
+        }
+    }
+
     fn textinput_sorted_selection_offsets_range(self) -> Range<UTF8Bytes> {
         unsafe {
             self.unsafe_get()
@@ -1036,8 +1123,20 @@ impl<'dom> LayoutHTMLInputElementHelpers<'dom> for LayoutDom<'dom, HTMLInputElem
             InputType::Password => {
                 let text = self.get_raw_textinput_value();
                 if !text.is_empty() {
+                    // The character that was just typed stays readable while the field
+                    // has focus.
+                    let revealed = self
+                        .revealed_password_character()
+                        .filter(|_| self.upcast::<Element>().focus_state());
                     text.chars()
-                        .map(|_| PASSWORD_REPLACEMENT_CHAR)
+                        .enumerate()
+                        .map(|(index, c)| {
+                            if Some(index) == revealed {
+                                c
+                            } else {
+                                PASSWORD_REPLACEMENT_CHAR
+                            }
+                        })
                         .collect::<String>()
                         .into()
                 } else {
@@ -1046,10 +1145,14 @@ impl<'dom> LayoutHTMLInputElementHelpers<'dom> for LayoutDom<'dom, HTMLInputElem
             },
             _ => {
                 let text = self.get_raw_textinput_value();
-                if !text.is_empty() {
+                let shown: Cow<'dom, str> = if !text.is_empty() {
                     text.into()
                 } else {
                     self.placeholder().into()
+                };
+                match self.controls_for_layout() {
+                    "" => shown,
+                    controls => format!("{}{}", shown, controls).into(),
                 }
             },
         }
@@ -1271,6 +1374,7 @@ impl HTMLInputElementMethods for HTMLInputElement {
 
                 // Step 4.
                 self.sanitize_value(&mut value);
+                self.mask_password_character();
 
                 let mut textinput = self.textinput.borrow_mut();
 
@@ -1817,6 +1921,285 @@ impl HTMLInputElement {
         //TODO: dispatch change event
     }
 
+    /// Offer the embedder to fill in the form of this field, which just gained focus, if it
+    /// is a login or address form.
+    pub fn request_autofill(&self) {
+        let form = match self.form_owner() {
+            Some(form) => form,
+            None => return,
+        };
+        let fields: Vec<DomRoot<HTMLInputElement>> = form
+            .input_elements()
+            .into_iter()
+            .filter(|input| input.input_type().is_textual_or_password() && input.is_mutable())
+            .collect();
+        let focused_field = match fields.iter().position(|input| &**input == self) {
+            Some(index) => index,
+            None => return,
+        };
+        let descriptions: Vec<AutofillField> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, input)| input.autofill_description(index))
+            .collect();
+        let kind = if fields
+            .iter()
+            .any(|input| input.input_type() == InputType::Password)
+        {
+            AutofillFormKind::Login
+        } else if descriptions.iter().any(|field| {
+            field
+                .autocomplete
+                .iter()
+                .any(|token| is_address_autofill_token(token))
+        }) {
+            AutofillFormKind::Address
+        } else {
+            return;
+        };
+        // Only password managers may ignore that a form asks not to be filled in.
+        if kind == AutofillFormKind::Address && &*form.Autocomplete() == "off" {
+            return;
+        }
+
+        let window = window_from_node(self);
+        let description = AutofillForm {
+            kind,
+            url: window.get_url(),
+            action: ServoUrl::parse(&form.Action()).ok(),
+            fields: descriptions,
+            focused_field,
+        };
+        let (sender, receiver) = ipc::channel(window.time_profiler_chan().clone()).unwrap();
+        let task_source = window.task_manager().user_interaction_task_source();
+        let canceller = window
+            .upcast::<GlobalScope>()
+            .task_canceller(TaskSourceName::UserInteraction);
+        let fields: Vec<Trusted<HTMLInputElement>> =
+            fields.iter().map(|input| Trusted::new(&**input)).collect();
+        let mut fields = Some(fields);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                // The router doesn't know that a single reply is ever sent.
+                let fields = match fields.take() {
+                    Some(fields) => fields,
+                    None => return,
+                };
+                let values: Vec<(usize, String)> = match message.to() {
+                    Ok(values) => values,
+                    Err(_) => return,
+                };
+                let _ = task_source.queue_with_canceller(
+                    task!(autofill_form: move || {
+                        for (index, value) in values {
+                            if let Some(input) = fields.get(index) {
+                                input.root().autofill(DOMString::from(value));
+                            }
+                        }
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        window.send_to_embedder(EmbedderMsg::AutofillRequested(description, sender));
+    }
+
+    fn autofill_description(&self, index: usize) -> AutofillField {
+        let element = self.upcast::<Element>();
+        let autocomplete = element
+            .get_string_attribute(&local_name!("autocomplete"))
+            .split_whitespace()
+            .map(|token| token.to_ascii_lowercase())
+            .collect();
+        let label = self
+            .GetLabels()
+            .map(|labels| {
+                labels
+                    .iter()
+                    .map(|label| String::from(label.GetTextContent().unwrap_or_default()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .unwrap_or_default();
+        AutofillField {
+            index,
+            input_type: self.input_type().to_str().to_owned(),
+            name: element.get_string_attribute(&local_name!("name")).into(),
+            id: element.get_string_attribute(&local_name!("id")).into(),
+            autocomplete,
+            label: label.trim().to_owned(),
+            value: self.Value().into(),
+        }
+    }
+
+    /// Fill in the field with a value from the embedder or one of its suggestions, as if the
+    /// user typed it.
+    pub(crate) fn autofill(&self, value: DOMString) {
+        if !self.is_mutable() || self.SetValue(value).is_err() {
+            return;
+        }
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"));
+        target.fire_bubbling_event(atom!("change"));
+    }
+
+    /// Handle a click on the character at `index` of the text drawn for the field, returning
+    /// whether it hit one of the controls drawn after its value.
+    fn handle_control_click(&self, index: usize) -> bool {
+        if !self.is_mutable() {
+            return false;
+        }
+        let shown = {
+            let textinput = self.textinput.borrow();
+            if textinput.is_empty() {
+                self.placeholder.borrow().chars().count()
+            } else {
+                textinput.get_content().chars().count()
+            }
+        };
+        // The controls are separated from the value by a space.
+        match (self.input_type(), index.checked_sub(shown)) {
+            (InputType::Number, Some(1)) => self.spin(StepDirection::Up),
+            (InputType::Number, Some(2)) => self.spin(StepDirection::Down),
+            (InputType::Search, Some(1)) if !self.textinput.borrow().is_empty() => {
+                self.clear_search()
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Handle the keys that operate the controls of number and search fields, returning
+    /// whether the key was used.
+    fn handle_control_keydown(&self, event: &KeyboardEvent) -> bool {
+        if !self.is_mutable() || event.modifiers() != Modifiers::empty() {
+            return false;
+        }
+        match (self.input_type(), event.key()) {
+            (InputType::Number, Key::ArrowUp) => self.spin(StepDirection::Up),
+            (InputType::Number, Key::ArrowDown) => self.spin(StepDirection::Down),
+            (InputType::Search, Key::Escape) if !self.textinput.borrow().is_empty() => {
+                self.clear_search()
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Step the value of a number field as its spin buttons do, firing the events a user
+    /// would cause if the value changed.
+    fn spin(&self, dir: StepDirection) {
+        self.commit_typed_number();
+        let old_value = self.Value();
+        if self.step_up_or_down(1, dir).is_err() || self.Value() == old_value {
+            return;
+        }
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"));
+        target.fire_bubbling_event(atom!("change"));
+    }
+
+    /// Accept numbers typed in a number field with a comma as their decimal separator, as
+    /// used by many locales, by converting them to valid floating-point numbers.
+    fn commit_typed_number(&self) {
+        if self.input_type() != InputType::Number {
+            return;
+        }
+        let typed: String = self
+            .textinput
+            .borrow()
+            .get_content()
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        if DOMString::from(typed.as_str()).is_valid_floating_point_number_string() ||
+            typed.contains('.') ||
+            typed.matches(',').count() != 1
+        {
+            return;
+        }
+        let number = DOMString::from(typed.replace(',', "."));
+        if !number.is_valid_floating_point_number_string() {
+            return;
+        }
+        self.textinput.borrow_mut().set_content(number);
+        self.value_dirty.set(true);
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+    }
+
+    /// Clear the value of a search field as its clear button does.
+    fn clear_search(&self) {
+        self.textinput.borrow_mut().set_content(DOMString::new());
+        self.value_dirty.set(true);
+        self.update_placeholder_shown_state();
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"));
+        target.fire_bubbling_event(atom!("search"));
+    }
+
+    /// The picker that the embedder draws for the value of this input, if it has one.
+    fn picker_kind(&self) -> Option<InputPickerKind> {
+        match self.input_type() {
+            InputType::Date => Some(InputPickerKind::Date),
+            InputType::Time => Some(InputPickerKind::Time),
+            InputType::DatetimeLocal => Some(InputPickerKind::DateTimeLocal),
+            InputType::Color => Some(InputPickerKind::Color),
+            _ => None,
+        }
+    }
+
+    /// Ask the embedder to pick a new value for a date, time or color input, and set it as
+    /// the user would, firing the events they would cause if the value changed.
+    fn pick_value(&self) {
+        let kind = match self.picker_kind() {
+            Some(kind) => kind,
+            None => return,
+        };
+        let element = self.upcast::<Element>();
+        let limit = |name| {
+            let value = element.get_string_attribute(&name);
+            (!value.is_empty()).then(|| value.into())
+        };
+        let picker = InputPicker {
+            kind,
+            value: self.Value().into(),
+            min: limit(local_name!("min")),
+            max: limit(local_name!("max")),
+            anchor: popups::device_client_rect(element),
+        };
+        let (sender, receiver) = ipc::channel(self.global().time_profiler_chan().clone())
+            .expect("Error initializing channel");
+        let prompt = PromptDefinition::PickValue(picker, sender);
+        window_from_node(self)
+            .send_to_embedder(EmbedderMsg::Prompt(prompt, PromptOrigin::Untrusted));
+        let picked = match receiver.recv() {
+            Ok(Some(picked)) => DOMString::from(picked),
+            _ => return,
+        };
+
+        // Values that are not valid for the input are ignored, rather than clearing it or,
+        // for colors, making it black.
+        let mut value = picked.clone();
+        self.sanitize_value(&mut value);
+        let valid = match kind {
+            InputPickerKind::Color => picked.is_valid_simple_color_string(),
+            _ => picked.is_empty() || !value.is_empty(),
+        };
+        if !valid || value == self.Value() {
+            return;
+        }
+        self.textinput.borrow_mut().set_content(value);
+        self.value_dirty.set(true);
+        self.update_placeholder_shown_state();
+        self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        let target = self.upcast::<EventTarget>();
+        target.fire_bubbling_event(atom!("input"));
+        target.fire_bubbling_event(atom!("change"));
+    }
+
     // https://html.spec.whatwg.org/multipage/#concept-fe-mutable
     fn is_mutable(&self) -> bool {
         // https://html.spec.whatwg.org/multipage/#the-input-element:concept-fe-mutable
@@ -1839,6 +2222,68 @@ impl HTMLInputElement {
         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
     }
 
+    /// Keep the clipboard and the embedder in step with whether this is a password field.
+    fn input_type_changed(&self, old_type: InputType) {
+        let is_password = self.input_type() == InputType::Password;
+        self.mask_password_character();
+        self.textinput
+            .borrow_mut()
+            .set_clipboard_copy_allowed(!is_password || pref!(dom.forms.password.allow_copy));
+        if (old_type == InputType::Password) != is_password &&
+            self.upcast::<Element>().focus_state()
+        {
+            window_from_node(self).send_to_embedder(EmbedderMsg::SecureTextInput(is_password));
+        }
+    }
+
+    /// Leave the character that was just typed into a password field readable for
+    /// `dom.forms.password.reveal_duration_ms`, so that typos can be spotted.
+    fn reveal_typed_password_character(&self, event: &KeyboardEvent) {
+        self.mask_password_character();
+        let duration = pref!(dom.forms.password.reveal_duration_ms);
+        if self.input_type() != InputType::Password || duration <= 0 {
+            return;
+        }
+        // Shortcuts such as pasting also dispatch input, but do not type a character.
+        if !matches!(event.key(), Key::Character(_)) ||
+            event
+                .modifiers()
+                .intersects(CMD_OR_CONTROL | Modifiers::ALT)
+        {
+            return;
+        }
+        let index = {
+            let textinput = self.textinput.borrow();
+            let edit_point = textinput.edit_point().index.0;
+            textinput.single_line_content()[..edit_point]
+                .chars()
+                .count()
+                .checked_sub(1)
+        };
+        let index = match index {
+            Some(index) => index,
+            None => return,
+        };
+        self.revealed_password_character.set(Some(index));
+        let callback = OneshotTimerCallback::PasswordRevealTimeout(PasswordRevealTimeoutCallback {
+            input: Trusted::new(self),
+        });
+        let handle = self
+            .global()
+            .schedule_callback(callback, Length::new(duration as u64));
+        self.password_reveal_timer.set(Some(handle));
+    }
+
+    /// Mask the character of a password field that was left readable, if any.
+    fn mask_password_character(&self) {
+        if let Some(handle) = self.password_reveal_timer.take() {
+            self.global().unschedule_callback(handle);
+        }
+        if self.revealed_password_character.take().is_some() {
+            self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
+        }
+    }
+
     fn update_placeholder_shown_state(&self) {
         if !self.input_type().is_textual_or_password() {
             return;
@@ -2337,8 +2782,10 @@ impl VirtualMethods for HTMLInputElement {
                         // https://html.spec.whatwg.org/multipage/#input-type-change
                         let (old_value_mode, old_idl_value) = (self.value_mode(), self.Value());
                         let previously_selectable = self.selection_api_applies();
+                        let old_type = self.input_type();
 
                         self.input_type.set(new_type);
+                        self.input_type_changed(old_type);
 
                         if new_type.is_textual() {
                             let read_write = !(self.ReadOnly() || el.disabled_state());
@@ -2407,7 +2854,9 @@ impl VirtualMethods for HTMLInputElement {
                         if self.input_type() == InputType::Radio {
                             broadcast_radio_checked(self, self.radio_group_name().as_ref());
                         }
+                        let old_type = self.input_type();
                         self.input_type.set(InputType::default());
+                        self.input_type_changed(old_type);
                         let el = self.upcast::<Element>();
 
                         let read_write = !(self.ReadOnly() || el.disabled_state());
@@ -2551,10 +3000,7 @@ impl VirtualMethods for HTMLInputElement {
 
             //TODO: set the editing position for text inputs
 
-            if self.input_type().is_textual_or_password() &&
-                // Check if we display a placeholder. Layout doesn't know about this.
-                !self.textinput.borrow().is_empty()
-            {
+            if self.input_type().is_textual_or_password() {
                 if let Some(mouse_event) = event.downcast::<MouseEvent>() {
                     // dispatch_key_event (document.rs) triggers a click event when releasing
                     // the space key. There's no nice way to catch this so let's use this for
@@ -2563,7 +3009,12 @@ impl VirtualMethods for HTMLInputElement {
                         let window = window_from_node(self);
                         let TextIndexResponse(index) =
                             window.text_index_query(self.upcast::<Node>(), point_in_target);
-                        if let Some(i) = index {
+                        if index.map_or(false, |i| self.handle_control_click(i)) {
+                            event.PreventDefault();
+                        } else if let Some(i) = index.filter(|_| {
+                            // Check if we display a placeholder. Layout doesn't know about this.
+                            !self.textinput.borrow().is_empty()
+                        }) {
                             self.textinput.borrow_mut().set_edit_point_index(i as usize);
                             // trigger redraw
                             self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
@@ -2577,18 +3028,43 @@ impl VirtualMethods for HTMLInputElement {
             self.input_type().is_textual_or_password()
         {
             if let Some(keyevent) = event.downcast::<KeyboardEvent>() {
+                // The keys that navigate the suggestions don't edit the field.
+                let document = document_from_node(self);
+                if document
+                    .popups()
+                    .handle_suggestions_keydown(&document, self, keyevent)
+                {
+                    event.PreventDefault();
+                    event.mark_as_handled();
+                    return;
+                }
+
+                // The arrow keys step numbers, and the escape key clears search fields.
+                if self.handle_control_keydown(keyevent) {
+                    event.PreventDefault();
+                    event.mark_as_handled();
+                    return;
+                }
+
                 // This can't be inlined, as holding on to textinput.borrow_mut()
                 // during self.implicit_submission will cause a panic.
                 let action = self.textinput.borrow_mut().handle_keydown(keyevent);
                 match action {
                     TriggerDefaultAction => {
+                        self.commit_typed_number();
+                        if self.input_type() == InputType::Search {
+                            self.upcast::<EventTarget>()
+                                .fire_bubbling_event(atom!("search"));
+                        }
                         self.implicit_submission();
                     },
                     DispatchInput => {
                         self.value_dirty.set(true);
+                        self.reveal_typed_password_character(keyevent);
                         self.update_placeholder_shown_state();
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
                         event.mark_as_handled();
+                        document.popups().update_suggestions(&document, self);
                     },
                     RedrawSelection => {
                         self.upcast::<Node>().dirty(NodeDamage::OtherNodeDamage);
@@ -2597,6 +3073,8 @@ impl VirtualMethods for HTMLInputElement {
                     Nothing => (),
                 }
             }
+        } else if event.type_() == atom!("blur") {
+            self.commit_typed_number();
         } else if event.type_() == atom!("keypress") &&
             !event.DefaultPrevented() &&
             self.input_type().is_textual_or_password()
@@ -2758,6 +3236,10 @@ impl Activatable for HTMLInputElement {
             // https://html.spec.whatwg.org/multipage/#radio-button-state-%28type=radio%29:activation-behaviour-2
             InputType::Submit | InputType::Reset | InputType::File => self.is_mutable(),
             InputType::Checkbox | InputType::Radio => true,
+            // Date, time and color inputs show the picker of the embedder.
+            InputType::Date | InputType::Time | InputType::DatetimeLocal | InputType::Color => {
+                self.is_mutable()
+            },
             _ => false,
         }
     }
@@ -2878,6 +3360,9 @@ impl Activatable for HTMLInputElement {
                 target.fire_bubbling_event(atom!("change"));
             },
             InputType::File => self.select_files(None),
+            InputType::Date | InputType::Time | InputType::DatetimeLocal | InputType::Color => {
+                self.pick_value()
+            },
             _ => (),
         }
     }
@@ -2953,6 +3438,42 @@ fn new_js_regex(cx: SafeJSContext, pattern: &str, mut out_regex: MutableHandleOb
     true
 }
 
+/// Whether an `autocomplete` token describes a part of an address.
+/// <https://html.spec.whatwg.org/multipage/#autofill-field>
+fn is_address_autofill_token(token: &str) -> bool {
+    matches!(
+        token,
+        "street-address" |
+            "address-line1" |
+            "address-line2" |
+            "address-line3" |
+            "address-level1" |
+            "address-level2" |
+            "address-level3" |
+            "address-level4" |
+            "country" |
+            "country-name" |
+            "postal-code"
+    )
+}
+
+/// Masks the character of a password field that was left readable once it has been shown for
+/// long enough.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct PasswordRevealTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    input: Trusted<HTMLInputElement>,
+}
+
+impl PasswordRevealTimeoutCallback {
+    pub fn invoke(self) {
+        let input = self.input.root();
+        // The timer has fired, so there is nothing left to unschedule.
+        input.password_reveal_timer.set(None);
+        input.mask_password_character();
+    }
+}
+
 #[allow(unsafe_code)]
 fn matches_js_regex(cx: SafeJSContext, regex_obj: HandleObject, value: &str) -> Result<bool, ()> {
     let mut value: Vec<u16> = value.encode_utf16().collect();
@@ -7,13 +7,17 @@ use std::cell::Cell;
 use std::default::Default;
 
 use cssparser::{Parser as CssParser, ParserInput};
+use devtools_traits::ResourceHint;
 use dom_struct::dom_struct;
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{EmbedderMsg, FaviconImage};
 use html5ever::{local_name, namespace_url, ns, LocalName, Prefix};
 use js::rust::HandleObject;
-use net_traits::ReferrerPolicy;
+use net_traits::request::{Destination, Initiator};
+use net_traits::{CoreResourceMsg, FetchChannels, IpcSend, ReferrerPolicy};
+use script_traits::ScriptMsg;
 use servo_arc::Arc;
 use servo_atoms::Atom;
+use servo_url::ServoUrl;
 use style::attr::AttrValue;
 use style::media_queries::MediaList;
 use style::parser::ParserContext as CssParserContext;
@@ -32,9 +36,12 @@ use crate::dom::cssstylesheet::CSSStyleSheet;
 use crate::dom::document::Document;
 use crate::dom::domtokenlist::DOMTokenList;
 use crate::dom::element::{
-    cors_setting_for_element, reflect_cross_origin_attribute, reflect_referrer_policy_attribute,
-    set_cross_origin_attribute, AttributeMutation, Element, ElementCreator,
+    cors_setting_for_element, referrer_policy_for_element, reflect_cross_origin_attribute,
+    reflect_fetch_priority_attribute, reflect_referrer_policy_attribute,
+    set_cross_origin_attribute, set_fetch_priority_attribute, AttributeMutation, Element,
+    ElementCreator,
 };
+use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
 use crate::dom::node::{
     document_from_node, stylesheets_owner_from_node, window_from_node, BindContext, Node,
@@ -42,8 +49,12 @@ use crate::dom::node::{
 };
 use crate::dom::stylesheet::StyleSheet as DOMStyleSheet;
 use crate::dom::virtualmethods::VirtualMethods;
+use crate::fetch::create_a_potential_cors_request;
 use crate::stylesheet_loader::{StylesheetContextSource, StylesheetLoader, StylesheetOwner};
 
+/// The size in CSS pixels at which embedders usually draw favicons.
+const FAVICON_SIZE: f32 = 16.;
+
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
 pub struct RequestGenerationId(u32);
 
@@ -192,6 +203,22 @@ fn is_favicon(value: &Option<String>) -> bool {
     }
 }
 
+/// The resource hints given by a link of the `rel` types `value`.
+fn resource_hints(value: &Option<String>) -> Vec<ResourceHint> {
+    match *value {
+        Some(ref value) => value
+            .split(HTML_SPACE_CHARACTERS)
+            .filter_map(|s| match &*s.to_ascii_lowercase() {
+                "dns-prefetch" => Some(ResourceHint::DnsPrefetch),
+                "preconnect" => Some(ResourceHint::Preconnect),
+                "prefetch" => Some(ResourceHint::Prefetch),
+                _ => None,
+            })
+            .collect(),
+        None => vec![],
+    }
+}
+
 impl VirtualMethods for HTMLLinkElement {
     fn super_type(&self) -> Option<&dyn VirtualMethods> {
         Some(self.upcast::<HTMLElement>() as &dyn VirtualMethods)
@@ -212,6 +239,9 @@ impl VirtualMethods for HTMLLinkElement {
                     let sizes = get_attr(self.upcast(), &local_name!("sizes"));
                     self.handle_favicon_url(rel.as_ref().unwrap(), &attr.value(), &sizes);
                 }
+                for hint in resource_hints(&rel) {
+                    self.handle_resource_hint(hint, &attr.value());
+                }
             },
             &local_name!("sizes") => {
                 if is_favicon(&rel) {
@@ -259,6 +289,12 @@ impl VirtualMethods for HTMLLinkElement {
                 },
                 _ => {},
             }
+
+            if let Some(ref href) = href {
+                for hint in resource_hints(&rel) {
+                    self.handle_resource_hint(hint, href);
+                }
+            }
         }
     }
 
@@ -356,11 +392,102 @@ impl HTMLLinkElement {
                 if window.is_top_level() {
                     let msg = EmbedderMsg::NewFavicon(url.clone());
                     window.send_to_embedder(msg);
+                    self.fetch_favicon(url);
                 }
             },
             Err(e) => debug!("Parsing url {} failed: {}", href, e),
         }
     }
+
+    /// Have the image cache fetch and decode the favicon, and send it to the embedder.
+    fn fetch_favicon(&self, url: ServoUrl) {
+        let document = document_from_node(self);
+        let window = document.window();
+        let global = window.upcast::<GlobalScope>();
+        let element = self.upcast::<Element>();
+        let request = create_a_potential_cors_request(
+            url.clone(),
+            Destination::Image,
+            cors_setting_for_element(element),
+            None,
+            global.get_referrer(),
+        )
+        .origin(document.origin().immutable().clone())
+        .pipeline_id(Some(global.pipeline_id()))
+        .referrer_policy(referrer_policy_for_element(element));
+
+        let size = (FAVICON_SIZE * window.device_pixel_ratio().get()).round() as u32;
+        let script_to_constellation_chan = global.script_to_constellation_chan().clone();
+        window.image_cache().fetch_favicon(
+            request,
+            size,
+            &global.core_resource_thread(),
+            Box::new(move |favicon| {
+                let favicon = match favicon {
+                    Some(favicon) => favicon,
+                    None => {
+                        debug!("Favicon {} could not be loaded", url);
+                        return;
+                    },
+                };
+                // Decoded images are in BGRA order.
+                let mut rgba = favicon.bytes.to_vec();
+                pixels::rgba8_byte_swap_colors_inplace(&mut rgba);
+                let favicon = FaviconImage {
+                    width: favicon.width,
+                    height: favicon.height,
+                    rgba,
+                };
+                let msg = EmbedderMsg::FaviconLoaded(url, favicon);
+                let _ = script_to_constellation_chan.send(ScriptMsg::ForwardToEmbedder(msg));
+            }),
+        );
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#link-type-dns-prefetch>,
+    /// <https://html.spec.whatwg.org/multipage/#link-type-preconnect> and
+    /// <https://html.spec.whatwg.org/multipage/#link-type-prefetch>
+    fn handle_resource_hint(&self, hint: ResourceHint, href: &str) {
+        let document = document_from_node(self);
+        if document.browsing_context().is_none() || href.is_empty() {
+            return;
+        }
+
+        let url = match document.base_url().join(href) {
+            Ok(url) => url,
+            Err(e) => {
+                debug!("Parsing url {} failed: {}", href, e);
+                return;
+            },
+        };
+        if !matches!(url.scheme(), "http" | "https") {
+            return;
+        }
+
+        let global = document.window().upcast::<GlobalScope>();
+        let msg = match hint {
+            ResourceHint::DnsPrefetch => CoreResourceMsg::DnsPrefetch(url, global.pipeline_id()),
+            // The `crossorigin` attribute asks for a connection without credentials, but
+            // Servo never presents client certificates, so every connection is one.
+            ResourceHint::Preconnect => CoreResourceMsg::Preconnect(url, global.pipeline_id()),
+            ResourceHint::Prefetch => {
+                let element = self.upcast::<Element>();
+                let request = create_a_potential_cors_request(
+                    url,
+                    Destination::None,
+                    cors_setting_for_element(element),
+                    None,
+                    global.get_referrer(),
+                )
+                .initiator(Initiator::Prefetch)
+                .origin(document.origin().immutable().clone())
+                .pipeline_id(Some(global.pipeline_id()))
+                .referrer_policy(referrer_policy_for_element(element));
+                CoreResourceMsg::Fetch(request, FetchChannels::Prefetch)
+            },
+        };
+        let _ = global.resource_threads().send(msg);
+    }
 }
 
 impl StylesheetOwner for HTMLLinkElement {
@@ -506,6 +633,16 @@ impl HTMLLinkElementMethods for HTMLLinkElement {
     // https://html.spec.whatwg.org/multipage/#dom-link-referrerpolicy
     make_setter!(SetReferrerPolicy, "referrerpolicy");
 
+    // https://html.spec.whatwg.org/multipage/#dom-link-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-link-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value)
+    }
+
     // https://drafts.csswg.org/cssom/#dom-linkstyle-sheet
     fn GetSheet(&self) -> Option<DomRoot<DOMStyleSheet>> {
         self.get_cssom_stylesheet().map(DomRoot::upcast)
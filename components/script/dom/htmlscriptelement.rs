@@ -53,9 +53,10 @@ use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::trace::NoTrace;
 use crate::dom::document::Document;
 use crate::dom::element::{
-    cors_setting_for_element, referrer_policy_for_element, reflect_cross_origin_attribute,
-    reflect_referrer_policy_attribute, set_cross_origin_attribute, AttributeMutation, Element,
-    ElementCreator,
+    cors_setting_for_element, fetch_priority_for_element, referrer_policy_for_element,
+    reflect_cross_origin_attribute, reflect_fetch_priority_attribute,
+    reflect_referrer_policy_attribute, set_cross_origin_attribute, set_fetch_priority_attribute,
+    AttributeMutation, Element, ElementCreator,
 };
 use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::globalscope::GlobalScope;
@@ -523,6 +524,7 @@ pub(crate) fn script_fetch_request(
     .parser_metadata(options.parser_metadata)
     .integrity_metadata(options.integrity_metadata.clone())
     .referrer_policy(options.referrer_policy)
+    .priority(options.fetch_priority)
 }
 
 /// <https://html.spec.whatwg.org/multipage/#fetch-a-classic-script>
@@ -725,6 +727,7 @@ impl HTMLScriptElement {
             referrer: self.global().get_referrer(),
             referrer_policy: referrer_policy_for_element(self.upcast::<Element>()),
             credentials_mode: module_credentials_mode,
+            fetch_priority: fetch_priority_for_element(self.upcast::<Element>()),
         };
 
         // TODO: Step 23: environment settings object.
@@ -1370,6 +1373,16 @@ impl HTMLScriptElementMethods for HTMLScriptElement {
     // https://html.spec.whatwg.org/multipage/#dom-script-referrerpolicy
     make_setter!(SetReferrerPolicy, "referrerpolicy");
 
+    // https://html.spec.whatwg.org/multipage/#dom-script-fetchpriority
+    fn FetchPriority(&self) -> DOMString {
+        reflect_fetch_priority_attribute(self.upcast::<Element>())
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-script-fetchpriority
+    fn SetFetchPriority(&self, value: DOMString) {
+        set_fetch_priority_attribute(self.upcast::<Element>(), value)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-script-text
     fn Text(&self) -> DOMString {
         self.upcast::<Node>().child_text_content()
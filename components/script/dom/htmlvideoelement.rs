@@ -12,8 +12,8 @@ use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
 use js::rust::HandleObject;
 use net_traits::image_cache::{
-    ImageCache, ImageCacheResult, ImageOrMetadataAvailable, ImageResponse, PendingImageId,
-    UsePlaceholder,
+    ImageCache, ImageCacheResult, ImageDecoding, ImageOrMetadataAvailable, ImageResponse,
+    PendingImageId, UsePlaceholder,
 };
 use net_traits::request::{CredentialsMode, Destination, RequestBuilder};
 use net_traits::{
@@ -165,6 +165,7 @@ impl HTMLVideoElement {
             None,
             sender,
             UsePlaceholder::No,
+            ImageDecoding::Sync,
         );
 
         match cache_result {
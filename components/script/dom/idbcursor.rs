@@ -0,0 +1,426 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::HandleValue;
+use net_traits::indexeddb_thread::{
+    CursorDirection, IndexedDBKey, IndexedDBKeyRange, IndexedDBRecord, IndexedDBTxnMode,
+    RecordSource, TransactionOperation,
+};
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::{
+    IDBCursorDirection, IDBCursorMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::{
+    IDBObjectStoreOrIDBIndex, IDBObjectStoreOrIDBIndexOrIDBCursor,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursorwithvalue::IDBCursorWithValue;
+use crate::dom::idbindex::IDBIndex;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbrequest::{IDBRequest, ResultKind};
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::indexed_db::{
+    convert_key_to_value, convert_value_to_valid_key, extract_key, record_value, ExtractedKey,
+};
+use crate::script_runtime::JSContext;
+
+impl From<IDBCursorDirection> for CursorDirection {
+    fn from(direction: IDBCursorDirection) -> CursorDirection {
+        match direction {
+            IDBCursorDirection::Next => CursorDirection::Next,
+            IDBCursorDirection::Nextunique => CursorDirection::NextUnique,
+            IDBCursorDirection::Prev => CursorDirection::Prev,
+            IDBCursorDirection::Prevunique => CursorDirection::PrevUnique,
+        }
+    }
+}
+
+#[crown::unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+enum CursorSource {
+    ObjectStore(Dom<IDBObjectStore>),
+    Index(Dom<IDBIndex>),
+}
+
+impl From<&IDBObjectStoreOrIDBIndex> for CursorSource {
+    #[allow(crown::unrooted_must_root)]
+    fn from(source: &IDBObjectStoreOrIDBIndex) -> CursorSource {
+        match source {
+            IDBObjectStoreOrIDBIndex::IDBObjectStore(store) => {
+                CursorSource::ObjectStore(Dom::from_ref(&*store))
+            },
+            IDBObjectStoreOrIDBIndex::IDBIndex(index) => {
+                CursorSource::Index(Dom::from_ref(&*index))
+            },
+        }
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#cursor-interface>
+#[dom_struct]
+pub struct IDBCursor {
+    reflector_: Reflector,
+    source: CursorSource,
+    transaction: Dom<IDBTransaction>,
+    request: Dom<IDBRequest>,
+    direction: IDBCursorDirection,
+    #[no_trace]
+    range: IndexedDBKeyRange,
+    /// The key and primary key of the record the cursor is at.
+    #[no_trace]
+    position: DomRefCell<Option<(IndexedDBKey, IndexedDBKey)>>,
+    /// <https://w3c.github.io/IndexedDB/#cursor-key-only-flag>
+    keys_only: bool,
+    /// <https://w3c.github.io/IndexedDB/#cursor-got-value-flag>
+    got_value: Cell<bool>,
+    #[ignore_malloc_size_of = "mozjs"]
+    key: Heap<JSVal>,
+    #[ignore_malloc_size_of = "mozjs"]
+    primary_key: Heap<JSVal>,
+    #[ignore_malloc_size_of = "mozjs"]
+    value: Heap<JSVal>,
+}
+
+impl IDBCursor {
+    pub fn new_inherited(
+        source: &IDBObjectStoreOrIDBIndex,
+        transaction: &IDBTransaction,
+        request: &IDBRequest,
+        direction: IDBCursorDirection,
+        range: IndexedDBKeyRange,
+        keys_only: bool,
+    ) -> IDBCursor {
+        IDBCursor {
+            reflector_: Reflector::new(),
+            source: CursorSource::from(source),
+            transaction: Dom::from_ref(transaction),
+            request: Dom::from_ref(request),
+            direction,
+            range,
+            position: DomRefCell::new(None),
+            keys_only,
+            got_value: Cell::new(false),
+            key: Heap::default(),
+            primary_key: Heap::default(),
+            value: Heap::default(),
+        }
+    }
+
+    fn new(
+        global: &GlobalScope,
+        source: &IDBObjectStoreOrIDBIndex,
+        transaction: &IDBTransaction,
+        request: &IDBRequest,
+        direction: IDBCursorDirection,
+        range: IndexedDBKeyRange,
+    ) -> DomRoot<IDBCursor> {
+        reflect_dom_object(
+            Box::new(IDBCursor::new_inherited(
+                source,
+                transaction,
+                request,
+                direction,
+                range,
+                true,
+            )),
+            global,
+        )
+    }
+
+    /// Open a cursor over the records of `source` in `range`, returning the request it
+    /// iterates with.
+    /// <https://w3c.github.io/IndexedDB/#dom-idbobjectstore-opencursor>
+    pub fn open(
+        global: &GlobalScope,
+        source: IDBObjectStoreOrIDBIndex,
+        transaction: &IDBTransaction,
+        range: IndexedDBKeyRange,
+        direction: IDBCursorDirection,
+        keys_only: bool,
+    ) -> DomRoot<IDBRequest> {
+        let request_source = match source {
+            IDBObjectStoreOrIDBIndex::IDBObjectStore(ref store) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(store.clone())
+            },
+            IDBObjectStoreOrIDBIndex::IDBIndex(ref index) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(index.clone())
+            },
+        };
+        let request = IDBRequest::new(global, request_source, transaction, ResultKind::Cursor);
+        let cursor = if keys_only {
+            IDBCursor::new(global, &source, transaction, &request, direction, range)
+        } else {
+            DomRoot::upcast(IDBCursorWithValue::new(
+                global,
+                &source,
+                transaction,
+                &request,
+                direction,
+                range,
+            ))
+        };
+        request.set_cursor(&cursor);
+        cursor.iterate(None, 1);
+        request
+    }
+
+    fn record_source(&self) -> RecordSource {
+        match self.source {
+            CursorSource::ObjectStore(ref store) => store.record_source(),
+            CursorSource::Index(ref index) => index.record_source(),
+        }
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#cursor-effective-object-store>
+    fn effective_object_store(&self) -> DomRoot<IDBObjectStore> {
+        match self.source {
+            CursorSource::ObjectStore(ref store) => DomRoot::from_ref(&**store),
+            CursorSource::Index(ref index) => index.object_store(),
+        }
+    }
+
+    fn is_source_deleted(&self) -> bool {
+        match self.source {
+            CursorSource::ObjectStore(ref store) => store.is_deleted(),
+            CursorSource::Index(ref index) => index.is_deleted(),
+        }
+    }
+
+    fn request_source(&self) -> IDBObjectStoreOrIDBIndexOrIDBCursor {
+        IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(DomRoot::from_ref(self))
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#iterate-a-cursor>
+    fn iterate(&self, target: Option<(IndexedDBKey, Option<IndexedDBKey>)>, count: u32) {
+        self.got_value.set(false);
+        self.transaction.execute(
+            &self.request,
+            TransactionOperation::Iterate {
+                source: self.record_source(),
+                range: self.range.clone(),
+                direction: self.direction.into(),
+                position: self.position.borrow().clone(),
+                target,
+                count,
+                keys_only: self.keys_only,
+            },
+        );
+    }
+
+    /// Move the cursor to the record it iterated to, returning whether it found one.
+    pub fn iterated(
+        &self,
+        cx: JSContext,
+        global: &GlobalScope,
+        record: Option<IndexedDBRecord>,
+    ) -> Result<bool, ()> {
+        let record = match record {
+            Some(record) => record,
+            None => {
+                *self.position.borrow_mut() = None;
+                self.key.set(UndefinedValue());
+                self.primary_key.set(UndefinedValue());
+                self.value.set(UndefinedValue());
+                return Ok(false);
+            },
+        };
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        convert_key_to_value(cx, &record.key, value.handle_mut());
+        self.key.set(value.get());
+        convert_key_to_value(cx, &record.primary_key, value.handle_mut());
+        self.primary_key.set(value.get());
+        *self.position.borrow_mut() = Some((record.key.clone(), record.primary_key.clone()));
+        if !self.keys_only {
+            let key_path = self.effective_object_store().generated_key_path();
+            record_value(cx, global, record, key_path.as_deref(), value.handle_mut())?;
+            self.value.set(value.get());
+        }
+        self.got_value.set(true);
+        Ok(true)
+    }
+
+    pub fn value(&self) -> JSVal {
+        self.value.get()
+    }
+
+    /// Check that the cursor can be moved.
+    fn check_iterable(&self) -> ErrorResult {
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        if self.is_source_deleted() || !self.got_value.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    /// Check that the record the cursor is at can be changed.
+    fn check_writable(&self) -> ErrorResult {
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        if self.transaction.mode() == IndexedDBTxnMode::Readonly {
+            return Err(Error::ReadOnly);
+        }
+        if self.is_source_deleted() || !self.got_value.get() || self.keys_only {
+            return Err(Error::InvalidState);
+        }
+        Ok(())
+    }
+
+    /// Whether `key` is in the direction the cursor moves from `position`, or also equal to
+    /// it if `inclusive`.
+    fn is_ahead(&self, key: &IndexedDBKey, position: &IndexedDBKey, inclusive: bool) -> bool {
+        let ordering = key.cmp(position);
+        if inclusive && ordering.is_eq() {
+            return true;
+        }
+        match self.direction {
+            IDBCursorDirection::Next | IDBCursorDirection::Nextunique => ordering.is_gt(),
+            IDBCursorDirection::Prev | IDBCursorDirection::Prevunique => ordering.is_lt(),
+        }
+    }
+
+    fn position(&self) -> (IndexedDBKey, IndexedDBKey) {
+        self.position
+            .borrow()
+            .clone()
+            .expect("A cursor with a value has a position")
+    }
+}
+
+impl IDBCursorMethods for IDBCursor {
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-source
+    fn Source(&self) -> IDBObjectStoreOrIDBIndex {
+        match self.source {
+            CursorSource::ObjectStore(ref store) => {
+                IDBObjectStoreOrIDBIndex::IDBObjectStore(DomRoot::from_ref(&**store))
+            },
+            CursorSource::Index(ref index) => {
+                IDBObjectStoreOrIDBIndex::IDBIndex(DomRoot::from_ref(&**index))
+            },
+        }
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-direction
+    fn Direction(&self) -> IDBCursorDirection {
+        self.direction
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-key
+    fn Key(&self, _cx: JSContext) -> JSVal {
+        self.key.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-primarykey
+    fn PrimaryKey(&self, _cx: JSContext) -> JSVal {
+        self.primary_key.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-request
+    fn Request(&self) -> DomRoot<IDBRequest> {
+        DomRoot::from_ref(&*self.request)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-advance
+    fn Advance(&self, count: u32) -> ErrorResult {
+        if count == 0 {
+            return Err(Error::Type("The count can't be 0".to_owned()));
+        }
+        self.check_iterable()?;
+        self.iterate(None, count);
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-continue
+    fn Continue(&self, cx: JSContext, key: HandleValue) -> ErrorResult {
+        self.check_iterable()?;
+        let target = if key.is_undefined() {
+            None
+        } else {
+            let key = convert_value_to_valid_key(cx, key)?;
+            if !self.is_ahead(&key, &self.position().0, false) {
+                return Err(Error::Data);
+            }
+            Some((key, None))
+        };
+        self.iterate(target, 1);
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-continueprimarykey
+    fn ContinuePrimaryKey(
+        &self,
+        cx: JSContext,
+        key: HandleValue,
+        primary_key: HandleValue,
+    ) -> ErrorResult {
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        if self.is_source_deleted() {
+            return Err(Error::InvalidState);
+        }
+        match (&self.source, self.direction) {
+            (CursorSource::Index(_), IDBCursorDirection::Next) |
+            (CursorSource::Index(_), IDBCursorDirection::Prev) => {},
+            _ => return Err(Error::InvalidAccess),
+        }
+        if !self.got_value.get() {
+            return Err(Error::InvalidState);
+        }
+        let key = convert_value_to_valid_key(cx, key)?;
+        let primary_key = convert_value_to_valid_key(cx, primary_key)?;
+        let (position, object_store_position) = self.position();
+        let ahead = if key == position {
+            self.is_ahead(&primary_key, &object_store_position, false)
+        } else {
+            self.is_ahead(&key, &position, false)
+        };
+        if !ahead {
+            return Err(Error::Data);
+        }
+        self.iterate(Some((key, Some(primary_key))), 1);
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-update
+    fn Update(&self, cx: JSContext, value: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_writable()?;
+        let store = self.effective_object_store();
+        let primary_key = self.position().1;
+        if let Some(key_path) = store.metadata().and_then(|metadata| metadata.key_path) {
+            match extract_key(cx, value, &key_path)? {
+                ExtractedKey::Key(ref key) if *key == primary_key => {},
+                _ => return Err(Error::Data),
+            }
+        }
+        store.store_value(cx, value, Some(primary_key), true, self.request_source())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbcursor-delete
+    fn Delete(&self) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_writable()?;
+        let store = self.effective_object_store();
+        let primary_key = self.position().1;
+        let store_name = store.name().clone();
+        Ok(self.transaction.make_request(
+            self.request_source(),
+            ResultKind::Undefined,
+            TransactionOperation::Delete {
+                store: store_name,
+                range: IndexedDBKeyRange::only(primary_key),
+            },
+        ))
+    }
+}
@@ -0,0 +1,366 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Cell, Ref};
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcReceiver};
+use ipc_channel::router::ROUTER;
+use net_traits::indexeddb_thread::{
+    ConnectionEvent, ConnectionId, DatabaseMetadata, IndexedDBThreadMsg, IndexedDBTxnMode, KeyPath,
+    ObjectStoreMetadata, TransactionId, TransactionOperation,
+};
+use net_traits::IpcSend;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::{
+    IDBDatabaseMethods, IDBObjectStoreParameters, IDBTransactionOptions,
+};
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::{
+    IDBTransactionMethods, IDBTransactionMode,
+};
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::domstringlist::DOMStringList;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::dom::idbversionchangeevent::IDBVersionChangeEvent;
+use crate::indexed_db::is_valid_key_path;
+use crate::task_source::{TaskSource, TaskSourceName};
+
+/// <https://w3c.github.io/IndexedDB/#database-interface>
+#[dom_struct]
+pub struct IDBDatabase {
+    eventtarget: EventTarget,
+    #[no_trace]
+    connection: ConnectionId,
+    /// What the connection knows of the database, which only its upgrade transaction
+    /// changes.
+    #[no_trace]
+    metadata: DomRefCell<DatabaseMetadata>,
+    /// <https://w3c.github.io/IndexedDB/#connection-close-pending-flag>
+    close_pending: Cell<bool>,
+    next_transaction: Cell<u64>,
+    upgrade_transaction: MutNullableDom<IDBTransaction>,
+}
+
+impl IDBDatabase {
+    fn new_inherited(connection: ConnectionId, metadata: DatabaseMetadata) -> IDBDatabase {
+        IDBDatabase {
+            eventtarget: EventTarget::new_inherited(),
+            connection,
+            metadata: DomRefCell::new(metadata),
+            close_pending: Cell::new(false),
+            next_transaction: Cell::new(1),
+            upgrade_transaction: Default::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        connection: ConnectionId,
+        metadata: DatabaseMetadata,
+    ) -> DomRoot<IDBDatabase> {
+        reflect_dom_object(
+            Box::new(IDBDatabase::new_inherited(connection, metadata)),
+            global,
+        )
+    }
+
+    pub fn connection(&self) -> ConnectionId {
+        self.connection
+    }
+
+    pub fn metadata(&self) -> Ref<DatabaseMetadata> {
+        self.metadata.borrow()
+    }
+
+    /// The metadata of the object store `name`.
+    pub fn object_store(&self, name: &str) -> Option<ObjectStoreMetadata> {
+        self.metadata
+            .borrow()
+            .object_stores
+            .iter()
+            .find(|store| store.name == name)
+            .cloned()
+    }
+
+    pub fn has_object_store(&self, name: &str) -> bool {
+        self.metadata
+            .borrow()
+            .object_stores
+            .iter()
+            .any(|store| store.name == name)
+    }
+
+    /// The names of the object stores of the database, sorted.
+    pub fn object_store_names(&self) -> Vec<String> {
+        let mut names: Vec<_> = self
+            .metadata
+            .borrow()
+            .object_stores
+            .iter()
+            .map(|store| store.name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Change the metadata of the object store `name` during the upgrade transaction.
+    pub fn update_object_store(&self, name: &str, update: impl FnOnce(&mut ObjectStoreMetadata)) {
+        if let Some(store) = self
+            .metadata
+            .borrow_mut()
+            .object_stores
+            .iter_mut()
+            .find(|store| store.name == name)
+        {
+            update(store);
+        }
+    }
+
+    pub fn set_upgrade_transaction(&self, transaction: &IDBTransaction) {
+        self.upgrade_transaction.set(Some(transaction));
+    }
+
+    pub fn upgrade_finished(&self) {
+        self.upgrade_transaction.set(None);
+    }
+
+    /// Go back to what the connection knew of the database before its upgrade transaction,
+    /// which closes it.
+    /// <https://w3c.github.io/IndexedDB/#abort-an-upgrade-transaction>
+    pub fn upgrade_aborted(&self, metadata: DatabaseMetadata) {
+        *self.metadata.borrow_mut() = metadata;
+        self.upgrade_transaction.set(None);
+        self.close_pending.set(true);
+    }
+
+    /// Handle the events of the connection sent by the IndexedDB thread as tasks.
+    pub fn listen(&self, receiver: IpcReceiver<ConnectionEvent>) {
+        let global = self.global();
+        let task_source = global.database_access_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DatabaseAccess);
+        let trusted = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: ConnectionEvent = match message.to() {
+                    Ok(event) => event,
+                    Err(error) => return warn!("Bad IndexedDB connection event ({})", error),
+                };
+                let db = trusted.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_connection_event: move || {
+                        db.root().handle_event(event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#fire-a-version-change-event>
+    /// <https://w3c.github.io/IndexedDB/#close-a-database-connection>
+    fn handle_event(&self, event: ConnectionEvent) {
+        match event {
+            ConnectionEvent::VersionChange {
+                old_version,
+                new_version,
+            } => {
+                if !self.close_pending.get() {
+                    IDBVersionChangeEvent::fire(
+                        self.upcast(),
+                        Atom::from("versionchange"),
+                        old_version,
+                        new_version,
+                    );
+                }
+                self.send(IndexedDBThreadMsg::VersionChangeHandled(self.connection));
+            },
+            ConnectionEvent::ForcedClose => {
+                // The IndexedDB thread already aborted the transactions and closed the
+                // connection.
+                self.close_pending.set(true);
+                self.upcast::<EventTarget>().fire_event(Atom::from("close"));
+            },
+        }
+    }
+
+    fn send(&self, message: IndexedDBThreadMsg) {
+        let _ = self.global().resource_threads().send(message);
+    }
+
+    /// The upgrade transaction, if it is running and active, as the methods changing the
+    /// object stores of the database require.
+    fn active_upgrade_transaction(&self) -> Fallible<DomRoot<IDBTransaction>> {
+        let transaction = self.upgrade_transaction.get().ok_or(Error::InvalidState)?;
+        if !transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        Ok(transaction)
+    }
+}
+
+impl IDBDatabaseMethods for IDBDatabase {
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-name
+    fn Name(&self) -> DOMString {
+        DOMString::from(self.metadata.borrow().name.clone())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-version
+    fn Version(&self) -> u64 {
+        self.metadata.borrow().version
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-objectstorenames
+    fn ObjectStoreNames(&self) -> DomRoot<DOMStringList> {
+        DOMStringList::new(
+            &self.global(),
+            self.object_store_names()
+                .into_iter()
+                .map(DOMString::from)
+                .collect(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-transaction
+    fn Transaction(
+        &self,
+        store_names: StringOrStringSequence,
+        mode: IDBTransactionMode,
+        options: &IDBTransactionOptions,
+    ) -> Fallible<DomRoot<IDBTransaction>> {
+        if self.upgrade_transaction.get().is_some() || self.close_pending.get() {
+            return Err(Error::InvalidState);
+        }
+        let scope: Vec<String> = match store_names {
+            StringOrStringSequence::String(name) => vec![String::from(name)],
+            StringOrStringSequence::StringSequence(names) => {
+                names.into_iter().map(String::from).collect()
+            },
+        };
+        if scope.iter().any(|name| !self.has_object_store(name)) {
+            return Err(Error::NotFound);
+        }
+        if scope.is_empty() {
+            return Err(Error::InvalidAccess);
+        }
+        let mode = match mode {
+            IDBTransactionMode::Versionchange => {
+                return Err(Error::Type(
+                    "Only opening a database can start a versionchange transaction".to_owned(),
+                ));
+            },
+            mode => IndexedDBTxnMode::from(mode),
+        };
+
+        let serial = self.next_transaction.get();
+        self.next_transaction.set(serial + 1);
+        let id = TransactionId {
+            connection: self.connection,
+            serial,
+        };
+        let global = self.global();
+        let transaction =
+            IDBTransaction::new(&global, self, id, mode, options.durability, scope.clone());
+        let (events, receiver) = ipc::channel().unwrap();
+        transaction.listen(receiver);
+        self.send(IndexedDBThreadMsg::CreateTransaction {
+            id,
+            scope,
+            mode,
+            events,
+        });
+        transaction.deactivate_after_task();
+        Ok(transaction)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-close
+    fn Close(&self) {
+        if self.close_pending.get() {
+            return;
+        }
+        self.close_pending.set(true);
+        self.send(IndexedDBThreadMsg::CloseConnection(self.connection));
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-createobjectstore
+    fn CreateObjectStore(
+        &self,
+        name: DOMString,
+        options: &IDBObjectStoreParameters,
+    ) -> Fallible<DomRoot<IDBObjectStore>> {
+        let transaction = self.active_upgrade_transaction()?;
+        let key_path = options.keyPath.as_ref().map(KeyPath::from);
+        if let Some(ref key_path) = key_path {
+            if !is_valid_key_path(key_path) {
+                return Err(Error::Syntax);
+            }
+        }
+        let name = String::from(name);
+        if self.has_object_store(&name) {
+            return Err(Error::Constraint);
+        }
+        let auto_increment = options.autoIncrement;
+        match key_path {
+            Some(KeyPath::String(ref path)) if auto_increment && path.is_empty() => {
+                return Err(Error::InvalidAccess);
+            },
+            Some(KeyPath::Sequence(_)) if auto_increment => {
+                return Err(Error::InvalidAccess);
+            },
+            _ => {},
+        }
+
+        let metadata = ObjectStoreMetadata {
+            name: name.clone(),
+            key_path,
+            auto_increment,
+            indexes: vec![],
+        };
+        self.metadata
+            .borrow_mut()
+            .object_stores
+            .push(metadata.clone());
+        transaction.execute_schema_change(TransactionOperation::CreateObjectStore(metadata));
+        transaction.ObjectStore(DOMString::from(name))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-deleteobjectstore
+    fn DeleteObjectStore(&self, name: DOMString) -> ErrorResult {
+        let transaction = self.active_upgrade_transaction()?;
+        if !self.has_object_store(&name) {
+            return Err(Error::NotFound);
+        }
+        let name = String::from(name);
+        transaction.object_store_deleted(&name);
+        self.metadata
+            .borrow_mut()
+            .object_stores
+            .retain(|store| store.name != name);
+        transaction.execute_schema_change(TransactionOperation::DeleteObjectStore(name));
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onclose
+    event_handler!(close, GetOnclose, SetOnclose);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbdatabase-onversionchange
+    event_handler!(versionchange, GetOnversionchange, SetOnversionchange);
+}
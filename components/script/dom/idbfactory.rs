@@ -0,0 +1,128 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use js::rust::HandleValue;
+use net_traits::indexeddb_thread::IndexedDBThreadMsg;
+use net_traits::IpcSend;
+
+use crate::dom::bindings::codegen::Bindings::IDBFactoryBinding::{
+    IDBDatabaseInfo, IDBFactoryMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::TrustedPromise;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbopendbrequest::IDBOpenDBRequest;
+use crate::dom::promise::Promise;
+use crate::indexed_db::convert_value_to_valid_key;
+use crate::script_runtime::JSContext;
+use crate::task_source::{TaskSource, TaskSourceName};
+
+/// <https://w3c.github.io/IndexedDB/#factory-interface>
+#[dom_struct]
+pub struct IDBFactory {
+    reflector_: Reflector,
+}
+
+impl IDBFactory {
+    fn new_inherited() -> IDBFactory {
+        IDBFactory {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<IDBFactory> {
+        reflect_dom_object(Box::new(IDBFactory::new_inherited()), global)
+    }
+}
+
+impl IDBFactoryMethods for IDBFactory {
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-open
+    fn Open(&self, name: DOMString, version: Option<u64>) -> Fallible<DomRoot<IDBOpenDBRequest>> {
+        if version == Some(0) {
+            return Err(Error::Type("The version must be at least 1".to_owned()));
+        }
+        let global = self.global();
+        let origin = global.origin().immutable().clone();
+        if !origin.is_tuple() {
+            return Err(Error::Security);
+        }
+        let request = IDBOpenDBRequest::new(&global);
+        request.open(origin, String::from(name), version);
+        Ok(request)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-deletedatabase
+    fn DeleteDatabase(&self, name: DOMString) -> Fallible<DomRoot<IDBOpenDBRequest>> {
+        let global = self.global();
+        let origin = global.origin().immutable().clone();
+        if !origin.is_tuple() {
+            return Err(Error::Security);
+        }
+        let request = IDBOpenDBRequest::new(&global);
+        request.delete_database(origin, String::from(name));
+        Ok(request)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-databases
+    fn Databases(&self) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+        let origin = global.origin().immutable().clone();
+        if !origin.is_tuple() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+
+        let task_source = global.database_access_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DatabaseAccess);
+        let (sender, receiver) = ipc::channel().unwrap();
+        let mut trusted = Some(TrustedPromise::new(promise.clone()));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                // The IndexedDB thread replies once.
+                let trusted = match trusted.take() {
+                    Some(trusted) => trusted,
+                    None => return,
+                };
+                let databases: Vec<(String, u64)> = match message.to() {
+                    Ok(databases) => databases,
+                    Err(error) => return warn!("Bad IndexedDB databases reply ({})", error),
+                };
+                let _ = task_source.queue_with_canceller(
+                    task!(resolve_databases: move || {
+                        let databases: Vec<_> = databases
+                            .into_iter()
+                            .map(|(name, version)| IDBDatabaseInfo {
+                                name: Some(DOMString::from(name)),
+                                version: Some(version),
+                            })
+                            .collect();
+                        trusted.root().resolve_native(&databases);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let _ = global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::Databases(sender, origin));
+        promise
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbfactory-cmp
+    fn Cmp(&self, cx: JSContext, first: HandleValue, second: HandleValue) -> Fallible<i16> {
+        let first = convert_value_to_valid_key(cx, first)?;
+        let second = convert_value_to_valid_key(cx, second)?;
+        Ok(first.cmp(&second) as i16)
+    }
+}
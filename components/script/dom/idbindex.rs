@@ -0,0 +1,306 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Cell, Ref};
+
+use dom_struct::dom_struct;
+use js::jsval::{JSVal, NullValue};
+use js::rust::HandleValue;
+use net_traits::indexeddb_thread::{
+    IndexMetadata, IndexedDBKeyRange, RecordSource, TransactionOperation,
+};
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBIndexBinding::IDBIndexMethods;
+use crate::dom::bindings::codegen::UnionTypes::{
+    IDBObjectStoreOrIDBIndex, IDBObjectStoreOrIDBIndexOrIDBCursor,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbrequest::{IDBRequest, ResultKind};
+use crate::indexed_db::{convert_value_to_key_range, key_path_to_value};
+use crate::script_runtime::JSContext;
+
+/// <https://w3c.github.io/IndexedDB/#index-interface>
+#[dom_struct]
+pub struct IDBIndex {
+    reflector_: Reflector,
+    name: DomRefCell<String>,
+    object_store: Dom<IDBObjectStore>,
+    deleted: Cell<bool>,
+}
+
+impl IDBIndex {
+    fn new_inherited(name: String, object_store: &IDBObjectStore) -> IDBIndex {
+        IDBIndex {
+            reflector_: Reflector::new(),
+            name: DomRefCell::new(name),
+            object_store: Dom::from_ref(object_store),
+            deleted: Cell::new(false),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        name: String,
+        object_store: &IDBObjectStore,
+    ) -> DomRoot<IDBIndex> {
+        reflect_dom_object(
+            Box::new(IDBIndex::new_inherited(name, object_store)),
+            global,
+        )
+    }
+
+    pub fn name(&self) -> Ref<String> {
+        self.name.borrow()
+    }
+
+    pub fn object_store(&self) -> DomRoot<IDBObjectStore> {
+        DomRoot::from_ref(&*self.object_store)
+    }
+
+    /// Whether the index or its object store was deleted.
+    pub fn is_deleted(&self) -> bool {
+        self.deleted.get() || self.object_store.is_deleted()
+    }
+
+    pub fn set_deleted(&self) {
+        self.deleted.set(true);
+    }
+
+    /// The metadata of the index, unless it or its object store was deleted.
+    fn metadata(&self) -> Option<IndexMetadata> {
+        self.object_store.metadata().and_then(|metadata| {
+            metadata
+                .indexes
+                .into_iter()
+                .find(|index| index.name == *self.name.borrow())
+        })
+    }
+
+    pub fn record_source(&self) -> RecordSource {
+        RecordSource::Index {
+            store: self.object_store.name().clone(),
+            index: self.name.borrow().clone(),
+        }
+    }
+
+    fn request_source(&self) -> IDBObjectStoreOrIDBIndexOrIDBCursor {
+        IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(DomRoot::from_ref(self))
+    }
+
+    /// Check that requests reading the index can be made.
+    fn check_readable(&self) -> ErrorResult {
+        if self.is_deleted() {
+            return Err(Error::InvalidState);
+        }
+        if !self.object_store.transaction().is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        Ok(())
+    }
+
+    /// Make a request for the result of reading the records of the index in the range
+    /// `query` is converted to.
+    fn read(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        null_disallowed: bool,
+        kind: ResultKind,
+        operation: impl FnOnce(RecordSource, IndexedDBKeyRange) -> TransactionOperation,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_readable()?;
+        let range = convert_value_to_key_range(cx, query, null_disallowed)?;
+        Ok(self.object_store.transaction().make_request(
+            self.request_source(),
+            kind,
+            operation(self.record_source(), range),
+        ))
+    }
+
+    fn open_cursor(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+        keys_only: bool,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_readable()?;
+        let range = convert_value_to_key_range(cx, query, false)?;
+        Ok(IDBCursor::open(
+            &self.global(),
+            IDBObjectStoreOrIDBIndex::IDBIndex(DomRoot::from_ref(self)),
+            &self.object_store.transaction(),
+            range,
+            direction,
+            keys_only,
+        ))
+    }
+}
+
+impl IDBIndexMethods for IDBIndex {
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-name
+    fn Name(&self) -> DOMString {
+        DOMString::from(self.name.borrow().clone())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-name
+    fn SetName(&self, name: DOMString) -> ErrorResult {
+        let transaction = self.object_store.transaction();
+        if !transaction.is_upgrade() || self.is_deleted() {
+            return Err(Error::InvalidState);
+        }
+        if !transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        let name = String::from(name);
+        if *self.name.borrow() == name {
+            return Ok(());
+        }
+        let store = self.object_store.name().clone();
+        let exists = self.object_store.metadata().map_or(false, |metadata| {
+            metadata.indexes.iter().any(|index| index.name == name)
+        });
+        if exists {
+            return Err(Error::Constraint);
+        }
+        let from = std::mem::replace(&mut *self.name.borrow_mut(), name.clone());
+        transaction.db().update_object_store(&store, |metadata| {
+            if let Some(index) = metadata.indexes.iter_mut().find(|index| index.name == from) {
+                index.name = name.clone();
+            }
+        });
+        transaction.execute_schema_change(TransactionOperation::RenameIndex {
+            store,
+            from,
+            to: name,
+        });
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-objectstore
+    fn ObjectStore(&self) -> DomRoot<IDBObjectStore> {
+        self.object_store()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-keypath
+    fn KeyPath(&self, cx: JSContext) -> JSVal {
+        rooted!(in(*cx) let mut key_path = NullValue());
+        if let Some(metadata) = self.metadata() {
+            key_path_to_value(cx, &metadata.key_path, key_path.handle_mut());
+        }
+        key_path.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-multientry
+    fn MultiEntry(&self) -> bool {
+        self.metadata()
+            .map_or(false, |metadata| metadata.multi_entry)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-unique
+    fn Unique(&self) -> bool {
+        self.metadata().map_or(false, |metadata| metadata.unique)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-get
+    fn Get(&self, cx: JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        let key_path = self.object_store.generated_key_path();
+        self.read(
+            cx,
+            query,
+            true,
+            ResultKind::Value(key_path),
+            |source, range| TransactionOperation::Get {
+                source,
+                range,
+                keys_only: false,
+            },
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-getkey
+    fn GetKey(&self, cx: JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        self.read(cx, query, true, ResultKind::Key, |source, range| {
+            TransactionOperation::Get {
+                source,
+                range,
+                keys_only: true,
+            }
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-getall
+    fn GetAll(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        let key_path = self.object_store.generated_key_path();
+        self.read(
+            cx,
+            query,
+            false,
+            ResultKind::Values(key_path),
+            |source, range| TransactionOperation::GetAll {
+                source,
+                range,
+                count: count.filter(|count| *count > 0),
+                keys_only: false,
+            },
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-getallkeys
+    fn GetAllKeys(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.read(cx, query, false, ResultKind::Keys, |source, range| {
+            TransactionOperation::GetAll {
+                source,
+                range,
+                count: count.filter(|count| *count > 0),
+                keys_only: true,
+            }
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-count
+    fn Count(&self, cx: JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        self.read(cx, query, false, ResultKind::Count, |source, range| {
+            TransactionOperation::Count { source, range }
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-opencursor
+    fn OpenCursor(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.open_cursor(cx, query, direction, false)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbindex-openkeycursor
+    fn OpenKeyCursor(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.open_cursor(cx, query, direction, true)
+    }
+}
@@ -0,0 +1,154 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::jsval::{JSVal, UndefinedValue};
+use js::rust::HandleValue;
+use net_traits::indexeddb_thread::{IndexedDBKey, IndexedDBKeyRange};
+
+use crate::dom::bindings::codegen::Bindings::IDBKeyRangeBinding::IDBKeyRangeMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::globalscope::GlobalScope;
+use crate::indexed_db::{convert_key_to_value, convert_value_to_valid_key};
+use crate::script_runtime::JSContext;
+
+/// <https://w3c.github.io/IndexedDB/#keyrange>
+#[dom_struct]
+pub struct IDBKeyRange {
+    reflector_: Reflector,
+    #[no_trace]
+    inner: IndexedDBKeyRange,
+}
+
+impl IDBKeyRange {
+    fn new_inherited(inner: IndexedDBKeyRange) -> IDBKeyRange {
+        IDBKeyRange {
+            reflector_: Reflector::new(),
+            inner,
+        }
+    }
+
+    pub fn new(global: &GlobalScope, inner: IndexedDBKeyRange) -> DomRoot<IDBKeyRange> {
+        reflect_dom_object(Box::new(IDBKeyRange::new_inherited(inner)), global)
+    }
+
+    pub fn inner(&self) -> &IndexedDBKeyRange {
+        &self.inner
+    }
+
+    fn bound_to_value(cx: JSContext, bound: &Option<IndexedDBKey>) -> JSVal {
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        if let Some(ref key) = *bound {
+            convert_key_to_value(cx, key, value.handle_mut());
+        }
+        value.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-only
+    #[allow(non_snake_case)]
+    pub fn Only(
+        cx: JSContext,
+        global: &GlobalScope,
+        value: HandleValue,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        let key = convert_value_to_valid_key(cx, value)?;
+        Ok(IDBKeyRange::new(global, IndexedDBKeyRange::only(key)))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-lowerbound
+    #[allow(non_snake_case)]
+    pub fn LowerBound(
+        cx: JSContext,
+        global: &GlobalScope,
+        lower: HandleValue,
+        open: bool,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        let lower = convert_value_to_valid_key(cx, lower)?;
+        Ok(IDBKeyRange::new(
+            global,
+            IndexedDBKeyRange {
+                lower: Some(lower),
+                upper: None,
+                lower_open: open,
+                upper_open: true,
+            },
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-upperbound
+    #[allow(non_snake_case)]
+    pub fn UpperBound(
+        cx: JSContext,
+        global: &GlobalScope,
+        upper: HandleValue,
+        open: bool,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        let upper = convert_value_to_valid_key(cx, upper)?;
+        Ok(IDBKeyRange::new(
+            global,
+            IndexedDBKeyRange {
+                lower: None,
+                upper: Some(upper),
+                lower_open: true,
+                upper_open: open,
+            },
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-bound
+    #[allow(non_snake_case)]
+    pub fn Bound(
+        cx: JSContext,
+        global: &GlobalScope,
+        lower: HandleValue,
+        upper: HandleValue,
+        lower_open: bool,
+        upper_open: bool,
+    ) -> Fallible<DomRoot<IDBKeyRange>> {
+        let lower = convert_value_to_valid_key(cx, lower)?;
+        let upper = convert_value_to_valid_key(cx, upper)?;
+        if lower > upper {
+            return Err(Error::Data);
+        }
+        Ok(IDBKeyRange::new(
+            global,
+            IndexedDBKeyRange {
+                lower: Some(lower),
+                upper: Some(upper),
+                lower_open,
+                upper_open,
+            },
+        ))
+    }
+}
+
+impl IDBKeyRangeMethods for IDBKeyRange {
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-lower
+    fn Lower(&self, cx: JSContext) -> JSVal {
+        IDBKeyRange::bound_to_value(cx, &self.inner.lower)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-upper
+    fn Upper(&self, cx: JSContext) -> JSVal {
+        IDBKeyRange::bound_to_value(cx, &self.inner.upper)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-loweropen
+    fn LowerOpen(&self) -> bool {
+        self.inner.lower_open
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-upperopen
+    fn UpperOpen(&self) -> bool {
+        self.inner.upper_open
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbkeyrange-includes
+    fn Includes(&self, cx: JSContext, key: HandleValue) -> Fallible<bool> {
+        let key = convert_value_to_valid_key(cx, key)?;
+        Ok(self.inner.contains(&key))
+    }
+}
@@ -0,0 +1,610 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::{Cell, Ref};
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc;
+use js::jsval::{JSVal, NullValue, UndefinedValue};
+use js::rust::HandleValue;
+use net_traits::indexeddb_thread::{
+    IndexMetadata, IndexedDBKey, IndexedDBKeyRange, IndexedDBThreadMsg, IndexedDBTxnMode, KeyPath,
+    ObjectStoreMetadata, OperationResult, RecordSource, TransactionOperation,
+};
+use net_traits::IpcSend;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBCursorBinding::IDBCursorDirection;
+use crate::dom::bindings::codegen::Bindings::IDBObjectStoreBinding::{
+    IDBIndexParameters, IDBObjectStoreMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::{
+    IDBObjectStoreOrIDBIndex, IDBObjectStoreOrIDBIndexOrIDBCursor, StringOrStringSequence,
+};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::domstringlist::DOMStringList;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbindex::IDBIndex;
+use crate::dom::idbrequest::{IDBRequest, ResultKind};
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::indexed_db::{
+    convert_value_to_key_range, convert_value_to_valid_key, could_inject_key, deserialize_value,
+    extract_key, index_keys, is_valid_key_path, key_path_to_value, record_value, serialize_value,
+    ExtractedKey,
+};
+use crate::script_runtime::JSContext;
+
+/// <https://w3c.github.io/IndexedDB/#object-store-interface>
+#[dom_struct]
+pub struct IDBObjectStore {
+    reflector_: Reflector,
+    name: DomRefCell<String>,
+    transaction: Dom<IDBTransaction>,
+    deleted: Cell<bool>,
+    /// <https://w3c.github.io/IndexedDB/#object-store-handle-index-set>
+    indexes: DomRefCell<Vec<Dom<IDBIndex>>>,
+}
+
+impl IDBObjectStore {
+    fn new_inherited(name: String, transaction: &IDBTransaction) -> IDBObjectStore {
+        IDBObjectStore {
+            reflector_: Reflector::new(),
+            name: DomRefCell::new(name),
+            transaction: Dom::from_ref(transaction),
+            deleted: Cell::new(false),
+            indexes: Default::default(),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        name: String,
+        transaction: &IDBTransaction,
+    ) -> DomRoot<IDBObjectStore> {
+        reflect_dom_object(
+            Box::new(IDBObjectStore::new_inherited(name, transaction)),
+            global,
+        )
+    }
+
+    pub fn name(&self) -> Ref<String> {
+        self.name.borrow()
+    }
+
+    pub fn transaction(&self) -> DomRoot<IDBTransaction> {
+        DomRoot::from_ref(&*self.transaction)
+    }
+
+    pub fn is_deleted(&self) -> bool {
+        self.deleted.get()
+    }
+
+    pub fn set_deleted(&self) {
+        self.deleted.set(true);
+        for index in self.indexes.borrow().iter() {
+            index.set_deleted();
+        }
+    }
+
+    /// Mark the object store as deleted if the aborted upgrade transaction created it.
+    pub fn reset_after_upgrade_abort(&self) {
+        if !self.transaction.db().has_object_store(&self.name.borrow()) {
+            self.set_deleted();
+        }
+    }
+
+    /// The metadata of the object store, unless it was deleted.
+    pub fn metadata(&self) -> Option<ObjectStoreMetadata> {
+        self.transaction.db().object_store(&self.name.borrow())
+    }
+
+    /// The key path the primary key of a record is put back in at when it is read, for an
+    /// object store that generates the keys of its values.
+    pub fn generated_key_path(&self) -> Option<String> {
+        match self.metadata() {
+            Some(ObjectStoreMetadata {
+                key_path: Some(KeyPath::String(key_path)),
+                auto_increment: true,
+                ..
+            }) => Some(key_path),
+            _ => None,
+        }
+    }
+
+    pub fn record_source(&self) -> RecordSource {
+        RecordSource::ObjectStore(self.name.borrow().clone())
+    }
+
+    fn request_source(&self) -> IDBObjectStoreOrIDBIndexOrIDBCursor {
+        IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(DomRoot::from_ref(self))
+    }
+
+    /// Check that requests reading the object store can be made.
+    fn check_readable(&self) -> ErrorResult {
+        if self.deleted.get() {
+            return Err(Error::InvalidState);
+        }
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        Ok(())
+    }
+
+    /// Check that requests changing the object store can be made.
+    pub fn check_writable(&self) -> ErrorResult {
+        self.check_readable()?;
+        if self.transaction.mode() == IndexedDBTxnMode::Readonly {
+            return Err(Error::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Check that the object store or its indexes can be changed by the upgrade transaction.
+    fn check_upgradable(&self) -> ErrorResult {
+        if !self.transaction.is_upgrade() || self.deleted.get() {
+            return Err(Error::InvalidState);
+        }
+        if !self.transaction.is_active() {
+            return Err(Error::TransactionInactive);
+        }
+        Ok(())
+    }
+
+    /// Make a request for the result of reading the records of the object store in the
+    /// range `query` is converted to.
+    fn read(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        null_disallowed: bool,
+        kind: ResultKind,
+        operation: impl FnOnce(RecordSource, IndexedDBKeyRange) -> TransactionOperation,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_readable()?;
+        let range = convert_value_to_key_range(cx, query, null_disallowed)?;
+        Ok(self.transaction.make_request(
+            self.request_source(),
+            kind,
+            operation(self.record_source(), range),
+        ))
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#add-or-put>
+    fn put(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        key: HandleValue,
+        overwrite: bool,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_writable()?;
+        let metadata = self.metadata().ok_or(Error::InvalidState)?;
+        if metadata.key_path.is_some() && !key.is_undefined() {
+            return Err(Error::Data);
+        }
+        if metadata.key_path.is_none() && !metadata.auto_increment && key.is_undefined() {
+            return Err(Error::Data);
+        }
+        let key = if key.is_undefined() {
+            None
+        } else {
+            Some(convert_value_to_valid_key(cx, key)?)
+        };
+        self.store_value(cx, value, key, overwrite, self.request_source())
+    }
+
+    /// Store a clone of `value` with the keys of the object store and of its indexes taken
+    /// from it, as `put` and `IDBCursor.update` do.
+    pub fn store_value(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        mut key: Option<IndexedDBKey>,
+        overwrite: bool,
+        source: IDBObjectStoreOrIDBIndexOrIDBCursor,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        let metadata = self.metadata().ok_or(Error::InvalidState)?;
+        let serialized = serialize_value(cx, value)?;
+        rooted!(in(*cx) let mut clone = UndefinedValue());
+        deserialize_value(&self.global(), serialized.clone(), clone.handle_mut())
+            .map_err(|()| Error::DataClone)?;
+
+        if let Some(ref key_path) = metadata.key_path {
+            match extract_key(cx, clone.handle(), key_path)? {
+                ExtractedKey::Key(extracted) => key = Some(extracted),
+                ExtractedKey::Invalid => return Err(Error::Data),
+                ExtractedKey::Failure => match *key_path {
+                    KeyPath::String(ref key_path) if metadata.auto_increment => {
+                        if !could_inject_key(cx, clone.handle(), key_path)? {
+                            return Err(Error::Data);
+                        }
+                    },
+                    _ => return Err(Error::Data),
+                },
+            }
+        }
+
+        let index_keys = metadata
+            .indexes
+            .iter()
+            .map(|index| {
+                let keys = index_keys(cx, clone.handle(), &index.key_path, index.multi_entry)?;
+                Ok((index.name.clone(), keys))
+            })
+            .collect::<Fallible<Vec<_>>>()?;
+        Ok(self.transaction.make_request(
+            source,
+            ResultKind::Key,
+            TransactionOperation::Put {
+                store: metadata.name,
+                key,
+                value: serialized,
+                index_keys,
+                overwrite,
+            },
+        ))
+    }
+
+    /// The keys of the index `index` for each record already in the object store.
+    fn compute_index_keys(
+        &self,
+        index: &IndexMetadata,
+    ) -> Fallible<Vec<(IndexedDBKey, Vec<IndexedDBKey>)>> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let operation = TransactionOperation::GetAll {
+            source: self.record_source(),
+            range: IndexedDBKeyRange::unbounded(),
+            count: None,
+            keys_only: false,
+        };
+        let global = self.global();
+        let _ = global
+            .resource_threads()
+            .send(IndexedDBThreadMsg::SyncOperation(
+                sender,
+                self.transaction.id(),
+                operation,
+            ));
+        let records = match receiver.recv() {
+            Ok(Ok(OperationResult::Records(records))) => records,
+            _ => return Err(Error::InvalidState),
+        };
+
+        let cx = GlobalScope::get_cx();
+        let key_path = self.generated_key_path();
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        records
+            .into_iter()
+            .map(|record| {
+                let primary_key = record.primary_key.clone();
+                record_value(cx, &global, record, key_path.as_deref(), value.handle_mut())
+                    .map_err(|()| Error::DataClone)?;
+                let keys = index_keys(cx, value.handle(), &index.key_path, index.multi_entry)?;
+                Ok((primary_key, keys))
+            })
+            .collect()
+    }
+
+    /// Rename the object store, as the upgrade transaction did.
+    fn rename(&self, name: String) {
+        let from = std::mem::replace(&mut *self.name.borrow_mut(), name.clone());
+        let db = self.transaction.db();
+        db.update_object_store(&from, |metadata| metadata.name = name.clone());
+        self.transaction
+            .execute_schema_change(TransactionOperation::RenameObjectStore { from, to: name });
+    }
+
+    pub fn open_cursor(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+        keys_only: bool,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_readable()?;
+        let range = convert_value_to_key_range(cx, query, false)?;
+        Ok(IDBCursor::open(
+            &self.global(),
+            IDBObjectStoreOrIDBIndex::IDBObjectStore(DomRoot::from_ref(self)),
+            &self.transaction,
+            range,
+            direction,
+            keys_only,
+        ))
+    }
+}
+
+impl IDBObjectStoreMethods for IDBObjectStore {
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-name
+    fn Name(&self) -> DOMString {
+        DOMString::from(self.name.borrow().clone())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-name
+    fn SetName(&self, name: DOMString) -> ErrorResult {
+        self.check_upgradable()?;
+        let name = String::from(name);
+        if *self.name.borrow() == name {
+            return Ok(());
+        }
+        if self.transaction.db().has_object_store(&name) {
+            return Err(Error::Constraint);
+        }
+        self.rename(name);
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-keypath
+    fn KeyPath(&self, cx: JSContext) -> JSVal {
+        rooted!(in(*cx) let mut key_path = NullValue());
+        if let Some(ref path) = self.metadata().and_then(|metadata| metadata.key_path) {
+            key_path_to_value(cx, path, key_path.handle_mut());
+        }
+        key_path.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-indexnames
+    fn IndexNames(&self) -> DomRoot<DOMStringList> {
+        let mut names: Vec<_> = self
+            .metadata()
+            .map(|metadata| metadata.indexes)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| index.name)
+            .collect();
+        names.sort();
+        DOMStringList::new(
+            &self.global(),
+            names.into_iter().map(DOMString::from).collect(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-transaction
+    fn Transaction(&self) -> DomRoot<IDBTransaction> {
+        self.transaction()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-autoincrement
+    fn AutoIncrement(&self) -> bool {
+        self.metadata()
+            .map_or(false, |metadata| metadata.auto_increment)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-put
+    fn Put(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        key: HandleValue,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.put(cx, value, key, true)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-add
+    fn Add(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        key: HandleValue,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.put(cx, value, key, false)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-delete
+    fn Delete(&self, cx: JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_writable()?;
+        let range = convert_value_to_key_range(cx, query, true)?;
+        Ok(self.transaction.make_request(
+            self.request_source(),
+            ResultKind::Undefined,
+            TransactionOperation::Delete {
+                store: self.name.borrow().clone(),
+                range,
+            },
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-clear
+    fn Clear(&self) -> Fallible<DomRoot<IDBRequest>> {
+        self.check_writable()?;
+        Ok(self.transaction.make_request(
+            self.request_source(),
+            ResultKind::Undefined,
+            TransactionOperation::Clear(self.name.borrow().clone()),
+        ))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-get
+    fn Get(&self, cx: JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        let key_path = self.generated_key_path();
+        self.read(
+            cx,
+            query,
+            true,
+            ResultKind::Value(key_path),
+            |source, range| TransactionOperation::Get {
+                source,
+                range,
+                keys_only: false,
+            },
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-getkey
+    fn GetKey(&self, cx: JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        self.read(cx, query, true, ResultKind::Key, |source, range| {
+            TransactionOperation::Get {
+                source,
+                range,
+                keys_only: true,
+            }
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-getall
+    fn GetAll(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        let key_path = self.generated_key_path();
+        self.read(
+            cx,
+            query,
+            false,
+            ResultKind::Values(key_path),
+            |source, range| TransactionOperation::GetAll {
+                source,
+                range,
+                count: count.filter(|count| *count > 0),
+                keys_only: false,
+            },
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-getallkeys
+    fn GetAllKeys(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        count: Option<u32>,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.read(cx, query, false, ResultKind::Keys, |source, range| {
+            TransactionOperation::GetAll {
+                source,
+                range,
+                count: count.filter(|count| *count > 0),
+                keys_only: true,
+            }
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-count
+    fn Count(&self, cx: JSContext, query: HandleValue) -> Fallible<DomRoot<IDBRequest>> {
+        self.read(cx, query, false, ResultKind::Count, |source, range| {
+            TransactionOperation::Count { source, range }
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-opencursor
+    fn OpenCursor(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.open_cursor(cx, query, direction, false)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-openkeycursor
+    fn OpenKeyCursor(
+        &self,
+        cx: JSContext,
+        query: HandleValue,
+        direction: IDBCursorDirection,
+    ) -> Fallible<DomRoot<IDBRequest>> {
+        self.open_cursor(cx, query, direction, true)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-index
+    fn Index(&self, name: DOMString) -> Fallible<DomRoot<IDBIndex>> {
+        if self.deleted.get() || self.transaction.is_finished() {
+            return Err(Error::InvalidState);
+        }
+        let has_index = self.metadata().map_or(false, |metadata| {
+            metadata.indexes.iter().any(|index| *index.name == *name)
+        });
+        if !has_index {
+            return Err(Error::NotFound);
+        }
+        if let Some(index) = self
+            .indexes
+            .borrow()
+            .iter()
+            .find(|index| *index.name() == *name)
+        {
+            return Ok(DomRoot::from_ref(&**index));
+        }
+        let index = IDBIndex::new(&self.global(), String::from(name), self);
+        self.indexes.borrow_mut().push(Dom::from_ref(&*index));
+        Ok(index)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-createindex
+    fn CreateIndex(
+        &self,
+        name: DOMString,
+        key_path: StringOrStringSequence,
+        options: &IDBIndexParameters,
+    ) -> Fallible<DomRoot<IDBIndex>> {
+        self.check_upgradable()?;
+        let metadata = self.metadata().ok_or(Error::InvalidState)?;
+        let name = String::from(name);
+        if metadata.indexes.iter().any(|index| index.name == name) {
+            return Err(Error::Constraint);
+        }
+        let key_path = KeyPath::from(&key_path);
+        if !is_valid_key_path(&key_path) {
+            return Err(Error::Syntax);
+        }
+        if let KeyPath::Sequence(_) = key_path {
+            if options.multiEntry {
+                return Err(Error::InvalidAccess);
+            }
+        }
+
+        let index = IndexMetadata {
+            name: name.clone(),
+            key_path,
+            unique: options.unique,
+            multi_entry: options.multiEntry,
+        };
+        let keys = self.compute_index_keys(&index)?;
+        self.transaction
+            .db()
+            .update_object_store(&metadata.name, |metadata| {
+                metadata.indexes.push(index.clone())
+            });
+        self.transaction
+            .execute_schema_change(TransactionOperation::CreateIndex {
+                store: metadata.name,
+                index,
+                keys,
+            });
+        self.Index(DOMString::from(name))
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbobjectstore-deleteindex
+    fn DeleteIndex(&self, name: DOMString) -> ErrorResult {
+        self.check_upgradable()?;
+        let metadata = self.metadata().ok_or(Error::InvalidState)?;
+        let name = String::from(name);
+        if !metadata.indexes.iter().any(|index| index.name == name) {
+            return Err(Error::NotFound);
+        }
+        self.indexes.borrow_mut().retain(|index| {
+            if *index.name() != name {
+                return true;
+            }
+            index.set_deleted();
+            false
+        });
+        self.transaction
+            .db()
+            .update_object_store(&metadata.name, |metadata| {
+                metadata.indexes.retain(|index| index.name != name)
+            });
+        self.transaction
+            .execute_schema_change(TransactionOperation::DeleteIndex {
+                store: metadata.name,
+                index: name,
+            });
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::{self, IpcReceiver};
+use ipc_channel::router::ROUTER;
+use js::jsval::ObjectValue;
+use js::rust::HandleValue;
+use net_traits::indexeddb_thread::{
+    ConnectionEvent, DatabaseMetadata, DeleteEvent, IndexedDBThreadMsg, OpenEvent, TransactionEvent,
+};
+use net_traits::IpcSend;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use servo_atoms::Atom;
+use servo_url::ImmutableOrigin;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBOpenDBRequestBinding::IDBOpenDBRequestMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbdatabase::IDBDatabase;
+use crate::dom::idbrequest::{IDBRequest, ResultKind};
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::dom::idbversionchangeevent::IDBVersionChangeEvent;
+use crate::indexed_db::backend_error_to_dom_exception;
+use crate::task_source::{TaskSource, TaskSourceName};
+
+/// <https://w3c.github.io/IndexedDB/#idbopendbrequest>
+#[dom_struct]
+pub struct IDBOpenDBRequest {
+    request: IDBRequest,
+    /// The channels the connection and its upgrade transaction get their events from, until
+    /// the connection is made.
+    #[ignore_malloc_size_of = "defined in ipc-channel"]
+    #[no_trace]
+    pending_channels:
+        DomRefCell<Option<(IpcReceiver<ConnectionEvent>, IpcReceiver<TransactionEvent>)>>,
+}
+
+impl IDBOpenDBRequest {
+    fn new_inherited() -> IDBOpenDBRequest {
+        IDBOpenDBRequest {
+            request: IDBRequest::new_inherited(None, None, ResultKind::Undefined),
+            pending_channels: DomRefCell::new(None),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<IDBOpenDBRequest> {
+        reflect_dom_object(Box::new(IDBOpenDBRequest::new_inherited()), global)
+    }
+
+    /// Handle the events of the request sent by the IndexedDB thread as tasks.
+    fn listen<T>(&self, receiver: IpcReceiver<T>, handler: fn(&IDBOpenDBRequest, T))
+    where
+        T: DeserializeOwned + Serialize + Send + 'static,
+    {
+        let global = self.global();
+        let task_source = global.database_access_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DatabaseAccess);
+        let trusted = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: T = match message.to() {
+                    Ok(event) => event,
+                    Err(error) => return warn!("Bad IndexedDB request event ({})", error),
+                };
+                let request = trusted.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_open_request_event: move || {
+                        handler(&request.root(), event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#open-a-database-connection>
+    pub fn open(&self, origin: ImmutableOrigin, name: String, version: Option<u64>) {
+        let (request, request_receiver) = ipc::channel().unwrap();
+        let (connection, connection_receiver) = ipc::channel().unwrap();
+        let (upgrade_transaction, upgrade_receiver) = ipc::channel().unwrap();
+        *self.pending_channels.borrow_mut() = Some((connection_receiver, upgrade_receiver));
+        self.listen(request_receiver, IDBOpenDBRequest::handle_open_event);
+        let _ = self
+            .global()
+            .resource_threads()
+            .send(IndexedDBThreadMsg::Open {
+                origin,
+                name,
+                version,
+                request,
+                connection,
+                upgrade_transaction,
+            });
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#delete-a-database>
+    pub fn delete_database(&self, origin: ImmutableOrigin, name: String) {
+        let (request, request_receiver) = ipc::channel().unwrap();
+        self.listen(request_receiver, IDBOpenDBRequest::handle_delete_event);
+        let _ = self
+            .global()
+            .resource_threads()
+            .send(IndexedDBThreadMsg::DeleteDatabase {
+                origin,
+                name,
+                request,
+            });
+    }
+
+    fn handle_open_event(&self, event: OpenEvent) {
+        let global = self.global();
+        match event {
+            OpenEvent::Blocked {
+                old_version,
+                new_version,
+            } => {
+                IDBVersionChangeEvent::fire(
+                    self.upcast(),
+                    Atom::from("blocked"),
+                    old_version,
+                    Some(new_version),
+                );
+            },
+            // <https://w3c.github.io/IndexedDB/#run-an-upgrade-transaction>
+            OpenEvent::UpgradeNeeded {
+                connection,
+                old_version,
+                metadata,
+            } => {
+                let previous_metadata = DatabaseMetadata {
+                    version: old_version,
+                    ..metadata.clone()
+                };
+                let new_version = metadata.version;
+                let (connection_receiver, upgrade_receiver) =
+                    match self.pending_channels.borrow_mut().take() {
+                        Some(channels) => channels,
+                        None => return,
+                    };
+                let db = IDBDatabase::new(&global, connection, metadata);
+                db.listen(connection_receiver);
+                let transaction =
+                    IDBTransaction::new_upgrade(&global, &db, self, previous_metadata);
+                transaction.listen(upgrade_receiver);
+                db.set_upgrade_transaction(&transaction);
+
+                let request = self.upcast::<IDBRequest>();
+                request.set_transaction(Some(&transaction));
+                self.set_result_to_db(&db);
+                IDBVersionChangeEvent::fire(
+                    self.upcast(),
+                    Atom::from("upgradeneeded"),
+                    old_version,
+                    Some(new_version),
+                );
+                transaction.deactivate();
+            },
+            OpenEvent::Success {
+                connection,
+                metadata,
+            } => {
+                let connection_receiver = match self.pending_channels.borrow_mut().take() {
+                    Some((connection_receiver, _)) => connection_receiver,
+                    None => return,
+                };
+                let db = IDBDatabase::new(&global, connection, metadata);
+                db.listen(connection_receiver);
+                self.set_result_to_db(&db);
+                self.upcast::<EventTarget>()
+                    .fire_event(Atom::from("success"));
+            },
+            OpenEvent::Error(error) => {
+                let error = backend_error_to_dom_exception(&global, &error);
+                self.upcast::<IDBRequest>().fire_error(&error);
+            },
+        }
+    }
+
+    fn set_result_to_db(&self, db: &IDBDatabase) {
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let result = ObjectValue(db.reflector().get_jsobject().get()));
+        self.upcast::<IDBRequest>().set_done(result.handle(), None);
+    }
+
+    /// The upgrade transaction of the connection completed.
+    pub fn upgrade_complete(&self) {
+        let request = self.upcast::<IDBRequest>();
+        request.set_transaction(None);
+        self.upcast::<EventTarget>()
+            .fire_event(Atom::from("success"));
+    }
+
+    /// The upgrade transaction of the connection was aborted, which closed it.
+    /// <https://w3c.github.io/IndexedDB/#abort-an-upgrade-transaction>
+    pub fn upgrade_aborted(&self) {
+        let request = self.upcast::<IDBRequest>();
+        request.set_transaction(None);
+        let error = DOMException::new(&self.global(), DOMErrorName::AbortError);
+        request.fire_error(&error);
+    }
+
+    fn handle_delete_event(&self, event: DeleteEvent) {
+        let global = self.global();
+        match event {
+            DeleteEvent::Blocked { old_version } => {
+                IDBVersionChangeEvent::fire(
+                    self.upcast(),
+                    Atom::from("blocked"),
+                    old_version,
+                    None,
+                );
+            },
+            DeleteEvent::Success { old_version } => {
+                self.upcast::<IDBRequest>()
+                    .set_done(HandleValue::undefined(), None);
+                IDBVersionChangeEvent::fire(
+                    self.upcast(),
+                    Atom::from("success"),
+                    old_version,
+                    None,
+                );
+            },
+            DeleteEvent::Error(error) => {
+                let error = backend_error_to_dom_exception(&global, &error);
+                self.upcast::<IDBRequest>().fire_error(&error);
+            },
+        }
+    }
+}
+
+impl IDBOpenDBRequestMethods for IDBOpenDBRequest {
+    // https://w3c.github.io/IndexedDB/#dom-idbopendbrequest-onblocked
+    event_handler!(blocked, GetOnblocked, SetOnblocked);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbopendbrequest-onupgradeneeded
+    event_handler!(upgradeneeded, GetOnupgradeneeded, SetOnupgradeneeded);
+}
@@ -0,0 +1,344 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use js::jsapi::{HandleValueArray, Heap, NewArrayObject, JSPROP_ENUMERATE};
+use js::jsval::{DoubleValue, JSVal, NullValue, ObjectValue, UndefinedValue};
+use js::rust::wrappers::JS_DefineElement;
+use js::rust::{HandleValue, MutableHandleValue};
+use net_traits::indexeddb_thread::{BackendResult, IndexedDBRecord, OperationResult};
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::IDBRequestBinding::{
+    IDBRequestMethods, IDBRequestReadyState,
+};
+use crate::dom::bindings::codegen::UnionTypes::IDBObjectStoreOrIDBIndexOrIDBCursor;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbcursor::IDBCursor;
+use crate::dom::idbindex::IDBIndex;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbtransaction::IDBTransaction;
+use crate::indexed_db::{backend_error_to_dom_exception, convert_key_to_value, record_value};
+use crate::realms::enter_realm;
+use crate::script_runtime::JSContext;
+
+#[crown::unrooted_must_root_lint::must_root]
+#[derive(JSTraceable, MallocSizeOf)]
+enum RequestSource {
+    ObjectStore(Dom<IDBObjectStore>),
+    Index(Dom<IDBIndex>),
+    Cursor(Dom<IDBCursor>),
+}
+
+impl From<&IDBObjectStoreOrIDBIndexOrIDBCursor> for RequestSource {
+    #[allow(crown::unrooted_must_root)]
+    fn from(source: &IDBObjectStoreOrIDBIndexOrIDBCursor) -> RequestSource {
+        match source {
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(store) => {
+                RequestSource::ObjectStore(Dom::from_ref(&*store))
+            },
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(index) => {
+                RequestSource::Index(Dom::from_ref(&*index))
+            },
+            IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(cursor) => {
+                RequestSource::Cursor(Dom::from_ref(&*cursor))
+            },
+        }
+    }
+}
+
+/// How the result of the operation a request asked for is given to script.
+#[derive(Clone, MallocSizeOf)]
+pub enum ResultKind {
+    /// The value of the record read, with its primary key put back in at this key path if its
+    /// object store generated it.
+    Value(Option<String>),
+    /// The values of the records read, as for `Value`.
+    Values(Option<String>),
+    /// The primary key of the record read or written.
+    Key,
+    /// The primary keys of the records read.
+    Keys,
+    Count,
+    Undefined,
+    /// The record the cursor of the request moved to.
+    Cursor,
+}
+
+/// <https://w3c.github.io/IndexedDB/#request-api>
+#[dom_struct]
+pub struct IDBRequest {
+    eventtarget: EventTarget,
+    source: Option<RequestSource>,
+    transaction: MutNullableDom<IDBTransaction>,
+    /// The cursor the request iterates, if it was made by opening one.
+    cursor: MutNullableDom<IDBCursor>,
+    #[ignore_malloc_size_of = "mozjs"]
+    result: Heap<JSVal>,
+    error: MutNullableDom<DOMException>,
+    ready_state: Cell<IDBRequestReadyState>,
+    #[no_trace]
+    kind: ResultKind,
+    /// The number the transaction gave the operation the request waits for.
+    serial: Cell<u64>,
+}
+
+impl IDBRequest {
+    pub fn new_inherited(
+        source: Option<&IDBObjectStoreOrIDBIndexOrIDBCursor>,
+        transaction: Option<&IDBTransaction>,
+        kind: ResultKind,
+    ) -> IDBRequest {
+        IDBRequest {
+            eventtarget: EventTarget::new_inherited(),
+            source: source.map(RequestSource::from),
+            transaction: MutNullableDom::new(transaction),
+            cursor: Default::default(),
+            result: Heap::default(),
+            error: Default::default(),
+            ready_state: Cell::new(IDBRequestReadyState::Pending),
+            kind,
+            serial: Cell::new(0),
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        source: IDBObjectStoreOrIDBIndexOrIDBCursor,
+        transaction: &IDBTransaction,
+        kind: ResultKind,
+    ) -> DomRoot<IDBRequest> {
+        reflect_dom_object(
+            Box::new(IDBRequest::new_inherited(
+                Some(&source),
+                Some(transaction),
+                kind,
+            )),
+            global,
+        )
+    }
+
+    pub fn transaction(&self) -> Option<DomRoot<IDBTransaction>> {
+        self.transaction.get()
+    }
+
+    pub fn set_transaction(&self, transaction: Option<&IDBTransaction>) {
+        self.transaction.set(transaction);
+    }
+
+    pub fn set_cursor(&self, cursor: &IDBCursor) {
+        self.cursor.set(Some(cursor));
+    }
+
+    pub fn serial(&self) -> u64 {
+        self.serial.get()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.ready_state.get() == IDBRequestReadyState::Pending
+    }
+
+    /// Wait for the result of the operation numbered `serial`, as a request does again when
+    /// its cursor is continued.
+    pub fn set_pending(&self, serial: u64) {
+        self.serial.set(serial);
+        self.ready_state.set(IDBRequestReadyState::Pending);
+        self.result.set(UndefinedValue());
+        self.error.set(None);
+    }
+
+    pub fn set_done(&self, result: HandleValue, error: Option<&DOMException>) {
+        self.ready_state.set(IDBRequestReadyState::Done);
+        self.result.set(result.get());
+        self.error.set(error);
+    }
+
+    /// Give script the result of the operation the request waited for.
+    pub fn handle_result(&self, result: BackendResult<OperationResult>) {
+        let global = self.global();
+        let cx = GlobalScope::get_cx();
+        let _ac = enter_realm(self);
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        let outcome = match result {
+            Ok(result) => self
+                .convert_result(cx, &global, result, value.handle_mut())
+                .map_err(|()| DOMException::new(&global, DOMErrorName::DataCloneError)),
+            Err(error) => Err(backend_error_to_dom_exception(&global, &error)),
+        };
+        match outcome {
+            Ok(()) => self.fire_success(value.handle()),
+            Err(error) => self.fire_error(&error),
+        }
+    }
+
+    fn convert_result(
+        &self,
+        cx: JSContext,
+        global: &GlobalScope,
+        result: OperationResult,
+        mut rval: MutableHandleValue,
+    ) -> Result<(), ()> {
+        match (&self.kind, result) {
+            (ResultKind::Value(key_path), OperationResult::Record(Some(record))) => {
+                record_value(cx, global, record, key_path.as_deref(), rval)?;
+            },
+            (ResultKind::Values(key_path), OperationResult::Records(records)) => {
+                records_to_array(cx, records, rval, |record, value| {
+                    record_value(cx, global, record, key_path.as_deref(), value)
+                })?;
+            },
+            (ResultKind::Key, OperationResult::Record(Some(record))) => {
+                convert_key_to_value(cx, &record.primary_key, rval);
+            },
+            (ResultKind::Key, OperationResult::Key(key)) => {
+                convert_key_to_value(cx, &key, rval);
+            },
+            (ResultKind::Keys, OperationResult::Records(records)) => {
+                records_to_array(cx, records, rval, |record, value| {
+                    convert_key_to_value(cx, &record.primary_key, value);
+                    Ok(())
+                })?;
+            },
+            (ResultKind::Count, OperationResult::Count(count)) => {
+                rval.set(DoubleValue(count as f64));
+            },
+            (ResultKind::Cursor, OperationResult::Record(record)) => {
+                let cursor = self
+                    .cursor
+                    .get()
+                    .expect("A cursor request without a cursor");
+                if cursor.iterated(cx, global, record)? {
+                    rval.set(ObjectValue(cursor.reflector().get_jsobject().get()));
+                } else {
+                    rval.set(NullValue());
+                }
+            },
+            _ => {},
+        }
+        Ok(())
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#fire-a-success-event>
+    pub fn fire_success(&self, result: HandleValue) {
+        self.set_done(result, None);
+        let transaction = self.transaction();
+        if let Some(ref transaction) = transaction {
+            transaction.activate();
+        }
+        self.upcast::<EventTarget>()
+            .fire_event(Atom::from("success"));
+        if let Some(transaction) = transaction {
+            transaction.deactivate();
+        }
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#fire-an-error-event>
+    pub fn fire_error(&self, error: &DOMException) {
+        self.set_done(HandleValue::undefined(), Some(error));
+        let transaction = self.transaction();
+        if let Some(ref transaction) = transaction {
+            transaction.activate();
+        }
+        let event = Event::new(
+            &self.global(),
+            atom!("error"),
+            EventBubbles::Bubbles,
+            EventCancelable::Cancelable,
+        );
+        let status = event.fire(self.upcast());
+        if let Some(transaction) = transaction {
+            if status == EventStatus::NotCanceled {
+                transaction.abort(Some(error), true);
+            } else {
+                transaction.deactivate();
+            }
+        }
+    }
+}
+
+/// Make an array of the values of `records`.
+#[allow(unsafe_code)]
+fn records_to_array(
+    cx: JSContext,
+    records: Vec<IndexedDBRecord>,
+    mut rval: MutableHandleValue,
+    mut convert: impl FnMut(IndexedDBRecord, MutableHandleValue) -> Result<(), ()>,
+) -> Result<(), ()> {
+    rooted!(in(*cx) let array = unsafe { NewArrayObject(*cx, &HandleValueArray::new()) });
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    for (index, record) in records.into_iter().enumerate() {
+        convert(record, value.handle_mut())?;
+        unsafe {
+            if !JS_DefineElement(
+                *cx,
+                array.handle(),
+                index as u32,
+                value.handle(),
+                JSPROP_ENUMERATE as u32,
+            ) {
+                return Err(());
+            }
+        }
+    }
+    rval.set(ObjectValue(array.get()));
+    Ok(())
+}
+
+impl IDBRequestMethods for IDBRequest {
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-result
+    fn Result(&self, _cx: JSContext) -> Fallible<JSVal> {
+        if self.is_pending() {
+            return Err(Error::InvalidState);
+        }
+        Ok(self.result.get())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-error
+    fn GetError(&self) -> Fallible<Option<DomRoot<DOMException>>> {
+        if self.is_pending() {
+            return Err(Error::InvalidState);
+        }
+        Ok(self.error.get())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-source
+    fn GetSource(&self) -> Option<IDBObjectStoreOrIDBIndexOrIDBCursor> {
+        self.source.as_ref().map(|source| match source {
+            RequestSource::ObjectStore(store) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBObjectStore(DomRoot::from_ref(&**store))
+            },
+            RequestSource::Index(index) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBIndex(DomRoot::from_ref(&**index))
+            },
+            RequestSource::Cursor(cursor) => {
+                IDBObjectStoreOrIDBIndexOrIDBCursor::IDBCursor(DomRoot::from_ref(&**cursor))
+            },
+        })
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-transaction
+    fn GetTransaction(&self) -> Option<DomRoot<IDBTransaction>> {
+        self.transaction()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-readystate
+    fn ReadyState(&self) -> IDBRequestReadyState {
+        self.ready_state.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-onsuccess
+    event_handler!(success, GetOnsuccess, SetOnsuccess);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbrequest-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+}
@@ -0,0 +1,468 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcReceiver;
+use ipc_channel::router::ROUTER;
+use net_traits::indexeddb_thread::{
+    DatabaseMetadata, IndexedDBThreadMsg, IndexedDBTxnMode, TransactionEvent, TransactionId,
+    TransactionOperation,
+};
+use net_traits::IpcSend;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::IDBDatabaseBinding::IDBTransactionDurability;
+use crate::dom::bindings::codegen::Bindings::IDBTransactionBinding::{
+    IDBTransactionMethods, IDBTransactionMode,
+};
+use crate::dom::bindings::codegen::UnionTypes::IDBObjectStoreOrIDBIndexOrIDBCursor;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::domstringlist::DOMStringList;
+use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbdatabase::IDBDatabase;
+use crate::dom::idbobjectstore::IDBObjectStore;
+use crate::dom::idbopendbrequest::IDBOpenDBRequest;
+use crate::dom::idbrequest::{IDBRequest, ResultKind};
+use crate::indexed_db::backend_error_to_dom_exception;
+use crate::task_source::{TaskSource, TaskSourceName};
+
+impl From<IDBTransactionMode> for IndexedDBTxnMode {
+    fn from(mode: IDBTransactionMode) -> IndexedDBTxnMode {
+        match mode {
+            IDBTransactionMode::Readonly => IndexedDBTxnMode::Readonly,
+            IDBTransactionMode::Readwrite => IndexedDBTxnMode::Readwrite,
+            IDBTransactionMode::Versionchange => IndexedDBTxnMode::Versionchange,
+        }
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#transaction-lifetime>
+#[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
+enum TransactionState {
+    Active,
+    Inactive,
+    Committing,
+    Finished,
+}
+
+/// <https://w3c.github.io/IndexedDB/#transaction>
+#[dom_struct]
+pub struct IDBTransaction {
+    eventtarget: EventTarget,
+    db: Dom<IDBDatabase>,
+    #[no_trace]
+    id: TransactionId,
+    #[no_trace]
+    mode: IndexedDBTxnMode,
+    durability: IDBTransactionDurability,
+    /// The names of the object stores the transaction can use, sorted.
+    scope: Vec<String>,
+    state: Cell<TransactionState>,
+    error: MutNullableDom<DOMException>,
+    /// The requests waiting for the result of an operation, in the order they were made.
+    requests: DomRefCell<Vec<Dom<IDBRequest>>>,
+    next_request: Cell<u64>,
+    /// <https://w3c.github.io/IndexedDB/#transaction-object-store-handles>
+    object_stores: DomRefCell<Vec<Dom<IDBObjectStore>>>,
+    /// The request that opened the connection, for its upgrade transaction.
+    open_request: MutNullableDom<IDBOpenDBRequest>,
+    /// What the connection knew of the database before its upgrade transaction.
+    #[no_trace]
+    previous_metadata: Option<DatabaseMetadata>,
+}
+
+impl IDBTransaction {
+    fn new_inherited(
+        db: &IDBDatabase,
+        id: TransactionId,
+        mode: IndexedDBTxnMode,
+        durability: IDBTransactionDurability,
+        mut scope: Vec<String>,
+        open_request: Option<&IDBOpenDBRequest>,
+        previous_metadata: Option<DatabaseMetadata>,
+    ) -> IDBTransaction {
+        scope.sort();
+        scope.dedup();
+        IDBTransaction {
+            eventtarget: EventTarget::new_inherited(),
+            db: Dom::from_ref(db),
+            id,
+            mode,
+            durability,
+            scope,
+            state: Cell::new(TransactionState::Active),
+            error: Default::default(),
+            requests: Default::default(),
+            next_request: Cell::new(0),
+            object_stores: Default::default(),
+            open_request: MutNullableDom::new(open_request),
+            previous_metadata,
+        }
+    }
+
+    pub fn new(
+        global: &GlobalScope,
+        db: &IDBDatabase,
+        id: TransactionId,
+        mode: IndexedDBTxnMode,
+        durability: IDBTransactionDurability,
+        scope: Vec<String>,
+    ) -> DomRoot<IDBTransaction> {
+        reflect_dom_object(
+            Box::new(IDBTransaction::new_inherited(
+                db, id, mode, durability, scope, None, None,
+            )),
+            global,
+        )
+    }
+
+    /// The upgrade transaction of a connection, over all the object stores of its database.
+    pub fn new_upgrade(
+        global: &GlobalScope,
+        db: &IDBDatabase,
+        open_request: &IDBOpenDBRequest,
+        previous_metadata: DatabaseMetadata,
+    ) -> DomRoot<IDBTransaction> {
+        reflect_dom_object(
+            Box::new(IDBTransaction::new_inherited(
+                db,
+                TransactionId::upgrade(db.connection()),
+                IndexedDBTxnMode::Versionchange,
+                IDBTransactionDurability::Default,
+                db.object_store_names(),
+                Some(open_request),
+                Some(previous_metadata),
+            )),
+            global,
+        )
+    }
+
+    pub fn db(&self) -> DomRoot<IDBDatabase> {
+        DomRoot::from_ref(&*self.db)
+    }
+
+    pub fn mode(&self) -> IndexedDBTxnMode {
+        self.mode
+    }
+
+    pub fn is_upgrade(&self) -> bool {
+        self.mode == IndexedDBTxnMode::Versionchange
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.get() == TransactionState::Active
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state.get() == TransactionState::Finished
+    }
+
+    /// Handle the events of the transaction sent by the IndexedDB thread as tasks.
+    pub fn listen(&self, receiver: IpcReceiver<TransactionEvent>) {
+        let global = self.global();
+        let task_source = global.database_access_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DatabaseAccess);
+        let trusted = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event: TransactionEvent = match message.to() {
+                    Ok(event) => event,
+                    Err(error) => return warn!("Bad IndexedDB transaction event ({})", error),
+                };
+                let transaction = trusted.clone();
+                let _ = task_source.queue_with_canceller(
+                    task!(handle_transaction_event: move || {
+                        transaction.root().handle_event(event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+    }
+
+    fn handle_event(&self, event: TransactionEvent) {
+        match event {
+            TransactionEvent::RequestDone(serial, result) => {
+                if self.is_finished() {
+                    return;
+                }
+                let request = {
+                    let mut requests = self.requests.borrow_mut();
+                    match requests
+                        .iter()
+                        .position(|request| request.serial() == serial)
+                    {
+                        Some(position) => DomRoot::from_ref(&*requests.remove(position)),
+                        None => return,
+                    }
+                };
+                request.handle_result(result);
+            },
+            TransactionEvent::Complete => {
+                self.state.set(TransactionState::Finished);
+                if self.is_upgrade() {
+                    self.db.upgrade_finished();
+                }
+                self.upcast::<EventTarget>().fire_event(atom!("complete"));
+                if let Some(open_request) = self.open_request.get() {
+                    open_request.upgrade_complete();
+                }
+            },
+            TransactionEvent::Aborted(error) => {
+                let error = backend_error_to_dom_exception(&self.global(), &error);
+                self.abort(Some(&error), false);
+            },
+        }
+    }
+
+    /// Make a request for the result of `operation`.
+    pub fn make_request(
+        &self,
+        source: IDBObjectStoreOrIDBIndexOrIDBCursor,
+        kind: ResultKind,
+        operation: TransactionOperation,
+    ) -> DomRoot<IDBRequest> {
+        let request = IDBRequest::new(&self.global(), source, self, kind);
+        self.execute(&request, operation);
+        request
+    }
+
+    /// Send `operation` to run in the transaction once the ones before it are done, giving
+    /// its result to `request`.
+    pub fn execute(&self, request: &IDBRequest, operation: TransactionOperation) {
+        let serial = self.next_request.get();
+        self.next_request.set(serial + 1);
+        request.set_pending(serial);
+        self.requests.borrow_mut().push(Dom::from_ref(request));
+        self.send(IndexedDBThreadMsg::Operation(
+            self.id,
+            Some(serial),
+            operation,
+        ));
+    }
+
+    /// Send an operation changing the object stores or indexes of the database, which aborts
+    /// the transaction if it fails.
+    pub fn execute_schema_change(&self, operation: TransactionOperation) {
+        self.send(IndexedDBThreadMsg::Operation(self.id, None, operation));
+    }
+
+    pub fn id(&self) -> TransactionId {
+        self.id
+    }
+
+    fn send(&self, message: IndexedDBThreadMsg) {
+        let _ = self.global().resource_threads().send(message);
+    }
+
+    /// Make the transaction active while an event is fired for one of its requests.
+    pub fn activate(&self) {
+        if self.state.get() == TransactionState::Inactive {
+            self.state.set(TransactionState::Active);
+        }
+    }
+
+    /// Make the transaction inactive once script is done with it, committing it if it has
+    /// nothing left to do.
+    /// <https://w3c.github.io/IndexedDB/#transaction-commit>
+    pub fn deactivate(&self) {
+        if self.state.get() != TransactionState::Active {
+            return;
+        }
+        self.state.set(TransactionState::Inactive);
+        if self.requests.borrow().is_empty() {
+            self.commit();
+        }
+    }
+
+    /// Deactivate the transaction once the task that created it is done.
+    pub fn deactivate_after_task(&self) {
+        let global = self.global();
+        let trusted = Trusted::new(self);
+        let _ = global.database_access_task_source().queue(
+            task!(deactivate_transaction: move || {
+                trusted.root().deactivate();
+            }),
+            &global,
+        );
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#commit-a-transaction>
+    fn commit(&self) {
+        self.state.set(TransactionState::Committing);
+        self.send(IndexedDBThreadMsg::Commit(self.id));
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#abort-a-transaction>
+    pub fn abort(&self, error: Option<&DOMException>, notify_backend: bool) {
+        if self.is_finished() {
+            return;
+        }
+        if notify_backend {
+            self.send(IndexedDBThreadMsg::Abort(self.id));
+        }
+        self.state.set(TransactionState::Finished);
+
+        // <https://w3c.github.io/IndexedDB/#abort-an-upgrade-transaction>
+        if let Some(ref metadata) = self.previous_metadata {
+            self.db.upgrade_aborted(metadata.clone());
+            for store in self.object_stores.borrow().iter() {
+                store.reset_after_upgrade_abort();
+            }
+        }
+        self.error.set(error);
+
+        let requests: Vec<_> = self
+            .requests
+            .borrow_mut()
+            .drain(..)
+            .map(|request| Trusted::new(&*request))
+            .collect();
+        let transaction = Trusted::new(self);
+        let global = self.global();
+        let _ = global.database_access_task_source().queue(
+            task!(fire_abort_events: move || {
+                let transaction = transaction.root();
+                let global = transaction.global();
+                for request in requests {
+                    let error = DOMException::new(&global, DOMErrorName::AbortError);
+                    request.root().fire_error(&error);
+                }
+                let event = Event::new(
+                    &global,
+                    atom!("abort"),
+                    EventBubbles::Bubbles,
+                    EventCancelable::NotCancelable,
+                );
+                event.fire(transaction.upcast());
+                if let Some(open_request) = transaction.open_request.get() {
+                    open_request.upgrade_aborted();
+                }
+            }),
+            &global,
+        );
+    }
+
+    /// Whether the object store `name` is in the scope of the transaction, which is every
+    /// object store of the database for an upgrade transaction.
+    pub fn has_in_scope(&self, name: &str) -> bool {
+        if self.is_upgrade() {
+            return self.db.has_object_store(name);
+        }
+        self.scope
+            .binary_search_by(|n| n.as_str().cmp(name))
+            .is_ok()
+    }
+
+    /// Mark the handle of an object store the upgrade transaction deleted as such.
+    pub fn object_store_deleted(&self, name: &str) {
+        self.object_stores.borrow_mut().retain(|store| {
+            if *store.name() != name {
+                return true;
+            }
+            store.set_deleted();
+            false
+        });
+    }
+}
+
+impl IDBTransactionMethods for IDBTransaction {
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-objectstorenames
+    fn ObjectStoreNames(&self) -> DomRoot<DOMStringList> {
+        let names = if self.is_upgrade() {
+            self.db.object_store_names()
+        } else {
+            self.scope.clone()
+        };
+        DOMStringList::new(
+            &self.global(),
+            names.into_iter().map(DOMString::from).collect(),
+        )
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-mode
+    fn Mode(&self) -> IDBTransactionMode {
+        match self.mode {
+            IndexedDBTxnMode::Readonly => IDBTransactionMode::Readonly,
+            IndexedDBTxnMode::Readwrite => IDBTransactionMode::Readwrite,
+            IndexedDBTxnMode::Versionchange => IDBTransactionMode::Versionchange,
+        }
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-durability
+    fn Durability(&self) -> IDBTransactionDurability {
+        self.durability
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-db
+    fn Db(&self) -> DomRoot<IDBDatabase> {
+        self.db()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-error
+    fn GetError(&self) -> Option<DomRoot<DOMException>> {
+        self.error.get()
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-objectstore
+    fn ObjectStore(&self, name: DOMString) -> Fallible<DomRoot<IDBObjectStore>> {
+        if self.is_finished() {
+            return Err(Error::InvalidState);
+        }
+        if !self.has_in_scope(&name) {
+            return Err(Error::NotFound);
+        }
+        if let Some(store) = self
+            .object_stores
+            .borrow()
+            .iter()
+            .find(|store| *store.name() == *name)
+        {
+            return Ok(DomRoot::from_ref(&**store));
+        }
+        let store = IDBObjectStore::new(&self.global(), String::from(name), self);
+        self.object_stores.borrow_mut().push(Dom::from_ref(&*store));
+        Ok(store)
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-commit
+    fn Commit(&self) -> ErrorResult {
+        if !self.is_active() {
+            return Err(Error::InvalidState);
+        }
+        self.commit();
+        Ok(())
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-abort
+    fn Abort(&self) -> ErrorResult {
+        match self.state.get() {
+            TransactionState::Committing | TransactionState::Finished => Err(Error::InvalidState),
+            _ => {
+                self.abort(None, true);
+                Ok(())
+            },
+        }
+    }
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-onabort
+    event_handler!(abort, GetOnabort, SetOnabort);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-oncomplete
+    event_handler!(complete, GetOncomplete, SetOncomplete);
+
+    // https://w3c.github.io/IndexedDB/#dom-idbtransaction-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+}
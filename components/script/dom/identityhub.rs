@@ -5,8 +5,8 @@
 use smallvec::SmallVec;
 use webgpu::wgpu::id::{
     AdapterId, BindGroupId, BindGroupLayoutId, BufferId, CommandEncoderId, ComputePipelineId,
-    DeviceId, PipelineLayoutId, RenderBundleId, RenderPipelineId, SamplerId, ShaderModuleId,
-    TextureId, TextureViewId,
+    DeviceId, PipelineLayoutId, QuerySetId, RenderBundleId, RenderPipelineId, SamplerId,
+    ShaderModuleId, TextureId, TextureViewId,
 };
 use webgpu::wgpu::identity::IdentityManager;
 use webgpu::wgt::Backend;
@@ -27,6 +27,7 @@ pub struct IdentityHub {
     samplers: IdentityManager,
     render_pipelines: IdentityManager,
     render_bundles: IdentityManager,
+    query_sets: IdentityManager,
 }
 
 impl IdentityHub {
@@ -46,6 +47,7 @@ impl IdentityHub {
             samplers: IdentityManager::default(),
             render_pipelines: IdentityManager::default(),
             render_bundles: IdentityManager::default(),
+            query_sets: IdentityManager::default(),
         }
     }
 }
@@ -223,4 +225,12 @@ impl Identities {
     pub fn kill_render_bundle_id(&mut self, id: RenderBundleId) {
         self.select(id.backend()).render_bundles.free(id);
     }
+
+    pub fn create_query_set_id(&mut self, backend: Backend) -> QuerySetId {
+        self.select(backend).query_sets.alloc(backend)
+    }
+
+    pub fn kill_query_set_id(&mut self, id: QuerySetId) {
+        self.select(id.backend()).query_sets.free(id);
+    }
 }
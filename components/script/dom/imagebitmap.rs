@@ -3,15 +3,20 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::collections::HashMap;
 use std::vec::Vec;
 
 use dom_struct::dom_struct;
+use js::jsapi::MutableHandleObject;
+use script_traits::transferable::ImageBitmapImpl;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::ImageBitmapMethods;
 use crate::dom::bindings::error::Fallible;
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
 use crate::dom::globalscope::GlobalScope;
 
 #[dom_struct]
@@ -21,6 +26,8 @@ pub struct ImageBitmap {
     height: u32,
     bitmap_data: DomRefCell<Vec<u8>>,
     origin_clean: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#detached>
+    detached: Cell<bool>,
 }
 
 impl ImageBitmap {
@@ -31,6 +38,7 @@ impl ImageBitmap {
             height: height_arg,
             bitmap_data: DomRefCell::new(vec![]),
             origin_clean: Cell::new(true),
+            detached: Cell::new(false),
         }
     }
 
@@ -51,18 +59,84 @@ impl ImageBitmap {
     }
 }
 
+impl Transferable for ImageBitmap {
+    /// <https://html.spec.whatwg.org/multipage/#the-imagebitmap-interface:transfer-steps>
+    fn transfer(&self, sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        if self.detached.get() {
+            return Err(());
+        }
+
+        let image_bitmaps = match sc_holder {
+            StructuredDataHolder::Write { image_bitmaps, .. } => image_bitmaps,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+
+        // Step 1. Set dataHolder.[[BitmapData]] to value's bitmap data.
+        // Step 2. Unset value's bitmap data.
+        self.detached.set(true);
+        let transferred_bitmap = ImageBitmapImpl {
+            width: self.width,
+            height: self.height,
+            bitmap_data: std::mem::take(&mut *self.bitmap_data.borrow_mut()),
+            origin_clean: self.origin_clean.get(),
+        };
+
+        let image_bitmaps = image_bitmaps.get_or_insert_with(HashMap::new);
+        let key = image_bitmaps.len() as u64;
+        image_bitmaps.insert(key, transferred_bitmap);
+        Ok(key)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-imagebitmap-interface:transfer-receiving-steps>
+    fn transfer_receive(
+        owner: &GlobalScope,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        let image_bitmap_impls = match sc_holder {
+            StructuredDataHolder::Read {
+                image_bitmap_impls, ..
+            } => image_bitmap_impls,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+        let transferred_bitmap = image_bitmap_impls
+            .as_mut()
+            .and_then(|impls| impls.remove(&extra_data))
+            .ok_or(())?;
+
+        // Step 1. Set value's bitmap data to dataHolder.[[BitmapData]].
+        let bitmap = ImageBitmap::new(owner, transferred_bitmap.width, transferred_bitmap.height)
+            .map_err(|_| ())?;
+        bitmap.set_bitmap_data(transferred_bitmap.bitmap_data);
+        bitmap.set_origin_clean(transferred_bitmap.origin_clean);
+        return_object.set(bitmap.reflector().rootable().get());
+        Ok(())
+    }
+}
+
 impl ImageBitmapMethods for ImageBitmap {
     // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-height
     fn Height(&self) -> u32 {
-        //to do: add a condition for checking detached internal slot
-        //and return 0 if set to true
+        if self.detached.get() {
+            return 0;
+        }
         self.height
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-width
     fn Width(&self) -> u32 {
-        //to do: add a condition to check detached internal slot
-        ////and return 0 if set to true
+        if self.detached.get() {
+            return 0;
+        }
         self.width
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-imagebitmap-close
+    fn Close(&self) {
+        // Step 1. Set this's [[Detached]] internal slot value to true.
+        self.detached.set(true);
+        // Step 2. Unset this's bitmap data.
+        self.bitmap_data.borrow_mut().clear();
+    }
 }
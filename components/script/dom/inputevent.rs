@@ -9,8 +9,9 @@ use crate::dom::bindings::codegen::Bindings::InputEventBinding::{self, InputEven
 use crate::dom::bindings::codegen::Bindings::UIEventBinding::UIEvent_Binding::UIEventMethods;
 use crate::dom::bindings::error::Fallible;
 use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::staticrange::StaticRange;
 use crate::dom::uievent::UIEvent;
 use crate::dom::window::Window;
 
@@ -19,10 +20,12 @@ pub struct InputEvent {
     uievent: UIEvent,
     data: Option<DOMString>,
     is_composing: bool,
+    input_type: DOMString,
+    target_ranges: Vec<Dom<StaticRange>>,
 }
 
 impl InputEvent {
-    fn new(
+    pub fn new(
         window: &Window,
         proto: Option<HandleObject>,
         type_: DOMString,
@@ -32,12 +35,19 @@ impl InputEvent {
         detail: i32,
         data: Option<DOMString>,
         is_composing: bool,
+        input_type: DOMString,
+        target_ranges: &[DomRoot<StaticRange>],
     ) -> DomRoot<InputEvent> {
         let ev = reflect_dom_object_with_proto(
             Box::new(InputEvent {
                 uievent: UIEvent::new_inherited(),
                 data: data,
                 is_composing: is_composing,
+                input_type,
+                target_ranges: target_ranges
+                    .iter()
+                    .map(|range| Dom::from_ref(&**range))
+                    .collect(),
             }),
             window,
             proto,
@@ -64,6 +74,8 @@ impl InputEvent {
             init.parent.detail,
             init.data.clone(),
             init.isComposing,
+            init.inputType.clone(),
+            &init.targetRanges,
         );
         Ok(event)
     }
@@ -80,6 +92,19 @@ impl InputEventMethods for InputEvent {
         self.is_composing
     }
 
+    // https://w3c.github.io/input-events/#dom-inputevent-inputtype
+    fn InputType(&self) -> DOMString {
+        self.input_type.clone()
+    }
+
+    // https://w3c.github.io/input-events/#dom-inputevent-gettargetranges
+    fn GetTargetRanges(&self) -> Vec<DomRoot<StaticRange>> {
+        self.target_ranges
+            .iter()
+            .map(|range| DomRoot::from_ref(&**range))
+            .collect()
+    }
+
     // https://dom.spec.whatwg.org/#dom-event-istrusted
     fn IsTrusted(&self) -> bool {
         self.uievent.IsTrusted()
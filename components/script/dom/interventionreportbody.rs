@@ -0,0 +1,78 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::InterventionReportBodyBinding::InterventionReportBodyMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::report::SourceLocation;
+use crate::dom::reportbody::ReportBody;
+
+/// <https://wicg.github.io/intervention-reporting/#interventionreportbody>
+#[dom_struct]
+pub struct InterventionReportBody {
+    report_body: ReportBody,
+    id: DOMString,
+    message: DOMString,
+    location: Option<SourceLocation>,
+}
+
+impl InterventionReportBody {
+    fn new_inherited(
+        id: DOMString,
+        message: DOMString,
+        location: Option<SourceLocation>,
+    ) -> InterventionReportBody {
+        InterventionReportBody {
+            report_body: ReportBody::new_inherited(),
+            id,
+            message,
+            location,
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        id: DOMString,
+        message: DOMString,
+        location: Option<SourceLocation>,
+    ) -> DomRoot<InterventionReportBody> {
+        reflect_dom_object(
+            Box::new(InterventionReportBody::new_inherited(id, message, location)),
+            global,
+        )
+    }
+}
+
+impl InterventionReportBodyMethods for InterventionReportBody {
+    /// <https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-id>
+    fn Id(&self) -> DOMString {
+        self.id.clone()
+    }
+
+    /// <https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-message>
+    fn Message(&self) -> DOMString {
+        self.message.clone()
+    }
+
+    /// <https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-sourcefile>
+    fn GetSourceFile(&self) -> Option<DOMString> {
+        self.location
+            .as_ref()
+            .map(|location| DOMString::from(location.file.clone()))
+    }
+
+    /// <https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-linenumber>
+    fn GetLineNumber(&self) -> Option<u32> {
+        self.location.as_ref().map(|location| location.line)
+    }
+
+    /// <https://wicg.github.io/intervention-reporting/#dom-interventionreportbody-columnnumber>
+    fn GetColumnNumber(&self) -> Option<u32> {
+        self.location.as_ref().map(|location| location.column)
+    }
+}
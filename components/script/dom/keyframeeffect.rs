@@ -0,0 +1,675 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::conversions::{ConversionResult, FromJSValConvertible, ToJSValConvertible};
+use js::jsapi::{JSContext as RawJSContext, JSObject, JS_NewPlainObject};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::{HandleObject, HandleValue};
+use style::properties::animated_properties::AnimationValue;
+use style::properties::{
+    parse_one_declaration_into, Importance, LonghandId, PropertyDeclarationBlock, PropertyId,
+    SourcePropertyDeclaration, StyleBuilder,
+};
+use style::rule_cache::RuleCacheConditions;
+use style::stylesheets::container_rule::ContainerSizeQuery;
+use style::stylesheets::{CssRuleType, Origin, UrlExtraData};
+use style::values::animated::{Animate, Procedure};
+use style::values::computed::Context;
+use style_traits::ParsingMode;
+
+use crate::dom::animationeffect::{AnimationEffect, Easing, Timing};
+use crate::dom::bindings::cell::{DomRefCell, Ref};
+use crate::dom::bindings::codegen::Bindings::KeyframeEffectBinding::KeyframeEffectMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrKeyframeEffectOptions;
+use crate::dom::bindings::conversions::{is_array_like, StringificationBehavior};
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::record::Record;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::set_dictionary_property;
+use crate::dom::element::Element;
+use crate::dom::node::document_from_node;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+
+/// A keyframe of a keyframe effect,
+/// <https://drafts.csswg.org/web-animations-1/#keyframe>.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct Keyframe {
+    offset: Option<f64>,
+    /// <https://drafts.csswg.org/web-animations-1/#computed-keyframe-offset>
+    computed_offset: f64,
+    easing: Easing,
+    easing_serialization: String,
+    /// The values of the properties in this keyframe, keyed by the IDL attribute name
+    /// of each property.
+    values: Vec<(String, String)>,
+    /// The declarations parsed from `values`, without the values that are invalid for
+    /// their property.
+    #[ignore_malloc_size_of = "Defined in style"]
+    #[no_trace]
+    declarations: PropertyDeclarationBlock,
+}
+
+impl Keyframe {
+    fn new(
+        window: &Window,
+        offset: Option<f64>,
+        easing: &str,
+        values: Vec<(String, String)>,
+    ) -> Fallible<Keyframe> {
+        if let Some(offset) = offset {
+            if !(0. ..=1.).contains(&offset) {
+                return Err(Error::Type(
+                    "Keyframe offsets must be between 0 and 1".to_owned(),
+                ));
+            }
+        }
+        let (easing, easing_serialization) = Easing::parse(easing)
+            .ok_or_else(|| Error::Type(format!("Invalid easing '{}'", easing)))?;
+        Ok(Keyframe {
+            offset,
+            computed_offset: offset.unwrap_or(0.),
+            easing,
+            easing_serialization,
+            declarations: parse_declarations(window, &values),
+            values,
+        })
+    }
+}
+
+/// Parse the property values of a keyframe into a declaration block. Values that are
+/// invalid for their property are left out, so that they have no effect.
+fn parse_declarations(window: &Window, values: &[(String, String)]) -> PropertyDeclarationBlock {
+    let document = window.Document();
+    let url_data = UrlExtraData(document.base_url().get_arc());
+    let mut block = PropertyDeclarationBlock::new();
+    for (name, value) in values {
+        let id = match animation_property_name(name)
+            .and_then(|property| PropertyId::parse_enabled_for_all_content(&property).ok())
+        {
+            Some(id) => id,
+            None => continue,
+        };
+        let mut declarations = SourcePropertyDeclaration::default();
+        let result = parse_one_declaration_into(
+            &mut declarations,
+            id,
+            value,
+            Origin::Author,
+            &url_data,
+            window.css_error_reporter(),
+            ParsingMode::DEFAULT,
+            document.quirks_mode(),
+            CssRuleType::Style,
+        );
+        if result.is_ok() {
+            block.extend(declarations.drain(), Importance::Normal);
+        }
+    }
+    block
+}
+
+/// The properties of a keyframe in the array form of a keyframes argument, with their
+/// values converted to strings.
+struct KeyframeObject(Record<DOMString, DOMString>);
+
+impl FromJSValConvertible for KeyframeObject {
+    type Config = ();
+
+    unsafe fn from_jsval(
+        cx: *mut RawJSContext,
+        value: HandleValue,
+        _: (),
+    ) -> Result<ConversionResult<KeyframeObject>, ()> {
+        if value.is_null_or_undefined() {
+            return Ok(ConversionResult::Success(KeyframeObject(Record::new())));
+        }
+        Ok(
+            match Record::from_jsval(cx, value, StringificationBehavior::Default)? {
+                ConversionResult::Success(record) => {
+                    ConversionResult::Success(KeyframeObject(record))
+                },
+                ConversionResult::Failure(message) => ConversionResult::Failure(message),
+            },
+        )
+    }
+}
+
+/// A value of the property-indexed form of a keyframes argument, either a single value
+/// or a list of values.
+enum PropertyIndexedValue {
+    Single(DOMString),
+    List(Vec<DOMString>),
+}
+
+impl PropertyIndexedValue {
+    fn to_vec(&self) -> Vec<DOMString> {
+        match self {
+            PropertyIndexedValue::Single(value) => vec![value.clone()],
+            PropertyIndexedValue::List(values) => values.clone(),
+        }
+    }
+}
+
+impl FromJSValConvertible for PropertyIndexedValue {
+    type Config = ();
+
+    unsafe fn from_jsval(
+        cx: *mut RawJSContext,
+        value: HandleValue,
+        _: (),
+    ) -> Result<ConversionResult<PropertyIndexedValue>, ()> {
+        if is_array_like(cx, value) {
+            return Ok(
+                match Vec::from_jsval(cx, value, StringificationBehavior::Default)? {
+                    ConversionResult::Success(values) => {
+                        ConversionResult::Success(PropertyIndexedValue::List(values))
+                    },
+                    ConversionResult::Failure(message) => ConversionResult::Failure(message),
+                },
+            );
+        }
+        Ok(
+            match DOMString::from_jsval(cx, value, StringificationBehavior::Default)? {
+                ConversionResult::Success(value) => {
+                    ConversionResult::Success(PropertyIndexedValue::Single(value))
+                },
+                ConversionResult::Failure(message) => ConversionResult::Failure(message),
+            },
+        )
+    }
+}
+
+/// Unwrap the result of converting a keyframes argument.
+fn conversion_result<T>(result: Result<ConversionResult<T>, ()>) -> Fallible<T> {
+    match result {
+        Ok(ConversionResult::Success(value)) => Ok(value),
+        Ok(ConversionResult::Failure(message)) => Err(Error::Type(message.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+/// The CSS name of the property with the IDL attribute name `name`, if it is a
+/// property that can be animated,
+/// <https://drafts.csswg.org/web-animations-1/#idl-attribute-name-to-animation-property-name>.
+fn animation_property_name(name: &str) -> Option<String> {
+    let property = match name {
+        "cssFloat" => "float".to_owned(),
+        "cssOffset" => "offset".to_owned(),
+        // These are only reachable through their "css"-prefixed names.
+        "float" | "offset" => return None,
+        name if name.starts_with("--") => name.to_owned(),
+        name => {
+            let mut property = String::with_capacity(name.len());
+            for character in name.chars() {
+                if character.is_ascii_uppercase() {
+                    property.push('-');
+                    property.push(character.to_ascii_lowercase());
+                } else if character == '-' {
+                    // Hyphenated names are not IDL attribute names.
+                    return None;
+                } else {
+                    property.push(character);
+                }
+            }
+            property
+        },
+    };
+    PropertyId::parse_enabled_for_all_content(&property).ok()?;
+    Some(property)
+}
+
+/// The properties of a keyframe that are not animated properties.
+const KEYFRAME_MEMBERS: [&str; 3] = ["offset", "easing", "composite"];
+
+/// Parse a keyframe offset given as a string, where "null" and the empty string mean
+/// that it has none.
+fn parse_offset(offset: &str) -> Fallible<Option<f64>> {
+    if offset.is_empty() || offset == "null" || offset == "undefined" {
+        return Ok(None);
+    }
+    match offset.trim().parse::<f64>() {
+        Ok(offset) if offset.is_finite() => Ok(Some(offset)),
+        _ => Err(Error::Type(format!("Invalid keyframe offset '{}'", offset))),
+    }
+}
+
+/// The values of the property `name` in the property-indexed form, if it is a property
+/// that can be animated.
+fn property_values(name: &str, values: Vec<DOMString>) -> Option<(String, Vec<String>)> {
+    animation_property_name(name)?;
+    Some((
+        name.to_owned(),
+        values.into_iter().map(String::from).collect(),
+    ))
+}
+
+/// <https://drafts.csswg.org/web-animations-1/#processing-a-keyframes-argument>
+#[allow(unsafe_code)]
+fn process_keyframes(
+    cx: JSContext,
+    window: &Window,
+    keyframes: *mut JSObject,
+) -> Fallible<Vec<Keyframe>> {
+    // Step 1.
+    if keyframes.is_null() {
+        return Ok(vec![]);
+    }
+    rooted!(in(*cx) let value = ObjectValue(keyframes));
+
+    let mut keyframes = if unsafe { is_array_like(*cx, value.handle()) } {
+        // Step 6 - 7, the array form.
+        let objects: Vec<KeyframeObject> =
+            conversion_result(unsafe { Vec::from_jsval(*cx, value.handle(), ()) })?;
+        let mut keyframes = Vec::with_capacity(objects.len());
+        for KeyframeObject(object) in objects {
+            let offset = match object.get(&DOMString::from("offset")) {
+                Some(offset) => parse_offset(offset)?,
+                None => None,
+            };
+            let easing = object
+                .get(&DOMString::from("easing"))
+                .map_or("linear", |easing| &**easing);
+            let values = object
+                .iter()
+                .filter(|(name, _)| !KEYFRAME_MEMBERS.contains(&&***name))
+                .filter(|(name, _)| animation_property_name(name).is_some())
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            keyframes.push(Keyframe::new(window, offset, easing, values)?);
+        }
+
+        // Step 8, offsets must be loosely sorted.
+        let mut previous_offset = 0.;
+        for offset in keyframes.iter().filter_map(|keyframe| keyframe.offset) {
+            if offset < previous_offset {
+                return Err(Error::Type(
+                    "Keyframe offsets must be loosely sorted".to_owned(),
+                ));
+            }
+            previous_offset = offset;
+        }
+        keyframes
+    } else {
+        // Step 6, the property-indexed form.
+        let record: Record<DOMString, PropertyIndexedValue> =
+            conversion_result(unsafe { Record::from_jsval(*cx, value.handle(), ()) })?;
+        let mut offsets = vec![];
+        let mut easings = vec![];
+        let mut properties = vec![];
+        for (name, value) in record.iter() {
+            match &**name {
+                "offset" => {
+                    offsets = value
+                        .to_vec()
+                        .iter()
+                        .map(|offset| parse_offset(offset))
+                        .collect::<Fallible<_>>()?;
+                },
+                "easing" => easings = value.to_vec(),
+                "composite" => {},
+                _ => properties.extend(property_values(name, value.to_vec())),
+            }
+        }
+
+        // Build a keyframe for each offset at which some property has a value.
+        let mut keyframes: Vec<(f64, Vec<(String, String)>)> = vec![];
+        for (name, values) in properties {
+            let count = values.len();
+            for (index, value) in values.into_iter().enumerate() {
+                let offset = if count == 1 {
+                    1.
+                } else {
+                    index as f64 / (count - 1) as f64
+                };
+                match keyframes.iter_mut().find(|(other, _)| *other == offset) {
+                    Some((_, values)) => values.push((name.clone(), value)),
+                    None => keyframes.push((offset, vec![(name.clone(), value)])),
+                }
+            }
+        }
+        keyframes.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let mut result = Vec::with_capacity(keyframes.len());
+        for (index, (computed_offset, values)) in keyframes.into_iter().enumerate() {
+            let offset = offsets.get(index).copied().flatten();
+            let easing = match easings.len() {
+                0 => "linear",
+                count => &*easings[index % count],
+            };
+            let mut keyframe = Keyframe::new(window, offset, easing, values)?;
+            keyframe.computed_offset = computed_offset;
+            result.push(keyframe);
+        }
+        if !offsets.is_empty() {
+            compute_missing_offsets(&mut result);
+        }
+        return Ok(result);
+    };
+
+    compute_missing_offsets(&mut keyframes);
+    Ok(keyframes)
+}
+
+/// <https://drafts.csswg.org/web-animations-1/#compute-missing-keyframe-offsets>
+fn compute_missing_offsets(keyframes: &mut [Keyframe]) {
+    let count = keyframes.len();
+    if count == 0 {
+        return;
+    }
+
+    // Step 1 - 2.
+    for keyframe in keyframes.iter_mut() {
+        keyframe.computed_offset = keyframe.offset.unwrap_or(f64::NAN);
+    }
+    if count > 1 && keyframes[0].computed_offset.is_nan() {
+        keyframes[0].computed_offset = 0.;
+    }
+    if keyframes[count - 1].computed_offset.is_nan() {
+        keyframes[count - 1].computed_offset = 1.;
+    }
+
+    // Step 3, space the keyframes without an offset evenly between their neighbours.
+    let mut previous = 0;
+    for index in 1..count {
+        if keyframes[index].computed_offset.is_nan() {
+            continue;
+        }
+        let (start, end) = (
+            keyframes[previous].computed_offset,
+            keyframes[index].computed_offset,
+        );
+        let steps = (index - previous) as f64;
+        for (step, keyframe) in keyframes[previous + 1..index].iter_mut().enumerate() {
+            keyframe.computed_offset = start + (end - start) * (step + 1) as f64 / steps;
+        }
+        previous = index;
+    }
+}
+
+/// The keyframes of a keyframe effect for a single property, with their values computed
+/// for the target of the effect,
+/// <https://drafts.csswg.org/web-animations-1/#property-specific-keyframes>.
+struct PropertyKeyframes {
+    /// The computed offset, easing and value of each keyframe. There are always keyframes
+    /// at the offsets 0 and 1, where the implicit ones hold the underlying value.
+    keyframes: Vec<(f64, Easing, AnimationValue)>,
+}
+
+impl PropertyKeyframes {
+    /// The value of the property at the iteration progress `progress`,
+    /// <https://drafts.csswg.org/web-animations-1/#the-effect-value-of-a-keyframe-animation-effect>.
+    fn value_at(&self, progress: f64) -> AnimationValue {
+        let keyframes = &self.keyframes;
+        let count_at = |offset: f64| {
+            keyframes
+                .iter()
+                .filter(|(other, _, _)| *other == offset)
+                .count()
+        };
+
+        // Step 10 - 11.
+        if progress < 0. && count_at(0.) > 1 {
+            return keyframes[0].2.clone();
+        }
+        if progress >= 1. && count_at(1.) > 1 {
+            return keyframes[keyframes.len() - 1].2.clone();
+        }
+
+        // Step 12. As there are always keyframes at 0 and 1, the start keyframe is never
+        // the last one.
+        let start = keyframes
+            .iter()
+            .rposition(|(offset, _, _)| *offset <= progress && *offset < 1.)
+            .unwrap_or(0);
+        let (from_offset, easing, from) = &keyframes[start];
+        let (to_offset, _, to) = &keyframes[start + 1];
+
+        // Step 13 - 18.
+        let progress = easing.apply((progress - from_offset) / (to_offset - from_offset), false);
+        from.animate(to, Procedure::Interpolate { progress })
+            .unwrap_or_else(|()| {
+                // Values that cannot be interpolated are animated discretely,
+                // <https://drafts.csswg.org/web-animations-1/#discrete>.
+                if progress < 0.5 { from } else { to }.clone()
+            })
+    }
+}
+
+/// Compute the values of `keyframes` for `target`, and group them by property, adding
+/// implicit keyframes for the properties without a value at the offsets 0 or 1,
+/// <https://drafts.csswg.org/web-animations-1/#computing-property-values>. Returns `None`
+/// if `target` has no computed style, as when it is in a `display: none` subtree.
+///
+/// The underlying value of implicit keyframes is the value of the property in the
+/// computed style of `target` when the keyframes are computed, as CSS animations do with
+/// the style of their element when they start.
+fn compute_property_keyframes(
+    keyframes: &[Keyframe],
+    target: &Element,
+) -> Option<Vec<PropertyKeyframes>> {
+    let style = target.style()?;
+    let document = document_from_node(target);
+    let device = document.device();
+    let mut rule_cache_conditions = RuleCacheConditions::default();
+    let mut context = Context::new_for_animation(
+        StyleBuilder::for_animation(&device, &style, None),
+        /* for_smil_animation = */ false,
+        document.quirks_mode(),
+        &mut rule_cache_conditions,
+        ContainerSizeQuery::none(),
+    );
+
+    let mut properties: Vec<(LonghandId, PropertyKeyframes)> = vec![];
+    for keyframe in keyframes {
+        for declaration in keyframe.declarations.declarations() {
+            let value = match AnimationValue::from_declaration(
+                declaration,
+                &mut context,
+                style.custom_properties(),
+                device.default_computed_values(),
+            ) {
+                Some(value) => value,
+                None => continue,
+            };
+            let property = value.id();
+            let entry = (keyframe.computed_offset, keyframe.easing, value);
+            match properties.iter_mut().find(|(other, _)| *other == property) {
+                Some((_, property_keyframes)) => property_keyframes.keyframes.push(entry),
+                None => properties.push((
+                    property,
+                    PropertyKeyframes {
+                        keyframes: vec![entry],
+                    },
+                )),
+            }
+        }
+    }
+
+    Some(
+        properties
+            .into_iter()
+            .filter_map(|(property, mut property_keyframes)| {
+                let keyframes = &mut property_keyframes.keyframes;
+                let needs_start = keyframes.first().map_or(true, |(offset, ..)| *offset != 0.);
+                let needs_end = keyframes.last().map_or(true, |(offset, ..)| *offset != 1.);
+                if needs_start || needs_end {
+                    let underlying_value = AnimationValue::from_computed_values(property, &style)?;
+                    if needs_start {
+                        keyframes.insert(0, (0., Easing::Linear, underlying_value.clone()));
+                    }
+                    if needs_end {
+                        keyframes.push((1., Easing::Linear, underlying_value));
+                    }
+                }
+                Some(property_keyframes)
+            })
+            .collect(),
+    )
+}
+
+/// <https://drafts.csswg.org/web-animations-1/#the-keyframeeffect-interface>
+#[dom_struct]
+pub struct KeyframeEffect {
+    effect: AnimationEffect,
+    target: MutNullableDom<Element>,
+    keyframes: DomRefCell<Vec<Keyframe>>,
+    /// The keyframes of each property, computed for the target when the effect is first
+    /// sampled after its keyframes or its target changed.
+    #[ignore_malloc_size_of = "Defined in style"]
+    #[no_trace]
+    property_keyframes: DomRefCell<Option<Vec<PropertyKeyframes>>>,
+}
+
+impl KeyframeEffect {
+    #[allow(crown::unrooted_must_root)]
+    fn new_inherited(
+        target: Option<&Element>,
+        keyframes: Vec<Keyframe>,
+        timing: Timing,
+    ) -> KeyframeEffect {
+        KeyframeEffect {
+            effect: AnimationEffect::new_inherited(timing),
+            target: MutNullableDom::new(target),
+            keyframes: DomRefCell::new(keyframes),
+            property_keyframes: DomRefCell::new(None),
+        }
+    }
+
+    /// Create a keyframe effect for `target` from the keyframes argument `keyframes`.
+    pub fn new(
+        cx: JSContext,
+        window: &Window,
+        proto: Option<HandleObject>,
+        target: Option<&Element>,
+        keyframes: *mut JSObject,
+        timing: Timing,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let keyframes = process_keyframes(cx, window, keyframes)?;
+        Ok(reflect_dom_object_with_proto(
+            Box::new(KeyframeEffect::new_inherited(target, keyframes, timing)),
+            window,
+            proto,
+        ))
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-keyframeeffect-keyframeeffect>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        cx: JSContext,
+        window: &Window,
+        proto: Option<HandleObject>,
+        target: Option<&Element>,
+        keyframes: *mut JSObject,
+        options: UnrestrictedDoubleOrKeyframeEffectOptions,
+    ) -> Fallible<DomRoot<KeyframeEffect>> {
+        let timing = match options {
+            UnrestrictedDoubleOrKeyframeEffectOptions::UnrestrictedDouble(duration) => {
+                Timing::from_duration(duration)?
+            },
+            UnrestrictedDoubleOrKeyframeEffectOptions::KeyframeEffectOptions(options) => {
+                Timing::from_effect_timing(&options.parent)?
+            },
+        };
+        KeyframeEffect::new(cx, window, proto, target, keyframes, timing)
+    }
+
+    pub fn target(&self) -> Option<DomRoot<Element>> {
+        self.target.get()
+    }
+
+    /// The keyframes of each property animated by this effect, with their values
+    /// computed for its target, computing them if they were not yet.
+    fn property_keyframes(&self, target: &Element) -> Option<Ref<Vec<PropertyKeyframes>>> {
+        if self.property_keyframes.borrow().is_none() {
+            let property_keyframes = compute_property_keyframes(&self.keyframes.borrow(), target)?;
+            *self.property_keyframes.borrow_mut() = Some(property_keyframes);
+        }
+        Some(Ref::map(
+            self.property_keyframes.borrow(),
+            |property_keyframes| property_keyframes.as_ref().unwrap(),
+        ))
+    }
+
+    /// The values of the properties animated by this effect at its current progress, or
+    /// `None` if it is not in effect or has no target with a computed style,
+    /// <https://drafts.csswg.org/web-animations-1/#the-effect-value-of-a-keyframe-animation-effect>.
+    pub fn animated_values(&self) -> Option<Vec<AnimationValue>> {
+        let (progress, _) = self.effect.progress()?;
+        let target = self.target.get()?;
+        let property_keyframes = self.property_keyframes(&target)?;
+        Some(
+            property_keyframes
+                .iter()
+                .map(|property_keyframes| property_keyframes.value_at(progress))
+                .collect(),
+        )
+    }
+
+    /// Forget the computed values of the keyframes, so that they are computed again the
+    /// next time this effect is sampled.
+    fn invalidate_property_keyframes(&self) {
+        *self.property_keyframes.borrow_mut() = None;
+    }
+}
+
+impl KeyframeEffectMethods for KeyframeEffect {
+    /// <https://drafts.csswg.org/web-animations-1/#dom-keyframeeffect-target>
+    fn GetTarget(&self) -> Option<DomRoot<Element>> {
+        self.target.get()
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-keyframeeffect-target>
+    fn SetTarget(&self, target: Option<&Element>) {
+        self.target.set(target);
+        self.invalidate_property_keyframes();
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-keyframeeffect-getkeyframes>
+    #[allow(unsafe_code)]
+    fn GetKeyframes(&self, cx: JSContext) -> JSVal {
+        rooted!(in(*cx) let mut result = UndefinedValue());
+        let empty: Vec<JSVal> = vec![];
+        unsafe { empty.to_jsval(*cx, result.handle_mut()) };
+        rooted!(in(*cx) let array = result.to_object());
+
+        rooted!(in(*cx) let mut value = UndefinedValue());
+        for (index, keyframe) in self.keyframes.borrow().iter().enumerate() {
+            rooted!(in(*cx) let object = unsafe { JS_NewPlainObject(*cx) });
+            assert!(!object.is_null());
+            let set = |name: &str, value: &dyn ToJSValConvertible| unsafe {
+                rooted!(in(*cx) let mut property = UndefinedValue());
+                value.to_jsval(*cx, property.handle_mut());
+                let _ = set_dictionary_property(*cx, object.handle(), name, property.handle());
+            };
+            set("offset", &keyframe.offset);
+            set("computedOffset", &keyframe.computed_offset);
+            set(
+                "easing",
+                &DOMString::from(keyframe.easing_serialization.clone()),
+            );
+            set("composite", &DOMString::from("auto"));
+            for (name, property_value) in keyframe.values.iter() {
+                set(name, &DOMString::from(property_value.clone()));
+            }
+
+            value.set(ObjectValue(object.get()));
+            let _ =
+                set_dictionary_property(*cx, array.handle(), &index.to_string(), value.handle());
+        }
+        *result
+    }
+
+    /// <https://drafts.csswg.org/web-animations-1/#dom-keyframeeffect-setkeyframes>
+    fn SetKeyframes(&self, cx: JSContext, keyframes: *mut JSObject) -> ErrorResult {
+        let window = self.global();
+        *self.keyframes.borrow_mut() = process_keyframes(cx, window.as_window(), keyframes)?;
+        self.invalidate_property_keyframes();
+        Ok(())
+    }
+}
@@ -449,6 +449,7 @@ macro_rules! global_event_handlers(
         event_handler!(emptied, GetOnemptied, SetOnemptied);
         event_handler!(ended, GetOnended, SetOnended);
         event_handler!(formdata, GetOnformdata, SetOnformdata);
+        event_handler!(gotpointercapture, GetOngotpointercapture, SetOngotpointercapture);
         event_handler!(input, GetOninput, SetOninput);
         event_handler!(invalid, GetOninvalid, SetOninvalid);
         event_handler!(keydown, GetOnkeydown, SetOnkeydown);
@@ -457,6 +458,7 @@ macro_rules! global_event_handlers(
         event_handler!(loadeddata, GetOnloadeddata, SetOnloadeddata);
         event_handler!(loadedmetadata, GetOnloadedmetadata, SetOnloadedmetadata);
         event_handler!(loadstart, GetOnloadstart, SetOnloadstart);
+        event_handler!(lostpointercapture, GetOnlostpointercapture, SetOnlostpointercapture);
         event_handler!(mousedown, GetOnmousedown, SetOnmousedown);
         event_handler!(mouseenter, GetOnmouseenter, SetOnmouseenter);
         event_handler!(mouseleave, GetOnmouseleave, SetOnmouseleave);
@@ -468,6 +470,14 @@ macro_rules! global_event_handlers(
         event_handler!(pause, GetOnpause, SetOnpause);
         event_handler!(play, GetOnplay, SetOnplay);
         event_handler!(playing, GetOnplaying, SetOnplaying);
+        event_handler!(pointercancel, GetOnpointercancel, SetOnpointercancel);
+        event_handler!(pointerdown, GetOnpointerdown, SetOnpointerdown);
+        event_handler!(pointerenter, GetOnpointerenter, SetOnpointerenter);
+        event_handler!(pointerleave, GetOnpointerleave, SetOnpointerleave);
+        event_handler!(pointermove, GetOnpointermove, SetOnpointermove);
+        event_handler!(pointerout, GetOnpointerout, SetOnpointerout);
+        event_handler!(pointerover, GetOnpointerover, SetOnpointerover);
+        event_handler!(pointerup, GetOnpointerup, SetOnpointerup);
         event_handler!(progress, GetOnprogress, SetOnprogress);
         event_handler!(ratechange, GetOnratechange, SetOnratechange);
         event_handler!(reset, GetOnreset, SetOnreset);
@@ -575,44 +585,6 @@ macro_rules! rooted_vec {
     };
 }
 
-/// DOM struct implementation for simple interfaces inheriting from PerformanceEntry.
-macro_rules! impl_performance_entry_struct(
-    ($binding:ident, $struct:ident, $type:expr) => (
-        use crate::dom::bindings::reflector::reflect_dom_object;
-        use crate::dom::bindings::root::DomRoot;
-        use crate::dom::bindings::str::DOMString;
-        use crate::dom::globalscope::GlobalScope;
-        use crate::dom::performanceentry::PerformanceEntry;
-        use dom_struct::dom_struct;
-
-        #[dom_struct]
-        pub struct $struct {
-            entry: PerformanceEntry,
-        }
-
-        impl $struct {
-            fn new_inherited(name: DOMString, start_time: f64, duration: f64)
-                -> $struct {
-                $struct {
-                    entry: PerformanceEntry::new_inherited(name,
-                                                           DOMString::from($type),
-                                                           start_time,
-                                                           duration)
-                }
-            }
-
-            #[allow(crown::unrooted_must_root)]
-            pub fn new(global: &GlobalScope,
-                       name: DOMString,
-                       start_time: f64,
-                       duration: f64) -> DomRoot<$struct> {
-                let entry = $struct::new_inherited(name, start_time, duration);
-                reflect_dom_object(Box::new(entry), global)
-            }
-        }
-    );
-);
-
 macro_rules! handle_potential_webgl_error {
     ($context:expr, $call:expr, $return_on_error:expr) => {
         match $call {
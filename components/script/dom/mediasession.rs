@@ -5,7 +5,10 @@
 use std::rc::Rc;
 
 use dom_struct::dom_struct;
-use embedder_traits::{MediaMetadata as EmbedderMediaMetadata, MediaSessionEvent};
+use embedder_traits::{
+    MediaMetadata as EmbedderMediaMetadata, MediaSessionEvent,
+    MediaSessionPlaybackState as EmbedderMediaSessionPlaybackState,
+};
 use script_traits::{MediaSessionActionType, ScriptMsg};
 
 use super::bindings::trace::HashMapTracedValues;
@@ -16,8 +19,8 @@ use crate::dom::bindings::codegen::Bindings::MediaMetadataBinding::{
     MediaMetadataInit, MediaMetadataMethods,
 };
 use crate::dom::bindings::codegen::Bindings::MediaSessionBinding::{
-    MediaPositionState, MediaSessionAction, MediaSessionActionHandler, MediaSessionMethods,
-    MediaSessionPlaybackState,
+    MediaPositionState, MediaSessionAction, MediaSessionActionDetails, MediaSessionActionHandler,
+    MediaSessionMethods, MediaSessionPlaybackState,
 };
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::num::Finite;
@@ -71,8 +74,12 @@ impl MediaSession {
     pub fn handle_action(&self, action: MediaSessionActionType) {
         debug!("Handle media session action {:?}", action);
 
-        if let Some(handler) = self.action_handlers.borrow().get(&action) {
-            if handler.Call__(ExceptionHandling::Report).is_err() {
+        let handler = self.action_handlers.borrow().get(&action).cloned();
+        if let Some(handler) = handler {
+            let details = MediaSessionActionDetails {
+                action: action.clone().into(),
+            };
+            if handler.Call__(&details, ExceptionHandling::Report).is_err() {
                 warn!("Error calling MediaSessionActionHandler callback");
             }
             return;
@@ -176,6 +183,9 @@ impl MediaSessionMethods for MediaSession {
     /// <https://w3c.github.io/mediasession/#dom-mediasession-playbackstate>
     fn SetPlaybackState(&self, state: MediaSessionPlaybackState) {
         *self.playback_state.borrow_mut() = state;
+        // Let the embedder show whether the page is playing, for its media controls to offer
+        // to play or to pause.
+        self.send_event(MediaSessionEvent::PlaybackStateChange(state.into()));
     }
 
     /// <https://w3c.github.io/mediasession/#update-action-handler-algorithm>
@@ -250,6 +260,32 @@ impl MediaSessionMethods for MediaSession {
     }
 }
 
+impl From<MediaSessionPlaybackState> for EmbedderMediaSessionPlaybackState {
+    fn from(state: MediaSessionPlaybackState) -> EmbedderMediaSessionPlaybackState {
+        match state {
+            MediaSessionPlaybackState::None => EmbedderMediaSessionPlaybackState::None_,
+            MediaSessionPlaybackState::Paused => EmbedderMediaSessionPlaybackState::Paused,
+            MediaSessionPlaybackState::Playing => EmbedderMediaSessionPlaybackState::Playing,
+        }
+    }
+}
+
+impl From<MediaSessionActionType> for MediaSessionAction {
+    fn from(action: MediaSessionActionType) -> MediaSessionAction {
+        match action {
+            MediaSessionActionType::Play => MediaSessionAction::Play,
+            MediaSessionActionType::Pause => MediaSessionAction::Pause,
+            MediaSessionActionType::SeekBackward => MediaSessionAction::Seekbackward,
+            MediaSessionActionType::SeekForward => MediaSessionAction::Seekforward,
+            MediaSessionActionType::PreviousTrack => MediaSessionAction::Previoustrack,
+            MediaSessionActionType::NextTrack => MediaSessionAction::Nexttrack,
+            MediaSessionActionType::SkipAd => MediaSessionAction::Skipad,
+            MediaSessionActionType::Stop => MediaSessionAction::Stop,
+            MediaSessionActionType::SeekTo => MediaSessionAction::Seekto,
+        }
+    }
+}
+
 impl From<MediaSessionAction> for MediaSessionActionType {
     fn from(action: MediaSessionAction) -> MediaSessionActionType {
         match action {
@@ -209,11 +209,17 @@ pub mod types {
     include!(concat!(env!("OUT_DIR"), "/InterfaceTypes.rs"));
 }
 
+pub mod abortcontroller;
+pub mod abortsignal;
 pub mod abstractworker;
 pub mod abstractworkerglobalscope;
 pub mod activation;
 pub mod analysernode;
+pub mod animation;
+pub mod animationeffect;
 pub mod animationevent;
+pub mod animationplaybackevent;
+pub mod animationtimeline;
 pub mod attr;
 pub mod audiobuffer;
 pub mod audiobuffersourcenode;
@@ -249,6 +255,8 @@ pub mod channelmergernode;
 pub mod channelsplitternode;
 pub mod characterdata;
 pub mod client;
+pub mod clipboard;
+pub mod clipboarditem;
 pub mod closeevent;
 pub mod comment;
 pub mod compositionevent;
@@ -256,6 +264,7 @@ pub mod console;
 pub mod constantsourcenode;
 mod create;
 pub mod crypto;
+pub mod cryptokey;
 pub mod css;
 pub mod cssconditionrule;
 pub mod cssfontfacerule;
@@ -276,12 +285,17 @@ pub mod cssstylevalue;
 pub mod csssupportsrule;
 pub mod customelementregistry;
 pub mod customevent;
+pub mod datatransfer;
+pub mod datatransferitem;
+pub mod datatransferitemlist;
 pub mod dedicatedworkerglobalscope;
+pub mod deprecationreportbody;
 pub mod dissimilaroriginlocation;
 pub mod dissimilaroriginwindow;
 pub mod document;
 pub mod documentfragment;
 pub mod documentorshadowroot;
+pub mod documenttimeline;
 pub mod documenttype;
 pub mod domexception;
 pub mod domimplementation;
@@ -296,6 +310,7 @@ pub mod domrectreadonly;
 pub mod domstringlist;
 pub mod domstringmap;
 pub mod domtokenlist;
+pub mod dragevent;
 pub mod dynamicmoduleowner;
 pub mod element;
 pub mod errorevent;
@@ -304,12 +319,23 @@ pub mod eventsource;
 pub mod eventtarget;
 pub mod extendableevent;
 pub mod extendablemessageevent;
+pub mod extensionbrowser;
+pub mod extensionevent;
+pub mod extensionruntime;
+pub mod extensionstorage;
+pub mod extensionstoragearea;
+pub mod extensiontabs;
 pub mod fakexrdevice;
 pub mod fakexrinputcontroller;
+pub mod fetchevent;
 pub mod file;
 pub mod filelist;
 pub mod filereader;
 pub mod filereadersync;
+pub mod filesystemdirectoryhandle;
+pub mod filesystemfilehandle;
+pub mod filesystemhandle;
+pub mod filesystemsyncaccesshandle;
 pub mod focusevent;
 pub mod formdata;
 pub mod formdataevent;
@@ -432,11 +458,24 @@ pub mod htmltrackelement;
 pub mod htmlulistelement;
 pub mod htmlunknownelement;
 pub mod htmlvideoelement;
+pub mod idbcursor;
+pub mod idbcursorwithvalue;
+pub mod idbdatabase;
+pub mod idbfactory;
+pub mod idbindex;
+pub mod idbkeyrange;
+pub mod idbobjectstore;
+pub mod idbopendbrequest;
+pub mod idbrequest;
+pub mod idbtransaction;
+pub mod idbversionchangeevent;
 pub mod identityhub;
 pub mod imagebitmap;
 pub mod imagedata;
 pub mod inputevent;
+pub mod interventionreportbody;
 pub mod keyboardevent;
+pub mod keyframeeffect;
 pub mod location;
 pub mod mediadeviceinfo;
 pub mod mediadevices;
@@ -465,6 +504,8 @@ pub mod namednodemap;
 pub mod navigationpreloadmanager;
 pub mod navigator;
 pub mod navigatorinfo;
+pub mod navigatoruadata;
+pub mod networkinformation;
 pub mod node;
 pub mod nodeiterator;
 pub mod nodelist;
@@ -478,8 +519,11 @@ pub mod paintrenderingcontext2d;
 pub mod paintsize;
 pub mod paintworkletglobalscope;
 pub mod pannernode;
+pub mod path2d;
 pub mod performance;
 pub mod performanceentry;
+pub mod performanceeventtiming;
+pub mod performancelongtasktiming;
 pub mod performancemark;
 pub mod performancemeasure;
 pub mod performancenavigation;
@@ -488,10 +532,12 @@ pub mod performanceobserver;
 pub mod performanceobserverentrylist;
 pub mod performancepainttiming;
 pub mod performanceresourcetiming;
+pub mod performanceservertiming;
 pub mod permissions;
 pub mod permissionstatus;
 pub mod plugin;
 pub mod pluginarray;
+pub mod pointerevent;
 pub mod popstateevent;
 pub mod processinginstruction;
 pub mod progressevent;
@@ -502,7 +548,13 @@ pub mod radionodelist;
 pub mod range;
 pub mod raredata;
 pub mod readablestream;
+pub mod report;
+pub mod reportbody;
+pub mod reportingobserver;
 pub mod request;
+pub mod resizeobserver;
+pub mod resizeobserverentry;
+pub mod resizeobserversize;
 pub mod response;
 pub mod rtcdatachannel;
 pub mod rtcdatachannelevent;
@@ -523,16 +575,25 @@ pub mod serviceworkerglobalscope;
 pub mod serviceworkerregistration;
 pub mod servoparser;
 pub mod shadowroot;
+pub mod speechsynthesis;
+pub mod speechsynthesiserrorevent;
+pub mod speechsynthesisevent;
+pub mod speechsynthesisutterance;
+pub mod speechsynthesisvoice;
+pub mod staticrange;
 pub mod stereopannernode;
 pub mod storage;
 pub mod storageevent;
+pub mod storagemanager;
 pub mod stylepropertymapreadonly;
 pub mod stylesheet;
 pub mod stylesheetlist;
 pub mod submitevent;
+pub mod subtlecrypto;
 pub mod svgelement;
 pub mod svggraphicselement;
 pub mod svgsvgelement;
+pub mod taskattributiontiming;
 pub mod testbinding;
 pub mod testbindingiterable;
 pub mod testbindingmaplike;
@@ -557,6 +618,10 @@ pub mod touch;
 pub mod touchevent;
 pub mod touchlist;
 pub mod trackevent;
+pub mod transformstream;
+pub mod transformstreamdefaultcontroller;
+pub mod transformstreamunderlyingsink;
+pub mod transformstreamunderlyingsource;
 pub mod transitionevent;
 pub mod treewalker;
 pub mod uievent;
@@ -5,19 +5,24 @@
 use std::rc::Rc;
 
 use dom_struct::dom_struct;
+use ipc_channel::router::ROUTER;
 use js::jsval::UndefinedValue;
+use profile_traits::ipc;
+use script_traits::{self, NavigationPreloadAction, ScriptMsg};
 
 use crate::dom::bindings::codegen::Bindings::NavigationPreloadManagerBinding::{
     NavigationPreloadManagerMethods, NavigationPreloadState,
 };
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::refcounted::{Trusted, TrustedPromise};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::ByteString;
-use crate::dom::domexception::{DOMErrorName, DOMException};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::promise::Promise;
 use crate::dom::serviceworkerregistration::ServiceWorkerRegistration;
 use crate::realms::InRealm;
+use crate::task_source::{TaskSource, TaskSourceName};
 
 #[dom_struct]
 pub struct NavigationPreloadManager {
@@ -41,99 +46,99 @@ impl NavigationPreloadManager {
         let manager = NavigationPreloadManager::new_inherited(&*registration);
         reflect_dom_object(Box::new(manager), global)
     }
+
+    /// Ask the service worker manager, which holds the state of the registration, to run
+    /// `action`, and settle `promise` with its answer. The state only changes while the
+    /// registration has an active worker, so the promise is rejected otherwise.
+    fn run_action(
+        &self,
+        action: NavigationPreloadAction,
+        promise: &Rc<Promise>,
+        resolve_with_state: bool,
+    ) {
+        let global = self.global();
+        let task_source = global.dom_manipulation_task_source();
+        let canceller = global.task_canceller(TaskSourceName::DOMManipulation);
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let mut pending = Some((Trusted::new(self), TrustedPromise::new(promise.clone())));
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                // The router doesn't know that a single reply is ever sent.
+                let (manager, trusted) = match pending.take() {
+                    Some(pending) => pending,
+                    None => return error!("Navigation preload state replied to twice"),
+                };
+                let state: Option<script_traits::NavigationPreloadState> =
+                    message.to().unwrap_or(None);
+                let _ = task_source.queue_with_canceller(
+                    task!(settle_navigation_preload: move || {
+                        let manager = manager.root();
+                        let promise = trusted.root();
+                        let state = match state {
+                            Some(state) => state,
+                            None => return promise.reject_error(Error::InvalidState),
+                        };
+                        // Keep the copy of the state in this realm in sync.
+                        let registration = &manager.serviceworker_registration;
+                        registration.set_navigation_preload_enabled(state.enabled);
+                        registration.set_navigation_preload_header_value(
+                            ByteString::new(state.header_value.clone()),
+                        );
+                        if resolve_with_state {
+                            let mut dictionary = NavigationPreloadState::empty();
+                            dictionary.enabled = state.enabled;
+                            dictionary.headerValue = Some(ByteString::new(state.header_value));
+                            promise.resolve_native(&dictionary);
+                        } else {
+                            promise.resolve_native(&UndefinedValue());
+                        }
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        let scope = self.serviceworker_registration.scope().clone();
+        let msg = ScriptMsg::NavigationPreload(scope, action, sender);
+        if let Err(e) = global.script_to_constellation_chan().send(msg) {
+            warn!(
+                "Failed to send navigation preload request to the constellation ({:?})",
+                e
+            );
+        }
+    }
 }
 
 impl NavigationPreloadManagerMethods for NavigationPreloadManager {
     // https://w3c.github.io/ServiceWorker/#navigation-preload-manager-enable
     fn Enable(&self, comp: InRealm) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
-
-        // 2.
-        if self.serviceworker_registration.is_active() {
-            promise.reject_native(&DOMException::new(
-                &self.global(),
-                DOMErrorName::InvalidStateError,
-            ));
-        } else {
-            // 3.
-            self.serviceworker_registration
-                .set_navigation_preload_enabled(true);
-
-            // 4.
-            promise.resolve_native(&UndefinedValue());
-        }
-
+        self.run_action(NavigationPreloadAction::SetEnabled(true), &promise, false);
         promise
     }
 
     // https://w3c.github.io/ServiceWorker/#navigation-preload-manager-disable
     fn Disable(&self, comp: InRealm) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
-
-        // 2.
-        if self.serviceworker_registration.is_active() {
-            promise.reject_native(&DOMException::new(
-                &self.global(),
-                DOMErrorName::InvalidStateError,
-            ));
-        } else {
-            // 3.
-            self.serviceworker_registration
-                .set_navigation_preload_enabled(false);
-
-            // 4.
-            promise.resolve_native(&UndefinedValue());
-        }
-
+        self.run_action(NavigationPreloadAction::SetEnabled(false), &promise, false);
         promise
     }
 
     // https://w3c.github.io/ServiceWorker/#navigation-preload-manager-setheadervalue
     fn SetHeaderValue(&self, value: ByteString, comp: InRealm) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
-
-        // 2.
-        if self.serviceworker_registration.is_active() {
-            promise.reject_native(&DOMException::new(
-                &self.global(),
-                DOMErrorName::InvalidStateError,
-            ));
-        } else {
-            // 3.
-            self.serviceworker_registration
-                .set_navigation_preload_header_value(value);
-
-            // 4.
-            promise.resolve_native(&UndefinedValue());
-        }
-
+        self.run_action(
+            NavigationPreloadAction::SetHeaderValue(value.into()),
+            &promise,
+            false,
+        );
         promise
     }
 
     // https://w3c.github.io/ServiceWorker/#navigation-preload-manager-getstate
     fn GetState(&self, comp: InRealm) -> Rc<Promise> {
         let promise = Promise::new_in_current_realm(comp);
-        // 2.
-        let mut state = NavigationPreloadState::empty();
-
-        // 3.
-        if self.serviceworker_registration.is_active() {
-            if self
-                .serviceworker_registration
-                .get_navigation_preload_enabled()
-            {
-                state.enabled = true;
-            }
-        }
-
-        // 4.
-        state.headerValue = self
-            .serviceworker_registration
-            .get_navigation_preload_header_value();
-
-        // 5.
-        promise.resolve_native(&state);
-
+        self.run_action(NavigationPreloadAction::GetState, &promise, true);
         promise
     }
 }
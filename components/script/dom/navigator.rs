@@ -3,29 +3,48 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::convert::TryInto;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
 use dom_struct::dom_struct;
+use http::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+use http::Method;
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
 use js::jsval::JSVal;
 use lazy_static::lazy_static;
+use net_traits::request::{
+    is_cors_safelisted_request_header, CredentialsMode, RequestBuilder, RequestMode,
+    KEEPALIVE_QUOTA,
+};
+use net_traits::{CoreResourceMsg, FetchChannels, FetchResponseMsg};
 
+use crate::body::Extractable;
 use crate::dom::bindings::codegen::Bindings::NavigatorBinding::NavigatorMethods;
+use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
+use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::bluetooth::Bluetooth;
+use crate::dom::clipboard::Clipboard;
 use crate::dom::gamepadlist::GamepadList;
 use crate::dom::gpu::GPU;
 use crate::dom::mediadevices::MediaDevices;
 use crate::dom::mediasession::MediaSession;
 use crate::dom::mimetypearray::MimeTypeArray;
 use crate::dom::navigatorinfo;
+use crate::dom::navigatoruadata::NavigatorUAData;
+use crate::dom::networkinformation::NetworkInformation;
 use crate::dom::permissions::Permissions;
 use crate::dom::pluginarray::PluginArray;
 use crate::dom::serviceworkercontainer::ServiceWorkerContainer;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::window::Window;
 use crate::dom::xrsystem::XRSystem;
 use crate::script_runtime::JSContext;
+use crate::script_thread::ScriptThread;
 
 pub(super) fn hardware_concurrency() -> u64 {
     lazy_static! {
@@ -47,6 +66,14 @@ pub struct Navigator {
     permissions: MutNullableDom<Permissions>,
     mediasession: MutNullableDom<MediaSession>,
     gpu: MutNullableDom<GPU>,
+    connection: MutNullableDom<NetworkInformation>,
+    clipboard: MutNullableDom<Clipboard>,
+    user_agent_data: MutNullableDom<NavigatorUAData>,
+    storage: MutNullableDom<StorageManager>,
+    /// The bytes of the data of the beacons in flight, which their fetch gives back once it
+    /// is done, even after the window is gone.
+    #[ignore_malloc_size_of = "Arc"]
+    beacon_bytes_in_flight: Arc<AtomicUsize>,
 }
 
 impl Navigator {
@@ -63,6 +90,11 @@ impl Navigator {
             permissions: Default::default(),
             mediasession: Default::default(),
             gpu: Default::default(),
+            connection: Default::default(),
+            clipboard: Default::default(),
+            user_agent_data: Default::default(),
+            storage: Default::default(),
+            beacon_bytes_in_flight: Default::default(),
         }
     }
 
@@ -73,6 +105,10 @@ impl Navigator {
     pub fn xr(&self) -> Option<DomRoot<XRSystem>> {
         self.xr.get()
     }
+
+    pub fn connection(&self) -> Option<DomRoot<NetworkInformation>> {
+        self.connection.get()
+    }
 }
 
 impl NavigatorMethods for Navigator {
@@ -221,4 +257,114 @@ impl NavigatorMethods for Navigator {
     fn HardwareConcurrency(&self) -> u64 {
         hardware_concurrency()
     }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-navigator-online>
+    fn OnLine(&self) -> bool {
+        ScriptThread::network_status().online
+    }
+
+    /// <https://wicg.github.io/netinfo/#connection-attribute>
+    fn Connection(&self) -> DomRoot<NetworkInformation> {
+        self.connection
+            .or_init(|| NetworkInformation::new(self.global().as_window()))
+    }
+
+    /// <https://w3c.github.io/clipboard-apis/#dom-navigator-clipboard>
+    fn Clipboard(&self) -> DomRoot<Clipboard> {
+        self.clipboard.or_init(|| Clipboard::new(&self.global()))
+    }
+
+    /// <https://wicg.github.io/ua-client-hints/#dom-navigatorua-useragentdata>
+    fn UserAgentData(&self) -> DomRoot<NavigatorUAData> {
+        self.user_agent_data
+            .or_init(|| NavigatorUAData::new(&self.global()))
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-navigatorstorage-storage>
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
+
+    /// <https://w3c.github.io/beacon/#sec-sendBeacon-method>
+    fn SendBeacon(&self, url: USVString, data: Option<BodyInit>) -> Fallible<bool> {
+        let global = self.global();
+
+        // Step 1-2. Parse the URL against the API base URL.
+        let url = global
+            .api_base_url()
+            .join(&url.0)
+            .map_err(|_| Error::Type("Beacon URL is not a valid URL".to_owned()))?;
+
+        // Step 3.
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(Error::Type("Beacon URL is not an HTTP(S) URL".to_owned()));
+        }
+
+        // Step 4-5.
+        let mut headers = HeaderMap::new();
+        let mut mode = RequestMode::NoCors;
+
+        // Step 6.
+        let mut body = None;
+        let mut bytes = 0;
+        if let Some(data) = data {
+            // Step 6.1 Extract the data with the keepalive flag set, which rejects streams.
+            if let BodyInit::ReadableStream(_) = data {
+                return Err(Error::Type("Beacon data cannot be a stream".to_owned()));
+            }
+            let mut extracted = data.extract(&global)?;
+            bytes = extracted.total_bytes.unwrap_or(0);
+
+            // Step 6.2 The beacon is not queued if its data does not fit in the quota left.
+            if self.beacon_bytes_in_flight.load(Ordering::SeqCst) + bytes > KEEPALIVE_QUOTA {
+                return Ok(false);
+            }
+
+            // Step 6.3
+            if let Some(content_type) = extracted.content_type.take() {
+                if let Ok(value) = HeaderValue::from_str(&content_type) {
+                    if !is_cors_safelisted_request_header(&CONTENT_TYPE, &value) {
+                        mode = RequestMode::CorsMode;
+                    }
+                    headers.insert(CONTENT_TYPE, value);
+                }
+            }
+            body = Some(extracted.into_net_request_body().0);
+        }
+
+        // Step 7. Fetch the beacon in parallel. Its fetch is not cancelled when the window
+        // goes away, and the network reads its data before the window can.
+        let mut request = RequestBuilder::new(url, global.get_referrer())
+            .method(Method::POST)
+            .headers(headers)
+            .unsafe_request(true)
+            .body(body)
+            .keep_alive(true)
+            .mode(mode)
+            .credentials_mode(CredentialsMode::Include)
+            .origin(global.origin().immutable().clone())
+            .pipeline_id(Some(global.pipeline_id()));
+        request.csp_list = global.get_csp_list();
+
+        self.beacon_bytes_in_flight
+            .fetch_add(bytes, Ordering::SeqCst);
+        let beacon_bytes_in_flight = self.beacon_bytes_in_flight.clone();
+        let (action_sender, action_receiver) = ipc::channel().unwrap();
+        ROUTER.add_route(
+            action_receiver.to_opaque(),
+            Box::new(move |message| {
+                if let Ok(FetchResponseMsg::ProcessResponseEOF(_)) = message.to() {
+                    beacon_bytes_in_flight.fetch_sub(bytes, Ordering::SeqCst);
+                }
+            }),
+        );
+        global
+            .core_resource_thread()
+            .send(CoreResourceMsg::Fetch(
+                request,
+                FetchChannels::ResponseMsg(action_sender, None),
+            ))
+            .unwrap();
+        Ok(true)
+    }
 }
@@ -4,6 +4,8 @@
 
 use std::borrow::Cow;
 
+use servo_config::pref;
+
 use crate::dom::bindings::str::DOMString;
 
 #[allow(non_snake_case)]
@@ -42,26 +44,31 @@ pub fn AppCodeName() -> DOMString {
 }
 
 #[allow(non_snake_case)]
-#[cfg(target_os = "windows")]
 pub fn Platform() -> DOMString {
+    // The platform of the user agent reported while resisting fingerprinting.
+    if pref!(privacy.resist_fingerprinting.enabled) {
+        return DOMString::from("Win32");
+    }
+    native_platform()
+}
+
+#[cfg(target_os = "windows")]
+fn native_platform() -> DOMString {
     DOMString::from("Win32")
 }
 
-#[allow(non_snake_case)]
 #[cfg(any(target_os = "android", target_os = "linux"))]
-pub fn Platform() -> DOMString {
+fn native_platform() -> DOMString {
     DOMString::from("Linux")
 }
 
-#[allow(non_snake_case)]
 #[cfg(target_os = "macos")]
-pub fn Platform() -> DOMString {
+fn native_platform() -> DOMString {
     DOMString::from("Mac")
 }
 
-#[allow(non_snake_case)]
 #[cfg(target_os = "ios")]
-pub fn Platform() -> DOMString {
+fn native_platform() -> DOMString {
     DOMString::from("iOS")
 }
 
@@ -0,0 +1,127 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use embedder_traits::UserAgentBrand;
+use js::jsval::JSVal;
+use servo_config::pref;
+
+use crate::dom::bindings::codegen::Bindings::NavigatorUADataBinding::{
+    NavigatorUABrandVersion, NavigatorUADataMethods, UADataValues, UALowEntropyJSON,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::script_runtime::JSContext;
+
+/// The hints of `getHighEntropyValues` that tell more than the low-entropy values, and that
+/// are only given out when the user agent allows it.
+/// <https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-gethighentropyvalues>
+const HIGH_ENTROPY_HINTS: [&str; 6] = [
+    "architecture",
+    "bitness",
+    "fullVersionList",
+    "model",
+    "platformVersion",
+    "wow64",
+];
+
+/// <https://wicg.github.io/ua-client-hints/#navigatoruadata>
+#[dom_struct]
+pub struct NavigatorUAData {
+    reflector_: Reflector,
+}
+
+impl NavigatorUAData {
+    fn new_inherited() -> NavigatorUAData {
+        NavigatorUAData {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<NavigatorUAData> {
+        reflect_dom_object(Box::new(NavigatorUAData::new_inherited()), global)
+    }
+}
+
+fn brand_versions(brands: &[UserAgentBrand]) -> Vec<NavigatorUABrandVersion> {
+    brands
+        .iter()
+        .map(|brand| NavigatorUABrandVersion {
+            brand: Some(DOMString::from(brand.brand.clone())),
+            version: Some(DOMString::from(brand.version.clone())),
+        })
+        .collect()
+}
+
+impl NavigatorUADataMethods for NavigatorUAData {
+    /// <https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-brands>
+    fn Brands(&self, cx: JSContext) -> JSVal {
+        let metadata = self.global().user_agent_metadata();
+        to_frozen_array(&brand_versions(&metadata.brands), cx)
+    }
+
+    /// <https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-mobile>
+    fn Mobile(&self) -> bool {
+        self.global().user_agent_metadata().mobile
+    }
+
+    /// <https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-platform>
+    fn Platform(&self) -> DOMString {
+        DOMString::from(self.global().user_agent_metadata().platform)
+    }
+
+    /// <https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-gethighentropyvalues>
+    fn GetHighEntropyValues(&self, hints: Vec<DOMString>) -> Rc<Promise> {
+        let global = self.global();
+        let promise = Promise::new(&global);
+
+        // Step 2. If the user agent decides one or more values in hints should not be
+        // returned, then reject promise with a "NotAllowedError" DOMException.
+        let asks_high_entropy_values = hints
+            .iter()
+            .any(|hint| HIGH_ENTROPY_HINTS.contains(&&**hint));
+        if asks_high_entropy_values && !pref!(network.client_hints.high_entropy_values.enabled) {
+            promise.reject_error(Error::NotAllowed);
+            return promise;
+        }
+
+        // Step 3. Otherwise, resolve promise with a UADataValues dictionary holding the
+        // low-entropy values and the requested ones.
+        let metadata = global.user_agent_metadata();
+        let requested = |name: &str| hints.iter().any(|hint| &**hint == name);
+        let values = UADataValues {
+            architecture: requested("architecture")
+                .then(|| DOMString::from(metadata.architecture.clone())),
+            bitness: requested("bitness").then(|| DOMString::from(metadata.bitness.clone())),
+            brands: Some(brand_versions(&metadata.brands)),
+            fullVersionList: requested("fullVersionList")
+                .then(|| brand_versions(&metadata.full_version_list)),
+            model: requested("model").then(|| DOMString::from(metadata.model.clone())),
+            mobile: Some(metadata.mobile),
+            platform: Some(DOMString::from(metadata.platform.clone())),
+            platformVersion: requested("platformVersion")
+                .then(|| DOMString::from(metadata.platform_version.clone())),
+            wow64: requested("wow64").then_some(false),
+        };
+        promise.resolve_native(&values);
+        promise
+    }
+
+    /// <https://wicg.github.io/ua-client-hints/#dom-navigatoruadata-tojson>
+    fn ToJSON(&self) -> UALowEntropyJSON {
+        let metadata = self.global().user_agent_metadata();
+        UALowEntropyJSON {
+            brands: Some(brand_versions(&metadata.brands)),
+            mobile: Some(metadata.mobile),
+            platform: Some(DOMString::from(metadata.platform)),
+        }
+    }
+}
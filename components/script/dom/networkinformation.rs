@@ -0,0 +1,53 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::ConnectionType as DeviceConnectionType;
+
+use crate::dom::bindings::codegen::Bindings::NetworkInformationBinding::{
+    ConnectionType, NetworkInformationMethods,
+};
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::window::Window;
+use crate::script_thread::ScriptThread;
+
+/// <https://wicg.github.io/netinfo/#networkinformation-interface>
+#[dom_struct]
+pub struct NetworkInformation {
+    eventtarget: EventTarget,
+}
+
+impl NetworkInformation {
+    fn new_inherited() -> NetworkInformation {
+        NetworkInformation {
+            eventtarget: EventTarget::new_inherited(),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<NetworkInformation> {
+        reflect_dom_object(Box::new(NetworkInformation::new_inherited()), window)
+    }
+}
+
+impl NetworkInformationMethods for NetworkInformation {
+    /// <https://wicg.github.io/netinfo/#type-attribute>
+    fn Type(&self) -> ConnectionType {
+        match ScriptThread::network_status().connection_type {
+            DeviceConnectionType::Bluetooth => ConnectionType::Bluetooth,
+            DeviceConnectionType::Cellular => ConnectionType::Cellular,
+            DeviceConnectionType::Ethernet => ConnectionType::Ethernet,
+            DeviceConnectionType::Mixed => ConnectionType::Mixed,
+            DeviceConnectionType::None => ConnectionType::None,
+            DeviceConnectionType::Other => ConnectionType::Other,
+            DeviceConnectionType::Unknown => ConnectionType::Unknown,
+            DeviceConnectionType::Wifi => ConnectionType::Wifi,
+            DeviceConnectionType::Wimax => ConnectionType::Wimax,
+        }
+    }
+
+    /// <https://wicg.github.io/netinfo/#onchange-attribute>
+    event_handler!(change, GetOnchange, SetOnchange);
+}
@@ -181,7 +181,12 @@ bitflags! {
         /// to be reachable with using sequential focus navigation."]
         const SEQUENTIALLY_FOCUSABLE = 1 << 3;
 
-        // There are two free bits here.
+        /// Whether this element is relevant to the user, which decides whether layout
+        /// skips its contents when it has `content-visibility: auto`.
+        /// <https://drafts.csswg.org/css-contain-2/#relevant-to-the-user>
+        const IS_RELEVANT_TO_THE_USER = 1 << 4;
+
+        // There is one free bit here.
 
         /// Specifies whether the parser has set an associated form owner for
         /// this element. Only applicable for form-associatable elements.
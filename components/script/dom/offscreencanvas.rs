@@ -3,27 +3,34 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::cell::Cell;
+use std::collections::HashMap;
 
-use canvas_traits::canvas::{CanvasMsg, FromScriptMsg};
+use canvas_traits::canvas::{CanvasMsg, FromScriptMsg, PlaceholderCanvasFrame};
 use dom_struct::dom_struct;
 use euclid::default::Size2D;
-use ipc_channel::ipc::IpcSharedMemory;
+use ipc_channel::ipc::{IpcSender, IpcSharedMemory};
+use js::jsapi::MutableHandleObject;
 use js::rust::{HandleObject, HandleValue};
 use profile_traits::ipc;
+use script_traits::transferable::OffscreenCanvasImpl;
 
 use crate::dom::bindings::cell::{ref_filter_map, DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::OffscreenCanvasBinding::{
     OffscreenCanvasMethods, OffscreenRenderingContext,
 };
-use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone::StructuredDataHolder;
+use crate::dom::bindings::transferable::Transferable;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::offscreencanvasrenderingcontext2d::OffscreenCanvasRenderingContext2D;
 use crate::script_runtime::JSContext;
+use crate::task_source::TaskSource;
 
 #[crown::unrooted_must_root_lint::must_root]
 #[derive(Clone, JSTraceable, MallocSizeOf)]
@@ -40,6 +47,15 @@ pub struct OffscreenCanvas {
     height: Cell<u64>,
     context: DomRefCell<Option<OffscreenCanvasContext>>,
     placeholder: Option<Dom<HTMLCanvasElement>>,
+    /// Where the frames of the canvas are presented, if it took control of a canvas
+    /// element, which may live in another global than this one.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    #[no_trace]
+    placeholder_chan: Option<IpcSender<PlaceholderCanvasFrame>>,
+    /// Whether a task to present the current frame to the placeholder is queued.
+    frame_pending: Cell<bool>,
+    /// <https://html.spec.whatwg.org/multipage/#offscreencanvas-detached>
+    detached: Cell<bool>,
 }
 
 impl OffscreenCanvas {
@@ -47,6 +63,7 @@ impl OffscreenCanvas {
         width: u64,
         height: u64,
         placeholder: Option<&HTMLCanvasElement>,
+        placeholder_chan: Option<IpcSender<PlaceholderCanvasFrame>>,
     ) -> OffscreenCanvas {
         OffscreenCanvas {
             eventtarget: EventTarget::new_inherited(),
@@ -54,18 +71,27 @@ impl OffscreenCanvas {
             height: Cell::new(height),
             context: DomRefCell::new(None),
             placeholder: placeholder.map(Dom::from_ref),
+            placeholder_chan,
+            frame_pending: Cell::new(false),
+            detached: Cell::new(false),
         }
     }
 
-    fn new(
+    pub fn new(
         global: &GlobalScope,
         proto: Option<HandleObject>,
         width: u64,
         height: u64,
         placeholder: Option<&HTMLCanvasElement>,
+        placeholder_chan: Option<IpcSender<PlaceholderCanvasFrame>>,
     ) -> DomRoot<OffscreenCanvas> {
         reflect_dom_object_with_proto(
-            Box::new(OffscreenCanvas::new_inherited(width, height, placeholder)),
+            Box::new(OffscreenCanvas::new_inherited(
+                width,
+                height,
+                placeholder,
+                placeholder_chan,
+            )),
             global,
             proto,
         )
@@ -78,7 +104,7 @@ impl OffscreenCanvas {
         width: u64,
         height: u64,
     ) -> Fallible<DomRoot<OffscreenCanvas>> {
-        let offscreencanvas = OffscreenCanvas::new(global, proto, width, height, None);
+        let offscreencanvas = OffscreenCanvas::new(global, proto, width, height, None, None);
         Ok(offscreencanvas)
     }
 
@@ -139,12 +165,114 @@ impl OffscreenCanvas {
         *self.context.borrow_mut() = Some(OffscreenCanvasContext::OffscreenContext2d(
             Dom::from_ref(&*context),
         ));
+        self.mark_as_dirty();
         Some(context)
     }
 
     pub fn is_valid(&self) -> bool {
         self.Width() != 0 && self.Height() != 0
     }
+
+    /// Present the current frame of the canvas to its placeholder canvas element, if any.
+    /// <https://html.spec.whatwg.org/multipage/#offscreencanvas-placeholder>
+    pub fn commit_frame(&self) {
+        self.frame_pending.set(false);
+        let placeholder_chan = match self.placeholder_chan {
+            Some(ref placeholder_chan) if !self.detached.get() => placeholder_chan,
+            _ => return,
+        };
+        let frame = match self.context.borrow().as_ref() {
+            Some(OffscreenCanvasContext::OffscreenContext2d(ref context)) => {
+                PlaceholderCanvasFrame {
+                    canvas_id: context.get_canvas_id(),
+                    ipc_renderer: context.get_ipc_renderer(),
+                    size: self.get_size(),
+                }
+            },
+            None => return,
+        };
+        if placeholder_chan.send(frame).is_err() {
+            debug!("The placeholder of an offscreen canvas went away.");
+        }
+    }
+
+    /// Queue a task presenting the frame drawn by the current task to the placeholder
+    /// canvas element, so that it follows the drawing without `commit()` being called.
+    pub fn mark_as_dirty(&self) {
+        if self.placeholder_chan.is_none() || self.frame_pending.replace(true) {
+            return;
+        }
+        let this = Trusted::new(self);
+        let global = self.global();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(propagate_offscreen_canvas_frame: move || {
+                this.root().commit_frame();
+            }),
+            &global,
+        );
+    }
+}
+
+impl Transferable for OffscreenCanvas {
+    /// <https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-steps>
+    fn transfer(&self, sc_holder: &mut StructuredDataHolder) -> Result<u64, ()> {
+        // Step 1. If value's context mode is not equal to none, then throw a "DataCloneError".
+        if self.detached.get() || self.context.borrow().is_some() {
+            return Err(());
+        }
+
+        let offscreen_canvases = match sc_holder {
+            StructuredDataHolder::Write {
+                offscreen_canvases, ..
+            } => offscreen_canvases,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+
+        // Step 2-3. Unset value's bitmap, keeping its dimensions.
+        self.detached.set(true);
+        let transferred_canvas = OffscreenCanvasImpl {
+            width: self.width.replace(0),
+            height: self.height.replace(0),
+            placeholder: self.placeholder_chan.clone(),
+        };
+
+        // Step 4. Store them and the placeholder canvas element at the next key.
+        let offscreen_canvases = offscreen_canvases.get_or_insert_with(HashMap::new);
+        let key = offscreen_canvases.len() as u64;
+        offscreen_canvases.insert(key, transferred_canvas);
+        Ok(key)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#the-offscreencanvas-interface:transfer-receiving-steps>
+    fn transfer_receive(
+        owner: &GlobalScope,
+        sc_holder: &mut StructuredDataHolder,
+        extra_data: u64,
+        return_object: MutableHandleObject,
+    ) -> Result<(), ()> {
+        let offscreen_canvas_impls = match sc_holder {
+            StructuredDataHolder::Read {
+                offscreen_canvas_impls,
+                ..
+            } => offscreen_canvas_impls,
+            _ => panic!("Unexpected variant of StructuredDataHolder"),
+        };
+        let transferred_canvas = offscreen_canvas_impls
+            .as_mut()
+            .and_then(|impls| impls.remove(&extra_data))
+            .ok_or(())?;
+
+        let canvas = OffscreenCanvas::new(
+            owner,
+            None,
+            transferred_canvas.width,
+            transferred_canvas.height,
+            None,
+            transferred_canvas.placeholder,
+        );
+        return_object.set(canvas.reflector().rootable().get());
+        Ok(())
+    }
 }
 
 impl OffscreenCanvasMethods for OffscreenCanvas {
@@ -154,8 +282,11 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
         _cx: JSContext,
         id: DOMString,
         _options: HandleValue,
-    ) -> Option<OffscreenRenderingContext> {
-        match &*id {
+    ) -> Fallible<Option<OffscreenRenderingContext>> {
+        if self.detached.get() {
+            return Err(Error::InvalidState);
+        }
+        Ok(match &*id {
             "2d" => self
                 .get_or_init_2d_context()
                 .map(OffscreenRenderingContext::OffscreenCanvasRenderingContext2D),
@@ -166,7 +297,12 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
                 .get_or_init_webgl2_context(cx, options)
                 .map(OffscreenRenderingContext::WebGL2RenderingContext),*/
             _ => None,
-        }
+        })
+    }
+
+    // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-commit
+    fn Commit(&self) {
+        self.commit_frame();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-width
@@ -185,6 +321,7 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
                 },
             }
         }
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-offscreencanvas-height
@@ -203,5 +340,6 @@ impl OffscreenCanvasMethods for OffscreenCanvas {
                 },
             }
         }
+        self.mark_as_dirty();
     }
 }
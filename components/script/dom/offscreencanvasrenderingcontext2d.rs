@@ -13,7 +13,10 @@ use crate::dom::bindings::codegen::Bindings::CanvasRenderingContext2DBinding::{
     CanvasTextAlign, CanvasTextBaseline,
 };
 use crate::dom::bindings::codegen::Bindings::OffscreenCanvasRenderingContext2DBinding::OffscreenCanvasRenderingContext2DMethods;
-use crate::dom::bindings::codegen::UnionTypes::StringOrCanvasGradientOrCanvasPattern;
+use crate::dom::bindings::codegen::UnionTypes::{
+    StringOrCanvasGradientOrCanvasPattern,
+    UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+};
 use crate::dom::bindings::error::{ErrorResult, Fallible};
 use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
@@ -26,6 +29,7 @@ use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlcanvaselement::HTMLCanvasElement;
 use crate::dom::imagedata::ImageData;
 use crate::dom::offscreencanvas::OffscreenCanvas;
+use crate::dom::path2d::Path2D;
 use crate::dom::textmetrics::TextMetrics;
 
 #[dom_struct]
@@ -80,6 +84,10 @@ impl OffscreenCanvasRenderingContext2D {
     pub fn get_ipc_renderer(&self) -> IpcSender<CanvasMsg> {
         self.canvas_state.get_ipc_renderer().clone()
     }
+
+    fn mark_as_dirty(&self) {
+        self.canvas.mark_as_dirty();
+    }
 }
 
 impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContext2D {
@@ -91,16 +99,19 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-fillrect
     fn FillRect(&self, x: f64, y: f64, width: f64, height: f64) {
         self.canvas_state.fill_rect(x, y, width, height);
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-clearrect
     fn ClearRect(&self, x: f64, y: f64, width: f64, height: f64) {
         self.canvas_state.clear_rect(x, y, width, height);
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-strokerect
     fn StrokeRect(&self, x: f64, y: f64, width: f64, height: f64) {
         self.canvas_state.stroke_rect(x, y, width, height);
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-shadowoffsetx
@@ -192,6 +203,17 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
             .create_radial_gradient(&self.global(), x0, y0, r0, x1, y1, r1)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-createconicgradient>
+    fn CreateConicGradient(
+        &self,
+        start_angle: Finite<f64>,
+        x: Finite<f64>,
+        y: Finite<f64>,
+    ) -> DomRoot<CanvasGradient> {
+        self.canvas_state
+            .create_conic_gradient(&self.global(), start_angle, x, y)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-createpattern
     fn CreatePattern(
         &self,
@@ -256,7 +278,8 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
             x,
             y,
             max_width,
-        )
+        );
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#textmetrics
@@ -365,7 +388,8 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-putimagedata
     fn PutImageData(&self, imagedata: &ImageData, dx: i32, dy: i32) {
         self.canvas_state
-            .put_image_data(self.canvas.get_size(), imagedata, dx, dy)
+            .put_image_data(self.canvas.get_size(), imagedata, dx, dy);
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-putimagedata
@@ -389,13 +413,16 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
             dirty_y,
             dirty_width,
             dirty_height,
-        )
+        );
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
     fn DrawImage(&self, image: CanvasImageSource, dx: f64, dy: f64) -> ErrorResult {
         self.canvas_state
-            .draw_image(self.htmlcanvas.as_ref().map(|c| &**c), image, dx, dy)
+            .draw_image(self.htmlcanvas.as_ref().map(|c| &**c), image, dx, dy)?;
+        self.mark_as_dirty();
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
@@ -414,7 +441,9 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
             dy,
             dw,
             dh,
-        )
+        )?;
+        self.mark_as_dirty();
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-drawimage
@@ -441,7 +470,9 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
             dy,
             dw,
             dh,
-        )
+        )?;
+        self.mark_as_dirty();
+        Ok(())
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-beginpath
@@ -451,12 +482,26 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-fill
     fn Fill(&self, fill_rule: CanvasFillRule) {
-        self.canvas_state.fill(fill_rule)
+        self.canvas_state.fill(fill_rule);
+        self.mark_as_dirty();
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-fill>
+    fn Fill_(&self, path: &Path2D, fill_rule: CanvasFillRule) {
+        self.canvas_state.fill_path(path, fill_rule);
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-stroke
     fn Stroke(&self) {
-        self.canvas_state.stroke()
+        self.canvas_state.stroke();
+        self.mark_as_dirty();
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-stroke>
+    fn Stroke_(&self, path: &Path2D) {
+        self.canvas_state.stroke_path(path);
+        self.mark_as_dirty();
     }
 
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-clip
@@ -464,12 +509,23 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
         self.canvas_state.clip(fill_rule)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-clip>
+    fn Clip_(&self, path: &Path2D, fill_rule: CanvasFillRule) {
+        self.canvas_state.clip_path(path, fill_rule)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath
     fn IsPointInPath(&self, x: f64, y: f64, fill_rule: CanvasFillRule) -> bool {
         self.canvas_state
             .is_point_in_path(&self.global(), x, y, fill_rule)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-ispointinpath>
+    fn IsPointInPath_(&self, path: &Path2D, x: f64, y: f64, fill_rule: CanvasFillRule) -> bool {
+        self.canvas_state
+            .is_point_in_path_2d(&self.global(), path, x, y, fill_rule)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-scale
     fn Scale(&self, x: f64, y: f64) {
         self.canvas_state.scale(x, y)
@@ -525,6 +581,18 @@ impl OffscreenCanvasRenderingContext2DMethods for OffscreenCanvasRenderingContex
         self.canvas_state.rect(x, y, width, height)
     }
 
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    fn RoundRect(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+    ) -> ErrorResult {
+        self.canvas_state.round_rect(x, y, width, height, radii)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto
     fn QuadraticCurveTo(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
         self.canvas_state.quadratic_curve_to(cpx, cpy, x, y)
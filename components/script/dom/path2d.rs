@@ -0,0 +1,243 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use canvas_traits::canvas::PathSegment;
+use dom_struct::dom_struct;
+use euclid::default::{Point2D, Rect, Size2D};
+use js::rust::HandleObject;
+
+use crate::canvas_state::normalize_corner_radii;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DOMMatrixBinding::DOMMatrix2DInit;
+use crate::dom::bindings::codegen::Bindings::Path2DBinding::Path2DMethods;
+use crate::dom::bindings::codegen::UnionTypes::UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence;
+use crate::dom::bindings::error::{Error, ErrorResult};
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::dommatrixreadonly::dommatrix2dinit_to_matrix;
+use crate::dom::globalscope::GlobalScope;
+
+/// <https://html.spec.whatwg.org/multipage/#path2d-objects>
+#[dom_struct]
+pub struct Path2D {
+    reflector_: Reflector,
+    /// The segments of the path, which the canvas paint thread flattens when it is drawn.
+    #[ignore_malloc_size_of = "defined in canvas_traits"]
+    #[no_trace]
+    segments: DomRefCell<Vec<PathSegment>>,
+}
+
+impl Path2D {
+    fn new_inherited(segments: Vec<PathSegment>) -> Path2D {
+        Path2D {
+            reflector_: Reflector::new(),
+            segments: DomRefCell::new(segments),
+        }
+    }
+
+    fn new(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        segments: Vec<PathSegment>,
+    ) -> DomRoot<Path2D> {
+        reflect_dom_object_with_proto(Box::new(Path2D::new_inherited(segments)), global, proto)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-path2d>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        path: Option<&Path2D>,
+    ) -> DomRoot<Path2D> {
+        let segments = path.map_or_else(Vec::new, |path| path.segments());
+        Path2D::new(global, proto, segments)
+    }
+
+    pub fn segments(&self) -> Vec<PathSegment> {
+        self.segments.borrow().clone()
+    }
+
+    fn push(&self, segment: PathSegment) {
+        self.segments.borrow_mut().push(segment);
+    }
+}
+
+impl Path2DMethods for Path2D {
+    /// <https://html.spec.whatwg.org/multipage/#dom-path2d-addpath>
+    fn AddPath(&self, path: &Path2D, transform: &DOMMatrix2DInit) -> ErrorResult {
+        // Step 1.
+        let matrix = dommatrix2dinit_to_matrix(transform)?;
+        // Step 2.
+        if !matrix.to_array().iter().all(|entry| entry.is_finite()) {
+            return Ok(());
+        }
+        // Steps 3-7, the copy of the segments being transformed as the path is flattened.
+        let segments = path.segments();
+        self.push(PathSegment::AddPath(segments, matrix.cast()));
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-closepath>
+    fn ClosePath(&self) {
+        self.push(PathSegment::ClosePath);
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-moveto>
+    fn MoveTo(&self, x: f64, y: f64) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+        self.push(PathSegment::MoveTo(Point2D::new(x as f32, y as f32)));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-lineto>
+    fn LineTo(&self, x: f64, y: f64) {
+        if !(x.is_finite() && y.is_finite()) {
+            return;
+        }
+        self.push(PathSegment::LineTo(Point2D::new(x as f32, y as f32)));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-quadraticcurveto>
+    fn QuadraticCurveTo(&self, cpx: f64, cpy: f64, x: f64, y: f64) {
+        if !([cpx, cpy, x, y].iter().all(|val| val.is_finite())) {
+            return;
+        }
+        self.push(PathSegment::QuadraticCurveTo(
+            Point2D::new(cpx as f32, cpy as f32),
+            Point2D::new(x as f32, y as f32),
+        ));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-beziercurveto>
+    fn BezierCurveTo(&self, cp1x: f64, cp1y: f64, cp2x: f64, cp2y: f64, x: f64, y: f64) {
+        if !([cp1x, cp1y, cp2x, cp2y, x, y]
+            .iter()
+            .all(|val| val.is_finite()))
+        {
+            return;
+        }
+        self.push(PathSegment::BezierCurveTo(
+            Point2D::new(cp1x as f32, cp1y as f32),
+            Point2D::new(cp2x as f32, cp2y as f32),
+            Point2D::new(x as f32, y as f32),
+        ));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-arcto>
+    fn ArcTo(&self, x1: f64, y1: f64, x2: f64, y2: f64, radius: f64) -> ErrorResult {
+        if !([x1, y1, x2, y2, radius].iter().all(|val| val.is_finite())) {
+            return Ok(());
+        }
+        if radius < 0.0 {
+            return Err(Error::IndexSize);
+        }
+        self.push(PathSegment::ArcTo(
+            Point2D::new(x1 as f32, y1 as f32),
+            Point2D::new(x2 as f32, y2 as f32),
+            radius as f32,
+        ));
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-rect>
+    fn Rect(&self, x: f64, y: f64, width: f64, height: f64) {
+        if !([x, y, width, height].iter().all(|val| val.is_finite())) {
+            return;
+        }
+        self.push(PathSegment::Rect(Rect::new(
+            Point2D::new(x as f32, y as f32),
+            Size2D::new(width as f32, height as f32),
+        )));
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+    fn RoundRect(
+        &self,
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        radii: UnrestrictedDoubleOrDOMPointInitOrUnrestrictedDoubleOrDOMPointInitSequence,
+    ) -> ErrorResult {
+        if !([x, y, width, height].iter().all(|val| val.is_finite())) {
+            return Ok(());
+        }
+        let radii = match normalize_corner_radii(width, height, radii)? {
+            Some(radii) => radii,
+            None => return Ok(()),
+        };
+        self.push(PathSegment::RoundRect(
+            Rect::new(
+                Point2D::new(x as f32, y as f32),
+                Size2D::new(width as f32, height as f32),
+            ),
+            radii,
+        ));
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-arc>
+    fn Arc(
+        &self,
+        x: f64,
+        y: f64,
+        radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+        anticlockwise: bool,
+    ) -> ErrorResult {
+        if !([x, y, radius, start_angle, end_angle]
+            .iter()
+            .all(|val| val.is_finite()))
+        {
+            return Ok(());
+        }
+        if radius < 0.0 {
+            return Err(Error::IndexSize);
+        }
+        self.push(PathSegment::Arc(
+            Point2D::new(x as f32, y as f32),
+            radius as f32,
+            start_angle as f32,
+            end_angle as f32,
+            anticlockwise,
+        ));
+        Ok(())
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-context-2d-ellipse>
+    fn Ellipse(
+        &self,
+        x: f64,
+        y: f64,
+        radius_x: f64,
+        radius_y: f64,
+        rotation: f64,
+        start_angle: f64,
+        end_angle: f64,
+        anticlockwise: bool,
+    ) -> ErrorResult {
+        if !([x, y, radius_x, radius_y, rotation, start_angle, end_angle]
+            .iter()
+            .all(|val| val.is_finite()))
+        {
+            return Ok(());
+        }
+        if radius_x < 0.0 || radius_y < 0.0 {
+            return Err(Error::IndexSize);
+        }
+        self.push(PathSegment::Ellipse(
+            Point2D::new(x as f32, y as f32),
+            radius_x as f32,
+            radius_y as f32,
+            rotation as f32,
+            start_angle as f32,
+            end_angle as f32,
+            anticlockwise,
+        ));
+        Ok(())
+    }
+}
@@ -4,32 +4,48 @@
 
 use std::cell::Cell;
 use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 
 use dom_struct::dom_struct;
+use js::rust::HandleValue;
 use metrics::ToMs;
 
+use crate::clock;
 use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::KeyboardEventBinding::KeyboardEventMethods;
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::{
-    DOMHighResTimeStamp, PerformanceEntryList as DOMPerformanceEntryList, PerformanceMethods,
+    DOMHighResTimeStamp, PerformanceEntryList as DOMPerformanceEntryList, PerformanceMarkOptions,
+    PerformanceMethods,
+};
+use crate::dom::bindings::codegen::Bindings::PerformanceNavigationTimingBinding::PerformanceNavigationTimingMethods;
+use crate::dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding::PerformanceResourceTimingMethods;
+use crate::dom::bindings::codegen::UnionTypes::{
+    StringOrDouble, StringOrPerformanceMeasureOptions,
 };
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::inheritance::Castable;
-use crate::dom::bindings::num::Finite;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::keyboardevent::KeyboardEvent;
+use crate::dom::node::Node;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performanceeventtiming::{EventTimingData, PerformanceEventTiming};
 use crate::dom::performancemark::PerformanceMark;
 use crate::dom::performancemeasure::PerformanceMeasure;
 use crate::dom::performancenavigation::PerformanceNavigation;
 use crate::dom::performancenavigationtiming::PerformanceNavigationTiming;
 use crate::dom::performanceobserver::PerformanceObserver as DOMPerformanceObserver;
+use crate::dom::performanceresourcetiming::PerformanceResourceTiming;
 use crate::dom::window::Window;
 
-const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
+/// The names of the attributes of `PerformanceTiming`, which marks cannot take in windows.
+pub const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "navigationStart",
     "unloadEventStart",
     "unloadEventEnd",
@@ -53,6 +69,74 @@ const INVALID_ENTRY_NAMES: &'static [&'static str] = &[
     "loadEventEnd",
 ];
 
+/// The types of the events whose dispatch is measured.
+/// <https://w3c.github.io/event-timing/#sec-events-exposed>
+const EVENT_TIMING_TYPES: &'static [&'static str] = &[
+    "auxclick",
+    "click",
+    "contextmenu",
+    "dblclick",
+    "mousedown",
+    "mouseenter",
+    "mouseleave",
+    "mouseout",
+    "mouseover",
+    "mouseup",
+    "pointerover",
+    "pointerenter",
+    "pointerdown",
+    "pointerup",
+    "pointercancel",
+    "pointerout",
+    "pointerleave",
+    "gotpointercapture",
+    "lostpointercapture",
+    "touchstart",
+    "touchend",
+    "touchcancel",
+    "keydown",
+    "keypress",
+    "keyup",
+    "beforeinput",
+    "input",
+    "compositionstart",
+    "compositionupdate",
+    "compositionend",
+    "dragstart",
+    "dragend",
+    "dragenter",
+    "dragleave",
+    "dragover",
+    "drop",
+];
+
+/// The types of the events that can be the first input of the user.
+/// <https://w3c.github.io/event-timing/#sec-fini-event-timing>
+const FIRST_INPUT_TYPES: &'static [&'static str] =
+    &["keydown", "mousedown", "pointerdown", "click"];
+
+/// The duration, in milliseconds, of the shortest events reported to observers that do not
+/// ask for another threshold, and kept in the buffer.
+const DEFAULT_DURATION_THRESHOLD: f64 = 104.;
+
+/// The duration, in milliseconds, of the shortest events reported at all.
+const MIN_DURATION_THRESHOLD: f64 = 16.;
+
+/// The entry types whose entries are only exposed to observers, and not by `getEntries()`.
+/// <https://w3c.github.io/timing-entrytypes-registry/#registry>
+const OBSERVER_ONLY_ENTRY_TYPES: &'static [&'static str] = &["event", "longtask"];
+
+/// The most entries of `entry_type` kept in the buffer, if they are limited.
+/// <https://w3c.github.io/timing-entrytypes-registry/#registry>
+fn max_buffer_size(entry_type: &str) -> Option<usize> {
+    match entry_type {
+        "event" => Some(150),
+        "first-input" => Some(1),
+        "longtask" => Some(200),
+        _ => None,
+    }
+}
+
 /// Implementation of a list of PerformanceEntry items shared by the
 /// Performance and PerformanceObserverEntryList interfaces implementations.
 #[derive(JSTraceable, MallocSizeOf)]
@@ -105,18 +189,14 @@ impl PerformanceEntryList {
 
     fn get_last_entry_start_time_with_name_and_type(
         &self,
-        name: DOMString,
-        entry_type: DOMString,
-    ) -> f64 {
-        match self
-            .entries
+        name: &str,
+        entry_type: &str,
+    ) -> Option<f64> {
+        self.entries
             .iter()
             .rev()
-            .find(|e| *e.entry_type() == *entry_type && *e.name() == *name)
-        {
-            Some(entry) => entry.start_time(),
-            None => 0.,
-        }
+            .find(|e| &**e.entry_type() == entry_type && &**e.name() == name)
+            .map(|entry| entry.start_time())
     }
 }
 
@@ -133,6 +213,16 @@ impl IntoIterator for PerformanceEntryList {
 struct PerformanceObserver {
     observer: DomRoot<DOMPerformanceObserver>,
     entry_types: Vec<DOMString>,
+    /// The duration of the shortest events the observer is notified of.
+    /// <https://w3c.github.io/event-timing/#sec-modifications-perf-timeline>
+    duration_threshold: f64,
+}
+
+impl PerformanceObserver {
+    fn accepts(&self, entry: &PerformanceEntry) -> bool {
+        self.entry_types.contains(entry.entry_type()) &&
+            (*entry.entry_type() != "event" || entry.duration() >= self.duration_threshold)
+    }
 }
 
 #[dom_struct]
@@ -149,6 +239,16 @@ pub struct Performance {
     resource_timing_buffer_current_size: Cell<usize>,
     resource_timing_buffer_pending_full_event: Cell<bool>,
     resource_timing_secondary_entries: DomRefCell<VecDeque<DomRoot<PerformanceEntry>>>,
+    /// <https://w3c.github.io/event-timing/#has-dispatched-input-event>
+    has_dispatched_input_event: Cell<bool>,
+    /// The identifier given to the last interaction of the user.
+    /// <https://w3c.github.io/event-timing/#user-interaction-value>
+    user_interaction_value: Cell<u64>,
+    /// The interactions of the keys being pressed, by key code.
+    /// <https://w3c.github.io/event-timing/#pending-key-downs>
+    pending_key_downs: DomRefCell<HashMap<u32, u64>>,
+    /// The interaction of the pointer being pressed, which its click is part of.
+    pending_pointer_interaction: Cell<Option<u64>>,
 }
 
 impl Performance {
@@ -163,6 +263,12 @@ impl Performance {
             resource_timing_buffer_current_size: Cell::new(0),
             resource_timing_buffer_pending_full_event: Cell::new(false),
             resource_timing_secondary_entries: DomRefCell::new(VecDeque::new()),
+            has_dispatched_input_event: Cell::new(false),
+            // The identifiers start at a random value, so that they do not tell how many
+            // interactions there were.
+            user_interaction_value: Cell::new(100 + servo_rand::random::<u64>() % 9900),
+            pending_key_downs: Default::default(),
+            pending_pointer_interaction: Cell::new(None),
         }
     }
 
@@ -199,6 +305,7 @@ impl Performance {
             None => observers.push(PerformanceObserver {
                 observer: DomRoot::from_ref(observer),
                 entry_types,
+                duration_threshold: DEFAULT_DURATION_THRESHOLD,
             }),
         };
     }
@@ -208,11 +315,21 @@ impl Performance {
         observer: &DOMPerformanceObserver,
         entry_type: &DOMString,
         buffered: bool,
+        duration_threshold: Option<f64>,
     ) {
+        let duration_threshold = match duration_threshold {
+            Some(duration_threshold) if *entry_type == "event" => {
+                duration_threshold.max(MIN_DURATION_THRESHOLD)
+            },
+            _ => DEFAULT_DURATION_THRESHOLD,
+        };
         if buffered {
             let buffer = self.buffer.borrow();
             let mut new_entries =
                 buffer.get_entries_by_name_and_type(None, Some(entry_type.clone()));
+            new_entries.retain(|entry| {
+                *entry.entry_type() != "event" || entry.duration() >= duration_threshold
+            });
             if new_entries.len() > 0 {
                 let mut obs_entries = observer.entries();
                 obs_entries.append(&mut new_entries);
@@ -234,11 +351,15 @@ impl Performance {
                 if !observers[p].entry_types.contains(entry_type) {
                     observers[p].entry_types.push(entry_type.clone())
                 }
+                if *entry_type == "event" {
+                    observers[p].duration_threshold = duration_threshold;
+                }
             },
             // Otherwise, we create and insert the new PerformanceObserver.
             None => observers.push(PerformanceObserver {
                 observer: DomRoot::from_ref(observer),
                 entry_types: vec![entry_type.clone()],
+                duration_threshold,
             }),
         };
     }
@@ -272,23 +393,19 @@ impl Performance {
         // Add the performance entry to the list of performance entries that have not
         // been notified to each performance observer owner, filtering the ones it's
         // interested in.
-        for o in self
-            .observers
-            .borrow()
-            .iter()
-            .filter(|o| o.entry_types.contains(entry.entry_type()))
-        {
+        for o in self.observers.borrow().iter().filter(|o| o.accepts(entry)) {
             o.observer.queue_entry(entry);
         }
 
         // Step 4.
-        //add the new entry to the buffer.
-        self.buffer
-            .borrow_mut()
-            .entries
-            .push(DomRoot::from_ref(entry));
-
-        let entry_last_index = self.buffer.borrow_mut().entries.len() - 1;
+        // Add the new entry to the buffer, if there is room for it.
+        let entry_last_index = if self.should_buffer_entry(entry) {
+            let mut buffer = self.buffer.borrow_mut();
+            buffer.entries.push(DomRoot::from_ref(entry));
+            Some(buffer.entries.len() - 1)
+        } else {
+            None
+        };
 
         // Step 5.
         // If there is already a queued notification task, we just bail out.
@@ -302,7 +419,132 @@ impl Performance {
         let task_source = self.global().performance_timeline_task_source();
         task_source.queue_notification(&self.global());
 
-        Some(entry_last_index)
+        entry_last_index
+    }
+
+    /// Whether `entry` is kept in the buffer, which only keeps the longer events, and a
+    /// limited number of the entries of some types.
+    /// <https://w3c.github.io/performance-timeline/#dfn-determine-if-a-performance-entry-buffer-is-full>
+    fn should_buffer_entry(&self, entry: &PerformanceEntry) -> bool {
+        if *entry.entry_type() == "event" && entry.duration() < DEFAULT_DURATION_THRESHOLD {
+            return false;
+        }
+        let max_buffer_size = match max_buffer_size(entry.entry_type()) {
+            Some(max_buffer_size) => max_buffer_size,
+            None => return true,
+        };
+        let buffer = self.buffer.borrow();
+        let buffered = buffer
+            .entries
+            .iter()
+            .filter(|buffered| buffered.entry_type() == entry.entry_type())
+            .count();
+        buffered < max_buffer_size
+    }
+
+    /// Report how long the dispatch of `event`, a trusted event targeting `target` whose
+    /// listeners ran from `processing_start` to `processing_end` in nanoseconds of the
+    /// monotonic clock, took.
+    ///
+    /// The duration of the event lasts until the next paint after its processing, which is not
+    /// known here, so it is measured until the end of its processing instead.
+    /// <https://w3c.github.io/event-timing/#sec-fini-event-timing>
+    pub fn queue_event_timing(
+        &self,
+        event: &Event,
+        target: &EventTarget,
+        processing_start: u64,
+        processing_end: u64,
+    ) {
+        let type_ = event.type_();
+        if !EVENT_TIMING_TYPES.contains(&&*type_) {
+            return;
+        }
+        let start_time = self.relative_high_resolution_time(event.precise_time_ns());
+        let processing_end = self.relative_high_resolution_time(processing_end);
+        let data = EventTimingData {
+            start_time,
+            // Durations are rounded to 8ms, to not tell more precisely when the event was
+            // processed.
+            duration: ((processing_end - start_time) / 8.).round() * 8.,
+            processing_start: self.relative_high_resolution_time(processing_start),
+            processing_end,
+            cancelable: event.Cancelable(),
+            interaction_id: self.interaction_id(event),
+        };
+        let global = self.global();
+        let name = DOMString::from(&*type_);
+        let target = target.downcast::<Node>();
+
+        // The first input of the user is reported however long it took.
+        if FIRST_INPUT_TYPES.contains(&&*type_) && !self.has_dispatched_input_event.replace(true) {
+            let entry = PerformanceEventTiming::new(
+                &global,
+                name.clone(),
+                DOMString::from("first-input"),
+                &data,
+                target,
+            );
+            self.queue_entry(entry.upcast::<PerformanceEntry>());
+        }
+
+        if data.duration < MIN_DURATION_THRESHOLD {
+            return;
+        }
+        let entry =
+            PerformanceEventTiming::new(&global, name, DOMString::from("event"), &data, target);
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
+    }
+
+    /// The identifier of the interaction of the user that `event` is part of, the events of a
+    /// key press or of a click sharing one, or 0 if it is not part of one.
+    /// <https://w3c.github.io/event-timing/#sec-computing-interactionid>
+    fn interaction_id(&self, event: &Event) -> u64 {
+        let type_ = event.type_();
+        match &*type_ {
+            "keydown" | "keypress" | "keyup" => {
+                let key_code = match event.downcast::<KeyboardEvent>() {
+                    Some(event) => event.KeyCode(),
+                    None => return 0,
+                };
+                let mut pending_key_downs = self.pending_key_downs.borrow_mut();
+                match &*type_ {
+                    "keydown" => {
+                        let interaction_id = self.new_interaction_id();
+                        pending_key_downs.insert(key_code, interaction_id);
+                        interaction_id
+                    },
+                    "keypress" => pending_key_downs.get(&key_code).copied().unwrap_or(0),
+                    _ => pending_key_downs.remove(&key_code).unwrap_or(0),
+                }
+            },
+            "pointerdown" => {
+                let interaction_id = self.new_interaction_id();
+                self.pending_pointer_interaction.set(Some(interaction_id));
+                interaction_id
+            },
+            "mousedown" => match self.pending_pointer_interaction.get() {
+                Some(interaction_id) => interaction_id,
+                None => {
+                    let interaction_id = self.new_interaction_id();
+                    self.pending_pointer_interaction.set(Some(interaction_id));
+                    interaction_id
+                },
+            },
+            "pointerup" | "mouseup" => self.pending_pointer_interaction.get().unwrap_or(0),
+            "click" => self
+                .pending_pointer_interaction
+                .take()
+                .unwrap_or_else(|| self.new_interaction_id()),
+            _ => 0,
+        }
+    }
+
+    /// <https://w3c.github.io/event-timing/#increase-interaction-count>
+    fn new_interaction_id(&self) -> u64 {
+        let interaction_id = self.user_interaction_value.get() + 7;
+        self.user_interaction_value.set(interaction_id);
+        interaction_id
     }
 
     /// Observers notifications task.
@@ -331,8 +573,18 @@ impl Performance {
         }
     }
 
-    fn now(&self) -> f64 {
-        (time::precise_time_ns() - self.navigation_start_precise).to_ms()
+    pub fn now(&self) -> f64 {
+        self.relative_high_resolution_time(clock::monotonic_time_ns())
+    }
+
+    /// The time elapsed between the time origin and `time_ns`, a time of the monotonic
+    /// clock, in milliseconds.
+    ///
+    /// <https://w3c.github.io/hr-time/#dfn-relative-high-resolution-time>
+    pub fn relative_high_resolution_time(&self, time_ns: u64) -> f64 {
+        time_ns
+            .saturating_sub(self.navigation_start_precise)
+            .to_ms()
     }
 
     fn can_add_resource_timing_entry(&self) -> bool {
@@ -396,11 +648,105 @@ impl Performance {
         false
     }
 
+    /// The buffered entries of `name` and `entry_type` that the timeline exposes, which
+    /// leaves out the ones only exposed to observers.
+    /// <https://w3c.github.io/performance-timeline/#filter-buffer-map-by-name-and-type>
+    fn get_timeline_entries(
+        &self,
+        name: Option<DOMString>,
+        entry_type: Option<DOMString>,
+    ) -> Vec<DomRoot<PerformanceEntry>> {
+        let mut entries = self
+            .buffer
+            .borrow()
+            .get_entries_by_name_and_type(name, entry_type);
+        entries.retain(|entry| !OBSERVER_ONLY_ENTRY_TYPES.contains(&&**entry.entry_type()));
+        entries
+    }
+
     pub fn update_entry(&self, index: usize, entry: &PerformanceEntry) {
         if let Some(e) = self.buffer.borrow_mut().entries.get_mut(index) {
             *e = DomRoot::from_ref(entry);
         }
     }
+
+    /// The time a measure starts or ends at when given `mark`: the start time of the last
+    /// mark of that name, the time of the navigation timing attribute of that name, or the
+    /// timestamp itself.
+    /// <https://w3c.github.io/user-timing/#convert-a-mark-to-a-timestamp>
+    fn convert_mark_to_timestamp(&self, mark: &StringOrDouble) -> Fallible<f64> {
+        match *mark {
+            StringOrDouble::String(ref name) if INVALID_ENTRY_NAMES.contains(&&**name) => {
+                // Step 1.
+                if !self.global().is::<Window>() {
+                    return Err(Error::Type(format!(
+                        "{} is only known to the performance timing of windows",
+                        name
+                    )));
+                }
+                if &**name == "navigationStart" {
+                    return Ok(0.);
+                }
+                let start_time = self.navigation_timing_value(name);
+                if start_time == 0. {
+                    return Err(Error::InvalidAccess);
+                }
+                Ok(start_time)
+            },
+            // Step 2.
+            StringOrDouble::String(ref name) => self
+                .buffer
+                .borrow()
+                .get_last_entry_start_time_with_name_and_type(name, "mark")
+                .ok_or(Error::Syntax),
+            // Step 3.
+            StringOrDouble::Double(time) if *time < 0. => Err(Error::Type(
+                "A timestamp of a measure cannot be negative".to_owned(),
+            )),
+            StringOrDouble::Double(time) => Ok(*time),
+        }
+    }
+
+    /// The value of the attribute of `PerformanceTiming` named `name`, relative to the time
+    /// origin, which is 0 as long as it is not known.
+    fn navigation_timing_value(&self, name: &str) -> f64 {
+        let entries = self
+            .buffer
+            .borrow()
+            .get_entries_by_name_and_type(None, Some(DOMString::from("navigation")));
+        let timing = match entries
+            .first()
+            .and_then(|entry| entry.downcast::<PerformanceNavigationTiming>())
+        {
+            Some(timing) => timing,
+            None => return 0.,
+        };
+        let resource_timing = timing.upcast::<PerformanceResourceTiming>();
+        let value = match name {
+            "unloadEventStart" => timing.UnloadEventStart(),
+            "unloadEventEnd" => timing.UnloadEventEnd(),
+            "redirectStart" => resource_timing.RedirectStart(),
+            "redirectEnd" => resource_timing.RedirectEnd(),
+            "fetchStart" => resource_timing.FetchStart(),
+            "domainLookupStart" => resource_timing.DomainLookupStart(),
+            "domainLookupEnd" => resource_timing.DomainLookupEnd(),
+            "connectStart" => resource_timing.ConnectStart(),
+            "connectEnd" => resource_timing.ConnectEnd(),
+            "secureConnectionStart" => resource_timing.SecureConnectionStart(),
+            "requestStart" => resource_timing.RequestStart(),
+            "responseStart" => resource_timing.ResponseStart(),
+            "responseEnd" => resource_timing.ResponseEnd(),
+            "domInteractive" => timing.DomInteractive(),
+            "domContentLoadedEventStart" => timing.DomContentLoadedEventStart(),
+            "domContentLoadedEventEnd" => timing.DomContentLoadedEventEnd(),
+            "domComplete" => timing.DomComplete(),
+            "loadEventStart" => timing.LoadEventStart(),
+            "loadEventEnd" => timing.LoadEventEnd(),
+            // There is no `domLoading` in navigation timing.
+            _ => return 0.,
+        };
+        *value
+    }
 }
 
 impl PerformanceMethods for Performance {
@@ -425,26 +771,23 @@ impl PerformanceMethods for Performance {
 
     // https://dvcs.w3.org/hg/webperf/raw-file/tip/specs/HighResolutionTime/Overview.html#dom-performance-now
     fn Now(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.now())
+        self.global().coarsen_time(self.now())
     }
 
     // https://www.w3.org/TR/hr-time-2/#dom-performance-timeorigin
     fn TimeOrigin(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.navigation_start_precise as f64)
+        self.global()
+            .coarsen_time(self.navigation_start_precise as f64)
     }
 
     // https://www.w3.org/TR/performance-timeline-2/#dom-performance-getentries
     fn GetEntries(&self) -> Vec<DomRoot<PerformanceEntry>> {
-        self.buffer
-            .borrow()
-            .get_entries_by_name_and_type(None, None)
+        self.get_timeline_entries(None, None)
     }
 
     // https://www.w3.org/TR/performance-timeline-2/#dom-performance-getentriesbytype
     fn GetEntriesByType(&self, entry_type: DOMString) -> Vec<DomRoot<PerformanceEntry>> {
-        self.buffer
-            .borrow()
-            .get_entries_by_name_and_type(None, Some(entry_type))
+        self.get_timeline_entries(None, Some(entry_type))
     }
 
     // https://www.w3.org/TR/performance-timeline-2/#dom-performance-getentriesbyname
@@ -453,26 +796,23 @@ impl PerformanceMethods for Performance {
         name: DOMString,
         entry_type: Option<DOMString>,
     ) -> Vec<DomRoot<PerformanceEntry>> {
-        self.buffer
-            .borrow()
-            .get_entries_by_name_and_type(Some(name), entry_type)
+        self.get_timeline_entries(Some(name), entry_type)
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-mark
-    fn Mark(&self, mark_name: DOMString) -> Fallible<()> {
-        let global = self.global();
+    fn Mark(
+        &self,
+        mark_name: DOMString,
+        mark_options: RootedTraceableBox<PerformanceMarkOptions>,
+    ) -> Fallible<DomRoot<PerformanceMark>> {
         // Step 1.
-        if global.is::<Window>() && INVALID_ENTRY_NAMES.contains(&mark_name.as_ref()) {
-            return Err(Error::Syntax);
-        }
+        let entry = PerformanceMark::Constructor(&self.global(), None, mark_name, mark_options)?;
 
-        // Steps 2 to 6.
-        let entry = PerformanceMark::new(&global, mark_name, self.now(), 0.);
-        // Steps 7 and 8.
-        self.queue_entry(&entry.upcast::<PerformanceEntry>());
+        // Steps 2 and 3.
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
 
-        // Step 9.
-        Ok(())
+        // Step 4.
+        Ok(entry)
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-clearmarks
@@ -486,40 +826,88 @@ impl PerformanceMethods for Performance {
     fn Measure(
         &self,
         measure_name: DOMString,
-        start_mark: Option<DOMString>,
+        start_or_measure_options: StringOrPerformanceMeasureOptions,
         end_mark: Option<DOMString>,
-    ) -> Fallible<()> {
-        // Steps 1 and 2.
-        let end_time = match end_mark {
-            Some(name) => self
-                .buffer
-                .borrow()
-                .get_last_entry_start_time_with_name_and_type(DOMString::from("mark"), name),
-            None => self.now(),
+    ) -> Fallible<DomRoot<PerformanceMeasure>> {
+        let options = match start_or_measure_options {
+            StringOrPerformanceMeasureOptions::PerformanceMeasureOptions(ref options) => {
+                Some(options)
+            },
+            StringOrPerformanceMeasureOptions::String(_) => None,
+        };
+
+        // Step 1.
+        if let Some(options) = options {
+            let has_members = options.start.is_some() ||
+                options.end.is_some() ||
+                options.duration.is_some() ||
+                !options.detail.get().is_undefined();
+            if has_members {
+                if end_mark.is_some() {
+                    return Err(Error::Type(
+                        "An end mark cannot be given along with measure options".to_owned(),
+                    ));
+                }
+                if options.start.is_none() && options.end.is_none() {
+                    return Err(Error::Type(
+                        "Measure options need a start or an end".to_owned(),
+                    ));
+                }
+                if options.start.is_some() && options.duration.is_some() && options.end.is_some() {
+                    return Err(Error::Type(
+                        "Measure options cannot have a start, a duration and an end".to_owned(),
+                    ));
+                }
+            }
+        }
+        let start = options.and_then(|options| options.start.as_ref());
+        let end = options.and_then(|options| options.end.as_ref());
+        let duration = options
+            .and_then(|options| options.duration)
+            .map(StringOrDouble::Double);
+
+        // Step 2.
+        let end_time = match (end_mark, end, start, &duration) {
+            (Some(end_mark), ..) => {
+                self.convert_mark_to_timestamp(&StringOrDouble::String(end_mark))?
+            },
+            (None, Some(end), ..) => self.convert_mark_to_timestamp(end)?,
+            (None, None, Some(start), Some(duration)) => {
+                self.convert_mark_to_timestamp(start)? + self.convert_mark_to_timestamp(duration)?
+            },
+            _ => self.now(),
         };
 
         // Step 3.
-        let start_time = match start_mark {
-            Some(name) => self
-                .buffer
-                .borrow()
-                .get_last_entry_start_time_with_name_and_type(DOMString::from("mark"), name),
-            None => 0.,
+        let start_time = match (start, &duration, end, &start_or_measure_options) {
+            (Some(start), ..) => self.convert_mark_to_timestamp(start)?,
+            (None, Some(duration), Some(_), _) => {
+                end_time - self.convert_mark_to_timestamp(duration)?
+            },
+            (None, _, _, StringOrPerformanceMeasureOptions::String(start_mark)) => {
+                self.convert_mark_to_timestamp(&StringOrDouble::String(start_mark.clone()))?
+            },
+            _ => 0.,
         };
 
-        // Steps 4 to 8.
+        // Steps 4 to 9.
+        let detail = match options {
+            Some(options) => options.detail.handle(),
+            None => HandleValue::undefined(),
+        };
         let entry = PerformanceMeasure::new(
             &self.global(),
             measure_name,
             start_time,
             end_time - start_time,
-        );
+            detail,
+        )?;
 
-        // Step 9 and 10.
-        self.queue_entry(&entry.upcast::<PerformanceEntry>());
+        // Steps 10 and 11.
+        self.queue_entry(entry.upcast::<PerformanceEntry>());
 
-        // Step 11.
-        Ok(())
+        // Step 12.
+        Ok(entry)
     }
 
     // https://w3c.github.io/user-timing/#dom-performance-clearmeasures
@@ -549,14 +937,3 @@ impl PerformanceMethods for Performance {
         SetOnresourcetimingbufferfull
     );
 }
-
-// https://www.w3.org/TR/hr-time-2/#clock-resolution
-pub fn reduce_timing_resolution(exact: f64) -> DOMHighResTimeStamp {
-    // We need a granularity no finer than 5 microseconds.
-    // 5 microseconds isn't an exactly representable f64 so WPT tests
-    // might occasionally corner-case on rounding.
-    // web-platform-tests/wpt#21526 wants us to use an integer number of
-    // microseconds; the next divisor of milliseconds up from 5 microseconds
-    // is 10, which is 1/100th of a millisecond.
-    Finite::wrap((exact * 100.0).floor() / 100.0)
-}
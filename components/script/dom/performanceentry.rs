@@ -3,14 +3,18 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::{JSVal, NullValue, UndefinedValue};
+use js::rust::HandleValue;
 
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
 use crate::dom::bindings::codegen::Bindings::PerformanceEntryBinding::PerformanceEntryMethods;
-use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone;
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::performance::reduce_timing_resolution;
 
 #[dom_struct]
 pub struct PerformanceEntry {
@@ -66,6 +70,24 @@ impl PerformanceEntry {
     }
 }
 
+/// Set the `detail` of a mark or measure to a copy of `value` made by serializing and
+/// deserializing it in the current realm, so that later changes to `value` do not show in
+/// the entry. Without a `value`, the detail is null.
+/// <https://w3c.github.io/user-timing/#the-performancemark-constructor> (step 7)
+pub fn set_user_timing_detail(detail: &Heap<JSVal>, value: HandleValue) -> Fallible<()> {
+    if value.is_null_or_undefined() {
+        detail.set(NullValue());
+        return Ok(());
+    }
+    let cx = GlobalScope::get_cx();
+    let record = structuredclone::write(cx, value, None)?;
+    rooted!(in(*cx) let mut copy = UndefinedValue());
+    structuredclone::read_in_current_realm(record, copy.handle_mut())
+        .map_err(|_| Error::DataClone)?;
+    detail.set(copy.get());
+    Ok(())
+}
+
 impl PerformanceEntryMethods for PerformanceEntry {
     // https://w3c.github.io/performance-timeline/#dom-performanceentry-name
     fn Name(&self) -> DOMString {
@@ -79,11 +101,11 @@ impl PerformanceEntryMethods for PerformanceEntry {
 
     // https://w3c.github.io/performance-timeline/#dom-performanceentry-starttime
     fn StartTime(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.start_time)
+        self.global().coarsen_time(self.start_time)
     }
 
     // https://w3c.github.io/performance-timeline/#dom-performanceentry-duration
     fn Duration(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.duration)
+        self.global().coarsen_time(self.duration)
     }
 }
@@ -0,0 +1,104 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
+use crate::dom::bindings::codegen::Bindings::PerformanceEventTimingBinding::PerformanceEventTimingMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::node::Node;
+use crate::dom::performanceentry::PerformanceEntry;
+
+/// How long the user agent took to handle an event caused by the user, from when it happened
+/// to when its listeners and default action were done.
+/// <https://w3c.github.io/event-timing/#sec-performance-event-timing>
+#[dom_struct]
+pub struct PerformanceEventTiming {
+    entry: PerformanceEntry,
+    processing_start: f64,
+    processing_end: f64,
+    cancelable: bool,
+    target: Option<Dom<Node>>,
+    interaction_id: u64,
+}
+
+/// What is measured of the dispatch of an event, in milliseconds since the time origin.
+pub struct EventTimingData {
+    pub start_time: f64,
+    pub duration: f64,
+    pub processing_start: f64,
+    pub processing_end: f64,
+    pub cancelable: bool,
+    pub interaction_id: u64,
+}
+
+impl PerformanceEventTiming {
+    fn new_inherited(
+        name: DOMString,
+        entry_type: DOMString,
+        data: &EventTimingData,
+        target: Option<&Node>,
+    ) -> PerformanceEventTiming {
+        PerformanceEventTiming {
+            entry: PerformanceEntry::new_inherited(
+                name,
+                entry_type,
+                data.start_time,
+                data.duration,
+            ),
+            processing_start: data.processing_start,
+            processing_end: data.processing_end,
+            cancelable: data.cancelable,
+            target: target.map(Dom::from_ref),
+            interaction_id: data.interaction_id,
+        }
+    }
+
+    /// An entry of type `entry_type`, `event` or `first-input`, for an event of type `name`.
+    #[allow(crown::unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        entry_type: DOMString,
+        data: &EventTimingData,
+        target: Option<&Node>,
+    ) -> DomRoot<PerformanceEventTiming> {
+        let entry = PerformanceEventTiming::new_inherited(name, entry_type, data, target);
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+impl PerformanceEventTimingMethods for PerformanceEventTiming {
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingstart
+    fn ProcessingStart(&self) -> DOMHighResTimeStamp {
+        self.global().coarsen_time(self.processing_start)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-processingend
+    fn ProcessingEnd(&self) -> DOMHighResTimeStamp {
+        self.global().coarsen_time(self.processing_end)
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-cancelable
+    fn Cancelable(&self) -> bool {
+        self.cancelable
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-target
+    fn GetTarget(&self) -> Option<DomRoot<Node>> {
+        // Nodes that were removed from the document are not exposed.
+        self.target
+            .as_ref()
+            .filter(|target| target.is_connected())
+            .map(|target| DomRoot::from_ref(&**target))
+    }
+
+    // https://w3c.github.io/event-timing/#dom-performanceeventtiming-interactionid
+    fn InteractionId(&self) -> u64 {
+        self.interaction_id
+    }
+}
@@ -0,0 +1,122 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::jsval::JSVal;
+
+use crate::dom::bindings::codegen::Bindings::PerformanceLongTaskTimingBinding::PerformanceLongTaskTimingMethods;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::taskattributiontiming::TaskAttributionTiming;
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+
+/// A task of the event loop that kept it busy for more than 50ms.
+/// <https://w3c.github.io/longtasks/#sec-PerformanceLongTaskTiming>
+#[dom_struct]
+pub struct PerformanceLongTaskTiming {
+    entry: PerformanceEntry,
+    attribution: Vec<Dom<TaskAttributionTiming>>,
+}
+
+impl PerformanceLongTaskTiming {
+    fn new_inherited(
+        name: DOMString,
+        start_time: f64,
+        duration: f64,
+        attribution: &TaskAttributionTiming,
+    ) -> PerformanceLongTaskTiming {
+        PerformanceLongTaskTiming {
+            entry: PerformanceEntry::new_inherited(
+                name,
+                DOMString::from("longtask"),
+                start_time,
+                duration,
+            ),
+            attribution: vec![Dom::from_ref(attribution)],
+        }
+    }
+
+    /// A long task whose relation to the document it is reported to is `name`, such as
+    /// `self`, and that ran in the browsing context of `attribution`.
+    #[allow(crown::unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        name: DOMString,
+        start_time: f64,
+        duration: f64,
+        attribution: &TaskAttributionTiming,
+    ) -> DomRoot<PerformanceLongTaskTiming> {
+        let entry =
+            PerformanceLongTaskTiming::new_inherited(name, start_time, duration, attribution);
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+/// How a long task that ran in the browsing context of `culprit` relates to the document of
+/// `destination` it is reported to, and the element of that document containing the browsing
+/// context, if any.
+/// <https://w3c.github.io/longtasks/#report-long-tasks>
+pub(crate) fn long_task_attribution(
+    destination: &Window,
+    culprit: &Window,
+) -> (&'static str, Option<DomRoot<Element>>) {
+    let destination_proxy = destination.window_proxy();
+    let culprit_proxy = culprit.window_proxy();
+    if destination_proxy.browsing_context_id() == culprit_proxy.browsing_context_id() {
+        return ("self", None);
+    }
+    let same_origin = destination.origin().same_origin(culprit.origin());
+
+    // The culprit is a descendant: its container is the one in the document of destination.
+    let mut child = culprit_proxy.clone();
+    while let Some(parent) = child.parent().map(DomRoot::from_ref) {
+        if parent.browsing_context_id() == destination_proxy.browsing_context_id() {
+            let name = if same_origin {
+                "same-origin-descendant"
+            } else {
+                "cross-origin-descendant"
+            };
+            return (name, child.frame_element().map(DomRoot::from_ref));
+        }
+        child = parent;
+    }
+
+    // The culprit is an ancestor.
+    let mut descendant = destination_proxy;
+    while let Some(parent) = descendant.parent().map(DomRoot::from_ref) {
+        if parent.browsing_context_id() == culprit_proxy.browsing_context_id() {
+            let name = if same_origin {
+                "same-origin-ancestor"
+            } else {
+                "cross-origin-ancestor"
+            };
+            return (name, None);
+        }
+        descendant = parent;
+    }
+
+    if same_origin {
+        ("same-origin", None)
+    } else {
+        ("cross-origin-unreachable", None)
+    }
+}
+
+impl PerformanceLongTaskTimingMethods for PerformanceLongTaskTiming {
+    // https://w3c.github.io/longtasks/#dom-performancelongtasktiming-attribution
+    fn Attribution(&self, cx: JSContext) -> JSVal {
+        let attribution: Vec<DomRoot<TaskAttributionTiming>> = self
+            .attribution
+            .iter()
+            .map(|attribution| DomRoot::from_ref(&**attribution))
+            .collect();
+        to_frozen_array(attribution.as_slice(), cx)
+    }
+}
@@ -2,4 +2,81 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-impl_performance_entry_struct!(PerformanceMarkBinding, PerformanceMark, "mark");
+use dom_struct::dom_struct;
+use js::jsapi::Heap;
+use js::jsval::JSVal;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::codegen::Bindings::PerformanceBinding::PerformanceMarkOptions;
+use crate::dom::bindings::codegen::Bindings::PerformanceMarkBinding::PerformanceMarkMethods;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::performance::INVALID_ENTRY_NAMES;
+use crate::dom::performanceentry::{set_user_timing_detail, PerformanceEntry};
+use crate::dom::window::Window;
+use crate::script_runtime::JSContext;
+
+/// <https://w3c.github.io/user-timing/#performancemark>
+#[dom_struct]
+pub struct PerformanceMark {
+    entry: PerformanceEntry,
+    #[ignore_malloc_size_of = "Defined in rust-mozjs"]
+    detail: Heap<JSVal>,
+}
+
+impl PerformanceMark {
+    fn new_inherited(name: DOMString, start_time: f64) -> PerformanceMark {
+        PerformanceMark {
+            entry: PerformanceEntry::new_inherited(name, DOMString::from("mark"), start_time, 0.),
+            detail: Heap::default(),
+        }
+    }
+
+    /// <https://w3c.github.io/user-timing/#the-performancemark-constructor>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        mark_name: DOMString,
+        mark_options: RootedTraceableBox<PerformanceMarkOptions>,
+    ) -> Fallible<DomRoot<PerformanceMark>> {
+        // Step 1.
+        if global.is::<Window>() && INVALID_ENTRY_NAMES.contains(&&*mark_name) {
+            return Err(Error::Syntax);
+        }
+
+        // Step 5.
+        let start_time = match mark_options.startTime {
+            Some(start_time) if *start_time < 0. => {
+                return Err(Error::Type(
+                    "The start time of a mark cannot be negative".to_owned(),
+                ));
+            },
+            Some(start_time) => *start_time,
+            None => global.performance().now(),
+        };
+
+        // Steps 2 to 4 and 6.
+        let entry = reflect_dom_object_with_proto(
+            Box::new(PerformanceMark::new_inherited(mark_name, start_time)),
+            global,
+            proto,
+        );
+
+        // Step 7.
+        set_user_timing_detail(&entry.detail, mark_options.detail.handle())?;
+        Ok(entry)
+    }
+}
+
+impl PerformanceMarkMethods for PerformanceMark {
+    // https://w3c.github.io/user-timing/#dom-performancemark-detail
+    fn Detail(&self, _cx: JSContext) -> JSVal {
+        self.detail.get()
+    }
+}
@@ -29,12 +29,15 @@ use crate::script_runtime::JSContext;
 /// List of allowed performance entry types, in alphabetical order.
 pub const VALID_ENTRY_TYPES: &'static [&'static str] = &[
     // "frame", //TODO Frame Timing API
-    "mark",       // User Timing API
-    "measure",    // User Timing API
-    "navigation", // Navigation Timing API
-    "paint",      // Paint Timing API
-    "resource",   // Resource Timing API
-                  // "server", XXX Server Timing API
+    "event",       // Event Timing API
+    "first-input", // Event Timing API
+    "longtask",    // Long Tasks API
+    "mark",        // User Timing API
+    "measure",     // User Timing API
+    "navigation",  // Navigation Timing API
+    "paint",       // Paint Timing API
+    "resource",    // Resource Timing API
+                   // "server", XXX Server Timing API
 ];
 
 #[derive(Clone, Copy, JSTraceable, MallocSizeOf, PartialEq)]
@@ -222,6 +225,7 @@ impl PerformanceObserverMethods for PerformanceObserver {
                 self,
                 entry_type,
                 options.buffered.unwrap_or(false),
+                options.durationThreshold.map(|threshold| *threshold),
             );
             Ok(())
         } else {
@@ -3,17 +3,20 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
+use js::jsval::JSVal;
 use net_traits::ResourceFetchTiming;
 use servo_url::ServoUrl;
 
 use crate::dom::bindings::codegen::Bindings::PerformanceBinding::DOMHighResTimeStamp;
 use crate::dom::bindings::codegen::Bindings::PerformanceResourceTimingBinding::PerformanceResourceTimingMethods;
-use crate::dom::bindings::reflector::reflect_dom_object;
-use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performanceservertiming::PerformanceServerTiming;
+use crate::script_runtime::JSContext;
 
 // TODO UA may choose to limit how many resources are included as PerformanceResourceTiming objects
 // recommended minimum is 150, can be changed by setResourceTimingBufferSize in performance
@@ -52,6 +55,7 @@ pub struct PerformanceResourceTiming {
     transfer_size: u64,     //size in octets
     encoded_body_size: u64, //size in octets
     decoded_body_size: u64, //size in octets
+    server_timing: Vec<Dom<PerformanceServerTiming>>,
 }
 
 // TODO(#21269): next_hop
@@ -97,6 +101,7 @@ impl PerformanceResourceTiming {
             transfer_size: 0,
             encoded_body_size: 0,
             decoded_body_size: 0,
+            server_timing: vec![],
         }
     }
 
@@ -107,6 +112,7 @@ impl PerformanceResourceTiming {
         initiator_type: InitiatorType,
         next_hop: Option<DOMString>,
         resource_timing: &ResourceFetchTiming,
+        server_timing: &[DomRoot<PerformanceServerTiming>],
     ) -> PerformanceResourceTiming {
         PerformanceResourceTiming {
             entry: PerformanceEntry::new_inherited(
@@ -133,6 +139,10 @@ impl PerformanceResourceTiming {
             transfer_size: 0,
             encoded_body_size: 0,
             decoded_body_size: 0,
+            server_timing: server_timing
+                .iter()
+                .map(|metric| Dom::from_ref(&**metric))
+                .collect(),
         }
     }
 
@@ -143,12 +153,18 @@ impl PerformanceResourceTiming {
         next_hop: Option<DOMString>,
         resource_timing: &ResourceFetchTiming,
     ) -> DomRoot<PerformanceResourceTiming> {
+        let server_timing: Vec<_> = resource_timing
+            .server_timing
+            .iter()
+            .map(|metric| PerformanceServerTiming::new(global, metric))
+            .collect();
         reflect_dom_object(
             Box::new(PerformanceResourceTiming::from_resource_timing(
                 url,
                 initiator_type,
                 next_hop,
                 resource_timing,
+                &server_timing,
             )),
             global,
         )
@@ -180,17 +196,17 @@ impl PerformanceResourceTimingMethods for PerformanceResourceTiming {
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-domainlookupstart
     fn DomainLookupStart(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.domain_lookup_start)
+        self.global().coarsen_time(self.domain_lookup_start)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-domainlookupend
     fn DomainLookupEnd(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.domain_lookup_end)
+        self.global().coarsen_time(self.domain_lookup_end)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-secureconnectionstart
     fn SecureConnectionStart(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.secure_connection_start)
+        self.global().coarsen_time(self.secure_connection_start)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-transfersize
@@ -210,41 +226,51 @@ impl PerformanceResourceTimingMethods for PerformanceResourceTiming {
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-requeststart
     fn RequestStart(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.request_start)
+        self.global().coarsen_time(self.request_start)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-redirectstart
     fn RedirectStart(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.redirect_start)
+        self.global().coarsen_time(self.redirect_start)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-redirectend
     fn RedirectEnd(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.redirect_end)
+        self.global().coarsen_time(self.redirect_end)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-responsestart
     fn ResponseStart(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.response_start)
+        self.global().coarsen_time(self.response_start)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-fetchstart
     fn FetchStart(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.fetch_start)
+        self.global().coarsen_time(self.fetch_start)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-connectstart
     fn ConnectStart(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.connect_start)
+        self.global().coarsen_time(self.connect_start)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-connectend
     fn ConnectEnd(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.connect_end)
+        self.global().coarsen_time(self.connect_end)
     }
 
     // https://w3c.github.io/resource-timing/#dom-performanceresourcetiming-responseend
     fn ResponseEnd(&self) -> DOMHighResTimeStamp {
-        reduce_timing_resolution(self.response_end)
+        self.global().coarsen_time(self.response_end)
+    }
+
+    // https://w3c.github.io/server-timing/#dom-performanceresourcetiming-servertiming
+    fn ServerTiming(&self, cx: JSContext) -> JSVal {
+        let server_timing: Vec<DomRoot<PerformanceServerTiming>> = self
+            .server_timing
+            .iter()
+            .map(|metric| DomRoot::from_ref(&**metric))
+            .collect();
+        to_frozen_array(server_timing.as_slice(), cx)
     }
 }
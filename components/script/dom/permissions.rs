@@ -10,6 +10,9 @@ use ipc_channel::ipc;
 use js::conversions::ConversionResult;
 use js::jsapi::JSObject;
 use js::jsval::{ObjectValue, UndefinedValue};
+use net_traits::storage_thread::StorageThreadMsg;
+use net_traits::IpcSend;
+use script_traits::ScriptMsg;
 use servo_config::pref;
 
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
@@ -131,11 +134,11 @@ impl Permissions {
 
                     &Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name.to_string());
+                        set_permission_state(
+                            root_desc.name,
+                            PermissionState::Prompt,
+                            &self.global(),
+                        );
 
                         // (Revoke) Step 4.
                         Bluetooth::permission_revoke(&bluetooth_desc, &result)
@@ -143,6 +146,9 @@ impl Permissions {
                 }
             },
             _ => {
+                if !matches!(op, Operation::Revoke) {
+                    self.global().track_permission_status(&status);
+                }
                 match &op {
                     &Operation::Request => {
                         // (Request) Step 6.
@@ -163,11 +169,11 @@ impl Permissions {
 
                     &Operation::Revoke => {
                         // (Revoke) Step 3.
-                        let globalscope = self.global();
-                        globalscope
-                            .permission_state_invocation_results()
-                            .borrow_mut()
-                            .remove(&root_desc.name.to_string());
+                        set_permission_state(
+                            root_desc.name,
+                            PermissionState::Prompt,
+                            &self.global(),
+                        );
 
                         // (Revoke) Step 4.
                         Permissions::permission_revoke(&root_desc, &status);
@@ -253,10 +259,7 @@ impl PermissionAlgorithm for Permissions {
                 // https://w3c.github.io/permissions/#request-permission-to-use (Step 3 - 4)
                 let globalscope = GlobalScope::current().expect("No current global object");
                 let state = prompt_user_from_embedder(prompt, &globalscope);
-                globalscope
-                    .permission_state_invocation_results()
-                    .borrow_mut()
-                    .insert(perm_name.to_string(), state);
+                set_permission_state(perm_name, state, &globalscope);
             },
 
             // Step 2.
@@ -270,6 +273,24 @@ impl PermissionAlgorithm for Permissions {
     fn permission_revoke(_descriptor: &PermissionDescriptor, _status: &PermissionStatus) {}
 }
 
+// https://w3c.github.io/permissions/#request-permission-to-use
+pub fn request_permission_to_use(
+    permission_name: PermissionName,
+    globalscope: &GlobalScope,
+) -> PermissionState {
+    // Step 2.
+    let state = get_descriptor_permission_state(permission_name, Some(globalscope));
+    if state != PermissionState::Prompt {
+        return state;
+    }
+
+    // Step 3 - 4.
+    let prompt = PermissionPrompt::Request(embedder_traits::PermissionName::from(permission_name));
+    let state = prompt_user_from_embedder(prompt, globalscope);
+    set_permission_state(permission_name, state, globalscope);
+    state
+}
+
 // https://w3c.github.io/permissions/#permission-state
 pub fn get_descriptor_permission_state(
     permission_name: PermissionName,
@@ -286,43 +307,77 @@ pub fn get_descriptor_permission_state(
     // The current solution is a workaround with a message box to warn about this,
     // if the feature is not allowed in non-secure contexcts,
     // and let the user decide to grant the permission or not.
-    let state = if allowed_in_nonsecure_contexts(&permission_name) {
-        PermissionState::Prompt
-    } else {
-        if pref!(dom.permissions.testing.allowed_in_nonsecure_contexts) {
-            PermissionState::Granted
-        } else {
-            globalscope
-                .permission_state_invocation_results()
-                .borrow_mut()
-                .remove(&permission_name.to_string());
-
-            prompt_user_from_embedder(
-                PermissionPrompt::Insecure(embedder_traits::PermissionName::from(permission_name)),
-                &globalscope,
-            )
-        }
-    };
-
-    // Step 3.
-    if let Some(prev_result) = globalscope
-        .permission_state_invocation_results()
-        .borrow()
-        .get(&permission_name.to_string())
+    let allowed_in_nonsecure_contexts = allowed_in_nonsecure_contexts(&permission_name);
+    if !allowed_in_nonsecure_contexts &&
+        pref!(dom.permissions.testing.allowed_in_nonsecure_contexts)
     {
-        return prev_result.clone();
+        return PermissionState::Granted;
     }
 
-    // Store the invocation result
-    globalscope
-        .permission_state_invocation_results()
-        .borrow_mut()
-        .insert(permission_name.to_string(), state);
+    // Step 3. The state the user decided for the origin, in this session or an earlier one.
+    let state = stored_permission_state(permission_name, &globalscope);
+    if state != PermissionState::Prompt || allowed_in_nonsecure_contexts {
+        return state;
+    }
+
+    let state = prompt_user_from_embedder(
+        PermissionPrompt::Insecure(embedder_traits::PermissionName::from(permission_name)),
+        &globalscope,
+    );
+    set_permission_state(permission_name, state, &globalscope);
 
     // Step 4.
     state
 }
 
+/// Get the state of a permission for the origin of a global, as kept by the storage thread
+/// across sessions.
+fn stored_permission_state(
+    permission_name: PermissionName,
+    globalscope: &GlobalScope,
+) -> PermissionState {
+    let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+    globalscope
+        .resource_threads()
+        .send(StorageThreadMsg::GetPermission(
+            sender,
+            globalscope.get_url(),
+            permission_name.into(),
+        ))
+        .unwrap();
+    match receiver.recv() {
+        Ok(state) => state.into(),
+        Err(e) => {
+            warn!("Failed to receive permission state from storage ({:?}).", e);
+            PermissionState::Prompt
+        },
+    }
+}
+
+/// Set the state of a permission for the origin of a global, and update the `PermissionStatus`
+/// objects of every global of that origin.
+fn set_permission_state(
+    permission_name: PermissionName,
+    state: PermissionState,
+    globalscope: &GlobalScope,
+) {
+    let url = globalscope.get_url();
+    globalscope
+        .resource_threads()
+        .send(StorageThreadMsg::SetPermission(
+            url.clone(),
+            permission_name.into(),
+            state.into(),
+        ))
+        .unwrap();
+    globalscope.permission_changed(permission_name, state);
+    let msg = ScriptMsg::BroadcastPermissionChange(url, permission_name.into(), state.into());
+    globalscope
+        .script_to_constellation_chan()
+        .send(msg)
+        .unwrap();
+}
+
 // https://w3c.github.io/permissions/#allowed-in-non-secure-contexts
 fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
     match *permission_name {
@@ -348,6 +403,13 @@ fn allowed_in_nonsecure_contexts(permission_name: &PermissionName) -> bool {
         PermissionName::Bluetooth => false,
         // https://storage.spec.whatwg.org/#dom-permissionname-persistent-storage
         PermissionName::Persistent_storage => false,
+        // https://w3c.github.io/clipboard-apis/#clipboard-permissions
+        PermissionName::Clipboard_read => false,
+        PermissionName::Clipboard_write => false,
+        // https://w3c.github.io/gamepad/#permission-policy
+        PermissionName::Gamepad => false,
+        // https://immersive-web.github.io/webxr/#permissions-policy
+        PermissionName::Xr => false,
     }
 }
 
@@ -384,6 +446,54 @@ impl From<PermissionName> for embedder_traits::PermissionName {
             PermissionName::Persistent_storage => {
                 embedder_traits::PermissionName::PersistentStorage
             },
+            PermissionName::Clipboard_read => embedder_traits::PermissionName::ClipboardRead,
+            PermissionName::Clipboard_write => embedder_traits::PermissionName::ClipboardWrite,
+            PermissionName::Gamepad => embedder_traits::PermissionName::Gamepad,
+            PermissionName::Xr => embedder_traits::PermissionName::Xr,
+        }
+    }
+}
+
+impl From<embedder_traits::PermissionName> for PermissionName {
+    fn from(permission_name: embedder_traits::PermissionName) -> Self {
+        match permission_name {
+            embedder_traits::PermissionName::Geolocation => PermissionName::Geolocation,
+            embedder_traits::PermissionName::Notifications => PermissionName::Notifications,
+            embedder_traits::PermissionName::Push => PermissionName::Push,
+            embedder_traits::PermissionName::Midi => PermissionName::Midi,
+            embedder_traits::PermissionName::Camera => PermissionName::Camera,
+            embedder_traits::PermissionName::Microphone => PermissionName::Microphone,
+            embedder_traits::PermissionName::Speaker => PermissionName::Speaker,
+            embedder_traits::PermissionName::DeviceInfo => PermissionName::Device_info,
+            embedder_traits::PermissionName::BackgroundSync => PermissionName::Background_sync,
+            embedder_traits::PermissionName::Bluetooth => PermissionName::Bluetooth,
+            embedder_traits::PermissionName::PersistentStorage => {
+                PermissionName::Persistent_storage
+            },
+            embedder_traits::PermissionName::ClipboardRead => PermissionName::Clipboard_read,
+            embedder_traits::PermissionName::ClipboardWrite => PermissionName::Clipboard_write,
+            embedder_traits::PermissionName::Gamepad => PermissionName::Gamepad,
+            embedder_traits::PermissionName::Xr => PermissionName::Xr,
+        }
+    }
+}
+
+impl From<PermissionState> for embedder_traits::PermissionState {
+    fn from(state: PermissionState) -> Self {
+        match state {
+            PermissionState::Granted => embedder_traits::PermissionState::Granted,
+            PermissionState::Denied => embedder_traits::PermissionState::Denied,
+            PermissionState::Prompt => embedder_traits::PermissionState::Prompt,
+        }
+    }
+}
+
+impl From<embedder_traits::PermissionState> for PermissionState {
+    fn from(state: embedder_traits::PermissionState) -> Self {
+        match state {
+            embedder_traits::PermissionState::Granted => PermissionState::Granted,
+            embedder_traits::PermissionState::Denied => PermissionState::Denied,
+            embedder_traits::PermissionState::Prompt => PermissionState::Prompt,
         }
     }
 }
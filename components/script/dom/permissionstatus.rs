@@ -10,6 +10,7 @@ use dom_struct::dom_struct;
 use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
     PermissionDescriptor, PermissionName, PermissionState, PermissionStatusMethods,
 };
+use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::eventtarget::EventTarget;
@@ -43,6 +44,15 @@ impl PermissionStatus {
         self.state.set(state);
     }
 
+    /// <https://w3c.github.io/permissions/#dfn-permissionstatus-update-steps>
+    pub fn update_state(&self, state: PermissionState) {
+        if self.state.get() == state {
+            return;
+        }
+        self.state.set(state);
+        self.upcast::<EventTarget>().fire_event(atom!("change"));
+    }
+
     pub fn get_query(&self) -> PermissionName {
         self.query.get()
     }
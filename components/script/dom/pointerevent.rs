@@ -0,0 +1,477 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use dom_struct::dom_struct;
+use euclid::default::Point2D;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::MouseEventBinding::MouseEventMethods;
+use crate::dom::bindings::codegen::Bindings::PointerEventBinding;
+use crate::dom::bindings::codegen::Bindings::PointerEventBinding::PointerEventMethods;
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::{EventBubbles, EventCancelable};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::mouseevent::MouseEvent;
+use crate::dom::window::Window;
+
+/// The pointer id of the mouse, which is always the primary pointer of its type.
+/// <https://w3c.github.io/pointerevents/#pointerid>
+pub const MOUSE_POINTER_ID: i32 = 1;
+
+/// Touch points get pointer ids above the one of the mouse, so that both can be active
+/// at the same time.
+pub const TOUCH_POINTER_ID_OFFSET: i32 = 2;
+
+/// The properties a pointer event has on top of the ones of a mouse event.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub struct PointerProperties {
+    pub pointer_id: i32,
+    pub width: f64,
+    pub height: f64,
+    pub pressure: f32,
+    pub tangential_pressure: f32,
+    pub tilt_x: i32,
+    pub tilt_y: i32,
+    pub twist: i32,
+    pub altitude_angle: f64,
+    pub azimuth_angle: f64,
+    pub pointer_type: DOMString,
+    pub is_primary: bool,
+}
+
+impl PointerProperties {
+    /// The properties of the pointer events fired for the mouse. The pressure follows
+    /// whether any button is pressed.
+    /// <https://w3c.github.io/pointerevents/#dom-pointerevent-pressure>
+    pub fn mouse(buttons: u16) -> PointerProperties {
+        PointerProperties {
+            pointer_id: MOUSE_POINTER_ID,
+            pressure: if buttons != 0 { 0.5 } else { 0.0 },
+            pointer_type: DOMString::from("mouse"),
+            is_primary: true,
+            ..PointerProperties::default()
+        }
+    }
+
+    /// The properties of the pointer events fired for the touch point `identifier`.
+    pub fn touch(identifier: i32, is_primary: bool, pressed: bool) -> PointerProperties {
+        PointerProperties {
+            pointer_id: identifier.saturating_add(TOUCH_POINTER_ID_OFFSET),
+            pressure: if pressed { 0.5 } else { 0.0 },
+            pointer_type: DOMString::from("touch"),
+            is_primary,
+            ..PointerProperties::default()
+        }
+    }
+}
+
+impl Default for PointerProperties {
+    fn default() -> PointerProperties {
+        PointerProperties {
+            pointer_id: 0,
+            width: 1.0,
+            height: 1.0,
+            pressure: 0.0,
+            tangential_pressure: 0.0,
+            tilt_x: 0,
+            tilt_y: 0,
+            twist: 0,
+            altitude_angle: FRAC_PI_2,
+            azimuth_angle: 0.0,
+            pointer_type: DOMString::new(),
+            is_primary: false,
+        }
+    }
+}
+
+#[dom_struct]
+pub struct PointerEvent {
+    mouseevent: MouseEvent,
+    properties: DomRefCell<PointerProperties>,
+    coalesced_events: DomRefCell<Vec<Dom<PointerEvent>>>,
+    predicted_events: DomRefCell<Vec<Dom<PointerEvent>>>,
+    /// Whether the coalesced events of this event contain this event itself, which is the
+    /// case of the trusted `pointermove` events.
+    coalesces_itself: Cell<bool>,
+}
+
+impl PointerEvent {
+    fn new_inherited() -> PointerEvent {
+        PointerEvent {
+            mouseevent: MouseEvent::new_inherited(),
+            properties: DomRefCell::new(PointerProperties::default()),
+            coalesced_events: DomRefCell::new(vec![]),
+            predicted_events: DomRefCell::new(vec![]),
+            coalesces_itself: Cell::new(false),
+        }
+    }
+
+    fn new_unintialized(window: &Window, proto: Option<HandleObject>) -> DomRoot<PointerEvent> {
+        reflect_dom_object_with_proto(Box::new(PointerEvent::new_inherited()), window, proto)
+    }
+
+    pub fn new(
+        window: &Window,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        buttons: u16,
+        related_target: Option<&EventTarget>,
+        point_in_target: Option<Point2D<f32>>,
+        properties: PointerProperties,
+    ) -> DomRoot<PointerEvent> {
+        Self::new_with_proto(
+            window,
+            None,
+            type_,
+            can_bubble,
+            cancelable,
+            view,
+            detail,
+            screen_x,
+            screen_y,
+            client_x,
+            client_y,
+            ctrl_key,
+            alt_key,
+            shift_key,
+            meta_key,
+            button,
+            buttons,
+            related_target,
+            point_in_target,
+            properties,
+        )
+    }
+
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        can_bubble: EventBubbles,
+        cancelable: EventCancelable,
+        view: Option<&Window>,
+        detail: i32,
+        screen_x: i32,
+        screen_y: i32,
+        client_x: i32,
+        client_y: i32,
+        ctrl_key: bool,
+        alt_key: bool,
+        shift_key: bool,
+        meta_key: bool,
+        button: i16,
+        buttons: u16,
+        related_target: Option<&EventTarget>,
+        point_in_target: Option<Point2D<f32>>,
+        properties: PointerProperties,
+    ) -> DomRoot<PointerEvent> {
+        let ev = PointerEvent::new_unintialized(window, proto);
+        ev.mouseevent.initialize_mouse_event(
+            type_,
+            can_bubble,
+            cancelable,
+            view,
+            detail,
+            screen_x,
+            screen_y,
+            client_x,
+            client_y,
+            ctrl_key,
+            alt_key,
+            shift_key,
+            meta_key,
+            button,
+            buttons,
+            related_target,
+            point_in_target,
+        );
+        *ev.properties.borrow_mut() = properties;
+        ev
+    }
+
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: &PointerEventBinding::PointerEventInit,
+    ) -> Fallible<DomRoot<PointerEvent>> {
+        let (tilt_x, tilt_y, altitude_angle, azimuth_angle) = resolve_orientation(
+            init.tiltX,
+            init.tiltY,
+            init.altitudeAngle.map(|angle| *angle),
+            init.azimuthAngle.map(|angle| *angle),
+        );
+        let properties = PointerProperties {
+            pointer_id: init.pointerId,
+            width: *init.width,
+            height: *init.height,
+            pressure: *init.pressure,
+            tangential_pressure: *init.tangentialPressure,
+            tilt_x,
+            tilt_y,
+            twist: init.twist,
+            altitude_angle,
+            azimuth_angle,
+            pointer_type: init.pointerType.clone(),
+            is_primary: init.isPrimary,
+        };
+        let event = PointerEvent::new_with_proto(
+            window,
+            proto,
+            type_,
+            EventBubbles::from(init.parent.parent.parent.parent.bubbles),
+            EventCancelable::from(init.parent.parent.parent.parent.cancelable),
+            init.parent.parent.parent.view.as_deref(),
+            init.parent.parent.parent.detail,
+            init.parent.screenX,
+            init.parent.screenY,
+            init.parent.clientX,
+            init.parent.clientY,
+            init.parent.parent.ctrlKey,
+            init.parent.parent.altKey,
+            init.parent.parent.shiftKey,
+            init.parent.parent.metaKey,
+            init.parent.button,
+            init.parent.buttons,
+            init.parent.relatedTarget.as_deref(),
+            None,
+            properties,
+        );
+        *event.coalesced_events.borrow_mut() = init
+            .coalescedEvents
+            .iter()
+            .map(|event| Dom::from_ref(&**event))
+            .collect();
+        *event.predicted_events.borrow_mut() = init
+            .predictedEvents
+            .iter()
+            .map(|event| Dom::from_ref(&**event))
+            .collect();
+        Ok(event)
+    }
+
+    pub fn pointer_id(&self) -> i32 {
+        self.properties.borrow().pointer_id
+    }
+
+    /// Make the coalesced event list of this event contain this event itself.
+    /// <https://w3c.github.io/pointerevents/#dom-pointerevent-getcoalescedevents>
+    pub fn set_coalesces_itself(&self) {
+        self.coalesces_itself.set(true);
+    }
+}
+
+/// Fill in whichever of the tilt and the angles of a pointer that were not given from the
+/// other ones, or use the defaults of a pointer perpendicular to the screen.
+/// <https://w3c.github.io/pointerevents/#converting-between-tiltx-tilty-and-altitudeangle-azimuthangle>
+fn resolve_orientation(
+    tilt_x: Option<i32>,
+    tilt_y: Option<i32>,
+    altitude_angle: Option<f64>,
+    azimuth_angle: Option<f64>,
+) -> (i32, i32, f64, f64) {
+    if tilt_x.is_none() && tilt_y.is_none() && (altitude_angle.is_some() || azimuth_angle.is_some())
+    {
+        let altitude_angle = altitude_angle.unwrap_or(FRAC_PI_2);
+        let azimuth_angle = azimuth_angle.unwrap_or(0.0);
+        let (tilt_x, tilt_y) = spherical_to_tilt(altitude_angle, azimuth_angle);
+        return (tilt_x, tilt_y, altitude_angle, azimuth_angle);
+    }
+
+    let tilt_x = tilt_x.unwrap_or(0);
+    let tilt_y = tilt_y.unwrap_or(0);
+    let (computed_altitude, computed_azimuth) = tilt_to_spherical(tilt_x, tilt_y);
+    (
+        tilt_x,
+        tilt_y,
+        altitude_angle.unwrap_or(computed_altitude),
+        azimuth_angle.unwrap_or(computed_azimuth),
+    )
+}
+
+fn tilt_to_spherical(tilt_x: i32, tilt_y: i32) -> (f64, f64) {
+    let tilt_x_rad = (tilt_x as f64).to_radians();
+    let tilt_y_rad = (tilt_y as f64).to_radians();
+    let vertical = tilt_x.abs() == 90 || tilt_y.abs() == 90;
+
+    let azimuth = if tilt_x == 0 {
+        if tilt_y > 0 {
+            FRAC_PI_2
+        } else if tilt_y < 0 {
+            3.0 * FRAC_PI_2
+        } else {
+            0.0
+        }
+    } else if tilt_y == 0 {
+        if tilt_x < 0 {
+            PI
+        } else {
+            0.0
+        }
+    } else if vertical {
+        0.0
+    } else {
+        let azimuth = tilt_y_rad.tan().atan2(tilt_x_rad.tan());
+        if azimuth < 0.0 {
+            azimuth + 2.0 * PI
+        } else {
+            azimuth
+        }
+    };
+
+    let altitude = if vertical {
+        0.0
+    } else if tilt_x == 0 {
+        FRAC_PI_2 - tilt_y_rad.abs()
+    } else if tilt_y == 0 {
+        FRAC_PI_2 - tilt_x_rad.abs()
+    } else {
+        (1.0 / (tilt_x_rad.tan().powi(2) + tilt_y_rad.tan().powi(2)).sqrt()).atan()
+    };
+
+    (altitude, azimuth)
+}
+
+fn spherical_to_tilt(altitude: f64, azimuth: f64) -> (i32, i32) {
+    let (tilt_x_rad, tilt_y_rad) = if altitude == 0.0 {
+        if azimuth == 0.0 || azimuth == 2.0 * PI {
+            (FRAC_PI_2, 0.0)
+        } else if azimuth == FRAC_PI_2 {
+            (0.0, FRAC_PI_2)
+        } else if azimuth == PI {
+            (-FRAC_PI_2, 0.0)
+        } else if azimuth == 3.0 * FRAC_PI_2 {
+            (0.0, -FRAC_PI_2)
+        } else if azimuth < FRAC_PI_2 {
+            (FRAC_PI_2, FRAC_PI_2)
+        } else if azimuth < PI {
+            (-FRAC_PI_2, FRAC_PI_2)
+        } else if azimuth < 3.0 * FRAC_PI_2 {
+            (-FRAC_PI_2, -FRAC_PI_2)
+        } else {
+            (FRAC_PI_2, -FRAC_PI_2)
+        }
+    } else {
+        let tan_altitude = altitude.tan();
+        (
+            (azimuth.cos() / tan_altitude).atan(),
+            (azimuth.sin() / tan_altitude).atan(),
+        )
+    };
+    (
+        tilt_x_rad.to_degrees().round() as i32,
+        tilt_y_rad.to_degrees().round() as i32,
+    )
+}
+
+impl PointerEventMethods for PointerEvent {
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointerid
+    fn PointerId(&self) -> i32 {
+        self.properties.borrow().pointer_id
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-width
+    fn Width(&self) -> Finite<f64> {
+        Finite::wrap(self.properties.borrow().width)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-height
+    fn Height(&self) -> Finite<f64> {
+        Finite::wrap(self.properties.borrow().height)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pressure
+    fn Pressure(&self) -> Finite<f32> {
+        Finite::wrap(self.properties.borrow().pressure)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tangentialpressure
+    fn TangentialPressure(&self) -> Finite<f32> {
+        Finite::wrap(self.properties.borrow().tangential_pressure)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tiltx
+    fn TiltX(&self) -> i32 {
+        self.properties.borrow().tilt_x
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-tilty
+    fn TiltY(&self) -> i32 {
+        self.properties.borrow().tilt_y
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-twist
+    fn Twist(&self) -> i32 {
+        self.properties.borrow().twist
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-altitudeangle
+    fn AltitudeAngle(&self) -> Finite<f64> {
+        Finite::wrap(self.properties.borrow().altitude_angle)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-azimuthangle
+    fn AzimuthAngle(&self) -> Finite<f64> {
+        Finite::wrap(self.properties.borrow().azimuth_angle)
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-pointertype
+    fn PointerType(&self) -> DOMString {
+        self.properties.borrow().pointer_type.clone()
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-isprimary
+    fn IsPrimary(&self) -> bool {
+        self.properties.borrow().is_primary
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-getcoalescedevents
+    fn GetCoalescedEvents(&self) -> Vec<DomRoot<PointerEvent>> {
+        let mut events: Vec<_> = self
+            .coalesced_events
+            .borrow()
+            .iter()
+            .map(|event| DomRoot::from_ref(&**event))
+            .collect();
+        if self.coalesces_itself.get() && events.is_empty() {
+            events.push(DomRoot::from_ref(self));
+        }
+        events
+    }
+
+    // https://w3c.github.io/pointerevents/#dom-pointerevent-getpredictedevents
+    fn GetPredictedEvents(&self) -> Vec<DomRoot<PointerEvent>> {
+        self.predicted_events
+            .borrow()
+            .iter()
+            .map(|event| DomRoot::from_ref(&**event))
+            .collect()
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.mouseevent.IsTrusted()
+    }
+}
@@ -25,7 +25,7 @@ use js::jsapi::{
 use js::jsval::{Int32Value, JSVal, ObjectValue, UndefinedValue};
 use js::rust::wrappers::{
     AddPromiseReactions, CallOriginalPromiseReject, CallOriginalPromiseResolve, GetPromiseState,
-    IsPromiseObject, NewPromiseObject, RejectPromise, ResolvePromise,
+    IsPromiseObject, NewPromiseObject, RejectPromise, ResolvePromise, SetAnyPromiseIsHandled,
     SetPromiseUserInputEventHandlingState,
 };
 use js::rust::{HandleObject, HandleValue, MutableHandleObject, Runtime};
@@ -226,6 +226,13 @@ impl Promise {
         }
     }
 
+    /// Mark this promise as handled, so that its rejection is not reported as unhandled.
+    #[allow(unsafe_code)]
+    pub fn set_promise_is_handled(&self) -> bool {
+        let cx = GlobalScope::get_cx();
+        unsafe { SetAnyPromiseIsHandled(*cx, self.promise_obj()) }
+    }
+
     #[allow(unsafe_code)]
     pub fn is_fulfilled(&self) -> bool {
         let state = unsafe { GetPromiseState(self.promise_obj()) };
@@ -172,14 +172,21 @@ impl ReadableStream {
         let _ar = enter_realm(&*global);
         let cx = GlobalScope::get_cx();
 
+        rooted!(in(*cx) let mut js_error = UndefinedValue());
+        unsafe { error.to_jsval(*cx, &global, js_error.handle_mut()) };
+        self.error(js_error.handle());
+    }
+
+    /// Error the stream with an arbitrary JS value, such as the reason an `AbortSignal`
+    /// was aborted with.
+    #[allow(unsafe_code)]
+    pub fn error(&self, reason: SafeHandleValue) {
+        let global = self.global();
+        let _ar = enter_realm(&*global);
+        let cx = GlobalScope::get_cx();
+
         unsafe {
-            rooted!(in(*cx) let mut js_error = UndefinedValue());
-            error.to_jsval(*cx, &global, js_error.handle_mut());
-            ReadableStreamError(
-                *cx,
-                self.js_stream.handle(),
-                js_error.handle().into_handle(),
-            );
+            ReadableStreamError(*cx, self.js_stream.handle(), reason.into_handle());
         }
     }
 
@@ -0,0 +1,157 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use chrono::NaiveDateTime;
+use dom_struct::dom_struct;
+use serde_json::{json, Value};
+
+use crate::dom::bindings::codegen::Bindings::ReportBinding::ReportMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::deprecationreportbody::DeprecationReportBody;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::interventionreportbody::InterventionReportBody;
+use crate::dom::reportbody::ReportBody;
+
+/// Where the script whose doings a report is about was running.
+#[derive(Clone, JSTraceable, MallocSizeOf)]
+pub(crate) struct SourceLocation {
+    pub(crate) file: String,
+    pub(crate) line: u32,
+    pub(crate) column: u32,
+}
+
+/// What a report generated by the user agent tells, from which its body is made.
+pub(crate) enum ReportData {
+    /// The page uses a feature that is going to be removed, at the given time in
+    /// milliseconds since the epoch if it is known.
+    /// <https://wicg.github.io/deprecation-reporting/#deprecation-report>
+    Deprecation {
+        id: &'static str,
+        message: String,
+        anticipated_removal: Option<f64>,
+    },
+    /// The user agent did not do something the page asked for, for the user's sake.
+    /// <https://wicg.github.io/intervention-reporting/#intervention-report>
+    Intervention { id: &'static str, message: String },
+}
+
+impl ReportData {
+    pub(crate) fn type_(&self) -> &'static str {
+        match *self {
+            ReportData::Deprecation { .. } => "deprecation",
+            ReportData::Intervention { .. } => "intervention",
+        }
+    }
+
+    /// The body of the report, as observers get it.
+    pub(crate) fn body(
+        &self,
+        global: &GlobalScope,
+        location: Option<SourceLocation>,
+    ) -> DomRoot<ReportBody> {
+        match *self {
+            ReportData::Deprecation {
+                id,
+                ref message,
+                anticipated_removal,
+            } => DomRoot::upcast(DeprecationReportBody::new(
+                global,
+                DOMString::from(id),
+                anticipated_removal,
+                DOMString::from(message.clone()),
+                location,
+            )),
+            ReportData::Intervention { id, ref message } => {
+                DomRoot::upcast(InterventionReportBody::new(
+                    global,
+                    DOMString::from(id),
+                    DOMString::from(message.clone()),
+                    location,
+                ))
+            },
+        }
+    }
+
+    /// The body of the report, as it is sent to an endpoint: what the default `toJSON` of its
+    /// interface gives.
+    pub(crate) fn serialize(&self, location: Option<&SourceLocation>) -> String {
+        let (id, message) = match *self {
+            ReportData::Deprecation {
+                id, ref message, ..
+            } |
+            ReportData::Intervention { id, ref message } => (id, message),
+        };
+        let mut body = json!({
+            "id": id,
+            "message": message,
+            "sourceFile": location.map(|location| location.file.as_str()),
+            "lineNumber": location.map(|location| location.line),
+            "columnNumber": location.map(|location| location.column),
+        });
+        if let ReportData::Deprecation {
+            anticipated_removal,
+            ..
+        } = *self
+        {
+            // Dates are serialized as `Date.prototype.toJSON` does.
+            let anticipated_removal = anticipated_removal
+                .and_then(|time| NaiveDateTime::from_timestamp_millis(time as i64))
+                .map(|time| time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string());
+            body["anticipatedRemoval"] = anticipated_removal.map_or(Value::Null, Value::from);
+        }
+        body.to_string()
+    }
+}
+
+/// <https://w3c.github.io/reporting/#interface-report>
+#[dom_struct]
+pub struct Report {
+    reflector_: Reflector,
+    type_: DOMString,
+    url: DOMString,
+    body: Option<Dom<ReportBody>>,
+}
+
+impl Report {
+    fn new_inherited(type_: DOMString, url: DOMString, body: Option<&ReportBody>) -> Report {
+        Report {
+            reflector_: Reflector::new(),
+            type_,
+            url,
+            body: body.map(Dom::from_ref),
+        }
+    }
+
+    pub(crate) fn new(
+        global: &GlobalScope,
+        type_: DOMString,
+        url: DOMString,
+        body: Option<&ReportBody>,
+    ) -> DomRoot<Report> {
+        reflect_dom_object(Box::new(Report::new_inherited(type_, url, body)), global)
+    }
+
+    pub(crate) fn type_(&self) -> &DOMString {
+        &self.type_
+    }
+}
+
+impl ReportMethods for Report {
+    /// <https://w3c.github.io/reporting/#dom-report-type>
+    fn Type(&self) -> DOMString {
+        self.type_.clone()
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-report-url>
+    fn Url(&self) -> DOMString {
+        self.url.clone()
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-report-body>
+    fn GetBody(&self) -> Option<DomRoot<ReportBody>> {
+        self.body.as_ref().map(|body| DomRoot::from_ref(&**body))
+    }
+}
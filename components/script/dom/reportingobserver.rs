@@ -0,0 +1,143 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ReportingObserverBinding::{
+    ReportingObserverCallback, ReportingObserverMethods, ReportingObserverOptions,
+};
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::report::Report;
+use crate::task_source::TaskSource;
+
+/// <https://w3c.github.io/reporting/#interface-reporting-observer>
+#[dom_struct]
+pub struct ReportingObserver {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Rc<ReportingObserverCallback>,
+    /// The types of the reports the observer is notified of, or every type if empty.
+    types: Vec<DOMString>,
+    /// Whether the observer gets the reports generated before it observes, which it only does
+    /// the first time.
+    buffered: Cell<bool>,
+    /// <https://w3c.github.io/reporting/#reportingobserver-report-queue>
+    report_queue: DomRefCell<Vec<Dom<Report>>>,
+}
+
+impl ReportingObserver {
+    fn new_inherited(
+        callback: Rc<ReportingObserverCallback>,
+        options: &ReportingObserverOptions,
+    ) -> ReportingObserver {
+        ReportingObserver {
+            reflector_: Reflector::new(),
+            callback,
+            types: options.types.clone().unwrap_or_default(),
+            buffered: Cell::new(options.buffered),
+            report_queue: Default::default(),
+        }
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-reportingobserver-reportingobserver>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        callback: Rc<ReportingObserverCallback>,
+        options: &ReportingObserverOptions,
+    ) -> DomRoot<ReportingObserver> {
+        reflect_dom_object_with_proto(
+            Box::new(ReportingObserver::new_inherited(callback, options)),
+            global,
+            proto,
+        )
+    }
+
+    /// <https://w3c.github.io/reporting/#add-report>
+    pub(crate) fn add_report(&self, report: &Report) {
+        // Step 1. If the observer is not interested in the type of the report, return.
+        if !self.types.is_empty() && !self.types.contains(report.type_()) {
+            return;
+        }
+
+        // Step 2-3. Append the report to the report queue.
+        let mut report_queue = self.report_queue.borrow_mut();
+        report_queue.push(Dom::from_ref(report));
+
+        // Step 4. If it is the only report in the queue, queue a task to invoke the observer.
+        if report_queue.len() != 1 {
+            return;
+        }
+        let this = Trusted::new(self);
+        let global = self.global();
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(invoke_reporting_observer: move || {
+                this.root().invoke();
+            }),
+            &global,
+        );
+    }
+
+    /// <https://w3c.github.io/reporting/#invoke-reporting-observers>
+    fn invoke(&self) {
+        let reports = self.TakeRecords();
+        if reports.is_empty() {
+            return;
+        }
+        let _ = self
+            .callback
+            .Call_(self, reports, self, ExceptionHandling::Report);
+    }
+}
+
+impl ReportingObserverMethods for ReportingObserver {
+    /// <https://w3c.github.io/reporting/#dom-reportingobserver-observe>
+    fn Observe(&self) {
+        // Step 1-2. Append the observer to the registered observers of its global.
+        let global = self.global();
+        global.register_reporting_observer(self);
+
+        // Step 3-4. Unless it asks for them, the reports generated before are not added.
+        if !self.buffered.replace(false) {
+            return;
+        }
+
+        // Step 5. Add the reports of the report buffer of the global, in a task.
+        let this = Trusted::new(self);
+        let _ = global.dom_manipulation_task_source().queue(
+            task!(add_buffered_reports: move || {
+                let this = this.root();
+                for report in this.global().buffered_reports() {
+                    this.add_report(&report);
+                }
+            }),
+            &global,
+        );
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-reportingobserver-disconnect>
+    fn Disconnect(&self) {
+        self.global().unregister_reporting_observer(self);
+    }
+
+    /// <https://w3c.github.io/reporting/#dom-reportingobserver-takerecords>
+    fn TakeRecords(&self) -> Vec<DomRoot<Report>> {
+        self.report_queue
+            .borrow_mut()
+            .drain(..)
+            .map(|report| DomRoot::from_ref(&*report))
+            .collect()
+    }
+}
@@ -17,18 +17,20 @@ use net_traits::request::{
     CacheMode as NetTraitsRequestCache, CredentialsMode as NetTraitsRequestCredentials,
     Destination as NetTraitsRequestDestination, Origin, RedirectMode as NetTraitsRequestRedirect,
     Referrer as NetTraitsRequestReferrer, Request as NetTraitsRequest,
-    RequestMode as NetTraitsRequestMode, Window,
+    RequestMode as NetTraitsRequestMode, RequestPriority as NetTraitsRequestPriority, Window,
 };
 use net_traits::ReferrerPolicy as MsgReferrerPolicy;
 use servo_url::ServoUrl;
 
 use crate::body::{consume_body, BodyMixin, BodyType, Extractable};
+use crate::dom::abortsignal::AbortSignal;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::HeadersBinding::{HeadersInit, HeadersMethods};
 use crate::dom::bindings::codegen::Bindings::RequestBinding::{
     ReferrerPolicy, RequestCache, RequestCredentials, RequestDestination, RequestInfo, RequestInit,
-    RequestMethods, RequestMode, RequestRedirect,
+    RequestMethods, RequestMode, RequestPriority, RequestRedirect,
 };
+use crate::dom::bindings::codegen::Bindings::XMLHttpRequestBinding::BodyInit;
 use crate::dom::bindings::error::{Error, Fallible};
 use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
 use crate::dom::bindings::root::{DomRoot, MutNullableDom};
@@ -47,6 +49,7 @@ pub struct Request {
     request: DomRefCell<NetTraitsRequest>,
     body_stream: MutNullableDom<ReadableStream>,
     headers: MutNullableDom<Headers>,
+    signal: MutNullableDom<AbortSignal>,
 }
 
 impl Request {
@@ -56,6 +59,7 @@ impl Request {
             request: DomRefCell::new(net_request_from_global(global, url)),
             body_stream: MutNullableDom::new(None),
             headers: Default::default(),
+            signal: Default::default(),
         }
     }
 
@@ -80,7 +84,8 @@ impl Request {
         // Step 3
         let base_url = global.api_base_url();
 
-        // Step 4 TODO: "Let signal be null."
+        // Step 4
+        let mut signal: Option<DomRoot<AbortSignal>> = None;
 
         match input {
             // Step 5
@@ -110,7 +115,8 @@ impl Request {
                 }
                 // Step 6.1
                 temporary_request = input_request.request.borrow().clone();
-                // Step 6.2 TODO: "Set signal to input's signal."
+                // Step 6.2
+                signal = Some(input_request.Signal());
             },
         }
 
@@ -150,6 +156,7 @@ impl Request {
         request.cache_mode = temporary_request.cache_mode;
         request.redirect_mode = temporary_request.redirect_mode;
         request.integrity_metadata = temporary_request.integrity_metadata;
+        request.priority = temporary_request.priority;
 
         // Step 13
         if init.body.is_some() ||
@@ -159,6 +166,7 @@ impl Request {
             init.headers.is_some() ||
             init.method.is_some() ||
             init.mode.is_some() ||
+            init.priority.is_some() ||
             init.redirect.is_some() ||
             init.referrer.is_some() ||
             init.referrerPolicy.is_some() ||
@@ -262,7 +270,10 @@ impl Request {
             request.integrity_metadata = integrity;
         }
 
-        // Step 24 TODO: "If init["keepalive"] exists..."
+        // Step 24
+        if let Some(keepalive) = init.keepalive {
+            request.keep_alive = keepalive;
+        }
 
         // Step 25.1
         if let Some(init_method) = init.method.as_ref() {
@@ -283,14 +294,23 @@ impl Request {
             request.method = method;
         }
 
-        // Step 26 TODO: "If init["signal"] exists..."
-        // Step 27 TODO: "If init["priority"] exists..."
+        // Step 26
+        if let Some(init_signal) = init.signal.as_ref() {
+            signal = init_signal.clone();
+        }
+        // Step 27
+        if let Some(init_priority) = init.priority.as_ref() {
+            request.priority = init_priority.clone().into();
+        }
 
         // Step 28
         let r = Request::from_net_request(global, proto, request);
 
-        // Step 29 TODO: "Set this's signal to new AbortSignal object..."
-        // Step 30 TODO: "If signal is not null..."
+        // Step 29-30. Set this's signal to a dependent abort signal following signal,
+        // if it is not null.
+        let signals: Vec<_> = signal.into_iter().collect();
+        r.signal
+            .set(Some(&AbortSignal::new_dependent(global, &signals)));
 
         // Step 31
         // "or_init" looks unclear here, but it always enters the block since r
@@ -382,7 +402,15 @@ impl Request {
 
         // Step 36-37
         if let Some(Some(ref init_body)) = init.body {
-            // Step 37.1 TODO "If init["keepalive"] exists and is true..."
+            // Step 37.1 A keep-alive request outlives its global, so its body cannot be a
+            // stream the global reads from.
+            if init.keepalive == Some(true) {
+                if let BodyInit::ReadableStream(_) = init_body {
+                    return Err(Error::Type(
+                        "Keep-alive request body cannot be a stream".to_string(),
+                    ));
+                }
+            }
 
             // Step 37.2
             let mut extracted_body = init_body.extract(global)?;
@@ -459,6 +487,8 @@ impl Request {
         *r_clone.request.borrow_mut() = req.clone();
         r_clone.Headers().copy_from_headers(r.Headers())?;
         r_clone.Headers().set_guard(headers_guard);
+        let signal = AbortSignal::new_dependent(&r.global(), &[r.Signal()]);
+        r_clone.signal.set(Some(&signal));
         Ok(r_clone)
     }
 
@@ -599,6 +629,16 @@ impl RequestMethods for Request {
         DOMString::from_string(r.integrity_metadata.clone())
     }
 
+    /// <https://fetch.spec.whatwg.org/#dom-request-keepalive>
+    fn Keepalive(&self) -> bool {
+        self.request.borrow().keep_alive
+    }
+
+    // https://fetch.spec.whatwg.org/#dom-request-signal
+    fn Signal(&self) -> DomRoot<AbortSignal> {
+        self.signal.or_init(|| AbortSignal::new(&self.global()))
+    }
+
     /// <https://fetch.spec.whatwg.org/#dom-body-body>
     fn GetBody(&self, _cx: SafeJSContext) -> Option<NonNull<JSObject>> {
         self.body().map(|stream| stream.get_js_stream())
@@ -855,6 +895,16 @@ impl Into<RequestRedirect> for NetTraitsRequestRedirect {
     }
 }
 
+impl Into<NetTraitsRequestPriority> for RequestPriority {
+    fn into(self) -> NetTraitsRequestPriority {
+        match self {
+            RequestPriority::High => NetTraitsRequestPriority::High,
+            RequestPriority::Low => NetTraitsRequestPriority::Low,
+            RequestPriority::Auto => NetTraitsRequestPriority::Auto,
+        }
+    }
+}
+
 impl Clone for HeadersInit {
     fn clone(&self) -> HeadersInit {
         match self {
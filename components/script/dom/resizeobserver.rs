@@ -0,0 +1,340 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use euclid::default::{Point2D, Rect, Size2D};
+use js::rust::HandleObject;
+use style::properties::{LonghandId, PropertyId};
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::ResizeObserverBinding::{
+    ResizeObserverBoxOptions, ResizeObserverCallback, ResizeObserverMethods, ResizeObserverOptions,
+};
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::domrectreadonly::DOMRectReadOnly;
+use crate::dom::element::Element;
+use crate::dom::node::{document_from_node, window_from_node, Node, ShadowIncluding};
+use crate::dom::resizeobserverentry::ResizeObserverEntry;
+use crate::dom::resizeobserversize::{ResizeObserverSize, ResizeObserverSizeImpl};
+use crate::dom::window::Window;
+
+/// <https://drafts.csswg.org/resize-observer/#calculate-depth-for-node>
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+pub struct ResizeObservationDepth(usize);
+
+impl ResizeObservationDepth {
+    pub fn max() -> ResizeObservationDepth {
+        ResizeObservationDepth(usize::MAX)
+    }
+}
+
+/// <https://drafts.csswg.org/resize-observer/#resize-observer-slots>
+#[dom_struct]
+pub struct ResizeObserver {
+    reflector_: Reflector,
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-callback-slot>
+    #[ignore_malloc_size_of = "can't measure Rc values"]
+    callback: Rc<ResizeObserverCallback>,
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-observationtargets-slot>
+    ///
+    /// The observations in `activeTargets` are those flagged as active.
+    observation_targets: DomRefCell<Vec<ResizeObservation>>,
+    /// Whether `skippedTargets` is not empty, which is all that is needed of it.
+    ///
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-skippedtargets-slot>
+    has_skipped_targets: Cell<bool>,
+}
+
+impl ResizeObserver {
+    fn new_inherited(callback: Rc<ResizeObserverCallback>) -> ResizeObserver {
+        ResizeObserver {
+            reflector_: Reflector::new(),
+            callback,
+            observation_targets: Default::default(),
+            has_skipped_targets: Cell::new(false),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        callback: Rc<ResizeObserverCallback>,
+    ) -> DomRoot<ResizeObserver> {
+        let observer = Box::new(ResizeObserver::new_inherited(callback));
+        reflect_dom_object_with_proto(observer, window, proto)
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-resizeobserver>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        callback: Rc<ResizeObserverCallback>,
+    ) -> DomRoot<ResizeObserver> {
+        ResizeObserver::new(window, proto, callback)
+    }
+
+    /// Steps 2 and 3 of
+    /// <https://drafts.csswg.org/resize-observer/#gather-active-observations-h>,
+    /// for this observer. Returns whether any of its observations is active.
+    pub fn gather_active_resize_observations_at_depth(
+        &self,
+        depth: ResizeObservationDepth,
+    ) -> bool {
+        let mut has_active = false;
+        self.has_skipped_targets.set(false);
+        for observation in self.observation_targets.borrow_mut().iter_mut() {
+            observation.active = false;
+            if !observation.is_active() {
+                continue;
+            }
+            if calculate_depth_for_node(&observation.target) > depth {
+                observation.active = true;
+                has_active = true;
+            } else {
+                self.has_skipped_targets.set(true);
+            }
+        }
+        has_active
+    }
+
+    /// Step 2 of <https://drafts.csswg.org/resize-observer/#broadcast-active-resize-observations>,
+    /// for this observer. Returns the depth of its shallowest active target.
+    pub fn broadcast_active_resize_observations(&self) -> ResizeObservationDepth {
+        let mut shallowest_target_depth = ResizeObservationDepth::max();
+
+        // Step 2.2
+        let mut entries: Vec<DomRoot<ResizeObserverEntry>> = vec![];
+        for observation in self.observation_targets.borrow_mut().iter_mut() {
+            // Step 2.3
+            if !observation.active {
+                continue;
+            }
+            observation.active = false;
+            let target = &observation.target;
+            let window = window_from_node(&**target);
+
+            // Steps 2.3.1 and 2.3.2
+            let border_box = calculate_box_size(target, ResizeObserverBoxOptions::Border_box);
+            let content_box = calculate_box_size(target, ResizeObserverBoxOptions::Content_box);
+            let device_pixel_content_box =
+                calculate_box_size(target, ResizeObserverBoxOptions::Device_pixel_content_box);
+            let content_rect = calculate_content_rect(target);
+            let content_rect = DOMRectReadOnly::new(
+                window.upcast(),
+                None,
+                content_rect.origin.x,
+                content_rect.origin.y,
+                content_rect.size.width,
+                content_rect.size.height,
+            );
+            let entry = ResizeObserverEntry::new(
+                &window,
+                target,
+                &content_rect,
+                &[&*ResizeObserverSize::new(&window, border_box)],
+                &[&*ResizeObserverSize::new(&window, content_box)],
+                &[&*ResizeObserverSize::new(&window, device_pixel_content_box)],
+            );
+
+            // Step 2.3.3
+            entries.push(entry);
+
+            // Step 2.3.4
+            observation.last_reported_size = match observation.observed_box {
+                ResizeObserverBoxOptions::Border_box => border_box,
+                ResizeObserverBoxOptions::Content_box => content_box,
+                ResizeObserverBoxOptions::Device_pixel_content_box => device_pixel_content_box,
+            };
+
+            // Steps 2.3.5 and 2.3.6
+            shallowest_target_depth = shallowest_target_depth.min(calculate_depth_for_node(target));
+        }
+
+        // Step 2.4
+        if !entries.is_empty() {
+            let _ = self
+                .callback
+                .Call_(self, entries, self, ExceptionHandling::Report);
+        }
+
+        shallowest_target_depth
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#has-skipped-observations-h>, for this observer.
+    pub fn has_skipped_resize_observations(&self) -> bool {
+        self.has_skipped_targets.get()
+    }
+}
+
+impl ResizeObserverMethods for ResizeObserver {
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-observe>
+    #[allow(crown::unrooted_must_root)]
+    fn Observe(&self, target: &Element, options: &ResizeObserverOptions) {
+        // The observers of a document are those observing at least one of its elements.
+        document_from_node(target).add_resize_observer(self);
+
+        // Step 1
+        let mut observation_targets = self.observation_targets.borrow_mut();
+        if let Some(index) = observation_targets
+            .iter()
+            .position(|observation| &*observation.target == target)
+        {
+            observation_targets.remove(index);
+        }
+
+        // Steps 2 to 4
+        observation_targets.push(ResizeObservation::new(target, options.box_));
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-unobserve>
+    fn Unobserve(&self, target: &Element) {
+        self.observation_targets
+            .borrow_mut()
+            .retain(|observation| &*observation.target != target);
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobserver-disconnect>
+    fn Disconnect(&self) {
+        self.observation_targets.borrow_mut().clear();
+        self.has_skipped_targets.set(false);
+    }
+}
+
+/// <https://drafts.csswg.org/resize-observer/#resizeobservation>
+#[derive(JSTraceable, MallocSizeOf)]
+#[crown::unrooted_must_root_lint::must_root]
+struct ResizeObservation {
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobservation-target>
+    target: Dom<Element>,
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobservation-observedbox>
+    observed_box: ResizeObserverBoxOptions,
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobservation-lastreportedsizes>
+    ///
+    /// An element only ever has one box here, as fragments are not reported separately.
+    last_reported_size: ResizeObserverSizeImpl,
+    /// Whether the observation was gathered as active, and is to be broadcast.
+    active: bool,
+}
+
+impl ResizeObservation {
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobservation-resizeobservation>
+    #[allow(crown::unrooted_must_root)]
+    fn new(target: &Element, observed_box: ResizeObserverBoxOptions) -> ResizeObservation {
+        ResizeObservation {
+            target: Dom::from_ref(target),
+            observed_box,
+            last_reported_size: ResizeObserverSizeImpl::default(),
+            active: false,
+        }
+    }
+
+    /// <https://drafts.csswg.org/resize-observer/#dom-resizeobservation-isactive>
+    fn is_active(&self) -> bool {
+        calculate_box_size(&self.target, self.observed_box) != self.last_reported_size
+    }
+}
+
+/// <https://drafts.csswg.org/resize-observer/#calculate-depth-for-node>
+fn calculate_depth_for_node(target: &Element) -> ResizeObservationDepth {
+    let ancestors = target
+        .upcast::<Node>()
+        .inclusive_ancestors(ShadowIncluding::Yes)
+        .count();
+    ResizeObservationDepth(ancestors - 1)
+}
+
+/// <https://drafts.csswg.org/resize-observer/#calculate-box-size>
+fn calculate_box_size(
+    target: &Element,
+    observed_box: ResizeObserverBoxOptions,
+) -> ResizeObserverSizeImpl {
+    // TODO: SVG elements are observed through their bounding box.
+    let size = match observed_box {
+        ResizeObserverBoxOptions::Border_box => border_box_size(target),
+        ResizeObserverBoxOptions::Content_box => content_box_size(target),
+        ResizeObserverBoxOptions::Device_pixel_content_box => {
+            let device_pixel_ratio = window_from_node(target).device_pixel_ratio().get() as f64;
+            (content_box_size(target) * device_pixel_ratio).round()
+        },
+    };
+
+    let vertical = target
+        .style()
+        .map_or(false, |style| style.writing_mode.is_vertical());
+    if vertical {
+        ResizeObserverSizeImpl {
+            inline_size: size.height,
+            block_size: size.width,
+        }
+    } else {
+        ResizeObserverSizeImpl {
+            inline_size: size.width,
+            block_size: size.height,
+        }
+    }
+}
+
+/// The size of the border box of `target`, which is empty when it is not rendered.
+fn border_box_size(target: &Element) -> Size2D<f64> {
+    window_from_node(target)
+        .content_box_query(target.upcast())
+        .map_or_else(Size2D::zero, |rect| {
+            Size2D::new(rect.size.width.to_f64_px(), rect.size.height.to_f64_px())
+        })
+}
+
+/// The size of the content box of `target`, which is empty when it is not rendered.
+fn content_box_size(target: &Element) -> Size2D<f64> {
+    if !is_rendered(target) {
+        return Size2D::zero();
+    }
+    Size2D::new(
+        used_value(target, LonghandId::Width),
+        used_value(target, LonghandId::Height),
+    )
+}
+
+/// The content box of `target`, relative to its padding box as in
+/// <https://drafts.csswg.org/resize-observer/#dom-resizeobserverentry-contentrect>, which is
+/// empty when it is not rendered.
+fn calculate_content_rect(target: &Element) -> Rect<f64> {
+    if !is_rendered(target) {
+        return Rect::zero();
+    }
+    Rect::new(
+        Point2D::new(
+            used_value(target, LonghandId::PaddingLeft),
+            used_value(target, LonghandId::PaddingTop),
+        ),
+        Size2D::new(
+            used_value(target, LonghandId::Width),
+            used_value(target, LonghandId::Height),
+        ),
+    )
+}
+
+/// Whether `target` has a box, without which the box model properties resolve to their
+/// computed values rather than the used ones.
+fn is_rendered(target: &Element) -> bool {
+    window_from_node(target)
+        .content_box_query(target.upcast())
+        .is_some()
+}
+
+/// The used value of a box model property of `target`, in CSS pixels, as resolved by layout.
+fn used_value(target: &Element, longhand: LonghandId) -> f64 {
+    let address = target.upcast::<Node>().to_trusted_node_address();
+    window_from_node(target)
+        .resolved_style_query(address, None, PropertyId::Longhand(longhand))
+        .strip_suffix("px")
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or(0.)
+}
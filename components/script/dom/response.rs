@@ -11,7 +11,7 @@ use http::header::HeaderMap as HyperHeaders;
 use http::StatusCode;
 use hyper_serde::Serde;
 use js::jsapi::JSObject;
-use js::rust::HandleObject;
+use js::rust::{HandleObject, HandleValue};
 use servo_url::ServoUrl;
 use url::Position;
 
@@ -219,6 +219,12 @@ impl Response {
             body.error_native(error);
         }
     }
+
+    pub fn error_stream_with_reason(&self, reason: HandleValue) {
+        if let Some(body) = self.body_stream.get() {
+            body.error(reason);
+        }
+    }
 }
 
 impl BodyMixin for Response {
@@ -63,14 +63,14 @@ impl RTCDataChannel {
         let mut init: DataChannelInit = options.into();
         init.label = label.to_string();
 
-        let controller = peer_connection.get_webrtc_controller().borrow();
-        let servo_media_id = servo_media_id.unwrap_or(
+        let servo_media_id = servo_media_id.unwrap_or_else(|| {
+            let controller = peer_connection.get_webrtc_controller().borrow();
             controller
                 .as_ref()
                 .unwrap()
                 .create_data_channel(init)
-                .expect("Expected data channel id"),
-        );
+                .expect("Expected data channel id")
+        });
 
         let channel = RTCDataChannel {
             eventtarget: EventTarget::new_inherited(),
@@ -112,7 +112,12 @@ impl RTCDataChannel {
         rtc_data_channel
     }
 
+    /// <https://www.w3.org/TR/webrtc/#announce-the-rtcdatachannel-as-open>
     pub fn on_open(&self) {
+        if self.ready_state.get() == RTCDataChannelState::Closed {
+            return;
+        }
+        self.ready_state.set(RTCDataChannelState::Open);
         let event = Event::new(
             &self.global(),
             atom!("open"),
@@ -123,6 +128,7 @@ impl RTCDataChannel {
     }
 
     pub fn on_close(&self) {
+        self.ready_state.set(RTCDataChannelState::Closed);
         let event = Event::new(
             &self.global(),
             atom!("close"),
@@ -256,12 +262,6 @@ enum SendSource<'a, 'b> {
 impl RTCDataChannelMethods for RTCDataChannel {
     // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onopen
     event_handler!(open, GetOnopen, SetOnopen);
-    // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onbufferedamountlow
-    event_handler!(
-        bufferedamountlow,
-        GetOnbufferedamountlow,
-        SetOnbufferedamountlow
-    );
     // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onerror
     event_handler!(error, GetOnerror, SetOnerror);
     // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-onclosing
@@ -310,13 +310,6 @@ impl RTCDataChannelMethods for RTCDataChannel {
         self.ready_state.get()
     }
 
-    // XXX We need a way to know when the underlying data transport
-    // actually sends data from its queue to decrease buffered amount.
-
-    //    fn BufferedAmount(&self) -> u32;
-    //    fn BufferedAmountLowThreshold(&self) -> u32;
-    //    fn SetBufferedAmountLowThreshold(&self, value: u32) -> ();
-
     // https://www.w3.org/TR/webrtc/#dom-rtcdatachannel-close
     fn Close(&self) {
         let controller = self.peer_connection.get_webrtc_controller().borrow();
@@ -333,7 +326,7 @@ impl RTCDataChannelMethods for RTCDataChannel {
 
     // https://www.w3.org/TR/webrtc/#dom-datachannel-binarytype
     fn SetBinaryType(&self, value: DOMString) -> Fallible<()> {
-        if value != "blob" || value != "arraybuffer" {
+        if value != "blob" && value != "arraybuffer" {
             return Err(Error::Syntax);
         }
         *self.binary_type.borrow_mut() = value;
@@ -8,6 +8,7 @@ use std::rc::Rc;
 
 use dom_struct::dom_struct;
 use js::rust::HandleObject;
+use servo_atoms::Atom;
 use servo_media::streams::registry::MediaStreamId;
 use servo_media::streams::MediaStreamType;
 use servo_media::webrtc::{
@@ -22,8 +23,8 @@ use crate::dom::bindings::codegen::Bindings::RTCDataChannelBinding::RTCDataChann
 use crate::dom::bindings::codegen::Bindings::RTCIceCandidateBinding::RTCIceCandidateInit;
 use crate::dom::bindings::codegen::Bindings::RTCPeerConnectionBinding::{
     RTCAnswerOptions, RTCBundlePolicy, RTCConfiguration, RTCIceConnectionState,
-    RTCIceGatheringState, RTCOfferOptions, RTCPeerConnectionMethods, RTCRtpTransceiverInit,
-    RTCSignalingState,
+    RTCIceGatheringState, RTCOfferOptions, RTCPeerConnectionMethods, RTCPeerConnectionState,
+    RTCRtpTransceiverInit, RTCSignalingState,
 };
 use crate::dom::bindings::codegen::Bindings::RTCSessionDescriptionBinding::{
     RTCSdpType, RTCSessionDescriptionInit,
@@ -72,6 +73,7 @@ pub struct RTCPeerConnection {
     remote_description: MutNullableDom<RTCSessionDescription>,
     gathering_state: Cell<RTCIceGatheringState>,
     ice_connection_state: Cell<RTCIceConnectionState>,
+    connection_state: Cell<RTCPeerConnectionState>,
     signaling_state: Cell<RTCSignalingState>,
     #[ignore_malloc_size_of = "defined in servo-media"]
     data_channels: DomRefCell<HashMap<DataChannelId, Dom<RTCDataChannel>>>,
@@ -188,6 +190,7 @@ impl RTCPeerConnection {
             remote_description: Default::default(),
             gathering_state: Cell::new(RTCIceGatheringState::New),
             ice_connection_state: Cell::new(RTCIceConnectionState::New),
+            connection_state: Cell::new(RTCPeerConnectionState::New),
             signaling_state: Cell::new(RTCSignalingState::Stable),
             data_channels: DomRefCell::new(HashMap::new()),
         }
@@ -426,6 +429,36 @@ impl RTCPeerConnection {
             EventCancelable::NotCancelable,
         );
         event.upcast::<Event>().fire(self.upcast());
+
+        // step 6
+        self.update_connection_state(state.into());
+    }
+
+    /// <https://www.w3.org/TR/webrtc/#update-the-connection-state>
+    fn update_connection_state(&self, state: RTCPeerConnectionState) {
+        // step 1
+        if self.closed.get() {
+            return;
+        }
+
+        // step 2 (the DTLS transport is not exposed, so the state only derives from ICE)
+
+        // step 3
+        if state == self.connection_state.get() {
+            return;
+        }
+
+        // step 4
+        self.connection_state.set(state);
+
+        // step 5
+        let event = Event::new(
+            &self.global(),
+            Atom::from("connectionstatechange"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+        );
+        event.upcast::<Event>().fire(self.upcast());
     }
 
     fn update_signaling_state(&self, state: SignalingState) {
@@ -550,6 +583,13 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         SetOnsignalingstatechange
     );
 
+    // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-onconnectionstatechange
+    event_handler!(
+        connectionstatechange,
+        GetOnconnectionstatechange,
+        SetOnconnectionstatechange
+    );
+
     // https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-ondatachannel
     event_handler!(datachannel, GetOndatachannel, SetOndatachannel);
 
@@ -723,6 +763,11 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         self.ice_connection_state.get()
     }
 
+    /// <https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-connectionstate>
+    fn ConnectionState(&self) -> RTCPeerConnectionState {
+        self.connection_state.get()
+    }
+
     /// <https://www.w3.org/TR/webrtc/#dom-rtcpeerconnection-signalingstate>
     fn SignalingState(&self) -> RTCSignalingState {
         self.signaling_state.get()
@@ -754,8 +799,8 @@ impl RTCPeerConnectionMethods for RTCPeerConnection {
         // Step 11
         self.ice_connection_state.set(RTCIceConnectionState::Closed);
 
-        // Step 11
-        // (no current support for connection state)
+        // Step 12
+        self.connection_state.set(RTCPeerConnectionState::Closed);
     }
 
     /// <https://www.w3.org/TR/webrtc/#dom-peerconnection-createdatachannel>
@@ -831,6 +876,21 @@ impl From<IceConnectionState> for RTCIceConnectionState {
     }
 }
 
+impl From<RTCIceConnectionState> for RTCPeerConnectionState {
+    fn from(state: RTCIceConnectionState) -> Self {
+        match state {
+            RTCIceConnectionState::New => RTCPeerConnectionState::New,
+            RTCIceConnectionState::Checking => RTCPeerConnectionState::Connecting,
+            RTCIceConnectionState::Connected | RTCIceConnectionState::Completed => {
+                RTCPeerConnectionState::Connected
+            },
+            RTCIceConnectionState::Disconnected => RTCPeerConnectionState::Disconnected,
+            RTCIceConnectionState::Failed => RTCPeerConnectionState::Failed,
+            RTCIceConnectionState::Closed => RTCPeerConnectionState::Closed,
+        }
+    }
+}
+
 impl From<SignalingState> for RTCSignalingState {
     fn from(state: SignalingState) -> Self {
         match state {
@@ -6,6 +6,7 @@ use dom_struct::dom_struct;
 use euclid::Size2D;
 use profile_traits::ipc;
 use script_traits::ScriptMsg;
+use servo_config::pref;
 use style_traits::CSSPixel;
 use webrender_api::units::DeviceIntSize;
 
@@ -35,7 +36,16 @@ impl Screen {
         reflect_dom_object(Box::new(Screen::new_inherited(window)), window)
     }
 
+    /// The size of the viewport, which is what the screen is said to be while resisting
+    /// fingerprinting, so that sites do not learn more than they can already.
+    fn viewport_size(&self) -> Size2D<u32, CSSPixel> {
+        self.window.window_size().initial_viewport.to_u32()
+    }
+
     fn screen_size(&self) -> Size2D<u32, CSSPixel> {
+        if pref!(privacy.resist_fingerprinting.enabled) {
+            return self.viewport_size();
+        }
         let (send, recv) =
             ipc::channel::<DeviceIntSize>(self.global().time_profiler_chan().clone()).unwrap();
         self.window
@@ -49,6 +59,9 @@ impl Screen {
     }
 
     fn screen_avail_size(&self) -> Size2D<u32, CSSPixel> {
+        if pref!(privacy.resist_fingerprinting.enabled) {
+            return self.viewport_size();
+        }
         let (send, recv) =
             ipc::channel::<DeviceIntSize>(self.global().time_profiler_chan().clone()).unwrap();
         self.window
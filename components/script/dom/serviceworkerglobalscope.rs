@@ -18,32 +18,41 @@ use msg::constellation_msg::PipelineId;
 use net_traits::request::{CredentialsMode, Destination, ParserMetadata, Referrer, RequestBuilder};
 use net_traits::{CustomResponseMediator, IpcSend};
 use parking_lot::Mutex;
-use script_traits::{ScopeThings, ServiceWorkerMsg, WorkerGlobalScopeInit, WorkerScriptLoadOrigin};
+use script_traits::{
+    NavigationPreloadState, ScopeThings, ServiceWorkerMsg, WorkerGlobalScopeInit,
+    WorkerScriptLoadOrigin,
+};
 use servo_config::pref;
 use servo_rand::random;
 use servo_url::ServoUrl;
 use style::thread_state::{self, ThreadState};
+use uuid::Uuid;
 
 use crate::devtools;
 use crate::dom::abstractworker::WorkerScriptMsg;
 use crate::dom::abstractworkerglobalscope::{run_worker_event_loop, WorkerEventLoopMethods};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::{RequestInfo, RequestInit};
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding;
 use crate::dom::bindings::codegen::Bindings::ServiceWorkerGlobalScopeBinding::ServiceWorkerGlobalScopeMethods;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
 use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::root::{DomRoot, RootCollection, ThreadLocalStackRoots};
-use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::str::{DOMString, USVString};
 use crate::dom::bindings::structuredclone;
+use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::dedicatedworkerglobalscope::AutoWorkerReset;
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::extendableevent::ExtendableEvent;
 use crate::dom::extendablemessageevent::ExtendableMessageEvent;
+use crate::dom::fetchevent::FetchEvent;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::identityhub::Identities;
+use crate::dom::promise::Promise;
+use crate::dom::request::Request;
 use crate::dom::worker::TrustedWorkerAddress;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
-use crate::fetch::load_whole_resource;
+use crate::fetch::{fetch_navigation_preload, load_whole_resource};
 use crate::realms::{enter_realm, AlreadyInRealm, InRealm};
 use crate::script_runtime::{
     new_rt_and_cx, CommonScriptMsg, ContextForRequestInterrupt, JSContext as SafeJSContext,
@@ -56,8 +65,9 @@ use crate::task_source::TaskSourceName;
 pub enum ServiceWorkerScriptMsg {
     /// Message common to all workers
     CommonWorker(WorkerScriptMsg),
-    /// Message to request a custom response by the service worker
-    Response(CustomResponseMediator),
+    /// Message to request a custom response by the service worker, with the navigation
+    /// preload state of its registration if the request is a navigation to preload.
+    Response(CustomResponseMediator, Option<NavigationPreloadState>),
     /// Wake-up call from the task queue.
     WakeUp,
 }
@@ -465,12 +475,8 @@ impl ServiceWorkerGlobalScope {
             CommonWorker(WorkerScriptMsg::Common(msg)) => {
                 self.upcast::<WorkerGlobalScope>().process_event(msg);
             },
-            Response(mediator) => {
-                // TODO XXXcreativcoder This will eventually use a FetchEvent interface to fire event
-                // when we have the Request and Response dom api's implemented
-                // https://w3c.github.io/ServiceWorker/#fetchevent-interface
-                self.upcast::<EventTarget>().fire_event(atom!("fetch"));
-                let _ = mediator.response_chan.send(None);
+            Response(mediator, preload) => {
+                self.dispatch_fetch(mediator, preload);
             },
             WakeUp => {},
         }
@@ -482,6 +488,63 @@ impl ServiceWorkerGlobalScope {
         })
     }
 
+    /// <https://w3c.github.io/ServiceWorker/#handle-fetch> steps 15 and 24. The response
+    /// is sent to the resource thread once known, see `FetchEvent`.
+    fn dispatch_fetch(
+        &self,
+        mediator: CustomResponseMediator,
+        preload: Option<NavigationPreloadState>,
+    ) {
+        let global = self.upcast::<GlobalScope>();
+        let _ac = enter_realm(global);
+        let comp = InRealm::Already(&AlreadyInRealm::assert());
+
+        // Start the preload request first, so that it races the handling of the event.
+        let preload_requested = preload.is_some();
+        let preload_response = match preload {
+            Some(state) => fetch_navigation_preload(
+                global,
+                mediator.load_url.clone(),
+                state.header_value,
+                comp,
+            ),
+            None => {
+                let promise = Promise::new_in_current_realm(comp);
+                promise.resolve_native(&UndefinedValue());
+                promise
+            },
+        };
+
+        let request = match Request::Constructor(
+            global,
+            None,
+            RequestInfo::USVString(USVString(mediator.load_url.to_string())),
+            RootedTraceableBox::new(RequestInit::empty()),
+        ) {
+            Ok(request) => request,
+            Err(_) => {
+                warn!("Failed to create the request of a fetch event.");
+                let _ = mediator.response_chan.send(None);
+                return;
+            },
+        };
+        let resulting_client_id = if mediator.is_navigation {
+            DOMString::from(Uuid::new_v4().to_string())
+        } else {
+            DOMString::new()
+        };
+        let event = FetchEvent::new(
+            self,
+            atom!("fetch"),
+            &request,
+            preload_response,
+            resulting_client_id,
+            mediator.response_chan,
+        );
+        event.upcast::<Event>().fire(self.upcast());
+        event.respond_after_dispatch(preload_requested, comp);
+    }
+
     fn dispatch_activate(&self) {
         let event = ExtendableEvent::new(self, atom!("activate"), false, false);
         let event = (&*event).upcast::<Event>();
@@ -85,6 +85,10 @@ impl ServiceWorkerRegistration {
         *self.installing.borrow_mut() = Some(Dom::from_ref(worker));
     }
 
+    pub fn scope(&self) -> &ServoUrl {
+        &self.scope
+    }
+
     pub fn get_navigation_preload_header_value(&self) -> Option<ByteString> {
         self.navigation_preload_header_value.borrow().clone()
     }
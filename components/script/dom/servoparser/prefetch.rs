@@ -10,13 +10,16 @@ use html5ever::tokenizer::{
 use html5ever::{local_name, Attribute, LocalName};
 use js::jsapi::JSTracer;
 use msg::constellation_msg::PipelineId;
-use net_traits::request::{CorsSettings, CredentialsMode, ParserMetadata, Referrer};
+use net_traits::request::{
+    CorsSettings, CredentialsMode, ParserMetadata, Referrer, RequestPriority,
+};
 use net_traits::{CoreResourceMsg, FetchChannels, IpcSend, ReferrerPolicy, ResourceThreads};
 use servo_url::{ImmutableOrigin, ServoUrl};
 
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::trace::{CustomTraceable, JSTraceable};
 use crate::dom::document::{determine_policy_for_token, Document};
+use crate::dom::element::fetch_priority_for_token;
 use crate::dom::htmlimageelement::{image_fetch_request, FromPictureOrSrcSet};
 use crate::dom::htmlscriptelement::script_fetch_request;
 use crate::script_module::ScriptFetchOptions;
@@ -115,6 +118,8 @@ impl TokenSink for PrefetchSink {
                             cryptographic_nonce: String::new(),
                             credentials_mode: CredentialsMode::CredentialsSameOrigin,
                             parser_metadata: ParserMetadata::ParserInserted,
+                            fetch_priority: self
+                                .get_fetch_priority(tag, LocalName::from("fetchpriority")),
                         },
                     );
                     let _ = self
@@ -133,6 +138,7 @@ impl TokenSink for PrefetchSink {
                         self.pipeline_id,
                         self.get_cors_settings(tag, local_name!("crossorigin")),
                         self.get_referrer_policy(tag, local_name!("referrerpolicy")),
+                        self.get_fetch_priority(tag, LocalName::from("fetchpriority")),
                         FromPictureOrSrcSet::No,
                     );
                     let _ = self
@@ -154,6 +160,8 @@ impl TokenSink for PrefetchSink {
                                 .get_attr(tag, local_name!("integrity"))
                                 .map(|attr| String::from(&attr.value))
                                 .unwrap_or_default();
+                            let fetch_priority =
+                                self.get_fetch_priority(tag, LocalName::from("fetchpriority"));
                             let request = stylesheet_fetch_request(
                                 url,
                                 cors_setting,
@@ -161,6 +169,7 @@ impl TokenSink for PrefetchSink {
                                 self.pipeline_id,
                                 self.referrer.clone(),
                                 referrer_policy,
+                                fetch_priority,
                                 integrity_metadata,
                             );
                             let _ = self
@@ -210,6 +219,13 @@ impl PrefetchSink {
             .or(self.referrer_policy)
     }
 
+    fn get_fetch_priority(&self, tag: &Tag, name: LocalName) -> RequestPriority {
+        self.get_attr(tag, name)
+            .map_or(RequestPriority::Auto, |attr| {
+                fetch_priority_for_token(&attr.value)
+            })
+    }
+
     fn get_cors_settings(&self, tag: &Tag, name: LocalName) -> Option<CorsSettings> {
         let crossorigin = self.get_attr(tag, name)?;
         if crossorigin.value.eq_ignore_ascii_case("anonymous") {
@@ -0,0 +1,309 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::collections::VecDeque;
+
+use dom_struct::dom_struct;
+use embedder_traits::{
+    EmbedderMsg, SpeechSynthesisErrorCode, SpeechSynthesisEvent as EmbedderSpeechSynthesisEvent,
+    SpeechSynthesisEventKind, SpeechSynthesisMsg,
+};
+use ipc_channel::ipc;
+use ipc_channel::router::ROUTER;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisBinding::SpeechSynthesisMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::speechsynthesiserrorevent::SpeechSynthesisErrorEvent;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::speechsynthesisvoice::SpeechSynthesisVoice;
+use crate::dom::window::Window;
+use crate::task_source::TaskSource;
+
+/// <https://wicg.github.io/speech-api/#tts-section>
+///
+/// The utterances are queued here, and given to the embedder one at a time, which speaks
+/// them with the text-to-speech engine of the platform and sends back their events.
+#[dom_struct]
+pub struct SpeechSynthesis {
+    eventtarget: EventTarget,
+    /// The utterances to speak, the first one being spoken if `speaking` is set.
+    queue: DomRefCell<VecDeque<Dom<SpeechSynthesisUtterance>>>,
+    speaking: Cell<bool>,
+    paused: Cell<bool>,
+    /// The voices of the embedder, once asked for, kept so that they are the same objects
+    /// every time.
+    voices: DomRefCell<Option<Vec<Dom<SpeechSynthesisVoice>>>>,
+    /// The number of utterances given to the embedder, which tells the events of the
+    /// utterance being spoken from the late ones of the utterances that were canceled.
+    utterance_id: Cell<u64>,
+}
+
+impl SpeechSynthesis {
+    fn new_inherited() -> SpeechSynthesis {
+        SpeechSynthesis {
+            eventtarget: EventTarget::new_inherited(),
+            queue: Default::default(),
+            speaking: Cell::new(false),
+            paused: Cell::new(false),
+            voices: Default::default(),
+            utterance_id: Cell::new(0),
+        }
+    }
+
+    pub fn new(window: &Window) -> DomRoot<SpeechSynthesis> {
+        reflect_dom_object(Box::new(SpeechSynthesis::new_inherited()), window)
+    }
+
+    /// Stop speaking and drop the queued utterances without firing any event, as the
+    /// document goes away.
+    pub fn stop(&self) {
+        self.utterance_id.set(self.utterance_id.get() + 1);
+        self.queue.borrow_mut().clear();
+        if self.speaking.replace(false) {
+            self.send(SpeechSynthesisMsg::Cancel);
+        }
+    }
+
+    fn send(&self, msg: SpeechSynthesisMsg) {
+        self.global()
+            .as_window()
+            .send_to_embedder(EmbedderMsg::SpeechSynthesis(msg));
+    }
+
+    /// Give the first queued utterance to the embedder to speak, if any.
+    fn speak_next(&self) {
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+        self.speaking.set(true);
+        let id = self.utterance_id.get() + 1;
+        self.utterance_id.set(id);
+
+        let global = self.global();
+        let window = global.as_window();
+        let document_lang = window
+            .Document()
+            .GetDocumentElement()
+            .map_or_else(String::new, |element| element.get_lang());
+
+        let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+        let (source, canceller) = window
+            .task_manager()
+            .dom_manipulation_task_source_with_canceller();
+        let this = Trusted::new(self);
+        ROUTER.add_route(
+            receiver.to_opaque(),
+            Box::new(move |message| {
+                let event = match message.to::<EmbedderSpeechSynthesisEvent>() {
+                    Ok(event) => event,
+                    Err(_) => return,
+                };
+                let this = this.clone();
+                let _ = source.queue_with_canceller(
+                    task!(speech_synthesis_event: move || {
+                        this.root().handle_event(id, event);
+                    }),
+                    &canceller,
+                );
+            }),
+        );
+        self.send(SpeechSynthesisMsg::Speak(
+            utterance.snapshot(&document_lang),
+            sender,
+        ));
+    }
+
+    /// Fire an event the embedder sent about the utterance of the given id, moving on to the
+    /// next utterance once it is done.
+    fn handle_event(&self, id: u64, event: EmbedderSpeechSynthesisEvent) {
+        if id != self.utterance_id.get() || !self.speaking.get() {
+            return;
+        }
+        let utterance = match self.queue.borrow().front() {
+            Some(utterance) => DomRoot::from_ref(&**utterance),
+            None => return,
+        };
+        let done = matches!(
+            event.kind,
+            SpeechSynthesisEventKind::End | SpeechSynthesisEventKind::Error(_)
+        );
+        if done {
+            self.queue.borrow_mut().pop_front();
+            self.speaking.set(false);
+        }
+        self.fire_event(&utterance, event);
+        if done && !self.speaking.get() && !self.paused.get() {
+            self.speak_next();
+        }
+    }
+
+    fn fire_event(
+        &self,
+        utterance: &SpeechSynthesisUtterance,
+        event: EmbedderSpeechSynthesisEvent,
+    ) {
+        let global = self.global();
+        let window = global.as_window();
+        let (type_, name) = match event.kind {
+            SpeechSynthesisEventKind::Start => ("start", DOMString::new()),
+            SpeechSynthesisEventKind::End => ("end", DOMString::new()),
+            SpeechSynthesisEventKind::Pause => ("pause", DOMString::new()),
+            SpeechSynthesisEventKind::Resume => ("resume", DOMString::new()),
+            SpeechSynthesisEventKind::WordBoundary => ("boundary", DOMString::from("word")),
+            SpeechSynthesisEventKind::SentenceBoundary => ("boundary", DOMString::from("sentence")),
+            SpeechSynthesisEventKind::Mark(name) => ("mark", DOMString::from(name)),
+            SpeechSynthesisEventKind::Error(error) => {
+                let event = SpeechSynthesisErrorEvent::new(
+                    window,
+                    utterance,
+                    event.char_index,
+                    event.elapsed_time,
+                    error.into(),
+                );
+                event.upcast::<Event>().fire(utterance.upcast());
+                return;
+            },
+        };
+        let event = SpeechSynthesisEvent::new(
+            window,
+            Atom::from(type_),
+            utterance,
+            event.char_index,
+            event.char_length.unwrap_or(0),
+            event.elapsed_time,
+            name,
+        );
+        event.upcast::<Event>().fire(utterance.upcast());
+    }
+}
+
+impl SpeechSynthesisMethods for SpeechSynthesis {
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-pending>
+    fn Pending(&self) -> bool {
+        self.queue.borrow().len() > usize::from(self.speaking.get())
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-speaking>
+    fn Speaking(&self) -> bool {
+        self.speaking.get()
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-paused>
+    fn Paused(&self) -> bool {
+        self.paused.get()
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesis-onvoiceschanged
+    event_handler!(voiceschanged, GetOnvoiceschanged, SetOnvoiceschanged);
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-speak>
+    fn Speak(&self, utterance: &SpeechSynthesisUtterance) {
+        self.queue.borrow_mut().push_back(Dom::from_ref(utterance));
+        if !self.speaking.get() && !self.paused.get() {
+            self.speak_next();
+        }
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-cancel>
+    fn Cancel(&self) {
+        let speaking = self.speaking.get();
+        let canceled: Vec<_> = self
+            .queue
+            .borrow()
+            .iter()
+            .map(|utterance| Trusted::new(&**utterance))
+            .collect();
+        self.stop();
+
+        // The utterance being spoken is interrupted, and the other ones are removed from
+        // the queue before they were spoken.
+        let global = self.global();
+        let task_source = global
+            .as_window()
+            .task_manager()
+            .dom_manipulation_task_source();
+        for (index, utterance) in canceled.into_iter().enumerate() {
+            let error = if index == 0 && speaking {
+                SpeechSynthesisErrorCode::Interrupted
+            } else {
+                SpeechSynthesisErrorCode::Canceled
+            };
+            let this = Trusted::new(self);
+            let _ = task_source.queue(
+                task!(speech_synthesis_canceled: move || {
+                    let event = EmbedderSpeechSynthesisEvent::new(
+                        SpeechSynthesisEventKind::Error(error),
+                    );
+                    this.root().fire_event(&utterance.root(), event);
+                }),
+                &global,
+            );
+        }
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-pause>
+    fn Pause(&self) {
+        if self.paused.replace(true) {
+            return;
+        }
+        if self.speaking.get() {
+            self.send(SpeechSynthesisMsg::Pause);
+        }
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-resume>
+    fn Resume(&self) {
+        if !self.paused.replace(false) {
+            return;
+        }
+        if self.speaking.get() {
+            self.send(SpeechSynthesisMsg::Resume);
+        } else {
+            self.speak_next();
+        }
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesis-getvoices>
+    fn GetVoices(&self) -> Vec<DomRoot<SpeechSynthesisVoice>> {
+        if self.voices.borrow().is_none() {
+            let (sender, receiver) = ipc::channel().expect("Failed to create IPC channel!");
+            self.send(SpeechSynthesisMsg::GetVoices(sender));
+            let voices = receiver.recv().unwrap_or_else(|e| {
+                warn!(
+                    "Failed to receive speech synthesis voices from embedder ({:?}).",
+                    e
+                );
+                vec![]
+            });
+            let global = self.global();
+            let mut cached = self.voices.borrow_mut();
+            let cached = cached.get_or_insert_with(Vec::new);
+            for voice in voices {
+                cached.push(Dom::from_ref(&*SpeechSynthesisVoice::new(
+                    global.as_window(),
+                    voice,
+                )));
+            }
+        }
+        self.voices
+            .borrow()
+            .iter()
+            .flatten()
+            .map(|voice| DomRoot::from_ref(&**voice))
+            .collect()
+    }
+}
@@ -0,0 +1,148 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::SpeechSynthesisErrorCode as EmbedderSpeechSynthesisErrorCode;
+use js::rust::HandleObject;
+use servo_atoms::Atom;
+
+use crate::dom::bindings::codegen::Bindings::EventBinding::EventMethods;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisErrorEventBinding::{
+    SpeechSynthesisErrorCode, SpeechSynthesisErrorEventInit, SpeechSynthesisErrorEventMethods,
+};
+use crate::dom::bindings::error::Fallible;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::event::Event;
+use crate::dom::speechsynthesisevent::SpeechSynthesisEvent;
+use crate::dom::speechsynthesisutterance::SpeechSynthesisUtterance;
+use crate::dom::window::Window;
+
+/// <https://wicg.github.io/speech-api/#speechsynthesiserrorevent>
+#[dom_struct]
+pub struct SpeechSynthesisErrorEvent {
+    event: SpeechSynthesisEvent,
+    error: SpeechSynthesisErrorCode,
+}
+
+impl SpeechSynthesisErrorEvent {
+    fn new_inherited(
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        elapsed_time: f32,
+        error: SpeechSynthesisErrorCode,
+    ) -> SpeechSynthesisErrorEvent {
+        SpeechSynthesisErrorEvent {
+            event: SpeechSynthesisEvent::new_inherited(
+                utterance,
+                char_index,
+                0,
+                elapsed_time,
+                DOMString::new(),
+            ),
+            error,
+        }
+    }
+
+    /// An `error` event for `utterance`, which was spoken up to `char_index` when it failed.
+    pub fn new(
+        window: &Window,
+        utterance: &SpeechSynthesisUtterance,
+        char_index: u32,
+        elapsed_time: f32,
+        error: SpeechSynthesisErrorCode,
+    ) -> DomRoot<SpeechSynthesisErrorEvent> {
+        let ev = reflect_dom_object_with_proto(
+            Box::new(SpeechSynthesisErrorEvent::new_inherited(
+                utterance,
+                char_index,
+                elapsed_time,
+                error,
+            )),
+            window,
+            None,
+        );
+        ev.upcast::<Event>()
+            .init_event(atom!("error"), false, false);
+        ev
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesiserrorevent-speechsynthesiserrorevent>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        type_: DOMString,
+        init: &SpeechSynthesisErrorEventInit,
+    ) -> Fallible<DomRoot<SpeechSynthesisErrorEvent>> {
+        let parent = &init.parent;
+        let ev = reflect_dom_object_with_proto(
+            Box::new(SpeechSynthesisErrorEvent {
+                event: SpeechSynthesisEvent::new_inherited(
+                    &parent.utterance,
+                    parent.charIndex,
+                    parent.charLength,
+                    *parent.elapsedTime,
+                    parent.name.clone(),
+                ),
+                error: init.error,
+            }),
+            window,
+            proto,
+        );
+        ev.upcast::<Event>().init_event(
+            Atom::from(type_),
+            parent.parent.bubbles,
+            parent.parent.cancelable,
+        );
+        Ok(ev)
+    }
+}
+
+impl From<EmbedderSpeechSynthesisErrorCode> for SpeechSynthesisErrorCode {
+    fn from(error: EmbedderSpeechSynthesisErrorCode) -> Self {
+        match error {
+            EmbedderSpeechSynthesisErrorCode::Canceled => SpeechSynthesisErrorCode::Canceled,
+            EmbedderSpeechSynthesisErrorCode::Interrupted => SpeechSynthesisErrorCode::Interrupted,
+            EmbedderSpeechSynthesisErrorCode::AudioBusy => SpeechSynthesisErrorCode::Audio_busy,
+            EmbedderSpeechSynthesisErrorCode::AudioHardware => {
+                SpeechSynthesisErrorCode::Audio_hardware
+            },
+            EmbedderSpeechSynthesisErrorCode::Network => SpeechSynthesisErrorCode::Network,
+            EmbedderSpeechSynthesisErrorCode::SynthesisUnavailable => {
+                SpeechSynthesisErrorCode::Synthesis_unavailable
+            },
+            EmbedderSpeechSynthesisErrorCode::SynthesisFailed => {
+                SpeechSynthesisErrorCode::Synthesis_failed
+            },
+            EmbedderSpeechSynthesisErrorCode::LanguageUnavailable => {
+                SpeechSynthesisErrorCode::Language_unavailable
+            },
+            EmbedderSpeechSynthesisErrorCode::VoiceUnavailable => {
+                SpeechSynthesisErrorCode::Voice_unavailable
+            },
+            EmbedderSpeechSynthesisErrorCode::TextTooLong => {
+                SpeechSynthesisErrorCode::Text_too_long
+            },
+            EmbedderSpeechSynthesisErrorCode::InvalidArgument => {
+                SpeechSynthesisErrorCode::Invalid_argument
+            },
+            EmbedderSpeechSynthesisErrorCode::NotAllowed => SpeechSynthesisErrorCode::Not_allowed,
+        }
+    }
+}
+
+impl SpeechSynthesisErrorEventMethods for SpeechSynthesisErrorEvent {
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesiserrorevent-error>
+    fn Error(&self) -> SpeechSynthesisErrorCode {
+        self.error
+    }
+
+    // https://dom.spec.whatwg.org/#dom-event-istrusted
+    fn IsTrusted(&self) -> bool {
+        self.upcast::<Event>().IsTrusted()
+    }
+}
@@ -0,0 +1,168 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+
+use dom_struct::dom_struct;
+use embedder_traits::SpeechSynthesisUtterance as EmbedderSpeechSynthesisUtterance;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisUtteranceBinding::SpeechSynthesisUtteranceMethods;
+use crate::dom::bindings::num::Finite;
+use crate::dom::bindings::reflector::reflect_dom_object_with_proto;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::speechsynthesisvoice::SpeechSynthesisVoice;
+use crate::dom::window::Window;
+
+/// <https://wicg.github.io/speech-api/#speechsynthesisutterance>
+#[dom_struct]
+pub struct SpeechSynthesisUtterance {
+    eventtarget: EventTarget,
+    text: DomRefCell<DOMString>,
+    lang: DomRefCell<DOMString>,
+    voice: MutNullableDom<SpeechSynthesisVoice>,
+    volume: Cell<f32>,
+    rate: Cell<f32>,
+    pitch: Cell<f32>,
+}
+
+impl SpeechSynthesisUtterance {
+    fn new_inherited(text: DOMString) -> SpeechSynthesisUtterance {
+        SpeechSynthesisUtterance {
+            eventtarget: EventTarget::new_inherited(),
+            text: DomRefCell::new(text),
+            lang: DomRefCell::new(DOMString::new()),
+            voice: Default::default(),
+            volume: Cell::new(1.),
+            rate: Cell::new(1.),
+            pitch: Cell::new(1.),
+        }
+    }
+
+    fn new(
+        window: &Window,
+        proto: Option<HandleObject>,
+        text: DOMString,
+    ) -> DomRoot<SpeechSynthesisUtterance> {
+        reflect_dom_object_with_proto(
+            Box::new(SpeechSynthesisUtterance::new_inherited(text)),
+            window,
+            proto,
+        )
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-speechsynthesisutterance>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        text: Option<DOMString>,
+    ) -> DomRoot<SpeechSynthesisUtterance> {
+        SpeechSynthesisUtterance::new(window, proto, text.unwrap_or_default())
+    }
+
+    /// The utterance as it is to be spoken, the attributes out of range being clamped to
+    /// the closest value in range, and no language standing for the one of the document.
+    pub fn snapshot(&self, document_lang: &str) -> EmbedderSpeechSynthesisUtterance {
+        let lang = self.lang.borrow();
+        EmbedderSpeechSynthesisUtterance {
+            text: self.text.borrow().to_string(),
+            lang: if lang.is_empty() {
+                document_lang.to_owned()
+            } else {
+                lang.to_string()
+            },
+            voice_uri: self.voice.get().map(|voice| voice.voice_uri().to_string()),
+            volume: self.volume.get().clamp(0., 1.),
+            rate: self.rate.get().clamp(0.1, 10.),
+            pitch: self.pitch.get().clamp(0., 2.),
+        }
+    }
+}
+
+impl SpeechSynthesisUtteranceMethods for SpeechSynthesisUtterance {
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-text>
+    fn Text(&self) -> DOMString {
+        self.text.borrow().clone()
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-text>
+    fn SetText(&self, value: DOMString) {
+        *self.text.borrow_mut() = value;
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-lang>
+    fn Lang(&self) -> DOMString {
+        self.lang.borrow().clone()
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-lang>
+    fn SetLang(&self, value: DOMString) {
+        *self.lang.borrow_mut() = value;
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-voice>
+    fn GetVoice(&self) -> Option<DomRoot<SpeechSynthesisVoice>> {
+        self.voice.get()
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-voice>
+    fn SetVoice(&self, value: Option<&SpeechSynthesisVoice>) {
+        self.voice.set(value);
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-volume>
+    fn Volume(&self) -> Finite<f32> {
+        Finite::wrap(self.volume.get())
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-volume>
+    fn SetVolume(&self, value: Finite<f32>) {
+        self.volume.set(*value);
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-rate>
+    fn Rate(&self) -> Finite<f32> {
+        Finite::wrap(self.rate.get())
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-rate>
+    fn SetRate(&self, value: Finite<f32>) {
+        self.rate.set(*value);
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-pitch>
+    fn Pitch(&self) -> Finite<f32> {
+        Finite::wrap(self.pitch.get())
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-pitch>
+    fn SetPitch(&self, value: Finite<f32>) {
+        self.pitch.set(*value);
+    }
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onstart
+    event_handler!(start, GetOnstart, SetOnstart);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onend
+    event_handler!(end, GetOnend, SetOnend);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onerror
+    event_handler!(error, GetOnerror, SetOnerror);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onpause
+    event_handler!(pause, GetOnpause, SetOnpause);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onresume
+    event_handler!(resume, GetOnresume, SetOnresume);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onmark
+    event_handler!(mark, GetOnmark, SetOnmark);
+
+    // https://wicg.github.io/speech-api/#dom-speechsynthesisutterance-onboundary
+    event_handler!(boundary, GetOnboundary, SetOnboundary);
+}
@@ -0,0 +1,74 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use embedder_traits::SpeechSynthesisVoice as EmbedderSpeechSynthesisVoice;
+
+use crate::dom::bindings::codegen::Bindings::SpeechSynthesisVoiceBinding::SpeechSynthesisVoiceMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::window::Window;
+
+/// <https://wicg.github.io/speech-api/#speechsynthesisvoice>
+#[dom_struct]
+pub struct SpeechSynthesisVoice {
+    reflector_: Reflector,
+    voice_uri: DOMString,
+    name: DOMString,
+    lang: DOMString,
+    local_service: bool,
+    default: bool,
+}
+
+impl SpeechSynthesisVoice {
+    fn new_inherited(voice: EmbedderSpeechSynthesisVoice) -> SpeechSynthesisVoice {
+        SpeechSynthesisVoice {
+            reflector_: Reflector::new(),
+            voice_uri: DOMString::from(voice.voice_uri),
+            name: DOMString::from(voice.name),
+            lang: DOMString::from(voice.lang),
+            local_service: voice.local_service,
+            default: voice.default,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        voice: EmbedderSpeechSynthesisVoice,
+    ) -> DomRoot<SpeechSynthesisVoice> {
+        reflect_dom_object(Box::new(SpeechSynthesisVoice::new_inherited(voice)), window)
+    }
+
+    pub fn voice_uri(&self) -> &DOMString {
+        &self.voice_uri
+    }
+}
+
+impl SpeechSynthesisVoiceMethods for SpeechSynthesisVoice {
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-voiceuri>
+    fn VoiceURI(&self) -> DOMString {
+        self.voice_uri.clone()
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-name>
+    fn Name(&self) -> DOMString {
+        self.name.clone()
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-lang>
+    fn Lang(&self) -> DOMString {
+        self.lang.clone()
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-localservice>
+    fn LocalService(&self) -> bool {
+        self.local_service
+    }
+
+    /// <https://wicg.github.io/speech-api/#dom-speechsynthesisvoice-default>
+    fn Default(&self) -> bool {
+        self.default
+    }
+}
@@ -0,0 +1,135 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+use js::rust::HandleObject;
+
+use crate::dom::bindings::codegen::Bindings::StaticRangeBinding::{
+    StaticRangeInit, StaticRangeMethods,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::inheritance::NodeTypeId;
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::node::Node;
+use crate::dom::window::Window;
+
+/// <https://dom.spec.whatwg.org/#staticrange>, a range that is not updated when the
+/// tree is mutated.
+#[dom_struct]
+pub struct StaticRange {
+    reflector_: Reflector,
+    start_container: Dom<Node>,
+    start_offset: u32,
+    end_container: Dom<Node>,
+    end_offset: u32,
+}
+
+impl StaticRange {
+    fn new_inherited(
+        start_container: &Node,
+        start_offset: u32,
+        end_container: &Node,
+        end_offset: u32,
+    ) -> StaticRange {
+        StaticRange {
+            reflector_: Reflector::new(),
+            start_container: Dom::from_ref(start_container),
+            start_offset,
+            end_container: Dom::from_ref(end_container),
+            end_offset,
+        }
+    }
+
+    pub fn new(
+        window: &Window,
+        start_container: &Node,
+        start_offset: u32,
+        end_container: &Node,
+        end_offset: u32,
+    ) -> DomRoot<StaticRange> {
+        Self::new_with_proto(
+            window,
+            None,
+            start_container,
+            start_offset,
+            end_container,
+            end_offset,
+        )
+    }
+
+    fn new_with_proto(
+        window: &Window,
+        proto: Option<HandleObject>,
+        start_container: &Node,
+        start_offset: u32,
+        end_container: &Node,
+        end_offset: u32,
+    ) -> DomRoot<StaticRange> {
+        reflect_dom_object_with_proto(
+            Box::new(StaticRange::new_inherited(
+                start_container,
+                start_offset,
+                end_container,
+                end_offset,
+            )),
+            window,
+            proto,
+        )
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-staticrange-staticrange>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        window: &Window,
+        proto: Option<HandleObject>,
+        init: &StaticRangeInit,
+    ) -> Fallible<DomRoot<StaticRange>> {
+        // Step 1. If init["startContainer"] or init["endContainer"] is a DocumentType or Attr
+        // node, then throw an "InvalidNodeTypeError" DOMException.
+        let is_invalid_container =
+            |node: &Node| matches!(node.type_id(), NodeTypeId::DocumentType | NodeTypeId::Attr);
+        if is_invalid_container(&init.startContainer) || is_invalid_container(&init.endContainer) {
+            return Err(Error::InvalidNodeType);
+        }
+
+        // Step 2. Set this's start to (init["startContainer"], init["startOffset"]) and end to
+        // (init["endContainer"], init["endOffset"]).
+        Ok(StaticRange::new_with_proto(
+            window,
+            proto,
+            &init.startContainer,
+            init.startOffset,
+            &init.endContainer,
+            init.endOffset,
+        ))
+    }
+}
+
+impl StaticRangeMethods for StaticRange {
+    /// <https://dom.spec.whatwg.org/#dom-range-startcontainer>
+    fn StartContainer(&self) -> DomRoot<Node> {
+        DomRoot::from_ref(&*self.start_container)
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-range-startoffset>
+    fn StartOffset(&self) -> u32 {
+        self.start_offset
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-range-endcontainer>
+    fn EndContainer(&self) -> DomRoot<Node> {
+        DomRoot::from_ref(&*self.end_container)
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-range-endoffset>
+    fn EndOffset(&self) -> u32 {
+        self.end_offset
+    }
+
+    /// <https://dom.spec.whatwg.org/#dom-range-collapsed>
+    fn Collapsed(&self) -> bool {
+        self.start_container == self.end_container && self.start_offset == self.end_offset
+    }
+}
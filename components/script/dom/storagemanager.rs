@@ -0,0 +1,136 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use ipc_channel::ipc::IpcSender;
+use net_traits::storage_thread::StorageThreadMsg;
+use net_traits::IpcSend;
+use profile_traits::ipc;
+use serde::{Deserialize, Serialize};
+use servo_url::ServoUrl;
+
+use crate::dom::bindings::codegen::Bindings::PermissionStatusBinding::{
+    PermissionName, PermissionState,
+};
+use crate::dom::bindings::codegen::Bindings::StorageManagerBinding::{
+    StorageEstimate, StorageManagerMethods,
+};
+use crate::dom::bindings::error::Error;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::filesystemdirectoryhandle::FileSystemDirectoryHandle;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::permissions::request_permission_to_use;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+
+/// <https://storage.spec.whatwg.org/#storagemanager>
+#[dom_struct]
+pub struct StorageManager {
+    reflector_: Reflector,
+}
+
+impl StorageManager {
+    fn new_inherited() -> StorageManager {
+        StorageManager {
+            reflector_: Reflector::new(),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<StorageManager> {
+        reflect_dom_object(Box::new(StorageManager::new_inherited()), global)
+    }
+
+    /// Ask the storage thread about the storage of the origin of the global, or reject
+    /// `promise` if there is no such storage.
+    /// <https://storage.spec.whatwg.org/#obtain-a-local-storage-shelf>
+    fn query<T>(
+        &self,
+        promise: &Promise,
+        message: impl FnOnce(IpcSender<T>, ServoUrl) -> StorageThreadMsg,
+    ) -> Option<T>
+    where
+        T: for<'de> Deserialize<'de> + Serialize,
+    {
+        let global = self.global();
+        if !global.origin().immutable().is_tuple() {
+            promise.reject_error(Error::Type("The origin has no storage".to_owned()));
+            return None;
+        }
+        let (sender, receiver) = ipc::channel(global.time_profiler_chan().clone()).unwrap();
+        let _ = global
+            .resource_threads()
+            .send(message(sender, global.get_url()));
+        match receiver.recv() {
+            Ok(reply) => Some(reply),
+            Err(_) => {
+                promise.reject_error(Error::Operation);
+                None
+            },
+        }
+    }
+}
+
+impl StorageManagerMethods for StorageManager {
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persisted>
+    fn Persisted(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        if let Some(persisted) = self.query(&promise, StorageThreadMsg::Persisted) {
+            promise.resolve_native(&persisted);
+        }
+        promise
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persist>
+    fn Persist(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let global = self.global();
+        if !global.origin().immutable().is_tuple() {
+            promise.reject_error(Error::Type("The origin has no storage".to_owned()));
+            return promise;
+        }
+        // Step 3. Let permission be the result of requesting permission to use
+        // "persistent-storage".
+        let permission = request_permission_to_use(PermissionName::Persistent_storage, &global);
+        // Step 4.2-4.3. If permission is "granted", make the bucket persistent, and resolve
+        // with whether it is.
+        let message = if permission == PermissionState::Granted {
+            StorageThreadMsg::Persist
+        } else {
+            StorageThreadMsg::Persisted
+        };
+        if let Some(persisted) = self.query(&promise, message) {
+            promise.resolve_native(&persisted);
+        }
+        promise
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-estimate>
+    fn Estimate(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        if let Some((usage, quota)) = self.query(&promise, StorageThreadMsg::Estimate) {
+            let mut estimate = StorageEstimate::empty();
+            estimate.usage = Some(usage);
+            estimate.quota = Some(quota);
+            promise.resolve_native(&estimate);
+        }
+        promise
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-storagemanager-getdirectory>
+    fn GetDirectory(&self, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let global = self.global();
+        // Step 3. Documents and workers of opaque origins have no storage bottle map.
+        if !global.origin().immutable().is_tuple() {
+            promise.reject_error(Error::Security);
+            return promise;
+        }
+        // The root directory is created by the file system thread when first used.
+        promise.resolve_native(&FileSystemDirectoryHandle::new(&global, vec![]));
+        promise
+    }
+}
@@ -0,0 +1,1444 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::ptr;
+use std::rc::Rc;
+
+use aes_kw::{KekAes128, KekAes192, KekAes256};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use dom_struct::dom_struct;
+use ed25519_dalek::{Signer, Verifier};
+use js::conversions::{ConversionResult, FromJSValConvertible};
+use js::jsapi::{Heap, JSObject};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::typedarray::ArrayBufferU8;
+use p256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p256::elliptic_curve::sec1::{EncodedPoint, FromEncodedPoint, ToEncodedPoint};
+use pkcs8::der::asn1::{BitStringRef, OctetStringRef};
+use pkcs8::der::{Decode, Encode};
+use pkcs8::spki::{
+    AlgorithmIdentifierRef, DecodePublicKey, EncodePublicKey, SubjectPublicKeyInfoRef,
+};
+use pkcs8::{DecodePrivateKey, EncodePrivateKey, ObjectIdentifier, PrivateKeyInfo};
+use serde::{Deserialize, Serialize};
+use servo_rand::{RngCore, ServoRng};
+use sha2::Digest;
+
+use crate::dom::bindings::buffer_source::create_buffer_source;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::CryptoKeyBinding::{CryptoKeyPair, KeyType, KeyUsage};
+use crate::dom::bindings::codegen::Bindings::SubtleCryptoBinding::{
+    AesDerivedKeyParams, AesKeyGenParams, Algorithm, AlgorithmIdentifier, EcKeyGenParams,
+    EcKeyImportParams, EcdhKeyDeriveParams, EcdsaParams, JsonWebKey, KeyFormat,
+    SubtleCryptoMethods,
+};
+use crate::dom::bindings::codegen::UnionTypes::{
+    ArrayBufferViewOrArrayBuffer, ArrayBufferViewOrArrayBufferOrJsonWebKey,
+};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::trace::RootedTraceableBox;
+use crate::dom::cryptokey::{CryptoKey, CryptoKeyAlgorithm, Handle, NamedCurve};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
+
+const ALG_ECDSA: &str = "ECDSA";
+const ALG_ECDH: &str = "ECDH";
+const ALG_AES_KW: &str = "AES-KW";
+const ALG_ED25519: &str = "Ed25519";
+const ALG_X25519: &str = "X25519";
+const ALG_SHA1: &str = "SHA-1";
+const ALG_SHA256: &str = "SHA-256";
+const ALG_SHA384: &str = "SHA-384";
+const ALG_SHA512: &str = "SHA-512";
+
+/// The object identifiers of the algorithms of Ed25519 and X25519 keys.
+/// <https://www.rfc-editor.org/rfc/rfc8410#section-3>
+const OID_ED25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.112");
+const OID_X25519: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.101.110");
+
+/// The algorithms registered for each operation.
+/// <https://w3c.github.io/webcrypto/#dfn-supportedAlgorithms>
+const DIGEST_ALGORITHMS: &[&str] = &[ALG_SHA1, ALG_SHA256, ALG_SHA384, ALG_SHA512];
+const SIGNATURE_ALGORITHMS: &[&str] = &[ALG_ECDSA, ALG_ED25519];
+const KEY_ALGORITHMS: &[&str] = &[ALG_ECDSA, ALG_ECDH, ALG_AES_KW, ALG_ED25519, ALG_X25519];
+const DERIVE_BITS_ALGORITHMS: &[&str] = &[ALG_ECDH, ALG_X25519];
+const KEY_LENGTH_ALGORITHMS: &[&str] = &[ALG_AES_KW];
+const WRAP_KEY_ALGORITHMS: &[&str] = &[ALG_AES_KW];
+
+/// <https://w3c.github.io/webcrypto/#subtlecrypto-interface>
+#[dom_struct]
+pub struct SubtleCrypto {
+    reflector_: Reflector,
+    #[ignore_malloc_size_of = "Defined in rand"]
+    #[no_trace]
+    rng: DomRefCell<ServoRng>,
+}
+
+impl SubtleCrypto {
+    fn new_inherited() -> SubtleCrypto {
+        SubtleCrypto {
+            reflector_: Reflector::new(),
+            rng: DomRefCell::new(ServoRng::new()),
+        }
+    }
+
+    pub fn new(global: &GlobalScope) -> DomRoot<SubtleCrypto> {
+        reflect_dom_object(Box::new(SubtleCrypto::new_inherited()), global)
+    }
+
+    fn random_bytes<const N: usize>(&self) -> [u8; N] {
+        let mut bytes = [0; N];
+        self.rng.borrow_mut().fill_bytes(&mut bytes);
+        bytes
+    }
+
+    /// Generate the key material for `algorithm`: a secret key, or a private key followed by
+    /// its public key.
+    fn generate_handles(
+        &self,
+        algorithm: CryptoKeyAlgorithm,
+    ) -> Fallible<(Handle, Option<Handle>)> {
+        Ok(match algorithm {
+            CryptoKeyAlgorithm::Ecdsa(curve) | CryptoKeyAlgorithm::Ecdh(curve) => match curve {
+                // Random bytes that are not a valid scalar are very unlikely, and skipped.
+                NamedCurve::P256 => loop {
+                    if let Ok(key) = p256::SecretKey::from_slice(&self.random_bytes::<32>()) {
+                        let public = key.public_key();
+                        break (Handle::P256Private(key), Some(Handle::P256Public(public)));
+                    }
+                },
+                NamedCurve::P384 => loop {
+                    if let Ok(key) = p384::SecretKey::from_slice(&self.random_bytes::<48>()) {
+                        let public = key.public_key();
+                        break (Handle::P384Private(key), Some(Handle::P384Public(public)));
+                    }
+                },
+            },
+            CryptoKeyAlgorithm::AesKw(length) => {
+                if ![128, 192, 256].contains(&length) {
+                    return Err(Error::Operation);
+                }
+                let bytes = self.random_bytes::<32>();
+                (Handle::Aes(bytes[..length as usize / 8].to_vec()), None)
+            },
+            CryptoKeyAlgorithm::Ed25519 => {
+                let key = ed25519_dalek::SigningKey::from_bytes(&self.random_bytes());
+                let public = key.verifying_key();
+                (
+                    Handle::Ed25519Private(key),
+                    Some(Handle::Ed25519Public(public)),
+                )
+            },
+            CryptoKeyAlgorithm::X25519 => {
+                let key = x25519_dalek::StaticSecret::from(self.random_bytes::<32>());
+                let public = x25519_dalek::PublicKey::from(&key);
+                (
+                    Handle::X25519Private(key),
+                    Some(Handle::X25519Public(public)),
+                )
+            },
+        })
+    }
+
+    /// The steps of <https://w3c.github.io/webcrypto/#SubtleCrypto-method-generateKey> after
+    /// the algorithm is normalized, resolving `promise` with the key or key pair.
+    fn generate_key(
+        &self,
+        algorithm: CryptoKeyAlgorithm,
+        extractable: bool,
+        usages: &[KeyUsage],
+        promise: &Promise,
+    ) -> Fallible<()> {
+        let global = self.global();
+        let (handle, public_handle) = self.generate_handles(algorithm)?;
+        let public_handle = match public_handle {
+            Some(public_handle) => public_handle,
+            None => {
+                check_usages(usages, allowed_usages(algorithm, KeyType::Secret))?;
+                let key = new_key(
+                    &global,
+                    algorithm,
+                    KeyType::Secret,
+                    extractable,
+                    normalize_usages(usages, allowed_usages(algorithm, KeyType::Secret)),
+                    handle,
+                )?;
+                promise.resolve_native(&key);
+                return Ok(());
+            },
+        };
+
+        let private_usages = allowed_usages(algorithm, KeyType::Private);
+        let public_usages = allowed_usages(algorithm, KeyType::Public);
+        if usages
+            .iter()
+            .any(|usage| !private_usages.contains(usage) && !public_usages.contains(usage))
+        {
+            return Err(Error::Syntax);
+        }
+        let private_key = new_key(
+            &global,
+            algorithm,
+            KeyType::Private,
+            extractable,
+            normalize_usages(usages, private_usages),
+            handle,
+        )?;
+        // Public keys are always extractable.
+        let public_key = new_key(
+            &global,
+            algorithm,
+            KeyType::Public,
+            true,
+            normalize_usages(usages, public_usages),
+            public_handle,
+        )?;
+        promise.resolve_native(&CryptoKeyPair {
+            publicKey: Some(public_key),
+            privateKey: Some(private_key),
+        });
+        Ok(())
+    }
+}
+
+impl SubtleCryptoMethods for SubtleCrypto {
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-sign>
+    fn Sign(
+        &self,
+        cx: JSContext,
+        algorithm: AlgorithmIdentifier,
+        key: &CryptoKey,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = normalize_signature_algorithm(cx, &algorithm).and_then(|algorithm| {
+            check_key(key, algorithm.name(), KeyUsage::Sign)?;
+            sign(algorithm, key.handle(), &buffer_source_bytes(data))
+        });
+        match result {
+            Ok(signature) => resolve_with_bytes(cx, &promise, &signature),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-verify>
+    fn Verify(
+        &self,
+        cx: JSContext,
+        algorithm: AlgorithmIdentifier,
+        key: &CryptoKey,
+        signature: ArrayBufferViewOrArrayBuffer,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = normalize_signature_algorithm(cx, &algorithm).and_then(|algorithm| {
+            check_key(key, algorithm.name(), KeyUsage::Verify)?;
+            verify(
+                algorithm,
+                key.handle(),
+                &buffer_source_bytes(signature),
+                &buffer_source_bytes(data),
+            )
+        });
+        match result {
+            Ok(valid) => promise.resolve_native(&valid),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-digest>
+    fn Digest(
+        &self,
+        cx: JSContext,
+        algorithm: AlgorithmIdentifier,
+        data: ArrayBufferViewOrArrayBuffer,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        match normalize_hash_algorithm(cx, &algorithm) {
+            Ok(hash) => resolve_with_bytes(cx, &promise, &hash.digest(&buffer_source_bytes(data))),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-generateKey>
+    fn GenerateKey(
+        &self,
+        cx: JSContext,
+        algorithm: AlgorithmIdentifier,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = normalize_generate_key_algorithm(cx, &algorithm)
+            .and_then(|algorithm| self.generate_key(algorithm, extractable, &key_usages, &promise));
+        if let Err(error) = result {
+            promise.reject_error(error);
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-deriveKey>
+    fn DeriveKey(
+        &self,
+        cx: JSContext,
+        algorithm: AlgorithmIdentifier,
+        base_key: &CryptoKey,
+        derived_key_type: AlgorithmIdentifier,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = (|| {
+            let (name, public_key) = normalize_derive_bits_algorithm(cx, &algorithm)?;
+            let import_algorithm = normalize_import_key_algorithm(cx, &derived_key_type)?;
+            let length = normalize_key_length_algorithm(cx, &derived_key_type)?;
+            check_key(base_key, name, KeyUsage::DeriveKey)?;
+            let secret = derive_bits(base_key, &public_key, Some(length))?;
+            import_key(
+                &self.global(),
+                import_algorithm,
+                KeyData::Raw(secret),
+                extractable,
+                &key_usages,
+            )
+        })();
+        match result {
+            Ok(key) => promise.resolve_native(&key),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-deriveBits>
+    fn DeriveBits(
+        &self,
+        cx: JSContext,
+        algorithm: AlgorithmIdentifier,
+        base_key: &CryptoKey,
+        length: Option<u32>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = normalize_derive_bits_algorithm(cx, &algorithm).and_then(|(name, public)| {
+            check_key(base_key, name, KeyUsage::DeriveBits)?;
+            derive_bits(base_key, &public, length)
+        });
+        match result {
+            Ok(bits) => resolve_with_bytes(cx, &promise, &bits),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-importKey>
+    fn ImportKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        key_data: ArrayBufferViewOrArrayBufferOrJsonWebKey,
+        algorithm: AlgorithmIdentifier,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = (|| {
+            let key_data = match (format, key_data) {
+                (KeyFormat::Jwk, ArrayBufferViewOrArrayBufferOrJsonWebKey::JsonWebKey(jwk)) => {
+                    KeyData::Jwk(Jwk::from(&jwk))
+                },
+                (KeyFormat::Jwk, _) |
+                (_, ArrayBufferViewOrArrayBufferOrJsonWebKey::JsonWebKey(_)) => {
+                    return Err(Error::Type(
+                        "The key data does not match the format".to_owned(),
+                    ))
+                },
+                (format, ArrayBufferViewOrArrayBufferOrJsonWebKey::ArrayBufferView(view)) => {
+                    KeyData::from_bytes(format, view.to_vec())?
+                },
+                (format, ArrayBufferViewOrArrayBufferOrJsonWebKey::ArrayBuffer(buffer)) => {
+                    KeyData::from_bytes(format, buffer.to_vec())?
+                },
+            };
+            let algorithm = normalize_import_key_algorithm(cx, &algorithm)?;
+            import_key(
+                &self.global(),
+                algorithm,
+                key_data,
+                extractable,
+                &key_usages,
+            )
+        })();
+        match result {
+            Ok(key) => promise.resolve_native(&key),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-exportKey>
+    fn ExportKey(&self, format: KeyFormat, key: &CryptoKey, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let cx = GlobalScope::get_cx();
+        match export_key(format, key) {
+            Ok(KeyData::Jwk(jwk)) => promise.resolve_native(&JsonWebKey::from(jwk)),
+            Ok(key_data) => match key_data.into_bytes() {
+                Ok(bytes) => resolve_with_bytes(cx, &promise, &bytes),
+                Err(error) => promise.reject_error(error),
+            },
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-wrapKey>
+    fn WrapKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        key: &CryptoKey,
+        wrapping_key: &CryptoKey,
+        wrap_algorithm: AlgorithmIdentifier,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = (|| {
+            let name = normalize_wrap_key_algorithm(cx, &wrap_algorithm)?;
+            check_key(wrapping_key, name, KeyUsage::WrapKey)?;
+            let bytes = export_key(format, key)?.into_bytes()?;
+            aes_key_wrap(wrapping_key.handle(), &bytes)
+        })();
+        match result {
+            Ok(wrapped_key) => resolve_with_bytes(cx, &promise, &wrapped_key),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+
+    /// <https://w3c.github.io/webcrypto/#SubtleCrypto-method-unwrapKey>
+    fn UnwrapKey(
+        &self,
+        cx: JSContext,
+        format: KeyFormat,
+        wrapped_key: ArrayBufferViewOrArrayBuffer,
+        unwrapping_key: &CryptoKey,
+        unwrap_algorithm: AlgorithmIdentifier,
+        unwrapped_key_algorithm: AlgorithmIdentifier,
+        extractable: bool,
+        key_usages: Vec<KeyUsage>,
+        comp: InRealm,
+    ) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let result = (|| {
+            let name = normalize_wrap_key_algorithm(cx, &unwrap_algorithm)?;
+            let algorithm = normalize_import_key_algorithm(cx, &unwrapped_key_algorithm)?;
+            check_key(unwrapping_key, name, KeyUsage::UnwrapKey)?;
+            let bytes = aes_key_unwrap(unwrapping_key.handle(), &buffer_source_bytes(wrapped_key))?;
+            let key_data = KeyData::from_bytes(format, bytes)?;
+            import_key(
+                &self.global(),
+                algorithm,
+                key_data,
+                extractable,
+                &key_usages,
+            )
+        })();
+        match result {
+            Ok(key) => promise.resolve_native(&key),
+            Err(error) => promise.reject_error(error),
+        }
+        promise
+    }
+}
+
+#[derive(Clone, Copy)]
+enum HashAlgorithm {
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl HashAlgorithm {
+    fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha1 => sha1::Sha1::digest(data).to_vec(),
+            HashAlgorithm::Sha256 => sha2::Sha256::digest(data).to_vec(),
+            HashAlgorithm::Sha384 => sha2::Sha384::digest(data).to_vec(),
+            HashAlgorithm::Sha512 => sha2::Sha512::digest(data).to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum SignatureAlgorithm {
+    Ecdsa(HashAlgorithm),
+    Ed25519,
+}
+
+impl SignatureAlgorithm {
+    fn name(&self) -> &'static str {
+        match self {
+            SignatureAlgorithm::Ecdsa(_) => ALG_ECDSA,
+            SignatureAlgorithm::Ed25519 => ALG_ED25519,
+        }
+    }
+}
+
+/// The algorithms that keys can be imported for, with their parameters. The length of AES
+/// keys is that of the imported key.
+#[derive(Clone, Copy)]
+enum ImportAlgorithm {
+    Ecdsa(NamedCurve),
+    Ecdh(NamedCurve),
+    AesKw,
+    Ed25519,
+    X25519,
+}
+
+/// Key data that is imported or exported.
+enum KeyData {
+    Raw(Vec<u8>),
+    /// A DER encoded SubjectPublicKeyInfo.
+    /// <https://www.rfc-editor.org/rfc/rfc5280#section-4.1>
+    Spki(Vec<u8>),
+    /// A DER encoded PrivateKeyInfo.
+    /// <https://www.rfc-editor.org/rfc/rfc5208#section-5>
+    Pkcs8(Vec<u8>),
+    Jwk(Jwk),
+}
+
+impl KeyData {
+    /// The key data in `format` held in `bytes`, with JSON Web Keys in their JSON encoding.
+    fn from_bytes(format: KeyFormat, bytes: Vec<u8>) -> Fallible<KeyData> {
+        Ok(match format {
+            KeyFormat::Raw => KeyData::Raw(bytes),
+            KeyFormat::Spki => KeyData::Spki(bytes),
+            KeyFormat::Pkcs8 => KeyData::Pkcs8(bytes),
+            KeyFormat::Jwk => {
+                KeyData::Jwk(serde_json::from_slice(&bytes).map_err(|_| Error::Data)?)
+            },
+        })
+    }
+
+    /// The bytes of the key data, with JSON Web Keys in their JSON encoding.
+    fn into_bytes(self) -> Fallible<Vec<u8>> {
+        match self {
+            KeyData::Raw(bytes) | KeyData::Spki(bytes) | KeyData::Pkcs8(bytes) => Ok(bytes),
+            KeyData::Jwk(jwk) => serde_json::to_vec(&jwk).map_err(|_| Error::Operation),
+        }
+    }
+}
+
+/// A JSON Web Key, as imported and exported as a `JsonWebKey` dictionary, and as wrapped and
+/// unwrapped in its JSON encoding.
+/// <https://www.rfc-editor.org/rfc/rfc7517>
+#[derive(Default, Deserialize, Serialize)]
+struct Jwk {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    kty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    r#use: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key_ops: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alg: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ext: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    y: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    d: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    k: Option<String>,
+}
+
+impl From<&JsonWebKey> for Jwk {
+    fn from(jwk: &JsonWebKey) -> Jwk {
+        let string = |member: &Option<DOMString>| member.as_ref().map(|value| value.to_string());
+        Jwk {
+            kty: string(&jwk.kty),
+            r#use: string(&jwk.use_),
+            key_ops: jwk
+                .key_ops
+                .as_ref()
+                .map(|key_ops| key_ops.iter().map(|op| op.to_string()).collect()),
+            alg: string(&jwk.alg),
+            ext: jwk.ext,
+            crv: string(&jwk.crv),
+            x: string(&jwk.x),
+            y: string(&jwk.y),
+            d: string(&jwk.d),
+            k: string(&jwk.k),
+        }
+    }
+}
+
+impl From<Jwk> for JsonWebKey {
+    fn from(jwk: Jwk) -> JsonWebKey {
+        JsonWebKey {
+            kty: jwk.kty.map(DOMString::from),
+            use_: jwk.r#use.map(DOMString::from),
+            key_ops: jwk
+                .key_ops
+                .map(|key_ops| key_ops.into_iter().map(DOMString::from).collect()),
+            alg: jwk.alg.map(DOMString::from),
+            ext: jwk.ext,
+            crv: jwk.crv.map(DOMString::from),
+            x: jwk.x.map(DOMString::from),
+            y: jwk.y.map(DOMString::from),
+            d: jwk.d.map(DOMString::from),
+            k: jwk.k.map(DOMString::from),
+        }
+    }
+}
+
+impl Jwk {
+    /// The bytes of a base64url encoded member, which must be present.
+    fn bytes(member: &Option<String>) -> Fallible<Vec<u8>> {
+        let member = member.as_ref().ok_or(Error::Data)?;
+        URL_SAFE_NO_PAD.decode(member).map_err(|_| Error::Data)
+    }
+
+    fn encode(bytes: &[u8]) -> Option<String> {
+        Some(URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    /// The checks that apply to importing JSON Web Keys for any algorithm.
+    fn check(
+        &self,
+        kty: &str,
+        key_use: &str,
+        extractable: bool,
+        usages: &[KeyUsage],
+    ) -> Fallible<()> {
+        if self.kty.as_deref() != Some(kty) {
+            return Err(Error::Data);
+        }
+        if !usages.is_empty() &&
+            self.r#use
+                .as_deref()
+                .map_or(false, |value| value != key_use)
+        {
+            return Err(Error::Data);
+        }
+        if let Some(ref key_ops) = self.key_ops {
+            if usages
+                .iter()
+                .any(|usage| !key_ops.iter().any(|op| op == usage.as_str()))
+            {
+                return Err(Error::Data);
+            }
+        }
+        if self.ext == Some(false) && extractable {
+            return Err(Error::Data);
+        }
+        Ok(())
+    }
+}
+
+/// Convert the dictionary object of an algorithm, or the lack of one for algorithms given by
+/// name, to the parameters dictionary `T`.
+#[allow(unsafe_code)]
+fn parameters<T>(cx: JSContext, object: Option<&Heap<*mut JSObject>>) -> Fallible<T>
+where
+    T: FromJSValConvertible<Config = ()>,
+{
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    if let Some(object) = object {
+        value.set(ObjectValue(object.get()));
+    }
+    match unsafe { T::from_jsval(*cx, value.handle(), ()) } {
+        Ok(ConversionResult::Success(parameters)) => Ok(parameters),
+        Ok(ConversionResult::Failure(error)) => Err(Error::Type(error.into_owned())),
+        Err(()) => Err(Error::JSFailed),
+    }
+}
+
+/// The first steps of <https://w3c.github.io/webcrypto/#algorithm-normalization-normalize-an-algorithm>,
+/// finding the registered name of the algorithm among those of the operation. Callers then
+/// convert the parameters they expect from the returned dictionary object.
+fn normalize_algorithm_name<'a>(
+    cx: JSContext,
+    algorithm: &'a AlgorithmIdentifier,
+    registered: &[&'static str],
+) -> Fallible<(&'static str, Option<&'a Heap<*mut JSObject>>)> {
+    let (name, object) = match algorithm {
+        AlgorithmIdentifier::String(name) => (name.to_string(), None),
+        AlgorithmIdentifier::Object(object) => {
+            let algorithm: Algorithm = parameters(cx, Some(&**object))?;
+            (algorithm.name.to_string(), Some(&**object))
+        },
+    };
+    // Algorithm names are matched case-insensitively.
+    registered
+        .iter()
+        .find(|registered| registered.eq_ignore_ascii_case(&name))
+        .map(|registered| (*registered, object))
+        .ok_or(Error::NotSupported)
+}
+
+fn normalize_hash_algorithm(
+    cx: JSContext,
+    algorithm: &AlgorithmIdentifier,
+) -> Fallible<HashAlgorithm> {
+    let (name, _) = normalize_algorithm_name(cx, algorithm, DIGEST_ALGORITHMS)?;
+    Ok(match name {
+        ALG_SHA1 => HashAlgorithm::Sha1,
+        ALG_SHA256 => HashAlgorithm::Sha256,
+        ALG_SHA384 => HashAlgorithm::Sha384,
+        _ => HashAlgorithm::Sha512,
+    })
+}
+
+fn named_curve(name: &DOMString) -> Fallible<NamedCurve> {
+    NamedCurve::from_name(name).ok_or(Error::NotSupported)
+}
+
+fn normalize_signature_algorithm(
+    cx: JSContext,
+    algorithm: &AlgorithmIdentifier,
+) -> Fallible<SignatureAlgorithm> {
+    let (name, object) = normalize_algorithm_name(cx, algorithm, SIGNATURE_ALGORITHMS)?;
+    Ok(match name {
+        ALG_ECDSA => {
+            let params: RootedTraceableBox<EcdsaParams> = parameters(cx, object)?;
+            SignatureAlgorithm::Ecdsa(normalize_hash_algorithm(cx, &params.hash)?)
+        },
+        _ => SignatureAlgorithm::Ed25519,
+    })
+}
+
+fn normalize_generate_key_algorithm(
+    cx: JSContext,
+    algorithm: &AlgorithmIdentifier,
+) -> Fallible<CryptoKeyAlgorithm> {
+    let (name, object) = normalize_algorithm_name(cx, algorithm, KEY_ALGORITHMS)?;
+    Ok(match name {
+        ALG_ECDSA | ALG_ECDH => {
+            let params: EcKeyGenParams = parameters(cx, object)?;
+            let curve = named_curve(&params.namedCurve)?;
+            if name == ALG_ECDSA {
+                CryptoKeyAlgorithm::Ecdsa(curve)
+            } else {
+                CryptoKeyAlgorithm::Ecdh(curve)
+            }
+        },
+        ALG_AES_KW => {
+            let params: AesKeyGenParams = parameters(cx, object)?;
+            CryptoKeyAlgorithm::AesKw(params.length)
+        },
+        ALG_ED25519 => CryptoKeyAlgorithm::Ed25519,
+        _ => CryptoKeyAlgorithm::X25519,
+    })
+}
+
+fn normalize_import_key_algorithm(
+    cx: JSContext,
+    algorithm: &AlgorithmIdentifier,
+) -> Fallible<ImportAlgorithm> {
+    let (name, object) = normalize_algorithm_name(cx, algorithm, KEY_ALGORITHMS)?;
+    Ok(match name {
+        ALG_ECDSA | ALG_ECDH => {
+            let params: EcKeyImportParams = parameters(cx, object)?;
+            let curve = named_curve(&params.namedCurve)?;
+            if name == ALG_ECDSA {
+                ImportAlgorithm::Ecdsa(curve)
+            } else {
+                ImportAlgorithm::Ecdh(curve)
+            }
+        },
+        ALG_AES_KW => ImportAlgorithm::AesKw,
+        ALG_ED25519 => ImportAlgorithm::Ed25519,
+        _ => ImportAlgorithm::X25519,
+    })
+}
+
+/// Normalize an algorithm for the "deriveBits" operation, returning its name and the public
+/// key of the other party.
+fn normalize_derive_bits_algorithm(
+    cx: JSContext,
+    algorithm: &AlgorithmIdentifier,
+) -> Fallible<(&'static str, DomRoot<CryptoKey>)> {
+    let (name, object) = normalize_algorithm_name(cx, algorithm, DERIVE_BITS_ALGORITHMS)?;
+    let params: EcdhKeyDeriveParams = parameters(cx, object)?;
+    Ok((name, params.public))
+}
+
+/// Normalize an algorithm for the "get key length" operation, returning the length in bits.
+fn normalize_key_length_algorithm(cx: JSContext, algorithm: &AlgorithmIdentifier) -> Fallible<u32> {
+    let (_, object) = normalize_algorithm_name(cx, algorithm, KEY_LENGTH_ALGORITHMS)?;
+    let params: AesDerivedKeyParams = parameters(cx, object)?;
+    if ![128, 192, 256].contains(&params.length) {
+        return Err(Error::Operation);
+    }
+    Ok(params.length as u32)
+}
+
+fn normalize_wrap_key_algorithm(
+    cx: JSContext,
+    algorithm: &AlgorithmIdentifier,
+) -> Fallible<&'static str> {
+    normalize_algorithm_name(cx, algorithm, WRAP_KEY_ALGORITHMS).map(|(name, _)| name)
+}
+
+/// Check that `key` is for the algorithm `name` and may be used for `usage`.
+fn check_key(key: &CryptoKey, name: &str, usage: KeyUsage) -> Fallible<()> {
+    if key.algorithm().name() != name || !key.usages().contains(&usage) {
+        return Err(Error::InvalidAccess);
+    }
+    Ok(())
+}
+
+/// The usages that keys of `key_type` for `algorithm` may have, in the order of
+/// <https://w3c.github.io/webcrypto/#concept-usage-intersection>.
+fn allowed_usages(algorithm: CryptoKeyAlgorithm, key_type: KeyType) -> &'static [KeyUsage] {
+    match (algorithm, key_type) {
+        (CryptoKeyAlgorithm::Ecdsa(_), KeyType::Private) |
+        (CryptoKeyAlgorithm::Ed25519, KeyType::Private) => &[KeyUsage::Sign],
+        (CryptoKeyAlgorithm::Ecdsa(_), KeyType::Public) |
+        (CryptoKeyAlgorithm::Ed25519, KeyType::Public) => &[KeyUsage::Verify],
+        (CryptoKeyAlgorithm::Ecdh(_), KeyType::Private) |
+        (CryptoKeyAlgorithm::X25519, KeyType::Private) => {
+            &[KeyUsage::DeriveKey, KeyUsage::DeriveBits]
+        },
+        (CryptoKeyAlgorithm::AesKw(_), KeyType::Secret) => {
+            &[KeyUsage::WrapKey, KeyUsage::UnwrapKey]
+        },
+        _ => &[],
+    }
+}
+
+fn check_usages(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Fallible<()> {
+    if usages.iter().any(|usage| !allowed.contains(usage)) {
+        return Err(Error::Syntax);
+    }
+    Ok(())
+}
+
+/// The usages among `usages` that are in `allowed`, without duplicates and in the order of
+/// `allowed`.
+fn normalize_usages(usages: &[KeyUsage], allowed: &[KeyUsage]) -> Vec<KeyUsage> {
+    allowed
+        .iter()
+        .filter(|usage| usages.contains(usage))
+        .copied()
+        .collect()
+}
+
+fn new_key(
+    global: &GlobalScope,
+    algorithm: CryptoKeyAlgorithm,
+    key_type: KeyType,
+    extractable: bool,
+    usages: Vec<KeyUsage>,
+    handle: Handle,
+) -> Fallible<DomRoot<CryptoKey>> {
+    // Secret and private keys that cannot be used for anything are refused.
+    if key_type != KeyType::Public && usages.is_empty() {
+        return Err(Error::Syntax);
+    }
+    Ok(CryptoKey::new(
+        global,
+        key_type,
+        extractable,
+        algorithm,
+        usages,
+        handle,
+    ))
+}
+
+fn buffer_source_bytes(buffer_source: ArrayBufferViewOrArrayBuffer) -> Vec<u8> {
+    match buffer_source {
+        ArrayBufferViewOrArrayBuffer::ArrayBufferView(view) => view.to_vec(),
+        ArrayBufferViewOrArrayBuffer::ArrayBuffer(buffer) => buffer.to_vec(),
+    }
+}
+
+fn resolve_with_bytes(cx: JSContext, promise: &Promise, bytes: &[u8]) {
+    rooted!(in(*cx) let mut array_buffer = ptr::null_mut::<JSObject>());
+    match create_buffer_source::<ArrayBufferU8>(cx, bytes, array_buffer.handle_mut()) {
+        Ok(_) => promise.resolve_native(&*array_buffer),
+        Err(()) => promise.reject_error(Error::JSFailed),
+    }
+}
+
+fn sign(algorithm: SignatureAlgorithm, handle: &Handle, data: &[u8]) -> Fallible<Vec<u8>> {
+    match (algorithm, handle) {
+        (SignatureAlgorithm::Ecdsa(hash), Handle::P256Private(key)) => {
+            let signature: p256::ecdsa::Signature = p256::ecdsa::SigningKey::from(key)
+                .sign_prehash(&hash.digest(data))
+                .map_err(|_| Error::Operation)?;
+            Ok(signature.to_bytes().to_vec())
+        },
+        (SignatureAlgorithm::Ecdsa(hash), Handle::P384Private(key)) => {
+            let signature: p384::ecdsa::Signature = p384::ecdsa::SigningKey::from(key)
+                .sign_prehash(&hash.digest(data))
+                .map_err(|_| Error::Operation)?;
+            Ok(signature.to_bytes().to_vec())
+        },
+        (SignatureAlgorithm::Ed25519, Handle::Ed25519Private(key)) => {
+            Ok(key.sign(data).to_bytes().to_vec())
+        },
+        _ => Err(Error::InvalidAccess),
+    }
+}
+
+fn verify(
+    algorithm: SignatureAlgorithm,
+    handle: &Handle,
+    signature: &[u8],
+    data: &[u8],
+) -> Fallible<bool> {
+    // Signatures that cannot be decoded are not valid, rather than an error.
+    Ok(match (algorithm, handle) {
+        (SignatureAlgorithm::Ecdsa(hash), Handle::P256Public(key)) => {
+            p256::ecdsa::Signature::from_slice(signature).map_or(false, |signature| {
+                p256::ecdsa::VerifyingKey::from(key)
+                    .verify_prehash(&hash.digest(data), &signature)
+                    .is_ok()
+            })
+        },
+        (SignatureAlgorithm::Ecdsa(hash), Handle::P384Public(key)) => {
+            p384::ecdsa::Signature::from_slice(signature).map_or(false, |signature| {
+                p384::ecdsa::VerifyingKey::from(key)
+                    .verify_prehash(&hash.digest(data), &signature)
+                    .is_ok()
+            })
+        },
+        (SignatureAlgorithm::Ed25519, Handle::Ed25519Public(key)) => {
+            ed25519_dalek::Signature::from_slice(signature)
+                .map_or(false, |signature| key.verify(data, &signature).is_ok())
+        },
+        _ => return Err(Error::InvalidAccess),
+    })
+}
+
+/// The steps of the "derive bits" operations of ECDH and X25519.
+/// <https://w3c.github.io/webcrypto/#ecdh-operations>
+fn derive_bits(
+    base_key: &CryptoKey,
+    public_key: &CryptoKey,
+    length: Option<u32>,
+) -> Fallible<Vec<u8>> {
+    if base_key.key_type() != KeyType::Private ||
+        public_key.key_type() != KeyType::Public ||
+        base_key.algorithm() != public_key.algorithm()
+    {
+        return Err(Error::InvalidAccess);
+    }
+    let mut secret = match (base_key.handle(), public_key.handle()) {
+        (Handle::P256Private(private), Handle::P256Public(public)) => {
+            p256::ecdh::diffie_hellman(private.to_nonzero_scalar(), public.as_affine())
+                .raw_secret_bytes()
+                .to_vec()
+        },
+        (Handle::P384Private(private), Handle::P384Public(public)) => {
+            p384::ecdh::diffie_hellman(private.to_nonzero_scalar(), public.as_affine())
+                .raw_secret_bytes()
+                .to_vec()
+        },
+        (Handle::X25519Private(private), Handle::X25519Public(public)) => {
+            let shared_secret = private.diffie_hellman(public);
+            // Small order public keys give an all-zero secret.
+            if !shared_secret.was_contributory() {
+                return Err(Error::Operation);
+            }
+            shared_secret.as_bytes().to_vec()
+        },
+        _ => return Err(Error::InvalidAccess),
+    };
+    if let Some(length) = length {
+        let length = length as usize;
+        if length > secret.len() * 8 {
+            return Err(Error::Operation);
+        }
+        secret.truncate((length + 7) / 8);
+        if length % 8 != 0 {
+            if let Some(last) = secret.last_mut() {
+                *last &= 0xff << (8 - length % 8);
+            }
+        }
+    }
+    Ok(secret)
+}
+
+fn aes_key_wrap(handle: &Handle, data: &[u8]) -> Fallible<Vec<u8>> {
+    let key = match handle {
+        Handle::Aes(key) => key,
+        _ => return Err(Error::InvalidAccess),
+    };
+    // Key wrapping works on whole blocks of 64 bits.
+    if data.len() % 8 != 0 {
+        return Err(Error::Operation);
+    }
+    let wrapped = match key.len() {
+        16 => KekAes128::from(<[u8; 16]>::try_from(&key[..]).unwrap()).wrap_vec(data),
+        24 => KekAes192::from(<[u8; 24]>::try_from(&key[..]).unwrap()).wrap_vec(data),
+        _ => KekAes256::from(<[u8; 32]>::try_from(&key[..]).unwrap()).wrap_vec(data),
+    };
+    wrapped.map_err(|_| Error::Operation)
+}
+
+fn aes_key_unwrap(handle: &Handle, data: &[u8]) -> Fallible<Vec<u8>> {
+    let key = match handle {
+        Handle::Aes(key) => key,
+        _ => return Err(Error::InvalidAccess),
+    };
+    let unwrapped = match key.len() {
+        16 => KekAes128::from(<[u8; 16]>::try_from(&key[..]).unwrap()).unwrap_vec(data),
+        24 => KekAes192::from(<[u8; 24]>::try_from(&key[..]).unwrap()).unwrap_vec(data),
+        _ => KekAes256::from(<[u8; 32]>::try_from(&key[..]).unwrap()).unwrap_vec(data),
+    };
+    unwrapped.map_err(|_| Error::Operation)
+}
+
+/// The "import key" operations of the supported algorithms.
+fn import_key(
+    global: &GlobalScope,
+    algorithm: ImportAlgorithm,
+    key_data: KeyData,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<DomRoot<CryptoKey>> {
+    let (key_algorithm, key_type, handle) = match algorithm {
+        ImportAlgorithm::Ecdsa(curve) | ImportAlgorithm::Ecdh(curve) => {
+            let key_algorithm = match algorithm {
+                ImportAlgorithm::Ecdsa(_) => CryptoKeyAlgorithm::Ecdsa(curve),
+                _ => CryptoKeyAlgorithm::Ecdh(curve),
+            };
+            let (key_type, handle) =
+                import_ec_key(key_algorithm, curve, key_data, extractable, usages)?;
+            (key_algorithm, key_type, handle)
+        },
+        ImportAlgorithm::AesKw => {
+            let key = match key_data {
+                KeyData::Raw(bytes) => bytes,
+                KeyData::Spki(_) | KeyData::Pkcs8(_) => return Err(Error::NotSupported),
+                KeyData::Jwk(jwk) => {
+                    jwk.check("oct", "enc", extractable, usages)?;
+                    let key = Jwk::bytes(&jwk.k)?;
+                    let alg = match key.len() {
+                        16 => "A128KW",
+                        24 => "A192KW",
+                        _ => "A256KW",
+                    };
+                    if jwk.alg.as_deref().map_or(false, |value| value != alg) {
+                        return Err(Error::Data);
+                    }
+                    key
+                },
+            };
+            if ![16, 24, 32].contains(&key.len()) {
+                return Err(Error::Data);
+            }
+            (
+                CryptoKeyAlgorithm::AesKw(key.len() as u16 * 8),
+                KeyType::Secret,
+                Handle::Aes(key),
+            )
+        },
+        ImportAlgorithm::Ed25519 | ImportAlgorithm::X25519 => {
+            let key_algorithm = match algorithm {
+                ImportAlgorithm::Ed25519 => CryptoKeyAlgorithm::Ed25519,
+                _ => CryptoKeyAlgorithm::X25519,
+            };
+            let (key_type, handle) = import_okp_key(key_algorithm, key_data, extractable, usages)?;
+            (key_algorithm, key_type, handle)
+        },
+    };
+
+    check_usages(usages, allowed_usages(key_algorithm, key_type))?;
+    let usages = normalize_usages(usages, allowed_usages(key_algorithm, key_type));
+    // Public keys are always extractable.
+    let extractable = extractable || key_type == KeyType::Public;
+    new_key(global, key_algorithm, key_type, extractable, usages, handle)
+}
+
+fn fixed_bytes<const N: usize>(bytes: &[u8]) -> Fallible<[u8; N]> {
+    <[u8; N]>::try_from(bytes).map_err(|_| Error::Data)
+}
+
+/// Import the key of an ECDSA or ECDH key `algorithm`, as a public key for raw and SPKI data
+/// and as a private key for PKCS #8 data. The curve of DER encoded keys must be `curve`.
+fn import_ec_key(
+    algorithm: CryptoKeyAlgorithm,
+    curve: NamedCurve,
+    key_data: KeyData,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<(KeyType, Handle)> {
+    let jwk = match key_data {
+        KeyData::Raw(bytes) => {
+            let handle = match curve {
+                NamedCurve::P256 => Handle::P256Public(
+                    p256::PublicKey::from_sec1_bytes(&bytes).map_err(|_| Error::Data)?,
+                ),
+                NamedCurve::P384 => Handle::P384Public(
+                    p384::PublicKey::from_sec1_bytes(&bytes).map_err(|_| Error::Data)?,
+                ),
+            };
+            return Ok((KeyType::Public, handle));
+        },
+        KeyData::Spki(bytes) => {
+            let handle = match curve {
+                NamedCurve::P256 => Handle::P256Public(
+                    p256::PublicKey::from_public_key_der(&bytes).map_err(|_| Error::Data)?,
+                ),
+                NamedCurve::P384 => Handle::P384Public(
+                    p384::PublicKey::from_public_key_der(&bytes).map_err(|_| Error::Data)?,
+                ),
+            };
+            return Ok((KeyType::Public, handle));
+        },
+        KeyData::Pkcs8(bytes) => {
+            let handle = match curve {
+                NamedCurve::P256 => Handle::P256Private(
+                    p256::SecretKey::from_pkcs8_der(&bytes).map_err(|_| Error::Data)?,
+                ),
+                NamedCurve::P384 => Handle::P384Private(
+                    p384::SecretKey::from_pkcs8_der(&bytes).map_err(|_| Error::Data)?,
+                ),
+            };
+            return Ok((KeyType::Private, handle));
+        },
+        KeyData::Jwk(jwk) => jwk,
+    };
+
+    let (key_use, alg) = match (algorithm, curve) {
+        (CryptoKeyAlgorithm::Ecdsa(_), NamedCurve::P256) => ("sig", Some("ES256")),
+        (CryptoKeyAlgorithm::Ecdsa(_), NamedCurve::P384) => ("sig", Some("ES384")),
+        _ => ("enc", None),
+    };
+    jwk.check("EC", key_use, extractable, usages)?;
+    if jwk.crv.as_deref() != Some(curve.name()) {
+        return Err(Error::Data);
+    }
+    if let (Some(alg), Some(jwk_alg)) = (alg, jwk.alg.as_deref()) {
+        if alg != jwk_alg {
+            return Err(Error::Data);
+        }
+    }
+
+    let x = Jwk::bytes(&jwk.x)?;
+    let y = Jwk::bytes(&jwk.y)?;
+    let private = jwk.d.is_some();
+    // The private key must match the public key that is given with it.
+    let handle = match curve {
+        NamedCurve::P256 => {
+            let point = EncodedPoint::<p256::NistP256>::from_affine_coordinates(
+                &fixed_bytes::<32>(&x)?.into(),
+                &fixed_bytes::<32>(&y)?.into(),
+                false,
+            );
+            let public =
+                Option::from(p256::PublicKey::from_encoded_point(&point)).ok_or(Error::Data)?;
+            if private {
+                let key =
+                    p256::SecretKey::from_slice(&Jwk::bytes(&jwk.d)?).map_err(|_| Error::Data)?;
+                if key.public_key() != public {
+                    return Err(Error::Data);
+                }
+                Handle::P256Private(key)
+            } else {
+                Handle::P256Public(public)
+            }
+        },
+        NamedCurve::P384 => {
+            let point = EncodedPoint::<p384::NistP384>::from_affine_coordinates(
+                &fixed_bytes::<48>(&x)?.into(),
+                &fixed_bytes::<48>(&y)?.into(),
+                false,
+            );
+            let public =
+                Option::from(p384::PublicKey::from_encoded_point(&point)).ok_or(Error::Data)?;
+            if private {
+                let key =
+                    p384::SecretKey::from_slice(&Jwk::bytes(&jwk.d)?).map_err(|_| Error::Data)?;
+                if key.public_key() != public {
+                    return Err(Error::Data);
+                }
+                Handle::P384Private(key)
+            } else {
+                Handle::P384Public(public)
+            }
+        },
+    };
+    let key_type = if private {
+        KeyType::Private
+    } else {
+        KeyType::Public
+    };
+    Ok((key_type, handle))
+}
+
+/// Import the key of an Ed25519 or X25519 key `algorithm`, as a public key for raw and SPKI
+/// data and as a private key for PKCS #8 data.
+fn import_okp_key(
+    algorithm: CryptoKeyAlgorithm,
+    key_data: KeyData,
+    extractable: bool,
+    usages: &[KeyUsage],
+) -> Fallible<(KeyType, Handle)> {
+    let ed25519 = algorithm == CryptoKeyAlgorithm::Ed25519;
+    let oid = if ed25519 { OID_ED25519 } else { OID_X25519 };
+    let (x, d) = match key_data {
+        KeyData::Raw(bytes) => (Some(fixed_bytes::<32>(&bytes)?), None),
+        KeyData::Spki(bytes) => (Some(okp_key_from_spki(oid, &bytes)?), None),
+        KeyData::Pkcs8(bytes) => (None, Some(okp_key_from_pkcs8(oid, &bytes)?)),
+        KeyData::Jwk(jwk) => {
+            jwk.check(
+                "OKP",
+                if ed25519 { "sig" } else { "enc" },
+                extractable,
+                usages,
+            )?;
+            if jwk.crv.as_deref() != Some(algorithm.name()) {
+                return Err(Error::Data);
+            }
+            if ed25519 &&
+                jwk.alg
+                    .as_deref()
+                    .map_or(false, |alg| alg != "EdDSA" && alg != "Ed25519")
+            {
+                return Err(Error::Data);
+            }
+            let d = match jwk.d {
+                Some(_) => Some(fixed_bytes::<32>(&Jwk::bytes(&jwk.d)?)?),
+                None => None,
+            };
+            (Some(fixed_bytes::<32>(&Jwk::bytes(&jwk.x)?)?), d)
+        },
+    };
+
+    // The private key must match the public key that is given with it, if any.
+    Ok(match (ed25519, d) {
+        (true, None) => (
+            KeyType::Public,
+            Handle::Ed25519Public(
+                ed25519_dalek::VerifyingKey::from_bytes(&x.ok_or(Error::Data)?)
+                    .map_err(|_| Error::Data)?,
+            ),
+        ),
+        (true, Some(d)) => {
+            let key = ed25519_dalek::SigningKey::from_bytes(&d);
+            if x.map_or(false, |x| key.verifying_key().to_bytes() != x) {
+                return Err(Error::Data);
+            }
+            (KeyType::Private, Handle::Ed25519Private(key))
+        },
+        (false, None) => (
+            KeyType::Public,
+            Handle::X25519Public(x25519_dalek::PublicKey::from(x.ok_or(Error::Data)?)),
+        ),
+        (false, Some(d)) => {
+            let key = x25519_dalek::StaticSecret::from(d);
+            let public = x25519_dalek::PublicKey::from(&key);
+            if x.map_or(false, |x| public.to_bytes() != x) {
+                return Err(Error::Data);
+            }
+            (KeyType::Private, Handle::X25519Private(key))
+        },
+    })
+}
+
+/// The public key of `der`, the SubjectPublicKeyInfo of an Ed25519 or X25519 key of algorithm
+/// `oid`.
+/// <https://www.rfc-editor.org/rfc/rfc8410#section-4>
+fn okp_key_from_spki(oid: ObjectIdentifier, der: &[u8]) -> Fallible<[u8; 32]> {
+    let info = SubjectPublicKeyInfoRef::from_der(der).map_err(|_| Error::Data)?;
+    if info.algorithm.oid != oid || info.algorithm.parameters.is_some() {
+        return Err(Error::Data);
+    }
+    fixed_bytes(info.subject_public_key.as_bytes().ok_or(Error::Data)?)
+}
+
+/// The private key of `der`, the PrivateKeyInfo of an Ed25519 or X25519 key of algorithm
+/// `oid`, which holds the key as an octet string.
+/// <https://www.rfc-editor.org/rfc/rfc8410#section-7>
+fn okp_key_from_pkcs8(oid: ObjectIdentifier, der: &[u8]) -> Fallible<[u8; 32]> {
+    let info = PrivateKeyInfo::from_der(der).map_err(|_| Error::Data)?;
+    if info.algorithm.oid != oid || info.algorithm.parameters.is_some() {
+        return Err(Error::Data);
+    }
+    let key = OctetStringRef::from_der(info.private_key).map_err(|_| Error::Data)?;
+    fixed_bytes(key.as_bytes())
+}
+
+fn okp_spki(oid: ObjectIdentifier, key: &[u8; 32]) -> Fallible<Vec<u8>> {
+    let info = SubjectPublicKeyInfoRef {
+        algorithm: AlgorithmIdentifierRef {
+            oid,
+            parameters: None,
+        },
+        subject_public_key: BitStringRef::from_bytes(key).map_err(|_| Error::Operation)?,
+    };
+    info.to_der().map_err(|_| Error::Operation)
+}
+
+fn okp_pkcs8(oid: ObjectIdentifier, key: &[u8; 32]) -> Fallible<Vec<u8>> {
+    let private_key = OctetStringRef::new(key)
+        .and_then(|key| key.to_der())
+        .map_err(|_| Error::Operation)?;
+    let algorithm = AlgorithmIdentifierRef {
+        oid,
+        parameters: None,
+    };
+    PrivateKeyInfo::new(algorithm, &private_key)
+        .to_der()
+        .map_err(|_| Error::Operation)
+}
+
+/// The "export key" operations of the supported algorithms.
+fn export_key(format: KeyFormat, key: &CryptoKey) -> Fallible<KeyData> {
+    if !key.extractable() {
+        return Err(Error::InvalidAccess);
+    }
+    match format {
+        KeyFormat::Raw => export_raw_key(key.handle()).map(KeyData::Raw),
+        KeyFormat::Jwk => {
+            let mut jwk = export_jwk(key.algorithm(), key.handle());
+            jwk.key_ops = Some(
+                key.usages()
+                    .iter()
+                    .map(|usage| usage.as_str().to_owned())
+                    .collect(),
+            );
+            jwk.ext = Some(key.extractable());
+            Ok(KeyData::Jwk(jwk))
+        },
+        KeyFormat::Spki => export_spki(key.handle()).map(KeyData::Spki),
+        KeyFormat::Pkcs8 => export_pkcs8(key.handle()).map(KeyData::Pkcs8),
+    }
+}
+
+fn export_spki(handle: &Handle) -> Fallible<Vec<u8>> {
+    let der = match handle {
+        Handle::P256Public(key) => key.to_public_key_der(),
+        Handle::P384Public(key) => key.to_public_key_der(),
+        Handle::Ed25519Public(key) => return okp_spki(OID_ED25519, &key.to_bytes()),
+        Handle::X25519Public(key) => return okp_spki(OID_X25519, &key.to_bytes()),
+        Handle::Aes(_) => return Err(Error::NotSupported),
+        // Only public keys can be exported as SPKI.
+        _ => return Err(Error::InvalidAccess),
+    };
+    der.map(|der| der.as_bytes().to_vec())
+        .map_err(|_| Error::Operation)
+}
+
+fn export_pkcs8(handle: &Handle) -> Fallible<Vec<u8>> {
+    let der = match handle {
+        Handle::P256Private(key) => key.to_pkcs8_der(),
+        Handle::P384Private(key) => key.to_pkcs8_der(),
+        Handle::Ed25519Private(key) => return okp_pkcs8(OID_ED25519, &key.to_bytes()),
+        Handle::X25519Private(key) => return okp_pkcs8(OID_X25519, &key.to_bytes()),
+        Handle::Aes(_) => return Err(Error::NotSupported),
+        // Only private keys can be exported as PKCS #8.
+        _ => return Err(Error::InvalidAccess),
+    };
+    der.map(|der| der.as_bytes().to_vec())
+        .map_err(|_| Error::Operation)
+}
+
+fn export_raw_key(handle: &Handle) -> Fallible<Vec<u8>> {
+    Ok(match handle {
+        Handle::Aes(key) => key.clone(),
+        Handle::P256Public(key) => key.to_encoded_point(false).as_bytes().to_vec(),
+        Handle::P384Public(key) => key.to_encoded_point(false).as_bytes().to_vec(),
+        Handle::Ed25519Public(key) => key.to_bytes().to_vec(),
+        Handle::X25519Public(key) => key.to_bytes().to_vec(),
+        // Only public and secret keys can be exported raw.
+        _ => return Err(Error::InvalidAccess),
+    })
+}
+
+fn export_jwk(algorithm: CryptoKeyAlgorithm, handle: &Handle) -> Jwk {
+    let ec_jwk = |point: &[u8], coordinate_length: usize, d: Option<Vec<u8>>| Jwk {
+        kty: Some("EC".to_owned()),
+        // Uncompressed points are a tag byte followed by the coordinates.
+        x: Jwk::encode(&point[1..1 + coordinate_length]),
+        y: Jwk::encode(&point[1 + coordinate_length..]),
+        d: d.as_deref().and_then(Jwk::encode),
+        ..Default::default()
+    };
+    let mut jwk = match handle {
+        Handle::Aes(key) => Jwk {
+            kty: Some("oct".to_owned()),
+            k: Jwk::encode(key),
+            alg: Some(format!("A{}KW", key.len() * 8)),
+            ..Default::default()
+        },
+        Handle::P256Private(key) => ec_jwk(
+            key.public_key().to_encoded_point(false).as_bytes(),
+            32,
+            Some(key.to_bytes().to_vec()),
+        ),
+        Handle::P256Public(key) => ec_jwk(key.to_encoded_point(false).as_bytes(), 32, None),
+        Handle::P384Private(key) => ec_jwk(
+            key.public_key().to_encoded_point(false).as_bytes(),
+            48,
+            Some(key.to_bytes().to_vec()),
+        ),
+        Handle::P384Public(key) => ec_jwk(key.to_encoded_point(false).as_bytes(), 48, None),
+        Handle::Ed25519Private(key) => Jwk {
+            x: Jwk::encode(&key.verifying_key().to_bytes()),
+            d: Jwk::encode(&key.to_bytes()),
+            ..Default::default()
+        },
+        Handle::Ed25519Public(key) => Jwk {
+            x: Jwk::encode(&key.to_bytes()),
+            ..Default::default()
+        },
+        Handle::X25519Private(key) => Jwk {
+            x: Jwk::encode(&x25519_dalek::PublicKey::from(key).to_bytes()),
+            d: Jwk::encode(&key.to_bytes()),
+            ..Default::default()
+        },
+        Handle::X25519Public(key) => Jwk {
+            x: Jwk::encode(&key.to_bytes()),
+            ..Default::default()
+        },
+    };
+    match algorithm {
+        CryptoKeyAlgorithm::Ecdsa(curve) | CryptoKeyAlgorithm::Ecdh(curve) => {
+            jwk.crv = Some(curve.name().to_owned());
+            if let CryptoKeyAlgorithm::Ecdsa(curve) = algorithm {
+                jwk.alg = Some(
+                    match curve {
+                        NamedCurve::P256 => "ES256",
+                        NamedCurve::P384 => "ES384",
+                    }
+                    .to_owned(),
+                );
+            }
+        },
+        CryptoKeyAlgorithm::Ed25519 | CryptoKeyAlgorithm::X25519 => {
+            jwk.kty = Some("OKP".to_owned());
+            jwk.crv = Some(algorithm.name().to_owned());
+            if algorithm == CryptoKeyAlgorithm::Ed25519 {
+                jwk.alg = Some("Ed25519".to_owned());
+            }
+        },
+        CryptoKeyAlgorithm::AesKw(_) => {},
+    }
+    jwk
+}
@@ -0,0 +1,94 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use dom_struct::dom_struct;
+
+use crate::dom::bindings::codegen::Bindings::TaskAttributionTimingBinding::TaskAttributionTimingMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::reflector::reflect_dom_object;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::performanceentry::PerformanceEntry;
+
+/// The container of the browsing context a long task ran in, as the document it is reported
+/// to sees it.
+/// <https://w3c.github.io/longtasks/#sec-TaskAttributionTiming>
+#[dom_struct]
+pub struct TaskAttributionTiming {
+    entry: PerformanceEntry,
+    container_type: DOMString,
+    container_src: DOMString,
+    container_id: DOMString,
+    container_name: DOMString,
+}
+
+impl TaskAttributionTiming {
+    fn new_inherited(container: Option<&Element>) -> TaskAttributionTiming {
+        // Without a container in the document, the task is attributed to a window.
+        let (container_type, container_src, container_id, container_name) = match container {
+            Some(container) => (
+                DOMString::from(if container.is::<HTMLIFrameElement>() {
+                    "iframe"
+                } else {
+                    "window"
+                }),
+                container.get_string_attribute(&local_name!("src")),
+                container.get_string_attribute(&local_name!("id")),
+                container.get_string_attribute(&local_name!("name")),
+            ),
+            None => (
+                DOMString::from("window"),
+                DOMString::new(),
+                DOMString::new(),
+                DOMString::new(),
+            ),
+        };
+        TaskAttributionTiming {
+            entry: PerformanceEntry::new_inherited(
+                DOMString::from("unknown"),
+                DOMString::from("taskattribution"),
+                0.,
+                0.,
+            ),
+            container_type,
+            container_src,
+            container_id,
+            container_name,
+        }
+    }
+
+    #[allow(crown::unrooted_must_root)]
+    pub fn new(
+        global: &GlobalScope,
+        container: Option<&Element>,
+    ) -> DomRoot<TaskAttributionTiming> {
+        let entry = TaskAttributionTiming::new_inherited(container);
+        reflect_dom_object(Box::new(entry), global)
+    }
+}
+
+impl TaskAttributionTimingMethods for TaskAttributionTiming {
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containertype
+    fn ContainerType(&self) -> DOMString {
+        self.container_type.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containersrc
+    fn ContainerSrc(&self) -> DOMString {
+        self.container_src.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containerid
+    fn ContainerId(&self) -> DOMString {
+        self.container_id.clone()
+    }
+
+    // https://w3c.github.io/longtasks/#dom-taskattributiontiming-containername
+    fn ContainerName(&self) -> DOMString {
+        self.container_name.clone()
+    }
+}
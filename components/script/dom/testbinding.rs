@@ -10,11 +10,11 @@ use std::rc::Rc;
 
 use dom_struct::dom_struct;
 use js::jsapi::{Heap, JSObject, JS_NewPlainObject};
-use js::jsval::{JSVal, NullValue};
+use js::jsval::{JSVal, NullValue, UndefinedValue};
 use js::rust::{CustomAutoRooterGuard, HandleObject, HandleValue};
 use js::typedarray::{self, Uint8ClampedArray};
 use script_traits::serializable::BlobImpl;
-use script_traits::MsDuration;
+use script_traits::{JsWorld, MsDuration};
 use servo_config::prefs;
 
 use crate::dom::bindings::buffer_source::create_buffer_source;
@@ -1073,6 +1073,18 @@ impl TestBindingMethods for TestBinding {
         true
     }
 
+    fn EvaluateInIsolatedWorld(&self, world: DOMString, code: DOMString) -> bool {
+        let global = self.global();
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        global.as_window().evaluate_js_in_isolated_world(
+            &JsWorld::Isolated(world.into()),
+            &code,
+            rval.handle_mut(),
+        ) && rval.is_boolean() &&
+            rval.to_boolean()
+    }
+
     fn GetDictionaryWithParent(&self, s1: DOMString, s2: DOMString) -> TestDictionaryWithParent {
         TestDictionaryWithParent {
             parent: TestDictionaryParent {
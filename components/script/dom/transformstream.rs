@@ -0,0 +1,606 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::cell::Cell;
+use std::ffi::CString;
+use std::ptr::{self, NonNull};
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{HandleValueArray, Heap, JSObject, JS_ClearPendingException};
+use js::jsval::{JSVal, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{Construct1, JS_CallFunctionName, JS_GetPendingException, JS_GetProperty};
+use js::rust::{HandleObject, HandleValue, MutableHandleObject, MutableHandleValue};
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::QueuingStrategyBinding::QueuingStrategy;
+use crate::dom::bindings::codegen::Bindings::TransformStreamBinding::{
+    TransformStreamMethods, Transformer,
+};
+use crate::dom::bindings::conversions::ConversionResult;
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object_with_proto, DomObject, Reflector};
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::transformstreamdefaultcontroller::TransformStreamDefaultController;
+use crate::dom::transformstreamunderlyingsink::TransformStreamUnderlyingSink;
+use crate::dom::transformstreamunderlyingsource::TransformStreamUnderlyingSource;
+use crate::realms::{enter_realm, InRealm};
+use crate::script_runtime::JSContext;
+
+/// <https://streams.spec.whatwg.org/#ts-class>
+///
+/// The readable and writable sides are streams of SpiderMonkey, whose controllers are
+/// driven through their JS interfaces. What the specification reads from their internal
+/// slots is tracked here instead.
+#[dom_struct]
+pub struct TransformStream {
+    reflector_: Reflector,
+    /// <https://streams.spec.whatwg.org/#transformstream-readable>
+    #[ignore_malloc_size_of = "SM handles JS values"]
+    readable: Heap<*mut JSObject>,
+    /// The controller of `readable`, given to its underlying source when it starts.
+    #[ignore_malloc_size_of = "SM handles JS values"]
+    readable_controller: Heap<*mut JSObject>,
+    /// Whether `readable` was closed, or asked to be, or cancelled.
+    readable_closed: Cell<bool>,
+    /// Whether `readable` was errored, with `readable_stored_error`.
+    readable_errored: Cell<bool>,
+    #[ignore_malloc_size_of = "SM handles JS values"]
+    readable_stored_error: Heap<JSVal>,
+    /// <https://streams.spec.whatwg.org/#transformstream-writable>
+    #[ignore_malloc_size_of = "SM handles JS values"]
+    writable: Heap<*mut JSObject>,
+    /// The controller of `writable`, given to its underlying sink when it starts.
+    #[ignore_malloc_size_of = "SM handles JS values"]
+    writable_controller: Heap<*mut JSObject>,
+    /// Whether `writable` was errored or aborted, with `writable_stored_error`.
+    writable_errored: Cell<bool>,
+    #[ignore_malloc_size_of = "SM handles JS values"]
+    writable_stored_error: Heap<JSVal>,
+    /// <https://streams.spec.whatwg.org/#transformstream-backpressure>
+    backpressure: Cell<bool>,
+    /// <https://streams.spec.whatwg.org/#transformstream-backpressurechangepromise>
+    #[ignore_malloc_size_of = "Rc"]
+    backpressure_change_promise: DomRefCell<Option<Rc<Promise>>>,
+    /// The promise that both sides start with, resolved once the transformer has started.
+    #[ignore_malloc_size_of = "Rc"]
+    start_promise: DomRefCell<Option<Rc<Promise>>>,
+    /// <https://streams.spec.whatwg.org/#transformstream-controller>
+    controller: MutNullableDom<TransformStreamDefaultController>,
+}
+
+impl TransformStream {
+    fn new_inherited() -> TransformStream {
+        TransformStream {
+            reflector_: Reflector::new(),
+            readable: Heap::default(),
+            readable_controller: Heap::default(),
+            readable_closed: Cell::new(false),
+            readable_errored: Cell::new(false),
+            readable_stored_error: Heap::default(),
+            writable: Heap::default(),
+            writable_controller: Heap::default(),
+            writable_errored: Cell::new(false),
+            writable_stored_error: Heap::default(),
+            backpressure: Cell::new(false),
+            backpressure_change_promise: DomRefCell::new(None),
+            start_promise: DomRefCell::new(None),
+            controller: MutNullableDom::new(None),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#ts-constructor>
+    #[allow(non_snake_case)]
+    pub fn Constructor(
+        cx: JSContext,
+        global: &GlobalScope,
+        proto: Option<HandleObject>,
+        transformer: Option<*mut JSObject>,
+        writable_strategy: &QueuingStrategy,
+        readable_strategy: &QueuingStrategy,
+    ) -> Fallible<DomRoot<TransformStream>> {
+        let _ac = enter_realm(global);
+
+        // Steps 1-2.
+        rooted!(in(*cx) let transformer = transformer.unwrap_or(ptr::null_mut()));
+        rooted!(in(*cx) let mut transformer_value = UndefinedValue());
+        if !transformer.is_null() {
+            transformer_value.set(ObjectValue(transformer.get()));
+        }
+        let transformer_dict = match Transformer::new(cx, transformer_value.handle()) {
+            Ok(ConversionResult::Success(dict)) => dict,
+            Ok(ConversionResult::Failure(error)) => return Err(Error::Type(error.into_owned())),
+            Err(_) => return Err(Error::JSFailed),
+        };
+
+        // Steps 3-4.
+        if !transformer_dict.readableType.get().is_undefined() {
+            return Err(Error::Range("Invalid readableType".to_owned()));
+        }
+        if !transformer_dict.writableType.get().is_undefined() {
+            return Err(Error::Range("Invalid writableType".to_owned()));
+        }
+
+        // Steps 5-8.
+        let readable_strategy = QueuingStrategy {
+            highWaterMark: Some(extract_high_water_mark(readable_strategy, 0.)?),
+            size: readable_strategy.size.clone(),
+        };
+        let writable_strategy = QueuingStrategy {
+            highWaterMark: Some(extract_high_water_mark(writable_strategy, 1.)?),
+            size: writable_strategy.size.clone(),
+        };
+
+        // Steps 9-10.
+        let stream = reflect_dom_object_with_proto(
+            Box::new(TransformStream::new_inherited()),
+            global,
+            proto,
+        );
+        *stream.start_promise.borrow_mut() = Some(Promise::new(global));
+        stream.initialize(cx, global, &writable_strategy, &readable_strategy)?;
+
+        // Step 11.
+        let controller = TransformStreamDefaultController::new(
+            global,
+            &stream,
+            transformer.handle(),
+            transformer_dict.transform.clone(),
+            transformer_dict.flush.clone(),
+        );
+        stream.controller.set(Some(&controller));
+
+        // Step 12.
+        rooted!(in(*cx) let mut start_result = UndefinedValue());
+        if let Some(start) = &transformer_dict.start {
+            start_result.set(start.Call_(
+                &transformer.handle(),
+                &controller,
+                ExceptionHandling::Rethrow,
+            )?);
+        }
+        stream.start_promise().resolve(cx, start_result.handle());
+
+        Ok(stream)
+    }
+
+    /// <https://streams.spec.whatwg.org/#initialize-transform-stream>
+    fn initialize(
+        &self,
+        cx: JSContext,
+        global: &GlobalScope,
+        writable_strategy: &QueuingStrategy,
+        readable_strategy: &QueuingStrategy,
+    ) -> Fallible<()> {
+        // Steps 1-5.
+        let sink = TransformStreamUnderlyingSink::new(global, self);
+        rooted!(in(*cx) let mut writable = ptr::null_mut::<JSObject>());
+        construct_stream(
+            cx,
+            global,
+            "WritableStream",
+            sink.reflector().get_jsobject(),
+            writable_strategy,
+            writable.handle_mut(),
+        )?;
+        self.writable.set(writable.get());
+
+        // Steps 6-8.
+        let source = TransformStreamUnderlyingSource::new(global, self);
+        rooted!(in(*cx) let mut readable = ptr::null_mut::<JSObject>());
+        construct_stream(
+            cx,
+            global,
+            "ReadableStream",
+            source.reflector().get_jsobject(),
+            readable_strategy,
+            readable.handle_mut(),
+        )?;
+        self.readable.set(readable.get());
+
+        // Steps 9-12.
+        self.set_backpressure(true);
+        Ok(())
+    }
+
+    pub fn start_promise(&self) -> Rc<Promise> {
+        self.start_promise.borrow().clone().unwrap()
+    }
+
+    pub fn set_readable_controller(&self, controller: *mut JSObject) {
+        self.readable_controller.set(controller);
+    }
+
+    pub fn set_writable_controller(&self, controller: *mut JSObject) {
+        self.writable_controller.set(controller);
+    }
+
+    pub fn controller(&self) -> DomRoot<TransformStreamDefaultController> {
+        self.controller.get().unwrap()
+    }
+
+    pub fn backpressure(&self) -> bool {
+        self.backpressure.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-set-backpressure>
+    pub fn set_backpressure(&self, backpressure: bool) {
+        // Steps 1-2.
+        let promise = Promise::new(&self.global());
+        if let Some(old) = self
+            .backpressure_change_promise
+            .borrow_mut()
+            .replace(promise)
+        {
+            old.resolve_native(&());
+        }
+        // Step 3.
+        self.backpressure.set(backpressure);
+    }
+
+    pub fn backpressure_change_promise(&self) -> Rc<Promise> {
+        self.backpressure_change_promise.borrow().clone().unwrap()
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-error>
+    pub fn error(&self, cx: JSContext, error: HandleValue) {
+        // Step 1.
+        self.error_readable(cx, error);
+        // Step 2.
+        self.error_writable_and_unblock_write(cx, error);
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-error-writable-and-unblock-write>
+    pub fn error_writable_and_unblock_write(&self, cx: JSContext, error: HandleValue) {
+        // Step 1.
+        self.controller().clear_algorithms();
+
+        // Step 2, WritableStreamDefaultControllerErrorIfNeeded.
+        if !self.writable_errored.replace(true) {
+            self.writable_stored_error.set(error.get());
+            rooted!(in(*cx) let mut rval = UndefinedValue());
+            let _ = self.call_controller_method(
+                cx,
+                &self.writable_controller,
+                "error",
+                &[error.get()],
+                rval.handle_mut(),
+            );
+        }
+
+        // Step 3.
+        self.unblock_write();
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-unblock-write>
+    pub fn unblock_write(&self) {
+        if self.backpressure.get() {
+            self.set_backpressure(false);
+        }
+    }
+
+    /// The stored error of the writable side, if it is erroring.
+    pub fn writable_stored_error(&self, mut rval: MutableHandleValue) -> bool {
+        rval.set(self.writable_stored_error.get());
+        self.writable_errored.get()
+    }
+
+    /// The stored error of the readable side, if it is errored.
+    pub fn readable_stored_error(&self, mut rval: MutableHandleValue) -> bool {
+        rval.set(self.readable_stored_error.get());
+        self.readable_errored.get()
+    }
+
+    /// Note that the readable side was cancelled by its consumer.
+    pub fn set_readable_cancelled(&self) {
+        self.readable_closed.set(true);
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-can-close-or-enqueue>
+    pub fn readable_can_close_or_enqueue(&self) -> bool {
+        !self.readable_closed.get() && !self.readable_errored.get()
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-get-desired-size>
+    #[allow(unsafe_code)]
+    pub fn readable_desired_size(&self, cx: JSContext) -> Option<f64> {
+        let _ac = enter_realm(self);
+        rooted!(in(*cx) let controller = self.readable_controller.get());
+        rooted!(in(*cx) let mut size = UndefinedValue());
+        let name = CString::new("desiredSize").unwrap();
+        unsafe {
+            if !JS_GetProperty(*cx, controller.handle(), name.as_ptr(), size.handle_mut()) {
+                return None;
+            }
+        }
+        if size.is_number() {
+            Some(size.to_number())
+        } else {
+            None
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-has-backpressure>
+    ///
+    /// This is approximated by the desired size, as the pending read requests are unknown.
+    pub fn readable_has_backpressure(&self, cx: JSContext) -> bool {
+        !matches!(self.readable_desired_size(cx), Some(size) if size > 0.)
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-enqueue>
+    ///
+    /// If the size of the chunk cannot be computed, the readable side is errored with the
+    /// exception, which is taken.
+    #[allow(unsafe_code)]
+    pub fn readable_enqueue(&self, cx: JSContext, chunk: HandleValue) -> Fallible<()> {
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        let result = self.call_controller_method(
+            cx,
+            &self.readable_controller,
+            "enqueue",
+            &[chunk.get()],
+            rval.handle_mut(),
+        );
+        if result.is_err() {
+            rooted!(in(*cx) let mut error = UndefinedValue());
+            unsafe {
+                JS_GetPendingException(*cx, error.handle_mut());
+                JS_ClearPendingException(*cx);
+            }
+            self.readable_errored.set(true);
+            self.readable_stored_error.set(error.get());
+        }
+        result
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-close>
+    pub fn readable_close(&self, cx: JSContext) {
+        if !self.readable_can_close_or_enqueue() {
+            return;
+        }
+        self.readable_closed.set(true);
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        if self
+            .call_controller_method(
+                cx,
+                &self.readable_controller,
+                "close",
+                &[],
+                rval.handle_mut(),
+            )
+            .is_err()
+        {
+            clear_pending_exception(cx);
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#readable-stream-default-controller-error>
+    fn error_readable(&self, cx: JSContext, error: HandleValue) {
+        if self.readable_errored.get() {
+            return;
+        }
+        if !self.readable_closed.get() {
+            self.readable_errored.set(true);
+            self.readable_stored_error.set(error.get());
+        }
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        let _ = self.call_controller_method(
+            cx,
+            &self.readable_controller,
+            "error",
+            &[error.get()],
+            rval.handle_mut(),
+        );
+    }
+
+    /// Call a method of the controller of one of the sides, in the realm of this stream.
+    /// Exceptions are left pending.
+    #[allow(unsafe_code)]
+    fn call_controller_method(
+        &self,
+        cx: JSContext,
+        controller: &Heap<*mut JSObject>,
+        name: &str,
+        args: &[JSVal],
+        rval: MutableHandleValue,
+    ) -> Fallible<()> {
+        let _ac = enter_realm(self);
+        rooted!(in(*cx) let controller = controller.get());
+        if controller.is_null() {
+            return Err(Error::InvalidState);
+        }
+        let name = CString::new(name).unwrap();
+        unsafe {
+            let args = HandleValueArray::from_rooted_slice(args);
+            if !JS_CallFunctionName(*cx, controller.handle(), name.as_ptr(), &args, rval) {
+                return Err(Error::JSFailed);
+            }
+        }
+        Ok(())
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-write-algorithm>
+    pub fn sink_write(&self, cx: JSContext, chunk: HandleValue, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let controller = self.controller();
+
+        // Step 2.
+        if self.backpressure.get() {
+            // Steps 2.1-2.2.
+            let handler = PromiseNativeHandler::new(
+                &self.global(),
+                Some(Box::new(BackpressureChangeHandler {
+                    stream: DomRoot::from_ref(self),
+                    chunk: Heap::boxed(chunk.get()),
+                    promise: promise.clone(),
+                })),
+                None,
+            );
+            self.backpressure_change_promise()
+                .append_native_handler(&handler, comp);
+            return promise;
+        }
+
+        // Step 3.
+        controller.perform_transform(cx, chunk, &promise, comp);
+        promise
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-abort-algorithm>
+    pub fn sink_abort(&self, cx: JSContext, reason: HandleValue) {
+        self.error(cx, reason);
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-close-algorithm>
+    pub fn sink_close(&self, cx: JSContext, comp: InRealm) -> Rc<Promise> {
+        let promise = Promise::new_in_current_realm(comp);
+        let controller = self.controller();
+
+        // Steps 1-4.
+        let flush_promise = controller.flush(cx, comp);
+        controller.clear_algorithms();
+
+        // Step 5.
+        let handler = PromiseNativeHandler::new(
+            &self.global(),
+            Some(Box::new(FlushHandler {
+                stream: DomRoot::from_ref(self),
+                promise: promise.clone(),
+                rejected: false,
+            })),
+            Some(Box::new(FlushHandler {
+                stream: DomRoot::from_ref(self),
+                promise: promise.clone(),
+                rejected: true,
+            })),
+        );
+        flush_promise.append_native_handler(&handler, comp);
+        promise
+    }
+}
+
+impl TransformStreamMethods for TransformStream {
+    /// <https://streams.spec.whatwg.org/#ts-readable>
+    fn Readable(&self, _cx: JSContext) -> NonNull<JSObject> {
+        NonNull::new(self.readable.get()).unwrap()
+    }
+
+    /// <https://streams.spec.whatwg.org/#ts-writable>
+    fn Writable(&self, _cx: JSContext) -> NonNull<JSObject> {
+        NonNull::new(self.writable.get()).unwrap()
+    }
+}
+
+/// <https://streams.spec.whatwg.org/#validate-and-normalize-high-water-mark>
+fn extract_high_water_mark(strategy: &QueuingStrategy, default: f64) -> Fallible<f64> {
+    match strategy.highWaterMark {
+        None => Ok(default),
+        Some(high_water_mark) if high_water_mark.is_nan() || high_water_mark < 0. => Err(
+            Error::Range("The high water mark must be a non-negative number".to_owned()),
+        ),
+        Some(high_water_mark) => Ok(high_water_mark),
+    }
+}
+
+/// Create one of the streams of SpiderMonkey, as `new name(underlying, strategy)` does.
+///
+/// TODO: The constructors are looked up on the global, where pages can replace them.
+#[allow(unsafe_code)]
+fn construct_stream(
+    cx: JSContext,
+    global: &GlobalScope,
+    name: &str,
+    underlying: *mut JSObject,
+    strategy: &QueuingStrategy,
+    rval: MutableHandleObject,
+) -> Fallible<()> {
+    rooted!(in(*cx) let global_object = global.reflector().get_jsobject().get());
+    rooted!(in(*cx) let mut constructor = UndefinedValue());
+    rooted!(in(*cx) let underlying = ObjectValue(underlying));
+    rooted!(in(*cx) let mut strategy_value = UndefinedValue());
+    let name = CString::new(name).unwrap();
+    unsafe {
+        if !JS_GetProperty(
+            *cx,
+            global_object.handle(),
+            name.as_ptr(),
+            constructor.handle_mut(),
+        ) {
+            return Err(Error::JSFailed);
+        }
+        strategy.to_jsval(*cx, strategy_value.handle_mut());
+        let args = [underlying.get(), strategy_value.get()];
+        let args = HandleValueArray::from_rooted_slice(&args);
+        if !Construct1(*cx, constructor.handle(), &args, rval) {
+            return Err(Error::JSFailed);
+        }
+    }
+    Ok(())
+}
+
+#[allow(unsafe_code)]
+fn clear_pending_exception(cx: JSContext) {
+    unsafe { JS_ClearPendingException(*cx) };
+}
+
+/// Transforms a chunk once the backpressure of a stream is relieved.
+/// Steps 2.2 of <https://streams.spec.whatwg.org/#transform-stream-default-sink-write-algorithm>
+#[derive(JSTraceable, MallocSizeOf)]
+struct BackpressureChangeHandler {
+    stream: DomRoot<TransformStream>,
+    #[ignore_malloc_size_of = "mozjs"]
+    chunk: Box<Heap<JSVal>>,
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+}
+
+impl Callback for BackpressureChangeHandler {
+    fn callback(&self, cx: JSContext, _v: HandleValue, realm: InRealm) {
+        // Steps 2.2.1-2.2.3.
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        if self.stream.writable_stored_error(error.handle_mut()) {
+            return self.promise.reject(cx, error.handle());
+        }
+        // Step 2.2.4.
+        rooted!(in(*cx) let chunk = self.chunk.get());
+        self.stream
+            .controller()
+            .perform_transform(cx, chunk.handle(), &self.promise, realm);
+    }
+}
+
+/// Settles the promise of closing the writable side of a stream once the transformer was
+/// flushed.
+/// Step 5 of <https://streams.spec.whatwg.org/#transform-stream-default-sink-close-algorithm>
+#[derive(JSTraceable, MallocSizeOf)]
+struct FlushHandler {
+    stream: DomRoot<TransformStream>,
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    rejected: bool,
+}
+
+impl Callback for FlushHandler {
+    fn callback(&self, cx: JSContext, v: HandleValue, _realm: InRealm) {
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        if self.rejected {
+            // Steps 5.2.1-5.2.2.
+            self.stream.error(cx, v);
+            self.stream.readable_stored_error(error.handle_mut());
+            return self.promise.reject(cx, error.handle());
+        }
+        // Step 5.1.1.
+        if self.stream.readable_stored_error(error.handle_mut()) {
+            return self.promise.reject(cx, error.handle());
+        }
+        // Step 5.1.2.
+        self.stream.readable_close(cx);
+        self.promise.resolve_native(&());
+    }
+}
@@ -0,0 +1,258 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::jsapi::{ExceptionStackBehavior, Heap, JSObject};
+use js::jsval::UndefinedValue;
+use js::rust::wrappers::JS_SetPendingException;
+use js::rust::{HandleObject, HandleValue};
+
+use crate::dom::bindings::callback::ExceptionHandling;
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::TransformStreamBinding::{
+    TransformerFlushCallback, TransformerTransformCallback,
+};
+use crate::dom::bindings::codegen::Bindings::TransformStreamDefaultControllerBinding::TransformStreamDefaultControllerMethods;
+use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::promisenativehandler::{Callback, PromiseNativeHandler};
+use crate::dom::transformstream::TransformStream;
+use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
+
+/// <https://streams.spec.whatwg.org/#ts-default-controller-class>
+#[dom_struct]
+pub struct TransformStreamDefaultController {
+    reflector_: Reflector,
+    /// <https://streams.spec.whatwg.org/#transformstreamdefaultcontroller-stream>
+    stream: Dom<TransformStream>,
+    /// The transformer that the algorithms are called on.
+    #[ignore_malloc_size_of = "SM handles JS values"]
+    transformer: Heap<*mut JSObject>,
+    /// <https://streams.spec.whatwg.org/#transformstreamdefaultcontroller-transformalgorithm>,
+    /// which enqueues chunks as they are when the transformer has no `transform`.
+    #[ignore_malloc_size_of = "Rc"]
+    transform: DomRefCell<Option<Rc<TransformerTransformCallback>>>,
+    /// <https://streams.spec.whatwg.org/#transformstreamdefaultcontroller-flushalgorithm>
+    #[ignore_malloc_size_of = "Rc"]
+    flush: DomRefCell<Option<Rc<TransformerFlushCallback>>>,
+}
+
+impl TransformStreamDefaultController {
+    fn new_inherited(
+        stream: &TransformStream,
+        transform: Option<Rc<TransformerTransformCallback>>,
+        flush: Option<Rc<TransformerFlushCallback>>,
+    ) -> TransformStreamDefaultController {
+        TransformStreamDefaultController {
+            reflector_: Reflector::new(),
+            stream: Dom::from_ref(stream),
+            transformer: Heap::default(),
+            transform: DomRefCell::new(transform),
+            flush: DomRefCell::new(flush),
+        }
+    }
+
+    /// <https://streams.spec.whatwg.org/#set-up-transform-stream-default-controller-from-transformer>
+    pub fn new(
+        global: &GlobalScope,
+        stream: &TransformStream,
+        transformer: HandleObject,
+        transform: Option<Rc<TransformerTransformCallback>>,
+        flush: Option<Rc<TransformerFlushCallback>>,
+    ) -> DomRoot<TransformStreamDefaultController> {
+        let controller = reflect_dom_object(
+            Box::new(TransformStreamDefaultController::new_inherited(
+                stream, transform, flush,
+            )),
+            global,
+        );
+        controller.transformer.set(transformer.get());
+        controller
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-clear-algorithms>
+    pub fn clear_algorithms(&self) {
+        *self.transform.borrow_mut() = None;
+        *self.flush.borrow_mut() = None;
+        self.transformer.set(std::ptr::null_mut());
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-enqueue>
+    #[allow(unsafe_code)]
+    fn enqueue(&self, cx: JSContext, chunk: HandleValue) -> ErrorResult {
+        // Steps 1-3.
+        if !self.stream.readable_can_close_or_enqueue() {
+            return Err(Error::Type(
+                "The readable side of the stream is closed or errored".to_owned(),
+            ));
+        }
+
+        // Step 4-5.
+        if self.stream.readable_enqueue(cx, chunk).is_err() {
+            rooted!(in(*cx) let mut error = UndefinedValue());
+            self.stream.readable_stored_error(error.handle_mut());
+            self.stream
+                .error_writable_and_unblock_write(cx, error.handle());
+            unsafe {
+                JS_SetPendingException(*cx, error.handle(), ExceptionStackBehavior::Capture);
+            }
+            return Err(Error::JSFailed);
+        }
+
+        // Steps 6-7.
+        let backpressure = self.stream.readable_has_backpressure(cx);
+        if backpressure != self.stream.backpressure() {
+            self.stream.set_backpressure(true);
+        }
+        Ok(())
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-controller-perform-transform>
+    ///
+    /// `promise` is settled with the outcome of the transform.
+    pub fn perform_transform(
+        &self,
+        cx: JSContext,
+        chunk: HandleValue,
+        promise: &Rc<Promise>,
+        comp: InRealm,
+    ) {
+        let global = self.global();
+
+        // Step 1.
+        let transform = self.transform.borrow().clone();
+        let transform_promise = match transform {
+            Some(transform) => {
+                rooted!(in(*cx) let transformer = self.transformer.get());
+                transform
+                    .Call_(
+                        &transformer.handle(),
+                        chunk,
+                        self,
+                        ExceptionHandling::Rethrow,
+                    )
+                    .and_then(|result| {
+                        rooted!(in(*cx) let result = result);
+                        Promise::new_resolved(&global, cx, result.handle())
+                    })
+            },
+            // Step 2 of
+            // <https://streams.spec.whatwg.org/#set-up-transform-stream-default-controller-from-transformer>
+            None => self.enqueue(cx, chunk).and_then(|()| {
+                rooted!(in(*cx) let undefined = UndefinedValue());
+                Promise::new_resolved(&global, cx, undefined.handle())
+            }),
+        };
+        let transform_promise = transform_promise.unwrap_or_else(|error| {
+            let rejected = Promise::new_in_current_realm(comp);
+            rejected.reject_error(error);
+            rejected
+        });
+
+        // Step 2.
+        let handler = PromiseNativeHandler::new(
+            &global,
+            Some(Box::new(TransformHandler {
+                stream: DomRoot::from_ref(&*self.stream),
+                promise: promise.clone(),
+                rejected: false,
+            })),
+            Some(Box::new(TransformHandler {
+                stream: DomRoot::from_ref(&*self.stream),
+                promise: promise.clone(),
+                rejected: true,
+            })),
+        );
+        transform_promise.append_native_handler(&handler, comp);
+    }
+
+    /// Run the <https://streams.spec.whatwg.org/#transformstreamdefaultcontroller-flushalgorithm>.
+    pub fn flush(&self, cx: JSContext, comp: InRealm) -> Rc<Promise> {
+        let global = self.global();
+        let flush = self.flush.borrow().clone();
+        let result = match flush {
+            Some(flush) => {
+                rooted!(in(*cx) let transformer = self.transformer.get());
+                flush
+                    .Call_(&transformer.handle(), self, ExceptionHandling::Rethrow)
+                    .and_then(|result| {
+                        rooted!(in(*cx) let result = result);
+                        Promise::new_resolved(&global, cx, result.handle())
+                    })
+            },
+            None => {
+                rooted!(in(*cx) let undefined = UndefinedValue());
+                Promise::new_resolved(&global, cx, undefined.handle())
+            },
+        };
+        result.unwrap_or_else(|error| {
+            let rejected = Promise::new_in_current_realm(comp);
+            rejected.reject_error(error);
+            rejected
+        })
+    }
+}
+
+impl TransformStreamDefaultControllerMethods for TransformStreamDefaultController {
+    /// <https://streams.spec.whatwg.org/#ts-default-controller-desired-size>
+    fn GetDesiredSize(&self) -> Option<f64> {
+        self.stream.readable_desired_size(GlobalScope::get_cx())
+    }
+
+    /// <https://streams.spec.whatwg.org/#ts-default-controller-enqueue>
+    fn Enqueue(&self, cx: JSContext, chunk: HandleValue) -> Fallible<()> {
+        self.enqueue(cx, chunk)
+    }
+
+    /// <https://streams.spec.whatwg.org/#ts-default-controller-error>
+    fn Error(&self, cx: JSContext, reason: HandleValue) {
+        self.stream.error(cx, reason);
+    }
+
+    /// <https://streams.spec.whatwg.org/#ts-default-controller-terminate>
+    #[allow(unsafe_code)]
+    fn Terminate(&self) {
+        let cx = GlobalScope::get_cx();
+        // Steps 1-3.
+        self.stream.readable_close(cx);
+        // Steps 4-5.
+        rooted!(in(*cx) let mut error = UndefinedValue());
+        unsafe {
+            Error::Type("The stream was terminated".to_owned()).to_jsval(
+                *cx,
+                &self.global(),
+                error.handle_mut(),
+            );
+        }
+        self.stream
+            .error_writable_and_unblock_write(cx, error.handle());
+    }
+}
+
+/// Settles the promise of a write to the writable side of a stream with the outcome of
+/// transforming the chunk, erroring the stream if that failed.
+/// Step 2 of <https://streams.spec.whatwg.org/#transform-stream-default-controller-perform-transform>
+#[derive(JSTraceable, MallocSizeOf)]
+struct TransformHandler {
+    stream: DomRoot<TransformStream>,
+    #[ignore_malloc_size_of = "Rc"]
+    promise: Rc<Promise>,
+    rejected: bool,
+}
+
+impl Callback for TransformHandler {
+    fn callback(&self, cx: JSContext, v: HandleValue, _realm: InRealm) {
+        if self.rejected {
+            self.stream.error(cx, v);
+            return self.promise.reject(cx, v);
+        }
+        self.promise.resolve_native(&());
+    }
+}
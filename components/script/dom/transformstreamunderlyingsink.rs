@@ -0,0 +1,66 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::jsapi::JSObject;
+use js::rust::HandleValue;
+
+use crate::dom::bindings::codegen::Bindings::TransformStreamUnderlyingSinkBinding::TransformStreamUnderlyingSinkMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::transformstream::TransformStream;
+use crate::realms::InRealm;
+use crate::script_runtime::JSContext;
+
+/// The underlying sink of the writable side of a [TransformStream].
+/// Steps 1-5 of <https://streams.spec.whatwg.org/#initialize-transform-stream>
+#[dom_struct]
+pub struct TransformStreamUnderlyingSink {
+    reflector_: Reflector,
+    stream: Dom<TransformStream>,
+}
+
+impl TransformStreamUnderlyingSink {
+    pub fn new(
+        global: &GlobalScope,
+        stream: &TransformStream,
+    ) -> DomRoot<TransformStreamUnderlyingSink> {
+        reflect_dom_object(
+            Box::new(TransformStreamUnderlyingSink {
+                reflector_: Reflector::new(),
+                stream: Dom::from_ref(stream),
+            }),
+            global,
+        )
+    }
+}
+
+impl TransformStreamUnderlyingSinkMethods for TransformStreamUnderlyingSink {
+    fn Start(&self, _cx: JSContext, controller: *mut JSObject) -> Rc<Promise> {
+        self.stream.set_writable_controller(controller);
+        self.stream.start_promise()
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-write-algorithm>
+    fn Write(&self, cx: JSContext, chunk: HandleValue, comp: InRealm) -> Rc<Promise> {
+        self.stream.sink_write(cx, chunk, comp)
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-close-algorithm>
+    fn Close(&self, comp: InRealm) -> Rc<Promise> {
+        self.stream.sink_close(GlobalScope::get_cx(), comp)
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-sink-abort-algorithm>
+    fn Abort(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        self.stream.sink_abort(cx, reason);
+        let promise = Promise::new(&self.global());
+        promise.resolve_native(&());
+        promise
+    }
+}
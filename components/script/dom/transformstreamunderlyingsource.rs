@@ -0,0 +1,64 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::rc::Rc;
+
+use dom_struct::dom_struct;
+use js::jsapi::JSObject;
+use js::rust::HandleValue;
+
+use crate::dom::bindings::codegen::Bindings::TransformStreamUnderlyingSourceBinding::TransformStreamUnderlyingSourceMethods;
+use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
+use crate::dom::bindings::root::{Dom, DomRoot};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::promise::Promise;
+use crate::dom::transformstream::TransformStream;
+use crate::script_runtime::JSContext;
+
+/// The underlying source of the readable side of a [TransformStream].
+/// Steps 6-8 of <https://streams.spec.whatwg.org/#initialize-transform-stream>
+#[dom_struct]
+pub struct TransformStreamUnderlyingSource {
+    reflector_: Reflector,
+    stream: Dom<TransformStream>,
+}
+
+impl TransformStreamUnderlyingSource {
+    pub fn new(
+        global: &GlobalScope,
+        stream: &TransformStream,
+    ) -> DomRoot<TransformStreamUnderlyingSource> {
+        reflect_dom_object(
+            Box::new(TransformStreamUnderlyingSource {
+                reflector_: Reflector::new(),
+                stream: Dom::from_ref(stream),
+            }),
+            global,
+        )
+    }
+}
+
+impl TransformStreamUnderlyingSourceMethods for TransformStreamUnderlyingSource {
+    fn Start(&self, _cx: JSContext, controller: *mut JSObject) -> Rc<Promise> {
+        self.stream.set_readable_controller(controller);
+        self.stream.start_promise()
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-source-pull>
+    fn Pull(&self) -> Rc<Promise> {
+        // Steps 1-3.
+        self.stream.set_backpressure(false);
+        // Step 4.
+        self.stream.backpressure_change_promise()
+    }
+
+    /// <https://streams.spec.whatwg.org/#transform-stream-default-source-cancel>
+    fn Cancel(&self, cx: JSContext, reason: HandleValue) -> Rc<Promise> {
+        self.stream.set_readable_cancelled();
+        self.stream.error_writable_and_unblock_write(cx, reason);
+        let promise = Promise::new(&self.global());
+        promise.resolve_native(&());
+        promise
+    }
+}
@@ -10,7 +10,7 @@ use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::htmldatalistelement::HTMLDataListElement;
 use crate::dom::htmlelement::HTMLElement;
-use crate::dom::node::Node;
+use crate::dom::node::{document_from_node, Node};
 use crate::dom::validitystate::{ValidationFlags, ValidityState};
 
 /// Trait for elements with constraint validation support
@@ -60,13 +60,16 @@ pub trait Validatable {
 
         // Step 1.2.
         if !event.DefaultPrevented() {
-            println!(
-                "Validation error: {}",
-                validation_message_for_flags(&self.validity_state(), flags)
-            );
-            if let Some(html_elem) = self.as_element().downcast::<HTMLElement>() {
+            let element = self.as_element();
+            if let Some(html_elem) = element.downcast::<HTMLElement>() {
                 html_elem.Focus();
             }
+            let document = document_from_node(element);
+            document.popups().show_validation_message(
+                &document,
+                element,
+                validation_message_for_flags(&self.validity_state(), flags),
+            );
         }
 
         // Step 1.3.
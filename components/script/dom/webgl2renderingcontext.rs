@@ -218,46 +218,79 @@ impl WebGL2RenderingContext {
         self.base.current_vao_webgl2()
     }
 
-    pub fn validate_uniform_block_for_draw(&self) {
+    /// Check that each uniform block of the current program reads from a buffer that holds
+    /// at least its data, as laid out by the shader.
+    /// <https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.16>
+    fn validate_uniform_blocks_for_draw(&self) -> WebGLResult<()> {
         let program = match self.base.current_program() {
             Some(program) => program,
-            None => return,
+            None => return Ok(()),
         };
         for uniform_block in program.active_uniform_blocks().iter() {
-            let data_size = uniform_block.size as usize;
-            for block in program.active_uniforms().iter() {
-                let index = match block.bind_index {
-                    Some(index) => index,
-                    None => continue,
-                };
-                let indexed = &self.indexed_uniform_buffer_bindings[index as usize];
-                let buffer = match indexed.buffer.get() {
-                    Some(buffer) => buffer,
-                    None => {
-                        self.base.webgl_error(InvalidOperation);
-                        return;
-                    },
-                };
-                if indexed.size.get() == 0 {
-                    if data_size > buffer.capacity() {
-                        self.base.webgl_error(InvalidOperation);
-                        return;
-                    }
-                } else {
-                    let start = indexed.start.get() as usize;
-                    let mut size = indexed.size.get() as usize;
-                    if start >= size {
-                        self.base.webgl_error(InvalidOperation);
-                        return;
-                    }
-                    size -= start;
-                    if data_size > size {
-                        self.base.webgl_error(InvalidOperation);
-                        return;
-                    }
-                }
+            let indexed = self
+                .indexed_uniform_buffer_bindings
+                .get(uniform_block.binding as usize)
+                .ok_or(InvalidOperation)?;
+            let buffer = indexed.buffer.get().ok_or(InvalidOperation)?;
+            let start = indexed.start.get() as usize;
+            let available = buffer.capacity().saturating_sub(start);
+            // A binding of the whole buffer has no size of its own.
+            let available = match indexed.size.get() as usize {
+                0 => available,
+                size => cmp::min(size, available),
+            };
+            if available < uniform_block.size as usize {
+                return Err(InvalidOperation);
+            }
+            // A buffer cannot be read from and written to by the same draw.
+            if self.is_bound_for_transform_feedback(&buffer) {
+                return Err(InvalidOperation);
             }
         }
+        Ok(())
+    }
+
+    /// Check that a draw with `mode` can take place while transform feedback is active,
+    /// which captures primitives of one kind only, and only from the draws without indices.
+    /// <https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.15>
+    fn validate_transform_feedback_for_draw(&self, mode: u32, indexed: bool) -> WebGLResult<()> {
+        if !self.is_transform_feedback_capturing() {
+            return Ok(());
+        }
+        let primitive_mode = self
+            .current_transform_feedback
+            .get()
+            .map_or(0, |transform_feedback| transform_feedback.primitive_mode());
+        if indexed || mode != primitive_mode {
+            return Err(InvalidOperation);
+        }
+        Ok(())
+    }
+
+    /// Whether transform feedback is active and not paused.
+    fn is_transform_feedback_capturing(&self) -> bool {
+        self.current_transform_feedback
+            .get()
+            .map_or(false, |transform_feedback| {
+                transform_feedback.is_active() && !transform_feedback.is_paused()
+            })
+    }
+
+    /// Whether transform feedback is active and writes to `buffer`.
+    fn is_bound_for_transform_feedback(&self, buffer: &WebGLBuffer) -> bool {
+        let active = self
+            .current_transform_feedback
+            .get()
+            .map_or(false, |transform_feedback| transform_feedback.is_active());
+        active &&
+            self.indexed_transform_feedback_buffer_bindings
+                .iter()
+                .any(|binding| {
+                    binding
+                        .buffer
+                        .get()
+                        .map_or(false, |bound| &*bound == buffer)
+                })
     }
 
     fn validate_vertex_attribs_for_draw(&self) {
@@ -1501,7 +1534,14 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         }
         let copy_bytes = copy_count * dst_elem_size;
 
-        // TODO(mmatyas): Transform Feedback
+        // The buffers transform feedback writes to cannot be read while it is active.
+        if target == constants::TRANSFORM_FEEDBACK_BUFFER &&
+            self.current_transform_feedback
+                .get()
+                .map_or(false, |transform_feedback| transform_feedback.is_active())
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
 
         let src_byte_offset = src_byte_offset as usize;
         if src_byte_offset + copy_bytes > bound_buffer.capacity() ||
@@ -1793,14 +1833,24 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// <https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11>
     fn DrawArrays(&self, mode: u32, first: i32, count: i32) {
-        self.validate_uniform_block_for_draw();
+        handle_potential_webgl_error!(self.base, self.validate_uniform_blocks_for_draw(), return);
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_for_draw(mode, false),
+            return
+        );
         self.validate_vertex_attribs_for_draw();
         self.base.DrawArrays(mode, first, count)
     }
 
     /// <https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.11>
     fn DrawElements(&self, mode: u32, count: i32, type_: u32, offset: i64) {
-        self.validate_uniform_block_for_draw();
+        handle_potential_webgl_error!(self.base, self.validate_uniform_blocks_for_draw(), return);
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_for_draw(mode, true),
+            return
+        );
         self.validate_vertex_attribs_for_draw();
         self.base.DrawElements(mode, count, type_, offset)
     }
@@ -2198,6 +2248,18 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// <https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9>
     fn LinkProgram(&self, program: &WebGLProgram) {
+        // The program transform feedback captures the output of cannot change meanwhile.
+        let transform_feedback_active = self
+            .current_transform_feedback
+            .get()
+            .map_or(false, |transform_feedback| transform_feedback.is_active());
+        if transform_feedback_active &&
+            self.base
+                .current_program()
+                .map_or(false, |current| &*current == program)
+        {
+            return self.base.webgl_error(InvalidOperation);
+        }
         self.base.LinkProgram(program)
     }
 
@@ -2770,6 +2832,9 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// <https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.9>
     fn UseProgram(&self, program: Option<&WebGLProgram>) {
+        if self.is_transform_feedback_capturing() {
+            return self.base.webgl_error(InvalidOperation);
+        }
         self.base.UseProgram(program)
     }
 
@@ -3308,7 +3373,12 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// <https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.9>
     fn DrawArraysInstanced(&self, mode: u32, first: i32, count: i32, primcount: i32) {
-        self.validate_uniform_block_for_draw();
+        handle_potential_webgl_error!(self.base, self.validate_uniform_blocks_for_draw(), return);
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_for_draw(mode, false),
+            return
+        );
         self.validate_vertex_attribs_for_draw();
         handle_potential_webgl_error!(
             self.base,
@@ -3326,7 +3396,12 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
         offset: i64,
         primcount: i32,
     ) {
-        self.validate_uniform_block_for_draw();
+        handle_potential_webgl_error!(self.base, self.validate_uniform_blocks_for_draw(), return);
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_for_draw(mode, true),
+            return
+        );
         self.validate_vertex_attribs_for_draw();
         handle_potential_webgl_error!(
             self.base,
@@ -3349,7 +3424,12 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
             self.base.webgl_error(InvalidValue);
             return;
         }
-        self.validate_uniform_block_for_draw();
+        handle_potential_webgl_error!(self.base, self.validate_uniform_blocks_for_draw(), return);
+        handle_potential_webgl_error!(
+            self.base,
+            self.validate_transform_feedback_for_draw(mode, true),
+            return
+        );
         self.validate_vertex_attribs_for_draw();
         handle_potential_webgl_error!(
             self.base,
@@ -3655,19 +3735,29 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
     /// <https://www.khronos.org/registry/webgl/specs/latest/2.0/#3.7.13>
     fn BindSampler(&self, unit: u32, sampler: Option<&WebGLSampler>) {
-        if let Some(sampler) = sampler {
-            handle_potential_webgl_error!(self.base, self.base.validate_ownership(sampler), return);
-
-            if unit as usize >= self.samplers.len() {
-                self.base.webgl_error(InvalidValue);
-                return;
-            }
+        if unit as usize >= self.samplers.len() {
+            self.base.webgl_error(InvalidValue);
+            return;
+        }
 
-            let result = sampler.bind(&self.base, unit);
-            match result {
-                Ok(_) => self.samplers[unit as usize].set(Some(sampler)),
-                Err(error) => self.base.webgl_error(error),
-            }
+        match sampler {
+            Some(sampler) => {
+                handle_potential_webgl_error!(
+                    self.base,
+                    self.base.validate_ownership(sampler),
+                    return
+                );
+                let result = sampler.bind(&self.base, unit);
+                match result {
+                    Ok(_) => self.samplers[unit as usize].set(Some(sampler)),
+                    Err(error) => self.base.webgl_error(error),
+                }
+            },
+            None => {
+                self.base
+                    .send_command(WebGLCommand::BindSampler(unit, None));
+                self.samplers[unit as usize].set(None);
+            },
         }
     }
 
@@ -3729,7 +3819,13 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
                 return;
             }
             tf.delete(Operation::Infallible);
-            self.current_transform_feedback.set(None);
+            if self
+                .current_transform_feedback
+                .get()
+                .map_or(false, |current_tf| &*current_tf == tf)
+            {
+                self.current_transform_feedback.set(None);
+            }
         }
     }
 
@@ -3760,6 +3856,10 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
             self.base.webgl_error(InvalidEnum);
             return;
         }
+        if self.is_transform_feedback_capturing() {
+            self.base.webgl_error(InvalidOperation);
+            return;
+        }
         match tf {
             Some(transform_feedback) => {
                 handle_potential_webgl_error!(
@@ -3771,19 +3871,15 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
                     self.base.webgl_error(InvalidOperation);
                     return;
                 }
-                if let Some(current_tf) = self.current_transform_feedback.get() {
-                    if current_tf.is_active() && !current_tf.is_paused() {
-                        self.base.webgl_error(InvalidOperation);
-                        return;
-                    }
-                }
                 transform_feedback.bind(&self.base, target);
                 self.current_transform_feedback
                     .set(Some(transform_feedback));
             },
-            None => self
-                .base
-                .send_command(WebGLCommand::BindTransformFeedback(target, 0)),
+            None => {
+                self.base
+                    .send_command(WebGLCommand::BindTransformFeedback(target, 0));
+                self.current_transform_feedback.set(None);
+            },
         }
     }
 
@@ -3819,6 +3915,23 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
             self.base.webgl_error(InvalidOperation);
             return;
         };
+        // Each varying written to a buffer of its own needs one bound, and the interleaved
+        // ones need the first.
+        let buffers_needed = match program.transform_feedback_buffer_mode() as u32 {
+            constants::SEPARATE_ATTRIBS => program.transform_feedback_varyings_length() as usize,
+            _ => 1,
+        };
+        if self
+            .indexed_transform_feedback_buffer_bindings
+            .iter()
+            .take(buffers_needed)
+            .filter(|binding| binding.buffer.get().is_some())
+            .count() <
+            buffers_needed
+        {
+            self.base.webgl_error(InvalidOperation);
+            return;
+        }
         current_tf.begin(&self.base, primitiveMode);
     }
 
@@ -4004,7 +4117,7 @@ impl WebGL2RenderingContextMethods for WebGL2RenderingContext {
 
         match target {
             constants::TRANSFORM_FEEDBACK_BUFFER => {
-                if size % 4 != 0 && offset % 4 != 0 {
+                if size % 4 != 0 || offset % 4 != 0 {
                     return self.base.webgl_error(InvalidValue);
                 }
             },
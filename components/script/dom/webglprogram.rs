@@ -573,13 +573,13 @@ impl WebGLProgram {
     }
 
     pub fn bind_uniform_block(&self, block_index: u32, block_binding: u32) -> WebGLResult<()> {
-        if block_index as usize >= self.active_uniform_blocks.borrow().len() {
-            return Err(WebGLError::InvalidValue);
-        }
-
-        let mut active_uniforms = self.active_uniforms.borrow_mut();
-        if active_uniforms.len() > block_binding as usize {
-            active_uniforms[block_binding as usize].bind_index = Some(block_binding);
+        match self
+            .active_uniform_blocks
+            .borrow_mut()
+            .get_mut(block_index as usize)
+        {
+            Some(block) => block.binding = block_binding,
+            None => return Err(WebGLError::InvalidValue),
         }
 
         self.upcast::<WebGLObject>()
@@ -35,6 +35,7 @@ use net_traits::image_cache::ImageResponse;
 use pixels::{self, PixelFormat};
 use script_layout_interface::HTMLCanvasDataSource;
 use serde::{Deserialize, Serialize};
+use servo_atoms::Atom;
 use servo_config::pref;
 use webrender_api::ImageKey;
 
@@ -53,11 +54,12 @@ use crate::dom::bindings::codegen::UnionTypes::{
 use crate::dom::bindings::conversions::{DerivedFrom, ToJSValConvertible};
 use crate::dom::bindings::error::{Error, ErrorResult, Fallible};
 use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::{reflect_dom_object, DomObject, Reflector};
 use crate::dom::bindings::root::{Dom, DomOnceCell, DomRoot, LayoutDom, MutNullableDom};
 use crate::dom::bindings::str::DOMString;
 use crate::dom::element::cors_setting_for_element;
-use crate::dom::event::{Event, EventBubbles, EventCancelable};
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
 use crate::dom::htmlcanvaselement::{
     utils as canvas_utils, HTMLCanvasElement, LayoutCanvasRenderingContextHelpers,
 };
@@ -213,6 +215,8 @@ pub struct WebGLRenderingContext {
     textures: Textures,
     #[no_trace]
     api_type: GlType,
+    /// <https://registry.khronos.org/webgl/specs/latest/1.0/#webgl-context-lost-flag>
+    context_lost: Cell<bool>,
 }
 
 impl WebGLRenderingContext {
@@ -278,6 +282,7 @@ impl WebGLRenderingContext {
                 current_vao_webgl2: Default::default(),
                 textures: Textures::new(max_combined_texture_image_units),
                 api_type: ctx_data.api_type,
+                context_lost: Cell::new(false),
             }
         })
     }
@@ -394,6 +399,118 @@ impl WebGLRenderingContext {
         self.webgl_sender.context_id()
     }
 
+    /// The driver reset the context, as found out when swapping its buffers.
+    /// <https://registry.khronos.org/webgl/specs/latest/1.0/#CONTEXT_LOST>
+    pub fn lose_context(&self) {
+        // Step 2. If context's webgl context lost flag is set, abort these steps.
+        if self.context_lost.get() {
+            return;
+        }
+
+        // Step 3. Set context's webgl context lost flag.
+        self.context_lost.set(true);
+
+        // Step 5. Perform the necessary steps to reset the error list, and add
+        // CONTEXT_LOST_WEBGL to it.
+        self.last_error.set(Some(WebGLError::ContextLost));
+
+        // Step 6. Queue a WebGL task to fire a WebGL context event named
+        // "webglcontextlost" at canvas, with its statusMessage attribute set to "".
+        let this = Trusted::new(self);
+        let window = window_from_node(&*self.canvas);
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(webgl_context_lost: move || {
+                this.root().fire_context_lost();
+            }),
+            window.upcast(),
+        );
+    }
+
+    fn fire_context_lost(&self) {
+        let window = window_from_node(&*self.canvas);
+        let event = WebGLContextEvent::new(
+            &window,
+            Atom::from("webglcontextlost"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::Cancelable,
+            DOMString::new(),
+        );
+
+        // Step 7. If the event's canceled flag is not set, abort these steps.
+        if event.upcast::<Event>().fire(self.canvas.upcast()) == EventStatus::NotCanceled {
+            return;
+        }
+
+        // Step 8-9. Queue a WebGL task to restore the drawing buffer for context.
+        let this = Trusted::new(self);
+        let _ = window.task_manager().dom_manipulation_task_source().queue(
+            task!(webgl_context_restore: move || {
+                this.root().restore_context();
+            }),
+            window.upcast(),
+        );
+    }
+
+    /// <https://registry.khronos.org/webgl/specs/latest/1.0/#restore-the-drawing-buffer>
+    fn restore_context(&self) {
+        // Step 1. If context's webgl context lost flag is not set, abort these steps.
+        if !self.context_lost.get() {
+            return;
+        }
+
+        // Step 2-3. Attempt to create a drawing buffer, with the same attributes.
+        let (sender, receiver) = webgl_channel().unwrap();
+        self.webgl_sender
+            .send_restore(self.size.get(), sender)
+            .unwrap();
+        if let Err(msg) = receiver.recv().unwrap() {
+            return error!("Error restoring WebGLContext: {}", msg);
+        }
+
+        // Step 4. Reset the state of context, and clear its webgl context lost flag.
+        self.reset_state();
+        self.last_error.set(None);
+        self.context_lost.set(false);
+
+        // Step 5. Fire a WebGL context event named "webglcontextrestored" at canvas,
+        // with its statusMessage attribute set to "".
+        let window = window_from_node(&*self.canvas);
+        let event = WebGLContextEvent::new(
+            &window,
+            Atom::from("webglcontextrestored"),
+            EventBubbles::DoesNotBubble,
+            EventCancelable::NotCancelable,
+            DOMString::new(),
+        );
+        event.upcast::<Event>().fire(self.canvas.upcast());
+
+        self.mark_as_dirty();
+    }
+
+    /// Forget the bindings of a context which was restored, as its new GL context is in
+    /// its initial state.
+    fn reset_state(&self) {
+        let size = self.size.get();
+        self.texture_packing_alignment.set(4);
+        self.texture_unpacking_settings
+            .set(TextureUnpacking::CONVERT_COLORSPACE);
+        self.texture_unpacking_alignment.set(4);
+        self.bound_draw_framebuffer.set(None);
+        self.bound_read_framebuffer.set(None);
+        self.bound_renderbuffer.set(None);
+        self.bound_buffer_array.set(None);
+        self.current_program.set(None);
+        for attrib in self.current_vertex_attribs.borrow_mut().iter_mut() {
+            *attrib = VertexAttrib::Float(0f32, 0f32, 0f32, 1f32);
+        }
+        self.current_scissor.set((0, 0, size.width, size.height));
+        self.current_clear_color.set((0.0, 0.0, 0.0, 0.0));
+        self.capabilities.value.set(Default::default());
+        self.current_vao.set(None);
+        self.current_vao_webgl2.set(None);
+        self.textures.reset();
+    }
+
     pub fn onscreen(&self) -> bool {
         self.canvas.upcast::<Node>().is_connected()
     }
@@ -2328,7 +2445,7 @@ impl WebGLRenderingContextMethods for WebGLRenderingContext {
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.13
     fn IsContextLost(&self) -> bool {
-        false
+        self.context_lost.get()
     }
 
     // https://www.khronos.org/registry/webgl/specs/latest/1.0/#5.14.14
@@ -4818,6 +4935,16 @@ impl Textures {
         &self.units[self.active_unit.get() as usize]
     }
 
+    fn reset(&self) {
+        self.active_unit.set(0);
+        for unit in self.units.iter() {
+            unit.tex_2d.set(None);
+            unit.tex_cube_map.set(None);
+            unit.tex_2d_array.set(None);
+            unit.tex_3d.set(None);
+        }
+    }
+
     fn iter(&self) -> impl Iterator<Item = (u32, &TextureUnit)> {
         self.units
             .iter()
@@ -118,7 +118,7 @@ impl WebGLSampler {
         if !self.is_valid() {
             return Err(InvalidOperation);
         }
-        context.send_command(WebGLCommand::BindSampler(unit, self.gl_id));
+        context.send_command(WebGLCommand::BindSampler(unit, Some(self.gl_id)));
         Ok(())
     }
 
@@ -131,6 +131,20 @@ impl WebGLSampler {
         if !self.is_valid() {
             return Err(InvalidOperation);
         }
+        // Both samplerParameteri and samplerParameterf set any parameter, converting the
+        // value to its type.
+        let value = match (pname, value) {
+            (
+                constants::TEXTURE_MIN_LOD | constants::TEXTURE_MAX_LOD,
+                WebGLSamplerValue::GLenum(value),
+            ) => WebGLSamplerValue::Float(value as i32 as f32),
+            (
+                constants::TEXTURE_MIN_LOD | constants::TEXTURE_MAX_LOD,
+                WebGLSamplerValue::Float(value),
+            ) => WebGLSamplerValue::Float(value),
+            (_, WebGLSamplerValue::Float(value)) => WebGLSamplerValue::GLenum(value as i32 as u32),
+            (_, value) => value,
+        };
         if !validate_params(pname, value) {
             return Err(InvalidEnum);
         }
@@ -21,6 +21,8 @@ pub struct WebGLTransformFeedback {
     has_been_bound: Cell<bool>,
     is_active: Cell<bool>,
     is_paused: Cell<bool>,
+    /// The kind of primitives captured while it is active, which the draws must use.
+    primitive_mode: Cell<u32>,
 }
 
 impl WebGLTransformFeedback {
@@ -32,6 +34,7 @@ impl WebGLTransformFeedback {
             has_been_bound: Cell::new(false),
             is_active: Cell::new(false),
             is_paused: Cell::new(false),
+            primitive_mode: Cell::new(0),
         }
     }
 
@@ -57,6 +60,7 @@ impl WebGLTransformFeedback {
         if self.has_been_bound.get() && !self.is_active() {
             context.send_command(WebGLCommand::BeginTransformFeedback(primitive_mode));
             self.set_active(true);
+            self.primitive_mode.set(primitive_mode);
         }
     }
 
@@ -66,6 +70,7 @@ impl WebGLTransformFeedback {
                 context.send_command(WebGLCommand::ResumeTransformFeedback());
             }
             context.send_command(WebGLCommand::EndTransformFeedback());
+            self.set_pause(false);
             self.set_active(false);
         }
     }
@@ -100,6 +105,10 @@ impl WebGLTransformFeedback {
         self.is_paused.get()
     }
 
+    pub fn primitive_mode(&self) -> u32 {
+        self.primitive_mode.get()
+    }
+
     pub fn delete(&self, operation_fallibility: Operation) {
         if self.is_valid() && self.id() != 0 {
             self.marked_for_deletion.set(true);
@@ -120,7 +129,7 @@ impl WebGLTransformFeedback {
 
     pub fn set_pause(&self, value: bool) {
         if self.is_valid() && self.is_active() {
-            self.is_active.set(value);
+            self.is_paused.set(value);
         }
     }
 }
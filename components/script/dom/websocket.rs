@@ -112,7 +112,8 @@ pub struct WebSocket {
     #[no_trace]
     sender: IpcSender<WebSocketDomAction>,
     binary_type: Cell<BinaryType>,
-    protocol: DomRefCell<String>, //Subprotocol selected by server
+    protocol: DomRefCell<String>,   //Subprotocol selected by server
+    extensions: DomRefCell<String>, //Extensions in use
 }
 
 impl WebSocket {
@@ -126,6 +127,7 @@ impl WebSocket {
             sender: sender,
             binary_type: Cell::new(BinaryType::Blob),
             protocol: DomRefCell::new("".to_owned()),
+            extensions: DomRefCell::new("".to_owned()),
         }
     }
 
@@ -221,10 +223,14 @@ impl WebSocket {
         ROUTER.add_route(
             dom_event_receiver.to_opaque(),
             Box::new(move |message| match message.to().unwrap() {
-                WebSocketNetworkEvent::ConnectionEstablished { protocol_in_use } => {
+                WebSocketNetworkEvent::ConnectionEstablished {
+                    protocol_in_use,
+                    extensions,
+                } => {
                     let open_thread = ConnectionEstablishedTask {
                         address: address.clone(),
                         protocol_in_use,
+                        extensions,
                     };
                     let _ = task_source.queue_with_canceller(open_thread, &canceller);
                 },
@@ -344,6 +350,11 @@ impl WebSocketMethods for WebSocket {
         DOMString::from(self.protocol.borrow().clone())
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-websocket-extensions
+    fn Extensions(&self) -> DOMString {
+        DOMString::from(self.extensions.borrow().clone())
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-websocket-send
     fn Send(&self, data: USVString) -> ErrorResult {
         let data_byte_len = data.0.as_bytes().len() as u64;
@@ -457,6 +468,7 @@ impl WebSocketMethods for WebSocket {
 struct ConnectionEstablishedTask {
     address: Trusted<WebSocket>,
     protocol_in_use: Option<String>,
+    extensions: String,
 }
 
 impl TaskOnce for ConnectionEstablishedTask {
@@ -468,7 +480,7 @@ impl TaskOnce for ConnectionEstablishedTask {
         ws.ready_state.set(WebSocketRequestState::Open);
 
         // Step 2: Extensions.
-        // TODO: Set extensions to extensions in use.
+        *ws.extensions.borrow_mut() = self.extensions;
 
         // Step 3.
         if let Some(protocol_name) = self.protocol_in_use {
@@ -23,7 +23,7 @@ use crossbeam_channel::{unbounded, Sender, TryRecvError};
 use cssparser::{Parser, ParserInput, SourceLocation};
 use devtools_traits::{ScriptToDevtoolsControlMsg, TimelineMarker, TimelineMarkerType};
 use dom_struct::dom_struct;
-use embedder_traits::{EmbedderMsg, PromptDefinition, PromptOrigin, PromptResult};
+use embedder_traits::{EmbedderMsg, NetworkStatus, PromptDefinition, PromptOrigin, PromptResult};
 use euclid::default::{Point2D as UntypedPoint2D, Rect as UntypedRect};
 use euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use ipc_channel::ipc::{self, IpcSender};
@@ -34,6 +34,7 @@ use js::jsval::{JSVal, NullValue, UndefinedValue};
 use js::rust::wrappers::JS_DefineProperty;
 use js::rust::{
     CustomAutoRooter, CustomAutoRooterGuard, HandleObject, HandleValue, MutableHandleObject,
+    MutableHandleValue,
 };
 use malloc_size_of::MallocSizeOf;
 use media::WindowGLContext;
@@ -50,13 +51,16 @@ use profile_traits::mem::ProfilerChan as MemProfilerChan;
 use profile_traits::time::ProfilerChan as TimeProfilerChan;
 use script_layout_interface::message::{Msg, QueryMsg, Reflow, ReflowGoal, ScriptReflow};
 use script_layout_interface::rpc::{
-    ContentBoxResponse, ContentBoxesResponse, LayoutRPC, NodeScrollIdResponse,
-    ResolvedStyleResponse, TextIndexResponse,
+    CaretPositionResponse, CaretRectResponse, ContentBoxResponse, ContentBoxesResponse, LayoutRPC,
+    NodeScrollIdResponse, ResolvedStyleResponse, TextIndexResponse,
 };
 use script_layout_interface::{Layout, PendingImageState, TrustedNodeAddress};
+use script_traits::extension::{
+    ExtensionApiError, ExtensionApiResult, ExtensionId, ExtensionMessage,
+};
 use script_traits::webdriver_msg::{WebDriverJSError, WebDriverJSResult};
 use script_traits::{
-    ConstellationControlMsg, DocumentState, HistoryEntryReplacement, LoadData, ScriptMsg,
+    ConstellationControlMsg, DocumentState, HistoryEntryReplacement, JsWorld, LoadData, ScriptMsg,
     ScriptToConstellationChan, ScrollState, StructuredSerializedData, TimerEventId,
     TimerSchedulerMsg, WebrenderIpcSender, WindowSizeData, WindowSizeType,
 };
@@ -90,6 +94,7 @@ use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
     ImageBitmapOptions, ImageBitmapSource,
 };
 use crate::dom::bindings::codegen::Bindings::MediaQueryListBinding::MediaQueryList_Binding::MediaQueryListMethods;
+use crate::dom::bindings::codegen::Bindings::MessagePortBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WindowBinding::{
@@ -116,11 +121,13 @@ use crate::dom::document::{AnimationFrameCallback, Document, ReflowTriggerCondit
 use crate::dom::element::Element;
 use crate::dom::event::{Event, EventStatus};
 use crate::dom::eventtarget::EventTarget;
+use crate::dom::extensionbrowser::ExtensionBrowser;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::hashchangeevent::HashChangeEvent;
 use crate::dom::history::History;
 use crate::dom::htmlcollection::{CollectionFilter, HTMLCollection};
 use crate::dom::htmliframeelement::HTMLIFrameElement;
+use crate::dom::idbfactory::IDBFactory;
 use crate::dom::identityhub::Identities;
 use crate::dom::location::Location;
 use crate::dom::mediaquerylist::{MediaQueryList, MediaQueryListMatchState};
@@ -132,6 +139,7 @@ use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
 use crate::dom::screen::Screen;
 use crate::dom::selection::Selection;
+use crate::dom::speechsynthesis::SpeechSynthesis;
 use crate::dom::storage::Storage;
 use crate::dom::testrunner::TestRunner;
 use crate::dom::webglrenderingcontext::WebGLCommandSender;
@@ -152,7 +160,7 @@ use crate::task_manager::TaskManager;
 use crate::task_source::{TaskSource, TaskSourceName};
 use crate::timers::{IsInterval, TimerCallback};
 use crate::webdriver_handlers::jsval_to_webdriver;
-use crate::{fetch, window_named_properties};
+use crate::{clock, fetch, isolated_world, window_named_properties};
 
 /// Current state of the window object
 #[derive(Clone, Copy, Debug, JSTraceable, MallocSizeOf, PartialEq)]
@@ -169,6 +177,7 @@ pub enum ReflowReason {
     DocumentLoaded,
     ElementStateChanged,
     FirstLoad,
+    ForcedColorsChanged,
     FramedContentChanged,
     IFrameLoadEvent,
     ImageLoaded,
@@ -212,6 +221,7 @@ pub struct Window {
     screen: MutNullableDom<Screen>,
     session_storage: MutNullableDom<Storage>,
     local_storage: MutNullableDom<Storage>,
+    speech_synthesis: MutNullableDom<SpeechSynthesis>,
     status: DomRefCell<DOMString>,
 
     /// For sending timeline markers. Will be ignored if
@@ -359,6 +369,13 @@ pub struct Window {
 
     /// <https://dom.spec.whatwg.org/#window-current-event>
     current_event: DomRefCell<Option<Dom<Event>>>,
+
+    /// The global objects of the isolated worlds of this window.
+    #[ignore_malloc_size_of = "mozjs"]
+    isolated_worlds: DomRefCell<HashMapTracedValues<JsWorld, Box<Heap<*mut JSObject>>>>,
+
+    /// The `browser` objects of the extensions whose scripts run in this window.
+    extension_apis: DomRefCell<HashMapTracedValues<ExtensionId, Dom<ExtensionBrowser>>>,
 }
 
 impl Window {
@@ -836,6 +853,11 @@ impl WindowMethods for Window {
         self.upcast::<GlobalScope>().crypto()
     }
 
+    // https://w3c.github.io/IndexedDB/#dom-windoworworkerglobalscope-indexeddb
+    fn IndexedDB(&self) -> DomRoot<IDBFactory> {
+        self.upcast::<GlobalScope>().indexed_db()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-frameelement
     fn GetFrameElement(&self) -> Option<DomRoot<Element>> {
         // Steps 1-3.
@@ -922,6 +944,17 @@ impl WindowMethods for Window {
             .queue_function_as_microtask(callback);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    fn StructuredClone(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        self.upcast::<GlobalScope>()
+            .structured_clone(cx, value, options)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
     fn CreateImageBitmap(
         &self,
@@ -997,6 +1030,11 @@ impl WindowMethods for Window {
         self.screen.or_init(|| Screen::new(self))
     }
 
+    /// <https://wicg.github.io/speech-api/#dom-window-speechsynthesis>
+    fn SpeechSynthesis(&self) -> DomRoot<SpeechSynthesis> {
+        self.speech_synthesis.or_init(|| SpeechSynthesis::new(self))
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowbase64-btoa
     fn Btoa(&self, btoa: DOMString) -> Fallible<DOMString> {
         base64_btoa(btoa)
@@ -1394,6 +1432,11 @@ impl WindowMethods for Window {
         self.upcast::<GlobalScope>().is_secure_context()
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-crossoriginisolated
+    fn CrossOriginIsolated(&self) -> bool {
+        self.upcast::<GlobalScope>().cross_origin_isolated()
+    }
+
     // https://html.spec.whatwg.org/multipage/#named-access-on-the-window-object
     #[allow(unsafe_code)]
     fn NamedGetter(&self, _cx: JSContext, name: DOMString) -> Option<NonNull<JSObject>> {
@@ -1655,6 +1698,11 @@ impl Window {
             custom_elements.teardown();
         }
 
+        // A document that is gone no longer speaks.
+        if let Some(speech_synthesis) = self.speech_synthesis.get() {
+            speech_synthesis.stop();
+        }
+
         // The above code may not catch all DOM objects (e.g. DOM
         // objects removed from the tree that haven't been collected
         // yet). There should not be any such DOM nodes with layout
@@ -1756,6 +1804,11 @@ impl Window {
     pub fn update_viewport_for_scroll(&self, x: f32, y: f32) {
         let size = self.current_viewport.get().size;
         let new_viewport = Rect::new(Point2D::new(Au::from_f32_px(x), Au::from_f32_px(y)), size);
+        if new_viewport.origin != self.current_viewport.get().origin {
+            // The constellation persists the scroll position with the current session history
+            // entry, to restore it when the history is traversed back to it.
+            self.send_to_constellation(ScriptMsg::ScrollPositionChanged(Vector2D::new(x, y)));
+        }
         self.current_viewport.set(new_viewport)
     }
 
@@ -1939,6 +1992,16 @@ impl Window {
             }
         }
 
+        if let Some(nodes) = complete.content_visibility_auto_nodes {
+            let elements = nodes
+                .into_iter()
+                .filter_map(|address| {
+                    DomRoot::downcast::<Element>(unsafe { from_untrusted_node_address(address) })
+                })
+                .collect();
+            document.set_content_visibility_auto_elements(elements);
+        }
+
         document.update_animations_post_reflow();
         self.update_constellation_epoch();
 
@@ -2185,6 +2248,36 @@ impl Window {
         self.layout_rpc().text_index()
     }
 
+    /// The caret rectangle at a character offset in the text of `node`, for editing, IME
+    /// composition or the devtools to draw at the caret.
+    pub fn caret_rect_query(&self, node: &Node, offset: usize) -> Option<UntypedRect<Au>> {
+        if !self.layout_reflow(QueryMsg::CaretRectQuery(node.to_opaque(), offset)) {
+            return None;
+        }
+        let CaretRectResponse(rect) = self.layout_rpc().caret_rect();
+        rect
+    }
+
+    /// The node under a point of the viewport, and the character offset in its text of the
+    /// caret position closest to the point.
+    #[allow(unsafe_code)]
+    pub fn caret_position_from_point_query(
+        &self,
+        client_point: UntypedPoint2D<f32>,
+    ) -> Option<(DomRoot<Node>, usize)> {
+        if !self.layout_reflow(QueryMsg::CaretPositionFromPointQuery(client_point)) {
+            return None;
+        }
+
+        // FIXME(nox): Layout can reply with a garbage value which doesn't
+        // actually correspond to a node, that's unsound.
+        let CaretPositionResponse(position) = self.layout_rpc().caret_position();
+        position.map(|(address, offset)| {
+            let node = unsafe { from_untrusted_node_address(address) };
+            (node, offset)
+        })
+    }
+
     #[allow(unsafe_code)]
     pub fn init_window_proxy(&self, window_proxy: &WindowProxy) {
         assert!(self.window_proxy.get().is_none());
@@ -2303,6 +2396,77 @@ impl Window {
         self.window_size.get()
     }
 
+    /// Evaluate `code` in the isolated world `world`, creating the world if this
+    /// is the first script to run in it.
+    pub fn evaluate_js_in_isolated_world(
+        &self,
+        world: &JsWorld,
+        code: &str,
+        rval: MutableHandleValue,
+    ) -> bool {
+        let global = self.isolated_world_global(world);
+        isolated_world::evaluate_in_isolated_world(self, global, code, rval)
+    }
+
+    /// The global object of the isolated world `world`, which is created if needed.
+    fn isolated_world_global(&self, world: &JsWorld) -> *mut JSObject {
+        debug_assert_ne!(*world, JsWorld::Main);
+        let existing = self
+            .isolated_worlds
+            .borrow()
+            .get(world)
+            .map(|global| global.get());
+        if let Some(global) = existing {
+            return global;
+        }
+
+        // Creating the world creates the interface objects of the world,
+        // so it must not happen while `isolated_worlds` is borrowed.
+        let global = isolated_world::create_isolated_world_global(self);
+        let heap = Box::new(Heap::default());
+        heap.set(global);
+        self.isolated_worlds
+            .borrow_mut()
+            .insert(world.clone(), heap);
+        if let JsWorld::Extension(extension_id) = *world {
+            let browser = ExtensionBrowser::new(self, extension_id);
+            isolated_world::expose_extension_api(global, &browser);
+            self.extension_apis
+                .borrow_mut()
+                .insert(extension_id, Dom::from_ref(&*browser));
+        }
+        global
+    }
+
+    /// Dispatch a message sent by a script of an extension to the scripts of the
+    /// extension that run in this window, and send their response through
+    /// `response_sender`.
+    pub fn dispatch_extension_message(
+        &self,
+        extension_id: ExtensionId,
+        message: ExtensionMessage,
+        response_sender: IpcSender<ExtensionApiResult<StructuredSerializedData>>,
+    ) {
+        let browser = self
+            .extension_apis
+            .borrow()
+            .get(&extension_id)
+            .map(|browser| DomRoot::from_ref(&**browser));
+        let browser = match browser {
+            Some(browser) => browser,
+            None => {
+                let _ = response_sender.send(Err(ExtensionApiError::NoReceivingEnd));
+                return;
+            },
+        };
+        let cx = GlobalScope::get_cx();
+        rooted!(in(*cx) let global = self.isolated_world_global(&JsWorld::Extension(extension_id)));
+        let ac = JSAutoRealm::new(*cx, global.get());
+        browser
+            .runtime()
+            .dispatch_message(message, response_sender, InRealm::entered(&ac));
+    }
+
     pub fn get_url(&self) -> ServoUrl {
         self.Document().url()
     }
@@ -2486,7 +2650,8 @@ impl Window {
         let current_time = time::get_time();
         let now = (current_time.sec * 1000 + current_time.nsec as i64 / 1000000) as u64;
         self.navigation_start.set(now);
-        self.navigation_start_precise.set(time::precise_time_ns());
+        self.navigation_start_precise
+            .set(clock::monotonic_time_ns());
     }
 
     pub fn send_to_embedder(&self, msg: EmbedderMsg) {
@@ -2511,6 +2676,35 @@ impl Window {
             .and_then(|nav| nav.xr())
             .map_or(false, |xr| xr.pending_or_active_session())
     }
+
+    /// Let the page know the connectivity of the device changed.
+    pub fn network_changed(&self, old_status: NetworkStatus, new_status: NetworkStatus) {
+        let this = Trusted::new(self);
+        let task = task!(network_changed: move || {
+            let window = this.root();
+
+            // https://html.spec.whatwg.org/multipage/#dom-navigator-online
+            if old_status.online != new_status.online {
+                let name = if new_status.online { "online" } else { "offline" };
+                window.upcast::<EventTarget>().fire_event(Atom::from(name));
+            }
+
+            // https://wicg.github.io/netinfo/#handling-changes-to-the-underlying-connection
+            if old_status.connection_type != new_status.connection_type {
+                let connection = window
+                    .navigator
+                    .get()
+                    .and_then(|navigator| navigator.connection());
+                if let Some(connection) = connection {
+                    connection.upcast::<EventTarget>().fire_event(atom!("change"));
+                }
+            }
+        });
+        let _ = self
+            .task_manager()
+            .networking_task_source()
+            .queue(task, self.upcast());
+    }
 }
 
 impl Window {
@@ -2590,6 +2784,7 @@ impl Window {
             screen: Default::default(),
             session_storage: Default::default(),
             local_storage: Default::default(),
+            speech_synthesis: Default::default(),
             status: DomRefCell::new(DOMString::new()),
             parent_info,
             dom_static: GlobalStaticData::new(),
@@ -2630,6 +2825,8 @@ impl Window {
             visible: Cell::new(true),
             layout_marker: DomRefCell::new(Rc::new(Cell::new(true))),
             current_event: DomRefCell::new(None),
+            isolated_worlds: Default::default(),
+            extension_apis: Default::default(),
         });
 
         unsafe { WindowBinding::Wrap(JSContext::from_ptr(runtime.cx()), win) }
@@ -2724,6 +2921,8 @@ fn debug_reflow_events(id: PipelineId, reflow_goal: &ReflowGoal, reason: &Reflow
             &QueryMsg::OffsetParentQuery(_n) => "\tOffsetParentQuery",
             &QueryMsg::StyleQuery => "\tStyleQuery",
             &QueryMsg::TextIndexQuery(..) => "\tTextIndexQuery",
+            &QueryMsg::CaretRectQuery(..) => "\tCaretRectQuery",
+            &QueryMsg::CaretPositionFromPointQuery(_) => "\tCaretPositionFromPointQuery",
             &QueryMsg::ElementInnerTextQuery(_) => "\tElementInnerTextQuery",
             &QueryMsg::InnerWindowDimensionsQuery(_) => "\tInnerWindowDimensionsQuery",
         },
@@ -11,7 +11,7 @@ use crossbeam_channel::Receiver;
 use devtools_traits::{DevtoolScriptControlMsg, WorkerId};
 use dom_struct::dom_struct;
 use ipc_channel::ipc::IpcSender;
-use js::jsval::UndefinedValue;
+use js::jsval::{JSVal, UndefinedValue};
 use js::panic::maybe_resume_unwind;
 use js::rust::{HandleValue, ParentRuntime};
 use msg::constellation_msg::{PipelineId, PipelineNamespace};
@@ -22,13 +22,13 @@ use net_traits::IpcSend;
 use parking_lot::Mutex;
 use script_traits::WorkerGlobalScopeInit;
 use servo_url::{MutableOrigin, ServoUrl};
-use time::precise_time_ns;
 use uuid::Uuid;
 
 use crate::dom::bindings::cell::{DomRefCell, Ref};
 use crate::dom::bindings::codegen::Bindings::ImageBitmapBinding::{
     ImageBitmapOptions, ImageBitmapSource,
 };
+use crate::dom::bindings::codegen::Bindings::MessagePortBinding::StructuredSerializeOptions;
 use crate::dom::bindings::codegen::Bindings::RequestBinding::RequestInit;
 use crate::dom::bindings::codegen::Bindings::VoidFunctionBinding::VoidFunction;
 use crate::dom::bindings::codegen::Bindings::WorkerBinding::WorkerType;
@@ -44,6 +44,7 @@ use crate::dom::bindings::trace::RootedTraceableBox;
 use crate::dom::crypto::Crypto;
 use crate::dom::dedicatedworkerglobalscope::DedicatedWorkerGlobalScope;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbfactory::IDBFactory;
 use crate::dom::identityhub::Identities;
 use crate::dom::performance::Performance;
 use crate::dom::promise::Promise;
@@ -51,13 +52,13 @@ use crate::dom::serviceworkerglobalscope::ServiceWorkerGlobalScope;
 use crate::dom::window::{base64_atob, base64_btoa};
 use crate::dom::workerlocation::WorkerLocation;
 use crate::dom::workernavigator::WorkerNavigator;
-use crate::fetch;
 use crate::realms::{enter_realm, InRealm};
 use crate::script_runtime::{
     get_reports, CommonScriptMsg, ContextForRequestInterrupt, JSContext, Runtime, ScriptChan,
     ScriptPort,
 };
 use crate::task::TaskCanceller;
+use crate::task_source::database_access::DatabaseAccessTaskSource;
 use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::networking::NetworkingTaskSource;
@@ -67,6 +68,7 @@ use crate::task_source::remote_event::RemoteEventTaskSource;
 use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::timers::{IsInterval, TimerCallback};
+use crate::{clock, fetch};
 
 pub fn prepare_workerscope_init(
     global: &GlobalScope,
@@ -87,6 +89,8 @@ pub fn prepare_workerscope_init(
         creation_url: global.creation_url().clone(),
         is_headless: global.is_headless(),
         user_agent: global.get_user_agent(),
+        user_agent_override: global.user_agent_override(),
+        script_policy: global.script_policy(),
         inherited_secure_context: Some(global.is_secure_context()),
     };
 
@@ -141,7 +145,9 @@ impl WorkerGlobalScope {
     ) -> Self {
         // Install a pipeline-namespace in the current thread.
         PipelineNamespace::auto_install();
-        Self {
+        let user_agent_override = init.user_agent_override;
+        let script_policy = init.script_policy;
+        let scope = Self {
             globalscope: GlobalScope::new_inherited(
                 init.pipeline_id,
                 init.to_devtools_sender,
@@ -168,9 +174,14 @@ impl WorkerGlobalScope {
             navigator: Default::default(),
             from_devtools_sender: init.from_devtools_sender,
             from_devtools_receiver,
-            navigation_start_precise: precise_time_ns(),
+            navigation_start_precise: clock::monotonic_time_ns(),
             performance: Default::default(),
-        }
+        };
+        scope
+            .globalscope
+            .set_user_agent_override(user_agent_override);
+        scope.globalscope.apply_script_policy(script_policy);
+        scope
     }
 
     /// Clear various items when the worker event-loop shuts-down.
@@ -319,6 +330,11 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
         self.upcast::<GlobalScope>().crypto()
     }
 
+    // https://w3c.github.io/IndexedDB/#dom-windoworworkerglobalscope-indexeddb
+    fn IndexedDB(&self) -> DomRoot<IDBFactory> {
+        self.upcast::<GlobalScope>().indexed_db()
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-windowbase64-btoa
     fn Btoa(&self, btoa: DOMString) -> Fallible<DOMString> {
         base64_btoa(btoa)
@@ -386,6 +402,17 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
             .queue_function_as_microtask(callback);
     }
 
+    // https://html.spec.whatwg.org/multipage/#dom-structuredclone
+    fn StructuredClone(
+        &self,
+        cx: JSContext,
+        value: HandleValue,
+        options: RootedTraceableBox<StructuredSerializeOptions>,
+    ) -> Fallible<JSVal> {
+        self.upcast::<GlobalScope>()
+            .structured_clone(cx, value, options)
+    }
+
     // https://html.spec.whatwg.org/multipage/#dom-createimagebitmap
     fn CreateImageBitmap(
         &self,
@@ -431,6 +458,11 @@ impl WorkerGlobalScopeMethods for WorkerGlobalScope {
     fn IsSecureContext(&self) -> bool {
         self.upcast::<GlobalScope>().is_secure_context()
     }
+
+    // https://html.spec.whatwg.org/multipage/#dom-crossoriginisolated
+    fn CrossOriginIsolated(&self) -> bool {
+        self.upcast::<GlobalScope>().cross_origin_isolated()
+    }
 }
 
 impl WorkerGlobalScope {
@@ -475,6 +507,10 @@ impl WorkerGlobalScope {
         }
     }
 
+    pub fn database_access_task_source(&self) -> DatabaseAccessTaskSource {
+        DatabaseAccessTaskSource(self.script_chan(), self.pipeline_id())
+    }
+
     pub fn dom_manipulation_task_source(&self) -> DOMManipulationTaskSource {
         DOMManipulationTaskSource(self.script_chan(), self.pipeline_id())
     }
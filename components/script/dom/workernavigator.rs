@@ -13,7 +13,9 @@ use crate::dom::bindings::utils::to_frozen_array;
 use crate::dom::gpu::GPU;
 use crate::dom::navigator::hardware_concurrency;
 use crate::dom::navigatorinfo;
+use crate::dom::navigatoruadata::NavigatorUAData;
 use crate::dom::permissions::Permissions;
+use crate::dom::storagemanager::StorageManager;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
 use crate::script_runtime::JSContext;
 
@@ -23,6 +25,8 @@ pub struct WorkerNavigator {
     reflector_: Reflector,
     permissions: MutNullableDom<Permissions>,
     gpu: MutNullableDom<GPU>,
+    user_agent_data: MutNullableDom<NavigatorUAData>,
+    storage: MutNullableDom<StorageManager>,
 }
 
 impl WorkerNavigator {
@@ -31,6 +35,8 @@ impl WorkerNavigator {
             reflector_: Reflector::new(),
             permissions: Default::default(),
             gpu: Default::default(),
+            user_agent_data: Default::default(),
+            storage: Default::default(),
         }
     }
 
@@ -112,8 +118,19 @@ impl WorkerNavigatorMethods for WorkerNavigator {
         self.gpu.or_init(|| GPU::new(&self.global()))
     }
 
+    /// <https://wicg.github.io/ua-client-hints/#dom-navigatorua-useragentdata>
+    fn UserAgentData(&self) -> DomRoot<NavigatorUAData> {
+        self.user_agent_data
+            .or_init(|| NavigatorUAData::new(&self.global()))
+    }
+
     /// <https://html.spec.whatwg.org/multipage/#dom-navigator-hardwareconcurrency>
     fn HardwareConcurrency(&self) -> u64 {
         hardware_concurrency()
     }
+
+    /// <https://storage.spec.whatwg.org/#dom-navigatorstorage-storage>
+    fn Storage(&self) -> DomRoot<StorageManager> {
+        self.storage.or_init(|| StorageManager::new(&self.global()))
+    }
 }
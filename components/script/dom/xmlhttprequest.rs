@@ -64,6 +64,7 @@ use crate::dom::node::Node;
 use crate::dom::performanceresourcetiming::InitiatorType;
 use crate::dom::progressevent::ProgressEvent;
 use crate::dom::readablestream::ReadableStream;
+use crate::dom::report::ReportData;
 use crate::dom::servoparser::ServoParser;
 use crate::dom::window::Window;
 use crate::dom::workerglobalscope::WorkerGlobalScope;
@@ -419,6 +420,16 @@ impl XMLHttpRequestMethods for XMLHttpRequest {
                     {
                         return Err(Error::InvalidAccess);
                     }
+                    if self.global().is::<Window>() {
+                        self.global().queue_report(ReportData::Deprecation {
+                            id: "sync-xhr",
+                            message: "Synchronous XMLHttpRequest on the main thread is \
+                                      deprecated, as it blocks the page until the response \
+                                      arrives."
+                                .to_owned(),
+                            anticipated_removal: None,
+                        });
+                    }
                 }
                 // Step 11 - abort existing requests
                 self.terminate_ongoing_fetch();
@@ -5,6 +5,8 @@
 use std::cell::Cell;
 
 use dom_struct::dom_struct;
+use js::rust::CustomAutoRooterGuard;
+use js::typedarray::Float32Array;
 use webxr_api::{Frame, LayerId, SubImages};
 
 use crate::dom::bindings::codegen::Bindings::XRFrameBinding::XRFrameMethods;
@@ -135,7 +137,7 @@ impl XRFrameMethods for XRFrame {
         Ok(Some(XRPose::new(&self.global(), pose)))
     }
 
-    /// <https://immersive-web.github.io/webxr/#dom-xrframe-getpose>
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrframe-getjointpose>
     fn GetJointPose(
         &self,
         space: &XRJointSpace,
@@ -163,10 +165,91 @@ impl XRFrameMethods for XRFrame {
         Ok(Some(XRJointPose::new(
             &self.global(),
             pose.cast_unit(),
-            Some(joint_frame.radius),
+            joint_frame.radius,
         )))
     }
 
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrframe-filljointradii>
+    #[allow(unsafe_code)]
+    fn FillJointRadii(
+        &self,
+        joint_spaces: Vec<DomRoot<XRJointSpace>>,
+        mut radii: CustomAutoRooterGuard<Float32Array>,
+    ) -> Result<bool, Error> {
+        // Step 1.
+        if !self.active.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2.
+        if joint_spaces
+            .iter()
+            .any(|space| self.session != space.upcast::<XRSpace>().session())
+        {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        if radii.len() < joint_spaces.len() {
+            return Err(Error::Type(
+                "The radii array is shorter than the list of joint spaces".to_owned(),
+            ));
+        }
+        // Steps 4-5. No JS code that may touch the array runs whilst it is written to.
+        let radii = unsafe { radii.as_mut_slice() };
+        let mut all_valid = true;
+        for (radius, space) in radii.iter_mut().zip(&joint_spaces) {
+            match space.frame(&self.data) {
+                Some(joint_frame) => *radius = joint_frame.radius,
+                None => {
+                    *radius = f32::NAN;
+                    all_valid = false;
+                },
+            }
+        }
+        Ok(all_valid)
+    }
+
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrframe-fillposes>
+    #[allow(unsafe_code)]
+    fn FillPoses(
+        &self,
+        spaces: Vec<DomRoot<XRSpace>>,
+        base_space: &XRSpace,
+        mut transforms: CustomAutoRooterGuard<Float32Array>,
+    ) -> Result<bool, Error> {
+        // Step 1.
+        if !self.active.get() {
+            return Err(Error::InvalidState);
+        }
+        // Step 2.
+        if self.session != base_space.session() ||
+            spaces.iter().any(|space| self.session != space.session())
+        {
+            return Err(Error::InvalidState);
+        }
+        // Step 3.
+        if transforms.len() < spaces.len() * 16 {
+            return Err(Error::Type(
+                "The transforms array is too short for the list of spaces".to_owned(),
+            ));
+        }
+        // Steps 4-5. No JS code that may touch the array runs whilst it is written to.
+        let base_pose = self.get_pose(base_space);
+        let transforms = unsafe { transforms.as_mut_slice() };
+        let mut all_valid = true;
+        for (transform, space) in transforms.chunks_exact_mut(16).zip(&spaces) {
+            let pose = base_pose
+                .and_then(|base_pose| Some(self.get_pose(space)?.then(&base_pose.inverse())));
+            match pose {
+                Some(pose) => transform.copy_from_slice(&pose.to_transform().to_array()),
+                None => {
+                    transform.fill(f32::NAN);
+                    all_valid = false;
+                },
+            }
+        }
+        Ok(all_valid)
+    }
+
     /// <https://immersive-web.github.io/hit-test/#dom-xrframe-gethittestresults>
     fn GetHitTestResults(&self, source: &XRHitTestSource) -> Vec<DomRoot<XRHitTestResult>> {
         self.data
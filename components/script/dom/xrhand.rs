@@ -3,88 +3,165 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use dom_struct::dom_struct;
-use webxr_api::{FingerJoint, Hand, Joint};
+use webxr_api::{FingerJoint, Joint};
 
-use crate::dom::bindings::codegen::Bindings::XRHandBinding::{XRHandConstants, XRHandMethods};
+use crate::dom::bindings::codegen::Bindings::XRHandBinding::{XRHandJoint, XRHandMethods};
+use crate::dom::bindings::iterable::Iterable;
 use crate::dom::bindings::reflector::{reflect_dom_object, Reflector};
 use crate::dom::bindings::root::{Dom, DomRoot};
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::xrinputsource::XRInputSource;
 use crate::dom::xrjointspace::XRJointSpace;
 
+/// The joints of a hand, in the order of the `XRHandJoint` enum, which is the order they
+/// are iterated in.
+const JOINTS: [(XRHandJoint, Joint); 25] = [
+    (XRHandJoint::Wrist, Joint::Wrist),
+    (XRHandJoint::Thumb_metacarpal, Joint::ThumbMetacarpal),
+    (
+        XRHandJoint::Thumb_phalanx_proximal,
+        Joint::ThumbPhalanxProximal,
+    ),
+    (XRHandJoint::Thumb_phalanx_distal, Joint::ThumbPhalanxDistal),
+    (XRHandJoint::Thumb_tip, Joint::ThumbPhalanxTip),
+    (
+        XRHandJoint::Index_finger_metacarpal,
+        Joint::Index(FingerJoint::Metacarpal),
+    ),
+    (
+        XRHandJoint::Index_finger_phalanx_proximal,
+        Joint::Index(FingerJoint::PhalanxProximal),
+    ),
+    (
+        XRHandJoint::Index_finger_phalanx_intermediate,
+        Joint::Index(FingerJoint::PhalanxIntermediate),
+    ),
+    (
+        XRHandJoint::Index_finger_phalanx_distal,
+        Joint::Index(FingerJoint::PhalanxDistal),
+    ),
+    (
+        XRHandJoint::Index_finger_tip,
+        Joint::Index(FingerJoint::PhalanxTip),
+    ),
+    (
+        XRHandJoint::Middle_finger_metacarpal,
+        Joint::Middle(FingerJoint::Metacarpal),
+    ),
+    (
+        XRHandJoint::Middle_finger_phalanx_proximal,
+        Joint::Middle(FingerJoint::PhalanxProximal),
+    ),
+    (
+        XRHandJoint::Middle_finger_phalanx_intermediate,
+        Joint::Middle(FingerJoint::PhalanxIntermediate),
+    ),
+    (
+        XRHandJoint::Middle_finger_phalanx_distal,
+        Joint::Middle(FingerJoint::PhalanxDistal),
+    ),
+    (
+        XRHandJoint::Middle_finger_tip,
+        Joint::Middle(FingerJoint::PhalanxTip),
+    ),
+    (
+        XRHandJoint::Ring_finger_metacarpal,
+        Joint::Ring(FingerJoint::Metacarpal),
+    ),
+    (
+        XRHandJoint::Ring_finger_phalanx_proximal,
+        Joint::Ring(FingerJoint::PhalanxProximal),
+    ),
+    (
+        XRHandJoint::Ring_finger_phalanx_intermediate,
+        Joint::Ring(FingerJoint::PhalanxIntermediate),
+    ),
+    (
+        XRHandJoint::Ring_finger_phalanx_distal,
+        Joint::Ring(FingerJoint::PhalanxDistal),
+    ),
+    (
+        XRHandJoint::Ring_finger_tip,
+        Joint::Ring(FingerJoint::PhalanxTip),
+    ),
+    (
+        XRHandJoint::Pinky_finger_metacarpal,
+        Joint::Little(FingerJoint::Metacarpal),
+    ),
+    (
+        XRHandJoint::Pinky_finger_phalanx_proximal,
+        Joint::Little(FingerJoint::PhalanxProximal),
+    ),
+    (
+        XRHandJoint::Pinky_finger_phalanx_intermediate,
+        Joint::Little(FingerJoint::PhalanxIntermediate),
+    ),
+    (
+        XRHandJoint::Pinky_finger_phalanx_distal,
+        Joint::Little(FingerJoint::PhalanxDistal),
+    ),
+    (
+        XRHandJoint::Pinky_finger_tip,
+        Joint::Little(FingerJoint::PhalanxTip),
+    ),
+];
+
+/// <https://immersive-web.github.io/webxr-hand-input/#xrhand-interface>
 #[dom_struct]
 pub struct XRHand {
     reflector_: Reflector,
     #[ignore_malloc_size_of = "defined in webxr"]
     source: Dom<XRInputSource>,
-    #[ignore_malloc_size_of = "partially defind in webxr"]
-    #[custom_trace]
-    spaces: Hand<Dom<XRJointSpace>>,
+    /// The spaces of all the joints, in the order of `JOINTS`. The joints the device does not
+    /// track have a space too, which never has a pose.
+    spaces: Vec<Dom<XRJointSpace>>,
 }
 
 impl XRHand {
-    fn new_inherited(source: &XRInputSource, spaces: &Hand<DomRoot<XRJointSpace>>) -> XRHand {
+    fn new_inherited(source: &XRInputSource, spaces: &[DomRoot<XRJointSpace>]) -> XRHand {
         XRHand {
             reflector_: Reflector::new(),
             source: Dom::from_ref(source),
-            spaces: spaces.map(|j, _| j.as_ref().map(|j| Dom::from_ref(&**j))),
+            spaces: spaces.iter().map(|space| Dom::from_ref(&**space)).collect(),
         }
     }
 
-    pub fn new(global: &GlobalScope, source: &XRInputSource, support: Hand<()>) -> DomRoot<XRHand> {
+    pub fn new(global: &GlobalScope, source: &XRInputSource) -> DomRoot<XRHand> {
         let id = source.id();
         let session = source.session();
-        let spaces = support
-            .map(|field, joint| field.map(|_| XRJointSpace::new(global, session, id, joint)));
+        let spaces: Vec<_> = JOINTS
+            .iter()
+            .map(|&(name, joint)| XRJointSpace::new(global, session, id, joint, name))
+            .collect();
         reflect_dom_object(Box::new(XRHand::new_inherited(source, &spaces)), global)
     }
 }
 
 impl XRHandMethods for XRHand {
-    /// <https://github.com/immersive-web/webxr-hands-input/blob/master/explainer.md>
-    fn Length(&self) -> i32 {
-        XRHandConstants::LITTLE_PHALANX_TIP as i32 + 1
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrhand-size>
+    fn Size(&self) -> u32 {
+        JOINTS.len() as u32
+    }
+
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrhand-get>
+    fn Get(&self, key: XRHandJoint) -> DomRoot<XRJointSpace> {
+        DomRoot::from_ref(&*self.spaces[key as usize])
+    }
+}
+
+impl Iterable for XRHand {
+    type Key = XRHandJoint;
+    type Value = DomRoot<XRJointSpace>;
+
+    fn get_iterable_length(&self) -> u32 {
+        JOINTS.len() as u32
+    }
+
+    fn get_value_at_index(&self, n: u32) -> DomRoot<XRJointSpace> {
+        DomRoot::from_ref(&*self.spaces[n as usize])
     }
 
-    /// <https://github.com/immersive-web/webxr-hands-input/blob/master/explainer.md>
-    fn IndexedGetter(&self, joint_index: u32) -> Option<DomRoot<XRJointSpace>> {
-        let joint = match joint_index {
-            XRHandConstants::WRIST => Joint::Wrist,
-            XRHandConstants::THUMB_METACARPAL => Joint::ThumbMetacarpal,
-            XRHandConstants::THUMB_PHALANX_PROXIMAL => Joint::ThumbPhalanxProximal,
-            XRHandConstants::THUMB_PHALANX_DISTAL => Joint::ThumbPhalanxDistal,
-            XRHandConstants::THUMB_PHALANX_TIP => Joint::ThumbPhalanxTip,
-            XRHandConstants::INDEX_METACARPAL => Joint::Index(FingerJoint::Metacarpal),
-            XRHandConstants::INDEX_PHALANX_PROXIMAL => Joint::Index(FingerJoint::PhalanxProximal),
-            XRHandConstants::INDEX_PHALANX_INTERMEDIATE => {
-                Joint::Index(FingerJoint::PhalanxIntermediate)
-            },
-            XRHandConstants::INDEX_PHALANX_DISTAL => Joint::Index(FingerJoint::PhalanxDistal),
-            XRHandConstants::INDEX_PHALANX_TIP => Joint::Index(FingerJoint::PhalanxTip),
-            XRHandConstants::MIDDLE_METACARPAL => Joint::Middle(FingerJoint::Metacarpal),
-            XRHandConstants::MIDDLE_PHALANX_PROXIMAL => Joint::Middle(FingerJoint::PhalanxProximal),
-            XRHandConstants::MIDDLE_PHALANX_INTERMEDIATE => {
-                Joint::Middle(FingerJoint::PhalanxIntermediate)
-            },
-            XRHandConstants::MIDDLE_PHALANX_DISTAL => Joint::Middle(FingerJoint::PhalanxDistal),
-            XRHandConstants::MIDDLE_PHALANX_TIP => Joint::Middle(FingerJoint::PhalanxTip),
-            XRHandConstants::RING_METACARPAL => Joint::Ring(FingerJoint::Metacarpal),
-            XRHandConstants::RING_PHALANX_PROXIMAL => Joint::Ring(FingerJoint::PhalanxProximal),
-            XRHandConstants::RING_PHALANX_INTERMEDIATE => {
-                Joint::Ring(FingerJoint::PhalanxIntermediate)
-            },
-            XRHandConstants::RING_PHALANX_DISTAL => Joint::Ring(FingerJoint::PhalanxDistal),
-            XRHandConstants::RING_PHALANX_TIP => Joint::Ring(FingerJoint::PhalanxTip),
-            XRHandConstants::LITTLE_METACARPAL => Joint::Little(FingerJoint::Metacarpal),
-            XRHandConstants::LITTLE_PHALANX_PROXIMAL => Joint::Little(FingerJoint::PhalanxProximal),
-            XRHandConstants::LITTLE_PHALANX_INTERMEDIATE => {
-                Joint::Little(FingerJoint::PhalanxIntermediate)
-            },
-            XRHandConstants::LITTLE_PHALANX_DISTAL => Joint::Little(FingerJoint::PhalanxDistal),
-            XRHandConstants::LITTLE_PHALANX_TIP => Joint::Little(FingerJoint::PhalanxTip),
-            // XXXManishearth should this be a TypeError?
-            _ => return None,
-        };
-        self.spaces.get(joint).map(|j| DomRoot::from_ref(&**j))
+    fn get_key_at_index(&self, n: u32) -> XRHandJoint {
+        JOINTS[n as usize].0
     }
 }
@@ -120,15 +120,11 @@ impl XRInputSourceMethods for XRInputSource {
         self.profiles.get()
     }
 
-    // https://github.com/immersive-web/webxr-hands-input/blob/master/explainer.md
+    // https://immersive-web.github.io/webxr-hand-input/#dom-xrinputsource-hand
     fn GetHand(&self) -> Option<DomRoot<XRHand>> {
-        if let Some(ref hand) = self.info.hand_support {
-            Some(
-                self.hand
-                    .or_init(|| XRHand::new(&self.global(), &self, hand.clone())),
-            )
-        } else {
-            None
+        if self.info.hand_support.is_none() || !self.session.has_feature("hand-tracking") {
+            return None;
         }
+        Some(self.hand.or_init(|| XRHand::new(&self.global(), self)))
     }
 }
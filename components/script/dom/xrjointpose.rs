@@ -16,11 +16,11 @@ use crate::dom::xrsession::ApiRigidTransform;
 #[dom_struct]
 pub struct XRJointPose {
     pose: XRPose,
-    radius: Option<f32>,
+    radius: f32,
 }
 
 impl XRJointPose {
-    fn new_inherited(transform: &XRRigidTransform, radius: Option<f32>) -> XRJointPose {
+    fn new_inherited(transform: &XRRigidTransform, radius: f32) -> XRJointPose {
         XRJointPose {
             pose: XRPose::new_inherited(transform),
             radius,
@@ -28,11 +28,7 @@ impl XRJointPose {
     }
 
     #[allow(unsafe_code)]
-    pub fn new(
-        global: &GlobalScope,
-        pose: ApiRigidTransform,
-        radius: Option<f32>,
-    ) -> DomRoot<XRJointPose> {
+    pub fn new(global: &GlobalScope, pose: ApiRigidTransform, radius: f32) -> DomRoot<XRJointPose> {
         let transform = XRRigidTransform::new(global, pose);
         reflect_dom_object(
             Box::new(XRJointPose::new_inherited(&transform, radius)),
@@ -42,8 +38,8 @@ impl XRJointPose {
 }
 
 impl XRJointPoseMethods for XRJointPose {
-    /// <https://immersive-web.github.io/webxr/#dom-XRJointPose-views>
-    fn GetRadius(&self) -> Option<Finite<f32>> {
-        self.radius.map(Finite::wrap)
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrjointpose-radius>
+    fn Radius(&self) -> Finite<f32> {
+        Finite::wrap(self.radius)
     }
 }
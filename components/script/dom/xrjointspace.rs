@@ -6,6 +6,8 @@ use dom_struct::dom_struct;
 use euclid::RigidTransform3D;
 use webxr_api::{BaseSpace, Frame, InputId, Joint, JointFrame, Space};
 
+use crate::dom::bindings::codegen::Bindings::XRHandBinding::XRHandJoint;
+use crate::dom::bindings::codegen::Bindings::XRJointSpaceBinding::XRJointSpaceMethods;
 use crate::dom::bindings::reflector::reflect_dom_object;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::globalscope::GlobalScope;
@@ -21,25 +23,35 @@ pub struct XRJointSpace {
     #[ignore_malloc_size_of = "defined in rust-webxr"]
     #[no_trace]
     joint: Joint,
+    joint_name: XRHandJoint,
 }
 
 impl XRJointSpace {
-    pub fn new_inherited(session: &XRSession, input: InputId, joint: Joint) -> XRJointSpace {
+    pub fn new_inherited(
+        session: &XRSession,
+        input: InputId,
+        joint: Joint,
+        joint_name: XRHandJoint,
+    ) -> XRJointSpace {
         XRJointSpace {
             xrspace: XRSpace::new_inherited(session),
             input,
             joint,
+            joint_name,
         }
     }
 
-    #[allow(unused)]
     pub fn new(
         global: &GlobalScope,
         session: &XRSession,
         input: InputId,
         joint: Joint,
+        joint_name: XRHandJoint,
     ) -> DomRoot<XRJointSpace> {
-        reflect_dom_object(Box::new(Self::new_inherited(session, input, joint)), global)
+        reflect_dom_object(
+            Box::new(Self::new_inherited(session, input, joint, joint_name)),
+            global,
+        )
     }
 
     pub fn space(&self) -> Space {
@@ -61,3 +73,10 @@ impl XRJointSpace {
         self.frame(frame).map(|f| f.pose).map(|t| t.cast_unit())
     }
 }
+
+impl XRJointSpaceMethods for XRJointSpace {
+    /// <https://immersive-web.github.io/webxr-hand-input/#dom-xrjointspace-jointname>
+    fn JointName(&self) -> XRHandJoint {
+        self.joint_name
+    }
+}
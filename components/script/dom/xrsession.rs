@@ -43,7 +43,6 @@ use crate::dom::bindings::root::{Dom, DomRoot, MutDom, MutNullableDom};
 use crate::dom::event::Event;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
-use crate::dom::performance::reduce_timing_resolution;
 use crate::dom::promise::Promise;
 use crate::dom::xrframe::XRFrame;
 use crate::dom::xrhittestsource::XRHitTestSource;
@@ -165,6 +164,16 @@ impl XRSession {
         self.mode != XRSessionMode::Inline
     }
 
+    /// Whether the feature of the given descriptor was granted to the session.
+    /// <https://immersive-web.github.io/webxr/#feature-descriptor>
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.session
+            .borrow()
+            .granted_features()
+            .iter()
+            .any(|granted| granted == feature)
+    }
+
     // https://immersive-web.github.io/layers/#feature-descriptor-layers
     pub fn has_layers_feature(&self) -> bool {
         // We do not support creating layers other than projection layers
@@ -421,7 +430,7 @@ impl XRSession {
             mem::swap(&mut *self.raf_callback_list.borrow_mut(), &mut current);
         }
         let start = self.global().as_window().get_navigation_start();
-        let time = reduce_timing_resolution((frame.time_ns - start).to_ms());
+        let time = self.global().coarsen_time((frame.time_ns - start).to_ms());
 
         let frame = XRFrame::new(&self.global(), self, frame);
         // Step 8-9
@@ -778,15 +787,7 @@ impl XRSessionMethods for XRSession {
                 if ty != XRReferenceSpaceType::Viewer &&
                     (!self.is_immersive() || ty != XRReferenceSpaceType::Local)
                 {
-                    let s = ty.as_str();
-                    if self
-                        .session
-                        .borrow()
-                        .granted_features()
-                        .iter()
-                        .find(|f| &**f == s)
-                        .is_none()
-                    {
+                    if !self.has_feature(ty.as_str()) {
                         p.reject_error(Error::NotSupported);
                         return p;
                     }
@@ -833,14 +834,7 @@ impl XRSessionMethods for XRSession {
     fn RequestHitTestSource(&self, options: &XRHitTestOptionsInit) -> Rc<Promise> {
         let p = Promise::new(&self.global());
 
-        if self
-            .session
-            .borrow()
-            .granted_features()
-            .iter()
-            .find(|f| &**f == "hit-test")
-            .is_none()
-        {
+        if !self.has_feature("hit-test") {
             p.reject_error(Error::NotSupported);
             return p;
         }
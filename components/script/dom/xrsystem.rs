@@ -222,6 +222,19 @@ impl XRSystemMethods for XRSystem {
             }
         }
 
+        // Hand tracking is not offered to content while the hand input API is disabled.
+        // https://immersive-web.github.io/webxr-hand-input/#feature-descriptor-hand-tracking
+        if !pref!(dom.webxr.hands.enabled) {
+            if required_features.iter().any(|f| f == "hand-tracking") {
+                if mode != XRSessionMode::Inline {
+                    self.pending_immersive_session.set(false);
+                }
+                promise.reject_error(Error::NotSupported);
+                return promise;
+            }
+            optional_features.retain(|f| f != "hand-tracking");
+        }
+
         let init = SessionInit {
             required_features,
             optional_features,
@@ -0,0 +1,843 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! HTML drag and drop: drags of the draggable elements of a document, started by moving
+//! the pointer with the left button pressed, and drags of files coming from the embedder.
+//! <https://html.spec.whatwg.org/multipage/#dnd>
+
+use std::cell::Cell;
+
+use embedder_traits::{DragDataItem, EmbedderMsg};
+use euclid::default::Point2D;
+use script_traits::{DragEventType, MouseButton};
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLFormElementBinding::SelectionMode;
+use crate::dom::bindings::codegen::Bindings::HTMLImageElementBinding::HTMLImageElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputElementMethods;
+use crate::dom::bindings::codegen::Bindings::HTMLTextAreaElementBinding::HTMLTextAreaElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::datatransfer::DataTransfer;
+use crate::dom::document::Document;
+use crate::dom::dragevent::DragEvent;
+use crate::dom::element::Element;
+use crate::dom::event::{Event, EventBubbles, EventCancelable, EventStatus};
+use crate::dom::eventtarget::EventTarget;
+use crate::dom::htmlanchorelement::HTMLAnchorElement;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlimageelement::HTMLImageElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::htmltextareaelement::HTMLTextAreaElement;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::dom::textcontrol::TextControlElement;
+use crate::editing;
+
+/// How far in CSS pixels the pointer has to move with the left button pressed for the drag
+/// of a draggable element to start.
+const DRAG_THRESHOLD: f32 = 4.0;
+
+/// <https://html.spec.whatwg.org/multipage/#drag-data-store-mode>
+#[derive(Clone, Copy, Debug, Eq, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum DragDataStoreMode {
+    /// The data can be read and changed, during `dragstart`.
+    ReadWrite,
+    /// The data can be read but not changed, during `drop`.
+    ReadOnly,
+    /// Only the types and kinds of the data can be read.
+    Protected,
+}
+
+/// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-dropeffect>
+#[derive(Clone, Copy, Debug, Default, Eq, JSTraceable, MallocSizeOf, PartialEq)]
+pub enum DropEffect {
+    #[default]
+    None,
+    Copy,
+    Link,
+    Move,
+}
+
+impl DropEffect {
+    pub fn parse(value: &str) -> Option<DropEffect> {
+        match value {
+            "none" => Some(DropEffect::None),
+            "copy" => Some(DropEffect::Copy),
+            "link" => Some(DropEffect::Link),
+            "move" => Some(DropEffect::Move),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            DropEffect::None => "none",
+            DropEffect::Copy => "copy",
+            DropEffect::Link => "link",
+            DropEffect::Move => "move",
+        }
+    }
+
+    /// The `dropEffect` of `dragenter` and `dragover` events before they are dispatched.
+    /// <https://html.spec.whatwg.org/multipage/#dropEffect-initialisation>
+    pub fn default_for(effect_allowed: &str) -> DropEffect {
+        match effect_allowed {
+            "none" => DropEffect::None,
+            "link" | "linkMove" => DropEffect::Link,
+            "move" => DropEffect::Move,
+            _ => DropEffect::Copy,
+        }
+    }
+
+    /// Whether this operation is one of the ones allowed by `effect_allowed`.
+    pub fn is_allowed_by(self, effect_allowed: &str) -> bool {
+        match self {
+            DropEffect::None => true,
+            DropEffect::Copy => matches!(
+                effect_allowed,
+                "copy" | "copyLink" | "copyMove" | "all" | "uninitialized"
+            ),
+            DropEffect::Link => matches!(
+                effect_allowed,
+                "link" | "copyLink" | "linkMove" | "all" | "uninitialized"
+            ),
+            DropEffect::Move => matches!(
+                effect_allowed,
+                "move" | "copyMove" | "linkMove" | "all" | "uninitialized"
+            ),
+        }
+    }
+}
+
+/// The data of a drag, which is shared by the `DataTransfer` objects of its events.
+/// <https://html.spec.whatwg.org/multipage/#drag-data-store>
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct DragDataStore {
+    /// The items of the data, with the ids that `DataTransferItem` objects refer to them by.
+    #[no_trace]
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    items: Vec<(u32, DragDataItem)>,
+    next_id: u32,
+    mode: DragDataStoreMode,
+    /// <https://html.spec.whatwg.org/multipage/#drag-data-store-allowed-effects-state>
+    allowed_effects: String,
+}
+
+impl DragDataStore {
+    pub fn new(items: Vec<DragDataItem>, mode: DragDataStoreMode) -> DragDataStore {
+        let next_id = items.len() as u32;
+        DragDataStore {
+            items: (0..).zip(items).collect(),
+            next_id,
+            mode,
+            allowed_effects: "uninitialized".to_owned(),
+        }
+    }
+
+    pub fn mode(&self) -> DragDataStoreMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: DragDataStoreMode) {
+        self.mode = mode;
+    }
+
+    pub fn allowed_effects(&self) -> &str {
+        &self.allowed_effects
+    }
+
+    pub fn set_allowed_effects(&mut self, allowed_effects: String) {
+        self.allowed_effects = allowed_effects;
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// The id of the item at `index`.
+    pub fn id_at(&self, index: usize) -> Option<u32> {
+        self.items.get(index).map(|(id, _)| *id)
+    }
+
+    /// The item with the id `id`, unless it was removed.
+    pub fn item(&self, id: u32) -> Option<&DragDataItem> {
+        self.items
+            .iter()
+            .find(|(item_id, _)| *item_id == id)
+            .map(|(_, item)| item)
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &DragDataItem> {
+        self.items.iter().map(|(_, item)| item)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-types>
+    pub fn types(&self) -> Vec<String> {
+        let mut types: Vec<String> = self
+            .items()
+            .filter_map(|item| match item {
+                DragDataItem::Text { type_, .. } => Some(type_.clone()),
+                DragDataItem::File { .. } => None,
+            })
+            .collect();
+        if self
+            .items()
+            .any(|item| matches!(item, DragDataItem::File { .. }))
+        {
+            types.push("Files".to_owned());
+        }
+        types
+    }
+
+    /// The string data of the type `format`, if any.
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-getdata>
+    pub fn get_data(&self, format: &str) -> Option<String> {
+        let (format, convert_to_url) = normalize_format(format);
+        let data = self.items().find_map(|item| match item {
+            DragDataItem::Text { type_, data } if *type_ == format => Some(data.clone()),
+            _ => None,
+        })?;
+        if !convert_to_url {
+            return Some(data);
+        }
+        // The first URL of the list, skipping the comments.
+        data.split('\n')
+            .map(|line| line.trim_end_matches('\r'))
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::to_owned)
+    }
+
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-setdata>
+    pub fn set_data(&mut self, format: &str, data: String) {
+        let (format, _) = normalize_format(format);
+        if let Some(index) = self.text_index(&format) {
+            self.items.remove(index);
+        }
+        self.push(DragDataItem::Text {
+            type_: format,
+            data,
+        });
+    }
+
+    /// Remove the string data of the type `format`, or all the string data.
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransfer-cleardata>
+    pub fn clear_data(&mut self, format: Option<&str>) {
+        match format {
+            Some(format) => {
+                let (format, _) = normalize_format(format);
+                if let Some(index) = self.text_index(&format) {
+                    self.items.remove(index);
+                }
+            },
+            None => self
+                .items
+                .retain(|(_, item)| matches!(item, DragDataItem::File { .. })),
+        }
+    }
+
+    /// Add an item, returning its id, or `None` if there already is string data of its type.
+    /// <https://html.spec.whatwg.org/multipage/#dom-datatransferitemlist-add>
+    pub fn add(&mut self, item: DragDataItem) -> Option<u32> {
+        if let DragDataItem::Text { ref type_, .. } = item {
+            if self.text_index(type_).is_some() {
+                return None;
+            }
+        }
+        Some(self.push(item))
+    }
+
+    /// Remove the item at `index`, returning its id.
+    pub fn remove(&mut self, index: usize) -> Option<u32> {
+        if index >= self.items.len() {
+            return None;
+        }
+        Some(self.items.remove(index).0)
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    fn push(&mut self, item: DragDataItem) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.items.push((id, item));
+        id
+    }
+
+    fn text_index(&self, format: &str) -> Option<usize> {
+        self.items.iter().position(|(_, item)| match item {
+            DragDataItem::Text { type_, .. } => type_ == format,
+            DragDataItem::File { .. } => false,
+        })
+    }
+}
+
+/// The type that a format given to `getData`, `setData` or `clearData` refers to, and
+/// whether only the first URL of the data is wanted.
+fn normalize_format(format: &str) -> (String, bool) {
+    let format = format.to_ascii_lowercase();
+    match &*format {
+        "text" => ("text/plain".to_owned(), false),
+        "url" => ("text/uri-list".to_owned(), true),
+        _ => (format, false),
+    }
+}
+
+/// The state of the drag and drop operation of a document.
+///
+/// TODO: Drags are not followed into or out of the documents of iframes, and there is no
+/// drag feedback drawn under the pointer.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[crown::unrooted_must_root_lint::must_root]
+pub(crate) struct DragAndDrop {
+    /// The draggable element that the left button was pressed on, which is dragged once the
+    /// pointer moves far enough.
+    pending_source: MutNullableDom<Element>,
+    /// Where the left button was pressed, in CSS pixels.
+    #[no_trace]
+    pending_point: Cell<Point2D<f32>>,
+    /// The data of the drag in progress, if any.
+    data_store: DomRefCell<Option<DragDataStore>>,
+    /// <https://html.spec.whatwg.org/multipage/#source-node>, which is `None` for drags
+    /// coming from the embedder.
+    source: MutNullableDom<Element>,
+    /// <https://html.spec.whatwg.org/multipage/#immediate-user-selection>
+    immediate_selection: MutNullableDom<Element>,
+    /// <https://html.spec.whatwg.org/multipage/#current-target-element>
+    current_target: MutNullableDom<Element>,
+    /// <https://html.spec.whatwg.org/multipage/#current-drag-operation>
+    current_operation: Cell<DropEffect>,
+    /// Whether the click following the mouse up that ended a drag is to be ignored.
+    suppress_click: Cell<bool>,
+}
+
+impl DragAndDrop {
+    fn is_active(&self) -> bool {
+        self.data_store.borrow().is_some()
+    }
+
+    /// Remember the draggable element the left button was pressed on, if any.
+    pub(crate) fn handle_mouse_down(
+        &self,
+        document: &Document,
+        target: &Element,
+        point: Point2D<f32>,
+        button: MouseButton,
+    ) {
+        self.suppress_click.set(false);
+        if self.is_active() {
+            // The button was released outside of the document.
+            self.cancel(document, point, 0);
+        }
+        let source = match button {
+            MouseButton::Left => draggable_ancestor(target),
+            _ => None,
+        };
+        self.pending_source.set(source.as_deref());
+        self.pending_point.set(point);
+    }
+
+    /// Start or continue a drag. Returns whether the pointer moved as part of a drag, in which
+    /// case no mouse events are fired for it.
+    pub(crate) fn handle_mouse_move(
+        &self,
+        document: &Document,
+        target: &Element,
+        point: Point2D<f32>,
+        pressed_mouse_buttons: u16,
+    ) -> bool {
+        let left_button_pressed = pressed_mouse_buttons & MouseButton::Left as u16 != 0;
+        if self.is_active() {
+            if !left_button_pressed {
+                self.cancel(document, point, pressed_mouse_buttons);
+                return false;
+            }
+            self.update(document, Some(target), point, pressed_mouse_buttons);
+            return true;
+        }
+
+        if !left_button_pressed {
+            self.pending_source.set(None);
+            return false;
+        }
+        let source = match self.pending_source.get() {
+            Some(source) => source,
+            None => return false,
+        };
+        if (point - self.pending_point.get()).length() < DRAG_THRESHOLD {
+            return false;
+        }
+        self.pending_source.set(None);
+        if !self.start(document, &source, point, pressed_mouse_buttons) {
+            return false;
+        }
+        self.update(document, Some(target), point, pressed_mouse_buttons);
+        true
+    }
+
+    /// Drop the data of the drag in progress, if any, returning whether there was one.
+    pub(crate) fn handle_mouse_up(
+        &self,
+        document: &Document,
+        target: &Element,
+        point: Point2D<f32>,
+        pressed_mouse_buttons: u16,
+    ) -> bool {
+        self.pending_source.set(None);
+        if !self.is_active() || pressed_mouse_buttons & MouseButton::Left as u16 != 0 {
+            return false;
+        }
+        self.update(document, Some(target), point, pressed_mouse_buttons);
+        self.perform_drop(document, point, pressed_mouse_buttons);
+        self.suppress_click.set(true);
+        true
+    }
+
+    /// Whether the click being handled follows the end of a drag, and is to be ignored.
+    pub(crate) fn take_suppressed_click(&self) -> bool {
+        self.suppress_click.replace(false)
+    }
+
+    /// Handle the embedder dragging data, such as files, over the document.
+    pub(crate) fn handle_external_drag(
+        &self,
+        document: &Document,
+        event_type: DragEventType,
+        target: Option<&Element>,
+        point: Point2D<f32>,
+    ) {
+        let buttons = MouseButton::Left as u16;
+        if let DragEventType::Enter(items) = event_type {
+            if self.is_active() {
+                return;
+            }
+            *self.data_store.borrow_mut() =
+                Some(DragDataStore::new(items, DragDataStoreMode::Protected));
+            self.update(document, target, point, buttons);
+            return;
+        }
+
+        if !self.is_active() || self.source.get().is_some() {
+            return;
+        }
+        match event_type {
+            DragEventType::Enter(_) => unreachable!(),
+            DragEventType::Over => self.update(document, target, point, buttons),
+            DragEventType::Leave => {
+                self.current_operation.set(DropEffect::None);
+                self.cancel(document, point, buttons);
+            },
+            DragEventType::Drop => {
+                self.update(document, target, point, buttons);
+                self.perform_drop(document, point, buttons);
+            },
+        }
+    }
+
+    /// Fire `dragstart` at `source`, and start dragging it unless the event is canceled.
+    /// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+    fn start(
+        &self,
+        document: &Document,
+        source: &Element,
+        point: Point2D<f32>,
+        buttons: u16,
+    ) -> bool {
+        let items = default_drag_data(document, source);
+        *self.data_store.borrow_mut() =
+            Some(DragDataStore::new(items, DragDataStoreMode::ReadWrite));
+        self.source.set(Some(source));
+
+        let (status, _) = self.fire(
+            document,
+            "dragstart",
+            source,
+            point,
+            buttons,
+            None,
+            DropEffect::None,
+        );
+        if status == EventStatus::Canceled {
+            self.clear();
+            return false;
+        }
+
+        let items: Vec<DragDataItem> = self
+            .data_store
+            .borrow()
+            .as_ref()
+            .map(|data_store| data_store.items().cloned().collect())
+            .unwrap_or_default();
+        document
+            .window()
+            .send_to_embedder(EmbedderMsg::StartDrag(items));
+        true
+    }
+
+    /// Fire the events of the pointer having moved over `immediate_selection` during the drag,
+    /// and update the operation that dropping the data there would perform.
+    /// <https://html.spec.whatwg.org/multipage/#drag-and-drop-processing-model>
+    fn update(
+        &self,
+        document: &Document,
+        immediate_selection: Option<&Element>,
+        point: Point2D<f32>,
+        buttons: u16,
+    ) {
+        // Step 1. Fire `drag` at the source node, which cancels the drag if it is canceled.
+        if let Some(source) = self.source.get() {
+            let (status, _) = self.fire(
+                document,
+                "drag",
+                &source,
+                point,
+                buttons,
+                None,
+                DropEffect::None,
+            );
+            if status == EventStatus::Canceled {
+                self.current_operation.set(DropEffect::None);
+                self.cancel(document, point, buttons);
+                return;
+            }
+        }
+
+        // Step 2. If the immediate user selection changed, fire `dragenter` at it, and pick
+        // the new current target element.
+        if self.immediate_selection.get().as_deref() != immediate_selection {
+            self.immediate_selection.set(immediate_selection);
+            let new_target = immediate_selection
+                .and_then(|element| self.enter(document, element, point, buttons));
+
+            // Step 3. Fire `dragleave` at the previous current target element, if it changed.
+            let previous_target = self.current_target.get();
+            if previous_target != new_target {
+                if let Some(previous_target) = previous_target {
+                    self.fire(
+                        document,
+                        "dragleave",
+                        &previous_target,
+                        point,
+                        buttons,
+                        new_target.as_deref(),
+                        DropEffect::None,
+                    );
+                }
+                self.current_target.set(new_target.as_deref());
+            }
+        }
+
+        // Step 4. Fire `dragover` at the current target element, which picks the operation.
+        let target = match self.current_target.get() {
+            Some(target) => target,
+            None => {
+                self.current_operation.set(DropEffect::None);
+                return;
+            },
+        };
+        let allowed_effects = self.allowed_effects();
+        let (status, drop_effect) = self.fire(
+            document,
+            "dragover",
+            &target,
+            point,
+            buttons,
+            None,
+            DropEffect::default_for(&allowed_effects),
+        );
+        let operation = if status == EventStatus::Canceled {
+            if drop_effect.is_allowed_by(&allowed_effects) {
+                drop_effect
+            } else {
+                DropEffect::None
+            }
+        } else if accepts_text(&target) && self.dropped_text().is_some() {
+            [DropEffect::Copy, DropEffect::Move]
+                .iter()
+                .copied()
+                .find(|effect| effect.is_allowed_by(&allowed_effects))
+                .unwrap_or(DropEffect::None)
+        } else {
+            DropEffect::None
+        };
+        self.current_operation.set(operation);
+    }
+
+    /// Fire `dragenter` at the new immediate user selection, and return the new current
+    /// target element, which is the body if the selection does not accept the drop.
+    fn enter(
+        &self,
+        document: &Document,
+        element: &Element,
+        point: Point2D<f32>,
+        buttons: u16,
+    ) -> Option<DomRoot<Element>> {
+        let allowed_effects = self.allowed_effects();
+        let related_target = self.current_target.get();
+        let (status, _) = self.fire(
+            document,
+            "dragenter",
+            element,
+            point,
+            buttons,
+            related_target.as_deref(),
+            DropEffect::default_for(&allowed_effects),
+        );
+        if status == EventStatus::Canceled || accepts_text(element) {
+            return Some(DomRoot::from_ref(element));
+        }
+
+        let body = DomRoot::upcast::<Element>(document.GetBody()?);
+        if &*body != element {
+            self.fire(
+                document,
+                "dragenter",
+                &body,
+                point,
+                buttons,
+                related_target.as_deref(),
+                DropEffect::default_for(&allowed_effects),
+            );
+        }
+        Some(body)
+    }
+
+    /// Fire `drop` at the current target element, or `dragleave` if the data can't be dropped
+    /// there, and end the drag.
+    fn perform_drop(&self, document: &Document, point: Point2D<f32>, buttons: u16) {
+        if let Some(target) = self.current_target.take() {
+            if self.current_operation.get() == DropEffect::None {
+                self.fire(
+                    document,
+                    "dragleave",
+                    &target,
+                    point,
+                    buttons,
+                    None,
+                    DropEffect::None,
+                );
+            } else {
+                let (status, drop_effect) = self.fire(
+                    document,
+                    "drop",
+                    &target,
+                    point,
+                    buttons,
+                    None,
+                    self.current_operation.get(),
+                );
+                if status == EventStatus::Canceled {
+                    self.current_operation.set(drop_effect);
+                } else {
+                    // The default action of dropping text into a text field or an editing host
+                    // is to insert it.
+                    let inserted = self
+                        .dropped_text()
+                        .map_or(false, |text| insert_dropped_text(&target, &text));
+                    if !inserted {
+                        self.current_operation.set(DropEffect::None);
+                    }
+                }
+            }
+        }
+        self.end(document, point, buttons);
+    }
+
+    /// Cancel the drag, firing `dragleave` at the current target element.
+    fn cancel(&self, document: &Document, point: Point2D<f32>, buttons: u16) {
+        if let Some(target) = self.current_target.take() {
+            self.fire(
+                document,
+                "dragleave",
+                &target,
+                point,
+                buttons,
+                None,
+                DropEffect::None,
+            );
+        }
+        self.end(document, point, buttons);
+    }
+
+    /// Fire `dragend` at the source node, if any, and forget about the drag.
+    fn end(&self, document: &Document, point: Point2D<f32>, buttons: u16) {
+        if let Some(source) = self.source.get() {
+            self.fire(
+                document,
+                "dragend",
+                &source,
+                point,
+                buttons,
+                None,
+                self.current_operation.get(),
+            );
+        }
+        self.clear();
+    }
+
+    fn clear(&self) {
+        *self.data_store.borrow_mut() = None;
+        self.source.set(None);
+        self.immediate_selection.set(None);
+        self.current_target.set(None);
+        self.current_operation.set(DropEffect::None);
+    }
+
+    fn allowed_effects(&self) -> String {
+        self.data_store
+            .borrow()
+            .as_ref()
+            .map(|data_store| data_store.allowed_effects().to_owned())
+            .unwrap_or_default()
+    }
+
+    /// The text that dropping the data into a text field inserts, if any.
+    fn dropped_text(&self) -> Option<String> {
+        self.data_store.borrow().as_ref()?.get_data("text/plain")
+    }
+
+    /// Fire the drag event `name` at `target` with a `DataTransfer` giving access to the data
+    /// of the drag in the mode of the event, returning the status of the event and the
+    /// `dropEffect` its listeners left.
+    /// <https://html.spec.whatwg.org/multipage/#fire-a-dnd-event>
+    fn fire(
+        &self,
+        document: &Document,
+        name: &str,
+        target: &Element,
+        point: Point2D<f32>,
+        buttons: u16,
+        related_target: Option<&Element>,
+        drop_effect: DropEffect,
+    ) -> (EventStatus, DropEffect) {
+        let mut data_store = match self.data_store.borrow_mut().take() {
+            Some(data_store) => data_store,
+            None => return (EventStatus::NotCanceled, DropEffect::None),
+        };
+        data_store.set_mode(match name {
+            "dragstart" => DragDataStoreMode::ReadWrite,
+            "drop" => DragDataStoreMode::ReadOnly,
+            _ => DragDataStoreMode::Protected,
+        });
+
+        let window = document.window();
+        let data_transfer = DataTransfer::new(window, data_store);
+        data_transfer.set_drop_effect(drop_effect);
+        let client_x = point.x as i32;
+        let client_y = point.y as i32;
+        let event = DragEvent::new(
+            window,
+            DOMString::from(name),
+            EventBubbles::Bubbles,
+            EventCancelable::from(!matches!(name, "dragleave" | "dragend")),
+            Some(window),
+            0,
+            client_x,
+            client_y,
+            client_x,
+            client_y,
+            false,
+            false,
+            false,
+            false,
+            0,
+            buttons,
+            related_target.map(|element| element.upcast()),
+            None,
+            Some(&data_transfer),
+        );
+        let event = event.upcast::<Event>();
+        event.set_trusted(true);
+        let status = event.fire(target.upcast());
+
+        // The `DataTransfer` object is disabled once the event has been dispatched.
+        *self.data_store.borrow_mut() = data_transfer.take_data_store();
+        (status, data_transfer.drop_effect())
+    }
+}
+
+/// The nearest draggable inclusive ancestor of `element`.
+fn draggable_ancestor(element: &Element) -> Option<DomRoot<Element>> {
+    element
+        .upcast::<Node>()
+        .inclusive_ancestors(ShadowIncluding::No)
+        .filter_map(DomRoot::downcast::<HTMLElement>)
+        .find(|element| element.is_draggable())
+        .map(DomRoot::upcast)
+}
+
+/// The data of a drag of `source` before `dragstart` is fired, which is the URL of images and
+/// links.
+fn default_drag_data(document: &Document, source: &Element) -> Vec<DragDataItem> {
+    let url = if let Some(image) = source.downcast::<HTMLImageElement>() {
+        Some(image.CurrentSrc().0)
+    } else if source.is::<HTMLAnchorElement>() {
+        source
+            .get_attribute(&ns!(), &local_name!("href"))
+            .and_then(|href| document.url().join(&href.value()).ok())
+            .map(|url| url.to_string())
+    } else {
+        None
+    };
+    match url.filter(|url| !url.is_empty()) {
+        Some(url) => vec![
+            DragDataItem::Text {
+                type_: "text/uri-list".to_owned(),
+                data: url.clone(),
+            },
+            DragDataItem::Text {
+                type_: "text/plain".to_owned(),
+                data: url,
+            },
+        ],
+        None => vec![],
+    }
+}
+
+/// Whether text can be dropped into `element`: whether it is a mutable text field or is in an
+/// editing host.
+fn accepts_text(element: &Element) -> bool {
+    if let Some(input) = element.downcast::<HTMLInputElement>() {
+        return input.selection_api_applies() && element.read_write_state();
+    }
+    if element.is::<HTMLTextAreaElement>() {
+        return element.read_write_state();
+    }
+    editing::editing_host_of(element.upcast()).is_some()
+}
+
+/// Insert dropped text at the selection of the text field `target`, or of the editing host it
+/// is in. Returns whether the text was inserted.
+fn insert_dropped_text(target: &Element, text: &str) -> bool {
+    if !accepts_text(target) {
+        return false;
+    }
+    let replacement = DOMString::from(text);
+    let result = if let Some(input) = target.downcast::<HTMLInputElement>() {
+        let start = input.GetSelectionStart().unwrap_or(0);
+        let end = input.GetSelectionEnd().unwrap_or(start);
+        input.SetRangeText_(replacement, start, end, SelectionMode::End)
+    } else if let Some(textarea) = target.downcast::<HTMLTextAreaElement>() {
+        let start = textarea.GetSelectionStart().unwrap_or(0);
+        let end = textarea.GetSelectionEnd().unwrap_or(start);
+        textarea.SetRangeText_(replacement, start, end, SelectionMode::End)
+    } else {
+        return editing::editing_host_of(target.upcast())
+            .map_or(false, |host| editing::insert_dropped_text(&host, text));
+    };
+    if result.is_err() {
+        return false;
+    }
+    target
+        .upcast::<EventTarget>()
+        .fire_bubbling_event(atom!("input"));
+    true
+}
@@ -0,0 +1,757 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Editing of the contents of editing hosts, the elements made editable by their
+//! `contenteditable` attribute. Like `TextInput` does for text controls, this turns key
+//! presses and IME commits into edits, but it edits the DOM at the document's selection,
+//! firing the `beforeinput` and `input` events of <https://w3c.github.io/input-events/>.
+
+use std::iter;
+
+use html5ever::{local_name, namespace_url, ns};
+use keyboard_types::{Key, KeyState, Modifiers, ShortcutMatcher};
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::dom::bindings::codegen::Bindings::CharacterDataBinding::CharacterDataMethods;
+use crate::dom::bindings::codegen::Bindings::DocumentBinding::DocumentMethods;
+use crate::dom::bindings::codegen::Bindings::KeyboardEventBinding::KeyboardEventMethods;
+use crate::dom::bindings::codegen::Bindings::NodeBinding::NodeMethods;
+use crate::dom::bindings::codegen::Bindings::RangeBinding::RangeMethods;
+use crate::dom::bindings::codegen::Bindings::SelectionBinding::SelectionMethods;
+use crate::dom::bindings::codegen::Bindings::TextBinding::TextMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::characterdata::CharacterData;
+use crate::dom::compositionevent::CompositionEvent;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::event::{Event, EventStatus};
+use crate::dom::htmlbrelement::HTMLBRElement;
+use crate::dom::htmldivelement::HTMLDivElement;
+use crate::dom::htmlelement::{ContentEditableState, HTMLElement};
+use crate::dom::inputevent::InputEvent;
+use crate::dom::keyboardevent::KeyboardEvent;
+use crate::dom::node::{document_from_node, CloneChildrenFlag, Node, ShadowIncluding};
+use crate::dom::range::Range;
+use crate::dom::selection::Selection;
+use crate::dom::staticrange::StaticRange;
+use crate::dom::text::Text;
+pub use crate::textinput::Direction;
+
+/// How far a deletion reaches from the caret.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Granularity {
+    Character,
+    Word,
+    Line,
+}
+
+/// An editing command, which is named by its `inputType` in
+/// <https://w3c.github.io/input-events/#interface-InputEvent-Attributes>.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum EditCommand {
+    InsertText(String),
+    InsertCompositionText(String),
+    InsertFromDrop(String),
+    InsertParagraph,
+    InsertLineBreak,
+    Delete(Direction, Granularity),
+}
+
+impl EditCommand {
+    pub fn input_type(&self) -> &'static str {
+        match *self {
+            EditCommand::InsertText(_) => "insertText",
+            EditCommand::InsertCompositionText(_) => "insertCompositionText",
+            EditCommand::InsertFromDrop(_) => "insertFromDrop",
+            EditCommand::InsertParagraph => "insertParagraph",
+            EditCommand::InsertLineBreak => "insertLineBreak",
+            EditCommand::Delete(Direction::Backward, Granularity::Character) => {
+                "deleteContentBackward"
+            },
+            EditCommand::Delete(Direction::Forward, Granularity::Character) => {
+                "deleteContentForward"
+            },
+            EditCommand::Delete(Direction::Backward, Granularity::Word) => "deleteWordBackward",
+            EditCommand::Delete(Direction::Forward, Granularity::Word) => "deleteWordForward",
+            EditCommand::Delete(Direction::Backward, Granularity::Line) => "deleteSoftLineBackward",
+            EditCommand::Delete(Direction::Forward, Granularity::Line) => "deleteSoftLineForward",
+        }
+    }
+
+    fn data(&self) -> Option<DOMString> {
+        match *self {
+            EditCommand::InsertText(ref text) | EditCommand::InsertCompositionText(ref text) => {
+                Some(DOMString::from(text.clone()))
+            },
+            _ => None,
+        }
+    }
+
+    /// Whether canceling the `beforeinput` event prevents the command. IME compositions
+    /// can't be canceled.
+    fn is_cancelable(&self) -> bool {
+        !matches!(*self, EditCommand::InsertCompositionText(_))
+    }
+}
+
+/// The editing command run by a key press, if any.
+pub fn command_for_keydown(key: Key, mut mods: Modifiers, macos: bool) -> Option<EditCommand> {
+    let shift = mods.contains(Modifiers::SHIFT);
+    mods.remove(Modifiers::SHIFT);
+    let deletion = |direction, granularity| Some(EditCommand::Delete(direction, granularity));
+    // Control+Shift deletes to the edge of the line on other systems than Mac OS.
+    let word_or_line = if shift {
+        Granularity::Line
+    } else {
+        Granularity::Word
+    };
+    ShortcutMatcher::new(KeyState::Down, key.clone(), mods)
+        .optional_shortcut(macos, Modifiers::ALT, Key::Backspace, || {
+            deletion(Direction::Backward, Granularity::Word)
+        })
+        .optional_shortcut(macos, Modifiers::ALT, Key::Delete, || {
+            deletion(Direction::Forward, Granularity::Word)
+        })
+        .optional_shortcut(macos, Modifiers::META, Key::Backspace, || {
+            deletion(Direction::Backward, Granularity::Line)
+        })
+        .optional_shortcut(macos, Modifiers::META, Key::Delete, || {
+            deletion(Direction::Forward, Granularity::Line)
+        })
+        .optional_shortcut(!macos, Modifiers::CONTROL, Key::Backspace, || {
+            deletion(Direction::Backward, word_or_line)
+        })
+        .optional_shortcut(!macos, Modifiers::CONTROL, Key::Delete, || {
+            deletion(Direction::Forward, word_or_line)
+        })
+        .shortcut(Modifiers::empty(), Key::Backspace, || {
+            deletion(Direction::Backward, Granularity::Character)
+        })
+        .shortcut(Modifiers::empty(), Key::Delete, || {
+            deletion(Direction::Forward, Granularity::Character)
+        })
+        .shortcut(Modifiers::empty(), Key::Enter, || {
+            Some(if shift {
+                EditCommand::InsertLineBreak
+            } else {
+                EditCommand::InsertParagraph
+            })
+        })
+        .otherwise(|| match key {
+            Key::Character(ref text) if !mods.intersects(Modifiers::CONTROL | Modifiers::META) => {
+                Some(EditCommand::InsertText(text.clone()))
+            },
+            _ => None,
+        })
+        .unwrap()
+}
+
+/// The UTF-16 offset in `text` that is `granularity` away from the UTF-16 `offset`, in
+/// `direction`. Lines end at the ends of the text, as line breaks are elements.
+pub fn find_boundary(
+    text: &str,
+    offset: u32,
+    direction: Direction,
+    granularity: Granularity,
+) -> u32 {
+    let index = utf8_index(text, offset);
+    let boundary = match (direction, granularity) {
+        (Direction::Backward, Granularity::Line) => 0,
+        (Direction::Forward, Granularity::Line) => text.len(),
+        (Direction::Backward, Granularity::Character) => text[..index]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(start, _)| start),
+        (Direction::Forward, Granularity::Character) => text[index..]
+            .graphemes(true)
+            .next()
+            .map_or(index, |grapheme| index + grapheme.len()),
+        // Like in text controls, words extend over the punctuation and spaces before them.
+        (Direction::Backward, Granularity::Word) => {
+            let mut start = index;
+            for segment in text[..index].split_word_bounds().rev() {
+                start -= segment.len();
+                if segment.chars().any(char::is_alphanumeric) {
+                    break;
+                }
+            }
+            start
+        },
+        (Direction::Forward, Granularity::Word) => {
+            let mut end = index;
+            for segment in text[index..].split_word_bounds() {
+                end += segment.len();
+                if segment.chars().any(char::is_alphanumeric) {
+                    break;
+                }
+            }
+            end
+        },
+    };
+    text[..boundary].encode_utf16().count() as u32
+}
+
+/// The index in `text` of the UTF-16 `offset`, rounded up to a character boundary.
+fn utf8_index(text: &str, offset: u32) -> usize {
+    let mut utf16_offset = 0;
+    for (index, character) in text.char_indices() {
+        if utf16_offset >= offset as usize {
+            return index;
+        }
+        utf16_offset += character.len_utf16();
+    }
+    text.len()
+}
+
+/// <https://w3c.github.io/editing/docs/execCommand/#editing-host-of>
+pub(crate) fn editing_host_of(node: &Node) -> Option<DomRoot<HTMLElement>> {
+    for ancestor in node.inclusive_ancestors(ShadowIncluding::No) {
+        if let Some(element) = ancestor.downcast::<HTMLElement>() {
+            match element.content_editable_state() {
+                ContentEditableState::True => return Some(DomRoot::from_ref(element)),
+                ContentEditableState::False => return None,
+                ContentEditableState::Inherit => {},
+            }
+        }
+    }
+    None
+}
+
+/// Run the editing command for a key press in the editing host. Returns whether the key
+/// press was handled.
+pub(crate) fn handle_keydown(host: &HTMLElement, event: &KeyboardEvent) -> bool {
+    if event.IsComposing() {
+        return false;
+    }
+    match command_for_keydown(event.key(), event.modifiers(), cfg!(target_os = "macos")) {
+        Some(command) => run_command(host, command),
+        None => false,
+    }
+}
+
+/// Insert the text committed by an IME into the editing host.
+pub(crate) fn handle_compositionend(host: &HTMLElement, event: &CompositionEvent) -> bool {
+    if event.data().is_empty() {
+        return false;
+    }
+    run_command(
+        host,
+        EditCommand::InsertCompositionText(event.data().to_owned()),
+    )
+}
+
+/// Insert the text dropped at the end of a drag and drop operation into the editing host.
+pub(crate) fn insert_dropped_text(host: &HTMLElement, text: &str) -> bool {
+    run_command(host, EditCommand::InsertFromDrop(text.to_owned()))
+}
+
+/// A boundary point.
+type Point = (DomRoot<Node>, u32);
+
+/// Run an editing command at the document's selection, or at the end of the editing host if
+/// the selection is elsewhere. Returns whether the command was handled.
+fn run_command(host: &HTMLElement, command: EditCommand) -> bool {
+    let document = document_from_node(host);
+    let window = document.window();
+    let host_node = host.upcast::<Node>();
+    let selection = match document.GetSelection() {
+        Some(selection) => selection,
+        None => return false,
+    };
+
+    let (start, end) = selection_in(&selection, host_node);
+    let (start, end) = match command {
+        EditCommand::Delete(direction, granularity) if start == end => {
+            match deletion_range(host_node, start, direction, granularity) {
+                Some(range) => range,
+                // There is nothing to delete in this direction.
+                None => return true,
+            }
+        },
+        _ => (start, end),
+    };
+
+    let input_type = DOMString::from(command.input_type());
+    let target_range = StaticRange::new(window, &start.0, start.1, &end.0, end.1);
+    let beforeinput = InputEvent::new(
+        window,
+        None,
+        DOMString::from("beforeinput"),
+        true,
+        command.is_cancelable(),
+        Some(window),
+        0,
+        command.data(),
+        false,
+        input_type.clone(),
+        &[target_range],
+    );
+    if beforeinput.upcast::<Event>().fire(host.upcast()) == EventStatus::Canceled {
+        return true;
+    }
+    // The event listeners may have moved the target range out of the editing host.
+    if !host_node.is_inclusive_ancestor_of(&start.0) || !host_node.is_inclusive_ancestor_of(&end.0)
+    {
+        return true;
+    }
+
+    let mut caret = if start == end {
+        start
+    } else {
+        delete_range(&document, host_node, &start, &end)
+    };
+    caret = match command {
+        EditCommand::InsertText(ref text) |
+        EditCommand::InsertCompositionText(ref text) |
+        EditCommand::InsertFromDrop(ref text) => insert_text(&document, caret, text),
+        EditCommand::InsertParagraph => insert_paragraph(&document, host_node, caret),
+        EditCommand::InsertLineBreak => insert_line_break(&document, host_node, caret),
+        EditCommand::Delete(..) => caret,
+    };
+    let _ = selection.Collapse(Some(&caret.0), caret.1);
+
+    let input = InputEvent::new(
+        window,
+        None,
+        DOMString::from("input"),
+        true,
+        false,
+        Some(window),
+        0,
+        command.data(),
+        false,
+        input_type,
+        &[],
+    );
+    input.upcast::<Event>().fire(host.upcast());
+    true
+}
+
+/// The selection, if it is within the editing host, or the end of the editing host.
+fn selection_in(selection: &Selection, host: &Node) -> (Point, Point) {
+    if let Ok(range) = selection.GetRangeAt(0) {
+        let start = (range.StartContainer(), range.StartOffset());
+        let end = (range.EndContainer(), range.EndOffset());
+        if host.is_inclusive_ancestor_of(&start.0) && host.is_inclusive_ancestor_of(&end.0) {
+            return (start, end);
+        }
+    }
+    let end = (DomRoot::from_ref(host), host.children_count());
+    (end.clone(), end)
+}
+
+/// Whether the caret moves over the node, i.e. whether it is non-empty text or a line break.
+fn is_leaf(node: &Node) -> bool {
+    if node.is::<Text>() {
+        node.len() > 0
+    } else {
+        node.is::<HTMLBRElement>()
+    }
+}
+
+fn has_leaves(node: &Node) -> bool {
+    node.traverse_preorder(ShadowIncluding::No)
+        .any(|node| is_leaf(&node))
+}
+
+/// Whether the node is an element that lays out its contents as a paragraph of its own.
+fn is_block(node: &Node) -> bool {
+    let element = match node.downcast::<Element>() {
+        Some(element) => element,
+        None => return false,
+    };
+    *element.namespace() == ns!(html) &&
+        matches!(
+            *element.local_name(),
+            local_name!("address") |
+                local_name!("article") |
+                local_name!("aside") |
+                local_name!("blockquote") |
+                local_name!("dd") |
+                local_name!("div") |
+                local_name!("dt") |
+                local_name!("footer") |
+                local_name!("h1") |
+                local_name!("h2") |
+                local_name!("h3") |
+                local_name!("h4") |
+                local_name!("h5") |
+                local_name!("h6") |
+                local_name!("header") |
+                local_name!("li") |
+                local_name!("main") |
+                local_name!("nav") |
+                local_name!("p") |
+                local_name!("pre") |
+                local_name!("section") |
+                local_name!("td") |
+                local_name!("th")
+        )
+}
+
+fn is_heading(node: &Node) -> bool {
+    node.downcast::<Element>().map_or(false, |element| {
+        matches!(
+            *element.local_name(),
+            local_name!("h1") |
+                local_name!("h2") |
+                local_name!("h3") |
+                local_name!("h4") |
+                local_name!("h5") |
+                local_name!("h6")
+        )
+    })
+}
+
+/// The paragraph of the node: its nearest block ancestor within the editing host, or the
+/// editing host itself.
+fn block_of(node: &Node, host: &Node) -> DomRoot<Node> {
+    node.inclusive_ancestors(ShadowIncluding::No)
+        .take_while(|ancestor| &**ancestor != host)
+        .find(|ancestor| is_block(ancestor))
+        .unwrap_or_else(|| DomRoot::from_ref(host))
+}
+
+/// The first leaf before or after a boundary point, within the editing host.
+fn leaf_next_to(point: &Point, direction: Direction, host: &Node) -> Option<DomRoot<Node>> {
+    let (ref container, offset) = *point;
+    let child = if container.is::<CharacterData>() {
+        None
+    } else {
+        match direction {
+            Direction::Backward => offset
+                .checked_sub(1)
+                .and_then(|index| container.children().nth(index as usize)),
+            Direction::Forward => container.children().nth(offset as usize),
+        }
+    };
+    match (direction, child) {
+        (Direction::Backward, Some(child)) => {
+            let last = child.descending_last_children().last().unwrap_or(child);
+            iter::once(last.clone())
+                .chain(last.preceding_nodes(host))
+                .find(|node| is_leaf(node))
+        },
+        (Direction::Backward, None) => container.preceding_nodes(host).find(|node| is_leaf(node)),
+        (Direction::Forward, Some(child)) => iter::once(child.clone())
+            .chain(child.following_nodes(host))
+            .find(|node| is_leaf(node)),
+        (Direction::Forward, None) => container
+            .following_nodes(host)
+            .find(|node| !container.is_ancestor_of(node) && is_leaf(node)),
+    }
+}
+
+fn ordered(point: Point, other: Point, direction: Direction) -> (Point, Point) {
+    match direction {
+        Direction::Backward => (other, point),
+        Direction::Forward => (point, other),
+    }
+}
+
+/// The range that a deletion from the caret removes, or None if there is nothing to delete.
+/// At the edge of a paragraph, this is the range up to the adjacent paragraph, which the
+/// deletion joins.
+fn deletion_range(
+    host: &Node,
+    caret: Point,
+    direction: Direction,
+    mut granularity: Granularity,
+) -> Option<(Point, Point)> {
+    let block = block_of(&caret.0, host);
+    let next_leaf = leaf_next_to(&caret, direction, host);
+    let next_leaf_in_block = next_leaf
+        .as_ref()
+        .map_or(false, |leaf| block_of(leaf, host) == block);
+
+    if granularity == Granularity::Line {
+        // At the edge of the line, delete the line break instead.
+        if next_leaf_in_block {
+            let edge = match direction {
+                Direction::Backward => (block.clone(), 0),
+                Direction::Forward => (block.clone(), block.len()),
+            };
+            return Some(ordered(caret, edge, direction));
+        }
+        granularity = Granularity::Character;
+    }
+
+    if let Some(text) = caret.0.downcast::<CharacterData>() {
+        let boundary = find_boundary(&text.data(), caret.1, direction, granularity);
+        if boundary != caret.1 {
+            let far = (caret.0.clone(), boundary);
+            return Some(ordered(caret, far, direction));
+        }
+    }
+
+    let leaf = next_leaf?;
+    let far = if let Some(text) = leaf.downcast::<CharacterData>() {
+        let near = match direction {
+            Direction::Backward => leaf.len(),
+            Direction::Forward => 0,
+        };
+        let offset = if next_leaf_in_block {
+            find_boundary(&text.data(), near, direction, granularity)
+        } else {
+            near
+        };
+        (leaf.clone(), offset)
+    } else {
+        // A line break, which is deleted within the paragraph.
+        let parent = leaf.GetParentNode()?;
+        let index = leaf.index();
+        let offset = match (direction, next_leaf_in_block) {
+            (Direction::Backward, true) | (Direction::Forward, false) => index,
+            (Direction::Backward, false) | (Direction::Forward, true) => index + 1,
+        };
+        (parent, offset)
+    };
+    Some(ordered(caret, far, direction))
+}
+
+/// Delete the contents of a range and join the paragraphs of its ends. Returns the caret
+/// position after the deletion.
+fn delete_range(document: &Document, host: &Node, start: &Point, end: &Point) -> Point {
+    let start_block = block_of(&start.0, host);
+    let end_block = block_of(&end.0, host);
+    let range = Range::new(document, &start.0, start.1, &end.0, end.1);
+    let _ = range.DeleteContents();
+    let caret = (range.StartContainer(), range.StartOffset());
+
+    if start_block != end_block {
+        join_blocks(document, host, &start_block, &end_block);
+    }
+    // Keep the height of paragraphs that became empty.
+    if &*start_block != host && start_block.is_connected() && !has_leaves(&start_block) {
+        append_line_break(document, &start_block);
+    }
+    caret
+}
+
+/// Join a paragraph with the paragraph that follows it.
+fn join_blocks(document: &Document, host: &Node, first: &Node, second: &Node) {
+    if first.is_ancestor_of(second) {
+        // The second paragraph is nested in the first one, right after the first one's line.
+        let parent = match second.GetParentNode() {
+            Some(parent) => parent,
+            None => return,
+        };
+        for child in second.children() {
+            let _ = parent.InsertBefore(&child, Some(second));
+        }
+        second.remove_self();
+    } else if second.is_ancestor_of(first) {
+        // The first paragraph is nested in the second one: move the line that follows it
+        // into it.
+        let top = match first
+            .inclusive_ancestors(ShadowIncluding::No)
+            .find(|ancestor| second.is_parent_of(ancestor))
+        {
+            Some(top) => top,
+            None => return,
+        };
+        remove_trailing_line_break(first);
+        let line: Vec<_> = top
+            .following_siblings()
+            .take_while(|sibling| !is_block(sibling) && !sibling.is::<HTMLBRElement>())
+            .collect();
+        for node in line {
+            let _ = first.AppendChild(&node);
+        }
+    } else {
+        remove_trailing_line_break(first);
+        for child in second.children() {
+            let _ = first.AppendChild(&child);
+        }
+        // Remove the second paragraph, and the containers that it leaves empty.
+        let mut node = DomRoot::from_ref(second);
+        while let Some(parent) = node.GetParentNode() {
+            node.remove_self();
+            if &*parent == host || parent.children_count() > 0 {
+                break;
+            }
+            node = parent;
+        }
+    }
+    if !has_leaves(first) {
+        append_line_break(document, first);
+    }
+}
+
+/// Remove the line break at the end of a paragraph, which only keeps an otherwise empty
+/// paragraph from collapsing.
+fn remove_trailing_line_break(block: &Node) {
+    if let Some(last_child) = block.GetLastChild() {
+        if last_child.is::<HTMLBRElement>() {
+            last_child.remove_self();
+        }
+    }
+}
+
+fn append_line_break(document: &Document, parent: &Node) {
+    let br = HTMLBRElement::new(local_name!("br"), None, document, None);
+    let _ = parent.AppendChild(br.upcast());
+}
+
+/// Split the text at a boundary point, if any. Returns the parent and the index at which
+/// to insert a node at the boundary point.
+fn split_text_at(point: Point) -> Point {
+    let (container, offset) = point;
+    let text = match container.downcast::<Text>() {
+        Some(text) => text,
+        None => return (container, offset),
+    };
+    let parent = match container.GetParentNode() {
+        Some(parent) => parent,
+        None => return (container, offset),
+    };
+    let index = container.index();
+    if offset == 0 {
+        return (parent, index);
+    }
+    if offset < container.len() {
+        let _ = text.SplitText(offset);
+    }
+    (parent, index + 1)
+}
+
+fn insert_text(document: &Document, caret: Point, text: &str) -> Point {
+    let (container, offset) = caret;
+    let length = text.encode_utf16().count() as u32;
+    if let Some(character_data) = container.downcast::<CharacterData>() {
+        let _ = character_data.InsertData(offset, DOMString::from(text));
+        return (container, offset + length);
+    }
+
+    // Extend the text right before the caret rather than adding a text node.
+    let previous = offset
+        .checked_sub(1)
+        .and_then(|index| container.children().nth(index as usize));
+    if let Some(previous) = previous {
+        if let Some(character_data) = previous.downcast::<Text>() {
+            character_data
+                .upcast::<CharacterData>()
+                .AppendData(DOMString::from(text));
+            let offset = previous.len();
+            return (previous, offset);
+        }
+    }
+
+    let text_node = Text::new(DOMString::from(text), document);
+    let child = container.children().nth(offset as usize);
+    let _ = container.InsertBefore(text_node.upcast(), child.as_deref());
+    (DomRoot::upcast(text_node), length)
+}
+
+fn insert_line_break(document: &Document, host: &Node, caret: Point) -> Point {
+    let (parent, index) = split_text_at(caret);
+    let br = HTMLBRElement::new(local_name!("br"), None, document, None);
+    let child = parent.children().nth(index as usize);
+    let _ = parent.InsertBefore(br.upcast(), child.as_deref());
+
+    // A line break at the end of a paragraph doesn't start a line by itself.
+    let caret = (parent, index + 1);
+    let block = block_of(&caret.0, host);
+    let ends_block = leaf_next_to(&caret, Direction::Forward, host)
+        .map_or(true, |leaf| block_of(&leaf, host) != block);
+    if ends_block {
+        let br = HTMLBRElement::new(local_name!("br"), None, document, None);
+        let child = caret.0.children().nth(caret.1 as usize);
+        let _ = caret.0.InsertBefore(br.upcast(), child.as_deref());
+    }
+    caret
+}
+
+/// Split the paragraph at the caret, moving what follows it into a new paragraph.
+fn insert_paragraph(document: &Document, host: &Node, caret: Point) -> Point {
+    let block = block_of(&caret.0, host);
+    if &*block == host {
+        return insert_paragraph_in_host(document, host, caret);
+    }
+    let parent = match block.GetParentNode() {
+        Some(parent) => parent,
+        None => return caret,
+    };
+
+    let range = Range::new(document, &caret.0, caret.1, &block, block.len());
+    let rest = match range.ExtractContents() {
+        Ok(rest) => rest,
+        Err(_) => return caret,
+    };
+    // Like other browsers, start a div rather than another heading after a heading.
+    let new_block = if is_heading(&block) && !has_leaves(rest.upcast()) {
+        DomRoot::upcast(HTMLDivElement::new(
+            local_name!("div"),
+            None,
+            document,
+            None,
+        ))
+    } else {
+        let new_block = Node::clone(&block, None, CloneChildrenFlag::DoNotCloneChildren);
+        if let Some(element) = new_block.downcast::<Element>() {
+            element.remove_attribute(&ns!(), &local_name!("id"));
+        }
+        new_block
+    };
+    let _ = new_block.AppendChild(rest.upcast());
+    let _ = parent.InsertBefore(&new_block, block.GetNextSibling().as_deref());
+
+    for block in [&*block, &*new_block] {
+        if !has_leaves(block) {
+            append_line_break(document, block);
+        }
+    }
+    (new_block, 0)
+}
+
+/// Move the rest of a line of the editing host's own contents into a new paragraph.
+fn insert_paragraph_in_host(document: &Document, host: &Node, caret: Point) -> Point {
+    // The host's child that contains the caret, and the end of its line.
+    let top = caret
+        .0
+        .inclusive_ancestors(ShadowIncluding::No)
+        .find(|ancestor| host.is_parent_of(ancestor));
+    let line_start = top.as_ref().map_or(caret.1, |top| top.index());
+    let line_end = host
+        .children()
+        .skip(line_start as usize)
+        .find(|child| is_block(child) || child.is::<HTMLBRElement>())
+        .map_or(host.children_count(), |child| child.index());
+
+    let range = Range::new(document, &caret.0, caret.1, host, line_end);
+    let rest = match range.ExtractContents() {
+        Ok(rest) => rest,
+        Err(_) => return caret,
+    };
+    let new_block = HTMLDivElement::new(local_name!("div"), None, document, None);
+    let new_block = new_block.upcast::<Node>();
+    let _ = new_block.AppendChild(rest.upcast());
+    if !has_leaves(new_block) {
+        append_line_break(document, new_block);
+    }
+
+    // The new paragraph goes after what is left of the line, which is kept as an empty line
+    // if there is nothing left.
+    let index = top.as_ref().map_or(range.StartOffset(), |top| {
+        if host.is_parent_of(top) {
+            top.index() + 1
+        } else {
+            line_start
+        }
+    });
+    let line_is_empty = !host
+        .children()
+        .take(index as usize)
+        .skip(line_start as usize)
+        .any(|child| has_leaves(&child));
+    let child = host.children().nth(index as usize);
+    let _ = host.InsertBefore(new_block, child.as_deref());
+    if line_is_empty {
+        let br = HTMLBRElement::new(local_name!("br"), None, document, None);
+        let _ = host.InsertBefore(br.upcast(), Some(new_block));
+    }
+    (DomRoot::from_ref(new_block), 0)
+}
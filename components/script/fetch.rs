@@ -5,11 +5,14 @@
 use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 
+use http::header::{HeaderMap, HeaderValue};
 use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
+use js::jsval::UndefinedValue;
+use js::rust::HandleValue;
 use net_traits::request::{
-    CorsSettings, CredentialsMode, Destination, Referrer, Request as NetTraitsRequest,
-    RequestBuilder, RequestMode, ServiceWorkersMode,
+    CorsSettings, CredentialsMode, Destination, RedirectMode, Referrer,
+    Request as NetTraitsRequest, RequestBuilder, RequestMode, ServiceWorkersMode,
 };
 use net_traits::CoreResourceMsg::Fetch as NetTraitsFetch;
 use net_traits::{
@@ -19,7 +22,10 @@ use net_traits::{
 };
 use servo_url::ServoUrl;
 
-use crate::dom::bindings::codegen::Bindings::RequestBinding::{RequestInfo, RequestInit};
+use crate::dom::abortsignal::{AbortAlgorithm, AbortSignal};
+use crate::dom::bindings::codegen::Bindings::RequestBinding::{
+    RequestInfo, RequestInit, RequestMethods,
+};
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::ResponseType as DOMResponseType;
 use crate::dom::bindings::codegen::Bindings::ResponseBinding::Response_Binding::ResponseMethods;
 use crate::dom::bindings::error::Error;
@@ -45,6 +51,43 @@ struct FetchContext {
     fetch_promise: Option<TrustedPromise>,
     response_object: Trusted<Response>,
     resource_timing: ResourceFetchTiming,
+    /// <https://fetch.spec.whatwg.org/#dom-global-fetch> "locallyAborted"
+    locally_aborted: bool,
+}
+
+/// The abort steps a `fetch()` call adds to its request's signal.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct FetchAbortAlgorithm {
+    #[ignore_malloc_size_of = "Arc"]
+    #[no_trace]
+    fetch_context: Arc<Mutex<FetchContext>>,
+    #[ignore_malloc_size_of = "channels are hard"]
+    #[no_trace]
+    cancel_chan: ipc::IpcSender<()>,
+}
+
+impl FetchAbortAlgorithm {
+    /// <https://fetch.spec.whatwg.org/#dom-global-fetch> Step 11
+    pub fn abort(self, reason: HandleValue) {
+        let mut context = self.fetch_context.lock().unwrap();
+
+        // Step 11.1. Set locallyAborted to true.
+        context.locally_aborted = true;
+
+        // Step 11.3. Terminate controller. The fetch may already be over, in which
+        // case nobody is listening anymore.
+        let _ = self.cancel_chan.send(());
+
+        // Step 11.2. Abort the fetch() call with p, request, responseObject,
+        // and requestObject's signal's abort reason.
+        let response = context.response_object.root();
+        if let Some(promise) = context.fetch_promise.take() {
+            let promise = promise.root();
+            let _ac = enter_realm(&*promise);
+            promise.reject(GlobalScope::get_cx(), reason);
+        }
+        response.error_stream_with_reason(reason);
+    }
 }
 
 /// RAII fetch canceller object. By default initialized to not having a canceller
@@ -107,6 +150,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         headers: request.headers.clone(),
         unsafe_request: request.unsafe_request,
         body: request.body.clone(),
+        keep_alive: request.keep_alive,
         service_workers_mode: ServiceWorkersMode::All,
         destination: request.destination,
         synchronous: request.synchronous,
@@ -128,6 +172,7 @@ fn request_init_from_request(request: NetTraitsRequest) -> RequestBuilder {
         url_list: vec![],
         parser_metadata: request.parser_metadata,
         initiator: request.initiator,
+        priority: request.priority,
         csp_list: None,
         https_state: request.https_state,
         response_tainting: request.response_tainting,
@@ -143,21 +188,30 @@ pub fn Fetch(
     init: RootedTraceableBox<RequestInit>,
     comp: InRealm,
 ) -> Rc<Promise> {
-    let core_resource_thread = global.core_resource_thread();
-
     // Step 1
     let promise = Promise::new_in_current_realm(comp);
     let response = Response::new(global);
 
     // Step 2
-    let request = match Request::Constructor(global, None, input, init) {
+    let request_object = match Request::Constructor(global, None, input, init) {
         Err(e) => {
             response.error_stream(e.clone());
             promise.reject_error(e);
             return promise;
         },
-        Ok(r) => r.get_request(),
+        Ok(r) => r,
     };
+    let request = request_object.get_request();
+
+    // Step 4. If requestObject's signal is aborted, then abort the fetch() call
+    // with its abort reason.
+    let signal = request_object.Signal();
+    if signal.aborted() {
+        rooted!(in(*GlobalScope::get_cx()) let mut reason = UndefinedValue());
+        signal.abort_reason(reason.handle_mut());
+        promise.reject(GlobalScope::get_cx(), reason.handle());
+        return promise;
+    }
     let timing_type = request.timing_type();
 
     let mut request_init = request_init_from_request(request);
@@ -168,6 +222,75 @@ pub fn Fetch(
         request_init.service_workers_mode = ServiceWorkersMode::None;
     }
 
+    send_fetch(
+        global,
+        request_init,
+        timing_type,
+        &promise,
+        &response,
+        Some(&*signal),
+    );
+
+    promise
+}
+
+/// <https://w3c.github.io/ServiceWorker/#handle-fetch> step 15.3
+///
+/// Start the navigation preload request for the navigation to `url`, which races the
+/// service worker handling the navigation. Returns the promise of its response.
+pub fn fetch_navigation_preload(
+    global: &GlobalScope,
+    url: ServoUrl,
+    header_value: Vec<u8>,
+    comp: InRealm,
+) -> Rc<Promise> {
+    let promise = Promise::new_in_current_realm(comp);
+    let response = Response::new(global);
+
+    let mut headers = HeaderMap::new();
+    match HeaderValue::from_bytes(&header_value) {
+        Ok(value) => {
+            headers.insert("Service-Worker-Navigation-Preload", value);
+        },
+        Err(_) => {
+            promise.reject_error(Error::Type("Invalid preload header value".to_string()));
+            return promise;
+        },
+    }
+    let mut request_init = RequestBuilder::new(url, Referrer::NoReferrer)
+        .mode(RequestMode::Navigate)
+        .destination(Destination::Document)
+        .credentials_mode(CredentialsMode::Include)
+        .redirect_mode(RedirectMode::Manual)
+        .headers(headers)
+        .origin(global.origin().immutable().clone());
+    // The preload request must not be handed back to the service worker.
+    request_init.service_workers_mode = ServiceWorkersMode::None;
+
+    send_fetch(
+        global,
+        request_init,
+        ResourceTimingType::Navigation,
+        &promise,
+        &response,
+        None,
+    );
+
+    promise
+}
+
+/// Send `request_init` to the network, settling `promise` with `response` once its
+/// headers arrive.
+fn send_fetch(
+    global: &GlobalScope,
+    request_init: RequestBuilder,
+    timing_type: ResourceTimingType,
+    promise: &Rc<Promise>,
+    response: &Response,
+    signal: Option<&AbortSignal>,
+) {
+    let core_resource_thread = global.core_resource_thread();
+
     // Step 4
     response.Headers().set_guard(Guard::Immutable);
 
@@ -175,9 +298,20 @@ pub fn Fetch(
     let (action_sender, action_receiver) = ipc::channel().unwrap();
     let fetch_context = Arc::new(Mutex::new(FetchContext {
         fetch_promise: Some(TrustedPromise::new(promise.clone())),
-        response_object: Trusted::new(&*response),
+        response_object: Trusted::new(response),
         resource_timing: ResourceFetchTiming::new(timing_type),
+        locally_aborted: false,
     }));
+
+    // Step 11. Add the abort steps to requestObject's signal.
+    let (cancel_chan, cancel_receiver) = ipc::channel().unwrap();
+    if let Some(signal) = signal {
+        signal.add_algorithm(AbortAlgorithm::Fetch(FetchAbortAlgorithm {
+            fetch_context: fetch_context.clone(),
+            cancel_chan,
+        }));
+    }
+
     let listener = NetworkListener {
         context: fetch_context,
         task_source: global.networking_task_source(),
@@ -193,11 +327,9 @@ pub fn Fetch(
     core_resource_thread
         .send(NetTraitsFetch(
             request_init,
-            FetchChannels::ResponseMsg(action_sender, None),
+            FetchChannels::ResponseMsg(action_sender, Some(cancel_receiver)),
         ))
         .unwrap();
-
-    promise
 }
 
 impl PreInvoke for FetchContext {}
@@ -213,6 +345,11 @@ impl FetchResponseListener for FetchContext {
 
     #[allow(crown::unrooted_must_root)]
     fn process_response(&mut self, fetch_metadata: Result<FetchMetadata, NetworkError>) {
+        // Step 12.1. If locallyAborted is true, then abort these steps.
+        if self.locally_aborted {
+            return;
+        }
+
         let promise = self
             .fetch_promise
             .take()
@@ -266,11 +403,17 @@ impl FetchResponseListener for FetchContext {
     }
 
     fn process_response_chunk(&mut self, chunk: Vec<u8>) {
+        if self.locally_aborted {
+            return;
+        }
         let response = self.response_object.root();
         response.stream_chunk(chunk);
     }
 
     fn process_response_eof(&mut self, _response: Result<ResourceFetchTiming, NetworkError>) {
+        if self.locally_aborted {
+            return;
+        }
         let response = self.response_object.root();
         let _ac = enter_realm(&*response);
         response.finish();
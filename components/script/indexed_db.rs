@@ -0,0 +1,533 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Conversions between script values and the keys, key ranges, key paths and records of
+//! IndexedDB.
+//! <https://w3c.github.io/IndexedDB/#key-construct>
+
+use std::ffi::CString;
+use std::ptr;
+
+use js::conversions::ToJSValConvertible;
+use js::jsapi::{
+    ClippedTime, DateGetMsecSinceEpoch, Handle, HandleValueArray, JSObject, JS_NewPlainObject,
+    NewArrayObject, NewDateObject, ObjectIsDate, JSPROP_ENUMERATE,
+};
+use js::jsval::{DoubleValue, ObjectValue, UndefinedValue};
+use js::rust::wrappers::{
+    GetArrayLength, IsArrayObject, JS_DefineElement, JS_DefineProperty, JS_GetElement,
+    JS_GetProperty, JS_HasOwnProperty,
+};
+use js::rust::{HandleValue, MutableHandleValue};
+use js::typedarray::{ArrayBuffer, ArrayBufferView, CreateWith};
+use net_traits::indexeddb_thread::{
+    BackendError, IndexedDBKey, IndexedDBKeyRange, IndexedDBRecord, KeyPath,
+};
+use script_traits::StructuredSerializedData;
+
+use crate::dom::bindings::codegen::UnionTypes::StringOrStringSequence;
+use crate::dom::bindings::conversions::{jsstring_to_str, root_from_handlevalue};
+use crate::dom::bindings::error::{Error, Fallible};
+use crate::dom::bindings::root::DomRoot;
+use crate::dom::bindings::str::DOMString;
+use crate::dom::bindings::structuredclone;
+use crate::dom::domexception::{DOMErrorName, DOMException};
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::idbkeyrange::IDBKeyRange;
+use crate::script_runtime::JSContext as SafeJSContext;
+
+/// <https://w3c.github.io/IndexedDB/#convert-a-value-to-a-key>
+///
+/// Returns `Ok(None)` if the value is not a valid key, and an error if script threw while
+/// reading it.
+#[allow(unsafe_code)]
+pub fn convert_value_to_key(
+    cx: SafeJSContext,
+    input: HandleValue,
+    seen: &mut Vec<*mut JSObject>,
+) -> Fallible<Option<IndexedDBKey>> {
+    if input.is_number() {
+        let number = input.to_number();
+        if number.is_nan() {
+            return Ok(None);
+        }
+        return Ok(Some(IndexedDBKey::Number(number)));
+    }
+    if input.is_string() {
+        let string = unsafe { jsstring_to_str(*cx, input.to_string()) };
+        return Ok(Some(IndexedDBKey::String(string.into())));
+    }
+    if !input.is_object() {
+        return Ok(None);
+    }
+
+    rooted!(in(*cx) let object = input.to_object());
+    unsafe {
+        let mut is_date = false;
+        if !ObjectIsDate(*cx, Handle::from(object.handle()), &mut is_date) {
+            return Err(Error::JSFailed);
+        }
+        if is_date {
+            let mut time = 0.;
+            if !DateGetMsecSinceEpoch(*cx, Handle::from(object.handle()), &mut time) {
+                return Err(Error::JSFailed);
+            }
+            if time.is_nan() {
+                return Ok(None);
+            }
+            return Ok(Some(IndexedDBKey::Date(time)));
+        }
+    }
+
+    typedarray!(in(*cx) let buffer: ArrayBuffer = object.get());
+    if let Ok(buffer) = buffer {
+        return Ok(Some(IndexedDBKey::Binary(buffer.to_vec())));
+    }
+    typedarray!(in(*cx) let view: ArrayBufferView = object.get());
+    if let Ok(view) = view {
+        return Ok(Some(IndexedDBKey::Binary(view.to_vec())));
+    }
+
+    let mut is_array = false;
+    if !unsafe { IsArrayObject(*cx, input, &mut is_array) } {
+        return Err(Error::JSFailed);
+    }
+    if !is_array || seen.contains(&object.get()) {
+        return Ok(None);
+    }
+    seen.push(object.get());
+    let mut length = 0;
+    if !unsafe { GetArrayLength(*cx, object.handle(), &mut length) } {
+        return Err(Error::JSFailed);
+    }
+    let mut keys = Vec::with_capacity(length as usize);
+    rooted!(in(*cx) let mut entry = UndefinedValue());
+    for index in 0..length {
+        let name = CString::new(index.to_string()).unwrap();
+        let mut found = false;
+        unsafe {
+            if !JS_HasOwnProperty(*cx, object.handle(), name.as_ptr(), &mut found) {
+                return Err(Error::JSFailed);
+            }
+            if !found {
+                return Ok(None);
+            }
+            if !JS_GetElement(*cx, object.handle(), index, entry.handle_mut()) {
+                return Err(Error::JSFailed);
+            }
+        }
+        match convert_value_to_key(cx, entry.handle(), seen)? {
+            Some(key) => keys.push(key),
+            None => return Ok(None),
+        }
+    }
+    seen.pop();
+    Ok(Some(IndexedDBKey::Array(keys)))
+}
+
+/// Convert a value to a key, throwing a `DataError` if it is not a valid one.
+pub fn convert_value_to_valid_key(cx: SafeJSContext, input: HandleValue) -> Fallible<IndexedDBKey> {
+    convert_value_to_key(cx, input, &mut vec![])?.ok_or(Error::Data)
+}
+
+/// <https://w3c.github.io/IndexedDB/#convert-a-key-to-a-value>
+#[allow(unsafe_code)]
+pub fn convert_key_to_value(cx: SafeJSContext, key: &IndexedDBKey, mut rval: MutableHandleValue) {
+    match *key {
+        IndexedDBKey::Number(number) => rval.set(DoubleValue(number)),
+        IndexedDBKey::Date(time) => unsafe {
+            rval.set(ObjectValue(NewDateObject(*cx, ClippedTime { t: time })));
+        },
+        IndexedDBKey::String(ref string) => unsafe {
+            DOMString::from(string.clone()).to_jsval(*cx, rval);
+        },
+        IndexedDBKey::Binary(ref bytes) => unsafe {
+            rooted!(in(*cx) let mut buffer = ptr::null_mut::<JSObject>());
+            assert!(
+                ArrayBuffer::create(*cx, CreateWith::Slice(bytes), buffer.handle_mut()).is_ok()
+            );
+            rval.set(ObjectValue(buffer.get()));
+        },
+        IndexedDBKey::Array(ref keys) => unsafe {
+            rooted!(in(*cx) let array = NewArrayObject(*cx, &HandleValueArray::new()));
+            rooted!(in(*cx) let mut entry = UndefinedValue());
+            for (index, key) in keys.iter().enumerate() {
+                convert_key_to_value(cx, key, entry.handle_mut());
+                assert!(JS_DefineElement(
+                    *cx,
+                    array.handle(),
+                    index as u32,
+                    entry.handle(),
+                    JSPROP_ENUMERATE as u32,
+                ));
+            }
+            rval.set(ObjectValue(array.get()));
+        },
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#convert-a-value-to-a-key-range>
+pub fn convert_value_to_key_range(
+    cx: SafeJSContext,
+    input: HandleValue,
+    null_disallowed: bool,
+) -> Fallible<IndexedDBKeyRange> {
+    if let Ok(range) = root_from_handlevalue::<IDBKeyRange>(input, *cx) {
+        return Ok(range.inner().clone());
+    }
+    if input.is_undefined() || input.is_null() {
+        if null_disallowed {
+            return Err(Error::Data);
+        }
+        return Ok(IndexedDBKeyRange::unbounded());
+    }
+    Ok(IndexedDBKeyRange::only(convert_value_to_valid_key(
+        cx, input,
+    )?))
+}
+
+/// <https://w3c.github.io/IndexedDB/#valid-key-path>
+pub fn is_valid_key_path(key_path: &KeyPath) -> bool {
+    fn is_valid_identifier(identifier: &str) -> bool {
+        let mut chars = identifier.chars();
+        match chars.next() {
+            Some(first) if first.is_alphabetic() || first == '$' || first == '_' => {},
+            _ => return false,
+        }
+        chars.all(|c| c.is_alphanumeric() || c == '$' || c == '_')
+    }
+    fn is_valid_string(key_path: &str) -> bool {
+        key_path.is_empty() || key_path.split('.').all(is_valid_identifier)
+    }
+    match *key_path {
+        KeyPath::String(ref key_path) => is_valid_string(key_path),
+        KeyPath::Sequence(ref key_paths) => {
+            !key_paths.is_empty() && key_paths.iter().all(|key_path| is_valid_string(key_path))
+        },
+    }
+}
+
+impl From<&StringOrStringSequence> for KeyPath {
+    fn from(key_path: &StringOrStringSequence) -> KeyPath {
+        match *key_path {
+            StringOrStringSequence::String(ref key_path) => KeyPath::String(key_path.to_string()),
+            StringOrStringSequence::StringSequence(ref key_paths) => {
+                KeyPath::Sequence(key_paths.iter().map(|path| path.to_string()).collect())
+            },
+        }
+    }
+}
+
+/// The value of the `keyPath` attribute of an object store or an index.
+#[allow(unsafe_code)]
+pub fn key_path_to_value(cx: SafeJSContext, key_path: &KeyPath, mut rval: MutableHandleValue) {
+    match *key_path {
+        KeyPath::String(ref key_path) => unsafe {
+            DOMString::from(key_path.clone()).to_jsval(*cx, rval);
+        },
+        KeyPath::Sequence(ref key_paths) => unsafe {
+            let key_paths: Vec<DOMString> =
+                key_paths.iter().cloned().map(DOMString::from).collect();
+            key_paths.to_jsval(*cx, rval);
+        },
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#evaluate-a-key-path-on-a-value>
+///
+/// Returns `Ok(false)` if the value has nothing at `key_path`.
+#[allow(unsafe_code)]
+fn evaluate_key_path(
+    cx: SafeJSContext,
+    value: HandleValue,
+    key_path: &str,
+    mut rval: MutableHandleValue,
+) -> Fallible<bool> {
+    rooted!(in(*cx) let mut current = value.get());
+    for identifier in key_path
+        .split('.')
+        .filter(|identifier| !identifier.is_empty())
+    {
+        if identifier == "length" && current.is_string() {
+            let string = unsafe { jsstring_to_str(*cx, current.to_string()) };
+            current.set(DoubleValue(string.encode_utf16().count() as f64));
+            continue;
+        }
+        if !current.is_object() {
+            return Ok(false);
+        }
+        rooted!(in(*cx) let object = current.to_object());
+        let name = CString::new(identifier).unwrap();
+        let mut found = false;
+        unsafe {
+            if !JS_HasOwnProperty(*cx, object.handle(), name.as_ptr(), &mut found) {
+                return Err(Error::JSFailed);
+            }
+            if !found {
+                return Ok(false);
+            }
+            if !JS_GetProperty(*cx, object.handle(), name.as_ptr(), current.handle_mut()) {
+                return Err(Error::JSFailed);
+            }
+        }
+    }
+    rval.set(current.get());
+    Ok(true)
+}
+
+/// What extracting a key from a value gave.
+pub enum ExtractedKey {
+    Key(IndexedDBKey),
+    /// The value at the key path is not a valid key.
+    Invalid,
+    /// The value has nothing at the key path.
+    Failure,
+}
+
+/// <https://w3c.github.io/IndexedDB/#extract-a-key-from-a-value-using-a-key-path>
+pub fn extract_key(
+    cx: SafeJSContext,
+    value: HandleValue,
+    key_path: &KeyPath,
+) -> Fallible<ExtractedKey> {
+    rooted!(in(*cx) let mut result = UndefinedValue());
+    match *key_path {
+        KeyPath::String(ref key_path) => {
+            if !evaluate_key_path(cx, value, key_path, result.handle_mut())? {
+                return Ok(ExtractedKey::Failure);
+            }
+            Ok(convert_value_to_key(cx, result.handle(), &mut vec![])?
+                .map_or(ExtractedKey::Invalid, ExtractedKey::Key))
+        },
+        KeyPath::Sequence(ref key_paths) => {
+            let mut keys = Vec::with_capacity(key_paths.len());
+            for key_path in key_paths {
+                if !evaluate_key_path(cx, value, key_path, result.handle_mut())? {
+                    return Ok(ExtractedKey::Failure);
+                }
+                match convert_value_to_key(cx, result.handle(), &mut vec![])? {
+                    Some(key) => keys.push(key),
+                    None => return Ok(ExtractedKey::Invalid),
+                }
+            }
+            Ok(ExtractedKey::Key(IndexedDBKey::Array(keys)))
+        },
+    }
+}
+
+/// The keys a record with `value` has in an index with `key_path`, none if the value has no
+/// valid key there, and each valid distinct key of an array for a multi-entry index.
+/// <https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store>
+#[allow(unsafe_code)]
+pub fn index_keys(
+    cx: SafeJSContext,
+    value: HandleValue,
+    key_path: &KeyPath,
+    multi_entry: bool,
+) -> Fallible<Vec<IndexedDBKey>> {
+    let key_path = match *key_path {
+        KeyPath::String(ref key_path) if multi_entry => key_path,
+        _ => {
+            return Ok(match extract_key(cx, value, key_path)? {
+                ExtractedKey::Key(key) => vec![key],
+                ExtractedKey::Invalid | ExtractedKey::Failure => vec![],
+            });
+        },
+    };
+
+    // <https://w3c.github.io/IndexedDB/#convert-a-value-to-a-multientry-key>
+    rooted!(in(*cx) let mut result = UndefinedValue());
+    if !evaluate_key_path(cx, value, key_path, result.handle_mut())? {
+        return Ok(vec![]);
+    }
+    let mut is_array = false;
+    unsafe {
+        if !IsArrayObject(*cx, result.handle(), &mut is_array) {
+            return Err(Error::JSFailed);
+        }
+    }
+    if !is_array {
+        return Ok(convert_value_to_key(cx, result.handle(), &mut vec![])?
+            .into_iter()
+            .collect());
+    }
+
+    rooted!(in(*cx) let array = result.to_object());
+    let mut length = 0;
+    let mut keys: Vec<IndexedDBKey> = vec![];
+    rooted!(in(*cx) let mut entry = UndefinedValue());
+    unsafe {
+        if !GetArrayLength(*cx, array.handle(), &mut length) {
+            return Err(Error::JSFailed);
+        }
+        for index in 0..length {
+            if !JS_GetElement(*cx, array.handle(), index, entry.handle_mut()) {
+                return Err(Error::JSFailed);
+            }
+            if let Some(key) = convert_value_to_key(cx, entry.handle(), &mut vec![array.get()])? {
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+    }
+    Ok(keys)
+}
+
+/// <https://w3c.github.io/IndexedDB/#check-that-a-key-could-be-injected-into-a-value>
+#[allow(unsafe_code)]
+pub fn could_inject_key(cx: SafeJSContext, value: HandleValue, key_path: &str) -> Fallible<bool> {
+    let mut identifiers: Vec<&str> = key_path.split('.').collect();
+    identifiers.pop();
+    rooted!(in(*cx) let mut current = value.get());
+    for identifier in identifiers {
+        if !current.is_object() {
+            return Ok(false);
+        }
+        rooted!(in(*cx) let object = current.to_object());
+        let name = CString::new(identifier).unwrap();
+        let mut found = false;
+        unsafe {
+            if !JS_HasOwnProperty(*cx, object.handle(), name.as_ptr(), &mut found) {
+                return Err(Error::JSFailed);
+            }
+            if !found {
+                return Ok(true);
+            }
+            if !JS_GetProperty(*cx, object.handle(), name.as_ptr(), current.handle_mut()) {
+                return Err(Error::JSFailed);
+            }
+        }
+    }
+    Ok(current.is_object())
+}
+
+/// <https://w3c.github.io/IndexedDB/#inject-a-key-into-a-value-using-a-key-path>
+///
+/// Values of object stores with a key generator and an inline key are stored without the
+/// generated key, which is put back into them when they are read.
+#[allow(unsafe_code)]
+pub fn inject_key(
+    cx: SafeJSContext,
+    value: HandleValue,
+    key: &IndexedDBKey,
+    key_path: &str,
+) -> Fallible<()> {
+    if !could_inject_key(cx, value, key_path)? {
+        return Ok(());
+    }
+    let mut identifiers: Vec<&str> = key_path.split('.').collect();
+    let last = CString::new(identifiers.pop().unwrap()).unwrap();
+    rooted!(in(*cx) let mut object = value.to_object());
+    rooted!(in(*cx) let mut property = UndefinedValue());
+    for identifier in identifiers {
+        let name = CString::new(identifier).unwrap();
+        let mut found = false;
+        unsafe {
+            if !JS_HasOwnProperty(*cx, object.handle(), name.as_ptr(), &mut found) {
+                return Err(Error::JSFailed);
+            }
+            if found {
+                if !JS_GetProperty(*cx, object.handle(), name.as_ptr(), property.handle_mut()) {
+                    return Err(Error::JSFailed);
+                }
+            } else {
+                property.set(ObjectValue(JS_NewPlainObject(*cx)));
+                if !JS_DefineProperty(
+                    *cx,
+                    object.handle(),
+                    name.as_ptr(),
+                    property.handle(),
+                    JSPROP_ENUMERATE as u32,
+                ) {
+                    return Err(Error::JSFailed);
+                }
+            }
+        }
+        object.set(property.to_object());
+    }
+    convert_key_to_value(cx, key, property.handle_mut());
+    unsafe {
+        if !JS_DefineProperty(
+            *cx,
+            object.handle(),
+            last.as_ptr(),
+            property.handle(),
+            JSPROP_ENUMERATE as u32,
+        ) {
+            return Err(Error::JSFailed);
+        }
+    }
+    Ok(())
+}
+
+/// Serialize a value to store it in a record, which can't hold blobs or message ports since
+/// those live only as long as the global that made them.
+pub fn serialize_value(cx: SafeJSContext, value: HandleValue) -> Fallible<Vec<u8>> {
+    let data = structuredclone::write(cx, value, None)?;
+    if data.blobs.is_some() || data.ports.is_some() {
+        return Err(Error::DataClone);
+    }
+    Ok(data.serialized)
+}
+
+/// Deserialize a serialized value in the realm of `global`.
+pub fn deserialize_value(
+    global: &GlobalScope,
+    serialized: Vec<u8>,
+    rval: MutableHandleValue,
+) -> Result<(), ()> {
+    let data = StructuredSerializedData {
+        serialized,
+        blobs: None,
+        ports: None,
+        offscreen_canvases: None,
+        image_bitmaps: None,
+    };
+    structuredclone::read(global, data, rval).map(|_| ())
+}
+
+/// Deserialize the value of a record read from an object store, putting its primary key back
+/// in at `key_path` if the store generated it.
+pub fn record_value(
+    cx: SafeJSContext,
+    global: &GlobalScope,
+    record: IndexedDBRecord,
+    key_path: Option<&str>,
+    mut rval: MutableHandleValue,
+) -> Result<(), ()> {
+    let serialized = match record.value {
+        Some(serialized) => serialized,
+        None => {
+            rval.set(UndefinedValue());
+            return Ok(());
+        },
+    };
+    rooted!(in(*cx) let mut value = UndefinedValue());
+    deserialize_value(global, serialized, value.handle_mut())?;
+    if let Some(key_path) = key_path {
+        inject_key(cx, value.handle(), &record.primary_key, key_path).map_err(|_| ())?;
+    }
+    rval.set(value.get());
+    Ok(())
+}
+
+pub fn backend_error_to_dom_exception(
+    global: &GlobalScope,
+    error: &BackendError,
+) -> DomRoot<DOMException> {
+    let name = match *error {
+        BackendError::Constraint => DOMErrorName::ConstraintError,
+        BackendError::NotFound => DOMErrorName::NotFoundError,
+        BackendError::Data => DOMErrorName::DataError,
+        BackendError::Version => DOMErrorName::VersionError,
+        BackendError::Abort => DOMErrorName::AbortError,
+        BackendError::QuotaExceeded => DOMErrorName::QuotaExceededError,
+        BackendError::Unknown(ref message) => {
+            warn!("IndexedDB backend error: {}", message);
+            DOMErrorName::UnknownError
+        },
+    };
+    DOMException::new(global, name)
+}
@@ -0,0 +1,698 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Isolated worlds: JavaScript realms that run alongside the realm of a page
+//! and share its DOM, but not its global scope, its built-in objects or the
+//! prototypes of its DOM interfaces.
+//!
+//! Each isolated world has a global object of its own, in a compartment of its
+//! own. That global object is another reflector of the page's `Window`, created
+//! by `Window_Binding::WrapIsolatedWorld`: the members of `Window` are defined on
+//! it by the world's own bindings, and its interface objects and prototypes are
+//! created in the world, so that nothing the page does to its globals or its
+//! prototypes reaches the scripts of the world. `window`, `self` and the page's
+//! `WindowProxy` are the world's global object in the world.
+//!
+//! The DOM objects of the page reach the world as views, see
+//! [`maybe_wrap_object_for_isolated_world`]: proxies whose prototype is the one
+//! of their interface in the world and whose own properties are kept in an
+//! expando object of the world, so that the expandos of the page and those of
+//! each world are invisible to one another. The members of the DOM interfaces
+//! unwrap a view to the DOM object it is of. The indexed properties of the DOM
+//! objects that have them are read from the object, their named properties are
+//! not available through views.
+//!
+//! What the world can reach of the page's JavaScript is limited to:
+//!
+//! - the values that DOM APIs hand over as `any` or `object`, such as
+//!   `CustomEvent.detail`, `MessageEvent.data` or `History.state`, which are
+//!   cross-compartment wrappers of objects of the page and must be treated as
+//!   untrusted by the scripts of the world;
+//! - the other windows, such as the ones of frames or `opener`, which are
+//!   cross-compartment wrappers too.
+
+use std::cell::Cell;
+use std::ptr;
+
+use js::glue::{
+    CreateWrapperProxyHandler, GetProxyPrivate, GetProxyReservedSlot, IsWrapper,
+    JS_GetReservedSlot, ProxyTraps, SetProxyReservedSlot, UnwrapObjectStatic, WrapperNew,
+};
+use js::jsapi::{
+    Compile1, CurrentGlobalOrNull, GetStaticPrototype, Handle as RawHandle,
+    HandleId as RawHandleId, HandleObject as RawHandleObject, HandleValue as RawHandleValue,
+    IsWindowProxy, JSAutoRealm, JSContext, JSObject, JS_DefinePropertyById,
+    JS_ForwardGetPropertyTo, JS_ForwardSetPropertyTo, JS_GetOwnPropertyDescriptorById,
+    JS_HasOwnPropertyById, JS_HasPropertyById, JS_IsGlobalObject, JS_SetImmutablePrototype,
+    JS_SetPrototype, JS_SetReservedSlot, MutableHandle as RawMutableHandle,
+    MutableHandleIdVector as RawMutableHandleIdVector,
+    MutableHandleObject as RawMutableHandleObject, MutableHandleValue as RawMutableHandleValue,
+    NewWeakMapObject, ObjectOpResult, PropertyDescriptor, JSCLASS_RESERVED_SLOTS_SHIFT,
+    JSITER_HIDDEN, JSITER_OWNONLY, JSITER_SYMBOLS, JSPROP_ENUMERATE, JSPROP_PERMANENT,
+    JSPROP_READONLY,
+};
+use js::jsval::{ObjectValue, UndefinedValue};
+use js::rust::wrappers::{
+    AppendToIdVector, GetPropertyKeys, GetWeakMapEntry, JS_CopyOwnPropertiesAndPrivateFields,
+    JS_DefineProperty, JS_ExecuteScript, JS_NewObjectWithGivenProto, JS_WrapObject,
+    SetWeakMapEntry,
+};
+use js::rust::{
+    get_object_class, transform_str_to_source_text, CompileOptionsWrapper, Handle, HandleObject,
+    IdVector, MutableHandle, MutableHandleObject, MutableHandleValue,
+};
+use js::JSCLASS_RESERVED_SLOTS_MASK;
+
+use crate::dom::bindings::codegen::Bindings::WindowBinding::Window_Binding;
+use crate::dom::bindings::codegen::RegisterBindings::GetProtoObjectForId;
+use crate::dom::bindings::conversions::{
+    get_dom_class, is_dom_proxy, private_from_object, ToJSValConvertible,
+};
+use crate::dom::bindings::error::report_pending_exception;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::proxyhandler::{
+    is_extensible, prevent_extensions, set_property_descriptor,
+};
+use crate::dom::bindings::reflector::DomObject;
+use crate::dom::bindings::settings_stack::AutoEntryScript;
+use crate::dom::bindings::utils::{
+    delete_property_by_id, get_array_index_from_id, DOMClass, DOM_PROTO_UNFORGEABLE_HOLDER_SLOT,
+};
+use crate::dom::extensionbrowser::ExtensionBrowser;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::window::Window;
+use crate::realms::{AlreadyInRealm, InRealm};
+use crate::script_runtime::JSContext as SafeJSContext;
+
+/// The reserved slot of an isolated world's global object that holds a wrapper of
+/// the global object of the page, which must live as long as the world does since
+/// they share the `Window`. Slot 0 holds the `Window` itself.
+const PAGE_GLOBAL_SLOT: u32 = 1;
+
+/// The reserved slot of an isolated world's global object that holds the weak map
+/// from the wrappers of the DOM objects of the page to their views.
+const VIEWS_SLOT: u32 = 2;
+
+/// The reserved slot of a view that holds its expando object.
+const VIEW_EXPANDO_SLOT: u32 = 0;
+
+/// The names under which the extension APIs are available in the isolated world of
+/// an extension.
+const EXTENSION_API_NAMES: &[&[u8]] = &[b"browser\0", b"chrome\0"];
+
+thread_local! {
+    /// The proxy handler of the views of this script thread.
+    static VIEW_HANDLER: Cell<*const libc::c_void> = Cell::new(ptr::null());
+}
+
+/// Create the global object of a new isolated world of `window`.
+#[allow(unsafe_code)]
+pub fn create_isolated_world_global(window: &Window) -> *mut JSObject {
+    let cx = GlobalScope::get_cx();
+    let page_global = window.reflector().get_jsobject();
+    unsafe {
+        rooted!(in(*cx) let global = Window_Binding::WrapIsolatedWorld(cx, window, page_global));
+        let _ac = JSAutoRealm::new(*cx, global.get());
+
+        rooted!(in(*cx) let mut page_global = page_global.get());
+        assert!(JS_WrapObject(*cx, page_global.handle_mut()));
+        JS_SetReservedSlot(
+            global.get(),
+            PAGE_GLOBAL_SLOT,
+            &ObjectValue(page_global.get()),
+        );
+        rooted!(in(*cx) let views = NewWeakMapObject(*cx));
+        assert!(!views.is_null());
+        JS_SetReservedSlot(global.get(), VIEWS_SLOT, &ObjectValue(views.get()));
+
+        global.get()
+    }
+}
+
+/// Make the extension APIs available to the scripts running in the isolated world
+/// of an extension, whose global object is `global`, as `browser` and as `chrome`.
+#[allow(unsafe_code)]
+pub fn expose_extension_api(global: *mut JSObject, browser: &ExtensionBrowser) {
+    let cx = GlobalScope::get_cx();
+    unsafe {
+        rooted!(in(*cx) let global = global);
+        let _ac = JSAutoRealm::new(*cx, global.get());
+        rooted!(in(*cx) let mut browser_value = UndefinedValue());
+        browser
+            .reflector()
+            .to_jsval(*cx, browser_value.handle_mut());
+        for name in EXTENSION_API_NAMES {
+            assert!(JS_DefineProperty(
+                *cx,
+                global.handle(),
+                name.as_ptr() as *const libc::c_char,
+                browser_value.handle(),
+                (JSPROP_ENUMERATE | JSPROP_READONLY | JSPROP_PERMANENT) as u32,
+            ));
+        }
+    }
+}
+
+/// Whether `global` is the global object of an isolated world.
+#[allow(unsafe_code)]
+pub fn is_isolated_world_global(global: *mut JSObject) -> bool {
+    !global.is_null() &&
+        unsafe { get_object_class(global) } ==
+            &Window_Binding::IsolatedWorldClass.base as *const _
+}
+
+/// If `global` is the global object of an isolated world, return the global
+/// object of the page it belongs to. Everything that needs a `GlobalScope`
+/// for code running in an isolated world uses the one of the page.
+#[allow(unsafe_code)]
+pub unsafe fn page_global_for_isolated_world(global: *mut JSObject) -> Option<*mut JSObject> {
+    if !is_isolated_world_global(global) {
+        return None;
+    }
+    let window = &*(private_from_object(global) as *const Window);
+    Some(window.reflector().get_jsobject().get())
+}
+
+/// Evaluate `code` as a classic script in the isolated world whose global
+/// object is `global`. Uncaught exceptions are logged but, unlike those of
+/// the page's own scripts, not reported to the page.
+#[allow(unsafe_code)]
+pub fn evaluate_in_isolated_world(
+    window: &Window,
+    global: *mut JSObject,
+    code: &str,
+    rval: MutableHandleValue,
+) -> bool {
+    let cx = GlobalScope::get_cx();
+    let _aes = AutoEntryScript::new(window.upcast());
+    unsafe {
+        rooted!(in(*cx) let global = global);
+        let _ac = JSAutoRealm::new(*cx, global.get());
+        let options = CompileOptionsWrapper::new(*cx, "", 1);
+        rooted!(in(*cx) let script = Compile1(
+            *cx,
+            options.ptr,
+            &mut transform_str_to_source_text(code),
+        ));
+        let result = !script.is_null() && JS_ExecuteScript(*cx, script.handle(), rval);
+        if !result {
+            report_pending_exception(
+                *cx,
+                false,
+                InRealm::Already(&AlreadyInRealm::assert_for_cx(cx)),
+            );
+        }
+        result
+    }
+}
+
+/// If the current realm is the one of an isolated world and `obj` a wrapper of a
+/// DOM object of the page, replace it with the view of that object in the world,
+/// creating it if needed. A wrapper of the page's window is replaced with the
+/// world's global object. Anything else is left as it is.
+#[allow(unsafe_code)]
+pub unsafe fn maybe_wrap_object_for_isolated_world(
+    cx: *mut JSContext,
+    mut obj: MutableHandleObject,
+) {
+    rooted!(in(cx) let world = CurrentGlobalOrNull(cx));
+    if !is_isolated_world_global(world.get()) || !IsWrapper(obj.get()) {
+        return;
+    }
+    let target = UnwrapObjectStatic(obj.get());
+    if target.is_null() {
+        return;
+    }
+
+    let page_global = page_global_for_isolated_world(world.get()).unwrap();
+    if is_window(target, page_global) {
+        obj.set(world.get());
+        return;
+    }
+    // Other windows are not given views: the members of `Window` are defined on
+    // the window itself rather than on its prototype.
+    if JS_IsGlobalObject(target) {
+        return;
+    }
+    let dom_class = match get_dom_class(target) {
+        Ok(dom_class) => dom_class,
+        Err(()) => return,
+    };
+
+    let mut slot = UndefinedValue();
+    JS_GetReservedSlot(world.get(), VIEWS_SLOT, &mut slot);
+    rooted!(in(cx) let views = slot.to_object());
+    rooted!(in(cx) let key = ObjectValue(obj.get()));
+    rooted!(in(cx) let mut view = UndefinedValue());
+    assert!(GetWeakMapEntry(
+        cx,
+        views.handle(),
+        key.handle(),
+        view.handle_mut()
+    ));
+    if view.is_object() {
+        obj.set(view.to_object());
+        return;
+    }
+
+    rooted!(in(cx) let mut new_view = ptr::null_mut::<JSObject>());
+    if !create_view(
+        cx,
+        world.handle(),
+        obj.handle(),
+        dom_class,
+        new_view.handle_mut(),
+    ) {
+        return;
+    }
+    view.set(ObjectValue(new_view.get()));
+    assert!(SetWeakMapEntry(
+        cx,
+        views.handle(),
+        key.handle(),
+        view.handle()
+    ));
+    obj.set(new_view.get());
+}
+
+/// Like [`maybe_wrap_object_for_isolated_world`], for a value.
+#[allow(unsafe_code)]
+pub unsafe fn maybe_wrap_value_for_isolated_world(
+    cx: *mut JSContext,
+    mut rval: MutableHandleValue,
+) {
+    if !rval.get().is_object() {
+        return;
+    }
+    rooted!(in(cx) let mut obj = rval.get().to_object());
+    maybe_wrap_object_for_isolated_world(cx, obj.handle_mut());
+    rval.set(ObjectValue(obj.get()));
+}
+
+/// Whether `obj` is the global object whose global object is `global`, or its
+/// `WindowProxy`.
+#[allow(unsafe_code)]
+unsafe fn is_window(obj: *mut JSObject, global: *mut JSObject) -> bool {
+    if obj == global {
+        return true;
+    }
+    if !IsWindowProxy(obj) {
+        return false;
+    }
+    let mut slot = UndefinedValue();
+    GetProxyPrivate(obj, &mut slot);
+    slot.is_object() && slot.to_object() == global
+}
+
+/// Create the view of the DOM object that `wrapper` wraps in the isolated world whose
+/// global object is `world`. Returns false if the world has no prototype object for
+/// the interfaces of the object.
+#[allow(unsafe_code)]
+unsafe fn create_view(
+    cx: *mut JSContext,
+    world: HandleObject,
+    wrapper: HandleObject,
+    dom_class: &'static DOMClass,
+    mut rval: MutableHandleObject,
+) -> bool {
+    // The prototype of the most derived interface of the object that has one.
+    rooted!(in(cx) let mut proto = ptr::null_mut::<JSObject>());
+    let chain = &dom_class.interface_chain[..=dom_class.depth as usize];
+    for id in chain.iter().rev() {
+        GetProtoObjectForId(*id, SafeJSContext::from_ptr(cx), world, proto.handle_mut());
+        if !proto.is_null() {
+            break;
+        }
+    }
+    if proto.is_null() {
+        return false;
+    }
+
+    rooted!(in(cx) let expando = JS_NewObjectWithGivenProto(cx, ptr::null(), proto.handle()));
+    assert!(!expando.is_null());
+    // The unforgeable members are own properties of the objects that implement
+    // them, see `CopyLegacyUnforgeablePropertiesToInstance`.
+    let reserved_slots = ((*get_object_class(proto.get())).flags >> JSCLASS_RESERVED_SLOTS_SHIFT) &
+        JSCLASS_RESERVED_SLOTS_MASK;
+    if reserved_slots > DOM_PROTO_UNFORGEABLE_HOLDER_SLOT {
+        let mut slot = UndefinedValue();
+        JS_GetReservedSlot(proto.get(), DOM_PROTO_UNFORGEABLE_HOLDER_SLOT, &mut slot);
+        if slot.is_object() {
+            rooted!(in(cx) let unforgeable_holder = slot.to_object());
+            assert!(JS_CopyOwnPropertiesAndPrivateFields(
+                cx,
+                expando.handle(),
+                unforgeable_holder.handle()
+            ));
+        }
+    }
+
+    let handler = VIEW_HANDLER.with(|handler| {
+        if handler.get().is_null() {
+            handler.set(CreateWrapperProxyHandler(&VIEW_TRAPS));
+        }
+        handler.get()
+    });
+    rval.set(WrapperNew(cx, wrapper.into(), handler, ptr::null(), false));
+    assert!(!rval.is_null());
+    SetProxyReservedSlot(rval.get(), VIEW_EXPANDO_SLOT, &ObjectValue(expando.get()));
+    true
+}
+
+// The proxy traps of the views. A view is a wrapper of the wrapper of a DOM
+// object of the page, so that the DOM object is what it unwraps to, but all of
+// its traps but those of the indexed properties go to its expando object.
+
+#[allow(unsafe_code)]
+unsafe fn view_target(proxy: RawHandleObject) -> *mut JSObject {
+    let mut slot = UndefinedValue();
+    GetProxyPrivate(proxy.get(), &mut slot);
+    slot.to_object()
+}
+
+#[allow(unsafe_code)]
+unsafe fn view_expando(proxy: RawHandleObject) -> *mut JSObject {
+    let mut slot = UndefinedValue();
+    GetProxyReservedSlot(proxy.get(), VIEW_EXPANDO_SLOT, &mut slot);
+    slot.to_object()
+}
+
+/// Whether `id` is an index and the view `proxy` is of a DOM object with indexed
+/// properties, which are read from the DOM object.
+#[allow(unsafe_code)]
+unsafe fn is_indexed_property(cx: *mut JSContext, proxy: RawHandleObject, id: RawHandleId) -> bool {
+    get_array_index_from_id(cx, Handle::from_raw(id)).is_some() &&
+        is_dom_proxy(UnwrapObjectStatic(view_target(proxy)))
+}
+
+/// Get the indexed property `id` of the DOM object that the view `proxy` is of, as
+/// seen from the world. Sets `found` to false if the object has no such property.
+#[allow(unsafe_code)]
+unsafe fn get_indexed_property(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    found: &mut bool,
+    mut vp: MutableHandleValue,
+) -> bool {
+    rooted!(in(cx) let target = view_target(proxy));
+    if !JS_HasOwnPropertyById(cx, target.handle().into(), id, found) {
+        return false;
+    }
+    if !*found {
+        return true;
+    }
+    rooted!(in(cx) let receiver = ObjectValue(target.get()));
+    if !JS_ForwardGetPropertyTo(
+        cx,
+        target.handle().into(),
+        id,
+        receiver.handle().into(),
+        vp.into(),
+    ) {
+        return false;
+    }
+    maybe_wrap_value_for_isolated_world(cx, vp);
+    true
+}
+
+#[allow(unsafe_code)]
+unsafe fn own_property_keys(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    flags: u32,
+    props: RawMutableHandleIdVector,
+) -> bool {
+    rooted!(in(cx) let target = view_target(proxy));
+    if is_dom_proxy(UnwrapObjectStatic(target.get())) {
+        let mut ids = IdVector::new(cx);
+        if !GetPropertyKeys(cx, target.handle(), JSITER_OWNONLY, ids.handle_mut()) {
+            return false;
+        }
+        for id in ids.iter() {
+            rooted!(in(cx) let id = *id);
+            if get_array_index_from_id(cx, id.handle()).is_some() {
+                AppendToIdVector(props, id.handle());
+            }
+        }
+    }
+    rooted!(in(cx) let expando = view_expando(proxy));
+    GetPropertyKeys(cx, expando.handle(), flags, props)
+}
+
+#[allow(unsafe_code, non_snake_case)]
+unsafe extern "C" fn getOwnPropertyDescriptor(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    desc: RawMutableHandle<PropertyDescriptor>,
+    is_none: *mut bool,
+) -> bool {
+    if is_indexed_property(cx, proxy, id) {
+        let mut found = false;
+        rooted!(in(cx) let mut value = UndefinedValue());
+        if !get_indexed_property(cx, proxy, id, &mut found, value.handle_mut()) {
+            return false;
+        }
+        if !found {
+            *is_none = true;
+            return true;
+        }
+        set_property_descriptor(
+            MutableHandle::from_raw(desc),
+            value.handle(),
+            (JSPROP_ENUMERATE | JSPROP_READONLY) as u32,
+            &mut *is_none,
+        );
+        return true;
+    }
+
+    rooted!(in(cx) let expando = view_expando(proxy));
+    JS_GetOwnPropertyDescriptorById(cx, expando.handle().into(), id, desc, is_none)
+}
+
+#[allow(unsafe_code, non_snake_case)]
+unsafe extern "C" fn defineProperty(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    desc: RawHandle<PropertyDescriptor>,
+    res: *mut ObjectOpResult,
+) -> bool {
+    rooted!(in(cx) let object = if is_indexed_property(cx, proxy, id) {
+        view_target(proxy)
+    } else {
+        view_expando(proxy)
+    });
+    JS_DefinePropertyById(cx, object.handle().into(), id, desc, res)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn own_keys(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    props: RawMutableHandleIdVector,
+) -> bool {
+    own_property_keys(
+        cx,
+        proxy,
+        JSITER_OWNONLY | JSITER_HIDDEN | JSITER_SYMBOLS,
+        props,
+    )
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn own_enumerable_keys(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    props: RawMutableHandleIdVector,
+) -> bool {
+    own_property_keys(cx, proxy, JSITER_OWNONLY, props)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn enumerate(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    props: RawMutableHandleIdVector,
+) -> bool {
+    // The enumerable properties of the view and of its prototype chain.
+    own_property_keys(cx, proxy, 0, props)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn delete(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    res: *mut ObjectOpResult,
+) -> bool {
+    rooted!(in(cx) let object = if is_indexed_property(cx, proxy, id) {
+        view_target(proxy)
+    } else {
+        view_expando(proxy)
+    });
+    delete_property_by_id(cx, object.handle(), Handle::from_raw(id), res)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn get_prototype_if_ordinary(
+    _: *mut JSContext,
+    proxy: RawHandleObject,
+    is_ordinary: *mut bool,
+    proto: RawMutableHandleObject,
+) -> bool {
+    *is_ordinary = true;
+    proto.set(GetStaticPrototype(view_expando(proxy)));
+    true
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn get_prototype(
+    _: *mut JSContext,
+    proxy: RawHandleObject,
+    proto: RawMutableHandleObject,
+) -> bool {
+    proto.set(GetStaticPrototype(view_expando(proxy)));
+    true
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn set_prototype(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    proto: RawHandleObject,
+    res: *mut ObjectOpResult,
+) -> bool {
+    rooted!(in(cx) let expando = view_expando(proxy));
+    if !JS_SetPrototype(cx, expando.handle().into(), proto) {
+        return false;
+    }
+    (*res).code_ = 0 /* OkCode */;
+    true
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn set_immutable_prototype(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    succeeded: *mut bool,
+) -> bool {
+    rooted!(in(cx) let expando = view_expando(proxy));
+    JS_SetImmutablePrototype(cx, expando.handle().into(), succeeded)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn has(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    bp: *mut bool,
+) -> bool {
+    if is_indexed_property(cx, proxy, id) {
+        rooted!(in(cx) let target = view_target(proxy));
+        if !JS_HasOwnPropertyById(cx, target.handle().into(), id, bp) {
+            return false;
+        }
+        if *bp {
+            return true;
+        }
+    }
+    rooted!(in(cx) let expando = view_expando(proxy));
+    JS_HasPropertyById(cx, expando.handle().into(), id, bp)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn has_own(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    bp: *mut bool,
+) -> bool {
+    rooted!(in(cx) let object = if is_indexed_property(cx, proxy, id) {
+        view_target(proxy)
+    } else {
+        view_expando(proxy)
+    });
+    JS_HasOwnPropertyById(cx, object.handle().into(), id, bp)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn get(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    receiver: RawHandleValue,
+    id: RawHandleId,
+    vp: RawMutableHandleValue,
+) -> bool {
+    if is_indexed_property(cx, proxy, id) {
+        let mut found = false;
+        if !get_indexed_property(cx, proxy, id, &mut found, MutableHandle::from_raw(vp)) {
+            return false;
+        }
+        if found {
+            return true;
+        }
+    }
+    rooted!(in(cx) let expando = view_expando(proxy));
+    JS_ForwardGetPropertyTo(cx, expando.handle().into(), id, receiver, vp)
+}
+
+#[allow(unsafe_code)]
+unsafe extern "C" fn set(
+    cx: *mut JSContext,
+    proxy: RawHandleObject,
+    id: RawHandleId,
+    v: RawHandleValue,
+    receiver: RawHandleValue,
+    res: *mut ObjectOpResult,
+) -> bool {
+    if is_indexed_property(cx, proxy, id) {
+        rooted!(in(cx) let target = view_target(proxy));
+        rooted!(in(cx) let receiver = ObjectValue(target.get()));
+        return JS_ForwardSetPropertyTo(
+            cx,
+            target.handle().into(),
+            id,
+            v,
+            receiver.handle().into(),
+            res,
+        );
+    }
+    rooted!(in(cx) let expando = view_expando(proxy));
+    JS_ForwardSetPropertyTo(cx, expando.handle().into(), id, v, receiver, res)
+}
+
+static VIEW_TRAPS: ProxyTraps = ProxyTraps {
+    enter: None,
+    getOwnPropertyDescriptor: Some(getOwnPropertyDescriptor),
+    defineProperty: Some(defineProperty),
+    ownPropertyKeys: Some(own_keys),
+    delete_: Some(delete),
+    enumerate: Some(enumerate),
+    getPrototypeIfOrdinary: Some(get_prototype_if_ordinary),
+    getPrototype: Some(get_prototype),
+    setPrototype: Some(set_prototype),
+    setImmutablePrototype: Some(set_immutable_prototype),
+    preventExtensions: Some(prevent_extensions),
+    isExtensible: Some(is_extensible),
+    has: Some(has),
+    get: Some(get),
+    set: Some(set),
+    call: None,
+    construct: None,
+    hasOwn: Some(has_own),
+    getOwnEnumerablePropertyKeys: Some(own_enumerable_keys),
+    nativeCall: None,
+    objectClassIs: None,
+    className: None,
+    fun_toString: None,
+    boxedValue_unbox: None,
+    defaultValue: None,
+    trace: None,
+    finalize: None,
+    objectMoved: None,
+    isCallable: None,
+    isConstructor: None,
+};
@@ -6,9 +6,13 @@ use std::marker::PhantomData;
 
 use script_layout_interface::wrapper_traits::LayoutDataTrait;
 use selectors::matching::QuirksMode;
+use servo_arc::Arc;
+use style::animation::AnimationSetKey;
 use style::dom::{TDocument, TNode};
+use style::properties::PropertyDeclarationBlock;
 use style::shared_lock::{
-    SharedRwLock as StyleSharedRwLock, SharedRwLockReadGuard as StyleSharedRwLockReadGuard,
+    Locked as StyleLocked, SharedRwLock as StyleSharedRwLock,
+    SharedRwLockReadGuard as StyleSharedRwLockReadGuard,
 };
 use style::stylist::Stylist;
 
@@ -75,6 +79,15 @@ impl<'ld, LayoutDataType: LayoutDataTrait> ServoLayoutDocument<'ld, LayoutDataTy
         self.document.style_shared_lock()
     }
 
+    /// The declarations of the effects of the web animations targeting the element or
+    /// pseudo-element `key`.
+    pub fn web_animation_declarations(
+        &self,
+        key: &AnimationSetKey,
+    ) -> Option<Arc<StyleLocked<PropertyDeclarationBlock>>> {
+        self.document.web_animation_declarations(key)
+    }
+
     pub fn shadow_roots(&self) -> Vec<ServoShadowRoot<LayoutDataType>> {
         unsafe {
             self.document
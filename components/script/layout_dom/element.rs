@@ -174,11 +174,7 @@ impl<'dom, LayoutDataType: LayoutDataTrait> style::dom::TElement
     }
 
     fn traversal_children(&self) -> LayoutIterator<Self::TraversalChildrenIterator> {
-        LayoutIterator(if let Some(shadow) = self.shadow_root() {
-            shadow.as_node().dom_children()
-        } else {
-            self.as_node().dom_children()
-        })
+        LayoutIterator(self.as_node().flat_tree_children())
     }
 
     fn is_html_element(&self) -> bool {
@@ -219,11 +215,29 @@ impl<'dom, LayoutDataType: LayoutDataTrait> style::dom::TElement
     ) -> Option<Arc<StyleLocked<PropertyDeclarationBlock>>> {
         let node = self.as_node();
         let document = node.owner_doc();
-        context.animations.get_animation_declarations(
-            &AnimationSetKey::new_for_non_pseudo(node.opaque()),
+        let key = AnimationSetKey::new_for_non_pseudo(node.opaque());
+        let css_animations = context.animations.get_animation_declarations(
+            &key,
             context.current_time_for_animations,
             document.style_shared_lock(),
-        )
+        );
+        let web_animations = document.web_animation_declarations(&key);
+
+        match (css_animations, web_animations) {
+            (Some(css_animations), Some(web_animations)) => {
+                // The effects of web animations apply on top of those of CSS animations.
+                let guard = document.style_shared_lock().read();
+                let mut block = css_animations.read_with(&guard).clone();
+                for (declaration, importance) in web_animations
+                    .read_with(&guard)
+                    .declaration_importance_iter()
+                {
+                    block.push(declaration.clone(), importance);
+                }
+                Some(Arc::new(document.style_shared_lock().wrap(block)))
+            },
+            (css_animations, web_animations) => css_animations.or(web_animations),
+        }
     }
 
     fn transition_rule(
@@ -770,6 +784,15 @@ impl<'dom, LayoutDataType: LayoutDataTrait> ThreadSafeLayoutElement<'dom>
     fn is_body_element_of_html_element_root(&self) -> bool {
         self.element.is_html_document_body_element()
     }
+
+    fn is_relevant_to_the_user(&self) -> bool {
+        unsafe {
+            self.element
+                .as_node()
+                .node
+                .get_flag(NodeFlags::IS_RELEVANT_TO_THE_USER)
+        }
+    }
 }
 
 /// This implementation of `::selectors::Element` is used for implementing lazy
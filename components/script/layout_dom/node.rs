@@ -27,7 +27,7 @@ use servo_arc::Arc;
 use servo_url::ServoUrl;
 use style;
 use style::context::SharedStyleContext;
-use style::dom::{NodeInfo, TElement, TNode, TShadowRoot};
+use style::dom::{DomChildren, NodeInfo, TElement, TNode, TShadowRoot};
 use style::properties::ComputedValues;
 use style::str::is_whitespace;
 
@@ -226,6 +226,13 @@ impl<'dom, LayoutDataType: LayoutDataTrait> LayoutNode<'dom>
         self.get_jsmanaged().take_style_and_opaque_layout_data()
     }
 
+    fn flat_tree_children(self) -> DomChildren<Self> {
+        match self.as_element().and_then(|element| element.shadow_root()) {
+            Some(shadow_root) => shadow_root.as_node().dom_children(),
+            None => self.dom_children(),
+        }
+    }
+
     fn is_connected(&self) -> bool {
         unsafe { self.node.get_flag(NodeFlags::IS_CONNECTED) }
     }
@@ -299,10 +306,7 @@ impl<'dom, LayoutDataType: LayoutDataTrait> ServoThreadSafeLayoutNode<'dom, Layo
     /// Get the first child of this node. Important: this is not safe for
     /// layout to call, so it should *never* be made public.
     unsafe fn dangerous_first_child(&self) -> Option<Self> {
-        self.get_jsmanaged()
-            .first_child_ref()
-            .map(ServoLayoutNode::from_layout_js)
-            .map(Self::new)
+        self.node.flat_tree_children().next().map(Self::new)
     }
 
     /// Get the next sibling of this node. Important: this is not safe for
@@ -358,11 +362,6 @@ impl<'dom, LayoutDataType: LayoutDataTrait> ThreadSafeLayoutNode<'dom>
     }
 
     fn children(&self) -> style::dom::LayoutIterator<Self::ChildrenIterator> {
-        if let Some(shadow) = self.node.as_element().and_then(|e| e.shadow_root()) {
-            return style::dom::LayoutIterator(ServoThreadSafeLayoutNodeChildrenIterator::new(
-                shadow.as_node().to_threadsafe(),
-            ));
-        }
         style::dom::LayoutIterator(ServoThreadSafeLayoutNodeChildrenIterator::new(*self))
     }
 
@@ -36,18 +36,28 @@ mod devtools;
 #[warn(deprecated)]
 pub mod document_loader;
 #[warn(deprecated)]
+mod drag_and_drop;
+#[warn(deprecated)]
+pub mod editing;
+#[warn(deprecated)]
 #[macro_use]
 mod dom;
 #[warn(deprecated)]
 mod canvas_state;
+#[warn(deprecated)]
+mod clock;
 mod euclidext;
 #[warn(deprecated)]
 pub mod fetch;
 #[warn(deprecated)]
 mod image_listener;
 #[warn(deprecated)]
+mod indexed_db;
+#[warn(deprecated)]
 mod init;
 #[warn(deprecated)]
+mod isolated_world;
+#[warn(deprecated)]
 mod layout_image;
 
 pub mod layout_dom;
@@ -58,6 +68,8 @@ mod microtask;
 #[warn(deprecated)]
 mod network_listener;
 #[warn(deprecated)]
+mod popups;
+#[warn(deprecated)]
 mod realms;
 #[warn(deprecated)]
 mod script_module;
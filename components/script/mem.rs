@@ -4,6 +4,7 @@
 
 //! Routines for handling measuring the memory usage of arbitrary DOM nodes.
 
+use std::alloc::Layout;
 use std::os::raw::c_void;
 
 use malloc_size_of::{MallocSizeOf, MallocSizeOfOps};
@@ -16,5 +17,9 @@ pub unsafe fn malloc_size_of_including_raw_self<T: MallocSizeOf>(
     ops: &mut MallocSizeOfOps,
     obj: *const c_void,
 ) -> usize {
-    ops.malloc_size_of(obj) + (*(obj as *const T)).size_of(ops)
+    // DOM objects are boxed on their own, so the size of their heap block follows from
+    // their type, which is much cheaper than asking the allocator about the pointer.
+    let self_size = servo_allocator::usable_size_for_layout(Layout::new::<T>())
+        .unwrap_or_else(|| ops.malloc_size_of(obj));
+    self_size + (*(obj as *const T)).size_of(ops)
 }
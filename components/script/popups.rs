@@ -0,0 +1,382 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The popups that a document shows over its content, which are drawn by the embedder:
+//! tooltips with the advisory information of hovered elements, the messages of
+//! interactive form validation, and the suggestions for text fields.
+
+use std::cell::Cell;
+
+use embedder_traits::{EmbedderMsg, Popup, PopupKind};
+use euclid::default::{Point2D, Rect, Size2D};
+use euclid::Length;
+use keyboard_types::Key;
+use webrender_api::units::DeviceIntRect;
+
+use crate::dom::bindings::cell::DomRefCell;
+use crate::dom::bindings::codegen::Bindings::DOMRectBinding::DOMRectMethods;
+use crate::dom::bindings::codegen::Bindings::ElementBinding::ElementMethods;
+use crate::dom::bindings::inheritance::Castable;
+use crate::dom::bindings::refcounted::Trusted;
+use crate::dom::bindings::root::{DomRoot, MutNullableDom};
+use crate::dom::bindings::str::DOMString;
+use crate::dom::document::Document;
+use crate::dom::element::Element;
+use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlelement::HTMLElement;
+use crate::dom::htmlinputelement::HTMLInputElement;
+use crate::dom::keyboardevent::KeyboardEvent;
+use crate::dom::node::{Node, ShadowIncluding};
+use crate::timers::{OneshotTimerCallback, OneshotTimerHandle};
+
+/// How long the pointer has to rest on an element for its tooltip to be shown.
+const TOOLTIP_DELAY_MS: u64 = 500;
+
+/// How long validation messages are shown for, unless the user interacts with the page.
+const VALIDATION_MESSAGE_DURATION_MS: u64 = 5000;
+
+/// Tooltips are anchored to an area of this height in CSS pixels under the pointer, so
+/// that they are shown below the pointer rather than under it.
+const POINTER_HEIGHT: f32 = 20.0;
+
+/// The popups of a document.
+///
+/// TODO: The anchors of popups of documents in iframes are not offset by the position of
+/// the iframe.
+#[derive(Default, JSTraceable, MallocSizeOf)]
+#[crown::unrooted_must_root_lint::must_root]
+pub(crate) struct Popups {
+    /// The nearest element with advisory information to the hovered element.
+    tooltip_element: MutNullableDom<HTMLElement>,
+    /// Where the pointer last moved over `tooltip_element`, in CSS pixels.
+    #[no_trace]
+    tooltip_point: Cell<Point2D<f32>>,
+    /// Fires once the pointer has rested on `tooltip_element`.
+    tooltip_timer: Cell<Option<OneshotTimerHandle>>,
+    tooltip_shown: Cell<bool>,
+    /// Whether the tooltip of `tooltip_element` was dismissed by the user clicking or typing,
+    /// in which case it is not shown again until the pointer moves to another element.
+    tooltip_dismissed: Cell<bool>,
+    /// Hides the validation message that is shown, if any.
+    validation_message_timer: Cell<Option<OneshotTimerHandle>>,
+    validation_message_shown: Cell<bool>,
+    /// The text field whose suggestions are shown.
+    suggestions_element: MutNullableDom<HTMLInputElement>,
+    /// The suggestions shown, and the one that was selected with the keyboard.
+    suggestions: DomRefCell<Vec<DOMString>>,
+    selected_suggestion: Cell<Option<usize>>,
+}
+
+impl Popups {
+    /// Update the tooltip for the pointer having moved to `point` over `target`.
+    /// <https://html.spec.whatwg.org/multipage/#the-title-attribute>
+    pub(crate) fn handle_mouse_move(
+        &self,
+        document: &Document,
+        target: &Element,
+        point: Point2D<f32>,
+    ) {
+        // The advisory information of an element is that of its nearest HTML ancestor with a
+        // title attribute.
+        let element = target
+            .upcast::<Node>()
+            .inclusive_ancestors(ShadowIncluding::No)
+            .filter_map(DomRoot::downcast::<HTMLElement>)
+            .find(|element| {
+                element
+                    .upcast::<Element>()
+                    .has_attribute(&local_name!("title"))
+            });
+
+        if self.tooltip_element.get() != element {
+            self.hide_tooltip(document);
+            self.tooltip_dismissed.set(false);
+            self.tooltip_element.set(element.as_deref());
+        } else if self.tooltip_shown.get() || self.tooltip_dismissed.get() {
+            // Tooltips stay put while the pointer moves over their element.
+            return;
+        }
+
+        // The pointer has to rest for the tooltip to be shown.
+        if let Some(handle) = self.tooltip_timer.take() {
+            document
+                .window()
+                .upcast::<GlobalScope>()
+                .unschedule_callback(handle);
+        }
+        if element.is_none() {
+            return;
+        }
+        self.tooltip_point.set(point);
+        let callback = OneshotTimerCallback::PopupTimeout(PopupTimeoutCallback {
+            document: Trusted::new(document),
+            kind: PopupKind::Tooltip,
+        });
+        let handle = document
+            .window()
+            .upcast::<GlobalScope>()
+            .schedule_callback(callback, Length::new(TOOLTIP_DELAY_MS));
+        self.tooltip_timer.set(Some(handle));
+    }
+
+    fn show_tooltip(&self, document: &Document) {
+        let element = match self.tooltip_element.get() {
+            Some(element) => element,
+            None => return,
+        };
+        // The title may have been removed or emptied while the pointer rested.
+        let text = element
+            .upcast::<Element>()
+            .get_string_attribute(&local_name!("title"));
+        if text.is_empty() || !element.upcast::<Node>().is_connected() {
+            return;
+        }
+        let point = self.tooltip_point.get();
+        let anchor = Rect::new(point, Size2D::new(1.0, POINTER_HEIGHT));
+        send_popup(document, PopupKind::Tooltip, text, anchor, None);
+        self.tooltip_shown.set(true);
+    }
+
+    fn hide_tooltip(&self, document: &Document) {
+        if let Some(handle) = self.tooltip_timer.take() {
+            document
+                .window()
+                .upcast::<GlobalScope>()
+                .unschedule_callback(handle);
+        }
+        if self.tooltip_shown.replace(false) {
+            document
+                .window()
+                .send_to_embedder(EmbedderMsg::HidePopup(PopupKind::Tooltip));
+        }
+    }
+
+    /// Show why `element` failed interactive validation, for a while.
+    pub(crate) fn show_validation_message(
+        &self,
+        document: &Document,
+        element: &Element,
+        message: DOMString,
+    ) {
+        self.hide_validation_message(document);
+        send_popup(
+            document,
+            PopupKind::ValidationMessage,
+            message,
+            client_rect(element),
+            None,
+        );
+        self.validation_message_shown.set(true);
+
+        let callback = OneshotTimerCallback::PopupTimeout(PopupTimeoutCallback {
+            document: Trusted::new(document),
+            kind: PopupKind::ValidationMessage,
+        });
+        let handle = document
+            .window()
+            .upcast::<GlobalScope>()
+            .schedule_callback(callback, Length::new(VALIDATION_MESSAGE_DURATION_MS));
+        self.validation_message_timer.set(Some(handle));
+    }
+
+    fn hide_validation_message(&self, document: &Document) {
+        if let Some(handle) = self.validation_message_timer.take() {
+            document
+                .window()
+                .upcast::<GlobalScope>()
+                .unschedule_callback(handle);
+        }
+        if self.validation_message_shown.replace(false) {
+            document
+                .window()
+                .send_to_embedder(EmbedderMsg::HidePopup(PopupKind::ValidationMessage));
+        }
+    }
+
+    /// Show the suggestions for the value of a focused text field, or hide them if there are
+    /// none.
+    /// <https://html.spec.whatwg.org/multipage/#the-list-attribute>
+    pub(crate) fn update_suggestions(&self, document: &Document, input: &HTMLInputElement) {
+        let suggestions = input.suggestions();
+        if suggestions.is_empty() {
+            return self.hide_suggestions(document);
+        }
+        self.suggestions_element.set(Some(input));
+        *self.suggestions.borrow_mut() = suggestions;
+        self.selected_suggestion.set(None);
+        self.send_suggestions(document);
+    }
+
+    fn send_suggestions(&self, document: &Document) {
+        let input = match self.suggestions_element.get() {
+            Some(input) => input,
+            None => return,
+        };
+        let text = self
+            .suggestions
+            .borrow()
+            .iter()
+            .map(|suggestion| &**suggestion)
+            .collect::<Vec<&str>>()
+            .join("\n");
+        send_popup(
+            document,
+            PopupKind::Suggestions,
+            DOMString::from(text),
+            client_rect(input.upcast()),
+            self.selected_suggestion.get(),
+        );
+    }
+
+    /// Hide the suggestions, because their text field lost focus or one was chosen.
+    pub(crate) fn hide_suggestions(&self, document: &Document) {
+        self.suggestions.borrow_mut().clear();
+        self.selected_suggestion.set(None);
+        if self.suggestions_element.take().is_some() {
+            document
+                .window()
+                .send_to_embedder(EmbedderMsg::HidePopup(PopupKind::Suggestions));
+        }
+    }
+
+    /// Navigate the suggestions of `input` with a key that was pressed in it, returning
+    /// whether the key was used for that.
+    ///
+    /// As in Firefox, the suggestions are shown by pressing the down arrow key, and going
+    /// past the first or last suggestion selects none of them.
+    ///
+    /// TODO: Suggestions cannot be chosen with the mouse.
+    pub(crate) fn handle_suggestions_keydown(
+        &self,
+        document: &Document,
+        input: &HTMLInputElement,
+        event: &KeyboardEvent,
+    ) -> bool {
+        if self.suggestions_element.get().as_deref() != Some(input) {
+            if event.key() != Key::ArrowDown {
+                return false;
+            }
+            self.update_suggestions(document, input);
+            return self.suggestions_element.get().is_some();
+        }
+
+        let count = self.suggestions.borrow().len();
+        let selected = self.selected_suggestion.get();
+        let selected = match event.key() {
+            Key::ArrowDown => match selected {
+                None => Some(0),
+                Some(index) if index + 1 < count => Some(index + 1),
+                Some(_) => None,
+            },
+            Key::ArrowUp => match selected {
+                None => count.checked_sub(1),
+                Some(index) => index.checked_sub(1),
+            },
+            Key::Enter => {
+                // Without a selected suggestion, the key submits the form as usual.
+                let suggestion = match selected {
+                    Some(index) => self.suggestions.borrow()[index].clone(),
+                    None => return false,
+                };
+                self.hide_suggestions(document);
+                input.autofill(suggestion);
+                return true;
+            },
+            Key::Escape => {
+                self.hide_suggestions(document);
+                return true;
+            },
+            _ => return false,
+        };
+        self.selected_suggestion.set(selected);
+        self.send_suggestions(document);
+        true
+    }
+
+    /// Hide the popups because the user clicked, scrolled or typed. The tooltip of the hovered
+    /// element is not shown again until the pointer moves to another element.
+    pub(crate) fn dismiss(&self, document: &Document) {
+        self.hide_tooltip(document);
+        self.tooltip_dismissed.set(true);
+        self.hide_validation_message(document);
+    }
+
+    /// Hide the popups of a document that is going away.
+    pub(crate) fn clear(&self, document: &Document) {
+        self.hide_tooltip(document);
+        self.tooltip_element.set(None);
+        self.hide_validation_message(document);
+        self.hide_suggestions(document);
+    }
+}
+
+/// The border box of an element, in CSS pixels relative to the viewport.
+fn client_rect(element: &Element) -> Rect<f32> {
+    let rect = element.GetBoundingClientRect();
+    Rect::new(
+        Point2D::new(rect.X() as f32, rect.Y() as f32),
+        Size2D::new(rect.Width() as f32, rect.Height() as f32),
+    )
+}
+
+/// The border box of an element, in device pixels relative to the viewport, which is how
+/// the embedder anchors what it draws for the element, such as the picker of a form control.
+pub(crate) fn device_client_rect(element: &Element) -> DeviceIntRect {
+    device_rect(&element.upcast::<Node>().owner_doc(), client_rect(element))
+}
+
+/// An area given in CSS pixels relative to the viewport, in device pixels.
+fn device_rect(document: &Document, rect: Rect<f32>) -> DeviceIntRect {
+    let scale = document.window().device_pixel_ratio().get();
+    DeviceIntRect::from_untyped(&rect.scale(scale, scale).round_out().to_i32())
+}
+
+/// Send a popup to the embedder, anchored to an area given in CSS pixels.
+fn send_popup(
+    document: &Document,
+    kind: PopupKind,
+    text: DOMString,
+    anchor: Rect<f32>,
+    selected_line: Option<usize>,
+) {
+    document
+        .window()
+        .send_to_embedder(EmbedderMsg::ShowPopup(Popup {
+            kind,
+            text: text.into(),
+            anchor: device_rect(document, anchor),
+            selected_line,
+        }));
+}
+
+/// Shows a tooltip once the pointer has rested on its element, or hides a validation message
+/// once it has been shown for long enough.
+#[derive(JSTraceable, MallocSizeOf)]
+pub struct PopupTimeoutCallback {
+    #[ignore_malloc_size_of = "Because it is non-owning"]
+    document: Trusted<Document>,
+    #[ignore_malloc_size_of = "Defined in embedder_traits"]
+    #[no_trace]
+    kind: PopupKind,
+}
+
+impl PopupTimeoutCallback {
+    pub fn invoke(self) {
+        let document = self.document.root();
+        let popups = document.popups();
+        // The timer has fired, so there is nothing left to unschedule.
+        match self.kind {
+            PopupKind::Tooltip => {
+                popups.tooltip_timer.set(None);
+                popups.show_tooltip(&document);
+            },
+            PopupKind::ValidationMessage => {
+                popups.validation_message_timer.set(None);
+                popups.hide_validation_message(&document);
+            },
+            // Suggestions are shown until they are not needed anymore.
+            PopupKind::Suggestions => {},
+        }
+    }
+}
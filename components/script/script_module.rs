@@ -36,6 +36,7 @@ use js::rust::{
 use mime::Mime;
 use net_traits::request::{
     CredentialsMode, Destination, ParserMetadata, Referrer, RequestBuilder, RequestMode,
+    RequestPriority,
 };
 use net_traits::{
     CoreResourceMsg, FetchChannels, FetchMetadata, FetchResponseListener, IpcSend, Metadata,
@@ -1288,6 +1289,8 @@ pub struct ScriptFetchOptions {
     pub parser_metadata: ParserMetadata,
     #[no_trace]
     pub referrer_policy: Option<ReferrerPolicy>,
+    #[no_trace]
+    pub fetch_priority: RequestPriority,
 }
 
 impl ScriptFetchOptions {
@@ -1300,6 +1303,7 @@ impl ScriptFetchOptions {
             parser_metadata: ParserMetadata::NotParserInserted,
             credentials_mode: CredentialsMode::CredentialsSameOrigin,
             referrer_policy: None,
+            fetch_priority: RequestPriority::Auto,
         }
     }
 
@@ -1312,6 +1316,7 @@ impl ScriptFetchOptions {
             credentials_mode: self.credentials_mode,
             parser_metadata: self.parser_metadata,
             referrer_policy: self.referrer_policy,
+            fetch_priority: RequestPriority::Auto,
         }
     }
 }
@@ -1654,6 +1659,7 @@ fn fetch_single_module_script(
         .parser_metadata(options.parser_metadata)
         .integrity_metadata(options.integrity_metadata.clone())
         .credentials_mode(options.credentials_mode)
+        .priority(options.fetch_priority)
         .mode(mode);
 
     let context = Arc::new(Mutex::new(ModuleContext {
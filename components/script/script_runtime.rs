@@ -34,7 +34,7 @@ use js::jsapi::{
     JS_SetParallelParsingEnabled, JS_SetSecurityCallbacks, JobQueue, MimeType,
     PromiseRejectionHandlingState, PromiseUserInputEventHandlingState, SetDOMCallbacks,
     SetGCSliceCallback, SetJobQueue, SetPreserveWrapperCallbacks, SetProcessBuildIdOp,
-    SetPromiseRejectionTrackerCallback, StreamConsumer as JSStreamConsumer,
+    SetPromiseRejectionTrackerCallback, SetTimeResolutionUsec, StreamConsumer as JSStreamConsumer,
 };
 use js::jsval::UndefinedValue;
 use js::panic::wrap_panic;
@@ -442,6 +442,26 @@ pub unsafe fn new_child_runtime(
 }
 
 #[allow(unsafe_code)]
+/// Make the runtime of `cx` run JavaScript in the interpreter only, without the JITs or
+/// WebAssembly, for the realms of origins the embedder does not trust with them. The options
+/// are global to the runtime, so they stay off for as long as it lives.
+#[allow(unsafe_code)]
+pub fn disable_jit(cx: JSContext) {
+    unsafe {
+        for option in [
+            JSJitCompilerOption::JSJITCOMPILER_BASELINE_INTERPRETER_ENABLE,
+            JSJitCompilerOption::JSJITCOMPILER_BASELINE_ENABLE,
+            JSJitCompilerOption::JSJITCOMPILER_ION_ENABLE,
+            JSJitCompilerOption::JSJITCOMPILER_NATIVE_REGEXP_ENABLE,
+        ] {
+            JS_SetGlobalJitCompilerOption(*cx, option, 0);
+        }
+        let cx_opts = &mut *ContextOptionsRef(*cx);
+        cx_opts.set_asmJS_(false);
+        cx_opts.set_wasm_(false);
+    }
+}
+
 pub fn new_rt_and_cx(networking_task_source: Option<NetworkingTaskSource>) -> Runtime {
     unsafe { new_rt_and_cx_with_parent(None, networking_task_source) }
 }
@@ -528,6 +548,12 @@ unsafe fn new_rt_and_cx_with_parent(
 
     set_gc_zeal_options(cx);
 
+    // `Date` is only as precise as `performance.now()` while resisting fingerprinting.
+    if pref!(privacy.resist_fingerprinting.enabled) {
+        let precision = pref!(privacy.resist_fingerprinting.timer_precision).max(1);
+        SetTimeResolutionUsec((precision * 1000) as u32, false);
+    }
+
     // Enable or disable the JITs.
     let cx_opts = &mut *ContextOptionsRef(cx);
     JS_SetGlobalJitCompilerOption(
@@ -692,6 +718,12 @@ unsafe extern "C" fn get_size(obj: *mut JSObject) -> usize {
     }
 }
 
+/// The memory taken up by a DOM object, in bytes.
+#[allow(unsafe_code)]
+pub fn dom_object_size<T: DomObject>(object: &T) -> usize {
+    unsafe { get_size(object.reflector().get_jsobject().get()) }
+}
+
 #[allow(unsafe_code)]
 pub fn get_reports(cx: *mut RawJSContext, path_seg: String) -> Vec<Report> {
     let mut reports = vec![];
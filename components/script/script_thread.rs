@@ -28,7 +28,7 @@ use std::result::Result;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use std::{ptr, thread};
+use std::{process, ptr, thread};
 
 use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
@@ -38,7 +38,10 @@ use devtools_traits::{
     CSSError, DevtoolScriptControlMsg, DevtoolsPageInfo, NavigationState,
     ScriptToDevtoolsControlMsg, WorkerId,
 };
-use embedder_traits::EmbedderMsg;
+use embedder_traits::{
+    EmbedderMsg, NetworkChange, NetworkStatus, PermissionName, PermissionState, PipelineUsage,
+    ScriptPolicies, UserAgentOverride,
+};
 use euclid::default::{Point2D, Rect};
 use euclid::Vector2D;
 use gfx::font_cache_thread::FontCacheThread;
@@ -49,7 +52,8 @@ use ipc_channel::ipc::{self, IpcSender};
 use ipc_channel::router::ROUTER;
 use js::glue::GetWindowProxyClass;
 use js::jsapi::{
-    JSContext as UnsafeJSContext, JSTracer, JS_AddInterruptCallback, SetWindowProxyClass,
+    GCReason, JSContext as UnsafeJSContext, JSTracer, JS_AddInterruptCallback, SetWindowProxyClass,
+    JS_GC,
 };
 use js::jsval::UndefinedValue;
 use js::rust::ParentRuntime;
@@ -62,38 +66,44 @@ use msg::constellation_msg::{
     ScriptHangAnnotation, TopLevelBrowsingContextId,
 };
 use net_traits::image_cache::{ImageCache, PendingImageResponse};
+use net_traits::reporting::parse_reporting_endpoints;
 use net_traits::request::{CredentialsMode, Destination, RedirectMode, RequestBuilder};
 use net_traits::storage_thread::StorageType;
 use net_traits::{
-    FetchMetadata, FetchResponseListener, FetchResponseMsg, Metadata, NetworkError, ReferrerPolicy,
-    ResourceFetchTiming, ResourceThreads, ResourceTimingType,
+    CoreResourceMsg, FetchMetadata, FetchResponseListener, FetchResponseMsg, IpcSend, Metadata,
+    NetworkError, ReferrerPolicy, ResourceFetchTiming, ResourceThreads, ResourceTimingType,
 };
 use parking_lot::Mutex;
 use percent_encoding::percent_decode;
 use profile_traits::mem::{self as profile_mem, OpaqueSender, ReportsChan};
-use profile_traits::time::{self as profile_time, profile, ProfilerCategory};
+use profile_traits::time::{self as profile_time, profile, ProfilerCategory, ProfilerMsg};
 use script_layout_interface::message::{Msg, ReflowGoal};
 use script_layout_interface::{Layout, LayoutConfig, LayoutFactory, ScriptThreadFactory};
+use script_traits::content_script::{ContentScript, ContentScriptRunAt};
+use script_traits::extension::{
+    ExtensionApiError, ExtensionApiResult, ExtensionId, ExtensionMessage,
+};
 use script_traits::webdriver_msg::WebDriverScriptCommand;
 use script_traits::CompositorEvent::{
-    CompositionEvent, GamepadEvent, IMEDismissedEvent, KeyboardEvent, MouseButtonEvent,
+    CompositionEvent, DragEvent, GamepadEvent, IMEDismissedEvent, KeyboardEvent, MouseButtonEvent,
     MouseMoveEvent, ResizeEvent, TouchEvent, WheelEvent,
 };
 use script_traits::{
     AnimationTickType, CompositorEvent, ConstellationControlMsg, DiscardBrowsingContext,
-    DocumentActivity, EventResult, HistoryEntryReplacement, InitialScriptState, JsEvalResult,
-    LayoutControlMsg, LayoutMsg, LoadData, LoadOrigin, MediaSessionActionType, MouseButton,
-    MouseEventType, NewLayoutInfo, Painter, ProgressiveWebMetricType, ScriptMsg,
-    ScriptToConstellationChan, StructuredSerializedData, TimerSchedulerMsg, TouchEventType,
-    TouchId, UntrustedNodeAddress, UpdatePipelineIdReason, WebrenderIpcSender, WheelDelta,
-    WindowSizeData, WindowSizeType,
+    DocumentActivity, EventResult, HistoryEntryReplacement, HistoryEntryScrollState,
+    InitialScriptState, JsEvalResult, JsWorld, LayoutControlMsg, LayoutMsg, LoadData, LoadOrigin,
+    MediaSessionActionType, MouseButton, MouseEventType, NewLayoutInfo, Painter,
+    ProgressiveWebMetricType, ScriptMsg, ScriptToConstellationChan, StructuredSerializedData,
+    TimerSchedulerMsg, TouchEventType, TouchId, UntrustedNodeAddress, UpdatePipelineIdReason,
+    UserStylesheet, UserStylesheetId, WebrenderIpcSender, WheelDelta, WindowSizeData,
+    WindowSizeType,
 };
+use servo_allocator::{ArenaConfig, Partition};
 use servo_atoms::Atom;
 use servo_config::opts;
 use servo_url::{ImmutableOrigin, MutableOrigin, ServoUrl};
 use style::dom::OpaqueNode;
 use style::thread_state::{self, ThreadState};
-use time::precise_time_ns;
 use url::Position;
 use webgpu::identity::WebGPUMsg;
 use webrender_api::units::LayoutPixel;
@@ -132,9 +142,11 @@ use crate::dom::identityhub::Identities;
 use crate::dom::mutationobserver::MutationObserver;
 use crate::dom::node::{window_from_node, Node, ShadowIncluding};
 use crate::dom::performanceentry::PerformanceEntry;
+use crate::dom::performancelongtasktiming::{long_task_attribution, PerformanceLongTaskTiming};
 use crate::dom::performancepainttiming::PerformancePaintTiming;
 use crate::dom::serviceworker::TrustedServiceWorkerAddress;
 use crate::dom::servoparser::{ParserContext, ServoParser};
+use crate::dom::taskattributiontiming::TaskAttributionTiming;
 use crate::dom::uievent::UIEvent;
 use crate::dom::window::{ReflowReason, Window};
 use crate::dom::windowproxy::{CreatorBrowsingContextInfo, WindowProxy};
@@ -146,11 +158,12 @@ use crate::microtask::{Microtask, MicrotaskQueue};
 use crate::realms::enter_realm;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_runtime::{
-    get_reports, new_rt_and_cx, CommonScriptMsg, ContextForRequestInterrupt, JSContext, Runtime,
-    ScriptChan, ScriptPort, ScriptThreadEventCategory,
+    dom_object_size, get_reports, new_rt_and_cx, CommonScriptMsg, ContextForRequestInterrupt,
+    JSContext, Runtime, ScriptChan, ScriptPort, ScriptThreadEventCategory,
 };
 use crate::task_manager::TaskManager;
 use crate::task_queue::{QueuedTask, QueuedTaskConversion, TaskQueue};
+use crate::task_source::database_access::DatabaseAccessTaskSource;
 use crate::task_source::dom_manipulation::DOMManipulationTaskSource;
 use crate::task_source::file_reading::FileReadingTaskSource;
 use crate::task_source::gamepad::GamepadTaskSource;
@@ -164,10 +177,14 @@ use crate::task_source::timer::TimerTaskSource;
 use crate::task_source::user_interaction::UserInteractionTaskSource;
 use crate::task_source::websocket::WebsocketTaskSource;
 use crate::task_source::{TaskSource, TaskSourceName};
-use crate::{devtools, webdriver_handlers};
+use crate::{clock, devtools, webdriver_handlers};
 
 pub type ImageCacheMsg = (PipelineId, PendingImageResponse);
 
+/// How long a task runs before it is a long task.
+/// <https://w3c.github.io/longtasks/#long-task>
+const LONG_TASK_DURATION: Duration = Duration::from_millis(50);
+
 thread_local!(static SCRIPT_THREAD_ROOT: Cell<Option<*const ScriptThread>> = Cell::new(None));
 
 pub unsafe fn trace_thread(tr: *mut JSTracer) {
@@ -222,6 +239,20 @@ struct InProgressLoad {
     canceller: FetchCanceller,
     /// If inheriting the security context
     inherited_secure_context: Option<bool>,
+    /// The user stylesheets to hand to layout once it is created.
+    #[no_trace]
+    user_stylesheets: Vec<UserStylesheet>,
+    /// The content scripts to consider injecting into the document once it is created.
+    #[no_trace]
+    content_scripts: Vec<ContentScript>,
+    /// Whether this load reloads a document the user agent discarded.
+    was_discarded: bool,
+    /// The history state of the session history entry that this load reloads, if any.
+    #[no_trace]
+    history_state_id: Option<HistoryStateId>,
+    /// The scroll state of the session history entry that this load reloads, if any.
+    #[no_trace]
+    scroll_state: Option<HistoryEntryScrollState>,
 }
 
 impl InProgressLoad {
@@ -241,7 +272,7 @@ impl InProgressLoad {
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default();
         let navigation_start = duration.as_millis();
-        let navigation_start_precise = precise_time_ns();
+        let navigation_start_precise = clock::monotonic_time_ns();
         InProgressLoad {
             pipeline_id: id,
             browsing_context_id: browsing_context_id,
@@ -257,6 +288,11 @@ impl InProgressLoad {
             navigation_start_precise: navigation_start_precise,
             canceller: Default::default(),
             inherited_secure_context: inherited_secure_context,
+            user_stylesheets: vec![],
+            content_scripts: vec![],
+            was_discarded: false,
+            history_state_id: None,
+            scroll_state: None,
         }
     }
 }
@@ -551,6 +587,8 @@ pub struct ScriptThread {
     /// events in the event queue.
     chan: MainThreadScriptChan,
 
+    database_access_task_sender: Box<dyn ScriptChan>,
+
     dom_manipulation_task_sender: Box<dyn ScriptChan>,
 
     gamepad_task_sender: Box<dyn ScriptChan>,
@@ -644,6 +682,31 @@ pub struct ScriptThread {
     /// Microtask Queue for adding support for mutation observer microtasks
     mutation_observer_microtask_queued: Cell<bool>,
 
+    /// The connectivity of the device, as last reported by the embedder.
+    #[no_trace]
+    network_status: Cell<NetworkStatus>,
+
+    /// Whether the user chose to work offline, in which case the pages are offline whatever
+    /// the connectivity of the device.
+    work_offline: Cell<bool>,
+
+    /// Whether the system is in forced colors mode, in which layout paints the pages with
+    /// its palette.
+    forced_colors: Cell<bool>,
+
+    /// What the embedder or WebDriver chose to tell about the user agent instead of the
+    /// defaults, for the pipelines of this script thread that have one.
+    #[no_trace]
+    user_agent_overrides: DomRefCell<HashMap<PipelineId, UserAgentOverride>>,
+
+    /// What the embedder lets the script of origins it does not trust do.
+    #[no_trace]
+    script_policies: DomRefCell<ScriptPolicies>,
+
+    /// The time spent running tasks for each pipeline of this script thread.
+    #[no_trace]
+    task_times: DomRefCell<HashMap<PipelineId, Duration>>,
+
     /// The unit of related similar-origin browsing contexts' list of MutationObserver objects
     mutation_observers: DomRefCell<Vec<Dom<MutationObserver>>>,
 
@@ -798,6 +861,9 @@ impl ScriptThreadFactory for ScriptThread {
         thread::Builder::new()
             .name(format!("Script{:?}", state.id))
             .spawn(move || {
+                // Layout runs on this thread too, and both keep a lot of long-lived
+                // allocations around, so keep them apart from the other threads.
+                servo_allocator::use_dedicated_arena("script", ArenaConfig::default());
                 thread_state::initialize(ThreadState::SCRIPT | ThreadState::LAYOUT);
                 PipelineNamespace::install(state.pipeline_namespace_id);
                 TopLevelBrowsingContextId::install(state.top_level_browsing_context_id);
@@ -911,6 +977,22 @@ impl ScriptThread {
         })
     }
 
+    /// The connectivity of the pages, offline when the user works offline.
+    pub fn network_status() -> NetworkStatus {
+        SCRIPT_THREAD_ROOT.with(|root| {
+            let script_thread = unsafe { &*root.get().unwrap() };
+            script_thread.effective_network_status()
+        })
+    }
+
+    fn effective_network_status(&self) -> NetworkStatus {
+        let mut status = self.network_status.get();
+        if self.work_offline.get() {
+            status.online = false;
+        }
+        status
+    }
+
     pub fn add_mutation_observer(observer: &MutationObserver) {
         SCRIPT_THREAD_ROOT.with(|root| {
             let script_thread = unsafe { &*root.get().unwrap() };
@@ -1033,6 +1115,9 @@ impl ScriptThread {
                     // Important re security. See https://github.com/servo/servo/issues/23373
                     // TODO: check according to https://w3c.github.io/webappsec-csp/#should-block-navigation-request
                     if let Some(window) = trusted_global.root().downcast::<Window>() {
+                        if !window.Document().is_scripting_enabled() {
+                            return;
+                        }
                         if ScriptThread::check_load_origin(&load_data.load_origin, &window.get_url().origin()) {
                             ScriptThread::eval_js_url(&trusted_global.root(), &mut load_data);
                             sender
@@ -1125,7 +1210,9 @@ impl ScriptThread {
                     .borrow()
                     .iter()
                     .filter_map(|(id, document)| {
-                        if document.is_fully_active() {
+                        // The tasks of a frozen document, including those of its fetches,
+                        // are held back until it is resumed.
+                        if document.is_fully_active() && !document.is_frozen() {
                             Some(id.clone())
                         } else {
                             None
@@ -1363,6 +1450,7 @@ impl ScriptThread {
             closing,
 
             chan: MainThreadScriptChan(chan.clone()),
+            database_access_task_sender: boxed_script_sender.clone(),
             dom_manipulation_task_sender: boxed_script_sender.clone(),
             gamepad_task_sender: boxed_script_sender.clone(),
             media_element_task_sender: chan.clone(),
@@ -1398,6 +1486,13 @@ impl ScriptThread {
 
             mutation_observer_microtask_queued: Default::default(),
 
+            network_status: Default::default(),
+            work_offline: Cell::new(false),
+            forced_colors: Cell::new(false),
+            user_agent_overrides: Default::default(),
+            task_times: Default::default(),
+            script_policies: Default::default(),
+
             mutation_observers: Default::default(),
 
             layout_to_constellation_chan: state.layout_to_constellation_chan,
@@ -1465,6 +1560,7 @@ impl ScriptThread {
     /// messages on its port.
     pub fn start(&self) {
         debug!("Starting script thread.");
+        let _partition = servo_allocator::enter_partition(Partition::Dom);
         while self.handle_msgs() {
             // Go on...
             debug!("Running script thread.");
@@ -1724,6 +1820,16 @@ impl ScriptThread {
                 .upcast::<GlobalScope>()
                 .perform_a_dom_garbage_collection_checkpoint();
 
+            // Steps 14 to 16, which update the layout as needed before the reflow below
+            // sends the resulting display list, once the elements with
+            // `content-visibility: auto` that are relevant to the user are known.
+            document.update_content_visibility_relevancy();
+            document.update_resize_observations();
+
+            // Step 19, for the lazy load intersection observer, which is the only one
+            // implemented so far.
+            document.update_lazy_load_intersections();
+
             let pending_reflows = window.get_pending_reflow_count();
             if pending_reflows > 0 {
                 window.reflow(ReflowGoal::Full, ReflowReason::PendingReflow);
@@ -1736,9 +1842,36 @@ impl ScriptThread {
             }
         }
 
+        self.report_task_queue_state();
+
         true
     }
 
+    /// Report the counters of the task queue to the time profiler every so often, and have
+    /// the net stack hold back the response chunks of our documents while the task queue
+    /// is saturated.
+    fn report_task_queue_state(&self) {
+        if let Some(saturated) = self.task_queue.take_saturation_change() {
+            let pipelines = self.documents.borrow().iter().map(|(id, _)| id).collect();
+            let msg = CoreResourceMsg::SetBackpressure(pipelines, saturated);
+            if let Err(e) = self.resource_threads.send(msg) {
+                warn!(
+                    "Failed to signal backpressure to the resource thread ({:?}).",
+                    e
+                );
+            }
+        }
+
+        if let Some(counters) = self.task_queue.take_counters() {
+            for (task_source, counters) in counters {
+                self.time_profiler_chan.send(ProfilerMsg::TaskQueueCounters(
+                    task_source.as_str().to_owned(),
+                    counters,
+                ));
+            }
+        }
+    }
+
     // Perform step 7.10 from https://html.spec.whatwg.org/multipage/#event-loop-processing-model.
     // Described at: https://drafts.csswg.org/web-animations-1/#update-animations-and-send-events
     fn update_animations_and_send_events(&self) {
@@ -1851,12 +1984,26 @@ impl ScriptThread {
                     child: _,
                 } => Some(id),
                 DispatchStorageEvent(id, ..) => Some(id),
+                DispatchPermissionChange(id, ..) => Some(id),
                 ReportCSSError(id, ..) => Some(id),
                 Reload(id, ..) => Some(id),
                 PaintMetric(id, ..) => Some(id),
                 ExitFullScreen(id, ..) => Some(id),
                 MediaSessionAction(..) => None,
                 SetWebGPUPort(..) => None,
+                AddUserStylesheet(id, ..) => Some(id),
+                RemoveUserStylesheet(id, ..) => Some(id),
+                AddContentScript(id, ..) => Some(id),
+                RunExtensionBackgroundScript(id, ..) => Some(id),
+                DispatchExtensionMessage(id, ..) => Some(id),
+                MemoryPressure => None,
+                SetDocumentFrozen(id, ..) => Some(id),
+                ReportPipelineUsage(..) => None,
+                NetworkChanged(..) => None,
+                SetWorkOffline(..) => None,
+                SetForcedColors(..) => None,
+                SetUserAgentOverride(id, ..) => Some(id),
+                SetScriptPolicies(..) => None,
                 ForLayoutFromConstellation(_, id) => Some(id),
                 ForLayoutFromFontCache(id) => Some(id),
             },
@@ -1887,6 +2034,7 @@ impl ScriptThread {
     {
         self.notify_activity_to_hang_monitor(&category);
         let start = Instant::now();
+        let start_ns = clock::monotonic_time_ns();
         let value = if self.profile_script_events {
             let profiler_cat = match category {
                 ScriptThreadEventCategory::AttachLayout => ProfilerCategory::ScriptAttachLayout,
@@ -1934,6 +2082,12 @@ impl ScriptThread {
             f()
         };
         let task_duration = start.elapsed();
+        if let Some(pipeline_id) = pipeline_id {
+            *self.task_times.borrow_mut().entry(pipeline_id).or_default() += task_duration;
+            if task_duration > LONG_TASK_DURATION {
+                self.report_long_task(pipeline_id, start_ns, clock::monotonic_time_ns());
+            }
+        }
         for (doc_id, doc) in self.documents.borrow().iter() {
             if let Some(pipeline_id) = pipeline_id {
                 if pipeline_id == doc_id && task_duration.as_nanos() > MAX_TASK_NS.into() {
@@ -1952,6 +2106,45 @@ impl ScriptThread {
         value
     }
 
+    /// Report a task of `culprit` that ran from `start_ns` to `end_ns`, and took too long, to
+    /// the documents of this event loop, telling each how it relates to the task.
+    /// <https://w3c.github.io/longtasks/#report-long-tasks>
+    fn report_long_task(&self, culprit: PipelineId, start_ns: u64, end_ns: u64) {
+        let culprit = match self.documents.borrow().find_window(culprit) {
+            Some(culprit) => culprit,
+            None => return,
+        };
+        // The task may have discarded the browsing context it ran in.
+        if culprit.undiscarded_window_proxy().is_none() {
+            return;
+        }
+        let documents: Vec<_> = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(_, document)| document)
+            .collect();
+        for document in documents {
+            if !document.is_fully_active() {
+                continue;
+            }
+            let window = document.window();
+            let (name, container) = long_task_attribution(window, &culprit);
+            let global = window.upcast::<GlobalScope>();
+            let performance = window.Performance();
+            let start_time = performance.relative_high_resolution_time(start_ns);
+            let attribution = TaskAttributionTiming::new(global, container.as_deref());
+            let entry = PerformanceLongTaskTiming::new(
+                global,
+                DOMString::from(name),
+                start_time,
+                performance.relative_high_resolution_time(end_ns) - start_time,
+                &attribution,
+            );
+            performance.queue_entry(entry.upcast::<PerformanceEntry>());
+        }
+    }
+
     fn handle_msg_from_constellation(&self, msg: ConstellationControlMsg) {
         match msg {
             ConstellationControlMsg::StopDelayingLoadEventsMode(pipeline_id) => {
@@ -2033,9 +2226,17 @@ impl ScriptThread {
                 new_pipeline_id,
                 reason,
             ),
-            ConstellationControlMsg::UpdateHistoryState(pipeline_id, history_state_id, url) => {
-                self.handle_update_history_state_msg(pipeline_id, history_state_id, url)
-            },
+            ConstellationControlMsg::UpdateHistoryState(
+                pipeline_id,
+                history_state_id,
+                url,
+                scroll_state,
+            ) => self.handle_update_history_state_msg(
+                pipeline_id,
+                history_state_id,
+                url,
+                scroll_state,
+            ),
             ConstellationControlMsg::RemoveHistoryStates(pipeline_id, history_states) => {
                 self.handle_remove_history_states(pipeline_id, history_states)
             },
@@ -2064,6 +2265,9 @@ impl ScriptThread {
                 old_value,
                 new_value,
             ) => self.handle_storage_event(pipeline_id, storage, url, key, old_value, new_value),
+            ConstellationControlMsg::DispatchPermissionChange(pipeline_id, name, state) => {
+                self.handle_permission_change(pipeline_id, name, state)
+            },
             ConstellationControlMsg::ReportCSSError(pipeline_id, filename, line, column, msg) => {
                 self.handle_css_error_reporting(pipeline_id, filename, line, column, msg)
             },
@@ -2077,6 +2281,52 @@ impl ScriptThread {
             ConstellationControlMsg::MediaSessionAction(pipeline_id, action) => {
                 self.handle_media_session_action(pipeline_id, action)
             },
+            ConstellationControlMsg::AddUserStylesheet(pipeline_id, stylesheet) => {
+                self.handle_add_user_stylesheet(pipeline_id, stylesheet)
+            },
+            ConstellationControlMsg::RemoveUserStylesheet(pipeline_id, stylesheet_id) => {
+                self.handle_remove_user_stylesheet(pipeline_id, stylesheet_id)
+            },
+            ConstellationControlMsg::AddContentScript(pipeline_id, content_script) => {
+                self.handle_add_content_script(pipeline_id, content_script)
+            },
+            ConstellationControlMsg::RunExtensionBackgroundScript(
+                pipeline_id,
+                extension_id,
+                source,
+            ) => self.handle_run_extension_background_script(pipeline_id, extension_id, source),
+            ConstellationControlMsg::DispatchExtensionMessage(
+                pipeline_id,
+                extension_id,
+                message,
+                response_sender,
+            ) => self.handle_dispatch_extension_message(
+                pipeline_id,
+                extension_id,
+                message,
+                response_sender,
+            ),
+            ConstellationControlMsg::MemoryPressure => self.handle_memory_pressure(),
+            ConstellationControlMsg::SetDocumentFrozen(pipeline_id, frozen) => {
+                self.handle_set_document_frozen(pipeline_id, frozen)
+            },
+            ConstellationControlMsg::ReportPipelineUsage(sender) => {
+                self.handle_report_pipeline_usage(sender)
+            },
+            ConstellationControlMsg::NetworkChanged(change) => self.handle_network_changed(change),
+            ConstellationControlMsg::SetWorkOffline(work_offline) => {
+                self.handle_set_work_offline(work_offline)
+            },
+            ConstellationControlMsg::SetForcedColors(forced_colors) => {
+                self.handle_set_forced_colors(forced_colors)
+            },
+            ConstellationControlMsg::SetUserAgentOverride(pipeline_id, user_agent_override) => {
+                self.handle_set_user_agent_override(pipeline_id, user_agent_override)
+            },
+            ConstellationControlMsg::SetScriptPolicies(script_policies) => {
+                // The policies apply to the realms made from now on.
+                *self.script_policies.borrow_mut() = script_policies;
+            },
             ConstellationControlMsg::SetWebGPUPort(port) => {
                 if self.webgpu_port.borrow().is_some() {
                     warn!("WebGPU port already exists for this content process");
@@ -2116,6 +2366,7 @@ impl ScriptThread {
             WebGPUMsg::FreeDevice(id) => self.gpu_id_hub.lock().kill_device_id(id),
             WebGPUMsg::FreeBuffer(id) => self.gpu_id_hub.lock().kill_buffer_id(id),
             WebGPUMsg::FreePipelineLayout(id) => self.gpu_id_hub.lock().kill_pipeline_layout_id(id),
+            WebGPUMsg::FreeQuerySet(id) => self.gpu_id_hub.lock().kill_query_set_id(id),
             WebGPUMsg::FreeComputePipeline(id) => {
                 self.gpu_id_hub.lock().kill_compute_pipeline_id(id)
             },
@@ -2148,6 +2399,15 @@ impl ScriptThread {
                 let global = self.documents.borrow().find_global(pipeline_id).unwrap();
                 global.remove_gpu_device(device);
             },
+            WebGPUMsg::DeviceLost {
+                device,
+                pipeline_id,
+                msg,
+            } => {
+                let global = self.documents.borrow().find_global(pipeline_id).unwrap();
+                let _ac = enter_realm(&*global);
+                global.gpu_device_lost(device, msg);
+            },
             _ => {},
         }
     }
@@ -2216,6 +2476,12 @@ impl ScriptThread {
                 devtools::handle_request_animation_frame(&*documents, id, name)
             },
             DevtoolScriptControlMsg::Reload(id) => devtools::handle_reload(&*documents, id),
+            DevtoolScriptControlMsg::SetNetworkThrottling(id, throttling) => {
+                devtools::handle_set_network_throttling(&*documents, id, throttling)
+            },
+            DevtoolScriptControlMsg::HighlightBoxModel(id, node_id) => {
+                devtools::handle_highlight_box_model(&*documents, id, node_id)
+            },
         }
     }
 
@@ -2426,6 +2692,14 @@ impl ScriptThread {
                     reply,
                 )
             },
+            WebDriverScriptCommand::ScrollIntoView(node_id, reply) => {
+                webdriver_handlers::handle_scroll_into_view(
+                    &*documents,
+                    pipeline_id,
+                    node_id,
+                    reply,
+                )
+            },
             WebDriverScriptCommand::GetElementText(node_id, reply) => {
                 webdriver_handlers::handle_get_text(&*documents, pipeline_id, node_id, reply)
             },
@@ -2457,6 +2731,17 @@ impl ScriptThread {
             WebDriverScriptCommand::GetTitle(reply) => {
                 webdriver_handlers::handle_get_title(&*documents, pipeline_id, reply)
             },
+            WebDriverScriptCommand::SetAnimationsPaused(paused, reply) => {
+                webdriver_handlers::handle_set_animations_paused(
+                    &*documents,
+                    pipeline_id,
+                    paused,
+                    reply,
+                )
+            },
+            WebDriverScriptCommand::WaitForAnimationFrame(reply) => {
+                webdriver_handlers::handle_wait_for_animation_frame(&*documents, pipeline_id, reply)
+            },
             _ => (),
         }
     }
@@ -2475,6 +2760,194 @@ impl ScriptThread {
         warn!("resize sent to nonexistent pipeline");
     }
 
+    fn handle_add_user_stylesheet(&self, id: PipelineId, stylesheet: UserStylesheet) {
+        let document = self.documents.borrow().find_document(id);
+        if let Some(document) = document {
+            let _ = Self::with_layout(id, |layout| {
+                layout.process(Msg::AddUserStylesheet(stylesheet))
+            });
+            self.rebuild_and_force_reflow(&document, ReflowReason::StylesheetLoaded);
+            return;
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        if let Some(load) = loads.iter_mut().find(|load| load.pipeline_id == id) {
+            load.user_stylesheets.push(stylesheet);
+            return;
+        }
+        warn!("User stylesheet sent to nonexistent pipeline");
+    }
+
+    fn handle_remove_user_stylesheet(&self, id: PipelineId, stylesheet_id: UserStylesheetId) {
+        let document = self.documents.borrow().find_document(id);
+        if let Some(document) = document {
+            let _ = Self::with_layout(id, |layout| {
+                layout.process(Msg::RemoveUserStylesheet(stylesheet_id))
+            });
+            self.rebuild_and_force_reflow(&document, ReflowReason::StylesheetLoaded);
+            return;
+        }
+        let mut loads = self.incomplete_loads.borrow_mut();
+        if let Some(load) = loads.iter_mut().find(|load| load.pipeline_id == id) {
+            load.user_stylesheets
+                .retain(|stylesheet| stylesheet.id != stylesheet_id);
+        }
+    }
+
+    fn handle_add_content_script(&self, id: PipelineId, content_script: ContentScript) {
+        let mut loads = self.incomplete_loads.borrow_mut();
+        if let Some(load) = loads.iter_mut().find(|load| load.pipeline_id == id) {
+            load.content_scripts.push(content_script);
+            return;
+        }
+        // Content scripts are only injected into documents that have yet to be created.
+        warn!("Content script sent to pipeline without a pending load");
+    }
+
+    fn handle_run_extension_background_script(
+        &self,
+        id: PipelineId,
+        extension_id: ExtensionId,
+        source: String,
+    ) {
+        let window = match self.documents.borrow().find_window(id) {
+            Some(window) => window,
+            None => return warn!("Background script sent to closed pipeline {}.", id),
+        };
+        let cx = window.get_cx();
+        rooted!(in(*cx) let mut rval = UndefinedValue());
+        window.evaluate_js_in_isolated_world(
+            &JsWorld::Extension(extension_id),
+            &source,
+            rval.handle_mut(),
+        );
+    }
+
+    fn handle_dispatch_extension_message(
+        &self,
+        id: PipelineId,
+        extension_id: ExtensionId,
+        message: ExtensionMessage,
+        response_sender: IpcSender<ExtensionApiResult<StructuredSerializedData>>,
+    ) {
+        match self.documents.borrow().find_window(id) {
+            Some(window) => {
+                window.dispatch_extension_message(extension_id, message, response_sender)
+            },
+            None => {
+                let _ = response_sender.send(Err(ExtensionApiError::NoReceivingEnd));
+            },
+        }
+    }
+
+    /// Collect garbage, then give the memory freed this way back to the system.
+    #[allow(unsafe_code)]
+    fn handle_memory_pressure(&self) {
+        unsafe { JS_GC(*self.get_cx(), GCReason::MEM_PRESSURE) };
+        servo_allocator::purge();
+    }
+
+    fn handle_set_document_frozen(&self, pipeline_id: PipelineId, frozen: bool) {
+        let document = match self.documents.borrow().find_document(pipeline_id) {
+            Some(document) => document,
+            None => return warn!("{}: Freezing or resuming a closed document", pipeline_id),
+        };
+        if frozen {
+            document.run_freeze_steps();
+        } else {
+            document.run_resume_steps();
+        }
+    }
+
+    /// Report what each document uses of this script thread.
+    fn handle_report_pipeline_usage(&self, sender: IpcSender<Vec<(PipelineId, PipelineUsage)>>) {
+        let process_id = process::id();
+        let event_loop_health = self.task_queue.health();
+        let task_times = self.task_times.borrow();
+        let usage = self
+            .documents
+            .borrow()
+            .iter()
+            .map(|(id, document)| {
+                let dom_memory = document
+                    .upcast::<Node>()
+                    .traverse_preorder(ShadowIncluding::Yes)
+                    .map(|node| dom_object_size(&*node))
+                    .sum();
+                let usage = PipelineUsage {
+                    process_id,
+                    dom_memory,
+                    task_time: task_times.get(&id).copied().unwrap_or_default(),
+                    event_loop_health,
+                };
+                (id, usage)
+            })
+            .collect();
+        let _ = sender.send(usage);
+    }
+
+    /// Let the documents know the connectivity of the device changed.
+    fn handle_network_changed(&self, change: NetworkChange) {
+        self.update_network_status(|| self.network_status.set(NetworkStatus::after(change)));
+    }
+
+    /// Take the pages offline or back online, as the user chose.
+    fn handle_set_work_offline(&self, work_offline: bool) {
+        self.update_network_status(|| self.work_offline.set(work_offline));
+    }
+
+    /// Repaint the documents in or out of forced colors mode. Documents that are not loaded
+    /// yet get it when their layout is created.
+    fn handle_set_forced_colors(&self, forced_colors: bool) {
+        if self.forced_colors.replace(forced_colors) == forced_colors {
+            return;
+        }
+        for (id, document) in self.documents.borrow().iter() {
+            let _ = Self::with_layout(id, |layout| {
+                layout.process(Msg::SetForcedColors(forced_colors))
+            });
+            self.rebuild_and_force_reflow(&document, ReflowReason::ForcedColorsChanged);
+        }
+    }
+
+    /// Tell `user_agent_override` about the user agent to the scripts of `pipeline_id` instead
+    /// of the defaults, or stop overriding them. Documents that are not loaded yet get it
+    /// when their window is created.
+    fn handle_set_user_agent_override(
+        &self,
+        pipeline_id: PipelineId,
+        user_agent_override: Option<UserAgentOverride>,
+    ) {
+        match user_agent_override {
+            Some(ref user_agent_override) => {
+                self.user_agent_overrides
+                    .borrow_mut()
+                    .insert(pipeline_id, user_agent_override.clone());
+            },
+            None => {
+                self.user_agent_overrides.borrow_mut().remove(&pipeline_id);
+            },
+        }
+        if let Some(document) = self.documents.borrow().find_document(pipeline_id) {
+            document
+                .window()
+                .upcast::<GlobalScope>()
+                .set_user_agent_override(user_agent_override);
+        }
+    }
+
+    /// Let the documents know if `update` changes their connectivity.
+    fn update_network_status(&self, update: impl FnOnce()) {
+        let old_status = self.effective_network_status();
+        update();
+        let new_status = self.effective_network_status();
+        if old_status == new_status {
+            return;
+        }
+        for (_, document) in self.documents.borrow().iter() {
+            document.window().network_changed(old_status, new_status);
+        }
+    }
+
     // exit_fullscreen creates a new JS promise object, so we need to have entered a realm
     fn handle_exit_fullscreen(&self, id: PipelineId) {
         let document = self.documents.borrow().find_document(id);
@@ -2740,6 +3213,7 @@ impl ScriptThread {
         pipeline_id: PipelineId,
         history_state_id: Option<HistoryStateId>,
         url: ServoUrl,
+        scroll_state: HistoryEntryScrollState,
     ) {
         let window = self.documents.borrow().find_window(pipeline_id);
         match window {
@@ -2749,7 +3223,9 @@ impl ScriptThread {
                     pipeline_id
                 );
             },
-            Some(window) => window.History().activate_state(history_state_id, url),
+            Some(window) => window
+                .History()
+                .activate_state(history_state_id, url, scroll_state),
         }
     }
 
@@ -2839,6 +3315,10 @@ impl ScriptThread {
         DOMManipulationTaskSource(self.dom_manipulation_task_sender.clone(), pipeline_id)
     }
 
+    pub fn database_access_task_source(&self, pipeline_id: PipelineId) -> DatabaseAccessTaskSource {
+        DatabaseAccessTaskSource(self.database_access_task_sender.clone(), pipeline_id)
+    }
+
     pub fn gamepad_task_source(&self, pipeline_id: PipelineId) -> GamepadTaskSource {
         GamepadTaskSource(self.gamepad_task_sender.clone(), pipeline_id)
     }
@@ -2906,6 +3386,8 @@ impl ScriptThread {
         debug!("{id}: Starting pipeline exit.");
 
         self.closed_pipelines.borrow_mut().insert(id);
+        self.user_agent_overrides.borrow_mut().remove(&id);
+        self.task_times.borrow_mut().remove(&id);
 
         // Abort the parser, if any,
         // to prevent any further incoming networking messages from being handled.
@@ -2950,6 +3432,9 @@ impl ScriptThread {
                 window.discard_browsing_context();
             }
 
+            // A pipeline that exits does not crash anymore.
+            window.upcast::<GlobalScope>().clear_crash_report_endpoint();
+
             debug!("{id}: Clearing JavaScript runtime");
             window.clear_js_runtime();
         }
@@ -3054,6 +3539,21 @@ impl ScriptThread {
         storage.queue_storage_event(url, key, old_value, new_value);
     }
 
+    fn handle_permission_change(
+        &self,
+        pipeline_id: PipelineId,
+        name: PermissionName,
+        state: PermissionState,
+    ) {
+        let window = match self.documents.borrow().find_window(pipeline_id) {
+            None => return warn!("Permission change sent to closed pipeline {}.", pipeline_id),
+            Some(window) => window,
+        };
+        window
+            .upcast::<GlobalScope>()
+            .permission_changed(name.into(), state.into());
+    }
+
     /// Notify the containing document of a child iframe that has completed loading.
     fn handle_iframe_load_event(
         &self,
@@ -3238,6 +3738,7 @@ impl ScriptThread {
         };
 
         let task_manager = TaskManager::new(
+            self.database_access_task_source(incomplete.pipeline_id),
             self.dom_manipulation_task_source(incomplete.pipeline_id),
             self.file_reading_task_source(incomplete.pipeline_id),
             self.gamepad_task_source(incomplete.pipeline_id),
@@ -3262,6 +3763,23 @@ impl ScriptThread {
             incomplete.navigation_start_precise,
         );
 
+        // Content scripts are matched against the final URL of the document. Their
+        // stylesheets are handed to layout along with the other user stylesheets.
+        let is_top_level = incomplete.parent_info.is_none();
+        let content_scripts: Vec<ContentScript> = incomplete
+            .content_scripts
+            .iter()
+            .filter(|content_script| content_script.applies_to(&final_url, is_top_level))
+            .cloned()
+            .collect();
+        let mut user_stylesheets = incomplete.user_stylesheets.clone();
+        user_stylesheets.extend(content_scripts.iter().flat_map(|content_script| {
+            content_script
+                .css
+                .iter()
+                .map(|css| UserStylesheet::new(css.clone(), final_url.clone()))
+        }));
+
         let layout_config = LayoutConfig {
             id: incomplete.pipeline_id,
             url: final_url.clone(),
@@ -3274,6 +3792,8 @@ impl ScriptThread {
             webrender_api_sender: self.webrender_api_sender.clone(),
             paint_time_metrics,
             window_size: incomplete.window_size.clone(),
+            user_stylesheets,
+            forced_colors: self.forced_colors.get(),
         };
         self.layouts.borrow_mut().insert(
             incomplete.pipeline_id,
@@ -3322,6 +3842,31 @@ impl ScriptThread {
 
         let _realm = enter_realm(&*window);
 
+        if let Some(user_agent_override) = self
+            .user_agent_overrides
+            .borrow()
+            .get(&incomplete.pipeline_id)
+        {
+            window
+                .upcast::<GlobalScope>()
+                .set_user_agent_override(Some(user_agent_override.clone()));
+        }
+        let script_policy = self
+            .script_policies
+            .borrow()
+            .get(origin.immutable())
+            .cloned()
+            .unwrap_or_default();
+        // The endpoints are needed for the reports applying the script policy generates.
+        if let Some(headers) = metadata.headers.as_ref() {
+            window
+                .upcast::<GlobalScope>()
+                .set_reporting_endpoints(parse_reporting_endpoints(headers, &final_url));
+        }
+        window
+            .upcast::<GlobalScope>()
+            .apply_script_policy(script_policy);
+
         // Initialize the browsing context for the window.
         let window_proxy = self.local_window_proxy(
             &window,
@@ -3399,12 +3944,18 @@ impl ScriptThread {
             incomplete.canceller,
         );
         document.set_ready_state(DocumentReadyState::Loading);
+        document.set_was_discarded(incomplete.was_discarded);
 
         self.documents
             .borrow_mut()
             .insert(incomplete.pipeline_id, &*document);
 
         window.init_document(&document);
+        if incomplete.history_state_id.is_some() || incomplete.scroll_state.is_some() {
+            window
+                .History()
+                .restore_entry(incomplete.history_state_id, incomplete.scroll_state);
+        }
 
         // For any similar-origin iframe, ensure that the contentWindow/contentDocument
         // APIs resolve to the new window/document as soon as parsing starts.
@@ -3435,6 +3986,8 @@ impl ScriptThread {
 
         document.set_https_state(metadata.https_state);
         document.set_navigation_start(incomplete.navigation_start_precise);
+        document.set_content_scripts(content_scripts);
+        document.run_content_scripts(ContentScriptRunAt::DocumentStart);
 
         if is_html_document == IsHTMLDocument::NonHTMLDocument {
             ServoParser::parse_xml_document(&document, None, final_url);
@@ -3629,6 +4182,14 @@ impl ScriptThread {
                 self.handle_wheel_event(pipeline_id, delta, point, node_address);
             },
 
+            DragEvent(event_type, point, node_address) => {
+                let document = match self.documents.borrow().find_document(pipeline_id) {
+                    Some(document) => document,
+                    None => return warn!("Message sent to closed pipeline {}.", pipeline_id),
+                };
+                unsafe { document.handle_drag_event(event_type, point, node_address) };
+            },
+
             KeyboardEvent(key_event) => {
                 let document = match self.documents.borrow().find_document(pipeline_id) {
                     Some(document) => document,
@@ -3831,6 +4392,9 @@ impl ScriptThread {
     /// argument until a notification is received that the fetch is complete.
     fn pre_page_load(&self, mut incomplete: InProgressLoad, load_data: LoadData) {
         let id = incomplete.pipeline_id.clone();
+        incomplete.was_discarded = load_data.was_discarded;
+        incomplete.history_state_id = load_data.history_state_id;
+        incomplete.scroll_state = load_data.scroll_state;
         let req_init = RequestBuilder::new(load_data.url.clone(), load_data.referrer)
             .method(load_data.method)
             .destination(Destination::Document)
@@ -18,8 +18,9 @@ use ipc_channel::router::ROUTER;
 use msg::constellation_msg::{PipelineNamespace, ServiceWorkerId, ServiceWorkerRegistrationId};
 use net_traits::{CoreResourceMsg, CustomResponseMediator};
 use script_traits::{
-    DOMMessage, Job, JobError, JobResult, JobResultValue, JobType, SWManagerMsg, SWManagerSenders,
-    ScopeThings, ServiceWorkerManagerFactory, ServiceWorkerMsg,
+    DOMMessage, Job, JobError, JobResult, JobResultValue, JobType, NavigationPreloadAction,
+    NavigationPreloadState, SWManagerMsg, SWManagerSenders, ScopeThings,
+    ServiceWorkerManagerFactory, ServiceWorkerMsg,
 };
 use servo_config::pref;
 use servo_url::{ImmutableOrigin, ServoUrl};
@@ -137,6 +138,9 @@ struct ServiceWorkerRegistration {
     context: Option<ContextForRequestInterrupt>,
     /// The closing flag for the worker.
     closing: Option<Arc<AtomicBool>>,
+    /// <https://w3c.github.io/ServiceWorker/#service-worker-registration-navigation-preload-enabled-flag>
+    /// and <https://w3c.github.io/ServiceWorker/#service-worker-registration-navigation-preload-header-value>
+    navigation_preload: NavigationPreloadState,
 }
 
 impl ServiceWorkerRegistration {
@@ -150,6 +154,10 @@ impl ServiceWorkerRegistration {
             control_sender: None,
             context: None,
             closing: None,
+            navigation_preload: NavigationPreloadState {
+                enabled: false,
+                header_value: b"true".to_vec(),
+            },
         }
     }
 
@@ -271,7 +279,11 @@ impl ServiceWorkerManager {
             if let Some(scope) = self.get_matching_scope(&mediator.load_url) {
                 if let Some(registration) = self.registrations.get(&scope) {
                     if let Some(ref worker) = registration.active_worker {
-                        worker.send_message(ServiceWorkerScriptMsg::Response(mediator));
+                        // https://w3c.github.io/ServiceWorker/#handle-fetch step 15:
+                        // start the preload request before the worker handles the event.
+                        let preload = Some(registration.navigation_preload.clone())
+                            .filter(|state| mediator.is_navigation && state.enabled);
+                        worker.send_message(ServiceWorkerScriptMsg::Response(mediator, preload));
                         return true;
                     }
                 }
@@ -311,11 +323,41 @@ impl ServiceWorkerManager {
                     // TODO: https://w3c.github.io/ServiceWorker/#unregister-algorithm
                 },
             },
+            ServiceWorkerMsg::NavigationPreload(scope_url, action, sender) => {
+                let _ = sender.send(self.handle_navigation_preload(&scope_url, action));
+            },
             ServiceWorkerMsg::Exit => return false,
         }
         true
     }
 
+    /// <https://w3c.github.io/ServiceWorker/#navigation-preload-manager>
+    ///
+    /// Returns the state after the action, or `None` if the state can't be changed because
+    /// the registration has no active worker.
+    fn handle_navigation_preload(
+        &mut self,
+        scope_url: &ServoUrl,
+        action: NavigationPreloadAction,
+    ) -> Option<NavigationPreloadState> {
+        let registration = self.registrations.get_mut(scope_url)?;
+        let has_active_worker = registration.active_worker.is_some();
+        let state = &mut registration.navigation_preload;
+        match action {
+            // https://w3c.github.io/ServiceWorker/#navigation-preload-manager-getstate
+            NavigationPreloadAction::GetState => {
+                return Some(NavigationPreloadState {
+                    enabled: state.enabled && has_active_worker,
+                    header_value: state.header_value.clone(),
+                });
+            },
+            _ if !has_active_worker => return None,
+            NavigationPreloadAction::SetEnabled(enabled) => state.enabled = enabled,
+            NavigationPreloadAction::SetHeaderValue(value) => state.header_value = value,
+        }
+        Some(state.clone())
+    }
+
     /// <https://w3c.github.io/ServiceWorker/#register-algorithm>
     fn handle_register_job(&mut self, mut job: Job) {
         if !job.script_url.is_origin_trustworthy() {
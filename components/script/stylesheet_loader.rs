@@ -12,7 +12,7 @@ use ipc_channel::ipc;
 use ipc_channel::router::ROUTER;
 use mime::{self, Mime};
 use msg::constellation_msg::PipelineId;
-use net_traits::request::{CorsSettings, Destination, Referrer, RequestBuilder};
+use net_traits::request::{CorsSettings, Destination, Referrer, RequestBuilder, RequestPriority};
 use net_traits::{
     FetchMetadata, FetchResponseListener, FilteredMetadata, Metadata, NetworkError, ReferrerPolicy,
     ResourceFetchTiming, ResourceTimingType,
@@ -35,7 +35,7 @@ use crate::dom::bindings::refcounted::Trusted;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::document::Document;
-use crate::dom::element::Element;
+use crate::dom::element::{fetch_priority_for_element, Element};
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
 use crate::dom::htmlelement::HTMLElement;
@@ -318,6 +318,13 @@ impl<'a> StylesheetLoader<'a> {
         if owner.parser_inserted() {
             document.increment_script_blocking_stylesheet_count();
         }
+        // Of the stylesheet owners, only `<link>` has a `fetchpriority` attribute.
+        let fetch_priority = self
+            .elem
+            .downcast::<HTMLLinkElement>()
+            .map_or(RequestPriority::Auto, |link| {
+                fetch_priority_for_element(link.upcast())
+            });
 
         let request = stylesheet_fetch_request(
             url.clone(),
@@ -326,6 +333,7 @@ impl<'a> StylesheetLoader<'a> {
             self.elem.global().pipeline_id(),
             self.elem.global().get_referrer(),
             referrer_policy,
+            fetch_priority,
             integrity_metadata,
         );
 
@@ -342,12 +350,14 @@ pub(crate) fn stylesheet_fetch_request(
     pipeline_id: PipelineId,
     referrer: Referrer,
     referrer_policy: Option<ReferrerPolicy>,
+    fetch_priority: RequestPriority,
     integrity_metadata: String,
 ) -> RequestBuilder {
     create_a_potential_cors_request(url, Destination::Style, cors_setting, None, referrer)
         .origin(origin)
         .pipeline_id(Some(pipeline_id))
         .referrer_policy(referrer_policy)
+        .priority(fetch_priority)
         .integrity_metadata(integrity_metadata)
 }
 
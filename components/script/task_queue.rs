@@ -7,9 +7,13 @@
 use std::cell::Cell;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::default::Default;
+use std::mem;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel::{self, Receiver, Sender};
+use embedder_traits::EventLoopHealth;
 use msg::constellation_msg::PipelineId;
+use profile_traits::time::TaskQueueCounters;
 
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::worker::TrustedWorkerAddress;
@@ -26,6 +30,15 @@ pub type QueuedTask = (
     TaskSourceName,
 );
 
+/// The number of waiting tasks above which the queue is saturated.
+const SATURATION_HIGH_WATERMARK: usize = 1000;
+
+/// The number of waiting tasks below which the queue is no longer saturated.
+const SATURATION_LOW_WATERMARK: usize = 250;
+
+/// How often the counters of the task sources are reported.
+const COUNTERS_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Defining the operations used to convert from a msg T to a QueuedTask.
 pub trait QueuedTaskConversion {
     fn task_source_name(&self) -> Option<&TaskSourceName>;
@@ -42,14 +55,25 @@ pub struct TaskQueue<T> {
     port: Receiver<T>,
     /// A sender to ensure the port doesn't block on select while there are throttled tasks.
     wake_up_sender: Sender<T>,
-    /// A queue from which the event-loop can drain tasks.
-    msg_queue: DomRefCell<VecDeque<T>>,
+    /// A queue from which the event-loop can drain tasks, with the time each entered the
+    /// task queue.
+    msg_queue: DomRefCell<VecDeque<(T, Instant)>>,
     /// A "business" counter, reset for each iteration of the event-loop
     taken_task_counter: Cell<u64>,
     /// Tasks that will be throttled for as long as we are "busy".
-    throttled: DomRefCell<HashMap<TaskSourceName, VecDeque<QueuedTask>>>,
+    throttled: DomRefCell<HashMap<TaskSourceName, VecDeque<(QueuedTask, Instant)>>>,
     /// Tasks for not fully-active documents.
     inactive: DomRefCell<HashMap<PipelineId, VecDeque<QueuedTask>>>,
+    /// What was seen of the tasks of each task source since the counters were last reported.
+    counters: DomRefCell<HashMap<TaskSourceName, TaskQueueCounters>>,
+    /// When the counters were last reported.
+    counters_reported: Cell<Instant>,
+    /// The time the recently dequeued tasks waited, on average.
+    dequeue_latency: Cell<Duration>,
+    /// Whether more tasks are waiting than the event-loop keeps up with.
+    saturated: Cell<bool>,
+    /// Whether `saturated` changed since it was last taken.
+    saturation_changed: Cell<bool>,
 }
 
 impl<T: QueuedTaskConversion> TaskQueue<T> {
@@ -61,6 +85,11 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             taken_task_counter: Default::default(),
             throttled: Default::default(),
             inactive: Default::default(),
+            counters: Default::default(),
+            counters_reported: Cell::new(Instant::now()),
+            dequeue_latency: Default::default(),
+            saturated: Cell::new(false),
+            saturation_changed: Cell::new(false),
         }
     }
 
@@ -97,13 +126,16 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             // Otherwise if the just stored inactive message
             // was the first and last of this iteration,
             // it will result in a spurious wake-up of the event-loop.
-            msg_queue.push_back(T::inactive_msg());
+            msg_queue.push_back((T::inactive_msg(), Instant::now()));
         }
     }
 
     /// Process incoming tasks, immediately sending priority ones downstream,
     /// and categorizing potential throttles.
     fn process_incoming_tasks(&self, first_msg: T, fully_active: &HashSet<PipelineId>) {
+        // The time the tasks, including those held back so far, enter the task queue.
+        let now = Instant::now();
+
         // 1. Make any previously stored task from now fully-active document available.
         let mut incoming = self.release_tasks_for_fully_active_documents(fully_active);
 
@@ -152,7 +184,7 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                 }
             }
             // Immediately send non-throttled tasks for processing.
-            let _ = self.msg_queue.borrow_mut().push_back(msg);
+            let _ = self.msg_queue.borrow_mut().push_back((msg, now));
         }
 
         for msg in to_be_throttled {
@@ -167,7 +199,7 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
             throttled_tasks
                 .entry(task_source.clone())
                 .or_default()
-                .push_back((worker, category, boxed, pipeline_id, task_source));
+                .push_back(((worker, category, boxed, pipeline_id, task_source), now));
         }
     }
 
@@ -183,7 +215,89 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
 
     /// Take a message from the front of the queue, without waiting if empty.
     pub fn recv(&self) -> Result<T, ()> {
-        self.msg_queue.borrow_mut().pop_front().ok_or(())
+        let (msg, enqueued) = self.msg_queue.borrow_mut().pop_front().ok_or(())?;
+        if let Some(task_source) = msg.task_source_name() {
+            self.count_dequeued_task(task_source, enqueued.elapsed());
+        }
+        Ok(msg)
+    }
+
+    fn count_dequeued_task(&self, task_source: &TaskSourceName, latency: Duration) {
+        let mut counters = self.counters.borrow_mut();
+        let counters = counters.entry(task_source.clone()).or_default();
+        counters.dequeued += 1;
+        counters.total_latency += latency;
+        counters.max_latency = counters.max_latency.max(latency);
+
+        // An exponential moving average, which mostly reflects the recent tasks.
+        let average = self.dequeue_latency.get();
+        self.dequeue_latency.set((average * 7 + latency) / 8);
+    }
+
+    /// The number of tasks waiting to be handled, but for those held back for documents
+    /// which are not fully-active.
+    fn queued_task_count(&self) -> usize {
+        let throttled: usize = self
+            .throttled
+            .borrow()
+            .values()
+            .map(|queue| queue.len())
+            .sum();
+        self.msg_queue.borrow().len() + throttled
+    }
+
+    /// Update whether more tasks are waiting than the event-loop keeps up with, going back
+    /// and forth between the two only past watermarks well apart.
+    fn update_saturation(&self) {
+        let queued = self.queued_task_count();
+        let saturated = if self.saturated.get() {
+            queued >= SATURATION_LOW_WATERMARK
+        } else {
+            queued > SATURATION_HIGH_WATERMARK
+        };
+        if saturated != self.saturated.get() {
+            self.saturated.set(saturated);
+            self.saturation_changed.set(true);
+        }
+    }
+
+    /// Whether the queue became saturated or stopped being so since this was last called.
+    pub fn take_saturation_change(&self) -> Option<bool> {
+        if !self.saturation_changed.replace(false) {
+            return None;
+        }
+        Some(self.saturated.get())
+    }
+
+    /// How well the event-loop keeps up with its tasks.
+    pub fn health(&self) -> EventLoopHealth {
+        EventLoopHealth {
+            queued_tasks: self.queued_task_count(),
+            dequeue_latency: self.dequeue_latency.get(),
+            saturated: self.saturated.get(),
+        }
+    }
+
+    /// The counters of the task sources which had tasks since they were last taken, with
+    /// the number of their tasks waiting now, if it is time to report them again.
+    pub fn take_counters(&self) -> Option<Vec<(TaskSourceName, TaskQueueCounters)>> {
+        if self.counters_reported.get().elapsed() < COUNTERS_REPORT_INTERVAL {
+            return None;
+        }
+        self.counters_reported.set(Instant::now());
+
+        let mut counters = mem::take(&mut *self.counters.borrow_mut());
+        for (msg, _) in self.msg_queue.borrow().iter() {
+            if let Some(task_source) = msg.task_source_name() {
+                counters.entry(task_source.clone()).or_default().queued += 1;
+            }
+        }
+        for (task_source, queue) in self.throttled.borrow().iter() {
+            if !queue.is_empty() {
+                counters.entry(task_source.clone()).or_default().queued += queue.len();
+            }
+        }
+        Some(counters.into_iter().collect())
     }
 
     /// Same as recv.
@@ -224,7 +338,7 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                         Some(queue) => queue,
                         None => continue,
                     };
-                    let queued_task = match throttled_queue.pop_front() {
+                    let (queued_task, enqueued) = match throttled_queue.pop_front() {
                         Some(queued_task) => queued_task,
                         None => continue,
                     };
@@ -243,12 +357,15 @@ impl<T: QueuedTaskConversion> TaskQueue<T> {
                     }
 
                     // Make the task available for the event-loop to handle as a message.
-                    let _ = self.msg_queue.borrow_mut().push_back(msg);
+                    let _ = self.msg_queue.borrow_mut().push_back((msg, enqueued));
                     self.taken_task_counter
                         .set(self.taken_task_counter.get() + 1);
                     throttled_length = throttled_length - 1;
                 },
             }
         }
+        drop(throttled);
+
+        self.update_saturation();
     }
 }
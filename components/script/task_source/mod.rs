@@ -2,6 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+pub mod database_access;
 pub mod dom_manipulation;
 pub mod file_reading;
 pub mod gamepad;
@@ -41,12 +42,33 @@ pub enum TaskSourceName {
     Timer,
     /// <https://www.w3.org/TR/gamepad/#dfn-gamepad-task-source>
     Gamepad,
+    /// <https://w3c.github.io/IndexedDB/#database-access-task-source>
+    DatabaseAccess,
 }
 
 impl TaskSourceName {
     pub fn all() -> Vec<TaskSourceName> {
         TaskSourceName::into_enum_iter().collect()
     }
+
+    /// The name of the task source, as reported to the time profiler.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            TaskSourceName::DOMManipulation => "DOM manipulation",
+            TaskSourceName::FileReading => "File reading",
+            TaskSourceName::HistoryTraversal => "History traversal",
+            TaskSourceName::Networking => "Networking",
+            TaskSourceName::PerformanceTimeline => "Performance timeline",
+            TaskSourceName::PortMessage => "Port message",
+            TaskSourceName::UserInteraction => "User interaction",
+            TaskSourceName::RemoteEvent => "Remote event",
+            TaskSourceName::MediaElement => "Media element",
+            TaskSourceName::Websocket => "WebSocket",
+            TaskSourceName::Timer => "Timer",
+            TaskSourceName::Gamepad => "Gamepad",
+            TaskSourceName::DatabaseAccess => "Database access",
+        }
+    }
 }
 
 pub trait TaskSource {
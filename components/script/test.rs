@@ -14,6 +14,10 @@ pub mod area {
     pub use crate::dom::htmlareaelement::{Area, Shape};
 }
 
+pub mod drag_and_drop {
+    pub use crate::drag_and_drop::{DragDataStore, DragDataStoreMode, DropEffect};
+}
+
 #[allow(non_snake_case)]
 pub mod size_of {
     use std::mem::size_of;
@@ -189,6 +189,10 @@ pub struct TextInput<T: ClipboardProvider> {
 
     /// Was last change made by set_content?
     was_last_change_by_set_content: bool,
+
+    /// Whether the selected text can be copied or cut to the clipboard, which masked fields
+    /// do not allow.
+    clipboard_copy_allowed: bool,
 }
 
 /// Resulting action to be taken by the owner of a text input that is handling an event.
@@ -216,7 +220,7 @@ pub enum Lines {
 }
 
 /// The direction in which to delete a character.
-#[derive(Clone, Copy, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Direction {
     Forward,
     Backward,
@@ -275,6 +279,7 @@ impl<T: ClipboardProvider> TextInput<T> {
             min_length: min_length,
             selection_direction: selection_direction,
             was_last_change_by_set_content: true,
+            clipboard_copy_allowed: true,
         };
         i.set_content(initial);
         i
@@ -298,6 +303,10 @@ impl<T: ClipboardProvider> TextInput<T> {
         self.selection_direction
     }
 
+    pub fn set_clipboard_copy_allowed(&mut self, allowed: bool) {
+        self.clipboard_copy_allowed = allowed;
+    }
+
     pub fn set_max_length(&mut self, length: Option<UTF16CodeUnits>) {
         self.max_length = length;
     }
@@ -884,6 +893,9 @@ impl<T: ClipboardProvider> TextInput<T> {
                 KeyReaction::RedrawSelection
             })
             .shortcut(CMD_OR_CONTROL, 'X', || {
+                if !self.clipboard_copy_allowed {
+                    return KeyReaction::Nothing;
+                }
                 if let Some(text) = self.get_selection_text() {
                     self.clipboard_provider.set_clipboard_contents(text);
                     self.delete_char(Direction::Backward);
@@ -891,6 +903,9 @@ impl<T: ClipboardProvider> TextInput<T> {
                 KeyReaction::DispatchInput
             })
             .shortcut(CMD_OR_CONTROL, 'C', || {
+                if !self.clipboard_copy_allowed {
+                    return KeyReaction::Nothing;
+                }
                 if let Some(text) = self.get_selection_text() {
                     self.clipboard_provider.set_clipboard_contents(text);
                 }
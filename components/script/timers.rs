@@ -15,11 +15,12 @@ use js::jsapi::Heap;
 use js::jsval::{JSVal, UndefinedValue};
 use js::rust::HandleValue;
 use script_traits::{
-    precise_time_ms, MsDuration, TimerEvent, TimerEventId, TimerEventRequest, TimerSchedulerMsg,
-    TimerSource,
+    MsDuration, TimerEvent, TimerEventId, TimerEventRequest, TimerSchedulerMsg, TimerSource,
 };
 use servo_config::pref;
 
+use crate::clock;
+use crate::dom::abortsignal::AbortSignalTimeoutCallback;
 use crate::dom::bindings::callback::ExceptionHandling::Report;
 use crate::dom::bindings::cell::DomRefCell;
 use crate::dom::bindings::codegen::Bindings::FunctionBinding::Function;
@@ -28,9 +29,11 @@ use crate::dom::bindings::str::DOMString;
 use crate::dom::document::FakeRequestAnimationFrameCallback;
 use crate::dom::eventsource::EventSourceTimeoutCallback;
 use crate::dom::globalscope::GlobalScope;
+use crate::dom::htmlinputelement::PasswordRevealTimeoutCallback;
 use crate::dom::htmlmetaelement::RefreshRedirectDue;
 use crate::dom::testbinding::TestBindingCallback;
 use crate::dom::xmlhttprequest::XHRTimeoutCallback;
+use crate::popups::PopupTimeoutCallback;
 use crate::script_module::ScriptFetchOptions;
 use crate::script_thread::ScriptThread;
 
@@ -91,6 +94,9 @@ pub enum OneshotTimerCallback {
     TestBindingCallback(TestBindingCallback),
     FakeRequestAnimationFrame(FakeRequestAnimationFrameCallback),
     RefreshRedirectDue(RefreshRedirectDue),
+    AbortSignalTimeout(AbortSignalTimeoutCallback),
+    PasswordRevealTimeout(PasswordRevealTimeoutCallback),
+    PopupTimeout(PopupTimeoutCallback),
 }
 
 impl OneshotTimerCallback {
@@ -102,6 +108,9 @@ impl OneshotTimerCallback {
             OneshotTimerCallback::TestBindingCallback(callback) => callback.invoke(),
             OneshotTimerCallback::FakeRequestAnimationFrame(callback) => callback.invoke(),
             OneshotTimerCallback::RefreshRedirectDue(callback) => callback.invoke(),
+            OneshotTimerCallback::AbortSignalTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::PasswordRevealTimeout(callback) => callback.invoke(),
+            OneshotTimerCallback::PopupTimeout(callback) => callback.invoke(),
         }
     }
 }
@@ -252,7 +261,7 @@ impl OneshotTimers {
 
         match self.suspended_since.get() {
             Some(time) => time - offset,
-            None => precise_time_ms() - offset,
+            None => clock::monotonic_time_ms() - offset,
         }
     }
 
@@ -272,14 +281,14 @@ impl OneshotTimers {
         }
 
         debug!("Suspending timers.");
-        self.suspended_since.set(Some(precise_time_ms()));
+        self.suspended_since.set(Some(clock::monotonic_time_ms()));
         self.invalidate_expected_event_id();
     }
 
     pub fn resume(&self) {
         // Resume is idempotent: do nothing if the timers are already resumed.
         let additional_offset = match self.suspended_since.get() {
-            Some(suspended_since) => precise_time_ms() - suspended_since,
+            Some(suspended_since) => clock::monotonic_time_ms() - suspended_since,
             None => return warn!("Resuming an already resumed timer."),
         };
 
@@ -306,7 +315,7 @@ impl OneshotTimers {
                 timer
                     .scheduled_for
                     .get()
-                    .saturating_sub(precise_time_ms().get()),
+                    .saturating_sub(clock::monotonic_time_ms().get()),
             );
             let request = TimerEventRequest(
                 self.timer_event_chan
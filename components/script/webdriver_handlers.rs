@@ -34,7 +34,7 @@ use crate::dom::bindings::codegen::Bindings::HTMLInputElementBinding::HTMLInputE
 use crate::dom::bindings::codegen::Bindings::HTMLOptionElementBinding::HTMLOptionElementMethods;
 use crate::dom::bindings::codegen::Bindings::HTMLSelectElementBinding::HTMLSelectElementMethods;
 use crate::dom::bindings::codegen::Bindings::NodeBinding::{GetRootNodeOptions, NodeMethods};
-use crate::dom::bindings::codegen::Bindings::WindowBinding::WindowMethods;
+use crate::dom::bindings::codegen::Bindings::WindowBinding::{ScrollBehavior, WindowMethods};
 use crate::dom::bindings::codegen::Bindings::XMLSerializerBinding::XMLSerializerMethods;
 use crate::dom::bindings::conversions::{
     get_property, get_property_jsval, is_array_like, root_from_object, ConversionBehavior,
@@ -45,6 +45,7 @@ use crate::dom::bindings::inheritance::Castable;
 use crate::dom::bindings::reflector::DomObject;
 use crate::dom::bindings::root::DomRoot;
 use crate::dom::bindings::str::DOMString;
+use crate::dom::document::AnimationFrameCallback;
 use crate::dom::element::Element;
 use crate::dom::eventtarget::EventTarget;
 use crate::dom::globalscope::GlobalScope;
@@ -877,6 +878,33 @@ pub fn handle_get_title(documents: &Documents, pipeline: PipelineId, reply: IpcS
         .unwrap();
 }
 
+pub fn handle_set_animations_paused(
+    documents: &Documents,
+    pipeline: PipelineId,
+    paused: bool,
+    reply: IpcSender<()>,
+) {
+    if let Some(document) = documents.find_document(pipeline) {
+        document.set_animations_paused(paused);
+    }
+    reply.send(()).unwrap();
+}
+
+pub fn handle_wait_for_animation_frame(
+    documents: &Documents,
+    pipeline: PipelineId,
+    reply: IpcSender<()>,
+) {
+    match documents.find_document(pipeline) {
+        Some(document) => {
+            document.request_animation_frame(AnimationFrameCallback::WebDriverFrameTick {
+                sender: reply,
+            });
+        },
+        None => reply.send(()).unwrap(),
+    }
+}
+
 pub fn handle_get_rect(
     documents: &Documents,
     pipeline: PipelineId,
@@ -946,6 +974,33 @@ pub fn handle_get_bounding_client_rect(
         .unwrap();
 }
 
+pub fn handle_scroll_into_view(
+    documents: &Documents,
+    pipeline: PipelineId,
+    element_id: String,
+    reply: IpcSender<Result<(), ErrorStatus>>,
+) {
+    reply
+        .send(
+            find_node_by_unique_id(documents, pipeline, element_id).and_then(|node| match node
+                .downcast::<Element>(
+            ) {
+                Some(element) => {
+                    let window = window_from_node(&*node);
+                    let rect = element.GetBoundingClientRect();
+                    window.scroll(
+                        window.ScrollX() as f64 + rect.X(),
+                        window.ScrollY() as f64 + rect.Y(),
+                        ScrollBehavior::Instant,
+                    );
+                    Ok(())
+                },
+                None => Err(ErrorStatus::UnknownError),
+            }),
+        )
+        .unwrap();
+}
+
 pub fn handle_get_text(
     documents: &Documents,
     pipeline: PipelineId,
@@ -49,7 +49,10 @@ use constellation::{
     UnprivilegedContent,
 };
 use crossbeam_channel::{unbounded, Sender};
-use embedder_traits::{EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker};
+use embedder_traits::{
+    EmbedderMsg, EmbedderProxy, EmbedderReceiver, EventLoopWaker, SpeechSynthesisErrorCode,
+    SpeechSynthesisEvent, SpeechSynthesisEventKind, SpeechSynthesisMsg, SpeechSynthesizer,
+};
 use env_logger::Builder as EnvLoggerBuilder;
 use euclid::Scale;
 #[cfg(all(
@@ -77,6 +80,7 @@ use script::serviceworker_manager::ServiceWorkerManager;
 use script::JSEngineSetup;
 use script_layout_interface::LayoutFactory;
 use script_traits::{ScriptToConstellationChan, WindowSizeData};
+use servo_allocator::ArenaConfig;
 use servo_config::{opts, pref, prefs};
 use servo_media::player::context::GlContext;
 use servo_media::ServoMedia;
@@ -174,6 +178,10 @@ pub struct Servo<Window: WindowMethods + 'static + ?Sized> {
     embedder_receiver: EmbedderReceiver,
     messages_for_embedder: Vec<(Option<TopLevelBrowsingContextId>, EmbedderMsg)>,
     profiler_enabled: bool,
+    /// The text-to-speech engine of the embedder, which the speech synthesis requests of
+    /// documents are handed to.
+    speech_synthesizer: Option<Box<dyn SpeechSynthesizer>>,
+    font_cache_thread: FontCacheThread,
     /// For single-process Servo instances, this field controls the initialization
     /// and deinitialization of the JS Engine. Multiprocess Servo instances have their
     /// own instance that exists in the content process instead.
@@ -221,6 +229,18 @@ impl webrender_api::RenderNotifier for RenderNotifier {
     }
 }
 
+/// Moves the long-lived WebRender threads to an allocator arena of their own,
+/// so that they do not contend with the rest of the process for allocations.
+struct WebRenderThreadListener;
+
+impl webrender::ThreadListener for WebRenderThreadListener {
+    fn thread_started(&self, _thread_name: &str) {
+        servo_allocator::use_dedicated_arena("webrender", ArenaConfig::default());
+    }
+
+    fn thread_stopped(&self, _thread_name: &str) {}
+}
+
 pub struct InitializedServo<Window: WindowMethods + 'static + ?Sized> {
     pub servo: Servo<Window>,
     pub browser_id: TopLevelBrowsingContextId,
@@ -253,6 +273,10 @@ where
         }
 
         let user_agent = match user_agent {
+            // Every instance resisting fingerprinting says it is the same desktop browser.
+            _ if pref!(privacy.resist_fingerprinting.enabled) => {
+                default_user_agent_string_for(UserAgent::Generic).into()
+            },
             Some(ref ua) if ua == "ios" => default_user_agent_string_for(UserAgent::iOS).into(),
             Some(ref ua) if ua == "android" => {
                 default_user_agent_string_for(UserAgent::Android).into()
@@ -346,6 +370,7 @@ where
                         !opts.debug.disable_subpixel_text_antialiasing,
                     allow_texture_swizzling: pref!(gfx.texture_swizzling.enabled),
                     clear_color: None,
+                    thread_listener: Some(Box::new(WebRenderThreadListener)),
                     ..Default::default()
                 },
                 None,
@@ -396,6 +421,12 @@ where
             embedder.register_webxr(&mut webxr_main_thread, embedder_proxy.clone());
         }
 
+        let speech_synthesizer = if pref!(dom.speech_synthesis.enabled) {
+            embedder.speech_synthesizer()
+        } else {
+            None
+        };
+
         let wgpu_image_handler = webgpu::WGPUExternalImages::new();
         let wgpu_image_map = wgpu_image_handler.images.clone();
         external_image_handlers.set_handler(
@@ -420,7 +451,7 @@ where
 
         // Create the constellation, which maintains the engine pipelines, including script and
         // layout, as well as the navigation context.
-        let constellation_chan = create_constellation(
+        let (constellation_chan, font_cache_thread) = create_constellation(
             user_agent,
             opts.config_dir.clone(),
             embedder_proxy,
@@ -481,6 +512,8 @@ where
             embedder_receiver: embedder_receiver,
             messages_for_embedder: Vec::new(),
             profiler_enabled: false,
+            speech_synthesizer,
+            font_cache_thread,
             _js_engine_setup: js_engine_setup,
         };
         InitializedServo {
@@ -652,6 +685,10 @@ where
                 self.compositor.on_wheel_event(delta, location);
             },
 
+            EmbedderEvent::Drag(event_type, location) => {
+                self.compositor.on_drag_event(event_type, location);
+            },
+
             EmbedderEvent::Scroll(scroll_location, cursor, phase) => {
                 self.compositor
                     .on_scroll_event(scroll_location, cursor, phase);
@@ -798,6 +835,172 @@ where
                     warn!("Sending Gamepad event to constellation failed ({:?}).", e);
                 }
             },
+
+            EmbedderEvent::AddUserStylesheet(webview_id, stylesheet) => {
+                let msg = ConstellationMsg::AddUserStylesheet(webview_id, stylesheet);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending AddUserStylesheet to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::RemoveUserStylesheet(stylesheet_id) => {
+                let msg = ConstellationMsg::RemoveUserStylesheet(stylesheet_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending RemoveUserStylesheet to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::RegisterContentScript(webview_id, content_script) => {
+                let msg = ConstellationMsg::RegisterContentScript(webview_id, content_script);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending RegisterContentScript to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::UnregisterContentScript(content_script_id) => {
+                let msg = ConstellationMsg::UnregisterContentScript(content_script_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!(
+                        "Sending UnregisterContentScript to constellation failed ({:?}).",
+                        e
+                    );
+                }
+            },
+
+            EmbedderEvent::LoadExtension(extension) => {
+                let msg = ConstellationMsg::LoadExtension(extension);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending LoadExtension to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::UnloadExtension(extension_id) => {
+                let msg = ConstellationMsg::UnloadExtension(extension_id);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending UnloadExtension to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::MemoryPressure => {
+                servo_allocator::purge();
+                let msg = ConstellationMsg::MemoryPressure;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending MemoryPressure to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::InterceptRequests(enabled) => {
+                let msg = ConstellationMsg::InterceptRequests(enabled);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending InterceptRequests to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::StartHarRecording(include_bodies) => {
+                let msg = ConstellationMsg::StartHarRecording(include_bodies);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending StartHarRecording to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::StopHarRecording(path) => {
+                let msg = ConstellationMsg::StopHarRecording(path);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending StopHarRecording to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::ReportConnectionPool => {
+                let msg = ConstellationMsg::ReportConnectionPool;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending ReportConnectionPool to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::ReportContentProcesses => {
+                let msg = ConstellationMsg::ReportContentProcesses;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending ReportContentProcesses to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::ReportFrameTree => {
+                let msg = ConstellationMsg::ReportFrameTree;
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending ReportFrameTree to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::NetworkChanged(change) => {
+                let msg = ConstellationMsg::NetworkChanged(change);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending NetworkChanged to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetWorkOffline(work_offline) => {
+                let msg = ConstellationMsg::SetWorkOffline(work_offline);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetWorkOffline to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetForcedColors(forced_colors) => {
+                let msg = ConstellationMsg::SetForcedColors(forced_colors);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetForcedColors to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetUserAgentOverride(
+                top_level_browsing_context_id,
+                user_agent_override,
+            ) => {
+                let msg = ConstellationMsg::SetUserAgentOverride(
+                    top_level_browsing_context_id,
+                    user_agent_override,
+                );
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetUserAgentOverride to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetScriptPolicy(origin, script_policy) => {
+                let msg = ConstellationMsg::SetScriptPolicy(origin, script_policy);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetScriptPolicy to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::SetPermission(url, name, state) => {
+                let msg = ConstellationMsg::SetPermission(url, name, state);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending SetPermission to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::ExportElement(top_level_browsing_context_id, request) => {
+                let msg = ConstellationMsg::ExportElement(top_level_browsing_context_id, request);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending ExportElement to constellation failed ({:?}).", e);
+                }
+            },
+
+            EmbedderEvent::Prerender(top_level_browsing_context_id, request) => {
+                let msg = ConstellationMsg::Prerender(top_level_browsing_context_id, request);
+                if let Err(e) = self.constellation_chan.send(msg) {
+                    warn!("Sending Prerender to constellation failed ({:?}).", e);
+                }
+            },
         }
         return false;
     }
@@ -820,6 +1023,10 @@ where
                     self.messages_for_embedder.push(event);
                 },
 
+                (EmbedderMsg::SpeechSynthesis(msg), ShutdownState::NotShuttingDown) => {
+                    self.handle_speech_synthesis_msg(top_level_browsing_context, msg);
+                },
+
                 (msg, ShutdownState::NotShuttingDown) => {
                     self.messages_for_embedder
                         .push((top_level_browsing_context, msg));
@@ -828,6 +1035,51 @@ where
         }
     }
 
+    fn handle_speech_synthesis_msg(
+        &mut self,
+        webview: Option<TopLevelBrowsingContextId>,
+        msg: SpeechSynthesisMsg,
+    ) {
+        let synthesizer = match self.speech_synthesizer.as_mut() {
+            Some(synthesizer) => synthesizer,
+            None => {
+                match msg {
+                    SpeechSynthesisMsg::GetVoices(sender) => {
+                        let _ = sender.send(vec![]);
+                    },
+                    SpeechSynthesisMsg::Speak(_, events) => {
+                        let _ = events.send(SpeechSynthesisEvent::new(
+                            SpeechSynthesisEventKind::Error(
+                                SpeechSynthesisErrorCode::SynthesisUnavailable,
+                            ),
+                        ));
+                    },
+                    SpeechSynthesisMsg::Pause |
+                    SpeechSynthesisMsg::Resume |
+                    SpeechSynthesisMsg::Cancel => {},
+                }
+                return;
+            },
+        };
+        match msg {
+            SpeechSynthesisMsg::GetVoices(sender) => {
+                let _ = sender.send(synthesizer.voices());
+            },
+            SpeechSynthesisMsg::Speak(utterance, events) => {
+                synthesizer.speak(webview, utterance, events)
+            },
+            SpeechSynthesisMsg::Pause => synthesizer.pause(webview),
+            SpeechSynthesisMsg::Resume => synthesizer.resume(webview),
+            SpeechSynthesisMsg::Cancel => synthesizer.cancel(webview),
+        }
+    }
+
+    /// The font cache of this instance, for embedders shaping the text of their own user
+    /// interface with [`gfx::text::measure::measure_text`], which takes a `FontContext` of it.
+    pub fn font_cache_thread(&self) -> FontCacheThread {
+        self.font_cache_thread.clone()
+    }
+
     pub fn get_events(&mut self) -> Drain<'_, (Option<TopLevelBrowsingContextId>, EmbedderMsg)> {
         self.messages_for_embedder.drain(..)
     }
@@ -937,7 +1189,7 @@ fn create_constellation(
     initial_window_size: WindowSizeData,
     external_images: Arc<Mutex<WebrenderExternalImageRegistry>>,
     wgpu_image_map: Arc<Mutex<HashMap<u64, webgpu::PresentationData>>>,
-) -> Sender<ConstellationMsg> {
+) -> (Sender<ConstellationMsg>, FontCacheThread) {
     // Global configuration options, parsed from the command line.
     let opts = opts::get();
 
@@ -950,7 +1202,7 @@ fn create_constellation(
         time_profiler_chan.clone(),
         mem_profiler_chan.clone(),
         embedder_proxy.clone(),
-        config_dir,
+        config_dir.clone(),
         opts.certificate_path.clone(),
         opts.ignore_certificate_errors,
     );
@@ -958,6 +1210,7 @@ fn create_constellation(
     let font_cache_thread = FontCacheThread::new(
         public_resource_threads.sender(),
         Box::new(FontCacheWR(compositor_proxy.clone())),
+        opts.bundled_fonts_dir.clone(),
     );
 
     let (canvas_create_sender, canvas_ipc_sender) = CanvasPaintThread::start(
@@ -970,7 +1223,7 @@ fn create_constellation(
         embedder_proxy,
         devtools_sender,
         bluetooth_thread,
-        font_cache_thread,
+        font_cache_thread: font_cache_thread.clone(),
         public_resource_threads,
         private_resource_threads,
         time_profiler_chan,
@@ -982,6 +1235,7 @@ fn create_constellation(
         glplayer_threads,
         player_context,
         user_agent,
+        config_dir,
         webrender_external_images: external_images,
         wgpu_image_map,
     };
@@ -992,7 +1246,7 @@ fn create_constellation(
         Arc::new(layout_thread_2020::LayoutFactoryImpl())
     };
 
-    Constellation::<
+    let constellation_chan = Constellation::<
         script::script_thread::ScriptThread,
         script::serviceworker_manager::ServiceWorkerManager,
     >::start(
@@ -1006,7 +1260,9 @@ fn create_constellation(
         !opts.debug.disable_canvas_antialiasing,
         canvas_create_sender,
         canvas_ipc_sender,
-    )
+    );
+
+    (constellation_chan, font_cache_thread)
 }
 
 struct FontCacheWR(CompositorProxy);
@@ -1103,7 +1359,12 @@ pub fn run_content_process(token: String) {
         .send(unprivileged_content_sender)
         .unwrap();
 
-    let unprivileged_content = unprivileged_content_receiver.recv().unwrap();
+    let unprivileged_content = match unprivileged_content_receiver.recv() {
+        Ok(content) => content,
+        // This process was started ahead of time, and the constellation shut down before
+        // giving it content to run.
+        Err(_) => return,
+    };
     opts::set_options(unprivileged_content.opts());
     prefs::pref_map()
         .set_all(unprivileged_content.prefs())
@@ -1170,6 +1431,8 @@ enum UserAgent {
     Android,
     #[allow(non_camel_case_types)]
     iOS,
+    /// The user agent reported while resisting fingerprinting, whatever the platform.
+    Generic,
 }
 
 fn default_user_agent_string_for(agent: UserAgent) -> &'static str {
@@ -1200,6 +1463,9 @@ fn default_user_agent_string_for(agent: UserAgent) -> &'static str {
         UserAgent::iOS => {
             "Mozilla/5.0 (iPhone; CPU iPhone OS 16_4 like Mac OS X; rv:109.0) Servo/1.0 Firefox/111.0"
         },
+        UserAgent::Generic => {
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Servo/1.0 Firefox/111.0"
+        },
     }
 }
 
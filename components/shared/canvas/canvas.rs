@@ -37,6 +37,15 @@ pub struct CanvasImageData {
     pub image_key: ImageKey,
 }
 
+/// A frame of an `OffscreenCanvas` presented to the canvas element it took control from,
+/// which then renders the canvas with the given id through the given renderer.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PlaceholderCanvasFrame {
+    pub canvas_id: CanvasId,
+    pub ipc_renderer: IpcSender<CanvasMsg>,
+    pub size: Size2D<u64>,
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum Canvas2dMsg {
     Arc(Point2D<f32>, f32, f32, f32, bool),
@@ -48,23 +57,28 @@ pub enum Canvas2dMsg {
     BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
     ClearRect(Rect<f32>),
     Clip,
+    ClipPath(Vec<PathSegment>),
     ClosePath,
     Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
     Fill(FillOrStrokeStyle),
+    FillPath(Vec<PathSegment>, FillOrStrokeStyle),
     FillText(String, f64, f64, Option<f64>, FillOrStrokeStyle, bool),
     FillRect(Rect<f32>, FillOrStrokeStyle),
     GetImageData(Rect<u64>, Size2D<u64>, IpcBytesSender),
     GetTransform(IpcSender<Transform2D<f32>>),
     IsPointInPath(f64, f64, FillRule, IpcSender<bool>),
+    IsPointInPath2D(Vec<PathSegment>, f64, f64, FillRule, IpcSender<bool>),
     LineTo(Point2D<f32>),
     MoveTo(Point2D<f32>),
     PutImageData(Rect<u64>, IpcBytesReceiver),
     QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
     Rect(Rect<f32>),
+    RoundRect(Rect<f32>, CornerRadii),
     RestoreContext,
     SaveContext,
     StrokeRect(Rect<f32>, FillOrStrokeStyle),
     Stroke(FillOrStrokeStyle),
+    StrokePath(Vec<PathSegment>, FillOrStrokeStyle),
     SetLineWidth(f32),
     SetLineCap(LineCapStyle),
     SetLineJoin(LineJoinStyle),
@@ -81,6 +95,31 @@ pub enum Canvas2dMsg {
     SetTextBaseline(TextBaseline),
 }
 
+/// The radii of the corners of a rounded rectangle, horizontal then vertical, from the
+/// upper left corner clockwise.
+/// <https://html.spec.whatwg.org/multipage/#dom-context-2d-roundrect>
+pub type CornerRadii = [Size2D<f32>; 4];
+
+/// A segment of a `Path2D`, in the coordinate space of the path. The canvas paint thread
+/// flattens them into a path when the `Path2D` is drawn.
+/// <https://html.spec.whatwg.org/multipage/#path2d-objects>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum PathSegment {
+    Arc(Point2D<f32>, f32, f32, f32, bool),
+    ArcTo(Point2D<f32>, Point2D<f32>, f32),
+    BezierCurveTo(Point2D<f32>, Point2D<f32>, Point2D<f32>),
+    ClosePath,
+    Ellipse(Point2D<f32>, f32, f32, f32, f32, f32, bool),
+    LineTo(Point2D<f32>),
+    MoveTo(Point2D<f32>),
+    QuadraticCurveTo(Point2D<f32>, Point2D<f32>),
+    Rect(Rect<f32>),
+    RoundRect(Rect<f32>, CornerRadii),
+    /// The segments of another path, transformed by the given matrix.
+    /// <https://html.spec.whatwg.org/multipage/#dom-path2d-addpath>
+    AddPath(Vec<PathSegment>, Transform2D<f32>),
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub enum FromLayoutMsg {
     SendData(IpcSender<CanvasImageData>),
@@ -157,6 +196,31 @@ impl RadialGradientStyle {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ConicGradientStyle {
+    /// The angle, in radians clockwise from the positive x axis, the gradient starts at.
+    pub start_angle: f64,
+    pub x: f64,
+    pub y: f64,
+    pub stops: Vec<CanvasGradientStop>,
+}
+
+impl ConicGradientStyle {
+    pub fn new(
+        start_angle: f64,
+        x: f64,
+        y: f64,
+        stops: Vec<CanvasGradientStop>,
+    ) -> ConicGradientStyle {
+        ConicGradientStyle {
+            start_angle,
+            x,
+            y,
+            stops,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct SurfaceStyle {
     pub surface_data: ByteBuf,
@@ -186,6 +250,7 @@ pub enum FillOrStrokeStyle {
     Color(RGBA),
     LinearGradient(LinearGradientStyle),
     RadialGradient(RadialGradientStyle),
+    ConicGradient(ConicGradientStyle),
     Surface(SurfaceStyle),
 }
 
@@ -72,6 +72,8 @@ pub enum WebGLMsg {
     ResizeContext(WebGLContextId, Size2D<u32>, WebGLSender<Result<(), String>>),
     /// Drops a WebGLContext.
     RemoveContext(WebGLContextId),
+    /// Recreates a WebGLContext which was lost, in its initial state.
+    RestoreContext(WebGLContextId, Size2D<u32>, WebGLSender<Result<(), String>>),
     /// Runs a WebGLCommand in a specific WebGLContext.
     WebGLCommand(WebGLContextId, WebGLCommand, WebGLCommandBacktrace),
     /// Runs a WebXRCommand (WebXR layers need to be created in the WebGL
@@ -81,8 +83,13 @@ pub enum WebGLMsg {
     ///
     /// The third field contains the time (in ns) when the request
     /// was initiated. The u64 in the second field will be the time the
-    /// request is fulfilled
-    SwapBuffers(Vec<WebGLContextId>, WebGLSender<u64>, u64),
+    /// request is fulfilled, along with the contexts which were found
+    /// to be lost and were not swapped.
+    SwapBuffers(
+        Vec<WebGLContextId>,
+        WebGLSender<(u64, Vec<WebGLContextId>)>,
+        u64,
+    ),
     /// Frees all resources and closes the thread.
     Exit,
 }
@@ -173,6 +180,17 @@ impl WebGLMsgSender {
     pub fn send_remove(&self) -> WebGLSendResult {
         self.sender.send(WebGLMsg::RemoveContext(self.ctx_id))
     }
+
+    /// Send a restore message
+    #[inline]
+    pub fn send_restore(
+        &self,
+        size: Size2D<u32>,
+        sender: WebGLSender<Result<(), String>>,
+    ) -> WebGLSendResult {
+        self.sender
+            .send(WebGLMsg::RestoreContext(self.ctx_id, size, sender))
+    }
 }
 
 #[derive(Deserialize, Serialize)]
@@ -490,7 +508,7 @@ pub enum WebGLCommand {
     GetQueryState(WebGLSender<u32>, WebGLQueryId, u32),
     GenerateSampler(WebGLSender<WebGLSamplerId>),
     DeleteSampler(WebGLSamplerId),
-    BindSampler(u32, WebGLSamplerId),
+    BindSampler(u32, Option<WebGLSamplerId>),
     SetSamplerParameterFloat(WebGLSamplerId, u32, f32),
     SetSamplerParameterInt(WebGLSamplerId, u32, i32),
     GetSamplerParameterFloat(WebGLSamplerId, u32, WebGLSender<f32>),
@@ -696,8 +714,6 @@ pub struct ActiveUniformInfo {
     pub size: Option<i32>,
     /// The type of the uniform.
     pub type_: u32,
-    /// The index of the indexed uniform buffer binding, if it is bound.
-    pub bind_index: Option<u32>,
 }
 
 impl ActiveUniformInfo {
@@ -717,8 +733,11 @@ impl ActiveUniformInfo {
 pub struct ActiveUniformBlockInfo {
     /// The name of the uniform block.
     pub name: String,
-    /// The size of the uniform block.
+    /// The size of the data of the uniform block, as laid out by the shader, which the
+    /// buffer it reads from must have at least.
     pub size: i32,
+    /// The index of the indexed uniform buffer binding the uniform block reads from.
+    pub binding: u32,
 }
 
 macro_rules! parameters {
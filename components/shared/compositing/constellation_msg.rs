@@ -4,20 +4,26 @@
 
 use std::collections::HashMap;
 use std::fmt;
+use std::path::PathBuf;
 use std::time::Duration;
 
-use embedder_traits::Cursor;
+use embedder_traits::{
+    Cursor, CustomCursor, ElementExportRequest, NetworkChange, PermissionName, PermissionState,
+    PrerenderRequest, ScriptPolicy, UserAgentOverride,
+};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use msg::constellation_msg::{
     BrowsingContextId, PipelineId, TopLevelBrowsingContextId, TraversalDirection,
 };
+use script_traits::content_script::{ContentScript, ContentScriptId};
+use script_traits::extension::{Extension, ExtensionId};
 use script_traits::{
     AnimationTickType, CompositorEvent, GamepadEvent, LogEntry, MediaSessionActionType,
-    WebDriverCommandMsg, WindowSizeData, WindowSizeType,
+    UserStylesheet, UserStylesheetId, WebDriverCommandMsg, WindowSizeData, WindowSizeType,
 };
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 
 /// Messages to the constellation.
 pub enum ConstellationMsg {
@@ -68,6 +74,8 @@ pub enum ConstellationMsg {
     ForwardEvent(PipelineId, CompositorEvent),
     /// Requesting a change to the onscreen cursor.
     SetCursor(Cursor),
+    /// Requesting a change to an image cursor, with the cursor to use if it cannot be shown.
+    SetCustomCursor(CustomCursor, Cursor),
     /// Enable the sampling profiler, with a given sampling rate and max total sampling duration.
     EnableProfiler(Duration, Duration),
     /// Disable the sampling profiler.
@@ -84,6 +92,49 @@ pub enum ConstellationMsg {
     ReadyToPresent(TopLevelBrowsingContextId),
     /// Gamepad state has changed
     Gamepad(GamepadEvent),
+    /// Apply a user stylesheet to the given top-level browsing context, or to all of them.
+    AddUserStylesheet(Option<TopLevelBrowsingContextId>, UserStylesheet),
+    /// Stop applying a previously added user stylesheet.
+    RemoveUserStylesheet(UserStylesheetId),
+    /// Inject a content script into the documents of the given top-level browsing context,
+    /// or of all of them, that are created from now on.
+    RegisterContentScript(Option<TopLevelBrowsingContextId>, ContentScript),
+    /// Stop injecting a previously registered content script.
+    UnregisterContentScript(ContentScriptId),
+    /// Load an extension.
+    LoadExtension(Extension),
+    /// Unload a previously loaded extension.
+    UnloadExtension(ExtensionId),
+    /// Release as much memory as possible in every script thread.
+    MemoryPressure,
+    /// Start or stop letting the embedder intercept network requests.
+    InterceptRequests(bool),
+    /// Start recording the network activity, possibly with the response bodies.
+    StartHarRecording(bool),
+    /// Stop recording the network activity, and save it as a HAR file.
+    StopHarRecording(PathBuf),
+    /// Report the state of the HTTP connection pool to the embedder.
+    ReportConnectionPool,
+    /// Report the state of the content processes to the embedder.
+    ReportContentProcesses,
+    /// Report a snapshot of the frame tree to the embedder.
+    ReportFrameTree,
+    /// Close the open connections and let the pages know, as the network changed.
+    NetworkChanged(NetworkChange),
+    /// Start or stop working offline.
+    SetWorkOffline(bool),
+    /// Paint the pages in forced colors mode, or stop.
+    SetForcedColors(bool),
+    /// Override what a top-level browsing context tells about the user agent, or stop.
+    SetUserAgentOverride(TopLevelBrowsingContextId, Option<UserAgentOverride>),
+    /// Set what the script of an origin is allowed to do.
+    SetScriptPolicy(ImmutableOrigin, ScriptPolicy),
+    /// Set the state of a permission for the origin of the url.
+    SetPermission(ServoUrl, PermissionName, PermissionState),
+    /// Render an element of a top-level browsing context for the embedder.
+    ExportElement(TopLevelBrowsingContextId, ElementExportRequest),
+    /// Load a page in a top-level browsing context and prerender it for the embedder.
+    Prerender(TopLevelBrowsingContextId, PrerenderRequest),
 }
 
 impl fmt::Debug for ConstellationMsg {
@@ -111,6 +162,7 @@ impl fmt::Debug for ConstellationMsg {
             SendError(..) => "SendError",
             ForwardEvent(..) => "ForwardEvent",
             SetCursor(..) => "SetCursor",
+            SetCustomCursor(..) => "SetCustomCursor",
             EnableProfiler(..) => "EnableProfiler",
             DisableProfiler => "DisableProfiler",
             ExitFullScreen(..) => "ExitFullScreen",
@@ -120,6 +172,27 @@ impl fmt::Debug for ConstellationMsg {
             ClearCache => "ClearCache",
             ReadyToPresent(..) => "ReadyToPresent",
             Gamepad(..) => "Gamepad",
+            AddUserStylesheet(..) => "AddUserStylesheet",
+            RemoveUserStylesheet(..) => "RemoveUserStylesheet",
+            RegisterContentScript(..) => "RegisterContentScript",
+            UnregisterContentScript(..) => "UnregisterContentScript",
+            LoadExtension(..) => "LoadExtension",
+            UnloadExtension(..) => "UnloadExtension",
+            MemoryPressure => "MemoryPressure",
+            InterceptRequests(..) => "InterceptRequests",
+            StartHarRecording(..) => "StartHarRecording",
+            StopHarRecording(..) => "StopHarRecording",
+            ReportConnectionPool => "ReportConnectionPool",
+            ReportContentProcesses => "ReportContentProcesses",
+            ReportFrameTree => "ReportFrameTree",
+            NetworkChanged(..) => "NetworkChanged",
+            SetWorkOffline(..) => "SetWorkOffline",
+            SetForcedColors(..) => "SetForcedColors",
+            SetUserAgentOverride(..) => "SetUserAgentOverride",
+            SetScriptPolicy(..) => "SetScriptPolicy",
+            SetPermission(..) => "SetPermission",
+            ExportElement(..) => "ExportElement",
+            Prerender(..) => "Prerender",
         };
         write!(formatter, "ConstellationMsg::{}", variant)
     }
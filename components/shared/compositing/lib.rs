@@ -19,6 +19,7 @@ use log::warn;
 use msg::constellation_msg::{PipelineId, TopLevelBrowsingContextId};
 use net_traits::image::base::Image;
 use net_traits::NetToCompositorMsg;
+use profile_traits::mem::ReportsChan;
 use script_traits::{
     AnimationState, ConstellationControlMsg, EventResult, MouseButton, MouseEventType,
     ScriptToCompositorMsg,
@@ -52,6 +53,8 @@ pub enum CompositingReason {
     NewWebRenderScrollFrame,
     /// The window has been resized and will need to be synchronously repainted.
     Resize,
+    /// The rendering surface was recreated and the whole scene needs to be repainted.
+    SurfaceReinitialized,
 }
 
 /// Sends messages to the compositor.
@@ -114,6 +117,10 @@ pub enum CompositorMsg {
     TouchEventProcessed(EventResult),
     /// Composite to a PNG file and return the Image over a passed channel.
     CreatePng(Option<Rect<f32, CSSPixel>>, IpcSender<Option<Image>>),
+    /// Render the content at the given number of device pixels per CSS pixel for the images
+    /// composited with `CreatePng`, rather than at the device pixel ratio of the window, until
+    /// this is sent again with `None`. The window is not composited meanwhile.
+    SetCaptureScale(Option<f32>),
     /// A reply to the compositor asking if the output image is stable.
     IsReadyToSaveImageReply(bool),
     /// Pipeline visibility changed
@@ -149,6 +156,9 @@ pub enum CompositorMsg {
     /// Get screen available size.
     GetScreenAvailSize(IpcSender<DeviceIntSize>),
 
+    /// Measure the memory used by WebRender for the memory profiler.
+    CollectMemoryReport(ReportsChan),
+
     /// Messages forwarded to the compositor by the constellation from other crates. These
     /// messages are mainly passed on from the compositor to WebRender.
     Forwarded(ForwardedToCompositorMsg),
@@ -196,6 +206,7 @@ impl Debug for CompositorMsg {
             CompositorMsg::Recomposite(..) => write!(f, "Recomposite"),
             CompositorMsg::TouchEventProcessed(..) => write!(f, "TouchEventProcessed"),
             CompositorMsg::CreatePng(..) => write!(f, "CreatePng"),
+            CompositorMsg::SetCaptureScale(..) => write!(f, "SetCaptureScale"),
             CompositorMsg::IsReadyToSaveImageReply(..) => write!(f, "IsReadyToSaveImageReply"),
             CompositorMsg::PipelineVisibilityChanged(..) => write!(f, "PipelineVisibilityChanged"),
             CompositorMsg::PipelineExited(..) => write!(f, "PipelineExited"),
@@ -208,6 +219,7 @@ impl Debug for CompositorMsg {
             CompositorMsg::GetClientWindow(..) => write!(f, "GetClientWindow"),
             CompositorMsg::GetScreenSize(..) => write!(f, "GetScreenSize"),
             CompositorMsg::GetScreenAvailSize(..) => write!(f, "GetScreenAvailSize"),
+            CompositorMsg::CollectMemoryReport(..) => write!(f, "CollectMemoryReport"),
             CompositorMsg::Forwarded(..) => write!(f, "Webrender"),
         }
     }
@@ -227,6 +227,11 @@ pub enum DevtoolScriptControlMsg {
     RequestAnimationFrame(PipelineId, String),
     /// Direct the given pipeline to reload the current page.
     Reload(PipelineId),
+    /// Emulate network conditions for the tab of the given pipeline, or stop.
+    SetNetworkThrottling(PipelineId, Option<NetworkThrottling>),
+    /// Highlight the box model of the given node of the given pipeline above the page, or
+    /// stop highlighting if there is no node.
+    HighlightBoxModel(PipelineId, Option<String>),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -311,6 +316,28 @@ pub struct HttpRequest {
     pub connect_time: u64,
     pub send_time: u64,
     pub is_xhr: bool,
+    /// The resource hint that made the request, or warmed up the connection it was sent on.
+    pub resource_hint: Option<ResourceHint>,
+}
+
+/// A resource hint, given by a `<link>` element of one of these
+/// [link types](https://html.spec.whatwg.org/multipage/#linkTypes).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResourceHint {
+    DnsPrefetch,
+    Preconnect,
+    Prefetch,
+}
+
+impl ResourceHint {
+    /// The link type giving the hint.
+    pub fn name(self) -> &'static str {
+        match self {
+            ResourceHint::DnsPrefetch => "dns-prefetch",
+            ResourceHint::Preconnect => "preconnect",
+            ResourceHint::Prefetch => "prefetch",
+        }
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -370,3 +397,71 @@ impl PreciseTime {
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
 pub struct WorkerId(pub Uuid);
+
+/// Network conditions emulated for the requests of a tab, to see how pages perform on slower
+/// networks.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NetworkThrottling {
+    /// The delay before each request is sent, in milliseconds.
+    pub latency: u64,
+    /// The most bytes received per second, or zero for no limit.
+    pub download_throughput: u64,
+    /// The most bytes sent per second, or zero for no limit.
+    pub upload_throughput: u64,
+    /// Whether requests fail as if the device was offline.
+    pub offline: bool,
+}
+
+impl NetworkThrottling {
+    /// The throttling named `name`, among those the Firefox devtools offer.
+    pub fn preset(name: &str) -> Option<NetworkThrottling> {
+        const KBPS: u64 = 1024 / 8;
+        const MBPS: u64 = 1024 * KBPS;
+        let (latency, download_throughput, upload_throughput) = match name {
+            "offline" => {
+                return Some(NetworkThrottling {
+                    latency: 0,
+                    download_throughput: 0,
+                    upload_throughput: 0,
+                    offline: true,
+                })
+            },
+            "gprs" => (500, 50 * KBPS, 20 * KBPS),
+            "regular-2g" => (300, 250 * KBPS, 50 * KBPS),
+            "good-2g" => (150, 450 * KBPS, 150 * KBPS),
+            "regular-3g" => (100, 750 * KBPS, 250 * KBPS),
+            "good-3g" => (40, 3 * MBPS / 2, 750 * KBPS),
+            "regular-4g" => (20, 4 * MBPS, 3 * MBPS),
+            "dsl" => (5, 2 * MBPS, MBPS),
+            "wifi" => (2, 30 * MBPS, 15 * MBPS),
+            _ => return None,
+        };
+        Some(NetworkThrottling {
+            latency,
+            download_throughput,
+            upload_throughput,
+            offline: false,
+        })
+    }
+
+    pub fn latency(&self) -> Duration {
+        Duration::from_millis(self.latency)
+    }
+
+    /// How long receiving `bytes` takes.
+    pub fn download_time(&self, bytes: usize) -> Duration {
+        transfer_time(self.download_throughput, bytes)
+    }
+
+    /// How long sending `bytes` takes.
+    pub fn upload_time(&self, bytes: usize) -> Duration {
+        transfer_time(self.upload_throughput, bytes)
+    }
+}
+
+fn transfer_time(throughput: u64, bytes: usize) -> Duration {
+    if throughput == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(bytes as f64 / throughput as f64)
+}
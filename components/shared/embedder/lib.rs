@@ -4,16 +4,21 @@
 
 pub mod resources;
 
+use std::collections::HashMap;
 use std::fmt::{Debug, Error, Formatter};
+use std::time::Duration;
 
 use crossbeam_channel::{Receiver, Sender};
+use http::{HeaderMap, Method, StatusCode};
 use ipc_channel::ipc::IpcSender;
 use keyboard_types::KeyboardEvent;
 use log::warn;
-use msg::constellation_msg::{InputMethodType, PipelineId, TopLevelBrowsingContextId};
+use msg::constellation_msg::{
+    BrowsingContextId, InputMethodType, PipelineId, TopLevelBrowsingContextId,
+};
 use num_derive::FromPrimitive;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
 pub use webxr_api::MainThreadWaker as EventLoopWaker;
 
@@ -59,6 +64,66 @@ pub enum Cursor {
     ZoomOut,
 }
 
+/// An image to use as the cursor, from a `url()` of the CSS `cursor` property.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CustomCursor {
+    /// The width of the image in pixels.
+    pub width: u32,
+    /// The height of the image in pixels.
+    pub height: u32,
+    /// The horizontal position of the pointer in the image, in pixels from its left edge.
+    pub hotspot_x: u32,
+    /// The vertical position of the pointer in the image, in pixels from its top edge.
+    pub hotspot_y: u32,
+    /// The pixels of the image, row by row, as RGBA with straight alpha.
+    pub pixels: Vec<u8>,
+}
+
+/// The kinds of popups that web content can show.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum PopupKind {
+    /// The advisory information of the hovered element, from its `title` attribute.
+    Tooltip,
+    /// Why a form control failed interactive validation.
+    ValidationMessage,
+    /// The values suggested for a text field by its `list` attribute, one per line.
+    Suggestions,
+}
+
+/// A popup with some text, drawn by the embedder over web content next to an anchor.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Popup {
+    pub kind: PopupKind,
+    /// The text of the popup, whose lines are separated by line feeds.
+    pub text: String,
+    /// The area that the popup is about, in the coordinates of the viewport.
+    pub anchor: DeviceIntRect,
+    /// The line of the text to highlight, such as the suggestion selected with the keyboard.
+    pub selected_line: Option<usize>,
+}
+
+impl Popup {
+    /// Where to place the popup once its size is known: below its anchor, or above it when
+    /// there is no room below, and moved inside the viewport where it would overflow an edge.
+    pub fn origin(&self, size: DeviceIntSize, viewport: DeviceIntRect) -> DeviceIntPoint {
+        let y = if self.anchor.max_y() + size.height <= viewport.max_y() {
+            self.anchor.max_y()
+        } else if self.anchor.min_y() - size.height >= viewport.min_y() {
+            self.anchor.min_y() - size.height
+        } else {
+            self.anchor.max_y()
+        };
+        // Popups larger than the viewport keep their start visible.
+        let x = self
+            .anchor
+            .min_x()
+            .min(viewport.max_x() - size.width)
+            .max(viewport.min_x());
+        let y = y.min(viewport.max_y() - size.height).max(viewport.min_y());
+        DeviceIntPoint::new(x, y)
+    }
+}
+
 /// Sends messages to the embedder.
 pub struct EmbedderProxy {
     pub sender: Sender<(Option<TopLevelBrowsingContextId>, EmbedderMsg)>,
@@ -117,6 +182,37 @@ pub enum PromptDefinition {
     YesNo(String, IpcSender<PromptResult>),
     /// Ask the user to enter text.
     Input(String, String, IpcSender<Option<String>>),
+    /// Ask the user to pick the value of a form control, replying `None` when they dismiss
+    /// the picker without picking a value.
+    PickValue(InputPicker, IpcSender<Option<String>>),
+}
+
+/// The kinds of form controls whose value is picked with a picker drawn by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum InputPickerKind {
+    /// `<input type=date>`, whose value is a date such as `2024-02-29`.
+    Date,
+    /// `<input type=time>`, whose value is a time such as `13:37` or `13:37:00.5`.
+    Time,
+    /// `<input type=datetime-local>`, whose value is a date and time such as
+    /// `2024-02-29T13:37`.
+    DateTimeLocal,
+    /// `<input type=color>`, whose value is a lowercase color such as `#ff8000`.
+    Color,
+}
+
+/// A form control whose value the user is asked to pick.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct InputPicker {
+    pub kind: InputPickerKind,
+    /// The current value of the control, which is empty when it has none.
+    pub value: String,
+    /// The earliest value that the control accepts, from its `min` attribute.
+    pub min: Option<String>,
+    /// The latest value that the control accepts, from its `max` attribute.
+    pub max: Option<String>,
+    /// The area of the control, in the coordinates of the viewport.
+    pub anchor: DeviceIntRect,
 }
 
 #[derive(Deserialize, PartialEq, Serialize)]
@@ -172,10 +268,19 @@ pub enum EmbedderMsg {
     GetClipboardContents(IpcSender<String>),
     /// Sets system clipboard contents
     SetClipboardContents(String),
+    /// Gets the system clipboard contents in every format web content can read
+    GetClipboardData(IpcSender<Vec<ClipboardData>>),
+    /// Sets system clipboard contents, in one or more formats
+    SetClipboardData(Vec<ClipboardData>),
     /// Changes the cursor.
     SetCursor(Cursor),
+    /// Changes the cursor to an image. Embedders that cannot draw images as cursors
+    /// should use the given cursor instead.
+    SetCustomCursor(CustomCursor, Cursor),
     /// A favicon was detected
     NewFavicon(ServoUrl),
+    /// A favicon was fetched and decoded, at about 16 CSS pixels in device pixels.
+    FaviconLoaded(ServoUrl, FaviconImage),
     /// <head> tag finished parsing
     HeadParsed,
     /// The history state has changed.
@@ -214,6 +319,41 @@ pub enum EmbedderMsg {
     ReadyToPresent,
     /// The given event was delivered to a pipeline in the given browser.
     EventDelivered(CompositorEventVariant),
+    /// A network request is about to be sent. Only sent once the embedder has asked for
+    /// requests to be intercepted; the request waits until an answer is sent back.
+    WebResourceRequested(WebResourceRequest, IpcSender<WebResourceResponseMsg>),
+    /// The state of the HTTP connection pool, as asked for by the embedder.
+    ReportConnectionPool(ConnectionPoolReport),
+    /// The state of the content processes, as asked for by the embedder.
+    ReportContentProcesses(ContentProcessReport),
+    /// A document of the webview crashed, and was replaced by the crash page. Reloading the
+    /// webview loads the document again, keeping its session history.
+    WebViewCrashed(CrashReport),
+    /// A snapshot of the frame tree, as asked for by the embedder.
+    ReportFrameTree(FrameTreeSnapshot),
+    /// A field of a login or address form gained focus. The embedder may reply with values
+    /// for the fields, by index, which are filled in as if the user typed them.
+    AutofillRequested(AutofillForm, IpcSender<Vec<(usize, String)>>),
+    /// A field whose text must be kept from other applications, such as a password field,
+    /// gained focus if true, or lost it if false. Platforms can enable secure keyboard entry
+    /// meanwhile.
+    SecureTextInput(bool),
+    /// Show a popup over web content, replacing any popup of the same kind. Embedders that
+    /// cannot draw over web content may show tooltips as native tooltips instead.
+    ShowPopup(Popup),
+    /// Hide the popup of the given kind, if any.
+    HidePopup(PopupKind),
+    /// Web content started dragging the given data. Embedders that can start drags of the
+    /// platform may let the user drop it in other applications once it leaves the webview.
+    StartDrag(Vec<DragDataItem>),
+    /// A request of the speech synthesis of a document. Servo hands these to the
+    /// `SpeechSynthesizer` of the embedder rather than passing them on.
+    SpeechSynthesis(SpeechSynthesisMsg),
+    /// The result of the `ElementExportRequest` of the given id: the PNG image or the PDF
+    /// document of the element.
+    ElementExported(u64, Result<Vec<u8>, ElementExportError>),
+    /// The result of the `PrerenderRequest` of the given id.
+    Prerendered(u64, Result<PrerenderedPage, PrerenderError>),
 }
 
 /// The variant of CompositorEvent that was delivered to a pipeline.
@@ -228,6 +368,7 @@ pub enum CompositorEventVariant {
     CompositionEvent,
     IMEDismissedEvent,
     GamepadEvent,
+    DragEvent,
 }
 
 impl Debug for EmbedderMsg {
@@ -243,8 +384,12 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::Keyboard(..) => write!(f, "Keyboard"),
             EmbedderMsg::GetClipboardContents(..) => write!(f, "GetClipboardContents"),
             EmbedderMsg::SetClipboardContents(..) => write!(f, "SetClipboardContents"),
+            EmbedderMsg::GetClipboardData(..) => write!(f, "GetClipboardData"),
+            EmbedderMsg::SetClipboardData(..) => write!(f, "SetClipboardData"),
             EmbedderMsg::SetCursor(..) => write!(f, "SetCursor"),
+            EmbedderMsg::SetCustomCursor(..) => write!(f, "SetCustomCursor"),
             EmbedderMsg::NewFavicon(..) => write!(f, "NewFavicon"),
+            EmbedderMsg::FaviconLoaded(..) => write!(f, "FaviconLoaded"),
             EmbedderMsg::HeadParsed => write!(f, "HeadParsed"),
             EmbedderMsg::HistoryChanged(..) => write!(f, "HistoryChanged"),
             EmbedderMsg::SetFullscreenState(..) => write!(f, "SetFullscreenState"),
@@ -268,6 +413,19 @@ impl Debug for EmbedderMsg {
             EmbedderMsg::ShowContextMenu(..) => write!(f, "ShowContextMenu"),
             EmbedderMsg::ReadyToPresent => write!(f, "ReadyToPresent"),
             EmbedderMsg::EventDelivered(..) => write!(f, "HitTestedEvent"),
+            EmbedderMsg::WebResourceRequested(..) => write!(f, "WebResourceRequested"),
+            EmbedderMsg::ReportConnectionPool(..) => write!(f, "ReportConnectionPool"),
+            EmbedderMsg::ReportContentProcesses(..) => write!(f, "ReportContentProcesses"),
+            EmbedderMsg::ReportFrameTree(..) => write!(f, "ReportFrameTree"),
+            EmbedderMsg::WebViewCrashed(..) => write!(f, "WebViewCrashed"),
+            EmbedderMsg::AutofillRequested(..) => write!(f, "AutofillRequested"),
+            EmbedderMsg::SecureTextInput(..) => write!(f, "SecureTextInput"),
+            EmbedderMsg::ShowPopup(..) => write!(f, "ShowPopup"),
+            EmbedderMsg::HidePopup(..) => write!(f, "HidePopup"),
+            EmbedderMsg::StartDrag(..) => write!(f, "StartDrag"),
+            EmbedderMsg::SpeechSynthesis(..) => write!(f, "SpeechSynthesis"),
+            EmbedderMsg::ElementExported(..) => write!(f, "ElementExported"),
+            EmbedderMsg::Prerendered(..) => write!(f, "Prerendered"),
         }
     }
 }
@@ -339,8 +497,143 @@ pub enum MediaSessionEvent {
     SetPositionState(MediaPositionState),
 }
 
-/// Enum with variants that match the DOM PermissionName enum
+/// A voice of the speech synthesizer of the platform.
+/// <https://wicg.github.io/speech-api/#speechsynthesisvoice>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechSynthesisVoice {
+    /// The URI that identifies the voice, unique among the voices.
+    pub voice_uri: String,
+    pub name: String,
+    /// The BCP 47 language tag of the voice.
+    pub lang: String,
+    /// Whether the voice is synthesized on the device rather than by a remote service.
+    pub local_service: bool,
+    /// Whether it is the voice used when an utterance asks for none.
+    pub default: bool,
+}
+
+/// The text to speak, and how to speak it, as it was when it was queued.
+/// <https://wicg.github.io/speech-api/#speechsynthesisutterance>
 #[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechSynthesisUtterance {
+    pub text: String,
+    /// The BCP 47 language tag of the text, or empty for the language of the document.
+    pub lang: String,
+    /// The URI of the voice to speak with, or `None` for the default voice for `lang`.
+    pub voice_uri: Option<String>,
+    /// Between 0 and 1.
+    pub volume: f32,
+    /// Between 0.1 and 10, 1 being the normal rate of the voice.
+    pub rate: f32,
+    /// Between 0 and 2, 1 being the normal pitch of the voice.
+    pub pitch: f32,
+}
+
+/// <https://wicg.github.io/speech-api/#enumdef-speechsynthesiserrorcode>
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum SpeechSynthesisErrorCode {
+    Canceled,
+    Interrupted,
+    AudioBusy,
+    AudioHardware,
+    Network,
+    SynthesisUnavailable,
+    SynthesisFailed,
+    LanguageUnavailable,
+    VoiceUnavailable,
+    TextTooLong,
+    InvalidArgument,
+    NotAllowed,
+}
+
+/// What happened to an utterance as it was spoken.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum SpeechSynthesisEventKind {
+    Start,
+    /// The utterance was spoken to its end. No more events follow.
+    End,
+    Pause,
+    Resume,
+    /// The synthesizer reached the word starting at the character index of the event.
+    WordBoundary,
+    /// The synthesizer reached the sentence starting at the character index of the event.
+    SentenceBoundary,
+    /// The synthesizer reached the SSML mark of the given name.
+    Mark(String),
+    /// The utterance could not be spoken to its end. No more events follow.
+    Error(SpeechSynthesisErrorCode),
+}
+
+/// An event of an utterance being spoken, sent back to the document that queued it.
+/// <https://wicg.github.io/speech-api/#speechsynthesisevent>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SpeechSynthesisEvent {
+    pub kind: SpeechSynthesisEventKind,
+    /// The index in UTF-16 code units in the text of the utterance of what is being spoken.
+    pub char_index: u32,
+    /// The length in UTF-16 code units of the word or sentence reached, if known.
+    pub char_length: Option<u32>,
+    /// The time in seconds since the utterance started being spoken.
+    pub elapsed_time: f32,
+}
+
+impl SpeechSynthesisEvent {
+    pub fn new(kind: SpeechSynthesisEventKind) -> Self {
+        Self {
+            kind,
+            char_index: 0,
+            char_length: None,
+            elapsed_time: 0.,
+        }
+    }
+}
+
+/// The requests of the speech synthesis of a document.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum SpeechSynthesisMsg {
+    /// Get the voices the utterances can be spoken with.
+    GetVoices(IpcSender<Vec<SpeechSynthesisVoice>>),
+    /// Speak an utterance, sending its events to the given sender. Documents keep the queue
+    /// of their utterances, and only ask for the next one once the previous one ended.
+    Speak(SpeechSynthesisUtterance, IpcSender<SpeechSynthesisEvent>),
+    /// Pause the utterance being spoken.
+    Pause,
+    /// Resume the utterance that was paused.
+    Resume,
+    /// Stop speaking the utterance being spoken.
+    Cancel,
+}
+
+/// The text-to-speech engine of the platform, which the speech synthesis of web content
+/// is delegated to. Documents speak one utterance at a time, but different webviews may
+/// speak at once, which platforms that cannot do so can handle by interrupting the previous
+/// utterance.
+pub trait SpeechSynthesizer {
+    /// The voices of the engine.
+    fn voices(&mut self) -> Vec<SpeechSynthesisVoice>;
+
+    /// Speak `utterance` in `webview`. Every utterance must eventually get an `End` or an
+    /// `Error` event.
+    fn speak(
+        &mut self,
+        webview: Option<TopLevelBrowsingContextId>,
+        utterance: SpeechSynthesisUtterance,
+        events: IpcSender<SpeechSynthesisEvent>,
+    );
+
+    /// Pause the utterance being spoken in `webview`, if any.
+    fn pause(&mut self, webview: Option<TopLevelBrowsingContextId>);
+
+    /// Resume the utterance that was paused in `webview`, if any.
+    fn resume(&mut self, webview: Option<TopLevelBrowsingContextId>);
+
+    /// Stop speaking the utterance of `webview`, if any. Its document no longer listens to
+    /// its events.
+    fn cancel(&mut self, webview: Option<TopLevelBrowsingContextId>);
+}
+
+/// Enum with variants that match the DOM PermissionName enum
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PermissionName {
     Geolocation,
     Notifications,
@@ -353,6 +646,18 @@ pub enum PermissionName {
     BackgroundSync,
     Bluetooth,
     PersistentStorage,
+    ClipboardRead,
+    ClipboardWrite,
+    Gamepad,
+    Xr,
+}
+
+/// Enum with variants that match the DOM PermissionState enum
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    Prompt,
 }
 
 /// Information required to display a permission prompt
@@ -368,3 +673,530 @@ pub enum PermissionRequest {
     Granted,
     Denied,
 }
+
+/// The contents of the system clipboard in one of the formats web content can read and write.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ClipboardData {
+    /// Plain text, `text/plain` on the web.
+    Text(String),
+    /// An image, `image/png` on the web.
+    Image(ClipboardImage),
+}
+
+/// An image on the system clipboard.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ClipboardImage {
+    pub width: u32,
+    pub height: u32,
+    /// The pixels, in RGBA order, row by row.
+    pub rgba: Vec<u8>,
+}
+
+impl Debug for ClipboardImage {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "ClipboardImage({}x{})", self.width, self.height)
+    }
+}
+
+/// An item of the data that is dragged and dropped, between web content or from or to other
+/// applications.
+#[derive(Clone, Deserialize, PartialEq, Serialize)]
+pub enum DragDataItem {
+    /// A string, with the MIME type of its format, such as `text/plain` or `text/uri-list`.
+    Text { type_: String, data: String },
+    /// A file, with its name and MIME type.
+    File {
+        name: String,
+        type_: String,
+        bytes: Vec<u8>,
+    },
+}
+
+impl Debug for DragDataItem {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            DragDataItem::Text { type_, .. } => write!(f, "Text({})", type_),
+            DragDataItem::File { name, type_, bytes } => {
+                write!(f, "File({}, {}, {} bytes)", name, type_, bytes.len())
+            },
+        }
+    }
+}
+
+/// A decoded favicon.
+#[derive(Clone, Deserialize, Serialize)]
+pub struct FaviconImage {
+    pub width: u32,
+    pub height: u32,
+    /// The pixels, in RGBA order with straight alpha, row by row.
+    pub rgba: Vec<u8>,
+}
+
+impl Debug for FaviconImage {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "FaviconImage({}x{})", self.width, self.height)
+    }
+}
+
+/// A network request, as shown to an embedder that intercepts requests.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebResourceRequest {
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub method: Method,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+    pub url: ServoUrl,
+    /// Whether the request is for a document, rather than for one of its subresources.
+    pub is_for_main_frame: bool,
+    /// Whether the request follows a redirection.
+    pub is_redirect: bool,
+}
+
+/// The head of a response supplied by the embedder in place of the network one.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct WebResourceResponse {
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub status_code: StatusCode,
+    pub status_message: String,
+    #[serde(
+        deserialize_with = "::hyper_serde::deserialize",
+        serialize_with = "::hyper_serde::serialize"
+    )]
+    pub headers: HeaderMap,
+}
+
+impl WebResourceResponse {
+    pub fn new(status_code: StatusCode, headers: HeaderMap) -> WebResourceResponse {
+        WebResourceResponse {
+            status_code,
+            status_message: status_code.canonical_reason().unwrap_or("").into(),
+            headers,
+        }
+    }
+}
+
+/// What to do with an intercepted request.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WebResourceResponseMsg {
+    /// Send the request to the network as usual.
+    None,
+    /// Fail the request with a network error.
+    Block,
+    /// Load the given URL instead. This is done through a redirection, so that the new
+    /// URL goes through the same checks (CSP, mixed content, CORS) as any other.
+    Redirect(ServoUrl),
+    /// Answer the request with the given response, whose body is then sent in `Body`
+    /// messages. The response is handled as if it came from the network: it is subject
+    /// to the CORS checks, and may be stored in the HTTP cache.
+    Start(WebResourceResponse),
+    /// A part of the body of the response given with `Start`.
+    Body(HttpBodyData),
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub enum HttpBodyData {
+    Chunk(Vec<u8>),
+    Done,
+    Cancelled,
+}
+
+/// The state of the pool of connections the HTTP requests are sent on.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConnectionPoolReport {
+    /// How long a connection is kept open while no request uses it, if it is not kept
+    /// open until the server closes it.
+    pub idle_timeout: Option<Duration>,
+    /// The most connections kept open to an origin while no request uses them.
+    pub max_idle_per_host: usize,
+    /// The origins connected to so far, including those without open connections.
+    pub origins: Vec<OriginConnections>,
+}
+
+/// The connections to an origin.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OriginConnections {
+    pub origin: String,
+    /// The connections currently open.
+    pub open: Vec<OpenConnection>,
+    /// The connections opened so far, including the closed ones.
+    pub opened: u64,
+    /// The requests sent so far. Those beyond the connections opened reused a connection.
+    pub requests: u64,
+    /// The requests sent over HTTP/2, which sends every request to an origin on the same
+    /// connection.
+    pub http2_requests: u64,
+}
+
+/// A connection currently open.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct OpenConnection {
+    /// Whether HTTP/2 was negotiated for the connection.
+    pub http2: bool,
+    /// How long ago the connection was opened.
+    pub age: Duration,
+    /// How long ago the connection was last read from or written to.
+    pub idle: Duration,
+}
+
+/// A change of the connectivity of the device, which the embedder reports with
+/// `EmbedderEvent::NetworkChanged`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum NetworkChange {
+    /// The device lost its connectivity.
+    Down,
+    /// The device regained connectivity, over the given type of connection.
+    Up(ConnectionType),
+    /// The device switched to another network, over the given type of connection. The open
+    /// connections no longer lead anywhere, and the host names may resolve differently.
+    Changed(ConnectionType),
+}
+
+/// The connectivity of the device, as last reported by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct NetworkStatus {
+    pub online: bool,
+    pub connection_type: ConnectionType,
+}
+
+impl Default for NetworkStatus {
+    fn default() -> NetworkStatus {
+        NetworkStatus {
+            online: true,
+            connection_type: ConnectionType::Unknown,
+        }
+    }
+}
+
+impl NetworkStatus {
+    /// The status after `change`.
+    pub fn after(change: NetworkChange) -> NetworkStatus {
+        match change {
+            NetworkChange::Down => NetworkStatus {
+                online: false,
+                connection_type: ConnectionType::None,
+            },
+            NetworkChange::Up(connection_type) | NetworkChange::Changed(connection_type) => {
+                NetworkStatus {
+                    online: true,
+                    connection_type,
+                }
+            },
+        }
+    }
+}
+
+/// <https://wicg.github.io/netinfo/#connectiontype-enum>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ConnectionType {
+    Bluetooth,
+    Cellular,
+    Ethernet,
+    Mixed,
+    None,
+    Other,
+    Unknown,
+    Wifi,
+    Wimax,
+}
+
+/// A brand the user agent identifies as, with its version, in the `Sec-CH-UA` headers and
+/// in `navigator.userAgentData`.
+/// <https://wicg.github.io/ua-client-hints/#interface>
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct UserAgentBrand {
+    pub brand: String,
+    pub version: String,
+}
+
+/// What the pages of a top-level browsing context are told about the user agent instead of
+/// the defaults, which the embedder or WebDriver sets to test how sites treat other browsers.
+/// The values left unset keep their default.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct UserAgentOverride {
+    /// The `User-Agent` header and `navigator.userAgent`.
+    pub user_agent: Option<String>,
+    /// The brands, with their significant version.
+    pub brands: Option<Vec<UserAgentBrand>>,
+    /// The brands, with their full version.
+    pub full_version_list: Option<Vec<UserAgentBrand>>,
+    pub platform: Option<String>,
+    pub platform_version: Option<String>,
+    pub architecture: Option<String>,
+    pub bitness: Option<String>,
+    pub model: Option<String>,
+    pub mobile: Option<bool>,
+}
+
+/// What the embedder lets the script of an origin do, for origins it does not trust.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ScriptPolicy {
+    /// Run script as usual.
+    #[default]
+    Allow,
+    /// Run script in the interpreter only, without the JITs or WebAssembly.
+    DisableJit,
+    /// Run no script at all, as if scripting was disabled for the documents of the origin.
+    Block,
+}
+
+/// The script policies the embedder set, by origin. Origins without one are allowed to
+/// run script as usual.
+pub type ScriptPolicies = HashMap<ImmutableOrigin, ScriptPolicy>;
+
+/// The state of the event loops running the documents, and of the content processes they
+/// run in, in multiprocess mode.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ContentProcessReport {
+    /// The content processes started ahead of time, which no event loop runs in yet.
+    pub spare: usize,
+    /// The event loops kept running while they run no document, in case another document
+    /// of their site is loaded.
+    pub idle: usize,
+    /// The sites with event loops running, including the idle ones.
+    pub sites: Vec<SiteEventLoops>,
+    /// The event loops started so far.
+    pub started: u64,
+    /// The event loops which were started in a spare content process.
+    pub started_in_spare_process: u64,
+    /// The idle event loops which were reused, rather than starting a new one.
+    pub idle_reused: u64,
+    /// The idle event loops which were shut down, having run no document for too long.
+    pub idle_reaped: u64,
+    /// The times a browsing context group shared the event loop of another one, since its
+    /// site had as many event loops as allowed.
+    pub shared_over_site_cap: u64,
+}
+
+/// A document which crashed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CrashReport {
+    /// The URL of the document.
+    pub url: ServoUrl,
+    /// Whether the document was the top-level one of the webview, rather than that of an
+    /// iframe.
+    pub top_level: bool,
+    pub reason: CrashReason,
+}
+
+/// Why a document crashed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum CrashReason {
+    /// A thread running the document panicked, with this message and backtrace.
+    Panic(String, Option<String>),
+    /// The content process running the document exited unexpectedly.
+    ContentProcessExited,
+}
+
+/// The event loops running the documents of a site.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SiteEventLoops {
+    pub site: String,
+    pub event_loops: usize,
+}
+
+/// A snapshot of the frame tree of every webview, with what runs each document in it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FrameTreeSnapshot {
+    /// The top-level browsing contexts, one per webview.
+    pub top_level_browsing_contexts: Vec<BrowsingContextSnapshot>,
+    /// Whether the event loops run in content processes, rather than in this process.
+    pub multiprocess: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BrowsingContextSnapshot {
+    pub id: BrowsingContextId,
+    pub is_private: bool,
+    pub is_visible: bool,
+    /// The documents of the browsing context: the active one, those of the other session
+    /// history entries which are kept alive, and the one being loaded, if any.
+    pub pipelines: Vec<PipelineSnapshot>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PipelineSnapshot {
+    pub id: PipelineId,
+    pub url: ServoUrl,
+    pub title: String,
+    pub state: PipelineSnapshotState,
+    pub completely_loaded: bool,
+    /// Whether the document is the crash page, shown in place of one which crashed.
+    pub crashed: bool,
+    /// The event loop running the document. Documents sharing an event loop have the same
+    /// number.
+    pub event_loop: usize,
+    /// What the document uses of its event loop, if the event loop answered in time.
+    pub usage: Option<PipelineUsage>,
+    /// The browsing contexts nested in the document.
+    pub children: Vec<BrowsingContextSnapshot>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PipelineSnapshotState {
+    /// The document of the current session history entry of its browsing context.
+    Active,
+    /// The document of another session history entry, kept alive for traversals.
+    Inactive,
+    /// A document being loaded, to become the active one once it is ready.
+    Loading,
+}
+
+/// What a document uses of the event loop running it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PipelineUsage {
+    /// The id of the process the event loop runs in.
+    pub process_id: u32,
+    /// The memory taken up by the nodes of the document, in bytes.
+    pub dom_memory: usize,
+    /// The time the event loop spent running tasks for the document.
+    pub task_time: Duration,
+    /// How well the event loop keeps up with its tasks.
+    pub event_loop_health: EventLoopHealth,
+}
+
+/// How well an event loop keeps up with the tasks queued for it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct EventLoopHealth {
+    /// The number of tasks waiting to run.
+    pub queued_tasks: usize,
+    /// The time tasks recently waited in the queue before running, on average.
+    pub dequeue_latency: Duration,
+    /// Whether so many tasks are waiting that the net stack holds back the chunks of the
+    /// fetches of the event loop's documents.
+    pub saturated: bool,
+}
+
+/// What a form offered to the embedder for autofill is for.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum AutofillFormKind {
+    /// A form with a password field, to sign in or up.
+    Login,
+    /// A form with fields whose `autocomplete` tokens describe an address.
+    Address,
+}
+
+/// A field of a form the embedder can fill in.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AutofillField {
+    /// The index by which the embedder fills the field in.
+    pub index: usize,
+    /// The type of the input element, such as "email" or "password".
+    pub input_type: String,
+    /// The `name` attribute of the field.
+    pub name: String,
+    /// The `id` attribute of the field.
+    pub id: String,
+    /// The tokens of the `autocomplete` attribute of the field, lowercased.
+    pub autocomplete: Vec<String>,
+    /// The text of the labels of the field.
+    pub label: String,
+    /// The current value of the field.
+    pub value: String,
+}
+
+/// A form whose field gained focus, which the embedder may fill in, for instance with what
+/// a password manager saved.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AutofillForm {
+    pub kind: AutofillFormKind,
+    /// The URL of the document of the form.
+    pub url: ServoUrl,
+    /// Where the form is submitted, if it has an action.
+    pub action: Option<ServoUrl>,
+    /// The fields of the form which can be filled in.
+    pub fields: Vec<AutofillField>,
+    /// The index of the field which gained focus.
+    pub focused_field: usize,
+}
+
+/// What an element is exported to.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ElementExportFormat {
+    Png,
+    /// A PDF document with one page the size of the element, showing the element as an
+    /// image.
+    Pdf,
+}
+
+/// A request to render an element of a webview into an image or a document, answered with
+/// `EmbedderMsg::ElementExported`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ElementExportRequest {
+    /// Chosen by the embedder, to tell the result of this request from those of others.
+    pub id: u64,
+    /// A CSS selector for the element. The first element of the document matching it is
+    /// exported.
+    pub selector: String,
+    pub format: ElementExportFormat,
+    /// The number of pixels of the image per CSS pixel, or `None` for the device pixel
+    /// ratio of the webview.
+    pub scale: Option<f32>,
+}
+
+/// Why an element could not be exported.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum ElementExportError {
+    /// The webview is closed, or its document went away.
+    NoSuchWebView,
+    /// The selector is not a valid CSS selector.
+    InvalidSelector,
+    /// No element of the document matches the selector.
+    NoSuchElement,
+    /// The element has no box, or an empty one.
+    EmptyElement,
+    /// The element is larger than the viewport of the webview at the requested scale, so
+    /// it cannot be rendered whole.
+    DoesNotFit,
+    /// The page did not become stable enough to be rendered in time.
+    Timeout,
+    /// The image or the document could not be encoded.
+    EncodingFailed,
+}
+
+/// A request to load a page in a webview and, once it is quiescent, serialize its DOM or
+/// take a screenshot of it, answered with `EmbedderMsg::Prerendered`. This is how Servo
+/// is used to render pages on a server, usually in headless mode.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PrerenderRequest {
+    /// Chosen by the embedder, to tell the result of this request from those of others.
+    pub id: u64,
+    pub url: ServoUrl,
+    pub serialize_dom: bool,
+    pub screenshot: bool,
+    /// How long no fetch of the page must be in progress for it to be quiescent. An
+    /// animation frame must also have run since the last one ended.
+    pub network_idle: Duration,
+    /// How long to wait for the page to load and become quiescent before giving up.
+    pub timeout: Duration,
+}
+
+/// What a page was prerendered into.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PrerenderedPage {
+    /// The URL of the page, after redirects.
+    pub url: ServoUrl,
+    /// The markup of the document, if it was asked for.
+    pub dom: Option<String>,
+    /// A PNG image of the viewport, if it was asked for.
+    pub screenshot: Option<Vec<u8>>,
+}
+
+/// Why a page could not be prerendered.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PrerenderError {
+    /// The webview is closed, or its document went away.
+    NoSuchWebView,
+    /// The page did not load and become quiescent in time.
+    Timeout,
+    /// The document could not be serialized, or the screenshot could not be encoded.
+    SerializationFailed,
+}
@@ -67,6 +67,7 @@ pub enum Resource {
     ServoCSS,
     PresentationalHintsCSS,
     QuirksModeCSS,
+    FormControlsCSS,
     RippyPNG,
     MediaControlsCSS,
     MediaControlsJS,
@@ -86,6 +87,7 @@ impl Resource {
             Resource::ServoCSS => "servo.css",
             Resource::PresentationalHintsCSS => "presentational-hints.css",
             Resource::QuirksModeCSS => "quirks-mode.css",
+            Resource::FormControlsCSS => "form-controls.css",
             Resource::RippyPNG => "rippy.png",
             Resource::MediaControlsCSS => "media-controls.css",
             Resource::MediaControlsJS => "media-controls.js",
@@ -138,6 +140,9 @@ fn resources_for_tests() -> Box<dyn ResourceReaderMethods + Sync + Send> {
                 Resource::QuirksModeCSS => {
                     &include_bytes!("../../../resources/quirks-mode.css")[..]
                 },
+                Resource::FormControlsCSS => {
+                    &include_bytes!("../../../resources/form-controls.css")[..]
+                },
                 Resource::RippyPNG => &include_bytes!("../../../resources/rippy.png")[..],
                 Resource::MediaControlsCSS => {
                     &include_bytes!("../../../resources/media-controls.css")[..]
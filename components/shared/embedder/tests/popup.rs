@@ -0,0 +1,40 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use embedder_traits::{Popup, PopupKind};
+use webrender_api::units::{DeviceIntPoint, DeviceIntRect, DeviceIntSize};
+
+fn tooltip_at(x: i32, y: i32) -> Popup {
+    Popup {
+        kind: PopupKind::Tooltip,
+        text: "Tooltip".to_owned(),
+        anchor: DeviceIntRect::new(DeviceIntPoint::new(x, y), DeviceIntSize::new(1, 20)),
+        selected_line: None,
+    }
+}
+
+#[test]
+fn test_popup_is_placed_below_its_anchor() {
+    let viewport = DeviceIntRect::new(DeviceIntPoint::zero(), DeviceIntSize::new(800, 600));
+    let origin = tooltip_at(100, 100).origin(DeviceIntSize::new(200, 30), viewport);
+    assert_eq!(origin, DeviceIntPoint::new(100, 120));
+}
+
+#[test]
+fn test_popup_avoids_viewport_edges() {
+    let viewport = DeviceIntRect::new(DeviceIntPoint::zero(), DeviceIntSize::new(800, 600));
+    let size = DeviceIntSize::new(200, 30);
+
+    // There is no room below the anchor, so the popup goes above it.
+    let origin = tooltip_at(100, 590).origin(size, viewport);
+    assert_eq!(origin, DeviceIntPoint::new(100, 560));
+
+    // The popup is moved left so that it does not overflow the right edge.
+    let origin = tooltip_at(700, 100).origin(size, viewport);
+    assert_eq!(origin, DeviceIntPoint::new(600, 120));
+
+    // Popups wider than the viewport start at its left edge.
+    let origin = tooltip_at(700, 100).origin(DeviceIntSize::new(1000, 30), viewport);
+    assert_eq!(origin, DeviceIntPoint::new(0, 120));
+}
@@ -432,15 +432,18 @@ pub const TEST_BROWSING_CONTEXT_ID: BrowsingContextId = BrowsingContextId {
 
 // Used to specify the kind of input method editor appropriate to edit a field.
 // This is a subset of htmlinputelement::InputType because some variants of InputType
-// don't make sense in this context.
+// don't make sense in this context, extended with the keyboards that the inputmode
+// attribute can ask for.
 #[derive(Debug, Deserialize, Serialize)]
 pub enum InputMethodType {
     Color,
     Date,
     DatetimeLocal,
+    Decimal,
     Email,
     Month,
     Number,
+    Numeric,
     Password,
     Search,
     Tel,
@@ -0,0 +1,242 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The user-agent client hints: what the user agent tells sites about itself through the
+//! `Sec-CH-UA` request headers and `navigator.userAgentData`.
+//! <https://wicg.github.io/ua-client-hints/>
+
+use embedder_traits::{UserAgentBrand, UserAgentOverride};
+use serde::{Deserialize, Serialize};
+
+/// The version of the `Servo` brand when the user agent string does not tell it, which is
+/// the one of the default user agent strings.
+const DEFAULT_SERVO_VERSION: &str = "1.0";
+
+/// A brand that is not a real one, listed so that sites do not come to rely on an exact
+/// list of brands.
+/// <https://wicg.github.io/ua-client-hints/#grease>
+const GREASE_BRAND: &str = "Not_A Brand";
+const GREASE_VERSION: &str = "8";
+
+/// A user-agent client hint, which sites get as a request header.
+/// <https://wicg.github.io/ua-client-hints/#http-ua-hints>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ClientHint {
+    Ua,
+    UaArch,
+    UaBitness,
+    UaFullVersionList,
+    UaMobile,
+    UaModel,
+    UaPlatform,
+    UaPlatformVersion,
+}
+
+impl ClientHint {
+    pub const ALL: [ClientHint; 8] = [
+        ClientHint::Ua,
+        ClientHint::UaArch,
+        ClientHint::UaBitness,
+        ClientHint::UaFullVersionList,
+        ClientHint::UaMobile,
+        ClientHint::UaModel,
+        ClientHint::UaPlatform,
+        ClientHint::UaPlatformVersion,
+    ];
+
+    pub fn header_name(&self) -> &'static str {
+        match *self {
+            ClientHint::Ua => "sec-ch-ua",
+            ClientHint::UaArch => "sec-ch-ua-arch",
+            ClientHint::UaBitness => "sec-ch-ua-bitness",
+            ClientHint::UaFullVersionList => "sec-ch-ua-full-version-list",
+            ClientHint::UaMobile => "sec-ch-ua-mobile",
+            ClientHint::UaModel => "sec-ch-ua-model",
+            ClientHint::UaPlatform => "sec-ch-ua-platform",
+            ClientHint::UaPlatformVersion => "sec-ch-ua-platform-version",
+        }
+    }
+
+    /// The hint named by a token of an `Accept-CH` header, if it is one we support.
+    pub fn from_header_name(name: &str) -> Option<ClientHint> {
+        let name = name.trim();
+        ClientHint::ALL
+            .iter()
+            .find(|hint| hint.header_name().eq_ignore_ascii_case(name))
+            .copied()
+    }
+
+    /// Whether the hint tells so little about the user that it is sent to every secure
+    /// origin, rather than only to the ones that ask for it.
+    /// <https://wicg.github.io/ua-client-hints/#low-entropy-hint-table>
+    pub fn is_low_entropy(&self) -> bool {
+        matches!(
+            *self,
+            ClientHint::Ua | ClientHint::UaMobile | ClientHint::UaPlatform
+        )
+    }
+}
+
+/// Everything the user agent tells sites about itself.
+/// <https://wicg.github.io/ua-client-hints/#user-agent-metadata>
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserAgentMetadata {
+    pub brands: Vec<UserAgentBrand>,
+    pub full_version_list: Vec<UserAgentBrand>,
+    pub platform: String,
+    pub platform_version: String,
+    pub architecture: String,
+    pub bitness: String,
+    pub model: String,
+    pub mobile: bool,
+}
+
+impl UserAgentMetadata {
+    /// The metadata matching the user agent string `user_agent`, with the values of
+    /// `user_agent_override` instead, if any.
+    pub fn new(
+        user_agent: &str,
+        user_agent_override: Option<&UserAgentOverride>,
+    ) -> UserAgentMetadata {
+        let user_agent = user_agent_override
+            .and_then(|user_agent_override| user_agent_override.user_agent.as_deref())
+            .unwrap_or(user_agent);
+        let full_version = user_agent
+            .split_whitespace()
+            .find_map(|token| token.strip_prefix("Servo/"))
+            .unwrap_or(DEFAULT_SERVO_VERSION);
+        let significant_version = full_version.split('.').next().unwrap_or(full_version);
+        let brands = |version: &str, grease_version: &str| {
+            vec![
+                UserAgentBrand {
+                    brand: GREASE_BRAND.to_owned(),
+                    version: grease_version.to_owned(),
+                },
+                UserAgentBrand {
+                    brand: "Servo".to_owned(),
+                    version: version.to_owned(),
+                },
+            ]
+        };
+
+        let mut metadata = UserAgentMetadata {
+            brands: brands(significant_version, GREASE_VERSION),
+            full_version_list: brands(full_version, &format!("{}.0.0.0", GREASE_VERSION)),
+            platform: default_platform().to_owned(),
+            platform_version: String::new(),
+            architecture: default_architecture().to_owned(),
+            bitness: if cfg!(target_pointer_width = "64") {
+                "64".to_owned()
+            } else {
+                "32".to_owned()
+            },
+            model: String::new(),
+            mobile: user_agent.contains("Mobile"),
+        };
+
+        let user_agent_override = match user_agent_override {
+            Some(user_agent_override) => user_agent_override,
+            None => return metadata,
+        };
+        if let Some(ref brands) = user_agent_override.brands {
+            metadata.brands = brands.clone();
+        }
+        if let Some(ref full_version_list) = user_agent_override.full_version_list {
+            metadata.full_version_list = full_version_list.clone();
+        }
+        if let Some(ref platform) = user_agent_override.platform {
+            metadata.platform = platform.clone();
+        }
+        if let Some(ref platform_version) = user_agent_override.platform_version {
+            metadata.platform_version = platform_version.clone();
+        }
+        if let Some(ref architecture) = user_agent_override.architecture {
+            metadata.architecture = architecture.clone();
+        }
+        if let Some(ref bitness) = user_agent_override.bitness {
+            metadata.bitness = bitness.clone();
+        }
+        if let Some(ref model) = user_agent_override.model {
+            metadata.model = model.clone();
+        }
+        if let Some(mobile) = user_agent_override.mobile {
+            metadata.mobile = mobile;
+        }
+        metadata
+    }
+
+    /// The value of the request header of `hint`, as a structured field.
+    /// <https://wicg.github.io/ua-client-hints/#http-ua-hints>
+    pub fn header_value(&self, hint: ClientHint) -> String {
+        match hint {
+            ClientHint::Ua => serialize_brand_list(&self.brands),
+            ClientHint::UaArch => serialize_string(&self.architecture),
+            ClientHint::UaBitness => serialize_string(&self.bitness),
+            ClientHint::UaFullVersionList => serialize_brand_list(&self.full_version_list),
+            ClientHint::UaMobile => if self.mobile { "?1" } else { "?0" }.to_owned(),
+            ClientHint::UaModel => serialize_string(&self.model),
+            ClientHint::UaPlatform => serialize_string(&self.platform),
+            ClientHint::UaPlatformVersion => serialize_string(&self.platform_version),
+        }
+    }
+}
+
+fn default_platform() -> &'static str {
+    if cfg!(target_os = "android") {
+        "Android"
+    } else if cfg!(target_os = "ios") {
+        "iOS"
+    } else if cfg!(target_os = "macos") {
+        "macOS"
+    } else if cfg!(target_os = "windows") {
+        "Windows"
+    } else if cfg!(target_os = "linux") {
+        "Linux"
+    } else {
+        "Unknown"
+    }
+}
+
+fn default_architecture() -> &'static str {
+    if cfg!(any(target_arch = "x86", target_arch = "x86_64")) {
+        "x86"
+    } else if cfg!(any(target_arch = "arm", target_arch = "aarch64")) {
+        "arm"
+    } else {
+        ""
+    }
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc8941#name-serializing-a-string>
+fn serialize_string(value: &str) -> String {
+    let mut serialized = String::with_capacity(value.len() + 2);
+    serialized.push('"');
+    for character in value
+        .chars()
+        .filter(|c| c.is_ascii() && !c.is_ascii_control())
+    {
+        if character == '"' || character == '\\' {
+            serialized.push('\\');
+        }
+        serialized.push(character);
+    }
+    serialized.push('"');
+    serialized
+}
+
+/// A list of the brands as strings, with their version as a `v` parameter.
+/// <https://wicg.github.io/ua-client-hints/#sec-ch-ua>
+fn serialize_brand_list(brands: &[UserAgentBrand]) -> String {
+    brands
+        .iter()
+        .map(|brand| {
+            format!(
+                "{};v={}",
+                serialize_string(&brand.brand),
+                serialize_string(&brand.version)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,119 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The messages script sends to the file system thread, which keeps the origin private file
+//! system of each origin in a directory of its own.
+//! <https://fs.spec.whatwg.org/#sandboxed-filesystem>
+
+use ipc_channel::ipc::IpcSender;
+use malloc_size_of_derive::MallocSizeOf;
+use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
+
+/// <https://fs.spec.whatwg.org/#valid-file-name>
+///
+/// Names are checked again by the file system thread, as they end up in paths on disk.
+pub fn is_valid_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
+
+/// The kind of an entry of a file system.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum FileSystemEntryKind {
+    File,
+    Directory,
+}
+
+/// Why an operation on a file system failed.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum FileSystemError {
+    /// The entry, or one of the directories it is in, does not exist.
+    NotFound,
+    /// The entry is a file where a directory was expected, or the reverse.
+    TypeMismatch,
+    /// The directory to remove is not empty, and was not removed recursively.
+    InvalidModification,
+    /// The file, or a file in the directory, is locked by an access handle.
+    NoModificationAllowed,
+    /// The access handle was closed.
+    InvalidState,
+    /// A name is not a valid file name.
+    InvalidName,
+    /// Reading or writing the disk failed.
+    Io,
+    /// The write would take the storage of the origin over its quota.
+    QuotaExceeded,
+}
+
+pub type FileSystemResult<T> = Result<T, FileSystemError>;
+
+/// An access handle opened on a file, which keeps the file locked until it is closed.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct SyncAccessHandleId(pub u64);
+
+/// The entries of a file system are found by their path from its root directory, which is
+/// empty for the root itself.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum FileSystemThreadMsg {
+    /// Get the entry `name`, of `kind`, in the directory at `path`, creating it if it does not
+    /// exist and `create` is set.
+    GetEntry {
+        origin: ImmutableOrigin,
+        path: Vec<String>,
+        name: String,
+        kind: FileSystemEntryKind,
+        create: bool,
+        sender: IpcSender<FileSystemResult<()>>,
+    },
+    /// Remove the entry `name` in the directory at `path`, and what it holds if `recursive` is
+    /// set.
+    RemoveEntry {
+        origin: ImmutableOrigin,
+        path: Vec<String>,
+        name: String,
+        recursive: bool,
+        sender: IpcSender<FileSystemResult<()>>,
+    },
+    /// Get the contents of the file at a path, and when it was last modified, in milliseconds
+    /// since the epoch.
+    GetFile(
+        IpcSender<FileSystemResult<(Vec<u8>, i64)>>,
+        ImmutableOrigin,
+        Vec<String>,
+    ),
+    /// Open an access handle on the file at a path, unless another one is open on it.
+    CreateSyncAccessHandle(
+        IpcSender<FileSystemResult<SyncAccessHandleId>>,
+        ImmutableOrigin,
+        Vec<String>,
+    ),
+    /// Read at most the given number of bytes at an offset in the file.
+    Read(
+        IpcSender<FileSystemResult<Vec<u8>>>,
+        SyncAccessHandleId,
+        u64,
+        u64,
+    ),
+    /// Write bytes at an offset in the file, extending it as needed, and reply with how many
+    /// were written.
+    Write(
+        IpcSender<FileSystemResult<u64>>,
+        SyncAccessHandleId,
+        u64,
+        Vec<u8>,
+    ),
+    /// Resize the file, filling it with zeros if it grows.
+    Truncate(IpcSender<FileSystemResult<()>>, SyncAccessHandleId, u64),
+    /// Get the size of the file.
+    GetSize(IpcSender<FileSystemResult<u64>>, SyncAccessHandleId),
+    /// Write the changes to the file to the disk.
+    Flush(IpcSender<FileSystemResult<()>>, SyncAccessHandleId),
+    /// Close an access handle, unlocking its file.
+    Close(SyncAccessHandleId),
+    /// Remove the file system of an origin, closing its access handles, as when the storage of
+    /// the origin is evicted.
+    ClearOrigin(ImmutableOrigin),
+    /// Send a reply when done cleaning up thread resources and then shut it down.
+    Exit(IpcSender<()>),
+}
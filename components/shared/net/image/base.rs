@@ -2,7 +2,9 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use std::cmp::Reverse;
 use std::fmt;
+use std::ops::Range;
 
 use image::ImageFormat;
 use ipc_channel::ipc::IpcSharedMemory;
@@ -77,6 +79,82 @@ pub fn load_from_memory(buffer: &[u8], cors_status: CorsStatus) -> Option<Image>
     }
 }
 
+/// Like [`load_from_memory`], for images that are drawn in a square of `size` device pixels,
+/// such as favicons. Of the images in an ICO file, this decodes the one that needs the least
+/// downscaling to be drawn at that size instead of the largest one.
+pub fn load_from_memory_at_size(
+    buffer: &[u8],
+    cors_status: CorsStatus,
+    size: u32,
+) -> Option<Image> {
+    if !is_ico(buffer) {
+        return load_from_memory(buffer, cors_status);
+    }
+
+    let entries = ico_entries(buffer);
+    let best_entry = entries.iter().min_by_key(|entry| {
+        (
+            entry.size < size,
+            if entry.size >= size {
+                entry.size
+            } else {
+                u32::MAX - entry.size
+            },
+            Reverse(entry.bits_per_pixel),
+        )
+    })?;
+
+    // Decode an ICO file that only contains the chosen image.
+    let data = &buffer[best_entry.data.clone()];
+    let mut single_entry = Vec::with_capacity(ICO_HEADER_LENGTH + ICO_ENTRY_LENGTH + data.len());
+    single_entry.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x01, 0x00]);
+    single_entry.extend_from_slice(&best_entry.directory_entry[..12]);
+    single_entry.extend_from_slice(&((ICO_HEADER_LENGTH + ICO_ENTRY_LENGTH) as u32).to_le_bytes());
+    single_entry.extend_from_slice(data);
+    load_from_memory(&single_entry, cors_status)
+}
+
+const ICO_HEADER_LENGTH: usize = 6;
+const ICO_ENTRY_LENGTH: usize = 16;
+
+/// An image in the directory of an ICO file.
+struct IcoEntry<'a> {
+    directory_entry: &'a [u8],
+    /// The largest dimension of the image. Icons are usually square.
+    size: u32,
+    bits_per_pixel: u16,
+    /// Where the image data is in the file.
+    data: Range<usize>,
+}
+
+/// <https://learn.microsoft.com/en-us/previous-versions/ms997538(v=msdn.10)>
+fn ico_entries(buffer: &[u8]) -> Vec<IcoEntry> {
+    let count = match buffer.get(4..ICO_HEADER_LENGTH) {
+        Some(count) => u16::from_le_bytes([count[0], count[1]]) as usize,
+        None => return vec![],
+    };
+    (0..count)
+        .filter_map(|index| {
+            let start = ICO_HEADER_LENGTH + index * ICO_ENTRY_LENGTH;
+            let entry = buffer.get(start..start + ICO_ENTRY_LENGTH)?;
+            // A width or height of 0 means 256 pixels.
+            let dimension = |byte: u8| if byte == 0 { 256 } else { byte as u32 };
+            let length = u32::from_le_bytes([entry[8], entry[9], entry[10], entry[11]]) as usize;
+            let offset = u32::from_le_bytes([entry[12], entry[13], entry[14], entry[15]]) as usize;
+            let end = offset.checked_add(length)?;
+            if end > buffer.len() {
+                return None;
+            }
+            Some(IcoEntry {
+                directory_entry: entry,
+                size: dimension(entry[0]).max(dimension(entry[1])),
+                bits_per_pixel: u16::from_le_bytes([entry[6], entry[7]]),
+                data: offset..end,
+            })
+        })
+        .collect()
+}
+
 // https://developer.mozilla.org/en-US/docs/Web/HTML/Element/img
 pub fn detect_image_format(buffer: &[u8]) -> Result<ImageFormat, &str> {
     if is_gif(buffer) {
@@ -11,8 +11,8 @@ use serde::{Deserialize, Serialize};
 use servo_url::{ImmutableOrigin, ServoUrl};
 
 use crate::image::base::{Image, ImageMetadata};
-use crate::request::CorsSettings;
-use crate::{FetchResponseMsg, WebrenderIpcSender};
+use crate::request::{CorsSettings, RequestBuilder};
+use crate::{CoreResourceThread, FetchResponseMsg, WebrenderIpcSender};
 
 // ======================================================================
 // Aux structs and enums.
@@ -83,6 +83,20 @@ pub struct PendingImageResponse {
     pub id: PendingImageId,
 }
 
+/// Called with a decoded favicon, or with `None` if it could not be fetched or decoded.
+pub type FaviconCallback = Box<dyn FnOnce(Option<Arc<Image>>) + Send>;
+
+/// When to decode an image whose data has been fetched but that has not been decoded yet.
+/// <https://html.spec.whatwg.org/multipage/#image-decoding-hint>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum ImageDecoding {
+    /// Decode the image right away on the thread that asks for it, so that it is shown
+    /// along with the content around it.
+    Sync,
+    /// Leave the image to the decode pool, so that it does not delay the content around it.
+    Async,
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum UsePlaceholder {
     No,
@@ -119,6 +133,7 @@ pub trait ImageCache: Sync + Send {
         origin: ImmutableOrigin,
         cors_setting: Option<CorsSettings>,
         use_placeholder: UsePlaceholder,
+        decoding: ImageDecoding,
     ) -> ImageCacheResult;
 
     /// Add a listener for the provided pending image id, eventually called by
@@ -134,6 +149,7 @@ pub trait ImageCache: Sync + Send {
         cors_setting: Option<CorsSettings>,
         sender: IpcSender<PendingImageResponse>,
         use_placeholder: UsePlaceholder,
+        decoding: ImageDecoding,
     ) -> ImageCacheResult;
 
     /// Add a new listener for the given pending image id. If the image is already present,
@@ -142,6 +158,17 @@ pub trait ImageCache: Sync + Send {
 
     /// Inform the image cache about a response for a pending request.
     fn notify_pending_response(&self, id: PendingImageId, action: FetchResponseMsg);
+
+    /// Fetch a favicon and decode it to be drawn in a square of `size` device pixels. The
+    /// image closest to that size is chosen in ICO files, and SVG icons are rasterized at it.
+    /// Favicons are kept once decoded, so asking for the same one again does not fetch it.
+    fn fetch_favicon(
+        &self,
+        request: RequestBuilder,
+        size: u32,
+        core_resource_thread: &CoreResourceThread,
+        callback: FaviconCallback,
+    );
 }
 
 /// Whether this response passed any CORS checks, and is thus safe to read from
@@ -0,0 +1,472 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The messages script sends to the IndexedDB thread, and the keys, ranges and records they
+//! carry.
+//! <https://w3c.github.io/IndexedDB/>
+
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use ipc_channel::ipc::IpcSender;
+use malloc_size_of_derive::MallocSizeOf;
+use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
+
+/// A key of a record in an object store or an index.
+/// <https://w3c.github.io/IndexedDB/#key-construct>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub enum IndexedDBKey {
+    Number(f64),
+    /// The time value of a date, in milliseconds since the epoch.
+    Date(f64),
+    String(String),
+    Binary(Vec<u8>),
+    Array(Vec<IndexedDBKey>),
+}
+
+impl IndexedDBKey {
+    /// The rank of the type of the key, keys of a higher rank being greater than all the keys
+    /// of a lower one.
+    fn type_rank(&self) -> u8 {
+        match *self {
+            IndexedDBKey::Number(_) => 0,
+            IndexedDBKey::Date(_) => 1,
+            IndexedDBKey::String(_) => 2,
+            IndexedDBKey::Binary(_) => 3,
+            IndexedDBKey::Array(_) => 4,
+        }
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#compare-two-keys>
+impl Ord for IndexedDBKey {
+    fn cmp(&self, other: &IndexedDBKey) -> Ordering {
+        match (self, other) {
+            (IndexedDBKey::Number(a), IndexedDBKey::Number(b)) |
+            (IndexedDBKey::Date(a), IndexedDBKey::Date(b)) => {
+                // Keys are never NaN.
+                a.partial_cmp(b).unwrap_or(Ordering::Equal)
+            },
+            // Strings are compared by code units.
+            (IndexedDBKey::String(a), IndexedDBKey::String(b)) => {
+                a.encode_utf16().cmp(b.encode_utf16())
+            },
+            (IndexedDBKey::Binary(a), IndexedDBKey::Binary(b)) => a.cmp(b),
+            (IndexedDBKey::Array(a), IndexedDBKey::Array(b)) => a.cmp(b),
+            _ => self.type_rank().cmp(&other.type_rank()),
+        }
+    }
+}
+
+impl PartialOrd for IndexedDBKey {
+    fn partial_cmp(&self, other: &IndexedDBKey) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for IndexedDBKey {
+    fn eq(&self, other: &IndexedDBKey) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for IndexedDBKey {}
+
+/// The keys a range of records of an object store or an index is bounded by.
+/// <https://w3c.github.io/IndexedDB/#range-construct>
+#[derive(Clone, Debug, Default, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct IndexedDBKeyRange {
+    pub lower: Option<IndexedDBKey>,
+    pub upper: Option<IndexedDBKey>,
+    pub lower_open: bool,
+    pub upper_open: bool,
+}
+
+impl IndexedDBKeyRange {
+    /// The range of all the keys.
+    pub fn unbounded() -> IndexedDBKeyRange {
+        IndexedDBKeyRange::default()
+    }
+
+    /// The range of `key` alone.
+    /// <https://w3c.github.io/IndexedDB/#only>
+    pub fn only(key: IndexedDBKey) -> IndexedDBKeyRange {
+        IndexedDBKeyRange {
+            lower: Some(key.clone()),
+            upper: Some(key),
+            lower_open: false,
+            upper_open: false,
+        }
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#in>
+    pub fn contains(&self, key: &IndexedDBKey) -> bool {
+        let above_lower = match self.lower {
+            Some(ref lower) if self.lower_open => lower < key,
+            Some(ref lower) => lower <= key,
+            None => true,
+        };
+        let below_upper = match self.upper {
+            Some(ref upper) if self.upper_open => key < upper,
+            Some(ref upper) => key <= upper,
+            None => true,
+        };
+        above_lower && below_upper
+    }
+
+    /// Whether no key is in the range.
+    pub fn is_empty(&self) -> bool {
+        match (&self.lower, &self.upper) {
+            (Some(lower), Some(upper)) => {
+                lower > upper || (lower == upper && (self.lower_open || self.upper_open))
+            },
+            _ => false,
+        }
+    }
+
+    /// The bounds of the range, to look its keys up in an ordered collection. The range must
+    /// not be empty.
+    pub fn bounds(&self) -> (Bound<&IndexedDBKey>, Bound<&IndexedDBKey>) {
+        fn bound(key: &Option<IndexedDBKey>, open: bool) -> Bound<&IndexedDBKey> {
+            match *key {
+                Some(ref key) if open => Bound::Excluded(key),
+                Some(ref key) => Bound::Included(key),
+                None => Bound::Unbounded,
+            }
+        }
+        (
+            bound(&self.lower, self.lower_open),
+            bound(&self.upper, self.upper_open),
+        )
+    }
+}
+
+/// The property a key is taken from in the values of an object store or an index.
+/// <https://w3c.github.io/IndexedDB/#key-path-construct>
+#[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum KeyPath {
+    String(String),
+    Sequence(Vec<String>),
+}
+
+/// <https://w3c.github.io/IndexedDB/#transaction-mode>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum IndexedDBTxnMode {
+    Readonly,
+    Readwrite,
+    Versionchange,
+}
+
+/// <https://w3c.github.io/IndexedDB/#cursor-direction>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum CursorDirection {
+    Next,
+    NextUnique,
+    Prev,
+    PrevUnique,
+}
+
+/// <https://w3c.github.io/IndexedDB/#index-construct>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct IndexMetadata {
+    pub name: String,
+    pub key_path: KeyPath,
+    pub unique: bool,
+    pub multi_entry: bool,
+}
+
+/// <https://w3c.github.io/IndexedDB/#object-store-construct>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct ObjectStoreMetadata {
+    pub name: String,
+    pub key_path: Option<KeyPath>,
+    pub auto_increment: bool,
+    pub indexes: Vec<IndexMetadata>,
+}
+
+/// What a connection knows of its database: its version and its object stores.
+/// <https://w3c.github.io/IndexedDB/#database-construct>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct DatabaseMetadata {
+    pub name: String,
+    pub version: u64,
+    pub object_stores: Vec<ObjectStoreMetadata>,
+}
+
+/// A connection to a database, as numbered by the IndexedDB thread.
+/// <https://w3c.github.io/IndexedDB/#connection>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct ConnectionId(pub u64);
+
+/// A transaction of a connection. The upgrade transaction of a connection is numbered 0 by
+/// the IndexedDB thread, and script numbers the other ones from 1.
+/// <https://w3c.github.io/IndexedDB/#transaction-construct>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct TransactionId {
+    pub connection: ConnectionId,
+    pub serial: u64,
+}
+
+impl TransactionId {
+    pub fn upgrade(connection: ConnectionId) -> TransactionId {
+        TransactionId {
+            connection,
+            serial: 0,
+        }
+    }
+}
+
+/// The records an operation reads: the ones of an object store, or the ones of an index of
+/// an object store.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum RecordSource {
+    ObjectStore(String),
+    Index { store: String, index: String },
+}
+
+impl RecordSource {
+    /// The object store holding the records.
+    pub fn store(&self) -> &str {
+        match *self {
+            RecordSource::ObjectStore(ref store) => store,
+            RecordSource::Index { ref store, .. } => store,
+        }
+    }
+}
+
+/// A record read from an object store or an index. The key is the one of the index, and the
+/// same as the primary key when reading an object store.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct IndexedDBRecord {
+    pub key: IndexedDBKey,
+    pub primary_key: IndexedDBKey,
+    /// The serialized value, unless only the keys were asked for.
+    pub value: Option<Vec<u8>>,
+}
+
+/// Why an operation or a transaction failed, as the name of the `DOMException` script
+/// reports it with.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BackendError {
+    /// The operation would break a uniqueness constraint, or the key generator is exhausted.
+    Constraint,
+    /// The object store or the index the operation uses does not exist.
+    NotFound,
+    /// A key given to the operation is not one it can use.
+    Data,
+    /// The database is newer than the version asked for.
+    Version,
+    /// The transaction was aborted.
+    Abort,
+    /// The changes would take the storage of the origin over its quota.
+    QuotaExceeded,
+    /// The store could not be read or written.
+    Unknown(String),
+}
+
+pub type BackendResult<T> = Result<T, BackendError>;
+
+/// An operation run within a transaction.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum TransactionOperation {
+    /// <https://w3c.github.io/IndexedDB/#retrieve-a-value-from-an-object-store>
+    /// <https://w3c.github.io/IndexedDB/#retrieve-a-referenced-value-from-an-index>
+    Get {
+        source: RecordSource,
+        range: IndexedDBKeyRange,
+        keys_only: bool,
+    },
+    /// <https://w3c.github.io/IndexedDB/#retrieve-multiple-values-from-an-object-store>
+    GetAll {
+        source: RecordSource,
+        range: IndexedDBKeyRange,
+        count: Option<u32>,
+        keys_only: bool,
+    },
+    /// <https://w3c.github.io/IndexedDB/#count-the-records-in-a-range>
+    Count {
+        source: RecordSource,
+        range: IndexedDBKeyRange,
+    },
+    /// The record a cursor moves to, `count` records after the one at `position` if it has
+    /// one, and at or past `target` if given.
+    /// <https://w3c.github.io/IndexedDB/#iterate-a-cursor>
+    Iterate {
+        source: RecordSource,
+        range: IndexedDBKeyRange,
+        direction: CursorDirection,
+        position: Option<(IndexedDBKey, IndexedDBKey)>,
+        target: Option<(IndexedDBKey, Option<IndexedDBKey>)>,
+        count: u32,
+        keys_only: bool,
+    },
+    /// Store `value` at `key`, or at a key from the key generator of the object store, with
+    /// the keys of each of its indexes taken from the value by script.
+    /// <https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store>
+    Put {
+        store: String,
+        key: Option<IndexedDBKey>,
+        value: Vec<u8>,
+        index_keys: Vec<(String, Vec<IndexedDBKey>)>,
+        overwrite: bool,
+    },
+    /// <https://w3c.github.io/IndexedDB/#delete-records-from-an-object-store>
+    Delete {
+        store: String,
+        range: IndexedDBKeyRange,
+    },
+    /// <https://w3c.github.io/IndexedDB/#clear-an-object-store>
+    Clear(String),
+    /// <https://w3c.github.io/IndexedDB/#dom-idbdatabase-createobjectstore>
+    CreateObjectStore(ObjectStoreMetadata),
+    /// <https://w3c.github.io/IndexedDB/#dom-idbdatabase-deleteobjectstore>
+    DeleteObjectStore(String),
+    /// <https://w3c.github.io/IndexedDB/#ref-for-dom-idbobjectstore-name%E2%91%A2>
+    RenameObjectStore { from: String, to: String },
+    /// Create an index of `store`, with the keys script took from the value of each record
+    /// already in the store.
+    /// <https://w3c.github.io/IndexedDB/#dom-idbobjectstore-createindex>
+    CreateIndex {
+        store: String,
+        index: IndexMetadata,
+        keys: Vec<(IndexedDBKey, Vec<IndexedDBKey>)>,
+    },
+    /// <https://w3c.github.io/IndexedDB/#dom-idbobjectstore-deleteindex>
+    DeleteIndex { store: String, index: String },
+    /// <https://w3c.github.io/IndexedDB/#ref-for-dom-idbindex-name%E2%91%A2>
+    RenameIndex {
+        store: String,
+        from: String,
+        to: String,
+    },
+}
+
+/// What an operation gives back.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub enum OperationResult {
+    Record(Option<IndexedDBRecord>),
+    Records(Vec<IndexedDBRecord>),
+    Count(u64),
+    Key(IndexedDBKey),
+    Done,
+}
+
+/// What happens to a request to open a database.
+/// <https://w3c.github.io/IndexedDB/#open-a-database-connection>
+#[derive(Debug, Deserialize, Serialize)]
+pub enum OpenEvent {
+    /// Other connections are still open after being told of the version change.
+    Blocked {
+        old_version: u64,
+        new_version: u64,
+    },
+    /// The connection was made, and its upgrade transaction started.
+    UpgradeNeeded {
+        connection: ConnectionId,
+        old_version: u64,
+        metadata: DatabaseMetadata,
+    },
+    /// The connection was made to a database of the version asked for.
+    Success {
+        connection: ConnectionId,
+        metadata: DatabaseMetadata,
+    },
+    Error(BackendError),
+}
+
+/// What happens to a request to delete a database.
+/// <https://w3c.github.io/IndexedDB/#delete-a-database>
+#[derive(Debug, Deserialize, Serialize)]
+pub enum DeleteEvent {
+    /// Other connections are still open after being told of the deletion.
+    Blocked {
+        old_version: u64,
+    },
+    /// The database was deleted, and had this version.
+    Success {
+        old_version: u64,
+    },
+    Error(BackendError),
+}
+
+/// What an open connection is told about.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum ConnectionEvent {
+    /// Another connection asks to upgrade the database to `new_version`, or to delete it.
+    /// <https://w3c.github.io/IndexedDB/#fire-a-version-change-event>
+    VersionChange {
+        old_version: u64,
+        new_version: Option<u64>,
+    },
+    /// The connection was closed because the databases of the origin were cleared, aborting
+    /// its transactions.
+    /// <https://w3c.github.io/IndexedDB/#close-a-database-connection>
+    ForcedClose,
+}
+
+/// What happens to a transaction.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum TransactionEvent {
+    /// The operation `request` asked for completed.
+    RequestDone(u64, BackendResult<OperationResult>),
+    /// The changes of the transaction were written.
+    Complete,
+    /// The transaction failed, and its changes were undone.
+    Aborted(BackendError),
+}
+
+/// Request operations on the databases of an origin.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum IndexedDBThreadMsg {
+    /// Open a connection to the database `name`, upgrading or creating it in an upgrade
+    /// transaction if it is older than `version` or does not exist.
+    Open {
+        origin: ImmutableOrigin,
+        name: String,
+        version: Option<u64>,
+        request: IpcSender<OpenEvent>,
+        connection: IpcSender<ConnectionEvent>,
+        upgrade_transaction: IpcSender<TransactionEvent>,
+    },
+    /// Delete the database `name`.
+    DeleteDatabase {
+        origin: ImmutableOrigin,
+        name: String,
+        request: IpcSender<DeleteEvent>,
+    },
+    /// Get the names and versions of the databases of an origin.
+    Databases(IpcSender<Vec<(String, u64)>>, ImmutableOrigin),
+    /// Script fired the version change event at a connection, and closed it if the page
+    /// asked to.
+    VersionChangeHandled(ConnectionId),
+    /// Close a connection once its transactions are finished.
+    CloseConnection(ConnectionId),
+    /// Create a transaction over the object stores `scope`, which starts once the
+    /// transactions it would conflict with are finished.
+    CreateTransaction {
+        id: TransactionId,
+        scope: Vec<String>,
+        mode: IndexedDBTxnMode,
+        events: IpcSender<TransactionEvent>,
+    },
+    /// Run an operation in a transaction, after the ones before it, telling the transaction
+    /// about the result of `request`, if any.
+    Operation(TransactionId, Option<u64>, TransactionOperation),
+    /// Run an operation in a running transaction right away, replying with its result.
+    SyncOperation(
+        IpcSender<BackendResult<OperationResult>>,
+        TransactionId,
+        TransactionOperation,
+    ),
+    /// Write the changes of a transaction once its operations are done.
+    Commit(TransactionId),
+    /// Undo the changes of a transaction and drop its pending operations.
+    Abort(TransactionId),
+    /// Delete the databases of an origin, closing the connections to them, as when the storage
+    /// of the origin is evicted.
+    ClearOrigin(ImmutableOrigin),
+    /// Send a reply when done cleaning up thread resources and then shut it down.
+    Exit(IpcSender<()>),
+}
@@ -4,9 +4,12 @@
 
 #![deny(unsafe_code)]
 
+use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use cookie::Cookie;
+use devtools_traits::NetworkThrottling;
+use embedder_traits::{NetworkChange, UserAgentOverride};
 use headers::{ContentType, HeaderMapExt, ReferrerPolicy as ReferrerPolicyHeader};
 use http::{Error as HttpError, HeaderMap, StatusCode};
 use hyper::Error as HyperError;
@@ -19,7 +22,7 @@ use log::warn;
 use malloc_size_of::malloc_size_of_is_0;
 use malloc_size_of_derive::MallocSizeOf;
 use mime::Mime;
-use msg::constellation_msg::HistoryStateId;
+use msg::constellation_msg::{HistoryStateId, PipelineId};
 use num_traits::Zero;
 use rustls::Certificate;
 use serde::{Deserialize, Serialize};
@@ -27,18 +30,27 @@ use servo_rand::RngCore;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use webrender_api::{ImageData, ImageDescriptor, ImageKey};
 
+use crate::file_system_thread::FileSystemThreadMsg;
 use crate::filemanager_thread::FileManagerThreadMsg;
+use crate::indexeddb_thread::IndexedDBThreadMsg;
+use crate::reporting::Report;
 use crate::request::{Request, RequestBuilder};
 use crate::response::{HttpsState, Response, ResponseInit};
+use crate::server_timing::ServerTimingMetric;
 use crate::storage_thread::StorageThreadMsg;
 
 pub mod blob_url_store;
+pub mod client_hints;
+pub mod file_system_thread;
 pub mod filemanager_thread;
 pub mod image_cache;
+pub mod indexeddb_thread;
 pub mod pub_domains;
 pub mod quality;
+pub mod reporting;
 pub mod request;
 pub mod response;
+pub mod server_timing;
 pub mod storage_thread;
 
 /// Image handling.
@@ -105,6 +117,9 @@ impl CustomResponse {
 pub struct CustomResponseMediator {
     pub response_chan: IpcSender<Option<CustomResponse>>,
     pub load_url: ServoUrl,
+    /// Whether the request is a navigation, which the service worker may race with a
+    /// navigation preload request.
+    pub is_navigation: bool,
 }
 
 /// [Policies](https://w3c.github.io/webappsec-referrer-policy/#referrer-policy-states)
@@ -354,19 +369,104 @@ where
 pub struct ResourceThreads {
     core_thread: CoreResourceThread,
     storage_thread: IpcSender<StorageThreadMsg>,
+    idb_thread: IpcSender<IndexedDBThreadMsg>,
+    file_system_thread: IpcSender<FileSystemThreadMsg>,
 }
 
 impl ResourceThreads {
-    pub fn new(c: CoreResourceThread, s: IpcSender<StorageThreadMsg>) -> ResourceThreads {
+    pub fn new(
+        c: CoreResourceThread,
+        s: IpcSender<StorageThreadMsg>,
+        i: IpcSender<IndexedDBThreadMsg>,
+        f: IpcSender<FileSystemThreadMsg>,
+    ) -> ResourceThreads {
         ResourceThreads {
             core_thread: c,
             storage_thread: s,
+            idb_thread: i,
+            file_system_thread: f,
         }
     }
 
     pub fn clear_cache(&self) {
         let _ = self.core_thread.send(CoreResourceMsg::ClearCache);
     }
+
+    pub fn start_har_recording(&self, include_bodies: bool) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::StartHarRecording(include_bodies));
+    }
+
+    pub fn stop_har_recording(&self, path: PathBuf) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::StopHarRecording(path));
+    }
+
+    pub fn network_changed(&self, change: NetworkChange) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::NetworkChanged(change));
+    }
+
+    pub fn set_work_offline(&self, work_offline: bool) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::SetWorkOffline(work_offline));
+    }
+
+    pub fn throttle_network(
+        &self,
+        pipelines: Vec<PipelineId>,
+        throttling: Option<NetworkThrottling>,
+    ) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::ThrottleNetwork(pipelines, throttling));
+    }
+
+    pub fn override_user_agent(
+        &self,
+        pipelines: Vec<PipelineId>,
+        user_agent_override: Option<UserAgentOverride>,
+    ) {
+        let _ = self.core_thread.send(CoreResourceMsg::OverrideUserAgent(
+            pipelines,
+            user_agent_override,
+        ));
+    }
+
+    pub fn set_network_partition(
+        &self,
+        pipeline: PipelineId,
+        top_level_document: Option<TopLevelDocument>,
+    ) {
+        let _ = self.core_thread.send(CoreResourceMsg::SetNetworkPartition(
+            pipeline,
+            top_level_document,
+        ));
+    }
+
+    pub fn report_connection_pool(&self) {
+        let _ = self.core_thread.send(CoreResourceMsg::ReportConnectionPool);
+    }
+
+    /// The number of fetches in progress for the given pipelines, or `None` if the resource
+    /// thread is gone. Blocks until the resource thread answers.
+    pub fn count_fetches_in_progress(&self, pipelines: Vec<PipelineId>) -> Option<usize> {
+        let (sender, receiver) = ipc::channel().ok()?;
+        self.core_thread
+            .send(CoreResourceMsg::CountFetchesInProgress(pipelines, sender))
+            .ok()?;
+        receiver.recv().ok()
+    }
+
+    pub fn intercept_requests(&self, enabled: bool) {
+        let _ = self
+            .core_thread
+            .send(CoreResourceMsg::InterceptRequests(enabled));
+    }
 }
 
 impl IpcSend<CoreResourceMsg> for ResourceThreads {
@@ -389,6 +489,26 @@ impl IpcSend<StorageThreadMsg> for ResourceThreads {
     }
 }
 
+impl IpcSend<IndexedDBThreadMsg> for ResourceThreads {
+    fn send(&self, msg: IndexedDBThreadMsg) -> IpcSendResult {
+        self.idb_thread.send(msg)
+    }
+
+    fn sender(&self) -> IpcSender<IndexedDBThreadMsg> {
+        self.idb_thread.clone()
+    }
+}
+
+impl IpcSend<FileSystemThreadMsg> for ResourceThreads {
+    fn send(&self, msg: FileSystemThreadMsg) -> IpcSendResult {
+        self.file_system_thread.send(msg)
+    }
+
+    fn sender(&self) -> IpcSender<FileSystemThreadMsg> {
+        self.file_system_thread.clone()
+    }
+}
+
 // Ignore the sub-fields
 malloc_size_of_is_0!(ResourceThreads);
 
@@ -412,7 +532,11 @@ pub enum WebSocketDomAction {
 
 #[derive(Debug, Deserialize, Serialize)]
 pub enum WebSocketNetworkEvent {
-    ConnectionEstablished { protocol_in_use: Option<String> },
+    ConnectionEstablished {
+        protocol_in_use: Option<String>,
+        /// The extensions in use, as the server listed them.
+        extensions: String,
+    },
     MessageReceived(MessageData),
     Close(Option<u16>, String),
     Fail,
@@ -434,6 +558,16 @@ pub enum FetchChannels {
     Prefetch,
 }
 
+/// The top-level document a pipeline belongs to, by whose site the network state used by
+/// the requests of the pipeline is partitioned.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum TopLevelDocument {
+    /// The pipeline is the top-level one, whose document is at the URL it navigates to.
+    Itself,
+    /// The pipeline is nested in a top-level document at this URL.
+    At(ServoUrl),
+}
+
 #[derive(Debug, Deserialize, Serialize)]
 pub enum CoreResourceMsg {
     Fetch(RequestBuilder, FetchChannels),
@@ -467,6 +601,48 @@ pub enum CoreResourceMsg {
     Synchronize(IpcSender<()>),
     /// Clear the network cache.
     ClearCache,
+    /// Start or stop asking the embedder about requests before sending them.
+    InterceptRequests(bool),
+    /// Resolve the host of a URL before the given pipeline requests it, for a `dns-prefetch`
+    /// hint.
+    DnsPrefetch(ServoUrl, PipelineId),
+    /// Open a connection to the origin of a URL before the given pipeline requests it, for a
+    /// `preconnect` hint.
+    Preconnect(ServoUrl, PipelineId),
+    /// The network changed, so the open connections no longer lead anywhere and must be
+    /// closed rather than reused, and the idempotent requests they fail may be retried.
+    NetworkChanged(NetworkChange),
+    /// Start or stop working offline, answering requests from the HTTP cache only.
+    SetWorkOffline(bool),
+    /// Emulate the given network conditions for the requests of the given pipelines, or
+    /// stop emulating them.
+    ThrottleNetwork(Vec<PipelineId>, Option<NetworkThrottling>),
+    /// Hold back the chunks of the responses to the requests of the given pipelines while
+    /// the task queue of their event loop is saturated, or stop holding them back.
+    SetBackpressure(Vec<PipelineId>, bool),
+    /// Tell the sites about the user agent what the override says in the requests of the
+    /// given pipelines, or stop overriding the defaults.
+    OverrideUserAgent(Vec<PipelineId>, Option<UserAgentOverride>),
+    /// Partition the network state used by the requests of the given pipeline by the site of
+    /// its top-level document, or forget about the pipeline.
+    SetNetworkPartition(PipelineId, Option<TopLevelDocument>),
+    /// Send the state of the HTTP connection pool to the embedder.
+    ReportConnectionPool,
+    /// Send the number of fetches in progress for the given pipelines, not counting those
+    /// for `prefetch` hints.
+    CountFetchesInProgress(Vec<PipelineId>, IpcSender<usize>),
+    /// Start recording the network activity, with the response bodies if the flag is set.
+    StartHarRecording(bool),
+    /// Stop recording the network activity, and save the recording as a HAR file.
+    StopHarRecording(PathBuf),
+    /// Deliver a report to an endpoint, along with the other reports queued for it shortly
+    /// after.
+    QueueReport(Report, ServoUrl),
+    /// Send the crash reports of the given pipeline to this endpoint, or stop sending them.
+    SetCrashReportEndpoint(PipelineId, Option<ServoUrl>),
+    /// The document of the given pipeline, at this URL, crashed, so a crash report is sent to
+    /// its endpoint, if it set one.
+    QueueCrashReport(PipelineId, ServoUrl),
     /// Send the service worker network mediator for an origin to CoreResourceThread
     NetworkMediator(IpcSender<CustomResponseMediator>, ImmutableOrigin),
     /// Message forwarded to file manager's handler
@@ -519,6 +695,9 @@ pub struct ResourceFetchTiming {
     pub connect_start: u64,
     pub connect_end: u64,
     pub start_time: u64,
+    /// The metrics of the `Server-Timing` header, which are only kept when the timing check
+    /// passed.
+    pub server_timing: Vec<ServerTimingMetric>,
 }
 
 pub enum RedirectStartValue {
@@ -581,6 +760,7 @@ impl ResourceFetchTiming {
             connect_end: 0,
             response_end: 0,
             start_time: 0,
+            server_timing: vec![],
         }
     }
 
@@ -651,6 +831,7 @@ impl ResourceFetchTiming {
         self.redirect_start = 0;
         self.connect_start = 0;
         self.connect_end = 0;
+        self.server_timing.clear();
     }
 }
 
@@ -0,0 +1,160 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The reports that documents and workers send to the endpoints their `Reporting-Endpoints`
+//! header configured, about the deprecated features they use, the interventions of the user
+//! agent and their crashes.
+//! <https://w3c.github.io/reporting/>
+
+use std::collections::HashMap;
+
+use http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use servo_url::{ImmutableOrigin, ServoUrl};
+
+/// The name of the endpoint that the reports the user agent generates are sent to.
+/// <https://wicg.github.io/crash-reporting/#crash-report>
+pub const DEFAULT_ENDPOINT: &str = "default";
+
+/// A report, as it is sent to an endpoint.
+/// <https://w3c.github.io/reporting/#concept-reports>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Report {
+    /// The type of the report, such as `deprecation`.
+    pub type_: String,
+    /// The URL of the document or worker the report is about.
+    pub url: ServoUrl,
+    pub user_agent: String,
+    /// The body of the report, serialized as a JSON object.
+    pub body: String,
+    /// When the report was generated, in milliseconds since the epoch.
+    pub timestamp: u64,
+}
+
+impl Report {
+    /// The origin that sends the report, whose credentials go with it to the endpoints of
+    /// the same origin.
+    pub fn origin(&self) -> ImmutableOrigin {
+        self.url.origin()
+    }
+}
+
+/// The endpoints, by name, that the `Reporting-Endpoints` header of a response for `url`
+/// configures. Only secure documents can configure endpoints, and only secure ones.
+/// <https://w3c.github.io/reporting/#process-header>
+pub fn parse_reporting_endpoints(headers: &HeaderMap, url: &ServoUrl) -> HashMap<String, ServoUrl> {
+    let mut endpoints = HashMap::new();
+    if !url.is_potentially_trustworthy() {
+        return endpoints;
+    }
+    // The values of several headers are combined into one structured field.
+    let values: Option<Vec<&str>> = headers
+        .get_all("reporting-endpoints")
+        .iter()
+        .map(|value| value.to_str().ok())
+        .collect();
+    let value = match values {
+        Some(values) => values.join(","),
+        None => return endpoints,
+    };
+    let members = match parse_dictionary(&value) {
+        Some(members) => members,
+        None => return endpoints,
+    };
+    for (name, endpoint) in members {
+        // Members whose value is not a string are ignored.
+        let endpoint = match endpoint {
+            Some(endpoint) => endpoint,
+            None => continue,
+        };
+        let endpoint = match url.join(&endpoint) {
+            Ok(endpoint) if endpoint.is_potentially_trustworthy() => endpoint,
+            _ => continue,
+        };
+        endpoints.insert(name, endpoint);
+    }
+    endpoints
+}
+
+/// The members of a structured field dictionary, with their value if it is a string. The
+/// parameters of the members are ignored, and `None` is returned if the field is not a
+/// dictionary.
+/// <https://www.rfc-editor.org/rfc/rfc8941#name-parsing-a-dictionary>
+fn parse_dictionary(value: &str) -> Option<Vec<(String, Option<String>)>> {
+    let mut members = vec![];
+    let value = value.trim_matches(|c| c == ' ' || c == '\t');
+    if value.is_empty() {
+        return Some(members);
+    }
+    for member in split_outside_strings(value, ',') {
+        let member = member.trim_matches(|c| c == ' ' || c == '\t');
+        let name_end = member
+            .find(|c| c == '=' || c == ';')
+            .unwrap_or(member.len());
+        let name = &member[..name_end];
+        if !is_key(name) {
+            return None;
+        }
+        // A member without a value is the boolean true.
+        let value = member[name_end..].strip_prefix('=').and_then(|value| {
+            let bare_item = split_outside_strings(value, ';').next().unwrap_or(value);
+            parse_string(bare_item)
+        });
+        // Later members replace the earlier ones of the same name.
+        members.retain(|(other, _)| other != name);
+        members.push((name.to_owned(), value));
+    }
+    Some(members)
+}
+
+/// <https://www.rfc-editor.org/rfc/rfc8941#name-parsing-a-key>
+fn is_key(name: &str) -> bool {
+    let mut chars = name.chars();
+    let first = match chars.next() {
+        Some(first) => first,
+        None => return false,
+    };
+    (first.is_ascii_lowercase() || first == '*') &&
+        chars.all(|c| {
+            c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.' | '*')
+        })
+}
+
+/// The string a structured field string item is, or `None` if it is not one.
+/// <https://www.rfc-editor.org/rfc/rfc8941#name-parsing-a-string>
+fn parse_string(item: &str) -> Option<String> {
+    let inner = item.strip_prefix('"')?.strip_suffix('"')?;
+    let mut string = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ ('\\' | '"')) => string.push(escaped),
+                _ => return None,
+            },
+            '"' => return None,
+            c if c.is_ascii() && !c.is_ascii_control() => string.push(c),
+            _ => return None,
+        }
+    }
+    Some(string)
+}
+
+/// Split `value` at the occurrences of `separator` that are not in a string.
+fn split_outside_strings(value: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut in_string = false;
+    let mut escaped = false;
+    value.split(move |c| {
+        if escaped {
+            escaped = false;
+        } else if in_string && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_string = !in_string;
+        } else if c == separator && !in_string {
+            return true;
+        }
+        false
+    })
+}
@@ -8,6 +8,7 @@ use content_security_policy::{self as csp, CspList};
 use http::header::{HeaderName, AUTHORIZATION};
 use http::{HeaderMap, Method};
 use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use ipc_channel::router::ROUTER;
 use malloc_size_of_derive::MallocSizeOf;
 use mime::Mime;
 use msg::constellation_msg::PipelineId;
@@ -24,9 +25,18 @@ pub enum Initiator {
     Download,
     ImageSet,
     Manifest,
+    Prefetch,
     XSLT,
 }
 
+/// A request [priority](https://fetch.spec.whatwg.org/#request-priority)
+#[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub enum RequestPriority {
+    High,
+    Low,
+    Auto,
+}
+
 /// A request [destination](https://fetch.spec.whatwg.org/#concept-request-destination)
 pub use csp::Destination;
 
@@ -210,8 +220,37 @@ impl RequestBody {
     pub fn len(&self) -> Option<usize> {
         self.total_bytes.clone()
     }
+
+    /// A body of `bytes`, which are sent in one chunk, again after each redirect. The bytes
+    /// are served by the process this is called in.
+    pub fn from_bytes(bytes: Vec<u8>) -> RequestBody {
+        let total_bytes = bytes.len();
+        let (chunk_request_sender, chunk_request_receiver) = ipc::channel().unwrap();
+        serve_request_body(chunk_request_receiver, bytes);
+        RequestBody::new(chunk_request_sender, BodySource::Object, Some(total_bytes))
+    }
 }
 
+/// Answer the fetch asking for the bytes of a body, again after each redirect.
+fn serve_request_body(receiver: IpcReceiver<BodyChunkRequest>, bytes: Vec<u8>) {
+    ROUTER.add_route(
+        receiver.to_opaque(),
+        Box::new(move |message| match message.to() {
+            Ok(BodyChunkRequest::Connect(sender)) => {
+                let _ = sender.send(BodyChunkResponse::Chunk(bytes.clone()));
+                let _ = sender.send(BodyChunkResponse::Done);
+            },
+            Ok(BodyChunkRequest::Extract(receiver)) => serve_request_body(receiver, bytes.clone()),
+            _ => {},
+        }),
+    );
+}
+
+/// The most bytes the bodies of the keep-alive requests of a document can add up to while
+/// they are in flight.
+/// <https://fetch.spec.whatwg.org/#http-network-or-cache-fetch>
+pub const KEEPALIVE_QUOTA: usize = 64 * 1024;
+
 #[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub struct RequestBuilder {
     #[serde(
@@ -229,6 +268,8 @@ pub struct RequestBuilder {
     pub headers: HeaderMap,
     pub unsafe_request: bool,
     pub body: Option<RequestBody>,
+    /// <https://fetch.spec.whatwg.org/#request-keepalive-flag>
+    pub keep_alive: bool,
     pub service_workers_mode: ServiceWorkersMode,
     // TODO: client object
     pub destination: Destination,
@@ -254,6 +295,7 @@ pub struct RequestBuilder {
     pub url_list: Vec<ServoUrl>,
     pub parser_metadata: ParserMetadata,
     pub initiator: Initiator,
+    pub priority: RequestPriority,
     pub https_state: HttpsState,
     pub response_tainting: ResponseTainting,
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
@@ -268,6 +310,7 @@ impl RequestBuilder {
             headers: HeaderMap::new(),
             unsafe_request: false,
             body: None,
+            keep_alive: false,
             service_workers_mode: ServiceWorkersMode::All,
             destination: Destination::None,
             synchronous: false,
@@ -285,6 +328,7 @@ impl RequestBuilder {
             url_list: vec![],
             parser_metadata: ParserMetadata::Default,
             initiator: Initiator::None,
+            priority: RequestPriority::Auto,
             csp_list: None,
             https_state: HttpsState::None,
             response_tainting: ResponseTainting::Basic,
@@ -297,6 +341,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn priority(mut self, priority: RequestPriority) -> RequestBuilder {
+        self.priority = priority;
+        self
+    }
+
     pub fn method(mut self, method: Method) -> RequestBuilder {
         self.method = method;
         self
@@ -317,6 +366,11 @@ impl RequestBuilder {
         self
     }
 
+    pub fn keep_alive(mut self, keep_alive: bool) -> RequestBuilder {
+        self.keep_alive = keep_alive;
+        self
+    }
+
     pub fn destination(mut self, destination: Destination) -> RequestBuilder {
         self.destination = destination;
         self
@@ -401,10 +455,12 @@ impl RequestBuilder {
             self.https_state,
         );
         request.initiator = self.initiator;
+        request.priority = self.priority;
         request.method = self.method;
         request.headers = self.headers;
         request.unsafe_request = self.unsafe_request;
         request.body = self.body;
+        request.keep_alive = self.keep_alive;
         request.service_workers_mode = self.service_workers_mode;
         request.destination = self.destination;
         request.synchronous = self.synchronous;
@@ -459,7 +515,8 @@ pub struct Request {
     pub initiator: Initiator,
     /// <https://fetch.spec.whatwg.org/#concept-request-destination>
     pub destination: Destination,
-    // TODO: priority object
+    /// <https://fetch.spec.whatwg.org/#request-priority>
+    pub priority: RequestPriority,
     /// <https://fetch.spec.whatwg.org/#concept-request-origin>
     pub origin: Origin,
     /// <https://fetch.spec.whatwg.org/#concept-request-referrer>
@@ -523,6 +580,7 @@ impl Request {
             service_workers_mode: ServiceWorkersMode::All,
             initiator: Initiator::None,
             destination: Destination::None,
+            priority: RequestPriority::Auto,
             origin: origin.unwrap_or(Origin::Client),
             referrer: referrer,
             referrer_policy: None,
@@ -0,0 +1,114 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The metrics that servers tell about their handling of a request through the
+//! `Server-Timing` response header, which pages get on the resource timing entries.
+//! <https://w3c.github.io/server-timing/>
+
+use http::HeaderMap;
+use malloc_size_of_derive::MallocSizeOf;
+use serde::{Deserialize, Serialize};
+
+/// A metric of the `Server-Timing` header.
+/// <https://w3c.github.io/server-timing/#the-performanceservertiming-interface>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, PartialEq, Serialize)]
+pub struct ServerTimingMetric {
+    pub name: String,
+    /// The duration in milliseconds, or 0 if the server did not tell it.
+    pub duration: f64,
+    pub description: String,
+}
+
+/// The metrics of the `Server-Timing` headers of a response, in order. The metrics whose
+/// name is not a token are ignored, as are the unknown parameters and the repeated ones.
+/// <https://w3c.github.io/server-timing/#the-server-timing-header-field>
+pub fn parse_server_timing(headers: &HeaderMap) -> Vec<ServerTimingMetric> {
+    let mut metrics = vec![];
+    for value in headers.get_all("server-timing") {
+        let value = match value.to_str() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        for metric in split_outside_quoted_strings(value, ',') {
+            let mut parts = split_outside_quoted_strings(metric, ';');
+            let name = parts.next().unwrap_or_default().trim_matches(is_ows);
+            if !is_token(name) {
+                continue;
+            }
+            let mut duration = None;
+            let mut description = None;
+            for param in parts {
+                let (param_name, param_value) = match param.split_once('=') {
+                    Some((param_name, param_value)) => (param_name, param_value),
+                    None => (param, ""),
+                };
+                let param_value = unquote(param_value.trim_matches(is_ows));
+                match &*param_name.trim_matches(is_ows).to_ascii_lowercase() {
+                    "dur" if duration.is_none() => {
+                        duration = Some(param_value.parse::<f64>().unwrap_or(0.))
+                    },
+                    "desc" if description.is_none() => description = Some(param_value),
+                    _ => {},
+                }
+            }
+            metrics.push(ServerTimingMetric {
+                name: name.to_owned(),
+                duration: duration
+                    .filter(|duration| duration.is_finite())
+                    .unwrap_or(0.),
+                description: description.unwrap_or_default(),
+            });
+        }
+    }
+    metrics
+}
+
+fn is_ows(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// <https://httpwg.org/specs/rfc9110.html#tokens>
+fn is_token(value: &str) -> bool {
+    !value.is_empty() &&
+        value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+/// The content of `value` if it is a quoted string, or `value` itself if it is a token.
+/// <https://httpwg.org/specs/rfc9110.html#quoted.strings>
+fn unquote(value: &str) -> String {
+    let inner = match value.strip_prefix('"') {
+        Some(inner) => inner,
+        None => return value.to_owned(),
+    };
+    let mut string = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => string.extend(chars.next()),
+            '"' => break,
+            c => string.push(c),
+        }
+    }
+    string
+}
+
+/// Split `value` at the occurrences of `separator` that are not in a quoted string.
+fn split_outside_quoted_strings(value: &str, separator: char) -> impl Iterator<Item = &str> {
+    let mut in_string = false;
+    let mut escaped = false;
+    value.split(move |c| {
+        if escaped {
+            escaped = false;
+        } else if in_string && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            in_string = !in_string;
+        } else if c == separator && !in_string {
+            return true;
+        }
+        false
+    })
+}
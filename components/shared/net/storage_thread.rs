@@ -2,10 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use embedder_traits::{PermissionName, PermissionState};
 use ipc_channel::ipc::IpcSender;
 use malloc_size_of_derive::MallocSizeOf;
 use serde::{Deserialize, Serialize};
-use servo_url::ServoUrl;
+use servo_url::{ImmutableOrigin, ServoUrl};
 
 #[derive(Clone, Copy, Debug, Deserialize, MallocSizeOf, Serialize)]
 pub enum StorageType {
@@ -43,6 +44,24 @@ pub enum StorageThreadMsg {
     /// clears the associated storage data by removing all the key/value pairs
     Clear(IpcSender<bool>, ServoUrl, StorageType),
 
+    /// gets the state of a permission for the origin of the given url, as kept across sessions
+    GetPermission(IpcSender<PermissionState>, ServoUrl, PermissionName),
+
+    /// sets the state of a permission for the origin of the given url
+    SetPermission(ServoUrl, PermissionName, PermissionState),
+
+    /// gets the bytes the storage of the origin of the given url takes, and its quota
+    Estimate(IpcSender<(u64, u64)>, ServoUrl),
+
+    /// makes the storage of the origin of the given url persist, sending whether it does
+    Persist(IpcSender<bool>, ServoUrl),
+
+    /// gets whether the storage of the origin of the given url persists
+    Persisted(IpcSender<bool>, ServoUrl),
+
+    /// removes the local storage data of an origin, as when the storage of the origin is evicted
+    ClearOrigin(ImmutableOrigin),
+
     /// send a reply when done cleaning up thread resources and then shut it down
     Exit(IpcSender<()>),
 }
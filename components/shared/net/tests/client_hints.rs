@@ -0,0 +1,56 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use embedder_traits::{UserAgentBrand, UserAgentOverride};
+use net_traits::client_hints::{ClientHint, UserAgentMetadata};
+
+const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Servo/1.0 Firefox/111.0";
+
+#[test]
+fn test_brands_follow_the_servo_version_of_the_user_agent() {
+    let metadata = UserAgentMetadata::new(USER_AGENT, None);
+    assert_eq!(
+        metadata.header_value(ClientHint::Ua),
+        r#""Not_A Brand";v="8", "Servo";v="1""#
+    );
+    assert_eq!(
+        metadata.header_value(ClientHint::UaFullVersionList),
+        r#""Not_A Brand";v="8.0.0.0", "Servo";v="1.0""#
+    );
+    assert_eq!(metadata.header_value(ClientHint::UaMobile), "?0");
+}
+
+#[test]
+fn test_override_replaces_the_defaults() {
+    let user_agent_override = UserAgentOverride {
+        brands: Some(vec![UserAgentBrand {
+            brand: "Quoted \"Brand\"".to_owned(),
+            version: "42".to_owned(),
+        }]),
+        platform: Some("Android".to_owned()),
+        mobile: Some(true),
+        ..UserAgentOverride::default()
+    };
+    let metadata = UserAgentMetadata::new(USER_AGENT, Some(&user_agent_override));
+    assert_eq!(
+        metadata.header_value(ClientHint::Ua),
+        r#""Quoted \"Brand\"";v="42""#
+    );
+    assert_eq!(
+        metadata.header_value(ClientHint::UaPlatform),
+        r#""Android""#
+    );
+    assert_eq!(metadata.header_value(ClientHint::UaMobile), "?1");
+}
+
+#[test]
+fn test_client_hint_header_names() {
+    assert_eq!(
+        ClientHint::from_header_name(" Sec-CH-UA-Full-Version-List "),
+        Some(ClientHint::UaFullVersionList)
+    );
+    assert_eq!(ClientHint::from_header_name("Sec-CH-Viewport-Width"), None);
+    assert!(ClientHint::UaPlatform.is_low_entropy());
+    assert!(!ClientHint::UaModel.is_low_entropy());
+}
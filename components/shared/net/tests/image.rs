@@ -2,7 +2,11 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use net_traits::image::base::detect_image_format;
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageOutputFormat, RgbaImage};
+use net_traits::image::base::{detect_image_format, load_from_memory, load_from_memory_at_size};
+use net_traits::image_cache::CorsStatus;
 
 #[test]
 fn test_supported_images() {
@@ -26,3 +30,58 @@ fn test_supported_images() {
     assert!(detect_image_format(&ico).is_ok());
     assert!(detect_image_format(&junk_format).is_err());
 }
+
+fn png(size: u32) -> Vec<u8> {
+    let mut png = vec![];
+    DynamicImage::ImageRgba8(RgbaImage::new(size, size))
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .unwrap();
+    png
+}
+
+/// An ICO file with a PNG of each of the given sizes.
+fn ico(sizes: &[u32]) -> Vec<u8> {
+    let images: Vec<_> = sizes.iter().map(|size| png(*size)).collect();
+    let mut ico = vec![0x00, 0x00, 0x01, 0x00, sizes.len() as u8, 0x00];
+    let mut offset = 6 + 16 * sizes.len();
+    for (size, image) in sizes.iter().zip(&images) {
+        // Sizes of 256 pixels are written as 0.
+        let dimension = *size as u8;
+        ico.extend_from_slice(&[dimension, dimension, 0, 0, 1, 0, 32, 0]);
+        ico.extend_from_slice(&(image.len() as u32).to_le_bytes());
+        ico.extend_from_slice(&(offset as u32).to_le_bytes());
+        offset += image.len();
+    }
+    for image in images {
+        ico.extend_from_slice(&image);
+    }
+    ico
+}
+
+#[test]
+fn test_ico_image_closest_to_size() {
+    let ico = ico(&[16, 64, 32, 256]);
+    let width_at = |size| {
+        load_from_memory_at_size(&ico, CorsStatus::Safe, size)
+            .unwrap()
+            .width
+    };
+
+    assert_eq!(width_at(16), 16);
+    // Larger images are scaled down rather than smaller ones scaled up.
+    assert_eq!(width_at(24), 32);
+    assert_eq!(width_at(48), 64);
+    assert_eq!(width_at(200), 256);
+    // The largest image is used when they are all too small.
+    assert_eq!(width_at(512), 256);
+
+    // Without a size, the largest image is used.
+    assert_eq!(load_from_memory(&ico, CorsStatus::Safe).unwrap().width, 256);
+}
+
+#[test]
+fn test_images_that_are_not_ico_ignore_size() {
+    let png = png(48);
+    let image = load_from_memory_at_size(&png, CorsStatus::Safe, 16).unwrap();
+    assert_eq!(image.width, 48);
+}
@@ -2,7 +2,7 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use ipc_channel::ipc::IpcSender;
 use log::warn;
@@ -48,10 +48,27 @@ pub enum ProfilerMsg {
     /// Report a layout query that could not be processed immediately for a particular URL.
     BlockedLayoutQuery(String),
 
+    /// Report what the task queue of an event loop saw of the tasks of the named task source
+    /// since it last reported it.
+    TaskQueueCounters(String, TaskQueueCounters),
+
     /// Tells the profiler to shut down.
     Exit(IpcSender<()>),
 }
 
+/// What the task queue of an event loop saw of the tasks of one of its task sources.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct TaskQueueCounters {
+    /// The number of tasks waiting in the queue.
+    pub queued: usize,
+    /// The number of tasks taken from the queue to run.
+    pub dequeued: u64,
+    /// The total time the tasks taken from the queue waited in it.
+    pub total_latency: Duration,
+    /// The longest time a task taken from the queue waited in it.
+    pub max_latency: Duration,
+}
+
 #[repr(u32)]
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub enum ProfilerCategory {
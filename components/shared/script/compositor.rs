@@ -4,7 +4,7 @@
 
 //! Defines data structures which are consumed by the Compositor.
 
-use embedder_traits::Cursor;
+use embedder_traits::{Cursor, CustomCursor};
 use serde::{Deserialize, Serialize};
 use webrender_api::units::{LayoutSize, LayoutVector2D};
 use webrender_api::{
@@ -21,6 +21,10 @@ pub struct HitTestInfo {
     /// The cursor of this node's hit test item.
     pub cursor: Option<Cursor>,
 
+    /// The index in [CompositorDisplayListInfo::custom_cursors] of the image cursor of this
+    /// node's hit test item, which is shown instead of `cursor` when there is one.
+    pub custom_cursor: Option<usize>,
+
     /// The id of the [ScrollTree] associated with this hit test item.
     pub scroll_tree_node: ScrollTreeNodeId,
 }
@@ -231,6 +235,10 @@ pub struct CompositorDisplayListInfo {
     /// scroll without layout) using a WebRender hit test result.
     pub hit_test_info: Vec<HitTestInfo>,
 
+    /// The image cursors used by the items of this display list, which are referred to by
+    /// index from `hit_test_info`.
+    pub custom_cursors: Vec<CustomCursor>,
+
     /// A ScrollTree used by the compositor to scroll the contents of the
     /// display list.
     pub scroll_tree: ScrollTree,
@@ -276,6 +284,7 @@ impl CompositorDisplayListInfo {
             content_size,
             epoch,
             hit_test_info: Default::default(),
+            custom_cursors: Default::default(),
             scroll_tree,
             root_reference_frame_id,
             root_scroll_node_id,
@@ -288,10 +297,11 @@ impl CompositorDisplayListInfo {
         &mut self,
         node: u64,
         cursor: Option<Cursor>,
+        custom_cursor: Option<usize>,
         scroll_tree_node: ScrollTreeNodeId,
     ) -> usize {
         if let Some(last) = self.hit_test_info.last() {
-            if node == last.node && cursor == last.cursor {
+            if node == last.node && cursor == last.cursor && custom_cursor == last.custom_cursor {
                 return self.hit_test_info.len() - 1;
             }
         }
@@ -299,8 +309,23 @@ impl CompositorDisplayListInfo {
         self.hit_test_info.push(HitTestInfo {
             node,
             cursor,
+            custom_cursor,
             scroll_tree_node,
         });
         self.hit_test_info.len() - 1
     }
+
+    /// Add or re-use an identical image cursor in this `CompositorDisplayListInfo` and return
+    /// its index.
+    pub fn add_custom_cursor(&mut self, custom_cursor: CustomCursor) -> usize {
+        if let Some(index) = self
+            .custom_cursors
+            .iter()
+            .position(|existing| *existing == custom_cursor)
+        {
+            return index;
+        }
+        self.custom_cursors.push(custom_cursor);
+        self.custom_cursors.len() - 1
+    }
 }
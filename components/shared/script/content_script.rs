@@ -0,0 +1,205 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Content scripts: JavaScript and CSS that the embedder asks to have injected into the
+//! documents whose URL matches a set of patterns, at a given point of their load.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use malloc_size_of_derive::MallocSizeOf;
+use serde::{Deserialize, Serialize};
+use servo_url::ServoUrl;
+
+use crate::JsWorld;
+
+/// Identifies a content script registered by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct ContentScriptId(pub u64);
+
+impl ContentScriptId {
+    /// Create an id that has not been handed out before in this process.
+    pub fn new_unique() -> ContentScriptId {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        ContentScriptId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The point of the document load at which the JavaScript of a content script runs.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub enum ContentScriptRunAt {
+    /// After the document is created, before any of its content has been parsed.
+    DocumentStart,
+    /// Once the document has been parsed, before `DOMContentLoaded` is fired.
+    DocumentEnd,
+    /// After the `load` event of the document has been fired.
+    DocumentIdle,
+}
+
+/// A pattern matching a set of URLs, with the syntax used by browser extensions:
+/// `<scheme>://<host><path>`, where the scheme may be `*` (any of `http` and `https`),
+/// the host may be `*` or start with `*.` to match any subdomain, and the path may
+/// contain `*` wildcards. The special pattern `<all_urls>` matches any URL with a
+/// supported scheme.
+#[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+pub struct MatchPattern {
+    scheme: Option<String>,
+    host: HostPattern,
+    path: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, MallocSizeOf, PartialEq, Serialize)]
+enum HostPattern {
+    Any,
+    Exact(String),
+    SubdomainOf(String),
+}
+
+/// The schemes that content scripts can be injected into.
+const SUPPORTED_SCHEMES: &[&str] = &["http", "https", "file", "data"];
+
+impl MatchPattern {
+    /// Parse a match pattern, returning `None` if it is malformed.
+    pub fn parse(pattern: &str) -> Option<MatchPattern> {
+        if pattern == "<all_urls>" {
+            return Some(MatchPattern {
+                scheme: None,
+                host: HostPattern::Any,
+                path: "/*".to_owned(),
+            });
+        }
+
+        let (scheme, rest) = pattern.split_once("://")?;
+        let scheme = match scheme {
+            "*" => None,
+            scheme if SUPPORTED_SCHEMES.contains(&scheme) => Some(scheme.to_owned()),
+            _ => return None,
+        };
+
+        let (host, path) = match rest.find('/') {
+            Some(index) => rest.split_at(index),
+            None => return None,
+        };
+        let host = match host {
+            "*" => HostPattern::Any,
+            "" if scheme.as_deref() == Some("file") => HostPattern::Any,
+            host if host.starts_with("*.") => {
+                HostPattern::SubdomainOf(host[2..].to_ascii_lowercase())
+            },
+            host if host.is_empty() || host.contains('*') => return None,
+            host => HostPattern::Exact(host.to_ascii_lowercase()),
+        };
+
+        Some(MatchPattern {
+            scheme,
+            host,
+            path: path.to_owned(),
+        })
+    }
+
+    /// Whether `url` is matched by this pattern.
+    pub fn matches(&self, url: &ServoUrl) -> bool {
+        let scheme_matches = match self.scheme {
+            Some(ref scheme) => url.scheme() == scheme,
+            None => SUPPORTED_SCHEMES.contains(&url.scheme()),
+        };
+        if !scheme_matches {
+            return false;
+        }
+
+        let host = url.host_str().unwrap_or("").to_ascii_lowercase();
+        let host_matches = match self.host {
+            HostPattern::Any => true,
+            HostPattern::Exact(ref pattern) => host == *pattern,
+            HostPattern::SubdomainOf(ref domain) => {
+                host == *domain || host.ends_with(&format!(".{}", domain))
+            },
+        };
+        if !host_matches {
+            return false;
+        }
+
+        let mut path = url.path().to_owned();
+        if let Some(query) = url.query() {
+            path.push('?');
+            path.push_str(query);
+        }
+        glob_matches(&self.path, &path)
+    }
+}
+
+/// Match `text` against `pattern`, where `*` in the pattern matches any run of characters.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    if !text.starts_with(first) {
+        return false;
+    }
+    let mut remaining = &text[first.len()..];
+    let mut parts: Vec<&str> = parts.collect();
+    let last = match parts.pop() {
+        Some(last) => last,
+        // There was no wildcard at all, so the pattern must match exactly.
+        None => return remaining.is_empty(),
+    };
+    for part in parts {
+        match remaining.find(part) {
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
+        }
+    }
+    remaining.ends_with(last)
+}
+
+/// A set of scripts and stylesheets to inject into the documents matching a set of
+/// patterns.
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct ContentScript {
+    /// The id used to unregister this content script.
+    pub id: ContentScriptId,
+    /// The URLs this content script is injected into.
+    pub matches: Vec<MatchPattern>,
+    /// URLs this content script is never injected into, even if they are matched by
+    /// `matches`.
+    pub exclude_matches: Vec<MatchPattern>,
+    /// The sources of the JavaScript to run, in order.
+    pub js: Vec<String>,
+    /// The sources of the CSS to inject. Stylesheets are always injected when the
+    /// document is created.
+    pub css: Vec<String>,
+    /// When to run the JavaScript.
+    pub run_at: ContentScriptRunAt,
+    /// The world to run the JavaScript in.
+    pub world: JsWorld,
+    /// Whether to inject into the documents of nested browsing contexts too, rather
+    /// than only into top-level documents.
+    pub all_frames: bool,
+}
+
+impl ContentScript {
+    /// Create a content script with a fresh id, injected at `DocumentIdle` into the
+    /// top-level documents matching `matches`, in an isolated world of its own.
+    pub fn new(matches: Vec<MatchPattern>) -> ContentScript {
+        let id = ContentScriptId::new_unique();
+        ContentScript {
+            id,
+            matches,
+            exclude_matches: vec![],
+            js: vec![],
+            css: vec![],
+            run_at: ContentScriptRunAt::DocumentIdle,
+            world: JsWorld::Isolated(format!("content-script-{}", id.0)),
+            all_frames: false,
+        }
+    }
+
+    /// Whether this content script should be injected into a document at `url`.
+    pub fn applies_to(&self, url: &ServoUrl, is_top_level: bool) -> bool {
+        (is_top_level || self.all_frames) &&
+            self.matches.iter().any(|pattern| pattern.matches(url)) &&
+            !self
+                .exclude_matches
+                .iter()
+                .any(|pattern| pattern.matches(url))
+    }
+}
@@ -0,0 +1,390 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Browser extensions: a minimal subset of the WebExtensions model, enough to run the
+//! content scripts and background script of a manifest v3 extension and let them talk
+//! to each other through `browser.runtime`, `browser.storage.local` and `browser.tabs`.
+//! <https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions>
+//!
+//! The background script named by `background.service_worker` does not run in a
+//! `ServiceWorkerGlobalScope`: it runs in the world of the extension in a hidden
+//! `about:blank` background page, as manifest v2 background pages do, so it has a
+//! `Window` and a `document`, and it lives as long as the extension is loaded rather
+//! than being stopped when idle.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{fmt, fs, io};
+
+use ipc_channel::ipc::IpcSender;
+use ipc_channel::Error as IpcError;
+use malloc_size_of_derive::MallocSizeOf;
+use serde::{Deserialize, Serialize};
+use servo_url::ServoUrl;
+
+use crate::content_script::{ContentScript, ContentScriptRunAt, MatchPattern};
+use crate::{JsWorld, StructuredSerializedData};
+
+/// Identifies an extension loaded by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct ExtensionId(pub u64);
+
+impl ExtensionId {
+    /// Create an id that has not been handed out before in this process.
+    pub fn new_unique() -> ExtensionId {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        ExtensionId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+impl fmt::Display for ExtensionId {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "servo-extension-{}", self.0)
+    }
+}
+
+/// The permission needed to use `browser.storage`.
+pub const STORAGE_PERMISSION: &str = "storage";
+
+/// The permission needed to see the URL and title of tabs in `browser.tabs`.
+pub const TABS_PERMISSION: &str = "tabs";
+
+/// An extension, with its scripts read from disk and ready to be injected.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Extension {
+    /// The id of this extension, also exposed to its scripts as `browser.runtime.id`.
+    pub id: ExtensionId,
+    /// The name of the extension, from its manifest.
+    pub name: String,
+    /// The version of the extension, from its manifest.
+    pub version: String,
+    /// The API permissions requested by the extension.
+    pub permissions: Vec<String>,
+    /// The source of the background script, if the extension has one. It runs in a
+    /// hidden background page rather than in a service worker.
+    pub background_script: Option<String>,
+    /// The content scripts of the extension. They all run in the world of the
+    /// extension, `JsWorld::Extension(id)`.
+    pub content_scripts: Vec<ContentScript>,
+}
+
+/// An error preventing an extension from being loaded.
+#[derive(Debug)]
+pub enum ExtensionError {
+    /// A file of the extension could not be read.
+    Io(String, io::Error),
+    /// The manifest is not valid JSON, or is missing required keys.
+    InvalidManifest(serde_json::Error),
+    /// The manifest is not a version 3 manifest.
+    UnsupportedManifestVersion(u32),
+    /// A content script has an invalid URL match pattern.
+    InvalidMatchPattern(String),
+}
+
+impl fmt::Display for ExtensionError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExtensionError::Io(ref path, ref error) => {
+                write!(formatter, "failed to read {}: {}", path, error)
+            },
+            ExtensionError::InvalidManifest(ref error) => {
+                write!(formatter, "invalid manifest: {}", error)
+            },
+            ExtensionError::UnsupportedManifestVersion(version) => {
+                write!(formatter, "unsupported manifest version {}", version)
+            },
+            ExtensionError::InvalidMatchPattern(ref pattern) => {
+                write!(formatter, "invalid match pattern {:?}", pattern)
+            },
+        }
+    }
+}
+
+impl std::error::Error for ExtensionError {}
+
+/// The subset of the keys of a `manifest.json` file that Servo understands.
+/// <https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/manifest.json>
+#[derive(Deserialize)]
+struct Manifest {
+    manifest_version: u32,
+    name: String,
+    version: String,
+    #[serde(default)]
+    permissions: Vec<String>,
+    background: Option<ManifestBackground>,
+    #[serde(default)]
+    content_scripts: Vec<ManifestContentScript>,
+}
+
+#[derive(Deserialize)]
+struct ManifestBackground {
+    service_worker: String,
+}
+
+#[derive(Deserialize)]
+struct ManifestContentScript {
+    matches: Vec<String>,
+    #[serde(default)]
+    exclude_matches: Vec<String>,
+    #[serde(default)]
+    js: Vec<String>,
+    #[serde(default)]
+    css: Vec<String>,
+    #[serde(default)]
+    run_at: ManifestRunAt,
+    #[serde(default)]
+    all_frames: bool,
+}
+
+#[derive(Default, Deserialize)]
+enum ManifestRunAt {
+    #[serde(rename = "document_start")]
+    Start,
+    #[serde(rename = "document_end")]
+    End,
+    #[default]
+    #[serde(rename = "document_idle")]
+    Idle,
+}
+
+impl From<ManifestRunAt> for ContentScriptRunAt {
+    fn from(run_at: ManifestRunAt) -> ContentScriptRunAt {
+        match run_at {
+            ManifestRunAt::Start => ContentScriptRunAt::DocumentStart,
+            ManifestRunAt::End => ContentScriptRunAt::DocumentEnd,
+            ManifestRunAt::Idle => ContentScriptRunAt::DocumentIdle,
+        }
+    }
+}
+
+impl Extension {
+    /// Load the unpacked extension in the directory at `path`.
+    pub fn load(path: &Path) -> Result<Extension, ExtensionError> {
+        let read_file = |name: &str| {
+            fs::read_to_string(path.join(name))
+                .map_err(|error| ExtensionError::Io(name.to_owned(), error))
+        };
+        let manifest = read_file("manifest.json")?;
+        Extension::from_manifest(&manifest, read_file)
+    }
+
+    /// Create an extension from the contents of its `manifest.json` file, using
+    /// `read_file` to read the scripts and stylesheets it refers to.
+    pub fn from_manifest(
+        manifest: &str,
+        mut read_file: impl FnMut(&str) -> Result<String, ExtensionError>,
+    ) -> Result<Extension, ExtensionError> {
+        let manifest: Manifest =
+            serde_json::from_str(manifest).map_err(ExtensionError::InvalidManifest)?;
+        if manifest.manifest_version != 3 {
+            return Err(ExtensionError::UnsupportedManifestVersion(
+                manifest.manifest_version,
+            ));
+        }
+
+        let id = ExtensionId::new_unique();
+        let background_script = match manifest.background {
+            Some(background) => Some(read_file(&background.service_worker)?),
+            None => None,
+        };
+
+        let parse_patterns = |patterns: Vec<String>| {
+            patterns
+                .into_iter()
+                .map(|pattern| {
+                    MatchPattern::parse(&pattern)
+                        .ok_or(ExtensionError::InvalidMatchPattern(pattern))
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let mut content_scripts = vec![];
+        for entry in manifest.content_scripts {
+            let mut content_script = ContentScript::new(parse_patterns(entry.matches)?);
+            content_script.exclude_matches = parse_patterns(entry.exclude_matches)?;
+            content_script.js = entry
+                .js
+                .iter()
+                .map(|name| read_file(name))
+                .collect::<Result<_, _>>()?;
+            content_script.css = entry
+                .css
+                .iter()
+                .map(|name| read_file(name))
+                .collect::<Result<_, _>>()?;
+            content_script.run_at = entry.run_at.into();
+            content_script.world = JsWorld::Extension(id);
+            content_script.all_frames = entry.all_frames;
+            content_scripts.push(content_script);
+        }
+
+        Ok(Extension {
+            id,
+            name: manifest.name,
+            version: manifest.version,
+            permissions: manifest.permissions,
+            background_script,
+            content_scripts,
+        })
+    }
+
+    /// Whether the manifest of this extension requests `permission`.
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|name| name == permission)
+    }
+
+    /// Whether any content script of this extension is injected into a document at
+    /// `url`.
+    pub fn injects_content_scripts_into(&self, url: &ServoUrl, is_top_level: bool) -> bool {
+        self.content_scripts
+            .iter()
+            .any(|content_script| content_script.applies_to(url, is_top_level))
+    }
+}
+
+/// An identifier for a tab, as seen by extensions.
+pub type ExtensionTabId = i32;
+
+/// A tab, as described to an extension.
+/// <https://developer.mozilla.org/en-US/docs/Mozilla/Add-ons/WebExtensions/API/tabs/Tab>
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ExtensionTab {
+    /// The id of the tab.
+    pub id: ExtensionTabId,
+    /// The URL of the document in the tab, if the extension has the `tabs` permission.
+    pub url: Option<ServoUrl>,
+    /// The title of the document in the tab, if the extension has the `tabs` permission.
+    pub title: Option<String>,
+    /// Whether this is the focused tab.
+    pub active: bool,
+}
+
+/// The properties of the tabs to return from `browser.tabs.query()`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ExtensionTabQuery {
+    /// Only return the tabs that are, or are not, focused.
+    pub active: Option<bool>,
+    /// Only return the tabs whose URL matches one of these patterns, if not empty.
+    pub url: Vec<MatchPattern>,
+}
+
+/// Where a message sent by an extension goes.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub enum ExtensionMessageTarget {
+    /// The background script, for `browser.runtime.sendMessage()`.
+    Background,
+    /// The content scripts of the document in a tab, for `browser.tabs.sendMessage()`.
+    Tab(ExtensionTabId),
+}
+
+/// A message sent by a script of an extension to another one.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ExtensionMessage {
+    /// The message itself.
+    pub data: StructuredSerializedData,
+    /// The tab the message was sent from, if it was sent by a content script.
+    pub sender_tab: Option<ExtensionTab>,
+    /// The URL of the document the message was sent from.
+    pub sender_url: ServoUrl,
+}
+
+/// An error reported to the script that made an extension API call.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ExtensionApiError {
+    /// The extension is not loaded anymore.
+    NoSuchExtension,
+    /// The call was made on behalf of an extension by a document that neither is its
+    /// background page nor has its content scripts injected.
+    NotExtensionContext,
+    /// The manifest of the extension does not request the given permission.
+    MissingPermission(String),
+    /// There is no tab with the given id.
+    NoSuchTab(ExtensionTabId),
+    /// No script of the extension listens to messages at the target.
+    NoReceivingEnd,
+    /// The response to a message could not be serialized.
+    UnserializableResponse,
+    /// The promise returned by the receiver of a message was rejected.
+    ResponseRejected,
+}
+
+impl fmt::Display for ExtensionApiError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ExtensionApiError::NoSuchExtension => write!(formatter, "The extension is not loaded"),
+            ExtensionApiError::NotExtensionContext => {
+                write!(formatter, "The caller is not a script of the extension")
+            },
+            ExtensionApiError::MissingPermission(ref permission) => write!(
+                formatter,
+                "The extension does not have the {:?} permission",
+                permission
+            ),
+            ExtensionApiError::NoSuchTab(id) => write!(formatter, "No tab with id {}", id),
+            ExtensionApiError::NoReceivingEnd => write!(
+                formatter,
+                "Could not establish connection. Receiving end does not exist."
+            ),
+            ExtensionApiError::UnserializableResponse => {
+                write!(formatter, "The response could not be serialized")
+            },
+            ExtensionApiError::ResponseRejected => {
+                write!(
+                    formatter,
+                    "The receiver of the message rejected its response"
+                )
+            },
+        }
+    }
+}
+
+/// The result of an extension API call.
+pub type ExtensionApiResult<T> = Result<T, ExtensionApiError>;
+
+/// A call made by a script of an extension to one of the APIs implemented by the
+/// constellation.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum ExtensionRequest {
+    /// `browser.runtime.sendMessage()` and `browser.tabs.sendMessage()`: send a message
+    /// and wait for the response of its receivers.
+    SendMessage(
+        ExtensionMessageTarget,
+        StructuredSerializedData,
+        IpcSender<ExtensionApiResult<StructuredSerializedData>>,
+    ),
+    /// `browser.tabs.query()`
+    QueryTabs(
+        ExtensionTabQuery,
+        IpcSender<ExtensionApiResult<Vec<ExtensionTab>>>,
+    ),
+    /// `browser.storage.local.get()`, for the given keys or for all of them.
+    StorageGet(
+        Option<Vec<String>>,
+        IpcSender<ExtensionApiResult<Vec<(String, StructuredSerializedData)>>>,
+    ),
+    /// `browser.storage.local.set()`
+    StorageSet(
+        Vec<(String, StructuredSerializedData)>,
+        IpcSender<ExtensionApiResult<()>>,
+    ),
+    /// `browser.storage.local.remove()`
+    StorageRemove(Vec<String>, IpcSender<ExtensionApiResult<()>>),
+    /// `browser.storage.local.clear()`
+    StorageClear(IpcSender<ExtensionApiResult<()>>),
+}
+
+impl ExtensionRequest {
+    /// Answer this call with `error` instead of handling it.
+    pub fn reject(self, error: ExtensionApiError) -> Result<(), IpcError> {
+        match self {
+            ExtensionRequest::SendMessage(_, _, response_sender) => {
+                response_sender.send(Err(error))
+            },
+            ExtensionRequest::QueryTabs(_, response_sender) => response_sender.send(Err(error)),
+            ExtensionRequest::StorageGet(_, response_sender) => response_sender.send(Err(error)),
+            ExtensionRequest::StorageSet(_, response_sender) |
+            ExtensionRequest::StorageRemove(_, response_sender) |
+            ExtensionRequest::StorageClear(response_sender) => response_sender.send(Err(error)),
+        }
+    }
+}
@@ -10,6 +10,8 @@
 #![deny(unsafe_code)]
 
 pub mod compositor;
+pub mod content_script;
+pub mod extension;
 mod script_msg;
 pub mod serializable;
 pub mod transferable;
@@ -18,6 +20,7 @@ pub mod webdriver_msg;
 use std::borrow::Cow;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use bitflags::bitflags;
@@ -25,8 +28,13 @@ use bluetooth_traits::BluetoothRequest;
 use canvas_traits::webgl::WebGLPipeline;
 use compositor::ScrollTreeNodeId;
 use crossbeam_channel::{RecvTimeoutError, Sender};
-use devtools_traits::{DevtoolScriptControlMsg, ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{CompositorEventVariant, Cursor};
+use devtools_traits::{
+    DevtoolScriptControlMsg, NetworkThrottling, ScriptToDevtoolsControlMsg, WorkerId,
+};
+use embedder_traits::{
+    CompositorEventVariant, Cursor, DragDataItem, NetworkChange, PermissionName, PermissionState,
+    PipelineUsage, ScriptPolicies, ScriptPolicy, UserAgentOverride,
+};
 use euclid::default::Point2D;
 use euclid::{Length, Rect, Scale, Size2D, UnknownUnit, Vector2D};
 use gfx_traits::Epoch;
@@ -56,20 +64,25 @@ use servo_atoms::Atom;
 use servo_url::{ImmutableOrigin, ServoUrl};
 use style_traits::{CSSPixel, SpeculativePainter};
 use webgpu::identity::WebGPUMsg;
-use webrender_api::units::{DeviceIntSize, DevicePixel, LayoutPixel, LayoutPoint, WorldPoint};
+use webrender_api::units::{
+    DeviceIntSize, DevicePixel, LayoutPixel, LayoutPoint, LayoutRect, WorldPoint,
+};
 use webrender_api::{
     BuiltDisplayList, BuiltDisplayListDescriptor, DocumentId, ExternalImageData, ExternalScrollId,
     HitTestFlags, ImageData, ImageDescriptor, ImageKey, PipelineId as WebRenderPipelineId,
 };
 
 use crate::compositor::CompositorDisplayListInfo;
+use crate::content_script::ContentScript;
+use crate::extension::{ExtensionApiResult, ExtensionId, ExtensionMessage};
 pub use crate::script_msg::{
-    DOMMessage, EventResult, HistoryEntryReplacement, IFrameSizeMsg, Job, JobError, JobResult,
-    JobResultValue, JobType, LayoutMsg, LogEntry, SWManagerMsg, SWManagerSenders, ScopeThings,
-    ScriptMsg, ServiceWorkerMsg,
+    DOMMessage, EventResult, HistoryEntryReplacement, HistoryEntryScrollState, IFrameSizeMsg, Job,
+    JobError, JobResult, JobResultValue, JobType, LayoutMsg, LogEntry, NavigationPreloadAction,
+    NavigationPreloadState, SWManagerMsg, SWManagerSenders, ScopeThings, ScriptMsg,
+    ScrollRestorationMode, ServiceWorkerMsg,
 };
 use crate::serializable::{BlobData, BlobImpl};
-use crate::transferable::MessagePortImpl;
+use crate::transferable::{ImageBitmapImpl, MessagePortImpl, OffscreenCanvasImpl};
 use crate::webdriver_msg::{LoadStatus, WebDriverScriptCommand};
 
 /// The address of a node. Layout sends these back. They must be validated via
@@ -170,6 +183,13 @@ pub struct LoadData {
 
     /// Servo internal: if crash details are present, trigger a crash error page with these details.
     pub crash: Option<String>,
+    /// Whether this load reloads a document the user agent discarded.
+    /// <https://wicg.github.io/page-lifecycle/#dom-document-wasdiscarded>
+    pub was_discarded: bool,
+    /// The history state of the session history entry that this load reloads, if any.
+    pub history_state_id: Option<HistoryStateId>,
+    /// The scroll state of the session history entry that this load reloads, if any.
+    pub scroll_state: Option<HistoryEntryScrollState>,
 }
 
 /// The result of evaluating a javascript scheme url.
@@ -205,6 +225,9 @@ impl LoadData {
             srcdoc: "".to_string(),
             inherited_secure_context,
             crash: None,
+            was_discarded: false,
+            history_state_id: None,
+            scroll_state: None,
         }
     }
 }
@@ -350,8 +373,14 @@ pub enum ConstellationControlMsg {
         PipelineId,
         UpdatePipelineIdReason,
     ),
-    /// Updates the history state and url of a given pipeline.
-    UpdateHistoryState(PipelineId, Option<HistoryStateId>, ServoUrl),
+    /// Updates the history state and url of a given pipeline, with the scroll state to restore
+    /// for the session history entry that was traversed to.
+    UpdateHistoryState(
+        PipelineId,
+        Option<HistoryStateId>,
+        ServoUrl,
+        HistoryEntryScrollState,
+    ),
     /// Removes inaccesible history states.
     RemoveHistoryStates(PipelineId, Vec<HistoryStateId>),
     /// Set an iframe to be focused. Used when an element in an iframe gains focus.
@@ -383,6 +412,9 @@ pub enum ConstellationControlMsg {
         Option<String>,
         Option<String>,
     ),
+    /// The state of a permission changed for the origin of the given pipeline: the
+    /// `PermissionStatus` objects following it are to be updated.
+    DispatchPermissionChange(PipelineId, PermissionName, PermissionState),
     /// Report an error from a CSS parser for the given pipeline
     ReportCSSError(PipelineId, String, u32, u32, String),
     /// Reload the given page.
@@ -393,6 +425,43 @@ pub enum ConstellationControlMsg {
     MediaSessionAction(PipelineId, MediaSessionActionType),
     /// Notifies script thread that WebGPU server has started
     SetWebGPUPort(IpcReceiver<WebGPUMsg>),
+    /// Apply a user stylesheet to the given pipeline.
+    AddUserStylesheet(PipelineId, UserStylesheet),
+    /// Stop applying a user stylesheet to the given pipeline.
+    RemoveUserStylesheet(PipelineId, UserStylesheetId),
+    /// Inject a content script into the documents of the given pipeline that it applies to.
+    AddContentScript(PipelineId, ContentScript),
+    /// Run the background script of an extension in the given pipeline, which hosts the
+    /// extension's background page.
+    RunExtensionBackgroundScript(PipelineId, ExtensionId, String),
+    /// Deliver a message sent by a script of an extension to the scripts of that
+    /// extension running in the given pipeline, and send back their response.
+    DispatchExtensionMessage(
+        PipelineId,
+        ExtensionId,
+        ExtensionMessage,
+        IpcSender<ExtensionApiResult<StructuredSerializedData>>,
+    ),
+    /// Release as much memory as possible, as the system is running low on it.
+    MemoryPressure,
+    /// Freeze or resume the document of the given pipeline, whose webview is in the
+    /// background.
+    /// <https://wicg.github.io/page-lifecycle/#sec-lifecycle-states>
+    SetDocumentFrozen(PipelineId, bool),
+    /// Report what each document of the event loop uses of it, for a snapshot of the frame
+    /// tree.
+    ReportPipelineUsage(IpcSender<Vec<(PipelineId, PipelineUsage)>>),
+    /// The connectivity of the device changed.
+    NetworkChanged(NetworkChange),
+    /// The user started or stopped working offline.
+    SetWorkOffline(bool),
+    /// The system entered or left forced colors mode.
+    SetForcedColors(bool),
+    /// Tell the pages of the given pipeline what the override says about the user agent
+    /// instead of the defaults, or go back to the defaults.
+    SetUserAgentOverride(PipelineId, Option<UserAgentOverride>),
+    /// The script policies the embedder set changed.
+    SetScriptPolicies(ScriptPolicies),
     /// A mesage for a layout from the constellation.
     ForLayoutFromConstellation(LayoutControlMsg, PipelineId),
     /// A message for a layout from the font cache.
@@ -429,12 +498,26 @@ impl fmt::Debug for ConstellationControlMsg {
             WebFontLoaded(..) => "WebFontLoaded",
             DispatchIFrameLoadEvent { .. } => "DispatchIFrameLoadEvent",
             DispatchStorageEvent(..) => "DispatchStorageEvent",
+            DispatchPermissionChange(..) => "DispatchPermissionChange",
             ReportCSSError(..) => "ReportCSSError",
             Reload(..) => "Reload",
             PaintMetric(..) => "PaintMetric",
             ExitFullScreen(..) => "ExitFullScreen",
             MediaSessionAction(..) => "MediaSessionAction",
             SetWebGPUPort(..) => "SetWebGPUPort",
+            AddUserStylesheet(..) => "AddUserStylesheet",
+            RemoveUserStylesheet(..) => "RemoveUserStylesheet",
+            AddContentScript(..) => "AddContentScript",
+            RunExtensionBackgroundScript(..) => "RunExtensionBackgroundScript",
+            DispatchExtensionMessage(..) => "DispatchExtensionMessage",
+            MemoryPressure => "MemoryPressure",
+            SetDocumentFrozen(..) => "SetDocumentFrozen",
+            ReportPipelineUsage(..) => "ReportPipelineUsage",
+            NetworkChanged(..) => "NetworkChanged",
+            SetWorkOffline(..) => "SetWorkOffline",
+            SetForcedColors(..) => "SetForcedColors",
+            SetUserAgentOverride(..) => "SetUserAgentOverride",
+            SetScriptPolicies(..) => "SetScriptPolicies",
             ForLayoutFromConstellation(..) => "ForLayoutFromConstellation",
             ForLayoutFromFontCache(..) => "ForLayoutFromFontCache",
         };
@@ -478,6 +561,20 @@ pub enum TouchEventType {
     Cancel,
 }
 
+/// A step of a drag that comes from outside of the webview, such as files dragged from
+/// the desktop.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum DragEventType {
+    /// The drag entered the webview with the given data.
+    Enter(Vec<DragDataItem>),
+    /// The drag moved over the webview.
+    Over,
+    /// The drag left the webview, or was canceled.
+    Leave,
+    /// The data was dropped on the webview.
+    Drop,
+}
+
 /// An opaque identifier for a touch point.
 ///
 /// <http://w3c.github.io/touch-events/#widl-Touch-identifier>
@@ -569,6 +666,8 @@ pub enum CompositorEvent {
     IMEDismissedEvent,
     /// Connected gamepad state updated
     GamepadEvent(GamepadEvent),
+    /// A drag from outside of the webview moved over a point.
+    DragEvent(DragEventType, Point2D<f32>, Option<UntrustedNodeAddress>),
 }
 
 impl From<&CompositorEvent> for CompositorEventVariant {
@@ -583,6 +682,7 @@ impl From<&CompositorEvent> for CompositorEventVariant {
             CompositorEvent::CompositionEvent(..) => CompositorEventVariant::CompositionEvent,
             CompositorEvent::IMEDismissedEvent => CompositorEventVariant::IMEDismissedEvent,
             CompositorEvent::GamepadEvent(..) => CompositorEventVariant::GamepadEvent,
+            CompositorEvent::DragEvent(..) => CompositorEventVariant::DragEvent,
         }
     }
 }
@@ -834,6 +934,12 @@ pub enum WebDriverCommandMsg {
         Option<Rect<f32, CSSPixel>>,
         IpcSender<Option<Image>>,
     ),
+    /// Emulate the given network conditions for the top-level browsing context with the
+    /// given ID, or stop emulating them.
+    SetNetworkThrottling(TopLevelBrowsingContextId, Option<NetworkThrottling>),
+    /// Override what the top-level browsing context with the given ID tells about the user
+    /// agent, or stop overriding it.
+    SetUserAgentOverride(TopLevelBrowsingContextId, Option<UserAgentOverride>),
 }
 
 /// Resources required by workerglobalscopes
@@ -865,6 +971,11 @@ pub struct WorkerGlobalScopeInit {
     pub is_headless: bool,
     /// An optional string allowing the user agnet to be set for testing.
     pub user_agent: Cow<'static, str>,
+    /// What the embedder or WebDriver chose to tell about the user agent instead of the
+    /// defaults, if anything.
+    pub user_agent_override: Option<UserAgentOverride>,
+    /// What the embedder lets the script of the worker do.
+    pub script_policy: ScriptPolicy,
     /// True if secure context
     pub inherited_secure_context: Option<bool>,
 }
@@ -958,6 +1069,10 @@ pub struct StructuredSerializedData {
     pub blobs: Option<HashMap<BlobId, BlobImpl>>,
     /// Transferred objects.
     pub ports: Option<HashMap<MessagePortId, MessagePortImpl>>,
+    /// Transferred offscreen canvases, keyed by the order they were transferred in.
+    pub offscreen_canvases: Option<HashMap<u64, OffscreenCanvasImpl>>,
+    /// Transferred image bitmaps, keyed by the order they were transferred in.
+    pub image_bitmaps: Option<HashMap<u64, ImageBitmapImpl>>,
 }
 
 impl StructuredSerializedData {
@@ -993,11 +1108,25 @@ impl StructuredSerializedData {
             warn!("Attempt to broadcast structured serialized data including ports(should never happen).");
         }
 
+        if self.offscreen_canvases.is_some() {
+            // Not panicking only because this is called from the constellation.
+            warn!("Attempt to broadcast structured serialized data including offscreen canvases(should never happen).");
+        }
+
+        if self.image_bitmaps.is_some() {
+            // Not panicking only because this is called from the constellation.
+            warn!("Attempt to broadcast structured serialized data including image bitmaps(should never happen).");
+        }
+
         StructuredSerializedData {
             serialized,
             blobs,
             // Ports cannot be broadcast.
             ports: None,
+            // Neither can offscreen canvases.
+            offscreen_canvases: None,
+            // Nor image bitmaps.
+            image_bitmaps: None,
         }
     }
 }
@@ -1047,6 +1176,56 @@ impl Clone for BroadcastMsg {
     }
 }
 
+/// Identifies a user stylesheet registered by the embedder.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub struct UserStylesheetId(pub u64);
+
+impl UserStylesheetId {
+    /// Create an id that has not been handed out before in this process.
+    pub fn new_unique() -> UserStylesheetId {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        UserStylesheetId(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// The JavaScript world a script injected by the embedder runs in.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
+pub enum JsWorld {
+    /// The world of the page's own scripts.
+    Main,
+    /// A world with its own global object and built-in objects, which only
+    /// shares the DOM with the page. Scripts that name the same world share
+    /// its global object within a given document.
+    Isolated(String),
+    /// The isolated world of an extension, shared by all of its scripts that run in
+    /// a given document, in which the extension APIs are available as `browser`.
+    Extension(ExtensionId),
+}
+
+/// A stylesheet injected by the embedder, which takes part in the cascade at
+/// the user origin.
+/// <https://drafts.csswg.org/css-cascade/#cascade-origin-user>
+#[derive(Clone, Debug, Deserialize, MallocSizeOf, Serialize)]
+pub struct UserStylesheet {
+    /// The id used to remove this stylesheet again.
+    pub id: UserStylesheetId,
+    /// The CSS source of the stylesheet.
+    pub source: String,
+    /// The URL against which relative URLs in the stylesheet are resolved.
+    pub url: ServoUrl,
+}
+
+impl UserStylesheet {
+    /// Create a user stylesheet with a fresh id.
+    pub fn new(source: String, url: ServoUrl) -> UserStylesheet {
+        UserStylesheet {
+            id: UserStylesheetId::new_unique(),
+            source,
+            url,
+        }
+    }
+}
+
 /// The type of MediaSession action.
 /// <https://w3c.github.io/mediasession/#enumdef-mediasessionaction>
 #[derive(Clone, Debug, Deserialize, Eq, Hash, MallocSizeOf, PartialEq, Serialize)]
@@ -1111,6 +1290,10 @@ pub struct CompositorHitTestResult {
     /// The cursor that should be used when hovering the item hit by the hit test.
     pub cursor: Option<Cursor>,
 
+    /// The index of the image cursor that should be used instead of `cursor`, if any, in the
+    /// display list of the pipeline that was hit.
+    pub custom_cursor: Option<usize>,
+
     /// The scroll tree node associated with this hit test item.
     pub scroll_tree_node: ScrollTreeNodeId,
 }
@@ -1144,6 +1327,18 @@ pub enum ScriptToCompositorMsg {
     GenerateImageKey(IpcSender<ImageKey>),
     /// Perform a resource update operation.
     UpdateImages(Vec<SerializedImageUpdate>),
+    /// Highlight the box model of an element of the given pipeline above its content, or
+    /// stop highlighting.
+    HighlightBoxModel(PipelineId, Option<BoxModelHighlight>),
+}
+
+/// The boxes of an element that the devtools highlight, in the coordinates of its document.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct BoxModelHighlight {
+    pub content_box: LayoutRect,
+    pub padding_box: LayoutRect,
+    pub border_box: LayoutRect,
+    pub margin_box: LayoutRect,
 }
 
 #[derive(Clone, Deserialize, Serialize)]
@@ -1176,6 +1371,15 @@ impl WebrenderIpcSender {
         }
     }
 
+    /// Highlight the box model of an element of the given pipeline, or stop highlighting.
+    pub fn highlight_box_model(&self, pipeline: PipelineId, highlight: Option<BoxModelHighlight>) {
+        if let Err(e) = self.0.send(ScriptToCompositorMsg::HighlightBoxModel(
+            pipeline, highlight,
+        )) {
+            warn!("Error sending box model highlight: {}", e);
+        }
+    }
+
     /// Inform WebRender of a new display list for the given pipeline.
     pub fn send_display_list(
         &self,
@@ -6,10 +6,10 @@ use std::collections::{HashMap, VecDeque};
 use std::fmt;
 
 use canvas_traits::canvas::{CanvasId, CanvasMsg};
-use devtools_traits::{ScriptToDevtoolsControlMsg, WorkerId};
-use embedder_traits::{EmbedderMsg, MediaSessionEvent};
+use devtools_traits::{NetworkThrottling, ScriptToDevtoolsControlMsg, WorkerId};
+use embedder_traits::{EmbedderMsg, MediaSessionEvent, PermissionName, PermissionState};
 use euclid::default::Size2D as UntypedSize2D;
-use euclid::Size2D;
+use euclid::{Size2D, Vector2D};
 use gfx_traits::Epoch;
 use ipc_channel::ipc::{IpcReceiver, IpcSender};
 use msg::constellation_msg::{
@@ -27,6 +27,7 @@ use style_traits::CSSPixel;
 use webgpu::{wgpu, WebGPU, WebGPUResponseResult};
 use webrender_api::units::{DeviceIntPoint, DeviceIntSize};
 
+use crate::extension::{ExtensionId, ExtensionRequest};
 use crate::{
     AnimationState, AuxiliaryBrowsingContextLoadInfo, BroadcastMsg, DocumentState,
     IFrameLoadInfoWithData, LoadData, MessagePortMsg, PortMessageTask, StructuredSerializedData,
@@ -96,6 +97,27 @@ pub enum HistoryEntryReplacement {
     Disabled,
 }
 
+/// <https://html.spec.whatwg.org/multipage/#scroll-restoration-mode>
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ScrollRestorationMode {
+    /// Restore the scroll position of the entry when traversing to it.
+    #[default]
+    Auto,
+    /// Leave scrolling to the page when traversing to the entry.
+    Manual,
+}
+
+/// The scroll state that is persisted with a session history entry, and restored when the
+/// history is traversed to it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct HistoryEntryScrollState {
+    /// The scroll position of the viewport.
+    /// <https://html.spec.whatwg.org/multipage/#she-scroll-position>
+    pub position: Vector2D<f32, CSSPixel>,
+    /// <https://html.spec.whatwg.org/multipage/#she-scroll-restoration-mode>
+    pub restoration_mode: ScrollRestorationMode,
+}
+
 /// Messages from the script to the constellation.
 #[derive(Deserialize, Serialize)]
 pub enum ScriptMsg {
@@ -153,6 +175,9 @@ pub enum ScriptMsg {
         Option<String>,
         Option<String>,
     ),
+    /// The state of a permission changed for the origin of the given url: let every other
+    /// same-origin pipeline know about it.
+    BroadcastPermissionChange(ServoUrl, PermissionName, PermissionState),
     /// Indicates whether this pipeline is currently running animations.
     ChangeRunningAnimationsState(AnimationState),
     /// Requests that a new 2D canvas thread be created. (This is done in the constellation because
@@ -210,6 +235,11 @@ pub enum ScriptMsg {
     PushHistoryState(HistoryStateId, ServoUrl),
     /// Inform the constellation of a replaced history state.
     ReplaceHistoryState(HistoryStateId, ServoUrl),
+    /// Inform the constellation that the viewport was scrolled, so that the scroll position can
+    /// be persisted with the current session history entry.
+    ScrollPositionChanged(Vector2D<f32, CSSPixel>),
+    /// Set the scroll restoration mode of the current session history entry.
+    SetScrollRestorationMode(ScrollRestorationMode),
     /// Gets the length of the joint session history from the constellation.
     JointSessionHistoryLength(IpcSender<u32>),
     /// Notification that this iframe should be removed.
@@ -246,6 +276,12 @@ pub enum ScriptMsg {
     ForwardDOMMessage(DOMMessage, ServoUrl),
     /// <https://w3c.github.io/ServiceWorker/#schedule-job-algorithm>
     ScheduleJob(Job),
+    /// Get or change the navigation preload state of the registration with the given scope.
+    NavigationPreload(
+        ServoUrl,
+        NavigationPreloadAction,
+        IpcSender<Option<NavigationPreloadState>>,
+    ),
     /// Get Window Informations size and position
     GetClientWindow(IpcSender<(DeviceIntSize, DeviceIntPoint)>),
     /// Get the screen size (pixel)
@@ -265,6 +301,11 @@ pub enum ScriptMsg {
     GetWebGPUChan(IpcSender<Option<WebGPU>>),
     /// Notify the constellation of a pipeline's document's title.
     TitleChanged(PipelineId, String),
+    /// A call to one of the extension APIs, made by a script of the given extension.
+    ExtensionRequest(ExtensionId, ExtensionRequest),
+    /// Emulate the given network conditions for the top-level browsing context of the
+    /// pipeline, or stop emulating them.
+    SetNetworkThrottling(Option<NetworkThrottling>),
 }
 
 impl fmt::Debug for ScriptMsg {
@@ -288,6 +329,7 @@ impl fmt::Debug for ScriptMsg {
             ForwardToEmbedder(..) => "ForwardToEmbedder",
             InitiateNavigateRequest(..) => "InitiateNavigateRequest",
             BroadcastStorageEvent(..) => "BroadcastStorageEvent",
+            BroadcastPermissionChange(..) => "BroadcastPermissionChange",
             ChangeRunningAnimationsState(..) => "ChangeRunningAnimationsState",
             CreateCanvasPaintThread(..) => "CreateCanvasPaintThread",
             Focus => "Focus",
@@ -302,6 +344,8 @@ impl fmt::Debug for ScriptMsg {
             TraverseHistory(..) => "TraverseHistory",
             PushHistoryState(..) => "PushHistoryState",
             ReplaceHistoryState(..) => "ReplaceHistoryState",
+            ScrollPositionChanged(..) => "ScrollPositionChanged",
+            SetScrollRestorationMode(..) => "SetScrollRestorationMode",
             JointSessionHistoryLength(..) => "JointSessionHistoryLength",
             RemoveIFrame(..) => "RemoveIFrame",
             VisibilityChangeComplete(..) => "VisibilityChangeComplete",
@@ -319,6 +363,7 @@ impl fmt::Debug for ScriptMsg {
             PipelineExited => "PipelineExited",
             ForwardDOMMessage(..) => "ForwardDOMMessage",
             ScheduleJob(..) => "ScheduleJob",
+            NavigationPreload(..) => "NavigationPreload",
             GetClientWindow(..) => "GetClientWindow",
             GetScreenSize(..) => "GetScreenSize",
             GetScreenAvailSize(..) => "GetScreenAvailSize",
@@ -326,6 +371,8 @@ impl fmt::Debug for ScriptMsg {
             RequestAdapter(..) => "RequestAdapter",
             GetWebGPUChan(..) => "GetWebGPUChan",
             TitleChanged(..) => "TitleChanged",
+            ExtensionRequest(..) => "ExtensionRequest",
+            SetNetworkThrottling(..) => "SetNetworkThrottling",
         };
         write!(formatter, "ScriptMsg::{}", variant)
     }
@@ -355,6 +402,26 @@ pub struct DOMMessage {
     pub data: StructuredSerializedData,
 }
 
+/// <https://w3c.github.io/ServiceWorker/#service-worker-registration-navigation-preload-enabled-flag>
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct NavigationPreloadState {
+    /// Whether navigations start a preload request alongside the service worker.
+    pub enabled: bool,
+    /// The value of the `Service-Worker-Navigation-Preload` header of the preload requests.
+    pub header_value: Vec<u8>,
+}
+
+/// What to do with the navigation preload state of a registration.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum NavigationPreloadAction {
+    /// Only get the state.
+    GetState,
+    /// <https://w3c.github.io/ServiceWorker/#navigation-preload-manager-enable>
+    SetEnabled(bool),
+    /// <https://w3c.github.io/ServiceWorker/#navigation-preload-manager-setheadervalue>
+    SetHeaderValue(Vec<u8>),
+}
+
 /// Channels to allow service worker manager to communicate with constellation and resource thread
 #[derive(Deserialize, Serialize)]
 pub struct SWManagerSenders {
@@ -377,6 +444,13 @@ pub enum ServiceWorkerMsg {
     ForwardDOMMessage(DOMMessage, ServoUrl),
     /// <https://w3c.github.io/ServiceWorker/#schedule-job-algorithm>
     ScheduleJob(Job),
+    /// Get or change the navigation preload state of the registration with the given scope.
+    /// The reply is `None` if the registration has no active worker.
+    NavigationPreload(
+        ServoUrl,
+        NavigationPreloadAction,
+        IpcSender<Option<NavigationPreloadState>>,
+    ),
     /// Exit the service worker manager
     Exit,
 }
@@ -2,10 +2,15 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+use embedder_traits::{Cursor, CustomCursor};
 use euclid::Size2D;
-use script_traits::compositor::{ScrollTree, ScrollTreeNodeId, ScrollableNodeInfo};
-use webrender_api::units::LayoutVector2D;
-use webrender_api::{ExternalScrollId, PipelineId, ScrollLocation, ScrollSensitivity, SpatialId};
+use script_traits::compositor::{
+    CompositorDisplayListInfo, ScrollTree, ScrollTreeNodeId, ScrollableNodeInfo,
+};
+use webrender_api::units::{LayoutSize, LayoutVector2D};
+use webrender_api::{
+    Epoch, ExternalScrollId, PipelineId, ScrollLocation, ScrollSensitivity, SpatialId,
+};
 
 fn add_mock_scroll_node(tree: &mut ScrollTree) -> ScrollTreeNodeId {
     let pipeline_id = PipelineId(0, 0);
@@ -177,3 +182,33 @@ fn test_scroll_tree_chain_through_overflow_hidden() {
         Some(LayoutVector2D::new(0.0, 0.0))
     );
 }
+
+#[test]
+fn test_custom_cursors_are_shared_between_hit_test_items() {
+    let size = LayoutSize::new(100.0, 100.0);
+    let mut info = CompositorDisplayListInfo::new(size, size, PipelineId(0, 0), Epoch(0));
+    let custom_cursor = |hotspot_x| CustomCursor {
+        width: 2,
+        height: 1,
+        hotspot_x,
+        hotspot_y: 0,
+        pixels: vec![255; 8],
+    };
+
+    let first = info.add_custom_cursor(custom_cursor(0));
+    let second = info.add_custom_cursor(custom_cursor(1));
+    assert_ne!(first, second);
+    assert_eq!(info.add_custom_cursor(custom_cursor(0)), first);
+    assert_eq!(info.custom_cursors.len(), 2);
+
+    let scroll_node = info.root_scroll_node_id;
+    let index = info.add_hit_test_info(1, Some(Cursor::Pointer), Some(first), scroll_node);
+    assert_eq!(
+        info.add_hit_test_info(1, Some(Cursor::Pointer), Some(first), scroll_node),
+        index
+    );
+    assert_ne!(
+        info.add_hit_test_info(1, Some(Cursor::Pointer), Some(second), scroll_node),
+        index
+    );
+}
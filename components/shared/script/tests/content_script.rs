@@ -0,0 +1,77 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script_traits::content_script::{ContentScript, MatchPattern};
+use script_traits::JsWorld;
+use servo_url::ServoUrl;
+
+fn url(url: &str) -> ServoUrl {
+    ServoUrl::parse(url).unwrap()
+}
+
+fn pattern(pattern: &str) -> MatchPattern {
+    MatchPattern::parse(pattern).unwrap()
+}
+
+#[test]
+fn test_all_urls_pattern() {
+    let all_urls = pattern("<all_urls>");
+    assert!(all_urls.matches(&url("https://example.com/index.html")));
+    assert!(all_urls.matches(&url("file:///home/user/index.html")));
+    assert!(!all_urls.matches(&url("about:blank")));
+}
+
+#[test]
+fn test_host_patterns() {
+    let subdomains = pattern("*://*.example.com/*");
+    assert!(subdomains.matches(&url("http://example.com/")));
+    assert!(subdomains.matches(&url("https://www.EXAMPLE.com/page?query")));
+    assert!(!subdomains.matches(&url("https://notexample.com/")));
+
+    let exact = pattern("https://example.com/*");
+    assert!(exact.matches(&url("https://example.com/")));
+    assert!(!exact.matches(&url("http://example.com/")));
+    assert!(!exact.matches(&url("https://www.example.com/")));
+}
+
+#[test]
+fn test_path_patterns() {
+    let wildcard = pattern("https://example.com/foo*bar");
+    assert!(wildcard.matches(&url("https://example.com/foobar")));
+    assert!(wildcard.matches(&url("https://example.com/foo/baz/bar")));
+    assert!(!wildcard.matches(&url("https://example.com/foo/bar/baz")));
+
+    let exact = pattern("https://example.com/foo");
+    assert!(exact.matches(&url("https://example.com/foo")));
+    assert!(!exact.matches(&url("https://example.com/foo/")));
+}
+
+#[test]
+fn test_invalid_patterns() {
+    assert!(MatchPattern::parse("ftp://example.com/*").is_none());
+    assert!(MatchPattern::parse("https://example.com").is_none());
+    assert!(MatchPattern::parse("https://exa*mple.com/*").is_none());
+    assert!(MatchPattern::parse("example.com/*").is_none());
+}
+
+#[test]
+fn test_content_script_applies_to() {
+    let mut content_script = ContentScript::new(vec![pattern("https://example.com/*")]);
+    content_script.exclude_matches = vec![pattern("https://example.com/private/*")];
+
+    assert!(content_script.applies_to(&url("https://example.com/"), true));
+    assert!(!content_script.applies_to(&url("https://example.com/"), false));
+    assert!(!content_script.applies_to(&url("https://example.com/private/"), true));
+
+    content_script.all_frames = true;
+    assert!(content_script.applies_to(&url("https://example.com/"), false));
+}
+
+#[test]
+fn test_content_scripts_default_to_their_own_isolated_world() {
+    let first = ContentScript::new(vec![pattern("<all_urls>")]);
+    let second = ContentScript::new(vec![pattern("<all_urls>")]);
+    assert!(matches!(first.world, JsWorld::Isolated(_)));
+    assert_ne!(first.world, second.world);
+}
@@ -0,0 +1,114 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use script_traits::content_script::ContentScriptRunAt;
+use script_traits::extension::{Extension, ExtensionError};
+use script_traits::JsWorld;
+use servo_url::ServoUrl;
+
+fn read_file(name: &str) -> Result<String, ExtensionError> {
+    match name {
+        "background.js" => Ok("// background".to_owned()),
+        "content.js" => Ok("// content".to_owned()),
+        "content.css" => Ok("body {}".to_owned()),
+        _ => Err(ExtensionError::Io(
+            name.to_owned(),
+            std::io::ErrorKind::NotFound.into(),
+        )),
+    }
+}
+
+#[test]
+fn test_load_manifest() {
+    let manifest = r#"{
+        "manifest_version": 3,
+        "name": "Example",
+        "version": "1.0",
+        "permissions": ["storage"],
+        "background": { "service_worker": "background.js" },
+        "content_scripts": [{
+            "matches": ["https://*.example.com/*"],
+            "exclude_matches": ["https://private.example.com/*"],
+            "js": ["content.js"],
+            "css": ["content.css"],
+            "run_at": "document_start"
+        }]
+    }"#;
+    let extension = Extension::from_manifest(manifest, read_file).unwrap();
+    assert_eq!(extension.name, "Example");
+    assert!(extension.has_permission("storage"));
+    assert!(!extension.has_permission("tabs"));
+    assert_eq!(
+        extension.background_script.as_deref(),
+        Some("// background")
+    );
+
+    assert_eq!(extension.content_scripts.len(), 1);
+    let content_script = &extension.content_scripts[0];
+    assert_eq!(content_script.js, vec!["// content".to_owned()]);
+    assert_eq!(content_script.css, vec!["body {}".to_owned()]);
+    assert_eq!(content_script.run_at, ContentScriptRunAt::DocumentStart);
+    assert_eq!(content_script.world, JsWorld::Extension(extension.id));
+    let url = |url: &str| ServoUrl::parse(url).unwrap();
+    assert!(content_script.applies_to(&url("https://www.example.com/"), true));
+    assert!(!content_script.applies_to(&url("https://private.example.com/"), true));
+    assert!(!content_script.applies_to(&url("https://www.example.com/"), false));
+
+    assert!(extension.injects_content_scripts_into(&url("https://www.example.com/"), true));
+    assert!(!extension.injects_content_scripts_into(&url("https://private.example.com/"), true));
+    assert!(!extension.injects_content_scripts_into(&url("https://example.org/"), true));
+}
+
+#[test]
+fn test_content_script_defaults() {
+    let manifest = r#"{
+        "manifest_version": 3,
+        "name": "Example",
+        "version": "1.0",
+        "content_scripts": [{ "matches": ["<all_urls>"], "js": ["content.js"] }]
+    }"#;
+    let extension = Extension::from_manifest(manifest, read_file).unwrap();
+    assert!(extension.background_script.is_none());
+    assert_eq!(
+        extension.content_scripts[0].run_at,
+        ContentScriptRunAt::DocumentIdle
+    );
+    assert!(!extension.content_scripts[0].all_frames);
+}
+
+#[test]
+fn test_invalid_manifests() {
+    let manifest = r#"{ "manifest_version": 2, "name": "Old", "version": "1.0" }"#;
+    assert!(matches!(
+        Extension::from_manifest(manifest, read_file),
+        Err(ExtensionError::UnsupportedManifestVersion(2))
+    ));
+
+    let manifest = r#"{
+        "manifest_version": 3,
+        "name": "Example",
+        "version": "1.0",
+        "content_scripts": [{ "matches": ["https://example.com"] }]
+    }"#;
+    assert!(matches!(
+        Extension::from_manifest(manifest, read_file),
+        Err(ExtensionError::InvalidMatchPattern(_))
+    ));
+
+    let manifest = r#"{
+        "manifest_version": 3,
+        "name": "Example",
+        "version": "1.0",
+        "background": { "service_worker": "missing.js" }
+    }"#;
+    assert!(matches!(
+        Extension::from_manifest(manifest, read_file),
+        Err(ExtensionError::Io(..))
+    ));
+
+    assert!(matches!(
+        Extension::from_manifest("{", read_file),
+        Err(ExtensionError::InvalidManifest(_))
+    ));
+}
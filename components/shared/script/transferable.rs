@@ -9,6 +9,8 @@
 
 use std::collections::VecDeque;
 
+use canvas_traits::canvas::PlaceholderCanvasFrame;
+use ipc_channel::ipc::IpcSender;
 use malloc_size_of_derive::MallocSizeOf;
 use msg::constellation_msg::MessagePortId;
 use serde::{Deserialize, Serialize};
@@ -162,3 +164,28 @@ impl MessagePortImpl {
         self.state = MessagePortState::Detached;
     }
 }
+
+#[derive(Debug, Deserialize, MallocSizeOf, Serialize)]
+/// The data backing a transferred `OffscreenCanvas`.
+pub struct OffscreenCanvasImpl {
+    /// The width of the bitmap of the canvas.
+    pub width: u64,
+    /// The height of the bitmap of the canvas.
+    pub height: u64,
+    /// Where the frames of the canvas are presented, if it took control of a canvas element.
+    #[ignore_malloc_size_of = "Defined in ipc-channel"]
+    pub placeholder: Option<IpcSender<PlaceholderCanvasFrame>>,
+}
+
+#[derive(Debug, Deserialize, MallocSizeOf, Serialize)]
+/// The data backing a transferred `ImageBitmap`.
+pub struct ImageBitmapImpl {
+    /// The width of the bitmap.
+    pub width: u32,
+    /// The height of the bitmap.
+    pub height: u32,
+    /// The pixels of the bitmap.
+    pub bitmap_data: Vec<u8>,
+    /// <https://html.spec.whatwg.org/multipage/#concept-imagebitmap-origin-clean>
+    pub origin_clean: bool,
+}
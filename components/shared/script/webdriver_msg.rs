@@ -80,6 +80,9 @@ pub enum WebDriverScriptCommand {
     GetElementText(String, IpcSender<Result<String, ErrorStatus>>),
     GetElementInViewCenterPoint(String, IpcSender<Result<Option<(i64, i64)>, ErrorStatus>>),
     GetBoundingClientRect(String, IpcSender<Result<Rect<f32>, ErrorStatus>>),
+    /// Scroll the viewport to the top left corner of the element, or as close to it as
+    /// the document can be scrolled.
+    ScrollIntoView(String, IpcSender<Result<(), ErrorStatus>>),
     GetBrowsingContextId(
         WebDriverFrameId,
         IpcSender<Result<BrowsingContextId, ErrorStatus>>,
@@ -89,6 +92,10 @@ pub enum WebDriverScriptCommand {
     IsEnabled(String, IpcSender<Result<bool, ErrorStatus>>),
     IsSelected(String, IpcSender<Result<bool, ErrorStatus>>),
     GetTitle(IpcSender<String>),
+    /// Pause or resume the animations of the document, freezing its animation timeline.
+    SetAnimationsPaused(bool, IpcSender<()>),
+    /// Reply once the next animation frame of the document ran.
+    WaitForAnimationFrame(IpcSender<()>),
 }
 
 #[derive(Debug, Deserialize, Serialize)]
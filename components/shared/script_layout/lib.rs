@@ -10,6 +10,7 @@
 
 pub mod message;
 pub mod rpc;
+pub mod user_stylesheets;
 pub mod wrapper_traits;
 
 use std::any::Any;
@@ -30,7 +31,7 @@ use net_traits::image_cache::{ImageCache, PendingImageId};
 use profile_traits::time;
 use script_traits::{
     ConstellationControlMsg, InitialScriptState, LayoutControlMsg, LayoutMsg, LoadData,
-    UntrustedNodeAddress, WebrenderIpcSender, WindowSizeData,
+    UntrustedNodeAddress, UserStylesheet, WebrenderIpcSender, WindowSizeData,
 };
 use servo_url::{ImmutableOrigin, ServoUrl};
 use style::data::ElementData;
@@ -185,6 +186,8 @@ pub struct LayoutConfig {
     pub webrender_api_sender: WebrenderIpcSender,
     pub paint_time_metrics: PaintTimeMetrics,
     pub window_size: WindowSizeData,
+    pub user_stylesheets: Vec<UserStylesheet>,
+    pub forced_colors: bool,
 }
 
 pub trait LayoutFactory: Send + Sync {
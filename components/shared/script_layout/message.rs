@@ -8,7 +8,9 @@ use euclid::default::{Point2D, Rect};
 use malloc_size_of_derive::MallocSizeOf;
 use msg::constellation_msg::BrowsingContextId;
 use profile_traits::mem::ReportsChan;
-use script_traits::{Painter, ScrollState, WindowSizeData};
+use script_traits::{
+    Painter, ScrollState, UntrustedNodeAddress, UserStylesheet, UserStylesheetId, WindowSizeData,
+};
 use servo_arc::Arc as ServoArc;
 use servo_atoms::Atom;
 use servo_url::ImmutableOrigin;
@@ -33,9 +35,18 @@ pub enum Msg {
     /// Removes a stylesheet from the document.
     RemoveStylesheet(ServoArc<Stylesheet>),
 
+    /// Adds a stylesheet at the user origin, injected by the embedder.
+    AddUserStylesheet(UserStylesheet),
+
+    /// Removes a stylesheet previously added with `AddUserStylesheet`.
+    RemoveUserStylesheet(UserStylesheetId),
+
     /// Change the quirks mode.
     SetQuirksMode(QuirksMode),
 
+    /// Paint in forced colors mode, or stop.
+    SetForcedColors(bool),
+
     /// Requests a reflow.
     Reflow(ScriptReflow),
 
@@ -72,6 +83,11 @@ pub enum QueryMsg {
     OffsetParentQuery(OpaqueNode),
     TextIndexQuery(OpaqueNode, Point2D<f32>),
     NodesFromPointQuery(Point2D<f32>, NodesFromPointQueryType),
+    /// The caret rectangle at a character offset in the text of a node.
+    CaretRectQuery(OpaqueNode, usize),
+    /// The node and character offset of the caret position closest to a point of the
+    /// viewport.
+    CaretPositionFromPointQuery(Point2D<f32>),
 
     // FIXME(nox): The following queries use the TrustedNodeAddress to
     // access actual DOM nodes, but those values can be constructed from
@@ -107,7 +123,9 @@ impl ReflowGoal {
                 QueryMsg::InnerWindowDimensionsQuery(_) |
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::ResolvedStyleQuery(..) |
-                QueryMsg::TextIndexQuery(..) => true,
+                QueryMsg::TextIndexQuery(..) |
+                QueryMsg::CaretRectQuery(..) |
+                QueryMsg::CaretPositionFromPointQuery(_) => true,
                 QueryMsg::ClientRectQuery(_) |
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
@@ -128,7 +146,9 @@ impl ReflowGoal {
             ReflowGoal::LayoutQuery(ref querymsg, _) => match *querymsg {
                 QueryMsg::NodesFromPointQuery(..) |
                 QueryMsg::TextIndexQuery(..) |
+                QueryMsg::CaretPositionFromPointQuery(_) |
                 QueryMsg::ElementInnerTextQuery(_) => true,
+                QueryMsg::CaretRectQuery(..) |
                 QueryMsg::ContentBoxQuery(_) |
                 QueryMsg::ContentBoxesQuery(_) |
                 QueryMsg::ClientRectQuery(_) |
@@ -155,6 +175,9 @@ pub struct Reflow {
 pub struct ReflowComplete {
     /// The list of images that were encountered that are in progress.
     pub pending_images: Vec<PendingImage>,
+    /// The elements with `content-visibility: auto` that generated boxes, if the fragment
+    /// tree was built again, which script keeps track of the relevancy to the user of.
+    pub content_visibility_auto_nodes: Option<Vec<UntrustedNodeAddress>>,
 }
 
 /// Information needed for a script-initiated reflow.
@@ -36,6 +36,10 @@ pub trait LayoutRPC {
     fn resolved_font_style(&self) -> Option<Arc<Font>>;
     fn offset_parent(&self) -> OffsetParentResponse;
     fn text_index(&self) -> TextIndexResponse;
+    /// Requests the caret rectangle at a character offset in the text of a node.
+    fn caret_rect(&self) -> CaretRectResponse;
+    /// Requests the caret position closest to a point of the viewport.
+    fn caret_position(&self) -> CaretPositionResponse;
     /// Requests the list of nodes from the given point.
     fn nodes_from_point_response(&self) -> Vec<UntrustedNodeAddress>;
     /// Query layout to get the inner text for a given element.
@@ -73,3 +77,13 @@ impl OffsetParentResponse {
 
 #[derive(Clone)]
 pub struct TextIndexResponse(pub Option<usize>);
+
+/// The caret rectangle at a character offset, with no width and as tall as the font of the
+/// text at that offset, in the same coordinates as `ContentBoxResponse`.
+#[derive(Clone)]
+pub struct CaretRectResponse(pub Option<Rect<Au>>);
+
+/// A caret position: a node, and a character offset in its text, which is 0 for a node
+/// that has no text of its own.
+#[derive(Clone)]
+pub struct CaretPositionResponse(pub Option<(UntrustedNodeAddress, usize)>);
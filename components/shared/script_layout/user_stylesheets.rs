@@ -0,0 +1,104 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The stylesheets injected by the embedder, which both layout engines add to their
+//! stylist at the user origin.
+
+use script_traits::{UserStylesheet, UserStylesheetId};
+use servo_arc::Arc as ServoArc;
+use style::context::QuirksMode;
+use style::error_reporting::RustLogReporter;
+use style::global_style_data::GLOBAL_STYLE_DATA;
+use style::media_queries::MediaList;
+use style::stylesheets::{DocumentStyleSheet, Origin, Stylesheet, UrlExtraData};
+
+/// The parsed user stylesheets of a layout, in the order they were added, which is the
+/// order they cascade in.
+#[derive(Default)]
+pub struct UserStylesheets {
+    stylesheets: Vec<(UserStylesheetId, DocumentStyleSheet)>,
+}
+
+impl UserStylesheets {
+    pub fn new(stylesheets: &[UserStylesheet]) -> UserStylesheets {
+        UserStylesheets {
+            stylesheets: stylesheets
+                .iter()
+                .map(|stylesheet| (stylesheet.id, parse(stylesheet)))
+                .collect(),
+        }
+    }
+
+    /// Parse and add `stylesheet` after the others, returning it so that it can be added
+    /// to the stylist.
+    pub fn add(&mut self, stylesheet: &UserStylesheet) -> DocumentStyleSheet {
+        let parsed = parse(stylesheet);
+        self.stylesheets.push((stylesheet.id, parsed.clone()));
+        parsed
+    }
+
+    /// Remove the stylesheet with the given id, returning it so that it can be removed
+    /// from the stylist.
+    pub fn remove(&mut self, id: UserStylesheetId) -> Option<DocumentStyleSheet> {
+        let index = self
+            .stylesheets
+            .iter()
+            .position(|(stylesheet_id, _)| *stylesheet_id == id)?;
+        Some(self.stylesheets.remove(index).1)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DocumentStyleSheet> {
+        self.stylesheets.iter().map(|(_, stylesheet)| stylesheet)
+    }
+}
+
+/// Parse a stylesheet injected by the embedder. These live at the user origin, and so
+/// share the lock of the user agent stylesheets.
+fn parse(stylesheet: &UserStylesheet) -> DocumentStyleSheet {
+    DocumentStyleSheet(ServoArc::new(Stylesheet::from_bytes(
+        stylesheet.source.as_bytes(),
+        UrlExtraData(stylesheet.url.get_arc()),
+        None,
+        None,
+        Origin::User,
+        MediaList::empty(),
+        GLOBAL_STYLE_DATA.shared_lock.clone(),
+        None,
+        Some(&RustLogReporter),
+        QuirksMode::NoQuirks,
+    )))
+}
+
+#[cfg(test)]
+mod test {
+    use script_traits::UserStylesheet;
+    use servo_arc::Arc as ServoArc;
+    use servo_url::ServoUrl;
+
+    use crate::user_stylesheets::UserStylesheets;
+
+    fn stylesheet(source: &str) -> UserStylesheet {
+        let url = ServoUrl::parse("file:///user.css").expect("Incorrect test case");
+        UserStylesheet::new(source.to_owned(), url)
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let first = stylesheet("p { color: red }");
+        let second = stylesheet("p { color: green }");
+        let mut stylesheets = UserStylesheets::new(&[first.clone()]);
+        let added = stylesheets.add(&second);
+        assert_eq!(stylesheets.iter().count(), 2);
+        // Stylesheets cascade in the order they were added.
+        let last = stylesheets.iter().last().expect("Incorrect test case");
+        assert!(ServoArc::ptr_eq(&last.0, &added.0));
+
+        let removed = stylesheets.remove(first.id).expect("Incorrect test case");
+        assert!(!ServoArc::ptr_eq(&removed.0, &added.0));
+        assert!(stylesheets.remove(first.id).is_none());
+        let remaining: Vec<_> = stylesheets.iter().collect();
+        assert_eq!(remaining.len(), 1);
+        assert!(ServoArc::ptr_eq(&remaining[0].0, &added.0));
+    }
+}
@@ -19,7 +19,7 @@ use servo_url::ServoUrl;
 use style::attr::AttrValue;
 use style::context::SharedStyleContext;
 use style::data::ElementData;
-use style::dom::{LayoutIterator, NodeInfo, OpaqueNode, TElement, TNode};
+use style::dom::{DomChildren, LayoutIterator, NodeInfo, OpaqueNode, TElement, TNode};
 use style::properties::ComputedValues;
 use style::selector_parser::{PseudoElement, PseudoElementCascadeType, SelectorImpl};
 use style::stylist::RuleInclusion;
@@ -99,6 +99,13 @@ pub trait LayoutNode<'dom>:
     unsafe fn init_style_and_opaque_layout_data(&self, data: Box<StyleAndOpaqueLayoutData>);
     unsafe fn take_style_and_opaque_layout_data(&self) -> Box<StyleAndOpaqueLayoutData>;
 
+    /// The children of this node in the flat tree, which is the tree that is styled and laid
+    /// out: the children of its shadow root if it is a shadow host, and its own children
+    /// otherwise. The parent of a node in the flat tree is its `parent_node`, which is the
+    /// host of its shadow root rather than the shadow root itself.
+    /// <https://drafts.csswg.org/css-scoping/#flat-tree>
+    fn flat_tree_children(self) -> DomChildren<Self>;
+
     fn rev_children(self) -> LayoutIterator<ReverseChildrenIterator<Self>> {
         LayoutIterator(ReverseChildrenIterator {
             current: self.last_child(),
@@ -499,4 +506,10 @@ pub trait ThreadSafeLayoutElement<'dom>:
     /// of the parent data is fine, since the bottom-up traversal will not process
     /// the parent until all the children have been processed.
     fn is_body_element_of_html_element_root(&self) -> bool;
+
+    /// Returns whether script last found this element to be relevant to the user, which
+    /// decides whether the contents of an element with `content-visibility: auto` are laid
+    /// out.
+    /// <https://drafts.csswg.org/css-contain-2/#relevant-to-the-user>
+    fn is_relevant_to_the_user(&self) -> bool;
 }
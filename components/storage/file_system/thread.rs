@@ -0,0 +1,430 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The thread keeping the origin private file systems: it finds the entries script asks for
+//! in the directory of their origin, and keeps the files access handles are open on, which
+//! stay locked until the handles are closed.
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+use std::{env, process, thread};
+
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use log::warn;
+use net_traits::file_system_thread::{
+    is_valid_file_name, FileSystemEntryKind, FileSystemError, FileSystemResult,
+    FileSystemThreadMsg, SyncAccessHandleId,
+};
+use servo_url::ImmutableOrigin;
+
+use crate::quota::{QuotaManager, StorageEndpoint};
+
+pub trait FileSystemThreadFactory {
+    fn new(config_dir: Option<PathBuf>, quota: Arc<QuotaManager>) -> Self;
+}
+
+impl FileSystemThreadFactory for IpcSender<FileSystemThreadMsg> {
+    /// Create a file system thread, keeping the file systems in `config_dir` if given, and in
+    /// a temporary directory removed on exit otherwise.
+    fn new(
+        config_dir: Option<PathBuf>,
+        quota: Arc<QuotaManager>,
+    ) -> IpcSender<FileSystemThreadMsg> {
+        let (chan, port) = ipc::channel().unwrap();
+        let (directory, temporary) = match config_dir {
+            Some(config_dir) => (config_dir.join("file_system"), false),
+            None => (
+                env::temp_dir().join(format!("servo-file-system-{}", process::id())),
+                true,
+            ),
+        };
+        let evictor_chan = chan.clone();
+        quota.register_evictor(StorageEndpoint::FileSystem, move |origin| {
+            let _ = evictor_chan.send(FileSystemThreadMsg::ClearOrigin(origin));
+        });
+        thread::Builder::new()
+            .name("FileSystemManager".to_owned())
+            .spawn(move || {
+                FileSystemManager::new(port, directory, temporary, quota).start();
+            })
+            .expect("Thread spawning failed");
+        chan
+    }
+}
+
+/// A file an access handle is open on.
+struct AccessHandle {
+    origin: ImmutableOrigin,
+    path: Vec<String>,
+    file: File,
+}
+
+struct FileSystemManager {
+    port: IpcReceiver<FileSystemThreadMsg>,
+    /// The directory holding the directory of each origin.
+    directory: PathBuf,
+    /// Whether `directory` is removed on exit.
+    temporary: bool,
+    quota: Arc<QuotaManager>,
+    access_handles: HashMap<SyncAccessHandleId, AccessHandle>,
+    next_access_handle_id: u64,
+}
+
+fn file_system_error(error: io::Error) -> FileSystemError {
+    if error.kind() == io::ErrorKind::NotFound {
+        return FileSystemError::NotFound;
+    }
+    warn!("File system operation failed ({:?})", error);
+    FileSystemError::Io
+}
+
+fn read_at(file: &mut File, offset: u64, length: u64) -> io::Result<Vec<u8>> {
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buffer = Vec::new();
+    file.by_ref().take(length).read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+fn write_at(file: &mut File, offset: u64, data: &[u8]) -> io::Result<u64> {
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(data)?;
+    Ok(data.len() as u64)
+}
+
+/// The bytes the files in `directory` take, counting those of the directories it holds.
+fn directory_size(directory: &Path) -> u64 {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+    entries
+        .filter_map(Result::ok)
+        .map(|entry| match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) => metadata.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+impl FileSystemManager {
+    fn new(
+        port: IpcReceiver<FileSystemThreadMsg>,
+        directory: PathBuf,
+        temporary: bool,
+        quota: Arc<QuotaManager>,
+    ) -> FileSystemManager {
+        FileSystemManager {
+            port,
+            directory,
+            temporary,
+            quota,
+            access_handles: HashMap::new(),
+            next_access_handle_id: 1,
+        }
+    }
+
+    fn start(&mut self) {
+        loop {
+            match self.port.recv().unwrap() {
+                FileSystemThreadMsg::GetEntry {
+                    origin,
+                    path,
+                    name,
+                    kind,
+                    create,
+                    sender,
+                } => {
+                    let _ = sender.send(self.get_entry(&origin, &path, &name, kind, create));
+                },
+                FileSystemThreadMsg::RemoveEntry {
+                    origin,
+                    path,
+                    name,
+                    recursive,
+                    sender,
+                } => {
+                    let result = self.remove_entry(&origin, &path, &name, recursive);
+                    if result.is_ok() {
+                        let directory = self.directory.join(crate::origin_file_name(&origin));
+                        self.quota.set_usage(
+                            &origin,
+                            StorageEndpoint::FileSystem,
+                            directory_size(&directory),
+                        );
+                    }
+                    let _ = sender.send(result);
+                },
+                FileSystemThreadMsg::GetFile(sender, origin, path) => {
+                    let _ = sender.send(self.get_file(&origin, &path));
+                },
+                FileSystemThreadMsg::CreateSyncAccessHandle(sender, origin, path) => {
+                    let _ = sender.send(self.create_sync_access_handle(origin, path));
+                },
+                FileSystemThreadMsg::Read(sender, id, offset, length) => {
+                    let result = self
+                        .access_handle(id)
+                        .and_then(|file| read_at(file, offset, length).map_err(file_system_error));
+                    let _ = sender.send(result);
+                },
+                FileSystemThreadMsg::Write(sender, id, offset, data) => {
+                    let end = offset + data.len() as u64;
+                    let result = self.resize(
+                        id,
+                        |length| length.max(end),
+                        |file| write_at(file, offset, &data),
+                    );
+                    let _ = sender.send(result);
+                },
+                FileSystemThreadMsg::Truncate(sender, id, size) => {
+                    let result = self.resize(id, |_| size, |file| file.set_len(size));
+                    let _ = sender.send(result);
+                },
+                FileSystemThreadMsg::GetSize(sender, id) => {
+                    let result = self.access_handle(id).and_then(|file| {
+                        file.metadata()
+                            .map(|metadata| metadata.len())
+                            .map_err(file_system_error)
+                    });
+                    let _ = sender.send(result);
+                },
+                FileSystemThreadMsg::Flush(sender, id) => {
+                    let result = self
+                        .access_handle(id)
+                        .and_then(|file| file.sync_all().map_err(file_system_error));
+                    let _ = sender.send(result);
+                },
+                FileSystemThreadMsg::Close(id) => {
+                    self.access_handles.remove(&id);
+                },
+                FileSystemThreadMsg::ClearOrigin(origin) => self.clear_origin(&origin),
+                FileSystemThreadMsg::Exit(sender) => {
+                    self.access_handles.clear();
+                    if self.temporary {
+                        let _ = fs::remove_dir_all(&self.directory);
+                    }
+                    let _ = sender.send(());
+                    break;
+                },
+            }
+        }
+    }
+
+    /// The path on disk of the entry at `path` in the file system of `origin`.
+    fn resolve(&self, origin: &ImmutableOrigin, path: &[String]) -> FileSystemResult<PathBuf> {
+        let mut resolved = self.directory.join(crate::origin_file_name(origin));
+        for name in path {
+            if !is_valid_file_name(name) {
+                return Err(FileSystemError::InvalidName);
+            }
+            resolved.push(name);
+        }
+        Ok(resolved)
+    }
+
+    /// The path on disk of the directory at `path`, which must exist. The root directory of
+    /// the file system is created when first used.
+    fn directory(&self, origin: &ImmutableOrigin, path: &[String]) -> FileSystemResult<PathBuf> {
+        let directory = self.resolve(origin, path)?;
+        if path.is_empty() {
+            fs::create_dir_all(&directory).map_err(file_system_error)?;
+        }
+        match fs::metadata(&directory) {
+            Ok(metadata) if metadata.is_dir() => Ok(directory),
+            Ok(_) => Err(FileSystemError::TypeMismatch),
+            Err(error) => Err(file_system_error(error)),
+        }
+    }
+
+    /// The path on disk of the file at `path`, which must exist.
+    fn file(&self, origin: &ImmutableOrigin, path: &[String]) -> FileSystemResult<PathBuf> {
+        if path.is_empty() {
+            return Err(FileSystemError::TypeMismatch);
+        }
+        let file = self.resolve(origin, path)?;
+        match fs::metadata(&file) {
+            Ok(metadata) if metadata.is_file() => Ok(file),
+            Ok(_) => Err(FileSystemError::TypeMismatch),
+            Err(error) => Err(file_system_error(error)),
+        }
+    }
+
+    /// Whether an access handle is open on the entry at `path`, or on a file it holds.
+    fn is_locked(&self, origin: &ImmutableOrigin, path: &[String]) -> bool {
+        self.access_handles
+            .values()
+            .any(|handle| handle.origin == *origin && handle.path.starts_with(path))
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getfilehandle>
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-getdirectoryhandle>
+    fn get_entry(
+        &self,
+        origin: &ImmutableOrigin,
+        path: &[String],
+        name: &str,
+        kind: FileSystemEntryKind,
+        create: bool,
+    ) -> FileSystemResult<()> {
+        let directory = self.directory(origin, path)?;
+        if !is_valid_file_name(name) {
+            return Err(FileSystemError::InvalidName);
+        }
+        let entry = directory.join(name);
+        match fs::metadata(&entry) {
+            Ok(metadata) => {
+                let found = if metadata.is_dir() {
+                    FileSystemEntryKind::Directory
+                } else {
+                    FileSystemEntryKind::File
+                };
+                if found == kind {
+                    Ok(())
+                } else {
+                    Err(FileSystemError::TypeMismatch)
+                }
+            },
+            Err(error) if error.kind() == io::ErrorKind::NotFound && create => match kind {
+                FileSystemEntryKind::File => OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .open(&entry)
+                    .map(|_| ()),
+                FileSystemEntryKind::Directory => fs::create_dir(&entry),
+            }
+            .map_err(file_system_error),
+            Err(error) => Err(file_system_error(error)),
+        }
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemdirectoryhandle-removeentry>
+    fn remove_entry(
+        &self,
+        origin: &ImmutableOrigin,
+        path: &[String],
+        name: &str,
+        recursive: bool,
+    ) -> FileSystemResult<()> {
+        let directory = self.directory(origin, path)?;
+        if !is_valid_file_name(name) {
+            return Err(FileSystemError::InvalidName);
+        }
+        let mut entry_path = path.to_vec();
+        entry_path.push(name.to_owned());
+        if self.is_locked(origin, &entry_path) {
+            return Err(FileSystemError::NoModificationAllowed);
+        }
+
+        let entry = directory.join(name);
+        let metadata = fs::metadata(&entry).map_err(file_system_error)?;
+        if !metadata.is_dir() {
+            return fs::remove_file(&entry).map_err(file_system_error);
+        }
+        if recursive {
+            return fs::remove_dir_all(&entry).map_err(file_system_error);
+        }
+        let mut children = fs::read_dir(&entry).map_err(file_system_error)?;
+        if children.next().is_some() {
+            return Err(FileSystemError::InvalidModification);
+        }
+        fs::remove_dir(&entry).map_err(file_system_error)
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-getfile>
+    fn get_file(
+        &self,
+        origin: &ImmutableOrigin,
+        path: &[String],
+    ) -> FileSystemResult<(Vec<u8>, i64)> {
+        let file = self.file(origin, path)?;
+        let contents = fs::read(&file).map_err(file_system_error)?;
+        let modified = fs::metadata(&file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |modified| modified.as_millis() as i64);
+        Ok((contents, modified))
+    }
+
+    /// <https://fs.spec.whatwg.org/#dom-filesystemfilehandle-createsyncaccesshandle>
+    fn create_sync_access_handle(
+        &mut self,
+        origin: ImmutableOrigin,
+        path: Vec<String>,
+    ) -> FileSystemResult<SyncAccessHandleId> {
+        let file = self.file(&origin, &path)?;
+        if self.is_locked(&origin, &path) {
+            return Err(FileSystemError::NoModificationAllowed);
+        }
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&file)
+            .map_err(file_system_error)?;
+        let id = SyncAccessHandleId(self.next_access_handle_id);
+        self.next_access_handle_id += 1;
+        self.access_handles
+            .insert(id, AccessHandle { origin, path, file });
+        Ok(id)
+    }
+
+    /// Change the file an access handle is open on with `change`, which makes the file as
+    /// long as `new_length` gives for its current length, if that keeps the storage of its
+    /// origin within its quota.
+    fn resize<T>(
+        &mut self,
+        id: SyncAccessHandleId,
+        new_length: impl FnOnce(u64) -> u64,
+        change: impl FnOnce(&mut File) -> io::Result<T>,
+    ) -> FileSystemResult<T> {
+        let handle = self
+            .access_handles
+            .get_mut(&id)
+            .ok_or(FileSystemError::InvalidState)?;
+        let length = handle.file.metadata().map_err(file_system_error)?.len();
+        let new_length = new_length(length);
+        let usage = self
+            .quota
+            .usage(&handle.origin, StorageEndpoint::FileSystem);
+        let new_usage = (usage + new_length).saturating_sub(length);
+        if new_length > length &&
+            !self
+                .quota
+                .allows_usage(&handle.origin, StorageEndpoint::FileSystem, new_usage)
+        {
+            return Err(FileSystemError::QuotaExceeded);
+        }
+        let result = change(&mut handle.file).map_err(file_system_error)?;
+        self.quota
+            .set_usage(&handle.origin, StorageEndpoint::FileSystem, new_usage);
+        Ok(result)
+    }
+
+    /// Remove the file system of `origin`, closing the access handles open on its files.
+    fn clear_origin(&mut self, origin: &ImmutableOrigin) {
+        self.access_handles
+            .retain(|_, handle| handle.origin != *origin);
+        let directory = self.directory.join(crate::origin_file_name(origin));
+        if let Err(error) = fs::remove_dir_all(directory) {
+            if error.kind() != io::ErrorKind::NotFound {
+                warn!(
+                    "Could not remove the file system of {:?} ({})",
+                    origin, error
+                );
+            }
+        }
+        self.quota.set_usage(origin, StorageEndpoint::FileSystem, 0);
+    }
+
+    fn access_handle(&mut self, id: SyncAccessHandleId) -> FileSystemResult<&mut File> {
+        self.access_handles
+            .get_mut(&id)
+            .map(|handle| &mut handle.file)
+            .ok_or(FileSystemError::InvalidState)
+    }
+}
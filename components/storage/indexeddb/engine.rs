@@ -0,0 +1,716 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The records of the databases, and the operations transactions run on them.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use net_traits::indexeddb_thread::{
+    BackendError, BackendResult, CursorDirection, DatabaseMetadata, IndexMetadata, IndexedDBKey,
+    IndexedDBKeyRange, IndexedDBRecord, KeyPath, ObjectStoreMetadata, OperationResult,
+    RecordSource, TransactionOperation,
+};
+use serde::{Deserialize, Serialize};
+
+/// The largest number a key generator generates.
+/// <https://w3c.github.io/IndexedDB/#key-generator-construct>
+const MAX_GENERATED_KEY: f64 = 9007199254740992.0;
+
+/// The serialized value of a record, and its keys in each index of its object store.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StoredRecord {
+    pub value: Vec<u8>,
+    pub index_keys: BTreeMap<String, Vec<IndexedDBKey>>,
+}
+
+/// The primary keys of the records of an object store, by their key in an index.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Index {
+    key_path: KeyPath,
+    unique: bool,
+    multi_entry: bool,
+    entries: BTreeMap<IndexedDBKey, BTreeSet<IndexedDBKey>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ObjectStore {
+    key_path: Option<KeyPath>,
+    auto_increment: bool,
+    /// The next key the key generator generates, if the store has one.
+    current_number: f64,
+    records: BTreeMap<IndexedDBKey, StoredRecord>,
+    indexes: BTreeMap<String, Index>,
+}
+
+impl ObjectStore {
+    fn insert_record(&mut self, key: IndexedDBKey, record: StoredRecord) {
+        for (name, keys) in &record.index_keys {
+            if let Some(index) = self.indexes.get_mut(name) {
+                for index_key in keys {
+                    index
+                        .entries
+                        .entry(index_key.clone())
+                        .or_default()
+                        .insert(key.clone());
+                }
+            }
+        }
+        self.records.insert(key, record);
+    }
+
+    fn remove_record(&mut self, key: &IndexedDBKey) -> Option<StoredRecord> {
+        let record = self.records.remove(key)?;
+        for (name, keys) in &record.index_keys {
+            if let Some(index) = self.indexes.get_mut(name) {
+                for index_key in keys {
+                    if let Some(primary_keys) = index.entries.get_mut(index_key) {
+                        primary_keys.remove(key);
+                        if primary_keys.is_empty() {
+                            index.entries.remove(index_key);
+                        }
+                    }
+                }
+            }
+        }
+        Some(record)
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#possibly-update-the-key-generator>
+    fn possibly_update_key_generator(&mut self, key: &IndexedDBKey) {
+        if let IndexedDBKey::Number(number) = *key {
+            let value = number.min(MAX_GENERATED_KEY).floor();
+            if value >= self.current_number {
+                self.current_number = value + 1.0;
+            }
+        }
+    }
+
+    fn metadata(&self, name: &str) -> ObjectStoreMetadata {
+        ObjectStoreMetadata {
+            name: name.to_owned(),
+            key_path: self.key_path.clone(),
+            auto_increment: self.auto_increment,
+            indexes: self
+                .indexes
+                .iter()
+                .map(|(name, index)| IndexMetadata {
+                    name: name.clone(),
+                    key_path: index.key_path.clone(),
+                    unique: index.unique,
+                    multi_entry: index.multi_entry,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// <https://w3c.github.io/IndexedDB/#database-construct>
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Database {
+    pub version: u64,
+    object_stores: BTreeMap<String, ObjectStore>,
+}
+
+/// A change of the records of a database, as written to the log once the transaction making
+/// it commits.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Change {
+    Put {
+        store: String,
+        key: IndexedDBKey,
+        record: StoredRecord,
+    },
+    Delete {
+        store: String,
+        key: IndexedDBKey,
+    },
+    Clear(String),
+    KeyGenerator {
+        store: String,
+        current_number: f64,
+    },
+}
+
+/// What undoes a change of a database when its transaction aborts.
+enum Undo {
+    Record {
+        store: String,
+        key: IndexedDBKey,
+        old: Option<StoredRecord>,
+    },
+    Store {
+        name: String,
+        old: Option<ObjectStore>,
+    },
+    KeyGenerator {
+        store: String,
+        current_number: f64,
+    },
+}
+
+/// The changes a transaction made so far, to write them once it commits or undo them if it
+/// aborts.
+#[derive(Default)]
+pub struct Journal {
+    pub changes: Vec<Change>,
+    undo: Vec<Undo>,
+}
+
+impl Database {
+    pub fn metadata(&self, name: &str) -> DatabaseMetadata {
+        DatabaseMetadata {
+            name: name.to_owned(),
+            version: self.version,
+            object_stores: self
+                .object_stores
+                .iter()
+                .map(|(name, store)| store.metadata(name))
+                .collect(),
+        }
+    }
+
+    pub fn store_names(&self) -> Vec<String> {
+        self.object_stores.keys().cloned().collect()
+    }
+
+    fn store(&self, name: &str) -> BackendResult<&ObjectStore> {
+        self.object_stores.get(name).ok_or(BackendError::NotFound)
+    }
+
+    fn store_mut(&mut self, name: &str) -> BackendResult<&mut ObjectStore> {
+        self.object_stores
+            .get_mut(name)
+            .ok_or(BackendError::NotFound)
+    }
+
+    /// Apply a change read from the log.
+    pub fn apply(&mut self, change: Change) {
+        let store_name = match change {
+            Change::Put { ref store, .. } |
+            Change::Delete { ref store, .. } |
+            Change::KeyGenerator { ref store, .. } => store,
+            Change::Clear(ref store) => store,
+        };
+        let store = match self.object_stores.get_mut(store_name) {
+            Some(store) => store,
+            None => return,
+        };
+        match change {
+            Change::Put { key, record, .. } => {
+                store.remove_record(&key);
+                store.insert_record(key, record);
+            },
+            Change::Delete { key, .. } => {
+                store.remove_record(&key);
+            },
+            Change::Clear(_) => {
+                store.records.clear();
+                for index in store.indexes.values_mut() {
+                    index.entries.clear();
+                }
+            },
+            Change::KeyGenerator { current_number, .. } => {
+                store.current_number = current_number;
+            },
+        }
+    }
+
+    /// Undo the changes of an aborted transaction, latest first.
+    pub fn rollback(&mut self, journal: Journal) {
+        for undo in journal.undo.into_iter().rev() {
+            match undo {
+                Undo::Record { store, key, old } => {
+                    if let Some(store) = self.object_stores.get_mut(&store) {
+                        store.remove_record(&key);
+                        if let Some(old) = old {
+                            store.insert_record(key, old);
+                        }
+                    }
+                },
+                Undo::Store { name, old } => match old {
+                    Some(old) => {
+                        self.object_stores.insert(name, old);
+                    },
+                    None => {
+                        self.object_stores.remove(&name);
+                    },
+                },
+                Undo::KeyGenerator {
+                    store,
+                    current_number,
+                } => {
+                    if let Some(store) = self.object_stores.get_mut(&store) {
+                        store.current_number = current_number;
+                    }
+                },
+            }
+        }
+    }
+
+    /// Run `operation`, recording what it changes in `journal`.
+    pub fn execute(
+        &mut self,
+        operation: TransactionOperation,
+        journal: &mut Journal,
+    ) -> BackendResult<OperationResult> {
+        match operation {
+            TransactionOperation::Get {
+                source,
+                range,
+                keys_only,
+            } => {
+                let entry = self.entries(&source, &range)?.next();
+                let record = match entry {
+                    Some((key, primary_key)) => {
+                        Some(self.record(&source, key, primary_key, keys_only)?)
+                    },
+                    None => None,
+                };
+                Ok(OperationResult::Record(record))
+            },
+            TransactionOperation::GetAll {
+                source,
+                range,
+                count,
+                keys_only,
+            } => {
+                let count = match count {
+                    Some(count) if count > 0 => count as usize,
+                    _ => usize::MAX,
+                };
+                let records = self
+                    .entries(&source, &range)?
+                    .take(count)
+                    .map(|(key, primary_key)| self.record(&source, key, primary_key, keys_only))
+                    .collect::<BackendResult<_>>()?;
+                Ok(OperationResult::Records(records))
+            },
+            TransactionOperation::Count { source, range } => Ok(OperationResult::Count(
+                self.entries(&source, &range)?.count() as u64,
+            )),
+            TransactionOperation::Iterate {
+                source,
+                range,
+                direction,
+                position,
+                target,
+                count,
+                keys_only,
+            } => {
+                let entry = self.iterate(&source, &range, direction, position, target, count)?;
+                let record = match entry {
+                    Some((key, primary_key)) => {
+                        Some(self.record(&source, &key, &primary_key, keys_only)?)
+                    },
+                    None => None,
+                };
+                Ok(OperationResult::Record(record))
+            },
+            TransactionOperation::Put {
+                store,
+                key,
+                value,
+                index_keys,
+                overwrite,
+            } => self
+                .put(store, key, value, index_keys, overwrite, journal)
+                .map(OperationResult::Key),
+            TransactionOperation::Delete { store, range } => {
+                let object_store = self.store_mut(&store)?;
+                let keys: Vec<_> = if range.is_empty() {
+                    vec![]
+                } else {
+                    object_store
+                        .records
+                        .range(range.bounds())
+                        .map(|(key, _)| key.clone())
+                        .collect()
+                };
+                for key in keys {
+                    let old = object_store.remove_record(&key);
+                    journal.undo.push(Undo::Record {
+                        store: store.clone(),
+                        key: key.clone(),
+                        old,
+                    });
+                    journal.changes.push(Change::Delete {
+                        store: store.clone(),
+                        key,
+                    });
+                }
+                Ok(OperationResult::Done)
+            },
+            TransactionOperation::Clear(store) => {
+                let object_store = self.store_mut(&store)?;
+                journal.undo.push(Undo::Store {
+                    name: store.clone(),
+                    old: Some(object_store.clone()),
+                });
+                object_store.records.clear();
+                for index in object_store.indexes.values_mut() {
+                    index.entries.clear();
+                }
+                journal.changes.push(Change::Clear(store));
+                Ok(OperationResult::Done)
+            },
+            TransactionOperation::CreateObjectStore(metadata) => {
+                if self.object_stores.contains_key(&metadata.name) {
+                    return Err(BackendError::Constraint);
+                }
+                journal.undo.push(Undo::Store {
+                    name: metadata.name.clone(),
+                    old: None,
+                });
+                self.object_stores.insert(
+                    metadata.name,
+                    ObjectStore {
+                        key_path: metadata.key_path,
+                        auto_increment: metadata.auto_increment,
+                        current_number: 1.0,
+                        records: BTreeMap::new(),
+                        indexes: BTreeMap::new(),
+                    },
+                );
+                Ok(OperationResult::Done)
+            },
+            TransactionOperation::DeleteObjectStore(name) => {
+                let old = self
+                    .object_stores
+                    .remove(&name)
+                    .ok_or(BackendError::NotFound)?;
+                journal.undo.push(Undo::Store {
+                    name,
+                    old: Some(old),
+                });
+                Ok(OperationResult::Done)
+            },
+            TransactionOperation::RenameObjectStore { from, to } => {
+                if self.object_stores.contains_key(&to) {
+                    return Err(BackendError::Constraint);
+                }
+                let store = self
+                    .object_stores
+                    .remove(&from)
+                    .ok_or(BackendError::NotFound)?;
+                journal.undo.push(Undo::Store {
+                    name: from,
+                    old: Some(store.clone()),
+                });
+                journal.undo.push(Undo::Store {
+                    name: to.clone(),
+                    old: None,
+                });
+                self.object_stores.insert(to, store);
+                Ok(OperationResult::Done)
+            },
+            TransactionOperation::CreateIndex { store, index, keys } => {
+                let object_store = self.store_mut(&store)?;
+                if object_store.indexes.contains_key(&index.name) {
+                    return Err(BackendError::Constraint);
+                }
+                journal.undo.push(Undo::Store {
+                    name: store,
+                    old: Some(object_store.clone()),
+                });
+                let mut entries: BTreeMap<IndexedDBKey, BTreeSet<IndexedDBKey>> = BTreeMap::new();
+                for (primary_key, index_keys) in keys {
+                    let record = match object_store.records.get_mut(&primary_key) {
+                        Some(record) => record,
+                        None => continue,
+                    };
+                    for index_key in &index_keys {
+                        let primary_keys = entries.entry(index_key.clone()).or_default();
+                        primary_keys.insert(primary_key.clone());
+                        if index.unique && primary_keys.len() > 1 {
+                            return Err(BackendError::Constraint);
+                        }
+                    }
+                    record.index_keys.insert(index.name.clone(), index_keys);
+                }
+                object_store.indexes.insert(
+                    index.name,
+                    Index {
+                        key_path: index.key_path,
+                        unique: index.unique,
+                        multi_entry: index.multi_entry,
+                        entries,
+                    },
+                );
+                Ok(OperationResult::Done)
+            },
+            TransactionOperation::DeleteIndex { store, index } => {
+                let object_store = self.store_mut(&store)?;
+                if !object_store.indexes.contains_key(&index) {
+                    return Err(BackendError::NotFound);
+                }
+                journal.undo.push(Undo::Store {
+                    name: store,
+                    old: Some(object_store.clone()),
+                });
+                object_store.indexes.remove(&index);
+                for record in object_store.records.values_mut() {
+                    record.index_keys.remove(&index);
+                }
+                Ok(OperationResult::Done)
+            },
+            TransactionOperation::RenameIndex { store, from, to } => {
+                let object_store = self.store_mut(&store)?;
+                if object_store.indexes.contains_key(&to) {
+                    return Err(BackendError::Constraint);
+                }
+                if !object_store.indexes.contains_key(&from) {
+                    return Err(BackendError::NotFound);
+                }
+                journal.undo.push(Undo::Store {
+                    name: store,
+                    old: Some(object_store.clone()),
+                });
+                let index = object_store.indexes.remove(&from).unwrap();
+                object_store.indexes.insert(to.clone(), index);
+                for record in object_store.records.values_mut() {
+                    if let Some(keys) = record.index_keys.remove(&from) {
+                        record.index_keys.insert(to.clone(), keys);
+                    }
+                }
+                Ok(OperationResult::Done)
+            },
+        }
+    }
+
+    /// The keys and primary keys of the records of `source` in `range`, in ascending order.
+    fn entries<'a>(
+        &'a self,
+        source: &RecordSource,
+        range: &'a IndexedDBKeyRange,
+    ) -> BackendResult<Box<dyn DoubleEndedIterator<Item = (&'a IndexedDBKey, &'a IndexedDBKey)> + 'a>>
+    {
+        let store = self.store(source.store())?;
+        if range.is_empty() {
+            return Ok(Box::new(std::iter::empty()));
+        }
+        match *source {
+            RecordSource::ObjectStore(_) => Ok(Box::new(
+                store
+                    .records
+                    .range(range.bounds())
+                    .map(|(key, _)| (key, key)),
+            )),
+            RecordSource::Index { ref index, .. } => {
+                let index = store.indexes.get(index).ok_or(BackendError::NotFound)?;
+                Ok(Box::new(index.entries.range(range.bounds()).flat_map(
+                    |(key, primary_keys)| {
+                        primary_keys
+                            .iter()
+                            .map(move |primary_key| (key, primary_key))
+                    },
+                )))
+            },
+        }
+    }
+
+    fn record(
+        &self,
+        source: &RecordSource,
+        key: &IndexedDBKey,
+        primary_key: &IndexedDBKey,
+        keys_only: bool,
+    ) -> BackendResult<IndexedDBRecord> {
+        let value = if keys_only {
+            None
+        } else {
+            let store = self.store(source.store())?;
+            let record = store
+                .records
+                .get(primary_key)
+                .ok_or(BackendError::NotFound)?;
+            Some(record.value.clone())
+        };
+        Ok(IndexedDBRecord {
+            key: key.clone(),
+            primary_key: primary_key.clone(),
+            value,
+        })
+    }
+
+    /// The key and primary key of the record a cursor moves to.
+    /// <https://w3c.github.io/IndexedDB/#iterate-a-cursor>
+    fn iterate(
+        &self,
+        source: &RecordSource,
+        range: &IndexedDBKeyRange,
+        direction: CursorDirection,
+        position: Option<(IndexedDBKey, IndexedDBKey)>,
+        target: Option<(IndexedDBKey, Option<IndexedDBKey>)>,
+        count: u32,
+    ) -> BackendResult<Option<(IndexedDBKey, IndexedDBKey)>> {
+        let forward = matches!(
+            direction,
+            CursorDirection::Next | CursorDirection::NextUnique
+        );
+        let unique = matches!(
+            direction,
+            CursorDirection::NextUnique | CursorDirection::PrevUnique
+        );
+        // Records of an object store have a key of their own, so only the ones of an index
+        // are ordered by their primary key too.
+        let by_key_only = unique || matches!(*source, RecordSource::ObjectStore(_));
+
+        // Both conditions hold for every record past some point in the direction of the
+        // cursor, so the records before that point can be skipped.
+        let is_past_position = |key: &IndexedDBKey, primary_key: &IndexedDBKey| {
+            let (position_key, position_primary_key) = match position {
+                Some((ref key, ref primary_key)) => (key, primary_key),
+                None => return true,
+            };
+            let ordering = if by_key_only {
+                key.cmp(position_key)
+            } else {
+                (key, primary_key).cmp(&(position_key, position_primary_key))
+            };
+            if forward {
+                ordering.is_gt()
+            } else {
+                ordering.is_lt()
+            }
+        };
+        let reaches_target = |key: &IndexedDBKey, primary_key: &IndexedDBKey| {
+            let (target_key, target_primary_key) = match target {
+                Some((ref key, ref primary_key)) => (key, primary_key),
+                None => return true,
+            };
+            let ordering = match *target_primary_key {
+                Some(ref target_primary_key) => {
+                    (key, primary_key).cmp(&(target_key, target_primary_key))
+                },
+                None => key.cmp(target_key),
+            };
+            if forward {
+                ordering.is_ge()
+            } else {
+                ordering.is_le()
+            }
+        };
+
+        let entries = self.entries(source, range)?;
+        let entries: Box<dyn Iterator<Item = _>> = if forward {
+            Box::new(entries)
+        } else {
+            Box::new(entries.rev())
+        };
+        let mut entries = entries
+            .skip_while(|&(key, primary_key)| {
+                !(is_past_position(key, primary_key) && reaches_target(key, primary_key))
+            })
+            .peekable();
+
+        let mut remaining = count.max(1);
+        while let Some((key, mut primary_key)) = entries.next() {
+            if unique {
+                // Skip the other records with the same key: the cursor moves to the one with
+                // the lowest primary key, which comes last when going backwards.
+                while let Some(&(next_key, next_primary_key)) = entries.peek() {
+                    if next_key != key {
+                        break;
+                    }
+                    if !forward {
+                        primary_key = next_primary_key;
+                    }
+                    entries.next();
+                }
+            }
+            remaining -= 1;
+            if remaining == 0 {
+                return Ok(Some((key.clone(), primary_key.clone())));
+            }
+        }
+        Ok(None)
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#store-a-record-into-an-object-store>
+    fn put(
+        &mut self,
+        store_name: String,
+        key: Option<IndexedDBKey>,
+        value: Vec<u8>,
+        index_keys: Vec<(String, Vec<IndexedDBKey>)>,
+        overwrite: bool,
+        journal: &mut Journal,
+    ) -> BackendResult<IndexedDBKey> {
+        let store = self.store_mut(&store_name)?;
+        let previous_number = store.current_number;
+
+        // Step 1. If store uses a key generator, generate a key if none was given, or
+        // possibly update the key generator with the one given.
+        let key = match key {
+            Some(key) => {
+                if store.auto_increment {
+                    store.possibly_update_key_generator(&key);
+                }
+                key
+            },
+            None if store.auto_increment => {
+                if store.current_number > MAX_GENERATED_KEY {
+                    return Err(BackendError::Constraint);
+                }
+                let key = IndexedDBKey::Number(store.current_number);
+                store.current_number += 1.0;
+                key
+            },
+            None => return Err(BackendError::Data),
+        };
+        if store.current_number != previous_number {
+            journal.undo.push(Undo::KeyGenerator {
+                store: store_name.clone(),
+                current_number: previous_number,
+            });
+            journal.changes.push(Change::KeyGenerator {
+                store: store_name.clone(),
+                current_number: store.current_number,
+            });
+        }
+
+        // Step 2. If the no-overwrite flag is set and a record with key exists, fail with a
+        // "ConstraintError".
+        if !overwrite && store.records.contains_key(&key) {
+            return Err(BackendError::Constraint);
+        }
+
+        // Step 5. Storing the record would give a unique index two records with the same
+        // key, fail with a "ConstraintError".
+        let index_keys: BTreeMap<String, Vec<IndexedDBKey>> = index_keys
+            .into_iter()
+            .filter(|(name, _)| store.indexes.contains_key(name))
+            .collect();
+        for (name, keys) in &index_keys {
+            let index = &store.indexes[name];
+            if !index.unique {
+                continue;
+            }
+            let taken = keys.iter().any(|index_key| {
+                index.entries.get(index_key).map_or(false, |primary_keys| {
+                    primary_keys.iter().any(|primary_key| *primary_key != key)
+                })
+            });
+            if taken {
+                return Err(BackendError::Constraint);
+            }
+        }
+
+        // Step 3 - 4. Replace the record with key, if any.
+        let record = StoredRecord { value, index_keys };
+        let old = store.remove_record(&key);
+        store.insert_record(key.clone(), record.clone());
+        journal.undo.push(Undo::Record {
+            store: store_name.clone(),
+            key: key.clone(),
+            old,
+        });
+        journal.changes.push(Change::Put {
+            store: store_name,
+            key: key.clone(),
+            record,
+        });
+        Ok(key)
+    }
+}
@@ -0,0 +1,270 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The log the databases of an origin are kept in on disk. Each committed transaction
+//! appends an entry with its changes, and the log is rewritten with the contents of the
+//! databases once enough entries piled up.
+//!
+//! The log starts with a header naming the version of its format, followed by the entries,
+//! each a little-endian `u32` length and the bincode serialization of a `LogEntry`. A log in
+//! another format is renamed after its version rather than read, so that a version of Servo
+//! that knows it can migrate it.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
+
+use super::engine::{Change, Database};
+
+/// How many entries the log holds before it is rewritten.
+const COMPACTION_THRESHOLD: usize = 1000;
+
+/// The bytes a log starts with, before the version of its format.
+const MAGIC: &[u8; 4] = b"SIDB";
+
+/// The version of the format of the log, to be bumped whenever the serialization of
+/// `LogEntry` changes.
+const FORMAT_VERSION: u32 = 1;
+
+const HEADER_LENGTH: u64 = 8;
+
+/// An entry of the log.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum LogEntry {
+    /// A transaction on the records of `database` committed.
+    Changes {
+        database: String,
+        changes: Vec<Change>,
+    },
+    /// The database was created or upgraded, or the log rewritten, and these are its
+    /// contents.
+    Contents {
+        database: String,
+        contents: Database,
+    },
+    /// The database was deleted.
+    Delete { database: String },
+}
+
+/// The log of the databases of an origin.
+pub struct OriginLog {
+    path: PathBuf,
+    file: File,
+    entries: usize,
+    /// The bytes the log takes on disk.
+    length: u64,
+}
+
+/// The name of the log file of `origin`.
+fn file_name(origin: &ImmutableOrigin) -> String {
+    format!("{}.log", crate::origin_file_name(origin))
+}
+
+impl OriginLog {
+    /// Open the log of `origin` in `directory`, creating it if needed, and read the databases
+    /// it holds. An entry left incomplete by a crash, and whatever follows it, is dropped.
+    pub fn open(
+        directory: &Path,
+        origin: &ImmutableOrigin,
+    ) -> io::Result<(OriginLog, BTreeMap<String, Database>)> {
+        fs::create_dir_all(directory)?;
+        let path = directory.join(file_name(origin));
+        set_aside_other_format(&path)?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+
+        let mut file_length = file.metadata()?.len();
+        if file_length < HEADER_LENGTH {
+            // A new log, or one whose header did not reach the disk.
+            file.set_len(0)?;
+            file.write_all(&header())?;
+            file.sync_data()?;
+            file_length = HEADER_LENGTH;
+        }
+        let mut databases = BTreeMap::new();
+        let mut entries = 0;
+        let mut valid_length = HEADER_LENGTH;
+        {
+            let mut reader = BufReader::new(&mut file);
+            reader.seek(SeekFrom::Start(HEADER_LENGTH))?;
+            while let Some((entry, length)) = read_entry(&mut reader, file_length - valid_length) {
+                apply(&mut databases, entry);
+                entries += 1;
+                valid_length += length;
+            }
+        }
+        if valid_length < file_length {
+            warn!("Dropping the end of the IndexedDB log {:?}", path);
+            file.set_len(valid_length)?;
+        }
+
+        let mut log = OriginLog {
+            path,
+            file,
+            entries,
+            length: valid_length,
+        };
+        if entries > COMPACTION_THRESHOLD {
+            log.compact(&databases)?;
+        }
+        Ok((log, databases))
+    }
+
+    /// Remove the log of `origin` in `directory`, which must not be open, along with the logs
+    /// in other formats that were set aside.
+    pub fn remove(directory: &Path, origin: &ImmutableOrigin) -> io::Result<()> {
+        let name = file_name(origin);
+        let set_aside_prefix = format!("{}.v", name);
+        let entries = match fs::read_dir(directory) {
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            entries => entries?,
+        };
+        for entry in entries {
+            let entry = entry?;
+            let file_name = entry.file_name();
+            let file_name = file_name.to_string_lossy();
+            if file_name == name || file_name.starts_with(&set_aside_prefix) {
+                fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The bytes the log takes on disk.
+    pub fn size(&self) -> u64 {
+        self.length
+    }
+
+    /// Write `entry` at the end of the log, making sure it reached the disk.
+    pub fn append(&mut self, entry: &LogEntry) -> io::Result<()> {
+        self.append_frame(&frame(entry)?)
+    }
+
+    /// Write an entry framed by `frame` at the end of the log, making sure it reached the
+    /// disk.
+    pub fn append_frame(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.file.write_all(bytes)?;
+        self.file.sync_data()?;
+        self.entries += 1;
+        self.length += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Rewrite the log with the contents of `databases` if enough entries piled up.
+    pub fn maybe_compact(&mut self, databases: &BTreeMap<String, Database>) -> io::Result<()> {
+        if self.entries <= COMPACTION_THRESHOLD {
+            return Ok(());
+        }
+        self.compact(databases)
+    }
+
+    /// Replace the log with one holding the contents of each database, so that a crash while
+    /// doing so leaves either the old log or the new one.
+    fn compact(&mut self, databases: &BTreeMap<String, Database>) -> io::Result<()> {
+        let temporary_path = self.path.with_extension("log.tmp");
+        let mut temporary_file = File::create(&temporary_path)?;
+        temporary_file.write_all(&header())?;
+        let mut length = HEADER_LENGTH;
+        for (name, database) in databases {
+            let entry = LogEntry::Contents {
+                database: name.clone(),
+                contents: database.clone(),
+            };
+            let bytes = frame(&entry)?;
+            temporary_file.write_all(&bytes)?;
+            length += bytes.len() as u64;
+        }
+        temporary_file.sync_all()?;
+        fs::rename(&temporary_path, &self.path)?;
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        self.entries = databases.len();
+        self.length = length;
+        Ok(())
+    }
+}
+
+fn header() -> [u8; HEADER_LENGTH as usize] {
+    let mut header = [0; HEADER_LENGTH as usize];
+    header[..4].copy_from_slice(MAGIC);
+    header[4..].copy_from_slice(&FORMAT_VERSION.to_le_bytes());
+    header
+}
+
+/// Rename the log at `path` after the version of its format if it is not the current one.
+/// Logs written before the format had a version are version 0.
+fn set_aside_other_format(path: &Path) -> io::Result<()> {
+    let mut header = [0; HEADER_LENGTH as usize];
+    let read = File::open(path).and_then(|mut file| file.read_exact(&mut header));
+    match read {
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+        // Too short to hold a header: a log whose header did not reach the disk.
+        Err(error) if error.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+        result => result?,
+    }
+    let version = if &header[..4] == MAGIC {
+        u32::from_le_bytes([header[4], header[5], header[6], header[7]])
+    } else {
+        0
+    };
+    if version == FORMAT_VERSION {
+        return Ok(());
+    }
+    let set_aside_path = path.with_extension(format!("log.v{}", version));
+    warn!(
+        "The IndexedDB log {:?} is in format {}, moving it to {:?}",
+        path, version, set_aside_path
+    );
+    fs::rename(path, set_aside_path)
+}
+
+/// An entry as written in the log: its length, then the entry itself.
+pub fn frame(entry: &LogEntry) -> io::Result<Vec<u8>> {
+    let payload =
+        bincode::serialize(entry).map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+    let mut bytes = Vec::with_capacity(payload.len() + 4);
+    bytes.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// The next entry of the log and the number of bytes it took, unless the log ends or the
+/// entry is incomplete, with `remaining` bytes left to read.
+fn read_entry(reader: &mut impl Read, remaining: u64) -> Option<(LogEntry, u64)> {
+    let mut length = [0; 4];
+    reader.read_exact(&mut length).ok()?;
+    let length = u32::from_le_bytes(length);
+    if length as u64 + 4 > remaining {
+        return None;
+    }
+    let mut payload = vec![0; length as usize];
+    reader.read_exact(&mut payload).ok()?;
+    let entry = bincode::deserialize(&payload).ok()?;
+    Some((entry, length as u64 + 4))
+}
+
+fn apply(databases: &mut BTreeMap<String, Database>, entry: LogEntry) {
+    match entry {
+        LogEntry::Changes { database, changes } => {
+            if let Some(database) = databases.get_mut(&database) {
+                for change in changes {
+                    database.apply(change);
+                }
+            }
+        },
+        LogEntry::Contents { database, contents } => {
+            databases.insert(database, contents);
+        },
+        LogEntry::Delete { database } => {
+            databases.remove(&database);
+        },
+    }
+}
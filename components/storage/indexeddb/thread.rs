@@ -0,0 +1,880 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The thread keeping the IndexedDB databases: it queues the requests to open and delete
+//! databases, starts the transactions once the ones they conflict with are finished, and
+//! writes their changes to the log of the origin when they commit.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::thread;
+
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use log::warn;
+use net_traits::indexeddb_thread::{
+    BackendError, BackendResult, ConnectionEvent, ConnectionId, DeleteEvent, IndexedDBThreadMsg,
+    IndexedDBTxnMode, OpenEvent, OperationResult, TransactionEvent, TransactionId,
+    TransactionOperation,
+};
+use servo_url::ImmutableOrigin;
+
+use super::engine::{Database, Journal};
+use super::persistence::{self, LogEntry, OriginLog};
+use crate::quota::{QuotaManager, StorageEndpoint};
+
+pub trait IndexedDBThreadFactory {
+    fn new(config_dir: Option<PathBuf>, quota: Arc<QuotaManager>) -> Self;
+}
+
+impl IndexedDBThreadFactory for IpcSender<IndexedDBThreadMsg> {
+    /// Create an IndexedDB thread, keeping the databases in `config_dir` if given, and only
+    /// in memory otherwise.
+    fn new(config_dir: Option<PathBuf>, quota: Arc<QuotaManager>) -> IpcSender<IndexedDBThreadMsg> {
+        let (chan, port) = ipc::channel().unwrap();
+        let directory = config_dir.map(|config_dir| config_dir.join("indexeddb"));
+        let evictor_chan = chan.clone();
+        quota.register_evictor(StorageEndpoint::IndexedDB, move |origin| {
+            let _ = evictor_chan.send(IndexedDBThreadMsg::ClearOrigin(origin));
+        });
+        thread::Builder::new()
+            .name("IndexedDBManager".to_owned())
+            .spawn(move || {
+                IndexedDBManager::new(port, directory, quota).start();
+            })
+            .expect("Thread spawning failed");
+        chan
+    }
+}
+
+/// A database, by its origin and its name.
+type DatabaseKey = (ImmutableOrigin, String);
+
+/// The databases of an origin, and the log they are kept in.
+struct OriginStore {
+    databases: BTreeMap<String, Database>,
+    log: Option<OriginLog>,
+}
+
+struct Connection {
+    database: DatabaseKey,
+    events: IpcSender<ConnectionEvent>,
+    /// Whether the connection closes once its transactions are finished.
+    close_pending: bool,
+}
+
+/// A request waiting in the connection queue of a database.
+/// <https://w3c.github.io/IndexedDB/#connection-queue>
+enum PendingRequest {
+    Open {
+        version: Option<u64>,
+        request: IpcSender<OpenEvent>,
+        connection: IpcSender<ConnectionEvent>,
+        upgrade_transaction: IpcSender<TransactionEvent>,
+    },
+    Delete {
+        request: IpcSender<DeleteEvent>,
+    },
+}
+
+#[derive(Default)]
+struct DatabaseState {
+    /// The connections that are not closed yet.
+    connections: Vec<ConnectionId>,
+    requests: VecDeque<PendingRequest>,
+    /// The connections told about the version change the first request waits for that did
+    /// not handle it yet, once they were told.
+    notified: Option<HashSet<ConnectionId>>,
+    /// Whether the first request was told it is blocked by the connections left open.
+    blocked: bool,
+    /// The transactions that are not finished, in the order they were created.
+    transactions: Vec<TransactionId>,
+    /// Whether an upgrade transaction is running, the next request waiting for it to finish.
+    upgrading: bool,
+}
+
+struct Transaction {
+    database: DatabaseKey,
+    scope: Vec<String>,
+    mode: IndexedDBTxnMode,
+    events: IpcSender<TransactionEvent>,
+    started: bool,
+    /// The operations waiting for the transaction to start, and their request.
+    queued: VecDeque<(Option<u64>, TransactionOperation)>,
+    commit_requested: bool,
+    journal: Journal,
+    /// For an upgrade transaction, the version of the database before it, which was just
+    /// created if 0.
+    previous_version: Option<u64>,
+}
+
+impl Transaction {
+    /// <https://w3c.github.io/IndexedDB/#transaction-scheduling>
+    fn conflicts_with(&self, other: &Transaction) -> bool {
+        let overlap = self.scope.iter().any(|name| other.scope.contains(name));
+        overlap &&
+            (self.mode != IndexedDBTxnMode::Readonly || other.mode != IndexedDBTxnMode::Readonly)
+    }
+}
+
+struct IndexedDBManager {
+    port: IpcReceiver<IndexedDBThreadMsg>,
+    directory: Option<PathBuf>,
+    quota: Arc<QuotaManager>,
+    origins: HashMap<ImmutableOrigin, OriginStore>,
+    databases: HashMap<DatabaseKey, DatabaseState>,
+    connections: HashMap<ConnectionId, Connection>,
+    transactions: HashMap<TransactionId, Transaction>,
+    next_connection_id: u64,
+}
+
+impl IndexedDBManager {
+    fn new(
+        port: IpcReceiver<IndexedDBThreadMsg>,
+        directory: Option<PathBuf>,
+        quota: Arc<QuotaManager>,
+    ) -> IndexedDBManager {
+        IndexedDBManager {
+            port,
+            directory,
+            quota,
+            origins: HashMap::new(),
+            databases: HashMap::new(),
+            connections: HashMap::new(),
+            transactions: HashMap::new(),
+            next_connection_id: 1,
+        }
+    }
+
+    fn start(&mut self) {
+        loop {
+            match self.port.recv().unwrap() {
+                IndexedDBThreadMsg::Open {
+                    origin,
+                    name,
+                    version,
+                    request,
+                    connection,
+                    upgrade_transaction,
+                } => {
+                    let key = (origin, name);
+                    self.database_state(&key)
+                        .requests
+                        .push_back(PendingRequest::Open {
+                            version,
+                            request,
+                            connection,
+                            upgrade_transaction,
+                        });
+                    self.schedule(&key);
+                },
+                IndexedDBThreadMsg::DeleteDatabase {
+                    origin,
+                    name,
+                    request,
+                } => {
+                    let key = (origin, name);
+                    self.database_state(&key)
+                        .requests
+                        .push_back(PendingRequest::Delete { request });
+                    self.schedule(&key);
+                },
+                IndexedDBThreadMsg::Databases(sender, origin) => {
+                    let databases = self
+                        .origin_store(&origin)
+                        .databases
+                        .iter()
+                        .map(|(name, database)| (name.clone(), database.version))
+                        .collect();
+                    let _ = sender.send(databases);
+                },
+                IndexedDBThreadMsg::VersionChangeHandled(connection) => {
+                    if let Some(key) = self.connection_database(connection) {
+                        if let Some(ref mut notified) = self.database_state(&key).notified {
+                            notified.remove(&connection);
+                        }
+                        self.schedule(&key);
+                    }
+                },
+                IndexedDBThreadMsg::CloseConnection(connection) => {
+                    if let Some(key) = self.connection_database(connection) {
+                        self.close_connection(connection);
+                        self.schedule(&key);
+                    }
+                },
+                IndexedDBThreadMsg::CreateTransaction {
+                    id,
+                    scope,
+                    mode,
+                    events,
+                } => {
+                    if let Some(key) = self.create_transaction(id, scope, mode, events) {
+                        self.schedule(&key);
+                    }
+                },
+                IndexedDBThreadMsg::Operation(transaction, request, operation) => {
+                    if let Some(key) = self.transaction_database(transaction) {
+                        self.queue_operation(transaction, request, operation);
+                        self.schedule(&key);
+                    }
+                },
+                IndexedDBThreadMsg::SyncOperation(sender, transaction, operation) => {
+                    let key = self.transaction_database(transaction);
+                    let result = self.run_sync_operation(transaction, operation);
+                    let _ = sender.send(result);
+                    if let Some(key) = key {
+                        self.schedule(&key);
+                    }
+                },
+                IndexedDBThreadMsg::Commit(transaction) => {
+                    if let Some(key) = self.transaction_database(transaction) {
+                        self.request_commit(transaction);
+                        self.schedule(&key);
+                    }
+                },
+                IndexedDBThreadMsg::Abort(transaction) => {
+                    if let Some(key) = self.transaction_database(transaction) {
+                        self.abort(transaction, None);
+                        self.schedule(&key);
+                    }
+                },
+                IndexedDBThreadMsg::ClearOrigin(origin) => self.clear_origin(origin),
+                IndexedDBThreadMsg::Exit(sender) => {
+                    // Nothing to do since committed transactions are written eagerly.
+                    let _ = sender.send(());
+                    break;
+                },
+            }
+        }
+    }
+
+    /// The databases of `origin`, read from its log on first use.
+    fn origin_store(&mut self, origin: &ImmutableOrigin) -> &mut OriginStore {
+        let directory = &self.directory;
+        let quota = &self.quota;
+        self.origins.entry(origin.clone()).or_insert_with(|| {
+            let opened = directory
+                .as_ref()
+                .map(|directory| OriginLog::open(directory, origin));
+            match opened {
+                Some(Ok((log, databases))) => {
+                    quota.set_usage(origin, StorageEndpoint::IndexedDB, log.size());
+                    OriginStore {
+                        databases,
+                        log: Some(log),
+                    }
+                },
+                Some(Err(error)) => {
+                    warn!("Could not open the IndexedDB log ({}), keeping the databases of {:?} in memory", error, origin);
+                    OriginStore {
+                        databases: BTreeMap::new(),
+                        log: None,
+                    }
+                },
+                None => OriginStore {
+                    databases: BTreeMap::new(),
+                    log: None,
+                },
+            }
+        })
+    }
+
+    fn database_state(&mut self, key: &DatabaseKey) -> &mut DatabaseState {
+        self.databases.entry(key.clone()).or_default()
+    }
+
+    fn connection_database(&self, id: ConnectionId) -> Option<DatabaseKey> {
+        self.connections
+            .get(&id)
+            .map(|connection| connection.database.clone())
+    }
+
+    fn transaction_database(&self, id: TransactionId) -> Option<DatabaseKey> {
+        self.transactions
+            .get(&id)
+            .map(|transaction| transaction.database.clone())
+    }
+
+    /// Move the transactions and the requests of a database forward after a change of its
+    /// state: start the transactions that can, then handle the requests that no longer wait,
+    /// and start the transactions the connections dropped on the way held back.
+    fn schedule(&mut self, key: &DatabaseKey) {
+        self.start_transactions(key);
+        self.process_requests(key);
+        self.start_transactions(key);
+    }
+
+    /// Handle the requests of the connection queue of a database, until one has to wait.
+    /// <https://w3c.github.io/IndexedDB/#open-a-database-connection>
+    /// <https://w3c.github.io/IndexedDB/#delete-a-database>
+    fn process_requests(&mut self, key: &DatabaseKey) {
+        loop {
+            if self.database_state(key).upgrading {
+                return;
+            }
+            let current_version = self
+                .origin_store(&key.0)
+                .databases
+                .get(&key.1)
+                .map(|database| database.version);
+            let new_version = match self.database_state(key).requests.front() {
+                None => return,
+                Some(PendingRequest::Open { version, .. }) => {
+                    let version = version.or(current_version).unwrap_or(1);
+                    if current_version.map_or(false, |current| version < current) {
+                        if let Some(PendingRequest::Open { request, .. }) = self.pop_request(key) {
+                            let _ = request.send(OpenEvent::Error(BackendError::Version));
+                        }
+                        continue;
+                    }
+                    if current_version == Some(version) {
+                        if let Some(PendingRequest::Open {
+                            request,
+                            connection,
+                            ..
+                        }) = self.pop_request(key)
+                        {
+                            let id = self.add_connection(key, connection);
+                            let metadata =
+                                self.origin_store(&key.0).databases[&key.1].metadata(&key.1);
+                            let _ = request.send(OpenEvent::Success {
+                                connection: id,
+                                metadata,
+                            });
+                        }
+                        continue;
+                    }
+                    Some(version)
+                },
+                Some(PendingRequest::Delete { .. }) => {
+                    if current_version.is_none() {
+                        if let Some(PendingRequest::Delete { request }) = self.pop_request(key) {
+                            let _ = request.send(DeleteEvent::Success { old_version: 0 });
+                        }
+                        continue;
+                    }
+                    None
+                },
+            };
+
+            let old_version = current_version.unwrap_or(0);
+            if !self.wait_for_connections(key, old_version, new_version) {
+                return;
+            }
+
+            match self.pop_request(key) {
+                Some(PendingRequest::Open {
+                    request,
+                    connection,
+                    upgrade_transaction,
+                    ..
+                }) => {
+                    let new_version = new_version.unwrap_or(1);
+                    let id = self.add_connection(key, connection);
+                    let database = self
+                        .origin_store(&key.0)
+                        .databases
+                        .entry(key.1.clone())
+                        .or_default();
+                    database.version = new_version;
+                    let scope = database.store_names();
+                    let metadata = database.metadata(&key.1);
+
+                    // Run an upgrade transaction over all the object stores.
+                    // <https://w3c.github.io/IndexedDB/#upgrade-a-database>
+                    let transaction = TransactionId::upgrade(id);
+                    self.transactions.insert(
+                        transaction,
+                        Transaction {
+                            database: key.clone(),
+                            scope,
+                            mode: IndexedDBTxnMode::Versionchange,
+                            events: upgrade_transaction,
+                            started: true,
+                            queued: VecDeque::new(),
+                            commit_requested: false,
+                            journal: Journal::default(),
+                            previous_version: Some(old_version),
+                        },
+                    );
+                    let state = self.database_state(key);
+                    state.transactions.push(transaction);
+                    state.upgrading = true;
+                    let _ = request.send(OpenEvent::UpgradeNeeded {
+                        connection: id,
+                        old_version,
+                        metadata,
+                    });
+                    return;
+                },
+                Some(PendingRequest::Delete { request }) => {
+                    let origin_store = self.origin_store(&key.0);
+                    origin_store.databases.remove(&key.1);
+                    let size = origin_store.log.as_mut().map(|log| {
+                        let entry = LogEntry::Delete {
+                            database: key.1.clone(),
+                        };
+                        if let Err(error) = log.append(&entry) {
+                            warn!(
+                                "Could not write the deletion of an IndexedDB database ({})",
+                                error
+                            );
+                        }
+                        log.size()
+                    });
+                    if let Some(size) = size {
+                        self.quota
+                            .set_usage(&key.0, StorageEndpoint::IndexedDB, size);
+                    }
+                    let _ = request.send(DeleteEvent::Success { old_version });
+                },
+                None => return,
+            }
+        }
+    }
+
+    fn pop_request(&mut self, key: &DatabaseKey) -> Option<PendingRequest> {
+        let state = self.database_state(key);
+        state.notified = None;
+        state.blocked = false;
+        state.requests.pop_front()
+    }
+
+    fn add_connection(
+        &mut self,
+        key: &DatabaseKey,
+        events: IpcSender<ConnectionEvent>,
+    ) -> ConnectionId {
+        let id = ConnectionId(self.next_connection_id);
+        self.next_connection_id += 1;
+        self.connections.insert(
+            id,
+            Connection {
+                database: key.clone(),
+                events,
+                close_pending: false,
+            },
+        );
+        self.database_state(key).connections.push(id);
+        id
+    }
+
+    /// Tell the open connections to a database about a version change to `new_version`, or
+    /// its deletion, and whether they all closed.
+    fn wait_for_connections(
+        &mut self,
+        key: &DatabaseKey,
+        old_version: u64,
+        new_version: Option<u64>,
+    ) -> bool {
+        if self.database_state(key).notified.is_none() {
+            let connections = self.database_state(key).connections.clone();
+            let mut notified = HashSet::new();
+            let mut gone = vec![];
+            for id in connections {
+                let connection = &self.connections[&id];
+                if connection.close_pending {
+                    continue;
+                }
+                let event = ConnectionEvent::VersionChange {
+                    old_version,
+                    new_version,
+                };
+                if connection.events.send(event).is_ok() {
+                    notified.insert(id);
+                } else {
+                    gone.push(id);
+                }
+            }
+            self.database_state(key).notified = Some(notified);
+            // The script threads of these connections are gone.
+            for id in gone {
+                self.drop_connection(id, None);
+            }
+        }
+
+        let state = self.database_state(key);
+        if state
+            .notified
+            .as_ref()
+            .map_or(false, |notified| !notified.is_empty())
+        {
+            return false;
+        }
+        if state.connections.is_empty() {
+            return true;
+        }
+        if !state.blocked {
+            state.blocked = true;
+            match state.requests.front() {
+                Some(PendingRequest::Open { request, .. }) => {
+                    let _ = request.send(OpenEvent::Blocked {
+                        old_version,
+                        new_version: new_version.unwrap_or(1),
+                    });
+                },
+                Some(PendingRequest::Delete { request }) => {
+                    let _ = request.send(DeleteEvent::Blocked { old_version });
+                },
+                None => {},
+            }
+        }
+        false
+    }
+
+    /// <https://w3c.github.io/IndexedDB/#close-a-database-connection>
+    fn close_connection(&mut self, id: ConnectionId) {
+        if let Some(connection) = self.connections.get_mut(&id) {
+            connection.close_pending = true;
+            self.maybe_finish_closing(id);
+        }
+    }
+
+    /// Close a connection whose script thread is gone, or whose databases were cleared,
+    /// aborting its transactions with `error`.
+    fn drop_connection(&mut self, id: ConnectionId, error: Option<BackendError>) {
+        let transactions: Vec<_> = self
+            .transactions
+            .keys()
+            .filter(|transaction| transaction.connection == id)
+            .copied()
+            .collect();
+        for transaction in transactions {
+            self.abort(transaction, error.clone());
+        }
+        if let Some(connection) = self.connections.get_mut(&id) {
+            connection.close_pending = true;
+        }
+        self.maybe_finish_closing(id);
+    }
+
+    /// Delete the databases of `origin` and their log, forcibly closing the connections to
+    /// them.
+    fn clear_origin(&mut self, origin: ImmutableOrigin) {
+        let connections: Vec<_> = self
+            .connections
+            .iter()
+            .filter(|(_, connection)| connection.database.0 == origin)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in connections {
+            let _ = self.connections[&id]
+                .events
+                .send(ConnectionEvent::ForcedClose);
+            self.drop_connection(id, Some(BackendError::Abort));
+        }
+
+        self.origins.remove(&origin);
+        if let Some(ref directory) = self.directory {
+            if let Err(error) = OriginLog::remove(directory, &origin) {
+                warn!("Could not remove the IndexedDB log ({})", error);
+            }
+        }
+        self.quota.set_usage(&origin, StorageEndpoint::IndexedDB, 0);
+
+        let keys: Vec<_> = self
+            .databases
+            .keys()
+            .filter(|key| key.0 == origin)
+            .cloned()
+            .collect();
+        for key in keys {
+            self.schedule(&key);
+        }
+    }
+
+    /// Close a connection waiting to be closed once its transactions are finished.
+    fn maybe_finish_closing(&mut self, id: ConnectionId) {
+        let key = match self.connections.get(&id) {
+            Some(connection) if connection.close_pending => connection.database.clone(),
+            _ => return,
+        };
+        if self
+            .transactions
+            .keys()
+            .any(|transaction| transaction.connection == id)
+        {
+            return;
+        }
+        self.connections.remove(&id);
+        let state = self.database_state(&key);
+        state.connections.retain(|connection| *connection != id);
+        if let Some(ref mut notified) = state.notified {
+            notified.remove(&id);
+        }
+    }
+
+    fn create_transaction(
+        &mut self,
+        id: TransactionId,
+        scope: Vec<String>,
+        mode: IndexedDBTxnMode,
+        events: IpcSender<TransactionEvent>,
+    ) -> Option<DatabaseKey> {
+        let key = match self.connections.get(&id.connection) {
+            Some(connection) if !connection.close_pending => connection.database.clone(),
+            _ => {
+                let _ = events.send(TransactionEvent::Aborted(BackendError::Abort));
+                return None;
+            },
+        };
+        self.transactions.insert(
+            id,
+            Transaction {
+                database: key.clone(),
+                scope,
+                mode,
+                events,
+                started: false,
+                queued: VecDeque::new(),
+                commit_requested: false,
+                journal: Journal::default(),
+                previous_version: None,
+            },
+        );
+        self.database_state(&key).transactions.push(id);
+        Some(key)
+    }
+
+    /// Start the transactions of a database that no unfinished transaction created before
+    /// them conflicts with.
+    /// <https://w3c.github.io/IndexedDB/#transaction-scheduling>
+    fn start_transactions(&mut self, key: &DatabaseKey) {
+        let ids = self.database_state(key).transactions.clone();
+        for (position, id) in ids.iter().enumerate() {
+            let transaction = match self.transactions.get(id) {
+                Some(transaction) if !transaction.started => transaction,
+                _ => continue,
+            };
+            let blocked = ids[..position].iter().any(|earlier| {
+                self.transactions
+                    .get(earlier)
+                    .map_or(false, |earlier| earlier.conflicts_with(transaction))
+            });
+            if blocked {
+                continue;
+            }
+            self.transactions.get_mut(id).unwrap().started = true;
+            self.run_queued_operations(*id);
+        }
+    }
+
+    /// Run the operations queued while the transaction waited to start, and commit it if it
+    /// was asked to.
+    fn run_queued_operations(&mut self, id: TransactionId) {
+        loop {
+            let next = match self.transactions.get_mut(&id) {
+                Some(transaction) => transaction.queued.pop_front(),
+                None => return,
+            };
+            match next {
+                Some((request, operation)) => self.run_operation(id, request, operation),
+                None => break,
+            }
+        }
+        if self
+            .transactions
+            .get(&id)
+            .map_or(false, |transaction| transaction.commit_requested)
+        {
+            self.commit(id);
+        }
+    }
+
+    fn queue_operation(
+        &mut self,
+        id: TransactionId,
+        request: Option<u64>,
+        operation: TransactionOperation,
+    ) {
+        let transaction = match self.transactions.get_mut(&id) {
+            Some(transaction) => transaction,
+            // The transaction was aborted.
+            None => return,
+        };
+        if transaction.started {
+            self.run_operation(id, request, operation);
+        } else {
+            transaction.queued.push_back((request, operation));
+        }
+    }
+
+    fn execute(
+        &mut self,
+        id: TransactionId,
+        operation: TransactionOperation,
+    ) -> BackendResult<OperationResult> {
+        let transaction = self.transactions.get_mut(&id).ok_or(BackendError::Abort)?;
+        let (ref origin, ref name) = transaction.database;
+        let database = self
+            .origins
+            .get_mut(origin)
+            .and_then(|origin_store| origin_store.databases.get_mut(name))
+            .ok_or(BackendError::NotFound)?;
+        database.execute(operation, &mut transaction.journal)
+    }
+
+    /// Run an operation of a running transaction, telling it about the result of `request`,
+    /// or aborting it if an operation without a request fails.
+    fn run_operation(
+        &mut self,
+        id: TransactionId,
+        request: Option<u64>,
+        operation: TransactionOperation,
+    ) {
+        let result = self.execute(id, operation);
+        match request {
+            Some(request) => {
+                if let Some(transaction) = self.transactions.get(&id) {
+                    let _ = transaction
+                        .events
+                        .send(TransactionEvent::RequestDone(request, result));
+                }
+            },
+            None => {
+                if let Err(error) = result {
+                    self.abort(id, Some(error));
+                }
+            },
+        }
+    }
+
+    fn run_sync_operation(
+        &mut self,
+        id: TransactionId,
+        operation: TransactionOperation,
+    ) -> BackendResult<OperationResult> {
+        let started = self
+            .transactions
+            .get(&id)
+            .map_or(false, |transaction| transaction.started);
+        if !started {
+            return Err(BackendError::Abort);
+        }
+        self.execute(id, operation)
+    }
+
+    fn request_commit(&mut self, id: TransactionId) {
+        let transaction = match self.transactions.get_mut(&id) {
+            Some(transaction) => transaction,
+            None => return,
+        };
+        transaction.commit_requested = true;
+        if transaction.started {
+            self.commit(id);
+        }
+    }
+
+    /// Write the changes of a transaction to the log, aborting it if that fails.
+    /// <https://w3c.github.io/IndexedDB/#commit-a-transaction>
+    fn commit(&mut self, id: TransactionId) {
+        let transaction = match self.transactions.get_mut(&id) {
+            Some(transaction) => transaction,
+            None => return,
+        };
+        let (ref origin, ref name) = transaction.database;
+        let origin_store = match self.origins.get_mut(origin) {
+            Some(origin_store) => origin_store,
+            None => return,
+        };
+
+        if let Some(ref mut log) = origin_store.log {
+            let entry = if transaction.mode == IndexedDBTxnMode::Versionchange {
+                origin_store
+                    .databases
+                    .get(name)
+                    .map(|database| LogEntry::Contents {
+                        database: name.clone(),
+                        contents: database.clone(),
+                    })
+            } else if !transaction.journal.changes.is_empty() {
+                Some(LogEntry::Changes {
+                    database: name.clone(),
+                    changes: std::mem::take(&mut transaction.journal.changes),
+                })
+            } else {
+                None
+            };
+            if let Some(entry) = entry {
+                let quota = &self.quota;
+                let written = persistence::frame(&entry).and_then(|bytes| {
+                    let size = log.size() + bytes.len() as u64;
+                    if !quota.allows_usage(origin, StorageEndpoint::IndexedDB, size) {
+                        return Ok(false);
+                    }
+                    log.append_frame(&bytes).map(|_| true)
+                });
+                match written {
+                    Ok(true) => {},
+                    Ok(false) => {
+                        self.abort(id, Some(BackendError::QuotaExceeded));
+                        return;
+                    },
+                    Err(error) => {
+                        warn!("Could not write an IndexedDB transaction ({})", error);
+                        self.abort(id, Some(BackendError::Unknown(error.to_string())));
+                        return;
+                    },
+                }
+            }
+            if let Err(error) = log.maybe_compact(&origin_store.databases) {
+                warn!("Could not rewrite the IndexedDB log ({})", error);
+            }
+            self.quota
+                .set_usage(origin, StorageEndpoint::IndexedDB, log.size());
+        }
+
+        let transaction = self.transactions.remove(&id).unwrap();
+        let _ = transaction.events.send(TransactionEvent::Complete);
+        self.transaction_finished(&transaction.database, id, false);
+    }
+
+    /// Undo the changes of a transaction, telling it about `error` if it is the reason, rather
+    /// than script asking for it.
+    /// <https://w3c.github.io/IndexedDB/#abort-a-transaction>
+    fn abort(&mut self, id: TransactionId, error: Option<BackendError>) {
+        let transaction = match self.transactions.remove(&id) {
+            Some(transaction) => transaction,
+            None => return,
+        };
+        let (ref origin, ref name) = transaction.database;
+        if let Some(origin_store) = self.origins.get_mut(origin) {
+            if let Some(database) = origin_store.databases.get_mut(name) {
+                database.rollback(transaction.journal);
+                // <https://w3c.github.io/IndexedDB/#abort-an-upgrade-transaction>
+                if let Some(previous_version) = transaction.previous_version {
+                    database.version = previous_version;
+                }
+            }
+            if transaction.previous_version == Some(0) {
+                origin_store.databases.remove(name);
+            }
+        }
+        if let Some(error) = error {
+            let _ = transaction.events.send(TransactionEvent::Aborted(error));
+        }
+        self.transaction_finished(
+            &transaction.database,
+            id,
+            transaction.previous_version.is_some(),
+        );
+    }
+
+    /// Forget about a finished transaction, closing its connection if it is waiting for it or
+    /// if `close` is set, as for an aborted upgrade transaction.
+    fn transaction_finished(&mut self, key: &DatabaseKey, id: TransactionId, close: bool) {
+        let upgrade = id == TransactionId::upgrade(id.connection);
+        let state = self.database_state(key);
+        state.transactions.retain(|transaction| *transaction != id);
+        if upgrade {
+            state.upgrading = false;
+        }
+        if close {
+            if let Some(connection) = self.connections.get_mut(&id.connection) {
+                connection.close_pending = true;
+            }
+        }
+        self.maybe_finish_closing(id.connection);
+    }
+}
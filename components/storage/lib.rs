@@ -0,0 +1,41 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+#![deny(unsafe_code)]
+
+use servo_url::ImmutableOrigin;
+
+/// The origin private file system of each origin, kept in a directory of its own, and the
+/// access handles opened on its files.
+pub mod file_system {
+    mod thread;
+
+    pub use self::thread::FileSystemThreadFactory;
+}
+
+/// The IndexedDB databases of each origin, kept in memory and in a log on disk, and the
+/// transactions reading and writing them.
+pub mod indexeddb {
+    mod engine;
+    mod persistence;
+    mod thread;
+
+    pub use self::thread::IndexedDBThreadFactory;
+}
+
+pub mod quota;
+
+/// The serialization of `origin`, with the characters that are not safe in file names escaped,
+/// to name what is kept on disk for it.
+fn origin_file_name(origin: &ImmutableOrigin) -> String {
+    let mut name = String::new();
+    for byte in origin.ascii_serialization().bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'.' || byte == b'-' {
+            name.push(byte as char);
+        } else {
+            name.push_str(&format!("_{:02x}", byte));
+        }
+    }
+    name
+}
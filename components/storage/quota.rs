@@ -0,0 +1,274 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! The bookkeeping of the storage each origin uses, shared by the threads keeping
+//! localStorage, the IndexedDB databases and the origin private file systems. Each of them
+//! reports the usage of an origin when it changes and asks before growing it, and once the
+//! storage of all origins takes more than the total limit, the origins that were not used for
+//! the longest time and did not ask for their storage to persist are cleared.
+//! <https://storage.spec.whatwg.org/#usage-and-quota>
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use servo_url::ImmutableOrigin;
+
+/// A kind of storage of an origin.
+/// <https://storage.spec.whatwg.org/#storage-endpoints>
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum StorageEndpoint {
+    LocalStorage,
+    IndexedDB,
+    FileSystem,
+}
+
+/// Clears the storage of an origin kept by one endpoint.
+type Evictor = Box<dyn Fn(ImmutableOrigin) + Send>;
+
+/// What is known about the storage of an origin.
+#[derive(Default, Deserialize, Serialize)]
+struct OriginUsage {
+    /// The bytes taken by each endpoint.
+    usage: HashMap<StorageEndpoint, u64>,
+    /// Whether the storage of the origin is only cleared when the user asks for it.
+    /// <https://storage.spec.whatwg.org/#bucket-mode>
+    persisted: bool,
+    /// When the storage of the origin was last used, in ticks of `QuotaState::clock`.
+    last_used: u64,
+}
+
+impl OriginUsage {
+    fn total(&self) -> u64 {
+        self.usage.values().sum()
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct QuotaState {
+    origins: HashMap<ImmutableOrigin, OriginUsage>,
+    /// Counts the uses of storage, to find the origins that were not used for the longest
+    /// time.
+    clock: u64,
+}
+
+impl QuotaState {
+    fn total(&self) -> u64 {
+        self.origins.values().map(OriginUsage::total).sum()
+    }
+
+    /// The record of `origin`, marked as just used.
+    fn use_origin(&mut self, origin: &ImmutableOrigin) -> &mut OriginUsage {
+        self.clock += 1;
+        let clock = self.clock;
+        let origin_usage = self.origins.entry(origin.clone()).or_default();
+        origin_usage.last_used = clock;
+        origin_usage
+    }
+
+    /// The bytes that clearing every origin other than `origin` that can be cleared would
+    /// free.
+    fn evictable(&self, origin: &ImmutableOrigin) -> u64 {
+        self.origins
+            .iter()
+            .filter(|(other, usage)| *other != origin && !usage.persisted)
+            .map(|(_, usage)| usage.total())
+            .sum()
+    }
+}
+
+pub struct QuotaManager {
+    state: Mutex<QuotaState>,
+    evictors: Mutex<HashMap<StorageEndpoint, Evictor>>,
+    /// The file the bookkeeping is kept in, if it is kept across sessions.
+    path: Option<PathBuf>,
+    /// The most bytes the storage of an origin takes.
+    origin_quota: u64,
+    /// The most bytes the storage of all origins takes before some are cleared.
+    total_limit: u64,
+}
+
+impl QuotaManager {
+    /// Create a quota manager, keeping its bookkeeping in `config_dir` if given.
+    pub fn new(config_dir: Option<PathBuf>, origin_quota: u64, total_limit: u64) -> QuotaManager {
+        let path = config_dir.map(|config_dir| config_dir.join("quota"));
+        let state = path
+            .as_ref()
+            .and_then(|path| fs::read(path).ok())
+            .and_then(|bytes| match bincode::deserialize(&bytes) {
+                Ok(state) => Some(state),
+                Err(error) => {
+                    warn!("Could not read the storage quota bookkeeping ({})", error);
+                    None
+                },
+            })
+            .unwrap_or_default();
+        QuotaManager {
+            state: Mutex::new(state),
+            evictors: Mutex::new(HashMap::new()),
+            path,
+            origin_quota,
+            total_limit,
+        }
+    }
+
+    /// Call `evictor` to clear the storage `endpoint` keeps for an origin when the origin is
+    /// evicted.
+    pub fn register_evictor(
+        &self,
+        endpoint: StorageEndpoint,
+        evictor: impl Fn(ImmutableOrigin) + Send + 'static,
+    ) {
+        self.evictors
+            .lock()
+            .unwrap()
+            .insert(endpoint, Box::new(evictor));
+    }
+
+    /// The bytes `endpoint` keeps for `origin`.
+    pub fn usage(&self, origin: &ImmutableOrigin, endpoint: StorageEndpoint) -> u64 {
+        let state = self.state.lock().unwrap();
+        state
+            .origins
+            .get(origin)
+            .and_then(|origin_usage| origin_usage.usage.get(&endpoint))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Whether `endpoint` can keep `usage` bytes for `origin`: the origin stays within its
+    /// quota, and the storage of all origins within the total limit once the origins that can
+    /// be are cleared.
+    pub fn allows_usage(
+        &self,
+        origin: &ImmutableOrigin,
+        endpoint: StorageEndpoint,
+        usage: u64,
+    ) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let origin_usage = state.use_origin(origin);
+        let current = origin_usage.usage.get(&endpoint).copied().unwrap_or(0);
+        if usage <= current {
+            return true;
+        }
+        let growth = usage - current;
+        if origin_usage.total() + growth > self.origin_quota {
+            return false;
+        }
+        state.total() + growth <= self.total_limit.saturating_add(state.evictable(origin))
+    }
+
+    /// Record that `endpoint` keeps `usage` bytes for `origin`, clearing other origins if the
+    /// storage of all of them takes more than the total limit.
+    pub fn set_usage(&self, origin: &ImmutableOrigin, endpoint: StorageEndpoint, usage: u64) {
+        let mut state = self.state.lock().unwrap();
+        let origin_usage = state.use_origin(origin);
+        if usage == 0 {
+            origin_usage.usage.remove(&endpoint);
+        } else {
+            origin_usage.usage.insert(endpoint, usage);
+        }
+        if !origin_usage.persisted && origin_usage.usage.is_empty() {
+            state.origins.remove(origin);
+        }
+        self.evict(&mut state, origin);
+        self.save(&state);
+    }
+
+    /// The bytes the storage of `origin` takes, and its quota.
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-estimate>
+    pub fn estimate(&self, origin: &ImmutableOrigin) -> (u64, u64) {
+        let state = self.state.lock().unwrap();
+        let usage = state.origins.get(origin).map_or(0, OriginUsage::total);
+        (usage, self.origin_quota)
+    }
+
+    /// Only clear the storage of `origin` when the user asks for it, returning whether it
+    /// is persisted.
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persist>
+    pub fn persist(&self, origin: &ImmutableOrigin) -> bool {
+        if !origin.is_tuple() {
+            return false;
+        }
+        let mut state = self.state.lock().unwrap();
+        state.use_origin(origin).persisted = true;
+        self.save(&state);
+        true
+    }
+
+    /// <https://storage.spec.whatwg.org/#dom-storagemanager-persisted>
+    pub fn persisted(&self, origin: &ImmutableOrigin) -> bool {
+        let state = self.state.lock().unwrap();
+        state
+            .origins
+            .get(origin)
+            .map_or(false, |origin_usage| origin_usage.persisted)
+    }
+
+    /// Clear the origins that were not used for the longest time, other than `origin`
+    /// which is being written to, until the storage of all origins is within the total
+    /// limit.
+    fn evict(&self, state: &mut QuotaState, origin: &ImmutableOrigin) {
+        let mut total = state.total();
+        if total <= self.total_limit {
+            return;
+        }
+        let mut candidates: Vec<_> = state
+            .origins
+            .iter()
+            .filter(|(other, usage)| *other != origin && !usage.persisted)
+            .map(|(other, usage)| (usage.last_used, other.clone()))
+            .collect();
+        candidates.sort_by_key(|(last_used, _)| *last_used);
+
+        let evictors = self.evictors.lock().unwrap();
+        for (_, evicted) in candidates {
+            if total <= self.total_limit {
+                break;
+            }
+            let origin_usage = match state.origins.remove(&evicted) {
+                Some(origin_usage) => origin_usage,
+                None => continue,
+            };
+            total -= origin_usage.total();
+            for endpoint in origin_usage.usage.keys() {
+                match evictors.get(endpoint) {
+                    Some(evictor) => evictor(evicted.clone()),
+                    None => warn!("Nothing clears the {:?} storage of {:?}", endpoint, evicted),
+                }
+            }
+        }
+    }
+
+    /// Write the bookkeeping of the origins whose storage is kept across sessions.
+    fn save(&self, state: &QuotaState) {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return,
+        };
+        let origins: HashMap<_, _> = state
+            .origins
+            .iter()
+            .filter(|(origin, _)| origin.is_tuple())
+            .collect();
+        // Serialized in the layout of `QuotaState`.
+        let result = bincode::serialize(&(&origins, state.clock))
+            .map_err(|error| error.to_string())
+            .and_then(|bytes| {
+                let temporary_path = path.with_extension("tmp");
+                fs::create_dir_all(path.parent().unwrap())
+                    .and_then(|_| File::create(&temporary_path))
+                    .and_then(|mut file| file.write_all(&bytes))
+                    .and_then(|_| fs::rename(&temporary_path, path))
+                    .map_err(|error| error.to_string())
+            });
+        if let Err(error) = result {
+            warn!("Could not write the storage quota bookkeeping ({})", error);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ipc_channel::ipc::{self, IpcSender};
+use net_traits::file_system_thread::{
+    FileSystemEntryKind, FileSystemError, FileSystemResult, FileSystemThreadMsg, SyncAccessHandleId,
+};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use storage::file_system::FileSystemThreadFactory;
+use storage::quota::QuotaManager;
+
+fn origin() -> ImmutableOrigin {
+    ServoUrl::parse("https://servo.org").unwrap().origin()
+}
+
+fn path(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+fn get_entry(
+    thread: &IpcSender<FileSystemThreadMsg>,
+    directory: &[&str],
+    name: &str,
+    kind: FileSystemEntryKind,
+    create: bool,
+) -> FileSystemResult<()> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(FileSystemThreadMsg::GetEntry {
+            origin: origin(),
+            path: path(directory),
+            name: name.to_owned(),
+            kind,
+            create,
+            sender,
+        })
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn remove_entry(
+    thread: &IpcSender<FileSystemThreadMsg>,
+    directory: &[&str],
+    name: &str,
+    recursive: bool,
+) -> FileSystemResult<()> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(FileSystemThreadMsg::RemoveEntry {
+            origin: origin(),
+            path: path(directory),
+            name: name.to_owned(),
+            recursive,
+            sender,
+        })
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn get_file(thread: &IpcSender<FileSystemThreadMsg>, file: &[&str]) -> FileSystemResult<Vec<u8>> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(FileSystemThreadMsg::GetFile(sender, origin(), path(file)))
+        .unwrap();
+    receiver.recv().unwrap().map(|(contents, _)| contents)
+}
+
+fn create_sync_access_handle(
+    thread: &IpcSender<FileSystemThreadMsg>,
+    file: &[&str],
+) -> FileSystemResult<SyncAccessHandleId> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(FileSystemThreadMsg::CreateSyncAccessHandle(
+            sender,
+            origin(),
+            path(file),
+        ))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn write(
+    thread: &IpcSender<FileSystemThreadMsg>,
+    id: SyncAccessHandleId,
+    offset: u64,
+    data: &[u8],
+) -> FileSystemResult<u64> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(FileSystemThreadMsg::Write(
+            sender,
+            id,
+            offset,
+            data.to_vec(),
+        ))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn read(
+    thread: &IpcSender<FileSystemThreadMsg>,
+    id: SyncAccessHandleId,
+    offset: u64,
+    length: u64,
+) -> FileSystemResult<Vec<u8>> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(FileSystemThreadMsg::Read(sender, id, offset, length))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn get_size(
+    thread: &IpcSender<FileSystemThreadMsg>,
+    id: SyncAccessHandleId,
+) -> FileSystemResult<u64> {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(FileSystemThreadMsg::GetSize(sender, id))
+        .unwrap();
+    receiver.recv().unwrap()
+}
+
+fn exit(thread: &IpcSender<FileSystemThreadMsg>) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(FileSystemThreadMsg::Exit(sender)).unwrap();
+    receiver.recv().unwrap();
+}
+
+fn new_thread(directory: Option<PathBuf>) -> IpcSender<FileSystemThreadMsg> {
+    let quota = QuotaManager::new(directory.clone(), u64::MAX, u64::MAX);
+    FileSystemThreadFactory::new(directory, Arc::new(quota))
+}
+
+#[test]
+fn test_entries_are_only_created_when_asked_to() {
+    let directory = tempfile::tempdir().unwrap();
+    let thread = new_thread(Some(directory.path().to_owned()));
+
+    assert_eq!(
+        get_entry(&thread, &[], "notes", FileSystemEntryKind::Directory, false),
+        Err(FileSystemError::NotFound)
+    );
+    assert_eq!(
+        get_entry(&thread, &[], "notes", FileSystemEntryKind::Directory, true),
+        Ok(())
+    );
+    assert_eq!(
+        get_entry(
+            &thread,
+            &["notes"],
+            "a.txt",
+            FileSystemEntryKind::File,
+            true
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        get_entry(&thread, &[], "notes", FileSystemEntryKind::File, true),
+        Err(FileSystemError::TypeMismatch)
+    );
+    assert_eq!(
+        get_entry(
+            &thread,
+            &["missing"],
+            "a.txt",
+            FileSystemEntryKind::File,
+            true
+        ),
+        Err(FileSystemError::NotFound)
+    );
+    assert_eq!(
+        get_entry(&thread, &[], "..", FileSystemEntryKind::Directory, true),
+        Err(FileSystemError::InvalidName)
+    );
+    assert_eq!(get_file(&thread, &["notes", "a.txt"]), Ok(vec![]));
+    exit(&thread);
+}
+
+#[test]
+fn test_access_handle_reads_and_writes_at_offsets() {
+    let directory = tempfile::tempdir().unwrap();
+    let thread = new_thread(Some(directory.path().to_owned()));
+    get_entry(&thread, &[], "data", FileSystemEntryKind::File, true).unwrap();
+
+    let id = create_sync_access_handle(&thread, &["data"]).unwrap();
+    assert_eq!(write(&thread, id, 0, b"hello"), Ok(5));
+    assert_eq!(write(&thread, id, 7, b"world"), Ok(5));
+    assert_eq!(get_size(&thread, id), Ok(12));
+    assert_eq!(read(&thread, id, 5, 4), Ok(vec![0, 0, b'w', b'o']));
+    assert_eq!(read(&thread, id, 20, 4), Ok(vec![]));
+    thread.send(FileSystemThreadMsg::Close(id)).unwrap();
+    assert_eq!(get_size(&thread, id), Err(FileSystemError::InvalidState));
+
+    assert_eq!(get_file(&thread, &["data"]), Ok(b"hello\0\0world".to_vec()));
+    exit(&thread);
+}
+
+#[test]
+fn test_access_handle_locks_its_file() {
+    let directory = tempfile::tempdir().unwrap();
+    let thread = new_thread(Some(directory.path().to_owned()));
+    get_entry(&thread, &[], "logs", FileSystemEntryKind::Directory, true).unwrap();
+    get_entry(&thread, &["logs"], "today", FileSystemEntryKind::File, true).unwrap();
+
+    let id = create_sync_access_handle(&thread, &["logs", "today"]).unwrap();
+    assert_eq!(
+        create_sync_access_handle(&thread, &["logs", "today"]),
+        Err(FileSystemError::NoModificationAllowed)
+    );
+    assert_eq!(
+        remove_entry(&thread, &[], "logs", true),
+        Err(FileSystemError::NoModificationAllowed)
+    );
+
+    thread.send(FileSystemThreadMsg::Close(id)).unwrap();
+    assert_eq!(
+        remove_entry(&thread, &[], "logs", false),
+        Err(FileSystemError::InvalidModification)
+    );
+    assert_eq!(remove_entry(&thread, &[], "logs", true), Ok(()));
+    assert_eq!(
+        get_entry(&thread, &[], "logs", FileSystemEntryKind::Directory, false),
+        Err(FileSystemError::NotFound)
+    );
+    exit(&thread);
+}
+
+#[test]
+fn test_writes_past_the_quota_of_the_origin_fail() {
+    let directory = tempfile::tempdir().unwrap();
+    let quota = Arc::new(QuotaManager::new(None, 8, u64::MAX));
+    let thread = FileSystemThreadFactory::new(Some(directory.path().to_owned()), quota.clone());
+    get_entry(&thread, &[], "data", FileSystemEntryKind::File, true).unwrap();
+
+    let id = create_sync_access_handle(&thread, &["data"]).unwrap();
+    assert_eq!(write(&thread, id, 0, b"hello"), Ok(5));
+    assert_eq!(
+        write(&thread, id, 5, b"world"),
+        Err(FileSystemError::QuotaExceeded)
+    );
+    assert_eq!(write(&thread, id, 0, b"HELLO"), Ok(5));
+    assert_eq!(quota.estimate(&origin()), (5, 8));
+
+    thread.send(FileSystemThreadMsg::Close(id)).unwrap();
+    assert_eq!(remove_entry(&thread, &[], "data", false), Ok(()));
+    assert_eq!(quota.estimate(&origin()), (0, 8));
+    exit(&thread);
+}
+
+#[test]
+fn test_files_are_kept_across_restarts() {
+    let directory = tempfile::tempdir().unwrap();
+    let thread = new_thread(Some(directory.path().to_owned()));
+    get_entry(&thread, &[], "data", FileSystemEntryKind::File, true).unwrap();
+    let id = create_sync_access_handle(&thread, &["data"]).unwrap();
+    write(&thread, id, 0, b"kept").unwrap();
+    exit(&thread);
+
+    let thread = new_thread(Some(directory.path().to_owned()));
+    assert_eq!(get_file(&thread, &["data"]), Ok(b"kept".to_vec()));
+    exit(&thread);
+}
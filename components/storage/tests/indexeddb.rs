@@ -0,0 +1,529 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use ipc_channel::ipc::{self, IpcReceiver, IpcSender};
+use net_traits::indexeddb_thread::{
+    BackendError, ConnectionEvent, ConnectionId, CursorDirection, IndexMetadata, IndexedDBKey,
+    IndexedDBKeyRange, IndexedDBThreadMsg, IndexedDBTxnMode, KeyPath, ObjectStoreMetadata,
+    OpenEvent, OperationResult, RecordSource, TransactionEvent, TransactionId,
+    TransactionOperation,
+};
+use servo_url::{ImmutableOrigin, ServoUrl};
+use storage::indexeddb::IndexedDBThreadFactory;
+use storage::quota::QuotaManager;
+
+fn origin() -> ImmutableOrigin {
+    ServoUrl::parse("https://servo.org").unwrap().origin()
+}
+
+fn number(number: f64) -> IndexedDBKey {
+    IndexedDBKey::Number(number)
+}
+
+fn string(string: &str) -> IndexedDBKey {
+    IndexedDBKey::String(string.to_owned())
+}
+
+/// A connection to a database, and the events of its upgrade transaction.
+struct Connection {
+    id: ConnectionId,
+    events: IpcReceiver<ConnectionEvent>,
+    upgrade: IpcReceiver<TransactionEvent>,
+}
+
+fn request_open(
+    thread: &IpcSender<IndexedDBThreadMsg>,
+    version: Option<u64>,
+) -> (
+    IpcReceiver<OpenEvent>,
+    IpcReceiver<ConnectionEvent>,
+    IpcReceiver<TransactionEvent>,
+) {
+    let (request_sender, request) = ipc::channel().unwrap();
+    let (connection_sender, connection) = ipc::channel().unwrap();
+    let (upgrade_sender, upgrade) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Open {
+            origin: origin(),
+            name: "library".to_owned(),
+            version,
+            request: request_sender,
+            connection: connection_sender,
+            upgrade_transaction: upgrade_sender,
+        })
+        .unwrap();
+    (request, connection, upgrade)
+}
+
+/// Open the database, expecting it to be upgraded, and run `operations` in the upgrade
+/// transaction without committing it.
+fn upgrade(
+    thread: &IpcSender<IndexedDBThreadMsg>,
+    version: u64,
+    operations: Vec<TransactionOperation>,
+) -> Connection {
+    let (request, events, upgrade) = request_open(thread, Some(version));
+    let id = match request.recv().unwrap() {
+        OpenEvent::UpgradeNeeded { connection, .. } => connection,
+        event => panic!("Unexpected open event {:?}", event),
+    };
+    for operation in operations {
+        thread
+            .send(IndexedDBThreadMsg::Operation(
+                TransactionId::upgrade(id),
+                None,
+                operation,
+            ))
+            .unwrap();
+    }
+    Connection {
+        id,
+        events,
+        upgrade,
+    }
+}
+
+fn commit(
+    thread: &IpcSender<IndexedDBThreadMsg>,
+    transaction: TransactionId,
+    events: &IpcReceiver<TransactionEvent>,
+) {
+    thread
+        .send(IndexedDBThreadMsg::Commit(transaction))
+        .unwrap();
+    match events.recv().unwrap() {
+        TransactionEvent::Complete => {},
+        event => panic!("Unexpected transaction event {:?}", event),
+    }
+}
+
+fn create_books_store(auto_increment: bool) -> TransactionOperation {
+    TransactionOperation::CreateObjectStore(ObjectStoreMetadata {
+        name: "books".to_owned(),
+        key_path: None,
+        auto_increment,
+        indexes: vec![],
+    })
+}
+
+fn create_title_index(unique: bool) -> TransactionOperation {
+    TransactionOperation::CreateIndex {
+        store: "books".to_owned(),
+        index: IndexMetadata {
+            name: "by_title".to_owned(),
+            key_path: KeyPath::String("title".to_owned()),
+            unique,
+            multi_entry: false,
+        },
+        keys: vec![],
+    }
+}
+
+fn put_book(key: Option<IndexedDBKey>, title: &str) -> TransactionOperation {
+    TransactionOperation::Put {
+        store: "books".to_owned(),
+        key,
+        value: title.as_bytes().to_vec(),
+        index_keys: vec![("by_title".to_owned(), vec![string(title)])],
+        overwrite: true,
+    }
+}
+
+/// Run `operation` as request 1 of `transaction`, and get its result.
+fn request(
+    thread: &IpcSender<IndexedDBThreadMsg>,
+    transaction: TransactionId,
+    events: &IpcReceiver<TransactionEvent>,
+    operation: TransactionOperation,
+) -> Result<OperationResult, BackendError> {
+    thread
+        .send(IndexedDBThreadMsg::Operation(
+            transaction,
+            Some(1),
+            operation,
+        ))
+        .unwrap();
+    match events.recv().unwrap() {
+        TransactionEvent::RequestDone(1, result) => result,
+        event => panic!("Unexpected transaction event {:?}", event),
+    }
+}
+
+fn create_transaction(
+    thread: &IpcSender<IndexedDBThreadMsg>,
+    connection: ConnectionId,
+    serial: u64,
+    mode: IndexedDBTxnMode,
+) -> (TransactionId, IpcReceiver<TransactionEvent>) {
+    let id = TransactionId { connection, serial };
+    let (events_sender, events) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::CreateTransaction {
+            id,
+            scope: vec!["books".to_owned()],
+            mode,
+            events: events_sender,
+        })
+        .unwrap();
+    (id, events)
+}
+
+fn exit(thread: &IpcSender<IndexedDBThreadMsg>) {
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread.send(IndexedDBThreadMsg::Exit(sender)).unwrap();
+    receiver.recv().unwrap();
+}
+
+fn new_thread(directory: Option<PathBuf>) -> IpcSender<IndexedDBThreadMsg> {
+    let quota = QuotaManager::new(directory.clone(), u64::MAX, u64::MAX);
+    IndexedDBThreadFactory::new(directory, Arc::new(quota))
+}
+
+#[test]
+fn test_keys_are_ordered_by_type_then_value() {
+    let keys = vec![
+        number(-1.0),
+        number(3.0),
+        IndexedDBKey::Date(0.0),
+        string(""),
+        string("a"),
+        string("b"),
+        IndexedDBKey::Binary(vec![0]),
+        IndexedDBKey::Array(vec![]),
+        IndexedDBKey::Array(vec![number(1.0)]),
+        IndexedDBKey::Array(vec![number(1.0), number(0.0)]),
+        IndexedDBKey::Array(vec![string("a")]),
+    ];
+    for (index, key) in keys.iter().enumerate() {
+        for other in &keys[index + 1..] {
+            assert!(key < other, "{:?} < {:?}", key, other);
+        }
+    }
+    // Strings are compared by UTF-16 code units, in which characters outside the BMP come
+    // before the last ones of the BMP.
+    assert!(string("\u{10000}") < string("\u{ffff}"));
+}
+
+#[test]
+fn test_committed_records_are_kept_across_restarts() {
+    let directory = tempfile::tempdir().unwrap();
+
+    let thread = new_thread(Some(directory.path().to_owned()));
+    let connection = upgrade(&thread, 1, vec![create_books_store(true)]);
+    let upgrade_transaction = TransactionId::upgrade(connection.id);
+    let result = request(
+        &thread,
+        upgrade_transaction,
+        &connection.upgrade,
+        put_book(None, "Dune"),
+    );
+    assert_eq!(result, Ok(OperationResult::Key(number(1.0))));
+    commit(&thread, upgrade_transaction, &connection.upgrade);
+
+    let (transaction, events) =
+        create_transaction(&thread, connection.id, 1, IndexedDBTxnMode::Readwrite);
+    let result = request(&thread, transaction, &events, put_book(None, "Emma"));
+    assert_eq!(result, Ok(OperationResult::Key(number(2.0))));
+    commit(&thread, transaction, &events);
+
+    // A transaction that is not committed before the thread exits is lost.
+    let (transaction, events) =
+        create_transaction(&thread, connection.id, 2, IndexedDBTxnMode::Readwrite);
+    let result = request(&thread, transaction, &events, put_book(None, "Ulysses"));
+    assert_eq!(result, Ok(OperationResult::Key(number(3.0))));
+    exit(&thread);
+
+    let thread = new_thread(Some(directory.path().to_owned()));
+    let (request_events, _, _) = request_open(&thread, None);
+    let (id, metadata) = match request_events.recv().unwrap() {
+        OpenEvent::Success {
+            connection,
+            metadata,
+        } => (connection, metadata),
+        event => panic!("Unexpected open event {:?}", event),
+    };
+    assert_eq!(metadata.version, 1);
+    assert_eq!(metadata.object_stores.len(), 1);
+    assert!(metadata.object_stores[0].auto_increment);
+
+    let (transaction, events) = create_transaction(&thread, id, 1, IndexedDBTxnMode::Readonly);
+    let result = request(
+        &thread,
+        transaction,
+        &events,
+        TransactionOperation::GetAll {
+            source: RecordSource::ObjectStore("books".to_owned()),
+            range: IndexedDBKeyRange::unbounded(),
+            count: None,
+            keys_only: false,
+        },
+    );
+    let records = match result {
+        Ok(OperationResult::Records(records)) => records,
+        result => panic!("Unexpected result {:?}", result),
+    };
+    let values: Vec<_> = records
+        .into_iter()
+        .map(|record| record.value.unwrap())
+        .collect();
+    assert_eq!(values, vec![b"Dune".to_vec(), b"Emma".to_vec()]);
+    exit(&thread);
+}
+
+#[test]
+fn test_logs_in_another_format_are_set_aside() {
+    let directory = tempfile::tempdir().unwrap();
+    let log_path = directory.path().join("https_3a_2f_2fservo.org.log");
+    std::fs::write(&log_path, b"a log without a header").unwrap();
+
+    let thread = new_thread(Some(directory.path().to_owned()));
+    let connection = upgrade(&thread, 1, vec![create_books_store(false)]);
+    commit(
+        &thread,
+        TransactionId::upgrade(connection.id),
+        &connection.upgrade,
+    );
+    exit(&thread);
+
+    let set_aside = std::fs::read(directory.path().join("https_3a_2f_2fservo.org.log.v0"));
+    assert_eq!(set_aside.unwrap(), b"a log without a header");
+    assert!(std::fs::read(&log_path).unwrap().starts_with(b"SIDB"));
+}
+
+#[test]
+fn test_aborted_upgrade_does_not_create_the_database() {
+    let thread = new_thread(None);
+    let connection = upgrade(&thread, 1, vec![create_books_store(false)]);
+    thread
+        .send(IndexedDBThreadMsg::Abort(TransactionId::upgrade(
+            connection.id,
+        )))
+        .unwrap();
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Databases(sender, origin()))
+        .unwrap();
+    assert!(receiver.recv().unwrap().is_empty());
+    exit(&thread);
+}
+
+#[test]
+fn test_unique_index_rejects_records_with_a_taken_key() {
+    let thread = new_thread(None);
+    let connection = upgrade(
+        &thread,
+        1,
+        vec![create_books_store(false), create_title_index(true)],
+    );
+    let transaction = TransactionId::upgrade(connection.id);
+    let events = &connection.upgrade;
+
+    let result = request(
+        &thread,
+        transaction,
+        events,
+        put_book(Some(number(1.0)), "Dune"),
+    );
+    assert!(result.is_ok());
+    let result = request(
+        &thread,
+        transaction,
+        events,
+        put_book(Some(number(2.0)), "Dune"),
+    );
+    assert_eq!(result, Err(BackendError::Constraint));
+    // Replacing the record holding the key keeps the index unique.
+    let result = request(
+        &thread,
+        transaction,
+        events,
+        put_book(Some(number(1.0)), "Dune"),
+    );
+    assert!(result.is_ok());
+
+    let result = request(
+        &thread,
+        transaction,
+        events,
+        TransactionOperation::Count {
+            source: RecordSource::ObjectStore("books".to_owned()),
+            range: IndexedDBKeyRange::unbounded(),
+        },
+    );
+    assert_eq!(result, Ok(OperationResult::Count(1)));
+    exit(&thread);
+}
+
+#[test]
+fn test_cursors_move_over_the_unique_keys_of_an_index() {
+    let thread = new_thread(None);
+    let connection = upgrade(
+        &thread,
+        1,
+        vec![create_books_store(true), create_title_index(false)],
+    );
+    let transaction = TransactionId::upgrade(connection.id);
+    let events = &connection.upgrade;
+    for title in ["Dune", "Dune", "Emma", "Emma"] {
+        assert!(request(&thread, transaction, events, put_book(None, title)).is_ok());
+    }
+
+    let iterate = |direction, position, count| {
+        let result = request(
+            &thread,
+            transaction,
+            events,
+            TransactionOperation::Iterate {
+                source: RecordSource::Index {
+                    store: "books".to_owned(),
+                    index: "by_title".to_owned(),
+                },
+                range: IndexedDBKeyRange::unbounded(),
+                direction,
+                position,
+                target: None,
+                count,
+                keys_only: true,
+            },
+        );
+        match result {
+            Ok(OperationResult::Record(record)) => {
+                record.map(|record| (record.key, record.primary_key))
+            },
+            result => panic!("Unexpected result {:?}", result),
+        }
+    };
+
+    let emma = iterate(CursorDirection::PrevUnique, None, 1);
+    assert_eq!(emma, Some((string("Emma"), number(3.0))));
+    let dune = iterate(CursorDirection::PrevUnique, emma, 1);
+    assert_eq!(dune, Some((string("Dune"), number(1.0))));
+    assert_eq!(iterate(CursorDirection::PrevUnique, dune, 1), None);
+
+    let second = iterate(CursorDirection::Next, None, 2);
+    assert_eq!(second, Some((string("Dune"), number(2.0))));
+    let fourth = iterate(CursorDirection::Next, second, 2);
+    assert_eq!(fourth, Some((string("Emma"), number(4.0))));
+    exit(&thread);
+}
+
+#[test]
+fn test_readwrite_transactions_over_the_same_store_run_one_after_the_other() {
+    let thread = new_thread(None);
+    let connection = upgrade(&thread, 1, vec![create_books_store(true)]);
+    commit(
+        &thread,
+        TransactionId::upgrade(connection.id),
+        &connection.upgrade,
+    );
+
+    let (first, first_events) =
+        create_transaction(&thread, connection.id, 1, IndexedDBTxnMode::Readwrite);
+    let (second, second_events) =
+        create_transaction(&thread, connection.id, 2, IndexedDBTxnMode::Readwrite);
+    thread
+        .send(IndexedDBThreadMsg::Operation(
+            second,
+            Some(1),
+            TransactionOperation::Count {
+                source: RecordSource::ObjectStore("books".to_owned()),
+                range: IndexedDBKeyRange::unbounded(),
+            },
+        ))
+        .unwrap();
+    let result = request(&thread, first, &first_events, put_book(None, "Dune"));
+    assert!(result.is_ok());
+    assert!(second_events.try_recv().is_err());
+
+    commit(&thread, first, &first_events);
+    match second_events.recv().unwrap() {
+        TransactionEvent::RequestDone(1, result) => {
+            assert_eq!(result, Ok(OperationResult::Count(1)))
+        },
+        event => panic!("Unexpected transaction event {:?}", event),
+    }
+    exit(&thread);
+}
+
+#[test]
+fn test_upgrades_wait_for_the_other_connections_to_close() {
+    let thread = new_thread(None);
+    let first = upgrade(&thread, 1, vec![]);
+    commit(&thread, TransactionId::upgrade(first.id), &first.upgrade);
+
+    let (request, _, _) = request_open(&thread, Some(2));
+    match first.events.recv().unwrap() {
+        ConnectionEvent::VersionChange {
+            old_version: 1,
+            new_version: Some(2),
+        } => {},
+        event => panic!("Unexpected connection event {:?}", event),
+    }
+    thread
+        .send(IndexedDBThreadMsg::VersionChangeHandled(first.id))
+        .unwrap();
+    match request.recv().unwrap() {
+        OpenEvent::Blocked {
+            old_version: 1,
+            new_version: 2,
+        } => {},
+        event => panic!("Unexpected open event {:?}", event),
+    }
+
+    thread
+        .send(IndexedDBThreadMsg::CloseConnection(first.id))
+        .unwrap();
+    match request.recv().unwrap() {
+        OpenEvent::UpgradeNeeded { old_version: 1, .. } => {},
+        event => panic!("Unexpected open event {:?}", event),
+    }
+    exit(&thread);
+}
+
+#[test]
+fn test_clearing_the_origin_closes_its_connections_and_deletes_its_databases() {
+    let directory = tempfile::tempdir().unwrap();
+    let thread = new_thread(Some(directory.path().to_owned()));
+    let connection = upgrade(&thread, 1, vec![create_books_store(true)]);
+    commit(
+        &thread,
+        TransactionId::upgrade(connection.id),
+        &connection.upgrade,
+    );
+    let (transaction, events) =
+        create_transaction(&thread, connection.id, 1, IndexedDBTxnMode::Readwrite);
+    let result = request(&thread, transaction, &events, put_book(None, "Dune"));
+    assert_eq!(result, Ok(OperationResult::Key(number(1.0))));
+
+    thread
+        .send(IndexedDBThreadMsg::ClearOrigin(origin()))
+        .unwrap();
+    match connection.events.recv().unwrap() {
+        ConnectionEvent::ForcedClose => {},
+        event => panic!("Unexpected connection event {:?}", event),
+    }
+    match events.recv().unwrap() {
+        TransactionEvent::Aborted(BackendError::Abort) => {},
+        event => panic!("Unexpected transaction event {:?}", event),
+    }
+
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Databases(sender, origin()))
+        .unwrap();
+    assert!(receiver.recv().unwrap().is_empty());
+    exit(&thread);
+
+    let thread = new_thread(Some(directory.path().to_owned()));
+    let (sender, receiver) = ipc::channel().unwrap();
+    thread
+        .send(IndexedDBThreadMsg::Databases(sender, origin()))
+        .unwrap();
+    assert!(receiver.recv().unwrap().is_empty());
+    exit(&thread);
+}
@@ -0,0 +1,93 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use std::sync::mpsc;
+
+use servo_url::{ImmutableOrigin, ServoUrl};
+use storage::quota::{QuotaManager, StorageEndpoint};
+
+fn origin(url: &str) -> ImmutableOrigin {
+    ServoUrl::parse(url).unwrap().origin()
+}
+
+#[test]
+fn test_origins_are_kept_within_their_quota() {
+    let quota = QuotaManager::new(None, 100, 1000);
+    let servo = origin("https://servo.org");
+    quota.set_usage(&servo, StorageEndpoint::LocalStorage, 30);
+    quota.set_usage(&servo, StorageEndpoint::IndexedDB, 50);
+    assert_eq!(quota.estimate(&servo), (80, 100));
+
+    assert!(quota.allows_usage(&servo, StorageEndpoint::IndexedDB, 70));
+    assert!(!quota.allows_usage(&servo, StorageEndpoint::IndexedDB, 71));
+    assert!(!quota.allows_usage(&servo, StorageEndpoint::FileSystem, 21));
+    // Shrinking is always allowed.
+    assert!(quota.allows_usage(&servo, StorageEndpoint::LocalStorage, 10));
+
+    let other = origin("https://example.com");
+    assert_eq!(quota.estimate(&other), (0, 100));
+    assert!(quota.allows_usage(&other, StorageEndpoint::FileSystem, 100));
+}
+
+#[test]
+fn test_least_recently_used_origins_are_evicted_past_the_total_limit() {
+    let quota = QuotaManager::new(None, 100, 150);
+    let (sender, receiver) = mpsc::channel();
+    quota.register_evictor(StorageEndpoint::IndexedDB, move |origin| {
+        sender.send(origin).unwrap();
+    });
+
+    let first = origin("https://first.example");
+    let second = origin("https://second.example");
+    let third = origin("https://third.example");
+    quota.set_usage(&first, StorageEndpoint::IndexedDB, 60);
+    quota.set_usage(&second, StorageEndpoint::IndexedDB, 60);
+    // Using the first origin again makes the second the least recently used one.
+    quota.set_usage(&first, StorageEndpoint::IndexedDB, 60);
+    assert!(receiver.try_recv().is_err());
+
+    assert!(quota.allows_usage(&third, StorageEndpoint::IndexedDB, 60));
+    quota.set_usage(&third, StorageEndpoint::IndexedDB, 60);
+    assert_eq!(receiver.try_recv(), Ok(second.clone()));
+    assert!(receiver.try_recv().is_err());
+    assert_eq!(quota.estimate(&second), (0, 100));
+    assert_eq!(quota.estimate(&first), (60, 100));
+}
+
+#[test]
+fn test_persisted_origins_are_not_evicted() {
+    let quota = QuotaManager::new(None, 100, 100);
+    let (sender, receiver) = mpsc::channel();
+    quota.register_evictor(StorageEndpoint::LocalStorage, move |origin| {
+        sender.send(origin).unwrap();
+    });
+
+    let kept = origin("https://kept.example");
+    let other = origin("https://other.example");
+    assert!(!quota.persisted(&kept));
+    assert!(quota.persist(&kept));
+    assert!(quota.persisted(&kept));
+    quota.set_usage(&kept, StorageEndpoint::LocalStorage, 80);
+
+    // Nothing can be evicted to make room for the other origin.
+    assert!(!quota.allows_usage(&other, StorageEndpoint::LocalStorage, 30));
+    assert!(quota.allows_usage(&other, StorageEndpoint::LocalStorage, 20));
+    quota.set_usage(&other, StorageEndpoint::LocalStorage, 20);
+    assert!(receiver.try_recv().is_err());
+    assert_eq!(quota.estimate(&kept), (80, 100));
+}
+
+#[test]
+fn test_usage_and_persistence_are_kept_across_restarts() {
+    let directory = tempfile::tempdir().unwrap();
+    let servo = origin("https://servo.org");
+    let quota = QuotaManager::new(Some(directory.path().to_owned()), 100, 1000);
+    quota.set_usage(&servo, StorageEndpoint::FileSystem, 42);
+    quota.persist(&servo);
+    drop(quota);
+
+    let quota = QuotaManager::new(Some(directory.path().to_owned()), 100, 1000);
+    assert_eq!(quota.estimate(&servo), (42, 100));
+    assert!(quota.persisted(&servo));
+}
@@ -8,6 +8,7 @@
 
 mod actions;
 mod capabilities;
+mod visual_diff;
 
 use std::borrow::ToOwned;
 use std::collections::{BTreeMap, HashMap};
@@ -20,6 +21,8 @@ use base64::Engine;
 use capabilities::ServoCapabilities;
 use compositing_traits::ConstellationMsg;
 use crossbeam_channel::{after, select, unbounded, Receiver, Sender};
+use devtools_traits::NetworkThrottling;
+use embedder_traits::UserAgentOverride;
 use euclid::{Rect, Size2D};
 use http::method::Method;
 use image::{DynamicImage, ImageFormat, RgbImage};
@@ -83,6 +86,31 @@ fn extension_routes() -> Vec<(Method, &'static str, ServoExtensionRoute)> {
             "/session/{sessionId}/servo/prefs/reset",
             ServoExtensionRoute::ResetPrefs,
         ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/network_conditions",
+            ServoExtensionRoute::SetNetworkConditions,
+        ),
+        (
+            Method::DELETE,
+            "/session/{sessionId}/servo/network_conditions",
+            ServoExtensionRoute::DeleteNetworkConditions,
+        ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/user_agent",
+            ServoExtensionRoute::SetUserAgentOverride,
+        ),
+        (
+            Method::DELETE,
+            "/session/{sessionId}/servo/user_agent",
+            ServoExtensionRoute::DeleteUserAgentOverride,
+        ),
+        (
+            Method::POST,
+            "/session/{sessionId}/servo/element/{elementId}/visual_screenshot",
+            ServoExtensionRoute::TakeVisualScreenshot,
+        ),
     ];
 }
 
@@ -193,6 +221,11 @@ enum ServoExtensionRoute {
     GetPrefs,
     SetPrefs,
     ResetPrefs,
+    SetNetworkConditions,
+    DeleteNetworkConditions,
+    SetUserAgentOverride,
+    DeleteUserAgentOverride,
+    TakeVisualScreenshot,
 }
 
 impl WebDriverExtensionRoute for ServoExtensionRoute {
@@ -200,7 +233,7 @@ impl WebDriverExtensionRoute for ServoExtensionRoute {
 
     fn command(
         &self,
-        _parameters: &Parameters,
+        parameters: &Parameters,
         body_data: &Value,
     ) -> WebDriverResult<WebDriverCommand<ServoExtensionCommand>> {
         let command = match *self {
@@ -216,6 +249,29 @@ impl WebDriverExtensionRoute for ServoExtensionRoute {
                 let parameters: GetPrefsParameters = serde_json::from_value(body_data.clone())?;
                 ServoExtensionCommand::ResetPrefs(parameters)
             },
+            ServoExtensionRoute::SetNetworkConditions => {
+                let parameters: NetworkConditionsParameters =
+                    serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::SetNetworkConditions(parameters)
+            },
+            ServoExtensionRoute::DeleteNetworkConditions => {
+                ServoExtensionCommand::DeleteNetworkConditions
+            },
+            ServoExtensionRoute::SetUserAgentOverride => {
+                let parameters: UserAgentOverride = serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::SetUserAgentOverride(parameters)
+            },
+            ServoExtensionRoute::DeleteUserAgentOverride => {
+                ServoExtensionCommand::DeleteUserAgentOverride
+            },
+            ServoExtensionRoute::TakeVisualScreenshot => {
+                let element = parameters.get("elementId").ok_or_else(|| {
+                    WebDriverError::new(ErrorStatus::InvalidArgument, "Missing elementId parameter")
+                })?;
+                let parameters: VisualScreenshotParameters =
+                    serde_json::from_value(body_data.clone())?;
+                ServoExtensionCommand::TakeVisualScreenshot(WebElement(element.clone()), parameters)
+            },
         };
         Ok(WebDriverCommand::Extension(command))
     }
@@ -226,6 +282,11 @@ enum ServoExtensionCommand {
     GetPrefs(GetPrefsParameters),
     SetPrefs(SetPrefsParameters),
     ResetPrefs(GetPrefsParameters),
+    SetNetworkConditions(NetworkConditionsParameters),
+    DeleteNetworkConditions,
+    SetUserAgentOverride(UserAgentOverride),
+    DeleteUserAgentOverride,
+    TakeVisualScreenshot(WebElement, VisualScreenshotParameters),
 }
 
 impl WebDriverExtensionCommand for ServoExtensionCommand {
@@ -234,6 +295,11 @@ impl WebDriverExtensionCommand for ServoExtensionCommand {
             ServoExtensionCommand::GetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::SetPrefs(ref x) => serde_json::to_value(x).ok(),
             ServoExtensionCommand::ResetPrefs(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::SetNetworkConditions(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::DeleteNetworkConditions => None,
+            ServoExtensionCommand::SetUserAgentOverride(ref x) => serde_json::to_value(x).ok(),
+            ServoExtensionCommand::DeleteUserAgentOverride => None,
+            ServoExtensionCommand::TakeVisualScreenshot(_, ref x) => serde_json::to_value(x).ok(),
         }
     }
 }
@@ -357,6 +423,55 @@ struct SetPrefsParameters {
     prefs: Vec<(String, WebDriverPrefValue)>,
 }
 
+/// The network conditions to emulate for the session's tab: those of a preset, such as
+/// `"regular-3g"` or `"offline"`, with any of its values overridden.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct NetworkConditionsParameters {
+    preset: Option<String>,
+    /// The delay before each request is sent, in milliseconds.
+    latency: Option<u64>,
+    /// The most bytes received per second, or zero for no limit.
+    download_throughput: Option<u64>,
+    /// The most bytes sent per second, or zero for no limit.
+    upload_throughput: Option<u64>,
+    offline: Option<bool>,
+}
+
+/// A screenshot of an element once the page is stable, compared with a baseline if one is
+/// given.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct VisualScreenshotParameters {
+    /// The expected PNG image, encoded in base64.
+    baseline: Option<String>,
+    /// How far apart the colours of two pixels may be for them to be the same, from 0 to 1.
+    /// Defaults to 0.1.
+    threshold: Option<f64>,
+    /// The ratio of pixels which may differ from the baseline for the screenshot to still
+    /// match it. Defaults to 0.
+    tolerance: Option<f64>,
+    /// Whether the animations of the page are paused while the screenshot is taken, rather
+    /// than waited for. Defaults to true.
+    pause_animations: Option<bool>,
+}
+
+fn encode_png(image: &RgbImage) -> String {
+    let mut png_data = Cursor::new(Vec::new());
+    DynamicImage::ImageRgb8(image.clone())
+        .write_to(&mut png_data, ImageFormat::Png)
+        .unwrap();
+    base64::engine::general_purpose::STANDARD.encode(png_data.get_ref())
+}
+
+fn decode_png(data: &str) -> WebDriverResult<RgbImage> {
+    let invalid = || WebDriverError::new(ErrorStatus::InvalidArgument, "Invalid baseline image");
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|_| invalid())?;
+    let image =
+        image::load_from_memory_with_format(&bytes, ImageFormat::Png).map_err(|_| invalid())?;
+    Ok(image.to_rgb8())
+}
+
 fn map_to_vec<'de, D>(de: D) -> Result<Vec<(String, WebDriverPrefValue)>, D::Error>
 where
     D: Deserializer<'de>,
@@ -1553,6 +1668,13 @@ impl Handler {
     }
 
     fn take_screenshot(&self, rect: Option<Rect<f32, CSSPixel>>) -> WebDriverResult<String> {
+        Ok(encode_png(&self.take_screenshot_image(rect)?))
+    }
+
+    fn take_screenshot_image(
+        &self,
+        rect: Option<Rect<f32, CSSPixel>>,
+    ) -> WebDriverResult<RgbImage> {
         let mut img = None;
 
         let interval = 1000;
@@ -1595,13 +1717,7 @@ impl Handler {
             "Unexpected screenshot pixel format"
         );
 
-        let rgb = RgbImage::from_raw(img.width, img.height, img.bytes.to_vec()).unwrap();
-        let mut png_data = Cursor::new(Vec::new());
-        DynamicImage::ImageRgb8(rgb)
-            .write_to(&mut png_data, ImageFormat::Png)
-            .unwrap();
-
-        Ok(base64::engine::general_purpose::STANDARD.encode(png_data.get_ref()))
+        Ok(RgbImage::from_raw(img.width, img.height, img.bytes.to_vec()).unwrap())
     }
 
     fn handle_take_screenshot(&self) -> WebDriverResult<WebDriverResponse> {
@@ -1638,6 +1754,72 @@ impl Handler {
         }
     }
 
+    fn handle_take_visual_screenshot(
+        &self,
+        element: &WebElement,
+        parameters: &VisualScreenshotParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let baseline = match parameters.baseline {
+            Some(ref baseline) => Some(decode_png(baseline)?),
+            None => None,
+        };
+
+        let pause_animations = parameters.pause_animations.unwrap_or(true);
+        if pause_animations {
+            self.set_animations_paused(true)?;
+        }
+        let screenshot = self.take_settled_element_screenshot(element);
+        if pause_animations {
+            self.set_animations_paused(false)?;
+        }
+        let screenshot = screenshot?;
+
+        let diff = baseline.map(|baseline| {
+            let threshold = parameters.threshold.unwrap_or(0.1);
+            let diff = visual_diff::compare(&screenshot, &baseline, threshold);
+            json!({
+                "matches": diff.mismatch_ratio() <= parameters.tolerance.unwrap_or(0.),
+                "mismatchedPixels": diff.mismatched_pixels,
+                "totalPixels": diff.total_pixels,
+                "mismatchRatio": diff.mismatch_ratio(),
+                "image": diff.image.as_ref().map(encode_png),
+            })
+        });
+
+        Ok(WebDriverResponse::Generic(ValueResponse(json!({
+            "screenshot": encode_png(&screenshot),
+            "diff": diff,
+        }))))
+    }
+
+    fn set_animations_paused(&self, paused: bool) -> WebDriverResult<()> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let command = WebDriverScriptCommand::SetAnimationsPaused(paused, sender);
+        self.browsing_context_script_command(command)?;
+        receiver.recv().unwrap();
+        Ok(())
+    }
+
+    /// Take a screenshot of `element` once an animation frame ran, and the page is stable:
+    /// its fonts and images are loaded, and it has no running animations.
+    fn take_settled_element_screenshot(&self, element: &WebElement) -> WebDriverResult<RgbImage> {
+        let (sender, receiver) = ipc::channel().unwrap();
+        let command = WebDriverScriptCommand::WaitForAnimationFrame(sender);
+        self.browsing_context_script_command(command)?;
+        receiver.recv().unwrap();
+
+        let (sender, receiver) = ipc::channel().unwrap();
+        let command = WebDriverScriptCommand::GetBoundingClientRect(element.to_string(), sender);
+        self.browsing_context_script_command(command)?;
+        match receiver.recv().unwrap() {
+            Ok(rect) => self.take_screenshot_image(Some(Rect::from_untyped(&rect))),
+            Err(_) => Err(WebDriverError::new(
+                ErrorStatus::StaleElementReference,
+                "Element not found",
+            )),
+        }
+    }
+
     fn handle_get_prefs(
         &self,
         parameters: &GetPrefsParameters,
@@ -1696,6 +1878,71 @@ impl Handler {
             serde_json::to_value(prefs)?,
         )))
     }
+
+    fn handle_set_network_conditions(
+        &self,
+        parameters: &NetworkConditionsParameters,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let preset = match parameters.preset {
+            Some(ref name) => NetworkThrottling::preset(name).ok_or_else(|| {
+                WebDriverError::new(
+                    ErrorStatus::InvalidArgument,
+                    format!("Unknown network conditions preset {}", name),
+                )
+            })?,
+            None => NetworkThrottling {
+                latency: 0,
+                download_throughput: 0,
+                upload_throughput: 0,
+                offline: false,
+            },
+        };
+        let throttling = NetworkThrottling {
+            latency: parameters.latency.unwrap_or(preset.latency),
+            download_throughput: parameters
+                .download_throughput
+                .unwrap_or(preset.download_throughput),
+            upload_throughput: parameters
+                .upload_throughput
+                .unwrap_or(preset.upload_throughput),
+            offline: parameters.offline.unwrap_or(preset.offline),
+        };
+        self.set_network_throttling(Some(throttling))
+    }
+
+    fn handle_delete_network_conditions(&self) -> WebDriverResult<WebDriverResponse> {
+        self.set_network_throttling(None)
+    }
+
+    fn set_network_throttling(
+        &self,
+        throttling: Option<NetworkThrottling>,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let top_level_browsing_context_id = self.session()?.top_level_browsing_context_id;
+        let cmd_msg =
+            WebDriverCommandMsg::SetNetworkThrottling(top_level_browsing_context_id, throttling);
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+        Ok(WebDriverResponse::Void)
+    }
+
+    /// Tell the sites of the session's tab what `user_agent_override` says about the user
+    /// agent instead of the defaults, or go back to the defaults.
+    fn set_user_agent_override(
+        &self,
+        user_agent_override: Option<UserAgentOverride>,
+    ) -> WebDriverResult<WebDriverResponse> {
+        let top_level_browsing_context_id = self.session()?.top_level_browsing_context_id;
+        let cmd_msg = WebDriverCommandMsg::SetUserAgentOverride(
+            top_level_browsing_context_id,
+            user_agent_override,
+        );
+        self.constellation_chan
+            .send(ConstellationMsg::WebDriverCommand(cmd_msg))
+            .unwrap();
+        Ok(WebDriverResponse::Void)
+    }
 }
 
 impl WebDriverHandler<ServoExtensionRoute> for Handler {
@@ -1785,6 +2032,21 @@ impl WebDriverHandler<ServoExtensionRoute> for Handler {
                 ServoExtensionCommand::GetPrefs(ref x) => self.handle_get_prefs(x),
                 ServoExtensionCommand::SetPrefs(ref x) => self.handle_set_prefs(x),
                 ServoExtensionCommand::ResetPrefs(ref x) => self.handle_reset_prefs(x),
+                ServoExtensionCommand::SetNetworkConditions(ref x) => {
+                    self.handle_set_network_conditions(x)
+                },
+                ServoExtensionCommand::DeleteNetworkConditions => {
+                    self.handle_delete_network_conditions()
+                },
+                ServoExtensionCommand::SetUserAgentOverride(ref x) => {
+                    self.set_user_agent_override(Some(x.clone()))
+                },
+                ServoExtensionCommand::DeleteUserAgentOverride => {
+                    self.set_user_agent_override(None)
+                },
+                ServoExtensionCommand::TakeVisualScreenshot(ref element, ref x) => {
+                    self.handle_take_visual_screenshot(element, x)
+                },
             },
             _ => Err(WebDriverError::new(
                 ErrorStatus::UnsupportedOperation,
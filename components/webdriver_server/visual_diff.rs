@@ -0,0 +1,88 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+//! Perceptual comparison of screenshots against baseline images, for the visual regression
+//! tests of embedders.
+
+use image::{Rgb, RgbImage};
+
+/// The largest weighted YIQ distance between two colours, that of black and white.
+const MAX_YIQ_DELTA: f64 = 35215.;
+
+/// The outcome of comparing a screenshot with a baseline.
+pub(crate) struct VisualDiff {
+    pub mismatched_pixels: u64,
+    pub total_pixels: u64,
+    /// The pixels that differ in red, over a faded copy of the baseline, if both images
+    /// have the same size.
+    pub image: Option<RgbImage>,
+}
+
+impl VisualDiff {
+    pub fn mismatch_ratio(&self) -> f64 {
+        if self.total_pixels == 0 {
+            return 0.;
+        }
+        self.mismatched_pixels as f64 / self.total_pixels as f64
+    }
+}
+
+fn yiq(color: &Rgb<u8>) -> (f64, f64, f64) {
+    let [r, g, b] = color.0.map(f64::from);
+    (
+        r * 0.29889531 + g * 0.58662247 + b * 0.11448223,
+        r * 0.59597799 - g * 0.27417610 - b * 0.32180189,
+        r * 0.21147017 - g * 0.52261711 + b * 0.31114694,
+    )
+}
+
+/// The perceptual distance between two colours, from 0 for the same colour to 1 for black
+/// and white. Differences in brightness weigh more than differences in hue, as they do for
+/// the eye, following "Measuring perceived color difference using YIQ NTSC transmission
+/// color space in mobile applications" by Kotsarenko and Ramos.
+pub(crate) fn color_distance(a: &Rgb<u8>, b: &Rgb<u8>) -> f64 {
+    if a == b {
+        return 0.;
+    }
+    let (y1, i1, q1) = yiq(a);
+    let (y2, i2, q2) = yiq(b);
+    let (y, i, q) = (y1 - y2, i1 - i2, q1 - q2);
+    let delta = 0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q;
+    (delta / MAX_YIQ_DELTA).sqrt().min(1.)
+}
+
+/// Compare `screenshot` with `baseline`, counting the pixels whose colours are further apart
+/// than `threshold`. Images of different sizes do not match at all.
+pub(crate) fn compare(screenshot: &RgbImage, baseline: &RgbImage, threshold: f64) -> VisualDiff {
+    let total_pixels = u64::from(screenshot.width()) * u64::from(screenshot.height());
+    if screenshot.dimensions() != baseline.dimensions() {
+        return VisualDiff {
+            mismatched_pixels: total_pixels,
+            total_pixels,
+            image: None,
+        };
+    }
+
+    let mut mismatched_pixels = 0;
+    let mut image = RgbImage::new(screenshot.width(), screenshot.height());
+    for ((actual, expected), output) in screenshot
+        .pixels()
+        .zip(baseline.pixels())
+        .zip(image.pixels_mut())
+    {
+        *output = if color_distance(actual, expected) > threshold {
+            mismatched_pixels += 1;
+            Rgb([255, 0, 0])
+        } else {
+            let gray = 255. - (255. - yiq(expected).0) * 0.1;
+            Rgb([gray as u8; 3])
+        };
+    }
+
+    VisualDiff {
+        mismatched_pixels,
+        total_pixels,
+        image: Some(image),
+    }
+}
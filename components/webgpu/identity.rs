@@ -53,6 +53,11 @@ pub enum WebGPUMsg {
         device: WebGPUDevice,
         pipeline_id: PipelineId,
     },
+    DeviceLost {
+        device: WebGPUDevice,
+        pipeline_id: PipelineId,
+        msg: String,
+    },
     Exit,
 }
 
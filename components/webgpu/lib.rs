@@ -10,7 +10,7 @@ pub mod identity;
 
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::num::NonZeroU64;
 use std::rc::Rc;
 use std::slice;
@@ -39,13 +39,15 @@ use wgpu::command::{
     ComputePass, ImageCopyBuffer, ImageCopyTexture, RenderBundleDescriptor, RenderBundleEncoder,
     RenderPass,
 };
-use wgpu::device::{DeviceDescriptor, HostMap, ImplicitPipelineIds};
+use wgpu::device::queue::QueueSubmitError;
+use wgpu::device::{DeviceDescriptor, DeviceError, HostMap, ImplicitPipelineIds, WaitIdleError};
 use wgpu::id;
 use wgpu::instance::RequestAdapterOptions;
 use wgpu::pipeline::{ComputePipelineDescriptor, RenderPipelineDescriptor, ShaderModuleDescriptor};
 use wgpu::resource::{
     BufferDescriptor, BufferMapAsyncStatus, BufferMapCallback, BufferMapCallbackC,
-    BufferMapOperation, SamplerDescriptor, TextureDescriptor, TextureViewDescriptor,
+    BufferMapOperation, QuerySetDescriptor, SamplerDescriptor, TextureDescriptor,
+    TextureViewDescriptor,
 };
 use wgt::{Dx12Compiler, InstanceDescriptor};
 
@@ -155,6 +157,11 @@ pub enum WebGPURequest {
         descriptor: Option<RenderPipelineDescriptor<'static>>,
         implicit_ids: Option<(id::PipelineLayoutId, Vec<id::BindGroupLayoutId>)>,
     },
+    CreateQuerySet {
+        device_id: id::DeviceId,
+        query_set_id: id::QuerySetId,
+        descriptor: QuerySetDescriptor<'static>,
+    },
     CreateSampler {
         device_id: id::DeviceId,
         sampler_id: id::SamplerId,
@@ -187,6 +194,7 @@ pub enum WebGPURequest {
     },
     DestroyBuffer(id::BufferId),
     DestroyDevice(id::DeviceId),
+    DestroyQuerySet(id::QuerySetId),
     DestroySwapChain {
         external_id: u64,
         image_key: ImageKey,
@@ -201,6 +209,14 @@ pub enum WebGPURequest {
         render_bundle_id: id::RenderBundleId,
         device_id: id::DeviceId,
     },
+    ResolveQuerySet {
+        command_encoder_id: id::CommandEncoderId,
+        query_set_id: id::QuerySetId,
+        first_query: u32,
+        query_count: u32,
+        destination_id: id::BufferId,
+        destination_offset: wgt::BufferAddress,
+    },
     RequestAdapter {
         sender: IpcSender<Option<WebGPUResponseResult>>,
         options: RequestAdapterOptions,
@@ -337,6 +353,8 @@ struct WGPU<'a> {
     global: wgpu::global::Global<IdentityRecyclerFactory>,
     adapters: Vec<WebGPUAdapter>,
     devices: HashMap<WebGPUDevice, PipelineId>,
+    // Devices which the driver lost, and which were reported to script as such
+    lost_devices: RefCell<HashSet<WebGPUDevice>>,
     // Track invalid adapters https://gpuweb.github.io/gpuweb/#invalid
     _invalid_adapters: Vec<WebGPUAdapter>,
     // Buffers with pending mapping
@@ -382,6 +400,7 @@ impl<'a> WGPU<'a> {
             ),
             adapters: Vec::new(),
             devices: HashMap::new(),
+            lost_devices: RefCell::new(HashSet::new()),
             _invalid_adapters: Vec::new(),
             buffer_maps: HashMap::new(),
             present_buffer_maps: HashMap::new(),
@@ -397,7 +416,11 @@ impl<'a> WGPU<'a> {
     fn run(&'a mut self) {
         loop {
             if self.last_poll.elapsed() >= Duration::from_millis(DEVICE_POLL_INTERVAL) {
-                let _ = self.global.poll_all_devices(false);
+                if let Err(WaitIdleError::Device(DeviceError::Lost)) =
+                    self.global.poll_all_devices(false)
+                {
+                    self.find_lost_devices();
+                }
                 self.last_poll = Instant::now();
             }
             if let Ok((scope_id, msg)) = self.receiver.try_recv() {
@@ -678,6 +701,21 @@ impl<'a> WGPU<'a> {
                             self.send_result(device_id, scope_id, result);
                         }
                     },
+                    WebGPURequest::CreateQuerySet {
+                        device_id,
+                        query_set_id,
+                        descriptor,
+                    } => {
+                        let global = &self.global;
+                        let result = tuple_to_result(
+                            gfx_select!(query_set_id => global.device_create_query_set(
+                                device_id,
+                                &descriptor,
+                                query_set_id
+                            )),
+                        );
+                        self.send_result(device_id, scope_id, result);
+                    },
                     WebGPURequest::CreateSampler {
                         device_id,
                         sampler_id,
@@ -799,6 +837,10 @@ impl<'a> WGPU<'a> {
                         let global = &self.global;
                         gfx_select!(device => global.device_drop(device));
                     },
+                    WebGPURequest::DestroyQuerySet(query_set) => {
+                        let global = &self.global;
+                        gfx_select!(query_set => global.query_set_drop(query_set));
+                    },
                     WebGPURequest::DestroySwapChain {
                         external_id,
                         image_key,
@@ -844,6 +886,7 @@ impl<'a> WGPU<'a> {
                     WebGPURequest::FreeDevice(device_id) => {
                         let device = WebGPUDevice(device_id);
                         let pipeline_id = self.devices.remove(&device).unwrap();
+                        self.lost_devices.borrow_mut().remove(&device);
                         if let Err(e) = self.script_sender.send(WebGPUMsg::CleanDevice {
                             device,
                             pipeline_id,
@@ -868,6 +911,25 @@ impl<'a> WGPU<'a> {
 
                         self.send_result(device_id, scope_id, result);
                     },
+                    WebGPURequest::ResolveQuerySet {
+                        command_encoder_id,
+                        query_set_id,
+                        first_query,
+                        query_count,
+                        destination_id,
+                        destination_offset,
+                    } => {
+                        let global = &self.global;
+                        let result = gfx_select!(command_encoder_id => global.command_encoder_resolve_query_set(
+                            command_encoder_id,
+                            query_set_id,
+                            first_query,
+                            query_count,
+                            destination_id,
+                            destination_offset
+                        ));
+                        self.encoder_record_error(command_encoder_id, &result);
+                    },
                     WebGPURequest::RequestAdapter {
                         sender,
                         options,
@@ -1000,8 +1062,11 @@ impl<'a> WGPU<'a> {
                         let result = if cmd_id.is_some() {
                             Err(String::from("Invalid command buffer submitted"))
                         } else {
-                            gfx_select!(queue_id => global.queue_submit(queue_id, &command_buffers))
-                                .map_err(|e| format!("{:?}", e))
+                            let result = gfx_select!(queue_id => global.queue_submit(queue_id, &command_buffers));
+                            if let Err(QueueSubmitError::Queue(DeviceError::Lost)) = result {
+                                self.device_lost(WebGPUDevice(queue_id));
+                            }
+                            result.map_err(|e| format!("{:?}", e))
                         };
                         self.send_result(queue_id, scope_id, result);
                     },
@@ -1277,6 +1342,36 @@ impl<'a> WGPU<'a> {
         }
     }
 
+    /// Polling the devices failed as one of them is lost: find which.
+    fn find_lost_devices(&self) {
+        for &device in self.devices.keys() {
+            let global = &self.global;
+            let result = gfx_select!(device.0 => global.device_poll(device.0, wgt::Maintain::Poll));
+            if let Err(WaitIdleError::Device(DeviceError::Lost)) = result {
+                self.device_lost(device);
+            }
+        }
+    }
+
+    /// <https://gpuweb.github.io/gpuweb/#lose-the-device>
+    fn device_lost(&self, device: WebGPUDevice) {
+        let pipeline_id = match self.devices.get(&device) {
+            Some(&pipeline_id) => pipeline_id,
+            None => return,
+        };
+        if !self.lost_devices.borrow_mut().insert(device) {
+            return;
+        }
+        warn!("{:?} was lost", device);
+        if let Err(e) = self.script_sender.send(WebGPUMsg::DeviceLost {
+            device,
+            pipeline_id,
+            msg: String::from("The device was lost by the driver."),
+        }) {
+            warn!("Unable to send DeviceLost({:?}) ({:?})", device, e);
+        }
+    }
+
     fn encoder_record_error<U, T: std::fmt::Debug>(
         &self,
         encoder_id: id::CommandEncoderId,
@@ -1327,6 +1422,7 @@ webgpu_resource!(WebGPUCommandEncoder, id::CommandEncoderId);
 webgpu_resource!(WebGPUComputePipeline, id::ComputePipelineId);
 webgpu_resource!(WebGPUDevice, id::DeviceId);
 webgpu_resource!(WebGPUPipelineLayout, id::PipelineLayoutId);
+webgpu_resource!(WebGPUQuerySet, id::QuerySetId);
 webgpu_resource!(WebGPUQueue, id::QueueId);
 webgpu_resource!(WebGPURenderBundle, id::RenderBundleId);
 webgpu_resource!(WebGPURenderPipeline, id::RenderPipelineId);
@@ -353,6 +353,12 @@ pub fn Java_org_mozilla_servoview_JNIServo_pauseCompositor(env: JNIEnv, _: JClas
     call(&env, |s| s.pause_compositor());
 }
 
+#[no_mangle]
+pub fn Java_org_mozilla_servoview_JNIServo_memoryPressure(env: JNIEnv, _: JClass) {
+    debug!("memoryPressure");
+    call(&env, |s| s.memory_pressure());
+}
+
 #[no_mangle]
 pub fn Java_org_mozilla_servoview_JNIServo_resumeCompositor(
     env: JNIEnv,
@@ -24,7 +24,8 @@ pub use servo::embedder_traits::{
     ContextMenuResult, MediaSessionPlaybackState, PermissionPrompt, PermissionRequest, PromptResult,
 };
 use servo::embedder_traits::{
-    EmbedderMsg, EmbedderProxy, MediaSessionEvent, PromptDefinition, PromptOrigin,
+    ClipboardData, EmbedderMsg, EmbedderProxy, MediaSessionEvent, PromptDefinition, PromptOrigin,
+    WebResourceResponseMsg,
 };
 use servo::euclid::{Point2D, Rect, Scale, Size2D, Vector2D};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
@@ -579,6 +580,12 @@ impl ServoGlue {
         self.process_event(EmbedderEvent::InvalidateNativeSurface)
     }
 
+    /// Ask Servo to release as much memory as possible, for example when
+    /// Android reports that memory is running low.
+    pub fn memory_pressure(&mut self) -> Result<(), &'static str> {
+        self.process_event(EmbedderEvent::MemoryPressure)
+    }
+
     pub fn resume_compositor(
         &mut self,
         native_surface: *mut c_void,
@@ -709,6 +716,8 @@ impl ServoGlue {
                         PromptDefinition::Input(message, default, sender) => {
                             sender.send(cb.prompt_input(message, default, trusted))
                         },
+                        // Pickers for form controls are not exposed to the host yet.
+                        PromptDefinition::PickValue(_picker, sender) => sender.send(None),
                     };
                     if let Err(e) = res {
                         let reason = format!("Failed to send Prompt response: {}", e);
@@ -753,6 +762,18 @@ impl ServoGlue {
                 EmbedderMsg::SetClipboardContents(text) => {
                     self.callbacks.host_callbacks.set_clipboard_contents(text);
                 },
+                EmbedderMsg::GetClipboardData(sender) => {
+                    let contents = self.callbacks.host_callbacks.get_clipboard_contents();
+                    let _ = sender.send(contents.map(ClipboardData::Text).into_iter().collect());
+                },
+                EmbedderMsg::SetClipboardData(data) => {
+                    // Only text can be put on the clipboard of the host.
+                    for data in data {
+                        if let ClipboardData::Text(text) = data {
+                            self.callbacks.host_callbacks.set_clipboard_contents(text);
+                        }
+                    }
+                },
                 EmbedderMsg::Shutdown => {
                     self.callbacks.host_callbacks.on_shutdown_complete();
                 },
@@ -821,16 +842,33 @@ impl ServoGlue {
                 EmbedderMsg::ReadyToPresent => {
                     need_present = true;
                 },
+                EmbedderMsg::WebResourceRequested(_, response_sender) => {
+                    let _ = response_sender.send(WebResourceResponseMsg::None);
+                },
                 EmbedderMsg::Status(..) |
                 EmbedderMsg::SelectFiles(..) |
                 EmbedderMsg::MoveTo(..) |
                 EmbedderMsg::ResizeTo(..) |
                 EmbedderMsg::Keyboard(..) |
                 EmbedderMsg::SetCursor(..) |
+                EmbedderMsg::SetCustomCursor(..) |
                 EmbedderMsg::NewFavicon(..) |
+                EmbedderMsg::FaviconLoaded(..) |
                 EmbedderMsg::HeadParsed |
                 EmbedderMsg::SetFullscreenState(..) |
                 EmbedderMsg::ReportProfile(..) |
+                EmbedderMsg::ReportConnectionPool(..) |
+                EmbedderMsg::ReportContentProcesses(..) |
+                EmbedderMsg::ReportFrameTree(..) |
+                EmbedderMsg::AutofillRequested(..) |
+                EmbedderMsg::SecureTextInput(..) |
+                EmbedderMsg::ShowPopup(..) |
+                EmbedderMsg::HidePopup(..) |
+                EmbedderMsg::StartDrag(..) |
+                EmbedderMsg::SpeechSynthesis(..) |
+                EmbedderMsg::ElementExported(..) |
+                EmbedderMsg::Prerendered(..) |
+                EmbedderMsg::WebViewCrashed(..) |
                 EmbedderMsg::EventDelivered(..) => {},
             }
         }
@@ -924,6 +962,9 @@ impl ResourceReaderMethods for ResourceReaderInstance {
                 &include_bytes!("../../../resources/presentational-hints.css")[..]
             },
             Resource::QuirksModeCSS => &include_bytes!("../../../resources/quirks-mode.css")[..],
+            Resource::FormControlsCSS => {
+                &include_bytes!("../../../resources/form-controls.css")[..]
+            },
             Resource::RippyPNG => &include_bytes!("../../../resources/rippy.png")[..],
             Resource::DomainList => &include_bytes!("../../../resources/public_domains.txt")[..],
             Resource::BluetoothBlocklist => {
@@ -15,7 +15,9 @@ use log::{info, trace, warn};
 use servo::compositing::windowing::EmbedderEvent;
 use servo::compositing::CompositeTarget;
 use servo::config::{opts, set_pref};
+use servo::script_traits::extension::Extension;
 use servo::servo_config::pref;
+use servo::servo_url::ServoUrl;
 use servo::Servo;
 use surfman::GLApi;
 use webxr::glwindow::GlWindowDiscovery;
@@ -52,6 +54,7 @@ enum PumpResult {
     Shutdown,
     Continue {
         history_changed: bool,
+        popups_changed: bool,
         present: Present,
     },
 }
@@ -62,6 +65,8 @@ impl App {
         device_pixel_ratio_override: Option<f32>,
         user_agent: Option<String>,
         url: Option<String>,
+        mut extensions: Vec<Extension>,
+        on_clean_shutdown: Box<dyn FnOnce()>,
     ) {
         let events_loop = EventsLoop::new(opts::get().headless, opts::get().output_file.is_some());
 
@@ -83,13 +88,20 @@ impl App {
         };
 
         // Handle browser state.
-        let webviews = WebViewManager::new(window.clone());
-        let initial_url = get_default_url(
+        let mut webviews = WebViewManager::new(window.clone());
+        let mut initial_url = get_default_url(
             url.as_ref().map(String::as_str),
             env::current_dir().unwrap(),
             |path| fs::metadata(path).is_ok(),
         );
 
+        // When prerendering, the page is loaded by the prerender request once the webview
+        // is open.
+        if opts::get().prerender_output.is_some() || opts::get().prerender_screenshot.is_some() {
+            webviews.prerender(initial_url);
+            initial_url = ServoUrl::parse("about:blank").unwrap();
+        }
+
         let mut app = App {
             event_queue: RefCell::new(vec![]),
             webviews: RefCell::new(webviews),
@@ -137,6 +149,10 @@ impl App {
         // frame, set this to false, so we can avoid an unnecessary recomposite.
         let mut need_recomposite = true;
 
+        // Only run once Servo has finished shutting down, as the winit event loop exits the
+        // process rather than return.
+        let mut on_clean_shutdown = Some(on_clean_shutdown);
+
         let t_start = Instant::now();
         let mut t = t_start;
         let ev_waker = events_loop.create_event_loop_waker();
@@ -202,10 +218,17 @@ impl App {
                     );
                     let mut servo = servo_data.servo;
 
-                    servo.handle_events(vec![EmbedderEvent::NewWebView(
+                    // Extensions are loaded first, so that their content scripts
+                    // are injected into the initial page.
+                    let mut events: Vec<_> = extensions
+                        .drain(..)
+                        .map(EmbedderEvent::LoadExtension)
+                        .collect();
+                    events.push(EmbedderEvent::NewWebView(
                         initial_url.to_owned(),
                         servo_data.browser_id,
-                    )]);
+                    ));
+                    servo.handle_events(events);
                     servo.setup_logging();
 
                     app.windows.insert(window.id(), window.clone());
@@ -311,9 +334,13 @@ impl App {
                     if let Some(mut minibrowser) = app.minibrowser() {
                         minibrowser.context.destroy();
                     }
+                    if let Some(on_clean_shutdown) = on_clean_shutdown.take() {
+                        on_clean_shutdown();
+                    }
                 },
                 PumpResult::Continue {
                     history_changed,
+                    popups_changed,
                     present,
                 } => {
                     if history_changed {
@@ -330,6 +357,12 @@ impl App {
                             }
                         }
                     }
+                    if popups_changed {
+                        // The popups are drawn when the minibrowser is next updated.
+                        if let Some(mut minibrowser) = app.minibrowser() {
+                            minibrowser.update_popups(&app.webviews.borrow());
+                        }
+                    }
                     match present {
                         Present::Immediate => {
                             // The window was resized.
@@ -447,11 +480,13 @@ impl App {
         let mut need_resize = false;
         let mut need_present = false;
         let mut history_changed = false;
+        let mut popups_changed = false;
         loop {
             // Consume and handle those embedder messages.
             let servo_event_response = webviews.handle_servo_events(embedder_messages);
             need_present |= servo_event_response.need_present;
             history_changed |= servo_event_response.history_changed;
+            popups_changed |= servo_event_response.popups_changed;
 
             // Route embedder events from the WebViewManager to the relevant Servo components,
             // receives and collects embedder messages from various Servo components,
@@ -482,6 +517,7 @@ impl App {
 
         PumpResult::Continue {
             history_changed,
+            popups_changed,
             present,
         }
     }
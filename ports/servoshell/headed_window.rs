@@ -6,6 +6,8 @@
 
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 
 use euclid::num::Zero;
@@ -15,10 +17,10 @@ use raw_window_handle::{HasRawDisplayHandle, HasRawWindowHandle};
 use servo::compositing::windowing::{
     AnimationState, EmbedderCoordinates, EmbedderEvent, MouseWindowEvent, WindowMethods,
 };
-use servo::embedder_traits::Cursor;
+use servo::embedder_traits::{Cursor, DragDataItem};
 use servo::keyboard_types::{Key, KeyState, KeyboardEvent};
 use servo::rendering_context::RenderingContext;
-use servo::script_traits::{TouchEventType, WheelDelta, WheelMode};
+use servo::script_traits::{DragEventType, TouchEventType, WheelDelta, WheelMode};
 use servo::servo_config::{opts, pref};
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::style_traits::DevicePixel;
@@ -60,6 +62,8 @@ pub struct Window {
     device_pixel_ratio_override: Option<f32>,
     xr_window_poses: RefCell<Vec<Rc<XRWindowPose>>>,
     modifiers_state: Cell<ModifiersState>,
+    /// The files dragged over the window from other applications.
+    dragged_files: RefCell<Vec<PathBuf>>,
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -142,6 +146,7 @@ impl Window {
             mouse_down_button: Cell::new(None),
             mouse_down_point: Cell::new(Point2D::new(0, 0)),
             mouse_pos: Cell::new(Point2D::new(0, 0)),
+            dragged_files: RefCell::new(vec![]),
             last_pressed: Cell::new(None),
             keys_down: RefCell::new(HashMap::new()),
             animation_state: Cell::new(AnimationState::Idle),
@@ -277,6 +282,14 @@ impl Window {
             .borrow_mut()
             .push(EmbedderEvent::MouseWindowEventClass(event));
     }
+
+    /// Send a step of a drag from another application, at the last position of the pointer.
+    fn push_drag_event(&self, event_type: DragEventType) {
+        let position = self.mouse_pos.get().to_f32();
+        self.event_queue
+            .borrow_mut()
+            .push(EmbedderEvent::Drag(event_type, position));
+    }
 }
 
 impl WindowPortsMethods for Window {
@@ -406,6 +419,35 @@ impl WindowPortsMethods for Window {
                 self.event_queue
                     .borrow_mut()
                     .push(EmbedderEvent::MouseWindowMoveEventClass(position.to_f32()));
+                if !self.dragged_files.borrow().is_empty() {
+                    self.push_drag_event(DragEventType::Over);
+                }
+            },
+            winit::event::WindowEvent::HoveredFile(path) => {
+                // Each of the files of a drag is reported separately, so the drag enters
+                // again with all of the files reported so far.
+                let mut dragged_files = self.dragged_files.borrow_mut();
+                dragged_files.push(path);
+                let items = dragged_files
+                    .iter()
+                    .filter_map(|path| drag_data_item_for_file(path))
+                    .collect();
+                drop(dragged_files);
+                self.push_drag_event(DragEventType::Enter(items));
+                self.push_drag_event(DragEventType::Over);
+            },
+            winit::event::WindowEvent::DroppedFile(path) => {
+                // The drop is reported once per file too, so it is sent with the last one.
+                let mut dragged_files = self.dragged_files.borrow_mut();
+                dragged_files.retain(|dragged_file| *dragged_file != path);
+                if dragged_files.is_empty() {
+                    drop(dragged_files);
+                    self.push_drag_event(DragEventType::Drop);
+                }
+            },
+            winit::event::WindowEvent::HoveredFileCancelled => {
+                self.dragged_files.borrow_mut().clear();
+                self.push_drag_event(DragEventType::Leave);
             },
             winit::event::WindowEvent::MouseWheel { delta, phase, .. } => {
                 let (mut dx, mut dy, mode) = match delta {
@@ -550,6 +592,20 @@ impl WindowMethods for Window {
     }
 }
 
+/// A file dragged from another application, as web content sees it.
+fn drag_data_item_for_file(path: &Path) -> Option<DragDataItem> {
+    let bytes = fs::read(path)
+        .map_err(|error| debug!("Failed to read dragged file {:?}: {}", path, error))
+        .ok()?;
+    Some(DragDataItem::File {
+        name: path.file_name()?.to_string_lossy().into_owned(),
+        type_: mime_guess::from_path(path)
+            .first()
+            .map_or_else(String::new, |mime| mime.to_string()),
+        bytes,
+    })
+}
+
 fn winit_phase_to_touch_event_type(phase: TouchPhase) -> TouchEventType {
     match phase {
         TouchPhase::Started => TouchEventType::Down,
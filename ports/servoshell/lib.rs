@@ -41,11 +41,13 @@ pub mod platform {
 }
 
 use std::io::Write;
+use std::path::Path;
 use std::{env, panic, process, thread};
 
 use getopts::Options;
 use log::{error, warn};
 use servo::config::opts::{self, ArgumentParsingResult};
+use servo::script_traits::extension::Extension;
 use servo::servo_config::pref;
 
 use crate::app::App;
@@ -88,6 +90,28 @@ pub fn main() {
         "A preference to set to disable",
         "dom.webgpu.enabled=false",
     );
+    opts.optmulti(
+        "",
+        "extension",
+        "Load the unpacked manifest v3 extension in the given directory",
+        "path/to/extension",
+    );
+    #[cfg(feature = "heap-sampling")]
+    opts.optopt(
+        "",
+        "heap-sampling",
+        "Sample one allocation every N bytes and write the sampled stacks, \
+         in collapsed-stack format, to heap-samples.txt on exit",
+        "524288",
+    );
+    #[cfg(feature = "leak-tracking")]
+    opts.optopt(
+        "",
+        "leak-report",
+        "Track every allocation made after startup and write the ones still alive \
+         once Servo has shut down cleanly, grouped by call stack, to the given file",
+        "leaks.txt",
+    );
 
     let opts_matches;
     let content_process_token;
@@ -168,6 +192,45 @@ pub fn main() {
 
     let user_agent = opts_matches.opt_str("u");
 
+    let extensions = opts_matches
+        .opt_strs("extension")
+        .iter()
+        .map(|path| {
+            Extension::load(Path::new(path)).unwrap_or_else(|err| {
+                error!("Error loading extension {}: {}", path, err);
+                process::exit(1);
+            })
+        })
+        .collect();
+
+    #[cfg(feature = "heap-sampling")]
+    let heap_sampling = match opts_matches.opt_str("heap-sampling") {
+        Some(interval) => {
+            servo_allocator::sampling::start(interval.parse().unwrap_or_else(|err| {
+                error!("Error parsing option: --heap-sampling ({})", err);
+                process::exit(1);
+            }));
+            true
+        },
+        None => false,
+    };
+
+    #[cfg(feature = "leak-tracking")]
+    let leak_report = opts_matches.opt_str("leak-report");
+    #[cfg(feature = "leak-tracking")]
+    if leak_report.is_some() {
+        servo_allocator::leak_tracking::start();
+    }
+
+    // Everything still alive after an unclean exit would be reported as a leak, so the
+    // report is only written once Servo has shut down cleanly.
+    let on_clean_shutdown = Box::new(move || {
+        #[cfg(feature = "leak-tracking")]
+        if let Some(path) = leak_report {
+            write_leak_report(&path);
+        }
+    });
+
     let url_opt = if !opts_matches.free.is_empty() {
         Some(&opts_matches.free[0][..])
     } else {
@@ -179,9 +242,35 @@ pub fn main() {
         device_pixel_ratio_override,
         user_agent,
         url_opt.map(|s| s.to_string()),
+        extensions,
+        on_clean_shutdown,
     );
 
-    crate::platform::deinit(clean_shutdown)
+    #[cfg(feature = "heap-sampling")]
+    if heap_sampling {
+        servo_allocator::sampling::stop();
+        let result = std::fs::File::create("heap-samples.txt")
+            .and_then(|mut file| servo_allocator::sampling::write_collapsed_stacks(&mut file));
+        if let Err(err) = result {
+            error!("Failed to write heap samples: {}", err);
+        }
+    }
+
+    crate::platform::deinit(clean_shutdown);
+}
+
+#[cfg(feature = "leak-tracking")]
+fn write_leak_report(path: &str) {
+    servo_allocator::leak_tracking::stop();
+    let (count, bytes) = servo_allocator::leak_tracking::live_allocations();
+    if count > 0 {
+        warn!("{} bytes in {} allocations are still alive", bytes, count);
+    }
+    let result = std::fs::File::create(path)
+        .and_then(|mut file| servo_allocator::leak_tracking::write_report(&mut file));
+    if let Err(err) = result {
+        error!("Failed to write leak report: {}", err);
+    }
 }
 
 pub fn servo_version() -> String {
@@ -7,7 +7,10 @@ use std::num::NonZeroU32;
 use std::sync::Arc;
 use std::time::Instant;
 
-use egui::{CentralPanel, Frame, InnerResponse, Key, Modifiers, PaintCallback, TopBottomPanel};
+use egui::{
+    CentralPanel, Frame, InnerResponse, Key, LayerId, Modifiers, Order, PaintCallback, TextStyle,
+    TopBottomPanel,
+};
 use egui_glow::CallbackFn;
 use egui_winit::EventResponse;
 use euclid::{Length, Point2D, Scale};
@@ -15,10 +18,12 @@ use gleam::gl;
 use glow::NativeFramebuffer;
 use log::{trace, warn};
 use servo::compositing::windowing::EmbedderEvent;
+use servo::embedder_traits::Popup;
 use servo::msg::constellation_msg::TraversalDirection;
 use servo::rendering_context::RenderingContext;
 use servo::servo_geometry::DeviceIndependentPixel;
 use servo::servo_url::ServoUrl;
+use servo::webrender_api::units::{DeviceIntRect, DeviceIntSize};
 
 use crate::egui_glue::EguiGlow;
 use crate::events_loop::EventsLoop;
@@ -42,6 +47,9 @@ pub struct Minibrowser {
 
     /// Whether the location has been edited by the user without clicking Go.
     location_dirty: Cell<bool>,
+
+    /// The popups to draw over the webview, such as tooltips.
+    popups: Vec<Popup>,
 }
 
 pub enum MinibrowserEvent {
@@ -83,6 +91,7 @@ impl Minibrowser {
             last_mouse_position: None,
             location: RefCell::new(initial_url.to_string()),
             location_dirty: false.into(),
+            popups: Vec::new(),
         }
     }
 
@@ -138,6 +147,7 @@ impl Minibrowser {
             last_update,
             location,
             location_dirty,
+            popups,
             ..
         } = self;
         let widget_fbo = *widget_surface_fbo;
@@ -197,6 +207,7 @@ impl Minibrowser {
                     let size = ui.available_size();
                     let rect = egui::Rect::from_min_size(min, size);
                     ui.allocate_space(size);
+                    paint_popups(ui.ctx(), popups, rect);
 
                     let Some(servo_fbo) = servo_framebuffer_id else {
                         return;
@@ -295,6 +306,11 @@ impl Minibrowser {
         }
     }
 
+    /// Takes the popups to draw over the webview from the given [WebViewManager].
+    pub fn update_popups(&mut self, browser: &WebViewManager<dyn WindowPortsMethods>) {
+        self.popups = browser.popups().to_vec();
+    }
+
     /// Updates the location field from the given [BrowserManager], unless the user has started
     /// editing it without clicking Go, returning true iff it has changed (needing an egui update).
     pub fn update_location_in_toolbar(
@@ -315,3 +331,46 @@ impl Minibrowser {
         }
     }
 }
+
+/// The space between the edges of popups and their text, in egui points.
+const POPUP_PADDING: f32 = 4.0;
+
+/// Paint popups over the webview, whose rect is given in egui points.
+fn paint_popups(ctx: &egui::Context, popups: &[Popup], webview_rect: egui::Rect) {
+    let scale = ctx.pixels_per_point();
+    let viewport = DeviceIntRect::from_size(DeviceIntSize::new(
+        (webview_rect.width() * scale) as i32,
+        (webview_rect.height() * scale) as i32,
+    ));
+    let style = ctx.style();
+    let painter = ctx.layer_painter(LayerId::new(Order::Tooltip, egui::Id::new("popups")));
+    for popup in popups {
+        let galley = painter.layout_no_wrap(
+            popup.text.clone(),
+            TextStyle::Body.resolve(&style),
+            style.visuals.text_color(),
+        );
+        let size = galley.size() + egui::vec2(2.0 * POPUP_PADDING, 2.0 * POPUP_PADDING);
+        let device_size = DeviceIntSize::new(
+            (size.x * scale).ceil() as i32,
+            (size.y * scale).ceil() as i32,
+        );
+        let origin = popup.origin(device_size, viewport);
+        let min = webview_rect.min + egui::vec2(origin.x as f32, origin.y as f32) / scale;
+        painter.rect(
+            egui::Rect::from_min_size(min, size),
+            style.visuals.window_rounding,
+            style.visuals.window_fill(),
+            style.visuals.window_stroke(),
+        );
+        let text_min = min + egui::vec2(POPUP_PADDING, POPUP_PADDING);
+        if let Some(row) = popup.selected_line.and_then(|line| galley.rows.get(line)) {
+            let rect = row
+                .rect
+                .translate(text_min.to_vec2())
+                .expand2(egui::vec2(POPUP_PADDING, 0.0));
+            painter.rect_filled(rect, 0.0, style.visuals.selection.bg_fill);
+        }
+        painter.galley(text_min, galley);
+    }
+}
@@ -3,31 +3,34 @@
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
 use std::collections::HashMap;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::Write;
 use std::rc::Rc;
 use std::time::Duration;
 use std::vec::Drain;
 use std::{env, thread};
 
-use arboard::Clipboard;
+use arboard::{Clipboard, ImageData};
 use euclid::{Point2D, Vector2D};
 use gilrs::{EventType, Gilrs};
-use keyboard_types::{Key, KeyboardEvent, Modifiers, ShortcutMatcher};
+use keyboard_types::{Key, KeyState, KeyboardEvent, Modifiers, ShortcutMatcher};
 use log::{debug, error, info, trace, warn};
 use servo::compositing::windowing::{EmbedderEvent, WebRenderDebugOption};
 use servo::embedder_traits::{
-    CompositorEventVariant, ContextMenuResult, EmbedderMsg, FilterPattern, PermissionPrompt,
-    PermissionRequest, PromptDefinition, PromptOrigin, PromptResult,
+    ClipboardData, ClipboardImage, CompositorEventVariant, ContextMenuResult, EmbedderMsg,
+    FilterPattern, InputPicker, InputPickerKind, MediaSessionEvent, MediaSessionPlaybackState,
+    PermissionPrompt, PermissionRequest, Popup, PrerenderRequest, PrerenderedPage,
+    PromptDefinition, PromptOrigin, PromptResult, WebResourceResponseMsg,
 };
 use servo::msg::constellation_msg::{TopLevelBrowsingContextId as WebViewId, TraversalDirection};
 use servo::script_traits::{
-    GamepadEvent, GamepadIndex, GamepadInputBounds, GamepadUpdateType, TouchEventType,
+    GamepadEvent, GamepadIndex, GamepadInputBounds, GamepadUpdateType, MediaSessionActionType,
+    TouchEventType,
 };
 use servo::servo_config::opts;
 use servo::servo_url::ServoUrl;
 use servo::webrender_api::ScrollLocation;
-use tinyfiledialogs::{self, MessageBoxIcon, OkCancel, YesNo};
+use tinyfiledialogs::{self, DefaultColorValue, MessageBoxIcon, OkCancel, YesNo};
 
 use crate::keyutils::{CMD_OR_ALT, CMD_OR_CONTROL};
 use crate::parser::location_bar_input_to_url;
@@ -51,11 +54,21 @@ pub struct WebViewManager<Window: WindowPortsMethods + ?Sized> {
 
     title: Option<String>,
 
+    /// The popups shown over web content, at most one of each kind.
+    popups: Vec<Popup>,
+
     window: Rc<Window>,
     event_queue: Vec<EmbedderEvent>,
     clipboard: Option<Clipboard>,
     gamepad: Option<Gilrs>,
     shutdown_requested: bool,
+
+    /// Whether the active media session is playing, for the play/pause media key to pause it.
+    /// Modified by EmbedderMsg::MediaSessionEvent.
+    media_session_playing: bool,
+
+    /// The page to prerender in the first webview opened, before exiting.
+    pending_prerender: Option<PrerenderRequest>,
 }
 
 #[derive(Debug)]
@@ -64,6 +77,7 @@ pub struct WebView {}
 pub struct ServoEventResponse {
     pub need_present: bool,
     pub history_changed: bool,
+    pub popups_changed: bool,
 }
 
 impl<Window> WebViewManager<Window>
@@ -73,6 +87,7 @@ where
     pub fn new(window: Rc<Window>) -> WebViewManager<Window> {
         WebViewManager {
             title: None,
+            popups: Vec::new(),
             current_url: None,
             current_url_string: None,
             webviews: HashMap::default(),
@@ -95,6 +110,38 @@ where
             },
             event_queue: Vec::new(),
             shutdown_requested: false,
+            media_session_playing: false,
+            pending_prerender: None,
+        }
+    }
+
+    /// Prerender this page in the first webview opened, write out what it was prerendered
+    /// into, then exit.
+    pub fn prerender(&mut self, url: ServoUrl) {
+        self.pending_prerender = Some(PrerenderRequest {
+            id: 0,
+            url,
+            serialize_dom: opts::get().prerender_output.is_some(),
+            screenshot: opts::get().prerender_screenshot.is_some(),
+            network_idle: Duration::from_millis(opts::get().prerender_network_idle),
+            timeout: Duration::from_secs(60),
+        });
+    }
+
+    fn write_prerendered_page(&self, page: PrerenderedPage) {
+        let outputs = [
+            (
+                &opts::get().prerender_output,
+                page.dom.map(String::into_bytes),
+            ),
+            (&opts::get().prerender_screenshot, page.screenshot),
+        ];
+        for (path, contents) in outputs {
+            if let (Some(path), Some(contents)) = (path, contents) {
+                if let Err(e) = fs::write(path, contents) {
+                    error!("Failed to write the prerendered page to {} ({})", path, e);
+                }
+            }
         }
     }
 
@@ -106,6 +153,10 @@ where
         self.current_url_string.as_deref()
     }
 
+    pub fn popups(&self) -> &[Popup] {
+        &self.popups
+    }
+
     pub fn get_events(&mut self) -> Vec<EmbedderEvent> {
         std::mem::take(&mut self.event_queue)
     }
@@ -233,6 +284,9 @@ where
 
     /// Handle key events before sending them to Servo.
     fn handle_key_from_window(&mut self, key_event: KeyboardEvent) {
+        if self.handle_media_key(&key_event) {
+            return;
+        }
         ShortcutMatcher::from_event(key_event.clone())
             .shortcut(CMD_OR_CONTROL, 'R', || {
                 if let Some(id) = self.focused_webview_id {
@@ -318,6 +372,27 @@ where
             .otherwise(|| self.platform_handle_key(key_event));
     }
 
+    /// Route a media key to the active media session, which may belong to a page in the
+    /// background, rather than to the focused webview. Returns whether it was a media key.
+    fn handle_media_key(&mut self, key_event: &KeyboardEvent) -> bool {
+        let action = match key_event.key {
+            Key::MediaPlayPause if self.media_session_playing => MediaSessionActionType::Pause,
+            Key::MediaPlayPause | Key::MediaPlay => MediaSessionActionType::Play,
+            Key::MediaPause => MediaSessionActionType::Pause,
+            Key::MediaStop => MediaSessionActionType::Stop,
+            Key::MediaTrackNext => MediaSessionActionType::NextTrack,
+            Key::MediaTrackPrevious => MediaSessionActionType::PreviousTrack,
+            Key::MediaFastForward => MediaSessionActionType::SeekForward,
+            Key::MediaRewind => MediaSessionActionType::SeekBackward,
+            _ => return false,
+        };
+        if key_event.state == KeyState::Down {
+            self.event_queue
+                .push(EmbedderEvent::MediaSessionAction(action));
+        }
+        true
+    }
+
     #[cfg(not(target_os = "win"))]
     fn platform_handle_key(&mut self, key_event: KeyboardEvent) {
         if let Some(id) = self.focused_webview_id {
@@ -411,6 +486,7 @@ where
     ) -> ServoEventResponse {
         let mut need_present = false;
         let mut history_changed = false;
+        let mut popups_changed = false;
         for (webview_id, msg) in events {
             trace!(
                 "embedder <- servo EmbedderMsg ({:?}, {:?})",
@@ -455,6 +531,7 @@ where
                             PromptDefinition::Input(_message, default, sender) => {
                                 sender.send(Some(default.to_owned()))
                             },
+                            PromptDefinition::PickValue(_picker, sender) => sender.send(None),
                         }
                     } else {
                         thread::Builder::new()
@@ -509,6 +586,9 @@ where
                                     let result = tinyfiledialogs::input_box("", &message, &default);
                                     sender.send(result)
                                 },
+                                PromptDefinition::PickValue(picker, sender) => {
+                                    sender.send(pick_value(&picker))
+                                },
                             })
                             .unwrap()
                             .join()
@@ -546,6 +626,10 @@ where
                     self.creation_order.push(new_webview_id);
                     self.event_queue
                         .push(EmbedderEvent::FocusWebView(new_webview_id));
+                    if let Some(request) = self.pending_prerender.take() {
+                        self.event_queue
+                            .push(EmbedderEvent::Prerender(new_webview_id, request));
+                    }
                 },
                 EmbedderMsg::WebViewClosed(webview_id) => {
                     self.webviews.retain(|&id, _| id != webview_id);
@@ -587,10 +671,49 @@ where
                         }
                     }
                 },
+                EmbedderMsg::GetClipboardData(sender) => {
+                    let mut data = vec![];
+                    if let Some(ref mut clipboard) = self.clipboard {
+                        if let Ok(text) = clipboard.get_text() {
+                            data.push(ClipboardData::Text(text));
+                        }
+                        if let Ok(image) = clipboard.get_image() {
+                            data.push(ClipboardData::Image(ClipboardImage {
+                                width: image.width as u32,
+                                height: image.height as u32,
+                                rgba: image.bytes.into_owned(),
+                            }));
+                        }
+                    }
+                    if let Err(e) = sender.send(data) {
+                        warn!("Failed to send clipboard ({})", e);
+                    }
+                },
+                EmbedderMsg::SetClipboardData(data) => {
+                    if let Some(ref mut clipboard) = self.clipboard {
+                        for data in data {
+                            let result = match data {
+                                ClipboardData::Text(text) => clipboard.set_text(text),
+                                ClipboardData::Image(image) => clipboard.set_image(ImageData {
+                                    width: image.width as usize,
+                                    height: image.height as usize,
+                                    bytes: image.rgba.into(),
+                                }),
+                            };
+                            if let Err(e) = result {
+                                warn!("Error setting clipboard contents ({})", e);
+                            }
+                        }
+                    }
+                },
                 EmbedderMsg::SetCursor(cursor) => {
                     self.window.set_cursor(cursor);
                 },
-                EmbedderMsg::NewFavicon(_url) => {
+                EmbedderMsg::SetCustomCursor(_custom_cursor, cursor) => {
+                    // FIXME: winit cannot draw images as cursors yet.
+                    self.window.set_cursor(cursor);
+                },
+                EmbedderMsg::NewFavicon(_url) | EmbedderMsg::FaviconLoaded(_url, _) => {
                     // FIXME: show favicons in the UI somehow
                 },
                 EmbedderMsg::HeadParsed => {
@@ -654,9 +777,12 @@ where
                         error!("Failed to store profile: {}", e);
                     }
                 },
-                EmbedderMsg::MediaSessionEvent(_) => {
-                    debug!("MediaSessionEvent received");
-                    // TODO(ferjm): MediaSession support for winit based browsers.
+                EmbedderMsg::MediaSessionEvent(event) => {
+                    debug!("MediaSessionEvent received: {:?}", event);
+                    if let MediaSessionEvent::PlaybackStateChange(state) = event {
+                        self.media_session_playing =
+                            matches!(state, MediaSessionPlaybackState::Playing);
+                    }
                 },
                 EmbedderMsg::OnDevtoolsStarted(port, _token) => match port {
                     Ok(p) => info!("Devtools Server running on port {}", p),
@@ -675,12 +801,74 @@ where
                     },
                     (_, _) => {},
                 },
+                EmbedderMsg::WebResourceRequested(_, response_sender) => {
+                    let _ = response_sender.send(WebResourceResponseMsg::None);
+                },
+                EmbedderMsg::ReportConnectionPool(report) => {
+                    info!("HTTP connection pool: {:?}", report);
+                },
+                EmbedderMsg::ReportContentProcesses(report) => {
+                    info!("Content processes: {:?}", report);
+                },
+                EmbedderMsg::ReportFrameTree(snapshot) => {
+                    info!("Frame tree: {:?}", snapshot);
+                },
+                EmbedderMsg::WebViewCrashed(report) => {
+                    warn!("{:?}: Crashed ({:?}).", webview_id, report);
+                },
+                EmbedderMsg::AutofillRequested(form, _sender) => {
+                    debug!(
+                        "{:?}: {:?} form with {} fields could be filled in.",
+                        webview_id,
+                        form.kind,
+                        form.fields.len()
+                    );
+                },
+                EmbedderMsg::SecureTextInput(secure) => {
+                    debug!("{:?}: Secure text input {}.", webview_id, secure);
+                },
+                EmbedderMsg::ShowPopup(popup) => {
+                    self.popups.retain(|shown| shown.kind != popup.kind);
+                    self.popups.push(popup);
+                    popups_changed = true;
+                    // The popups are drawn by the minibrowser when presenting.
+                    need_present = true;
+                },
+                EmbedderMsg::HidePopup(kind) => {
+                    self.popups.retain(|shown| shown.kind != kind);
+                    popups_changed = true;
+                    need_present = true;
+                },
+                EmbedderMsg::StartDrag(items) => {
+                    // winit cannot start drags, so the data can only be dropped within
+                    // web content.
+                    debug!("{:?}: Started dragging {:?}.", webview_id, items);
+                },
+                EmbedderMsg::SpeechSynthesis(_) => {
+                    // Handled by the speech synthesizer given to Servo, if any.
+                },
+                EmbedderMsg::ElementExported(id, result) => {
+                    debug!(
+                        "{:?}: Element export {} finished ({:?}).",
+                        webview_id,
+                        id,
+                        result.map(|data| data.len())
+                    );
+                },
+                EmbedderMsg::Prerendered(_, result) => {
+                    match result {
+                        Ok(page) => self.write_prerendered_page(page),
+                        Err(error) => error!("Failed to prerender the page ({:?})", error),
+                    }
+                    self.event_queue.push(EmbedderEvent::Quit);
+                },
             }
         }
 
         ServoEventResponse {
             need_present,
             history_changed,
+            popups_changed,
         }
     }
 }
@@ -785,6 +973,22 @@ fn get_selected_files(patterns: Vec<FilterPattern>, multiple_files: bool) -> Opt
         .expect("Thread spawning failed")
 }
 
+/// Ask the user for the value of a form control: a color with a color chooser, or a date or
+/// time typed in the format of the control, which script validates.
+fn pick_value(picker: &InputPicker) -> Option<String> {
+    let message = match picker.kind {
+        InputPickerKind::Color => {
+            let default = DefaultColorValue::Hex(&picker.value);
+            return tinyfiledialogs::color_chooser_dialog("Pick a color", default)
+                .map(|(hex, _)| hex.to_lowercase());
+        },
+        InputPickerKind::Date => "Enter a date (YYYY-MM-DD)",
+        InputPickerKind::Time => "Enter a time (HH:MM)",
+        InputPickerKind::DateTimeLocal => "Enter a date and time (YYYY-MM-DDTHH:MM)",
+    };
+    tinyfiledialogs::input_box("", message, &tiny_dialog_escape(&picker.value))
+}
+
 // This is a mitigation for #25498, not a verified solution.
 // There may be codepaths in tinyfiledialog.c that this is
 // inadquate against, as it passes the string via shell to
@@ -0,0 +1,103 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use embedder_traits::DragDataItem;
+use script::test::drag_and_drop::{DragDataStore, DragDataStoreMode, DropEffect};
+
+fn text(type_: &str, data: &str) -> DragDataItem {
+    DragDataItem::Text {
+        type_: type_.to_owned(),
+        data: data.to_owned(),
+    }
+}
+
+fn file(name: &str) -> DragDataItem {
+    DragDataItem::File {
+        name: name.to_owned(),
+        type_: "text/plain".to_owned(),
+        bytes: vec![],
+    }
+}
+
+#[test]
+fn test_types_list_files_once_after_strings() {
+    let data_store = DragDataStore::new(
+        vec![file("a.txt"), text("text/html", "<b>"), file("b.txt")],
+        DragDataStoreMode::Protected,
+    );
+    assert_eq!(data_store.types(), vec!["text/html", "Files"]);
+}
+
+#[test]
+fn test_get_data_normalizes_formats() {
+    let data_store = DragDataStore::new(
+        vec![
+            text("text/plain", "hello"),
+            text(
+                "text/uri-list",
+                "# comment\r\nhttps://servo.org/\r\nhttps://example.com/",
+            ),
+        ],
+        DragDataStoreMode::ReadOnly,
+    );
+    assert_eq!(data_store.get_data("Text").as_deref(), Some("hello"));
+    assert_eq!(data_store.get_data("TEXT/PLAIN").as_deref(), Some("hello"));
+    // Asking for the URL gives the first URL of the list.
+    assert_eq!(
+        data_store.get_data("url").as_deref(),
+        Some("https://servo.org/")
+    );
+    assert_eq!(data_store.get_data("text/html"), None);
+}
+
+#[test]
+fn test_set_data_replaces_data_of_the_same_type() {
+    let mut data_store = DragDataStore::new(vec![], DragDataStoreMode::ReadWrite);
+    data_store.set_data("text", "a".to_owned());
+    data_store.set_data("text/html", "<i>".to_owned());
+    data_store.set_data("text/plain", "b".to_owned());
+    assert_eq!(data_store.types(), vec!["text/html", "text/plain"]);
+    assert_eq!(data_store.get_data("text").as_deref(), Some("b"));
+}
+
+#[test]
+fn test_clear_data_keeps_files() {
+    let mut data_store = DragDataStore::new(
+        vec![
+            text("text/plain", "a"),
+            file("a.txt"),
+            text("text/html", "<b>"),
+        ],
+        DragDataStoreMode::ReadWrite,
+    );
+    data_store.clear_data(Some("Text"));
+    assert_eq!(data_store.types(), vec!["text/html", "Files"]);
+    data_store.clear_data(None);
+    assert_eq!(data_store.types(), vec!["Files"]);
+}
+
+#[test]
+fn test_add_and_remove_items() {
+    let mut data_store =
+        DragDataStore::new(vec![text("text/plain", "a")], DragDataStoreMode::ReadWrite);
+    // There can only be one string of each type.
+    assert_eq!(data_store.add(text("text/plain", "b")), None);
+    let id = data_store.add(file("a.txt")).unwrap();
+    assert_eq!(data_store.id_at(1), Some(id));
+    assert_eq!(data_store.remove(0), Some(0));
+    assert_eq!(data_store.id_at(0), Some(id));
+    assert!(data_store.item(0).is_none());
+    assert_eq!(data_store.remove(1), None);
+}
+
+#[test]
+fn test_drop_effects() {
+    assert_eq!(DropEffect::default_for("uninitialized"), DropEffect::Copy);
+    assert_eq!(DropEffect::default_for("linkMove"), DropEffect::Link);
+    assert_eq!(DropEffect::default_for("none"), DropEffect::None);
+    assert!(DropEffect::Move.is_allowed_by("copyMove"));
+    assert!(!DropEffect::Link.is_allowed_by("copyMove"));
+    assert_eq!(DropEffect::parse("move"), Some(DropEffect::Move));
+    assert_eq!(DropEffect::parse("Move"), None);
+}
@@ -0,0 +1,139 @@
+/* This Source Code Form is subject to the terms of the Mozilla Public
+ * License, v. 2.0. If a copy of the MPL was not distributed with this
+ * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
+
+use keyboard_types::{Key, Modifiers};
+use script::editing::{command_for_keydown, find_boundary, Direction, EditCommand, Granularity};
+
+#[test]
+fn test_find_boundary_by_character() {
+    let text = "ab\u{1F600}c";
+    assert_eq!(
+        find_boundary(text, 2, Direction::Backward, Granularity::Character),
+        1
+    );
+    // Characters outside of the Basic Multilingual Plane are two UTF-16 code units long.
+    assert_eq!(
+        find_boundary(text, 2, Direction::Forward, Granularity::Character),
+        4
+    );
+    assert_eq!(
+        find_boundary(text, 4, Direction::Backward, Granularity::Character),
+        2
+    );
+    assert_eq!(
+        find_boundary(text, 0, Direction::Backward, Granularity::Character),
+        0
+    );
+    assert_eq!(
+        find_boundary(text, 5, Direction::Forward, Granularity::Character),
+        5
+    );
+}
+
+#[test]
+fn test_find_boundary_by_character_keeps_grapheme_clusters() {
+    // "e" followed by a combining acute accent.
+    let text = "e\u{301}x";
+    assert_eq!(
+        find_boundary(text, 2, Direction::Backward, Granularity::Character),
+        0
+    );
+    assert_eq!(
+        find_boundary(text, 0, Direction::Forward, Granularity::Character),
+        2
+    );
+}
+
+#[test]
+fn test_find_boundary_by_word() {
+    let text = "hello, big world";
+    assert_eq!(
+        find_boundary(text, 16, Direction::Backward, Granularity::Word),
+        11
+    );
+    // The spaces and punctuation before a word are deleted with it.
+    assert_eq!(
+        find_boundary(text, 11, Direction::Backward, Granularity::Word),
+        7
+    );
+    assert_eq!(
+        find_boundary(text, 5, Direction::Forward, Granularity::Word),
+        10
+    );
+    assert_eq!(
+        find_boundary(text, 0, Direction::Forward, Granularity::Word),
+        5
+    );
+}
+
+#[test]
+fn test_find_boundary_by_line() {
+    let text = "one line";
+    assert_eq!(
+        find_boundary(text, 4, Direction::Backward, Granularity::Line),
+        0
+    );
+    assert_eq!(
+        find_boundary(text, 4, Direction::Forward, Granularity::Line),
+        8
+    );
+}
+
+#[test]
+fn test_command_for_keydown() {
+    let command = |key, mods| command_for_keydown(key, mods, false);
+    assert_eq!(
+        command(Key::Character("a".to_owned()), Modifiers::empty()),
+        Some(EditCommand::InsertText("a".to_owned()))
+    );
+    assert_eq!(
+        command(Key::Character("A".to_owned()), Modifiers::SHIFT),
+        Some(EditCommand::InsertText("A".to_owned()))
+    );
+    assert_eq!(
+        command(Key::Character("b".to_owned()), Modifiers::CONTROL),
+        None
+    );
+    assert_eq!(
+        command(Key::Enter, Modifiers::empty()),
+        Some(EditCommand::InsertParagraph)
+    );
+    assert_eq!(
+        command(Key::Enter, Modifiers::SHIFT),
+        Some(EditCommand::InsertLineBreak)
+    );
+    assert_eq!(
+        command(Key::Backspace, Modifiers::empty()),
+        Some(EditCommand::Delete(
+            Direction::Backward,
+            Granularity::Character
+        ))
+    );
+    assert_eq!(
+        command(Key::Delete, Modifiers::CONTROL),
+        Some(EditCommand::Delete(Direction::Forward, Granularity::Word))
+    );
+    assert_eq!(
+        command(Key::Backspace, Modifiers::CONTROL | Modifiers::SHIFT),
+        Some(EditCommand::Delete(Direction::Backward, Granularity::Line))
+    );
+    assert_eq!(command(Key::ArrowLeft, Modifiers::empty()), None);
+}
+
+#[test]
+fn test_command_for_keydown_on_mac_os() {
+    let command = |key, mods| command_for_keydown(key, mods, true);
+    assert_eq!(
+        command(Key::Backspace, Modifiers::ALT),
+        Some(EditCommand::Delete(Direction::Backward, Granularity::Word))
+    );
+    assert_eq!(
+        command(Key::Backspace, Modifiers::META),
+        Some(EditCommand::Delete(Direction::Backward, Granularity::Line))
+    );
+    assert_eq!(
+        command(Key::Character("z".to_owned()), Modifiers::META),
+        None
+    );
+}
@@ -2,6 +2,10 @@
  * License, v. 2.0. If a copy of the MPL was not distributed with this
  * file, You can obtain one at https://mozilla.org/MPL/2.0/. */
 
+#[cfg(test)]
+mod drag_and_drop;
+#[cfg(test)]
+mod editing;
 #[cfg(test)]
 mod headers;
 #[cfg(test)]
@@ -613,6 +613,31 @@ fn test_clipboard_paste() {
     assert_eq!(textinput.get_content(), "abcdefg");
 }
 
+#[test]
+fn test_clipboard_copy_not_allowed() {
+    #[cfg(target_os = "macos")]
+    const MODIFIERS: Modifiers = Modifiers::META;
+    #[cfg(not(target_os = "macos"))]
+    const MODIFIERS: Modifiers = Modifiers::CONTROL;
+
+    let mut textinput = TextInput::new(
+        Lines::Single,
+        DOMString::from("defg"),
+        DummyClipboardContext::new("abc"),
+        None,
+        None,
+        SelectionDirection::None,
+    );
+    textinput.set_clipboard_copy_allowed(false);
+    textinput.handle_keydown_aux(Key::Character("a".to_owned()), MODIFIERS, false);
+    textinput.handle_keydown_aux(Key::Character("x".to_owned()), MODIFIERS, false);
+    assert_eq!(textinput.get_content(), "defg");
+    textinput.handle_keydown_aux(Key::Character("c".to_owned()), MODIFIERS, false);
+    // The clipboard still holds what it held before, which replaces the selection.
+    textinput.handle_keydown_aux(Key::Character("v".to_owned()), MODIFIERS, false);
+    assert_eq!(textinput.get_content(), "abc");
+}
+
 #[test]
 fn test_textinput_cursor_position_correct_after_clearing_selection() {
     let mut textinput = text_input(Lines::Single, "abcdef");